@@ -23,6 +23,7 @@
 
 #![allow(clippy::result_large_err)]
 
+use ettlex_memory::commands::content_spill::hydrate_ettle_content;
 use ettlex_memory::{
     apply_command, ApprovalRouter, Command, CommandResult, Connection, EttleContext, EttleCursor,
     EttleListOpts, EttleListPage, EttleRecord, ExError, ExErrorKind, FsStore, PolicyProvider,
@@ -91,19 +92,26 @@ pub struct AgentEttleListOpts {
     /// Opaque base64 cursor from a previous `agent_ettle_list` response.
     pub cursor: Option<String>,
     pub include_tombstoned: bool,
+    /// Include archived Ettles (excluded by default).
+    pub include_archived: bool,
 }
 
 // ---------------------------------------------------------------------------
 // Read operations
 // ---------------------------------------------------------------------------
 
-/// Fetch a single Ettle record by ID (including tombstoned).
+/// Fetch a single Ettle record by ID (including tombstoned), with spilled
+/// WHY/WHAT/HOW read back from `cas`.
 ///
 /// Returns `ExErrorKind::NotFound` if the ID does not exist.
-pub fn agent_ettle_get(conn: &Connection, ettle_id: &str) -> Result<EttleRecord, ExError> {
+pub fn agent_ettle_get(
+    conn: &Connection,
+    cas: &FsStore,
+    ettle_id: &str,
+) -> Result<EttleRecord, ExError> {
     log_op_start!("agent_ettle_get", ettle_id = ettle_id);
     let start = std::time::Instant::now();
-    let result = _agent_ettle_get_inner(conn, ettle_id);
+    let result = _agent_ettle_get_inner(conn, cas, ettle_id);
     let elapsed = start.elapsed().as_millis() as u64;
     match &result {
         Ok(_) => log_op_end!("agent_ettle_get", duration_ms = elapsed),
@@ -115,24 +123,34 @@ pub fn agent_ettle_get(conn: &Connection, ettle_id: &str) -> Result<EttleRecord,
     result
 }
 
-fn _agent_ettle_get_inner(conn: &Connection, ettle_id: &str) -> Result<EttleRecord, ExError> {
-    SqliteRepo::get_ettle_record(conn, ettle_id)?.ok_or_else(|| {
+fn _agent_ettle_get_inner(
+    conn: &Connection,
+    cas: &FsStore,
+    ettle_id: &str,
+) -> Result<EttleRecord, ExError> {
+    let mut record = SqliteRepo::get_ettle_record(conn, ettle_id)?.ok_or_else(|| {
         ExError::new(ExErrorKind::NotFound)
             .with_op("agent_ettle_get")
             .with_entity_id(ettle_id)
             .with_message(format!("Ettle not found: {ettle_id}"))
-    })
+    })?;
+    hydrate_ettle_content(&mut record, cas)?;
+    Ok(record)
 }
 
 /// Assemble a rich EttleContext for the given Ettle ID.
 ///
 /// Returns WHY/WHAT/HOW fields along with active relations and active group memberships.
 /// Returns `ExErrorKind::NotFound` if the Ettle does not exist.
-pub fn agent_ettle_context(conn: &Connection, ettle_id: &str) -> Result<EttleContext, ExError> {
+pub fn agent_ettle_context(
+    conn: &Connection,
+    cas: &FsStore,
+    ettle_id: &str,
+) -> Result<EttleContext, ExError> {
     log_op_start!("agent_ettle_context", ettle_id = ettle_id);
     let start = std::time::Instant::now();
     let mm = memory_manager_instance();
-    let result = mm.assemble_ettle_context(ettle_id, conn);
+    let result = mm.assemble_ettle_context(ettle_id, conn, cas);
     let elapsed = start.elapsed().as_millis() as u64;
     match &result {
         Ok(_) => log_op_end!("agent_ettle_context", duration_ms = elapsed),
@@ -181,35 +199,14 @@ fn _agent_ettle_list_inner(
         limit: opts.limit,
         cursor,
         include_tombstoned: opts.include_tombstoned,
+        include_archived: opts.include_archived,
     };
     SqliteRepo::list_ettles(conn, &store_opts)
 }
 
-/// Decode a base64 cursor string into an EttleCursor.
+/// Decode an opaque cursor string into an EttleCursor.
 fn decode_cursor(s: &str) -> Result<EttleCursor, ExError> {
-    use base64::Engine as _;
-    let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
-        .decode(s)
-        .map_err(|_| {
-            ExError::new(ExErrorKind::InvalidInput)
-                .with_op("agent_ettle_list")
-                .with_message("invalid cursor encoding")
-        })?;
-    let decoded = String::from_utf8(bytes).map_err(|_| {
-        ExError::new(ExErrorKind::InvalidInput)
-            .with_op("agent_ettle_list")
-            .with_message("cursor is not valid UTF-8")
-    })?;
-    // Format: "{created_at},{id}"
-    let comma = decoded.find(',').ok_or_else(|| {
-        ExError::new(ExErrorKind::InvalidInput)
-            .with_op("agent_ettle_list")
-            .with_message("cursor has invalid format")
-    })?;
-    Ok(EttleCursor {
-        created_at: decoded[..comma].to_string(),
-        id: decoded[comma + 1..].to_string(),
-    })
+    SqliteRepo::decode_ettle_cursor(s)
 }
 
 // ---------------------------------------------------------------------------
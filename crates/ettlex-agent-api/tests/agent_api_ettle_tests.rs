@@ -115,7 +115,7 @@ fn test_agent_ettle_get_returns_full_record() {
     )
     .unwrap();
 
-    let record = agent_ettle_get(&h.conn, &create_result.ettle_id).unwrap();
+    let record = agent_ettle_get(&h.conn, &h.cas, &create_result.ettle_id).unwrap();
     assert_eq!(record.id, create_result.ettle_id);
     assert_eq!(record.title, "Full Record Title");
     assert_eq!(record.why, "because why");
@@ -135,7 +135,7 @@ fn test_agent_ettle_get_returns_tombstoned() {
     let id = h.create_ettle("Tombstone Me");
     h.tombstone_ettle(&id);
 
-    let record = agent_ettle_get(&h.conn, &id).unwrap();
+    let record = agent_ettle_get(&h.conn, &h.cas, &id).unwrap();
     assert!(record.tombstoned_at.is_some());
     // tombstoned_at should be ISO-8601
     let ts = record.tombstoned_at.unwrap();
@@ -149,7 +149,7 @@ fn test_agent_ettle_get_returns_tombstoned() {
 #[test]
 fn test_agent_ettle_get_not_found() {
     let h = Harness::new();
-    let err = agent_ettle_get(&h.conn, "ettle:does-not-exist").unwrap_err();
+    let err = agent_ettle_get(&h.conn, &h.cas, "ettle:does-not-exist").unwrap_err();
     assert_eq!(err.kind(), ExErrorKind::NotFound);
 }
 
@@ -162,8 +162,8 @@ fn test_agent_ettle_get_byte_identical() {
     let mut h = Harness::new();
     let id = h.create_ettle("Byte Identical");
 
-    let r1 = agent_ettle_get(&h.conn, &id).unwrap();
-    let r2 = agent_ettle_get(&h.conn, &id).unwrap();
+    let r1 = agent_ettle_get(&h.conn, &h.cas, &id).unwrap();
+    let r2 = agent_ettle_get(&h.conn, &h.cas, &id).unwrap();
 
     assert_eq!(r1.id, r2.id);
     assert_eq!(r1.title, r2.title);
@@ -188,7 +188,7 @@ fn test_agent_ettle_get_lifecycle_events() {
     // Clear any events from the create
     let _ = handle.events();
 
-    let _ = agent_ettle_get(&h.conn, &id);
+    let _ = agent_ettle_get(&h.conn, &h.cas, &id);
 
     let events = handle.events();
     let get_events: Vec<_> = events
@@ -253,7 +253,7 @@ fn test_agent_ettle_context_assembled() {
     )
     .unwrap();
 
-    let ctx = agent_ettle_context(&h.conn, &create_result.ettle_id).unwrap();
+    let ctx = agent_ettle_context(&h.conn, &h.cas, &create_result.ettle_id).unwrap();
     assert_eq!(ctx.ettle_id, create_result.ettle_id);
     assert_eq!(ctx.why, Some("why content".to_string()));
     assert_eq!(ctx.what, Some("what content".to_string()));
@@ -270,7 +270,7 @@ fn test_agent_ettle_context_assembled() {
 #[test]
 fn test_agent_ettle_context_not_found() {
     let h = Harness::new();
-    let err = agent_ettle_context(&h.conn, "ettle:missing").unwrap_err();
+    let err = agent_ettle_context(&h.conn, &h.cas, "ettle:missing").unwrap_err();
     assert_eq!(err.kind(), ExErrorKind::NotFound);
 }
 
@@ -294,6 +294,7 @@ fn test_agent_ettle_list_active_deterministic() {
             limit: 100,
             cursor: None,
             include_tombstoned: false,
+            include_archived: false,
         },
     )
     .unwrap();
@@ -324,6 +325,7 @@ fn test_agent_ettle_list_pagination() {
             limit: 5,
             cursor: None,
             include_tombstoned: false,
+            include_archived: false,
         },
     )
     .unwrap();
@@ -341,6 +343,7 @@ fn test_agent_ettle_list_pagination() {
             limit: 5,
             cursor: page1.next_cursor.clone(),
             include_tombstoned: false,
+            include_archived: false,
         },
     )
     .unwrap();
@@ -370,6 +373,7 @@ fn test_agent_ettle_list_limit_zero_rejected() {
             limit: 0,
             cursor: None,
             include_tombstoned: false,
+            include_archived: false,
         },
     )
     .unwrap_err();
@@ -403,7 +407,7 @@ fn test_agent_ettle_create_title_only() {
     );
 
     // Verify via get
-    let record = agent_ettle_get(&h.conn, &result.ettle_id).unwrap();
+    let record = agent_ettle_get(&h.conn, &h.cas, &result.ettle_id).unwrap();
     assert_eq!(record.title, "My Ettle");
     assert_eq!(record.why, "");
     assert_eq!(record.what, "");
@@ -530,7 +534,7 @@ fn test_agent_ettle_update_fields() {
 
     assert!(update_result.new_state_version > 0);
 
-    let record = agent_ettle_get(&h.conn, &id).unwrap();
+    let record = agent_ettle_get(&h.conn, &h.cas, &id).unwrap();
     assert_eq!(record.why, "W2");
     assert_eq!(record.what, "X2");
     assert_eq!(record.how, "H2");
@@ -575,7 +579,7 @@ fn test_agent_ettle_update_clears_reasoning_link() {
     .unwrap();
 
     // Verify link was set
-    let record = agent_ettle_get(&h.conn, &child_result.ettle_id).unwrap();
+    let record = agent_ettle_get(&h.conn, &h.cas, &child_result.ettle_id).unwrap();
     assert_eq!(record.reasoning_link_id, Some(parent_id.clone()));
 
     // Clear the link via double-Option: Some(None)
@@ -594,7 +598,7 @@ fn test_agent_ettle_update_clears_reasoning_link() {
     )
     .unwrap();
 
-    let updated = agent_ettle_get(&h.conn, &child_result.ettle_id).unwrap();
+    let updated = agent_ettle_get(&h.conn, &h.cas, &child_result.ettle_id).unwrap();
     assert!(
         updated.reasoning_link_id.is_none(),
         "reasoning_link_id should be null after clear"
@@ -640,7 +644,7 @@ fn test_agent_ettle_update_preserves_unspecified() {
     )
     .unwrap();
 
-    let record = agent_ettle_get(&h.conn, &create_result.ettle_id).unwrap();
+    let record = agent_ettle_get(&h.conn, &h.cas, &create_result.ettle_id).unwrap();
     assert_eq!(record.title, "T2");
     assert_eq!(record.why, "W", "why should be preserved");
     assert_eq!(record.what, "X", "what should be preserved");
@@ -694,7 +698,7 @@ fn test_agent_ettle_tombstone_marks_inactive() {
 
     assert!(result.new_state_version > 0);
 
-    let record = agent_ettle_get(&h.conn, &id).unwrap();
+    let record = agent_ettle_get(&h.conn, &h.cas, &id).unwrap();
     assert!(
         record.tombstoned_at.is_some(),
         "tombstoned_at should be set"
@@ -0,0 +1,92 @@
+//! Apply command — reconcile a refinement subtree with a declarative YAML file
+//!
+//! Planning, risk assessment and execution live in
+//! `ettlex_engine::commands::reconcile`; this module parses the file and
+//! prints the plan.
+
+#![allow(clippy::result_large_err)]
+
+use std::io::Read;
+use std::path::PathBuf;
+
+use clap::Args;
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_engine::commands::reconcile::{
+    assess_plan, execute_plan, plan_reconciliation, DesiredState, RiskLevel,
+};
+use ettlex_engine::feature_flags::require_feature;
+
+use super::import::print_cas_dedup;
+
+#[derive(Debug, Args)]
+pub struct ApplyArgs {
+    /// YAML file describing the desired subtree
+    #[arg(short = 'f', long = "file")]
+    pub file: PathBuf,
+
+    /// Print the plan without executing it
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Execute even if the plan contains high-risk changes
+    #[arg(long)]
+    pub allow_destructive: bool,
+
+    #[arg(long, default_value = ".ettlex/store.db")]
+    pub db: String,
+
+    #[arg(long, default_value = ".ettlex/cas")]
+    pub cas: String,
+}
+
+pub fn execute(args: ApplyArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let desired = parse_desired_state(std::fs::File::open(&args.file)?)?;
+    let mut conn = ettlex_store::env_keys::open_db(&args.db)?;
+    ettlex_store::migrations::apply_migrations(&mut conn)?;
+    require_feature(&conn, "declarative_apply")?;
+    let cas = ettlex_store::env_keys::open_cas(&args.cas)?;
+
+    let plan = plan_reconciliation(&conn, &desired)?;
+    if plan.is_empty() {
+        println!("No changes: the store matches {}", args.file.display());
+        return Ok(());
+    }
+    let assessed = assess_plan(&conn, plan)?;
+    for item in &assessed {
+        println!("[{}] {}", item.risk.as_str(), item.change);
+        for reason in &item.reasons {
+            println!("    ! {}", reason);
+        }
+    }
+    let count = |level: RiskLevel| assessed.iter().filter(|a| a.risk == level).count();
+    println!(
+        "Plan: {} change(s): {} high, {} medium, {} low risk",
+        assessed.len(),
+        count(RiskLevel::High),
+        count(RiskLevel::Medium),
+        count(RiskLevel::Low)
+    );
+    if args.dry_run {
+        println!("Dry run: nothing applied");
+        return Ok(());
+    }
+    let report = execute_plan(&mut conn, &cas, &assessed, args.allow_destructive)?;
+    println!("✓ Applied {} change(s)", report.changes);
+    for (path, ettle_id) in &report.created {
+        println!("  {} -> {}", path, ettle_id);
+    }
+    print_cas_dedup(&report.cas);
+    Ok(())
+}
+
+/// Parse a YAML [`DesiredState`]
+///
+/// # Errors
+/// * `InvalidInput` - Malformed YAML or unknown keys
+pub fn parse_desired_state(reader: impl Read) -> Result<DesiredState, ExError> {
+    serde_yaml::from_reader(reader).map_err(|e| {
+        ExError::new(ExErrorKind::InvalidInput)
+            .with_op("apply_desired_state")
+            .with_message(format!("invalid desired state: {}", e))
+    })
+}
@@ -0,0 +1,284 @@
+//! Approval queue commands
+
+#![allow(clippy::result_large_err)]
+
+use std::path::{Path, PathBuf};
+
+use clap::{Args, Subcommand, ValueEnum};
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_engine::commands::approval_offline::{
+    approval_response_command, export_approval, respond_to_export, ApprovalDecision,
+    ApprovalExport, ApprovalResponse,
+};
+use ettlex_engine::commands::approval_packet::render_approval_request;
+use ettlex_engine::commands::approval_sweep::approval_payload_sweep;
+use ettlex_engine::commands::command::{apply_command, CommandResult};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Environment variable holding the shared key that signs offline approval
+/// exports and responses.
+pub const REVIEW_KEY_ENV: &str = "ETTLEX_REVIEW_KEY";
+
+#[derive(Debug, Args)]
+pub struct ApprovalArgs {
+    #[command(subcommand)]
+    pub command: ApprovalCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ApprovalCommand {
+    /// Render a pending approval request as a Markdown review packet
+    Render(RenderArgs),
+    /// Delete CAS payloads of requests rejected or expired past the retention window
+    Sweep(SweepArgs),
+    /// Write a pending request to a signed file for offline review
+    Export(ExportArgs),
+    /// Answer an exported request offline, producing a signed response file
+    Respond(RespondArgs),
+    /// Verify a response file and resolve the request it answers
+    Import(ImportArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct RenderArgs {
+    /// Approval token
+    pub token: String,
+
+    /// Output file path (default: stdout)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    #[arg(long, default_value = ".ettlex/store.db")]
+    pub db: String,
+
+    #[arg(long, default_value = ".ettlex/cas")]
+    pub cas: String,
+}
+
+#[derive(Debug, Args)]
+pub struct SweepArgs {
+    /// Retention window in days (default: the `approval.retention_days`
+    /// setting)
+    #[arg(long)]
+    pub retention_days: Option<u32>,
+
+    /// List what would be swept without changing anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    #[arg(long, default_value = ".ettlex/store.db")]
+    pub db: String,
+
+    #[arg(long, default_value = ".ettlex/cas")]
+    pub cas: String,
+}
+
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    /// Approval token
+    pub token: String,
+
+    /// Export file to write
+    #[arg(short, long)]
+    pub output: PathBuf,
+
+    #[arg(long, default_value = ".ettlex/store.db")]
+    pub db: String,
+
+    #[arg(long, default_value = ".ettlex/cas")]
+    pub cas: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DecisionArg {
+    Approve,
+    Reject,
+}
+
+#[derive(Debug, Args)]
+pub struct RespondArgs {
+    /// Export file from `ettlex approval export`
+    pub export: PathBuf,
+
+    #[arg(long, value_enum)]
+    pub decision: DecisionArg,
+
+    /// Reviewer name, recorded as the actor of the transition
+    #[arg(long)]
+    pub reviewer: String,
+
+    #[arg(long)]
+    pub note: Option<String>,
+
+    /// Response file to write
+    #[arg(short, long)]
+    pub output: PathBuf,
+}
+
+#[derive(Debug, Args)]
+pub struct ImportArgs {
+    /// Response file from `ettlex approval respond`
+    pub response: PathBuf,
+
+    #[arg(long, default_value = ".ettlex/store.db")]
+    pub db: String,
+
+    #[arg(long, default_value = ".ettlex/cas")]
+    pub cas: String,
+}
+
+pub fn execute(args: ApprovalArgs) -> Result<(), Box<dyn std::error::Error>> {
+    match args.command {
+        ApprovalCommand::Render(render_args) => execute_render(render_args),
+        ApprovalCommand::Sweep(sweep_args) => execute_sweep(sweep_args),
+        ApprovalCommand::Export(export_args) => execute_export(export_args),
+        ApprovalCommand::Respond(respond_args) => execute_respond(respond_args),
+        ApprovalCommand::Import(import_args) => execute_import(import_args),
+    }
+}
+
+fn execute_render(args: RenderArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = ettlex_store::env_keys::open_db(&args.db)?;
+    ettlex_store::migrations::apply_migrations(&mut conn)?;
+    let cas = ettlex_store::env_keys::open_cas(&args.cas)?;
+
+    let markdown = render_approval_request(&conn, &cas, &args.token)?;
+
+    if let Some(output_path) = args.output {
+        std::fs::write(&output_path, markdown)?;
+        println!("✓ Rendered to {}", output_path.display());
+    } else {
+        print!("{}", markdown);
+    }
+
+    Ok(())
+}
+
+fn execute_sweep(args: SweepArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = ettlex_store::env_keys::open_db(&args.db)?;
+    ettlex_store::migrations::apply_migrations(&mut conn)?;
+    let cas = ettlex_store::env_keys::open_cas(&args.cas)?;
+
+    let report = approval_payload_sweep(&mut conn, &cas, args.retention_days, args.dry_run)?;
+
+    for item in &report.swept {
+        let blob = if item.blob_deleted {
+            item.request_digest.as_str()
+        } else {
+            "blob kept"
+        };
+        println!("  {} ({}, {})", item.approval_token, item.status, blob);
+    }
+    let verb = if args.dry_run {
+        "Would sweep"
+    } else {
+        "✓ Swept"
+    };
+    println!(
+        "{} {} approval payload(s): {} blob(s), {} bytes",
+        verb,
+        report.swept.len(),
+        report.blobs_deleted,
+        report.bytes_freed
+    );
+    Ok(())
+}
+
+fn execute_export(args: ExportArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let key = review_key()?;
+    let mut conn = ettlex_store::env_keys::open_db(&args.db)?;
+    ettlex_store::migrations::apply_migrations(&mut conn)?;
+    let cas = ettlex_store::env_keys::open_cas(&args.cas)?;
+
+    let export = export_approval(&conn, &cas, &args.token, &key)?;
+    write_json(&args.output, &export)?;
+    println!(
+        "✓ Exported {} to {} (key {})",
+        args.token,
+        args.output.display(),
+        export.key_id
+    );
+    Ok(())
+}
+
+fn execute_respond(args: RespondArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let key = review_key()?;
+    let export: ApprovalExport = read_json(&args.export)?;
+    let decision = match args.decision {
+        DecisionArg::Approve => ApprovalDecision::Approved,
+        DecisionArg::Reject => ApprovalDecision::Rejected,
+    };
+
+    let response = respond_to_export(
+        &export,
+        &key,
+        decision,
+        &args.reviewer,
+        args.note.as_deref(),
+    )?;
+    write_json(&args.output, &response)?;
+    println!(
+        "✓ {} {} in {}",
+        decision.as_status(),
+        response.approval_token,
+        args.output.display()
+    );
+    Ok(())
+}
+
+fn execute_import(args: ImportArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let key = review_key()?;
+    let response: ApprovalResponse = read_json(&args.response)?;
+    let mut conn = ettlex_store::env_keys::open_db(&args.db)?;
+    ettlex_store::migrations::apply_migrations(&mut conn)?;
+    let cas = ettlex_store::env_keys::open_cas(&args.cas)?;
+
+    let cmd = approval_response_command(&conn, &response, &key)?;
+    let (result, _) = apply_command(
+        cmd,
+        None,
+        &mut conn,
+        &cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )?;
+    if let CommandResult::ApprovalTransition { to_status, .. } = result {
+        println!(
+            "✓ {} {} (reviewer {})",
+            to_status, response.approval_token, response.reviewer
+        );
+    }
+    Ok(())
+}
+
+fn review_key() -> Result<Vec<u8>, ExError> {
+    std::env::var(REVIEW_KEY_ENV)
+        .ok()
+        .filter(|k| !k.is_empty())
+        .map(String::into_bytes)
+        .ok_or_else(|| {
+            ExError::new(ExErrorKind::InvalidInput)
+                .with_op("approval_review_key")
+                .with_message(format!("{} must hold the review key", REVIEW_KEY_ENV))
+        })
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T, Box<dyn std::error::Error>> {
+    let bytes = std::fs::read(path)?;
+    serde_json::from_slice(&bytes).map_err(|e| {
+        ExError::new(ExErrorKind::InvalidInput)
+            .with_op("approval_read_file")
+            .with_message(format!("{}: {}", path.display(), e))
+            .into()
+    })
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), Box<dyn std::error::Error>> {
+    let mut json = serde_json::to_string_pretty(value)?;
+    json.push('\n');
+    std::fs::write(path, json)?;
+    Ok(())
+}
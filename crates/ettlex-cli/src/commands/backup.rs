@@ -0,0 +1,61 @@
+//! Online store backup command
+
+use clap::Args;
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_store::backup::backup_store;
+use std::path::{Path, PathBuf};
+
+use super::summary::{self, CommandSummary};
+
+#[derive(Debug, Args)]
+pub struct BackupArgs {
+    /// Output directory (created if missing; must be empty)
+    #[arg(long)]
+    pub out: PathBuf,
+
+    /// Write a machine-readable JSON summary of the result to this file
+    #[arg(long)]
+    pub summary_out: Option<PathBuf>,
+
+    #[arg(long, default_value = ".ettlex/store.db")]
+    pub db: String,
+
+    #[arg(long, default_value = ".ettlex/cas")]
+    pub cas: String,
+}
+
+pub fn execute(args: BackupArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let summary_out = args.summary_out.clone();
+    let mut summary = CommandSummary::new("backup");
+    let result = run_backup(args, &mut summary);
+    summary::finish(summary_out.as_deref(), &summary, result)
+}
+
+fn run_backup(
+    args: BackupArgs,
+    summary: &mut CommandSummary,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Opening a missing path would back up a freshly created empty database.
+    if !Path::new(&args.db).exists() {
+        return Err(Box::new(
+            ExError::new(ExErrorKind::NotFound)
+                .with_op("backup_store")
+                .with_message(format!("database not found: {}", args.db)),
+        ));
+    }
+    let conn = ettlex_store::env_keys::open_db(&args.db)?;
+    let cas = ettlex_store::env_keys::open_cas(&args.cas)?;
+
+    let manifest = backup_store(&conn, &cas, &args.out)?;
+
+    println!(
+        "✓ Backed up database ({} bytes) and listed {} CAS blob(s) ({} bytes)",
+        manifest.db.size_bytes,
+        manifest.cas_blobs.len(),
+        manifest.cas_total_bytes
+    );
+    println!("  out: {}", args.out.display());
+    summary.digest("db_sha256", manifest.db.sha256);
+    summary.count("cas_blobs", manifest.cas_blobs.len());
+    Ok(())
+}
@@ -0,0 +1,230 @@
+//! Database and CAS maintenance commands
+
+use clap::{Args, Subcommand};
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_store::cas::encryption::parse_hex_key;
+use ettlex_store::cas::rotation::rotate_cas_key;
+use ettlex_store::cas::{gc, FsStore, GcOptions, StaticKeyProvider};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::summary::{self, CommandSummary};
+
+/// Print rotation progress every this many blobs (and on the last one)
+const PROGRESS_EVERY: usize = 100;
+
+#[derive(Debug, Args)]
+pub struct DbArgs {
+    #[command(subcommand)]
+    pub command: DbCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum DbCommand {
+    /// Re-encrypt CAS blobs and re-key the database under new keys
+    ///
+    /// Keys come from the environment so they never appear in process
+    /// listings: `ETTLEX_DB_KEY` → `ETTLEX_DB_NEW_KEY` for the database, and
+    /// `ETTLEX_CAS_KEY` (ID `ETTLEX_CAS_KEY_ID`, default `default`) →
+    /// `ETTLEX_CAS_NEW_KEY` (ID `--new-cas-key-id`) for CAS blobs. Either
+    /// half may be omitted. An interrupted rotation is resumed by running
+    /// the same command again.
+    RotateKey(RotateKeyArgs),
+    /// Delete CAS blobs that nothing in the database references
+    Gc(GcArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct RotateKeyArgs {
+    /// Key ID for the new CAS key (required with `ETTLEX_CAS_NEW_KEY`)
+    #[arg(long)]
+    pub new_cas_key_id: Option<String>,
+
+    /// Write a machine-readable JSON summary of the result to this file
+    #[arg(long)]
+    pub summary_out: Option<PathBuf>,
+
+    #[arg(long, default_value = ".ettlex/store.db")]
+    pub db: String,
+
+    #[arg(long, default_value = ".ettlex/cas")]
+    pub cas: String,
+}
+
+#[derive(Debug, Args)]
+pub struct GcArgs {
+    /// List unreferenced blobs and the space they use without deleting them
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Keep unreferenced blobs modified within this many seconds, in case a
+    /// writer has not committed their reference yet
+    #[arg(long, default_value_t = 3600)]
+    pub min_age_secs: u64,
+
+    /// Write a machine-readable JSON summary of the result to this file
+    #[arg(long)]
+    pub summary_out: Option<PathBuf>,
+
+    #[arg(long, default_value = ".ettlex/store.db")]
+    pub db: String,
+
+    #[arg(long, default_value = ".ettlex/cas")]
+    pub cas: String,
+}
+
+pub fn execute(args: DbArgs) -> Result<(), Box<dyn std::error::Error>> {
+    match args.command {
+        DbCommand::RotateKey(rotate_args) => execute_rotate_key(rotate_args),
+        DbCommand::Gc(gc_args) => execute_gc(gc_args),
+    }
+}
+
+fn execute_gc(args: GcArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let summary_out = args.summary_out.clone();
+    let mut summary = CommandSummary::new("db_gc");
+    let result = run_gc(args, &mut summary);
+    summary::finish(summary_out.as_deref(), &summary, result)
+}
+
+fn run_gc(args: GcArgs, summary: &mut CommandSummary) -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = ettlex_store::env_keys::open_db(&args.db)?;
+    ettlex_store::migrations::apply_migrations(&mut conn)?;
+    let cas = ettlex_store::env_keys::open_cas(&args.cas)?;
+
+    let report = gc(
+        &conn,
+        &cas,
+        GcOptions {
+            dry_run: args.dry_run,
+            min_age: Some(Duration::from_secs(args.min_age_secs)),
+        },
+    )?;
+    let verb = if args.dry_run {
+        "Would delete"
+    } else {
+        "Deleted"
+    };
+    if args.dry_run {
+        for blob in &report.deleted {
+            println!(
+                "  {}.{} ({} bytes)",
+                blob.digest, blob.extension, blob.size_bytes
+            );
+        }
+    }
+    println!(
+        "✓ {} {} of {} blobs, freeing {} bytes ({} referenced, {} too recent)",
+        verb,
+        report.deleted.len(),
+        report.scanned,
+        report.bytes_freed,
+        report.reachable,
+        report.too_recent
+    );
+    summary.count("cas_blobs", report.scanned);
+    summary.count("cas_reachable", report.reachable);
+    summary.count("cas_too_recent", report.too_recent);
+    summary.count("cas_deleted", report.deleted.len());
+    summary.count("bytes_freed", report.bytes_freed as usize);
+    Ok(())
+}
+
+fn execute_rotate_key(args: RotateKeyArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let summary_out = args.summary_out.clone();
+    let mut summary = CommandSummary::new("db_rotate_key");
+    let result = run_rotate_key(args, &mut summary);
+    summary::finish(summary_out.as_deref(), &summary, result)
+}
+
+fn run_rotate_key(
+    args: RotateKeyArgs,
+    summary: &mut CommandSummary,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let db_new_key = env("ETTLEX_DB_NEW_KEY");
+    let cas_new_key = env("ETTLEX_CAS_NEW_KEY");
+    if db_new_key.is_none() && cas_new_key.is_none() {
+        return Err(invalid(
+            "nothing to rotate: set ETTLEX_DB_NEW_KEY and/or ETTLEX_CAS_NEW_KEY",
+        ));
+    }
+
+    // CAS first: the database holds no key material for blobs, so the two
+    // rotations are independent and each can be resumed on its own.
+    if let Some(new_key) = cas_new_key {
+        let provider = cas_rotation_provider(&new_key, args.new_cas_key_id.as_deref())?;
+        let cas = FsStore::new(&args.cas).with_encryption(Arc::new(provider));
+        let report = rotate_cas_key(&cas, |p| {
+            if p.processed % PROGRESS_EVERY == 0 || p.processed == p.total {
+                eprintln!("  cas: {}/{} blobs", p.processed, p.total);
+            }
+        })?;
+        println!(
+            "✓ CAS now sealed with key '{}': {} re-sealed, {} already current",
+            report.key_id, report.resealed, report.already_current
+        );
+        summary.id("cas_key_id", report.key_id);
+        summary.count("cas_blobs", report.total);
+        summary.count("cas_resealed", report.resealed);
+        summary.count("cas_already_current", report.already_current);
+    }
+
+    if let Some(new_key) = db_new_key {
+        let old_key = env("ETTLEX_DB_KEY")
+            .ok_or_else(|| invalid("ETTLEX_DB_KEY must hold the database's current key"))?;
+        let outcome = rekey_db(&args.db, &old_key, &new_key)?;
+        println!("✓ Database {}", outcome);
+        summary.outcome("db", outcome);
+    }
+    Ok(())
+}
+
+/// Provider sealing with the new CAS key while still reading the old one
+fn cas_rotation_provider(
+    new_key: &str,
+    new_key_id: Option<&str>,
+) -> Result<StaticKeyProvider, Box<dyn std::error::Error>> {
+    let new_key_id = new_key_id
+        .ok_or_else(|| invalid("--new-cas-key-id is required with ETTLEX_CAS_NEW_KEY"))?;
+    let mut provider = StaticKeyProvider::new(new_key_id, parse_hex_key(new_key)?)?;
+    if let Some(old_key) = env("ETTLEX_CAS_KEY") {
+        let old_key_id = env("ETTLEX_CAS_KEY_ID").unwrap_or_else(|| "default".to_string());
+        if old_key_id != new_key_id {
+            provider = provider.with_retired_key(old_key_id, parse_hex_key(&old_key)?)?;
+        }
+    }
+    Ok(provider)
+}
+
+/// Re-key the database, or confirm a previous run already did
+fn rekey_db(
+    db: &str,
+    old_key: &str,
+    new_key: &str,
+) -> Result<&'static str, Box<dyn std::error::Error>> {
+    match ettlex_store::db::open_encrypted(db, old_key) {
+        Ok(conn) => {
+            ettlex_store::db::rekey(&conn, new_key)?;
+            Ok("re-keyed")
+        }
+        // A crash after the rekey committed leaves only the new key valid
+        Err(e) if e.kind() == ExErrorKind::Persistence => {
+            ettlex_store::db::open_encrypted(db, new_key).map_err(|_| e)?;
+            Ok("already on the new key")
+        }
+        Err(e) => Err(Box::new(e)),
+    }
+}
+
+fn env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.is_empty())
+}
+
+fn invalid(message: &str) -> Box<dyn std::error::Error> {
+    Box::new(
+        ExError::new(ExErrorKind::InvalidInput)
+            .with_op("db_rotate_key")
+            .with_message(message),
+    )
+}
@@ -0,0 +1,57 @@
+//! Event-stream export of the store's history
+
+use std::path::PathBuf;
+
+use clap::{Args, Subcommand};
+use ettlex_engine::commands::event_history::export_history_jsonl;
+
+#[derive(Debug, Args)]
+pub struct EventsArgs {
+    #[command(subcommand)]
+    pub command: EventsCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum EventsCommand {
+    /// Write the command-log event history as JSON lines
+    Export(ExportArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    /// Output JSONL file, or `-` for stdout
+    #[arg(long)]
+    pub out: PathBuf,
+
+    /// Only write events with a sequence number greater than this
+    #[arg(long, default_value_t = 0)]
+    pub after_seq: u64,
+
+    #[arg(long, default_value = ".ettlex/store.db")]
+    pub db: String,
+}
+
+pub fn execute(args: EventsArgs) -> Result<(), Box<dyn std::error::Error>> {
+    match args.command {
+        EventsCommand::Export(export_args) => execute_export(export_args),
+    }
+}
+
+fn execute_export(args: ExportArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = ettlex_store::env_keys::open_db(&args.db)?;
+    ettlex_store::migrations::apply_migrations(&mut conn)?;
+
+    let to_stdout = args.out.as_os_str() == "-";
+    let events = if to_stdout {
+        export_history_jsonl(&conn, args.after_seq, std::io::stdout().lock())?
+    } else {
+        let file = std::fs::File::create(&args.out)?;
+        export_history_jsonl(&conn, args.after_seq, std::io::BufWriter::new(file))?
+    };
+
+    if !to_stdout {
+        println!("✓ Exported {} event(s)", events);
+        println!("  out: {}", args.out.display());
+    }
+    Ok(())
+}
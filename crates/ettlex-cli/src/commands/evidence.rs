@@ -0,0 +1,67 @@
+//! Decision evidence maintenance commands
+
+use clap::{Args, Subcommand};
+use ettlex_engine::commands::evidence::evidence_sweep;
+
+#[derive(Debug, Args)]
+pub struct EvidenceArgs {
+    #[command(subcommand)]
+    pub command: EvidenceCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum EvidenceCommand {
+    /// Prune capture content of decisions tombstoned past the retention window
+    Sweep(SweepArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct SweepArgs {
+    /// Retention window in days (default: the `evidence.retention_days`
+    /// setting, then the default profile's `evidence.retention_days`)
+    #[arg(long)]
+    pub retention_days: Option<u32>,
+
+    /// List what would be pruned without deleting anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    #[arg(long, default_value = ".ettlex/store.db")]
+    pub db: String,
+
+    #[arg(long, default_value = ".ettlex/cas")]
+    pub cas: String,
+}
+
+pub fn execute(args: EvidenceArgs) -> Result<(), Box<dyn std::error::Error>> {
+    match args.command {
+        EvidenceCommand::Sweep(sweep_args) => execute_sweep(sweep_args),
+    }
+}
+
+fn execute_sweep(args: SweepArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = ettlex_store::env_keys::open_db(&args.db)?;
+    ettlex_store::migrations::apply_migrations(&mut conn)?;
+
+    let cas = ettlex_store::env_keys::open_cas(&args.cas)?;
+    let report = evidence_sweep(&mut conn, &cas, args.retention_days, args.dry_run)?;
+
+    for item in &report.pruned {
+        println!(
+            "  {} ({} bytes, decision {})",
+            item.evidence_capture_id, item.size_bytes, item.decision_id
+        );
+    }
+    let verb = if args.dry_run {
+        "Would prune"
+    } else {
+        "✓ Pruned"
+    };
+    println!(
+        "{} {} evidence capture(s), {} bytes",
+        verb,
+        report.pruned.len(),
+        report.bytes_freed
+    );
+    Ok(())
+}
@@ -0,0 +1,64 @@
+//! Feature flag commands
+
+use clap::{Args, Subcommand};
+use ettlex_engine::feature_flags::{list_features, set_feature, FeatureSource};
+
+#[derive(Debug, Args)]
+pub struct FeatureArgs {
+    #[command(subcommand)]
+    pub command: FeatureCommand,
+
+    #[arg(long, default_value = ".ettlex/store.db", global = true)]
+    pub db: String,
+
+    #[arg(long, default_value = ".ettlex/cas", global = true)]
+    pub cas: String,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum FeatureCommand {
+    /// Show every flag, its effective state and where that state comes from
+    List,
+    /// Enable a flag for this repository
+    Enable { name: String },
+    /// Disable a flag for this repository
+    Disable { name: String },
+    /// Drop the repository setting so the flag's default applies
+    Reset { name: String },
+}
+
+pub fn execute(args: FeatureArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = ettlex_store::env_keys::open_db(&args.db)?;
+    ettlex_store::migrations::apply_migrations(&mut conn)?;
+
+    let (name, enabled) = match args.command {
+        FeatureCommand::List => {
+            for state in list_features(&conn)? {
+                let source = match state.source {
+                    FeatureSource::Default => "default",
+                    FeatureSource::Setting => "setting",
+                    FeatureSource::Override => "ETTLEX_FEATURES",
+                };
+                println!(
+                    "{:<20} {:<3} ({})  {}",
+                    state.name,
+                    if state.enabled { "on" } else { "off" },
+                    source,
+                    state.description
+                );
+            }
+            return Ok(());
+        }
+        FeatureCommand::Enable { name } => (name, Some(true)),
+        FeatureCommand::Disable { name } => (name, Some(false)),
+        FeatureCommand::Reset { name } => (name, None),
+    };
+    let cas = ettlex_store::env_keys::open_cas(&args.cas)?;
+    set_feature(&mut conn, &cas, &name, enabled)?;
+    match enabled {
+        Some(true) => println!("✓ Enabled {}", name),
+        Some(false) => println!("✓ Disabled {}", name),
+        None => println!("✓ Reset {} to its default", name),
+    }
+    Ok(())
+}
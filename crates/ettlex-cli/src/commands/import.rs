@@ -0,0 +1,172 @@
+//! Import commands — bulk ingestion of Ettle trees from external formats
+//!
+//! The importers live in `ettlex_engine::commands::csv_import`; this module
+//! parses arguments and prints reports.
+
+#![allow(clippy::result_large_err)]
+
+use std::path::PathBuf;
+
+use clap::{Args, Subcommand};
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_engine::commands::csv_import::{
+    import_csv_file, resume_csv_import, CsvColumnMapping, CsvImportReport,
+};
+use ettlex_engine::commands::import_session::{
+    incomplete_import_sessions, rollback_import_session,
+};
+use ettlex_store::cas::CasDedupStats;
+use ettlex_store::repo::SqliteRepo;
+
+#[derive(Debug, Args)]
+pub struct ImportArgs {
+    #[command(subcommand)]
+    pub command: ImportCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ImportCommand {
+    /// Import an Ettle tree from a CSV file
+    Csv(CsvArgs),
+    /// List interrupted imports, or roll one back or finish it
+    Recover(RecoverArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct CsvArgs {
+    /// CSV file with a header row
+    pub file: PathBuf,
+
+    /// JSON column-mapping config (default: columns named `ref`, `parent`,
+    /// `title`, `ordinal`, `why`, `what`, `how`)
+    #[arg(long)]
+    pub mapping: Option<PathBuf>,
+
+    /// Commit every N created Ettles and relations instead of importing in
+    /// one transaction; an interrupted import is then finished or undone
+    /// with `import recover`
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub batch_size: Option<u64>,
+
+    #[arg(long, default_value = ".ettlex/store.db")]
+    pub db: String,
+
+    #[arg(long, default_value = ".ettlex/cas")]
+    pub cas: String,
+}
+
+#[derive(Debug, Args)]
+pub struct RecoverArgs {
+    /// Import session ID; omit to list interrupted sessions
+    pub session_id: Option<String>,
+
+    /// Delete everything the session imported
+    #[arg(long, requires = "session_id", conflicts_with = "complete")]
+    pub rollback: bool,
+
+    /// Import the remaining rows from the session's (unchanged) source file
+    #[arg(long, requires = "session_id")]
+    pub complete: bool,
+
+    #[arg(long, default_value = ".ettlex/store.db")]
+    pub db: String,
+
+    #[arg(long, default_value = ".ettlex/cas")]
+    pub cas: String,
+}
+
+pub fn execute(args: ImportArgs) -> Result<(), Box<dyn std::error::Error>> {
+    match args.command {
+        ImportCommand::Csv(csv_args) => execute_csv(csv_args),
+        ImportCommand::Recover(recover_args) => execute_recover(recover_args),
+    }
+}
+
+fn execute_csv(args: CsvArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let mapping = match &args.mapping {
+        Some(path) => serde_json::from_slice(&std::fs::read(path)?)?,
+        None => CsvColumnMapping::default(),
+    };
+    let mut conn = ettlex_store::env_keys::open_db(&args.db)?;
+    ettlex_store::migrations::apply_migrations(&mut conn)?;
+    let cas = ettlex_store::env_keys::open_cas(&args.cas)?;
+
+    let report = import_csv_file(&mut conn, &cas, &args.file, &mapping, args.batch_size)?;
+    print_report(&report);
+    Ok(())
+}
+
+fn execute_recover(args: RecoverArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = ettlex_store::env_keys::open_db(&args.db)?;
+    ettlex_store::migrations::apply_migrations(&mut conn)?;
+
+    let Some(session_id) = args.session_id else {
+        let sessions = incomplete_import_sessions(&conn)?;
+        if sessions.is_empty() {
+            println!("No interrupted imports.");
+        }
+        for session in sessions {
+            let created = SqliteRepo::list_import_session_items(&conn, &session.session_id)?;
+            println!(
+                "{}  {} {}  started {}  {} entities imported",
+                session.session_id,
+                session.kind,
+                session.source_path,
+                format_timestamp_ms(session.started_at_ms),
+                created.len()
+            );
+        }
+        return Ok(());
+    };
+
+    let cas = ettlex_store::env_keys::open_cas(&args.cas)?;
+    if args.rollback {
+        let removed = rollback_import_session(&mut conn, &cas, &session_id)?;
+        println!(
+            "✓ Rolled back {} ({} ettles, {} relations removed)",
+            session_id, removed.ettles, removed.relations
+        );
+    } else if args.complete {
+        let report = resume_csv_import(&mut conn, &cas, &session_id)?;
+        print_report(&report);
+    } else {
+        return Err(ExError::new(ExErrorKind::InvalidInput)
+            .with_op("import_recover")
+            .with_message("pass --rollback or --complete")
+            .into());
+    }
+    Ok(())
+}
+
+fn print_report(report: &CsvImportReport) {
+    println!(
+        "Imported {} ettles ({} refinements):",
+        report.ettles.len(),
+        report.refinements
+    );
+    for (key, ettle_id) in &report.ettles {
+        match key {
+            Some(key) => println!("  {} -> {}", key, ettle_id),
+            None => println!("  {}", ettle_id),
+        }
+    }
+    print_cas_dedup(&report.cas);
+}
+
+/// RFC 3339 rendering of a milliseconds-since-epoch store timestamp
+pub(crate) fn format_timestamp_ms(ms: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(ms)
+        .map(|at| at.to_rfc3339())
+        .unwrap_or_else(|| ms.to_string())
+}
+
+/// Print how many spilled content blobs were new versus already in CAS
+pub(crate) fn print_cas_dedup(stats: &CasDedupStats) {
+    if stats.is_empty() {
+        return;
+    }
+    println!(
+        "CAS: {} blob(s) written ({} bytes), {} reused ({} bytes not re-stored)",
+        stats.blobs_written, stats.bytes_written, stats.blobs_reused, stats.bytes_reused
+    );
+}
@@ -0,0 +1,48 @@
+//! Undo/redo of journaled commands
+
+use clap::Args;
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_engine::commands::engine_command::{
+    apply_engine_command, EngineCommand, EngineCommandResult,
+};
+
+#[derive(Debug, Args)]
+pub struct JournalArgs {
+    #[arg(long, default_value = ".ettlex/store.db")]
+    pub db: String,
+
+    #[arg(long, default_value = ".ettlex/cas")]
+    pub cas: String,
+}
+
+pub fn execute_undo(args: JournalArgs) -> Result<(), Box<dyn std::error::Error>> {
+    execute(args, EngineCommand::Undo)
+}
+
+pub fn execute_redo(args: JournalArgs) -> Result<(), Box<dyn std::error::Error>> {
+    execute(args, EngineCommand::Redo)
+}
+
+fn execute(args: JournalArgs, cmd: EngineCommand) -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = ettlex_store::env_keys::open_db(&args.db)?;
+    ettlex_store::migrations::apply_migrations(&mut conn)?;
+    let cas = ettlex_store::env_keys::open_cas(&args.cas)?;
+
+    let (verb, entry) = match apply_engine_command(
+        cmd,
+        &mut conn,
+        &cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )? {
+        EngineCommandResult::Undo(entry) => ("Undid", entry),
+        EngineCommandResult::Redo(entry) => ("Redid", entry),
+        _ => unreachable!("unexpected EngineCommandResult variant in undo/redo"),
+    };
+    match entry.entity_id {
+        Some(id) => println!("✓ {} #{} {} {}", verb, entry.seq, entry.op, id),
+        None => println!("✓ {} #{} {}", verb, entry.seq, entry.op),
+    }
+    Ok(())
+}
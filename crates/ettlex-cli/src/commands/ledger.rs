@@ -0,0 +1,72 @@
+//! Snapshot ledger commands
+
+use clap::{Args, Subcommand};
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_engine::commands::engine_query::{apply_engine_query, EngineQuery, EngineQueryResult};
+
+#[derive(Debug, Args)]
+pub struct LedgerArgs {
+    #[command(subcommand)]
+    pub command: LedgerCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum LedgerCommand {
+    /// Recompute the snapshot hash chain and report the first tampered row
+    Verify(VerifyArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct VerifyArgs {
+    #[arg(long, default_value = ".ettlex/store.db")]
+    pub db: String,
+
+    #[arg(long, default_value = ".ettlex/cas")]
+    pub cas: String,
+}
+
+pub fn execute(args: LedgerArgs) -> Result<(), Box<dyn std::error::Error>> {
+    match args.command {
+        LedgerCommand::Verify(verify_args) => execute_verify(verify_args),
+    }
+}
+
+fn execute_verify(args: VerifyArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = ettlex_store::env_keys::open_db(&args.db)?;
+    ettlex_store::migrations::apply_migrations(&mut conn)?;
+    let cas = ettlex_store::env_keys::open_cas(&args.cas)?;
+
+    let report = match apply_engine_query(EngineQuery::LedgerVerify, &conn, &cas, None)? {
+        EngineQueryResult::LedgerVerify(r) => r,
+        _ => {
+            return Err(Box::new(
+                ExError::new(ExErrorKind::Internal)
+                    .with_op("ledger_verify")
+                    .with_message("unexpected EngineQueryResult variant"),
+            ))
+        }
+    };
+
+    match report.first_break {
+        None => {
+            println!("Ledger intact:");
+            println!("  rows_checked: {}", report.rows_checked);
+            println!(
+                "  head_chain_hash: {}",
+                report.head_chain_hash.as_deref().unwrap_or("(empty)")
+            );
+            Ok(())
+        }
+        Some(brk) => Err(Box::new(
+            ExError::new(ExErrorKind::InvariantViolation)
+                .with_op("ledger_verify")
+                .with_entity_id(&brk.snapshot_id)
+                .with_message(format!(
+                    "ledger chain broken at position {}: expected {}, stored {}",
+                    brk.position,
+                    brk.expected_chain_hash,
+                    brk.stored_chain_hash.as_deref().unwrap_or("(none)")
+                )),
+        )),
+    }
+}
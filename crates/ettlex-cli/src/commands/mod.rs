@@ -1,4 +1,22 @@
 //! CLI commands
 
+pub mod apply;
+pub mod approval;
+pub mod backup;
+pub mod db;
+pub mod events;
+pub mod evidence;
+pub mod feature;
+pub mod import;
+pub mod journal;
+pub mod ledger;
+pub mod patch;
+pub mod query;
+pub mod refactor;
 pub mod render;
+pub mod root;
+pub mod serve;
+pub mod settings;
 pub mod snapshot;
+pub mod summary;
+pub mod token;
@@ -0,0 +1,90 @@
+//! Patch command — apply incremental seed updates declared as deltas
+
+#![allow(clippy::result_large_err)]
+
+use std::io::Read;
+use std::path::PathBuf;
+
+use clap::{Args, Subcommand};
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_engine::commands::patch::{apply_patch, patch_digest, PatchFile};
+
+use super::import::print_cas_dedup;
+
+#[derive(Debug, Args)]
+pub struct PatchArgs {
+    #[command(subcommand)]
+    pub command: PatchCommand,
+
+    #[arg(long, default_value = ".ettlex/store.db", global = true)]
+    pub db: String,
+
+    #[arg(long, default_value = ".ettlex/cas", global = true)]
+    pub cas: String,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PatchCommand {
+    /// Apply a patch file in one transaction
+    Apply {
+        /// YAML patch file
+        file: PathBuf,
+
+        /// Check preconditions and run the patch, then roll it back
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Print the content digests to use as `expect` preconditions
+    Digest {
+        /// Ettle IDs or slugs
+        #[arg(required = true)]
+        ettles: Vec<String>,
+    },
+}
+
+pub fn execute(args: PatchArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = ettlex_store::env_keys::open_db(&args.db)?;
+    ettlex_store::migrations::apply_migrations(&mut conn)?;
+
+    match args.command {
+        PatchCommand::Apply { file, dry_run } => {
+            let patch = parse_patch(std::fs::File::open(&file)?)?;
+            let cas = ettlex_store::env_keys::open_cas(&args.cas)?;
+            let report = apply_patch(&mut conn, &cas, &patch, dry_run)?;
+            if report.dry_run {
+                println!(
+                    "Dry run: {} op(s) would apply; nothing written",
+                    report.applied
+                );
+            } else {
+                println!("✓ Applied {} op(s)", report.applied);
+            }
+            for (key, ettle_id) in &report.created {
+                match key {
+                    Some(key) => println!("  {} -> {}", key, ettle_id),
+                    None => println!("  {}", ettle_id),
+                }
+            }
+            print_cas_dedup(&report.cas);
+        }
+        PatchCommand::Digest { ettles } => {
+            let cas = ettlex_store::env_keys::open_cas(&args.cas)?;
+            for reference in ettles {
+                let (digest, ettle_id) = patch_digest(&conn, &cas, &reference)?;
+                println!("{}  {}", digest, ettle_id);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// # Errors
+/// * `InvalidInput` - Malformed YAML or unknown keys
+pub fn parse_patch(reader: impl Read) -> Result<PatchFile, ExError> {
+    let de = serde_yaml::Deserializer::from_reader(reader);
+    serde_yaml::with::singleton_map_recursive::deserialize(de).map_err(|e| {
+        ExError::new(ExErrorKind::InvalidInput)
+            .with_op("patch_apply")
+            .with_message(format!("invalid patch: {}", e))
+    })
+}
@@ -0,0 +1,728 @@
+//! Read-only engine queries as JSON
+//!
+//! `ettlex query <verb>` runs one `EngineQuery` against the store and prints
+//! the result as a JSON document on stdout. List verbs take `--limit` and
+//! `--cursor`, and return `{ items, cursor, has_more }`; pass the returned
+//! `cursor` back to fetch the next page.
+
+use clap::{Args, Subcommand};
+use ettlex_core::policy_provider::PolicyProvider;
+use ettlex_engine::commands::engine_query::{apply_engine_query, EngineQuery, EngineQueryResult};
+use ettlex_engine::commands::read_tools::{
+    EptIssueKind, ExclusionReason, LeafSampleFilter, ListOptions, Page, ProfileGetResult,
+    RefinementReferrer, SnapshotGetResult,
+};
+use ettlex_store::cas::FsStore;
+use ettlex_store::file_policy_provider::FilePolicyProvider;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+#[derive(Debug, Args)]
+pub struct QueryArgs {
+    #[command(subcommand)]
+    pub verb: QueryVerb,
+
+    /// Print the JSON on one line
+    #[arg(long, global = true)]
+    pub compact: bool,
+
+    #[arg(long, default_value = ".ettlex/store.db", global = true)]
+    pub db: String,
+
+    #[arg(long, default_value = ".ettlex/cas", global = true)]
+    pub cas: String,
+
+    /// Directory of policy documents, for `policy-list` and `policy-read`
+    #[arg(long, default_value = "policies", global = true)]
+    pub policies: String,
+}
+
+/// Pagination flags of list verbs.
+#[derive(Debug, Clone, Default, Args, Deserialize)]
+pub struct PageArgs {
+    /// Maximum number of items to return (default 100)
+    #[arg(long)]
+    pub limit: Option<usize>,
+
+    /// `cursor` of the previous page
+    #[arg(long)]
+    pub cursor: Option<String>,
+}
+
+impl PageArgs {
+    fn options(self) -> ListOptions {
+        ListOptions {
+            limit: self.limit,
+            cursor: self.cursor,
+            ..ListOptions::default()
+        }
+    }
+}
+
+/// A query verb. `ettlex serve` accepts the same verbs as JSON, tagged by
+/// `verb` in kebab case: `{"verb": "ettle-get", "ettle": "ettle:a"}`.
+#[derive(Debug, Subcommand, Deserialize)]
+#[serde(tag = "verb", rename_all = "kebab-case")]
+pub enum QueryVerb {
+    /// State version, semantic head and workspace counts
+    StateVersion,
+    /// Row counts of the main store tables
+    StoreStats,
+    /// Digest over all active semantic content
+    StoreFingerprint,
+
+    /// An Ettle with its owner and reviewers
+    EttleGet {
+        /// Ettle ID or slug
+        ettle: String,
+    },
+    /// Ettles in ID order
+    EttleList {
+        #[command(flatten)]
+        #[serde(flatten)]
+        page: PageArgs,
+        /// Only Ettles whose ID starts with this prefix
+        #[arg(long)]
+        prefix: Option<String>,
+    },
+    /// Leaf Ettles in ID order
+    LeafList {
+        /// Only leaves under this root
+        #[arg(long)]
+        root: Option<String>,
+        #[command(flatten)]
+        #[serde(flatten)]
+        page: PageArgs,
+    },
+    /// Reproducible random sample of leaf Ettles for audit spot-checks
+    SampleLeaves {
+        /// Sampling seed; the same seed and state version give the same sample
+        #[arg(long)]
+        seed: u64,
+        /// Number of leaves to draw
+        #[arg(long)]
+        count: usize,
+        /// Only leaves under this root
+        #[arg(long)]
+        root: Option<String>,
+        /// Only leaves whose title contains this text (case-insensitive)
+        #[arg(long)]
+        title: Option<String>,
+    },
+    /// Refinement path from a leaf to its root, with any problems on it
+    EptDiagnose {
+        /// Leaf Ettle ID or slug
+        leaf: String,
+    },
+    /// Refinement relations pointing at an Ettle from its parents
+    EttleReferrers {
+        /// Ettle ID or slug
+        ettle: String,
+    },
+    /// Ettles a principal owns (and, with --reviewing, reviews)
+    EttleByOwner {
+        owner: String,
+        #[arg(long)]
+        #[serde(default)]
+        reviewing: bool,
+    },
+
+    /// A constraint, tombstoned or not
+    ConstraintGet { constraint_id: String },
+    /// Constraints of one family
+    ConstraintList {
+        #[arg(long)]
+        family: String,
+        #[arg(long)]
+        #[serde(default)]
+        include_tombstoned: bool,
+    },
+    /// Ettles a constraint Ettle is attached to
+    ConstraintAttachments {
+        constraint_id: String,
+        #[arg(long)]
+        #[serde(default)]
+        include_tombstoned: bool,
+    },
+    /// Active constraint relations with a missing or tombstoned end
+    ConstraintOrphans,
+    /// Constraints in force for an Ettle, inherited ones included
+    EffectiveConstraints {
+        /// Ettle ID or slug
+        ettle: String,
+    },
+
+    /// A registered root with its head snapshot
+    RootGet { ettle: String },
+    /// Registered roots and unregistered implicit roots
+    RootList {
+        #[arg(long)]
+        #[serde(default)]
+        include_retired: bool,
+    },
+
+    /// A decision, tombstoned or not
+    DecisionGet { decision_id: String },
+    /// Decisions in ID order
+    DecisionList {
+        #[command(flatten)]
+        #[serde(flatten)]
+        page: PageArgs,
+    },
+    /// Case-insensitive text search over decisions
+    DecisionSearch {
+        text: String,
+        #[arg(long)]
+        status: Option<String>,
+        #[command(flatten)]
+        #[serde(flatten)]
+        page: PageArgs,
+    },
+    /// Decisions linked to a target entity
+    DecisionByTarget {
+        /// Target kind (e.g. `ettle`)
+        target_kind: String,
+        target_id: String,
+        #[arg(long)]
+        #[serde(default)]
+        include_tombstoned: bool,
+    },
+    /// Decisions of an Ettle, optionally with its ancestors'
+    EttleDecisions {
+        ettle: String,
+        #[arg(long)]
+        #[serde(default)]
+        include_ancestors: bool,
+    },
+
+    /// A single comment
+    CommentGet { comment_id: String },
+    /// Comments on a target, oldest first
+    CommentList {
+        target_kind: String,
+        target_id: String,
+        #[arg(long)]
+        #[serde(default)]
+        include_resolved: bool,
+        #[command(flatten)]
+        #[serde(flatten)]
+        page: PageArgs,
+    },
+
+    /// A snapshot ledger row (ID or unique prefix)
+    SnapshotGet { snapshot: String },
+    /// Snapshot ledger rows, optionally for one root
+    SnapshotList {
+        #[arg(long)]
+        root: Option<String>,
+        /// Skip rows of roots that are not active in the registry
+        #[arg(long)]
+        #[serde(default)]
+        registered_only: bool,
+    },
+    /// Manifest digest of an Ettle's latest committed snapshot
+    SnapshotHead { ettle: String },
+    /// Head snapshot, age and drift of every root
+    Heads,
+    /// State of a queued snapshot commit
+    CommitTicket { ticket: String },
+    /// A snapshot manifest, by snapshot ID or manifest digest
+    ManifestGet {
+        /// Snapshot ID (or unique prefix)
+        #[arg(required_unless_present = "digest", conflicts_with = "digest")]
+        snapshot: Option<String>,
+        #[arg(long)]
+        digest: Option<String>,
+    },
+    /// Recompute the snapshot ledger hash chain
+    LedgerVerify,
+
+    /// A profile by reference
+    ProfileGet { profile_ref: String },
+    /// The default profile
+    ProfileDefault,
+    /// A profile with its `extends` chain merged
+    ProfileResolve {
+        profile_ref: Option<String>,
+        /// Environment whose default profile to resolve
+        #[arg(long, conflicts_with = "profile_ref")]
+        environment: Option<String>,
+    },
+    /// Profiles in reference order
+    ProfileList {
+        #[command(flatten)]
+        #[serde(flatten)]
+        page: PageArgs,
+    },
+
+    /// An approval request with its payload
+    ApprovalGet { approval_token: String },
+    /// Approval requests
+    ApprovalList {
+        #[command(flatten)]
+        #[serde(flatten)]
+        page: PageArgs,
+    },
+    /// Lifecycle events of an approval request, oldest first
+    ApprovalHistory { approval_token: String },
+
+    /// Policies in the policy directory
+    PolicyList,
+    /// Full text of a policy document
+    PolicyRead { policy_ref: String },
+
+    /// Effective value and source of one setting, or of all of them
+    SettingsGet { key: Option<String> },
+}
+
+impl QueryVerb {
+    fn into_query(self) -> EngineQuery {
+        match self {
+            QueryVerb::StateVersion => EngineQuery::StateGetVersion,
+            QueryVerb::StoreStats => EngineQuery::StoreStats,
+            QueryVerb::StoreFingerprint => EngineQuery::StoreFingerprint,
+            QueryVerb::EttleGet { ettle } => EngineQuery::EttleGet { ettle_id: ettle },
+            QueryVerb::EttleList { page, prefix } => EngineQuery::EttleList(ListOptions {
+                prefix_filter: prefix,
+                ..page.options()
+            }),
+            QueryVerb::LeafList { root, page } => EngineQuery::LeafList {
+                root_ettle_id: root,
+                options: page.options(),
+            },
+            QueryVerb::SampleLeaves {
+                seed,
+                count,
+                root,
+                title,
+            } => EngineQuery::SampleLeaves {
+                seed,
+                count,
+                filter: LeafSampleFilter {
+                    root_ettle_id: root,
+                    title_contains: title,
+                },
+            },
+            QueryVerb::EptDiagnose { leaf } => EngineQuery::EptDiagnose {
+                leaf_ettle_id: leaf,
+            },
+            QueryVerb::EttleReferrers { ettle } => {
+                EngineQuery::EttleListReferrers { ettle_id: ettle }
+            }
+            QueryVerb::EttleByOwner { owner, reviewing } => EngineQuery::EttleListByOwner {
+                owner,
+                include_reviewing: reviewing,
+            },
+            QueryVerb::ConstraintGet { constraint_id } => {
+                EngineQuery::ConstraintGet { constraint_id }
+            }
+            QueryVerb::ConstraintList {
+                family,
+                include_tombstoned,
+            } => EngineQuery::ConstraintListByFamily {
+                family,
+                include_tombstoned,
+            },
+            QueryVerb::ConstraintAttachments {
+                constraint_id,
+                include_tombstoned,
+            } => EngineQuery::ConstraintListAttachments {
+                constraint_id,
+                include_tombstoned,
+            },
+            QueryVerb::ConstraintOrphans => EngineQuery::ConstraintListOrphans,
+            QueryVerb::EffectiveConstraints { ettle } => {
+                EngineQuery::EttleEffectiveConstraints { ettle_id: ettle }
+            }
+            QueryVerb::RootGet { ettle } => EngineQuery::RootGet { ettle_id: ettle },
+            QueryVerb::RootList { include_retired } => EngineQuery::RootList { include_retired },
+            QueryVerb::DecisionGet { decision_id } => EngineQuery::DecisionGet { decision_id },
+            QueryVerb::DecisionList { page } => EngineQuery::DecisionList(page.options()),
+            QueryVerb::DecisionSearch { text, status, page } => EngineQuery::DecisionSearch {
+                text,
+                status_filter: status,
+                options: page.options(),
+            },
+            QueryVerb::DecisionByTarget {
+                target_kind,
+                target_id,
+                include_tombstoned,
+            } => EngineQuery::DecisionListByTarget {
+                target_kind,
+                target_id,
+                include_tombstoned,
+            },
+            QueryVerb::EttleDecisions {
+                ettle,
+                include_ancestors,
+            } => EngineQuery::EttleListDecisions {
+                ettle_id: ettle,
+                include_eps: false,
+                include_ancestors,
+            },
+            QueryVerb::CommentGet { comment_id } => EngineQuery::CommentGet { comment_id },
+            QueryVerb::CommentList {
+                target_kind,
+                target_id,
+                include_resolved,
+                page,
+            } => EngineQuery::CommentList {
+                target_kind,
+                target_id,
+                include_resolved,
+                options: page.options(),
+            },
+            QueryVerb::SnapshotGet { snapshot } => EngineQuery::SnapshotGet {
+                snapshot_id: snapshot,
+            },
+            QueryVerb::SnapshotList {
+                root,
+                registered_only,
+            } => EngineQuery::SnapshotList {
+                ettle_id: root,
+                registered_only,
+            },
+            QueryVerb::SnapshotHead { ettle } => EngineQuery::SnapshotGetHead {
+                realised_ettle_id: ettle,
+            },
+            QueryVerb::Heads => EngineQuery::HeadsList,
+            QueryVerb::CommitTicket { ticket } => EngineQuery::SnapshotCommitTicket { ticket },
+            QueryVerb::ManifestGet { snapshot, digest } => match digest {
+                Some(manifest_digest) => EngineQuery::ManifestGetByDigest { manifest_digest },
+                None => EngineQuery::ManifestGetBySnapshot {
+                    snapshot_id: snapshot.unwrap_or_default(),
+                },
+            },
+            QueryVerb::LedgerVerify => EngineQuery::LedgerVerify,
+            QueryVerb::ProfileGet { profile_ref } => EngineQuery::ProfileGet { profile_ref },
+            QueryVerb::ProfileDefault => EngineQuery::ProfileGetDefault,
+            QueryVerb::ProfileResolve {
+                profile_ref,
+                environment,
+            } => EngineQuery::ProfileResolve {
+                profile_ref,
+                environment,
+            },
+            QueryVerb::ProfileList { page } => EngineQuery::ProfileList(page.options()),
+            QueryVerb::ApprovalGet { approval_token } => {
+                EngineQuery::ApprovalGet { approval_token }
+            }
+            QueryVerb::ApprovalList { page } => EngineQuery::ApprovalList(page.options()),
+            QueryVerb::ApprovalHistory { approval_token } => {
+                EngineQuery::ApprovalHistory { approval_token }
+            }
+            QueryVerb::PolicyList => EngineQuery::PolicyList,
+            QueryVerb::PolicyRead { policy_ref } => EngineQuery::PolicyRead { policy_ref },
+            QueryVerb::SettingsGet { key } => EngineQuery::SettingsGet { key },
+        }
+    }
+}
+
+/// Run the query and return its result as JSON.
+pub fn run(args: QueryArgs) -> Result<Value, Box<dyn std::error::Error>> {
+    let mut conn = ettlex_store::env_keys::open_db(&args.db)?;
+    ettlex_store::migrations::apply_migrations(&mut conn)?;
+    let cas = ettlex_store::env_keys::open_cas(&args.cas)?;
+    let policies = FilePolicyProvider::new(&args.policies);
+    query_json(args.verb, &conn, &cas, &policies)
+}
+
+/// Run one verb against an open store and return its result as JSON.
+pub(crate) fn query_json(
+    verb: QueryVerb,
+    conn: &Connection,
+    cas: &FsStore,
+    policies: &dyn PolicyProvider,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let result = apply_engine_query(verb.into_query(), conn, cas, Some(policies))?;
+    result_to_json(result)
+}
+
+pub fn execute(args: QueryArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let compact = args.compact;
+    let value = run(args)?;
+    if compact {
+        println!("{}", serde_json::to_string(&value)?);
+    } else {
+        println!("{}", serde_json::to_string_pretty(&value)?);
+    }
+    Ok(())
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<Value, Box<dyn std::error::Error>> {
+    Ok(serde_json::to_value(value)?)
+}
+
+fn page_to_json<T>(
+    page: &Page<T>,
+    item: impl Fn(&T) -> Result<Value, Box<dyn std::error::Error>>,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    Ok(json!({
+        "items": page.items.iter().map(item).collect::<Result<Vec<_>, _>>()?,
+        "cursor": page.cursor,
+        "has_more": page.has_more,
+    }))
+}
+
+fn profile_to_json(p: &ProfileGetResult) -> Result<Value, Box<dyn std::error::Error>> {
+    Ok(json!({
+        "profile_ref": p.profile_ref,
+        "profile_digest": p.profile_digest,
+        "payload": p.payload_json,
+    }))
+}
+
+fn result_to_json(result: EngineQueryResult) -> Result<Value, Box<dyn std::error::Error>> {
+    Ok(match result {
+        EngineQueryResult::StateVersion(v) => json!({
+            "state_version": v.state_version,
+            "semantic_head_digest": v.semantic_head_digest,
+            "summary": v.summary.map(|s| json!({
+                "ettle_count": s.ettle_count,
+                "snapshot_count": s.snapshot_count,
+                "pending_approval_count": s.pending_approval_count,
+                "root_heads": s.root_heads.iter().map(|h| json!({
+                    "root_ettle_id": h.root_ettle_id,
+                    "display_name": h.display_name,
+                    "snapshot_id": h.snapshot_id,
+                    "manifest_digest": h.manifest_digest,
+                })).collect::<Vec<_>>(),
+            })),
+        }),
+        // Latency and outcome counters are per process, so always empty here
+        EngineQueryResult::StoreStats(s) => json!({ "tables": to_json(&s.tables)? }),
+        EngineQueryResult::StoreFingerprint(f) => json!({
+            "digest": f.digest,
+            "ettles_digest": f.ettles_digest,
+            "relations_digest": f.relations_digest,
+            "ettle_count": f.ettle_count,
+            "relation_count": f.relation_count,
+        }),
+        EngineQueryResult::EttleGet(r) => json!({
+            "ettle": json!({
+                "id": r.ettle.id,
+                "title": r.ettle.title,
+                "why": r.ettle.why,
+                "what": r.ettle.what,
+                "how": r.ettle.how,
+                "slug": r.ettle.slug,
+                "content_format": r.ettle.content_format,
+                "reasoning_link_id": r.ettle.reasoning_link_id,
+                "reasoning_link_type": r.ettle.reasoning_link_type,
+                "created_at": r.ettle.created_at,
+                "updated_at": r.ettle.updated_at,
+                "tombstoned_at": r.ettle.tombstoned_at,
+                "archived_at": r.ettle.archived_at,
+            }),
+            "owner": r.assignees.owner,
+            "reviewers": r.assignees.reviewers,
+        }),
+        EngineQueryResult::EttleList(page) | EngineQueryResult::LeafList(page) => {
+            page_to_json(&page, to_json)?
+        }
+        EngineQueryResult::SampleLeaves(s) => json!({
+            "seed": s.seed,
+            "state_version": s.state_version,
+            "population": s.population,
+            "items": to_json(&s.items)?,
+        }),
+        EngineQueryResult::EptDiagnose(d) => json!({
+            "leaf_ettle_id": d.leaf_ettle_id,
+            "path": d.path,
+            "issues": d.issues.iter().map(|i| {
+                let mut issue = match &i.kind {
+                    EptIssueKind::NotALeaf { child_ettle_ids } => json!({
+                        "kind": "not_a_leaf",
+                        "child_ettle_ids": child_ettle_ids,
+                    }),
+                    EptIssueKind::AmbiguousParent { candidates } => json!({
+                        "kind": "ambiguous_parent",
+                        "candidates": candidates.iter().map(|c| json!({
+                            "ordinal": c.ordinal,
+                            "relation_id": c.relation_id,
+                            "parent_ettle_id": c.parent_ettle_id,
+                            "title": c.title,
+                        })).collect::<Vec<_>>(),
+                    }),
+                    EptIssueKind::BrokenLink { relation_id, parent_ettle_id, parent_state } => {
+                        json!({
+                            "kind": "broken_link",
+                            "relation_id": relation_id,
+                            "parent_ettle_id": parent_ettle_id,
+                            "parent_state": parent_state,
+                        })
+                    }
+                    EptIssueKind::Cycle { parent_ettle_id } => json!({
+                        "kind": "cycle",
+                        "parent_ettle_id": parent_ettle_id,
+                    }),
+                };
+                issue["ettle_id"] = json!(i.ettle_id);
+                issue["suggested_fix"] = json!(i.suggested_fix);
+                issue
+            }).collect::<Vec<_>>(),
+        }),
+        EngineQueryResult::EttleListReferrers(r) => {
+            let referrer = |list: &[_]| {
+                list.iter()
+                    .map(|r: &RefinementReferrer| {
+                        json!({
+                            "relation_id": r.relation_id,
+                            "parent_ettle_id": r.parent_ettle_id,
+                            "parent_title": r.parent_title,
+                            "parent_tombstoned": r.parent_tombstoned,
+                            "ordinal": r.ordinal,
+                            "created_at": r.created_at,
+                            "tombstoned_at": r.tombstoned_at,
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            };
+            json!({ "active": referrer(&r.active), "tombstoned": referrer(&r.tombstoned) })
+        }
+        EngineQueryResult::EttleListByOwner(items) => json!(items
+            .iter()
+            .map(|e| json!({ "ettle_id": e.ettle_id, "title": e.title, "role": e.role }))
+            .collect::<Vec<_>>()),
+        EngineQueryResult::ConstraintGet(c) => to_json(&c)?,
+        EngineQueryResult::ConstraintListByFamily(cs) => to_json(&cs)?,
+        EngineQueryResult::ConstraintListAttachments(items) => json!(items
+            .iter()
+            .map(|a| json!({
+                "relation_id": a.relation_id,
+                "ettle_id": a.ettle_id,
+                "ettle_title": a.ettle_title,
+                "ettle_tombstoned": a.ettle_tombstoned,
+                "ordinal": a.ordinal,
+                "properties": a.properties_json,
+                "created_at": a.created_at,
+                "tombstoned_at": a.tombstoned_at,
+            }))
+            .collect::<Vec<_>>()),
+        EngineQueryResult::ConstraintListOrphans(relations) => to_json(&relations)?,
+        EngineQueryResult::EttleEffectiveConstraints(e) => json!({
+            "ettle_id": e.ettle_id,
+            "path": e.path,
+            "constraints": e.constraints.iter().map(|c| json!({
+                "constraint_id": c.constraint_id,
+                "constraint_title": c.constraint_title,
+                "relation_id": c.relation_id,
+                "attached_to": c.attached_to,
+                "inherited": c.inherited,
+                "family": c.family,
+                "ordinal": c.ordinal,
+                "properties": c.properties_json,
+            })).collect::<Vec<_>>(),
+            "excluded": e.excluded.iter().map(|x| {
+                let (reason, by) = match &x.reason {
+                    ExclusionReason::ScopedToSelf => ("scoped_to_self", None),
+                    ExclusionReason::Shadowed { by_relation_id } => ("shadowed", Some(by_relation_id)),
+                    ExclusionReason::Overridden { by_relation_id } => ("overridden", Some(by_relation_id)),
+                };
+                json!({
+                    "constraint_id": x.constraint_id,
+                    "relation_id": x.relation_id,
+                    "attached_to": x.attached_to,
+                    "reason": reason,
+                    "by_relation_id": by,
+                })
+            }).collect::<Vec<_>>(),
+        }),
+        EngineQueryResult::RootGet(r) => json!({
+            "root": to_json(&r.root)?,
+            "head_snapshot_id": r.head_snapshot_id,
+            "snapshot_count": r.snapshot_count,
+        }),
+        EngineQueryResult::RootList(r) => json!({
+            "registered": r.registered.iter().map(|e| Ok(json!({
+                "root": to_json(&e.root)?,
+                "head_snapshot_id": e.head_snapshot_id,
+                "snapshot_count": e.snapshot_count,
+            }))).collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?,
+            "unregistered": r.unregistered,
+        }),
+        EngineQueryResult::DecisionGet(d) => to_json(&d)?,
+        EngineQueryResult::DecisionList(page) | EngineQueryResult::DecisionSearch(page) => {
+            page_to_json(&page, to_json)?
+        }
+        EngineQueryResult::DecisionListByTarget(ds) | EngineQueryResult::EttleListDecisions(ds) => {
+            to_json(&ds)?
+        }
+        EngineQueryResult::CommentGet(c) => to_json(&c)?,
+        EngineQueryResult::CommentList(page) => page_to_json(&page, to_json)?,
+        EngineQueryResult::SnapshotGet(s) => snapshot_to_json(&s),
+        EngineQueryResult::SnapshotList(rows) => {
+            json!(rows.iter().map(snapshot_to_json).collect::<Vec<_>>())
+        }
+        EngineQueryResult::SnapshotGetHead(digest) => json!({ "manifest_digest": digest }),
+        EngineQueryResult::HeadsList(heads) => to_json(&heads)?,
+        EngineQueryResult::SnapshotCommitTicket(ticket) => to_json(&ticket)?,
+        EngineQueryResult::ManifestGet(m) => json!({
+            "snapshot_id": m.snapshot_id,
+            "manifest_digest": m.manifest_digest,
+            "semantic_manifest_digest": m.semantic_manifest_digest,
+            "manifest": serde_json::from_slice::<Value>(&m.manifest_bytes)?,
+        }),
+        EngineQueryResult::LedgerVerify(report) => to_json(&report)?,
+        EngineQueryResult::ProfileGet(p) => profile_to_json(&p)?,
+        EngineQueryResult::ProfileResolve(p) => json!({
+            "profile_ref": p.profile_ref,
+            "profile_digest": p.profile_digest,
+            "payload": p.parsed_profile,
+            "effective": p.effective_profile,
+            "extends_chain": p.extends_chain,
+        }),
+        EngineQueryResult::ProfileList(page) => page_to_json(&page, profile_to_json)?,
+        EngineQueryResult::ApprovalGet(a) => json!({
+            "approval_token": a.approval_token,
+            "request_digest": a.request_digest,
+            "semantic_request_digest": a.semantic_request_digest,
+            "payload": to_json(&a.payload)?,
+            "assignees": a.assignees,
+        }),
+        EngineQueryResult::ApprovalList(page) => page_to_json(&page, |a| {
+            Ok(json!({
+                "approval_token": a.approval_token,
+                "reason_code": a.reason_code,
+                "semantic_request_digest": a.semantic_request_digest,
+                "status": a.status,
+                "created_at": a.created_at,
+                "request_digest": a.request_digest,
+            }))
+        })?,
+        EngineQueryResult::ApprovalHistory(events) => json!(events
+            .iter()
+            .map(|e| json!({
+                "id": e.id,
+                "from_status": e.from_status,
+                "to_status": e.to_status,
+                "actor": e.actor,
+                "occurred_at_ms": e.occurred_at_ms,
+            }))
+            .collect::<Vec<_>>()),
+        EngineQueryResult::PolicyList(entries) => json!(entries
+            .iter()
+            .map(|p| json!({ "policy_ref": p.policy_ref, "version": p.version }))
+            .collect::<Vec<_>>()),
+        EngineQueryResult::PolicyRead(p) => json!({ "policy_ref": p.policy_ref, "text": p.text }),
+        EngineQueryResult::SettingsGet(entries) => to_json(&entries)?,
+        _ => unreachable!("query verbs only issue queries with a JSON rendering"),
+    })
+}
+
+fn snapshot_to_json(s: &SnapshotGetResult) -> Value {
+    json!({
+        "snapshot_id": s.snapshot_id,
+        "root_ettle_id": s.root_ettle_id,
+        "manifest_digest": s.manifest_digest,
+        "semantic_manifest_digest": s.semantic_manifest_digest,
+        "created_at": s.created_at,
+        "parent_snapshot_id": s.parent_snapshot_id,
+        "policy_ref": s.policy_ref,
+        "profile_ref": s.profile_ref,
+        "status": s.status,
+        "message": s.message,
+        "annotations": s.annotations,
+    })
+}
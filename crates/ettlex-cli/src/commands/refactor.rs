@@ -0,0 +1,194 @@
+//! Refactor commands — rewrite references across Ettle content
+
+#![allow(clippy::result_large_err)]
+
+use std::io::{BufRead, Write};
+
+use clap::{Args, Subcommand};
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_engine::commands::command::{apply_command_with_options, Command, CommandResult};
+use ettlex_engine::commands::constraint::TargetSelector;
+use ettlex_engine::commands::engine_command::CommandOptions;
+use ettlex_engine::commands::refactor::{ContentRefOccurrence, ContentRefSite};
+use ettlex_store::cas::FsStore;
+use rusqlite::Connection;
+
+#[derive(Debug, Args)]
+pub struct RefactorArgs {
+    #[command(subcommand)]
+    pub command: RefactorCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum RefactorCommand {
+    /// Rewrite references to a renamed ID or slug in Ettle why/what/how
+    Rename(RenameArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct RenameArgs {
+    /// Reference to replace (Ettle ID, slug or any other token)
+    #[arg(long)]
+    pub from: String,
+
+    /// Replacement reference
+    #[arg(long)]
+    pub to: String,
+
+    /// Only search this Ettle (ID or slug) and its refinement subtree
+    #[arg(long)]
+    pub root: Option<String>,
+
+    /// List occurrences without rewriting anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Rewrite every occurrence without asking
+    #[arg(long, conflicts_with = "dry_run")]
+    pub yes: bool,
+
+    #[arg(long, default_value = ".ettlex/store.db")]
+    pub db: String,
+
+    #[arg(long, default_value = ".ettlex/cas")]
+    pub cas: String,
+}
+
+pub fn execute(args: RefactorArgs) -> Result<(), Box<dyn std::error::Error>> {
+    match args.command {
+        RefactorCommand::Rename(rename_args) => execute_rename(rename_args),
+    }
+}
+
+fn execute_rename(args: RenameArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = ettlex_store::env_keys::open_db(&args.db)?;
+    ettlex_store::migrations::apply_migrations(&mut conn)?;
+    let cas = ettlex_store::env_keys::open_cas(&args.cas)?;
+    let selector = args
+        .root
+        .clone()
+        .map(|root_ettle_id| TargetSelector::Subtree { root_ettle_id });
+
+    let (preview, _) = rename(&mut conn, &cas, &args, selector.clone(), None, true)?;
+    if preview.is_empty() {
+        println!("No references to '{}' found", args.from);
+        return Ok(());
+    }
+    if args.dry_run {
+        for occurrence in &preview {
+            println!("{}", describe(occurrence));
+        }
+        println!(
+            "{} occurrence(s) of '{}' would be rewritten to '{}'",
+            preview.len(),
+            args.from,
+            args.to
+        );
+        return Ok(());
+    }
+
+    let only = if args.yes {
+        None
+    } else {
+        let stdin = std::io::stdin();
+        let accepted = confirm_occurrences(
+            &preview,
+            &args.to,
+            &mut stdin.lock(),
+            &mut std::io::stdout(),
+        )?;
+        if accepted.is_empty() {
+            println!("Nothing rewritten");
+            return Ok(());
+        }
+        Some(accepted)
+    };
+
+    let (occurrences, updated) = rename(&mut conn, &cas, &args, selector, only, false)?;
+    println!(
+        "✓ Rewrote {} occurrence(s) of '{}' to '{}' in {} ettle(s)",
+        occurrences.iter().filter(|o| o.rewritten).count(),
+        args.from,
+        args.to,
+        updated.len()
+    );
+    Ok(())
+}
+
+fn rename(
+    conn: &mut Connection,
+    cas: &FsStore,
+    args: &RenameArgs,
+    selector: Option<TargetSelector>,
+    only: Option<Vec<ContentRefSite>>,
+    dry_run: bool,
+) -> Result<(Vec<ContentRefOccurrence>, Vec<String>), ExError> {
+    let cmd = Command::ContentRefRename {
+        from: args.from.clone(),
+        to: args.to.clone(),
+        selector,
+        only,
+    };
+    let (result, _) = apply_command_with_options(
+        cmd,
+        None,
+        CommandOptions { dry_run },
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )?;
+    match result {
+        CommandResult::ContentRefRename {
+            occurrences,
+            updated_ettle_ids,
+        } => Ok((occurrences, updated_ettle_ids)),
+        _ => Err(ExError::new(ExErrorKind::Internal)
+            .with_op("refactor_rename")
+            .with_message("unexpected CommandResult variant")),
+    }
+}
+
+/// Ask about each occurrence in turn and return the accepted sites
+///
+/// Answers: `y` rewrite, `n` (or empty) skip, `a` rewrite this and all
+/// remaining, `q` skip this and all remaining. End of input counts as `q`.
+pub fn confirm_occurrences(
+    occurrences: &[ContentRefOccurrence],
+    to: &str,
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+) -> std::io::Result<Vec<ContentRefSite>> {
+    let mut accepted = Vec::new();
+    for (i, occurrence) in occurrences.iter().enumerate() {
+        writeln!(output, "{}", describe(occurrence))?;
+        write!(output, "Rewrite to '{}'? [y/N/a/q] ", to)?;
+        output.flush()?;
+        let mut answer = String::new();
+        if input.read_line(&mut answer)? == 0 {
+            break;
+        }
+        match answer.trim().to_ascii_lowercase().as_str() {
+            "y" | "yes" => accepted.push(occurrence.site.clone()),
+            "a" | "all" => {
+                accepted.extend(occurrences[i..].iter().map(|o| o.site.clone()));
+                break;
+            }
+            "q" | "quit" => break,
+            _ => {}
+        }
+    }
+    Ok(accepted)
+}
+
+fn describe(occurrence: &ContentRefOccurrence) -> String {
+    format!(
+        "{} {}:{}: {}",
+        occurrence.site.ettle_id,
+        occurrence.site.field.as_str(),
+        occurrence.line,
+        occurrence.context
+    )
+}
@@ -1,9 +1,19 @@
 //! Render command
 //!
 //! Usage: ettlex render <ETTLE_ID> [--output <FILE>]
+//!        ettlex render tree <ETTLE_ID> [--max-depth <N>] [--number] [--toc]
+//!
+//! `ettlex render ettle|bundle|tree ... --output <FILE> --watch` keeps running
+//! and re-renders whenever the store's state version moves, rewriting the
+//! file only when the document actually changed.
 
 use clap::{Args, Subcommand};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use ettlex_core::render::{TemplateMode, TemplateVariables};
+use ettlex_engine::commands::engine_query::read_state_version;
+use rusqlite::Connection;
 
 #[derive(Debug, Args)]
 pub struct RenderArgs {
@@ -17,16 +27,62 @@ pub enum RenderCommand {
     Ettle(RenderEttleArgs),
     /// Render a leaf bundle (full EPT path) to Markdown
     Bundle(RenderBundleArgs),
+    /// Render the "state of the architecture" overview report
+    Report(RenderReportArgs),
+    /// Render an ettle and its refinement subtree as one document
+    Tree(RenderTreeArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct WatchArgs {
+    /// Keep running and re-render to `--output` whenever the store changes
+    #[arg(long, requires = "output")]
+    pub watch: bool,
+
+    /// How often `--watch` polls the state version, in milliseconds
+    #[arg(
+        long,
+        default_value_t = 500,
+        requires = "watch",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub interval_ms: u64,
+}
+
+#[derive(Debug, Args)]
+pub struct TemplateArgs {
+    /// Profile whose `variables` payload resolves `{{variables}}` (default: the default profile)
+    #[arg(long)]
+    pub profile: Option<String>,
+
+    /// Environment whose default profile applies when `--profile` is not
+    /// given (default: `$ETTLEX_ENV`, else the global default profile)
+    #[arg(long)]
+    pub env: Option<String>,
+
+    /// Fail if any `{{variable}}` has no binding
+    #[arg(long)]
+    pub strict: bool,
 }
 
 #[derive(Debug, Args)]
 pub struct RenderEttleArgs {
-    /// Ettle ID to render
+    /// Ettle ID or slug to render
     pub ettle_id: String,
 
     /// Output file path (default: stdout)
     #[arg(short, long)]
     pub output: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub template: TemplateArgs,
+
+    /// Render the ettle even if it is archived
+    #[arg(long)]
+    pub include_archived: bool,
+
+    #[command(flatten)]
+    pub watch: WatchArgs,
 }
 
 #[derive(Debug, Args)]
@@ -41,6 +97,52 @@ pub struct RenderBundleArgs {
     /// Output file path (default: stdout)
     #[arg(short, long)]
     pub output: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub template: TemplateArgs,
+
+    #[command(flatten)]
+    pub watch: WatchArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct RenderReportArgs {
+    /// Output format: md or html
+    #[arg(long, default_value = "md")]
+    pub format: String,
+
+    /// Number of recent snapshots to include
+    #[arg(long, default_value_t = 10)]
+    pub snapshots: u32,
+
+    /// Output file path (default: stdout)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct RenderTreeArgs {
+    /// Root ettle ID or slug
+    pub ettle_id: String,
+
+    /// Levels below the root to render (default: all)
+    #[arg(long)]
+    pub max_depth: Option<usize>,
+
+    /// Number sections hierarchically (1., 1.1, 1.1.1)
+    #[arg(long)]
+    pub number: bool,
+
+    /// Add a table of contents linking to each section
+    #[arg(long)]
+    pub toc: bool,
+
+    /// Output file path (default: stdout)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub watch: WatchArgs,
 }
 
 /// Execute render command
@@ -48,53 +150,338 @@ pub fn execute(args: RenderArgs) -> Result<(), Box<dyn std::error::Error>> {
     match args.command {
         RenderCommand::Ettle(ettle_args) => execute_render_ettle(ettle_args),
         RenderCommand::Bundle(bundle_args) => execute_render_bundle(bundle_args),
+        RenderCommand::Report(report_args) => execute_render_report(report_args),
+        RenderCommand::Tree(tree_args) => execute_render_tree(tree_args),
     }
 }
 
+/// The active environment: the explicit flag, else a non-empty `ETTLEX_ENV`.
+fn active_environment(flag: Option<&str>) -> Option<String> {
+    flag.map(String::from)
+        .or_else(|| std::env::var("ETTLEX_ENV").ok())
+        .filter(|e| !e.is_empty())
+}
+
+/// Load `{{variable}}` bindings from `--profile`, else the active
+/// environment's default profile, else the global default profile. The mode
+/// is `Strict` under `--strict` or when the profile sets `strict_variables`.
+fn template_variables(
+    conn: &Connection,
+    args: &TemplateArgs,
+    op: &str,
+) -> Result<(TemplateVariables, TemplateMode), Box<dyn std::error::Error>> {
+    let payload = match &args.profile {
+        Some(profile_ref) => ettlex_store::profile::load_profile_payload(conn, profile_ref)?
+            .ok_or_else(|| {
+                ettlex_core::errors::ExError::new(ettlex_core::errors::ExErrorKind::ProfileNotFound)
+                    .with_op(op)
+                    .with_entity_id(profile_ref.clone())
+                    .with_message(format!("profile not found: {}", profile_ref))
+            })?,
+        None => {
+            let default = match active_environment(args.env.as_deref()) {
+                Some(env) => ettlex_store::profile::load_environment_default_profile(conn, &env)?,
+                None => ettlex_store::profile::load_default_profile(conn)?,
+            };
+            match default {
+                Some((profile_ref, _, _)) => {
+                    ettlex_store::profile::load_profile_payload(conn, &profile_ref)?
+                        .unwrap_or(serde_json::Value::Null)
+                }
+                None => serde_json::Value::Null,
+            }
+        }
+    };
+    let vars = ettlex_core::render::template::variables_from_profile_payload(&payload);
+    let mode = if args.strict {
+        TemplateMode::Strict
+    } else {
+        ettlex_core::render::template::template_mode_from_profile_payload(&payload)
+    };
+    Ok((vars, mode))
+}
+
 /// Execute render ettle command
 fn execute_render_ettle(args: RenderEttleArgs) -> Result<(), Box<dyn std::error::Error>> {
     // Open database and apply any pending migrations
     let db_path = ".ettlex/store.db";
-    let mut conn = rusqlite::Connection::open(db_path)?;
-    ettlex_store::migrations::apply_migrations(&mut conn)?;
+    let conn = open_watched_store(db_path)?;
 
+    emit(&conn, args.output.as_deref(), &args.watch, |conn| {
+        render_ettle_document(conn, &args)
+    })
+}
+
+fn render_ettle_document(
+    conn: &Connection,
+    args: &RenderEttleArgs,
+) -> Result<String, Box<dyn std::error::Error>> {
     // Load tree
-    let store = ettlex_store::repo::hydration::load_tree(&conn)?;
+    let store = ettlex_store::repo::hydration::load_tree(conn)?;
 
-    // Render ettle
-    let markdown = ettlex_core::render::render_ettle(&store, &args.ettle_id)?;
+    // Resolve template variables from the selected (or default) profile
+    let (vars, mode) = template_variables(conn, &args.template, "render_ettle")?;
 
-    // Output
-    if let Some(output_path) = args.output {
-        std::fs::write(&output_path, markdown)?;
-        println!("✓ Rendered to {}", output_path.display());
-    } else {
-        print!("{}", markdown);
+    // Render ettle (accepts an ID or a slug)
+    let ettle_id = ettlex_store::repo::SqliteRepo::resolve_ettle_ref(conn, &args.ettle_id)?
+        .unwrap_or_else(|| args.ettle_id.clone());
+    if !args.include_archived {
+        let archived = ettlex_store::repo::SqliteRepo::get_ettle_record(conn, &ettle_id)?
+            .is_some_and(|r| r.archived_at.is_some());
+        if archived {
+            return Err(Box::new(
+                ettlex_core::errors::ExError::new(ettlex_core::errors::ExErrorKind::InvalidInput)
+                    .with_op("render_ettle")
+                    .with_entity_id(ettle_id.clone())
+                    .with_message(format!(
+                        "ettle is archived (pass --include-archived to render it): {}",
+                        ettle_id
+                    )),
+            ));
+        }
     }
-
-    Ok(())
+    Ok(ettlex_core::render::render_ettle_with_variables(
+        &store, &ettle_id, &vars, mode,
+    )?)
 }
 
 /// Execute render bundle command
 fn execute_render_bundle(args: RenderBundleArgs) -> Result<(), Box<dyn std::error::Error>> {
     // Open database and apply any pending migrations
     let db_path = ".ettlex/store.db";
-    let mut conn = rusqlite::Connection::open(db_path)?;
-    ettlex_store::migrations::apply_migrations(&mut conn)?;
+    let conn = open_watched_store(db_path)?;
 
-    // Load tree
-    let store = ettlex_store::repo::hydration::load_tree(&conn)?;
+    emit(&conn, args.output.as_deref(), &args.watch, |conn| {
+        // Load tree
+        let store = ettlex_store::repo::hydration::load_tree(conn)?;
+
+        // Resolve template variables from the selected (or default) profile
+        let (vars, mode) = template_variables(conn, &args.template, "render_bundle")?;
+
+        // Render bundle
+        Ok(ettlex_core::render::render_leaf_bundle_with_variables(
+            &store,
+            &args.leaf_id,
+            args.ep_ordinal,
+            &vars,
+            mode,
+        )?)
+    })
+}
+
+/// Execute render report command
+fn execute_render_report(args: RenderReportArgs) -> Result<(), Box<dyn std::error::Error>> {
+    use ettlex_engine::commands::engine_query::{
+        apply_engine_query, EngineQuery, EngineQueryResult,
+    };
 
-    // Render bundle
-    let markdown = ettlex_core::render::render_leaf_bundle(&store, &args.leaf_id, args.ep_ordinal)?;
+    let render: fn(&ettlex_core::render::ArchitectureReport) -> String = match args.format.as_str()
+    {
+        "md" => ettlex_core::render::render_architecture_report_markdown,
+        "html" => ettlex_core::render::render_architecture_report_html,
+        other => {
+            return Err(Box::new(
+                ettlex_core::errors::ExError::new(ettlex_core::errors::ExErrorKind::InvalidInput)
+                    .with_op("render_report")
+                    .with_message(format!("unknown format '{}' (expected md or html)", other)),
+            ))
+        }
+    };
+
+    // Open database and apply any pending migrations
+    let db_path = ".ettlex/store.db";
+    let mut conn = ettlex_store::env_keys::open_db(db_path)?;
+    ettlex_store::migrations::apply_migrations(&mut conn)?;
+    let cas = ettlex_store::env_keys::open_cas(".ettlex/cas")?;
+
+    let report = match apply_engine_query(
+        EngineQuery::ArchitectureReport {
+            recent_snapshot_limit: args.snapshots,
+        },
+        &conn,
+        &cas,
+        None,
+    )? {
+        EngineQueryResult::ArchitectureReport(r) => r,
+        _ => unreachable!("unexpected EngineQueryResult variant in render report"),
+    };
+    let document = render(&report);
 
     // Output
     if let Some(output_path) = args.output {
-        std::fs::write(&output_path, markdown)?;
+        std::fs::write(&output_path, document)?;
         println!("✓ Rendered to {}", output_path.display());
     } else {
-        print!("{}", markdown);
+        print!("{}", document);
     }
 
     Ok(())
 }
+
+/// Execute render tree command
+fn execute_render_tree(args: RenderTreeArgs) -> Result<(), Box<dyn std::error::Error>> {
+    // Open database and apply any pending migrations
+    let db_path = ".ettlex/store.db";
+    let conn = open_watched_store(db_path)?;
+    let cas = ettlex_store::env_keys::open_cas(".ettlex/cas")?;
+
+    let options = ettlex_core::render::TreeRenderOptions {
+        max_depth: args.max_depth,
+        numbering: args.number,
+        toc: args.toc,
+    };
+    emit(&conn, args.output.as_deref(), &args.watch, |conn| {
+        Ok(ettlex_engine::commands::tree_render::render_ettle_tree(
+            conn,
+            &cas,
+            &args.ettle_id,
+            &options,
+        )?)
+    })
+}
+
+/// Open the store for a render that may `--watch`: configured with a busy
+/// timeout so polling alongside a writer waits rather than failing.
+fn open_watched_store(db_path: &str) -> Result<Connection, Box<dyn std::error::Error>> {
+    let mut conn = ettlex_store::env_keys::open_db(db_path)?;
+    ettlex_store::db::configure(&conn)?;
+    ettlex_store::migrations::apply_migrations(&mut conn)?;
+    Ok(conn)
+}
+
+/// Write `render`'s document to `output` (or stdout) once, or under
+/// `--watch` keep polling the state version and re-render each time it moves.
+///
+/// A failed re-render is reported and the previous file left in place, so a
+/// half-finished edit does not end the session; only the first render is
+/// fatal. The file is rewritten only when the document changed, which keeps
+/// preview tools from reloading on unrelated commands.
+fn emit(
+    conn: &Connection,
+    output: Option<&Path>,
+    watch: &WatchArgs,
+    mut render: impl FnMut(&Connection) -> Result<String, Box<dyn std::error::Error>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let document = render(conn)?;
+    let Some(output_path) = output else {
+        print!("{}", document);
+        return Ok(());
+    };
+    std::fs::write(output_path, &document)?;
+    println!("✓ Rendered to {}", output_path.display());
+    if !watch.watch {
+        return Ok(());
+    }
+
+    let mut state = WatchState {
+        version: read_state_version(conn)?.state_version,
+        last: document,
+    };
+    println!(
+        "Watching for changes (state version {}, Ctrl-C to stop)",
+        state.version
+    );
+    loop {
+        std::thread::sleep(Duration::from_millis(watch.interval_ms));
+        state.poll(conn, output_path, &mut render)?;
+    }
+}
+
+/// What `--watch` last rendered.
+struct WatchState {
+    version: u64,
+    last: String,
+}
+
+impl WatchState {
+    /// Re-render if the state version moved since the last poll, rewriting
+    /// `output_path` only when the document changed. Returns whether the
+    /// file was rewritten.
+    ///
+    /// Failing to read the version or to render is reported and retried on
+    /// the next poll; only a failed file write is returned.
+    fn poll(
+        &mut self,
+        conn: &Connection,
+        output_path: &Path,
+        render: &mut impl FnMut(&Connection) -> Result<String, Box<dyn std::error::Error>>,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let current = match read_state_version(conn) {
+            Ok(v) => v.state_version,
+            Err(e) => {
+                eprintln!("✗ Could not read state version: {}", e);
+                return Ok(false);
+            }
+        };
+        if current == self.version {
+            return Ok(false);
+        }
+        self.version = current;
+        match render(conn) {
+            Ok(document) if document == self.last => Ok(false),
+            Ok(document) => {
+                std::fs::write(output_path, &document)?;
+                println!(
+                    "✓ Re-rendered to {} (state version {})",
+                    output_path.display(),
+                    self.version
+                );
+                self.last = document;
+                Ok(true)
+            }
+            Err(e) => {
+                eprintln!("✗ Render failed at state version {}: {}", self.version, e);
+                Ok(false)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bump_state_version(conn: &Connection) {
+        conn.execute(
+            "INSERT INTO command_log (applied_at) VALUES ('2024-01-01T00:00:00Z')",
+            [],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_watch_rerenders_only_when_state_version_moves() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let output = dir.path().join("out.md");
+        let mut conn = ettlex_store::db::open(dir.path().join("store.db")).unwrap();
+        ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+
+        let mut document = "v1".to_string();
+        let mut renders = 0;
+        let mut state = WatchState {
+            version: read_state_version(&conn).unwrap().state_version,
+            last: document.clone(),
+        };
+        std::fs::write(&output, &document).unwrap();
+
+        // Unchanged state version: no render, no rewrite
+        let mut render = |_: &Connection| -> Result<String, Box<dyn std::error::Error>> {
+            renders += 1;
+            Ok(document.clone())
+        };
+        assert!(!state.poll(&conn, &output, &mut render).unwrap());
+
+        // Version moved but the document is the same: no rewrite
+        bump_state_version(&conn);
+        assert!(!state.poll(&conn, &output, &mut render).unwrap());
+        assert_eq!(renders, 1);
+
+        // Version moved and the document changed: rewritten
+        document = "v2".to_string();
+        let mut render =
+            |_: &Connection| -> Result<String, Box<dyn std::error::Error>> { Ok(document.clone()) };
+        bump_state_version(&conn);
+        assert!(state.poll(&conn, &output, &mut render).unwrap());
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), "v2");
+    }
+}
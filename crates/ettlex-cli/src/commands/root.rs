@@ -0,0 +1,122 @@
+//! Root registry commands
+
+use clap::{Args, Subcommand};
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_engine::commands::command::{apply_command, Command};
+use ettlex_engine::commands::engine_query::{apply_engine_query, EngineQuery, EngineQueryResult};
+
+#[derive(Debug, Args)]
+pub struct RootArgs {
+    #[command(subcommand)]
+    pub command: RootCommand,
+
+    #[arg(long, default_value = ".ettlex/store.db", global = true)]
+    pub db: String,
+
+    #[arg(long, default_value = ".ettlex/cas", global = true)]
+    pub cas: String,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum RootCommand {
+    /// Register a parentless Ettle as a root
+    Register {
+        /// Ettle ID or slug
+        ettle: String,
+        #[arg(long)]
+        name: String,
+        #[arg(long)]
+        owner: Option<String>,
+    },
+    /// Change a registered root's name, owner or status
+    Update {
+        /// Ettle ID or slug
+        ettle: String,
+        #[arg(long)]
+        name: Option<String>,
+        /// New owner; an empty string clears it
+        #[arg(long)]
+        owner: Option<String>,
+        /// `active` or `retired`
+        #[arg(long)]
+        status: Option<String>,
+    },
+    /// List registered roots with their heads, and unregistered implicit roots
+    List {
+        #[arg(long)]
+        include_retired: bool,
+    },
+}
+
+pub fn execute(args: RootArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = ettlex_store::env_keys::open_db(&args.db)?;
+    ettlex_store::migrations::apply_migrations(&mut conn)?;
+    let cas = ettlex_store::env_keys::open_cas(&args.cas)?;
+
+    let (cmd, done) = match args.command {
+        RootCommand::List { include_retired } => {
+            let registry = match apply_engine_query(
+                EngineQuery::RootList { include_retired },
+                &conn,
+                &cas,
+                None,
+            )? {
+                EngineQueryResult::RootList(registry) => registry,
+                _ => unreachable!("unexpected EngineQueryResult variant in root list"),
+            };
+            for entry in &registry.registered {
+                let root = &entry.root;
+                println!(
+                    "{:<24} {:<8} {:<16} {:<24} {} snapshot(s), head {}",
+                    root.display_name,
+                    root.status,
+                    root.owner.as_deref().unwrap_or("-"),
+                    root.ettle_id,
+                    entry.snapshot_count,
+                    entry.head_snapshot_id.as_deref().unwrap_or("-")
+                );
+            }
+            if !registry.unregistered.is_empty() {
+                println!("Unregistered roots:");
+                for id in &registry.unregistered {
+                    println!("  {}", id);
+                }
+            }
+            return Ok(());
+        }
+        RootCommand::Register { ettle, name, owner } => (
+            Command::RootRegister {
+                ettle_id: ettle.clone(),
+                display_name: name,
+                owner,
+            },
+            format!("Registered root {}", ettle),
+        ),
+        RootCommand::Update {
+            ettle,
+            name,
+            owner,
+            status,
+        } => (
+            Command::RootUpdate {
+                ettle_id: ettle.clone(),
+                display_name: name,
+                owner: owner.map(|o| Some(o).filter(|o| !o.is_empty())),
+                status,
+            },
+            format!("Updated root {}", ettle),
+        ),
+    };
+
+    apply_command(
+        cmd,
+        None,
+        &mut conn,
+        &cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )?;
+    println!("✓ {}", done);
+    Ok(())
+}
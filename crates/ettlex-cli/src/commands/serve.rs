@@ -0,0 +1,533 @@
+//! HTTP/JSON API over the engine
+//!
+//! `ettlex serve` opens one store and answers engine queries and commands
+//! over HTTP, so several tools can share a database without linking the Rust
+//! crates:
+//!
+//! | Route              | Body                                                       | Scope               |
+//! |--------------------|------------------------------------------------------------|---------------------|
+//! | `GET /v1/health`   | —                                                          | none                |
+//! | `POST /v1/query`   | A query verb, e.g. `{"verb": "ettle-get", "ettle": "…"}`   | `read`              |
+//! | `POST /v1/command` | `{"command": {"tag": …}, "expected_state_version"?, "dry_run"?}` | `write` (`approve` for `ApprovalTransition`) |
+//! | `POST /v1/backup`  | —                                                          | `admin`             |
+//!
+//! Successful responses are `{"result": …}`; commands add `new_state_version`.
+//! A backup is written on the server host, to a new `backup-<unix_ms>`
+//! directory under `--backup-dir`, with the same online copy and CAS manifest
+//! as `ettlex backup`; the result is that manifest and the directory path.
+//! Errors are `{"error": {"code", "category", "message", …}}` with the HTTP
+//! status of the error kind's transport mapping.
+//!
+//! Requests are handled one at a time on one connection, in arrival order.
+//! Each accepted stream gets read and write timeouts (`--io-timeout-ms`), so
+//! a client that stalls is answered with 408 instead of blocking the others.
+//! `X-Request-Id` and `X-Trace-Id` are read from the request, attached to
+//! errors and echoed on the response. IDs are at most 128 characters of
+//! `A-Z a-z 0-9 . _ : -`; a missing or invalid ID is replaced by a fresh one.
+//! With `--auth tokens`, every route except health needs
+//! `Authorization: Bearer <secret>` for a token from `ettlex token issue`.
+
+#![allow(clippy::result_large_err)]
+
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use clap::{Args, ValueEnum};
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core_types::{RequestContext, RequestId, TraceId};
+use ettlex_engine::commands::api_token::{authorize_api_token, ApiScope};
+use ettlex_engine::commands::command::{apply_command_with_options, Command};
+use ettlex_engine::commands::engine_command::CommandOptions;
+use ettlex_store::backup::backup_store;
+use ettlex_store::cas::FsStore;
+use ettlex_store::file_policy_provider::FilePolicyProvider;
+use rusqlite::Connection;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::query::{query_json, QueryVerb};
+
+/// Longest accepted request line plus headers.
+const MAX_HEAD_BYTES: u64 = 64 * 1024;
+
+/// Longest accepted `X-Request-Id` / `X-Trace-Id` value.
+const MAX_ID_LEN: usize = 128;
+
+#[derive(Debug, Args)]
+pub struct ServeArgs {
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:7878")]
+    pub addr: String,
+
+    #[arg(long, default_value = ".ettlex/store.db")]
+    pub db: String,
+
+    #[arg(long, default_value = ".ettlex/cas")]
+    pub cas: String,
+
+    /// Directory of policy documents
+    #[arg(long, default_value = "policies")]
+    pub policies: String,
+
+    /// Directory `POST /v1/backup` writes backups under
+    #[arg(long, default_value = ".ettlex/backups")]
+    pub backup_dir: PathBuf,
+
+    /// `none` accepts every request; `tokens` requires an API token
+    #[arg(long, value_enum, default_value_t = AuthMode::None)]
+    pub auth: AuthMode,
+
+    /// Largest accepted request body, in bytes
+    #[arg(long, default_value_t = 1024 * 1024)]
+    pub max_body_bytes: usize,
+
+    /// Read and write timeout for each connection, in milliseconds
+    #[arg(long, default_value_t = 10_000, value_parser = clap::value_parser!(u64).range(1..))]
+    pub io_timeout_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum AuthMode {
+    None,
+    Tokens,
+}
+
+/// A parsed HTTP request. Header names are lower case.
+#[derive(Debug, Clone, Default)]
+pub struct HttpRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: BTreeMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(String::as_str)
+    }
+}
+
+/// A response ready to be written.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Value,
+}
+
+/// Body of `POST /v1/command`.
+#[derive(Debug, Deserialize)]
+struct CommandRequest {
+    command: Value,
+    #[serde(default)]
+    expected_state_version: Option<u64>,
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// The server state: one store, handling one request at a time.
+pub struct Server {
+    conn: Connection,
+    cas: FsStore,
+    policies: FilePolicyProvider,
+    backup_dir: PathBuf,
+    auth: AuthMode,
+    max_body_bytes: usize,
+    io_timeout: Duration,
+}
+
+impl Server {
+    /// Open the store in `args` and apply pending migrations.
+    pub fn open(args: &ServeArgs) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut conn = ettlex_store::env_keys::open_db(&args.db)?;
+        ettlex_store::migrations::apply_migrations(&mut conn)?;
+        Ok(Self {
+            conn,
+            cas: ettlex_store::env_keys::open_cas(&args.cas)?,
+            policies: FilePolicyProvider::new(&args.policies),
+            backup_dir: args.backup_dir.clone(),
+            auth: args.auth,
+            max_body_bytes: args.max_body_bytes,
+            io_timeout: Duration::from_millis(args.io_timeout_ms),
+        })
+    }
+
+    /// Route one request.
+    pub fn handle(&mut self, req: &HttpRequest) -> HttpResponse {
+        let ctx = request_context(req);
+        let result = match (req.method.as_str(), req.path.as_str()) {
+            ("GET", "/v1/health") => Ok(json!({ "status": "ok" })),
+            ("POST", "/v1/query") => self.query(req),
+            ("POST", "/v1/command") => self.command(req),
+            ("POST", "/v1/backup") => self.backup(req),
+            (_, "/v1/health" | "/v1/query" | "/v1/command" | "/v1/backup") => {
+                return with_context(method_not_allowed(req), &ctx)
+            }
+            _ => Err(ExError::new(ExErrorKind::NotFound)
+                .with_op("serve")
+                .with_message(format!("no route for {} {}", req.method, req.path))),
+        };
+        let response = match result {
+            Ok(body) => HttpResponse {
+                status: 200,
+                headers: Vec::new(),
+                body,
+            },
+            Err(e) => error_response(e, &ctx),
+        };
+        with_context(response, &ctx)
+    }
+
+    /// Read one request from `stream`, handle it and write the response.
+    /// The connection is closed after each response.
+    ///
+    /// # Errors
+    /// Fails only if the stream cannot be read or written.
+    pub fn serve_connection<S: Read + Write>(&mut self, stream: &mut S) -> io::Result<()> {
+        let read = match read_request(&mut *stream, self.max_body_bytes) {
+            Err(e) if is_timeout(&e) => {
+                let ctx = RequestContext::new();
+                return write_response(stream, &with_context(request_timeout(), &ctx));
+            }
+            read => read?,
+        };
+        let response = match read {
+            Ok(req) => {
+                let response = self.handle(&req);
+                eprintln!(
+                    "{} {} {} {}",
+                    response_header(&response, "X-Request-Id").unwrap_or("-"),
+                    req.method,
+                    req.path,
+                    response.status
+                );
+                response
+            }
+            Err(e) => {
+                let ctx = RequestContext::new();
+                with_context(error_response(e, &ctx), &ctx)
+            }
+        };
+        write_response(stream, &response)
+    }
+
+    /// Serve one accepted TCP connection, applying the I/O timeouts.
+    ///
+    /// # Errors
+    /// Fails if the timeouts cannot be set or the stream fails.
+    pub fn serve_tcp(&mut self, mut stream: TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(self.io_timeout))?;
+        stream.set_write_timeout(Some(self.io_timeout))?;
+        self.serve_connection(&mut stream)
+    }
+
+    fn query(&mut self, req: &HttpRequest) -> Result<Value, ExError> {
+        self.authorize(req, ApiScope::Read)?;
+        let verb: QueryVerb = parse_body(req, "serve_query")?;
+        let result =
+            query_json(verb, &self.conn, &self.cas, &self.policies).map_err(into_ex_error)?;
+        Ok(json!({ "result": result }))
+    }
+
+    fn command(&mut self, req: &HttpRequest) -> Result<Value, ExError> {
+        let body: CommandRequest = parse_body(req, "serve_command")?;
+        let scope = match body.command.get("tag").and_then(Value::as_str) {
+            Some("ApprovalTransition") => ApiScope::Approve,
+            Some("ApiTokenIssue" | "ApiTokenRevoke") => ApiScope::Admin,
+            _ => ApiScope::Write,
+        };
+        self.authorize(req, scope)?;
+        let cmd: Command = serde_json::from_value(body.command).map_err(|e| {
+            ExError::new(ExErrorKind::InvalidInput)
+                .with_op("serve_command")
+                .with_message(format!("invalid command: {}", e))
+        })?;
+        let (result, new_state_version) = apply_command_with_options(
+            cmd,
+            body.expected_state_version,
+            CommandOptions {
+                dry_run: body.dry_run,
+            },
+            &mut self.conn,
+            &self.cas,
+            &self.policies,
+            &NoopApprovalRouter,
+        )?;
+        let mut response = json!({
+            "result": serde_json::to_value(&result)?,
+            "new_state_version": new_state_version,
+        });
+        if body.dry_run {
+            response["dry_run"] = json!(true);
+        }
+        Ok(response)
+    }
+
+    fn backup(&mut self, req: &HttpRequest) -> Result<Value, ExError> {
+        self.authorize(req, ApiScope::Admin)?;
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        let out = self.backup_dir.join(format!("backup-{}", stamp));
+        let manifest = backup_store(&self.conn, &self.cas, &out)?;
+        Ok(json!({
+            "result": {
+                "path": out.display().to_string(),
+                "manifest": serde_json::to_value(&manifest)?,
+            }
+        }))
+    }
+
+    fn authorize(&self, req: &HttpRequest, scope: ApiScope) -> Result<(), ExError> {
+        if self.auth == AuthMode::None {
+            return Ok(());
+        }
+        let secret = req
+            .header("authorization")
+            .and_then(|h| h.strip_prefix("Bearer "))
+            .map(str::trim);
+        authorize_api_token(&self.conn, secret, scope).map(|_| ())
+    }
+}
+
+pub fn execute(args: ServeArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let mut server = Server::open(&args)?;
+    let listener = TcpListener::bind(&args.addr)?;
+    eprintln!("Listening on http://{}", listener.local_addr()?);
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!("accept failed: {}", e);
+                continue;
+            }
+        };
+        if let Err(e) = server.serve_tcp(stream) {
+            eprintln!("connection error: {}", e);
+        }
+    }
+    Ok(())
+}
+
+fn request_context(req: &HttpRequest) -> RequestContext {
+    let ctx = match req.header("x-request-id").filter(|v| is_safe_id(v)) {
+        Some(id) => RequestContext::with_request_id(RequestId::from_string(id.to_string())),
+        None => RequestContext::new(),
+    };
+    match req.header("x-trace-id").filter(|v| !v.is_empty()) {
+        Some(id) if is_safe_id(id) => ctx.with_trace_id(TraceId::from_string(id.to_string())),
+        Some(_) => ctx.with_trace_id(TraceId::new()),
+        None => ctx,
+    }
+}
+
+/// Whether a client-supplied ID is safe to echo in a response header.
+fn is_safe_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_ID_LEN
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'.' | b'_' | b':' | b'-'))
+}
+
+fn is_timeout(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+    )
+}
+
+fn with_context(mut response: HttpResponse, ctx: &RequestContext) -> HttpResponse {
+    response
+        .headers
+        .push(("X-Request-Id".to_string(), ctx.request_id.to_string()));
+    if let Some(trace_id) = &ctx.trace_id {
+        response
+            .headers
+            .push(("X-Trace-Id".to_string(), trace_id.to_string()));
+    }
+    response
+}
+
+fn response_header<'a>(response: &'a HttpResponse, name: &str) -> Option<&'a str> {
+    response
+        .headers
+        .iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, v)| v.as_str())
+}
+
+fn parse_body<T: for<'de> Deserialize<'de>>(req: &HttpRequest, op: &str) -> Result<T, ExError> {
+    serde_json::from_slice(&req.body).map_err(|e| {
+        ExError::new(ExErrorKind::InvalidInput)
+            .with_op(op)
+            .with_message(format!("invalid request body: {}", e))
+    })
+}
+
+/// Recover the engine error from a query failure; anything else is internal.
+fn into_ex_error(e: Box<dyn std::error::Error>) -> ExError {
+    match e.downcast::<ExError>() {
+        Ok(ex) => *ex,
+        Err(e) => ExError::new(ExErrorKind::Internal)
+            .with_op("serve_query")
+            .with_message(e.to_string()),
+    }
+}
+
+fn error_response(e: ExError, ctx: &RequestContext) -> HttpResponse {
+    let mut e = e.with_request_id(ctx.request_id.clone());
+    if let Some(trace_id) = &ctx.trace_id {
+        e = e.with_trace_id(trace_id.clone());
+    }
+    let transport = e.transport();
+    HttpResponse {
+        status: transport.http_status,
+        headers: Vec::new(),
+        body: json!({
+            "error": {
+                "code": e.code(),
+                "category": transport.category.as_str(),
+                "message": e.message(),
+                "op": e.op(),
+                "entity_id": e.entity_id(),
+                "request_id": e.request_id().map(|id| id.as_str()),
+                "trace_id": e.trace_id().map(|id| id.as_str()),
+            }
+        }),
+    }
+}
+
+fn method_not_allowed(req: &HttpRequest) -> HttpResponse {
+    let allow = if req.path == "/v1/health" {
+        "GET"
+    } else {
+        "POST"
+    };
+    HttpResponse {
+        status: 405,
+        headers: vec![("Allow".to_string(), allow.to_string())],
+        body: json!({
+            "error": {
+                "code": ExErrorKind::InvalidInput.code(),
+                "category": "invalid_argument",
+                "message": format!("{} requires {}", req.path, allow),
+            }
+        }),
+    }
+}
+
+fn request_timeout() -> HttpResponse {
+    HttpResponse {
+        status: 408,
+        headers: Vec::new(),
+        body: json!({
+            "error": {
+                "code": ExErrorKind::Timeout.code(),
+                "category": "deadline_exceeded",
+                "message": "timed out reading the request",
+            }
+        }),
+    }
+}
+
+/// Read one request. The outer error is an I/O failure; the inner one a
+/// request that could not be accepted, to be answered with an error.
+fn read_request<R: Read>(
+    stream: R,
+    max_body_bytes: usize,
+) -> io::Result<Result<HttpRequest, ExError>> {
+    let bad = |message: String| {
+        Ok(Err(ExError::new(ExErrorKind::InvalidInput)
+            .with_op("serve")
+            .with_message(message)))
+    };
+    let mut reader = BufReader::new(stream);
+    let mut head = (&mut reader).take(MAX_HEAD_BYTES);
+
+    let mut line = String::new();
+    head.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target), Some(_version)) = (parts.next(), parts.next(), parts.next())
+    else {
+        return bad(format!("malformed request line: {:?}", line.trim_end()));
+    };
+    let mut req = HttpRequest {
+        method: method.to_string(),
+        path: target.split('?').next().unwrap_or_default().to_string(),
+        ..HttpRequest::default()
+    };
+
+    loop {
+        line.clear();
+        if head.read_line(&mut line)? == 0 {
+            return bad("request headers are incomplete or too large".to_string());
+        }
+        let header = line.trim_end_matches(['\r', '\n']);
+        if header.is_empty() {
+            break;
+        }
+        let Some((name, value)) = header.split_once(':') else {
+            return bad(format!("malformed header: {:?}", header));
+        };
+        req.headers
+            .insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+    }
+
+    let length = match req.header("content-length") {
+        None => 0,
+        Some(v) => match v.parse::<usize>() {
+            Ok(n) => n,
+            Err(_) => return bad(format!("invalid Content-Length: {:?}", v)),
+        },
+    };
+    if length > max_body_bytes {
+        return bad(format!(
+            "request body is {} bytes; limit is {}",
+            length, max_body_bytes
+        ));
+    }
+    req.body = vec![0; length];
+    reader.read_exact(&mut req.body)?;
+    Ok(Ok(req))
+}
+
+fn write_response<W: Write>(stream: &mut W, response: &HttpResponse) -> io::Result<()> {
+    let body = serde_json::to_vec(&response.body)?;
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        reason(response.status),
+        body.len()
+    );
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(&body)?;
+    stream.flush()
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        501 => "Not Implemented",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        _ => "Internal Server Error",
+    }
+}
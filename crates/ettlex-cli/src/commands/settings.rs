@@ -0,0 +1,85 @@
+//! Repository settings commands
+
+use clap::{Args, Subcommand};
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_engine::commands::engine_command::{apply_engine_command, EngineCommand};
+use ettlex_engine::commands::engine_query::{apply_engine_query, EngineQuery, EngineQueryResult};
+use ettlex_engine::commands::settings::SettingSource;
+
+#[derive(Debug, Args)]
+pub struct SettingsArgs {
+    #[command(subcommand)]
+    pub command: SettingsCommand,
+
+    #[arg(long, default_value = ".ettlex/store.db", global = true)]
+    pub db: String,
+
+    #[arg(long, default_value = ".ettlex/cas", global = true)]
+    pub cas: String,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SettingsCommand {
+    /// Show the effective value of one setting, or of every known setting
+    Get { key: Option<String> },
+    /// Store a value (parsed as JSON, otherwise taken as a string)
+    Set { key: String, value: String },
+    /// Drop the stored value so the default applies
+    Unset { key: String },
+}
+
+pub fn execute(args: SettingsArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = ettlex_store::env_keys::open_db(&args.db)?;
+    ettlex_store::migrations::apply_migrations(&mut conn)?;
+    let cas = ettlex_store::env_keys::open_cas(&args.cas)?;
+
+    let (key, value) = match args.command {
+        SettingsCommand::Get { key } => {
+            let entries =
+                match apply_engine_query(EngineQuery::SettingsGet { key }, &conn, &cas, None)? {
+                    EngineQueryResult::SettingsGet(entries) => entries,
+                    _ => unreachable!("unexpected EngineQueryResult variant in settings get"),
+                };
+            for entry in entries {
+                let value = entry
+                    .value
+                    .map(|v| v.to_string())
+                    .unwrap_or_else(|| "(unset)".to_string());
+                let source = match entry.source {
+                    SettingSource::Default => "default",
+                    SettingSource::Stored => "stored",
+                    SettingSource::Explicit => "explicit",
+                };
+                println!(
+                    "{:<24} {:<16} ({})  {}",
+                    entry.key, value, source, entry.description
+                );
+            }
+            return Ok(());
+        }
+        SettingsCommand::Set { key, value } => {
+            let value = serde_json::from_str(&value).unwrap_or(serde_json::Value::String(value));
+            (key, Some(value))
+        }
+        SettingsCommand::Unset { key } => (key, None),
+    };
+
+    let cleared = value.is_none();
+    apply_engine_command(
+        EngineCommand::SettingsSet {
+            key: key.clone(),
+            value,
+        },
+        &mut conn,
+        &cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )?;
+    if cleared {
+        println!("✓ Unset {}", key);
+    } else {
+        println!("✓ Set {}", key);
+    }
+    Ok(())
+}
@@ -1,13 +1,34 @@
-//! Snapshot commit command
+//! Snapshot commit, diff and materialize commands
 
 use clap::{Args, Subcommand};
 use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::diff::human_summary::{SummaryOptions, SummaryVerbosity};
+use ettlex_core::diff::ignore::DiffIgnoreList;
+use ettlex_core::diff::messages::MessageCatalog;
+use ettlex_core::diff::model::DiffSeverity;
+use ettlex_core::diff::rendered::{render_manifest_markdown, unified_line_diff};
+use ettlex_core::diff::severity::SeverityRules;
+use ettlex_core::diff::ParsedManifest;
+use ettlex_core::errors::{ExError, ExErrorKind};
 use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_engine::commands::commit_queue::CommitTicketOutcome;
+use ettlex_engine::commands::determinism::verify_manifest_determinism;
 use ettlex_engine::commands::engine_command::{
     apply_engine_command, EngineCommand, EngineCommandResult,
 };
+use ettlex_engine::commands::engine_query::{
+    apply_engine_query, EngineQuery, EngineQueryResult, SnapshotRef,
+};
+use ettlex_engine::commands::settings::dedup_enabled;
 use ettlex_engine::snapshot::{SnapshotCommitOutcome, SnapshotOptions};
 use ettlex_store::cas::FsStore;
+use ettlex_store::snapshot::{
+    export_all_snapshots, export_ledger_jsonl, materialize_snapshot, ExportAllOptions,
+    LedgerExportOptions, SnapshotIdScheme,
+};
+use std::path::PathBuf;
+
+use super::summary::{self, CommandSummary};
 
 #[derive(Debug, Args)]
 pub struct SnapshotArgs {
@@ -18,6 +39,20 @@ pub struct SnapshotArgs {
 #[derive(Debug, Subcommand)]
 pub enum SnapshotCommand {
     Commit(CommitArgs),
+    /// Apply queued commits one at a time, oldest first
+    DrainQueue(DrainQueueArgs),
+    /// Diff two snapshots and optionally fail above a severity threshold
+    Diff(DiffArgs),
+    /// Compare snapshots pairwise and group the semantically identical ones
+    Compare(CompareArgs),
+    /// Write a snapshot's manifest and referenced CAS blobs to a directory
+    Materialize(MaterializeArgs),
+    /// Export every committed manifest to a directory, resumably
+    ExportAll(ExportAllArgs),
+    /// Write every ledger row as a JSON line, for external analytics
+    LedgerExport(LedgerExportArgs),
+    /// Generate a manifest repeatedly and fail unless every run is byte-identical
+    VerifyDeterminism(VerifyDeterminismArgs),
 }
 
 #[derive(Debug, Args)]
@@ -35,6 +70,218 @@ pub struct CommitArgs {
     #[arg(long)]
     pub dry_run: bool,
 
+    /// Queue the commit and print its ticket instead of committing now;
+    /// `snapshot drain-queue` applies it
+    #[arg(long, conflicts_with = "dry_run")]
+    pub queue: bool,
+
+    /// Snapshot ID scheme: uuid, root-counter or digest-prefix
+    #[arg(long, default_value = "uuid")]
+    pub id_scheme: SnapshotIdScheme,
+
+    /// Dedup mode for this commit: append or semantic (overrides the
+    /// `snapshot.dedup_mode` setting)
+    #[arg(long, value_parser = ["append", "semantic"])]
+    pub dedup_mode: Option<String>,
+
+    /// Commit message recorded on the snapshot
+    #[arg(long, short = 'm')]
+    pub message: Option<String>,
+
+    /// Annotation recorded on the snapshot, as KEY=VALUE (repeatable)
+    #[arg(long = "annotate", value_name = "KEY=VALUE", value_parser = parse_annotation)]
+    pub annotations: Vec<(String, String)>,
+
+    /// Write a machine-readable JSON summary of the result to this file
+    #[arg(long)]
+    pub summary_out: Option<PathBuf>,
+
+    #[arg(long, default_value = ".ettlex/store.db")]
+    pub db: String,
+
+    #[arg(long, default_value = ".ettlex/cas")]
+    pub cas: String,
+}
+
+#[derive(Debug, Args)]
+pub struct DrainQueueArgs {
+    /// Apply at most this many queued commits (default: all)
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub limit: Option<u64>,
+
+    #[arg(long, default_value = ".ettlex/store.db")]
+    pub db: String,
+
+    #[arg(long, default_value = ".ettlex/cas")]
+    pub cas: String,
+}
+
+#[derive(Debug, Args)]
+pub struct DiffArgs {
+    /// Snapshot A (ID or unique prefix)
+    pub a: String,
+
+    /// Snapshot B (ID or unique prefix)
+    pub b: String,
+
+    /// JSON file of severity rule overrides
+    #[arg(long)]
+    pub rules: Option<PathBuf>,
+
+    /// JSON ignore list (`{"categories": [...], "fields": [...]}`); defaults
+    /// to the `diff_ignore` key of snapshot B's profile
+    #[arg(long)]
+    pub ignore: Option<PathBuf>,
+
+    /// Exit with an error if the diff severity is at or above this level
+    /// (none, informational, semantic, breaking)
+    #[arg(long)]
+    pub fail_on: Option<DiffSeverity>,
+
+    /// Human summary detail: one-line, summary or detailed
+    #[arg(long, default_value = "detailed")]
+    pub verbosity: SummaryVerbosity,
+
+    /// JSON message catalog (`{"locale": ..., "messages": {...}}`) for the
+    /// human summary; missing messages fall back to English
+    #[arg(long)]
+    pub catalog: Option<PathBuf>,
+
+    /// Print the structured diff as JSON instead of the human summary
+    #[arg(long)]
+    pub json: bool,
+
+    /// Also render both snapshots to Markdown and print a line diff of the two
+    #[arg(long, conflicts_with = "json")]
+    pub rendered: bool,
+
+    /// Line-diff the why/what/how content of each changed EP, loaded from CAS
+    #[arg(long)]
+    pub content: bool,
+
+    /// Output file (prints to stdout if not specified)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Write a machine-readable JSON summary of the result to this file
+    #[arg(long)]
+    pub summary_out: Option<PathBuf>,
+
+    #[arg(long, default_value = ".ettlex/store.db")]
+    pub db: String,
+
+    #[arg(long, default_value = ".ettlex/cas")]
+    pub cas: String,
+}
+
+#[derive(Debug, Args)]
+pub struct CompareArgs {
+    /// Snapshots to compare (IDs or unique prefixes; at least two)
+    #[arg(required = true, num_args = 2..)]
+    pub snapshots: Vec<String>,
+
+    /// Print the comparison as JSON
+    #[arg(long)]
+    pub json: bool,
+
+    /// Write a machine-readable JSON summary of the result to this file
+    #[arg(long)]
+    pub summary_out: Option<PathBuf>,
+
+    #[arg(long, default_value = ".ettlex/store.db")]
+    pub db: String,
+
+    #[arg(long, default_value = ".ettlex/cas")]
+    pub cas: String,
+}
+
+#[derive(Debug, Args)]
+pub struct MaterializeArgs {
+    /// Snapshot to materialize (ID or unique prefix)
+    #[arg(long)]
+    pub snapshot_id: String,
+
+    /// Output directory (created if missing; must be empty if it exists)
+    #[arg(long)]
+    pub out: PathBuf,
+
+    /// Write a machine-readable JSON summary of the result to this file
+    #[arg(long)]
+    pub summary_out: Option<PathBuf>,
+
+    #[arg(long, default_value = ".ettlex/store.db")]
+    pub db: String,
+
+    #[arg(long, default_value = ".ettlex/cas")]
+    pub cas: String,
+}
+
+#[derive(Debug, Args)]
+pub struct ExportAllArgs {
+    /// Output directory (created if missing; must be empty unless --resume)
+    #[arg(long)]
+    pub out: PathBuf,
+
+    /// Continue an interrupted or earlier export in --out
+    #[arg(long)]
+    pub resume: bool,
+
+    /// Also copy the CAS blobs each manifest references
+    #[arg(long)]
+    pub blobs: bool,
+
+    /// Write a machine-readable JSON summary of the result to this file
+    #[arg(long)]
+    pub summary_out: Option<PathBuf>,
+
+    #[arg(long, default_value = ".ettlex/store.db")]
+    pub db: String,
+
+    #[arg(long, default_value = ".ettlex/cas")]
+    pub cas: String,
+}
+
+#[derive(Debug, Args)]
+pub struct LedgerExportArgs {
+    /// Output JSONL file, or `-` for stdout
+    #[arg(long)]
+    pub out: PathBuf,
+
+    /// Also read each manifest from CAS and add its summary fields
+    #[arg(long)]
+    pub manifest_summary: bool,
+
+    /// Write a machine-readable JSON summary of the result to this file
+    #[arg(long)]
+    pub summary_out: Option<PathBuf>,
+
+    #[arg(long, default_value = ".ettlex/store.db")]
+    pub db: String,
+
+    #[arg(long, default_value = ".ettlex/cas")]
+    pub cas: String,
+}
+
+#[derive(Debug, Args)]
+pub struct VerifyDeterminismArgs {
+    /// Leaf Ettle to generate the manifest for
+    #[arg(long)]
+    pub leaf: String,
+
+    /// Number of generations to compare (at least 2)
+    #[arg(long, default_value_t = 10)]
+    pub iterations: u32,
+
+    #[arg(long, default_value = "policy/default@0")]
+    pub policy: String,
+
+    #[arg(long, default_value = "profile/default@0")]
+    pub profile: String,
+
+    /// Write a machine-readable JSON summary of the result to this file
+    #[arg(long)]
+    pub summary_out: Option<PathBuf>,
+
     #[arg(long, default_value = ".ettlex/store.db")]
     pub db: String,
 
@@ -45,25 +292,76 @@ pub struct CommitArgs {
 pub fn execute(args: SnapshotArgs) -> Result<(), Box<dyn std::error::Error>> {
     match args.command {
         SnapshotCommand::Commit(commit_args) => execute_commit(commit_args),
+        SnapshotCommand::DrainQueue(drain_args) => execute_drain_queue(drain_args),
+        SnapshotCommand::Diff(diff_args) => execute_diff(diff_args),
+        SnapshotCommand::Compare(compare_args) => execute_compare(compare_args),
+        SnapshotCommand::Materialize(materialize_args) => execute_materialize(materialize_args),
+        SnapshotCommand::ExportAll(export_args) => execute_export_all(export_args),
+        SnapshotCommand::LedgerExport(export_args) => execute_ledger_export(export_args),
+        SnapshotCommand::VerifyDeterminism(verify_args) => execute_verify_determinism(verify_args),
     }
 }
 
 fn execute_commit(args: CommitArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let summary_out = args.summary_out.clone();
+    let mut summary = CommandSummary::new("snapshot_commit");
+    let result = run_commit(args, &mut summary);
+    summary::finish(summary_out.as_deref(), &summary, result)
+}
+
+fn run_commit(
+    args: CommitArgs,
+    summary: &mut CommandSummary,
+) -> Result<(), Box<dyn std::error::Error>> {
+    summary.id("leaf", args.leaf.clone());
+    if args.dry_run {
+        summary.warn("dry run: nothing was persisted");
+    }
     std::fs::create_dir_all(
         std::path::Path::new(&args.cas)
             .parent()
             .unwrap_or(std::path::Path::new(".ettlex")),
     )?;
-    let mut conn = rusqlite::Connection::open(&args.db)?;
+    let mut conn = ettlex_store::env_keys::open_db(&args.db)?;
     ettlex_store::migrations::apply_migrations(&mut conn)?;
-    let cas = FsStore::new(&args.cas);
+    let cas = ettlex_store::env_keys::open_cas(&args.cas)?;
 
+    let explicit_dedup = args.dedup_mode.as_deref().map(|mode| mode == "semantic");
     let options = SnapshotOptions {
         expected_head: None,
         dry_run: args.dry_run,
-        allow_dedup: false,
+        allow_dedup: !args.dry_run && dedup_enabled(&conn, explicit_dedup)?,
+        id_scheme: args.id_scheme,
+        message: args.message,
+        annotations: args.annotations.into_iter().collect(),
     };
 
+    if args.queue {
+        let cmd = EngineCommand::SnapshotCommitEnqueue {
+            leaf_ep_id: args.leaf,
+            policy_ref: args.policy,
+            profile_ref: args.profile,
+            options,
+        };
+        let ticket = match apply_engine_command(
+            cmd,
+            &mut conn,
+            &cas,
+            &NoopPolicyProvider,
+            &NoopApprovalRouter,
+        )? {
+            EngineCommandResult::SnapshotCommitQueued(ticket) => ticket,
+            _ => unreachable!("unexpected EngineCommandResult variant in snapshot commit --queue"),
+        };
+        println!("Commit queued:");
+        println!("  ticket: {}", ticket.ticket);
+        if let CommitTicketOutcome::Queued { ahead } = ticket.outcome {
+            println!("  ahead: {}", ahead);
+        }
+        summary.id("ticket", ticket.ticket);
+        return Ok(());
+    }
+
     let cmd = EngineCommand::SnapshotCommit {
         leaf_ep_id: args.leaf,
         policy_ref: args.policy,
@@ -88,12 +386,418 @@ fn execute_commit(args: CommitArgs) -> Result<(), Box<dyn std::error::Error>> {
             println!("Snapshot committed:");
             println!("  snapshot_id: {}", r.snapshot_id);
             println!("  manifest_digest: {}", r.manifest_digest);
+            summary.id("snapshot_id", r.snapshot_id.clone());
+            summary.digest("manifest_digest", r.manifest_digest.clone());
+            let constraints = &r.constraints_summary;
+            summary.digest("constraints_digest", constraints.constraints_digest.clone());
+            summary.count("declared_constraint_refs", constraints.declared_ref_count);
+            summary.count("constraint_families", constraints.families.len());
         }
         SnapshotCommitOutcome::RoutedForApproval(r) => {
             println!("Routed for approval:");
             println!("  approval_token: {}", r.approval_token);
+            summary.id("approval_token", r.approval_token.clone());
+            summary.warn("commit routed for approval: no snapshot was created");
+        }
+    }
+
+    Ok(())
+}
+
+fn execute_drain_queue(args: DrainQueueArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = ettlex_store::env_keys::open_db(&args.db)?;
+    ettlex_store::migrations::apply_migrations(&mut conn)?;
+    let cas = ettlex_store::env_keys::open_cas(&args.cas)?;
+
+    let cmd = EngineCommand::SnapshotCommitQueueDrain {
+        limit: args.limit.map(|n| n as usize),
+    };
+    let tickets = match apply_engine_command(
+        cmd,
+        &mut conn,
+        &cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )? {
+        EngineCommandResult::SnapshotCommitQueueDrained(tickets) => tickets,
+        _ => unreachable!("unexpected EngineCommandResult variant in snapshot drain-queue"),
+    };
+
+    for t in &tickets {
+        let detail = match &t.outcome {
+            CommitTicketOutcome::Committed { snapshot_id, .. } => {
+                format!("committed {}", snapshot_id)
+            }
+            CommitTicketOutcome::Routed { approval_token } => {
+                format!("routed for approval {}", approval_token)
+            }
+            CommitTicketOutcome::Failed {
+                error_code,
+                message,
+            } => format!("failed {}: {}", error_code, message),
+            CommitTicketOutcome::Queued { .. } | CommitTicketOutcome::Applying => {
+                "unresolved".to_string()
+            }
+        };
+        println!("{} {} {}", t.ticket, t.leaf_ettle_id, detail);
+    }
+    println!("✓ Applied {} queued commit(s)", tickets.len());
+    Ok(())
+}
+
+/// Parse a `KEY=VALUE` annotation. The value may itself contain `=`.
+fn parse_annotation(raw: &str) -> Result<(String, String), String> {
+    match raw.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("expected KEY=VALUE, got '{}'", raw)),
+    }
+}
+
+fn execute_diff(args: DiffArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let summary_out = args.summary_out.clone();
+    let mut summary = CommandSummary::new("snapshot_diff");
+    let result = run_diff(args, &mut summary);
+    summary::finish(summary_out.as_deref(), &summary, result)
+}
+
+fn run_diff(
+    args: DiffArgs,
+    summary: &mut CommandSummary,
+) -> Result<(), Box<dyn std::error::Error>> {
+    summary.id("a", args.a.clone());
+    summary.id("b", args.b.clone());
+    let severity_rules = match &args.rules {
+        Some(path) => SeverityRules::from_json(&std::fs::read(path)?)?,
+        None => SeverityRules::default(),
+    };
+    let ignore = match &args.ignore {
+        Some(path) => Some(DiffIgnoreList::from_json(&std::fs::read(path)?)?),
+        None => None,
+    };
+
+    let mut conn = ettlex_store::env_keys::open_db(&args.db)?;
+    ettlex_store::migrations::apply_migrations(&mut conn)?;
+    let cas = ettlex_store::env_keys::open_cas(&args.cas)?;
+
+    let catalog = match &args.catalog {
+        Some(path) => MessageCatalog::from_json(&std::fs::read(path)?)?,
+        None => MessageCatalog::english(),
+    };
+
+    let query = EngineQuery::SnapshotDiff {
+        a_ref: SnapshotRef::SnapshotId(args.a.clone()),
+        b_ref: SnapshotRef::SnapshotId(args.b.clone()),
+        severity_rules,
+        ignore,
+        summary: SummaryOptions {
+            verbosity: args.verbosity,
+            catalog,
+        },
+        include_content: args.content,
+    };
+    let result = match apply_engine_query(query, &conn, &cas, None)? {
+        EngineQueryResult::SnapshotDiff(r) => r,
+        _ => unreachable!("unexpected EngineQueryResult variant in snapshot diff"),
+    };
+
+    let mut rendered = if args.json {
+        serde_json::to_string_pretty(&result.structured_diff)?
+    } else {
+        result.human_summary.clone()
+    };
+    if args.rendered {
+        let a_markdown = render_snapshot_markdown(&conn, &cas, &args.a)?;
+        let b_markdown = render_snapshot_markdown(&conn, &cas, &args.b)?;
+        let text_diff = unified_line_diff(&a_markdown, &b_markdown, &args.a, &args.b);
+        summary.count("rendered_diff_lines", text_diff.lines().count());
+        rendered.push_str("\n### Rendered Diff\n\n");
+        if text_diff.is_empty() {
+            rendered.push_str("_(renderings are identical)_\n");
+        } else {
+            rendered.push_str(&format!("```diff\n{}```\n", text_diff));
+        }
+    }
+    match &args.output {
+        Some(path) => {
+            std::fs::write(path, &rendered)?;
+            println!("✓ Rendered to {}", path.display());
+        }
+        None => println!("{}", rendered),
+    }
+
+    let diff = &result.structured_diff;
+    summary.digest("a_manifest_digest", diff.identity.a_manifest_digest.clone());
+    summary.digest("b_manifest_digest", diff.identity.b_manifest_digest.clone());
+    summary.count("added_eps", diff.ept_changes.added_eps.len());
+    summary.count("removed_eps", diff.ept_changes.removed_eps.len());
+    summary.count("changed_eps", diff.ep_content_changes.changed_eps.len());
+    summary.count(
+        "normative_flips",
+        diff.ep_content_changes.normative_flips.len(),
+    );
+    summary.count(
+        "changed_constraint_families",
+        diff.constraint_changes.family_changes.len(),
+    );
+    summary.count("invariant_violations", diff.invariant_violations.len());
+    summary.count("ignored_changes", diff.ignored_tags.len());
+    if !diff.invariant_violations.is_empty() {
+        summary.warn(format!(
+            "{} invariant violation(s) detected while diffing",
+            diff.invariant_violations.len()
+        ));
+    }
+    summary.outcome("severity", diff.severity.as_str());
+
+    match args.fail_on {
+        Some(threshold) if diff.meets_threshold(&threshold) => Err(Box::new(
+            ExError::new(ExErrorKind::PolicyDenied)
+                .with_op("snapshot_diff")
+                .with_message(format!(
+                    "diff severity {} meets --fail-on threshold {}",
+                    diff.severity.as_str(),
+                    threshold.as_str()
+                )),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Fetch a snapshot's manifest and render it to Markdown.
+fn render_snapshot_markdown(
+    conn: &rusqlite::Connection,
+    cas: &FsStore,
+    snapshot_id: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let query = EngineQuery::ManifestGetBySnapshot {
+        snapshot_id: snapshot_id.to_string(),
+    };
+    let bytes = match apply_engine_query(query, conn, cas, None)? {
+        EngineQueryResult::ManifestGet(r) => r.manifest_bytes,
+        _ => unreachable!("unexpected EngineQueryResult variant in manifest get"),
+    };
+    let parsed = ParsedManifest::parse(&bytes)?;
+    Ok(render_manifest_markdown(&parsed.manifest))
+}
+
+fn execute_compare(args: CompareArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let summary_out = args.summary_out.clone();
+    let mut summary = CommandSummary::new("snapshot_compare");
+    let result = run_compare(args, &mut summary);
+    summary::finish(summary_out.as_deref(), &summary, result)
+}
+
+fn run_compare(
+    args: CompareArgs,
+    summary: &mut CommandSummary,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = ettlex_store::env_keys::open_db(&args.db)?;
+    ettlex_store::migrations::apply_migrations(&mut conn)?;
+    let cas = ettlex_store::env_keys::open_cas(&args.cas)?;
+
+    let query = EngineQuery::SnapshotCompareMatrix {
+        refs: args
+            .snapshots
+            .iter()
+            .map(|s| SnapshotRef::SnapshotId(s.clone()))
+            .collect(),
+    };
+    let matrix = match apply_engine_query(query, &conn, &cas, None)? {
+        EngineQueryResult::SnapshotCompareMatrix(m) => m,
+        _ => unreachable!("unexpected EngineQueryResult variant in snapshot compare"),
+    };
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&matrix)?);
+    } else {
+        println!("Snapshots:");
+        for (i, entry) in matrix.entries.iter().enumerate() {
+            println!(
+                "  [{}] {}  semantic {}",
+                i,
+                entry.snapshot_id.as_deref().unwrap_or("-"),
+                entry.semantic_manifest_digest
+            );
+        }
+        println!("\nSemantic equality (= equal):");
+        let header: Vec<String> = (0..matrix.entries.len()).map(|i| i.to_string()).collect();
+        println!("       {}", header.join(" "));
+        for (i, row) in matrix.equal.iter().enumerate() {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(&header)
+                .map(|(eq, h)| {
+                    let mark = if *eq { "=" } else { "." };
+                    format!("{:>width$}", mark, width = h.len())
+                })
+                .collect();
+            println!("  {:>4} {}", format!("[{}]", i), cells.join(" "));
         }
+        println!("\nClusters:");
+        for cluster in &matrix.clusters {
+            let members: Vec<String> = cluster.iter().map(|i| format!("[{}]", i)).collect();
+            println!("  {}", members.join(" "));
+        }
+    }
+
+    let duplicates = matrix.duplicate_clusters().count();
+    summary.count("snapshots", matrix.entries.len());
+    summary.count("clusters", matrix.clusters.len());
+    summary.count("duplicate_clusters", duplicates);
+    summary.outcome(
+        "result",
+        if duplicates == 0 {
+            "all_distinct"
+        } else {
+            "duplicates_found"
+        },
+    );
+    Ok(())
+}
+
+fn execute_materialize(args: MaterializeArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let summary_out = args.summary_out.clone();
+    let mut summary = CommandSummary::new("snapshot_materialize");
+    let result = run_materialize(args, &mut summary);
+    summary::finish(summary_out.as_deref(), &summary, result)
+}
+
+fn run_materialize(
+    args: MaterializeArgs,
+    summary: &mut CommandSummary,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = ettlex_store::env_keys::open_db(&args.db)?;
+    ettlex_store::migrations::apply_migrations(&mut conn)?;
+    let cas = ettlex_store::env_keys::open_cas(&args.cas)?;
+
+    let index = materialize_snapshot(&conn, &cas, &args.snapshot_id, &args.out)?;
+
+    println!("✓ Materialized snapshot {}", index.snapshot_id);
+    println!("  out: {}", args.out.display());
+    println!("  manifest_digest: {}", index.manifest_digest);
+    println!("  blobs: {}", index.blobs.len());
+    if !index.unresolved.is_empty() {
+        println!("  unresolved digests: {}", index.unresolved.len());
+        summary.warn(format!(
+            "{} referenced digest(s) could not be resolved from CAS",
+            index.unresolved.len()
+        ));
+    }
+    summary.id("snapshot_id", index.snapshot_id.clone());
+    summary.digest("manifest_digest", index.manifest_digest.clone());
+    summary.count("blobs", index.blobs.len());
+    summary.count("unresolved", index.unresolved.len());
+    Ok(())
+}
+
+fn execute_export_all(args: ExportAllArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let summary_out = args.summary_out.clone();
+    let mut summary = CommandSummary::new("snapshot_export_all");
+    let result = run_export_all(args, &mut summary);
+    summary::finish(summary_out.as_deref(), &summary, result)
+}
+
+fn run_export_all(
+    args: ExportAllArgs,
+    summary: &mut CommandSummary,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = ettlex_store::env_keys::open_db(&args.db)?;
+    ettlex_store::migrations::apply_migrations(&mut conn)?;
+    let cas = ettlex_store::env_keys::open_cas(&args.cas)?;
+
+    let options = ExportAllOptions {
+        include_blobs: args.blobs,
+        resume: args.resume,
+    };
+    let report = export_all_snapshots(&conn, &cas, &args.out, options)?;
+
+    println!(
+        "✓ Exported {} snapshot(s) ({} already exported, {} in ledger)",
+        report.exported.len(),
+        report.skipped,
+        report.total
+    );
+    println!("  out: {}", args.out.display());
+    summary.count("exported", report.exported.len());
+    summary.count("skipped", report.skipped);
+    summary.count("total", report.total);
+    Ok(())
+}
+
+fn execute_ledger_export(args: LedgerExportArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let summary_out = args.summary_out.clone();
+    let mut summary = CommandSummary::new("snapshot_ledger_export");
+    let result = run_ledger_export(args, &mut summary);
+    summary::finish(summary_out.as_deref(), &summary, result)
+}
+
+fn run_ledger_export(
+    args: LedgerExportArgs,
+    summary: &mut CommandSummary,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = ettlex_store::env_keys::open_db(&args.db)?;
+    ettlex_store::migrations::apply_migrations(&mut conn)?;
+    let cas = ettlex_store::env_keys::open_cas(&args.cas)?;
+
+    let options = LedgerExportOptions {
+        include_manifest_summary: args.manifest_summary,
+    };
+    let to_stdout = args.out.as_os_str() == "-";
+    let rows = if to_stdout {
+        export_ledger_jsonl(&conn, &cas, std::io::stdout().lock(), options)?
+    } else {
+        let file = std::fs::File::create(&args.out)?;
+        export_ledger_jsonl(&conn, &cas, std::io::BufWriter::new(file), options)?
+    };
+
+    if !to_stdout {
+        println!("✓ Exported {} ledger row(s)", rows);
+        println!("  out: {}", args.out.display());
     }
+    summary.count("rows", rows);
+    Ok(())
+}
+
+fn execute_verify_determinism(
+    args: VerifyDeterminismArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let summary_out = args.summary_out.clone();
+    let mut summary = CommandSummary::new("snapshot_verify_determinism");
+    let result = run_verify_determinism(args, &mut summary);
+    summary::finish(summary_out.as_deref(), &summary, result)
+}
+
+fn run_verify_determinism(
+    args: VerifyDeterminismArgs,
+    summary: &mut CommandSummary,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = ettlex_store::env_keys::open_db(&args.db)?;
+    ettlex_store::migrations::apply_migrations(&mut conn)?;
+    let cas = ettlex_store::env_keys::open_cas(&args.cas)?;
+    summary.id("leaf", args.leaf.clone());
+
+    let report = verify_manifest_determinism(
+        &conn,
+        &cas,
+        &args.leaf,
+        &args.policy,
+        &args.profile,
+        args.iterations,
+    )?;
 
+    println!(
+        "✓ Manifest generation is deterministic over {} iteration(s)",
+        report.iterations
+    );
+    println!("  manifest_bytes_digest: {}", report.manifest_bytes_digest);
+    println!(
+        "  semantic_manifest_digest: {}",
+        report.semantic_manifest_digest
+    );
+    summary.count("iterations", report.iterations as usize);
+    summary.digest("manifest_bytes_digest", report.manifest_bytes_digest);
+    summary.digest("semantic_manifest_digest", report.semantic_manifest_digest);
     Ok(())
 }
@@ -0,0 +1,105 @@
+//! Machine-readable `--summary-out` files for CI
+//!
+//! Commands that accept `--summary-out <FILE>` write one JSON object there
+//! whether they succeed or fail:
+//!
+//! ```json
+//! {
+//!   "command": "snapshot_diff",
+//!   "ok": false,
+//!   "ids": { "a": "…", "b": "…" },
+//!   "digests": { "a_manifest_digest": "…", "b_manifest_digest": "…" },
+//!   "counts": { "added_eps": 0, "removed_eps": 1 },
+//!   "outcome": { "severity": "breaking" },
+//!   "warnings": [],
+//!   "error": { "code": "ERR_POLICY_DENIED", "message": "…" }
+//! }
+//! ```
+//!
+//! `error` is `null` on success. `code` is the stable `ExError` code, or
+//! `null` for errors that do not originate in EttleX (I/O, SQLite, …).
+
+use ettlex_core::errors::ExError;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Structured result of a CLI command, filled in as the command runs.
+#[derive(Debug, Clone, Default)]
+pub struct CommandSummary {
+    command: String,
+    ids: BTreeMap<String, String>,
+    digests: BTreeMap<String, String>,
+    counts: BTreeMap<String, u64>,
+    outcome: BTreeMap<String, String>,
+    warnings: Vec<String>,
+}
+
+impl CommandSummary {
+    pub fn new(command: &str) -> Self {
+        Self {
+            command: command.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn id(&mut self, key: &str, value: impl Into<String>) {
+        self.ids.insert(key.to_string(), value.into());
+    }
+
+    pub fn digest(&mut self, key: &str, value: impl Into<String>) {
+        self.digests.insert(key.to_string(), value.into());
+    }
+
+    pub fn count(&mut self, key: &str, value: usize) {
+        self.counts.insert(key.to_string(), value as u64);
+    }
+
+    /// Record an enumerated result such as a diff severity.
+    pub fn outcome(&mut self, key: &str, value: impl Into<String>) {
+        self.outcome.insert(key.to_string(), value.into());
+    }
+
+    pub fn warn(&mut self, warning: impl Into<String>) {
+        self.warnings.push(warning.into());
+    }
+
+    /// Render the summary, recording `error` if the command failed.
+    pub fn to_json(&self, error: Option<&(dyn std::error::Error + 'static)>) -> Value {
+        let error = error.map(|e| {
+            let code = e.downcast_ref::<ExError>().map(ExError::code);
+            json!({ "code": code, "message": e.to_string() })
+        });
+        json!({
+            "command": self.command,
+            "ok": error.is_none(),
+            "ids": self.ids,
+            "digests": self.digests,
+            "counts": self.counts,
+            "outcome": self.outcome,
+            "warnings": self.warnings,
+            "error": error,
+        })
+    }
+}
+
+/// Write `summary` to `path` (if given) and pass `result` through.
+///
+/// A failure to write the summary is only reported when the command itself
+/// succeeded, so the original error is never masked.
+pub fn finish(
+    path: Option<&Path>,
+    summary: &CommandSummary,
+    result: Result<(), Box<dyn std::error::Error>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(path) = path else {
+        return result;
+    };
+    let error = result.as_ref().err().map(|e| e.as_ref());
+    let rendered = serde_json::to_string_pretty(&summary.to_json(error))?;
+    match (std::fs::write(path, rendered), result) {
+        (_, Err(e)) => Err(e),
+        (Err(e), Ok(())) => Err(Box::new(e)),
+        (Ok(()), Ok(())) => Ok(()),
+    }
+}
@@ -0,0 +1,77 @@
+//! API token commands
+
+use super::import::format_timestamp_ms;
+use clap::{Args, Subcommand};
+use ettlex_engine::commands::api_token::{issue_api_token, list_api_tokens, revoke_api_token};
+
+#[derive(Debug, Args)]
+pub struct TokenArgs {
+    #[command(subcommand)]
+    pub command: TokenCommand,
+
+    #[arg(long, default_value = ".ettlex/store.db", global = true)]
+    pub db: String,
+
+    #[arg(long, default_value = ".ettlex/cas", global = true)]
+    pub cas: String,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum TokenCommand {
+    /// Issue a token and print its secret (shown only once)
+    Issue {
+        /// Label for the client or pipeline using the token
+        #[arg(long)]
+        name: String,
+        /// `read`, `write`, `approve` or `admin`; repeatable
+        #[arg(long = "scope", required = true)]
+        scopes: Vec<String>,
+    },
+    /// Revoke a token by ID
+    Revoke { token_id: String },
+    /// List issued tokens (never their secrets)
+    List {
+        #[arg(long)]
+        include_revoked: bool,
+    },
+}
+
+pub fn execute(args: TokenArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = ettlex_store::env_keys::open_db(&args.db)?;
+    ettlex_store::migrations::apply_migrations(&mut conn)?;
+    let cas = ettlex_store::env_keys::open_cas(&args.cas)?;
+
+    match args.command {
+        TokenCommand::Issue { name, scopes } => {
+            let issued = issue_api_token(&mut conn, &cas, &name, &scopes)?;
+            println!(
+                "✓ Issued {} ({})",
+                issued.record.token_id,
+                issued.record.scopes.join(", ")
+            );
+            println!("{}", issued.secret);
+            eprintln!("Store this secret now; it cannot be shown again.");
+        }
+        TokenCommand::Revoke { token_id } => {
+            revoke_api_token(&mut conn, &cas, &token_id)?;
+            println!("✓ Revoked {}", token_id);
+        }
+        TokenCommand::List { include_revoked } => {
+            for token in list_api_tokens(&conn, include_revoked)? {
+                let state = match token.revoked_at_ms {
+                    Some(at) => format!("revoked {}", format_timestamp_ms(at)),
+                    None => "active".to_string(),
+                };
+                println!(
+                    "{:<42} {:<20} {:<28} issued {}  {}",
+                    token.token_id,
+                    token.name,
+                    token.scopes.join(","),
+                    format_timestamp_ms(token.created_at_ms),
+                    state
+                );
+            }
+        }
+    }
+    Ok(())
+}
@@ -5,6 +5,7 @@
 use clap::{Parser, Subcommand};
 
 use ettlex_cli::commands;
+use ettlex_engine::feature_flags;
 
 #[derive(Debug, Parser)]
 #[command(name = "ettlex")]
@@ -16,22 +17,105 @@ struct Cli {
 
 #[derive(Debug, Subcommand)]
 enum Commands {
+    /// Reconcile a refinement subtree with a declarative YAML description
+    Apply(commands::apply::ApplyArgs),
+    /// Approval queue operations
+    Approval(commands::approval::ApprovalArgs),
+    /// Online backup of the store database plus a CAS manifest
+    Backup(commands::backup::BackupArgs),
+    /// Store maintenance (encryption key rotation, CAS garbage collection)
+    Db(commands::db::DbArgs),
+    /// Export the store's history as an event stream
+    Events(commands::events::EventsArgs),
+    /// Decision evidence maintenance
+    Evidence(commands::evidence::EvidenceArgs),
+    /// Experimental feature flags
+    Feature(commands::feature::FeatureArgs),
+    /// Bulk import of Ettle trees (CSV)
+    Import(commands::import::ImportArgs),
+    /// Snapshot ledger integrity operations
+    Ledger(commands::ledger::LedgerArgs),
+    /// Apply incremental patch files (add, update, tombstone)
+    Patch(commands::patch::PatchArgs),
+    /// Read-only engine queries, printed as JSON
+    Query(commands::query::QueryArgs),
+    /// Reapply the most recently undone command
+    Redo(commands::journal::JournalArgs),
+    /// Rewrite references across Ettle content
+    Refactor(commands::refactor::RefactorArgs),
     /// Render operations (ettle or bundle to Markdown)
     Render(commands::render::RenderArgs),
+    /// Root Ettle registry (register, update, list)
+    Root(commands::root::RootArgs),
+    /// HTTP/JSON API for engine queries and commands
+    Serve(commands::serve::ServeArgs),
+    /// Typed repository settings
+    Settings(commands::settings::SettingsArgs),
     /// Snapshot operations
     Snapshot(commands::snapshot::SnapshotArgs),
+    /// API tokens for server access (issue, revoke, list)
+    Token(commands::token::TokenArgs),
+    /// Revert the most recent journaled command
+    Undo(commands::journal::JournalArgs),
 }
 
 fn main() {
     let cli = Cli::parse();
 
+    // ETTLEX_FEATURES overrides repository feature flag settings
+    if let Some(spec) = std::env::var("ETTLEX_FEATURES")
+        .ok()
+        .filter(|s| !s.is_empty())
+    {
+        match feature_flags::parse_feature_overrides(&spec) {
+            Ok(overrides) => feature_flags::set_feature_overrides(overrides),
+            Err(e) => {
+                eprintln!("Error: invalid ETTLEX_FEATURES: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // ETTLEX_SQL_AUDIT records executed SQL, dumped to stderr on failure
+    if std::env::var("ETTLEX_SQL_AUDIT").is_ok_and(|v| !v.is_empty() && v != "0") {
+        ettlex_store::sql_audit::enable();
+    }
+
     let result = match cli.command {
+        Commands::Apply(args) => commands::apply::execute(args),
+        Commands::Approval(args) => commands::approval::execute(args),
+        Commands::Backup(args) => commands::backup::execute(args),
+        Commands::Db(args) => commands::db::execute(args),
+        Commands::Events(args) => commands::events::execute(args),
+        Commands::Evidence(args) => commands::evidence::execute(args),
+        Commands::Feature(args) => commands::feature::execute(args),
+        Commands::Import(args) => commands::import::execute(args),
+        Commands::Ledger(args) => commands::ledger::execute(args),
+        Commands::Patch(args) => commands::patch::execute(args),
+        Commands::Query(args) => commands::query::execute(args),
+        Commands::Redo(args) => commands::journal::execute_redo(args),
+        Commands::Refactor(args) => commands::refactor::execute(args),
         Commands::Render(args) => commands::render::execute(args),
+        Commands::Root(args) => commands::root::execute(args),
+        Commands::Serve(args) => commands::serve::execute(args),
+        Commands::Settings(args) => commands::settings::execute(args),
         Commands::Snapshot(args) => commands::snapshot::execute(args),
+        Commands::Token(args) => commands::token::execute(args),
+        Commands::Undo(args) => commands::journal::execute_undo(args),
     };
 
     if let Err(e) = result {
         eprintln!("Error: {}", e);
-        std::process::exit(1);
+        if ettlex_store::sql_audit::is_enabled() {
+            eprintln!("SQL audit (oldest first):");
+            for entry in ettlex_store::sql_audit::entries() {
+                eprintln!("  [{}] {}", entry.seq, entry.sql);
+            }
+        }
+        // Engine errors exit with their shared transport code; anything else is 1
+        let code = e
+            .downcast_ref::<ettlex_core::errors::ExError>()
+            .map_or(1, |ex| ex.transport().exit_code);
+        std::process::exit(code);
     }
 }
@@ -0,0 +1,343 @@
+//! CLI tests for `ettlex apply` — planning, execution and idempotence of
+//! declarative subtree reconciliation.
+
+#![allow(clippy::unwrap_used, clippy::result_large_err)]
+
+use ettlex_cli::commands::apply::parse_desired_state;
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::ExErrorKind;
+use ettlex_core::ops::Store;
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_core::snapshot::manifest::generate_manifest;
+use ettlex_engine::commands::command::{apply_command, Command};
+use ettlex_engine::commands::constraint::TargetSelector;
+use ettlex_engine::commands::ettle::handle_ettle_get;
+use ettlex_engine::commands::reconcile::{
+    assess_plan, execute_plan, plan_reconciliation, AssessedChange, DesiredState, PlannedChange,
+    RiskLevel,
+};
+use ettlex_store::cas::FsStore;
+use ettlex_store::model::RelationListOpts;
+use ettlex_store::repo::SqliteRepo;
+use ettlex_store::snapshot::persist::{commit_snapshot, SnapshotOptions};
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+fn setup() -> (Connection, FsStore, TempDir) {
+    let dir = TempDir::new().unwrap();
+    let mut conn = Connection::open(dir.path().join("store.db")).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    let cas = FsStore::new(dir.path().join("cas"));
+    (conn, cas, dir)
+}
+
+fn desired(yaml: &str) -> DesiredState {
+    parse_desired_state(yaml.as_bytes()).unwrap()
+}
+
+fn apply(conn: &mut Connection, cas: &FsStore, yaml: &str) -> Vec<PlannedChange> {
+    let plan = plan_reconciliation(conn, &desired(yaml)).unwrap();
+    let assessed = assess_plan(conn, plan.clone()).unwrap();
+    execute_plan(conn, cas, &assessed, true).unwrap();
+    plan
+}
+
+/// Active refinement children of `parent` as (child title, ordinal).
+fn children(conn: &Connection, parent: &str) -> Vec<(String, i64)> {
+    let mut out: Vec<(String, i64)> = SqliteRepo::list_relations(
+        conn,
+        &RelationListOpts {
+            source_ettle_id: Some(handle_ettle_get(conn, parent).unwrap().id),
+            relation_type: Some("refinement".to_string()),
+            ..Default::default()
+        },
+    )
+    .unwrap()
+    .into_iter()
+    .map(|r| {
+        let props: serde_json::Value = serde_json::from_str(&r.properties_json).unwrap();
+        let title = handle_ettle_get(conn, &r.target_ettle_id).unwrap().title;
+        (title, props["ordinal"].as_i64().unwrap())
+    })
+    .collect();
+    out.sort_by_key(|(_, ordinal)| *ordinal);
+    out
+}
+
+const INITIAL: &str = "
+root:
+  title: Payments
+  children:
+    - title: Refunds
+    - title: Disputes
+";
+
+#[test]
+fn test_apply_creates_tree_then_reconciles_minimally() {
+    let (mut conn, cas, _dir) = setup();
+    let plan = apply(&mut conn, &cas, INITIAL);
+    assert_eq!(plan.len(), 5); // 3 creates + 2 attaches
+    assert_eq!(
+        children(&conn, "payments"),
+        vec![("Refunds".to_string(), 0), ("Disputes".to_string(), 1)]
+    );
+
+    let target = "
+root:
+  id: payments
+  title: Payments
+  what: Moves money.
+  children:
+    - id: disputes
+      title: Disputes
+    - title: Chargebacks
+";
+    let plan = apply(&mut conn, &cas, target);
+    let kinds: Vec<&str> = plan
+        .iter()
+        .map(|c| match c {
+            PlannedChange::CreateEttle { .. } => "create",
+            PlannedChange::UpdateEttle { .. } => "update",
+            PlannedChange::DetachRefinement { .. } => "detach",
+            PlannedChange::AttachRefinement { .. } => "attach",
+            PlannedChange::ReorderRefinement { .. } => "reorder",
+            PlannedChange::TombstoneEttle { .. } => "tombstone",
+        })
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            "create",
+            "update",
+            "detach",
+            "attach",
+            "reorder",
+            "tombstone"
+        ]
+    );
+
+    assert_eq!(
+        handle_ettle_get(&conn, "payments").unwrap().what,
+        "Moves money."
+    );
+    assert!(handle_ettle_get(&conn, "refunds")
+        .unwrap()
+        .tombstoned_at
+        .is_some());
+    assert_eq!(
+        children(&conn, "payments"),
+        vec![("Disputes".to_string(), 0), ("Chargebacks".to_string(), 1)]
+    );
+
+    // Declaring the result again is a no-op.
+    let settled = target.replace(
+        "- title: Chargebacks",
+        "- id: chargebacks\n      title: Chargebacks",
+    );
+    assert!(plan_reconciliation(&conn, &desired(&settled))
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_failed_plan_applies_nothing() {
+    let (mut conn, cas, _dir) = setup();
+    apply(&mut conn, &cas, INITIAL);
+    let plan = plan_reconciliation(
+        &conn,
+        &desired("root:\n  id: payments\n  title: Payments v2\n"),
+    )
+    .unwrap();
+
+    // A final step that fails after the title update has run.
+    let mut broken = plan.clone();
+    broken.push(PlannedChange::TombstoneEttle {
+        ettle_id: "ettle:missing".to_string(),
+    });
+    let broken = assess_plan(&conn, broken).unwrap();
+    let err = execute_plan(&mut conn, &cas, &broken, true).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+    assert_eq!(
+        handle_ettle_get(&conn, "payments").unwrap().title,
+        "Payments"
+    );
+    assert_eq!(children(&conn, "payments").len(), 2);
+}
+
+#[test]
+fn test_invalid_desired_state_is_rejected() {
+    let (mut conn, cas, _dir) = setup();
+    apply(&mut conn, &cas, INITIAL);
+
+    let err = parse_desired_state("root:\n  title: X\n  colour: red\n".as_bytes()).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+
+    let err = plan_reconciliation(&conn, &desired("root:\n  id: nope\n  title: X\n")).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+
+    let twice = "
+root:
+  id: payments
+  title: Payments
+  children:
+    - id: refunds
+      title: Refunds
+    - id: refunds
+      title: Refunds
+";
+    let err = plan_reconciliation(&conn, &desired(twice)).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_assess_flags_destructive_changes_on_anchored_tree() {
+    let (mut conn, cas, _dir) = setup();
+    apply(&mut conn, &cas, INITIAL);
+    apply(&mut conn, &cas, "root:\n  title: Security\n");
+    apply_command(
+        Command::ConstraintAttachBulk {
+            constraint_ettle_id: handle_ettle_get(&conn, "security").unwrap().id,
+            selector: TargetSelector::TitleContains {
+                text: "refunds".to_string(),
+            },
+            properties_json: None,
+        },
+        None,
+        &mut conn,
+        &cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .unwrap();
+    let without_refunds = "
+root:
+  id: payments
+  title: Payments
+  children:
+    - id: disputes
+      title: Disputes
+";
+    let risks = |conn: &Connection| -> Vec<(RiskLevel, usize)> {
+        let plan = plan_reconciliation(conn, &desired(without_refunds)).unwrap();
+        assess_plan(conn, plan)
+            .unwrap()
+            .into_iter()
+            .map(|a| (a.risk, a.reasons.len()))
+            .collect()
+    };
+
+    // detach refunds, reorder disputes, tombstone refunds (+ constraint)
+    assert_eq!(
+        risks(&conn),
+        vec![
+            (RiskLevel::Medium, 0),
+            (RiskLevel::Low, 0),
+            (RiskLevel::High, 1)
+        ]
+    );
+
+    let root = handle_ettle_get(&conn, "payments").unwrap().id;
+    let manifest = generate_manifest(
+        vec!["ep:payments:0".to_string()],
+        "policy/default@0".into(),
+        "profile/default@0".into(),
+        root,
+        "0001".into(),
+        None,
+        &Store::new(),
+    )
+    .unwrap();
+    commit_snapshot(&mut conn, &cas, manifest, SnapshotOptions::default()).unwrap();
+
+    // Anchored: the detach breaks the EPT, and the rest touch snapshot content.
+    let anchored = risks(&conn);
+    assert_eq!(anchored[0].0, RiskLevel::High);
+    assert_eq!(anchored[1], (RiskLevel::Medium, 1));
+    assert_eq!(anchored[2], (RiskLevel::High, 2));
+
+    let plan = plan_reconciliation(&conn, &desired(without_refunds)).unwrap();
+    let assessed = assess_plan(&conn, plan).unwrap();
+    let err = execute_plan(&mut conn, &cas, &assessed, false).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::PolicyDenied);
+
+    // Execution re-assesses, so understated risk levels do not get through.
+    let understated: Vec<AssessedChange> = assessed
+        .iter()
+        .cloned()
+        .map(|a| AssessedChange {
+            risk: RiskLevel::Low,
+            reasons: Vec::new(),
+            ..a
+        })
+        .collect();
+    let err = execute_plan(&mut conn, &cas, &understated, false).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::PolicyDenied);
+    assert_eq!(children(&conn, "payments").len(), 2);
+
+    execute_plan(&mut conn, &cas, &assessed, true).unwrap();
+    assert_eq!(children(&conn, "payments").len(), 1);
+}
+
+#[test]
+fn test_apply_links_new_ettles_whose_titles_collide() {
+    let (mut conn, cas, _dir) = setup();
+    apply(&mut conn, &cas, INITIAL);
+
+    // "Refunds" is taken by an existing slug and declared twice more.
+    let yaml = "
+root:
+  title: Refunds
+  children:
+    - title: Refunds
+    - title: Refunds
+";
+    let plan = plan_reconciliation(&conn, &desired(yaml)).unwrap();
+    let assessed = assess_plan(&conn, plan).unwrap();
+    let report = execute_plan(&mut conn, &cas, &assessed, false).unwrap();
+
+    let root = &report.created["root"];
+    let mut linked: Vec<String> = SqliteRepo::list_relations(
+        &conn,
+        &RelationListOpts {
+            source_ettle_id: Some(root.clone()),
+            relation_type: Some("refinement".to_string()),
+            ..Default::default()
+        },
+    )
+    .unwrap()
+    .into_iter()
+    .map(|r| r.target_ettle_id)
+    .collect();
+    linked.sort();
+    let mut expected = vec![
+        report.created["root/0"].clone(),
+        report.created["root/1"].clone(),
+    ];
+    expected.sort();
+    assert_eq!(linked, expected);
+    assert_eq!(children(&conn, "payments").len(), 2);
+}
+
+#[test]
+fn test_apply_reports_reused_cas_blobs() {
+    let (mut conn, cas, _dir) = setup();
+    ettlex_engine::commands::settings::settings_set(
+        &conn,
+        "content.max_inline_bytes",
+        Some(serde_json::json!(8)),
+    )
+    .unwrap();
+    let yaml = "
+root:
+  title: Payments
+  how: Shared boilerplate body
+  children:
+    - title: Refunds
+      how: Shared boilerplate body
+";
+    let plan = plan_reconciliation(&conn, &desired(yaml)).unwrap();
+    let assessed = assess_plan(&conn, plan).unwrap();
+    let report = execute_plan(&mut conn, &cas, &assessed, true).unwrap();
+    assert_eq!(report.cas.blobs_written, 1);
+    assert_eq!(report.cas.blobs_reused, 1);
+}
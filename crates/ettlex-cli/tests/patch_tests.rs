@@ -0,0 +1,262 @@
+//! CLI tests for `ettlex patch` — deltas, preconditions and all-or-nothing
+//! application.
+
+#![allow(clippy::unwrap_used, clippy::result_large_err)]
+
+use ettlex_cli::commands::patch::parse_patch;
+use ettlex_core::errors::ExErrorKind;
+use ettlex_engine::commands::csv_import::{import_csv, CsvColumnMapping};
+use ettlex_engine::commands::ettle::ettle_content_digest;
+use ettlex_engine::commands::patch::apply_patch;
+use ettlex_store::cas::FsStore;
+use ettlex_store::model::{EttleRecord, RelationListOpts};
+use ettlex_store::repo::SqliteRepo;
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+/// Store with `root` refining `a` (ordinal 0) and `b` (ordinal 1).
+fn setup() -> (Connection, FsStore, TempDir, Vec<String>) {
+    let dir = TempDir::new().unwrap();
+    let mut conn = Connection::open(dir.path().join("store.db")).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    let cas = FsStore::new(dir.path().join("cas"));
+    let csv = "ref,parent,title,ordinal\nroot,,Root,\na,root,Alpha,0\nb,root,Beta,1\n";
+    let report = import_csv(
+        &mut conn,
+        &cas,
+        csv.as_bytes(),
+        &CsvColumnMapping::default(),
+    )
+    .unwrap();
+    let ids = report.ettles.into_iter().map(|(_, id)| id).collect();
+    (conn, cas, dir, ids)
+}
+
+fn record(conn: &Connection, id: &str) -> EttleRecord {
+    SqliteRepo::get_ettle_record(conn, id).unwrap().unwrap()
+}
+
+fn digest(conn: &Connection, cas: &FsStore, id: &str) -> String {
+    ettle_content_digest(&record(conn, id), cas).unwrap()
+}
+
+fn count(conn: &Connection, table: &str) -> i64 {
+    conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |r| r.get(0))
+        .unwrap()
+}
+
+#[test]
+fn test_patch_adds_updates_and_tombstones() {
+    let (mut conn, cas, _dir, ids) = setup();
+    let (root, a, b) = (&ids[0], &ids[1], &ids[2]);
+    let yaml = format!(
+        "\
+ops:
+  - add:
+      key: gamma
+      under: {root}
+      title: Gamma
+  - add:
+      under: gamma
+      title: Gamma child
+      ordinal: 5
+  - update:
+      id: {a}
+      expect: {a_digest}
+      what: Updated
+  - tombstone:
+      id: {b}
+      expect: {b_digest}
+",
+        a_digest = digest(&conn, &cas, a),
+        b_digest = digest(&conn, &cas, b),
+    );
+    let patch = parse_patch(yaml.as_bytes()).unwrap();
+    let report = apply_patch(&mut conn, &cas, &patch, false).unwrap();
+
+    assert_eq!(report.applied, 4);
+    assert_eq!(report.created.len(), 2);
+    let gamma = &report.created[0].1;
+    assert_eq!(report.created[0].0.as_deref(), Some("gamma"));
+    assert_eq!(record(&conn, a).what, "Updated");
+    assert!(record(&conn, b).tombstoned_at.is_some());
+
+    let under_root = SqliteRepo::list_relations(
+        &conn,
+        &RelationListOpts {
+            source_ettle_id: Some(root.clone()),
+            target_ettle_id: Some(gamma.clone()),
+            relation_type: Some("refinement".to_string()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    // Appended after the highest existing ordinal
+    assert_eq!(under_root[0].properties_json, r#"{"ordinal":2}"#);
+    let under_gamma = SqliteRepo::list_relations(
+        &conn,
+        &RelationListOpts {
+            source_ettle_id: Some(gamma.clone()),
+            relation_type: Some("refinement".to_string()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(under_gamma[0].properties_json, r#"{"ordinal":5}"#);
+}
+
+#[test]
+fn test_stale_precondition_rejects_the_whole_patch() {
+    let (mut conn, cas, _dir, ids) = setup();
+    let (root, a) = (&ids[0], &ids[1]);
+    let yaml = format!(
+        "\
+ops:
+  - add:
+      under: {root}
+      title: Gamma
+  - update:
+      id: {a}
+      expect: {stale}
+      title: Renamed
+",
+        stale = "0".repeat(64),
+    );
+    let ettles_before = count(&conn, "ettles");
+    let patch = parse_patch(yaml.as_bytes()).unwrap();
+    let err = apply_patch(&mut conn, &cas, &patch, false).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::HeadMismatch);
+    assert_eq!(count(&conn, "ettles"), ettles_before);
+    assert_eq!(record(&conn, a).title, "Alpha");
+}
+
+#[test]
+fn test_patch_rejects_double_targets_and_forward_keys() {
+    let (mut conn, cas, _dir, ids) = setup();
+    let a = &ids[1];
+    let a_digest = digest(&conn, &cas, a);
+    let twice = format!(
+        "ops:\n  - update: {{id: {a}, expect: {a_digest}, title: X}}\n  - tombstone: {{id: {a}, expect: {a_digest}}}\n"
+    );
+    let err = apply_patch(
+        &mut conn,
+        &cas,
+        &parse_patch(twice.as_bytes()).unwrap(),
+        false,
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+
+    let forward =
+        "ops:\n  - add: {under: later, title: Early}\n  - add: {key: later, title: Later}\n";
+    let err = apply_patch(
+        &mut conn,
+        &cas,
+        &parse_patch(forward.as_bytes()).unwrap(),
+        false,
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+
+    let err = parse_patch("ops:\n  - rename: {id: x}\n".as_bytes()).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_dry_run_writes_nothing() {
+    let (mut conn, cas, _dir, ids) = setup();
+    let yaml = format!("ops:\n  - add: {{under: {}, title: Gamma}}\n", ids[0]);
+    let before = count(&conn, "ettles");
+    let report = apply_patch(
+        &mut conn,
+        &cas,
+        &parse_patch(yaml.as_bytes()).unwrap(),
+        true,
+    )
+    .unwrap();
+    assert!(report.dry_run);
+    assert_eq!(report.created.len(), 1);
+    assert_eq!(count(&conn, "ettles"), before);
+}
+
+#[test]
+fn test_patch_keys_resolve_to_the_right_ettle_when_titles_collide() {
+    let (mut conn, cas, _dir, ids) = setup();
+    let yaml = format!(
+        "\
+ops:
+  - add: {{key: first, under: {root}, title: Alpha}}
+  - add: {{key: second, under: {root}, title: Alpha}}
+  - add: {{under: second, title: Leaf}}
+",
+        root = ids[0],
+    );
+    let log_before = count(&conn, "command_log");
+    let patch = parse_patch(yaml.as_bytes()).unwrap();
+    let report = apply_patch(&mut conn, &cas, &patch, false).unwrap();
+
+    let (first, second, leaf) = (
+        &report.created[0].1,
+        &report.created[1].1,
+        &report.created[2].1,
+    );
+    assert_ne!(first, second);
+    let under_second = SqliteRepo::list_relations(
+        &conn,
+        &RelationListOpts {
+            source_ettle_id: Some(second.clone()),
+            relation_type: Some("refinement".to_string()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(under_second.len(), 1);
+    assert_eq!(&under_second[0].target_ettle_id, leaf);
+    // Consecutive adds under one parent take consecutive ordinals
+    let ordinals: Vec<String> = [first, second]
+        .iter()
+        .map(|id| {
+            SqliteRepo::list_relations(
+                &conn,
+                &RelationListOpts {
+                    target_ettle_id: Some((*id).clone()),
+                    ..Default::default()
+                },
+            )
+            .unwrap()[0]
+                .properties_json
+                .clone()
+        })
+        .collect();
+    assert_eq!(ordinals, [r#"{"ordinal":2}"#, r#"{"ordinal":3}"#]);
+    // Three creates and three refinements, each a logged command
+    assert_eq!(count(&conn, "command_log"), log_before + 6);
+}
+
+#[test]
+fn test_patch_reports_reused_cas_blobs() {
+    let (mut conn, cas, _dir, ids) = setup();
+    ettlex_engine::commands::settings::settings_set(
+        &conn,
+        "content.max_inline_bytes",
+        Some(serde_json::json!(8)),
+    )
+    .unwrap();
+    let yaml = format!(
+        "\
+ops:
+  - add: {{under: {root}, title: Gamma, why: Shared boilerplate body}}
+  - add: {{under: {root}, title: Delta, why: Shared boilerplate body}}
+",
+        root = ids[0]
+    );
+    let report = apply_patch(
+        &mut conn,
+        &cas,
+        &parse_patch(yaml.as_bytes()).unwrap(),
+        false,
+    )
+    .unwrap();
+    assert_eq!(report.cas.blobs_written, 1);
+    assert_eq!(report.cas.blobs_reused, 1);
+}
@@ -0,0 +1,146 @@
+//! CLI tests for `ettlex query` JSON output.
+
+#![allow(clippy::unwrap_used)]
+
+use ettlex_cli::commands::query::{run, PageArgs, QueryArgs, QueryVerb};
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::ops::Store;
+use ettlex_core::snapshot::manifest::generate_manifest_with_input;
+use ettlex_engine::snapshot::manifest_eval_input;
+use ettlex_store::cas::FsStore;
+use ettlex_store::snapshot::persist::{commit_snapshot, SnapshotOptions};
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+struct Env {
+    dir: TempDir,
+    db: String,
+    cas: String,
+}
+
+impl Env {
+    /// A store holding Ettles `ettle:a` to `ettle:e`.
+    fn new() -> Self {
+        let dir = TempDir::new().unwrap();
+        let db = dir.path().join("store.db").display().to_string();
+        let cas = dir.path().join("cas").display().to_string();
+        let mut conn = rusqlite::Connection::open(&db).unwrap();
+        ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+        for id in ["ettle:a", "ettle:b", "ettle:c", "ettle:d", "ettle:e"] {
+            conn.execute(
+                "INSERT INTO ettles (id, title, created_at, updated_at)
+                 VALUES (?1, ?1, '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z')",
+                [id],
+            )
+            .unwrap();
+        }
+        Self { dir, db, cas }
+    }
+
+    fn commit(&self, root: &str) -> String {
+        let mut conn = rusqlite::Connection::open(&self.db).unwrap();
+        let cas = FsStore::new(&self.cas);
+        let ept = vec![root.to_string()];
+        let input = manifest_eval_input(&conn, &cas, &ept, "profile/default@0").unwrap();
+        let manifest = generate_manifest_with_input(
+            ept,
+            "policy/default@0".into(),
+            "profile/default@0".into(),
+            root.into(),
+            "0001".into(),
+            None,
+            &Store::new(),
+            &input,
+        )
+        .unwrap();
+        commit_snapshot(&mut conn, &cas, manifest, SnapshotOptions::default())
+            .unwrap()
+            .snapshot_id
+    }
+
+    fn query(&self, verb: QueryVerb) -> Result<Value, Box<dyn std::error::Error>> {
+        run(QueryArgs {
+            verb,
+            compact: false,
+            db: self.db.clone(),
+            cas: self.cas.clone(),
+            policies: self.dir.path().join("policies").display().to_string(),
+        })
+    }
+}
+
+fn ids(page: &Value) -> Vec<&str> {
+    page["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["id"].as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn test_query_ettle_list_pages_with_cursor() {
+    let env = Env::new();
+    let list = |cursor: Option<String>| {
+        env.query(QueryVerb::EttleList {
+            page: PageArgs {
+                limit: Some(2),
+                cursor,
+            },
+            prefix: None,
+        })
+        .unwrap()
+    };
+
+    let first = list(None);
+    assert_eq!(ids(&first), vec!["ettle:a", "ettle:b"]);
+    assert_eq!(first["has_more"], json!(true));
+
+    let second = list(first["cursor"].as_str().map(String::from));
+    assert_eq!(ids(&second), vec!["ettle:c", "ettle:d"]);
+
+    let last = list(second["cursor"].as_str().map(String::from));
+    assert_eq!(ids(&last), vec!["ettle:e"]);
+    assert_eq!(last["has_more"], json!(false));
+    assert_eq!(last["cursor"], Value::Null);
+}
+
+#[test]
+fn test_query_snapshot_and_manifest_verbs() {
+    let env = Env::new();
+    let snapshot_id = env.commit("ettle:a");
+
+    let row = env
+        .query(QueryVerb::SnapshotGet {
+            snapshot: snapshot_id.clone(),
+        })
+        .unwrap();
+    assert_eq!(row["root_ettle_id"], json!("ettle:a"));
+
+    let manifest = env
+        .query(QueryVerb::ManifestGet {
+            snapshot: None,
+            digest: row["manifest_digest"].as_str().map(String::from),
+        })
+        .unwrap();
+    assert_eq!(manifest["snapshot_id"], json!(snapshot_id));
+    assert_eq!(manifest["manifest"]["root_ettle_id"], json!("ettle:a"));
+
+    let heads = env.query(QueryVerb::Heads).unwrap();
+    assert_eq!(heads["heads"][0]["root_ettle_id"], json!("ettle:a"));
+    assert_eq!(heads["heads"][0]["drift"], json!("in_sync"));
+}
+
+#[test]
+fn test_query_missing_entity_keeps_engine_error_kind() {
+    let env = Env::new();
+
+    let err = env
+        .query(QueryVerb::EttleGet {
+            ettle: "ettle:nope".into(),
+        })
+        .unwrap_err();
+
+    let ex = err.downcast_ref::<ExError>().unwrap();
+    assert_eq!(ex.kind(), ExErrorKind::NotFound);
+}
@@ -0,0 +1,69 @@
+//! CLI tests for `ettlex refactor rename` — per-occurrence confirmation.
+
+#![allow(clippy::unwrap_used)]
+
+use ettlex_cli::commands::refactor::confirm_occurrences;
+use ettlex_engine::commands::refactor::{ContentField, ContentRefOccurrence, ContentRefSite};
+use std::io::Cursor;
+
+fn occurrences(n: usize) -> Vec<ContentRefOccurrence> {
+    (0..n)
+        .map(|i| ContentRefOccurrence {
+            site: ContentRefSite {
+                ettle_id: format!("ettle:{}", i),
+                field: ContentField::What,
+                offset: 0,
+            },
+            line: 1,
+            context: "payments-api".to_string(),
+            rewritten: true,
+        })
+        .collect()
+}
+
+fn ids(sites: Vec<ContentRefSite>) -> Vec<String> {
+    sites.into_iter().map(|s| s.ettle_id).collect()
+}
+
+#[test]
+fn test_confirm_accepts_yes_and_skips_other_answers() {
+    let mut output = Vec::new();
+    let accepted = confirm_occurrences(
+        &occurrences(3),
+        "billing-api",
+        &mut Cursor::new("y\n\nY\n"),
+        &mut output,
+    )
+    .unwrap();
+
+    assert_eq!(ids(accepted), vec!["ettle:0", "ettle:2"]);
+    let shown = String::from_utf8(output).unwrap();
+    assert!(shown.contains("ettle:1 what:1: payments-api"));
+    assert_eq!(shown.matches("[y/N/a/q]").count(), 3);
+}
+
+#[test]
+fn test_confirm_all_and_quit_end_the_prompt() {
+    let mut output = Vec::new();
+    let all = confirm_occurrences(
+        &occurrences(3),
+        "x",
+        &mut Cursor::new("n\na\n"),
+        &mut output,
+    )
+    .unwrap();
+    assert_eq!(ids(all), vec!["ettle:1", "ettle:2"]);
+
+    let quit = confirm_occurrences(
+        &occurrences(3),
+        "x",
+        &mut Cursor::new("y\nq\n"),
+        &mut output,
+    )
+    .unwrap();
+    assert_eq!(ids(quit), vec!["ettle:0"]);
+
+    // End of input stops asking.
+    let eof = confirm_occurrences(&occurrences(2), "x", &mut Cursor::new(""), &mut output).unwrap();
+    assert!(eof.is_empty());
+}
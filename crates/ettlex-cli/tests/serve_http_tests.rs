@@ -0,0 +1,285 @@
+//! CLI tests for the `ettlex serve` HTTP API.
+
+#![allow(clippy::unwrap_used)]
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+use ettlex_cli::commands::serve::{AuthMode, HttpRequest, HttpResponse, ServeArgs, Server};
+use ettlex_engine::commands::api_token::issue_api_token;
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+fn server(dir: &TempDir, auth: AuthMode) -> Server {
+    Server::open(&ServeArgs {
+        addr: "127.0.0.1:0".into(),
+        db: dir.path().join("store.db").display().to_string(),
+        cas: dir.path().join("cas").display().to_string(),
+        policies: dir.path().join("policies").display().to_string(),
+        backup_dir: dir.path().join("backups"),
+        auth,
+        max_body_bytes: 4096,
+        io_timeout_ms: 200,
+    })
+    .unwrap()
+}
+
+fn request(method: &str, path: &str, body: Value) -> HttpRequest {
+    HttpRequest {
+        method: method.into(),
+        path: path.into(),
+        body: serde_json::to_vec(&body).unwrap(),
+        ..HttpRequest::default()
+    }
+}
+
+fn header<'a>(response: &'a HttpResponse, name: &str) -> Option<&'a str> {
+    response
+        .headers
+        .iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, v)| v.as_str())
+}
+
+fn create_ettle(server: &mut Server, title: &str) -> String {
+    let response = server.handle(&request(
+        "POST",
+        "/v1/command",
+        json!({"command": {"tag": "EttleCreate", "title": title}}),
+    ));
+    assert_eq!(response.status, 200, "{}", response.body);
+    response.body["result"]["ettle_id"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+#[test]
+fn test_serve_command_then_query() {
+    let dir = TempDir::new().unwrap();
+    let mut server = server(&dir, AuthMode::None);
+    let ettle_id = create_ettle(&mut server, "Alpha");
+
+    let mut req = request(
+        "POST",
+        "/v1/query",
+        json!({"verb": "ettle-get", "ettle": ettle_id}),
+    );
+    req.headers.insert("x-request-id".into(), "req-1".into());
+    req.headers.insert("x-trace-id".into(), "trace-1".into());
+    let response = server.handle(&req);
+
+    assert_eq!(response.status, 200);
+    assert_eq!(response.body["result"]["ettle"]["title"], json!("Alpha"));
+    assert_eq!(header(&response, "X-Request-Id"), Some("req-1"));
+    assert_eq!(header(&response, "X-Trace-Id"), Some("trace-1"));
+
+    let list = server.handle(&request(
+        "POST",
+        "/v1/query",
+        json!({"verb": "ettle-list", "limit": 10}),
+    ));
+    assert_eq!(list.body["result"]["items"][0]["id"], json!(ettle_id));
+
+    let health = server.handle(&request("GET", "/v1/health", Value::Null));
+    assert_eq!(health.body, json!({"status": "ok"}));
+    assert!(header(&health, "X-Request-Id").is_some());
+}
+
+#[test]
+fn test_serve_errors_use_transport_status() {
+    let dir = TempDir::new().unwrap();
+    let mut server = server(&dir, AuthMode::None);
+
+    let mut req = request(
+        "POST",
+        "/v1/query",
+        json!({"verb": "ettle-get", "ettle": "ettle:nope"}),
+    );
+    req.headers.insert("x-request-id".into(), "req-404".into());
+    let missing = server.handle(&req);
+    assert_eq!(missing.status, 404);
+    assert_eq!(missing.body["error"]["code"], json!("ERR_NOT_FOUND"));
+    assert_eq!(missing.body["error"]["category"], json!("not_found"));
+    assert_eq!(missing.body["error"]["request_id"], json!("req-404"));
+
+    let unknown_verb = server.handle(&request("POST", "/v1/query", json!({"verb": "nope"})));
+    assert_eq!(unknown_verb.status, 400);
+    assert_eq!(
+        unknown_verb.body["error"]["code"],
+        json!("ERR_INVALID_INPUT")
+    );
+
+    let bad_command = server.handle(&request(
+        "POST",
+        "/v1/command",
+        json!({"command": {"tag": "NoSuchCommand"}}),
+    ));
+    assert_eq!(bad_command.status, 400);
+
+    let stale = server.handle(&request(
+        "POST",
+        "/v1/command",
+        json!({"command": {"tag": "EttleCreate", "title": "A"}, "expected_state_version": 99}),
+    ));
+    assert_eq!(stale.status, 409);
+
+    assert_eq!(
+        server
+            .handle(&request("GET", "/v1/nowhere", Value::Null))
+            .status,
+        404
+    );
+    let wrong_method = server.handle(&request("GET", "/v1/query", Value::Null));
+    assert_eq!(wrong_method.status, 405);
+    assert_eq!(header(&wrong_method, "Allow"), Some("POST"));
+}
+
+#[test]
+fn test_serve_token_auth_checks_scopes() {
+    let dir = TempDir::new().unwrap();
+    let mut server = server(&dir, AuthMode::Tokens);
+    let mut conn = rusqlite::Connection::open(dir.path().join("store.db")).unwrap();
+    let cas = ettlex_store::cas::FsStore::new(dir.path().join("cas"));
+    let reader = issue_api_token(&mut conn, &cas, "reader", &["read".to_string()])
+        .unwrap()
+        .secret;
+    let writer = issue_api_token(&mut conn, &cas, "writer", &["write".to_string()])
+        .unwrap()
+        .secret;
+
+    let create = json!({"command": {"tag": "EttleCreate", "title": "A"}});
+    let anonymous = server.handle(&request("POST", "/v1/command", create.clone()));
+    assert_eq!(anonymous.status, 401);
+
+    let mut req = request("POST", "/v1/command", create);
+    req.headers
+        .insert("authorization".into(), format!("Bearer {}", reader));
+    assert_eq!(server.handle(&req).status, 403);
+
+    let mut req = request("POST", "/v1/query", json!({"verb": "ettle-list"}));
+    req.headers
+        .insert("authorization".into(), format!("Bearer {}", reader));
+    assert_eq!(server.handle(&req).status, 200);
+
+    // Issuing tokens needs `admin`, not just `write`
+    let issue = json!({"command": {"tag": "ApiTokenIssue", "name": "x", "scopes": ["admin"]}});
+    let mut req = request("POST", "/v1/command", issue);
+    req.headers
+        .insert("authorization".into(), format!("Bearer {}", writer));
+    assert_eq!(server.handle(&req).status, 403);
+
+    assert_eq!(
+        server
+            .handle(&request("GET", "/v1/health", Value::Null))
+            .status,
+        200
+    );
+}
+
+#[test]
+fn test_serve_backup_needs_admin_and_writes_a_complete_backup() {
+    let dir = TempDir::new().unwrap();
+    let mut server = server(&dir, AuthMode::Tokens);
+    let mut conn = rusqlite::Connection::open(dir.path().join("store.db")).unwrap();
+    let cas = ettlex_store::cas::FsStore::new(dir.path().join("cas"));
+    cas.write(b"blob", "txt").unwrap();
+    let writer = issue_api_token(&mut conn, &cas, "writer", &["write".to_string()])
+        .unwrap()
+        .secret;
+    let admin = issue_api_token(&mut conn, &cas, "admin", &["admin".to_string()])
+        .unwrap()
+        .secret;
+    let backup = |server: &mut Server, secret: &str| {
+        let mut req = request("POST", "/v1/backup", Value::Null);
+        req.headers
+            .insert("authorization".into(), format!("Bearer {}", secret));
+        server.handle(&req)
+    };
+
+    assert_eq!(backup(&mut server, &writer).status, 403);
+    assert!(!dir.path().join("backups").exists());
+
+    let response = backup(&mut server, &admin);
+    assert_eq!(response.status, 200, "{}", response.body);
+    let out = std::path::PathBuf::from(response.body["result"]["path"].as_str().unwrap());
+    assert!(out.starts_with(dir.path().join("backups")));
+    assert!(out.join("store.db").is_file());
+    assert!(out.join("backup-manifest.json").is_file());
+    let manifest = &response.body["result"]["manifest"];
+    assert_eq!(manifest["cas_blobs"].as_array().unwrap().len(), 1);
+
+    let wrong_method = server.handle(&request("GET", "/v1/backup", Value::Null));
+    assert_eq!(wrong_method.status, 405);
+    assert_eq!(header(&wrong_method, "Allow"), Some("POST"));
+}
+
+#[test]
+fn test_serve_connection_over_tcp() {
+    let dir = TempDir::new().unwrap();
+    let mut server = server(&dir, AuthMode::None);
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+    let body = r#"{"verb": "state-version"}"#;
+    let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    write!(
+        client,
+        "POST /v1/query?pretty=1 HTTP/1.1\r\nHost: localhost\r\nX-Request-Id: tcp-1\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+        body.len(),
+        body
+    )
+    .unwrap();
+
+    let (mut stream, _) = listener.accept().unwrap();
+    server.serve_connection(&mut stream).unwrap();
+    drop(stream);
+
+    let mut raw = String::new();
+    client.read_to_string(&mut raw).unwrap();
+    let (head, body) = raw.split_once("\r\n\r\n").unwrap();
+    assert!(head.starts_with("HTTP/1.1 200 OK"));
+    assert!(head.contains("X-Request-Id: tcp-1"));
+    let value: Value = serde_json::from_str(body).unwrap();
+    assert_eq!(value["result"]["state_version"], json!(0));
+}
+
+#[test]
+fn test_serve_stalled_client_times_out() {
+    let dir = TempDir::new().unwrap();
+    let mut server = server(&dir, AuthMode::None);
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+    // Connect and send only part of the request line.
+    let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    write!(client, "POST /v1/qu").unwrap();
+
+    let (stream, _) = listener.accept().unwrap();
+    server.serve_tcp(stream).unwrap();
+
+    let mut raw = String::new();
+    client.read_to_string(&mut raw).unwrap();
+    assert!(raw.starts_with("HTTP/1.1 408 Request Timeout"), "{}", raw);
+    assert!(raw.contains("ERR_TIMEOUT"));
+}
+
+#[test]
+fn test_serve_replaces_unsafe_request_ids() {
+    let dir = TempDir::new().unwrap();
+    let mut server = server(&dir, AuthMode::None);
+
+    let mut req = request("GET", "/v1/health", Value::Null);
+    req.headers
+        .insert("x-request-id".into(), "ok-1\rSet-Cookie: a=b".into());
+    req.headers.insert("x-trace-id".into(), "x".repeat(129));
+    let response = server.handle(&req);
+    let request_id = header(&response, "X-Request-Id").unwrap();
+    let trace_id = header(&response, "X-Trace-Id").unwrap();
+    assert!(!request_id.contains('\r') && !request_id.contains(' '));
+    assert_ne!(trace_id, "x".repeat(129));
+
+    let mut req = request("GET", "/v1/health", Value::Null);
+    req.headers
+        .insert("x-request-id".into(), "req_1.a:b-c".into());
+    let response = server.handle(&req);
+    assert_eq!(header(&response, "X-Request-Id"), Some("req_1.a:b-c"));
+}
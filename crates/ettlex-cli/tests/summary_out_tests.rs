@@ -0,0 +1,198 @@
+//! CLI tests for `--summary-out` JSON result files.
+
+#![allow(clippy::unwrap_used)]
+
+use ettlex_cli::commands::snapshot::{
+    execute, CommitArgs, DiffArgs, ExportAllArgs, SnapshotArgs, SnapshotCommand,
+};
+use ettlex_core::diff::model::DiffSeverity;
+use ettlex_core::ops::Store;
+use ettlex_core::snapshot::manifest::generate_manifest;
+use ettlex_store::cas::FsStore;
+use ettlex_store::snapshot::persist::{commit_snapshot, SnapshotOptions};
+use ettlex_store::snapshot::SnapshotIdScheme;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+struct Env {
+    dir: TempDir,
+    db: String,
+    cas: String,
+}
+
+impl Env {
+    fn new() -> Self {
+        let dir = TempDir::new().unwrap();
+        let db = dir.path().join("store.db").display().to_string();
+        let cas = dir.path().join("cas").display().to_string();
+        let mut conn = rusqlite::Connection::open(&db).unwrap();
+        ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+        Self { dir, db, cas }
+    }
+
+    fn summary_path(&self) -> PathBuf {
+        self.dir.path().join("summary.json")
+    }
+
+    /// Commit a snapshot of `ept` directly through the store; returns its ID.
+    fn commit(&self, ept: &[&str]) -> String {
+        let manifest = generate_manifest(
+            ept.iter().map(|s| s.to_string()).collect(),
+            "policy/default@0".into(),
+            "profile/default@0".into(),
+            "ettle:root".into(),
+            "0001".into(),
+            None,
+            &Store::new(),
+        )
+        .unwrap();
+        let mut conn = rusqlite::Connection::open(&self.db).unwrap();
+        let cas = FsStore::new(&self.cas);
+        commit_snapshot(&mut conn, &cas, manifest, SnapshotOptions::default())
+            .unwrap()
+            .snapshot_id
+    }
+
+    fn diff_args(&self, a: String, b: String, fail_on: Option<DiffSeverity>) -> DiffArgs {
+        DiffArgs {
+            a,
+            b,
+            rules: None,
+            ignore: None,
+            fail_on,
+            verbosity: Default::default(),
+            catalog: None,
+            json: true,
+            rendered: false,
+            content: false,
+            output: Some(self.dir.path().join("diff.json")),
+            summary_out: Some(self.summary_path()),
+            db: self.db.clone(),
+            cas: self.cas.clone(),
+        }
+    }
+}
+
+fn read_summary(path: &Path) -> Value {
+    serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap()
+}
+
+#[test]
+fn test_summary_out_diff_success() {
+    let env = Env::new();
+    let a = env.commit(&["ep:root:0"]);
+    let b = env.commit(&["ep:root:0", "ep:root:1"]);
+
+    execute(SnapshotArgs {
+        command: SnapshotCommand::Diff(env.diff_args(a.clone(), b.clone(), None)),
+    })
+    .unwrap();
+
+    let summary = read_summary(&env.summary_path());
+    assert_eq!(summary["command"], "snapshot_diff");
+    assert_eq!(summary["ok"], true);
+    assert_eq!(summary["error"], Value::Null);
+    assert_eq!(summary["ids"]["a"], a.as_str());
+    assert_eq!(summary["ids"]["b"], b.as_str());
+    assert_eq!(summary["counts"]["added_eps"], 1);
+    assert_eq!(summary["counts"]["removed_eps"], 0);
+    assert!(summary["digests"]["a_manifest_digest"].is_string());
+    assert!(summary["outcome"]["severity"].is_string());
+}
+
+#[test]
+fn test_diff_rendered_appends_line_diff() {
+    let env = Env::new();
+    let a = env.commit(&["ep:root:0"]);
+    let b = env.commit(&["ep:root:0", "ep:root:1"]);
+
+    let mut args = env.diff_args(a, b, None);
+    args.json = false;
+    args.rendered = true;
+    let output = args.output.clone().unwrap();
+    execute(SnapshotArgs {
+        command: SnapshotCommand::Diff(args),
+    })
+    .unwrap();
+
+    let text = std::fs::read_to_string(output).unwrap();
+    assert!(text.contains("### Rendered Diff"));
+    assert!(text.lines().any(|l| l.starts_with("+1. ep:root:1 ")));
+    let summary = read_summary(&env.summary_path());
+    assert!(summary["counts"]["rendered_diff_lines"].as_u64().unwrap() > 0);
+}
+
+#[test]
+fn test_summary_out_export_all_counts_resumed_run() {
+    let env = Env::new();
+    env.commit(&["ep:root:0"]);
+    let export = |resume: bool| {
+        execute(SnapshotArgs {
+            command: SnapshotCommand::ExportAll(ExportAllArgs {
+                out: env.dir.path().join("archive"),
+                resume,
+                blobs: false,
+                summary_out: Some(env.summary_path()),
+                db: env.db.clone(),
+                cas: env.cas.clone(),
+            }),
+        })
+    };
+    export(false).unwrap();
+    env.commit(&["ep:root:0", "ep:root:1"]);
+    export(true).unwrap();
+
+    let summary = read_summary(&env.summary_path());
+    assert_eq!(summary["command"], "snapshot_export_all");
+    assert_eq!(summary["counts"]["exported"], 1);
+    assert_eq!(summary["counts"]["skipped"], 1);
+    assert_eq!(summary["counts"]["total"], 2);
+}
+
+#[test]
+fn test_summary_out_diff_fail_on_records_error_code() {
+    let env = Env::new();
+    let a = env.commit(&["ep:root:0"]);
+    let b = env.commit(&["ep:root:1"]);
+
+    let result = execute(SnapshotArgs {
+        command: SnapshotCommand::Diff(env.diff_args(a, b, Some(DiffSeverity::Informational))),
+    });
+    assert!(result.is_err());
+
+    let summary = read_summary(&env.summary_path());
+    assert_eq!(summary["ok"], false);
+    assert_eq!(summary["error"]["code"], "ERR_POLICY_DENIED");
+    // Results gathered before the failure are still reported
+    assert_eq!(summary["counts"]["added_eps"], 1);
+}
+
+#[test]
+fn test_summary_out_commit_failure_is_written() {
+    let env = Env::new();
+    let result = execute(SnapshotArgs {
+        command: SnapshotCommand::Commit(CommitArgs {
+            leaf: "ettle:leaf".to_string(),
+            policy: None,
+            profile: None,
+            dry_run: true,
+            queue: false,
+            id_scheme: SnapshotIdScheme::default(),
+            dedup_mode: None,
+            message: None,
+            annotations: Vec::new(),
+            summary_out: Some(env.summary_path()),
+            db: env.db.clone(),
+            cas: env.cas.clone(),
+        }),
+    });
+    assert!(result.is_err());
+
+    let summary = read_summary(&env.summary_path());
+    assert_eq!(summary["command"], "snapshot_commit");
+    assert_eq!(summary["ok"], false);
+    assert_eq!(summary["ids"]["leaf"], "ettle:leaf");
+    assert_eq!(summary["error"]["code"], "ERR_NOT_IMPLEMENTED");
+    assert_eq!(summary["warnings"].as_array().unwrap().len(), 1);
+}
@@ -19,6 +19,13 @@ pub const FIELD_EP_ORDINAL: &str = "ep_ordinal";
 // Collection sizes
 pub const FIELD_RT_LEN: &str = "rt_len";
 pub const FIELD_EPT_LEN: &str = "ept_len";
+pub const FIELD_ROW_COUNT: &str = "row_count";
+
+// Slow-op fields
+pub const FIELD_THRESHOLD_MS: &str = "threshold_ms";
+
+// Outcome fields
+pub const FIELD_OUTCOME: &str = "outcome";
 
 // Error fields
 pub const FIELD_ERR_KIND: &str = "err.kind";
@@ -28,6 +35,8 @@ pub const FIELD_ERR_CODE: &str = "err.code";
 pub const EVENT_START: &str = "start";
 pub const EVENT_END: &str = "end";
 pub const EVENT_END_ERROR: &str = "end_error";
+pub const EVENT_SLOW: &str = "slow";
+pub const EVENT_OUTCOME: &str = "outcome";
 
 #[cfg(test)]
 mod tests {
@@ -1,5 +1,10 @@
 //! Approval routing interface for commit policy ambiguity resolution.
 
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
 use crate::errors::{ExError, ExErrorKind};
 
 /// Route an ambiguous commit for human/workflow approval, returning an approval token.
@@ -30,6 +35,102 @@ impl ApprovalRouter for NoopApprovalRouter {
     }
 }
 
+/// The approval request payload stored in CAS under `request_digest`.
+///
+/// On disk the candidate set is a JSON-encoded string (`candidate_set_json`)
+/// for compatibility with the `approval_requests` row; this type exposes it as
+/// a plain list. Fields written by newer routers that this schema does not
+/// model are preserved in `extra`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApprovalRequestPayload {
+    /// Approval token (UUIDv7).
+    pub approval_token: String,
+    /// Why approval was requested (e.g. `AmbiguousSelection`); identifies the
+    /// kind of decision the approver is being asked to make.
+    pub reason_code: String,
+    /// Candidate IDs the approver chooses between, in routing order.
+    pub candidates: Vec<String>,
+    /// Deterministic digest over `reason_code` + sorted candidates.
+    pub semantic_request_digest: String,
+    /// Creation timestamp, milliseconds since epoch.
+    pub created_at: i64,
+    /// Unmodelled payload fields, keyed by name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, JsonValue>,
+}
+
+impl ApprovalRequestPayload {
+    /// Parse the stored CAS payload layout.
+    ///
+    /// # Errors
+    /// * `ApprovalStorageCorrupt` - a required field is missing or mistyped,
+    ///   or `candidate_set_json` is not a JSON array of strings.
+    pub fn from_stored_json(value: &JsonValue) -> Result<Self, ExError> {
+        let obj = value
+            .as_object()
+            .ok_or_else(|| corrupt("approval payload is not a JSON object"))?;
+        let str_field = |name: &str| -> Result<String, ExError> {
+            obj.get(name)
+                .and_then(JsonValue::as_str)
+                .map(str::to_string)
+                .ok_or_else(|| corrupt(&format!("approval payload field '{}' missing", name)))
+        };
+        let candidates: Vec<String> = serde_json::from_str(&str_field("candidate_set_json")?)
+            .map_err(|e| corrupt(&format!("candidate_set_json is not a string array: {}", e)))?;
+        let created_at = obj
+            .get("created_at")
+            .and_then(JsonValue::as_i64)
+            .ok_or_else(|| corrupt("approval payload field 'created_at' missing"))?;
+        let extra = obj
+            .iter()
+            .filter(|(k, _)| !STORED_FIELDS.contains(&k.as_str()))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        Ok(Self {
+            approval_token: str_field("approval_token")?,
+            reason_code: str_field("reason_code")?,
+            candidates,
+            semantic_request_digest: str_field("semantic_request_digest")?,
+            created_at,
+            extra,
+        })
+    }
+
+    /// Render the stored CAS payload layout (inverse of [`Self::from_stored_json`]).
+    pub fn to_stored_json(&self) -> JsonValue {
+        let mut obj = serde_json::Map::new();
+        for (k, v) in &self.extra {
+            obj.insert(k.clone(), v.clone());
+        }
+        obj.insert("approval_token".into(), self.approval_token.clone().into());
+        obj.insert("reason_code".into(), self.reason_code.clone().into());
+        obj.insert(
+            "candidate_set_json".into(),
+            JsonValue::String(JsonValue::from(self.candidates.clone()).to_string()),
+        );
+        obj.insert(
+            "semantic_request_digest".into(),
+            self.semantic_request_digest.clone().into(),
+        );
+        obj.insert("created_at".into(), self.created_at.into());
+        JsonValue::Object(obj)
+    }
+}
+
+const STORED_FIELDS: &[&str] = &[
+    "approval_token",
+    "reason_code",
+    "candidate_set_json",
+    "semantic_request_digest",
+    "created_at",
+];
+
+fn corrupt(message: &str) -> ExError {
+    ExError::new(ExErrorKind::ApprovalStorageCorrupt)
+        .with_op("approval_payload_parse")
+        .with_message(message)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -45,4 +146,33 @@ mod tests {
             ExErrorKind::ApprovalRoutingUnavailable
         );
     }
+
+    #[test]
+    fn test_payload_round_trips_stored_layout() {
+        let stored = serde_json::json!({
+            "approval_token": "tok",
+            "reason_code": "AmbiguousSelection",
+            "candidate_set_json": "[\"b\",\"a\"]",
+            "semantic_request_digest": "d",
+            "created_at": 42,
+            "future_field": true,
+        });
+        let payload = ApprovalRequestPayload::from_stored_json(&stored).unwrap();
+        assert_eq!(payload.candidates, vec!["b".to_string(), "a".to_string()]);
+        assert_eq!(payload.extra.len(), 1);
+        assert_eq!(payload.to_stored_json(), stored);
+    }
+
+    #[test]
+    fn test_payload_rejects_malformed_candidates() {
+        let stored = serde_json::json!({
+            "approval_token": "tok",
+            "reason_code": "r",
+            "candidate_set_json": "not json",
+            "semantic_request_digest": "d",
+            "created_at": 1,
+        });
+        let err = ApprovalRequestPayload::from_stored_json(&stored).unwrap_err();
+        assert_eq!(err.kind(), ExErrorKind::ApprovalStorageCorrupt);
+    }
 }
@@ -26,6 +26,25 @@ impl AmbiguityPolicy {
             _ => AmbiguityPolicy::FailFast,
         }
     }
+
+    /// Parse a known policy name; `None` for anything unrecognised.
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s {
+            "fail_fast" => Some(AmbiguityPolicy::FailFast),
+            "choose_deterministic" => Some(AmbiguityPolicy::ChooseDeterministic),
+            "route_for_approval" => Some(AmbiguityPolicy::RouteForApproval),
+            _ => None,
+        }
+    }
+
+    /// Canonical profile name of the policy.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AmbiguityPolicy::FailFast => "fail_fast",
+            AmbiguityPolicy::ChooseDeterministic => "choose_deterministic",
+            AmbiguityPolicy::RouteForApproval => "route_for_approval",
+        }
+    }
 }
 
 /// A constraint candidate for resolution.
@@ -15,6 +15,16 @@ use serde_json::Value as JsonValue;
 /// EP-related commands (EpCreate, EpUpdate, EpDelete, RefineLinkChild,
 /// RefineUnlinkChild, ConstraintAttachToEp, ConstraintDetachFromEp) have
 /// been retired in Slice 03 along with the EP construct.
+///
+/// This enum drives the in-memory `Store` only. Persistent Ettle writes go
+/// through the engine's SQLite-backed command enum
+/// (`ettlex_engine::commands::command::Command`): `EttleCreate`,
+/// `EttleUpdate` and `EttleTombstone`, with refinement link/unlink expressed
+/// as `RelationCreate` / `RelationTombstone` of type `refinement`. Each runs
+/// in one transaction (a savepoint inside an open one): the row write, its
+/// provenance event and its `command_log` row commit or roll back together,
+/// after the OCC check when an expected state version is given. Content
+/// spilled to CAS is written outside it and is not undone.
 #[derive(Debug, Clone, PartialEq)]
 pub enum Command {
     /// Create a new Ettle
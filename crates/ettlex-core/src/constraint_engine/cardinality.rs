@@ -0,0 +1,141 @@
+//! `cardinality` constraint family — bounds on an Ettle's refinement children.
+//!
+//! Payload:
+//!
+//! ```json
+//! { "ettle_id": "ettle:payments", "min": 1, "max": 4 }
+//! ```
+//!
+//! At least one of `min` / `max` is required. A constraint holds when the
+//! number of the Ettle's children that are in the EPT lies within the bounds
+//! (inclusive).
+//!
+//! Each constraint contributes one outcome to the family's opaque section:
+//!
+//! ```json
+//! { "outcomes": [{ "constraint_id": "c1", "ettle_id": "ettle:payments",
+//!                  "min": 1, "max": 4, "child_count": 2, "status": "SATISFIED" }] }
+//! ```
+
+use super::{ConstraintEvalCtx, ConstraintFamilyEvaluator, ConstraintFamilyStatus};
+use crate::errors::{ExError, ExErrorKind};
+use crate::model::Constraint;
+use serde_json::json;
+use std::collections::BTreeSet;
+
+/// Family name for cardinality constraints.
+pub const FAMILY: &str = "cardinality";
+
+/// Parsed `cardinality` payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CardinalityBounds {
+    /// Ettle whose children are counted
+    pub ettle_id: String,
+    /// Inclusive lower bound
+    pub min: Option<u64>,
+    /// Inclusive upper bound
+    pub max: Option<u64>,
+}
+
+impl CardinalityBounds {
+    /// Parse a constraint's payload.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidInput` if `ettle_id` is missing or blank, a bound is
+    /// not a non-negative integer, neither bound is given, or `min > max`.
+    #[allow(clippy::result_large_err)]
+    pub fn parse(constraint: &Constraint) -> Result<Self, ExError> {
+        let invalid = |msg: &str| {
+            ExError::new(ExErrorKind::InvalidInput)
+                .with_op("constraint_evaluate")
+                .with_entity_id(&constraint.constraint_id)
+                .with_message(format!("invalid cardinality payload: {}", msg))
+        };
+        let payload = &constraint.payload_json;
+
+        let ettle_id = payload
+            .get("ettle_id")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.trim().is_empty())
+            .ok_or_else(|| invalid("ettle_id must be a non-empty string"))?
+            .to_string();
+        let bound = |key: &str| match payload.get(key) {
+            None | Some(serde_json::Value::Null) => Ok(None),
+            Some(v) => v
+                .as_u64()
+                .map(Some)
+                .ok_or_else(|| invalid(&format!("{} must be a non-negative integer", key))),
+        };
+        let min = bound("min")?;
+        let max = bound("max")?;
+
+        match (min, max) {
+            (None, None) => Err(invalid("at least one of min or max is required")),
+            (Some(lo), Some(hi)) if lo > hi => Err(invalid("min must not exceed max")),
+            _ => Ok(Self { ettle_id, min, max }),
+        }
+    }
+
+    /// Whether `count` lies within the bounds.
+    pub fn admits(&self, count: u64) -> bool {
+        self.min.map_or(true, |lo| count >= lo) && self.max.map_or(true, |hi| count <= hi)
+    }
+}
+
+/// Built-in evaluator for the `cardinality` family.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CardinalityEvaluator;
+
+impl ConstraintFamilyEvaluator for CardinalityEvaluator {
+    fn family(&self) -> &str {
+        FAMILY
+    }
+
+    fn target_ettle_id(&self, constraint: &Constraint) -> Result<Option<String>, ExError> {
+        CardinalityBounds::parse(constraint).map(|bounds| Some(bounds.ettle_id))
+    }
+
+    /// Returns `Uncomputed` with no opaque section when `ctx.children` is `None`.
+    fn evaluate(
+        &self,
+        constraints: &[&Constraint],
+        ctx: &ConstraintEvalCtx,
+    ) -> Result<(ConstraintFamilyStatus, Option<serde_json::Value>), ExError> {
+        let Some(children) = &ctx.children else {
+            return Ok((ConstraintFamilyStatus::Uncomputed, None));
+        };
+        let in_ept: BTreeSet<&str> = ctx.ept_ep_ids.iter().map(String::as_str).collect();
+
+        let mut status = ConstraintFamilyStatus::Satisfied;
+        let mut outcomes = Vec::with_capacity(constraints.len());
+        for constraint in constraints {
+            let bounds = CardinalityBounds::parse(constraint)?;
+            let child_count = children
+                .get(&bounds.ettle_id)
+                .map(|ids| {
+                    ids.iter()
+                        .filter(|id| in_ept.contains(id.as_str()))
+                        .collect::<BTreeSet<_>>()
+                        .len()
+                })
+                .unwrap_or(0) as u64;
+            let outcome = if bounds.admits(child_count) {
+                ConstraintFamilyStatus::Satisfied
+            } else {
+                status = ConstraintFamilyStatus::Violated;
+                ConstraintFamilyStatus::Violated
+            };
+            outcomes.push(json!({
+                "constraint_id": constraint.constraint_id,
+                "ettle_id": bounds.ettle_id,
+                "min": bounds.min,
+                "max": bounds.max,
+                "child_count": child_count,
+                "status": outcome,
+            }));
+        }
+
+        Ok((status, Some(json!({ "outcomes": outcomes }))))
+    }
+}
@@ -0,0 +1,172 @@
+//! `doc_coverage` constraint family — minimum documentation across the EPT.
+//!
+//! Payload:
+//!
+//! ```json
+//! { "ettle_id": "ettle:root", "min_why": 20, "min_what": 20, "min_how": 1 }
+//! ```
+//!
+//! `ettle_id` anchors the constraint: it is declared when that Ettle is in
+//! the EPT, and then checks every Ettle of the EPT (all EPT entries are
+//! normative in v0). Each `min_*` is a minimum length in characters after
+//! trimming and defaults to 1, i.e. non-empty; 0 disables the check for that
+//! field.
+//!
+//! Each constraint contributes one outcome listing the failing Ettles:
+//!
+//! ```json
+//! { "outcomes": [{ "constraint_id": "c1", "ettle_id": "ettle:root",
+//!                  "min_why": 20, "min_what": 20, "min_how": 1,
+//!                  "checked": 3, "status": "VIOLATED",
+//!                  "failures": [{ "ettle_id": "ettle:a",
+//!                                 "fields": [{ "field": "why", "length": 0, "min": 20 }] }] }] }
+//! ```
+
+use super::{ConstraintEvalCtx, ConstraintFamilyEvaluator, ConstraintFamilyStatus};
+use crate::errors::{ExError, ExErrorKind};
+use crate::model::Constraint;
+use serde_json::json;
+
+/// Family name for documentation coverage constraints.
+pub const FAMILY: &str = "doc_coverage";
+
+/// The documentation text of one Ettle.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EttleContent {
+    pub why: String,
+    pub what: String,
+    pub how: String,
+}
+
+impl EttleContent {
+    fn fields(&self) -> [(&'static str, &str); 3] {
+        [("why", &self.why), ("what", &self.what), ("how", &self.how)]
+    }
+}
+
+/// Parsed `doc_coverage` payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocCoverageRule {
+    /// Ettle whose presence in the EPT declares the constraint
+    pub ettle_id: String,
+    /// Minimum trimmed length of `why`
+    pub min_why: u64,
+    /// Minimum trimmed length of `what`
+    pub min_what: u64,
+    /// Minimum trimmed length of `how`
+    pub min_how: u64,
+}
+
+impl DocCoverageRule {
+    /// Parse a constraint's payload.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidInput` if `ettle_id` is missing or blank, or a minimum
+    /// is not a non-negative integer.
+    #[allow(clippy::result_large_err)]
+    pub fn parse(constraint: &Constraint) -> Result<Self, ExError> {
+        let invalid = |msg: &str| {
+            ExError::new(ExErrorKind::InvalidInput)
+                .with_op("constraint_evaluate")
+                .with_entity_id(&constraint.constraint_id)
+                .with_message(format!("invalid doc_coverage payload: {}", msg))
+        };
+        let payload = &constraint.payload_json;
+
+        let ettle_id = payload
+            .get("ettle_id")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.trim().is_empty())
+            .ok_or_else(|| invalid("ettle_id must be a non-empty string"))?
+            .to_string();
+        let min = |key: &str| match payload.get(key) {
+            None | Some(serde_json::Value::Null) => Ok(1),
+            Some(v) => v
+                .as_u64()
+                .ok_or_else(|| invalid(&format!("{} must be a non-negative integer", key))),
+        };
+
+        Ok(Self {
+            ettle_id,
+            min_why: min("min_why")?,
+            min_what: min("min_what")?,
+            min_how: min("min_how")?,
+        })
+    }
+
+    fn min_for(&self, field: &str) -> u64 {
+        match field {
+            "why" => self.min_why,
+            "what" => self.min_what,
+            _ => self.min_how,
+        }
+    }
+}
+
+/// Built-in evaluator for the `doc_coverage` family.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DocCoverageEvaluator;
+
+impl ConstraintFamilyEvaluator for DocCoverageEvaluator {
+    fn family(&self) -> &str {
+        FAMILY
+    }
+
+    fn target_ettle_id(&self, constraint: &Constraint) -> Result<Option<String>, ExError> {
+        DocCoverageRule::parse(constraint).map(|rule| Some(rule.ettle_id))
+    }
+
+    /// Returns `Uncomputed` with no opaque section when `ctx.content` is `None`.
+    fn evaluate(
+        &self,
+        constraints: &[&Constraint],
+        ctx: &ConstraintEvalCtx,
+    ) -> Result<(ConstraintFamilyStatus, Option<serde_json::Value>), ExError> {
+        let Some(content) = &ctx.content else {
+            return Ok((ConstraintFamilyStatus::Uncomputed, None));
+        };
+        let empty = EttleContent::default();
+
+        let mut status = ConstraintFamilyStatus::Satisfied;
+        let mut outcomes = Vec::with_capacity(constraints.len());
+        for constraint in constraints {
+            let rule = DocCoverageRule::parse(constraint)?;
+            let mut failures = Vec::new();
+            for ettle_id in &ctx.ept_ep_ids {
+                let doc = content.get(ettle_id).unwrap_or(&empty);
+                let fields: Vec<_> = doc
+                    .fields()
+                    .into_iter()
+                    .filter_map(|(field, text)| {
+                        let length = text.trim().chars().count() as u64;
+                        let min = rule.min_for(field);
+                        (length < min)
+                            .then(|| json!({"field": field, "length": length, "min": min}))
+                    })
+                    .collect();
+                if !fields.is_empty() {
+                    failures.push(json!({"ettle_id": ettle_id, "fields": fields}));
+                }
+            }
+            let outcome = if failures.is_empty() {
+                ConstraintFamilyStatus::Satisfied
+            } else {
+                status = ConstraintFamilyStatus::Violated;
+                ConstraintFamilyStatus::Violated
+            };
+            outcomes.push(json!({
+                "constraint_id": constraint.constraint_id,
+                "ettle_id": rule.ettle_id,
+                "min_why": rule.min_why,
+                "min_what": rule.min_what,
+                "min_how": rule.min_how,
+                "checked": ctx.ept_ep_ids.len(),
+                "status": outcome,
+                "failures": failures,
+            }));
+        }
+
+        Ok((status, Some(json!({ "outcomes": outcomes }))))
+    }
+}
@@ -2,38 +2,63 @@
 //!
 //! This module defines the stable evaluation interface for constraints in EttleX Phase 1.
 //! It provides the `evaluate()` function which computes the constraint state for a given EPT,
-//! producing `declared_refs` (deduplicated, ordered) and per-family `FamilyEvaluation` records
-//! with `ConstraintFamilyStatus::Uncomputed` for all families in Phase 1.
+//! producing `declared_refs` (deduplicated, ordered) and per-family `FamilyEvaluation` records.
+//!
+//! ## Families
+//!
+//! Since EP constraint attachment was retired (Slice 03), a constraint is declared when
+//! its family has an evaluator in the [`ConstraintFamilyRegistry`] passed to `evaluate()`
+//! and the Ettle that evaluator reports as its target is in the EPT. Built-in families
+//! ([`ConstraintFamilyRegistry::builtin`]):
+//!
+//! - `cardinality` ([`cardinality`]) — bounds on an Ettle's refinement child count;
+//!   reports `Satisfied` or `Violated`
+//! - `doc_coverage` ([`doc_coverage`]) — minimum WHY/WHAT/HOW lengths for every Ettle
+//!   of the EPT; reports `Violated` with the failing Ettles and fields
+//!
+//! Other crates add families by implementing [`ConstraintFamilyEvaluator`].
 //!
 //! ## Ordering rules
 //!
-//! `declared_refs` are ordered by `(ordinal, constraint_id)` — None ordinals sort last
-//! (treated as `i32::MAX`). This is deterministic within a single EP. Across EPs in the EPT,
-//! the first-seen ordinal for a given constraint_id is used (earlier EPs win).
+//! `declared_refs` are ordered by the EPT position of their target Ettle, then by
+//! `constraint_id`.
 //!
 //! ## UNCOMPUTED semantics
 //!
-//! In Phase 1, no constraint families have active evaluation logic. All families report
-//! `status: Uncomputed`, meaning the manifest records which constraints are declared but
-//! does not validate them against the EPT. This is intentional and documented.
+//! A family reports `status: Uncomputed` when its evaluator cannot run, e.g. because the
+//! caller did not supply the structure it needs (e.g. `cardinality` without `ctx.children`,
+//! `doc_coverage` without `ctx.content`). The
+//! manifest then records which constraints are declared without validating them.
+
+pub mod cardinality;
+pub mod doc_coverage;
+pub mod registry;
+
+pub use doc_coverage::EttleContent;
+pub use registry::{ConstraintFamilyEvaluator, ConstraintFamilyRegistry};
 
 use crate::errors::ExError;
+use crate::model::Constraint;
 use crate::ops::Store;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
 /// Context passed to `evaluate()`.
 ///
-/// EP-era fields (`leaf_ep_id`, `ept_ep_ids`) are retired in Slice 03.
-/// The struct is retained for API compatibility; `evaluate()` now returns an
-/// empty evaluation unconditionally.
+/// `leaf_ep_id` is retired in Slice 03; `ept_ep_ids` now holds the Ettle IDs
+/// of the EPT in order.
 pub struct ConstraintEvalCtx {
     /// Retired — EP construct removed in Slice 03.
     #[allow(dead_code)]
     pub leaf_ep_id: String,
-    /// Retired — EP construct removed in Slice 03.
-    #[allow(dead_code)]
+    /// Ordered Ettle IDs of the EPT
     pub ept_ep_ids: Vec<String>,
+    /// Refinement children of each Ettle, or `None` when the caller has no
+    /// relation data. Ettles missing from the map have no children.
+    pub children: Option<BTreeMap<String, Vec<String>>>,
+    /// WHY/WHAT/HOW of each Ettle, or `None` when the caller has no content.
+    /// Ettles missing from the map have empty content.
+    pub content: Option<BTreeMap<String, EttleContent>>,
     /// Policy reference string (e.g. "policy/default@0")
     pub policy_ref: String,
     /// Profile reference string (e.g. "profile/default@0")
@@ -52,13 +77,17 @@ pub struct DeclaredConstraintRef {
 }
 
 /// Evaluation status for a constraint family.
-///
-/// In Phase 1, all families report `Uncomputed`.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum ConstraintFamilyStatus {
-    /// No evaluation has been performed (Phase 1 default for all families)
+    /// No evaluation has been performed (no evaluator, or missing input)
     #[serde(rename = "UNCOMPUTED")]
     Uncomputed,
+    /// Every declared constraint in the family holds
+    #[serde(rename = "SATISFIED")]
+    Satisfied,
+    /// At least one declared constraint in the family does not hold
+    #[serde(rename = "VIOLATED")]
+    Violated,
 }
 
 /// Per-family evaluation record.
@@ -68,7 +97,8 @@ pub struct FamilyEvaluation {
     pub status: ConstraintFamilyStatus,
     /// Digest of the family's declared constraint set
     pub digest: String,
-    /// Opaque family-specific section (reserved for future use)
+    /// Family-specific details; evaluating families put per-constraint
+    /// results under `"outcomes"`
     pub opaque_section: Option<serde_json::Value>,
 }
 
@@ -85,82 +115,95 @@ pub struct ConstraintEvaluation {
 
 /// Evaluate constraints for an EPT.
 ///
-/// Collects all constraint references attached to EPs in `ctx.ept_ep_ids`, deduplicates
-/// by `constraint_id` (first occurrence wins), sorts by `(ordinal, constraint_id)`,
-/// groups by family, and computes deterministic digests.
-///
-/// # Phase 1 behaviour
+/// Declares every live constraint whose family is in `registry` and whose target Ettle
+/// is in `ctx.ept_ep_ids`, sorts them by `(EPT position, constraint_id)`, groups them by
+/// family, evaluates each family with its registered evaluator, and computes
+/// deterministic digests.
 ///
-/// - All families report `ConstraintFamilyStatus::Uncomputed`
-/// - EPs not present in the store are silently skipped
-/// - Tombstoned constraints attached to EPs are excluded from `declared_refs`
+/// Tombstoned constraints are excluded from `declared_refs`.
 ///
 /// # Errors
 ///
-/// Returns `ExError` if JSON serialization fails during digest computation.
+/// Propagates evaluator errors, e.g. `InvalidInput` for a malformed payload.
 #[allow(clippy::result_large_err)]
-pub fn evaluate(_ctx: &ConstraintEvalCtx, store: &Store) -> Result<ConstraintEvaluation, ExError> {
-    use sha2::{Digest as _, Sha256};
+pub fn evaluate(
+    ctx: &ConstraintEvalCtx,
+    store: &Store,
+    registry: &ConstraintFamilyRegistry,
+) -> Result<ConstraintEvaluation, ExError> {
+    use crate::snapshot::manifest::{constraints_digest, family_digest};
 
-    // Slice 03: EP construct retired. EP constraint refs no longer exist.
-    // Return empty evaluation unconditionally.
-    let _ = store; // suppress unused warning
-    let declared_refs: Vec<DeclaredConstraintRef> = Vec::new();
+    let position: BTreeMap<&str, usize> = ctx
+        .ept_ep_ids
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(i, id)| (id.as_str(), i))
+        .collect();
+
+    // Collect (EPT position, constraint) for constraints targeting the EPT
+    let mut declared: Vec<(usize, &Constraint)> = Vec::new();
+    for constraint in store.list_constraints() {
+        let Some(evaluator) = registry.get(&constraint.family) else {
+            continue;
+        };
+        let Some(target) = evaluator.target_ettle_id(constraint)? else {
+            continue;
+        };
+        if let Some(&pos) = position.get(target.as_str()) {
+            declared.push((pos, constraint));
+        }
+    }
+    declared.sort_by(|a, b| (a.0, &a.1.constraint_id).cmp(&(b.0, &b.1.constraint_id)));
+
+    let declared_refs: Vec<DeclaredConstraintRef> = declared
+        .iter()
+        .map(|(_, c)| DeclaredConstraintRef {
+            constraint_id: c.constraint_id.clone(),
+            family: c.family.clone(),
+            payload_digest: c.payload_digest.clone(),
+        })
+        .collect();
 
     // Group by family for per-family evaluation
-    let mut family_groups: BTreeMap<String, Vec<&DeclaredConstraintRef>> = BTreeMap::new();
-    for r in &declared_refs {
-        family_groups.entry(r.family.clone()).or_default().push(r);
+    let mut family_groups: BTreeMap<String, Vec<&Constraint>> = BTreeMap::new();
+    for (_, c) in &declared {
+        family_groups.entry(c.family.clone()).or_default().push(c);
     }
 
     // Build per-family evaluations
     let mut families: BTreeMap<String, FamilyEvaluation> = BTreeMap::new();
-    for (family_name, refs) in &family_groups {
-        // Compute family digest from sorted constraint IDs in this family
-        let ids: Vec<&str> = refs.iter().map(|r| r.constraint_id.as_str()).collect();
-        let canonical = serde_json::to_string(&ids).map_err(|e| {
-            ExError::new(crate::errors::ExErrorKind::Serialization)
-                .with_message(format!("Failed to serialize family ids: {}", e))
-        })?;
-        let mut hasher = Sha256::new();
-        hasher.update(canonical.as_bytes());
-        let digest = hex::encode(hasher.finalize());
+    for (family_name, constraints) in &family_groups {
+        let ids: Vec<&str> = constraints
+            .iter()
+            .map(|c| c.constraint_id.as_str())
+            .collect();
+        let digest = family_digest(&ids);
+        let (status, opaque_section) = match registry.get(family_name) {
+            Some(evaluator) => evaluator.evaluate(constraints, ctx)?,
+            None => (ConstraintFamilyStatus::Uncomputed, None),
+        };
 
         families.insert(
             family_name.clone(),
             FamilyEvaluation {
-                status: ConstraintFamilyStatus::Uncomputed,
+                status,
                 digest,
-                opaque_section: None,
+                opaque_section,
             },
         );
     }
 
     // Compute constraints_digest over (declared_ref ids, family names + digests)
-    let digest_input: Vec<serde_json::Value> = {
-        let ref_ids: Vec<&str> = declared_refs
-            .iter()
-            .map(|r| r.constraint_id.as_str())
-            .collect();
-        let family_digests: Vec<(&str, &str)> = families
-            .iter()
-            .map(|(k, v)| (k.as_str(), v.digest.as_str()))
-            .collect();
-        vec![
-            serde_json::to_value(&ref_ids).unwrap_or(serde_json::Value::Null),
-            serde_json::to_value(&family_digests).unwrap_or(serde_json::Value::Null),
-        ]
-    };
-
-    let canonical = serde_json::to_string(&digest_input).map_err(|e| {
-        ExError::new(crate::errors::ExErrorKind::Serialization).with_message(format!(
-            "Failed to serialize constraints digest input: {}",
-            e
-        ))
-    })?;
-    let mut hasher = Sha256::new();
-    hasher.update(canonical.as_bytes());
-    let constraints_digest = hex::encode(hasher.finalize());
+    let ref_ids: Vec<&str> = declared_refs
+        .iter()
+        .map(|r| r.constraint_id.as_str())
+        .collect();
+    let family_digests: Vec<(&str, &str)> = families
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.digest.as_str()))
+        .collect();
+    let constraints_digest = constraints_digest(&ref_ids, &family_digests);
 
     Ok(ConstraintEvaluation {
         declared_refs,
@@ -0,0 +1,111 @@
+//! Constraint family evaluator registry.
+//!
+//! [`evaluate`](super::evaluate) only declares and evaluates constraints whose
+//! family has a registered [`ConstraintFamilyEvaluator`]. Downstream crates
+//! implement the trait for their own families and register them alongside
+//! (or instead of) the built-ins; declaration order, grouping and digests stay
+//! in core.
+
+use super::{
+    cardinality::CardinalityEvaluator, doc_coverage::DocCoverageEvaluator, ConstraintEvalCtx,
+    ConstraintFamilyStatus,
+};
+use crate::errors::{ExError, ExErrorKind};
+use crate::model::Constraint;
+use std::collections::BTreeMap;
+
+/// Family-specific constraint logic.
+pub trait ConstraintFamilyEvaluator: Send + Sync {
+    /// Family name handled, matched against [`Constraint::family`].
+    fn family(&self) -> &str;
+
+    /// The Ettle a constraint applies to. The constraint is declared when
+    /// this Ettle is in the EPT; `None` means it is never declared.
+    ///
+    /// # Errors
+    ///
+    /// Return `InvalidInput` for a malformed payload.
+    #[allow(clippy::result_large_err)]
+    fn target_ettle_id(&self, constraint: &Constraint) -> Result<Option<String>, ExError>;
+
+    /// Evaluate the family's declared constraints, in `declared_refs` order,
+    /// returning the family status and its opaque section. Put per-constraint
+    /// results under `"outcomes"` to have them copied into the envelope.
+    ///
+    /// # Errors
+    ///
+    /// Errors abort the whole evaluation.
+    #[allow(clippy::result_large_err)]
+    fn evaluate(
+        &self,
+        constraints: &[&Constraint],
+        ctx: &ConstraintEvalCtx,
+    ) -> Result<(ConstraintFamilyStatus, Option<serde_json::Value>), ExError>;
+}
+
+/// Evaluators keyed by family name.
+#[derive(Default)]
+pub struct ConstraintFamilyRegistry {
+    evaluators: BTreeMap<String, Box<dyn ConstraintFamilyEvaluator>>,
+}
+
+impl ConstraintFamilyRegistry {
+    /// An empty registry: no family is declared or evaluated.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A registry holding the built-in evaluators (`cardinality`,
+    /// `doc_coverage`).
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        registry.evaluators.insert(
+            CardinalityEvaluator.family().to_string(),
+            Box::new(CardinalityEvaluator),
+        );
+        registry.evaluators.insert(
+            DocCoverageEvaluator.family().to_string(),
+            Box::new(DocCoverageEvaluator),
+        );
+        registry
+    }
+
+    /// Add an evaluator.
+    ///
+    /// # Errors
+    ///
+    /// Returns `AlreadyExists` if its family is already registered.
+    #[allow(clippy::result_large_err)]
+    pub fn register(
+        &mut self,
+        evaluator: Box<dyn ConstraintFamilyEvaluator>,
+    ) -> Result<(), ExError> {
+        let family = evaluator.family().to_string();
+        if self.evaluators.contains_key(&family) {
+            return Err(ExError::new(ExErrorKind::AlreadyExists)
+                .with_op("constraint_family_register")
+                .with_entity_id(&family)
+                .with_message(format!("constraint family already registered: {}", family)));
+        }
+        self.evaluators.insert(family, evaluator);
+        Ok(())
+    }
+
+    /// The evaluator for `family`, if registered.
+    pub fn get(&self, family: &str) -> Option<&dyn ConstraintFamilyEvaluator> {
+        self.evaluators.get(family).map(|e| e.as_ref())
+    }
+
+    /// Registered family names, sorted.
+    pub fn families(&self) -> Vec<&str> {
+        self.evaluators.keys().map(String::as_str).collect()
+    }
+}
+
+impl std::fmt::Debug for ConstraintFamilyRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConstraintFamilyRegistry")
+            .field("families", &self.families())
+            .finish()
+    }
+}
@@ -0,0 +1,150 @@
+//! EP content-level diffs.
+//!
+//! The manifest records only each EP's `ep_digest`, so the structured diff
+//! can say *which* EPs changed but not *what* changed. When asked (see
+//! [`DiffOptions`]), the content blobs behind both digests are loaded from
+//! an [`EpContentSource`] (CAS in practice) and each of the `why`, `what`
+//! and `how` fields is line-diffed with [`unified_line_diff`].
+//!
+//! A manifest generated with content in its input records each entry's
+//! `ep_digest` as the digest of its [`EpContent::to_blob`] bytes; the engine
+//! stores those blobs in CAS alongside the manifest. Core has no CAS access;
+//! the engine supplies a CAS-backed source. A digest with no blob behind it
+//! (e.g. entries generated without content, whose digests are derived from
+//! the EP ID) is reported as unavailable rather than failing the diff.
+
+#![allow(clippy::result_large_err)]
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::constraint_engine::EttleContent;
+use crate::diff::model::{EpContentDiff, SnapshotDiff};
+use crate::diff::rendered::unified_line_diff;
+use crate::errors::ExError;
+use crate::snapshot::parse::ParsedManifest;
+
+/// Options for [`compute_diff_with_options`](crate::diff::engine::compute_diff_with_options).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiffOptions {
+    /// Load changed EPs' content and add line diffs as `content_diffs`.
+    pub include_content: bool,
+}
+
+/// The text fields of an EP content blob.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EpContent {
+    #[serde(default)]
+    pub why: String,
+    #[serde(default)]
+    pub what: String,
+    #[serde(default)]
+    pub how: String,
+}
+
+impl EpContent {
+    /// The canonical blob stored in CAS: a JSON object of the three fields.
+    pub fn to_blob(&self) -> Vec<u8> {
+        serde_json::json!({"why": self.why, "what": self.what, "how": self.how})
+            .to_string()
+            .into_bytes()
+    }
+
+    /// SHA-256 (hex) of [`to_blob`](Self::to_blob), the key CAS stores it under.
+    pub fn digest(&self) -> String {
+        use sha2::{Digest, Sha256};
+        hex::encode(Sha256::digest(self.to_blob()))
+    }
+
+    fn fields(&self) -> [(&'static str, &str); 3] {
+        [("why", &self.why), ("what", &self.what), ("how", &self.how)]
+    }
+}
+
+impl From<&EttleContent> for EpContent {
+    fn from(content: &EttleContent) -> Self {
+        Self {
+            why: content.why.clone(),
+            what: content.what.clone(),
+            how: content.how.clone(),
+        }
+    }
+}
+
+/// Where EP content is looked up by `ep_digest`.
+pub trait EpContentSource {
+    /// The content stored under `ep_digest`, or `None` if there is none.
+    ///
+    /// # Errors
+    ///
+    /// Errors other than a missing blob abort the diff.
+    fn ep_content(&self, ep_digest: &str) -> Result<Option<EpContent>, ExError>;
+}
+
+/// A source with no content: every EP is reported as unavailable.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoEpContent;
+
+impl EpContentSource for NoEpContent {
+    fn ep_content(&self, _ep_digest: &str) -> Result<Option<EpContent>, ExError> {
+        Ok(None)
+    }
+}
+
+/// Fill `diff.content_diffs` with one entry per EP in
+/// `ep_content_changes.changed_eps`, in that order.
+///
+/// # Errors
+///
+/// Any error from `source`.
+pub fn add_content_diffs(
+    diff: &mut SnapshotDiff,
+    a: &ParsedManifest,
+    b: &ParsedManifest,
+    source: &dyn EpContentSource,
+) -> Result<(), ExError> {
+    let digests = |m: &ParsedManifest| -> BTreeMap<String, String> {
+        m.manifest
+            .ept
+            .iter()
+            .map(|e| (e.ep_id.clone(), e.ep_digest.clone()))
+            .collect()
+    };
+    let (a_digests, b_digests) = (digests(a), digests(b));
+
+    let mut content_diffs = Vec::new();
+    for ep_id in &diff.ep_content_changes.changed_eps {
+        let (Some(old_digest), Some(new_digest)) = (a_digests.get(ep_id), b_digests.get(ep_id))
+        else {
+            continue;
+        };
+        let mut entry = EpContentDiff {
+            ep_id: ep_id.clone(),
+            old_digest: old_digest.clone(),
+            new_digest: new_digest.clone(),
+            available: false,
+            field_diffs: BTreeMap::new(),
+        };
+        if let (Some(old), Some(new)) = (
+            source.ep_content(old_digest)?,
+            source.ep_content(new_digest)?,
+        ) {
+            entry.available = true;
+            for ((field, old_text), (_, new_text)) in old.fields().into_iter().zip(new.fields()) {
+                let text_diff = unified_line_diff(
+                    old_text,
+                    new_text,
+                    &format!("a/{}/{}", ep_id, field),
+                    &format!("b/{}/{}", ep_id, field),
+                );
+                if !text_diff.is_empty() {
+                    entry.field_diffs.insert(field.to_string(), text_diff);
+                }
+            }
+        }
+        content_diffs.push(entry);
+    }
+    diff.content_diffs = Some(content_diffs);
+    Ok(())
+}
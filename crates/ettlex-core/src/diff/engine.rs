@@ -5,135 +5,27 @@
 
 #![allow(clippy::result_large_err)]
 
+use crate::diff::content::{add_content_diffs, DiffOptions, EpContentSource};
 use crate::diff::model::{
     AbbSbbProjectionChanges, ConstraintChanges, CoverageChanges, DeclaredRefChanges,
     DiffClassification, DiffIdentity, DiffSeverity, DigestChange, EpContentChanges, EptChanges,
     ExceptionChanges, FamilyDiffEntry, InvariantViolationEntry, MetadataChanges,
-    MetadataFieldChange, SnapshotDiff, UnknownChanges,
+    MetadataFieldChange, NormativeFlip, SnapshotDiff, UnknownChanges,
 };
+use crate::diff::severity::{ChangeCategory, SeverityRules, SeverityTag};
 use crate::errors::{ExError, ExErrorKind};
 use crate::snapshot::manifest::SnapshotManifest;
+pub use crate::snapshot::parse::ParsedManifest;
 use serde_json::Value;
-use sha2::{Digest as _, Sha256};
 use std::collections::{BTreeMap, BTreeSet};
 
-/// Known top-level manifest fields used for unknown-field detection.
-const KNOWN_FIELDS: &[&str] = &[
-    "manifest_schema_version",
-    "created_at",
-    "policy_ref",
-    "profile_ref",
-    "ept",
-    "constraints",
-    "coverage",
-    "exceptions",
-    "root_ettle_id",
-    "ept_digest",
-    "manifest_digest",
-    "semantic_manifest_digest",
-    "store_schema_version",
-    "seed_digest",
-];
-
-/// Parse raw manifest bytes into a typed `SnapshotManifest` and the raw JSON `Value`.
-///
-/// The raw `Value` is returned alongside the typed manifest so that the caller can
-/// detect unknown top-level fields without a second parse.
-///
-/// # Errors
-///
-/// - `InvalidManifest` — bytes are not valid UTF-8, not valid JSON, or
-///   `manifest_schema_version` is not an integer
-/// - `MissingField` — `semantic_manifest_digest` or `constraints` key absent
-pub fn parse_manifest_bytes(bytes: &[u8]) -> Result<(SnapshotManifest, Value), ExError> {
-    // 1. UTF-8 decode
-    let text = std::str::from_utf8(bytes).map_err(|e| {
-        ExError::new(ExErrorKind::InvalidManifest)
-            .with_op("parse_manifest_bytes")
-            .with_message(format!("manifest is not valid UTF-8: {}", e))
-    })?;
-
-    // 2. JSON parse to generic Value
-    let raw: Value = serde_json::from_str(text).map_err(|e| {
-        ExError::new(ExErrorKind::InvalidManifest)
-            .with_op("parse_manifest_bytes")
-            .with_message(format!("manifest is not valid JSON: {}", e))
-    })?;
-
-    let obj = raw.as_object().ok_or_else(|| {
-        ExError::new(ExErrorKind::InvalidManifest)
-            .with_op("parse_manifest_bytes")
-            .with_message("manifest JSON root must be an object")
-    })?;
-
-    // 3. schema_version must be an integer (accept both manifest_schema_version key names)
-    let schema_version_key = "manifest_schema_version";
-    if let Some(sv) = obj.get(schema_version_key) {
-        if !sv.is_number() || sv.as_u64().is_none() {
-            return Err(ExError::new(ExErrorKind::InvalidManifest)
-                .with_op("parse_manifest_bytes")
-                .with_message(format!(
-                    "`{}` must be an unsigned integer, got: {}",
-                    schema_version_key, sv
-                )));
-        }
-    }
-    // If absent we still allow serde to decide (it will error in step 6 if required)
-
-    // 4. semantic_manifest_digest must be present
-    if !obj.contains_key("semantic_manifest_digest") {
-        return Err(ExError::new(ExErrorKind::MissingField)
-            .with_op("parse_manifest_bytes")
-            .with_message("required field `semantic_manifest_digest` is absent"));
-    }
-
-    // 5. constraints must be present
-    if !obj.contains_key("constraints") {
-        return Err(ExError::new(ExErrorKind::MissingField)
-            .with_op("parse_manifest_bytes")
-            .with_message("required field `constraints` is absent"));
-    }
-
-    // 6. Full typed deserialisation
-    let manifest: SnapshotManifest = serde_json::from_value(raw.clone()).map_err(|e| {
-        ExError::new(ExErrorKind::InvalidManifest)
-            .with_op("parse_manifest_bytes")
-            .with_message(format!("failed to deserialize manifest: {}", e))
-    })?;
-
-    Ok((manifest, raw))
-}
-
-/// Recompute the `constraints_digest` from the envelope data stored in the manifest.
-///
-/// Mirrors the algorithm in `constraint_engine::evaluate` so that the diff engine
-/// can detect envelope/digest disagreements without re-running the constraint engine.
-fn recompute_constraints_digest(manifest: &SnapshotManifest) -> String {
-    let envelope = &manifest.constraints;
-    let ref_ids: Vec<&str> = envelope.declared_refs.iter().map(|s| s.as_str()).collect();
-    let family_digests: Vec<(&str, &str)> = envelope
-        .families
-        .iter()
-        .map(|(k, v)| (k.as_str(), v.digest.as_str()))
-        .collect();
-
-    let digest_input = vec![
-        serde_json::to_value(&ref_ids).unwrap_or(Value::Null),
-        serde_json::to_value(&family_digests).unwrap_or(Value::Null),
-    ];
-    let canonical = serde_json::to_string(&digest_input).unwrap_or_default();
-    let mut hasher = Sha256::new();
-    hasher.update(canonical.as_bytes());
-    hex::encode(hasher.finalize())
-}
-
 /// Check constraints envelope integrity and return any violations (non-fatal).
 fn check_envelope_invariants(
     which: &str,
     manifest: &SnapshotManifest,
     violations: &mut Vec<InvariantViolationEntry>,
 ) {
-    let computed = recompute_constraints_digest(manifest);
+    let computed = manifest.constraints.recompute_digest();
     let recorded = &manifest.constraints.constraints_digest;
     if &computed != recorded {
         violations.push(InvariantViolationEntry::ConstraintsEnvelopeDigestMismatch {
@@ -196,9 +88,80 @@ fn metadata_field_change(old: &Value, new: &Value) -> Option<MetadataFieldChange
 /// - `DeterminismViolation` — the computed diff fails its internal round-trip
 ///   sanity check (should never occur in correct builds)
 pub fn compute_diff(a_bytes: &[u8], b_bytes: &[u8]) -> Result<SnapshotDiff, ExError> {
-    // Parse both manifests
-    let (a_manifest, a_raw) = parse_manifest_bytes(a_bytes)?;
-    let (b_manifest, b_raw) = parse_manifest_bytes(b_bytes)?;
+    compute_diff_with_rules(a_bytes, b_bytes, &SeverityRules::default())
+}
+
+/// Compute a snapshot diff, classifying each change with `rules`.
+///
+/// Identical to [`compute_diff`] except that per-change severities (and so
+/// the overall `severity`) come from the supplied rules table.
+///
+/// # Errors
+///
+/// Same as [`compute_diff`].
+pub fn compute_diff_with_rules(
+    a_bytes: &[u8],
+    b_bytes: &[u8],
+    rules: &SeverityRules,
+) -> Result<SnapshotDiff, ExError> {
+    let a = ParsedManifest::parse(a_bytes)?;
+    if a_bytes == b_bytes {
+        return diff_parsed(&a, &a, true, rules);
+    }
+    let b = ParsedManifest::parse(b_bytes)?;
+    diff_parsed(&a, &b, false, rules)
+}
+
+/// Compute a snapshot diff with `rules`, optionally adding EP content diffs.
+///
+/// With `options.include_content`, each EP in `ep_content_changes` gets an
+/// entry in `content_diffs`, loaded from `source`; otherwise the result
+/// matches [`compute_diff_with_rules`] and `source` is not used.
+///
+/// # Errors
+///
+/// Same as [`compute_diff`], plus any error from `source`.
+pub fn compute_diff_with_options(
+    a_bytes: &[u8],
+    b_bytes: &[u8],
+    rules: &SeverityRules,
+    options: &DiffOptions,
+    source: &dyn EpContentSource,
+) -> Result<SnapshotDiff, ExError> {
+    let a = ParsedManifest::parse(a_bytes)?;
+    let b = ParsedManifest::parse(b_bytes)?;
+    let mut diff = diff_parsed(&a, &b, a_bytes == b_bytes, rules)?;
+    if options.include_content {
+        add_content_diffs(&mut diff, &a, &b, source)?;
+    }
+    Ok(diff)
+}
+
+/// Compute a snapshot diff between two pre-parsed manifests.
+///
+/// The result matches [`compute_diff_with_rules`] on the original bytes,
+/// except that two manifests which parse to the same value are classified
+/// `Identical` even if their bytes differ in formatting.
+///
+/// # Errors
+///
+/// - `DeterminismViolation` — see [`compute_diff`]
+pub fn compute_diff_parsed(
+    a: &ParsedManifest,
+    b: &ParsedManifest,
+    rules: &SeverityRules,
+) -> Result<SnapshotDiff, ExError> {
+    diff_parsed(a, b, a == b, rules)
+}
+
+fn diff_parsed(
+    a: &ParsedManifest,
+    b: &ParsedManifest,
+    identical: bool,
+    rules: &SeverityRules,
+) -> Result<SnapshotDiff, ExError> {
+    let a_manifest = &a.manifest;
+    let b_manifest = &b.manifest;
 
     // Identity block
     let identity = DiffIdentity {
@@ -208,15 +171,19 @@ pub fn compute_diff(a_bytes: &[u8], b_bytes: &[u8]) -> Result<SnapshotDiff, ExEr
         b_manifest_digest: b_manifest.manifest_digest.clone(),
         b_semantic_manifest_digest: b_manifest.semantic_manifest_digest.clone(),
         b_ept_digest: b_manifest.ept_digest.clone(),
+        a_topology_digest: a_manifest.topology_digest.clone(),
+        b_topology_digest: b_manifest.topology_digest.clone(),
     };
 
-    // Fast-path: byte-identical manifests
-    if a_bytes == b_bytes {
+    // Fast-path: identical manifests
+    if identical {
         return Ok(SnapshotDiff {
             diff_schema_version: 1,
             identity,
             classification: DiffClassification::Identical,
             severity: DiffSeverity::None,
+            severity_tags: Vec::new(),
+            ignored_tags: Vec::new(),
             ept_changes: EptChanges {
                 changed: false,
                 added_eps: Vec::new(),
@@ -225,6 +192,7 @@ pub fn compute_diff(a_bytes: &[u8], b_bytes: &[u8]) -> Result<SnapshotDiff, ExEr
             },
             ep_content_changes: EpContentChanges {
                 changed_eps: Vec::new(),
+                normative_flips: Vec::new(),
             },
             constraint_changes: ConstraintChanges {
                 declared_ref_changes: DeclaredRefChanges {
@@ -258,6 +226,7 @@ pub fn compute_diff(a_bytes: &[u8], b_bytes: &[u8]) -> Result<SnapshotDiff, ExEr
                 changed_fields: Vec::new(),
             },
             invariant_violations: Vec::new(),
+            content_diffs: None,
         });
     }
 
@@ -268,6 +237,8 @@ pub fn compute_diff(a_bytes: &[u8], b_bytes: &[u8]) -> Result<SnapshotDiff, ExEr
             identity,
             classification: DiffClassification::NoSemanticChange,
             severity: DiffSeverity::None,
+            severity_tags: Vec::new(),
+            ignored_tags: Vec::new(),
             ept_changes: EptChanges {
                 changed: false,
                 added_eps: Vec::new(),
@@ -276,6 +247,7 @@ pub fn compute_diff(a_bytes: &[u8], b_bytes: &[u8]) -> Result<SnapshotDiff, ExEr
             },
             ep_content_changes: EpContentChanges {
                 changed_eps: Vec::new(),
+                normative_flips: Vec::new(),
             },
             constraint_changes: ConstraintChanges {
                 declared_ref_changes: DeclaredRefChanges {
@@ -309,13 +281,14 @@ pub fn compute_diff(a_bytes: &[u8], b_bytes: &[u8]) -> Result<SnapshotDiff, ExEr
                 changed_fields: Vec::new(),
             },
             invariant_violations: Vec::new(),
+            content_diffs: None,
         });
     }
 
     // Invariant violations (non-fatal)
     let mut invariant_violations: Vec<InvariantViolationEntry> = Vec::new();
-    check_envelope_invariants("a", &a_manifest, &mut invariant_violations);
-    check_envelope_invariants("b", &b_manifest, &mut invariant_violations);
+    check_envelope_invariants("a", a_manifest, &mut invariant_violations);
+    check_envelope_invariants("b", b_manifest, &mut invariant_violations);
 
     // EPT changes
     let a_ep_ids: Vec<String> = a_manifest.ept.iter().map(|e| e.ep_id.clone()).collect();
@@ -358,7 +331,35 @@ pub fn compute_diff(a_bytes: &[u8], b_bytes: &[u8]) -> Result<SnapshotDiff, ExEr
         })
         .collect();
     changed_eps.sort();
-    let ep_content_changes = EpContentChanges { changed_eps };
+
+    // Normative flag flips (EPs present in both, flag differs)
+    let a_normative: BTreeMap<&str, bool> = a_manifest
+        .ept
+        .iter()
+        .map(|e| (e.ep_id.as_str(), e.normative))
+        .collect();
+    let b_normative: BTreeMap<&str, bool> = b_manifest
+        .ept
+        .iter()
+        .map(|e| (e.ep_id.as_str(), e.normative))
+        .collect();
+    let normative_flips: Vec<NormativeFlip> = a_normative
+        .iter()
+        .filter_map(|(ep_id, old)| {
+            b_normative
+                .get(ep_id)
+                .filter(|new| *new != old)
+                .map(|new| NormativeFlip {
+                    ep_id: ep_id.to_string(),
+                    old: *old,
+                    new: *new,
+                })
+        })
+        .collect();
+    let ep_content_changes = EpContentChanges {
+        changed_eps,
+        normative_flips,
+    };
 
     // Constraint changes
     let a_env = &a_manifest.constraints;
@@ -497,24 +498,8 @@ pub fn compute_diff(a_bytes: &[u8], b_bytes: &[u8]) -> Result<SnapshotDiff, ExEr
     let metadata_changes = MetadataChanges { changed_fields };
 
     // Unknown changes: keys not in KNOWN_FIELDS
-    let known_set: BTreeSet<&str> = KNOWN_FIELDS.iter().copied().collect();
-
-    let a_unknown: BTreeSet<&str> = if let Some(obj) = a_raw.as_object() {
-        obj.keys()
-            .map(|k| k.as_str())
-            .filter(|k| !known_set.contains(k))
-            .collect()
-    } else {
-        BTreeSet::new()
-    };
-    let b_unknown: BTreeSet<&str> = if let Some(obj) = b_raw.as_object() {
-        obj.keys()
-            .map(|k| k.as_str())
-            .filter(|k| !known_set.contains(k))
-            .collect()
-    } else {
-        BTreeSet::new()
-    };
+    let a_unknown: BTreeSet<&str> = a.unknown_fields.keys().map(|k| k.as_str()).collect();
+    let b_unknown: BTreeSet<&str> = b.unknown_fields.keys().map(|k| k.as_str()).collect();
 
     let unk_added: Vec<String> = b_unknown
         .difference(&a_unknown)
@@ -526,16 +511,11 @@ pub fn compute_diff(a_bytes: &[u8], b_bytes: &[u8]) -> Result<SnapshotDiff, ExEr
         .collect();
 
     // Changed unknown fields: present in both but different value
-    let unk_both: BTreeSet<&str> = a_unknown.intersection(&b_unknown).copied().collect();
-    let mut unk_changed: Vec<String> = Vec::new();
-    if let (Some(a_obj), Some(b_obj)) = (a_raw.as_object(), b_raw.as_object()) {
-        for key in &unk_both {
-            if a_obj.get(*key) != b_obj.get(*key) {
-                unk_changed.push(key.to_string());
-            }
-        }
-    }
-    unk_changed.sort();
+    let unk_changed: Vec<String> = a_unknown
+        .intersection(&b_unknown)
+        .filter(|key| a.unknown_fields.get(**key) != b.unknown_fields.get(**key))
+        .map(|s| s.to_string())
+        .collect();
 
     let unknown_changes = UnknownChanges {
         added_fields: unk_added,
@@ -543,59 +523,85 @@ pub fn compute_diff(a_bytes: &[u8], b_bytes: &[u8]) -> Result<SnapshotDiff, ExEr
         changed_fields: unk_changed,
     };
 
-    // Severity roll-up
-    let mut severities: Vec<DiffSeverity> = Vec::new();
-
-    if ept_changes.added_eps.is_empty() && ept_changes.removed_eps.is_empty() {
-        // no structural EPT change
-    } else {
-        severities.push(DiffSeverity::Breaking);
+    // Per-change severity tags, then roll-up
+    let mut severity_tags: Vec<SeverityTag> = Vec::new();
+    for ep_id in &ept_changes.added_eps {
+        severity_tags.push(rules.tag(ChangeCategory::EptEntryAdded, ep_id));
+    }
+    for ep_id in &ept_changes.removed_eps {
+        severity_tags.push(rules.tag(ChangeCategory::EptEntryRemoved, ep_id));
     }
     if ept_changes.ordering_changed {
-        severities.push(DiffSeverity::Semantic);
+        severity_tags.push(rules.tag(ChangeCategory::EptReordered, "ept"));
+    }
+    for ep_id in &ep_content_changes.changed_eps {
+        // An EP counts as normative if it is normative on either side
+        let normative = a_normative.get(ep_id.as_str()).copied().unwrap_or(false)
+            || b_normative.get(ep_id.as_str()).copied().unwrap_or(false);
+        let category = if normative {
+            ChangeCategory::NormativeContentChanged
+        } else {
+            ChangeCategory::NonNormativeContentChanged
+        };
+        severity_tags.push(rules.tag(category, ep_id));
+    }
+    for flip in &ep_content_changes.normative_flips {
+        severity_tags.push(rules.tag(ChangeCategory::NormativeFlagChanged, &flip.ep_id));
+    }
+    for r in &constraint_changes.declared_ref_changes.added {
+        severity_tags.push(rules.tag(ChangeCategory::ConstraintRefAdded, r));
+    }
+    for r in &constraint_changes.declared_ref_changes.removed {
+        severity_tags.push(rules.tag(ChangeCategory::ConstraintRefRemoved, r));
     }
-    if !ep_content_changes.changed_eps.is_empty() {
-        severities.push(DiffSeverity::Semantic);
+    for (family, entry) in &constraint_changes.family_changes {
+        let category = if entry.added {
+            ChangeCategory::ConstraintFamilyAdded
+        } else if entry.removed {
+            ChangeCategory::ConstraintFamilyRemoved
+        } else {
+            ChangeCategory::ConstraintFamilyChanged
+        };
+        severity_tags.push(rules.tag(category, family));
     }
-    let has_constraint_changes = !constraint_changes.declared_ref_changes.added.is_empty()
-        || !constraint_changes.declared_ref_changes.removed.is_empty()
-        || !constraint_changes.family_changes.is_empty()
-        || !constraint_changes
-            .abb_sbb_projection_changes
-            .abb_added
-            .is_empty()
-        || !constraint_changes
-            .abb_sbb_projection_changes
-            .abb_removed
-            .is_empty()
-        || !constraint_changes
-            .abb_sbb_projection_changes
-            .sbb_added
-            .is_empty()
-        || !constraint_changes
-            .abb_sbb_projection_changes
-            .sbb_removed
-            .is_empty()
-        || constraint_changes.constraints_digest_change.is_some();
-    if has_constraint_changes {
-        severities.push(DiffSeverity::Semantic);
+    let projection = &constraint_changes.abb_sbb_projection_changes;
+    for item in projection
+        .abb_added
+        .iter()
+        .chain(&projection.abb_removed)
+        .chain(&projection.sbb_added)
+        .chain(&projection.sbb_removed)
+    {
+        severity_tags.push(rules.tag(ChangeCategory::ProjectionChanged, item));
+    }
+    if constraint_changes.constraints_digest_change.is_some() {
+        severity_tags.push(rules.tag(
+            ChangeCategory::ConstraintsDigestChanged,
+            "constraints_digest",
+        ));
     }
     if coverage_changes.changed {
-        severities.push(DiffSeverity::Informational);
+        severity_tags.push(rules.tag(ChangeCategory::CoverageChanged, "coverage"));
     }
-    if !exception_changes.added.is_empty() || !exception_changes.removed.is_empty() {
-        severities.push(DiffSeverity::Informational);
+    for exc in &exception_changes.added {
+        severity_tags.push(rules.tag(ChangeCategory::ExceptionAdded, exc));
     }
-    if !metadata_changes.changed_fields.is_empty() {
-        severities.push(DiffSeverity::Informational);
+    for exc in &exception_changes.removed {
+        severity_tags.push(rules.tag(ChangeCategory::ExceptionRemoved, exc));
     }
-    if !unknown_changes.added_fields.is_empty()
-        || !unknown_changes.removed_fields.is_empty()
-        || !unknown_changes.changed_fields.is_empty()
+    for field in metadata_changes.changed_fields.keys() {
+        severity_tags.push(rules.tag(ChangeCategory::MetadataChanged, field));
+    }
+    for field in unknown_changes
+        .added_fields
+        .iter()
+        .chain(&unknown_changes.removed_fields)
+        .chain(&unknown_changes.changed_fields)
     {
-        severities.push(DiffSeverity::Informational);
+        severity_tags.push(rules.tag(ChangeCategory::UnknownFieldChanged, field));
     }
 
+    let severities: Vec<DiffSeverity> = severity_tags.iter().map(|t| t.severity.clone()).collect();
     let severity = max_severity(&severities);
 
     let diff = SnapshotDiff {
@@ -603,6 +609,8 @@ pub fn compute_diff(a_bytes: &[u8], b_bytes: &[u8]) -> Result<SnapshotDiff, ExEr
         identity,
         classification: DiffClassification::Changed,
         severity,
+        severity_tags,
+        ignored_tags: Vec::new(),
         ept_changes,
         ep_content_changes,
         constraint_changes,
@@ -611,6 +619,7 @@ pub fn compute_diff(a_bytes: &[u8], b_bytes: &[u8]) -> Result<SnapshotDiff, ExEr
         metadata_changes,
         unknown_changes,
         invariant_violations,
+        content_diffs: None,
     };
 
     // Determinism guard: round-trip through JSON must produce an equal struct
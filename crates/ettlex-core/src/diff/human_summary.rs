@@ -1,40 +1,141 @@
 //! Human-readable summary renderer for snapshot diffs.
+//!
+//! Three verbosity levels serve different audiences: a one-line headline for
+//! lists and notifications, a summary with change counts per section, and
+//! the detailed report listing every changed EP, constraint and field. All
+//! text comes from a [`MessageCatalog`], so front-ends can localise it.
 
-use crate::diff::model::{DiffClassification, DiffSeverity, SnapshotDiff};
+use std::fmt::Display;
+use std::str::FromStr;
 
-/// Render a human-readable Markdown/text summary of a [`SnapshotDiff`].
+use serde::{Deserialize, Serialize};
+
+use crate::diff::messages::MessageCatalog;
+use crate::diff::model::{
+    Diff3Change, Diff3ItemKind, DiffClassification, DiffSeverity, InvariantViolationEntry,
+    SnapshotDiff, SnapshotDiff3,
+};
+use crate::errors::{ExError, ExErrorKind};
+
+/// How much of a diff the human summary shows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SummaryVerbosity {
+    /// One line: classification, severity and change counts.
+    OneLine,
+    /// Classification, severity, one count per changed section, and any
+    /// invariant violations.
+    Summary,
+    /// Every change, with per-field old and new values.
+    #[default]
+    Detailed,
+}
+
+impl FromStr for SummaryVerbosity {
+    type Err = ExError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().replace('_', "-").as_str() {
+            "one-line" | "oneline" => Ok(SummaryVerbosity::OneLine),
+            "summary" => Ok(SummaryVerbosity::Summary),
+            "detailed" => Ok(SummaryVerbosity::Detailed),
+            _ => Err(ExError::new(ExErrorKind::InvalidInput)
+                .with_op("summary_verbosity_parse")
+                .with_message(format!("unknown summary verbosity: {}", s))
+                .with_candidates(vec![
+                    "one-line".to_string(),
+                    "summary".to_string(),
+                    "detailed".to_string(),
+                ])),
+        }
+    }
+}
+
+/// Verbosity and message catalog for [`render_human_summary_with`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SummaryOptions {
+    pub verbosity: SummaryVerbosity,
+    pub catalog: MessageCatalog,
+}
+
+/// Render the detailed English summary of a [`SnapshotDiff`].
 ///
 /// The summary is intended for review workflows and approval displays.
 /// It is informational only and does not affect the structured diff.
 pub fn render_human_summary(diff: &SnapshotDiff) -> String {
-    let mut out = String::new();
+    render_human_summary_with(diff, &SummaryOptions::default())
+}
 
-    // Header
-    out.push_str("## Snapshot Diff\n\n");
+/// Render a summary of a [`SnapshotDiff`] at the given verbosity, using the
+/// options' message catalog.
+pub fn render_human_summary_with(diff: &SnapshotDiff, options: &SummaryOptions) -> String {
+    let m = &options.catalog;
+    match options.verbosity {
+        SummaryVerbosity::OneLine => render_one_line(diff, m),
+        SummaryVerbosity::Summary => render_summary(diff, m),
+        SummaryVerbosity::Detailed => render_detailed(diff, m),
+    }
+}
 
-    // Classification and severity
-    let class_label = match &diff.classification {
-        DiffClassification::Identical => "Identical",
-        DiffClassification::NoSemanticChange => "No Semantic Change",
-        DiffClassification::Changed => "Changed",
-    };
-    let severity_label = match &diff.severity {
-        DiffSeverity::None => "None",
-        DiffSeverity::Informational => "Informational",
-        DiffSeverity::Semantic => "Semantic",
-        DiffSeverity::Breaking => "Breaking",
+fn render_one_line(diff: &SnapshotDiff, m: &MessageCatalog) -> String {
+    let classification = classification_label(&diff.classification, m);
+    if !has_semantic_change(diff) {
+        return m.format("one_line.unchanged", &[("classification", &classification)]);
+    }
+    let mut counts = change_counts(diff, m);
+    if !diff.invariant_violations.is_empty() {
+        counts.push(m.format(
+            "count.invariant_violations",
+            &[("count", &diff.invariant_violations.len())],
+        ));
+    }
+    let changes = if counts.is_empty() {
+        m.message("count.none").to_string()
+    } else {
+        counts.join(", ")
     };
-    out.push_str(&format!(
-        "**Classification**: {class_label}  \n**Severity**: {severity_label}\n\n"
-    ));
+    m.format(
+        "one_line.changed",
+        &[
+            ("classification", &classification),
+            ("severity", &severity_label(&diff.severity, m)),
+            ("changes", &changes),
+        ],
+    )
+}
+
+fn render_summary(diff: &SnapshotDiff, m: &MessageCatalog) -> String {
+    let mut out = header(diff, m);
+    if !has_semantic_change(diff) {
+        out.push_str(&format!("_{}_\n", m.message("no_semantic_changes")));
+        return out;
+    }
+    let counts = change_counts(diff, m);
+    if !counts.is_empty() {
+        out.push_str(&format!("### {}\n\n", m.message("section.changes")));
+        for count in counts {
+            out.push_str(&format!("- {}\n", count));
+        }
+        out.push('\n');
+    }
+    push_ignored(&mut out, diff, m);
+    push_invariant_violations(&mut out, diff, m);
+    out
+}
+
+fn render_detailed(diff: &SnapshotDiff, m: &MessageCatalog) -> String {
+    let mut out = header(diff, m);
 
     // Identity
-    out.push_str("### Identity\n\n");
+    out.push_str(&format!("### {}\n\n", m.message("section.identity")));
     out.push_str(&format!(
-        "| | Manifest Digest | Semantic Digest | EPT Digest |\n\
+        "| | {} | {} | {} |\n\
          |---|---|---|---|\n\
          | A | `{}` | `{}` | `{}` |\n\
          | B | `{}` | `{}` | `{}` |\n\n",
+        m.message("identity.manifest_digest"),
+        m.message("identity.semantic_digest"),
+        m.message("identity.ept_digest"),
         short(&diff.identity.a_manifest_digest),
         short(&diff.identity.a_semantic_manifest_digest),
         short(&diff.identity.a_ept_digest),
@@ -43,76 +144,121 @@ pub fn render_human_summary(diff: &SnapshotDiff) -> String {
         short(&diff.identity.b_ept_digest),
     ));
 
-    if matches!(
-        diff.classification,
-        DiffClassification::Identical | DiffClassification::NoSemanticChange
-    ) {
-        out.push_str("_No semantic changes detected._\n");
+    if !has_semantic_change(diff) {
+        out.push_str(&format!("_{}_\n", m.message("no_semantic_changes")));
         return out;
     }
 
     // EPT changes
     if diff.ept_changes.changed {
-        out.push_str("### EPT Changes\n\n");
+        out.push_str(&format!("### {}\n\n", m.message("section.ept")));
         if !diff.ept_changes.added_eps.is_empty() {
             out.push_str(&format!(
-                "- **Added EPs** ({}): {}\n",
+                "- **{}** ({}): {}\n",
+                m.message("ept.added_eps"),
                 diff.ept_changes.added_eps.len(),
                 diff.ept_changes.added_eps.join(", ")
             ));
         }
         if !diff.ept_changes.removed_eps.is_empty() {
             out.push_str(&format!(
-                "- **Removed EPs** ({}): {}\n",
+                "- **{}** ({}): {}\n",
+                m.message("ept.removed_eps"),
                 diff.ept_changes.removed_eps.len(),
                 diff.ept_changes.removed_eps.join(", ")
             ));
         }
         if diff.ept_changes.ordering_changed {
-            out.push_str("- **Ordering changed**\n");
+            out.push_str(&format!("- **{}**\n", m.message("ept.ordering_changed")));
+        }
+        out.push('\n');
+    }
+
+    // Normative flips change the weight of content, so they get their own section
+    if !diff.ep_content_changes.normative_flips.is_empty() {
+        out.push_str(&format!("### {}\n\n", m.message("section.normative_flips")));
+        for flip in &diff.ep_content_changes.normative_flips {
+            out.push_str(&format!(
+                "- `{}`: **{} → {}**\n",
+                flip.ep_id,
+                normative_label(flip.old, m),
+                normative_label(flip.new, m)
+            ));
         }
         out.push('\n');
     }
 
     // EP content changes
     if !diff.ep_content_changes.changed_eps.is_empty() {
-        out.push_str("### EP Content Changes\n\n");
+        out.push_str(&format!("### {}\n\n", m.message("section.ep_content")));
         for ep_id in &diff.ep_content_changes.changed_eps {
-            out.push_str(&format!("- `{}` (digest changed)\n", ep_id));
+            out.push_str(&format!(
+                "- `{}` ({})\n",
+                ep_id,
+                m.message("digest_changed")
+            ));
+        }
+        out.push('\n');
+    }
+
+    // EP content diffs, when requested
+    if let Some(content_diffs) = diff.content_diffs.as_ref().filter(|c| !c.is_empty()) {
+        out.push_str(&format!("### {}\n\n", m.message("section.content_diffs")));
+        for entry in content_diffs {
+            if !entry.available {
+                out.push_str(&format!(
+                    "- `{}`: _{}_\n",
+                    entry.ep_id,
+                    m.message("content_diff.unavailable")
+                ));
+                continue;
+            }
+            out.push_str(&format!("- `{}`\n", entry.ep_id));
+            for text_diff in entry.field_diffs.values() {
+                out.push_str(&format!("\n```diff\n{}```\n", text_diff));
+            }
         }
         out.push('\n');
     }
 
     // Constraint changes
     let cc = &diff.constraint_changes;
-    let has_cc = !cc.declared_ref_changes.added.is_empty()
-        || !cc.declared_ref_changes.removed.is_empty()
-        || !cc.family_changes.is_empty()
-        || cc.constraints_digest_change.is_some();
-    if has_cc {
-        out.push_str("### Constraint Changes\n\n");
+    if has_constraint_changes(diff) {
+        out.push_str(&format!("### {}\n\n", m.message("section.constraints")));
         if !cc.declared_ref_changes.added.is_empty() {
             out.push_str(&format!(
-                "- **Added refs**: {}\n",
+                "- **{}**: {}\n",
+                m.message("constraints.added_refs"),
                 cc.declared_ref_changes.added.join(", ")
             ));
         }
         if !cc.declared_ref_changes.removed.is_empty() {
             out.push_str(&format!(
-                "- **Removed refs**: {}\n",
+                "- **{}**: {}\n",
+                m.message("constraints.removed_refs"),
                 cc.declared_ref_changes.removed.join(", ")
             ));
         }
         for (family, entry) in &cc.family_changes {
             if entry.added {
-                out.push_str(&format!("- **Family added**: `{}`\n", family));
+                out.push_str(&format!(
+                    "- **{}**: `{}`\n",
+                    m.message("constraints.family_added"),
+                    family
+                ));
             } else if entry.removed {
-                out.push_str(&format!("- **Family removed**: `{}`\n", family));
-            } else if entry.digest_changed {
                 out.push_str(&format!(
-                    "- **Family changed**: `{}` (digest changed)\n",
+                    "- **{}**: `{}`\n",
+                    m.message("constraints.family_removed"),
                     family
                 ));
+            } else if entry.digest_changed {
+                out.push_str(&format!(
+                    "- **{}**: `{}` ({})\n",
+                    m.message("constraints.family_changed"),
+                    family,
+                    m.message("digest_changed")
+                ));
             }
         }
         out.push('\n');
@@ -120,22 +266,24 @@ pub fn render_human_summary(diff: &SnapshotDiff) -> String {
 
     // Coverage changes
     if diff.coverage_changes.changed {
-        out.push_str("### Coverage Changes\n\n");
-        out.push_str("- Coverage metrics changed\n\n");
+        out.push_str(&format!("### {}\n\n", m.message("section.coverage")));
+        out.push_str(&format!("- {}\n\n", m.message("coverage.changed")));
     }
 
     // Exception changes
     if !diff.exception_changes.added.is_empty() || !diff.exception_changes.removed.is_empty() {
-        out.push_str("### Exception Changes\n\n");
+        out.push_str(&format!("### {}\n\n", m.message("section.exceptions")));
         if !diff.exception_changes.added.is_empty() {
             out.push_str(&format!(
-                "- **Added**: {}\n",
+                "- **{}**: {}\n",
+                m.message("exceptions.added"),
                 diff.exception_changes.added.join(", ")
             ));
         }
         if !diff.exception_changes.removed.is_empty() {
             out.push_str(&format!(
-                "- **Removed**: {}\n",
+                "- **{}**: {}\n",
+                m.message("exceptions.removed"),
                 diff.exception_changes.removed.join(", ")
             ));
         }
@@ -144,7 +292,7 @@ pub fn render_human_summary(diff: &SnapshotDiff) -> String {
 
     // Metadata changes
     if !diff.metadata_changes.changed_fields.is_empty() {
-        out.push_str("### Metadata Changes\n\n");
+        out.push_str(&format!("### {}\n\n", m.message("section.metadata")));
         for (field, change) in &diff.metadata_changes.changed_fields {
             out.push_str(&format!(
                 "- **{}**: `{}` → `{}`\n",
@@ -155,57 +303,334 @@ pub fn render_human_summary(diff: &SnapshotDiff) -> String {
     }
 
     // Unknown changes
-    if !diff.unknown_changes.added_fields.is_empty()
-        || !diff.unknown_changes.removed_fields.is_empty()
-        || !diff.unknown_changes.changed_fields.is_empty()
+    let uc = &diff.unknown_changes;
+    if !uc.added_fields.is_empty() || !uc.removed_fields.is_empty() || !uc.changed_fields.is_empty()
     {
-        out.push_str("### Unknown Field Changes\n\n");
-        if !diff.unknown_changes.added_fields.is_empty() {
-            out.push_str(&format!(
-                "- **Added fields**: {}\n",
-                diff.unknown_changes.added_fields.join(", ")
-            ));
-        }
-        if !diff.unknown_changes.removed_fields.is_empty() {
-            out.push_str(&format!(
-                "- **Removed fields**: {}\n",
-                diff.unknown_changes.removed_fields.join(", ")
-            ));
+        out.push_str(&format!("### {}\n\n", m.message("section.unknown_fields")));
+        for (key, fields) in [
+            ("unknown_fields.added", &uc.added_fields),
+            ("unknown_fields.removed", &uc.removed_fields),
+            ("unknown_fields.changed", &uc.changed_fields),
+        ] {
+            if !fields.is_empty() {
+                out.push_str(&format!(
+                    "- **{}**: {}\n",
+                    m.message(key),
+                    fields.join(", ")
+                ));
+            }
         }
-        if !diff.unknown_changes.changed_fields.is_empty() {
+        out.push('\n');
+    }
+
+    // Severity breakdown, most severe first
+    if !diff.severity_tags.is_empty() {
+        out.push_str(&format!(
+            "### {}\n\n",
+            m.message("section.severity_breakdown")
+        ));
+        out.push_str(&format!(
+            "| {} | {} | {} |\n|---|---|---|\n",
+            m.message("label.severity"),
+            m.message("severity_breakdown.change"),
+            m.message("severity_breakdown.subject")
+        ));
+        let mut tags: Vec<_> = diff.severity_tags.iter().collect();
+        tags.sort_by(|a, b| b.severity.cmp(&a.severity));
+        for tag in tags {
             out.push_str(&format!(
-                "- **Changed fields**: {}\n",
-                diff.unknown_changes.changed_fields.join(", ")
+                "| {} | {} | `{}` |\n",
+                severity_label(&tag.severity, m),
+                tag.category.as_str(),
+                tag.subject
             ));
         }
         out.push('\n');
     }
 
-    // Invariant violations
-    if !diff.invariant_violations.is_empty() {
-        out.push_str("### ⚠ Invariant Violations\n\n");
-        for v in &diff.invariant_violations {
-            match v {
-                crate::diff::model::InvariantViolationEntry::ConstraintsEnvelopeDigestMismatch {
-                    which,
-                    computed,
-                    recorded,
-                } => {
-                    out.push_str(&format!(
-                        "- Manifest {which}: constraints_digest mismatch \
-                         (recorded `{}`, computed `{}`)\n",
-                        short(recorded),
-                        short(computed)
-                    ));
-                }
-            }
+    push_ignored(&mut out, diff, m);
+    push_invariant_violations(&mut out, diff, m);
+    out
+}
+
+/// Render the detailed English summary of a [`SnapshotDiff3`].
+pub fn render_diff3_summary(diff: &SnapshotDiff3) -> String {
+    render_diff3_summary_with(diff, &SummaryOptions::default())
+}
+
+/// Render a summary of a [`SnapshotDiff3`] at the given verbosity.
+///
+/// `OneLine` gives the per-list counts, `Summary` adds the snapshot digests,
+/// and `Detailed` lists every item with its base and side values, conflicts
+/// first.
+pub fn render_diff3_summary_with(diff: &SnapshotDiff3, options: &SummaryOptions) -> String {
+    let m = &options.catalog;
+    let counts = m.format(
+        "diff3.one_line",
+        &[
+            ("only_in_a", &diff.only_in_a.len()),
+            ("only_in_b", &diff.only_in_b.len()),
+            ("in_both", &diff.in_both.len()),
+            ("conflicting", &diff.conflicting.len()),
+        ],
+    );
+    if options.verbosity == SummaryVerbosity::OneLine {
+        return counts;
+    }
+
+    let mut out = format!(
+        "## {}\n\n{}\n\n",
+        m.message("diff3.title"),
+        m.format(
+            "diff3.identity",
+            &[
+                ("base", &short(&diff.base_manifest_digest)),
+                ("a", &short(&diff.a_manifest_digest)),
+                ("b", &short(&diff.b_manifest_digest)),
+            ],
+        )
+    );
+    let unchanged = diff.only_in_a.is_empty()
+        && diff.only_in_b.is_empty()
+        && diff.in_both.is_empty()
+        && diff.conflicting.is_empty();
+    if unchanged {
+        out.push_str(&format!("_{}_\n", m.message("diff3.no_changes")));
+        return out;
+    }
+    out.push_str(&format!("{}\n\n", counts));
+    if options.verbosity == SummaryVerbosity::Summary {
+        return out;
+    }
+
+    if !diff.conflicting.is_empty() {
+        out.push_str(&format!(
+            "### {}\n\n",
+            m.message("diff3.section.conflicting")
+        ));
+        for change in &diff.conflicting {
+            let args: [(&str, &dyn Display); 5] = [
+                ("kind", &diff3_kind_label(change.kind, m)),
+                ("subject", &change.subject),
+                ("base", &diff3_value(change, change.base.as_ref(), m)),
+                ("a", &diff3_value(change, change.a.as_ref(), m)),
+                ("b", &diff3_value(change, change.b.as_ref(), m)),
+            ];
+            out.push_str(&format!("- {}\n", m.format("diff3.conflict", &args)));
         }
         out.push('\n');
     }
+    let sections: [(&str, &[Diff3Change], bool); 3] = [
+        ("diff3.section.only_in_a", &diff.only_in_a, true),
+        ("diff3.section.only_in_b", &diff.only_in_b, false),
+        ("diff3.section.in_both", &diff.in_both, true),
+    ];
+    for (key, changes, from_a) in sections {
+        if changes.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("### {}\n\n", m.message(key)));
+        for change in changes {
+            let value = if from_a { &change.a } else { &change.b };
+            let args: [(&str, &dyn Display); 4] = [
+                ("kind", &diff3_kind_label(change.kind, m)),
+                ("subject", &change.subject),
+                ("base", &diff3_value(change, change.base.as_ref(), m)),
+                ("value", &diff3_value(change, value.as_ref(), m)),
+            ];
+            out.push_str(&format!("- {}\n", m.format("diff3.change", &args)));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn diff3_kind_label(kind: Diff3ItemKind, m: &MessageCatalog) -> &str {
+    m.message(match kind {
+        Diff3ItemKind::EptEntry => "diff3.kind.ept_entry",
+        Diff3ItemKind::EptOrder => "diff3.kind.ept_order",
+        Diff3ItemKind::ConstraintRef => "diff3.kind.constraint_ref",
+        Diff3ItemKind::ConstraintFamily => "diff3.kind.constraint_family",
+        Diff3ItemKind::ApplicableAbb => "diff3.kind.applicable_abb",
+        Diff3ItemKind::ResolvedSbb => "diff3.kind.resolved_sbb",
+        Diff3ItemKind::Coverage => "diff3.kind.coverage",
+        Diff3ItemKind::Exception => "diff3.kind.exception",
+        Diff3ItemKind::Metadata => "diff3.kind.metadata",
+        Diff3ItemKind::UnknownField => "diff3.kind.unknown_field",
+    })
+}
+
+/// Compact display of one side's value: EPT entries by content digest,
+/// membership items as present/absent, digests shortened.
+fn diff3_value(
+    change: &Diff3Change,
+    value: Option<&serde_json::Value>,
+    m: &MessageCatalog,
+) -> String {
+    use serde_json::Value;
+    match (change.kind, value) {
+        (_, None) => m.message("diff3.absent").to_string(),
+        (_, Some(Value::Bool(true))) => m.message("diff3.present").to_string(),
+        (Diff3ItemKind::EptEntry, Some(entry)) => format!(
+            "`{}`",
+            short(entry["ep_digest"].as_str().unwrap_or_default())
+        ),
+        (Diff3ItemKind::ConstraintFamily, Some(Value::String(digest))) => {
+            format!("`{}`", short(digest))
+        }
+        (_, Some(other)) => format!("`{}`", other),
+    }
+}
 
+/// Title plus classification and severity lines.
+fn header(diff: &SnapshotDiff, m: &MessageCatalog) -> String {
+    let mut out = format!(
+        "## {}\n\n**{}**: {}  \n**{}**: {}",
+        m.message("title"),
+        m.message("label.classification"),
+        classification_label(&diff.classification, m),
+        m.message("label.severity"),
+        severity_label(&diff.severity, m)
+    );
+    // Only manifests that record a topology digest can tell the two apart
+    if let Some(changed) = diff.identity.topology_changed() {
+        let key = if changed {
+            "topology.changed"
+        } else {
+            "topology.unchanged"
+        };
+        out.push_str(&format!(
+            "  \n**{}**: {}",
+            m.message("label.topology"),
+            m.message(key)
+        ));
+    }
+    out.push_str("\n\n");
     out
 }
 
+fn push_ignored(out: &mut String, diff: &SnapshotDiff, m: &MessageCatalog) {
+    if !diff.ignored_tags.is_empty() {
+        out.push_str(&format!(
+            "_{}_\n\n",
+            m.format("ignored", &[("count", &diff.ignored_tags.len())])
+        ));
+    }
+}
+
+fn push_invariant_violations(out: &mut String, diff: &SnapshotDiff, m: &MessageCatalog) {
+    if diff.invariant_violations.is_empty() {
+        return;
+    }
+    out.push_str(&format!("### {}\n\n", m.message("section.invariants")));
+    for v in &diff.invariant_violations {
+        match v {
+            InvariantViolationEntry::ConstraintsEnvelopeDigestMismatch {
+                which,
+                computed,
+                recorded,
+            } => {
+                let args: [(&str, &dyn Display); 3] = [
+                    ("which", which),
+                    ("recorded", &short(recorded)),
+                    ("computed", &short(computed)),
+                ];
+                out.push_str(&format!(
+                    "- {}\n",
+                    m.format("invariant.constraints_digest_mismatch", &args)
+                ));
+            }
+        }
+    }
+    out.push('\n');
+}
+
+/// One count line per changed section, in report order.
+fn change_counts(diff: &SnapshotDiff, m: &MessageCatalog) -> Vec<String> {
+    let mut counts = Vec::new();
+    let mut count = |key: &str, n: usize| {
+        if n > 0 {
+            counts.push(m.format(key, &[("count", &n)]));
+        }
+    };
+    count("count.eps_added", diff.ept_changes.added_eps.len());
+    count("count.eps_removed", diff.ept_changes.removed_eps.len());
+    count(
+        "count.ept_reordered",
+        usize::from(diff.ept_changes.ordering_changed),
+    );
+    count(
+        "count.normative_flips",
+        diff.ep_content_changes.normative_flips.len(),
+    );
+    count(
+        "count.ep_content",
+        diff.ep_content_changes.changed_eps.len(),
+    );
+    let cc = &diff.constraint_changes;
+    let constraint_changes = cc.declared_ref_changes.added.len()
+        + cc.declared_ref_changes.removed.len()
+        + cc.family_changes.len();
+    count(
+        "count.constraints",
+        if has_constraint_changes(diff) {
+            constraint_changes.max(1)
+        } else {
+            0
+        },
+    );
+    count("count.coverage", usize::from(diff.coverage_changes.changed));
+    count(
+        "count.exceptions",
+        diff.exception_changes.added.len() + diff.exception_changes.removed.len(),
+    );
+    count("count.metadata", diff.metadata_changes.changed_fields.len());
+    let uc = &diff.unknown_changes;
+    count(
+        "count.unknown_fields",
+        uc.added_fields.len() + uc.removed_fields.len() + uc.changed_fields.len(),
+    );
+    counts
+}
+
+fn has_semantic_change(diff: &SnapshotDiff) -> bool {
+    matches!(diff.classification, DiffClassification::Changed)
+}
+
+fn has_constraint_changes(diff: &SnapshotDiff) -> bool {
+    let cc = &diff.constraint_changes;
+    !cc.declared_ref_changes.added.is_empty()
+        || !cc.declared_ref_changes.removed.is_empty()
+        || !cc.family_changes.is_empty()
+        || cc.constraints_digest_change.is_some()
+}
+
+fn classification_label<'a>(classification: &DiffClassification, m: &'a MessageCatalog) -> &'a str {
+    m.message(match classification {
+        DiffClassification::Identical => "classification.identical",
+        DiffClassification::NoSemanticChange => "classification.no_semantic_change",
+        DiffClassification::Changed => "classification.changed",
+    })
+}
+
+/// Display label for a severity.
+fn severity_label<'a>(severity: &DiffSeverity, m: &'a MessageCatalog) -> &'a str {
+    m.message(match severity {
+        DiffSeverity::None => "severity.none",
+        DiffSeverity::Informational => "severity.informational",
+        DiffSeverity::Semantic => "severity.semantic",
+        DiffSeverity::Breaking => "severity.breaking",
+    })
+}
+
+fn normative_label(normative: bool, m: &MessageCatalog) -> &str {
+    m.message(if normative {
+        "normative.true"
+    } else {
+        "normative.false"
+    })
+}
+
 /// Return the first 12 characters of a digest for display purposes.
 fn short(digest: &str) -> &str {
     let end = digest.len().min(12);
@@ -525,4 +950,103 @@ mod tests {
         assert!(s.contains("Unknown Field Changes"));
         assert!(s.contains("gone_field"));
     }
+
+    fn ept_added_diff() -> SnapshotDiff {
+        let a = base();
+        let mut b = base();
+        b["semantic_manifest_digest"] = json!("bbbb");
+        b["manifest_digest"] = json!("dddd");
+        b["ept"] = json!([
+            {"ep_id": "ep:root:0", "ordinal": 0, "normative": true,
+             "ep_digest": "aa00000000000000000000000000000000000000000000000000000000000000"},
+            {"ep_id": "ep:root:1", "ordinal": 1, "normative": true,
+             "ep_digest": "bb00000000000000000000000000000000000000000000000000000000000000"}
+        ]);
+        compute_diff(&bytes(&a), &bytes(&b)).unwrap()
+    }
+
+    #[test]
+    fn test_one_line_and_summary_verbosity() {
+        let diff = ept_added_diff();
+        let one_line = render_human_summary_with(
+            &diff,
+            &SummaryOptions {
+                verbosity: SummaryVerbosity::OneLine,
+                ..Default::default()
+            },
+        );
+        // The fixture's recorded constraints digest is stale in both manifests
+        assert_eq!(
+            one_line,
+            "Changed (Breaking): 1 EP(s) added, 2 invariant violation(s)"
+        );
+
+        let summary = render_human_summary_with(
+            &diff,
+            &SummaryOptions {
+                verbosity: SummaryVerbosity::Summary,
+                ..Default::default()
+            },
+        );
+        assert!(summary.contains("### Changes\n\n- 1 EP(s) added\n"));
+        assert!(!summary.contains("Identity"));
+        assert!(!summary.contains("ep:root:1"));
+
+        let identical = compute_diff(&bytes(&base()), &bytes(&base())).unwrap();
+        let one_line = render_human_summary_with(
+            &identical,
+            &SummaryOptions {
+                verbosity: SummaryVerbosity::OneLine,
+                ..Default::default()
+            },
+        );
+        assert_eq!(one_line, "Identical: no semantic changes");
+    }
+
+    #[test]
+    fn test_catalog_localises_every_level() {
+        let catalog = MessageCatalog::from_value(&json!({
+            "locale": "de",
+            "messages": {
+                "classification.changed": "Geändert",
+                "severity.breaking": "Inkompatibel",
+                "count.eps_added": "{count} EP(s) hinzugefügt",
+                "section.ept": "EPT-Änderungen"
+            }
+        }))
+        .unwrap();
+        let diff = ept_added_diff();
+        let render = |verbosity| {
+            render_human_summary_with(
+                &diff,
+                &SummaryOptions {
+                    verbosity,
+                    catalog: catalog.clone(),
+                },
+            )
+        };
+        assert_eq!(
+            render(SummaryVerbosity::OneLine),
+            "Geändert (Inkompatibel): 1 EP(s) hinzugefügt, 2 invariant violation(s)"
+        );
+        assert!(render(SummaryVerbosity::Summary).contains("- 1 EP(s) hinzugefügt"));
+        let detailed = render(SummaryVerbosity::Detailed);
+        assert!(detailed.contains("### EPT-Änderungen"));
+        // Untranslated keys fall back to English
+        assert!(detailed.contains("**Added EPs** (1)"));
+    }
+
+    #[test]
+    fn test_verbosity_parse() {
+        assert_eq!(
+            "one-line".parse::<SummaryVerbosity>().unwrap(),
+            SummaryVerbosity::OneLine
+        );
+        assert_eq!(
+            "one_line".parse::<SummaryVerbosity>().unwrap(),
+            SummaryVerbosity::OneLine
+        );
+        let err = "verbose".parse::<SummaryVerbosity>().unwrap_err();
+        assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+    }
 }
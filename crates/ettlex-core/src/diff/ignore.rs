@@ -0,0 +1,202 @@
+//! Configurable ignore list for snapshot diffs.
+//!
+//! `created_at` is always suppressed by the engine. A [`DiffIgnoreList`]
+//! suppresses more: whole [`ChangeCategory`]s (e.g. every
+//! `non_normative_content_changed` change) and individual metadata or unknown
+//! top-level fields by name. Ignored changes are removed from the structured
+//! sections and from `severity_tags`, moved to `ignored_tags`, and no longer
+//! count towards the overall severity.
+//!
+//! The list is plain data:
+//!
+//! ```json
+//! { "categories": ["non_normative_content_changed", "coverage_changed"],
+//!   "fields": ["store_schema_version", "x_build_info"] }
+//! ```
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::diff::model::{DiffSeverity, SnapshotDiff};
+use crate::diff::severity::{ChangeCategory, SeverityTag};
+use crate::errors::{ExError, ExErrorKind};
+
+/// Changes to leave out of a snapshot diff.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DiffIgnoreList {
+    /// Change categories ignored wherever they occur.
+    pub categories: BTreeSet<ChangeCategory>,
+    /// Metadata and unknown top-level manifest fields ignored by name.
+    pub fields: BTreeSet<String>,
+}
+
+impl DiffIgnoreList {
+    /// Parse a JSON ignore list of the form `{ "categories": [...], "fields": [...] }`.
+    ///
+    /// # Errors
+    /// * `InvalidInput` - the document is not a valid ignore list.
+    pub fn from_json(bytes: &[u8]) -> Result<Self, ExError> {
+        serde_json::from_slice(bytes).map_err(invalid)
+    }
+
+    /// Parse an ignore list already held as a JSON value (e.g. a profile key).
+    ///
+    /// # Errors
+    /// * `InvalidInput` - the value is not a valid ignore list.
+    pub fn from_value(value: &serde_json::Value) -> Result<Self, ExError> {
+        Self::deserialize(value).map_err(invalid)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.categories.is_empty() && self.fields.is_empty()
+    }
+
+    /// True if `tag` is suppressed by this list.
+    pub fn ignores(&self, tag: &SeverityTag) -> bool {
+        self.categories.contains(&tag.category)
+            || (matches!(
+                tag.category,
+                ChangeCategory::MetadataChanged | ChangeCategory::UnknownFieldChanged
+            ) && self.fields.contains(&tag.subject))
+    }
+
+    /// Remove ignored changes from `diff` and recompute its severity.
+    ///
+    /// `classification` is left alone: it describes the digests, which still
+    /// differ.
+    pub fn apply(&self, diff: &mut SnapshotDiff) {
+        if self.is_empty() {
+            return;
+        }
+        let (ignored, kept): (Vec<SeverityTag>, Vec<SeverityTag>) =
+            std::mem::take(&mut diff.severity_tags)
+                .into_iter()
+                .partition(|tag| self.ignores(tag));
+        for tag in &ignored {
+            strip_change(diff, tag);
+        }
+        diff.severity = kept
+            .iter()
+            .map(|t| t.severity.clone())
+            .max()
+            .unwrap_or(DiffSeverity::None);
+        diff.severity_tags = kept;
+        diff.ignored_tags = ignored;
+    }
+}
+
+/// Remove the change described by `tag` from the structured sections.
+fn strip_change(diff: &mut SnapshotDiff, tag: &SeverityTag) {
+    let subject = tag.subject.as_str();
+    let drop = |items: &mut Vec<String>| items.retain(|s| s != subject);
+    match tag.category {
+        ChangeCategory::EptEntryAdded => drop(&mut diff.ept_changes.added_eps),
+        ChangeCategory::EptEntryRemoved => drop(&mut diff.ept_changes.removed_eps),
+        ChangeCategory::EptReordered => diff.ept_changes.ordering_changed = false,
+        ChangeCategory::NormativeContentChanged | ChangeCategory::NonNormativeContentChanged => {
+            drop(&mut diff.ep_content_changes.changed_eps);
+            if let Some(content_diffs) = &mut diff.content_diffs {
+                content_diffs.retain(|c| c.ep_id != subject);
+            }
+        }
+        ChangeCategory::NormativeFlagChanged => diff
+            .ep_content_changes
+            .normative_flips
+            .retain(|f| f.ep_id != subject),
+        ChangeCategory::ConstraintRefAdded => {
+            drop(&mut diff.constraint_changes.declared_ref_changes.added)
+        }
+        ChangeCategory::ConstraintRefRemoved => {
+            drop(&mut diff.constraint_changes.declared_ref_changes.removed)
+        }
+        ChangeCategory::ConstraintFamilyAdded
+        | ChangeCategory::ConstraintFamilyRemoved
+        | ChangeCategory::ConstraintFamilyChanged => {
+            diff.constraint_changes.family_changes.remove(subject);
+        }
+        ChangeCategory::ProjectionChanged => {
+            let projection = &mut diff.constraint_changes.abb_sbb_projection_changes;
+            drop(&mut projection.abb_added);
+            drop(&mut projection.abb_removed);
+            drop(&mut projection.sbb_added);
+            drop(&mut projection.sbb_removed);
+        }
+        ChangeCategory::ConstraintsDigestChanged => {
+            diff.constraint_changes.constraints_digest_change = None
+        }
+        ChangeCategory::CoverageChanged => diff.coverage_changes.changed = false,
+        ChangeCategory::ExceptionAdded => drop(&mut diff.exception_changes.added),
+        ChangeCategory::ExceptionRemoved => drop(&mut diff.exception_changes.removed),
+        ChangeCategory::MetadataChanged => {
+            diff.metadata_changes.changed_fields.remove(subject);
+        }
+        ChangeCategory::UnknownFieldChanged => {
+            drop(&mut diff.unknown_changes.added_fields);
+            drop(&mut diff.unknown_changes.removed_fields);
+            drop(&mut diff.unknown_changes.changed_fields);
+        }
+    }
+    let ept = &mut diff.ept_changes;
+    ept.changed = !ept.added_eps.is_empty() || !ept.removed_eps.is_empty() || ept.ordering_changed;
+}
+
+fn invalid(e: serde_json::Error) -> ExError {
+    ExError::new(ExErrorKind::InvalidInput)
+        .with_op("diff_ignore_list_parse")
+        .with_message(format!("invalid diff ignore list: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::engine::compute_diff;
+    use serde_json::json;
+
+    fn manifest_bytes(store_schema_version: &str, semantic: &str) -> Vec<u8> {
+        serde_json::to_vec(&json!({
+            "manifest_schema_version": 1,
+            "created_at": "2026-01-01T00:00:00Z",
+            "policy_ref": "policy/default@0",
+            "profile_ref": "profile/default@0",
+            "ept": [],
+            "constraints": {
+                "declared_refs": [], "families": {}, "applicable_abb": [],
+                "resolved_sbb": [], "resolution_evidence": [], "constraints_digest": "c"
+            },
+            "coverage": {},
+            "exceptions": [],
+            "root_ettle_id": "ettle:root",
+            "ept_digest": "e",
+            "manifest_digest": semantic,
+            "semantic_manifest_digest": semantic,
+            "store_schema_version": store_schema_version,
+            "x_build_info": semantic
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_ignored_fields_drop_metadata_and_unknown_changes() {
+        let mut diff =
+            compute_diff(&manifest_bytes("0001", "s1"), &manifest_bytes("0002", "s2")).unwrap();
+        assert_eq!(diff.severity, DiffSeverity::Informational);
+
+        let ignore =
+            DiffIgnoreList::from_json(br#"{"fields": ["store_schema_version", "x_build_info"]}"#)
+                .unwrap();
+        ignore.apply(&mut diff);
+        assert!(diff.metadata_changes.changed_fields.is_empty());
+        assert!(diff.unknown_changes.changed_fields.is_empty());
+        assert!(diff.severity_tags.is_empty());
+        assert_eq!(diff.ignored_tags.len(), 2);
+        assert_eq!(diff.severity, DiffSeverity::None);
+    }
+
+    #[test]
+    fn test_unknown_keys_rejected() {
+        let err = DiffIgnoreList::from_json(br#"{"eps": ["ep:a:0"]}"#).unwrap_err();
+        assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,253 @@
+//! Message catalogs for the human-readable diff summary.
+//!
+//! Every piece of text [`crate::diff::human_summary`] writes comes from a
+//! [`MessageCatalog`]. The built-in catalog is English; a front-end can load
+//! a translated or reworded one from JSON:
+//!
+//! ```json
+//! { "locale": "de",
+//!   "messages": { "title": "Snapshot-Vergleich",
+//!                 "count.eps_added": "{count} EP(s) hinzugefügt" } }
+//! ```
+//!
+//! Keys missing from a catalog fall back to English, so a partial catalog is
+//! valid. Unknown keys are rejected to catch typos. Messages may contain
+//! `{name}` placeholders; each key's English text shows which ones it takes.
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{ExError, ExErrorKind};
+
+/// Built-in English messages, by key.
+pub const ENGLISH: &[(&str, &str)] = &[
+    ("classification.changed", "Changed"),
+    ("classification.identical", "Identical"),
+    ("classification.no_semantic_change", "No Semantic Change"),
+    ("constraints.added_refs", "Added refs"),
+    ("constraints.family_added", "Family added"),
+    ("constraints.family_changed", "Family changed"),
+    ("constraints.family_removed", "Family removed"),
+    ("constraints.removed_refs", "Removed refs"),
+    (
+        "content_diff.unavailable",
+        "content unavailable (no blob for one of the digests)",
+    ),
+    ("count.constraints", "{count} constraint change(s)"),
+    ("count.coverage", "coverage changed"),
+    ("count.ep_content", "{count} EP(s) with changed content"),
+    ("count.eps_added", "{count} EP(s) added"),
+    ("count.eps_removed", "{count} EP(s) removed"),
+    ("count.ept_reordered", "EP order changed"),
+    ("count.exceptions", "{count} exception change(s)"),
+    ("count.invariant_violations", "{count} invariant violation(s)"),
+    ("count.metadata", "{count} metadata field(s) changed"),
+    ("count.none", "no changes listed"),
+    ("count.normative_flips", "{count} normative flag change(s)"),
+    ("count.unknown_fields", "{count} unknown field change(s)"),
+    ("coverage.changed", "Coverage metrics changed"),
+    ("diff3.absent", "absent"),
+    ("diff3.change", "{kind} `{subject}`: {base} → {value}"),
+    ("diff3.conflict", "{kind} `{subject}`: base {base}, A {a}, B {b}"),
+    ("diff3.identity", "Base `{base}`, A `{a}`, B `{b}`"),
+    ("diff3.kind.applicable_abb", "ABB"),
+    ("diff3.kind.constraint_family", "Constraint family"),
+    ("diff3.kind.constraint_ref", "Constraint ref"),
+    ("diff3.kind.coverage", "Coverage"),
+    ("diff3.kind.ept_entry", "EP"),
+    ("diff3.kind.ept_order", "EP order"),
+    ("diff3.kind.exception", "Exception"),
+    ("diff3.kind.metadata", "Metadata field"),
+    ("diff3.kind.resolved_sbb", "SBB"),
+    ("diff3.kind.unknown_field", "Unknown field"),
+    ("diff3.no_changes", "Neither side changed since the base."),
+    (
+        "diff3.one_line",
+        "{only_in_a} only in A, {only_in_b} only in B, {in_both} in both, {conflicting} conflicting",
+    ),
+    ("diff3.present", "present"),
+    ("diff3.section.conflicting", "⚠ Conflicts"),
+    ("diff3.section.in_both", "Changed Identically in Both"),
+    ("diff3.section.only_in_a", "Only in A"),
+    ("diff3.section.only_in_b", "Only in B"),
+    ("diff3.title", "Three-Way Snapshot Diff"),
+    ("digest_changed", "digest changed"),
+    ("ept.added_eps", "Added EPs"),
+    ("ept.ordering_changed", "Ordering changed"),
+    ("ept.removed_eps", "Removed EPs"),
+    ("exceptions.added", "Added"),
+    ("exceptions.removed", "Removed"),
+    ("identity.ept_digest", "EPT Digest"),
+    ("identity.manifest_digest", "Manifest Digest"),
+    ("identity.semantic_digest", "Semantic Digest"),
+    ("ignored", "{count} change(s) hidden by the diff ignore list."),
+    (
+        "invariant.constraints_digest_mismatch",
+        "Manifest {which}: constraints_digest mismatch (recorded `{recorded}`, computed `{computed}`)",
+    ),
+    ("label.classification", "Classification"),
+    ("label.severity", "Severity"),
+    ("label.topology", "Structure"),
+    ("no_semantic_changes", "No semantic changes detected."),
+    ("normative.false", "non-normative"),
+    ("normative.true", "normative"),
+    ("one_line.changed", "{classification} ({severity}): {changes}"),
+    ("one_line.unchanged", "{classification}: no semantic changes"),
+    ("section.changes", "Changes"),
+    ("section.constraints", "Constraint Changes"),
+    ("section.content_diffs", "EP Content Diffs"),
+    ("section.coverage", "Coverage Changes"),
+    ("section.ep_content", "EP Content Changes"),
+    ("section.ept", "EPT Changes"),
+    ("section.exceptions", "Exception Changes"),
+    ("section.identity", "Identity"),
+    ("section.invariants", "⚠ Invariant Violations"),
+    ("section.metadata", "Metadata Changes"),
+    ("section.normative_flips", "⚑ Normative Flag Changes"),
+    ("section.severity_breakdown", "Severity Breakdown"),
+    ("section.unknown_fields", "Unknown Field Changes"),
+    ("severity.breaking", "Breaking"),
+    ("severity.informational", "Informational"),
+    ("severity.none", "None"),
+    ("severity.semantic", "Semantic"),
+    ("severity_breakdown.change", "Change"),
+    ("severity_breakdown.subject", "Subject"),
+    ("title", "Snapshot Diff"),
+    ("topology.changed", "changed"),
+    ("topology.unchanged", "unchanged (content only)"),
+    ("unknown_fields.added", "Added fields"),
+    ("unknown_fields.changed", "Changed fields"),
+    ("unknown_fields.removed", "Removed fields"),
+];
+
+/// A locale plus message overrides on top of [`ENGLISH`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MessageCatalog {
+    locale: String,
+    messages: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CatalogDocument {
+    locale: String,
+    #[serde(default)]
+    messages: BTreeMap<String, String>,
+}
+
+impl Default for MessageCatalog {
+    fn default() -> Self {
+        Self::english()
+    }
+}
+
+impl MessageCatalog {
+    /// The built-in English catalog.
+    pub fn english() -> Self {
+        Self {
+            locale: "en".to_string(),
+            messages: BTreeMap::new(),
+        }
+    }
+
+    /// Parse a JSON catalog of the form `{ "locale": ..., "messages": {...} }`.
+    ///
+    /// # Errors
+    /// * `InvalidInput` - the document is malformed or names an unknown key.
+    pub fn from_json(bytes: &[u8]) -> Result<Self, ExError> {
+        let value: serde_json::Value = serde_json::from_slice(bytes).map_err(invalid)?;
+        Self::from_value(&value)
+    }
+
+    /// Parse a catalog already held as a JSON value.
+    ///
+    /// # Errors
+    /// * `InvalidInput` - the value is malformed or names an unknown key.
+    pub fn from_value(value: &serde_json::Value) -> Result<Self, ExError> {
+        let doc = CatalogDocument::deserialize(value).map_err(invalid)?;
+        if let Some(key) = doc.messages.keys().find(|k| english(k).is_none()) {
+            return Err(ExError::new(ExErrorKind::InvalidInput)
+                .with_op("message_catalog")
+                .with_entity_id(key.clone())
+                .with_message(format!("unknown message key '{}'", key)));
+        }
+        Ok(Self {
+            locale: doc.locale,
+            messages: doc.messages,
+        })
+    }
+
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// The message for `key`, falling back to English.
+    ///
+    /// An unknown key yields the key itself, so a renderer bug shows up in
+    /// the output instead of panicking.
+    pub fn message<'a>(&'a self, key: &'a str) -> &'a str {
+        self.messages
+            .get(key)
+            .map(String::as_str)
+            .or_else(|| english(key))
+            .unwrap_or(key)
+    }
+
+    /// The message for `key` with each `{name}` placeholder replaced.
+    pub fn format(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        let mut text = self.message(key).to_string();
+        for (name, value) in args {
+            text = text.replace(&format!("{{{}}}", name), &value.to_string());
+        }
+        text
+    }
+}
+
+fn english(key: &str) -> Option<&'static str> {
+    ENGLISH
+        .binary_search_by(|(k, _)| (*k).cmp(key))
+        .ok()
+        .map(|i| ENGLISH[i].1)
+}
+
+fn invalid(e: serde_json::Error) -> ExError {
+    ExError::new(ExErrorKind::InvalidInput)
+        .with_op("message_catalog")
+        .with_message(format!("invalid message catalog: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_english_keys_are_sorted_for_lookup() {
+        assert!(ENGLISH.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn test_partial_catalog_falls_back_to_english() {
+        let catalog = MessageCatalog::from_json(
+            r#"{"locale": "de", "messages": {"count.eps_added": "{count} EP(s) hinzugefügt"}}"#
+                .as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(catalog.locale(), "de");
+        assert_eq!(
+            catalog.format("count.eps_added", &[("count", &2)]),
+            "2 EP(s) hinzugefügt"
+        );
+        assert_eq!(catalog.message("title"), "Snapshot Diff");
+    }
+
+    #[test]
+    fn test_unknown_keys_are_rejected() {
+        let err = MessageCatalog::from_json(br#"{"locale": "de", "messages": {"titel": "x"}}"#)
+            .unwrap_err();
+        assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+        let err = MessageCatalog::from_json(br#"{"messages": {}}"#).unwrap_err();
+        assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+    }
+}
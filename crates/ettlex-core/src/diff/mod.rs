@@ -19,13 +19,49 @@
 //!   semantic changes.
 //! - **Additive manifest compatibility**: unknown future manifest fields are reported
 //!   only in `unknown_changes`, not as errors.
+//! - **Configurable suppression**: a [`ignore::DiffIgnoreList`] drops whole change
+//!   categories or named metadata fields, on top of the built-in `created_at` rule.
+//! - **Rule-driven severity**: every change is tagged with a severity from a
+//!   configurable [`severity::SeverityRules`] table; see [`compute_diff_with_rules`].
 //! - **Constraint-family agnosticism**: the diff operates on the constraints envelope
 //!   without knowledge of specific families.
+//! - **Large manifests**: each manifest is parsed once, without an intermediate JSON
+//!   tree for the EPT. Use [`engine::ParsedManifest`] with [`compute_diff_parsed`] to
+//!   reuse a parse across several comparisons.
+//! - **Audience-sized summaries**: [`human_summary`] renders one line, a per-section
+//!   summary or the detailed report, with text from a localisable
+//!   [`messages::MessageCatalog`].
+//! - **Three-way diff**: [`three_way::compute_diff3`] compares two snapshots against
+//!   their common base and sorts each change into only-in-A, only-in-B, in-both or
+//!   conflicting; [`render_diff3_summary`] renders the result.
+//! - **Content diffs**: with [`DiffOptions::include_content`], changed EPs' `why`/`what`/
+//!   `how` text is loaded from an [`EpContentSource`] and line-diffed into
+//!   `content_diffs`; see [`content`].
+//! - **Rendered view**: [`rendered`] turns each manifest into Markdown and diffs the
+//!   two renderings line by line, for reviewers who want to read the change.
 
+pub mod content;
 pub mod engine;
 pub mod human_summary;
+pub mod ignore;
+pub mod messages;
 pub mod model;
+pub mod rendered;
+pub mod severity;
+pub mod three_way;
 
-pub use engine::compute_diff;
-pub use human_summary::render_human_summary;
-pub use model::SnapshotDiff;
+pub use content::{add_content_diffs, DiffOptions, EpContent, EpContentSource, NoEpContent};
+pub use engine::{
+    compute_diff, compute_diff_parsed, compute_diff_with_options, compute_diff_with_rules,
+    ParsedManifest,
+};
+pub use human_summary::{
+    render_diff3_summary, render_diff3_summary_with, render_human_summary,
+    render_human_summary_with, SummaryOptions, SummaryVerbosity,
+};
+pub use ignore::DiffIgnoreList;
+pub use messages::MessageCatalog;
+pub use model::{SnapshotDiff, SnapshotDiff3};
+pub use rendered::{render_manifest_markdown, unified_line_diff};
+pub use severity::{ChangeCategory, SeverityRules, SeverityTag};
+pub use three_way::{compute_diff3, compute_diff3_parsed};
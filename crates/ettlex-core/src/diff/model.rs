@@ -6,6 +6,8 @@
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
+use crate::diff::severity::SeverityTag;
+
 /// The top-level structured diff between two snapshot manifests.
 ///
 /// `diff_schema_version` is always 1 for this implementation.
@@ -21,6 +23,13 @@ pub struct SnapshotDiff {
     pub classification: DiffClassification,
     /// Severity of the most significant change
     pub severity: DiffSeverity,
+    /// Per-change severity tags, in the order changes are reported
+    #[serde(default)]
+    pub severity_tags: Vec<SeverityTag>,
+    /// Changes suppressed by a [`DiffIgnoreList`](crate::diff::ignore::DiffIgnoreList);
+    /// not reflected in `severity` or the change sections
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignored_tags: Vec<SeverityTag>,
     /// Changes to the EPT structure (added/removed/reordered EPs)
     pub ept_changes: EptChanges,
     /// Changes to EP content digests (same EPs, different content)
@@ -37,6 +46,20 @@ pub struct SnapshotDiff {
     pub unknown_changes: UnknownChanges,
     /// Non-fatal invariant violations detected during diffing
     pub invariant_violations: Vec<InvariantViolationEntry>,
+    /// Line diffs of changed EPs' content; only present when requested via
+    /// [`DiffOptions`](crate::diff::content::DiffOptions)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_diffs: Option<Vec<EpContentDiff>>,
+}
+
+impl SnapshotDiff {
+    /// True if the overall severity is at or above `threshold`.
+    ///
+    /// Intended for CI gates, e.g. fail the build when
+    /// `diff.meets_threshold(&DiffSeverity::Breaking)`.
+    pub fn meets_threshold(&self, threshold: &DiffSeverity) -> bool {
+        self.severity != DiffSeverity::None && &self.severity >= threshold
+    }
 }
 
 /// Digest identity for both manifests being diffed.
@@ -54,6 +77,25 @@ pub struct DiffIdentity {
     pub b_semantic_manifest_digest: String,
     /// EPT digest of snapshot B
     pub b_ept_digest: String,
+    /// Topology digest of snapshot A, if its manifest records one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub a_topology_digest: Option<String>,
+    /// Topology digest of snapshot B, if its manifest records one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub b_topology_digest: Option<String>,
+}
+
+impl DiffIdentity {
+    /// Whether the tree structure differs between A and B.
+    ///
+    /// `Some(false)` means any change is content-only. `None` when either
+    /// manifest predates the topology digest.
+    pub fn topology_changed(&self) -> Option<bool> {
+        match (&self.a_topology_digest, &self.b_topology_digest) {
+            (Some(a), Some(b)) => Some(a != b),
+            _ => None,
+        }
+    }
 }
 
 /// High-level classification of the diff result.
@@ -80,6 +122,21 @@ pub enum DiffSeverity {
     Breaking,
 }
 
+/// Content-level diff of one EP listed in `EpContentChanges::changed_eps`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EpContentDiff {
+    /// EP identifier
+    pub ep_id: String,
+    /// `ep_digest` in snapshot A
+    pub old_digest: String,
+    /// `ep_digest` in snapshot B
+    pub new_digest: String,
+    /// False if either side's content could not be found
+    pub available: bool,
+    /// Unified line diff per changed field (`why`, `what`, `how`)
+    pub field_diffs: BTreeMap<String, String>,
+}
+
 /// Changes to the EPT structure.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct EptChanges {
@@ -98,6 +155,20 @@ pub struct EptChanges {
 pub struct EpContentChanges {
     /// EP IDs whose `ep_digest` changed between A and B
     pub changed_eps: Vec<String>,
+    /// EPs whose `normative` flag flipped, sorted by EP ID
+    #[serde(default)]
+    pub normative_flips: Vec<NormativeFlip>,
+}
+
+/// An EP whose `normative` flag differs between A and B.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NormativeFlip {
+    /// EP identifier
+    pub ep_id: String,
+    /// Flag in snapshot A
+    pub old: bool,
+    /// Flag in snapshot B
+    pub new: bool,
 }
 
 /// Changes to the constraints envelope.
@@ -227,3 +298,75 @@ pub enum InvariantViolationEntry {
         recorded: String,
     },
 }
+
+/// Three-way diff of two snapshots (A and B) against their common base.
+///
+/// Every item that differs from the base on either side appears in exactly
+/// one list, sorted by `(kind, subject)`. `diff3_schema_version` is always 1.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SnapshotDiff3 {
+    /// Schema version of this structure (always 1)
+    pub diff3_schema_version: u32,
+    /// Full manifest digest of the base snapshot
+    pub base_manifest_digest: String,
+    /// Full manifest digest of snapshot A
+    pub a_manifest_digest: String,
+    /// Full manifest digest of snapshot B
+    pub b_manifest_digest: String,
+    /// Items changed in A and left as in base by B
+    pub only_in_a: Vec<Diff3Change>,
+    /// Items changed in B and left as in base by A
+    pub only_in_b: Vec<Diff3Change>,
+    /// Items changed identically on both sides
+    pub in_both: Vec<Diff3Change>,
+    /// Items changed on both sides to different values
+    pub conflicting: Vec<Diff3Change>,
+}
+
+impl SnapshotDiff3 {
+    /// True if A and B cannot be reconciled without a decision.
+    pub fn has_conflicts(&self) -> bool {
+        !self.conflicting.is_empty()
+    }
+}
+
+/// The kind of manifest item a [`Diff3Change`] refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Diff3ItemKind {
+    /// An EPT entry, by EP ID (value: the whole entry)
+    EptEntry,
+    /// Relative order of the EPs present in all three manifests
+    EptOrder,
+    /// A declared constraint ref (value: `true` when present)
+    ConstraintRef,
+    /// A constraint family (value: its digest)
+    ConstraintFamily,
+    /// An applicable ABB projection entry (value: `true` when present)
+    ApplicableAbb,
+    /// A resolved SBB projection entry (value: `true` when present)
+    ResolvedSbb,
+    /// Coverage metrics
+    Coverage,
+    /// An exception entry (value: `true` when present)
+    Exception,
+    /// A manifest metadata field
+    Metadata,
+    /// An unknown (forward-compatible) manifest field
+    UnknownField,
+}
+
+/// One item's value in the base and on each side; `None` means absent.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Diff3Change {
+    /// What kind of item this is
+    pub kind: Diff3ItemKind,
+    /// The item (EP ID, constraint ref, family, field name, ...)
+    pub subject: String,
+    /// Value in the base snapshot
+    pub base: Option<serde_json::Value>,
+    /// Value in snapshot A
+    pub a: Option<serde_json::Value>,
+    /// Value in snapshot B
+    pub b: Option<serde_json::Value>,
+}
@@ -0,0 +1,215 @@
+//! Rendered (textual) snapshot diff.
+//!
+//! Renders each manifest to a stable Markdown view and compares the two
+//! renderings line by line. This complements the structured diff: it is what a
+//! reviewer reads, not what evaluators consume. `created_at` and
+//! `manifest_digest` are left out of the rendering so two snapshots of the
+//! same state render identically.
+
+use crate::snapshot::manifest::SnapshotManifest;
+
+/// Lines of unchanged context printed around each hunk.
+pub const DIFF_CONTEXT_LINES: usize = 3;
+
+/// Above this many `old × new` cells in the region that differs, the diff
+/// skips line matching and reports the whole region as replaced.
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+/// Render a manifest's EPT and constraints as Markdown.
+///
+/// The layout is one item per line so a line diff lines up with what changed.
+pub fn render_manifest_markdown(manifest: &SnapshotManifest) -> String {
+    let mut out = String::new();
+    out.push_str(&format!("# Snapshot of {}\n\n", manifest.root_ettle_id));
+    out.push_str(&format!("- Policy: {}\n", manifest.policy_ref));
+    out.push_str(&format!("- Profile: {}\n", manifest.profile_ref));
+    out.push_str(&format!(
+        "- Semantic digest: {}\n",
+        manifest.semantic_manifest_digest
+    ));
+
+    out.push_str("\n## EPT\n\n");
+    if manifest.ept.is_empty() {
+        out.push_str("_(empty)_\n");
+    }
+    for entry in &manifest.ept {
+        out.push_str(&format!(
+            "{}. {}{} ({})\n",
+            entry.ordinal,
+            entry.ep_id,
+            if entry.normative {
+                ""
+            } else {
+                " _(non-normative)_"
+            },
+            entry.ep_digest
+        ));
+    }
+
+    let constraints = &manifest.constraints;
+    out.push_str("\n## Constraints\n\n");
+    if constraints.declared_refs.is_empty() {
+        out.push_str("_(none declared)_\n");
+    }
+    for constraint_ref in &constraints.declared_refs {
+        out.push_str(&format!("- {}\n", constraint_ref));
+    }
+    for (family, data) in &constraints.families {
+        out.push_str(&format!("\n### Family {} ({:?})\n\n", family, data.status));
+        for active_ref in &data.active_refs {
+            out.push_str(&format!("- {}\n", active_ref));
+        }
+    }
+
+    if !manifest.exceptions.is_empty() {
+        out.push_str("\n## Exceptions\n\n");
+        for exception in &manifest.exceptions {
+            out.push_str(&format!("- {}\n", exception));
+        }
+    }
+    out
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    Keep,
+    Delete,
+    Insert,
+}
+
+/// Line-oriented unified diff of `a` against `b`.
+///
+/// Returns an empty string when the texts are identical. Hunk headers use the
+/// standard `@@ -start,len +start,len @@` form so the output can be fed to
+/// patch viewers.
+pub fn unified_line_diff(a: &str, b: &str, a_label: &str, b_label: &str) -> String {
+    let old: Vec<&str> = a.lines().collect();
+    let new: Vec<&str> = b.lines().collect();
+    let script = edit_script(&old, &new);
+    if script.iter().all(|e| *e == Edit::Keep) {
+        return String::new();
+    }
+
+    let mut out = format!("--- {}\n+++ {}\n", a_label, b_label);
+    // Position in `old`/`new` at the start of each edit step.
+    let mut positions = Vec::with_capacity(script.len());
+    let (mut i, mut j) = (0usize, 0usize);
+    for edit in &script {
+        positions.push((i, j));
+        match edit {
+            Edit::Keep => {
+                i += 1;
+                j += 1;
+            }
+            Edit::Delete => i += 1,
+            Edit::Insert => j += 1,
+        }
+    }
+
+    let mut k = 0;
+    while k < script.len() {
+        if script[k] == Edit::Keep {
+            k += 1;
+            continue;
+        }
+        // Extend the hunk while changes are within 2×context of each other.
+        let start = k.saturating_sub(DIFF_CONTEXT_LINES);
+        let mut end = k;
+        let mut keeps = 0;
+        while end < script.len() {
+            if script[end] == Edit::Keep {
+                keeps += 1;
+                if keeps > 2 * DIFF_CONTEXT_LINES {
+                    keeps -= 1;
+                    break;
+                }
+            } else {
+                keeps = 0;
+            }
+            end += 1;
+        }
+        let end = (end - keeps + DIFF_CONTEXT_LINES.min(keeps)).min(script.len());
+
+        let (old_start, new_start) = positions[start];
+        let old_len = script[start..end]
+            .iter()
+            .filter(|e| **e != Edit::Insert)
+            .count();
+        let new_len = script[start..end]
+            .iter()
+            .filter(|e| **e != Edit::Delete)
+            .count();
+        out.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(old_start, old_len),
+            hunk_range(new_start, new_len)
+        ));
+        for (edit, (oi, nj)) in script[start..end].iter().zip(&positions[start..end]) {
+            match edit {
+                Edit::Keep => out.push_str(&format!(" {}\n", old[*oi])),
+                Edit::Delete => out.push_str(&format!("-{}\n", old[*oi])),
+                Edit::Insert => out.push_str(&format!("+{}\n", new[*nj])),
+            }
+        }
+        k = end;
+    }
+    out
+}
+
+/// `start,len` with 1-based start, as in unified diff headers.
+fn hunk_range(start: usize, len: usize) -> String {
+    if len == 0 {
+        format!("{},0", start)
+    } else {
+        format!("{},{}", start + 1, len)
+    }
+}
+
+/// Minimal edit script via LCS over the region between common prefix and suffix.
+fn edit_script(old: &[&str], new: &[&str]) -> Vec<Edit> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut script = vec![Edit::Keep; prefix];
+    let (n, m) = (old_mid.len(), new_mid.len());
+    if n.saturating_mul(m) > MAX_LCS_CELLS {
+        script.extend(std::iter::repeat(Edit::Delete).take(n));
+        script.extend(std::iter::repeat(Edit::Insert).take(m));
+    } else {
+        // lcs[i][j] = LCS length of old_mid[i..] and new_mid[j..]
+        let width = m + 1;
+        let mut lcs = vec![0u32; (n + 1) * width];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i * width + j] = if old_mid[i] == new_mid[j] {
+                    lcs[(i + 1) * width + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            if i < n && j < m && old_mid[i] == new_mid[j] {
+                script.push(Edit::Keep);
+                i += 1;
+                j += 1;
+            } else if j == m || (i < n && lcs[(i + 1) * width + j] >= lcs[i * width + j + 1]) {
+                script.push(Edit::Delete);
+                i += 1;
+            } else {
+                script.push(Edit::Insert);
+                j += 1;
+            }
+        }
+    }
+    script.extend(std::iter::repeat(Edit::Keep).take(suffix));
+    script
+}
@@ -0,0 +1,247 @@
+//! Rule-driven severity classification for snapshot diffs.
+//!
+//! Every individual change found by the diff engine is assigned a
+//! [`ChangeCategory`]. A [`SeverityRules`] table maps each category to a
+//! [`DiffSeverity`], producing one [`SeverityTag`] per change. The diff's
+//! overall `severity` is the maximum over its tags, so CI gates can either
+//! threshold on the roll-up or inspect individual tags.
+//!
+//! Rules are plain data (`{ "constraint_ref_removed": "Breaking", ... }`);
+//! categories absent from a rules document keep their default severity.
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::diff::model::DiffSeverity;
+use crate::errors::{ExError, ExErrorKind};
+
+/// The kind of an individual change within a snapshot diff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeCategory {
+    /// An EP was added to the EPT.
+    EptEntryAdded,
+    /// An EP was removed from the EPT.
+    EptEntryRemoved,
+    /// The EPT contains the same EPs in a different order.
+    EptReordered,
+    /// Content of a normative EP changed.
+    NormativeContentChanged,
+    /// Content of a non-normative EP changed.
+    NonNormativeContentChanged,
+    /// An EP's normative flag flipped.
+    NormativeFlagChanged,
+    /// A declared constraint reference was added.
+    ConstraintRefAdded,
+    /// A declared constraint reference was removed.
+    ConstraintRefRemoved,
+    /// A constraint family appeared.
+    ConstraintFamilyAdded,
+    /// A constraint family disappeared.
+    ConstraintFamilyRemoved,
+    /// A constraint family's digest changed.
+    ConstraintFamilyChanged,
+    /// The ABB/SBB projection gained or lost an entry.
+    ProjectionChanged,
+    /// The constraints envelope digest changed.
+    ConstraintsDigestChanged,
+    /// Coverage metrics changed.
+    CoverageChanged,
+    /// An exception was added.
+    ExceptionAdded,
+    /// An exception was removed.
+    ExceptionRemoved,
+    /// A manifest metadata field changed.
+    MetadataChanged,
+    /// An unknown (forward-compatible) manifest field was added, removed or changed.
+    UnknownFieldChanged,
+}
+
+impl ChangeCategory {
+    /// Stable snake_case name, matching the serialized form.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChangeCategory::EptEntryAdded => "ept_entry_added",
+            ChangeCategory::EptEntryRemoved => "ept_entry_removed",
+            ChangeCategory::EptReordered => "ept_reordered",
+            ChangeCategory::NormativeContentChanged => "normative_content_changed",
+            ChangeCategory::NonNormativeContentChanged => "non_normative_content_changed",
+            ChangeCategory::NormativeFlagChanged => "normative_flag_changed",
+            ChangeCategory::ConstraintRefAdded => "constraint_ref_added",
+            ChangeCategory::ConstraintRefRemoved => "constraint_ref_removed",
+            ChangeCategory::ConstraintFamilyAdded => "constraint_family_added",
+            ChangeCategory::ConstraintFamilyRemoved => "constraint_family_removed",
+            ChangeCategory::ConstraintFamilyChanged => "constraint_family_changed",
+            ChangeCategory::ProjectionChanged => "projection_changed",
+            ChangeCategory::ConstraintsDigestChanged => "constraints_digest_changed",
+            ChangeCategory::CoverageChanged => "coverage_changed",
+            ChangeCategory::ExceptionAdded => "exception_added",
+            ChangeCategory::ExceptionRemoved => "exception_removed",
+            ChangeCategory::MetadataChanged => "metadata_changed",
+            ChangeCategory::UnknownFieldChanged => "unknown_field_changed",
+        }
+    }
+
+    /// Severity assigned when no rule overrides it.
+    pub fn default_severity(self) -> DiffSeverity {
+        match self {
+            ChangeCategory::EptEntryAdded
+            | ChangeCategory::EptEntryRemoved
+            | ChangeCategory::ConstraintRefRemoved => DiffSeverity::Breaking,
+            ChangeCategory::EptReordered
+            | ChangeCategory::NormativeContentChanged
+            | ChangeCategory::NormativeFlagChanged
+            | ChangeCategory::ConstraintRefAdded
+            | ChangeCategory::ConstraintFamilyAdded
+            | ChangeCategory::ConstraintFamilyRemoved
+            | ChangeCategory::ConstraintFamilyChanged
+            | ChangeCategory::ProjectionChanged
+            | ChangeCategory::ConstraintsDigestChanged => DiffSeverity::Semantic,
+            ChangeCategory::NonNormativeContentChanged
+            | ChangeCategory::CoverageChanged
+            | ChangeCategory::ExceptionAdded
+            | ChangeCategory::ExceptionRemoved
+            | ChangeCategory::MetadataChanged
+            | ChangeCategory::UnknownFieldChanged => DiffSeverity::Informational,
+        }
+    }
+}
+
+/// One classified change in a snapshot diff.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeverityTag {
+    /// What kind of change this is.
+    pub category: ChangeCategory,
+    /// The changed item (EP ID, constraint ref, family, field name, ...).
+    pub subject: String,
+    /// Severity assigned by the active rules.
+    pub severity: DiffSeverity,
+}
+
+/// Category → severity overrides applied when classifying a diff.
+///
+/// The default rules reproduce the engine's built-in classification.
+/// Overrides only need to list the categories they change.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SeverityRules {
+    overrides: BTreeMap<ChangeCategory, DiffSeverity>,
+}
+
+impl SeverityRules {
+    /// Rules with every category at its default severity.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Override the severity of one category.
+    pub fn with_rule(mut self, category: ChangeCategory, severity: DiffSeverity) -> Self {
+        self.overrides.insert(category, severity);
+        self
+    }
+
+    /// Parse a JSON rules document of the form `{ "<category>": "<Severity>" }`.
+    ///
+    /// # Errors
+    /// * `InvalidInput` - the document is not a valid rules object.
+    pub fn from_json(bytes: &[u8]) -> Result<Self, ExError> {
+        serde_json::from_slice(bytes).map_err(|e| {
+            ExError::new(ExErrorKind::InvalidInput)
+                .with_op("severity_rules_parse")
+                .with_message(format!("invalid severity rules: {}", e))
+        })
+    }
+
+    /// Severity assigned to `category` under these rules.
+    pub fn severity_for(&self, category: ChangeCategory) -> DiffSeverity {
+        self.overrides
+            .get(&category)
+            .cloned()
+            .unwrap_or_else(|| category.default_severity())
+    }
+
+    /// Build a tag for one change.
+    pub fn tag(&self, category: ChangeCategory, subject: impl Into<String>) -> SeverityTag {
+        SeverityTag {
+            category,
+            subject: subject.into(),
+            severity: self.severity_for(category),
+        }
+    }
+}
+
+impl DiffSeverity {
+    /// Lower-case name, as accepted by [`FromStr`].
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiffSeverity::None => "none",
+            DiffSeverity::Informational => "informational",
+            DiffSeverity::Semantic => "semantic",
+            DiffSeverity::Breaking => "breaking",
+        }
+    }
+}
+
+impl FromStr for DiffSeverity {
+    type Err = ExError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" => Ok(DiffSeverity::None),
+            "informational" | "minor" => Ok(DiffSeverity::Informational),
+            "semantic" => Ok(DiffSeverity::Semantic),
+            "breaking" | "major" => Ok(DiffSeverity::Breaking),
+            _ => Err(ExError::new(ExErrorKind::InvalidInput)
+                .with_op("diff_severity_parse")
+                .with_message(format!("unknown diff severity: {}", s))
+                .with_candidates(vec![
+                    "none".to_string(),
+                    "informational".to_string(),
+                    "semantic".to_string(),
+                    "breaking".to_string(),
+                ])),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_rules_fall_back_to_defaults() {
+        let rules = SeverityRules::from_json(br#"{"constraint_ref_removed": "Semantic"}"#).unwrap();
+        assert_eq!(
+            rules.severity_for(ChangeCategory::ConstraintRefRemoved),
+            DiffSeverity::Semantic
+        );
+        assert_eq!(
+            rules.severity_for(ChangeCategory::ConstraintRefAdded),
+            DiffSeverity::Semantic
+        );
+    }
+
+    #[test]
+    fn test_unknown_category_rejected() {
+        let err = SeverityRules::from_json(br#"{"nonsense": "Breaking"}"#).unwrap_err();
+        assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_severity_from_str_accepts_aliases() {
+        assert_eq!(
+            "MAJOR".parse::<DiffSeverity>().unwrap(),
+            DiffSeverity::Breaking
+        );
+        assert_eq!(
+            "minor".parse::<DiffSeverity>().unwrap(),
+            DiffSeverity::Informational
+        );
+        assert_eq!(
+            "bogus".parse::<DiffSeverity>().unwrap_err().kind(),
+            ExErrorKind::InvalidInput
+        );
+    }
+}
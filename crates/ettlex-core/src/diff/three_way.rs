@@ -0,0 +1,181 @@
+//! Three-way (merge-base aware) snapshot diff.
+//!
+//! [`compute_diff3`] compares two snapshots A and B that were both derived
+//! from a common base snapshot. Each manifest is flattened into items (EPT
+//! entries, declared constraint refs, family digests, projection entries,
+//! coverage, exceptions, metadata and unknown fields) and every item is
+//! classified by how each side moved it away from the base:
+//!
+//! - changed only in A, or only in B: safe to take from that side
+//! - changed identically in both: already reconciled
+//! - changed in both to different values: a conflict
+//!
+//! The EPT order is a single item covering the EPs present in all three
+//! manifests, so adding or removing an EP on one side does not also count as
+//! a reorder. The `constraints_digest` and the manifest digests are derived
+//! from the items above and are not compared.
+
+#![allow(clippy::result_large_err)]
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::Value;
+
+use crate::diff::model::{Diff3Change, Diff3ItemKind, SnapshotDiff3};
+use crate::errors::ExError;
+use crate::snapshot::parse::ParsedManifest;
+
+type Items = BTreeMap<(Diff3ItemKind, String), Value>;
+
+/// Compute a three-way diff of snapshots A and B against `base`.
+///
+/// # Errors
+///
+/// - `InvalidManifest` — any manifest fails UTF-8/JSON/schema validation
+/// - `MissingField` — a required field is absent from any manifest
+pub fn compute_diff3(
+    base_bytes: &[u8],
+    a_bytes: &[u8],
+    b_bytes: &[u8],
+) -> Result<SnapshotDiff3, ExError> {
+    let base = ParsedManifest::parse(base_bytes)?;
+    let a = ParsedManifest::parse(a_bytes)?;
+    let b = ParsedManifest::parse(b_bytes)?;
+    Ok(compute_diff3_parsed(&base, &a, &b))
+}
+
+/// Compute a three-way diff between pre-parsed manifests.
+pub fn compute_diff3_parsed(
+    base: &ParsedManifest,
+    a: &ParsedManifest,
+    b: &ParsedManifest,
+) -> SnapshotDiff3 {
+    let common_eps = common_ep_ids(&[base, a, b]);
+    let base_items = items(base, &common_eps);
+    let a_items = items(a, &common_eps);
+    let b_items = items(b, &common_eps);
+
+    let keys: BTreeSet<&(Diff3ItemKind, String)> = base_items
+        .keys()
+        .chain(a_items.keys())
+        .chain(b_items.keys())
+        .collect();
+
+    let mut diff = SnapshotDiff3 {
+        diff3_schema_version: 1,
+        base_manifest_digest: base.manifest.manifest_digest.clone(),
+        a_manifest_digest: a.manifest.manifest_digest.clone(),
+        b_manifest_digest: b.manifest.manifest_digest.clone(),
+        only_in_a: Vec::new(),
+        only_in_b: Vec::new(),
+        in_both: Vec::new(),
+        conflicting: Vec::new(),
+    };
+    for key in keys {
+        let base_value = base_items.get(key);
+        let a_value = a_items.get(key);
+        let b_value = b_items.get(key);
+        let target = match (a_value != base_value, b_value != base_value) {
+            (false, false) => continue,
+            (true, false) => &mut diff.only_in_a,
+            (false, true) => &mut diff.only_in_b,
+            (true, true) if a_value == b_value => &mut diff.in_both,
+            (true, true) => &mut diff.conflicting,
+        };
+        target.push(Diff3Change {
+            kind: key.0,
+            subject: key.1.clone(),
+            base: base_value.cloned(),
+            a: a_value.cloned(),
+            b: b_value.cloned(),
+        });
+    }
+    diff
+}
+
+/// EP IDs present in every manifest.
+fn common_ep_ids(manifests: &[&ParsedManifest]) -> BTreeSet<String> {
+    let mut sets = manifests.iter().map(|m| {
+        m.manifest
+            .ept
+            .iter()
+            .map(|e| e.ep_id.clone())
+            .collect::<BTreeSet<_>>()
+    });
+    let first = sets.next().unwrap_or_default();
+    sets.fold(first, |acc, set| acc.intersection(&set).cloned().collect())
+}
+
+/// Flatten a manifest into comparable items.
+fn items(parsed: &ParsedManifest, common_eps: &BTreeSet<String>) -> Items {
+    let m = &parsed.manifest;
+    let mut items = Items::new();
+    let mut put = |kind: Diff3ItemKind, subject: &str, value: Value| {
+        items.insert((kind, subject.to_string()), value);
+    };
+
+    for entry in &m.ept {
+        put(
+            Diff3ItemKind::EptEntry,
+            &entry.ep_id,
+            serde_json::to_value(entry).unwrap_or(Value::Null),
+        );
+    }
+    let order: Vec<Value> = m
+        .ept
+        .iter()
+        .filter(|e| common_eps.contains(&e.ep_id))
+        .map(|e| Value::String(e.ep_id.clone()))
+        .collect();
+    put(Diff3ItemKind::EptOrder, "ept", Value::Array(order));
+
+    let env = &m.constraints;
+    for r in &env.declared_refs {
+        put(Diff3ItemKind::ConstraintRef, r, Value::Bool(true));
+    }
+    for (family, fc) in &env.families {
+        put(
+            Diff3ItemKind::ConstraintFamily,
+            family,
+            Value::String(fc.digest.clone()),
+        );
+    }
+    for abb in &env.applicable_abb {
+        put(Diff3ItemKind::ApplicableAbb, abb, Value::Bool(true));
+    }
+    for sbb in &env.resolved_sbb {
+        put(Diff3ItemKind::ResolvedSbb, sbb, Value::Bool(true));
+    }
+
+    put(Diff3ItemKind::Coverage, "coverage", m.coverage.clone());
+    for exc in &m.exceptions {
+        put(Diff3ItemKind::Exception, exc, Value::Bool(true));
+    }
+
+    // Same metadata fields as the two-way diff
+    put(
+        Diff3ItemKind::Metadata,
+        "policy_ref",
+        Value::String(m.policy_ref.clone()),
+    );
+    put(
+        Diff3ItemKind::Metadata,
+        "profile_ref",
+        Value::String(m.profile_ref.clone()),
+    );
+    put(
+        Diff3ItemKind::Metadata,
+        "store_schema_version",
+        Value::String(m.store_schema_version.clone()),
+    );
+    put(
+        Diff3ItemKind::Metadata,
+        "manifest_schema_version",
+        Value::Number(m.manifest_schema_version.into()),
+    );
+
+    for (field, value) in &parsed.unknown_fields {
+        put(Diff3ItemKind::UnknownField, field, value.clone());
+    }
+    items
+}
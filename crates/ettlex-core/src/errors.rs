@@ -3,7 +3,10 @@
 //! All error types are now defined in `ettlex-errors` and re-exported here
 //! for backward compatibility within the crate.
 
-pub use ettlex_errors::{ExError, ExErrorKind, Result};
+pub use ettlex_errors::{
+    transport_mapping, transport_table, ErrorCategory, ExError, ExErrorKind, Result,
+    TransportMapping,
+};
 
 #[cfg(test)]
 mod tests {
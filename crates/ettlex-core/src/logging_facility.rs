@@ -7,5 +7,5 @@ pub mod macros;
 pub mod test_capture;
 
 // Re-export init function and Profile from ettlex-logging
-pub use ettlex_logging::{init, Profile};
+pub use ettlex_logging::{init, op_outcome, slow_op, Profile};
 pub use test_capture::{init_test_capture, CapturedEvent, TestCapture};
@@ -96,3 +96,79 @@ macro_rules! log_op_error {
         );
     }};
 }
+
+/// Record an operation's latency and warn if it exceeded its slow threshold
+///
+/// The warning carries the op, duration, threshold and any extra fields
+/// (entity ids, row counts). Nothing is logged when no threshold applies.
+///
+/// # Example
+///
+/// ```
+/// # use ettlex_core::log_op_slow;
+/// log_op_slow!("ettle_list", duration_ms = 1500u64, row_count = 100u64);
+/// ```
+#[macro_export]
+macro_rules! log_op_slow {
+    ($op:expr, duration_ms = $duration:expr) => {{
+        let duration: u64 = $duration;
+        if let Some(threshold) = $crate::logging_facility::slow_op::observe_op_latency($op, duration) {
+            tracing::warn!(
+                component = module_path!(),
+                op = $op,
+                event = ettlex_core_types::schema::EVENT_SLOW,
+                duration_ms = duration,
+                threshold_ms = threshold,
+            );
+        }
+    }};
+    ($op:expr, duration_ms = $duration:expr, $($field:tt)*) => {{
+        let duration: u64 = $duration;
+        if let Some(threshold) = $crate::logging_facility::slow_op::observe_op_latency($op, duration) {
+            tracing::warn!(
+                component = module_path!(),
+                op = $op,
+                event = ettlex_core_types::schema::EVENT_SLOW,
+                duration_ms = duration,
+                threshold_ms = threshold,
+                $($field)*
+            );
+        }
+    }};
+}
+
+/// Count an operation's outcome and log it
+///
+/// `outcome` is a short label (`committed`, `routed`, ...) that is also
+/// counted per op; extra fields are logged alongside it.
+///
+/// # Example
+///
+/// ```
+/// # use ettlex_core::log_op_outcome;
+/// log_op_outcome!("snapshot_commit", outcome = "committed", leaf_ep_id = "ep:a");
+/// ```
+#[macro_export]
+macro_rules! log_op_outcome {
+    ($op:expr, outcome = $outcome:expr) => {{
+        let outcome: &str = $outcome;
+        $crate::logging_facility::op_outcome::record_op_outcome($op, outcome);
+        tracing::info!(
+            component = module_path!(),
+            op = $op,
+            event = ettlex_core_types::schema::EVENT_OUTCOME,
+            outcome = outcome,
+        );
+    }};
+    ($op:expr, outcome = $outcome:expr, $($field:tt)*) => {{
+        let outcome: &str = $outcome;
+        $crate::logging_facility::op_outcome::record_op_outcome($op, outcome);
+        tracing::info!(
+            component = module_path!(),
+            op = $op,
+            event = ettlex_core_types::schema::EVENT_OUTCOME,
+            outcome = outcome,
+            $($field)*
+        );
+    }};
+}
@@ -0,0 +1,94 @@
+//! Declared format of an Ettle's why/what/how content.
+//!
+//! Renderers emit Markdown. Markdown content is inlined as-is; other formats
+//! are placed in a fenced code block tagged with the format so they survive
+//! export without being misparsed.
+
+use crate::errors::{ExError, ExErrorKind, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Format of an Ettle's content fields.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentFormat {
+    #[default]
+    Markdown,
+    Asciidoc,
+    Plain,
+}
+
+impl ContentFormat {
+    /// Every format, in declaration order.
+    pub const ALL: [ContentFormat; 3] = [Self::Markdown, Self::Asciidoc, Self::Plain];
+
+    /// Stored and serialised name.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Markdown => "markdown",
+            Self::Asciidoc => "asciidoc",
+            Self::Plain => "plain",
+        }
+    }
+
+    /// `true` for the default format, which manifests omit.
+    pub fn is_markdown(&self) -> bool {
+        *self == Self::Markdown
+    }
+
+    /// Info string for a fenced code block, or `None` when the content can
+    /// be inlined into Markdown directly.
+    pub fn fence_language(&self) -> Option<&'static str> {
+        match self {
+            Self::Markdown => None,
+            Self::Asciidoc => Some("asciidoc"),
+            Self::Plain => Some("text"),
+        }
+    }
+}
+
+impl fmt::Display for ContentFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ContentFormat {
+    type Err = ExError;
+
+    /// Parse a stored name. Unknown names are `InvalidInput`.
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|f| f.as_str() == s)
+            .ok_or_else(|| {
+                ExError::new(ExErrorKind::InvalidInput)
+                    .with_op("content_format")
+                    .with_message(format!(
+                        "unknown content_format '{}' (expected markdown, asciidoc or plain)",
+                        s
+                    ))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_format_round_trip() {
+        for format in ContentFormat::ALL {
+            assert_eq!(format.as_str().parse::<ContentFormat>().unwrap(), format);
+            assert_eq!(
+                serde_json::to_value(format).unwrap(),
+                serde_json::json!(format.as_str())
+            );
+        }
+        let err = "rst".parse::<ContentFormat>().unwrap_err();
+        assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+    }
+}
@@ -138,6 +138,12 @@ impl Decision {
         self.updated_at = Utc::now();
     }
 
+    /// Clear the tombstone, making the decision active again
+    pub fn restore(&mut self) {
+        self.tombstoned_at = None;
+        self.updated_at = Utc::now();
+    }
+
     /// Update decision fields
     #[allow(clippy::too_many_arguments)]
     pub fn update(
@@ -1,9 +1,12 @@
 pub mod constraint;
+pub mod content_format;
 pub mod decision;
 pub mod ettle;
 pub mod metadata;
+pub mod slug;
 
 pub use constraint::Constraint;
+pub use content_format::ContentFormat;
 pub use decision::{Decision, DecisionEvidenceItem, DecisionLink};
 pub use ettle::Ettle;
 pub use metadata::Metadata;
@@ -0,0 +1,57 @@
+//! Human-friendly slugs.
+//!
+//! A slug is lowercase ASCII `[a-z0-9-]`, with runs of any other characters
+//! collapsed to a single `-` and no leading or trailing `-`. Slugs are used
+//! as stable, typeable aliases for generated identifiers.
+
+/// Maximum length of a derived slug (before any collision suffix).
+pub const MAX_SLUG_LEN: usize = 64;
+
+/// Derive a slug from free text. Returns an empty string if `text` has no
+/// ASCII alphanumeric characters.
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::with_capacity(text.len().min(MAX_SLUG_LEN));
+    for c in text.chars() {
+        if slug.len() >= MAX_SLUG_LEN {
+            break;
+        }
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// True if `s` is a well-formed slug.
+pub fn is_slug(s: &str) -> bool {
+    !s.is_empty()
+        && !s.starts_with('-')
+        && !s.ends_with('-')
+        && !s.contains("--")
+        && s.chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Payments API (v2)"), "payments-api-v2");
+        assert_eq!(slugify("  --Hello__World--  "), "hello-world");
+        assert_eq!(slugify("日本"), "");
+        assert_eq!(slugify(&"a".repeat(100)).len(), MAX_SLUG_LEN);
+    }
+
+    #[test]
+    fn test_is_slug() {
+        assert!(is_slug("payments-api-2"));
+        assert!(!is_slug("Payments"));
+        assert!(!is_slug("a--b"));
+        assert!(!is_slug("-a"));
+        assert!(!is_slug(""));
+    }
+}
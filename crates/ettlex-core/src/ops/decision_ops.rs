@@ -258,6 +258,32 @@ pub fn tombstone_decision(store: &mut Store, decision_id: &str) -> Result<()> {
     Ok(())
 }
 
+/// Restore a tombstoned decision
+///
+/// Clears the decision's tombstoned_at timestamp. Links and evidence are
+/// untouched, so the decision reappears with its prior associations.
+///
+/// # Errors
+///
+/// Returns `NotFound` if the decision doesn't exist,
+/// or `InvalidInput` if it is not tombstoned.
+pub fn restore_decision(store: &mut Store, decision_id: &str) -> Result<()> {
+    let decision = store.decisions.get_mut(decision_id).ok_or_else(|| {
+        ExError::new(ExErrorKind::NotFound)
+            .with_entity_id(decision_id.to_string())
+            .with_message("Decision not found")
+    })?;
+
+    if !decision.is_tombstoned() {
+        return Err(ExError::new(ExErrorKind::InvalidInput)
+            .with_entity_id(decision_id.to_string())
+            .with_message("Decision is not tombstoned"));
+    }
+
+    decision.restore();
+    Ok(())
+}
+
 /// Attach a decision to a target (EP, Ettle, Constraint, or Decision)
 ///
 /// Creates a link record with the specified relation kind and ordinal.
@@ -267,7 +293,9 @@ pub fn tombstone_decision(store: &mut Store, decision_id: &str) -> Result<()> {
 ///
 /// Returns `DecisionNotFound` if the decision doesn't exist,
 /// `DecisionTombstoned` if the decision was tombstoned,
-/// `NotFound` if the target doesn't exist,
+/// `NotFound` if the target doesn't exist (a `"constraint"` target may be a
+/// constraint record or a constraint Ettle),
+/// `Deleted` if a constraint record target was deleted,
 /// `InvalidTargetKind` if the target_kind is not allowed,
 /// or `DuplicateDecisionLink` if the link already exists.
 pub fn attach_decision_to_target(
@@ -306,9 +334,14 @@ pub fn attach_decision_to_target(
         "ettle" => {
             store.get_ettle(&target_id)?;
         }
-        "constraint" => {
-            store.get_constraint(&target_id)?;
-        }
+        // A constraint is either a legacy constraint record or a constraint Ettle
+        "constraint" => match store.get_constraint(&target_id) {
+            Ok(_) => {}
+            Err(e) if e.kind() == ExErrorKind::NotFound => {
+                store.get_ettle(&target_id)?;
+            }
+            Err(e) => return Err(e),
+        },
         "decision" => {
             store.get_decision(&target_id)?;
         }
@@ -529,4 +562,33 @@ mod tests {
         // Old decision should still exist (not tombstoned)
         assert!(store.get_decision(&old_id).is_ok());
     }
+
+    #[test]
+    fn test_restore_decision() {
+        let mut store = Store::new();
+        let decision_id = create_decision(
+            &mut store,
+            None,
+            "Restorable".to_string(),
+            None,
+            "text".to_string(),
+            "rationale".to_string(),
+            None,
+            None,
+            "none".to_string(),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let err = restore_decision(&mut store, &decision_id).unwrap_err();
+        assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+
+        tombstone_decision(&mut store, &decision_id).unwrap();
+        assert!(store.get_decision(&decision_id).is_err());
+
+        restore_decision(&mut store, &decision_id).unwrap();
+        assert!(store.get_decision(&decision_id).is_ok());
+    }
 }
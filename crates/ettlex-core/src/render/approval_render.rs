@@ -0,0 +1,180 @@
+//! Approval review packet render.
+//!
+//! Turns a routed approval request into a self-contained Markdown document a
+//! human reviewer can read offline: why the request was raised, what the
+//! candidates are, where they sit in the refinement tree, and how to answer.
+//! The renderer is pure; callers hydrate an [`ApprovalPacket`] from the store.
+
+use serde::{Deserialize, Serialize};
+
+/// Maximum characters of a candidate's content shown in its summary.
+const SUMMARY_MAX_CHARS: usize = 280;
+
+/// A candidate offered for selection in an approval request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApprovalCandidate {
+    /// Candidate ID as recorded in the request payload.
+    pub id: String,
+    /// Title of the candidate Ettle, or `None` if it could not be resolved.
+    pub title: Option<String>,
+    /// Candidate content (`what`, falling back to `why`), untruncated.
+    pub summary: Option<String>,
+    /// Refinement path from the tree root down to the candidate (inclusive).
+    /// Empty if the candidate could not be resolved.
+    pub path: Vec<String>,
+    /// True if the candidate Ettle is tombstoned.
+    pub tombstoned: bool,
+}
+
+/// Everything needed to render a review packet for one approval request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApprovalPacket {
+    /// Approval token (UUIDv7).
+    pub approval_token: String,
+    /// Reason the request was routed (e.g. `AmbiguousSelection`).
+    pub reason_code: String,
+    /// Request status (`pending`, `approved`, `rejected`).
+    pub status: String,
+    /// Creation timestamp, milliseconds since epoch.
+    pub created_at: i64,
+    /// CAS digest of the full request payload blob.
+    pub request_digest: String,
+    /// Deterministic digest over `reason_code` + sorted candidates.
+    pub semantic_request_digest: String,
+    /// Candidates in the order recorded in the request payload.
+    pub candidates: Vec<ApprovalCandidate>,
+}
+
+/// Render an approval request as a Markdown review packet.
+///
+/// Output is deterministic for a given packet.
+pub fn render_approval_packet(packet: &ApprovalPacket) -> String {
+    let mut out = String::new();
+
+    out.push_str(&format!(
+        "# Approval request `{}`\n\n",
+        packet.approval_token
+    ));
+    if packet.status != "pending" {
+        out.push_str(&format!(
+            "> **Note:** this request is already `{}`; the packet is for reference only.\n\n",
+            packet.status
+        ));
+    }
+
+    out.push_str("## Context\n\n");
+    out.push_str(&format!("- **Reason:** `{}`\n", packet.reason_code));
+    out.push_str(&format!("- **Status:** `{}`\n", packet.status));
+    out.push_str(&format!("- **Created at (ms):** {}\n", packet.created_at));
+    out.push_str(&format!(
+        "- **Request digest:** `{}`\n",
+        packet.request_digest
+    ));
+    out.push_str(&format!(
+        "- **Semantic digest:** `{}`\n\n",
+        packet.semantic_request_digest
+    ));
+    out.push_str(&format!(
+        "Resolution could not pick a single candidate automatically ({} candidates).\n\n",
+        packet.candidates.len()
+    ));
+
+    out.push_str("## Candidates\n\n");
+    if packet.candidates.is_empty() {
+        out.push_str("*(no candidates recorded)*\n\n");
+    }
+    for (i, candidate) in packet.candidates.iter().enumerate() {
+        let title = candidate.title.as_deref().unwrap_or("(unresolved)");
+        out.push_str(&format!(
+            "### {}. {} — `{}`\n\n",
+            i + 1,
+            title,
+            candidate.id
+        ));
+        if candidate.tombstoned {
+            out.push_str("> **Warning:** this candidate is tombstoned.\n\n");
+        }
+        if candidate.path.is_empty() {
+            out.push_str("- **Path:** *(unknown)*\n");
+        } else {
+            out.push_str(&format!("- **Path:** {}\n", candidate.path.join(" › ")));
+        }
+        match candidate.summary.as_deref().map(str::trim) {
+            Some(s) if !s.is_empty() => {
+                out.push_str(&format!("- **Summary:** {}\n\n", truncate(s)));
+            }
+            _ => out.push_str("- **Summary:** *(empty)*\n\n"),
+        }
+    }
+
+    out.push_str("## How to respond\n\n");
+    out.push_str(&format!(
+        "Reply with the approval token `{}` and the ID of exactly one candidate \
+         above, or reject the request if none is acceptable.\n",
+        packet.approval_token
+    ));
+
+    out
+}
+
+fn truncate(s: &str) -> String {
+    let flat = s.split_whitespace().collect::<Vec<_>>().join(" ");
+    if flat.chars().count() <= SUMMARY_MAX_CHARS {
+        return flat;
+    }
+    let cut: String = flat.chars().take(SUMMARY_MAX_CHARS).collect();
+    format!("{}…", cut.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet() -> ApprovalPacket {
+        ApprovalPacket {
+            approval_token: "tok-1".to_string(),
+            reason_code: "AmbiguousSelection".to_string(),
+            status: "pending".to_string(),
+            created_at: 42,
+            request_digest: "rd".to_string(),
+            semantic_request_digest: "sd".to_string(),
+            candidates: vec![
+                ApprovalCandidate {
+                    id: "ettle:a".to_string(),
+                    title: Some("Alpha".to_string()),
+                    summary: Some("x".repeat(400)),
+                    path: vec!["Root".to_string(), "Alpha".to_string()],
+                    tombstoned: false,
+                },
+                ApprovalCandidate {
+                    id: "ettle:missing".to_string(),
+                    title: None,
+                    summary: None,
+                    path: vec![],
+                    tombstoned: false,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_render_approval_packet_sections() {
+        let out = render_approval_packet(&packet());
+        assert!(out.starts_with("# Approval request `tok-1`"));
+        assert!(out.contains("`AmbiguousSelection`"));
+        assert!(out.contains("### 1. Alpha — `ettle:a`"));
+        assert!(out.contains("Root › Alpha"));
+        assert!(out.contains("### 2. (unresolved) — `ettle:missing`"));
+        assert!(out.contains("## How to respond"));
+        assert!(!out.contains("reference only"));
+        // Summary is truncated
+        assert!(out.contains(&format!("{}…", "x".repeat(SUMMARY_MAX_CHARS))));
+    }
+
+    #[test]
+    fn test_render_approval_packet_non_pending_note() {
+        let mut p = packet();
+        p.status = "approved".to_string();
+        assert!(render_approval_packet(&p).contains("reference only"));
+    }
+}
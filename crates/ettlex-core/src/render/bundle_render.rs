@@ -5,6 +5,7 @@
 
 use crate::errors::{ExError, ExErrorKind, Result};
 use crate::ops::Store;
+use crate::render::template::{resolve_template, TemplateMode, TemplateVariables};
 
 /// Render a leaf bundle to Markdown — RETIRED in Slice 03.
 ///
@@ -24,3 +25,19 @@ pub fn render_leaf_bundle(
              Re-specify against Ettle/Relation model.",
     ))
 }
+
+/// Render a leaf bundle to Markdown, resolving `{{variables}}` in the output.
+///
+/// # Errors
+/// * `NotImplemented` - Bundle render is retired in Slice 03
+/// * `UnresolvedVariable` - In `Strict` mode, if a placeholder has no binding
+pub fn render_leaf_bundle_with_variables(
+    store: &Store,
+    leaf_id: &str,
+    leaf_ep_ordinal: Option<u32>,
+    vars: &TemplateVariables,
+    mode: TemplateMode,
+) -> Result<String> {
+    let output = render_leaf_bundle(store, leaf_id, leaf_ep_ordinal)?;
+    resolve_template(&output, vars, mode).map_err(|e| e.with_entity_id(leaf_id))
+}
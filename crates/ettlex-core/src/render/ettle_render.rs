@@ -2,6 +2,7 @@
 
 use crate::errors::Result;
 use crate::ops::Store;
+use crate::render::template::{resolve_template, TemplateMode, TemplateVariables};
 
 /// Render an Ettle to Markdown
 ///
@@ -29,6 +30,21 @@ pub fn render_ettle(store: &Store, ettle_id: &str) -> Result<String> {
     Ok(output)
 }
 
+/// Render an Ettle to Markdown, resolving `{{variables}}` in the output.
+///
+/// # Errors
+/// * `NotFound` - If Ettle doesn't exist
+/// * `UnresolvedVariable` - In `Strict` mode, if a placeholder has no binding
+pub fn render_ettle_with_variables(
+    store: &Store,
+    ettle_id: &str,
+    vars: &TemplateVariables,
+    mode: TemplateMode,
+) -> Result<String> {
+    let output = render_ettle(store, ettle_id)?;
+    resolve_template(&output, vars, mode).map_err(|e| e.with_entity_id(ettle_id))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -43,4 +59,26 @@ mod tests {
         let output = render_ettle(&store, "ettle-1").unwrap();
         assert!(output.contains("# Test Ettle"));
     }
+
+    #[test]
+    fn test_render_ettle_with_variables() {
+        let mut store = Store::new();
+        let ettle = Ettle::new("ettle-1".to_string(), "Service {{env}}".to_string());
+        store.insert_ettle(ettle);
+
+        let mut vars = TemplateVariables::new();
+        vars.insert("env".to_string(), "prod".to_string());
+        let output =
+            render_ettle_with_variables(&store, "ettle-1", &vars, TemplateMode::Strict).unwrap();
+        assert!(output.contains("# Service prod"));
+
+        let err = render_ettle_with_variables(
+            &store,
+            "ettle-1",
+            &TemplateVariables::new(),
+            TemplateMode::Strict,
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), crate::errors::ExErrorKind::UnresolvedVariable);
+    }
 }
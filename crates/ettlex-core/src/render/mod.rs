@@ -1,5 +1,15 @@
+pub mod approval_render;
 pub mod bundle_render;
 pub mod ettle_render;
+pub mod report_render;
+pub mod template;
+pub mod tree_render;
 
-pub use bundle_render::render_leaf_bundle;
-pub use ettle_render::render_ettle;
+pub use approval_render::{render_approval_packet, ApprovalCandidate, ApprovalPacket};
+pub use bundle_render::{render_leaf_bundle, render_leaf_bundle_with_variables};
+pub use ettle_render::{render_ettle, render_ettle_with_variables};
+pub use report_render::{
+    render_architecture_report_html, render_architecture_report_markdown, ArchitectureReport,
+};
+pub use template::{resolve_template, TemplateMode, TemplateVariables};
+pub use tree_render::{render_tree_markdown, RenderTreeNode, TreeRenderOptions};
@@ -0,0 +1,406 @@
+//! Architecture overview report render.
+//!
+//! A "state of the architecture" document combining tree analytics, recent
+//! snapshot history, open decisions, constraint coverage and pending
+//! approvals. The engine's `ArchitectureReport` query assembles an
+//! [`ArchitectureReport`]; the functions here render it as Markdown or as a
+//! standalone HTML page. Rendering is pure and deterministic.
+
+use std::collections::BTreeMap;
+
+/// Structural statistics over the Ettle graph.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TreeAnalytics {
+    /// Ettles that are neither tombstoned nor archived.
+    pub active_ettles: u64,
+    /// Tombstoned Ettles.
+    pub tombstoned_ettles: u64,
+    /// Archived (non-tombstoned) Ettles.
+    pub archived_ettles: u64,
+    /// Active Ettles with no active incoming `refinement` relation.
+    pub root_count: u64,
+    /// Active roots in the root registry.
+    pub registered_roots: u64,
+    /// Parentless, non-constraint active Ettles missing from the root
+    /// registry — often a mistyped parent in an import.
+    pub unregistered_roots: u64,
+    /// Active Ettles with no active outgoing `refinement` relation.
+    pub leaf_count: u64,
+    /// Longest root-to-leaf refinement chain, counted in Ettles.
+    pub max_depth: u64,
+    /// Active relation counts keyed by relation type.
+    pub relations_by_type: BTreeMap<String, u64>,
+    /// Active groups.
+    pub active_groups: u64,
+}
+
+/// A snapshot ledger entry shown in the history section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportSnapshot {
+    pub snapshot_id: String,
+    pub root_ettle_id: String,
+    pub manifest_digest: String,
+    /// Commit timestamp, milliseconds since epoch.
+    pub created_at: i64,
+    /// Commit message, if one was recorded.
+    pub message: Option<String>,
+}
+
+/// A decision that has not reached a terminal status.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportDecision {
+    pub decision_id: String,
+    pub title: String,
+    pub status: String,
+}
+
+/// How much of the active tree is governed by constraints.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConstraintCoverage {
+    /// Active Ettles acting as the source of at least one active `constraint` relation.
+    pub constraint_ettles: u64,
+    /// Constraint Ettles with at least one live decision linked to them as a
+    /// `constraint` target, i.e. with a recorded justification.
+    pub justified_constraints: u64,
+    /// Active Ettles that are the target of at least one active `constraint` relation.
+    pub governed_ettles: u64,
+    /// Active Ettles that are neither constraints nor governed.
+    pub ungoverned_ettles: u64,
+    /// Active `constraint` relations with a missing or tombstoned endpoint;
+    /// non-zero means the store needs a `ConstraintSweepOrphans`.
+    pub dangling_attachments: u64,
+}
+
+impl ConstraintCoverage {
+    /// Governed share of non-constraint Ettles, as a percentage (0–100).
+    pub fn percent(&self) -> f64 {
+        let denominator = self.governed_ettles + self.ungoverned_ettles;
+        if denominator == 0 {
+            0.0
+        } else {
+            self.governed_ettles as f64 * 100.0 / denominator as f64
+        }
+    }
+}
+
+/// A pending approval request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportApproval {
+    pub approval_token: String,
+    pub reason_code: String,
+    /// Creation timestamp, milliseconds since epoch.
+    pub created_at: i64,
+}
+
+/// Everything shown in the architecture overview report.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ArchitectureReport {
+    pub tree: TreeAnalytics,
+    /// Most recent snapshots, newest first.
+    pub recent_snapshots: Vec<ReportSnapshot>,
+    /// Open decisions, ordered by decision ID.
+    pub open_decisions: Vec<ReportDecision>,
+    pub constraint_coverage: ConstraintCoverage,
+    /// Pending approvals, oldest first.
+    pub pending_approvals: Vec<ReportApproval>,
+}
+
+/// Render the report as Markdown.
+pub fn render_architecture_report_markdown(report: &ArchitectureReport) -> String {
+    let mut out = String::new();
+    out.push_str("# State of the Architecture\n\n");
+    for section in sections(report) {
+        out.push_str(&format!("## {}\n\n", section.title));
+        if let Some(summary) = &section.summary {
+            out.push_str(&format!("{}\n\n", summary));
+        }
+        match &section.body {
+            Body::Pairs(pairs) => {
+                for (k, v) in pairs {
+                    out.push_str(&format!("- **{}:** {}\n", k, v));
+                }
+                out.push('\n');
+            }
+            Body::Table { rows, .. } if rows.is_empty() => {
+                out.push_str(&format!("*{}*\n\n", section.empty));
+            }
+            Body::Table { header, rows } => {
+                out.push_str(&format!("| {} |\n", header.join(" | ")));
+                out.push_str(&format!(
+                    "|{}\n",
+                    header.iter().map(|_| "---|").collect::<String>()
+                ));
+                for row in rows {
+                    let cells: Vec<String> = row.iter().map(|c| c.replace('|', "\\|")).collect();
+                    out.push_str(&format!("| {} |\n", cells.join(" | ")));
+                }
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
+/// Render the report as a standalone HTML document.
+pub fn render_architecture_report_html(report: &ArchitectureReport) -> String {
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str("<title>State of the Architecture</title>\n</head>\n<body>\n");
+    out.push_str("<h1>State of the Architecture</h1>\n");
+    for section in sections(report) {
+        out.push_str(&format!("<h2>{}</h2>\n", escape_html(section.title)));
+        if let Some(summary) = &section.summary {
+            out.push_str(&format!("<p>{}</p>\n", escape_html(summary)));
+        }
+        match &section.body {
+            Body::Pairs(pairs) => {
+                out.push_str("<ul>\n");
+                for (k, v) in pairs {
+                    out.push_str(&format!(
+                        "<li><strong>{}:</strong> {}</li>\n",
+                        escape_html(k),
+                        escape_html(v)
+                    ));
+                }
+                out.push_str("</ul>\n");
+            }
+            Body::Table { rows, .. } if rows.is_empty() => {
+                out.push_str(&format!("<p><em>{}</em></p>\n", escape_html(section.empty)));
+            }
+            Body::Table { header, rows } => {
+                out.push_str("<table>\n<tr>");
+                for h in header {
+                    out.push_str(&format!("<th>{}</th>", escape_html(h)));
+                }
+                out.push_str("</tr>\n");
+                for row in rows {
+                    out.push_str("<tr>");
+                    for cell in row {
+                        out.push_str(&format!("<td>{}</td>", escape_html(cell)));
+                    }
+                    out.push_str("</tr>\n");
+                }
+                out.push_str("</table>\n");
+            }
+        }
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Format-neutral section model
+// ---------------------------------------------------------------------------
+
+enum Body {
+    Pairs(Vec<(String, String)>),
+    Table {
+        header: Vec<&'static str>,
+        rows: Vec<Vec<String>>,
+    },
+}
+
+struct Section {
+    title: &'static str,
+    summary: Option<String>,
+    body: Body,
+    /// Text shown when a table has no rows.
+    empty: &'static str,
+}
+
+fn sections(report: &ArchitectureReport) -> Vec<Section> {
+    let tree = &report.tree;
+    let mut tree_pairs = vec![
+        ("Active ettles".to_string(), tree.active_ettles.to_string()),
+        (
+            "Tombstoned ettles".to_string(),
+            tree.tombstoned_ettles.to_string(),
+        ),
+        (
+            "Archived ettles".to_string(),
+            tree.archived_ettles.to_string(),
+        ),
+        ("Roots".to_string(), tree.root_count.to_string()),
+        (
+            "Registered roots".to_string(),
+            tree.registered_roots.to_string(),
+        ),
+        (
+            "Unregistered roots".to_string(),
+            tree.unregistered_roots.to_string(),
+        ),
+        ("Leaves".to_string(), tree.leaf_count.to_string()),
+        ("Max depth".to_string(), tree.max_depth.to_string()),
+        ("Active groups".to_string(), tree.active_groups.to_string()),
+    ];
+    for (relation_type, count) in &tree.relations_by_type {
+        tree_pairs.push((format!("Relations ({})", relation_type), count.to_string()));
+    }
+
+    let coverage = &report.constraint_coverage;
+    let coverage_pairs = vec![
+        (
+            "Constraint ettles".to_string(),
+            coverage.constraint_ettles.to_string(),
+        ),
+        (
+            "Justified constraints".to_string(),
+            coverage.justified_constraints.to_string(),
+        ),
+        (
+            "Governed ettles".to_string(),
+            coverage.governed_ettles.to_string(),
+        ),
+        (
+            "Ungoverned ettles".to_string(),
+            coverage.ungoverned_ettles.to_string(),
+        ),
+        (
+            "Coverage".to_string(),
+            format!("{:.1}%", coverage.percent()),
+        ),
+        (
+            "Dangling attachments".to_string(),
+            coverage.dangling_attachments.to_string(),
+        ),
+    ];
+
+    vec![
+        Section {
+            title: "Tree analytics",
+            summary: None,
+            body: Body::Pairs(tree_pairs),
+            empty: "",
+        },
+        Section {
+            title: "Recent snapshots",
+            summary: None,
+            body: Body::Table {
+                header: vec![
+                    "Snapshot",
+                    "Root",
+                    "Created (ms)",
+                    "Manifest digest",
+                    "Message",
+                ],
+                rows: report
+                    .recent_snapshots
+                    .iter()
+                    .map(|s| {
+                        vec![
+                            s.snapshot_id.clone(),
+                            s.root_ettle_id.clone(),
+                            s.created_at.to_string(),
+                            short_digest(&s.manifest_digest),
+                            s.message.clone().unwrap_or_default(),
+                        ]
+                    })
+                    .collect(),
+            },
+            empty: "No snapshots committed yet.",
+        },
+        Section {
+            title: "Open decisions",
+            summary: Some(format!("{} open.", report.open_decisions.len())),
+            body: Body::Table {
+                header: vec!["Decision", "Title", "Status"],
+                rows: report
+                    .open_decisions
+                    .iter()
+                    .map(|d| vec![d.decision_id.clone(), d.title.clone(), d.status.clone()])
+                    .collect(),
+            },
+            empty: "No open decisions.",
+        },
+        Section {
+            title: "Constraint coverage",
+            summary: None,
+            body: Body::Pairs(coverage_pairs),
+            empty: "",
+        },
+        Section {
+            title: "Pending approvals",
+            summary: Some(format!("{} pending.", report.pending_approvals.len())),
+            body: Body::Table {
+                header: vec!["Token", "Reason", "Created (ms)"],
+                rows: report
+                    .pending_approvals
+                    .iter()
+                    .map(|a| {
+                        vec![
+                            a.approval_token.clone(),
+                            a.reason_code.clone(),
+                            a.created_at.to_string(),
+                        ]
+                    })
+                    .collect(),
+            },
+            empty: "No pending approvals.",
+        },
+    ]
+}
+
+fn short_digest(digest: &str) -> String {
+    digest.chars().take(12).collect()
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> ArchitectureReport {
+        let mut relations_by_type = BTreeMap::new();
+        relations_by_type.insert("refinement".to_string(), 2);
+        ArchitectureReport {
+            tree: TreeAnalytics {
+                active_ettles: 3,
+                root_count: 1,
+                leaf_count: 2,
+                max_depth: 2,
+                relations_by_type,
+                ..Default::default()
+            },
+            open_decisions: vec![ReportDecision {
+                decision_id: "d1".to_string(),
+                title: "Use <Postgres> | maybe".to_string(),
+                status: "proposed".to_string(),
+            }],
+            constraint_coverage: ConstraintCoverage {
+                constraint_ettles: 1,
+                justified_constraints: 1,
+                governed_ettles: 1,
+                ungoverned_ettles: 3,
+                dangling_attachments: 2,
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_markdown_report_sections() {
+        let md = render_architecture_report_markdown(&report());
+        assert!(md.contains("## Tree analytics"));
+        assert!(md.contains("- **Relations (refinement):** 2"));
+        assert!(md.contains("No snapshots committed yet."));
+        assert!(md.contains("Use <Postgres> \\| maybe"));
+        assert!(md.contains("- **Coverage:** 25.0%"));
+        assert!(md.contains("- **Justified constraints:** 1"));
+        assert!(md.contains("- **Dangling attachments:** 2"));
+        assert!(md.contains("No pending approvals."));
+    }
+
+    #[test]
+    fn test_html_report_escapes_content() {
+        let html = render_architecture_report_html(&report());
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("Use &lt;Postgres&gt; | maybe"));
+        assert!(!html.contains("<Postgres>"));
+    }
+}
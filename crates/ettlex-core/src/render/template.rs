@@ -0,0 +1,192 @@
+//! Content templating — `{{variable}}` substitution at render time.
+//!
+//! Variables are resolved from a flat name → value map, typically the
+//! `variables` object of a profile payload. This allows one tree to produce
+//! environment-specific documentation by switching profiles.
+//!
+//! Syntax: `{{name}}`, where `name` is `[A-Za-z0-9_.-]+` with optional
+//! surrounding whitespace. Anything else between braces is left verbatim.
+//!
+//! The same bindings resolve content before manifest generation evaluates
+//! constraint families; a profile with `"strict_variables": true` makes an
+//! unbound variable fail there.
+
+use std::collections::BTreeMap;
+
+use crate::errors::{ExError, ExErrorKind, Result};
+
+/// Name → value bindings used to resolve `{{variables}}`.
+pub type TemplateVariables = BTreeMap<String, String>;
+
+/// How unresolved variables are handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TemplateMode {
+    /// Leave unresolved placeholders in the output unchanged.
+    #[default]
+    Lenient,
+    /// Fail with `UnresolvedVariable` if any placeholder has no binding.
+    Strict,
+}
+
+/// Extract template variables from a profile payload.
+///
+/// Reads the top-level `variables` object. String values are used as-is;
+/// numbers and booleans are stringified. Other value types are ignored.
+/// A payload without a `variables` object yields an empty map.
+pub fn variables_from_profile_payload(payload: &serde_json::Value) -> TemplateVariables {
+    let mut vars = TemplateVariables::new();
+    if let Some(obj) = payload.get("variables").and_then(|v| v.as_object()) {
+        for (k, v) in obj {
+            let value = match v {
+                serde_json::Value::String(s) => s.clone(),
+                serde_json::Value::Number(n) => n.to_string(),
+                serde_json::Value::Bool(b) => b.to_string(),
+                _ => continue,
+            };
+            vars.insert(k.clone(), value);
+        }
+    }
+    vars
+}
+
+/// Template mode a profile payload asks for: `Strict` when its top-level
+/// `strict_variables` is `true`, else `Lenient`.
+pub fn template_mode_from_profile_payload(payload: &serde_json::Value) -> TemplateMode {
+    match payload.get("strict_variables").and_then(|v| v.as_bool()) {
+        Some(true) => TemplateMode::Strict,
+        _ => TemplateMode::Lenient,
+    }
+}
+
+/// Resolve all `{{variables}}` in `text`.
+///
+/// # Errors
+/// * `UnresolvedVariable` - In `Strict` mode, if any placeholder has no
+///   binding. All unresolved names (sorted, deduplicated) are reported via
+///   the error's `candidates`.
+pub fn resolve_template(
+    text: &str,
+    vars: &TemplateVariables,
+    mode: TemplateMode,
+) -> Result<String> {
+    let mut out = String::with_capacity(text.len());
+    let mut unresolved: Vec<String> = Vec::new();
+    let mut rest = text;
+
+    while let Some(open) = rest.find("{{") {
+        out.push_str(&rest[..open]);
+        let after_open = &rest[open + 2..];
+        let Some(close) = after_open.find("}}") else {
+            out.push_str(&rest[open..]);
+            rest = "";
+            break;
+        };
+        let name = after_open[..close].trim();
+        let placeholder = &rest[open..open + 2 + close + 2];
+        if is_variable_name(name) {
+            match vars.get(name) {
+                Some(value) => out.push_str(value),
+                None => {
+                    unresolved.push(name.to_string());
+                    out.push_str(placeholder);
+                }
+            }
+        } else {
+            out.push_str(placeholder);
+        }
+        rest = &after_open[close + 2..];
+    }
+    out.push_str(rest);
+
+    if mode == TemplateMode::Strict && !unresolved.is_empty() {
+        unresolved.sort();
+        unresolved.dedup();
+        return Err(ExError::new(ExErrorKind::UnresolvedVariable)
+            .with_op("resolve_template")
+            .with_message(format!("unresolved variables: {}", unresolved.join(", ")))
+            .with_candidates(unresolved));
+    }
+
+    Ok(out)
+}
+
+fn is_variable_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> TemplateVariables {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_resolves_bound_variables() {
+        let v = vars(&[("env", "prod"), ("region", "eu-west-1")]);
+        let out = resolve_template(
+            "Deploy to {{env}} in {{ region }}",
+            &v,
+            TemplateMode::Strict,
+        )
+        .unwrap();
+        assert_eq!(out, "Deploy to prod in eu-west-1");
+    }
+
+    #[test]
+    fn test_lenient_leaves_unresolved_placeholder() {
+        let out = resolve_template("Hello {{who}}", &vars(&[]), TemplateMode::Lenient).unwrap();
+        assert_eq!(out, "Hello {{who}}");
+    }
+
+    #[test]
+    fn test_strict_fails_on_unresolved() {
+        let err =
+            resolve_template("{{b}} {{a}} {{b}}", &vars(&[]), TemplateMode::Strict).unwrap_err();
+        assert_eq!(err.kind(), ExErrorKind::UnresolvedVariable);
+        assert_eq!(
+            err.candidates().unwrap(),
+            &["a".to_string(), "b".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_non_variable_braces_are_verbatim() {
+        let text = "json: {{ not a name }} and {{unterminated";
+        let out = resolve_template(text, &vars(&[]), TemplateMode::Strict).unwrap();
+        assert_eq!(out, text);
+    }
+
+    #[test]
+    fn test_variables_from_profile_payload() {
+        let payload = serde_json::json!({
+            "variables": { "env": "staging", "replicas": 3, "debug": false, "nested": {} }
+        });
+        let v = variables_from_profile_payload(&payload);
+        assert_eq!(v.get("env").map(String::as_str), Some("staging"));
+        assert_eq!(v.get("replicas").map(String::as_str), Some("3"));
+        assert_eq!(v.get("debug").map(String::as_str), Some("false"));
+        assert!(!v.contains_key("nested"));
+    }
+
+    #[test]
+    fn test_template_mode_from_profile_payload() {
+        let strict = serde_json::json!({ "strict_variables": true });
+        assert_eq!(
+            template_mode_from_profile_payload(&strict),
+            TemplateMode::Strict
+        );
+        let lenient = serde_json::json!({ "variables": {} });
+        assert_eq!(
+            template_mode_from_profile_payload(&lenient),
+            TemplateMode::Lenient
+        );
+    }
+}
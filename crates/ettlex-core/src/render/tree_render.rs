@@ -0,0 +1,285 @@
+//! Hierarchical refinement-tree render.
+//!
+//! Renders an Ettle and its refinement descendants as one Markdown document:
+//! the root is the document title and each level below it is one heading
+//! level deeper (capped at `######`). Options add hierarchical section
+//! numbers (`1.`, `1.1`, `1.1.1`) that follow the refinement order, a table
+//! of contents linking to each section, and a depth limit. The engine
+//! assembles the [`RenderTreeNode`] tree; rendering here is pure.
+//!
+//! Content declared as anything other than Markdown is fenced and tagged
+//! with its format rather than inlined.
+
+use std::collections::BTreeMap;
+
+use crate::model::ContentFormat;
+
+/// One Ettle in a render tree, with its refinement children in order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RenderTreeNode {
+    pub ettle_id: String,
+    pub title: String,
+    pub why: String,
+    pub what: String,
+    pub how: String,
+    /// Format of `why`, `what` and `how`.
+    pub content_format: ContentFormat,
+    pub children: Vec<RenderTreeNode>,
+}
+
+/// Options for [`render_tree_markdown`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TreeRenderOptions {
+    /// Levels below the root to render (`Some(0)` renders the root only;
+    /// `None` renders every level).
+    pub max_depth: Option<usize>,
+    /// Prefix section headings with hierarchical numbers.
+    pub numbering: bool,
+    /// Insert a table of contents after the root's content.
+    pub toc: bool,
+}
+
+/// A rendered section: depth below the root, heading text and anchor.
+struct Section<'a> {
+    node: &'a RenderTreeNode,
+    depth: usize,
+    heading: String,
+    anchor: String,
+}
+
+/// Render `root` and its descendants to Markdown.
+pub fn render_tree_markdown(root: &RenderTreeNode, options: &TreeRenderOptions) -> String {
+    let mut anchors = AnchorAllocator::default();
+    anchors.allocate(&root.title);
+    if options.toc {
+        anchors.allocate("Contents");
+    }
+    let mut sections = Vec::new();
+    collect_sections(root, 1, "", options, &mut anchors, &mut sections);
+
+    let mut out = format!("# {}\n\n", root.title);
+    push_body(&mut out, root);
+
+    if options.toc && !sections.is_empty() {
+        out.push_str("## Contents\n\n");
+        for section in &sections {
+            out.push_str(&format!(
+                "{}- [{}](#{})\n",
+                "  ".repeat(section.depth - 1),
+                section.heading,
+                section.anchor
+            ));
+        }
+        out.push('\n');
+    }
+
+    for section in &sections {
+        let level = (section.depth + 1).min(6);
+        out.push_str(&format!("{} {}\n\n", "#".repeat(level), section.heading));
+        push_body(&mut out, section.node);
+    }
+    out
+}
+
+fn collect_sections<'a>(
+    node: &'a RenderTreeNode,
+    depth: usize,
+    parent_number: &str,
+    options: &TreeRenderOptions,
+    anchors: &mut AnchorAllocator,
+    sections: &mut Vec<Section<'a>>,
+) {
+    if options.max_depth.is_some_and(|max| depth > max) {
+        return;
+    }
+    for (i, child) in node.children.iter().enumerate() {
+        let number = if parent_number.is_empty() {
+            format!("{}", i + 1)
+        } else {
+            format!("{}.{}", parent_number, i + 1)
+        };
+        let heading = heading_text(&number, &child.title, options);
+        sections.push(Section {
+            node: child,
+            depth,
+            anchor: anchors.allocate(&heading),
+            heading,
+        });
+        collect_sections(child, depth + 1, &number, options, anchors, sections);
+    }
+}
+
+fn heading_text(number: &str, title: &str, options: &TreeRenderOptions) -> String {
+    if !options.numbering {
+        title.to_string()
+    } else if number.contains('.') {
+        format!("{} {}", number, title)
+    } else {
+        format!("{}. {}", number, title)
+    }
+}
+
+fn push_body(out: &mut String, node: &RenderTreeNode) {
+    for (label, text) in [("Why", &node.why), ("What", &node.what), ("How", &node.how)] {
+        let text = text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        match node.content_format.fence_language() {
+            None => out.push_str(&format!("**{}:** {}\n\n", label, text)),
+            Some(lang) => {
+                let fence = fence_for(text);
+                out.push_str(&format!(
+                    "**{}:**\n\n{}{}\n{}\n{}\n\n",
+                    label, fence, lang, text, fence
+                ));
+            }
+        }
+    }
+}
+
+/// A backtick fence longer than any backtick run inside `text`.
+fn fence_for(text: &str) -> String {
+    let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+/// GitHub-style heading anchors: lowercase, punctuation dropped, spaces to
+/// hyphens, and `-1`, `-2`, ... appended to repeats.
+#[derive(Default)]
+struct AnchorAllocator {
+    seen: BTreeMap<String, usize>,
+}
+
+impl AnchorAllocator {
+    fn allocate(&mut self, heading: &str) -> String {
+        let base: String = heading
+            .trim()
+            .to_lowercase()
+            .chars()
+            .filter_map(|c| match c {
+                ' ' => Some('-'),
+                c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+                _ => None,
+            })
+            .collect();
+        let count = self.seen.entry(base.clone()).or_insert(0);
+        let anchor = if *count == 0 {
+            base.clone()
+        } else {
+            format!("{}-{}", base, count)
+        };
+        *count += 1;
+        anchor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str, title: &str, children: Vec<RenderTreeNode>) -> RenderTreeNode {
+        RenderTreeNode {
+            ettle_id: id.to_string(),
+            title: title.to_string(),
+            what: format!("{} what", title),
+            children,
+            ..Default::default()
+        }
+    }
+
+    fn tree() -> RenderTreeNode {
+        node(
+            "ettle:root",
+            "Payments",
+            vec![
+                node(
+                    "ettle:a",
+                    "Checkout",
+                    vec![node("ettle:a1", "Card Entry", vec![])],
+                ),
+                node(
+                    "ettle:b",
+                    "Refunds",
+                    vec![node("ettle:b1", "Card Entry", vec![])],
+                ),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_numbered_headings_follow_refinement_order() {
+        let out = render_tree_markdown(
+            &tree(),
+            &TreeRenderOptions {
+                numbering: true,
+                ..Default::default()
+            },
+        );
+        assert!(out.starts_with("# Payments\n\n**What:** Payments what\n\n"));
+        assert!(out.contains("## 1. Checkout\n"));
+        assert!(out.contains("### 1.1 Card Entry\n"));
+        assert!(out.contains("## 2. Refunds\n"));
+        assert!(out.contains("### 2.1 Card Entry\n"));
+        assert!(!out.contains("Contents"));
+    }
+
+    #[test]
+    fn test_toc_links_to_unique_anchors() {
+        let out = render_tree_markdown(
+            &tree(),
+            &TreeRenderOptions {
+                toc: true,
+                ..Default::default()
+            },
+        );
+        assert!(out.contains(
+            "## Contents\n\n- [Checkout](#checkout)\n  - [Card Entry](#card-entry)\n- [Refunds](#refunds)\n  - [Card Entry](#card-entry-1)\n"
+        ));
+        assert!(out.contains("### Card Entry\n"));
+
+        let numbered = render_tree_markdown(
+            &tree(),
+            &TreeRenderOptions {
+                toc: true,
+                numbering: true,
+                ..Default::default()
+            },
+        );
+        assert!(numbered.contains("  - [1.1 Card Entry](#11-card-entry)\n"));
+    }
+
+    #[test]
+    fn test_max_depth_limits_levels() {
+        let out = render_tree_markdown(
+            &tree(),
+            &TreeRenderOptions {
+                max_depth: Some(1),
+                ..Default::default()
+            },
+        );
+        assert!(out.contains("## Checkout"));
+        assert!(!out.contains("Card Entry"));
+
+        let root_only = render_tree_markdown(
+            &tree(),
+            &TreeRenderOptions {
+                max_depth: Some(0),
+                toc: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(root_only, "# Payments\n\n**What:** Payments what\n\n");
+    }
+
+    #[test]
+    fn test_non_markdown_content_is_fenced() {
+        let mut root = node("ettle:root", "Spec", vec![]);
+        root.content_format = ContentFormat::Asciidoc;
+        root.how = "== Steps\n```\nrun\n```".to_string();
+
+        let out = render_tree_markdown(&root, &TreeRenderOptions::default());
+        assert!(out.contains("**What:**\n\n```asciidoc\nSpec what\n```\n\n"));
+        assert!(out.contains("**How:**\n\n````asciidoc\n== Steps\n```\nrun\n```\n````\n"));
+    }
+}
@@ -0,0 +1,168 @@
+//! Decision lifecycle check for snapshot manifests.
+//!
+//! Flags EPTs whose Ettles are linked to decisions still in `proposed`
+//! status, so an anchored architecture does not silently rest on choices
+//! nobody has made yet. The check is opt-in through the profile payload:
+//!
+//! ```json
+//! { "decision_lifecycle": { "mode": "flag" } }
+//! ```
+//!
+//! | Mode    | Effect                                                            |
+//! |---------|-------------------------------------------------------------------|
+//! | `off`   | Default. No check; the manifest has no `decision_lifecycle` field |
+//! | `flag`  | Findings are recorded in the manifest's `decision_lifecycle`     |
+//! | `block` | As `flag`, but any finding fails the commit with `PolicyDenied`   |
+//!
+//! The section is omitted entirely when the check is off, so manifests
+//! written before it existed keep their digests.
+
+use crate::errors::{ExError, ExErrorKind, Result};
+use crate::ops::Store;
+use crate::snapshot::digest::{compute_manifest_digest, compute_semantic_digest};
+use crate::snapshot::manifest::SnapshotManifest;
+use serde::{Deserialize, Serialize};
+
+/// Profile payload key holding the [`DecisionLifecyclePolicy`].
+pub const DECISION_LIFECYCLE_PROFILE_KEY: &str = "decision_lifecycle";
+
+/// Decision status the check looks for.
+pub const PROPOSED_STATUS: &str = "proposed";
+
+/// How a snapshot commit treats proposed decisions in its EPT.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DecisionLifecycleMode {
+    #[default]
+    Off,
+    Flag,
+    Block,
+}
+
+/// The `decision_lifecycle` object of a profile payload.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DecisionLifecyclePolicy {
+    #[serde(default)]
+    pub mode: DecisionLifecycleMode,
+}
+
+impl DecisionLifecyclePolicy {
+    /// Read the policy from a profile payload; a missing key means `off`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidInput` if the key is present but malformed.
+    pub fn from_profile_payload(payload: &serde_json::Value) -> Result<Self> {
+        match payload.get(DECISION_LIFECYCLE_PROFILE_KEY) {
+            None => Ok(Self::default()),
+            Some(value) => serde_json::from_value(value.clone()).map_err(|e| {
+                ExError::new(ExErrorKind::InvalidInput)
+                    .with_op("decision_lifecycle_policy")
+                    .with_message(format!(
+                        "invalid '{}' profile key: {}",
+                        DECISION_LIFECYCLE_PROFILE_KEY, e
+                    ))
+            }),
+        }
+    }
+}
+
+/// An EPT Ettle linked to a decision that is still proposed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProposedDecisionFinding {
+    pub ettle_id: String,
+    pub decision_id: String,
+    pub decision_title: String,
+    pub relation_kind: String,
+}
+
+/// The manifest's `decision_lifecycle` section.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DecisionLifecycleSection {
+    /// Mode the check ran in (`flag` or `block`)
+    pub mode: DecisionLifecycleMode,
+    /// Findings in EPT order, then by decision ID and relation kind
+    pub proposed: Vec<ProposedDecisionFinding>,
+}
+
+impl DecisionLifecycleSection {
+    /// Check `ept` against the decisions in `store`.
+    ///
+    /// Tombstoned links and tombstoned decisions are ignored.
+    pub fn check(ept: &[String], store: &Store, mode: DecisionLifecycleMode) -> Self {
+        let mut proposed = Vec::new();
+        for ettle_id in ept {
+            let mut found: Vec<ProposedDecisionFinding> = store
+                .list_decision_links_for_target("ettle", ettle_id)
+                .into_iter()
+                .filter(|link| !link.is_tombstoned())
+                .filter_map(|link| {
+                    let decision = store.get_decision(&link.decision_id).ok()?;
+                    (decision.status == PROPOSED_STATUS).then(|| ProposedDecisionFinding {
+                        ettle_id: ettle_id.clone(),
+                        decision_id: decision.decision_id.clone(),
+                        decision_title: decision.title.clone(),
+                        relation_kind: link.relation_kind.clone(),
+                    })
+                })
+                .collect();
+            found.sort_by(|a, b| {
+                (&a.decision_id, &a.relation_kind).cmp(&(&b.decision_id, &b.relation_kind))
+            });
+            proposed.extend(found);
+        }
+        Self { mode, proposed }
+    }
+}
+
+/// Run the check in `mode` and record it in `manifest`, recomputing the
+/// manifest digests. `off` leaves the manifest untouched.
+///
+/// # Errors
+///
+/// * `PolicyDenied` - `mode` is `block` and the EPT has proposed decisions
+/// * `Serialization` - Digest computation fails
+pub fn apply_decision_lifecycle(
+    manifest: &mut SnapshotManifest,
+    store: &Store,
+    mode: DecisionLifecycleMode,
+) -> Result<()> {
+    if mode == DecisionLifecycleMode::Off {
+        return Ok(());
+    }
+    let ept: Vec<String> = manifest.ept.iter().map(|e| e.ep_id.clone()).collect();
+    let section = DecisionLifecycleSection::check(&ept, store, mode);
+    if mode == DecisionLifecycleMode::Block && !section.proposed.is_empty() {
+        let first = &section.proposed[0];
+        return Err(ExError::new(ExErrorKind::PolicyDenied)
+            .with_op("decision_lifecycle_check")
+            .with_entity_id(first.ettle_id.clone())
+            .with_message(format!(
+                "{} proposed decision link(s) in EPT, first: {} on {}",
+                section.proposed.len(),
+                first.decision_id,
+                first.ettle_id
+            )));
+    }
+    record_decision_lifecycle(manifest, section)
+}
+
+/// Store `section` in `manifest` and recompute the manifest digests, without
+/// enforcing `block`. Used to regenerate a manifest the way a committed one
+/// was produced, e.g. for drift checks.
+///
+/// # Errors
+///
+/// Returns `Serialization` if digest computation fails.
+pub fn record_decision_lifecycle(
+    manifest: &mut SnapshotManifest,
+    section: DecisionLifecycleSection,
+) -> Result<()> {
+    manifest.decision_lifecycle = Some(section);
+    manifest.manifest_digest = String::new();
+    manifest.semantic_manifest_digest = String::new();
+    manifest.semantic_manifest_digest = compute_semantic_digest(manifest)?;
+    manifest.manifest_digest = compute_manifest_digest(manifest)?;
+    Ok(())
+}
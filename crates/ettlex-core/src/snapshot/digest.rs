@@ -8,6 +8,7 @@
 //! - **EPT Digest**: Hash of ordered EP IDs
 //! - **Manifest Digest**: Hash of full manifest (includes `created_at`)
 //! - **Semantic Digest**: Hash excluding `created_at` (for idempotency)
+//! - **Topology Digest**: Hash of EP IDs, ordinals and refinement edges only
 //!
 //! ## Determinism Guarantees
 //!
@@ -16,8 +17,13 @@
 //! - Semantic digest stable across timestamps
 
 use crate::errors::Result;
-use crate::snapshot::manifest::SnapshotManifest;
+use crate::snapshot::manifest::{EpEntry, SnapshotManifest};
+use serde::Serialize;
 use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+/// Refinement edges keyed by parent ID, as input to [`compute_topology_digest`].
+pub type RefinementEdges = BTreeMap<String, Vec<String>>;
 
 /// Compute digest of ordered EPT.
 ///
@@ -109,6 +115,72 @@ pub fn compute_semantic_digest(manifest: &SnapshotManifest) -> Result<String> {
     Ok(hash_string(&canonical))
 }
 
+/// Compute the structure-only topology digest of an EPT.
+///
+/// Hashes the EP IDs with their ordinals plus the refinement edges out of
+/// each EPT entry. EP digests and content formats are left out, so editing
+/// an EP's content keeps the digest while adding, removing, reordering or
+/// re-parenting an entry changes it.
+///
+/// ## Arguments
+///
+/// - `ept`: EPT entries, in manifest order
+/// - `edges`: Refinement children per parent; only parents present in `ept`
+///   are hashed, and children are sorted so query order does not matter
+///
+/// ## Returns
+///
+/// Hex-encoded SHA256 digest (64 characters)
+///
+/// ## Errors
+///
+/// Returns `Serialization error` if JSON serialization fails.
+pub fn compute_topology_digest(ept: &[EpEntry], edges: &RefinementEdges) -> Result<String> {
+    #[derive(Serialize)]
+    struct Topology<'a> {
+        ept: Vec<(&'a str, u32)>,
+        refinements: BTreeMap<&'a str, Vec<&'a str>>,
+    }
+
+    let mut refinements = BTreeMap::new();
+    for entry in ept {
+        if let Some(children) = edges.get(&entry.ep_id) {
+            let mut children: Vec<&str> = children.iter().map(String::as_str).collect();
+            children.sort_unstable();
+            children.dedup();
+            refinements.insert(entry.ep_id.as_str(), children);
+        }
+    }
+    let topology = Topology {
+        ept: ept.iter().map(|e| (e.ep_id.as_str(), e.ordinal)).collect(),
+        refinements,
+    };
+    let canonical = serde_json::to_string(&topology)?;
+    Ok(hash_string(&canonical))
+}
+
+/// Store the topology digest of `manifest` and recompute the manifest
+/// digests.
+///
+/// The digest is opt-in so manifests written before it existed keep their
+/// digests; callers regenerating a committed manifest should record it only
+/// if the committed one carried it.
+///
+/// ## Errors
+///
+/// Returns `Serialization error` if digest computation fails.
+pub fn record_topology_digest(
+    manifest: &mut SnapshotManifest,
+    edges: &RefinementEdges,
+) -> Result<()> {
+    manifest.topology_digest = Some(compute_topology_digest(&manifest.ept, edges)?);
+    manifest.manifest_digest = String::new();
+    manifest.semantic_manifest_digest = String::new();
+    manifest.semantic_manifest_digest = compute_semantic_digest(manifest)?;
+    manifest.manifest_digest = compute_manifest_digest(manifest)?;
+    Ok(())
+}
+
 /// Hash a string using SHA256.
 ///
 /// Internal helper for deterministic digest computation.
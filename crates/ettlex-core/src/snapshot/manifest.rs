@@ -29,11 +29,31 @@
 //! - `semantic_manifest_digest`: Digest excluding created_at (for idempotency)
 //! - `store_schema_version`: Store schema version
 //! - `seed_digest`: Optional seed digest
-
-use crate::constraint_engine::{ConstraintEvalCtx, ConstraintFamilyStatus};
+//! - `decision_lifecycle`: Proposed decisions linked into the EPT; omitted
+//!   unless the profile enables the check (see [`super::decision_lifecycle`])
+//! - `topology_digest`: Structure-only digest of EP IDs, ordinals and
+//!   refinement edges; omitted unless recorded with
+//!   [`record_topology_digest`](super::digest::record_topology_digest)
+//!
+//! ## Constraints Envelope Types
+//!
+//! [`ConstraintsEnvelope`] and [`FamilyConstraints`] are the one typed form of
+//! the envelope for generation, diff and external readers.
+//! [`family_digest`] and [`constraints_digest`] define how its digests are
+//! computed, and [`ConstraintsEnvelope::recompute_digest`] checks a stored
+//! envelope against them.
+
+use super::decision_lifecycle::DecisionLifecycleSection;
+use crate::constraint_engine::{
+    ConstraintEvalCtx, ConstraintFamilyRegistry, ConstraintFamilyStatus, EttleContent,
+};
+use crate::diff::EpContent;
 use crate::errors::{ExError, ExErrorKind, Result};
+use crate::model::ContentFormat;
 use crate::ops::Store;
+use crate::render::template::{resolve_template, TemplateMode, TemplateVariables};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
 use std::collections::BTreeMap;
 
 /// Constraints envelope for snapshot manifests.
@@ -71,12 +91,16 @@ pub struct ConstraintsEnvelope {
 impl ConstraintsEnvelope {
     /// Create a constraints envelope from EPT constraints
     ///
-    /// Collects all constraints attached to EPs in the EPT, groups them by family,
+    /// Collects the constraints declared on Ettles in the EPT, groups them by family,
     /// and computes digests. ABB/SBB projections are kept empty for backward compatibility.
+    /// Without relation data or content, evaluating families (e.g. `cardinality`,
+    /// `doc_coverage`) report `UNCOMPUTED`; see
+    /// [`ConstraintsEnvelope::from_ept_with_children`] and
+    /// [`ConstraintsEnvelope::from_ept_with_content`].
     ///
     /// # Arguments
     ///
-    /// * `ept` - Ordered list of EP IDs in the EPT
+    /// * `ept` - Ordered list of Ettle IDs in the EPT
     /// * `store` - Store containing constraint data
     ///
     /// # Returns
@@ -85,8 +109,70 @@ impl ConstraintsEnvelope {
     ///
     /// # Errors
     ///
-    /// Returns `Serialization` error if JSON serialization fails during digest computation.
+    /// Returns `InvalidInput` if a declared constraint's payload is malformed.
     pub fn from_ept(ept: &[String], store: &Store) -> Result<Self> {
+        Self::from_ept_with_registry(ept, None, store, &ConstraintFamilyRegistry::builtin())
+    }
+
+    /// Like [`ConstraintsEnvelope::from_ept`], with each Ettle's refinement
+    /// children so that structural families are evaluated. Their per-constraint
+    /// results are recorded in the family's `outcomes`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidInput` if a declared constraint's payload is malformed.
+    pub fn from_ept_with_children(
+        ept: &[String],
+        children: &BTreeMap<String, Vec<String>>,
+        store: &Store,
+    ) -> Result<Self> {
+        Self::from_ept_with_registry(
+            ept,
+            Some(children),
+            store,
+            &ConstraintFamilyRegistry::builtin(),
+        )
+    }
+
+    /// Create an envelope using the evaluators in `registry` instead of the
+    /// built-ins, e.g. to add a downstream crate's constraint families.
+    ///
+    /// # Errors
+    ///
+    /// Propagates evaluator errors, e.g. `InvalidInput` for a malformed payload.
+    pub fn from_ept_with_registry(
+        ept: &[String],
+        children: Option<&BTreeMap<String, Vec<String>>>,
+        store: &Store,
+        registry: &ConstraintFamilyRegistry,
+    ) -> Result<Self> {
+        Self::build(ept, children, None, store, registry)
+    }
+
+    /// Like [`ConstraintsEnvelope::from_ept_with_registry`], also passing each
+    /// Ettle's WHY/WHAT/HOW so that content families such as `doc_coverage`
+    /// are evaluated.
+    ///
+    /// # Errors
+    ///
+    /// Propagates evaluator errors, e.g. `InvalidInput` for a malformed payload.
+    pub fn from_ept_with_content(
+        ept: &[String],
+        children: Option<&BTreeMap<String, Vec<String>>>,
+        content: &BTreeMap<String, EttleContent>,
+        store: &Store,
+        registry: &ConstraintFamilyRegistry,
+    ) -> Result<Self> {
+        Self::build(ept, children, Some(content), store, registry)
+    }
+
+    fn build(
+        ept: &[String],
+        children: Option<&BTreeMap<String, Vec<String>>>,
+        content: Option<&BTreeMap<String, EttleContent>>,
+        store: &Store,
+        registry: &ConstraintFamilyRegistry,
+    ) -> Result<Self> {
         use crate::constraint_engine;
 
         // Use a synthetic leaf EP ID (first EP in EPT, or empty string for empty EPT)
@@ -95,14 +181,13 @@ impl ConstraintsEnvelope {
         let ctx = ConstraintEvalCtx {
             leaf_ep_id,
             ept_ep_ids: ept.to_vec(),
+            children: children.cloned(),
+            content: content.cloned(),
             policy_ref: String::new(),
             profile_ref: String::new(),
         };
 
-        let eval = constraint_engine::evaluate(&ctx, store).map_err(|e| {
-            ExError::new(ExErrorKind::Serialization)
-                .with_message(format!("constraint_engine::evaluate failed: {}", e))
-        })?;
+        let eval = constraint_engine::evaluate(&ctx, store, registry)?;
 
         // Build declared_refs as plain constraint IDs (ordinal-ordered, deduplicated)
         let declared_refs: Vec<String> = eval
@@ -122,12 +207,21 @@ impl ConstraintsEnvelope {
                 .map(|r| r.constraint_id.clone())
                 .collect();
 
+            // outcomes: per-constraint results from evaluating families
+            let outcomes = match family_eval.opaque_section {
+                Some(serde_json::Value::Object(mut section)) => match section.remove("outcomes") {
+                    Some(serde_json::Value::Array(outcomes)) => outcomes,
+                    _ => Vec::new(),
+                },
+                _ => Vec::new(),
+            };
+
             family_constraints_map.insert(
                 family_name,
                 FamilyConstraints {
                     status: family_eval.status,
                     active_refs,
-                    outcomes: Vec::new(), // Empty in v0
+                    outcomes,
                     evidence: Vec::new(), // Empty in v0
                     digest: family_eval.digest,
                 },
@@ -143,6 +237,103 @@ impl ConstraintsEnvelope {
             constraints_digest: eval.constraints_digest,
         })
     }
+
+    /// Parse an envelope from its JSON form, e.g. a manifest's `constraints`
+    /// value read by an external tool.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidManifest` if the value does not match the envelope schema.
+    pub fn from_json_value(value: &serde_json::Value) -> Result<Self> {
+        Self::deserialize(value).map_err(|e| {
+            ExError::new(ExErrorKind::InvalidManifest)
+                .with_op("parse_constraints_envelope")
+                .with_message(format!("invalid constraints envelope: {}", e))
+        })
+    }
+
+    /// Recompute `constraints_digest` from `declared_refs` and the family
+    /// digests, for comparison with the recorded value.
+    pub fn recompute_digest(&self) -> String {
+        let ref_ids: Vec<&str> = self.declared_refs.iter().map(String::as_str).collect();
+        let family_digests: Vec<(&str, &str)> = self
+            .families
+            .iter()
+            .map(|(name, family)| (name.as_str(), family.digest.as_str()))
+            .collect();
+        constraints_digest(&ref_ids, &family_digests)
+    }
+
+    /// Summarize the envelope: per-family ref counts, statuses and digests.
+    pub fn summary(&self) -> ConstraintsEnvelopeSummary {
+        ConstraintsEnvelopeSummary {
+            declared_ref_count: self.declared_refs.len(),
+            families: self
+                .families
+                .iter()
+                .map(|(name, family)| {
+                    (
+                        name.clone(),
+                        FamilyConstraintsSummary {
+                            status: family.status.clone(),
+                            active_ref_count: family.active_refs.len(),
+                            digest: family.digest.clone(),
+                        },
+                    )
+                })
+                .collect(),
+            constraints_digest: self.constraints_digest.clone(),
+        }
+    }
+}
+
+/// Digest input for [`constraints_digest`]: serialises as
+/// `[[ref_id, ...], [[family, digest], ...]]`.
+#[derive(Serialize)]
+struct ConstraintsDigestInput<'a>(&'a [&'a str], &'a [(&'a str, &'a str)]);
+
+/// Digest of one family: SHA-256 of its constraint IDs as a JSON array.
+pub fn family_digest(constraint_ids: &[&str]) -> String {
+    sha256_json(&constraint_ids)
+}
+
+/// Digest of a whole envelope from its declared ref IDs and
+/// `(family, family_digest)` pairs in family-name order.
+pub fn constraints_digest(declared_ref_ids: &[&str], family_digests: &[(&str, &str)]) -> String {
+    sha256_json(&ConstraintsDigestInput(declared_ref_ids, family_digests))
+}
+
+/// Serialising string slices and tuples to JSON cannot fail.
+fn sha256_json<T: Serialize + ?Sized>(value: &T) -> String {
+    let canonical = serde_json::to_string(value).unwrap_or_default();
+    hex::encode(Sha256::digest(canonical.as_bytes()))
+}
+
+/// Compact view of a [`ConstraintsEnvelope`], returned with snapshot commits
+/// so callers can report what was anchored without fetching the manifest.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct ConstraintsEnvelopeSummary {
+    /// Number of constraint refs declared on the EPT
+    pub declared_ref_count: usize,
+
+    /// Per-family summary, keyed by family name
+    pub families: BTreeMap<String, FamilyConstraintsSummary>,
+
+    /// Digest of the full constraints envelope
+    pub constraints_digest: String,
+}
+
+/// Per-family entry in a [`ConstraintsEnvelopeSummary`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FamilyConstraintsSummary {
+    /// Evaluation status for this family
+    pub status: ConstraintFamilyStatus,
+
+    /// Number of active constraint refs in this family
+    pub active_ref_count: usize,
+
+    /// Digest of this family's data
+    pub digest: String,
 }
 
 /// Family-specific constraint data.
@@ -151,13 +342,13 @@ impl ConstraintsEnvelope {
 /// to coexist without schema lock-in.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct FamilyConstraints {
-    /// Evaluation status for this family (UNCOMPUTED in Phase 1)
+    /// Evaluation status for this family (UNCOMPUTED, SATISFIED or VIOLATED)
     pub status: ConstraintFamilyStatus,
 
     /// Active constraint IDs for this family (plain IDs, not "family:kind:id")
     pub active_refs: Vec<String>,
 
-    /// Outcomes from constraint evaluation
+    /// Per-constraint outcomes from evaluation (empty when UNCOMPUTED)
     pub outcomes: Vec<serde_json::Value>,
 
     /// Evidence supporting the outcomes
@@ -214,6 +405,17 @@ pub struct SnapshotManifest {
 
     /// Optional seed digest (if imported from seed)
     pub seed_digest: Option<String>,
+
+    /// Decision lifecycle check results. Omitted when the check is off, so
+    /// earlier manifests keep their digests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decision_lifecycle: Option<DecisionLifecycleSection>,
+
+    /// Digest of the tree structure alone (EP IDs, ordinals, refinement
+    /// edges). Unlike `semantic_manifest_digest` it ignores content, so a
+    /// diff can tell a restructuring from an edit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topology_digest: Option<String>,
 }
 
 /// Entry in the EPT (Effective Processing Tree).
@@ -227,17 +429,42 @@ pub struct EpEntry {
     /// Zero-based ordinal position in EPT
     pub ordinal: u32,
 
-    /// Whether this EP is normative (always true in v0)
+    /// Whether this EP is normative (always true in v0). Required when parsing;
+    /// flips are reported by the diff as `normative_flag_changed`.
     pub normative: bool,
 
     /// Content digest of the EP
     pub ep_digest: String,
+
+    /// Declared format of the entry's content. Omitted when `markdown`, so
+    /// manifests written before the field existed keep their digests.
+    #[serde(default, skip_serializing_if = "ContentFormat::is_markdown")]
+    pub content_format: ContentFormat,
+}
+
+/// Relation data and content a manifest's constraint families are evaluated
+/// against.
+///
+/// The default input has none, so evaluating families report `UNCOMPUTED`.
+#[derive(Debug, Clone, Default)]
+pub struct ManifestEvalInput {
+    /// Refinement children of each EPT Ettle, for `cardinality`
+    pub children: Option<BTreeMap<String, Vec<String>>>,
+    /// WHY/WHAT/HOW of each EPT Ettle, for `doc_coverage` and the entries'
+    /// `ep_digest`
+    pub content: Option<BTreeMap<String, EttleContent>>,
+    /// Bindings for `{{variables}}` in `content`, resolved before evaluation
+    pub variables: Option<TemplateVariables>,
+    /// Whether an unresolved `{{variable}}` in `content` fails generation
+    pub template_mode: TemplateMode,
 }
 
 /// Generate a snapshot manifest from EPT state.
 ///
 /// Creates a canonical manifest with all required fields, computed digests,
 /// and RFC3339 timestamp. The manifest is ready for persistence to CAS.
+/// Constraints are declared but evaluating families stay `UNCOMPUTED`; use
+/// [`generate_manifest_with_input`] to evaluate them.
 ///
 /// ## Arguments
 ///
@@ -281,6 +508,40 @@ pub fn generate_manifest(
     store_schema_version: String,
     seed_digest: Option<String>,
     store: &Store,
+) -> Result<SnapshotManifest> {
+    generate_manifest_with_input(
+        ept,
+        policy_ref,
+        profile_ref,
+        root_ettle_id,
+        store_schema_version,
+        seed_digest,
+        store,
+        &ManifestEvalInput::default(),
+    )
+}
+
+/// Like [`generate_manifest`], evaluating the built-in constraint families
+/// against `input`.
+///
+/// When `input.variables` is set, `{{variables}}` in `input.content` are
+/// resolved first, so families see the content as it would render.
+///
+/// # Errors
+///
+/// Returns `Serialization` if digest computation fails, `InvalidInput`
+/// for a malformed constraint payload, or `UnresolvedVariable` when
+/// `input.template_mode` is `Strict` and content has an unbound variable.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_manifest_with_input(
+    ept: Vec<String>,
+    policy_ref: String,
+    profile_ref: String,
+    root_ettle_id: String,
+    store_schema_version: String,
+    seed_digest: Option<String>,
+    store: &Store,
+    input: &ManifestEvalInput,
 ) -> Result<SnapshotManifest> {
     use super::digest::{compute_ept_digest, compute_manifest_digest, compute_semantic_digest};
 
@@ -292,7 +553,9 @@ pub fn generate_manifest(
             ep_id: ep_id.clone(),
             ordinal: idx as u32,
             normative: true,
-            ep_digest: ep_content_digest(ep_id, store),
+            ep_digest: ep_content_digest(ep_id, input.content.as_ref().and_then(|c| c.get(ep_id))),
+            // EP content is retired; entries carry no content of their own.
+            content_format: ContentFormat::Markdown,
         })
         .collect();
 
@@ -302,8 +565,20 @@ pub fn generate_manifest(
     // Generate timestamp
     let created_at = chrono::Utc::now().to_rfc3339();
 
+    // Resolve template variables in content before evaluating families
+    let content = match (&input.content, &input.variables) {
+        (Some(content), Some(vars)) => Some(resolve_content(content, vars, input.template_mode)?),
+        (content, _) => content.clone(),
+    };
+
     // Create constraints envelope from EPT
-    let constraints = ConstraintsEnvelope::from_ept(&ept, store)?;
+    let constraints = ConstraintsEnvelope::build(
+        &ept,
+        input.children.as_ref(),
+        content.as_ref(),
+        store,
+        &ConstraintFamilyRegistry::builtin(),
+    )?;
 
     // Create manifest (without full digest initially)
     let mut manifest = SnapshotManifest {
@@ -321,6 +596,8 @@ pub fn generate_manifest(
         semantic_manifest_digest: String::new(), // Computed below
         store_schema_version,
         seed_digest,
+        decision_lifecycle: None,
+        topology_digest: None,
     };
 
     // Compute digests (with and without timestamp)
@@ -330,11 +607,43 @@ pub fn generate_manifest(
     Ok(manifest)
 }
 
+/// Resolve `{{variables}}` in every section of `content`.
+fn resolve_content(
+    content: &BTreeMap<String, EttleContent>,
+    vars: &TemplateVariables,
+    mode: TemplateMode,
+) -> Result<BTreeMap<String, EttleContent>> {
+    content
+        .iter()
+        .map(|(ettle_id, c)| {
+            let resolve = |text: &str| {
+                resolve_template(text, vars, mode).map_err(|e| {
+                    e.with_op("generate_manifest")
+                        .with_entity_id(ettle_id.as_str())
+                })
+            };
+            Ok((
+                ettle_id.clone(),
+                EttleContent {
+                    why: resolve(&c.why)?,
+                    what: resolve(&c.what)?,
+                    how: resolve(&c.how)?,
+                },
+            ))
+        })
+        .collect()
+}
+
 /// Derive the ep_digest for a manifest entry.
 ///
-/// EP-era content_digest is retired in Slice 03. Falls back to a SHA-256 of the
-/// ep_id string so callers always get a 64-char hex string.
-fn ep_content_digest(ep_id: &str, _store: &Store) -> String {
+/// With the entry's stored content at hand this is the digest of its
+/// [`EpContent`] blob, so a content diff can load it back from CAS. Without
+/// it, falls back to a SHA-256 of the ep_id string so callers always get a
+/// 64-char hex string.
+fn ep_content_digest(ep_id: &str, content: Option<&EttleContent>) -> String {
+    if let Some(content) = content {
+        return EpContent::from(content).digest();
+    }
     use sha2::{Digest, Sha256};
     let mut hasher = Sha256::new();
     hasher.update(ep_id.as_bytes());
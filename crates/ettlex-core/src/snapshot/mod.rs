@@ -8,15 +8,30 @@
 //! - Generate canonical snapshot manifests from EPT state
 //! - Compute deterministic digests (EPT, manifest, semantic)
 //! - Define snapshot manifest schema
+//! - Parse stored manifests back into typed form
 //!
 //! ## Non-Responsibilities
 //!
 //! - Persistence (handled by `ettlex-store`)
 //! - Orchestration (handled by `ettlex-engine`)
 
+pub mod decision_lifecycle;
 pub mod digest;
 pub mod manifest;
+pub mod parse;
 
 // Re-export primary types
-pub use digest::{compute_ept_digest, compute_manifest_digest, compute_semantic_digest};
-pub use manifest::{generate_manifest, EpEntry, SnapshotManifest};
+pub use decision_lifecycle::{
+    apply_decision_lifecycle, record_decision_lifecycle, DecisionLifecycleMode,
+    DecisionLifecyclePolicy, DecisionLifecycleSection, ProposedDecisionFinding,
+};
+pub use digest::{
+    compute_ept_digest, compute_manifest_digest, compute_semantic_digest, compute_topology_digest,
+    record_topology_digest, RefinementEdges,
+};
+pub use manifest::{
+    constraints_digest, family_digest, generate_manifest, generate_manifest_with_input,
+    ConstraintsEnvelope, ConstraintsEnvelopeSummary, EpEntry, FamilyConstraints,
+    FamilyConstraintsSummary, ManifestEvalInput, SnapshotManifest,
+};
+pub use parse::ParsedManifest;
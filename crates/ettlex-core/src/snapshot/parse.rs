@@ -0,0 +1,164 @@
+//! Parsing of stored snapshot manifests.
+//!
+//! [`ParsedManifest::parse`] is the one place manifest bytes are turned back
+//! into a [`SnapshotManifest`]. It validates the fields every reader relies on
+//! and keeps unknown top-level fields instead of dropping them.
+
+#![allow(clippy::result_large_err)]
+
+use crate::errors::{ExError, ExErrorKind};
+use crate::snapshot::manifest::SnapshotManifest;
+use serde::de::{Deserialize, Deserializer, IgnoredAny, MapAccess, Visitor};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Known top-level manifest fields used for unknown-field detection.
+const KNOWN_FIELDS: &[&str] = &[
+    "manifest_schema_version",
+    "created_at",
+    "policy_ref",
+    "profile_ref",
+    "ept",
+    "constraints",
+    "coverage",
+    "exceptions",
+    "root_ettle_id",
+    "ept_digest",
+    "manifest_digest",
+    "semantic_manifest_digest",
+    "store_schema_version",
+    "seed_digest",
+    "decision_lifecycle",
+    "topology_digest",
+];
+
+/// A manifest parsed from CAS bytes, with unknown fields preserved.
+///
+/// Holds the typed manifest plus any top-level fields this version does not
+/// know about, so manifests written by newer code still load. Parsing is the
+/// dominant cost of a diff on large manifests; callers comparing one manifest
+/// against several others should parse it once and use
+/// [`compute_diff_parsed`](crate::diff::compute_diff_parsed).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedManifest {
+    pub manifest: SnapshotManifest,
+    /// Top-level fields not in the known field list, keyed by name.
+    pub unknown_fields: BTreeMap<String, Value>,
+}
+
+impl ParsedManifest {
+    /// Parse and validate raw manifest bytes.
+    ///
+    /// The text is read twice: a cheap scan that captures unknown fields and
+    /// skips known ones, then a typed parse. No JSON tree is built for the EPT.
+    ///
+    /// # Errors
+    ///
+    /// - `InvalidManifest` — bytes are not valid UTF-8, not valid JSON, or
+    ///   `manifest_schema_version` is not an integer
+    /// - `MissingField` — `semantic_manifest_digest` or `constraints` key absent
+    pub fn parse(bytes: &[u8]) -> Result<Self, ExError> {
+        // 1. UTF-8 decode
+        let text = std::str::from_utf8(bytes).map_err(|e| {
+            ExError::new(ExErrorKind::InvalidManifest)
+                .with_op("parse_manifest_bytes")
+                .with_message(format!("manifest is not valid UTF-8: {}", e))
+        })?;
+
+        // 2. Scan top-level keys without building a tree for known fields
+        let scan: TopLevelScan = serde_json::from_str(text).map_err(|e| {
+            ExError::new(ExErrorKind::InvalidManifest)
+                .with_op("parse_manifest_bytes")
+                .with_message(format!("manifest is not valid JSON: {}", e))
+        })?;
+
+        // 3. schema_version must be an integer
+        let schema_version_key = "manifest_schema_version";
+        if let Some(sv) = &scan.schema_version {
+            if !sv.is_number() || sv.as_u64().is_none() {
+                return Err(ExError::new(ExErrorKind::InvalidManifest)
+                    .with_op("parse_manifest_bytes")
+                    .with_message(format!(
+                        "`{}` must be an unsigned integer, got: {}",
+                        schema_version_key, sv
+                    )));
+            }
+        }
+        // If absent we still allow serde to decide (it will error in step 6 if required)
+
+        // 4. semantic_manifest_digest must be present
+        if !scan.has_semantic_digest {
+            return Err(ExError::new(ExErrorKind::MissingField)
+                .with_op("parse_manifest_bytes")
+                .with_message("required field `semantic_manifest_digest` is absent"));
+        }
+
+        // 5. constraints must be present
+        if !scan.has_constraints {
+            return Err(ExError::new(ExErrorKind::MissingField)
+                .with_op("parse_manifest_bytes")
+                .with_message("required field `constraints` is absent"));
+        }
+
+        // 6. Typed deserialisation straight from the text (unknown keys are skipped)
+        let manifest: SnapshotManifest = serde_json::from_str(text).map_err(|e| {
+            ExError::new(ExErrorKind::InvalidManifest)
+                .with_op("parse_manifest_bytes")
+                .with_message(format!("failed to deserialize manifest: {}", e))
+        })?;
+
+        Ok(Self {
+            manifest,
+            unknown_fields: scan.unknown_fields,
+        })
+    }
+}
+
+/// First parsing pass: records which required keys are present and captures
+/// unknown fields, skipping the values of known fields.
+struct TopLevelScan {
+    schema_version: Option<Value>,
+    has_semantic_digest: bool,
+    has_constraints: bool,
+    unknown_fields: BTreeMap<String, Value>,
+}
+
+impl<'de> Deserialize<'de> for TopLevelScan {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ScanVisitor;
+
+        impl<'de> Visitor<'de> for ScanVisitor {
+            type Value = TopLevelScan;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a manifest JSON object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<TopLevelScan, A::Error> {
+                let mut scan = TopLevelScan {
+                    schema_version: None,
+                    has_semantic_digest: false,
+                    has_constraints: false,
+                    unknown_fields: BTreeMap::new(),
+                };
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "manifest_schema_version" => scan.schema_version = Some(map.next_value()?),
+                        k if KNOWN_FIELDS.contains(&k) => {
+                            scan.has_semantic_digest |= k == "semantic_manifest_digest";
+                            scan.has_constraints |= k == "constraints";
+                            map.next_value::<IgnoredAny>()?;
+                        }
+                        _ => {
+                            let value = map.next_value()?;
+                            scan.unknown_fields.insert(key, value);
+                        }
+                    }
+                }
+                Ok(scan)
+            }
+        }
+
+        deserializer.deserialize_map(ScanVisitor)
+    }
+}
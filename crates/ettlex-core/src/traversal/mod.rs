@@ -1,5 +1,7 @@
 pub mod ept;
 pub mod rt;
+pub mod visit;
 
 pub use ept::compute_ept;
 pub use rt::compute_rt;
+pub use visit::{walk, walk_iterative_deepening, EttleVisitor, VisitControl, WalkOptions};
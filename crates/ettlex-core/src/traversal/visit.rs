@@ -0,0 +1,232 @@
+//! Visitor-based traversal over the hydrated store.
+//!
+//! The store holds no parent links, so callers supply the refinement
+//! adjacency as a parent → ordered children map (the same shape the
+//! constraint engine takes). Walks are iterative, so tree depth is not
+//! bounded by the call stack, and results can be streamed out of the
+//! visitor callbacks instead of being collected into a tree first.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::errors::Result;
+use crate::model::Ettle;
+use crate::ops::Store;
+
+/// What the walk should do after a callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VisitControl {
+    /// Keep going.
+    Continue,
+    /// Do not descend into this Ettle's children (pre-order only; treated
+    /// as `Continue` when returned from `post`).
+    SkipChildren,
+    /// End the walk now; no further callbacks are made.
+    Stop,
+}
+
+/// Callbacks made during a walk. Both default to `Continue`.
+pub trait EttleVisitor {
+    /// Called when an Ettle is entered, before its children.
+    ///
+    /// # Errors
+    ///
+    /// An error aborts the walk and is returned to the caller.
+    #[allow(clippy::result_large_err)]
+    fn pre(&mut self, _ettle: &Ettle, _depth: usize) -> Result<VisitControl> {
+        Ok(VisitControl::Continue)
+    }
+
+    /// Called when an Ettle is left, after its children.
+    ///
+    /// # Errors
+    ///
+    /// An error aborts the walk and is returned to the caller.
+    #[allow(clippy::result_large_err)]
+    fn post(&mut self, _ettle: &Ettle, _depth: usize) -> Result<VisitControl> {
+        Ok(VisitControl::Continue)
+    }
+}
+
+/// Options for [`walk`].
+#[derive(Debug, Clone, Default)]
+pub struct WalkOptions {
+    /// Do not descend below this depth (the root is depth 0).
+    pub max_depth: Option<usize>,
+}
+
+/// Depth-first walk from `root_id`, calling `visitor.pre` on entry and
+/// `visitor.post` on exit of each Ettle.
+///
+/// Children are visited in the order given by `children`. Child IDs missing
+/// from the store are skipped, and so is any Ettle already on the path from
+/// the root, so a cycle cannot loop forever. Returns `Stop` if the visitor
+/// ended the walk early, `Continue` otherwise.
+///
+/// # Errors
+///
+/// * `NotFound` - `root_id` is not in the store
+/// * Any error returned by the visitor
+#[allow(clippy::result_large_err)]
+pub fn walk<V: EttleVisitor + ?Sized>(
+    store: &Store,
+    children: &BTreeMap<String, Vec<String>>,
+    root_id: &str,
+    options: &WalkOptions,
+    visitor: &mut V,
+) -> Result<VisitControl> {
+    struct Frame<'a> {
+        ettle: &'a Ettle,
+        depth: usize,
+        next_child: usize,
+    }
+
+    fn enter<'a, V: EttleVisitor + ?Sized>(
+        ettle: &'a Ettle,
+        depth: usize,
+        options: &WalkOptions,
+        visitor: &mut V,
+        path: &mut BTreeSet<&'a str>,
+        stack: &mut Vec<Frame<'a>>,
+    ) -> Result<VisitControl> {
+        match visitor.pre(ettle, depth)? {
+            VisitControl::Stop => return Ok(VisitControl::Stop),
+            VisitControl::SkipChildren => {
+                return Ok(match visitor.post(ettle, depth)? {
+                    VisitControl::Stop => VisitControl::Stop,
+                    _ => VisitControl::Continue,
+                })
+            }
+            VisitControl::Continue => {}
+        }
+        // A frame past `max_depth` has no children to walk.
+        let next_child = if options.max_depth.is_some_and(|max| depth >= max) {
+            usize::MAX
+        } else {
+            0
+        };
+        path.insert(ettle.id.as_str());
+        stack.push(Frame {
+            ettle,
+            depth,
+            next_child,
+        });
+        Ok(VisitControl::Continue)
+    }
+
+    let root = store.get_ettle(root_id)?;
+    let mut path = BTreeSet::new();
+    let mut stack = Vec::new();
+    if enter(root, 0, options, visitor, &mut path, &mut stack)? == VisitControl::Stop {
+        return Ok(VisitControl::Stop);
+    }
+
+    while let Some(frame) = stack.last_mut() {
+        let next = children
+            .get(&frame.ettle.id)
+            .and_then(|ids| ids.get(frame.next_child));
+        match next {
+            Some(child_id) => {
+                frame.next_child += 1;
+                let depth = frame.depth + 1;
+                if path.contains(child_id.as_str()) {
+                    continue;
+                }
+                let Ok(child) = store.get_ettle(child_id) else {
+                    continue;
+                };
+                if enter(child, depth, options, visitor, &mut path, &mut stack)?
+                    == VisitControl::Stop
+                {
+                    return Ok(VisitControl::Stop);
+                }
+            }
+            None => {
+                let Some(Frame { ettle, depth, .. }) = stack.pop() else {
+                    break;
+                };
+                path.remove(ettle.id.as_str());
+                if visitor.post(ettle, depth)? == VisitControl::Stop {
+                    return Ok(VisitControl::Stop);
+                }
+            }
+        }
+    }
+    Ok(VisitControl::Continue)
+}
+
+/// Iterative-deepening walk from `root_id`: depth-limited [`walk`]s with a
+/// limit of 0, 1, 2, …, calling `visit` once for each Ettle at the limit
+/// depth. Ettles are therefore reported level by level (breadth-first
+/// order) while only the current path is held in memory.
+///
+/// Deepening stops when a level has no Ettles, after `max_depth` if given,
+/// or when `visit` returns `Stop`. `SkipChildren` prunes the Ettle's
+/// subtree from deeper levels. An Ettle reachable at several depths is
+/// reported at each of them.
+///
+/// # Errors
+///
+/// * `NotFound` - `root_id` is not in the store
+/// * Any error returned by `visit`
+#[allow(clippy::result_large_err)]
+pub fn walk_iterative_deepening<F>(
+    store: &Store,
+    children: &BTreeMap<String, Vec<String>>,
+    root_id: &str,
+    max_depth: Option<usize>,
+    mut visit: F,
+) -> Result<VisitControl>
+where
+    F: FnMut(&Ettle, usize) -> Result<VisitControl>,
+{
+    struct Level<'f, F> {
+        limit: usize,
+        visit: &'f mut F,
+        reached: bool,
+        pruned: &'f mut BTreeSet<(usize, String)>,
+    }
+
+    impl<F> EttleVisitor for Level<'_, F>
+    where
+        F: FnMut(&Ettle, usize) -> Result<VisitControl>,
+    {
+        fn pre(&mut self, ettle: &Ettle, depth: usize) -> Result<VisitControl> {
+            if self.pruned.contains(&(depth, ettle.id.clone())) {
+                return Ok(VisitControl::SkipChildren);
+            }
+            if depth < self.limit {
+                return Ok(VisitControl::Continue);
+            }
+            self.reached = true;
+            match (self.visit)(ettle, depth)? {
+                VisitControl::SkipChildren => {
+                    self.pruned.insert((depth, ettle.id.clone()));
+                    Ok(VisitControl::SkipChildren)
+                }
+                control => Ok(control),
+            }
+        }
+    }
+
+    let mut pruned = BTreeSet::new();
+    let mut limit = 0;
+    while max_depth.map_or(true, |max| limit <= max) {
+        let mut level = Level {
+            limit,
+            visit: &mut visit,
+            reached: false,
+            pruned: &mut pruned,
+        };
+        let options = WalkOptions {
+            max_depth: Some(limit),
+        };
+        if walk(store, children, root_id, &options, &mut level)? == VisitControl::Stop {
+            return Ok(VisitControl::Stop);
+        }
+        if !level.reached {
+            break;
+        }
+        limit += 1;
+    }
+    Ok(VisitControl::Continue)
+}
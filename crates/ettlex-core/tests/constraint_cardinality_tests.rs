@@ -0,0 +1,189 @@
+// Test suite for the `cardinality` constraint family
+// Tests declaration against the EPT, SATISFIED/VIOLATED evaluation, outcomes in the
+// envelope, UNCOMPUTED without relation data, manifest generation with an
+// evaluation input, and payload validation
+
+use ettlex_core::constraint_engine::ConstraintFamilyStatus;
+use ettlex_core::errors::ExErrorKind;
+use ettlex_core::model::Constraint;
+use ettlex_core::ops::constraint_ops::tombstone_constraint;
+use ettlex_core::ops::Store;
+use ettlex_core::snapshot::manifest::{
+    generate_manifest, generate_manifest_with_input, ConstraintsEnvelope, ManifestEvalInput,
+};
+use serde_json::json;
+use std::collections::BTreeMap;
+
+fn store_with(constraints: &[(&str, serde_json::Value)]) -> Store {
+    let mut store = Store::new();
+    for (id, payload) in constraints {
+        store.insert_constraint(Constraint::new(
+            id.to_string(),
+            "cardinality".to_string(),
+            "ChildCount".to_string(),
+            "Ettle".to_string(),
+            payload.clone(),
+        ));
+    }
+    store
+}
+
+fn ept() -> Vec<String> {
+    ["ettle:root", "ettle:a", "ettle:b", "ettle:c"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn children() -> BTreeMap<String, Vec<String>> {
+    let mut children = BTreeMap::new();
+    children.insert(
+        "ettle:root".to_string(),
+        vec![
+            "ettle:a".to_string(),
+            "ettle:b".to_string(),
+            // Outside the EPT, so not counted
+            "ettle:elsewhere".to_string(),
+        ],
+    );
+    children.insert("ettle:a".to_string(), vec!["ettle:c".to_string()]);
+    children
+}
+
+#[test]
+fn test_cardinality_family_satisfied_records_outcomes() {
+    let store = store_with(&[
+        ("c2", json!({"ettle_id": "ettle:root", "min": 1, "max": 2})),
+        ("c1", json!({"ettle_id": "ettle:a", "max": 1})),
+        ("c0", json!({"ettle_id": "ettle:b", "max": 0})),
+    ]);
+
+    let envelope =
+        ConstraintsEnvelope::from_ept_with_children(&ept(), &children(), &store).unwrap();
+
+    // Ordered by EPT position of the target Ettle
+    assert_eq!(envelope.declared_refs, vec!["c2", "c1", "c0"]);
+    let family = &envelope.families["cardinality"];
+    assert_eq!(family.status, ConstraintFamilyStatus::Satisfied);
+    assert_eq!(family.active_refs, vec!["c2", "c1", "c0"]);
+    assert_eq!(family.outcomes.len(), 3);
+    assert_eq!(
+        family.outcomes[0],
+        json!({"constraint_id": "c2", "ettle_id": "ettle:root", "min": 1, "max": 2,
+               "child_count": 2, "status": "SATISFIED"})
+    );
+    assert_eq!(envelope.recompute_digest(), envelope.constraints_digest);
+}
+
+#[test]
+fn test_cardinality_family_violated_when_any_bound_fails() {
+    let store = store_with(&[
+        ("ok", json!({"ettle_id": "ettle:a", "min": 1})),
+        ("too-few", json!({"ettle_id": "ettle:c", "min": 1})),
+    ]);
+
+    let envelope =
+        ConstraintsEnvelope::from_ept_with_children(&ept(), &children(), &store).unwrap();
+
+    let family = &envelope.families["cardinality"];
+    assert_eq!(family.status, ConstraintFamilyStatus::Violated);
+    let statuses: Vec<&str> = family
+        .outcomes
+        .iter()
+        .map(|o| o["status"].as_str().unwrap())
+        .collect();
+    assert_eq!(statuses, vec!["SATISFIED", "VIOLATED"]);
+    assert_eq!(family.outcomes[1]["child_count"], json!(0));
+}
+
+#[test]
+fn test_cardinality_without_relation_data_is_uncomputed() {
+    let store = store_with(&[("c1", json!({"ettle_id": "ettle:a", "min": 5}))]);
+
+    let envelope = ConstraintsEnvelope::from_ept(&ept(), &store).unwrap();
+
+    assert_eq!(envelope.declared_refs, vec!["c1"]);
+    let family = &envelope.families["cardinality"];
+    assert_eq!(family.status, ConstraintFamilyStatus::Uncomputed);
+    assert!(family.outcomes.is_empty());
+}
+
+#[test]
+fn test_generate_manifest_with_input_evaluates_cardinality() {
+    let store = store_with(&[("c1", json!({"ettle_id": "ettle:root", "max": 1}))]);
+    let generate = |input: &ManifestEvalInput| {
+        generate_manifest_with_input(
+            ept(),
+            "policy/default@0".to_string(),
+            "profile/default@0".to_string(),
+            "ettle:root".to_string(),
+            "0001".to_string(),
+            None,
+            &store,
+            input,
+        )
+        .unwrap()
+    };
+
+    // generate_manifest evaluates with no relation data
+    let plain = generate_manifest(
+        ept(),
+        "policy/default@0".to_string(),
+        "profile/default@0".to_string(),
+        "ettle:root".to_string(),
+        "0001".to_string(),
+        None,
+        &store,
+    )
+    .unwrap();
+    assert_eq!(
+        plain.semantic_manifest_digest,
+        generate(&ManifestEvalInput::default()).semantic_manifest_digest
+    );
+    assert_eq!(
+        plain.constraints.families["cardinality"].status,
+        ConstraintFamilyStatus::Uncomputed
+    );
+
+    let evaluated = generate(&ManifestEvalInput {
+        children: Some(children()),
+        ..Default::default()
+    });
+    let family = &evaluated.constraints.families["cardinality"];
+    assert_eq!(family.status, ConstraintFamilyStatus::Violated);
+    assert_eq!(family.outcomes[0]["child_count"], 2);
+    assert_ne!(
+        evaluated.semantic_manifest_digest,
+        plain.semantic_manifest_digest
+    );
+}
+
+#[test]
+fn test_cardinality_ignores_other_ettles_and_tombstoned_constraints() {
+    let mut store = store_with(&[
+        ("off-ept", json!({"ettle_id": "ettle:elsewhere", "min": 9})),
+        ("gone", json!({"ettle_id": "ettle:a", "min": 9})),
+    ]);
+    tombstone_constraint(&mut store, "gone").unwrap();
+
+    let envelope =
+        ConstraintsEnvelope::from_ept_with_children(&ept(), &children(), &store).unwrap();
+
+    assert!(envelope.declared_refs.is_empty());
+    assert!(envelope.families.is_empty());
+}
+
+#[test]
+fn test_malformed_cardinality_payload_is_invalid_input() {
+    for payload in [
+        json!({"min": 1}),
+        json!({"ettle_id": "ettle:a"}),
+        json!({"ettle_id": "ettle:a", "min": -1}),
+        json!({"ettle_id": "ettle:a", "min": 3, "max": 2}),
+    ] {
+        let store = store_with(&[("bad", payload)]);
+        let err =
+            ConstraintsEnvelope::from_ept_with_children(&ept(), &children(), &store).unwrap_err();
+        assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+    }
+}
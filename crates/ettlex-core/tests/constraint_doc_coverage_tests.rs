@@ -0,0 +1,222 @@
+// Test suite for the `doc_coverage` constraint family
+// Tests per-Ettle failures in outcomes, default and configurable minimum lengths,
+// UNCOMPUTED without content, manifest generation with content, template
+// variable resolution, and payload validation
+
+use ettlex_core::constraint_engine::{
+    ConstraintFamilyRegistry, ConstraintFamilyStatus, EttleContent,
+};
+use ettlex_core::errors::ExErrorKind;
+use ettlex_core::model::Constraint;
+use ettlex_core::ops::Store;
+use ettlex_core::render::{TemplateMode, TemplateVariables};
+use ettlex_core::snapshot::manifest::{
+    generate_manifest_with_input, ConstraintsEnvelope, ManifestEvalInput,
+};
+use serde_json::json;
+use std::collections::BTreeMap;
+
+fn store_with(constraints: &[(&str, serde_json::Value)]) -> Store {
+    let mut store = Store::new();
+    for (id, payload) in constraints {
+        store.insert_constraint(Constraint::new(
+            id.to_string(),
+            "doc_coverage".to_string(),
+            "MinLength".to_string(),
+            "Ettle".to_string(),
+            payload.clone(),
+        ));
+    }
+    store
+}
+
+fn ept() -> Vec<String> {
+    ["ettle:root", "ettle:a", "ettle:b"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn content() -> BTreeMap<String, EttleContent> {
+    let doc = |why: &str, what: &str, how: &str| EttleContent {
+        why: why.to_string(),
+        what: what.to_string(),
+        how: how.to_string(),
+    };
+    let mut content = BTreeMap::new();
+    content.insert(
+        "ettle:root".to_string(),
+        doc("Payments must settle.", "A ledger.", "Double entry."),
+    );
+    content.insert("ettle:a".to_string(), doc("   ", "Refunds.", "Reverse."));
+    // ettle:b has no content at all
+    content
+}
+
+fn envelope(ept: &[String], store: &Store) -> ConstraintsEnvelope {
+    ConstraintsEnvelope::from_ept_with_content(
+        ept,
+        None,
+        &content(),
+        store,
+        &ConstraintFamilyRegistry::builtin(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_doc_coverage_reports_failing_ettles_and_fields() {
+    let store = store_with(&[("d1", json!({"ettle_id": "ettle:root"}))]);
+
+    let envelope = envelope(&ept(), &store);
+
+    let family = &envelope.families["doc_coverage"];
+    assert_eq!(family.status, ConstraintFamilyStatus::Violated);
+    assert_eq!(
+        family.outcomes,
+        vec![json!({
+            "constraint_id": "d1", "ettle_id": "ettle:root",
+            "min_why": 1, "min_what": 1, "min_how": 1,
+            "checked": 3, "status": "VIOLATED",
+            "failures": [
+                {"ettle_id": "ettle:a", "fields": [{"field": "why", "length": 0, "min": 1}]},
+                {"ettle_id": "ettle:b", "fields": [
+                    {"field": "why", "length": 0, "min": 1},
+                    {"field": "what", "length": 0, "min": 1},
+                    {"field": "how", "length": 0, "min": 1}
+                ]}
+            ]
+        })]
+    );
+}
+
+#[test]
+fn test_doc_coverage_minimums_are_configurable() {
+    // Only WHAT is checked; the blank WHY of ettle:a no longer fails
+    let ept = ept()[..2].to_vec();
+    let store = store_with(&[(
+        "d1",
+        json!({"ettle_id": "ettle:a", "min_why": 0, "min_what": 8, "min_how": 0}),
+    )]);
+
+    let family = &envelope(&ept, &store).families["doc_coverage"];
+    assert_eq!(family.status, ConstraintFamilyStatus::Satisfied);
+    assert_eq!(family.outcomes[0]["failures"], json!([]));
+
+    // Lengths are counted after trimming: "Refunds." is 8 characters
+    let store = store_with(&[(
+        "d2",
+        json!({"ettle_id": "ettle:a", "min_why": 0, "min_what": 9, "min_how": 0}),
+    )]);
+
+    let family = &envelope(&ept, &store).families["doc_coverage"];
+    assert_eq!(family.status, ConstraintFamilyStatus::Violated);
+    assert_eq!(
+        family.outcomes[0]["failures"],
+        json!([{"ettle_id": "ettle:a", "fields": [{"field": "what", "length": 8, "min": 9}]}])
+    );
+}
+
+#[test]
+fn test_doc_coverage_without_content_is_uncomputed() {
+    let store = store_with(&[("d1", json!({"ettle_id": "ettle:root"}))]);
+
+    let envelope = ConstraintsEnvelope::from_ept(&ept(), &store).unwrap();
+
+    assert_eq!(envelope.declared_refs, vec!["d1"]);
+    let family = &envelope.families["doc_coverage"];
+    assert_eq!(family.status, ConstraintFamilyStatus::Uncomputed);
+    assert!(family.outcomes.is_empty());
+}
+
+#[test]
+fn test_generate_manifest_with_input_evaluates_doc_coverage() {
+    let store = store_with(&[("d1", json!({"ettle_id": "ettle:root"}))]);
+
+    let manifest = generate_manifest_with_input(
+        ept(),
+        "policy/default@0".to_string(),
+        "profile/default@0".to_string(),
+        "ettle:root".to_string(),
+        "0001".to_string(),
+        None,
+        &store,
+        &ManifestEvalInput {
+            content: Some(content()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let family = &manifest.constraints.families["doc_coverage"];
+    assert_eq!(family.status, ConstraintFamilyStatus::Violated);
+    assert_eq!(
+        family.outcomes,
+        envelope(&ept(), &store).families["doc_coverage"].outcomes
+    );
+}
+
+#[test]
+fn test_manifest_generation_resolves_template_variables_in_content() {
+    let ept = vec!["ettle:a".to_string()];
+    let store = store_with(&[(
+        "d1",
+        json!({"ettle_id": "ettle:a", "min_why": 0, "min_what": 12, "min_how": 0}),
+    )]);
+    let mut content = BTreeMap::new();
+    content.insert(
+        "ettle:a".to_string(),
+        EttleContent {
+            why: String::new(),
+            what: "Runs in {{env}}.".to_string(),
+            how: String::new(),
+        },
+    );
+    let generate = |variables: Option<TemplateVariables>, template_mode: TemplateMode| {
+        generate_manifest_with_input(
+            ept.clone(),
+            "policy/default@0".to_string(),
+            "profile/default@0".to_string(),
+            "ettle:a".to_string(),
+            "0001".to_string(),
+            None,
+            &store,
+            &ManifestEvalInput {
+                content: Some(content.clone()),
+                variables,
+                template_mode,
+                ..Default::default()
+            },
+        )
+    };
+
+    // "Runs in production." clears the minimum that "Runs in ." would not
+    let mut vars = TemplateVariables::new();
+    vars.insert("env".to_string(), "production".to_string());
+    let manifest = generate(Some(vars), TemplateMode::Strict).unwrap();
+    assert_eq!(
+        manifest.constraints.families["doc_coverage"].status,
+        ConstraintFamilyStatus::Satisfied
+    );
+
+    let err = generate(Some(TemplateVariables::new()), TemplateMode::Strict).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::UnresolvedVariable);
+    assert_eq!(err.entity_id(), Some("ettle:a"));
+
+    // Lenient leaves the placeholder in place
+    generate(Some(TemplateVariables::new()), TemplateMode::Lenient).unwrap();
+}
+
+#[test]
+fn test_malformed_doc_coverage_payload_is_invalid_input() {
+    for payload in [
+        json!({"min_why": 1}),
+        json!({"ettle_id": " "}),
+        json!({"ettle_id": "ettle:a", "min_how": -1}),
+        json!({"ettle_id": "ettle:a", "min_what": "long"}),
+    ] {
+        let store = store_with(&[("bad", payload)]);
+        let err = ConstraintsEnvelope::from_ept(&ept(), &store).unwrap_err();
+        assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,135 @@
+// Test suite for the constraint family evaluator registry
+// Tests custom evaluators plugged into evaluation, registration conflicts, and that
+// only registered families are declared
+
+use ettlex_core::constraint_engine::{
+    ConstraintEvalCtx, ConstraintFamilyEvaluator, ConstraintFamilyRegistry, ConstraintFamilyStatus,
+};
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::model::Constraint;
+use ettlex_core::ops::Store;
+use ettlex_core::snapshot::manifest::ConstraintsEnvelope;
+use serde_json::json;
+
+/// Requires the target Ettle to be the EPT's first entry.
+struct RootOnly;
+
+impl ConstraintFamilyEvaluator for RootOnly {
+    fn family(&self) -> &str {
+        "root-only"
+    }
+
+    fn target_ettle_id(&self, constraint: &Constraint) -> Result<Option<String>, ExError> {
+        Ok(constraint.payload_json["ettle_id"]
+            .as_str()
+            .map(str::to_string))
+    }
+
+    fn evaluate(
+        &self,
+        constraints: &[&Constraint],
+        ctx: &ConstraintEvalCtx,
+    ) -> Result<(ConstraintFamilyStatus, Option<serde_json::Value>), ExError> {
+        let outcomes: Vec<_> = constraints
+            .iter()
+            .map(|c| {
+                let ok = ctx.ept_ep_ids.first()
+                    == c.payload_json["ettle_id"]
+                        .as_str()
+                        .map(str::to_string)
+                        .as_ref();
+                json!({"constraint_id": c.constraint_id, "ok": ok})
+            })
+            .collect();
+        let status = if outcomes.iter().all(|o| o["ok"] == true) {
+            ConstraintFamilyStatus::Satisfied
+        } else {
+            ConstraintFamilyStatus::Violated
+        };
+        Ok((status, Some(json!({ "outcomes": outcomes }))))
+    }
+}
+
+fn store() -> Store {
+    let mut store = Store::new();
+    for (id, family, payload) in [
+        ("r1", "root-only", json!({"ettle_id": "ettle:root"})),
+        ("r2", "root-only", json!({"ettle_id": "ettle:a"})),
+        ("r3", "root-only", json!({})),
+        (
+            "k1",
+            "cardinality",
+            json!({"ettle_id": "ettle:a", "max": 1}),
+        ),
+        ("x1", "unregistered", json!({"ettle_id": "ettle:a"})),
+    ] {
+        store.insert_constraint(Constraint::new(
+            id.to_string(),
+            family.to_string(),
+            "Rule".to_string(),
+            "Ettle".to_string(),
+            payload,
+        ));
+    }
+    store
+}
+
+fn ept() -> Vec<String> {
+    vec!["ettle:root".to_string(), "ettle:a".to_string()]
+}
+
+#[test]
+fn test_custom_evaluator_is_declared_and_evaluated() {
+    let mut registry = ConstraintFamilyRegistry::builtin();
+    registry.register(Box::new(RootOnly)).unwrap();
+    assert_eq!(
+        registry.families(),
+        vec!["cardinality", "doc_coverage", "root-only"]
+    );
+
+    let envelope =
+        ConstraintsEnvelope::from_ept_with_registry(&ept(), None, &store(), &registry).unwrap();
+
+    // r3 has no target and x1's family is unregistered: neither is declared
+    assert_eq!(envelope.declared_refs, vec!["r1", "k1", "r2"]);
+    let family = &envelope.families["root-only"];
+    assert_eq!(family.status, ConstraintFamilyStatus::Violated);
+    assert_eq!(family.active_refs, vec!["r1", "r2"]);
+    assert_eq!(
+        family.outcomes,
+        vec![
+            json!({"constraint_id": "r1", "ok": true}),
+            json!({"constraint_id": "r2", "ok": false})
+        ]
+    );
+    assert_eq!(
+        envelope.families["cardinality"].status,
+        ConstraintFamilyStatus::Uncomputed
+    );
+    assert_eq!(envelope.recompute_digest(), envelope.constraints_digest);
+}
+
+#[test]
+fn test_empty_registry_declares_nothing() {
+    let envelope = ConstraintsEnvelope::from_ept_with_registry(
+        &ept(),
+        None,
+        &store(),
+        &ConstraintFamilyRegistry::new(),
+    )
+    .unwrap();
+
+    assert!(envelope.declared_refs.is_empty());
+    assert!(envelope.families.is_empty());
+}
+
+#[test]
+fn test_registering_a_family_twice_is_already_exists() {
+    let mut registry = ConstraintFamilyRegistry::new();
+    registry.register(Box::new(RootOnly)).unwrap();
+
+    let err = registry.register(Box::new(RootOnly)).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::AlreadyExists);
+    assert!(registry.get("root-only").is_some());
+    assert!(registry.get("cardinality").is_none());
+}
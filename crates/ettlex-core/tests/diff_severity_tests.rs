@@ -0,0 +1,178 @@
+//! Rule-driven severity classification tests for the snapshot diff engine.
+//!
+//! All tests operate exclusively on manifest bytes (no I/O, no DB).
+
+use ettlex_core::diff::engine::{compute_diff, compute_diff_with_rules};
+use ettlex_core::diff::human_summary::render_human_summary;
+use ettlex_core::diff::model::DiffSeverity;
+use ettlex_core::diff::severity::{ChangeCategory, SeverityRules};
+use serde_json::{json, Value};
+
+fn manifest(semantic: &str) -> Value {
+    json!({
+        "manifest_schema_version": 1,
+        "created_at": "2026-01-01T00:00:00Z",
+        "policy_ref": "policy/default@0",
+        "profile_ref": "profile/default@0",
+        "ept": [
+            {"ep_id": "ep:root:0", "ordinal": 0, "normative": true,
+             "ep_digest": "aa00000000000000000000000000000000000000000000000000000000000000"},
+            {"ep_id": "ep:root:1", "ordinal": 1, "normative": false,
+             "ep_digest": "bb00000000000000000000000000000000000000000000000000000000000000"}
+        ],
+        "constraints": {
+            "declared_refs": ["c:security"],
+            "families": {},
+            "applicable_abb": [],
+            "resolved_sbb": [],
+            "resolution_evidence": [],
+            "constraints_digest": "0000000000000000000000000000000000000000000000000000000000000000"
+        },
+        "coverage": {},
+        "exceptions": [],
+        "root_ettle_id": "ettle:root",
+        "ept_digest": "0000000000000000000000000000000000000000000000000000000000000001",
+        "manifest_digest": semantic,
+        "semantic_manifest_digest": semantic,
+        "store_schema_version": "0001",
+        "seed_digest": null
+    })
+}
+
+fn pair() -> (Value, Value) {
+    (
+        manifest("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
+        manifest("bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"),
+    )
+}
+
+fn bytes(v: &Value) -> Vec<u8> {
+    serde_json::to_vec(v).unwrap()
+}
+
+#[test]
+fn test_non_normative_content_change_is_informational_by_default() {
+    let (a, mut b) = pair();
+    b["ept"][1]["ep_digest"] =
+        json!("cc00000000000000000000000000000000000000000000000000000000000000");
+
+    let diff = compute_diff(&bytes(&a), &bytes(&b)).unwrap();
+    assert_eq!(diff.severity, DiffSeverity::Informational);
+    assert_eq!(diff.severity_tags.len(), 1);
+    assert_eq!(
+        diff.severity_tags[0].category,
+        ChangeCategory::NonNormativeContentChanged
+    );
+    assert_eq!(diff.severity_tags[0].subject, "ep:root:1");
+}
+
+#[test]
+fn test_normative_content_change_is_semantic_by_default() {
+    let (a, mut b) = pair();
+    b["ept"][0]["ep_digest"] =
+        json!("cc00000000000000000000000000000000000000000000000000000000000000");
+
+    let diff = compute_diff(&bytes(&a), &bytes(&b)).unwrap();
+    assert_eq!(diff.severity, DiffSeverity::Semantic);
+    assert_eq!(
+        diff.severity_tags[0].category,
+        ChangeCategory::NormativeContentChanged
+    );
+}
+
+#[test]
+fn test_constraint_removal_is_breaking_by_default() {
+    let (a, mut b) = pair();
+    b["constraints"]["declared_refs"] = json!([]);
+
+    let diff = compute_diff(&bytes(&a), &bytes(&b)).unwrap();
+    assert_eq!(diff.severity, DiffSeverity::Breaking);
+    let tag = diff
+        .severity_tags
+        .iter()
+        .find(|t| t.category == ChangeCategory::ConstraintRefRemoved)
+        .unwrap();
+    assert_eq!(tag.subject, "c:security");
+    assert_eq!(tag.severity, DiffSeverity::Breaking);
+
+    let rules = SeverityRules::new()
+        .with_rule(ChangeCategory::ConstraintRefRemoved, DiffSeverity::Semantic);
+    let diff = compute_diff_with_rules(&bytes(&a), &bytes(&b), &rules).unwrap();
+    assert_eq!(diff.severity, DiffSeverity::Semantic);
+}
+
+#[test]
+fn test_rules_can_downgrade_and_roll_up_follows() {
+    let (a, mut b) = pair();
+    b["ept"][0]["ep_digest"] =
+        json!("cc00000000000000000000000000000000000000000000000000000000000000");
+
+    let rules =
+        SeverityRules::from_json(br#"{"normative_content_changed": "Informational"}"#).unwrap();
+    let diff = compute_diff_with_rules(&bytes(&a), &bytes(&b), &rules).unwrap();
+    assert_eq!(diff.severity, DiffSeverity::Informational);
+    assert!(!diff.meets_threshold(&DiffSeverity::Semantic));
+    assert!(diff.meets_threshold(&DiffSeverity::Informational));
+}
+
+#[test]
+fn test_identical_diff_never_meets_threshold() {
+    let (a, _) = pair();
+    let diff = compute_diff(&bytes(&a), &bytes(&a)).unwrap();
+    assert!(diff.severity_tags.is_empty());
+    assert!(!diff.meets_threshold(&DiffSeverity::None));
+}
+
+#[test]
+fn test_human_summary_lists_breakdown_most_severe_first() {
+    let (a, mut b) = pair();
+    b["ept"][1]["ep_digest"] =
+        json!("cc00000000000000000000000000000000000000000000000000000000000000");
+    b["constraints"]["declared_refs"] = json!([]);
+
+    let diff = compute_diff(&bytes(&a), &bytes(&b)).unwrap();
+    let summary = render_human_summary(&diff);
+
+    assert!(summary.contains("### Severity Breakdown"));
+    let breaking = summary
+        .find("| Breaking | constraint_ref_removed |")
+        .unwrap();
+    let info = summary
+        .find("| Informational | non_normative_content_changed |")
+        .unwrap();
+    assert!(breaking < info);
+}
+
+#[test]
+fn test_normative_flip_is_distinct_semantic_change() {
+    let (a, mut b) = pair();
+    b["ept"][1]["normative"] = json!(true);
+
+    let diff = compute_diff(&bytes(&a), &bytes(&b)).unwrap();
+    assert!(diff.ep_content_changes.changed_eps.is_empty());
+    let flips = &diff.ep_content_changes.normative_flips;
+    assert_eq!(flips.len(), 1);
+    assert_eq!(flips[0].ep_id, "ep:root:1");
+    assert!(!flips[0].old && flips[0].new);
+    assert_eq!(diff.severity, DiffSeverity::Semantic);
+    assert_eq!(
+        diff.severity_tags[0].category,
+        ChangeCategory::NormativeFlagChanged
+    );
+
+    let summary = render_human_summary(&diff);
+    assert!(summary.contains("### ⚑ Normative Flag Changes"));
+    assert!(summary.contains("`ep:root:1`: **non-normative → normative**"));
+}
+
+#[test]
+fn test_manifest_without_normative_flag_is_rejected() {
+    let (a, mut b) = pair();
+    b["ept"][0].as_object_mut().unwrap().remove("normative");
+
+    let err = compute_diff(&bytes(&a), &bytes(&b)).unwrap_err();
+    assert_eq!(
+        err.kind(),
+        ettlex_core::errors::ExErrorKind::InvalidManifest
+    );
+}
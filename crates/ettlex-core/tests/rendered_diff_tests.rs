@@ -0,0 +1,85 @@
+//! Rendered snapshot diff: Markdown rendering and line-oriented diff.
+
+use ettlex_core::diff::engine::ParsedManifest;
+use ettlex_core::diff::rendered::{render_manifest_markdown, unified_line_diff};
+use serde_json::{json, Value};
+
+fn manifest(ept: Value, created_at: &str) -> Value {
+    json!({
+        "manifest_schema_version": 1,
+        "created_at": created_at,
+        "policy_ref": "policy/default@0",
+        "profile_ref": "profile/default@0",
+        "ept": ept,
+        "constraints": {
+            "declared_refs": [],
+            "families": {},
+            "applicable_abb": [],
+            "resolved_sbb": [],
+            "resolution_evidence": [],
+            "constraints_digest": "c0"
+        },
+        "coverage": {},
+        "exceptions": [],
+        "root_ettle_id": "ettle:root",
+        "ept_digest": "e0",
+        "manifest_digest": format!("m-{}", created_at),
+        "semantic_manifest_digest": "s0",
+        "store_schema_version": "0001",
+        "seed_digest": null
+    })
+}
+
+fn render(value: &Value) -> String {
+    let bytes = serde_json::to_vec(value).unwrap();
+    render_manifest_markdown(&ParsedManifest::parse(&bytes).unwrap().manifest)
+}
+
+fn ep(id: &str, ordinal: u32) -> Value {
+    json!({"ep_id": id, "ordinal": ordinal, "normative": true, "ep_digest": format!("d-{}", id)})
+}
+
+#[test]
+fn test_rendering_ignores_created_at_and_manifest_digest() {
+    let ept = json!([ep("ep:root:0", 0)]);
+    let a = render(&manifest(ept.clone(), "2026-01-01T00:00:00Z"));
+    let b = render(&manifest(ept, "2026-02-01T00:00:00Z"));
+    assert_eq!(a, b);
+    assert!(unified_line_diff(&a, &b, "a", "b").is_empty());
+}
+
+#[test]
+fn test_added_ep_shows_as_single_inserted_line() {
+    let a = render(&manifest(json!([ep("ep:root:0", 0), ep("ep:b:0", 2)]), "t"));
+    let b = render(&manifest(
+        json!([ep("ep:root:0", 0), ep("ep:a:0", 1), ep("ep:b:0", 2)]),
+        "t",
+    ));
+    let diff = unified_line_diff(&a, &b, "snap-a", "snap-b");
+
+    assert!(diff.starts_with("--- snap-a\n+++ snap-b\n@@ "));
+    let changed: Vec<&str> = diff
+        .lines()
+        .skip(2)
+        .filter(|l| l.starts_with('+') || l.starts_with('-'))
+        .collect();
+    assert_eq!(changed, vec!["+1. ep:a:0 (d-ep:a:0)"]);
+}
+
+#[test]
+fn test_distant_changes_produce_separate_hunks() {
+    let old: String = (0..30).map(|i| format!("line {}\n", i)).collect();
+    let new = old
+        .replace("line 2\n", "line two\n")
+        .replace("line 25\n", "line twenty-five\n");
+    let diff = unified_line_diff(&old, &new, "a", "b");
+
+    let headers: Vec<&str> = diff.lines().filter(|l| l.starts_with("@@")).collect();
+    assert_eq!(headers, vec!["@@ -1,6 +1,6 @@", "@@ -23,7 +23,7 @@"]);
+}
+
+#[test]
+fn test_diff_against_empty_text() {
+    let diff = unified_line_diff("", "one\ntwo\n", "a", "b");
+    assert!(diff.contains("@@ -0,0 +1,2 @@\n+one\n+two\n"));
+}
@@ -1,8 +1,12 @@
 // Test suite for snapshot manifest constraints envelope (v4 requirements)
 // Tests that the constraints envelope structure is always present with all required fields
 
+use ettlex_core::constraint_engine::ConstraintFamilyStatus;
+use ettlex_core::errors::ExErrorKind;
 use ettlex_core::ops::Store;
-use ettlex_core::snapshot::manifest::generate_manifest;
+use ettlex_core::snapshot::manifest::{
+    constraints_digest, family_digest, generate_manifest, ConstraintsEnvelope, FamilyConstraints,
+};
 
 #[test]
 fn test_manifest_contains_constraints_envelope_even_when_empty() {
@@ -142,3 +146,56 @@ fn test_constraints_digest_is_computed() {
         .chars()
         .all(|c| c.is_ascii_hexdigit()));
 }
+
+fn empty_manifest() -> ettlex_core::snapshot::manifest::SnapshotManifest {
+    generate_manifest(
+        vec!["ep:a".to_string()],
+        "policy/default@0".to_string(),
+        "profile/default@0".to_string(),
+        "ettle:root".to_string(),
+        "0001".to_string(),
+        None,
+        &Store::new(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_envelope_round_trips_through_json_and_keeps_its_digest() {
+    let manifest = empty_manifest();
+    let json = serde_json::to_value(&manifest).unwrap();
+
+    let envelope = ConstraintsEnvelope::from_json_value(&json["constraints"]).unwrap();
+
+    assert_eq!(envelope, manifest.constraints);
+    assert_eq!(envelope.recompute_digest(), envelope.constraints_digest);
+}
+
+#[test]
+fn test_recompute_digest_reflects_family_digests() {
+    let mut envelope = empty_manifest().constraints;
+    let original = envelope.recompute_digest();
+
+    envelope.declared_refs.push("c:1".to_string());
+    envelope.families.insert(
+        "ABB".to_string(),
+        FamilyConstraints {
+            status: ConstraintFamilyStatus::Uncomputed,
+            active_refs: vec!["c:1".to_string()],
+            outcomes: vec![],
+            evidence: vec![],
+            digest: family_digest(&["c:1"]),
+        },
+    );
+
+    let expected = constraints_digest(&["c:1"], &[("ABB", family_digest(&["c:1"]).as_str())]);
+    assert_eq!(envelope.recompute_digest(), expected);
+    assert_ne!(expected, original);
+}
+
+#[test]
+fn test_malformed_envelope_json_is_invalid_manifest() {
+    let err = ConstraintsEnvelope::from_json_value(&serde_json::json!({ "declared_refs": 3 }))
+        .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidManifest);
+}
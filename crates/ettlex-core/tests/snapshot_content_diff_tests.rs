@@ -0,0 +1,141 @@
+//! EP content-level diffs inside the snapshot diff.
+//!
+//! All tests operate on manifest bytes plus an in-memory content source.
+
+use std::collections::BTreeMap;
+
+use ettlex_core::diff::{
+    compute_diff, compute_diff_with_options, render_human_summary, DiffIgnoreList, DiffOptions,
+    EpContent, EpContentSource, NoEpContent, SeverityRules,
+};
+use ettlex_core::errors::{ExError, ExErrorKind};
+use serde_json::{json, Value};
+
+struct MemContent(BTreeMap<String, EpContent>);
+
+impl EpContentSource for MemContent {
+    fn ep_content(&self, ep_digest: &str) -> Result<Option<EpContent>, ExError> {
+        if ep_digest == "f".repeat(64) {
+            return Err(ExError::new(ExErrorKind::Io).with_message("disk on fire"));
+        }
+        Ok(self.0.get(ep_digest).cloned())
+    }
+}
+
+fn content(why: &str, what: &str, how: &str) -> EpContent {
+    EpContent {
+        why: why.to_string(),
+        what: what.to_string(),
+        how: how.to_string(),
+    }
+}
+
+fn manifest(ept: &[(&str, &str)], semantic: &str) -> Vec<u8> {
+    let ept: Vec<Value> = ept
+        .iter()
+        .enumerate()
+        .map(|(i, (id, d))| json!({"ep_id": id, "ordinal": i, "normative": true, "ep_digest": d.repeat(64)}))
+        .collect();
+    serde_json::to_vec(&json!({
+        "manifest_schema_version": 1,
+        "created_at": "2026-01-01T00:00:00Z",
+        "policy_ref": "policy/default@0",
+        "profile_ref": "profile/default@0",
+        "ept": ept,
+        "constraints": {
+            "declared_refs": [], "families": {}, "applicable_abb": [], "resolved_sbb": [],
+            "resolution_evidence": [], "constraints_digest": "0".repeat(64)
+        },
+        "coverage": {},
+        "exceptions": [],
+        "root_ettle_id": "ettle:root",
+        "ept_digest": "1".repeat(64),
+        "manifest_digest": semantic.repeat(64),
+        "semantic_manifest_digest": semantic.repeat(64),
+        "store_schema_version": "0001",
+        "seed_digest": null
+    }))
+    .unwrap()
+}
+
+fn source() -> MemContent {
+    MemContent(BTreeMap::from([
+        (
+            "a".repeat(64),
+            content("Because.", "One\nTwo\nThree", "Same"),
+        ),
+        ("b".repeat(64), content("Because.", "One\n2\nThree", "Same")),
+    ]))
+}
+
+const INCLUDE: DiffOptions = DiffOptions {
+    include_content: true,
+};
+
+#[test]
+fn test_content_diffs_line_diff_changed_fields() {
+    let a = manifest(&[("ep:1", "a"), ("ep:2", "c")], "2");
+    let b = manifest(&[("ep:1", "b"), ("ep:2", "d")], "3");
+
+    let diff =
+        compute_diff_with_options(&a, &b, &SeverityRules::default(), &INCLUDE, &source()).unwrap();
+
+    let content_diffs = diff.content_diffs.as_ref().unwrap();
+    assert_eq!(content_diffs.len(), 2);
+    let ep1 = &content_diffs[0];
+    assert!(ep1.available);
+    assert_eq!(ep1.field_diffs.keys().collect::<Vec<_>>(), vec!["what"]);
+    assert_eq!(
+        ep1.field_diffs["what"],
+        "--- a/ep:1/what\n+++ b/ep:1/what\n@@ -1,3 +1,3 @@\n One\n-Two\n+2\n Three\n"
+    );
+    // No blobs for ep:2's digests
+    assert!(!content_diffs[1].available);
+    assert!(content_diffs[1].field_diffs.is_empty());
+
+    let summary = render_human_summary(&diff);
+    assert!(summary.contains("### EP Content Diffs"));
+    assert!(summary.contains("```diff\n--- a/ep:1/what"));
+    assert!(summary.contains("- `ep:2`: _content unavailable"));
+}
+
+#[test]
+fn test_content_diffs_are_opt_in() {
+    let a = manifest(&[("ep:1", "a")], "2");
+    let b = manifest(&[("ep:1", "b")], "3");
+
+    let diff = compute_diff_with_options(
+        &a,
+        &b,
+        &SeverityRules::default(),
+        &DiffOptions::default(),
+        &source(),
+    )
+    .unwrap();
+    assert_eq!(diff, compute_diff(&a, &b).unwrap());
+    assert!(diff.content_diffs.is_none());
+    let json = serde_json::to_value(&diff).unwrap();
+    assert!(json.get("content_diffs").is_none());
+
+    let diff = compute_diff_with_options(&a, &b, &SeverityRules::default(), &INCLUDE, &NoEpContent)
+        .unwrap();
+    assert!(!diff.content_diffs.unwrap()[0].available);
+}
+
+#[test]
+fn test_content_diffs_follow_ignore_list_and_propagate_errors() {
+    let a = manifest(&[("ep:1", "a")], "2");
+    let b = manifest(&[("ep:1", "b")], "3");
+    let mut diff =
+        compute_diff_with_options(&a, &b, &SeverityRules::default(), &INCLUDE, &source()).unwrap();
+    DiffIgnoreList::from_value(&json!({"categories": ["normative_content_changed"]}))
+        .unwrap()
+        .apply(&mut diff);
+    assert_eq!(diff.content_diffs, Some(Vec::new()));
+
+    let broken = manifest(&[("ep:1", "f")], "3");
+    let err =
+        compute_diff_with_options(&a, &broken, &SeverityRules::default(), &INCLUDE, &source())
+            .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::Io);
+}
@@ -0,0 +1,150 @@
+// Test suite for the decision lifecycle manifest section
+// Tests profile parsing, flag/block modes and digest stability when off
+
+use ettlex_core::errors::ExErrorKind;
+use ettlex_core::ops::decision_ops::{
+    attach_decision_to_target, create_decision, detach_decision_from_target,
+};
+use ettlex_core::ops::ettle_ops::create_ettle;
+use ettlex_core::ops::Store;
+use ettlex_core::snapshot::manifest::{generate_manifest, SnapshotManifest};
+use ettlex_core::snapshot::{
+    apply_decision_lifecycle, compute_semantic_digest, DecisionLifecycleMode,
+    DecisionLifecyclePolicy, ParsedManifest,
+};
+use serde_json::json;
+
+fn decision(store: &mut Store, id: &str, status: &str) {
+    create_decision(
+        store,
+        Some(id.to_string()),
+        format!("Decision {}", id),
+        Some(status.to_string()),
+        "Use the thing".to_string(),
+        "Because".to_string(),
+        None,
+        None,
+        "none".to_string(),
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+}
+
+fn link(store: &mut Store, decision_id: &str, ettle_id: &str) {
+    attach_decision_to_target(
+        store,
+        decision_id,
+        "ettle".to_string(),
+        ettle_id.to_string(),
+        "grounds".to_string(),
+        0,
+    )
+    .unwrap();
+}
+
+/// Two Ettles: the first linked to a proposed and an accepted decision,
+/// the second to nothing.
+fn setup() -> (Store, Vec<String>) {
+    let mut store = Store::new();
+    let a = create_ettle(&mut store, "A".to_string()).unwrap();
+    let b = create_ettle(&mut store, "B".to_string()).unwrap();
+    decision(&mut store, "d:proposed", "proposed");
+    decision(&mut store, "d:accepted", "accepted");
+    link(&mut store, "d:proposed", &a);
+    link(&mut store, "d:accepted", &a);
+    (store, vec![a, b])
+}
+
+fn manifest(store: &Store, ept: &[String]) -> SnapshotManifest {
+    generate_manifest(
+        ept.to_vec(),
+        "policy/default@0".into(),
+        "profile/default@0".into(),
+        ept[0].clone(),
+        "0001".into(),
+        None,
+        store,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_profile_payload_modes() {
+    let mode = |payload| {
+        DecisionLifecyclePolicy::from_profile_payload(&payload)
+            .map(|p| p.mode)
+            .map_err(|e| e.kind())
+    };
+    assert_eq!(mode(json!({})), Ok(DecisionLifecycleMode::Off));
+    assert_eq!(
+        mode(json!({"decision_lifecycle": {"mode": "block"}})),
+        Ok(DecisionLifecycleMode::Block)
+    );
+    assert_eq!(
+        mode(json!({"decision_lifecycle": {"mode": "warn"}})),
+        Err(ExErrorKind::InvalidInput)
+    );
+    assert_eq!(
+        mode(json!({"decision_lifecycle": {"mode": "flag", "extra": 1}})),
+        Err(ExErrorKind::InvalidInput)
+    );
+}
+
+#[test]
+fn test_flag_records_proposed_decisions_and_updates_digests() {
+    let (store, ept) = setup();
+    let mut m = manifest(&store, &ept);
+    let before = m.semantic_manifest_digest.clone();
+
+    apply_decision_lifecycle(&mut m, &store, DecisionLifecycleMode::Flag).unwrap();
+
+    let section = m.decision_lifecycle.as_ref().unwrap();
+    assert_eq!(section.mode, DecisionLifecycleMode::Flag);
+    assert_eq!(section.proposed.len(), 1);
+    assert_eq!(section.proposed[0].ettle_id, ept[0]);
+    assert_eq!(section.proposed[0].decision_id, "d:proposed");
+    assert_eq!(section.proposed[0].decision_title, "Decision d:proposed");
+    assert_ne!(m.semantic_manifest_digest, before);
+
+    let mut recomputed = m.clone();
+    recomputed.semantic_manifest_digest = String::new();
+    assert_eq!(
+        compute_semantic_digest(&recomputed).unwrap(),
+        m.semantic_manifest_digest
+    );
+
+    // The section round-trips through the manifest parser as a known field
+    let parsed = ParsedManifest::parse(&serde_json::to_vec(&m).unwrap()).unwrap();
+    assert!(parsed.unknown_fields.is_empty());
+    assert_eq!(parsed.manifest.decision_lifecycle, m.decision_lifecycle);
+}
+
+#[test]
+fn test_block_rejects_proposed_decisions() {
+    let (mut store, ept) = setup();
+    let mut m = manifest(&store, &ept);
+
+    let err = apply_decision_lifecycle(&mut m, &store, DecisionLifecycleMode::Block).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::PolicyDenied);
+    assert!(m.decision_lifecycle.is_none());
+
+    // Once the link is removed the check passes and records an empty section
+    detach_decision_from_target(&mut store, "d:proposed", "ettle", &ept[0], "grounds").unwrap();
+    apply_decision_lifecycle(&mut m, &store, DecisionLifecycleMode::Block).unwrap();
+    assert!(m.decision_lifecycle.unwrap().proposed.is_empty());
+}
+
+#[test]
+fn test_off_leaves_manifest_unchanged() {
+    let (store, ept) = setup();
+    let mut m = manifest(&store, &ept);
+    let before = m.clone();
+
+    apply_decision_lifecycle(&mut m, &store, DecisionLifecycleMode::Off).unwrap();
+
+    assert_eq!(m, before);
+    let value = serde_json::to_value(&m).unwrap();
+    assert!(value.get("decision_lifecycle").is_none());
+}
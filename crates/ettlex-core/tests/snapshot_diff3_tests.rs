@@ -0,0 +1,156 @@
+//! Three-way snapshot diff: classification against a merge base and the
+//! human summary.
+//!
+//! All tests operate exclusively on manifest bytes (no I/O, no DB).
+
+use ettlex_core::diff::model::Diff3ItemKind;
+use ettlex_core::diff::{
+    compute_diff3, render_diff3_summary, render_diff3_summary_with, SummaryOptions,
+    SummaryVerbosity,
+};
+use ettlex_core::errors::ExErrorKind;
+use serde_json::{json, Value};
+
+fn ep(id: &str, digest_byte: &str) -> Value {
+    json!({"ep_id": id, "ordinal": 0, "normative": true, "ep_digest": digest_byte.repeat(64)})
+}
+
+fn manifest(ept: Vec<Value>) -> Value {
+    json!({
+        "manifest_schema_version": 1,
+        "created_at": "2026-01-01T00:00:00Z",
+        "policy_ref": "policy/default@0",
+        "profile_ref": "profile/default@0",
+        "ept": ept,
+        "constraints": {
+            "declared_refs": [],
+            "families": {},
+            "applicable_abb": [],
+            "resolved_sbb": [],
+            "resolution_evidence": [],
+            "constraints_digest": "0".repeat(64)
+        },
+        "coverage": {},
+        "exceptions": [],
+        "root_ettle_id": "ettle:root",
+        "ept_digest": "1".repeat(64),
+        "manifest_digest": "2".repeat(64),
+        "semantic_manifest_digest": "3".repeat(64),
+        "store_schema_version": "0001",
+        "seed_digest": null
+    })
+}
+
+fn bytes(v: &Value) -> Vec<u8> {
+    serde_json::to_vec(v).unwrap()
+}
+
+fn subjects(changes: &[ettlex_core::diff::model::Diff3Change]) -> Vec<(Diff3ItemKind, &str)> {
+    changes
+        .iter()
+        .map(|c| (c.kind, c.subject.as_str()))
+        .collect()
+}
+
+#[test]
+fn test_diff3_classifies_changes_against_base() {
+    let base = manifest(vec![ep("ep:1", "a"), ep("ep:2", "a"), ep("ep:3", "a")]);
+
+    // A: edits ep:1, adds ep:4, changes policy, adds exception X
+    let mut a = manifest(vec![
+        ep("ep:1", "b"),
+        ep("ep:2", "a"),
+        ep("ep:3", "a"),
+        ep("ep:4", "a"),
+    ]);
+    a["policy_ref"] = json!("policy/strict@1");
+    a["exceptions"] = json!(["X"]);
+
+    // B: edits ep:1 differently, removes ep:3, same policy change, reorders
+    let mut b = manifest(vec![ep("ep:2", "a"), ep("ep:1", "c")]);
+    b["policy_ref"] = json!("policy/strict@1");
+
+    let diff = compute_diff3(&bytes(&base), &bytes(&a), &bytes(&b)).unwrap();
+
+    assert_eq!(
+        subjects(&diff.only_in_a),
+        vec![
+            (Diff3ItemKind::EptEntry, "ep:4"),
+            (Diff3ItemKind::Exception, "X")
+        ]
+    );
+    // Removing ep:3 in B does not make the common-EP order differ in A
+    assert_eq!(
+        subjects(&diff.only_in_b),
+        vec![
+            (Diff3ItemKind::EptEntry, "ep:3"),
+            (Diff3ItemKind::EptOrder, "ept")
+        ]
+    );
+    assert_eq!(
+        subjects(&diff.in_both),
+        vec![(Diff3ItemKind::Metadata, "policy_ref")]
+    );
+    assert_eq!(
+        subjects(&diff.conflicting),
+        vec![(Diff3ItemKind::EptEntry, "ep:1")]
+    );
+    assert!(diff.has_conflicts());
+
+    let removed = &diff.only_in_b[0];
+    assert!(removed.base.is_some() && removed.a.is_some() && removed.b.is_none());
+    assert_eq!(diff.only_in_b[1].b, Some(json!(["ep:2", "ep:1"])));
+}
+
+#[test]
+fn test_diff3_of_unchanged_sides_is_empty() {
+    let base = manifest(vec![ep("ep:1", "a")]);
+    let mut later = base.clone();
+    later["created_at"] = json!("2026-02-02T00:00:00Z");
+    later["manifest_digest"] = json!("9".repeat(64));
+
+    let diff = compute_diff3(&bytes(&base), &bytes(&later), &bytes(&base)).unwrap();
+
+    assert!(diff.only_in_a.is_empty() && diff.only_in_b.is_empty());
+    assert!(diff.in_both.is_empty() && !diff.has_conflicts());
+    assert!(render_diff3_summary(&diff).contains("Neither side changed since the base."));
+}
+
+#[test]
+fn test_diff3_invalid_manifest_is_rejected() {
+    let base = bytes(&manifest(vec![]));
+    let err = compute_diff3(&base, b"not json", &base).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidManifest);
+}
+
+#[test]
+fn test_diff3_summary_lists_conflicts_first() {
+    let base = manifest(vec![ep("ep:1", "a")]);
+    let mut a = manifest(vec![ep("ep:1", "b")]);
+    a["exceptions"] = json!(["X"]);
+    let b = manifest(vec![ep("ep:1", "c")]);
+
+    let diff = compute_diff3(&bytes(&base), &bytes(&a), &bytes(&b)).unwrap();
+
+    let one_line = render_diff3_summary_with(
+        &diff,
+        &SummaryOptions {
+            verbosity: SummaryVerbosity::OneLine,
+            ..Default::default()
+        },
+    );
+    assert_eq!(
+        one_line,
+        "1 only in A, 0 only in B, 0 in both, 1 conflicting"
+    );
+
+    let detailed = render_diff3_summary(&diff);
+    let conflicts = detailed.find("### ⚠ Conflicts").unwrap();
+    let only_a = detailed.find("### Only in A").unwrap();
+    assert!(conflicts < only_a);
+    assert!(
+        detailed.contains("- EP `ep:1`: base `aaaaaaaaaaaa`, A `bbbbbbbbbbbb`, B `cccccccccccc`")
+    );
+    assert!(detailed.contains("- Exception `X`: absent → present"));
+    assert!(!detailed.contains("### Only in B"));
+}
@@ -2,8 +2,10 @@
 //!
 //! All tests operate exclusively on manifest bytes (no I/O, no DB).
 
-use ettlex_core::diff::engine::compute_diff;
+use ettlex_core::diff::engine::{compute_diff, compute_diff_parsed, ParsedManifest};
+use ettlex_core::diff::human_summary::render_human_summary;
 use ettlex_core::diff::model::{DiffClassification, DiffSeverity, InvariantViolationEntry};
+use ettlex_core::diff::severity::SeverityRules;
 use ettlex_core::errors::ExErrorKind;
 use serde_json::{json, Value};
 
@@ -241,6 +243,35 @@ fn test_diff_detects_ordinal_reordering() {
     assert!(diff.ept_changes.removed_eps.is_empty());
 }
 
+// Topology digest separates restructuring from content-only edits
+#[test]
+fn test_diff_reports_topology_change() {
+    let (mut a, mut b) = two_different_manifests();
+    assert_eq!(
+        compute_diff(&to_bytes(&a), &to_bytes(&b))
+            .unwrap()
+            .identity
+            .topology_changed(),
+        None
+    );
+
+    let topology = "7070707070707070707070707070707070707070707070707070707070707070";
+    a["topology_digest"] = json!(topology);
+    b["topology_digest"] = json!(topology);
+    b["ept"][0]["ep_digest"] =
+        json!("ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff");
+    let diff = compute_diff(&to_bytes(&a), &to_bytes(&b)).unwrap();
+    assert_eq!(diff.identity.topology_changed(), Some(false));
+    assert!(diff.unknown_changes.added_fields.is_empty());
+    assert!(render_human_summary(&diff).contains("**Structure**: unchanged (content only)"));
+
+    b["topology_digest"] =
+        json!("7171717171717171717171717171717171717171717171717171717171717171");
+    let diff = compute_diff(&to_bytes(&a), &to_bytes(&b)).unwrap();
+    assert_eq!(diff.identity.topology_changed(), Some(true));
+    assert!(render_human_summary(&diff).contains("**Structure**: changed"));
+}
+
 // S8: Addition of unknown family constraint
 #[test]
 fn test_diff_detects_constraint_addition_unknown_family() {
@@ -664,3 +695,78 @@ fn test_diff_no_semantic_change_different_manifest_digest() {
         diff.identity.b_manifest_digest
     );
 }
+
+// S27: Pre-parsed manifests produce the same diff as raw bytes
+#[test]
+fn test_diff_parsed_matches_bytes() {
+    let (a, mut b) = two_different_manifests();
+    b["ept"] = json!([
+        {"ep_id": "ep:root:0", "ordinal": 0, "normative": true, "ep_digest": "ff"},
+        {"ep_id": "ep:root:1", "ordinal": 1, "normative": true, "ep_digest": "ee"}
+    ]);
+    b["future_field"] = json!({"x": 1});
+
+    let from_bytes = compute_diff(&to_bytes(&a), &to_bytes(&b)).unwrap();
+    let pa = ParsedManifest::parse(&to_bytes(&a)).unwrap();
+    let pb = ParsedManifest::parse(&to_bytes(&b)).unwrap();
+    let from_parsed = compute_diff_parsed(&pa, &pb, &SeverityRules::default()).unwrap();
+
+    assert_eq!(from_parsed, from_bytes);
+    assert_eq!(
+        from_parsed.unknown_changes.added_fields,
+        vec!["future_field"]
+    );
+    assert!(pb.unknown_fields.contains_key("future_field"));
+}
+
+/// Build a manifest with `n` EPs; each entry serializes to roughly 120 bytes.
+fn large_manifest(n: usize, digest_seed: char) -> Value {
+    let ept: Vec<Value> = (0..n)
+        .map(|i| {
+            json!({
+                "ep_id": format!("ep:bulk{:07}:0", i),
+                "ordinal": i,
+                "normative": i % 2 == 0,
+                "ep_digest": format!("{}{:063}", if i % 1000 == 0 { digest_seed } else { 'a' }, i),
+            })
+        })
+        .collect();
+    let mut m = base_manifest();
+    m["ept"] = Value::Array(ept);
+    m
+}
+
+// Performance target: diffing two ~50MB manifests. Run with
+// `cargo test --release -p ettlex-core --test snapshot_diff_tests -- --ignored`.
+#[test]
+#[ignore = "benchmark; run explicitly in release mode"]
+fn bench_diff_50mb_manifests() {
+    let (mut a, mut b) = two_different_manifests();
+    let n = 420_000;
+    a["ept"] = large_manifest(n, 'a')["ept"].take();
+    b["ept"] = large_manifest(n, 'b')["ept"].take();
+    let a_bytes = to_bytes(&a);
+    let b_bytes = to_bytes(&b);
+    assert!(
+        a_bytes.len() >= 50 * 1024 * 1024,
+        "got {} bytes",
+        a_bytes.len()
+    );
+
+    let start = std::time::Instant::now();
+    let diff = compute_diff(&a_bytes, &b_bytes).unwrap();
+    let elapsed = start.elapsed();
+    eprintln!(
+        "diff of {} + {} bytes took {:?}",
+        a_bytes.len(),
+        b_bytes.len(),
+        elapsed
+    );
+
+    assert_eq!(diff.ep_content_changes.changed_eps.len(), n / 1000);
+    assert!(
+        elapsed < std::time::Duration::from_secs(5),
+        "50MB diff took {:?}",
+        elapsed
+    );
+}
@@ -3,9 +3,10 @@
 
 use ettlex_core::ops::Store;
 use ettlex_core::snapshot::digest::{
-    compute_ept_digest, compute_manifest_digest, compute_semantic_digest,
+    compute_ept_digest, compute_manifest_digest, compute_semantic_digest, compute_topology_digest,
+    record_topology_digest, RefinementEdges,
 };
-use ettlex_core::snapshot::manifest::generate_manifest;
+use ettlex_core::snapshot::manifest::{generate_manifest, EpEntry};
 
 #[test]
 fn test_ept_digest_deterministic() {
@@ -119,3 +120,86 @@ fn test_digest_format_is_hex_sha256() {
     // All characters should be valid hex
     assert!(digest.chars().all(|c| c.is_ascii_hexdigit()));
 }
+
+fn entry(ep_id: &str, ordinal: u32, ep_digest: &str) -> EpEntry {
+    EpEntry {
+        ep_id: ep_id.into(),
+        ordinal,
+        normative: true,
+        ep_digest: ep_digest.into(),
+        content_format: Default::default(),
+    }
+}
+
+fn edges(pairs: &[(&str, &[&str])]) -> RefinementEdges {
+    pairs
+        .iter()
+        .map(|(parent, children)| {
+            (
+                parent.to_string(),
+                children.iter().map(|c| c.to_string()).collect(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_topology_digest_ignores_content() {
+    let a = vec![entry("ettle:a", 0, "aa"), entry("ettle:b", 1, "bb")];
+    let b = vec![entry("ettle:a", 0, "cc"), entry("ettle:b", 1, "dd")];
+    let e = edges(&[("ettle:a", &["ettle:b", "ettle:x"])]);
+
+    assert_eq!(
+        compute_topology_digest(&a, &e).unwrap(),
+        compute_topology_digest(&b, &e).unwrap()
+    );
+    // Child order as returned by the store does not matter
+    let reordered = edges(&[("ettle:a", &["ettle:x", "ettle:b"])]);
+    assert_eq!(
+        compute_topology_digest(&a, &e).unwrap(),
+        compute_topology_digest(&a, &reordered).unwrap()
+    );
+}
+
+#[test]
+fn test_topology_digest_tracks_structure() {
+    let ept = vec![entry("ettle:a", 0, "aa"), entry("ettle:b", 1, "bb")];
+    let e = edges(&[("ettle:a", &["ettle:b"])]);
+    let base = compute_topology_digest(&ept, &e).unwrap();
+
+    let swapped = vec![entry("ettle:b", 0, "bb"), entry("ettle:a", 1, "aa")];
+    assert_ne!(base, compute_topology_digest(&swapped, &e).unwrap());
+
+    let reparented = edges(&[("ettle:a", &["ettle:c"])]);
+    assert_ne!(base, compute_topology_digest(&ept, &reparented).unwrap());
+
+    // Edges from parents outside the EPT are not part of its structure
+    let outside = edges(&[("ettle:a", &["ettle:b"]), ("ettle:z", &["ettle:y"])]);
+    assert_eq!(base, compute_topology_digest(&ept, &outside).unwrap());
+}
+
+#[test]
+fn test_record_topology_digest_recomputes_manifest_digests() {
+    let mut manifest = generate_manifest(
+        vec!["ettle:root".into()],
+        "policy/default@0".into(),
+        "profile/default@0".into(),
+        "ettle:root".into(),
+        "0001".into(),
+        None,
+        &Store::new(),
+    )
+    .unwrap();
+    let before = manifest.clone();
+    assert!(!serde_json::to_string(&manifest)
+        .unwrap()
+        .contains("topology_digest"));
+
+    record_topology_digest(&mut manifest, &RefinementEdges::new()).unwrap();
+    assert!(manifest.topology_digest.is_some());
+    assert_ne!(
+        manifest.semantic_manifest_digest,
+        before.semantic_manifest_digest
+    );
+    assert_ne!(manifest.manifest_digest, before.manifest_digest);
+}
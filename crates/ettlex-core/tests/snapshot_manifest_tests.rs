@@ -1,8 +1,10 @@
 // Test suite for snapshot manifest generation
 // Tests basic manifest structure, field population, and schema compliance
 
+use ettlex_core::errors::ExErrorKind;
 use ettlex_core::ops::Store;
 use ettlex_core::snapshot::manifest::generate_manifest;
+use ettlex_core::snapshot::ParsedManifest;
 
 #[test]
 fn test_generate_manifest_basic() {
@@ -117,3 +119,34 @@ fn test_generate_manifest_v0_fields_empty() {
         serde_json::Value::Object(serde_json::Map::new())
     );
 }
+
+#[test]
+fn test_parsed_manifest_roundtrip_preserves_unknown_fields() {
+    let manifest = generate_manifest(
+        vec!["ep:root:0".into()],
+        "policy/default@0".into(),
+        "profile/default@0".into(),
+        "ettle:root".into(),
+        "0001".into(),
+        None,
+        &Store::new(),
+    )
+    .unwrap();
+    let mut json = serde_json::to_value(&manifest).unwrap();
+    json["added_by_newer_writer"] = serde_json::json!({ "v": 2 });
+
+    let parsed = ParsedManifest::parse(&serde_json::to_vec(&json).unwrap()).unwrap();
+
+    assert_eq!(parsed.manifest, manifest);
+    assert_eq!(
+        parsed.unknown_fields.get("added_by_newer_writer"),
+        Some(&serde_json::json!({ "v": 2 }))
+    );
+}
+
+#[test]
+fn test_parsed_manifest_rejects_missing_constraints() {
+    let bytes = br#"{"manifest_schema_version": 1, "semantic_manifest_digest": "x"}"#;
+    let err = ParsedManifest::parse(bytes).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::MissingField);
+}
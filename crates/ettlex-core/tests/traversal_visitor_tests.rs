@@ -0,0 +1,148 @@
+// Test suite for the visitor-based traversal API
+// Tests pre/post ordering, early exit, subtree skipping, cycle safety and
+// iterative deepening
+
+use std::collections::BTreeMap;
+
+use ettlex_core::errors::{ExErrorKind, Result};
+use ettlex_core::model::Ettle;
+use ettlex_core::ops::Store;
+use ettlex_core::traversal::{
+    walk, walk_iterative_deepening, EttleVisitor, VisitControl, WalkOptions,
+};
+
+/// root → (a → (a1, a2), b → (b1)); b1 → root closes a cycle.
+fn fixture() -> (Store, BTreeMap<String, Vec<String>>) {
+    let mut store = Store::new();
+    for id in ["root", "a", "a1", "a2", "b", "b1"] {
+        store.insert_ettle(Ettle::new(id.to_string(), id.to_uppercase()));
+    }
+    let children = [
+        ("root", vec!["a", "b", "missing"]),
+        ("a", vec!["a1", "a2"]),
+        ("b", vec!["b1"]),
+        ("b1", vec!["root"]),
+    ]
+    .into_iter()
+    .map(|(p, cs)| (p.to_string(), cs.into_iter().map(str::to_string).collect()))
+    .collect();
+    (store, children)
+}
+
+#[derive(Default)]
+struct Recorder {
+    events: Vec<String>,
+    skip: Option<&'static str>,
+    stop_at: Option<&'static str>,
+}
+
+impl EttleVisitor for Recorder {
+    fn pre(&mut self, ettle: &Ettle, depth: usize) -> Result<VisitControl> {
+        self.events.push(format!("+{}@{}", ettle.id, depth));
+        if self.stop_at == Some(ettle.id.as_str()) {
+            return Ok(VisitControl::Stop);
+        }
+        if self.skip == Some(ettle.id.as_str()) {
+            return Ok(VisitControl::SkipChildren);
+        }
+        Ok(VisitControl::Continue)
+    }
+
+    fn post(&mut self, ettle: &Ettle, _depth: usize) -> Result<VisitControl> {
+        self.events.push(format!("-{}", ettle.id));
+        Ok(VisitControl::Continue)
+    }
+}
+
+#[test]
+fn test_walk_calls_pre_and_post_in_order() {
+    let (store, children) = fixture();
+    let mut rec = Recorder::default();
+
+    let control = walk(&store, &children, "root", &WalkOptions::default(), &mut rec).unwrap();
+
+    assert_eq!(control, VisitControl::Continue);
+    // `missing` is skipped, and b1 → root does not loop
+    assert_eq!(
+        rec.events,
+        vec![
+            "+root@0", "+a@1", "+a1@2", "-a1", "+a2@2", "-a2", "-a", "+b@1", "+b1@2", "-b1", "-b",
+            "-root"
+        ]
+    );
+}
+
+#[test]
+fn test_walk_skip_children_stop_and_max_depth() {
+    let (store, children) = fixture();
+
+    let mut rec = Recorder {
+        skip: Some("a"),
+        ..Default::default()
+    };
+    walk(&store, &children, "root", &WalkOptions::default(), &mut rec).unwrap();
+    assert_eq!(
+        rec.events,
+        vec!["+root@0", "+a@1", "-a", "+b@1", "+b1@2", "-b1", "-b", "-root"]
+    );
+
+    let mut rec = Recorder {
+        stop_at: Some("a1"),
+        ..Default::default()
+    };
+    let control = walk(&store, &children, "root", &WalkOptions::default(), &mut rec).unwrap();
+    assert_eq!(control, VisitControl::Stop);
+    assert_eq!(rec.events, vec!["+root@0", "+a@1", "+a1@2"]);
+
+    let mut rec = Recorder::default();
+    let options = WalkOptions { max_depth: Some(1) };
+    walk(&store, &children, "root", &options, &mut rec).unwrap();
+    assert_eq!(
+        rec.events,
+        vec!["+root@0", "+a@1", "-a", "+b@1", "-b", "-root"]
+    );
+}
+
+#[test]
+fn test_walk_unknown_root_is_not_found() {
+    let (store, children) = fixture();
+    let err = walk(
+        &store,
+        &children,
+        "nope",
+        &WalkOptions::default(),
+        &mut Recorder::default(),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+}
+
+#[test]
+fn test_iterative_deepening_reports_levels() {
+    let (store, children) = fixture();
+
+    let mut seen = Vec::new();
+    walk_iterative_deepening(&store, &children, "root", None, |ettle, depth| {
+        seen.push(format!("{}@{}", ettle.id, depth));
+        Ok(if ettle.id == "b" {
+            VisitControl::SkipChildren
+        } else {
+            VisitControl::Continue
+        })
+    })
+    .unwrap();
+    assert_eq!(seen, vec!["root@0", "a@1", "b@1", "a1@2", "a2@2"]);
+
+    let mut seen = Vec::new();
+    let control = walk_iterative_deepening(&store, &children, "root", Some(5), |ettle, _| {
+        seen.push(ettle.id.clone());
+        Ok(if ettle.id == "a1" {
+            VisitControl::Stop
+        } else {
+            VisitControl::Continue
+        })
+    })
+    .unwrap();
+    assert_eq!(control, VisitControl::Stop);
+    assert_eq!(seen, vec!["root", "a", "b", "a1"]);
+}
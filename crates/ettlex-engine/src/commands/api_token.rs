@@ -0,0 +1,256 @@
+//! API tokens for server access.
+//!
+//! A token is a random bearer secret with a set of scopes. Only its SHA-256
+//! is stored, so a lost secret cannot be recovered — revoke it and issue a
+//! new one. Servers call [`authorize_api_token`] with the scope a route
+//! needs; `admin` satisfies every scope.
+//!
+//! Issuance and revocation are the `ApiTokenIssue` / `ApiTokenRevoke`
+//! commands, so they get provenance and a `command_log` row like any other
+//! mutation. Neither records the secret: it exists only in the command
+//! result. Servers require the `admin` scope to apply them.
+
+#![allow(clippy::result_large_err)]
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_store::cas::FsStore;
+use ettlex_store::model::ApiTokenRecord;
+use ettlex_store::repo::SqliteRepo;
+use rusqlite::Connection;
+use sha2::{Digest, Sha256};
+
+use super::command::{apply_command, Command, CommandResult};
+
+type Result<T> = std::result::Result<T, ExError>;
+
+/// Prefix of every issued secret, so leaked tokens are easy to grep for.
+pub const API_TOKEN_PREFIX: &str = "etx_";
+
+/// What a token may do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ApiScope {
+    /// Read-only queries.
+    Read,
+    /// Commands that change the store.
+    Write,
+    /// Approval decisions.
+    Approve,
+    /// Everything, including server administration.
+    Admin,
+}
+
+impl ApiScope {
+    pub const ALL: [ApiScope; 4] = [
+        ApiScope::Read,
+        ApiScope::Write,
+        ApiScope::Approve,
+        ApiScope::Admin,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ApiScope::Read => "read",
+            ApiScope::Write => "write",
+            ApiScope::Approve => "approve",
+            ApiScope::Admin => "admin",
+        }
+    }
+}
+
+impl fmt::Display for ApiScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ApiScope {
+    type Err = ExError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        ApiScope::ALL
+            .into_iter()
+            .find(|scope| scope.as_str() == s)
+            .ok_or_else(|| {
+                ExError::new(ExErrorKind::InvalidInput)
+                    .with_op("api_token_issue")
+                    .with_message(format!("unknown scope: {}", s))
+                    .with_candidates(ApiScope::ALL.iter().map(|s| s.to_string()).collect())
+            })
+    }
+}
+
+/// A freshly issued token. `secret` is shown once and never stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IssuedApiToken {
+    pub record: ApiTokenRecord,
+    pub secret: String,
+}
+
+/// Issue a new token named `name` with the given scopes by applying
+/// `Command::ApiTokenIssue`.
+///
+/// # Errors
+/// * `InvalidInput` - Blank name, no scopes, or an unknown scope
+pub fn issue_api_token(
+    conn: &mut Connection,
+    cas: &FsStore,
+    name: &str,
+    scopes: &[String],
+) -> Result<IssuedApiToken> {
+    let cmd = Command::ApiTokenIssue {
+        name: name.to_string(),
+        scopes: scopes.to_vec(),
+    };
+    match apply(conn, cas, cmd)? {
+        CommandResult::ApiTokenIssue {
+            token_id, secret, ..
+        } => {
+            let record = SqliteRepo::get_api_token(conn, &token_id)?.ok_or_else(|| {
+                ExError::new(ExErrorKind::Internal)
+                    .with_op("api_token_issue")
+                    .with_entity_id(&token_id)
+                    .with_message("issued token is missing")
+            })?;
+            Ok(IssuedApiToken { record, secret })
+        }
+        _ => Err(unexpected_result("api_token_issue")),
+    }
+}
+
+/// Revoke a token by applying `Command::ApiTokenRevoke`. Requests
+/// presenting it fail from then on.
+///
+/// # Errors
+/// * `NotFound` - No token has this ID
+/// * `AlreadyTombstoned` - The token is already revoked
+pub fn revoke_api_token(conn: &mut Connection, cas: &FsStore, token_id: &str) -> Result<()> {
+    let cmd = Command::ApiTokenRevoke {
+        token_id: token_id.to_string(),
+    };
+    match apply(conn, cas, cmd)? {
+        CommandResult::ApiTokenRevoke => Ok(()),
+        _ => Err(unexpected_result("api_token_revoke")),
+    }
+}
+
+/// Insert a new token. Applied as `Command::ApiTokenIssue`.
+pub(crate) fn handle_api_token_issue(
+    conn: &Connection,
+    name: &str,
+    scopes: &[String],
+) -> Result<IssuedApiToken> {
+    let op = "api_token_issue";
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ExError::new(ExErrorKind::InvalidInput)
+            .with_op(op)
+            .with_message("token name must not be empty"));
+    }
+    let scopes = scopes
+        .iter()
+        .map(|s| s.trim().parse::<ApiScope>())
+        .collect::<Result<BTreeSet<_>>>()?;
+    if scopes.is_empty() {
+        return Err(ExError::new(ExErrorKind::InvalidInput)
+            .with_op(op)
+            .with_message("a token needs at least one scope"));
+    }
+
+    let secret = format!(
+        "{}{}{}",
+        API_TOKEN_PREFIX,
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    );
+    let record = ApiTokenRecord {
+        token_id: format!("tok:{}", uuid::Uuid::now_v7()),
+        name: name.to_string(),
+        token_hash: hash_secret(&secret),
+        scopes: scopes.iter().map(|s| s.to_string()).collect(),
+        created_at_ms: chrono::Utc::now().timestamp_millis(),
+        revoked_at_ms: None,
+    };
+    SqliteRepo::insert_api_token(conn, &record)?;
+    Ok(IssuedApiToken { record, secret })
+}
+
+/// Mark a token revoked. Applied as `Command::ApiTokenRevoke`.
+pub(crate) fn handle_api_token_revoke(conn: &Connection, token_id: &str) -> Result<()> {
+    let op = "api_token_revoke";
+    if SqliteRepo::get_api_token(conn, token_id)?.is_none() {
+        return Err(ExError::new(ExErrorKind::NotFound)
+            .with_op(op)
+            .with_entity_id(token_id)
+            .with_message(format!("API token not found: {}", token_id)));
+    }
+    if !SqliteRepo::revoke_api_token(conn, token_id, chrono::Utc::now().timestamp_millis())? {
+        return Err(ExError::new(ExErrorKind::AlreadyTombstoned)
+            .with_op(op)
+            .with_entity_id(token_id)
+            .with_message(format!("API token already revoked: {}", token_id)));
+    }
+    Ok(())
+}
+
+/// Issued tokens in issuance order, optionally including revoked ones.
+pub fn list_api_tokens(conn: &Connection, include_revoked: bool) -> Result<Vec<ApiTokenRecord>> {
+    SqliteRepo::list_api_tokens(conn, include_revoked)
+}
+
+/// Resolve a presented secret to its token and check it grants `required`.
+///
+/// # Errors
+/// * `Unauthorised` - Missing, unknown or revoked secret
+/// * `Forbidden` - The token lacks `required` (and is not `admin`)
+pub fn authorize_api_token(
+    conn: &Connection,
+    secret: Option<&str>,
+    required: ApiScope,
+) -> Result<ApiTokenRecord> {
+    let op = "api_token_authorize";
+    let unauthorised = || {
+        ExError::new(ExErrorKind::Unauthorised)
+            .with_op(op)
+            .with_message("missing, unknown or revoked API token")
+    };
+    let secret = secret.filter(|s| !s.is_empty()).ok_or_else(unauthorised)?;
+    let record = SqliteRepo::get_api_token_by_hash(conn, &hash_secret(secret))?
+        .filter(|r| r.revoked_at_ms.is_none())
+        .ok_or_else(unauthorised)?;
+    let granted = |scope: ApiScope| record.scopes.iter().any(|s| s == scope.as_str());
+    if !granted(required) && !granted(ApiScope::Admin) {
+        return Err(ExError::new(ExErrorKind::Forbidden)
+            .with_op(op)
+            .with_entity_id(&record.token_id)
+            .with_message(format!("token lacks the '{}' scope", required)));
+    }
+    Ok(record)
+}
+
+fn apply(conn: &mut Connection, cas: &FsStore, cmd: Command) -> Result<CommandResult> {
+    apply_command(
+        cmd,
+        None,
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .map(|(result, _)| result)
+}
+
+fn unexpected_result(op: &str) -> ExError {
+    ExError::new(ExErrorKind::Internal)
+        .with_op(op)
+        .with_message("unexpected command result")
+}
+
+fn hash_secret(secret: &str) -> String {
+    format!("{:x}", Sha256::digest(secret.as_bytes()))
+}
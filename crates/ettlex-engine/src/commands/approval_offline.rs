@@ -0,0 +1,395 @@
+//! Signed export and import of approval requests for offline review.
+//!
+//! An air-gapped reviewer never touches the store. The flow is:
+//!
+//! 1. [`export_approval`] writes a pending request — its CAS payload, the
+//!    hydrated review packet and the Markdown render — into an
+//!    [`ApprovalExport`] signed with a shared review key.
+//! 2. On the offline machine, [`respond_to_export`] checks the export's
+//!    signature and produces an [`ApprovalResponse`] carrying the decision,
+//!    signed with the same key and bound to the export's signature.
+//! 3. Back online, [`approval_response_command`] verifies the response
+//!    against the key and the stored request, and returns the
+//!    `ApprovalTransition` command that resolves it. Callers apply it like
+//!    any other command so the decision lands in the command log.
+//!
+//! Signatures are hex HMAC-SHA256 over `<kind>:<json>`, where `<json>` is
+//! the document serialized with an empty `signature`. The kind tag keeps an
+//! export signature from being replayed as a response. Each document names
+//! the key it was signed with by `key_id`, the first 16 hex digits of the
+//! key's HMAC over a fixed label, so a wrong key is reported as such rather
+//! than as tampering without publishing anything derived from the key alone.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::render::{render_approval_packet, ApprovalPacket};
+use ettlex_store::cas::FsStore;
+use ettlex_store::crypto::{hmac_sha256, verify_hmac_sha256_hex};
+use ettlex_store::errors::Result;
+use ettlex_store::profile::{fetch_approval_row, APPROVAL_STATUS_PENDING};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::commands::approval_packet::build_approval_packet;
+use crate::commands::command::Command;
+use crate::commands::engine_query::{apply_engine_query, EngineQuery, EngineQueryResult};
+
+/// `format` of an [`ApprovalExport`].
+pub const APPROVAL_EXPORT_FORMAT: &str = "ettlex.approval_export/v1";
+
+/// `format` of an [`ApprovalResponse`].
+pub const APPROVAL_RESPONSE_FORMAT: &str = "ettlex.approval_response/v1";
+
+/// A pending approval request packaged for an offline reviewer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApprovalExport {
+    /// Always [`APPROVAL_EXPORT_FORMAT`].
+    pub format: String,
+    /// RFC 3339 time the export was written.
+    pub exported_at: String,
+    /// Request status, candidates and their refinement paths.
+    pub packet: ApprovalPacket,
+    /// The request payload as stored in CAS.
+    pub payload: serde_json::Value,
+    /// Markdown review packet, as `ettlex approval render` prints it.
+    pub render: String,
+    /// Key the export is signed with.
+    pub key_id: String,
+    /// Hex HMAC-SHA256 of the export.
+    pub signature: String,
+}
+
+/// The reviewer's answer to an approval request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalDecision {
+    Approved,
+    Rejected,
+}
+
+impl ApprovalDecision {
+    /// Approval status the decision transitions the request to.
+    pub fn as_status(self) -> &'static str {
+        match self {
+            ApprovalDecision::Approved => "approved",
+            ApprovalDecision::Rejected => "rejected",
+        }
+    }
+}
+
+/// A signed decision on an exported approval request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApprovalResponse {
+    /// Always [`APPROVAL_RESPONSE_FORMAT`].
+    pub format: String,
+    pub approval_token: String,
+    /// CAS digest of the request payload the reviewer saw.
+    pub request_digest: String,
+    /// Signature of the export being answered.
+    pub export_signature: String,
+    pub decision: ApprovalDecision,
+    /// Who made the decision; recorded as the transition's actor.
+    pub reviewer: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// RFC 3339 time the response was written.
+    pub responded_at: String,
+    /// Key the response is signed with.
+    pub key_id: String,
+    /// Hex HMAC-SHA256 of the response.
+    pub signature: String,
+}
+
+/// Message MACed under the review key to derive its [`review_key_id`].
+const REVIEW_KEY_ID_LABEL: &[u8] = b"ettlex.review_key_id/v1";
+
+/// Short, non-secret identifier for a review key: the key's HMAC over a
+/// fixed label, so it names the key without being a plain hash of it.
+pub fn review_key_id(key: &[u8]) -> String {
+    hex_encode(&hmac_sha256(key, REVIEW_KEY_ID_LABEL)[..8])
+}
+
+/// Export the pending approval request `approval_token`, signed with `key`.
+///
+/// # Errors
+/// * `InvalidInput` - `key` is empty
+/// * `ApprovalNotFound` - No request exists for `approval_token`
+/// * `ConstraintViolation` - The request is no longer pending
+/// * `ApprovalStorageCorrupt` - The CAS payload is missing or malformed
+pub fn export_approval(
+    conn: &Connection,
+    cas: &FsStore,
+    approval_token: &str,
+    key: &[u8],
+) -> Result<ApprovalExport> {
+    let op = "approval_export";
+    check_key(op, key)?;
+    let packet = build_approval_packet(conn, cas, approval_token)?;
+    if packet.status != APPROVAL_STATUS_PENDING {
+        return Err(ExError::new(ExErrorKind::ConstraintViolation)
+            .with_op(op)
+            .with_entity_id(approval_token)
+            .with_message(format!(
+                "approval request is already '{}'; only pending requests can be exported",
+                packet.status
+            )));
+    }
+    let payload = match apply_engine_query(
+        EngineQuery::ApprovalGet {
+            approval_token: approval_token.to_string(),
+        },
+        conn,
+        cas,
+        None,
+    )? {
+        EngineQueryResult::ApprovalGet(r) => r.payload_json,
+        _ => {
+            return Err(ExError::new(ExErrorKind::Internal)
+                .with_op(op)
+                .with_message("unexpected query result for ApprovalGet"))
+        }
+    };
+
+    let mut export = ApprovalExport {
+        format: APPROVAL_EXPORT_FORMAT.to_string(),
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        render: render_approval_packet(&packet),
+        packet,
+        payload,
+        key_id: review_key_id(key),
+        signature: String::new(),
+    };
+    export.signature = sign(&export_message(&export)?, key);
+    Ok(export)
+}
+
+impl ApprovalExport {
+    /// Check the format, key and signature.
+    ///
+    /// # Errors
+    /// * `InvalidInput` - Unknown `format`, or `key` is empty
+    /// * `Unauthorised` - Signed with another key, or the signature does not
+    ///   match the contents
+    pub fn verify(&self, key: &[u8]) -> Result<()> {
+        let op = "approval_export_verify";
+        check_document(
+            op,
+            &self.packet.approval_token,
+            (&self.format, APPROVAL_EXPORT_FORMAT),
+            &self.key_id,
+            key,
+        )?;
+        check_signature(
+            op,
+            &self.packet.approval_token,
+            &export_message(self)?,
+            &self.signature,
+            key,
+        )
+    }
+}
+
+/// Answer a verified export. Runs offline: needs only the export and the key.
+///
+/// # Errors
+/// * Any error from [`ApprovalExport::verify`]
+/// * `InvalidInput` - `reviewer` is blank
+pub fn respond_to_export(
+    export: &ApprovalExport,
+    key: &[u8],
+    decision: ApprovalDecision,
+    reviewer: &str,
+    note: Option<&str>,
+) -> Result<ApprovalResponse> {
+    export.verify(key)?;
+    let reviewer = reviewer.trim();
+    if reviewer.is_empty() {
+        return Err(ExError::new(ExErrorKind::InvalidInput)
+            .with_op("approval_respond")
+            .with_entity_id(&export.packet.approval_token)
+            .with_message("reviewer must not be empty"));
+    }
+    let mut response = ApprovalResponse {
+        format: APPROVAL_RESPONSE_FORMAT.to_string(),
+        approval_token: export.packet.approval_token.clone(),
+        request_digest: export.packet.request_digest.clone(),
+        export_signature: export.signature.clone(),
+        decision,
+        reviewer: reviewer.to_string(),
+        note: note
+            .map(str::trim)
+            .filter(|n| !n.is_empty())
+            .map(String::from),
+        responded_at: chrono::Utc::now().to_rfc3339(),
+        key_id: review_key_id(key),
+        signature: String::new(),
+    };
+    response.signature = sign(&response_message(&response)?, key);
+    Ok(response)
+}
+
+impl ApprovalResponse {
+    /// Check the format, key and signature.
+    ///
+    /// # Errors
+    /// Same as [`ApprovalExport::verify`].
+    pub fn verify(&self, key: &[u8]) -> Result<()> {
+        let op = "approval_response_verify";
+        check_document(
+            op,
+            &self.approval_token,
+            (&self.format, APPROVAL_RESPONSE_FORMAT),
+            &self.key_id,
+            key,
+        )?;
+        check_signature(
+            op,
+            &self.approval_token,
+            &response_message(self)?,
+            &self.signature,
+            key,
+        )
+    }
+}
+
+/// Verify `response` against `key` and the stored request, and build the
+/// `ApprovalTransition` command that resolves it.
+///
+/// Read-only: the caller applies the command.
+///
+/// # Errors
+/// * Any error from [`ApprovalResponse::verify`]
+/// * `ApprovalNotFound` - No request exists for the response's token
+/// * `ConstraintViolation` - The request was already resolved, or its
+///   payload digest differs from the one the reviewer saw
+pub fn approval_response_command(
+    conn: &Connection,
+    response: &ApprovalResponse,
+    key: &[u8],
+) -> Result<Command> {
+    let op = "approval_import";
+    response.verify(key)?;
+    let row = fetch_approval_row(conn, &response.approval_token)?.ok_or_else(|| {
+        ExError::new(ExErrorKind::ApprovalNotFound)
+            .with_op(op)
+            .with_entity_id(&response.approval_token)
+            .with_message("approval request not found")
+    })?;
+    if row.request_digest.as_deref() != Some(response.request_digest.as_str()) {
+        return Err(ExError::new(ExErrorKind::ConstraintViolation)
+            .with_op(op)
+            .with_entity_id(&response.approval_token)
+            .with_message(format!(
+                "response answers request digest {}, but the stored request has {}",
+                response.request_digest,
+                row.request_digest.as_deref().unwrap_or("none")
+            )));
+    }
+    if row.status != APPROVAL_STATUS_PENDING {
+        return Err(ExError::new(ExErrorKind::ConstraintViolation)
+            .with_op(op)
+            .with_entity_id(&response.approval_token)
+            .with_message(format!(
+                "approval request is already '{}'; only pending requests can be resolved",
+                row.status
+            )));
+    }
+    Ok(Command::ApprovalTransition {
+        approval_token: response.approval_token.clone(),
+        status: response.decision.as_status().to_string(),
+        actor: Some(response.reviewer.clone()),
+    })
+}
+
+fn check_key(op: &str, key: &[u8]) -> Result<()> {
+    if key.is_empty() {
+        return Err(ExError::new(ExErrorKind::InvalidInput)
+            .with_op(op)
+            .with_message("review key must not be empty"));
+    }
+    Ok(())
+}
+
+/// Check `(actual, expected)` format and that `key_id` names `key`.
+fn check_document(
+    op: &str,
+    approval_token: &str,
+    format: (&str, &str),
+    key_id: &str,
+    key: &[u8],
+) -> Result<()> {
+    check_key(op, key)?;
+    if format.0 != format.1 {
+        return Err(ExError::new(ExErrorKind::InvalidInput)
+            .with_op(op)
+            .with_entity_id(approval_token)
+            .with_message(format!(
+                "unsupported format '{}', expected '{}'",
+                format.0, format.1
+            )));
+    }
+    let expected = review_key_id(key);
+    if key_id != expected {
+        return Err(ExError::new(ExErrorKind::Unauthorised)
+            .with_op(op)
+            .with_entity_id(approval_token)
+            .with_message(format!(
+                "signed with key {}, but the review key is {}",
+                key_id, expected
+            )));
+    }
+    Ok(())
+}
+
+fn check_signature(
+    op: &str,
+    approval_token: &str,
+    message: &[u8],
+    signature: &str,
+    key: &[u8],
+) -> Result<()> {
+    if !verify_hmac_sha256_hex(key, message, signature) {
+        return Err(ExError::new(ExErrorKind::Unauthorised)
+            .with_op(op)
+            .with_entity_id(approval_token)
+            .with_message("signature does not match contents; the file was modified"));
+    }
+    Ok(())
+}
+
+/// The bytes an export's signature covers.
+fn export_message(export: &ApprovalExport) -> Result<Vec<u8>> {
+    let unsigned = ApprovalExport {
+        signature: String::new(),
+        ..export.clone()
+    };
+    signed_message("approval_export", &unsigned)
+}
+
+/// The bytes a response's signature covers.
+fn response_message(response: &ApprovalResponse) -> Result<Vec<u8>> {
+    let unsigned = ApprovalResponse {
+        signature: String::new(),
+        ..response.clone()
+    };
+    signed_message("approval_response", &unsigned)
+}
+
+/// `<kind>:<json>` for a document serialized with an empty signature.
+fn signed_message<T: Serialize>(kind: &str, document: &T) -> Result<Vec<u8>> {
+    let json = serde_json::to_string(document).map_err(|e| {
+        ExError::new(ExErrorKind::Serialization)
+            .with_op("approval_sign")
+            .with_message(format!("failed to serialize {}: {}", kind, e))
+    })?;
+    Ok(format!("{}:{}", kind, json).into_bytes())
+}
+
+fn sign(message: &[u8], key: &[u8]) -> String {
+    hex_encode(&hmac_sha256(key, message))
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
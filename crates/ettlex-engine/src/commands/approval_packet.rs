@@ -0,0 +1,150 @@
+//! Review packet assembly for routed approval requests.
+//!
+//! Hydrates an [`ApprovalPacket`] from the `approval_requests` row, its CAS
+//! payload, and the candidate Ettles, then hands it to the pure Markdown
+//! renderer in `ettlex_core::render`. Read-only.
+
+#![allow(clippy::result_large_err)]
+
+use std::collections::HashSet;
+
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::render::{render_approval_packet, ApprovalCandidate, ApprovalPacket};
+use ettlex_store::cas::FsStore;
+use ettlex_store::errors::Result;
+use ettlex_store::model::RelationListOpts;
+use ettlex_store::profile::fetch_approval_row;
+use ettlex_store::repo::SqliteRepo;
+use rusqlite::Connection;
+
+use crate::commands::content_spill::hydrate_ettle_content;
+use crate::commands::engine_query::{apply_engine_query, EngineQuery, EngineQueryResult};
+
+/// Build the review packet for an approval request.
+///
+/// Candidates that do not resolve to an Ettle are kept with no title, summary
+/// or path so the reviewer still sees every option that was offered.
+///
+/// # Errors
+/// * `ApprovalNotFound` - No request exists for `approval_token`
+/// * `ApprovalStorageCorrupt` - The CAS payload is missing or malformed
+pub fn build_approval_packet(
+    conn: &Connection,
+    cas: &FsStore,
+    approval_token: &str,
+) -> Result<ApprovalPacket> {
+    let approval = match apply_engine_query(
+        EngineQuery::ApprovalGet {
+            approval_token: approval_token.to_string(),
+        },
+        conn,
+        cas,
+        None,
+    )? {
+        EngineQueryResult::ApprovalGet(r) => r,
+        _ => {
+            return Err(ExError::new(ExErrorKind::Internal)
+                .with_op("approval_render")
+                .with_message("unexpected query result for ApprovalGet"))
+        }
+    };
+    let row = fetch_approval_row(conn, approval_token)?.ok_or_else(|| {
+        ExError::new(ExErrorKind::ApprovalNotFound)
+            .with_op("approval_render")
+            .with_entity_id(approval_token)
+            .with_message("approval request not found")
+    })?;
+
+    let reason_code = approval.payload.reason_code;
+    let candidate_ids = approval.payload.candidates;
+
+    let mut candidates = Vec::with_capacity(candidate_ids.len());
+    for id in candidate_ids {
+        candidates.push(hydrate_candidate(conn, cas, id)?);
+    }
+
+    Ok(ApprovalPacket {
+        approval_token: approval.approval_token,
+        reason_code,
+        status: row.status,
+        created_at: row.created_at,
+        request_digest: approval.request_digest,
+        semantic_request_digest: approval.semantic_request_digest,
+        candidates,
+    })
+}
+
+/// Build and render the Markdown review packet for an approval request.
+///
+/// # Errors
+/// Same as [`build_approval_packet`].
+pub fn render_approval_request(
+    conn: &Connection,
+    cas: &FsStore,
+    approval_token: &str,
+) -> Result<String> {
+    let packet = build_approval_packet(conn, cas, approval_token)?;
+    Ok(render_approval_packet(&packet))
+}
+
+fn hydrate_candidate(conn: &Connection, cas: &FsStore, id: String) -> Result<ApprovalCandidate> {
+    let Some(mut record) = SqliteRepo::get_ettle_record(conn, &id)? else {
+        return Ok(ApprovalCandidate {
+            id,
+            title: None,
+            summary: None,
+            path: Vec::new(),
+            tombstoned: false,
+        });
+    };
+    hydrate_ettle_content(&mut record, cas)?;
+    let summary = if record.what.trim().is_empty() {
+        record.why.clone()
+    } else {
+        record.what.clone()
+    };
+    let path = refinement_path(conn, &record.id, record.title.clone())?;
+    Ok(ApprovalCandidate {
+        id,
+        title: Some(record.title),
+        summary: Some(summary),
+        path,
+        tombstoned: record.tombstoned_at.is_some(),
+    })
+}
+
+/// Titles from the refinement root down to `ettle_id`.
+///
+/// Where an Ettle has several active parents, the lowest relation id wins so
+/// the path is deterministic.
+fn refinement_path(conn: &Connection, ettle_id: &str, title: String) -> Result<Vec<String>> {
+    let mut path = vec![title];
+    let mut seen: HashSet<String> = HashSet::new();
+    seen.insert(ettle_id.to_string());
+    let mut current = ettle_id.to_string();
+    loop {
+        let mut parents = SqliteRepo::list_relations(
+            conn,
+            &RelationListOpts {
+                source_ettle_id: None,
+                target_ettle_id: Some(current.clone()),
+                relation_type: Some("refinement".to_string()),
+                include_tombstoned: false,
+            },
+        )?;
+        parents.sort_by(|a, b| a.id.cmp(&b.id));
+        let Some(parent_id) = parents.into_iter().next().map(|r| r.source_ettle_id) else {
+            break;
+        };
+        if !seen.insert(parent_id.clone()) {
+            break;
+        }
+        match SqliteRepo::get_ettle_record(conn, &parent_id)? {
+            Some(parent) => path.push(parent.title),
+            None => break,
+        }
+        current = parent_id;
+    }
+    path.reverse();
+    Ok(path)
+}
@@ -0,0 +1,190 @@
+//! Retention sweep for approval request payloads.
+//!
+//! Routed approval requests store their full payload in CAS under
+//! `request_digest`. Once a request has been rejected or expired for longer
+//! than the retention window, the sweep drops the row's reference and
+//! deletes the blob if nothing else in the store still references it (see
+//! `ettlex_store::cas::reachability`). The row itself, its status and its
+//! lifecycle events are kept; only the payload goes. `approval.get` on a
+//! swept request fails with `Deleted`.
+
+#![allow(clippy::result_large_err)]
+
+use std::collections::BTreeSet;
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_core::{log_op_end, log_op_error, log_op_start};
+use ettlex_store::cas::reachability::reachable_digests;
+use ettlex_store::cas::FsStore;
+use ettlex_store::errors::Result;
+use ettlex_store::profile::{list_sweepable_approvals, mark_approval_payload_swept};
+use rusqlite::Connection;
+use serde::Serialize;
+
+use super::command::{apply_command_with_options, Command, CommandResult};
+use super::engine_command::CommandOptions;
+use super::settings::{approval_retention_days, SETTING_APPROVAL_RETENTION_DAYS};
+
+const MS_PER_DAY: i64 = 24 * 60 * 60 * 1000;
+
+/// One request whose payload was (or would be) swept.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SweptApproval {
+    pub approval_token: String,
+    pub status: String,
+    pub request_digest: String,
+    /// `false` when another row still references the blob, or it was
+    /// already missing from CAS.
+    pub blob_deleted: bool,
+}
+
+/// Outcome of [`approval_payload_sweep`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ApprovalSweepReport {
+    /// Swept requests, in token order.
+    pub swept: Vec<SweptApproval>,
+    pub blobs_deleted: usize,
+    pub bytes_freed: u64,
+}
+
+/// Sweep payload blobs of requests rejected or expired more than the
+/// retention window ago.
+///
+/// Applies `Command::ApprovalPayloadSweep`, which drops the references in
+/// one logged command, then deletes the blobs nothing else references once
+/// that command has committed. A failure part way leaves at most
+/// unreferenced blobs behind (for `cas gc`), never rows pointing at missing
+/// ones. `retention_days` overrides the `approval.retention_days` setting.
+/// With `dry_run` the command is rolled back and no blob is deleted.
+///
+/// ## Errors
+///
+/// - `InvalidInput`: No retention window given or configured
+/// - `Persistence`: Database or CAS error
+pub fn approval_payload_sweep(
+    conn: &mut Connection,
+    cas: &FsStore,
+    retention_days: Option<u32>,
+    dry_run: bool,
+) -> Result<ApprovalSweepReport> {
+    log_op_start!("approval_payload_sweep");
+    let start = std::time::Instant::now();
+
+    let result = approval_payload_sweep_impl(conn, cas, retention_days, dry_run);
+    match &result {
+        Ok(report) => log_op_end!(
+            "approval_payload_sweep",
+            duration_ms = start.elapsed().as_millis() as u64,
+            swept = report.swept.len(),
+            blobs_deleted = report.blobs_deleted
+        ),
+        Err(e) => log_op_error!(
+            "approval_payload_sweep",
+            e.clone(),
+            duration_ms = start.elapsed().as_millis() as u64
+        ),
+    }
+    result
+}
+
+fn approval_payload_sweep_impl(
+    conn: &mut Connection,
+    cas: &FsStore,
+    retention_days: Option<u32>,
+    dry_run: bool,
+) -> Result<ApprovalSweepReport> {
+    let report = match apply_command_with_options(
+        Command::ApprovalPayloadSweep { retention_days },
+        None,
+        CommandOptions { dry_run },
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )? {
+        (
+            CommandResult::ApprovalPayloadSweep {
+                swept,
+                blobs_deleted,
+                bytes_freed,
+            },
+            _,
+        ) => ApprovalSweepReport {
+            swept,
+            blobs_deleted,
+            bytes_freed,
+        },
+        _ => {
+            return Err(ExError::new(ExErrorKind::Internal)
+                .with_op("approval_payload_sweep")
+                .with_message("unexpected command result"))
+        }
+    };
+    if !dry_run {
+        for approval in report.swept.iter().filter(|a| a.blob_deleted) {
+            if let Some(blob) = cas.find_blob(&approval.request_digest)? {
+                cas.remove_blob(&blob)?;
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Drop the payload references of requests past the retention window and
+/// report which blobs nothing else still needs. Applied as
+/// `Command::ApprovalPayloadSweep`, inside `apply_command`'s transaction;
+/// the blobs themselves are left in CAS (see [`approval_payload_sweep`]).
+pub(crate) fn handle_approval_payload_sweep(
+    conn: &Connection,
+    cas: &FsStore,
+    retention_days: Option<u32>,
+) -> Result<ApprovalSweepReport> {
+    let days = match retention_days {
+        Some(days) => days,
+        None => approval_retention_days(conn)?.ok_or_else(|| {
+            ExError::new(ExErrorKind::InvalidInput)
+                .with_op("approval_payload_sweep")
+                .with_message(format!(
+                    "no retention window: pass one or set '{}'",
+                    SETTING_APPROVAL_RETENTION_DAYS
+                ))
+        })?,
+    };
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let cutoff_ms = now_ms - i64::from(days) * MS_PER_DAY;
+
+    // Drop the references, then see which blobs nothing else still needs.
+    let due = list_sweepable_approvals(conn, cutoff_ms)?;
+    for approval in &due {
+        mark_approval_payload_swept(conn, &approval.approval_token, now_ms)?;
+    }
+    let reachable = if due.is_empty() {
+        BTreeSet::new()
+    } else {
+        reachable_digests(conn)?
+    };
+
+    let mut report = ApprovalSweepReport::default();
+    let mut handled = BTreeSet::new();
+    for approval in due {
+        let mut blob_deleted = false;
+        if !reachable.contains(&approval.request_digest)
+            && handled.insert(approval.request_digest.clone())
+        {
+            if let Some(blob) = cas.find_blob(&approval.request_digest)? {
+                blob_deleted = true;
+                report.blobs_deleted += 1;
+                report.bytes_freed += blob.size_bytes;
+            }
+        }
+        report.swept.push(SweptApproval {
+            approval_token: approval.approval_token,
+            status: approval.status,
+            request_digest: approval.request_digest,
+            blob_deleted,
+        });
+    }
+    Ok(report)
+}
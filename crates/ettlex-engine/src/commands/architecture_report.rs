@@ -0,0 +1,190 @@
+//! Assembly of the "state of the architecture" report.
+//!
+//! Backs the `ArchitectureReport` engine query. All reads are aggregate and
+//! read-only; the result is rendered by `ettlex_core::render::report_render`.
+
+#![allow(clippy::result_large_err)]
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use ettlex_core::render::report_render::{
+    ArchitectureReport, ConstraintCoverage, ReportApproval, ReportDecision, ReportSnapshot,
+    TreeAnalytics,
+};
+use ettlex_store::errors::Result;
+use ettlex_store::model::{RelationListOpts, ROOT_STATUS_ACTIVE};
+use ettlex_store::repo::SqliteRepo;
+use rusqlite::Connection;
+
+/// Decision statuses that count as closed for the "open decisions" section.
+const TERMINAL_DECISION_STATUSES: &[&str] = &["accepted", "rejected", "superseded", "deprecated"];
+
+/// Build the architecture report, including at most `recent_snapshot_limit`
+/// snapshots in the history section.
+pub(crate) fn build_architecture_report(
+    conn: &Connection,
+    recent_snapshot_limit: u32,
+) -> Result<ArchitectureReport> {
+    let active: BTreeSet<String> = SqliteRepo::list_unarchived_ettle_ids(conn)?
+        .into_iter()
+        .collect();
+    let tombstoned_ettles = SqliteRepo::count_tombstoned_ettles(conn)?;
+    let archived_ettles = SqliteRepo::count_archived_ettles(conn)?;
+
+    // Active relations between active Ettles, grouped by type
+    let edges = SqliteRepo::list_relations(conn, &RelationListOpts::default())?;
+
+    let mut relations_by_type: BTreeMap<String, u64> = BTreeMap::new();
+    let mut children: HashMap<&str, Vec<&str>> = HashMap::new();
+    let mut has_parent: BTreeSet<&str> = BTreeSet::new();
+    let mut constraint_sources: BTreeSet<&str> = BTreeSet::new();
+    let mut governed: BTreeSet<&str> = BTreeSet::new();
+    for edge in &edges {
+        let (source, target) = (edge.source_ettle_id.as_str(), edge.target_ettle_id.as_str());
+        *relations_by_type
+            .entry(edge.relation_type.clone())
+            .or_default() += 1;
+        if !active.contains(source) || !active.contains(target) {
+            continue;
+        }
+        match edge.relation_type.as_str() {
+            "refinement" => {
+                children.entry(source).or_default().push(target);
+                has_parent.insert(target);
+            }
+            "constraint" => {
+                constraint_sources.insert(source);
+                governed.insert(target);
+            }
+            _ => {}
+        }
+    }
+
+    let roots: Vec<&str> = active
+        .iter()
+        .map(String::as_str)
+        .filter(|id| !has_parent.contains(id))
+        .collect();
+    let leaf_count = active
+        .iter()
+        .filter(|id| !children.get(id.as_str()).is_some_and(|c| !c.is_empty()))
+        .count() as u64;
+    let mut depth_memo: HashMap<&str, u64> = HashMap::new();
+    let max_depth = roots
+        .iter()
+        .map(|r| depth(r, &children, &mut depth_memo, &mut BTreeSet::new()))
+        .max()
+        .unwrap_or(0);
+
+    let registry = SqliteRepo::list_roots(conn, true)?;
+    let registered: BTreeSet<&str> = registry.iter().map(|r| r.ettle_id.as_str()).collect();
+    let unregistered_roots = SqliteRepo::list_implicit_roots(conn)?
+        .iter()
+        .filter(|id| !registered.contains(id.as_str()))
+        .count() as u64;
+
+    let tree = TreeAnalytics {
+        active_ettles: active.len() as u64,
+        tombstoned_ettles,
+        archived_ettles,
+        root_count: roots.len() as u64,
+        registered_roots: registry
+            .iter()
+            .filter(|r| r.status == ROOT_STATUS_ACTIVE)
+            .count() as u64,
+        unregistered_roots,
+        leaf_count,
+        max_depth,
+        relations_by_type,
+        active_groups: SqliteRepo::list_groups(conn, false)?.len() as u64,
+    };
+
+    let ungoverned_ettles = active
+        .iter()
+        .filter(|id| !governed.contains(id.as_str()) && !constraint_sources.contains(id.as_str()))
+        .count() as u64;
+    let justified: BTreeSet<String> = SqliteRepo::list_justified_constraint_ids(conn)?
+        .into_iter()
+        .collect();
+    let constraint_coverage = ConstraintCoverage {
+        constraint_ettles: constraint_sources.len() as u64,
+        justified_constraints: constraint_sources
+            .iter()
+            .filter(|id| justified.contains(**id))
+            .count() as u64,
+        governed_ettles: governed
+            .iter()
+            .filter(|id| !constraint_sources.contains(*id))
+            .count() as u64,
+        ungoverned_ettles,
+        dangling_attachments: SqliteRepo::list_dangling_constraint_relations(conn)?.len() as u64,
+    };
+
+    let recent_snapshots = SqliteRepo::list_recent_snapshots(conn, recent_snapshot_limit)?
+        .into_iter()
+        .map(|s| ReportSnapshot {
+            snapshot_id: s.snapshot_id,
+            root_ettle_id: s.root_ettle_id,
+            manifest_digest: s.manifest_digest,
+            created_at: s.created_at,
+            message: s.message,
+        })
+        .collect();
+
+    let mut open_decisions: Vec<ReportDecision> = SqliteRepo::list_decisions(conn)?
+        .into_iter()
+        .filter(|d| !d.is_tombstoned() && !TERMINAL_DECISION_STATUSES.contains(&d.status.as_str()))
+        .map(|d| ReportDecision {
+            decision_id: d.decision_id,
+            title: d.title,
+            status: d.status,
+        })
+        .collect();
+    open_decisions.sort_by(|a, b| a.decision_id.cmp(&b.decision_id));
+
+    let pending_approvals = SqliteRepo::list_pending_approvals(conn)?
+        .into_iter()
+        .map(|a| ReportApproval {
+            approval_token: a.approval_token,
+            reason_code: a.reason_code,
+            created_at: a.created_at,
+        })
+        .collect();
+
+    Ok(ArchitectureReport {
+        tree,
+        recent_snapshots,
+        open_decisions,
+        constraint_coverage,
+        pending_approvals,
+    })
+}
+
+/// Length in Ettles of the longest refinement chain starting at `node`.
+///
+/// `on_path` guards against cycles in corrupt data.
+fn depth<'a>(
+    node: &'a str,
+    children: &HashMap<&'a str, Vec<&'a str>>,
+    memo: &mut HashMap<&'a str, u64>,
+    on_path: &mut BTreeSet<&'a str>,
+) -> u64 {
+    if let Some(d) = memo.get(node) {
+        return *d;
+    }
+    if !on_path.insert(node) {
+        return 0;
+    }
+    let below = children
+        .get(node)
+        .map(|cs| {
+            cs.iter()
+                .map(|c| depth(c, children, memo, on_path))
+                .max()
+                .unwrap_or(0)
+        })
+        .unwrap_or(0);
+    on_path.remove(node);
+    memo.insert(node, below + 1);
+    below + 1
+}
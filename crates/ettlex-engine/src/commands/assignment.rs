@@ -0,0 +1,142 @@
+//! Engine handlers for Ettle ownership and review assignments.
+//!
+//! Each Ettle may name one owner and any number of reviewers. Principals are
+//! free-form strings; the engine only trims and deduplicates them. Approval
+//! requests surface the assignees of their candidate Ettles so routers and
+//! reviewers can find the right people.
+
+#![allow(clippy::result_large_err)]
+
+use std::collections::BTreeSet;
+
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_store::model::{ASSIGNMENT_ROLE_OWNER, ASSIGNMENT_ROLE_REVIEWER};
+use ettlex_store::repo::SqliteRepo;
+use rusqlite::Connection;
+
+use super::command::CommandResult;
+use super::read_tools::{AssignedEttle, EttleAssignees};
+
+type Result<T> = std::result::Result<T, ExError>;
+
+/// Set or clear an Ettle's owner and/or replace its reviewers.
+///
+/// `owner: Some(None)` clears the owner; `reviewers: Some(vec![])` clears
+/// the reviewers; `None` leaves either unchanged.
+///
+/// Invariants enforced:
+/// - At least one of `owner` / `reviewers` must be supplied (`EmptyUpdate`).
+/// - The Ettle must exist (`NotFound`) and not be tombstoned
+///   (`AlreadyTombstoned`).
+/// - Principals must be non-blank (`InvalidInput`).
+pub fn handle_ettle_assign(
+    conn: &mut Connection,
+    ettle_id: String,
+    owner: Option<Option<String>>,
+    reviewers: Option<Vec<String>>,
+) -> Result<CommandResult> {
+    let op = "ettle_assign";
+    if owner.is_none() && reviewers.is_none() {
+        return Err(ExError::new(ExErrorKind::EmptyUpdate)
+            .with_op(op)
+            .with_entity_id(&ettle_id)
+            .with_message("ettle_assign requires owner or reviewers"));
+    }
+    let ettle = SqliteRepo::get_ettle_record(conn, &ettle_id)?.ok_or_else(|| {
+        ExError::new(ExErrorKind::NotFound)
+            .with_op(op)
+            .with_entity_id(&ettle_id)
+            .with_message(format!("Ettle not found: {}", ettle_id))
+    })?;
+    if ettle.tombstoned_at.is_some() {
+        return Err(ExError::new(ExErrorKind::AlreadyTombstoned)
+            .with_op(op)
+            .with_entity_id(&ettle_id)
+            .with_message(format!("Ettle is tombstoned: {}", ettle_id)));
+    }
+
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    if let Some(owner) = owner {
+        let owners = principals(op, &ettle_id, owner.into_iter().collect())?;
+        SqliteRepo::set_ettle_assignments(conn, &ettle_id, ASSIGNMENT_ROLE_OWNER, &owners, now_ms)?;
+    }
+    if let Some(reviewers) = reviewers {
+        let reviewers = principals(op, &ettle_id, reviewers)?;
+        SqliteRepo::set_ettle_assignments(
+            conn,
+            &ettle_id,
+            ASSIGNMENT_ROLE_REVIEWER,
+            &reviewers,
+            now_ms,
+        )?;
+    }
+    Ok(CommandResult::EttleAssign)
+}
+
+/// The owner and reviewers of an Ettle.
+pub fn ettle_assignees(conn: &Connection, ettle_id: &str) -> Result<EttleAssignees> {
+    let mut assignees = EttleAssignees::default();
+    for record in SqliteRepo::list_ettle_assignments(conn, ettle_id)? {
+        if record.role == ASSIGNMENT_ROLE_OWNER {
+            assignees.owner = Some(record.principal);
+        } else {
+            assignees.reviewers.push(record.principal);
+        }
+    }
+    Ok(assignees)
+}
+
+/// Non-tombstoned Ettles owned by `owner`, plus those they review when
+/// `include_reviewing` is set, ordered by Ettle ID then role.
+pub(crate) fn ettles_by_owner(
+    conn: &Connection,
+    owner: &str,
+    include_reviewing: bool,
+) -> Result<Vec<AssignedEttle>> {
+    let roles: &[&str] = if include_reviewing {
+        &[ASSIGNMENT_ROLE_OWNER, ASSIGNMENT_ROLE_REVIEWER]
+    } else {
+        &[ASSIGNMENT_ROLE_OWNER]
+    };
+    SqliteRepo::list_assignments_by_principal(conn, owner, roles)?
+        .into_iter()
+        .map(|record| {
+            let title = SqliteRepo::get_ettle_record(conn, &record.ettle_id)?
+                .map(|e| e.title)
+                .unwrap_or_default();
+            Ok(AssignedEttle {
+                ettle_id: record.ettle_id,
+                title,
+                role: record.role,
+            })
+        })
+        .collect()
+}
+
+/// Owners and reviewers of the given Ettles, sorted and deduplicated.
+/// IDs that name no Ettle contribute nobody.
+pub(crate) fn assignees_of(conn: &Connection, ettle_ids: &[String]) -> Result<Vec<String>> {
+    let mut all = BTreeSet::new();
+    for ettle_id in ettle_ids {
+        for record in SqliteRepo::list_ettle_assignments(conn, ettle_id)? {
+            all.insert(record.principal);
+        }
+    }
+    Ok(all.into_iter().collect())
+}
+
+/// Trim, validate and deduplicate principals, sorted.
+fn principals(op: &str, ettle_id: &str, raw: Vec<String>) -> Result<Vec<String>> {
+    let mut out = BTreeSet::new();
+    for principal in raw {
+        let principal = principal.trim();
+        if principal.is_empty() {
+            return Err(ExError::new(ExErrorKind::InvalidInput)
+                .with_op(op)
+                .with_entity_id(ettle_id)
+                .with_message("principal must not be blank"));
+        }
+        out.insert(principal.to_string());
+    }
+    Ok(out.into_iter().collect())
+}
@@ -9,25 +9,54 @@
 
 use ettlex_core::approval_router::ApprovalRouter;
 use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::log_op_slow;
 use ettlex_core::policy_provider::PolicyProvider;
+use ettlex_core::snapshot::ConstraintsEnvelopeSummary;
 use ettlex_store::cas::FsStore;
 use ettlex_store::errors::Result;
 use ettlex_store::model::{GroupMemberRecord, GroupRecord, RelationRecord};
+use ettlex_store::profile::transition_approval;
+use ettlex_store::repo::SqliteRepo;
+use ettlex_store::snapshot::SnapshotIdScheme;
 use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
 
-use crate::commands::ettle::{handle_ettle_create, handle_ettle_tombstone, handle_ettle_update};
+use crate::commands::api_token::{handle_api_token_issue, handle_api_token_revoke};
+use crate::commands::approval_sweep::{handle_approval_payload_sweep, SweptApproval};
+use crate::commands::assignment::handle_ettle_assign;
+use crate::commands::comment::{handle_comment_add, handle_comment_reopen, handle_comment_resolve};
+use crate::commands::constraint::{
+    handle_constraint_attach_bulk, handle_constraint_sweep_orphans, TargetSelector,
+};
+use crate::commands::decision::{decision_create, decision_restore};
+use crate::commands::ettle::{
+    handle_ettle_archive, handle_ettle_create, handle_ettle_restore,
+    handle_ettle_set_content_format, handle_ettle_tombstone, handle_ettle_unarchive,
+    handle_ettle_update, resolve_ettle_ref,
+};
+use crate::commands::evidence::{handle_evidence_sweep, PrunedEvidence};
 use crate::commands::group::{
     handle_group_create, handle_group_get, handle_group_list, handle_group_member_add,
-    handle_group_member_list, handle_group_member_remove, handle_group_tombstone,
+    handle_group_member_list, handle_group_member_remove, handle_group_restore,
+    handle_group_tombstone,
 };
+use crate::commands::import_session::handle_import_session_rollback;
+use crate::commands::journal;
+use crate::commands::refactor::{handle_content_ref_rename, ContentRefOccurrence, ContentRefSite};
 use crate::commands::relation::{
-    handle_relation_create, handle_relation_get, handle_relation_list, handle_relation_tombstone,
-    handle_relation_update,
+    handle_relation_create, handle_relation_get, handle_relation_list, handle_relation_restore,
+    handle_relation_tombstone, handle_relation_update,
 };
+use crate::commands::root::{handle_root_register, handle_root_update};
+use crate::commands::settings::dedup_enabled;
+use crate::feature_flags::handle_feature_set;
 
-use crate::commands::engine_command::{apply_engine_command, EngineCommand, EngineCommandResult};
+use crate::commands::engine_command::{
+    apply_engine_command, with_rollback, CommandOptions, EngineCommand, EngineCommandResult,
+};
+use crate::commands::validate::validate_id;
 use crate::snapshot::SnapshotOptions;
 
 // ---------------------------------------------------------------------------
@@ -53,6 +82,16 @@ pub enum Command {
         #[serde(default)]
         dry_run: bool,
         expected_head: Option<String>,
+        /// Snapshot ID scheme (`uuid`, `root-counter`, `digest-prefix`); default `uuid`.
+        #[serde(default)]
+        id_scheme: SnapshotIdScheme,
+        /// Optional free-text commit message.
+        #[serde(default)]
+        message: Option<String>,
+        /// Optional key/value annotations. Neither these nor `message` affect
+        /// the manifest digest.
+        #[serde(default)]
+        annotations: BTreeMap<String, String>,
     },
 
     // ── Ettle ────────────────────────────────────────────────────────────────
@@ -103,6 +142,47 @@ pub enum Command {
     /// The Ettle must have no active dependants.
     EttleTombstone { ettle_id: String },
 
+    /// Restore a tombstoned Ettle.
+    ///
+    /// Its `reasoning_link_id` target (if any) must be active.
+    EttleRestore { ettle_id: String },
+
+    /// Archive an Ettle (and, with `subtree`, its refinement descendants).
+    ///
+    /// Archived Ettles are hidden from default listings and renders and cannot
+    /// root new snapshots, but remain readable and restorable.
+    EttleArchive {
+        ettle_id: String,
+        #[serde(default)]
+        subtree: bool,
+    },
+
+    /// Reverse [`Command::EttleArchive`].
+    EttleUnarchive {
+        ettle_id: String,
+        #[serde(default)]
+        subtree: bool,
+    },
+
+    /// Declare the format of an Ettle's why/what/how content (`markdown`,
+    /// `asciidoc` or `plain`). Renderers fence non-Markdown content.
+    EttleSetContentFormat {
+        ettle_id: String,
+        content_format: String,
+    },
+
+    /// Set an Ettle's owner and/or reviewers.
+    ///
+    /// `owner: null` clears the owner and `reviewers: []` clears the
+    /// reviewers; an absent field is left unchanged.
+    EttleAssign {
+        ettle_id: String,
+        #[serde(default, deserialize_with = "deserialize_double_option")]
+        owner: Option<Option<String>>,
+        #[serde(default)]
+        reviewers: Option<Vec<String>>,
+    },
+
     // ── Profile ───────────────────────────────────────────────────────────────
     /// Create a profile.
     ProfileCreate {
@@ -110,8 +190,29 @@ pub enum Command {
         payload_json: JsonValue,
         source: Option<String>,
     },
-    /// Set the repository default profile.
-    ProfileSetDefault { profile_ref: String },
+    /// Set the repository default profile, or the default for `environment`.
+    ProfileSetDefault {
+        profile_ref: String,
+        #[serde(default)]
+        environment: Option<String>,
+    },
+
+    // ── Approval ──────────────────────────────────────────────────────────────
+    /// Move a pending approval request to `approved`, `rejected` or `expired`,
+    /// recording the transition in its history.
+    ApprovalTransition {
+        approval_token: String,
+        status: String,
+        #[serde(default)]
+        actor: Option<String>,
+    },
+    /// Drop the payload references of requests rejected or expired past the
+    /// retention window (see [`super::approval_sweep::approval_payload_sweep`],
+    /// which also deletes the blobs afterwards).
+    ApprovalPayloadSweep {
+        #[serde(default)]
+        retention_days: Option<u32>,
+    },
 
     // ── Policy ───────────────────────────────────────────────────────────────
     /// Create a new policy document in the policy provider.
@@ -165,6 +266,40 @@ pub enum Command {
     /// Tombstone a relation.
     RelationTombstone { relation_id: String },
 
+    /// Restore a tombstoned relation. Both endpoint ettles must be active.
+    RelationRestore { relation_id: String },
+
+    // ── Constraints ───────────────────────────────────────────────────────────
+    /// Attach a constraint ettle to every ettle matched by `selector`.
+    ///
+    /// Creates one `constraint` relation per newly-attached target, all in a
+    /// single transaction. `properties_json` (an object) is copied onto each
+    /// relation together with a computed `ordinal`.
+    ConstraintAttachBulk {
+        constraint_ettle_id: String,
+        selector: TargetSelector,
+        #[serde(default)]
+        properties_json: Option<JsonValue>,
+    },
+
+    /// Tombstone every active `constraint` relation whose source or target
+    /// Ettle is missing or tombstoned.
+    ConstraintSweepOrphans,
+
+    // ── Refactoring ───────────────────────────────────────────────────────────
+    /// Rewrite whole-token references to `from` as `to` in Ettle content.
+    ///
+    /// `selector` limits the Ettles searched (default: all active Ettles);
+    /// `only` limits the rewrite to occurrences accepted from a dry-run preview.
+    ContentRefRename {
+        from: String,
+        to: String,
+        #[serde(default)]
+        selector: Option<TargetSelector>,
+        #[serde(default)]
+        only: Option<Vec<ContentRefSite>>,
+    },
+
     // ── Groups ────────────────────────────────────────────────────────────────
     /// Create a new group.
     GroupCreate { name: String },
@@ -181,6 +316,9 @@ pub enum Command {
     /// Tombstone a group.
     GroupTombstone { group_id: String },
 
+    /// Restore a tombstoned group.
+    GroupRestore { group_id: String },
+
     /// Add an ettle to a group.
     GroupMemberAdd { group_id: String, ettle_id: String },
 
@@ -193,6 +331,118 @@ pub enum Command {
         #[serde(default)]
         include_tombstoned: bool,
     },
+
+    // ── Decision ──────────────────────────────────────────────────────────────
+    /// Record a new decision. The ID is auto-generated; `status` defaults to
+    /// `proposed` and `evidence_kind` to `none`.
+    DecisionCreate {
+        title: String,
+        #[serde(default)]
+        status: Option<String>,
+        decision_text: String,
+        rationale: String,
+        #[serde(default)]
+        alternatives_text: Option<String>,
+        #[serde(default)]
+        consequences_text: Option<String>,
+        #[serde(default = "default_evidence_kind")]
+        evidence_kind: String,
+        #[serde(default)]
+        evidence_excerpt: Option<String>,
+        #[serde(default)]
+        evidence_capture_content: Option<String>,
+        #[serde(default)]
+        evidence_file_path: Option<String>,
+    },
+
+    /// Restore a tombstoned decision.
+    DecisionRestore { decision_id: String },
+
+    // ── Comments ──────────────────────────────────────────────────────────────
+    /// Add a comment to an Ettle, decision or snapshot.
+    ///
+    /// With `parent_comment_id`, the comment is a reply to that thread root.
+    CommentAdd {
+        target_kind: String,
+        target_id: String,
+        body: String,
+        #[serde(default)]
+        author: Option<String>,
+        #[serde(default)]
+        parent_comment_id: Option<String>,
+    },
+
+    /// Mark a comment thread resolved.
+    CommentResolve { comment_id: String },
+
+    /// Reopen a resolved comment thread.
+    CommentReopen { comment_id: String },
+
+    // ── Root registry ─────────────────────────────────────────────────────────
+    /// Register an existing parentless Ettle as a root.
+    RootRegister {
+        ettle_id: String,
+        display_name: String,
+        #[serde(default)]
+        owner: Option<String>,
+    },
+
+    /// Rename, reassign or retire/reactivate a registered root.
+    ///
+    /// `owner: null` clears the owner; an absent field is left unchanged.
+    RootUpdate {
+        ettle_id: String,
+        #[serde(default)]
+        display_name: Option<String>,
+        #[serde(default, deserialize_with = "deserialize_double_option")]
+        owner: Option<Option<String>>,
+        #[serde(default)]
+        status: Option<String>,
+    },
+
+    // ── API tokens ────────────────────────────────────────────────────────────
+    /// Issue a server API token (see [`super::api_token`]). The secret is
+    /// returned in the result only.
+    ApiTokenIssue { name: String, scopes: Vec<String> },
+    /// Revoke a server API token.
+    ApiTokenRevoke { token_id: String },
+
+    // ── Feature flags ─────────────────────────────────────────────────────────
+    /// Enable or disable an experimental feature for this repository, or
+    /// clear the setting with `enabled: None` so the flag's default applies
+    /// (see [`crate::feature_flags`]).
+    FeatureSet {
+        name: String,
+        #[serde(default)]
+        enabled: Option<bool>,
+    },
+
+    // ── Evidence ──────────────────────────────────────────────────────────────
+    /// Prune capture content of decisions tombstoned past the retention
+    /// window (see [`super::evidence::evidence_sweep`]). `retention_days`
+    /// overrides the configured window.
+    EvidenceSweep {
+        #[serde(default)]
+        retention_days: Option<u32>,
+    },
+
+    // ── Import sessions ───────────────────────────────────────────────────────
+    /// Delete the Ettles and relations a `running` import session created and
+    /// mark it rolled back (see [`super::import_session`]).
+    ImportSessionRollback { session_id: String },
+
+    // ── Batch ─────────────────────────────────────────────────────────────────
+    /// Apply several commands atomically, in order (see
+    /// [`apply_commands_atomic`]).
+    ///
+    /// Later commands may refer to Ettles created earlier in the batch by
+    /// slug. `Batch`, `SnapshotCommit`, `ApprovalTransition` and
+    /// `PolicyCreate` may not appear inside a batch.
+    Batch { commands: Vec<Command> },
+}
+
+fn default_evidence_kind() -> String {
+    "none".to_string()
 }
 
 /// Deserializer for `Option<Option<T>>` that distinguishes absent from null.
@@ -222,6 +472,7 @@ pub enum CommandResult {
     SnapshotCommit {
         snapshot_id: String,
         manifest_digest: String,
+        constraints_summary: ConstraintsEnvelopeSummary,
     },
     RoutedForApproval {
         approval_token: String,
@@ -231,8 +482,22 @@ pub enum CommandResult {
     },
     EttleUpdate,
     EttleTombstone,
+    EttleRestore,
+    EttleArchive {
+        archived_ettle_ids: Vec<String>,
+    },
+    EttleUnarchive {
+        unarchived_ettle_ids: Vec<String>,
+    },
+    EttleSetContentFormat,
+    EttleAssign,
     ProfileCreate,
     ProfileSetDefault,
+    ApprovalTransition {
+        event_id: i64,
+        from_status: String,
+        to_status: String,
+    },
     PolicyCreate {
         policy_ref: String,
     },
@@ -247,6 +512,19 @@ pub enum CommandResult {
         items: Vec<RelationRecord>,
     },
     RelationTombstone,
+    RelationRestore,
+    ConstraintAttachBulk {
+        relation_ids: Vec<String>,
+        attached_ettle_ids: Vec<String>,
+        skipped_ettle_ids: Vec<String>,
+    },
+    ConstraintSweepOrphans {
+        relation_ids: Vec<String>,
+    },
+    ContentRefRename {
+        occurrences: Vec<ContentRefOccurrence>,
+        updated_ettle_ids: Vec<String>,
+    },
     GroupCreate {
         group_id: String,
     },
@@ -257,11 +535,50 @@ pub enum CommandResult {
         items: Vec<GroupRecord>,
     },
     GroupTombstone,
+    GroupRestore,
     GroupMemberAdd,
     GroupMemberRemove,
     GroupMemberList {
         items: Vec<GroupMemberRecord>,
     },
+    DecisionCreate {
+        decision_id: String,
+    },
+    DecisionRestore,
+    CommentAdd {
+        comment_id: String,
+    },
+    CommentResolve,
+    CommentReopen,
+    RootRegister,
+    RootUpdate,
+    /// `secret` is shown once; only its hash is stored.
+    ApiTokenIssue {
+        token_id: String,
+        scopes: Vec<String>,
+        secret: String,
+    },
+    ApiTokenRevoke,
+    FeatureSet,
+    /// `blob_deleted` marks payload blobs nothing references any more; the
+    /// command itself leaves them in CAS.
+    ApprovalPayloadSweep {
+        swept: Vec<SweptApproval>,
+        blobs_deleted: usize,
+        bytes_freed: u64,
+    },
+    EvidenceSweep {
+        pruned: Vec<PrunedEvidence>,
+        bytes_freed: u64,
+    },
+    ImportSessionRollback {
+        relations: usize,
+        ettles: usize,
+    },
+    /// One result per command in the batch, in order.
+    Batch {
+        results: Vec<CommandResult>,
+    },
 }
 
 // ---------------------------------------------------------------------------
@@ -273,11 +590,20 @@ pub enum CommandResult {
 /// Steps:
 /// 1. Read current `state_version` (COUNT(*) from `command_log`).
 /// 2. If `expected_state_version` is `Some(v)` and `v != current` → `HeadMismatch`.
-/// 3. Execute the command.
+/// 3. Execute the command, recording reversible ones in the undo/redo
+///    journal (see [`super::journal`]).
 /// 4. Append provenance event for successful mutations.
-/// 5. Insert a row into `command_log` → `new_state_version = current + 1`.
+/// 5. Insert a row into `command_log`, naming the op and the entity it
+///    targeted or created → `new_state_version = current + 1`.
+///
+/// All five steps run in one transaction (a savepoint when the connection
+/// already has one open), so a failure at any step leaves no partial write
+/// in the database. Writes outside it — CAS blobs and policy files — are not
+/// undone.
 ///
-/// Returns `(CommandResult, new_state_version)`.
+/// Returns `(CommandResult, new_state_version)`. Each call's latency is
+/// recorded under the command's op name, and calls over the slow-op
+/// threshold log a warning with the target entity ID.
 pub fn apply_command(
     cmd: Command,
     expected_state_version: Option<u64>,
@@ -285,6 +611,313 @@ pub fn apply_command(
     cas: &FsStore,
     policy_provider: &dyn PolicyProvider,
     approval_router: &dyn ApprovalRouter,
+) -> Result<(CommandResult, u64)> {
+    if let Command::Batch { commands } = cmd {
+        return apply_commands_atomic(
+            commands,
+            expected_state_version,
+            conn,
+            cas,
+            policy_provider,
+            approval_router,
+        )
+        .map(|(results, sv)| (CommandResult::Batch { results }, sv));
+    }
+    let (op, entity_id) = command_op(&cmd);
+    let entity_id = entity_id.map(str::to_string);
+    let start = std::time::Instant::now();
+    let result = run_command(
+        cmd,
+        expected_state_version,
+        conn,
+        cas,
+        policy_provider,
+        approval_router,
+        true,
+    );
+    let elapsed = start.elapsed().as_millis() as u64;
+    log_op_slow!(
+        op,
+        duration_ms = elapsed,
+        entity_id = entity_id.as_deref(),
+        failed = result.is_err(),
+    );
+    result
+}
+
+/// Op name and target entity ID (if the command names one) for logging.
+fn command_op(cmd: &Command) -> (&'static str, Option<&str>) {
+    match cmd {
+        Command::SnapshotCommit { leaf_ep_id, .. } => ("snapshot_commit", Some(leaf_ep_id)),
+        Command::EttleCreate { .. } => ("ettle_create", None),
+        Command::EttleUpdate { ettle_id, .. } => ("ettle_update", Some(ettle_id)),
+        Command::EttleTombstone { ettle_id } => ("ettle_tombstone", Some(ettle_id)),
+        Command::EttleRestore { ettle_id } => ("ettle_restore", Some(ettle_id)),
+        Command::EttleArchive { ettle_id, .. } => ("ettle_archive", Some(ettle_id)),
+        Command::EttleUnarchive { ettle_id, .. } => ("ettle_unarchive", Some(ettle_id)),
+        Command::EttleSetContentFormat { ettle_id, .. } => {
+            ("ettle_set_content_format", Some(ettle_id))
+        }
+        Command::EttleAssign { ettle_id, .. } => ("ettle_assign", Some(ettle_id)),
+        Command::ProfileCreate { profile_ref, .. } => ("profile_create", Some(profile_ref)),
+        Command::ProfileSetDefault { profile_ref, .. } => {
+            ("profile_set_default", Some(profile_ref))
+        }
+        Command::ApprovalTransition { approval_token, .. } => {
+            ("approval_transition", Some(approval_token))
+        }
+        Command::PolicyCreate { policy_ref, .. } => ("policy_create", Some(policy_ref)),
+        Command::RelationCreate {
+            source_ettle_id, ..
+        } => ("relation_create", Some(source_ettle_id)),
+        Command::RelationUpdate { relation_id, .. } => ("relation_update", Some(relation_id)),
+        Command::RelationGet { relation_id } => ("relation_get", Some(relation_id)),
+        Command::RelationList { .. } => ("relation_list", None),
+        Command::RelationTombstone { relation_id } => ("relation_tombstone", Some(relation_id)),
+        Command::RelationRestore { relation_id } => ("relation_restore", Some(relation_id)),
+        Command::ConstraintAttachBulk {
+            constraint_ettle_id,
+            ..
+        } => ("constraint_attach_bulk", Some(constraint_ettle_id)),
+        Command::ConstraintSweepOrphans => ("constraint_sweep_orphans", None),
+        Command::ContentRefRename { .. } => ("content_ref_rename", None),
+        Command::GroupCreate { .. } => ("group_create", None),
+        Command::GroupGet { group_id } => ("group_get", Some(group_id)),
+        Command::GroupList { .. } => ("group_list", None),
+        Command::GroupTombstone { group_id } => ("group_tombstone", Some(group_id)),
+        Command::GroupRestore { group_id } => ("group_restore", Some(group_id)),
+        Command::GroupMemberAdd { group_id, .. } => ("group_member_add", Some(group_id)),
+        Command::GroupMemberRemove { group_id, .. } => ("group_member_remove", Some(group_id)),
+        Command::GroupMemberList { group_id, .. } => ("group_member_list", Some(group_id)),
+        Command::DecisionCreate { .. } => ("decision_create", None),
+        Command::DecisionRestore { decision_id } => ("decision_restore", Some(decision_id)),
+        Command::CommentAdd { target_id, .. } => ("comment_add", Some(target_id)),
+        Command::CommentResolve { comment_id } => ("comment_resolve", Some(comment_id)),
+        Command::CommentReopen { comment_id } => ("comment_reopen", Some(comment_id)),
+        Command::RootRegister { ettle_id, .. } => ("root_register", Some(ettle_id)),
+        Command::RootUpdate { ettle_id, .. } => ("root_update", Some(ettle_id)),
+        Command::ApprovalPayloadSweep { .. } => ("approval_payload_sweep", None),
+        Command::ApiTokenIssue { .. } => ("api_token_issue", None),
+        Command::ApiTokenRevoke { token_id } => ("api_token_revoke", Some(token_id)),
+        Command::FeatureSet { name, .. } => ("feature_set", Some(name)),
+        Command::EvidenceSweep { .. } => ("evidence_sweep", None),
+        Command::ImportSessionRollback { session_id } => {
+            ("import_session_rollback", Some(session_id))
+        }
+        Command::Batch { .. } => ("batch", None),
+    }
+}
+
+/// ID of the entity a command created, which its request could not name.
+fn created_entity_id(result: &CommandResult) -> Option<&str> {
+    match result {
+        CommandResult::SnapshotCommit { snapshot_id, .. } => Some(snapshot_id),
+        CommandResult::RoutedForApproval { approval_token } => Some(approval_token),
+        CommandResult::EttleCreate { ettle_id } => Some(ettle_id),
+        CommandResult::RelationCreate { relation_id } => Some(relation_id),
+        CommandResult::GroupCreate { group_id } => Some(group_id),
+        CommandResult::DecisionCreate { decision_id } => Some(decision_id),
+        CommandResult::CommentAdd { comment_id } => Some(comment_id),
+        CommandResult::ApiTokenIssue { token_id, .. } => Some(token_id),
+        _ => None,
+    }
+}
+
+/// Apply `cmds` in order as one all-or-nothing unit.
+///
+/// The OCC check against `expected_state_version` happens once, before the
+/// first command. Each command is then applied as by [`apply_command`] —
+/// with its own provenance event and `command_log` row — inside a single
+/// savepoint. If any command fails, every write made by the batch is rolled
+/// back and that command's error is returned with its index prepended to
+/// the message.
+///
+/// Returns the results in command order and the state version after the
+/// last command.
+///
+/// # Errors
+///
+/// `InvalidInput` for an empty batch or one containing `Batch`,
+/// `SnapshotCommit`, `ApprovalTransition`, `PolicyCreate`, `ApiTokenIssue` or
+/// `ApiTokenRevoke`; `HeadMismatch` for a stale
+/// `expected_state_version`; otherwise the first failing command's error.
+pub fn apply_commands_atomic(
+    cmds: Vec<Command>,
+    expected_state_version: Option<u64>,
+    conn: &mut Connection,
+    cas: &FsStore,
+    policy_provider: &dyn PolicyProvider,
+    approval_router: &dyn ApprovalRouter,
+) -> Result<(Vec<CommandResult>, u64)> {
+    let invalid = |message: String| {
+        ExError::new(ExErrorKind::InvalidInput)
+            .with_op("apply_commands_atomic")
+            .with_message(message)
+    };
+    if cmds.is_empty() {
+        return Err(invalid("batch must contain at least one command".into()));
+    }
+    for (i, cmd) in cmds.iter().enumerate() {
+        // Snapshot commits write CAS blobs and policy creation writes a
+        // policy file, neither of which a rollback can undo; approval
+        // decisions and API token commands need a scope the rest of the
+        // batch does not.
+        if matches!(
+            cmd,
+            Command::Batch { .. }
+                | Command::SnapshotCommit { .. }
+                | Command::ApprovalTransition { .. }
+                | Command::PolicyCreate { .. }
+                | Command::ApiTokenIssue { .. }
+                | Command::ApiTokenRevoke { .. }
+        ) {
+            return Err(invalid(format!(
+                "batch command {}: {} is not allowed in a batch",
+                i,
+                command_op(cmd).0
+            )));
+        }
+    }
+
+    let persistence = |e: rusqlite::Error| {
+        ExError::new(ExErrorKind::Persistence)
+            .with_op("apply_commands_atomic")
+            .with_message(e.to_string())
+    };
+    conn.execute_batch("SAVEPOINT command_batch")
+        .map_err(persistence)?;
+
+    let mut results = Vec::with_capacity(cmds.len());
+    let mut state_version = 0;
+    let mut expected = expected_state_version;
+    for (i, cmd) in cmds.into_iter().enumerate() {
+        match apply_command(
+            cmd,
+            expected.take(),
+            conn,
+            cas,
+            policy_provider,
+            approval_router,
+        ) {
+            Ok((result, sv)) => {
+                results.push(result);
+                state_version = sv;
+            }
+            Err(e) => {
+                conn.execute_batch("ROLLBACK TO command_batch; RELEASE command_batch")
+                    .map_err(persistence)?;
+                let message = format!("batch command {}: {}", i, e.message());
+                return Err(e.with_message(message));
+            }
+        }
+    }
+
+    conn.execute_batch("RELEASE command_batch")
+        .map_err(persistence)?;
+    Ok((results, state_version))
+}
+
+/// Split an [`apply_commands_atomic`] error into the index of the failing
+/// command and that command's own error (its `batch command <i>: ` message
+/// prefix removed). Errors raised before any command ran have no index.
+pub(crate) fn split_batch_error(e: ExError) -> (Option<usize>, ExError) {
+    let parsed = e
+        .message()
+        .strip_prefix("batch command ")
+        .and_then(|rest| rest.split_once(": "))
+        .and_then(|(index, message)| Some((index.parse().ok()?, message.to_string())));
+    match parsed {
+        Some((index, message)) => (Some(index), e.with_message(message)),
+        None => (None, e),
+    }
+}
+
+/// Apply a command as [`apply_command`] does, without recording it in the
+/// undo/redo journal. Used to replay journal steps.
+pub(crate) fn apply_command_unjournaled(
+    cmd: Command,
+    conn: &mut Connection,
+    cas: &FsStore,
+    policy_provider: &dyn PolicyProvider,
+    approval_router: &dyn ApprovalRouter,
+) -> Result<(CommandResult, u64)> {
+    run_command(
+        cmd,
+        None,
+        conn,
+        cas,
+        policy_provider,
+        approval_router,
+        false,
+    )
+}
+
+/// Run [`run_command_steps`] as one unit: every write it makes — the
+/// mutation, journal entry, provenance event and `command_log` row — is
+/// kept or discarded together.
+///
+/// At the top level this is a `BEGIN IMMEDIATE` transaction, so the write
+/// lock is taken before `state_version` is read and concurrent writers on
+/// other connections serialize on it. Inside an open transaction (a batch,
+/// dry run or journal replay) it is a savepoint.
+fn run_command(
+    cmd: Command,
+    expected_state_version: Option<u64>,
+    conn: &mut Connection,
+    cas: &FsStore,
+    policy_provider: &dyn PolicyProvider,
+    approval_router: &dyn ApprovalRouter,
+    journal: bool,
+) -> Result<(CommandResult, u64)> {
+    let persistence = |e: rusqlite::Error| {
+        ExError::new(ExErrorKind::Persistence)
+            .with_op("apply_command")
+            .with_message(e.to_string())
+    };
+    let top_level = conn.is_autocommit();
+    if top_level {
+        conn.execute_batch("BEGIN IMMEDIATE").map_err(|e| match e {
+            rusqlite::Error::SqliteFailure(ref f, _)
+                if f.code == rusqlite::ErrorCode::DatabaseBusy =>
+            {
+                ExError::new(ExErrorKind::Concurrency)
+                    .with_op("apply_command")
+                    .with_message(format!("Database is locked by another writer: {}", e))
+            }
+            _ => persistence(e),
+        })?;
+    } else {
+        conn.execute_batch("SAVEPOINT apply_command")
+            .map_err(persistence)?;
+    }
+
+    let result = run_command_steps(
+        cmd,
+        expected_state_version,
+        conn,
+        cas,
+        policy_provider,
+        approval_router,
+        journal,
+    );
+    let end = match (&result, top_level) {
+        (Ok(_), true) => "COMMIT",
+        (Ok(_), false) => "RELEASE apply_command",
+        (Err(_), true) => "ROLLBACK",
+        (Err(_), false) => "ROLLBACK TO apply_command; RELEASE apply_command",
+    };
+    conn.execute_batch(end).map_err(persistence)?;
+    result
+}
+
+fn run_command_steps(
+    cmd: Command,
+    expected_state_version: Option<u64>,
+    conn: &mut Connection,
+    cas: &FsStore,
+    policy_provider: &dyn PolicyProvider,
+    approval_router: &dyn ApprovalRouter,
+    journal: bool,
 ) -> Result<(CommandResult, u64)> {
     // 1. Read state_version
     let current_sv: u64 = conn
@@ -307,8 +940,19 @@ pub fn apply_command(
         }
     }
 
-    // 3. Dispatch
+    // 3. Dispatch, capturing what undo needs before the write
+    let cmd = resolve_command_ettle_refs(conn, cmd)?;
+    let (op, target) = command_op(&cmd);
+    let target = target.map(str::to_string);
+    let pending = if journal {
+        journal::prepare(conn, cas, &cmd)?
+    } else {
+        None
+    };
     let result = dispatch_command(cmd, conn, cas, policy_provider, approval_router)?;
+    if let Some(pending) = pending {
+        journal::record(conn, cas, pending, &result)?;
+    }
 
     // 4. Append provenance event for successful mutations
     let prov_kind: Option<(&str, String)> = match &result {
@@ -317,6 +961,17 @@ pub fn apply_command(
         CommandResult::EttleTombstone => {
             Some(("ettle_tombstoned", uuid::Uuid::now_v7().to_string()))
         }
+        CommandResult::EttleRestore => Some(("ettle_restored", uuid::Uuid::now_v7().to_string())),
+        CommandResult::EttleArchive { .. } => {
+            Some(("ettle_archived", uuid::Uuid::now_v7().to_string()))
+        }
+        CommandResult::EttleUnarchive { .. } => {
+            Some(("ettle_unarchived", uuid::Uuid::now_v7().to_string()))
+        }
+        CommandResult::EttleSetContentFormat => {
+            Some(("ettle_content_format_set", uuid::Uuid::now_v7().to_string()))
+        }
+        CommandResult::EttleAssign => Some(("ettle_assigned", uuid::Uuid::now_v7().to_string())),
         CommandResult::RelationCreate { relation_id } => {
             Some(("relation_created", relation_id.clone()))
         }
@@ -326,16 +981,64 @@ pub fn apply_command(
         CommandResult::RelationTombstone => {
             Some(("relation_tombstoned", uuid::Uuid::now_v7().to_string()))
         }
+        CommandResult::RelationRestore => {
+            Some(("relation_restored", uuid::Uuid::now_v7().to_string()))
+        }
+        CommandResult::ConstraintAttachBulk { .. } => {
+            Some(("constraint_attached_bulk", uuid::Uuid::now_v7().to_string()))
+        }
+        CommandResult::ConstraintSweepOrphans { .. } => {
+            Some(("constraint_orphans_swept", uuid::Uuid::now_v7().to_string()))
+        }
+        CommandResult::ContentRefRename { .. } => {
+            Some(("content_refs_renamed", uuid::Uuid::now_v7().to_string()))
+        }
         CommandResult::GroupCreate { group_id } => Some(("group_created", group_id.clone())),
         CommandResult::GroupTombstone => {
             Some(("group_tombstoned", uuid::Uuid::now_v7().to_string()))
         }
+        CommandResult::GroupRestore => Some(("group_restored", uuid::Uuid::now_v7().to_string())),
         CommandResult::GroupMemberAdd => {
             Some(("group_member_added", uuid::Uuid::now_v7().to_string()))
         }
         CommandResult::GroupMemberRemove => {
             Some(("group_member_removed", uuid::Uuid::now_v7().to_string()))
         }
+        CommandResult::ApprovalTransition { event_id, .. } => {
+            Some(("approval_transitioned", event_id.to_string()))
+        }
+        CommandResult::DecisionCreate { decision_id } => {
+            Some(("decision_created", decision_id.clone()))
+        }
+        CommandResult::DecisionRestore => {
+            Some(("decision_restored", uuid::Uuid::now_v7().to_string()))
+        }
+        CommandResult::CommentAdd { comment_id } => Some(("comment_added", comment_id.clone())),
+        CommandResult::CommentResolve => {
+            Some(("comment_resolved", uuid::Uuid::now_v7().to_string()))
+        }
+        CommandResult::CommentReopen => {
+            Some(("comment_reopened", uuid::Uuid::now_v7().to_string()))
+        }
+        CommandResult::RootRegister => Some(("root_registered", uuid::Uuid::now_v7().to_string())),
+        CommandResult::RootUpdate => Some(("root_updated", uuid::Uuid::now_v7().to_string())),
+        CommandResult::ApiTokenIssue { token_id, .. } => {
+            Some(("api_token_issued", token_id.clone()))
+        }
+        CommandResult::ApiTokenRevoke => {
+            Some(("api_token_revoked", uuid::Uuid::now_v7().to_string()))
+        }
+        CommandResult::FeatureSet => Some(("feature_set", uuid::Uuid::now_v7().to_string())),
+        CommandResult::ApprovalPayloadSweep { .. } => {
+            Some(("approval_payloads_swept", uuid::Uuid::now_v7().to_string()))
+        }
+        CommandResult::EvidenceSweep { .. } => {
+            Some(("evidence_swept", uuid::Uuid::now_v7().to_string()))
+        }
+        CommandResult::ImportSessionRollback { .. } => Some((
+            "import_session_rolled_back",
+            uuid::Uuid::now_v7().to_string(),
+        )),
         _ => None,
     };
 
@@ -352,21 +1055,90 @@ pub fn apply_command(
         })?;
     }
 
-    // 5. Insert log row with ISO-8601 timestamp
-    let now_iso = chrono::Utc::now().to_rfc3339();
-    conn.execute(
-        "INSERT INTO command_log (applied_at) VALUES (?1)",
-        rusqlite::params![now_iso],
-    )
-    .map_err(|e| {
-        ExError::new(ExErrorKind::Persistence)
-            .with_op("apply_command")
-            .with_message(format!("Failed to insert command_log row: {}", e))
-    })?;
+    // 5. Insert log row with ISO-8601 timestamp, naming the command and the
+    //    entity it targeted or created (see `event_history`)
+    let entity_id = created_entity_id(&result).map(str::to_string).or(target);
+    SqliteRepo::insert_command_log_entry(
+        conn,
+        &chrono::Utc::now().to_rfc3339(),
+        op,
+        entity_id.as_deref(),
+    )?;
 
     Ok((result, current_sv + 1))
 }
 
+/// Apply a command with per-call options.
+///
+/// With `dry_run`, the whole of [`apply_command`] — OCC check, validation,
+/// mutation, provenance and `command_log` — runs inside a savepoint that is
+/// rolled back. The returned state version is the one a real run would have
+/// produced; IDs in the result are placeholders that were never persisted.
+/// `SnapshotCommit` always takes its own dry-run path. CAS blob writes are not
+/// transactional and are not undone.
+///
+/// `PolicyCreate` is rejected with `InvalidInput` under `dry_run`: the policy
+/// provider writes outside the database and the write cannot be rolled back.
+pub fn apply_command_with_options(
+    cmd: Command,
+    expected_state_version: Option<u64>,
+    options: CommandOptions,
+    conn: &mut Connection,
+    cas: &FsStore,
+    policy_provider: &dyn PolicyProvider,
+    approval_router: &dyn ApprovalRouter,
+) -> Result<(CommandResult, u64)> {
+    if !options.dry_run {
+        return apply_command(
+            cmd,
+            expected_state_version,
+            conn,
+            cas,
+            policy_provider,
+            approval_router,
+        );
+    }
+    if matches!(cmd, Command::PolicyCreate { .. }) {
+        return Err(ExError::new(ExErrorKind::InvalidInput)
+            .with_op("apply_command")
+            .with_message(
+                "policy_create cannot be dry-run: the policy provider write is not transactional",
+            ));
+    }
+    let cmd = match cmd {
+        Command::SnapshotCommit {
+            leaf_ep_id,
+            policy_ref,
+            profile_ref,
+            expected_head,
+            id_scheme,
+            message,
+            annotations,
+            ..
+        } => Command::SnapshotCommit {
+            leaf_ep_id,
+            policy_ref,
+            profile_ref,
+            dry_run: true,
+            expected_head,
+            id_scheme,
+            message,
+            annotations,
+        },
+        other => other,
+    };
+    with_rollback(conn, |conn| {
+        apply_command(
+            cmd,
+            expected_state_version,
+            conn,
+            cas,
+            policy_provider,
+            approval_router,
+        )
+    })
+}
+
 // ---------------------------------------------------------------------------
 // Internal dispatch
 // ---------------------------------------------------------------------------
@@ -385,11 +1157,17 @@ fn dispatch_command(
             profile_ref,
             dry_run,
             expected_head,
+            id_scheme,
+            message,
+            annotations,
         } => {
             let options = SnapshotOptions {
                 expected_head,
                 dry_run,
-                allow_dedup: false,
+                allow_dedup: !dry_run && dedup_enabled(conn, None)?,
+                id_scheme,
+                message,
+                annotations,
             };
             let engine_cmd = EngineCommand::SnapshotCommit {
                 leaf_ep_id,
@@ -403,6 +1181,7 @@ fn dispatch_command(
                 EngineCommandResult::SnapshotCommit(r) => Ok(CommandResult::SnapshotCommit {
                     snapshot_id: r.snapshot_id,
                     manifest_digest: r.manifest_digest,
+                    constraints_summary: r.constraints_summary,
                 }),
                 EngineCommandResult::SnapshotCommitRouted(r) => {
                     Ok(CommandResult::RoutedForApproval {
@@ -432,6 +1211,7 @@ fn dispatch_command(
             }
             let new_id = handle_ettle_create(
                 conn,
+                cas,
                 &title,
                 why.as_deref(),
                 what.as_deref(),
@@ -458,6 +1238,7 @@ fn dispatch_command(
                 link_type_inner.as_ref().map(|v| v.as_deref());
             handle_ettle_update(
                 conn,
+                cas,
                 &ettle_id,
                 title.as_deref(),
                 why.as_deref(),
@@ -474,6 +1255,37 @@ fn dispatch_command(
             Ok(CommandResult::EttleTombstone)
         }
 
+        Command::EttleRestore { ettle_id } => {
+            handle_ettle_restore(conn, &ettle_id)?;
+            Ok(CommandResult::EttleRestore)
+        }
+
+        Command::EttleArchive { ettle_id, subtree } => {
+            let archived_ettle_ids = handle_ettle_archive(conn, &ettle_id, subtree)?;
+            Ok(CommandResult::EttleArchive { archived_ettle_ids })
+        }
+
+        Command::EttleUnarchive { ettle_id, subtree } => {
+            let unarchived_ettle_ids = handle_ettle_unarchive(conn, &ettle_id, subtree)?;
+            Ok(CommandResult::EttleUnarchive {
+                unarchived_ettle_ids,
+            })
+        }
+
+        Command::EttleSetContentFormat {
+            ettle_id,
+            content_format,
+        } => {
+            handle_ettle_set_content_format(conn, &ettle_id, &content_format)?;
+            Ok(CommandResult::EttleSetContentFormat)
+        }
+
+        Command::EttleAssign {
+            ettle_id,
+            owner,
+            reviewers,
+        } => handle_ettle_assign(conn, ettle_id, owner, reviewers),
+
         Command::ProfileCreate {
             profile_ref,
             payload_json,
@@ -488,12 +1300,34 @@ fn dispatch_command(
             Ok(CommandResult::ProfileCreate)
         }
 
-        Command::ProfileSetDefault { profile_ref } => {
-            let engine_cmd = EngineCommand::ProfileSetDefault { profile_ref };
+        Command::ProfileSetDefault {
+            profile_ref,
+            environment,
+        } => {
+            let engine_cmd = EngineCommand::ProfileSetDefault {
+                profile_ref,
+                environment,
+            };
             apply_engine_command(engine_cmd, conn, cas, policy_provider, approval_router)?;
             Ok(CommandResult::ProfileSetDefault)
         }
 
+        Command::ApprovalTransition {
+            approval_token,
+            status,
+            actor,
+        } => {
+            let op = "approval_transition";
+            let approval_token = validate_id(op, "approval_token", &approval_token)?;
+            let event =
+                transition_approval(conn, &approval_token, status.trim(), actor.as_deref())?;
+            Ok(CommandResult::ApprovalTransition {
+                event_id: event.id,
+                from_status: event.from_status.unwrap_or_default(),
+                to_status: event.to_status,
+            })
+        }
+
         Command::PolicyCreate { policy_ref, text } => {
             if policy_ref.is_empty() {
                 return Err(ExError::new(ExErrorKind::InvalidInput)
@@ -553,6 +1387,23 @@ fn dispatch_command(
 
         Command::RelationTombstone { relation_id } => handle_relation_tombstone(conn, relation_id),
 
+        Command::RelationRestore { relation_id } => handle_relation_restore(conn, relation_id),
+
+        Command::ConstraintAttachBulk {
+            constraint_ettle_id,
+            selector,
+            properties_json,
+        } => handle_constraint_attach_bulk(conn, constraint_ettle_id, selector, properties_json),
+
+        Command::ConstraintSweepOrphans => handle_constraint_sweep_orphans(conn),
+
+        Command::ContentRefRename {
+            from,
+            to,
+            selector,
+            only,
+        } => handle_content_ref_rename(conn, cas, from, to, selector, only),
+
         Command::GroupCreate { name } => handle_group_create(conn, name),
 
         Command::GroupGet { group_id } => handle_group_get(conn, group_id),
@@ -561,6 +1412,8 @@ fn dispatch_command(
 
         Command::GroupTombstone { group_id } => handle_group_tombstone(conn, group_id),
 
+        Command::GroupRestore { group_id } => handle_group_restore(conn, group_id),
+
         Command::GroupMemberAdd { group_id, ettle_id } => {
             handle_group_member_add(conn, group_id, ettle_id)
         }
@@ -573,5 +1426,187 @@ fn dispatch_command(
             group_id,
             include_tombstoned,
         } => handle_group_member_list(conn, group_id, include_tombstoned),
+
+        Command::DecisionCreate {
+            title,
+            status,
+            decision_text,
+            rationale,
+            alternatives_text,
+            consequences_text,
+            evidence_kind,
+            evidence_excerpt,
+            evidence_capture_content,
+            evidence_file_path,
+        } => {
+            let decision_id = decision_create(
+                None,
+                title,
+                status,
+                decision_text,
+                rationale,
+                alternatives_text,
+                consequences_text,
+                evidence_kind,
+                evidence_excerpt,
+                evidence_capture_content,
+                evidence_file_path,
+                conn,
+            )?;
+            Ok(CommandResult::DecisionCreate { decision_id })
+        }
+
+        Command::DecisionRestore { decision_id } => {
+            decision_restore(decision_id, conn)?;
+            Ok(CommandResult::DecisionRestore)
+        }
+
+        Command::CommentAdd {
+            target_kind,
+            target_id,
+            body,
+            author,
+            parent_comment_id,
+        } => handle_comment_add(
+            conn,
+            target_kind,
+            target_id,
+            body,
+            author,
+            parent_comment_id,
+        ),
+
+        Command::CommentResolve { comment_id } => handle_comment_resolve(conn, comment_id),
+
+        Command::CommentReopen { comment_id } => handle_comment_reopen(conn, comment_id),
+
+        Command::RootRegister {
+            ettle_id,
+            display_name,
+            owner,
+        } => handle_root_register(conn, ettle_id, display_name, owner),
+
+        Command::RootUpdate {
+            ettle_id,
+            display_name,
+            owner,
+            status,
+        } => handle_root_update(conn, ettle_id, display_name, owner, status),
+
+        Command::ApprovalPayloadSweep { retention_days } => {
+            handle_approval_payload_sweep(conn, cas, retention_days).map(|report| {
+                CommandResult::ApprovalPayloadSweep {
+                    swept: report.swept,
+                    blobs_deleted: report.blobs_deleted,
+                    bytes_freed: report.bytes_freed,
+                }
+            })
+        }
+
+        Command::ApiTokenIssue { name, scopes } => handle_api_token_issue(conn, &name, &scopes)
+            .map(|issued| CommandResult::ApiTokenIssue {
+                token_id: issued.record.token_id,
+                scopes: issued.record.scopes,
+                secret: issued.secret,
+            }),
+        Command::ApiTokenRevoke { token_id } => {
+            handle_api_token_revoke(conn, &token_id).map(|()| CommandResult::ApiTokenRevoke)
+        }
+
+        Command::FeatureSet { name, enabled } => {
+            handle_feature_set(conn, &name, enabled).map(|()| CommandResult::FeatureSet)
+        }
+
+        Command::EvidenceSweep { retention_days } => handle_evidence_sweep(conn, retention_days)
+            .map(|report| CommandResult::EvidenceSweep {
+                pruned: report.pruned,
+                bytes_freed: report.bytes_freed,
+            }),
+
+        Command::ImportSessionRollback { session_id } => {
+            handle_import_session_rollback(conn, &session_id).map(|removed| {
+                CommandResult::ImportSessionRollback {
+                    relations: removed.relations,
+                    ettles: removed.ettles,
+                }
+            })
+        }
+
+        // Intercepted by `apply_command`; only reachable if that changes.
+        Command::Batch { .. } => Err(ExError::new(ExErrorKind::InvalidInput)
+            .with_op("apply_command")
+            .with_message("Batch commands are applied by apply_commands_atomic")),
+    }
+}
+
+/// Rewrite Ettle slugs in a command's Ettle-reference fields to canonical IDs.
+fn resolve_command_ettle_refs(conn: &Connection, mut cmd: Command) -> Result<Command> {
+    let resolve = |r: &mut String| -> Result<()> {
+        *r = resolve_ettle_ref(conn, r)?;
+        Ok(())
+    };
+    match &mut cmd {
+        Command::EttleCreate {
+            reasoning_link_id: Some(link),
+            ..
+        } => resolve(link)?,
+        Command::EttleUpdate {
+            ettle_id,
+            reasoning_link_id,
+            ..
+        } => {
+            resolve(ettle_id)?;
+            if let Some(Some(link)) = reasoning_link_id {
+                resolve(link)?;
+            }
+        }
+        Command::EttleTombstone { ettle_id }
+        | Command::EttleRestore { ettle_id }
+        | Command::EttleArchive { ettle_id, .. }
+        | Command::EttleUnarchive { ettle_id, .. }
+        | Command::EttleSetContentFormat { ettle_id, .. }
+        | Command::EttleAssign { ettle_id, .. }
+        | Command::GroupMemberAdd { ettle_id, .. }
+        | Command::GroupMemberRemove { ettle_id, .. }
+        | Command::RootRegister { ettle_id, .. }
+        | Command::RootUpdate { ettle_id, .. } => resolve(ettle_id)?,
+        Command::RelationCreate {
+            source_ettle_id,
+            target_ettle_id,
+            ..
+        } => {
+            resolve(source_ettle_id)?;
+            resolve(target_ettle_id)?;
+        }
+        Command::RelationList {
+            source_ettle_id,
+            target_ettle_id,
+            ..
+        } => {
+            for r in [source_ettle_id, target_ettle_id].into_iter().flatten() {
+                resolve(r)?;
+            }
+        }
+        Command::ConstraintAttachBulk {
+            constraint_ettle_id,
+            selector,
+            ..
+        } => {
+            resolve(constraint_ettle_id)?;
+            if let TargetSelector::Subtree { root_ettle_id } = selector {
+                resolve(root_ettle_id)?;
+            }
+        }
+        Command::ContentRefRename {
+            selector: Some(TargetSelector::Subtree { root_ettle_id }),
+            ..
+        } => resolve(root_ettle_id)?,
+        Command::CommentAdd {
+            target_kind,
+            target_id,
+            ..
+        } if target_kind == "ettle" => resolve(target_id)?,
+        _ => {}
     }
+    Ok(cmd)
 }
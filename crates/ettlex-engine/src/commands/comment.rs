@@ -0,0 +1,149 @@
+//! Engine handler for comment threads.
+//!
+//! Comments attach to Ettles, decisions and snapshots. A thread is a root
+//! comment plus its replies; only roots carry a resolution status. This module
+//! enforces thread invariants and delegates persistence to `SqliteRepo`. Like
+//! the other handlers it MUST NOT touch `command_log` or `provenance_events`.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_store::model::CommentRecord;
+use ettlex_store::repo::SqliteRepo;
+use rusqlite::Connection;
+
+use super::command::CommandResult;
+
+type Result<T> = std::result::Result<T, ExError>;
+
+// ---------------------------------------------------------------------------
+// handle_comment_add
+// ---------------------------------------------------------------------------
+
+/// Add a comment, either starting a thread or replying to one.
+///
+/// Invariants enforced:
+/// - Body must be non-empty (`InvalidInput`).
+/// - `target_kind` must be `ettle`, `decision` or `snapshot` (`InvalidTargetKind`)
+///   and the target must exist (`NotFound`).
+/// - A reply's parent must exist (`NotFound`), be a thread root and share the
+///   reply's target (`InvalidInput`).
+pub fn handle_comment_add(
+    conn: &mut Connection,
+    target_kind: String,
+    target_id: String,
+    body: String,
+    author: Option<String>,
+    parent_comment_id: Option<String>,
+) -> Result<CommandResult> {
+    if body.trim().is_empty() {
+        return Err(ExError::new(ExErrorKind::InvalidInput)
+            .with_op("comment_add")
+            .with_message("comment body must not be empty"));
+    }
+
+    if !SqliteRepo::comment_target_exists(conn, &target_kind, &target_id)? {
+        return Err(ExError::new(ExErrorKind::NotFound)
+            .with_op("comment_add")
+            .with_entity_id(&target_id)
+            .with_message(format!(
+                "comment target not found: {} {}",
+                target_kind, target_id
+            )));
+    }
+
+    if let Some(parent_id) = parent_comment_id.as_deref() {
+        let parent = get_comment(conn, parent_id, "comment_add")?;
+        if parent.parent_comment_id.is_some() {
+            return Err(ExError::new(ExErrorKind::InvalidInput)
+                .with_op("comment_add")
+                .with_entity_id(parent_id)
+                .with_message("replies must target the thread's root comment"));
+        }
+        if parent.target_kind != target_kind || parent.target_id != target_id {
+            return Err(ExError::new(ExErrorKind::InvalidInput)
+                .with_op("comment_add")
+                .with_entity_id(parent_id)
+                .with_message("reply target does not match the thread's target"));
+        }
+    }
+
+    let id = format!("cmt:{}", uuid::Uuid::now_v7());
+    let record = CommentRecord {
+        id: id.clone(),
+        target_kind,
+        target_id,
+        parent_comment_id,
+        author,
+        body,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        resolved_at_ms: None,
+    };
+    SqliteRepo::insert_comment(conn, &record)?;
+
+    Ok(CommandResult::CommentAdd { comment_id: id })
+}
+
+// ---------------------------------------------------------------------------
+// handle_comment_resolve / handle_comment_reopen
+// ---------------------------------------------------------------------------
+
+/// Mark a thread resolved.
+///
+/// Invariants enforced:
+/// - Comment must exist (`NotFound`) and be a thread root (`InvalidInput`).
+/// - Thread must not already be resolved (`InvalidInput`).
+pub fn handle_comment_resolve(conn: &mut Connection, comment_id: String) -> Result<CommandResult> {
+    let root = get_thread_root(conn, &comment_id, "comment_resolve")?;
+    if root.resolved_at_ms.is_some() {
+        return Err(ExError::new(ExErrorKind::InvalidInput)
+            .with_op("comment_resolve")
+            .with_entity_id(&comment_id)
+            .with_message(format!("Thread is already resolved: {}", comment_id)));
+    }
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    SqliteRepo::set_comment_resolved(conn, &comment_id, Some(now_ms))?;
+    Ok(CommandResult::CommentResolve)
+}
+
+/// Reopen a resolved thread.
+///
+/// Invariants enforced:
+/// - Comment must exist (`NotFound`) and be a thread root (`InvalidInput`).
+/// - Thread must currently be resolved (`InvalidInput`).
+pub fn handle_comment_reopen(conn: &mut Connection, comment_id: String) -> Result<CommandResult> {
+    let root = get_thread_root(conn, &comment_id, "comment_reopen")?;
+    if root.resolved_at_ms.is_none() {
+        return Err(ExError::new(ExErrorKind::InvalidInput)
+            .with_op("comment_reopen")
+            .with_entity_id(&comment_id)
+            .with_message(format!("Thread is not resolved: {}", comment_id)));
+    }
+    SqliteRepo::set_comment_resolved(conn, &comment_id, None)?;
+    Ok(CommandResult::CommentReopen)
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Fetch a comment by ID, mapping absence to `NotFound`.
+pub(crate) fn get_comment(conn: &Connection, comment_id: &str, op: &str) -> Result<CommentRecord> {
+    SqliteRepo::get_comment(conn, comment_id)?.ok_or_else(|| {
+        ExError::new(ExErrorKind::NotFound)
+            .with_op(op)
+            .with_entity_id(comment_id)
+            .with_message(format!("Comment not found: {}", comment_id))
+    })
+}
+
+fn get_thread_root(conn: &Connection, comment_id: &str, op: &str) -> Result<CommentRecord> {
+    let record = get_comment(conn, comment_id, op)?;
+    if record.parent_comment_id.is_some() {
+        return Err(ExError::new(ExErrorKind::InvalidInput)
+            .with_op(op)
+            .with_entity_id(comment_id)
+            .with_message("only a thread's root comment carries resolution status"));
+    }
+    Ok(record)
+}
@@ -0,0 +1,253 @@
+//! Queued snapshot commit mode.
+//!
+//! Instead of committing directly, a client can enqueue a `SnapshotCommit`
+//! request and get a ticket back. [`drain_commit_queue`] applies queued
+//! requests one at a time, oldest first, through the same path as
+//! `EngineCommand::SnapshotCommit`. Every ticket's outcome is then
+//! recorded, so clients poll the ticket instead of retrying against each
+//! other.
+//!
+//! The queue lives in the `snapshot_commit_queue` table, so several processes
+//! can enqueue to and drain the same store. A worker claims a ticket
+//! (`queued` → `applying`) before applying it, and a ticket claimed by
+//! another worker is skipped. Because requests run serially, a request's
+//! `expected_head` is compared with the head left by the request before it,
+//! so `HeadMismatch` outcomes follow queue order.
+
+#![allow(clippy::result_large_err)]
+
+use std::collections::BTreeMap;
+
+use ettlex_core::approval_router::ApprovalRouter;
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::policy_provider::PolicyProvider;
+use ettlex_store::cas::FsStore;
+use ettlex_store::errors::Result;
+use ettlex_store::model::{
+    CommitTicketRecord, COMMIT_TICKET_APPLYING, COMMIT_TICKET_COMMITTED, COMMIT_TICKET_FAILED,
+    COMMIT_TICKET_QUEUED, COMMIT_TICKET_ROUTED,
+};
+use ettlex_store::repo::SqliteRepo;
+use ettlex_store::snapshot::SnapshotIdScheme;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use super::engine_command::{apply_engine_command, EngineCommand, EngineCommandResult};
+use crate::snapshot::SnapshotOptions;
+
+/// State of a queued commit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum CommitTicketOutcome {
+    /// Waiting for the worker behind `ahead` other queued tickets.
+    Queued { ahead: u64 },
+    /// Claimed by a worker. A ticket left here after its worker exited was
+    /// interrupted mid-commit; check the ledger for its snapshot.
+    Applying,
+    /// The commit wrote a snapshot, or matched an existing one.
+    Committed {
+        snapshot_id: String,
+        manifest_digest: String,
+        was_duplicate: bool,
+    },
+    /// The commit was routed for approval.
+    Routed { approval_token: String },
+    /// The commit failed; `error_code` is the `ERR_*` code.
+    Failed { error_code: String, message: String },
+}
+
+/// A queued commit and its current state.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CommitTicket {
+    pub ticket: String,
+    /// Queue position; lower positions are applied first.
+    pub seq: i64,
+    pub leaf_ettle_id: String,
+    /// When the ticket was queued, milliseconds since epoch.
+    pub enqueued_at_ms: i64,
+    /// When the outcome was recorded, milliseconds since epoch.
+    pub applied_at_ms: Option<i64>,
+    #[serde(flatten)]
+    pub outcome: CommitTicketOutcome,
+}
+
+/// Commit arguments as stored in `request_json`.
+#[derive(Debug, Serialize, Deserialize)]
+struct QueuedCommitRequest {
+    policy_ref: Option<String>,
+    profile_ref: Option<String>,
+    expected_head: Option<String>,
+    allow_dedup: bool,
+    id_scheme: SnapshotIdScheme,
+    message: Option<String>,
+    annotations: BTreeMap<String, String>,
+}
+
+fn internal(op: &str, e: serde_json::Error) -> ExError {
+    ExError::new(ExErrorKind::Internal)
+        .with_op(op)
+        .with_message(e.to_string())
+}
+
+/// Append a commit request to the queue. The arguments are validated as
+/// for `SnapshotCommit` by the caller.
+pub(crate) fn enqueue_commit(
+    conn: &Connection,
+    leaf_ep_id: String,
+    policy_ref: Option<String>,
+    profile_ref: Option<String>,
+    options: SnapshotOptions,
+) -> Result<CommitTicket> {
+    let op = "snapshot_commit_enqueue";
+    let request = QueuedCommitRequest {
+        policy_ref,
+        profile_ref,
+        expected_head: options.expected_head,
+        allow_dedup: options.allow_dedup,
+        id_scheme: options.id_scheme,
+        message: options.message,
+        annotations: options.annotations,
+    };
+    let mut record = CommitTicketRecord {
+        seq: 0,
+        ticket: format!("ctk:{}", uuid::Uuid::now_v7()),
+        leaf_ettle_id: leaf_ep_id,
+        request_json: serde_json::to_string(&request).map_err(|e| internal(op, e))?,
+        status: COMMIT_TICKET_QUEUED.to_string(),
+        enqueued_at_ms: chrono::Utc::now().timestamp_millis(),
+        applied_at_ms: None,
+        outcome_json: None,
+    };
+    record.seq = SqliteRepo::insert_commit_ticket(conn, &record)?;
+    ticket_from_record(conn, record)
+}
+
+/// Get a ticket's current state.
+///
+/// # Errors
+/// * `NotFound` - No ticket has that ID
+/// * `Persistence` - A SQLite read fails
+pub(crate) fn get_commit_ticket(conn: &Connection, ticket: &str) -> Result<CommitTicket> {
+    let record = SqliteRepo::get_commit_ticket(conn, ticket)?.ok_or_else(|| {
+        ExError::new(ExErrorKind::NotFound)
+            .with_op("snapshot_commit_ticket")
+            .with_entity_id(ticket)
+            .with_message("no such commit ticket")
+    })?;
+    ticket_from_record(conn, record)
+}
+
+/// Apply up to `limit` queued commits (all of them with `None`), oldest
+/// first, and return the resolved tickets in the order they were applied.
+///
+/// A commit that fails resolves its ticket as `failed`; the drain carries on
+/// with the next ticket.
+///
+/// # Errors
+/// * `Persistence` - Claiming a ticket or recording an outcome fails. The
+///   ticket being applied stays `applying`.
+pub(crate) fn drain_commit_queue(
+    conn: &mut Connection,
+    cas: &FsStore,
+    policy_provider: &dyn PolicyProvider,
+    approval_router: &dyn ApprovalRouter,
+    limit: Option<usize>,
+) -> Result<Vec<CommitTicket>> {
+    let op = "snapshot_commit_queue_drain";
+    let mut resolved = Vec::new();
+    while limit.map_or(true, |n| resolved.len() < n) {
+        let Some(mut record) = SqliteRepo::next_queued_commit_ticket(conn)? else {
+            break;
+        };
+        if !SqliteRepo::claim_commit_ticket(conn, &record.ticket)? {
+            continue;
+        }
+
+        let request: QueuedCommitRequest =
+            serde_json::from_str(&record.request_json).map_err(|e| internal(op, e))?;
+        let cmd = EngineCommand::SnapshotCommit {
+            leaf_ep_id: record.leaf_ettle_id.clone(),
+            policy_ref: request.policy_ref,
+            profile_ref: request.profile_ref,
+            options: SnapshotOptions {
+                expected_head: request.expected_head,
+                dry_run: false,
+                allow_dedup: request.allow_dedup,
+                id_scheme: request.id_scheme,
+                message: request.message,
+                annotations: request.annotations,
+            },
+        };
+        let (status, outcome) =
+            match apply_engine_command(cmd, conn, cas, policy_provider, approval_router) {
+                Ok(EngineCommandResult::SnapshotCommit(r)) => (
+                    COMMIT_TICKET_COMMITTED,
+                    CommitTicketOutcome::Committed {
+                        snapshot_id: r.snapshot_id,
+                        manifest_digest: r.manifest_digest,
+                        was_duplicate: r.was_duplicate,
+                    },
+                ),
+                Ok(EngineCommandResult::SnapshotCommitRouted(r)) => (
+                    COMMIT_TICKET_ROUTED,
+                    CommitTicketOutcome::Routed {
+                        approval_token: r.approval_token,
+                    },
+                ),
+                Ok(_) => (
+                    COMMIT_TICKET_FAILED,
+                    CommitTicketOutcome::Failed {
+                        error_code: ExErrorKind::Internal.code().to_string(),
+                        message: "unexpected snapshot commit result".to_string(),
+                    },
+                ),
+                Err(e) => (
+                    COMMIT_TICKET_FAILED,
+                    CommitTicketOutcome::Failed {
+                        error_code: e.code().to_string(),
+                        message: e.message().to_string(),
+                    },
+                ),
+            };
+
+        let applied_at_ms = chrono::Utc::now().timestamp_millis();
+        let outcome_json = serde_json::to_string(&outcome).map_err(|e| internal(op, e))?;
+        SqliteRepo::finish_commit_ticket(
+            conn,
+            &record.ticket,
+            status,
+            &outcome_json,
+            applied_at_ms,
+        )?;
+        record.status = status.to_string();
+        record.applied_at_ms = Some(applied_at_ms);
+        record.outcome_json = Some(outcome_json);
+        resolved.push(ticket_from_record(conn, record)?);
+    }
+    Ok(resolved)
+}
+
+fn ticket_from_record(conn: &Connection, record: CommitTicketRecord) -> Result<CommitTicket> {
+    let outcome = match (record.status.as_str(), &record.outcome_json) {
+        (COMMIT_TICKET_QUEUED, _) => CommitTicketOutcome::Queued {
+            ahead: SqliteRepo::count_queued_commit_tickets_before(conn, record.seq)?,
+        },
+        (COMMIT_TICKET_APPLYING, _) => CommitTicketOutcome::Applying,
+        (_, Some(json)) => serde_json::from_str(json)
+            .map_err(|e| internal("snapshot_commit_ticket", e).with_entity_id(&record.ticket))?,
+        (status, None) => {
+            return Err(ExError::new(ExErrorKind::Internal)
+                .with_op("snapshot_commit_ticket")
+                .with_entity_id(&record.ticket)
+                .with_message(format!("ticket is '{}' but has no outcome", status)))
+        }
+    };
+    Ok(CommitTicket {
+        ticket: record.ticket,
+        seq: record.seq,
+        leaf_ettle_id: record.leaf_ettle_id,
+        enqueued_at_ms: record.enqueued_at_ms,
+        applied_at_ms: record.applied_at_ms,
+        outcome,
+    })
+}
@@ -0,0 +1,539 @@
+//! Engine handlers for bulk constraint attachment, attachment listing,
+//! effective-constraint resolution and the orphan attachment sweeper.
+//!
+//! A constraint is an Ettle attached to the Ettles it governs through
+//! `constraint` relations (source = constraint Ettle, target = governed
+//! Ettle). `ConstraintAttachBulk` resolves a [`TargetSelector`] to a set of
+//! target Ettles and creates one relation per target in a single
+//! transaction; `list_constraint_attachments` reports where a constraint is
+//! attached, and `effective_constraints` which constraints govern an Ettle
+//! once inheritance along its refinement path is applied.
+//!
+//! An attachment is *dangling* when it is active but either endpoint Ettle
+//! is missing or tombstoned. `EttleTombstone` detaches incoming attachments,
+//! so dangling rows only come from older data or out-of-band edits;
+//! `ConstraintSweepOrphans` tombstones them. All handlers delegate persistence to `SqliteRepo` and never writes
+//! raw SQL itself.
+
+#![allow(clippy::result_large_err)]
+
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_store::errors::from_rusqlite;
+use ettlex_store::model::{RelationListOpts, RelationRecord};
+use ettlex_store::repo::SqliteRepo;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use super::command::CommandResult;
+use super::read_tools::{
+    ConstraintAttachment, EffectiveConstraint, EffectiveConstraints, ExcludedConstraint,
+    ExclusionReason,
+};
+use super::relation::{is_cycle_check_enabled, would_create_cycle};
+
+type Result<T> = std::result::Result<T, ExError>;
+
+const CONSTRAINT_RELATION_TYPE: &str = "constraint";
+
+// ---------------------------------------------------------------------------
+// TargetSelector
+// ---------------------------------------------------------------------------
+
+/// Selects the Ettles a bulk constraint attachment applies to.
+///
+/// Serialised as a tagged JSON object: `{ "kind": "...", ...fields }`.
+/// Only active (non-tombstoned) Ettles are ever selected.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TargetSelector {
+    /// The root Ettle and every Ettle reachable from it via active
+    /// `refinement` relations.
+    Subtree { root_ettle_id: String },
+    /// All active members of a group.
+    Group { group_id: String },
+    /// All Ettles whose title contains `text` (case-insensitive).
+    TitleContains { text: String },
+}
+
+// ---------------------------------------------------------------------------
+// handle_constraint_attach_bulk
+// ---------------------------------------------------------------------------
+
+/// Attach a constraint Ettle to every Ettle matched by `selector`.
+///
+/// Targets are processed in ascending id order. Each new relation records an
+/// `ordinal` in its `properties_json`, continuing from the constraint's
+/// existing active attachment count. Targets already carrying an active
+/// attachment from this constraint are skipped, as is the constraint Ettle
+/// itself.
+///
+/// Invariants enforced:
+/// - `constraint_ettle_id` must exist (`NotFound`) and be active (`AlreadyTombstoned`).
+/// - The `constraint` relation type must be registered and active (`InvalidInput`).
+/// - Selector roots (subtree root, group) must exist and be active.
+/// - The selector must match at least one Ettle (`InvalidInput`).
+/// - `properties_json`, if supplied, must be a JSON object (`InvalidInput`).
+/// - No attachment may introduce a constraint cycle (`CycleDetected`); on
+///   failure nothing is written.
+pub fn handle_constraint_attach_bulk(
+    conn: &mut Connection,
+    constraint_ettle_id: String,
+    selector: TargetSelector,
+    properties_json: Option<JsonValue>,
+) -> Result<CommandResult> {
+    let constraint =
+        SqliteRepo::get_ettle_record(conn, &constraint_ettle_id)?.ok_or_else(|| {
+            ExError::new(ExErrorKind::NotFound)
+                .with_op("constraint_attach_bulk")
+                .with_entity_id(&constraint_ettle_id)
+                .with_message(format!(
+                    "Constraint ettle not found: {}",
+                    constraint_ettle_id
+                ))
+        })?;
+    if constraint.tombstoned_at.is_some() {
+        return Err(ExError::new(ExErrorKind::AlreadyTombstoned)
+            .with_op("constraint_attach_bulk")
+            .with_entity_id(&constraint_ettle_id)
+            .with_message(format!(
+                "Constraint ettle is tombstoned: {}",
+                constraint_ettle_id
+            )));
+    }
+
+    let type_entry = SqliteRepo::get_relation_type_entry(conn, CONSTRAINT_RELATION_TYPE)?
+        .filter(|e| e.tombstoned_at.is_none())
+        .ok_or_else(|| {
+            ExError::new(ExErrorKind::InvalidInput)
+                .with_op("constraint_attach_bulk")
+                .with_message("Relation type 'constraint' is not registered or is tombstoned")
+        })?;
+
+    let base_props = match properties_json {
+        None => serde_json::Map::new(),
+        Some(JsonValue::Object(map)) => map,
+        Some(_) => {
+            return Err(ExError::new(ExErrorKind::InvalidInput)
+                .with_op("constraint_attach_bulk")
+                .with_message("properties_json must be a JSON object"))
+        }
+    };
+
+    let mut targets = resolve_selector(conn, &selector)?;
+    targets.remove(&constraint_ettle_id);
+    if targets.is_empty() {
+        return Err(ExError::new(ExErrorKind::InvalidInput)
+            .with_op("constraint_attach_bulk")
+            .with_message("selector matched no ettles"));
+    }
+
+    let existing = SqliteRepo::list_relations(
+        conn,
+        &RelationListOpts {
+            source_ettle_id: Some(constraint_ettle_id.clone()),
+            target_ettle_id: None,
+            relation_type: Some(CONSTRAINT_RELATION_TYPE.to_string()),
+            include_tombstoned: false,
+        },
+    )?;
+    let already: HashSet<&str> = existing
+        .iter()
+        .map(|r| r.target_ettle_id.as_str())
+        .collect();
+
+    let cycle_check = is_cycle_check_enabled(&type_entry.properties_json);
+    let mut attached_ettle_ids = Vec::new();
+    let mut skipped_ettle_ids = Vec::new();
+    for target in targets {
+        if already.contains(target.as_str()) {
+            skipped_ettle_ids.push(target);
+            continue;
+        }
+        if cycle_check
+            && would_create_cycle(
+                conn,
+                &constraint_ettle_id,
+                &target,
+                CONSTRAINT_RELATION_TYPE,
+            )?
+        {
+            return Err(ExError::new(ExErrorKind::CycleDetected)
+                .with_op("constraint_attach_bulk")
+                .with_entity_id(&target)
+                .with_message(format!(
+                    "Attaching constraint to {} would introduce a cycle",
+                    target
+                )));
+        }
+        attached_ettle_ids.push(target);
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let tx = conn.savepoint().map_err(from_rusqlite)?;
+    let mut relation_ids = Vec::with_capacity(attached_ettle_ids.len());
+    for (i, target) in attached_ettle_ids.iter().enumerate() {
+        let mut props = base_props.clone();
+        props.insert(
+            "ordinal".to_string(),
+            JsonValue::from((existing.len() + i) as u64),
+        );
+        let id = format!("rel:{}", uuid::Uuid::now_v7());
+        let record = RelationRecord {
+            id: id.clone(),
+            source_ettle_id: constraint_ettle_id.clone(),
+            target_ettle_id: target.clone(),
+            relation_type: CONSTRAINT_RELATION_TYPE.to_string(),
+            properties_json: JsonValue::Object(props).to_string(),
+            created_at: now.clone(),
+            tombstoned_at: None,
+        };
+        SqliteRepo::insert_relation(&tx, &record)?;
+        relation_ids.push(id);
+    }
+    tx.commit().map_err(from_rusqlite)?;
+
+    Ok(CommandResult::ConstraintAttachBulk {
+        relation_ids,
+        attached_ettle_ids,
+        skipped_ettle_ids,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// list_constraint_attachments
+// ---------------------------------------------------------------------------
+
+/// List the Ettles a constraint Ettle is attached to, in attachment order.
+///
+/// The constraint Ettle may itself be tombstoned; its attachments are still
+/// reported. Attachments whose relation is tombstoned are included only when
+/// `include_tombstoned` is set.
+///
+/// Invariants enforced:
+/// - `constraint_ettle_id` must exist (`NotFound`).
+pub(crate) fn list_constraint_attachments(
+    conn: &Connection,
+    constraint_ettle_id: &str,
+    include_tombstoned: bool,
+) -> Result<Vec<ConstraintAttachment>> {
+    if SqliteRepo::get_ettle_record(conn, constraint_ettle_id)?.is_none() {
+        return Err(ExError::new(ExErrorKind::NotFound)
+            .with_op("constraint_list_attachments")
+            .with_entity_id(constraint_ettle_id)
+            .with_message(format!(
+                "Constraint ettle not found: {}",
+                constraint_ettle_id
+            )));
+    }
+
+    let relations = SqliteRepo::list_relations(
+        conn,
+        &RelationListOpts {
+            source_ettle_id: Some(constraint_ettle_id.to_string()),
+            target_ettle_id: None,
+            relation_type: Some(CONSTRAINT_RELATION_TYPE.to_string()),
+            include_tombstoned,
+        },
+    )?;
+
+    let mut attachments = Vec::with_capacity(relations.len());
+    for rel in relations {
+        let target =
+            SqliteRepo::get_ettle_record(conn, &rel.target_ettle_id)?.ok_or_else(|| {
+                ExError::new(ExErrorKind::Persistence)
+                    .with_op("constraint_list_attachments")
+                    .with_entity_id(&rel.id)
+                    .with_message(format!(
+                        "Relation {} targets missing ettle {}",
+                        rel.id, rel.target_ettle_id
+                    ))
+            })?;
+        let properties_json: JsonValue =
+            serde_json::from_str(&rel.properties_json).map_err(|e| {
+                ExError::new(ExErrorKind::Serialization)
+                    .with_op("constraint_list_attachments")
+                    .with_entity_id(&rel.id)
+                    .with_message(e.to_string())
+            })?;
+        attachments.push(ConstraintAttachment {
+            relation_id: rel.id,
+            ettle_id: target.id,
+            ettle_title: target.title,
+            ettle_tombstoned: target.tombstoned_at.is_some(),
+            ordinal: properties_json.get("ordinal").and_then(JsonValue::as_u64),
+            properties_json,
+            created_at: rel.created_at,
+            tombstoned_at: rel.tombstoned_at,
+        });
+    }
+    Ok(attachments)
+}
+
+// ---------------------------------------------------------------------------
+// effective_constraints
+// ---------------------------------------------------------------------------
+
+/// Compute the constraints in force for an Ettle, including those inherited
+/// from its refinement ancestors.
+///
+/// The path is resolved from `ettle_id` up to its root, taking the lowest
+/// relation ID where an Ettle has several parents. Active attachments of
+/// active constraint Ettles on the path are then considered nearest-first:
+///
+/// - an attachment on an ancestor with `scope: "self"` applies only to that
+///   ancestor (`ScopedToSelf`);
+/// - a constraint attached at several levels takes the nearest attachment,
+///   the others are `Shadowed`;
+/// - an attachment with `override: true` and a `family` hides attachments of
+///   the same family further up the path (`Overridden`).
+///
+/// Invariants enforced:
+/// - `ettle_id` must exist (`NotFound`).
+pub(crate) fn effective_constraints(
+    conn: &Connection,
+    ettle_id: &str,
+) -> Result<EffectiveConstraints> {
+    if SqliteRepo::get_ettle_record(conn, ettle_id)?.is_none() {
+        return Err(ExError::new(ExErrorKind::NotFound)
+            .with_op("ettle_effective_constraints")
+            .with_entity_id(ettle_id)
+            .with_message(format!("Ettle not found: {}", ettle_id)));
+    }
+
+    // Nearest first: the Ettle, then its parent, up to the root
+    let mut path = vec![ettle_id.to_string()];
+    let mut current = ettle_id.to_string();
+    loop {
+        let mut parents = SqliteRepo::list_relations(
+            conn,
+            &RelationListOpts {
+                source_ettle_id: None,
+                target_ettle_id: Some(current.clone()),
+                relation_type: Some("refinement".to_string()),
+                include_tombstoned: false,
+            },
+        )?;
+        parents.sort_by(|a, b| a.id.cmp(&b.id));
+        let Some(parent_id) = parents.into_iter().next().map(|r| r.source_ettle_id) else {
+            break;
+        };
+        if path.contains(&parent_id) || SqliteRepo::get_ettle_record(conn, &parent_id)?.is_none() {
+            break;
+        }
+        path.push(parent_id.clone());
+        current = parent_id;
+    }
+
+    // Per level, nearest first; levels are reversed to root-first at the end
+    let mut levels: Vec<Vec<EffectiveConstraint>> = Vec::with_capacity(path.len());
+    let mut excluded = Vec::new();
+    let mut chosen: HashMap<String, String> = HashMap::new();
+    let mut overridden_families: HashMap<String, String> = HashMap::new();
+    for (depth, ettle_id) in path.iter().enumerate() {
+        let mut relations = SqliteRepo::list_relations(
+            conn,
+            &RelationListOpts {
+                source_ettle_id: None,
+                target_ettle_id: Some(ettle_id.clone()),
+                relation_type: Some(CONSTRAINT_RELATION_TYPE.to_string()),
+                include_tombstoned: false,
+            },
+        )?;
+        relations.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let mut level = Vec::new();
+        let mut level_overrides = Vec::new();
+        for rel in relations {
+            let Some(constraint) = SqliteRepo::get_ettle_record(conn, &rel.source_ettle_id)?
+                .filter(|c| c.tombstoned_at.is_none())
+            else {
+                continue;
+            };
+            let properties_json: JsonValue =
+                serde_json::from_str(&rel.properties_json).map_err(|e| {
+                    ExError::new(ExErrorKind::Serialization)
+                        .with_op("ettle_effective_constraints")
+                        .with_entity_id(&rel.id)
+                        .with_message(e.to_string())
+                })?;
+            let family = properties_json
+                .get("family")
+                .and_then(JsonValue::as_str)
+                .map(str::to_string);
+            let exclude = |reason| ExcludedConstraint {
+                constraint_id: constraint.id.clone(),
+                relation_id: rel.id.clone(),
+                attached_to: ettle_id.clone(),
+                reason,
+            };
+
+            if depth > 0 && properties_json.get("scope").and_then(JsonValue::as_str) == Some("self")
+            {
+                excluded.push(exclude(ExclusionReason::ScopedToSelf));
+                continue;
+            }
+            if let Some(by) = chosen.get(&constraint.id) {
+                excluded.push(exclude(ExclusionReason::Shadowed {
+                    by_relation_id: by.clone(),
+                }));
+                continue;
+            }
+            if let Some(by) = family.as_ref().and_then(|f| overridden_families.get(f)) {
+                excluded.push(exclude(ExclusionReason::Overridden {
+                    by_relation_id: by.clone(),
+                }));
+                continue;
+            }
+
+            if let Some(f) = &family {
+                if properties_json.get("override").and_then(JsonValue::as_bool) == Some(true) {
+                    level_overrides.push((f.clone(), rel.id.clone()));
+                }
+            }
+            chosen.insert(constraint.id.clone(), rel.id.clone());
+            level.push(EffectiveConstraint {
+                constraint_id: constraint.id,
+                constraint_title: constraint.title,
+                relation_id: rel.id,
+                attached_to: ettle_id.clone(),
+                inherited: depth > 0,
+                family,
+                ordinal: properties_json.get("ordinal").and_then(JsonValue::as_u64),
+                properties_json,
+            });
+        }
+        // Overrides take effect from the next level up, not among siblings
+        for (family, relation_id) in level_overrides {
+            overridden_families.entry(family).or_insert(relation_id);
+        }
+        level.sort_by(|a, b| {
+            (a.ordinal.unwrap_or(u64::MAX), &a.constraint_id)
+                .cmp(&(b.ordinal.unwrap_or(u64::MAX), &b.constraint_id))
+        });
+        levels.push(level);
+    }
+
+    path.reverse();
+    Ok(EffectiveConstraints {
+        ettle_id: ettle_id.to_string(),
+        path,
+        constraints: levels.into_iter().rev().flatten().collect(),
+        excluded,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// handle_constraint_sweep_orphans
+// ---------------------------------------------------------------------------
+
+/// Tombstone every dangling `constraint` relation in one transaction.
+///
+/// Returns the swept relation IDs in `(created_at, id)` order; an empty list
+/// when there is nothing to sweep.
+pub fn handle_constraint_sweep_orphans(conn: &mut Connection) -> Result<CommandResult> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let tx = conn.savepoint().map_err(from_rusqlite)?;
+    let orphans = SqliteRepo::list_dangling_constraint_relations(&tx)?;
+    let mut relation_ids = Vec::with_capacity(orphans.len());
+    for rel in orphans {
+        SqliteRepo::tombstone_relation(&tx, &rel.id, &now)?;
+        relation_ids.push(rel.id);
+    }
+    tx.commit().map_err(from_rusqlite)?;
+    Ok(CommandResult::ConstraintSweepOrphans { relation_ids })
+}
+
+// ---------------------------------------------------------------------------
+// Selector resolution
+// ---------------------------------------------------------------------------
+
+/// Resolve a selector to the set of active Ettle IDs it matches.
+pub(crate) fn resolve_selector(
+    conn: &Connection,
+    selector: &TargetSelector,
+) -> Result<BTreeSet<String>> {
+    match selector {
+        TargetSelector::Subtree { root_ettle_id } => {
+            require_active_ettle(conn, root_ettle_id)?;
+            let mut seen: BTreeSet<String> = BTreeSet::new();
+            let mut queue: VecDeque<String> = VecDeque::new();
+            queue.push_back(root_ettle_id.clone());
+            while let Some(current) = queue.pop_front() {
+                if !seen.insert(current.clone()) {
+                    continue;
+                }
+                for child in
+                    SqliteRepo::get_active_outgoing_relations_of_type(conn, &current, "refinement")?
+                {
+                    if !seen.contains(&child) {
+                        queue.push_back(child);
+                    }
+                }
+            }
+            // Drop descendants that are themselves tombstoned
+            let mut active = BTreeSet::new();
+            for id in seen {
+                if let Some(rec) = SqliteRepo::get_ettle_record(conn, &id)? {
+                    if rec.tombstoned_at.is_none() {
+                        active.insert(id);
+                    }
+                }
+            }
+            Ok(active)
+        }
+        TargetSelector::Group { group_id } => {
+            let group = SqliteRepo::get_group(conn, group_id)?.ok_or_else(|| {
+                ExError::new(ExErrorKind::NotFound)
+                    .with_op("constraint_attach_bulk")
+                    .with_entity_id(group_id)
+                    .with_message(format!("Group not found: {}", group_id))
+            })?;
+            if group.tombstoned_at.is_some() {
+                return Err(ExError::new(ExErrorKind::AlreadyTombstoned)
+                    .with_op("constraint_attach_bulk")
+                    .with_entity_id(group_id)
+                    .with_message(format!("Group is tombstoned: {}", group_id)));
+            }
+            let mut active = BTreeSet::new();
+            for member in SqliteRepo::list_group_members(conn, group_id, false)? {
+                if let Some(rec) = SqliteRepo::get_ettle_record(conn, &member.ettle_id)? {
+                    if rec.tombstoned_at.is_none() {
+                        active.insert(member.ettle_id);
+                    }
+                }
+            }
+            Ok(active)
+        }
+        TargetSelector::TitleContains { text } => {
+            if text.trim().is_empty() {
+                return Err(ExError::new(ExErrorKind::InvalidInput)
+                    .with_op("constraint_attach_bulk")
+                    .with_message("title_contains text must not be empty"));
+            }
+            Ok(
+                SqliteRepo::list_active_ettle_ids_title_contains(conn, text)?
+                    .into_iter()
+                    .collect(),
+            )
+        }
+    }
+}
+
+fn require_active_ettle(conn: &Connection, ettle_id: &str) -> Result<()> {
+    let rec = SqliteRepo::get_ettle_record(conn, ettle_id)?.ok_or_else(|| {
+        ExError::new(ExErrorKind::NotFound)
+            .with_op("constraint_attach_bulk")
+            .with_entity_id(ettle_id)
+            .with_message(format!("Ettle not found: {}", ettle_id))
+    })?;
+    if rec.tombstoned_at.is_some() {
+        return Err(ExError::new(ExErrorKind::AlreadyTombstoned)
+            .with_op("constraint_attach_bulk")
+            .with_entity_id(ettle_id)
+            .with_message(format!("Ettle is tombstoned: {}", ettle_id)));
+    }
+    Ok(())
+}
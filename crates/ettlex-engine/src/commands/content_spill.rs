@@ -0,0 +1,151 @@
+//! CAS spillover for large Ettle content.
+//!
+//! When the `content.max_inline_bytes` setting is stored, a why/what/how
+//! body longer than that many bytes is written to CAS instead of the
+//! `ettles` row. The column holds `""` and the matching `*_digest` column
+//! names the blob. Writers go through [`prepare_content`]; readers that hand
+//! content to a caller run [`hydrate_ettle_content`] first. With the setting
+//! unset every body stays inline, and an Ettle whose fields are later
+//! rewritten under the limit moves back inline.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_store::cas::FsStore;
+use ettlex_store::errors::Result;
+use ettlex_store::model::EttleRecord;
+use ettlex_store::repo::SqliteRepo;
+use rusqlite::Connection;
+use sha2::{Digest, Sha256};
+
+use super::settings::content_max_inline_bytes;
+
+/// Content fields as they are written to an `ettles` row.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct StoredContent {
+    /// Column values for the supplied fields; `None` leaves the column alone.
+    pub why: Option<String>,
+    pub what: Option<String>,
+    pub how: Option<String>,
+    /// Digest columns after the write, including unchanged fields.
+    pub why_digest: Option<String>,
+    pub what_digest: Option<String>,
+    pub how_digest: Option<String>,
+}
+
+impl StoredContent {
+    /// Write the digest columns for `ettle_id`.
+    pub(crate) fn write_digests(&self, conn: &Connection, ettle_id: &str) -> Result<()> {
+        SqliteRepo::set_ettle_content_digests(
+            conn,
+            ettle_id,
+            self.why_digest.as_deref(),
+            self.what_digest.as_deref(),
+            self.how_digest.as_deref(),
+        )
+    }
+
+    fn any_spilled(&self) -> bool {
+        self.why_digest.is_some() || self.what_digest.is_some() || self.how_digest.is_some()
+    }
+}
+
+/// Decide where each supplied field is stored, writing oversized bodies to
+/// CAS.
+///
+/// `existing` is the row being updated (`None` on create); its digests are
+/// kept for fields that are not supplied.
+pub(crate) fn prepare_content(
+    conn: &Connection,
+    cas: &FsStore,
+    existing: Option<&EttleRecord>,
+    why: Option<&str>,
+    what: Option<&str>,
+    how: Option<&str>,
+) -> Result<StoredContent> {
+    let limit = content_max_inline_bytes(conn)?;
+    let (why, why_digest) =
+        place_field(cas, limit, why, existing.and_then(|r| r.why_digest.clone()))?;
+    let (what, what_digest) = place_field(
+        cas,
+        limit,
+        what,
+        existing.and_then(|r| r.what_digest.clone()),
+    )?;
+    let (how, how_digest) =
+        place_field(cas, limit, how, existing.and_then(|r| r.how_digest.clone()))?;
+    Ok(StoredContent {
+        why,
+        what,
+        how,
+        why_digest,
+        what_digest,
+        how_digest,
+    })
+}
+
+/// Whether writing `stored` changes the digest columns of `existing`.
+pub(crate) fn digests_changed(existing: Option<&EttleRecord>, stored: &StoredContent) -> bool {
+    match existing {
+        None => stored.any_spilled(),
+        Some(r) => {
+            r.why_digest != stored.why_digest
+                || r.what_digest != stored.what_digest
+                || r.how_digest != stored.how_digest
+        }
+    }
+}
+
+/// Replace the spilled why/what/how fields of `record` with their CAS
+/// content. The digests are left set, so callers can still tell which
+/// fields were spilled.
+///
+/// # Errors
+/// * `NotFound` - a spilled blob is missing from CAS
+/// * `Serialization` - a spilled blob is not valid UTF-8
+pub fn hydrate_ettle_content(record: &mut EttleRecord, cas: &FsStore) -> Result<()> {
+    for (field, digest, text) in [
+        ("why", &record.why_digest, &mut record.why),
+        ("what", &record.what_digest, &mut record.what),
+        ("how", &record.how_digest, &mut record.how),
+    ] {
+        let Some(digest) = digest else {
+            continue;
+        };
+        let bytes = cas.read(digest)?;
+        *text = String::from_utf8(bytes).map_err(|e| {
+            ExError::new(ExErrorKind::Serialization)
+                .with_op("hydrate_ettle_content")
+                .with_entity_id(&record.id)
+                .with_message(format!("spilled {} content is not UTF-8: {}", field, e))
+        })?;
+    }
+    Ok(())
+}
+
+/// Whether `text` equals a stored field, given the column value and digest.
+///
+/// A spilled field is compared by hash against its digest, so planners can
+/// detect unchanged content without reading CAS.
+pub fn stored_content_eq(text: &str, inline: &str, digest: Option<&str>) -> bool {
+    match digest {
+        Some(digest) => format!("{:x}", Sha256::digest(text.as_bytes())) == digest,
+        None => text == inline,
+    }
+}
+
+fn place_field(
+    cas: &FsStore,
+    limit: Option<u64>,
+    text: Option<&str>,
+    current_digest: Option<String>,
+) -> Result<(Option<String>, Option<String>)> {
+    match text {
+        None => Ok((None, current_digest)),
+        Some(text) if limit.is_some_and(|max| text.len() as u64 > max) => {
+            let digest = cas.write(text.as_bytes(), "txt")?;
+            Ok((Some(String::new()), Some(digest)))
+        }
+        Some(text) => Ok((Some(text.to_string()), None)),
+    }
+}
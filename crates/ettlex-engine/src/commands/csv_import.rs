@@ -0,0 +1,513 @@
+//! CSV import — bulk ingestion of an Ettle tree from a CSV file.
+//!
+//! Rows become Ettles and parent references become refinement relations.
+//! Creates are applied in chunks through [`apply_commands_atomic`]; each
+//! chunk's import-session items are recorded in the same savepoint as the
+//! chunk, so an interrupted import can be rolled back or resumed (see
+//! [`super::import_session`]).
+
+#![allow(clippy::result_large_err)]
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::Read;
+use std::path::Path;
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_store::cas::{CasDedupStats, FsStore};
+use ettlex_store::model::{IMPORT_ITEM_ETTLE, IMPORT_ITEM_RELATION};
+use ettlex_store::repo::SqliteRepo;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::command::{apply_commands_atomic, split_batch_error, Command, CommandResult};
+use super::ettle::resolve_ettle_ref;
+use super::import_session::{
+    complete_import_session, open_import_session, record_import_item, rollback_import_session,
+    running_import_session, verify_import_source,
+};
+use super::root::ensure_new_root_allowed;
+
+type Result<T> = std::result::Result<T, ExError>;
+
+/// Maps CSV header names to Ettle fields
+///
+/// `key` names a column of row-local references that `parent` cells may
+/// point at; a `parent` cell that matches no row key is resolved as an
+/// existing Ettle ID or slug. Only `title` is required in the CSV.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CsvColumnMapping {
+    pub key: String,
+    pub parent: String,
+    pub title: String,
+    pub ordinal: String,
+    pub why: String,
+    pub what: String,
+    pub how: String,
+}
+
+impl Default for CsvColumnMapping {
+    fn default() -> Self {
+        Self {
+            key: "ref".to_string(),
+            parent: "parent".to_string(),
+            title: "title".to_string(),
+            ordinal: "ordinal".to_string(),
+            why: "why".to_string(),
+            what: "what".to_string(),
+            how: "how".to_string(),
+        }
+    }
+}
+
+/// Outcome of [`import_csv`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvImportReport {
+    /// Created Ettle IDs in CSV row order, with each row's key (if any)
+    pub ettles: Vec<(Option<String>, String)>,
+    /// Number of refinement relations created
+    pub refinements: usize,
+    /// CAS blobs written versus reused for spilled content
+    pub cas: CasDedupStats,
+}
+
+/// What a CSV import session needs to resume, stored as its options
+#[derive(Debug, Serialize, Deserialize)]
+struct CsvSessionOptions {
+    mapping: CsvColumnMapping,
+    batch_size: Option<u64>,
+}
+
+struct CsvRow {
+    line: u64,
+    key: Option<String>,
+    parent: Option<String>,
+    title: String,
+    ordinal: Option<i64>,
+    why: Option<String>,
+    what: Option<String>,
+    how: Option<String>,
+}
+
+/// Import CSV rows as Ettles linked by refinement relations
+///
+/// All Ettles are created first, in row order; refinements are then created
+/// per parent in `ordinal` order (rows without an ordinal last, in row
+/// order), with the ordinal copied into the relation's properties. Runs in
+/// one transaction: on any error nothing is imported.
+///
+/// # Errors
+/// * `InvalidInput` - Malformed CSV, missing `title` column, or duplicate row key
+/// * `InvalidTitle` - A row has an empty title
+/// * `InvalidOrdinal` - An ordinal cell is not an integer
+/// * `NotFound` - A parent matches neither a row key nor an existing Ettle
+/// * `CycleDetected` - Parent references form a cycle
+/// * `RootEttleInvalid` - A row has no parent while `roots.mode` is
+///   `registered`
+pub fn import_csv(
+    conn: &mut Connection,
+    cas: &FsStore,
+    reader: impl Read,
+    mapping: &CsvColumnMapping,
+) -> Result<CsvImportReport> {
+    let rows = parse_rows(reader, mapping)?;
+    run_import(conn, cas, &rows, &ImportRun::new(None, None))
+}
+
+/// Import a CSV file under an import session
+///
+/// Behaves like [`import_csv`], but the import is recorded as a session
+/// that `import recover` can find if the process dies mid-way. With
+/// `batch_size`, the Ettles and then the relations are committed in chunks
+/// of at most `batch_size` rather than once; if the import fails with an
+/// error, the chunks already committed are rolled back, so nothing is
+/// imported.
+///
+/// # Errors
+/// As [`import_csv`], plus `Io` if the file cannot be read.
+pub fn import_csv_file(
+    conn: &mut Connection,
+    cas: &FsStore,
+    path: &Path,
+    mapping: &CsvColumnMapping,
+    batch_size: Option<u64>,
+) -> Result<CsvImportReport> {
+    let source = read_source(path)?;
+    let rows = parse_rows(source.as_slice(), mapping)?;
+
+    let source_path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let options = CsvSessionOptions {
+        mapping: mapping.clone(),
+        batch_size,
+    };
+    let session = open_import_session(
+        conn,
+        "csv",
+        &source_path.display().to_string(),
+        &source,
+        &json!(options),
+    )?;
+
+    let run = ImportRun::new(Some(&session.session_id), batch_size);
+    match run_import(conn, cas, &rows, &run) {
+        Ok(report) => {
+            complete_import_session(conn, &session.session_id)?;
+            Ok(report)
+        }
+        Err(e) => {
+            // Best effort: if this fails too, the session stays `running`
+            // for `import recover`.
+            let _ = rollback_import_session(conn, cas, &session.session_id);
+            Err(e)
+        }
+    }
+}
+
+/// Finish an interrupted CSV import session
+///
+/// Re-reads the session's source file, skips the rows whose Ettles and
+/// relations were already imported, and imports the rest with the
+/// session's original mapping and batch size. On error the session stays
+/// `running`, holding whatever was committed.
+///
+/// # Errors
+/// * `NotFound` - No session has this ID
+/// * `InvalidInput` - The session is not a running CSV import
+/// * `HeadMismatch` - The source file changed since the import started
+/// * Otherwise as [`import_csv_file`]
+pub fn resume_csv_import(
+    conn: &mut Connection,
+    cas: &FsStore,
+    session_id: &str,
+) -> Result<CsvImportReport> {
+    let session = running_import_session(conn, session_id)?;
+    if session.kind != "csv" {
+        return Err(ExError::new(ExErrorKind::InvalidInput)
+            .with_op("import_recover")
+            .with_entity_id(session_id)
+            .with_message(format!("not a CSV import session: {}", session.kind)));
+    }
+    let options: CsvSessionOptions = serde_json::from_str(&session.options_json).map_err(|e| {
+        ExError::new(ExErrorKind::Serialization)
+            .with_op("import_recover")
+            .with_entity_id(session_id)
+            .with_message(e.to_string())
+    })?;
+    let source = read_source(Path::new(&session.source_path))?;
+    verify_import_source(&session, &source)?;
+    let rows = parse_rows(source.as_slice(), &options.mapping)?;
+
+    let mut run = ImportRun::new(Some(session_id), options.batch_size);
+    for item in SqliteRepo::list_import_session_items(conn, session_id)? {
+        if item.item_kind == IMPORT_ITEM_ETTLE {
+            run.done_ettles.insert(item.source_line, item.entity_id);
+        } else {
+            run.done_relations.insert(item.source_line);
+        }
+    }
+    let report = run_import(conn, cas, &rows, &run)?;
+    complete_import_session(conn, session_id)?;
+    Ok(report)
+}
+
+fn read_source(path: &Path) -> Result<Vec<u8>> {
+    std::fs::read(path).map_err(|e| {
+        ExError::new(ExErrorKind::Io)
+            .with_op("import_csv")
+            .with_message(format!("{}: {}", path.display(), e))
+    })
+}
+
+/// Chunking and session bookkeeping for one pass over the rows
+struct ImportRun<'a> {
+    session_id: Option<&'a str>,
+    /// Created entities per chunk; `None` means one transaction
+    batch_size: Option<u64>,
+    /// Ettle IDs already imported by this session, by source line
+    done_ettles: HashMap<u64, String>,
+    /// Source lines whose refinement this session already imported
+    done_relations: HashSet<u64>,
+}
+
+impl<'a> ImportRun<'a> {
+    fn new(session_id: Option<&'a str>, batch_size: Option<u64>) -> Self {
+        Self {
+            session_id,
+            batch_size,
+            done_ettles: HashMap::new(),
+            done_relations: HashSet::new(),
+        }
+    }
+
+    /// Apply `commands` (each with its source line) in chunks, recording
+    /// every created entity as a session item in its chunk's savepoint.
+    /// Returns the created entity IDs in order.
+    fn apply(
+        &self,
+        conn: &mut Connection,
+        cas: &FsStore,
+        item_kind: &str,
+        commands: Vec<(u64, Command)>,
+    ) -> Result<Vec<String>> {
+        let chunk_size = match self.batch_size {
+            Some(n) => usize::try_from(n).unwrap_or(usize::MAX),
+            None => commands.len().max(1),
+        };
+        let mut created = Vec::with_capacity(commands.len());
+        let mut commands = commands.into_iter().peekable();
+        while commands.peek().is_some() {
+            let (lines, chunk): (Vec<u64>, Vec<Command>) =
+                commands.by_ref().take(chunk_size).unzip();
+            in_savepoint(conn, "import_chunk", |conn| {
+                let (results, _) = apply_commands_atomic(
+                    chunk,
+                    None,
+                    conn,
+                    cas,
+                    &NoopPolicyProvider,
+                    &NoopApprovalRouter,
+                )
+                .map_err(|e| match split_batch_error(e) {
+                    (Some(i), e) => at_line(e, lines[i]),
+                    (None, e) => e,
+                })?;
+                for (line, result) in lines.iter().zip(results) {
+                    let entity_id = match result {
+                        CommandResult::EttleCreate { ettle_id } => ettle_id,
+                        CommandResult::RelationCreate { relation_id } => relation_id,
+                        _ => return Err(unexpected_result()),
+                    };
+                    if let Some(session_id) = self.session_id {
+                        record_import_item(conn, session_id, item_kind, *line, &entity_id)?;
+                    }
+                    created.push(entity_id);
+                }
+                Ok(())
+            })?;
+        }
+        Ok(created)
+    }
+}
+
+fn run_import(
+    conn: &mut Connection,
+    cas: &FsStore,
+    rows: &[CsvRow],
+    run: &ImportRun<'_>,
+) -> Result<CsvImportReport> {
+    let before = cas.dedup_stats();
+    let mut report = if run.batch_size.is_some() {
+        import_rows(conn, cas, rows, run)?
+    } else {
+        in_savepoint(conn, "csv_import", |conn| import_rows(conn, cas, rows, run))?
+    };
+    report.cas = cas.dedup_stats().since(&before);
+    Ok(report)
+}
+
+fn parse_rows(reader: impl Read, mapping: &CsvColumnMapping) -> Result<Vec<CsvRow>> {
+    let mut csv_reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(reader);
+    let headers = csv_reader.headers().map_err(invalid_csv)?.clone();
+    let column = |name: &str| headers.iter().position(|h| h == name);
+    let title_col = column(&mapping.title).ok_or_else(|| {
+        ExError::new(ExErrorKind::InvalidInput)
+            .with_op("import_csv")
+            .with_message(format!("CSV has no '{}' column", mapping.title))
+    })?;
+    let key_col = column(&mapping.key);
+    let parent_col = column(&mapping.parent);
+    let ordinal_col = column(&mapping.ordinal);
+    let why_col = column(&mapping.why);
+    let what_col = column(&mapping.what);
+    let how_col = column(&mapping.how);
+
+    let mut rows = Vec::new();
+    let mut keys: HashMap<String, u64> = HashMap::new();
+    for record in csv_reader.records() {
+        let record = record.map_err(invalid_csv)?;
+        let line = record.position().map(|p| p.line()).unwrap_or_default();
+        let cell = |col: Option<usize>| {
+            col.and_then(|c| record.get(c))
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+
+        let key = cell(key_col);
+        if let Some(key) = &key {
+            if let Some(first) = keys.insert(key.clone(), line) {
+                return Err(ExError::new(ExErrorKind::InvalidInput)
+                    .with_op("import_csv")
+                    .with_entity_id(key)
+                    .with_message(format!("line {}: key already used on line {}", line, first)));
+            }
+        }
+        let ordinal = cell(ordinal_col)
+            .map(|v| {
+                v.parse::<i64>().map_err(|_| {
+                    ExError::new(ExErrorKind::InvalidOrdinal)
+                        .with_op("import_csv")
+                        .with_message(format!("line {}: ordinal '{}' is not an integer", line, v))
+                })
+            })
+            .transpose()?;
+
+        rows.push(CsvRow {
+            line,
+            key,
+            parent: cell(parent_col),
+            title: cell(Some(title_col)).unwrap_or_default(),
+            ordinal,
+            why: cell(why_col),
+            what: cell(what_col),
+            how: cell(how_col),
+        });
+    }
+    Ok(rows)
+}
+
+fn import_rows(
+    conn: &mut Connection,
+    cas: &FsStore,
+    rows: &[CsvRow],
+    import: &ImportRun<'_>,
+) -> Result<CsvImportReport> {
+    for row in rows.iter().filter(|r| r.parent.is_none()) {
+        if !import.done_ettles.contains_key(&row.line) {
+            ensure_new_root_allowed(conn, "import_csv", &format!("line {}", row.line))?;
+        }
+    }
+
+    let creates = rows
+        .iter()
+        .filter(|row| !import.done_ettles.contains_key(&row.line))
+        .map(|row| {
+            (
+                row.line,
+                Command::EttleCreate {
+                    title: row.title.clone(),
+                    ettle_id: None,
+                    why: row.why.clone(),
+                    what: row.what.clone(),
+                    how: row.how.clone(),
+                    reasoning_link_id: None,
+                    reasoning_link_type: None,
+                },
+            )
+        })
+        .collect();
+    let mut created = import
+        .apply(conn, cas, IMPORT_ITEM_ETTLE, creates)?
+        .into_iter();
+
+    let mut ettles = Vec::with_capacity(rows.len());
+    let mut by_key: HashMap<&str, String> = HashMap::new();
+    for row in rows {
+        let ettle_id = match import.done_ettles.get(&row.line) {
+            Some(ettle_id) => ettle_id.clone(),
+            None => created.next().ok_or_else(unexpected_result)?,
+        };
+        if let Some(key) = &row.key {
+            by_key.insert(key, ettle_id.clone());
+        }
+        ettles.push((row.key.clone(), ettle_id));
+    }
+
+    // parent ID -> [(child row, child ID)]
+    let mut children: BTreeMap<String, Vec<(&CsvRow, &str)>> = BTreeMap::new();
+    for (row, (_, ettle_id)) in rows.iter().zip(&ettles) {
+        let Some(parent) = &row.parent else {
+            continue;
+        };
+        let parent_id = match by_key.get(parent.as_str()) {
+            Some(id) => id.clone(),
+            None => {
+                let id = resolve_ettle_ref(conn, parent)?;
+                if SqliteRepo::get_ettle(conn, &id)?.is_none() {
+                    return Err(ExError::new(ExErrorKind::NotFound)
+                        .with_op("import_csv")
+                        .with_entity_id(parent)
+                        .with_message(format!(
+                            "line {}: parent matches no row key or existing ettle",
+                            row.line
+                        )));
+                }
+                id
+            }
+        };
+        children.entry(parent_id).or_default().push((row, ettle_id));
+    }
+
+    let mut refinements = 0;
+    let mut links = Vec::new();
+    for (parent_id, mut kids) in children {
+        kids.sort_by_key(|(row, _)| (row.ordinal.is_none(), row.ordinal, row.line));
+        for (row, child_id) in kids {
+            refinements += 1;
+            if import.done_relations.contains(&row.line) {
+                continue;
+            }
+            links.push((
+                row.line,
+                Command::RelationCreate {
+                    source_ettle_id: parent_id.clone(),
+                    target_ettle_id: child_id.to_string(),
+                    relation_type: "refinement".to_string(),
+                    properties_json: row.ordinal.map(|o| json!({ "ordinal": o })),
+                    relation_id: None,
+                },
+            ));
+        }
+    }
+    import.apply(conn, cas, IMPORT_ITEM_RELATION, links)?;
+
+    Ok(CsvImportReport {
+        ettles,
+        refinements,
+        cas: CasDedupStats::default(),
+    })
+}
+
+/// Run `f` in a savepoint that is released on success and rolled back on
+/// error. At the top level, releasing it commits.
+fn in_savepoint<T>(
+    conn: &mut Connection,
+    name: &str,
+    f: impl FnOnce(&mut Connection) -> Result<T>,
+) -> Result<T> {
+    let persistence = |e: rusqlite::Error| {
+        ExError::new(ExErrorKind::Persistence)
+            .with_op("import_csv")
+            .with_message(e.to_string())
+    };
+    conn.execute_batch(&format!("SAVEPOINT {}", name))
+        .map_err(persistence)?;
+    let result = f(conn);
+    let end = match &result {
+        Ok(_) => format!("RELEASE {}", name),
+        Err(_) => format!("ROLLBACK TO {0}; RELEASE {0}", name),
+    };
+    conn.execute_batch(&end).map_err(persistence)?;
+    result
+}
+
+fn at_line(e: ExError, line: u64) -> ExError {
+    let message = format!("line {}: {}", line, e.message());
+    e.with_message(message)
+}
+
+fn invalid_csv(e: csv::Error) -> ExError {
+    ExError::new(ExErrorKind::InvalidInput)
+        .with_op("import_csv")
+        .with_message(format!("invalid CSV: {}", e))
+}
+
+fn unexpected_result() -> ExError {
+    ExError::new(ExErrorKind::Internal)
+        .with_op("import_csv")
+        .with_message("unexpected command result")
+}
@@ -1,7 +1,7 @@
 //! Decision command handlers with boundary logging.
 //!
 //! This module provides command handlers for decision operations:
-//! - Create, update, tombstone decisions
+//! - Create, update, tombstone, restore decisions
 //! - Link/unlink decisions to targets
 //! - Supersede decisions
 //!
@@ -16,12 +16,15 @@
 
 #![allow(clippy::result_large_err)]
 
+use ettlex_core::errors::{ExError, ExErrorKind};
 use ettlex_core::ops::decision_ops;
 use ettlex_core::{log_op_end, log_op_error, log_op_start};
 use ettlex_store::errors::Result;
 use ettlex_store::repo::SqliteRepo;
 use rusqlite::Connection;
 
+use super::evidence::{check_evidence_quota, load_evidence_policy};
+
 /// Create a new decision
 ///
 /// ## Arguments
@@ -46,7 +49,8 @@ use rusqlite::Connection;
 /// ## Errors
 ///
 /// - `InvalidDecision`: Validation failed
-/// - `InvalidEvidence`: Evidence validation failed
+/// - `InvalidEvidence`: Evidence validation failed or exceeds the
+///   default profile's evidence quota
 /// - `Persistence`: Database error
 #[allow(clippy::too_many_arguments)]
 pub fn decision_create(
@@ -113,6 +117,8 @@ fn decision_create_impl(
     evidence_file_path: Option<String>,
     conn: &Connection,
 ) -> Result<String> {
+    let policy = load_evidence_policy(conn)?;
+
     // Load current store
     let mut store = ettlex_store::repo::hydration::load_tree(conn)?;
 
@@ -132,8 +138,10 @@ fn decision_create_impl(
         evidence_file_path,
     )?;
 
-    // Persist decision
     let decision = store.get_decision(&decision_id)?;
+    check_evidence_quota(&policy, &store, decision)?;
+
+    // Persist decision
     SqliteRepo::persist_decision(conn, decision)?;
 
     // Persist evidence item if created
@@ -167,7 +175,8 @@ fn decision_create_impl(
 ///
 /// - `DecisionNotFound`: Decision doesn't exist
 /// - `DecisionDeleted`: Decision was tombstoned
-/// - `InvalidEvidence`: Evidence validation failed
+/// - `InvalidEvidence`: Evidence validation failed or exceeds the
+///   default profile's evidence quota
 /// - `Persistence`: Database error
 #[allow(clippy::too_many_arguments)]
 pub fn decision_update(
@@ -233,6 +242,11 @@ fn decision_update_impl(
     evidence_file_path: Option<Option<String>>,
     conn: &Connection,
 ) -> Result<()> {
+    // Quotas apply only when this update supplies evidence, so tightening
+    // the policy does not block unrelated edits.
+    let supplies_evidence = evidence_excerpt.is_some() || evidence_capture_content.is_some();
+    let policy = load_evidence_policy(conn)?;
+
     // Load current store
     let mut store = ettlex_store::repo::hydration::load_tree(conn)?;
 
@@ -252,8 +266,12 @@ fn decision_update_impl(
         evidence_file_path,
     )?;
 
-    // Persist decision
     let decision = store.get_decision(&decision_id)?;
+    if supplies_evidence {
+        check_evidence_quota(&policy, &store, decision)?;
+    }
+
+    // Persist decision
     SqliteRepo::persist_decision(conn, decision)?;
 
     // Persist evidence item if created
@@ -314,6 +332,50 @@ fn decision_tombstone_impl(decision_id: String, conn: &Connection) -> Result<()>
     Ok(())
 }
 
+/// Restore a tombstoned decision
+///
+/// ## Arguments
+///
+/// - `decision_id`: Decision ID to restore
+/// - `conn`: Database connection
+///
+/// ## Errors
+///
+/// - `NotFound`: Decision doesn't exist
+/// - `InvalidInput`: Decision is not tombstoned
+/// - `Persistence`: Database error
+pub fn decision_restore(decision_id: String, conn: &Connection) -> Result<()> {
+    log_op_start!("decision_restore", decision_id = &decision_id);
+    let start = std::time::Instant::now();
+
+    decision_restore_impl(decision_id.clone(), conn).map_err(|e| {
+        log_op_error!(
+            "decision_restore",
+            e.clone(),
+            duration_ms = start.elapsed().as_millis() as u64
+        );
+        e
+    })?;
+
+    log_op_end!(
+        "decision_restore",
+        duration_ms = start.elapsed().as_millis() as u64
+    );
+
+    Ok(())
+}
+
+fn decision_restore_impl(decision_id: String, conn: &Connection) -> Result<()> {
+    let mut store = ettlex_store::repo::hydration::load_tree(conn)?;
+
+    decision_ops::restore_decision(&mut store, &decision_id)?;
+
+    let decision = store.get_decision(&decision_id)?;
+    SqliteRepo::persist_decision(conn, decision)?;
+
+    Ok(())
+}
+
 /// Link a decision to a target (EP/Ettle/Constraint/Decision)
 ///
 /// ## Arguments
@@ -331,6 +393,7 @@ fn decision_tombstone_impl(decision_id: String, conn: &Connection) -> Result<()>
 /// - `DecisionTombstoned`: Decision was tombstoned
 /// - `InvalidTargetKind`: Target kind not allowed
 /// - `EpNotFound`/`EttleNotFound`: Target doesn't exist
+/// - `AlreadyTombstoned`: Constraint Ettle target was tombstoned
 /// - `DuplicateDecisionLink`: Link already exists
 /// - `Persistence`: Database error
 pub fn decision_link(
@@ -384,6 +447,10 @@ fn decision_link_impl(
     // Load current store
     let mut store = ettlex_store::repo::hydration::load_tree(conn)?;
 
+    if target_kind == "constraint" {
+        check_constraint_target(conn, &target_id)?;
+    }
+
     // Apply command
     decision_ops::attach_decision_to_target(
         &mut store,
@@ -404,6 +471,26 @@ fn decision_link_impl(
     Ok(())
 }
 
+/// Validate a `"constraint"` link target before the core check runs.
+///
+/// Constraints are constraint Ettles, and the target has to be live: a
+/// decision cannot start justifying a constraint that has been removed.
+fn check_constraint_target(conn: &Connection, target_id: &str) -> Result<()> {
+    let record = SqliteRepo::get_ettle_record(conn, target_id)?.ok_or_else(|| {
+        ExError::new(ExErrorKind::NotFound)
+            .with_op("decision_link")
+            .with_entity_id(target_id)
+            .with_message(format!("Constraint ettle not found: {}", target_id))
+    })?;
+    if record.tombstoned_at.is_some() {
+        return Err(ExError::new(ExErrorKind::AlreadyTombstoned)
+            .with_op("decision_link")
+            .with_entity_id(target_id)
+            .with_message(format!("Constraint ettle is tombstoned: {}", target_id)));
+    }
+    Ok(())
+}
+
 /// Unlink a decision from a target
 ///
 /// ## Arguments
@@ -0,0 +1,158 @@
+//! Manifest determinism check — regenerate a manifest repeatedly from the
+//! same state and require byte-identical output.
+//!
+//! Each iteration hydrates a fresh in-memory store, inserting ettles in a
+//! different order (rotated, and reversed on odd iterations); every fresh
+//! store also gets new hash-map seeds. Each manifest records its topology
+//! digest, so edge ordering is covered as well. Manifests
+//! are compared with `created_at` (and `manifest_digest`, which covers it)
+//! blanked, since the timestamp is the one field allowed to differ between
+//! generations.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::ops::Store;
+use ettlex_core::snapshot::digest::record_topology_digest;
+use ettlex_core::snapshot::manifest::{generate_manifest_with_input, SnapshotManifest};
+use ettlex_core::traversal::rt::compute_rt;
+use ettlex_store::cas::FsStore;
+use ettlex_store::errors::Result;
+use ettlex_store::repo::hydration::load_all_ettles;
+use rusqlite::{Connection, OptionalExtension};
+use sha2::{Digest, Sha256};
+
+use crate::snapshot::{manifest_eval_input, refinement_edges};
+
+/// Outcome of a passing [`verify_manifest_determinism`] run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeterminismReport {
+    pub iterations: u32,
+    /// SHA-256 of the (identical) manifest bytes, with `created_at` blanked
+    pub manifest_bytes_digest: String,
+    pub semantic_manifest_digest: String,
+}
+
+/// Generate the manifest for `leaf_id` `iterations` times and check every
+/// generation is byte-identical. Constraint families are evaluated against
+/// the stored relations and content, reading spilled content from `cas`.
+///
+/// # Errors
+/// * `InvalidInput` - `iterations` is below 2
+/// * `NotFound` - `leaf_id` names no Ettle
+/// * `DeterminismViolation` - two generations differ
+pub fn verify_manifest_determinism(
+    conn: &Connection,
+    cas: &FsStore,
+    leaf_id: &str,
+    policy_ref: &str,
+    profile_ref: &str,
+    iterations: u32,
+) -> Result<DeterminismReport> {
+    let op = "verify_manifest_determinism";
+    if iterations < 2 {
+        return Err(ExError::new(ExErrorKind::InvalidInput)
+            .with_op(op)
+            .with_message("iterations must be at least 2"));
+    }
+
+    let mut base = Store::new();
+    load_all_ettles(conn, &mut base)?;
+    let ettles: Vec<_> = base.list_ettles().into_iter().cloned().collect();
+    let store_schema_version: String = conn
+        .query_row(
+            "SELECT migration_id FROM schema_version ORDER BY id DESC LIMIT 1",
+            [],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| {
+            ExError::new(ExErrorKind::Persistence)
+                .with_op(op)
+                .with_message(e.to_string())
+        })?
+        .unwrap_or_default();
+
+    let mut first: Option<(Vec<u8>, SnapshotManifest)> = None;
+    for i in 0..iterations as usize {
+        let mut store = Store::new();
+        for ettle in reordered(&ettles, i) {
+            store.insert_ettle(ettle.clone());
+        }
+
+        let ept = compute_rt(&store, leaf_id).map_err(|e| e.with_op(op))?;
+        let input = manifest_eval_input(conn, cas, &ept, profile_ref)?;
+        let mut manifest = generate_manifest_with_input(
+            ept,
+            policy_ref.to_string(),
+            profile_ref.to_string(),
+            leaf_id.to_string(),
+            store_schema_version.clone(),
+            None,
+            &store,
+            &input,
+        )?;
+        let edges = refinement_edges(conn, &manifest.ept)?;
+        record_topology_digest(&mut manifest, &edges)?;
+        manifest.created_at = String::new();
+        manifest.manifest_digest = String::new();
+        let bytes = serde_json::to_vec(&manifest).map_err(|e| {
+            ExError::new(ExErrorKind::Serialization)
+                .with_op(op)
+                .with_message(e.to_string())
+        })?;
+
+        match &first {
+            None => first = Some((bytes, manifest)),
+            Some((expected, expected_manifest)) if *expected != bytes => {
+                let field = first_differing_field(expected_manifest, &manifest);
+                return Err(ExError::new(ExErrorKind::DeterminismViolation)
+                    .with_op(op)
+                    .with_entity_id(leaf_id)
+                    .with_message(format!(
+                        "iteration {} produced a different manifest than iteration 0 \
+                         (first difference in `{}`)",
+                        i, field
+                    )));
+            }
+            Some(_) => {}
+        }
+    }
+
+    let (bytes, manifest) = first.ok_or_else(|| {
+        ExError::new(ExErrorKind::Internal)
+            .with_op(op)
+            .with_message("no manifest generated")
+    })?;
+    Ok(DeterminismReport {
+        iterations,
+        manifest_bytes_digest: format!("{:x}", Sha256::digest(&bytes)),
+        semantic_manifest_digest: manifest.semantic_manifest_digest,
+    })
+}
+
+/// `items` rotated left by `i`, reversed when `i` is odd.
+fn reordered<T>(items: &[T], i: usize) -> Vec<&T> {
+    let mut out: Vec<&T> = items.iter().collect();
+    let len = out.len();
+    if len > 0 {
+        out.rotate_left(i % len);
+    }
+    if i % 2 == 1 {
+        out.reverse();
+    }
+    out
+}
+
+/// Name of the first top-level manifest field that differs, for the error.
+fn first_differing_field(a: &SnapshotManifest, b: &SnapshotManifest) -> String {
+    let (Ok(serde_json::Value::Object(a)), Ok(serde_json::Value::Object(b))) =
+        (serde_json::to_value(a), serde_json::to_value(b))
+    else {
+        return "(unknown)".to_string();
+    };
+    a.iter()
+        .find(|(key, value)| b.get(*key) != Some(*value))
+        .map(|(key, _)| key.clone())
+        .unwrap_or_else(|| "(field order)".to_string())
+}
@@ -3,15 +3,24 @@
 #![allow(clippy::result_large_err)]
 
 use crate::snapshot::{
-    RoutedForApprovalResult, SnapshotCommitOutcome, SnapshotCommitResult, SnapshotOptions,
+    RoutedForApprovalResult, SnapshotCommitClass, SnapshotCommitOutcome, SnapshotCommitResult,
+    SnapshotOptions,
 };
 use ettlex_core::approval_router::ApprovalRouter;
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::log_op_outcome;
 use ettlex_core::policy_provider::PolicyProvider;
 use ettlex_store::cas::FsStore;
 use ettlex_store::errors::Result;
-use ettlex_store::profile::{create_profile, set_default_profile};
+use ettlex_store::profile::{
+    create_profile, resolve_profile_payload, set_default_profile, set_environment_default_profile,
+};
 use rusqlite::Connection;
 
+use super::commit_queue::{drain_commit_queue, enqueue_commit, CommitTicket};
+use super::journal::{redo, undo, JournalEntry};
+use super::settings::{default_policy_ref, settings_set};
+
 /// Engine-level commands that require I/O (database, CAS).
 #[derive(Debug, Clone)]
 pub enum EngineCommand {
@@ -26,14 +35,46 @@ pub enum EngineCommand {
         profile_ref: Option<String>,
         options: SnapshotOptions,
     },
+    /// Queue a snapshot commit instead of applying it, and return a ticket
+    /// (see [`super::commit_queue`]). Arguments are validated as for
+    /// `SnapshotCommit`; `options.dry_run` is rejected.
+    SnapshotCommitEnqueue {
+        leaf_ep_id: String,
+        policy_ref: Option<String>,
+        profile_ref: Option<String>,
+        options: SnapshotOptions,
+    },
+    /// Apply queued snapshot commits serially, oldest first: at most `limit`
+    /// of them, or the whole queue with `None`.
+    SnapshotCommitQueueDrain { limit: Option<usize> },
     /// Create a profile (idempotent on same canonical content; ProfileConflict on mismatch).
+    ///
+    /// Profiles named in the payload's `extends` must already exist
+    /// (`ProfileNotFound`); a profile may not extend itself (`CycleDetected`).
     ProfileCreate {
         profile_ref: String,
         payload_json: serde_json::Value,
         source: Option<String>,
     },
-    /// Set a profile as the repository default.
-    ProfileSetDefault { profile_ref: String },
+    /// Set a profile as the repository default, or as the default for one
+    /// environment when `environment` is given.
+    ProfileSetDefault {
+        profile_ref: String,
+        environment: Option<String>,
+    },
+    /// Store a known setting (see [`super::settings::KNOWN_SETTINGS`]), or
+    /// clear it with `value: None` so its default applies. Values of the
+    /// wrong type fail with `InvalidInput`.
+    SettingsSet {
+        key: String,
+        value: Option<serde_json::Value>,
+    },
+    /// Revert the newest applied entry of the command journal (see
+    /// [`super::journal`]). Fails with `NotFound` when there is none.
+    Undo,
+    /// Reapply the entry most recently undone. Fails with `NotFound` when
+    /// there is none, including after a new command discarded the history.
+    Redo,
 }
 
 /// Result of applying an engine command.
@@ -43,13 +84,31 @@ pub enum EngineCommandResult {
     SnapshotCommit(SnapshotCommitResult),
     /// Snapshot commit was routed for approval.
     SnapshotCommitRouted(RoutedForApprovalResult),
+    /// Snapshot commit was queued.
+    SnapshotCommitQueued(CommitTicket),
+    /// Queued snapshot commits were applied, in queue order.
+    SnapshotCommitQueueDrained(Vec<CommitTicket>),
     /// Profile was created (or already existed with same content).
     ProfileCreate,
     /// Profile default was updated.
     ProfileSetDefault,
+    /// Setting was stored or cleared.
+    SettingsSet,
+    /// Journal entry that was undone.
+    Undo(JournalEntry),
+    /// Journal entry that was redone.
+    Redo(JournalEntry),
 }
 
 /// Apply an engine command with policy provider and approval router.
+///
+/// The payload is validated and normalized by
+/// [`validate_engine_command`](super::validate::validate_engine_command)
+/// before any I/O; malformed IDs, refs or option combinations fail with
+/// `InvalidInput`.
+///
+/// Every non-dry-run `SnapshotCommit`, including one rejected by validation,
+/// logs and counts its [`SnapshotCommitClass`] under the `snapshot_commit` op.
 pub fn apply_engine_command(
     cmd: EngineCommand,
     conn: &mut Connection,
@@ -57,13 +116,57 @@ pub fn apply_engine_command(
     policy_provider: &dyn PolicyProvider,
     approval_router: &dyn ApprovalRouter,
 ) -> Result<EngineCommandResult> {
-    match cmd {
+    let commit_leaf = match &cmd {
+        EngineCommand::SnapshotCommit {
+            leaf_ep_id,
+            options,
+            ..
+        } if !options.dry_run => Some(leaf_ep_id.clone()),
+        _ => None,
+    };
+    let result = dispatch_engine_command(cmd, conn, cas, policy_provider, approval_router);
+    if let Some(leaf_ep_id) = commit_leaf {
+        let class = classify_snapshot_commit(&result);
+        log_op_outcome!(
+            "snapshot_commit",
+            outcome = &class.label(),
+            leaf_ep_id = leaf_ep_id.as_str()
+        );
+    }
+    result
+}
+
+fn classify_snapshot_commit(result: &Result<EngineCommandResult>) -> SnapshotCommitClass {
+    match result {
+        Ok(EngineCommandResult::SnapshotCommit(r)) if r.was_duplicate => {
+            SnapshotCommitClass::Duplicate
+        }
+        Ok(EngineCommandResult::SnapshotCommitRouted(_)) => SnapshotCommitClass::Routed,
+        Ok(_) => SnapshotCommitClass::Committed,
+        Err(e) if e.kind() == ExErrorKind::PolicyDenied => SnapshotCommitClass::Denied,
+        Err(e) => SnapshotCommitClass::Failed(e.kind()),
+    }
+}
+
+fn dispatch_engine_command(
+    cmd: EngineCommand,
+    conn: &mut Connection,
+    cas: &FsStore,
+    policy_provider: &dyn PolicyProvider,
+    approval_router: &dyn ApprovalRouter,
+) -> Result<EngineCommandResult> {
+    match super::validate::validate_engine_command(cmd)? {
         EngineCommand::SnapshotCommit {
             leaf_ep_id,
             policy_ref,
             profile_ref,
             options,
         } => {
+            // An explicit ref wins over the repository's default setting
+            let policy_ref = match policy_ref {
+                Some(r) => Some(r),
+                None => default_policy_ref(conn)?,
+            };
             let outcome = crate::snapshot::snapshot_commit_by_leaf(
                 &leaf_ep_id,
                 policy_ref.as_deref(),
@@ -81,17 +184,128 @@ pub fn apply_engine_command(
                 }
             }
         }
+        EngineCommand::SnapshotCommitEnqueue {
+            leaf_ep_id,
+            policy_ref,
+            profile_ref,
+            options,
+        } => Ok(EngineCommandResult::SnapshotCommitQueued(enqueue_commit(
+            conn,
+            leaf_ep_id,
+            policy_ref,
+            profile_ref,
+            options,
+        )?)),
+        EngineCommand::SnapshotCommitQueueDrain { limit } => {
+            Ok(EngineCommandResult::SnapshotCommitQueueDrained(
+                drain_commit_queue(conn, cas, policy_provider, approval_router, limit)?,
+            ))
+        }
         EngineCommand::ProfileCreate {
             profile_ref,
             payload_json,
             ..
         } => {
+            // Bases must already exist; this also rejects self-extension
+            resolve_profile_payload(conn, &profile_ref, &payload_json)?;
             create_profile(conn, &profile_ref, &payload_json)?;
             Ok(EngineCommandResult::ProfileCreate)
         }
-        EngineCommand::ProfileSetDefault { profile_ref } => {
-            set_default_profile(conn, &profile_ref)?;
+        EngineCommand::ProfileSetDefault {
+            profile_ref,
+            environment,
+        } => {
+            match environment {
+                Some(env) => set_environment_default_profile(conn, &env, &profile_ref)?,
+                None => set_default_profile(conn, &profile_ref)?,
+            }
             Ok(EngineCommandResult::ProfileSetDefault)
         }
+        EngineCommand::SettingsSet { key, value } => {
+            settings_set(conn, &key, value)?;
+            Ok(EngineCommandResult::SettingsSet)
+        }
+        EngineCommand::Undo => Ok(EngineCommandResult::Undo(undo(
+            conn,
+            cas,
+            policy_provider,
+            approval_router,
+        )?)),
+        EngineCommand::Redo => Ok(EngineCommandResult::Redo(redo(
+            conn,
+            cas,
+            policy_provider,
+            approval_router,
+        )?)),
     }
 }
+
+/// Per-call options for [`apply_engine_command_with_options`] and
+/// [`apply_command_with_options`](super::command::apply_command_with_options).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CommandOptions {
+    /// Run validation and the full mutation, return the would-be result, then
+    /// roll every database write back.
+    ///
+    /// IDs in a dry-run result are freshly allocated placeholders that are
+    /// never persisted; a later real run allocates different ones.
+    pub dry_run: bool,
+}
+
+/// Apply an engine command with per-call options.
+///
+/// With `dry_run`, `SnapshotCommit` uses its own dry-run path (digests
+/// computed, nothing persisted) and every other command runs inside a
+/// savepoint that is rolled back. Errors are identical to a real run.
+pub fn apply_engine_command_with_options(
+    cmd: EngineCommand,
+    options: CommandOptions,
+    conn: &mut Connection,
+    cas: &FsStore,
+    policy_provider: &dyn PolicyProvider,
+    approval_router: &dyn ApprovalRouter,
+) -> Result<EngineCommandResult> {
+    if !options.dry_run {
+        return apply_engine_command(cmd, conn, cas, policy_provider, approval_router);
+    }
+    let cmd = match cmd {
+        EngineCommand::SnapshotCommit {
+            leaf_ep_id,
+            policy_ref,
+            profile_ref,
+            options,
+        } => EngineCommand::SnapshotCommit {
+            leaf_ep_id,
+            policy_ref,
+            profile_ref,
+            options: SnapshotOptions {
+                dry_run: true,
+                ..options
+            },
+        },
+        other => other,
+    };
+    with_rollback(conn, |conn| {
+        apply_engine_command(cmd, conn, cas, policy_provider, approval_router)
+    })
+}
+
+/// Run `f` inside a savepoint that is always rolled back, discarding every
+/// write `f` made. Nests inside an open transaction.
+pub(crate) fn with_rollback<T>(
+    conn: &mut Connection,
+    f: impl FnOnce(&mut Connection) -> Result<T>,
+) -> Result<T> {
+    let persistence = |e: rusqlite::Error| {
+        ExError::new(ExErrorKind::Persistence)
+            .with_op("dry_run")
+            .with_message(e.to_string())
+    };
+
+    conn.execute_batch("SAVEPOINT engine_dry_run")
+        .map_err(persistence)?;
+    let result = f(conn);
+    conn.execute_batch("ROLLBACK TO engine_dry_run; RELEASE engine_dry_run")
+        .map_err(persistence)?;
+    result
+}
@@ -6,33 +6,60 @@
 
 #![allow(clippy::result_large_err)]
 
+use ettlex_core::approval_router::ApprovalRequestPayload;
 use ettlex_core::candidate_resolver::{
     compute_dry_run_resolution, AmbiguityPolicy, CandidateEntry, DryRunConstraintStatus,
 };
 use ettlex_core::diff;
-use ettlex_core::diff::human_summary::render_human_summary;
+use ettlex_core::diff::human_summary::{render_human_summary_with, SummaryOptions};
+use ettlex_core::diff::ignore::DiffIgnoreList;
 use ettlex_core::diff::model::SnapshotDiff;
+use ettlex_core::diff::severity::SeverityRules;
+use ettlex_core::diff::ParsedManifest;
 use ettlex_core::errors::{ExError, ExErrorKind};
-use ettlex_core::{log_op_end, log_op_error, log_op_start};
+use ettlex_core::logging_facility::{op_outcome, slow_op};
+use ettlex_core::{log_op_end, log_op_error, log_op_slow, log_op_start};
 use ettlex_store::cas::FsStore;
 use ettlex_store::errors::Result;
 use ettlex_store::profile::{
-    fetch_approval_row, list_approval_rows_paginated, list_profiles_paginated,
-    load_default_profile, load_profile_full, ApprovalRow,
+    approval_payload_swept_at, fetch_approval_row, list_approval_events,
+    list_approval_rows_paginated, list_profiles_paginated, load_default_profile,
+    load_environment_default_profile, load_profile_full, resolve_profile_payload, ApprovalEventRow,
+    ApprovalRow,
 };
-use ettlex_store::repo::SqliteRepo;
+use ettlex_store::repo::{PageKey, SqliteRepo};
+use ettlex_store::snapshot::id_scheme::resolve_snapshot_id;
+use ettlex_store::snapshot::ledger::{verify_ledger, LedgerVerifyReport};
 use ettlex_store::snapshot::query::{
     fetch_manifest_bytes_by_digest, fetch_snapshot_manifest_digest, fetch_snapshot_row,
     list_snapshot_rows,
 };
 use rusqlite::Connection;
 
+use crate::commands::architecture_report::build_architecture_report;
+use crate::commands::assignment::{assignees_of, ettle_assignees, ettles_by_owner};
+use crate::commands::commit_queue::{get_commit_ticket, CommitTicket};
+use crate::commands::constraint::{effective_constraints, list_constraint_attachments};
+use crate::commands::content_spill::hydrate_ettle_content;
+use crate::commands::ept_diagnose::diagnose_ept;
+use crate::commands::ettle::{handle_ettle_list_referrers, resolve_ettle_ref};
+use crate::commands::heads::{list_heads, HeadsList};
+use crate::commands::leaf_sample::sample_leaves;
+use crate::commands::query_cancel::QueryInterrupt;
+use crate::commands::query_trace::{self, ExplainedQueryResult, QueryOptions, QueryTrace};
 use crate::commands::read_tools::{
-    ApprovalGetResult, ApprovalListItem, ApprovalPage, DecisionPage, EttleGetResult, EttlePage,
-    ListOptions, ManifestGetResult, Page, PolicyExportResult, PolicyProjectForHandoffResult,
-    PolicyReadResult, PredicatePreviewResult, PreviewStatus, ProfileGetResult, ProfilePage,
-    ProfileResolveResult, SnapshotGetResult, StateVersionResult,
+    ApprovalGetResult, ApprovalListItem, ApprovalPage, AssignedEttle, CommentPage,
+    ConstraintAttachment, DecisionPage, EffectiveConstraints, EptDiagnosis, EttleGetResult,
+    EttlePage, EttleReferrers, LeafSample, LeafSampleFilter, ListOptions, ManifestGetResult, Page,
+    PolicyExportResult, PolicyProjectForHandoffResult, PolicyReadResult, PredicatePreviewResult,
+    PreviewStatus, ProfileGetResult, ProfilePage, ProfileResolveResult, RegisteredRoot, RootHead,
+    RootRegistry, SnapshotGetResult, StateVersionResult, StoreFingerprint, StoreStats,
+    TableRowCount, WorkspaceSummary, STORE_STATS_TABLES,
 };
+use crate::commands::root::{active_root_ids, registered_root, root_registry};
+use crate::commands::settings::{settings_get, SettingEntry};
+use crate::commands::snapshot_compare::{compare_snapshots, SnapshotCompareMatrix};
+use crate::commands::store_fingerprint::compute_store_fingerprint;
 
 // ---------------------------------------------------------------------------
 // Snapshot diff types (re-exported for backward compat)
@@ -42,6 +69,7 @@ use crate::commands::read_tools::{
 #[derive(Debug, Clone)]
 pub enum SnapshotRef {
     /// Resolved via the `snapshots` table (`snapshot_id → manifest_digest → CAS`).
+    /// Accepts a full snapshot ID or an unambiguous prefix of one.
     SnapshotId(String),
     /// Resolved directly from CAS by manifest digest.
     ManifestDigest(String),
@@ -70,17 +98,66 @@ pub enum EngineQuery {
         a_ref: SnapshotRef,
         /// Reference to snapshot B
         b_ref: SnapshotRef,
+        /// Severity classification rules (defaults when empty)
+        severity_rules: SeverityRules,
+        /// Changes to suppress. `None` uses the `diff_ignore` key of snapshot
+        /// B's profile payload, if present.
+        ignore: Option<DiffIgnoreList>,
+        /// Verbosity and message catalog of `human_summary` (default:
+        /// detailed English)
+        summary: SummaryOptions,
+        /// Load changed EPs' content from CAS and add line diffs
+        /// (`content_diffs`)
+        include_content: bool,
     },
 
     // ── State ─────────────────────────────────────────────────────────────────
-    /// Get the current schema version and semantic head digest.
+    /// Get the current state version and semantic head digest, plus a
+    /// [`WorkspaceSummary`] of counts and per-root heads. Cheap enough to poll.
     StateGetVersion,
+    /// Row counts of the main store tables plus per-op latency percentiles and
+    /// outcome counts recorded by this process (see
+    /// `ettlex_core::logging_facility::{slow_op, op_outcome}`).
+    StoreStats,
+    /// A single digest over all active semantic content (ettles and
+    /// relations, timestamps excluded), for cheaply comparing two stores or
+    /// detecting out-of-band modification.
+    StoreFingerprint,
 
     // ── Ettle ─────────────────────────────────────────────────────────────────
     /// Get an ettle by ID.
     EttleGet { ettle_id: String },
     /// List ettles with pagination.
     EttleList(ListOptions),
+    /// List leaf Ettles (active, with no active refinement child) in ID
+    /// order. With `root_ettle_id`, only leaves under that root are listed.
+    /// `options.limit` and `options.cursor` apply; other filters are ignored.
+    LeafList {
+        root_ettle_id: Option<String>,
+        options: ListOptions,
+    },
+    /// Diagnose the refinement path from a leaf Ettle to its root: the
+    /// resolved path plus any ambiguous parents, broken links or cycles,
+    /// each with a suggested fix.
+    EptDiagnose { leaf_ettle_id: String },
+    /// Draw a reproducible random sample of up to `count` leaf Ettles
+    /// matching `filter`, for audit spot-checks. The same seed, filter and
+    /// state version always select the same leaves (see
+    /// [`super::leaf_sample`]).
+    SampleLeaves {
+        seed: u64,
+        count: usize,
+        filter: LeafSampleFilter,
+    },
+    /// List the `refinement` relations pointing at an Ettle (ID or slug)
+    /// from its parents, active and tombstoned separately.
+    EttleListReferrers { ettle_id: String },
+    /// List the non-tombstoned Ettles a principal owns — and, with
+    /// `include_reviewing`, those they review — by Ettle ID.
+    EttleListByOwner {
+        owner: String,
+        include_reviewing: bool,
+    },
 
     // ── Constraint ────────────────────────────────────────────────────────────
     /// Get a constraint by ID (including tombstoned).
@@ -90,12 +167,41 @@ pub enum EngineQuery {
         family: String,
         include_tombstoned: bool,
     },
+    /// List the Ettles a constraint Ettle is attached to via `constraint`
+    /// relations, in attachment order. Tombstoned attachments are included
+    /// only when `include_tombstoned` is set.
+    ConstraintListAttachments {
+        constraint_id: String,
+        include_tombstoned: bool,
+    },
+    /// List active `constraint` relations whose source or target Ettle is
+    /// missing or tombstoned (what `ConstraintSweepOrphans` would remove).
+    ConstraintListOrphans,
+    /// Constraints in force for an Ettle (ID or slug): those attached to
+    /// it plus those inherited along its refinement path, after `scope` and
+    /// `override` relation properties are applied.
+    EttleEffectiveConstraints { ettle_id: String },
+
+    // ── Root registry ─────────────────────────────────────────────────────────
+    /// Get a registered root with its head snapshot. `NotFound` when the
+    /// Ettle is not registered.
+    RootGet { ettle_id: String },
+    /// List registered roots (retired ones only with `include_retired`) and
+    /// the implicit roots missing from the registry.
+    RootList { include_retired: bool },
 
     // ── Decision ─────────────────────────────────────────────────────────────
     /// Get a decision by ID (including tombstoned).
     DecisionGet { decision_id: String },
     /// List all decisions with pagination.
     DecisionList(ListOptions),
+    /// Case-insensitive text search across decision title, decision_text
+    /// and rationale, optionally restricted to one status.
+    DecisionSearch {
+        text: String,
+        status_filter: Option<String>,
+        options: ListOptions,
+    },
     /// List decisions linked to a target entity.
     DecisionListByTarget {
         target_kind: String,
@@ -109,12 +215,29 @@ pub enum EngineQuery {
         include_ancestors: bool,
     },
 
+    // ── Comment ──────────────────────────────────────────────────────────────
+    /// Get a single comment by ID.
+    CommentGet { comment_id: String },
+    /// List comments on a target in (created_at, id) order. Comments in
+    /// resolved threads are skipped unless `include_resolved` is set.
+    CommentList {
+        target_kind: String,
+        target_id: String,
+        include_resolved: bool,
+        options: ListOptions,
+    },
+
     // ── Snapshot / Manifest ───────────────────────────────────────────────────
-    /// Get a snapshot ledger row by snapshot ID.
+    /// Get a snapshot ledger row by snapshot ID (or unambiguous ID prefix).
     SnapshotGet { snapshot_id: String },
-    /// List snapshot rows, optionally filtered by root ettle ID.
-    SnapshotList { ettle_id: Option<String> },
-    /// Get manifest bytes for a snapshot by snapshot ID.
+    /// List snapshot rows, optionally filtered by root ettle ID. With
+    /// `registered_only`, rows for roots that are not active in the root
+    /// registry are skipped.
+    SnapshotList {
+        ettle_id: Option<String>,
+        registered_only: bool,
+    },
+    /// Get manifest bytes for a snapshot by snapshot ID (or unambiguous ID prefix).
     ManifestGetBySnapshot { snapshot_id: String },
     /// Get manifest bytes for a snapshot by manifest digest.
     ManifestGetByDigest { manifest_digest: String },
@@ -122,8 +245,13 @@ pub enum EngineQuery {
     // ── Profile ──────────────────────────────────────────────────────────────
     /// Get a profile by reference.
     ProfileGet { profile_ref: String },
-    /// Resolve a profile (None → use default).
-    ProfileResolve { profile_ref: Option<String> },
+    /// Resolve a profile. With no `profile_ref`, use the default for
+    /// `environment` (falling back to the global default), or the global
+    /// default when no environment is given.
+    ProfileResolve {
+        profile_ref: Option<String>,
+        environment: Option<String>,
+    },
     /// Get the default profile.
     ProfileGetDefault,
     /// List profiles with pagination.
@@ -136,6 +264,8 @@ pub enum EngineQuery {
     ApprovalList(ListOptions),
     /// List approval requests filtered by kind (NotImplemented in Phase 1).
     ApprovalListByKind { kind: String, options: ListOptions },
+    /// List the lifecycle events of an approval request, oldest first.
+    ApprovalHistory { approval_token: String },
 
     // ── Predicate preview ────────────────────────────────────────────────────
     /// Preview constraint predicate resolution without side effects.
@@ -143,6 +273,9 @@ pub enum EngineQuery {
         profile_ref: Option<String>,
         context: serde_json::Value,
         candidates: Vec<String>,
+        /// Simulate with this ambiguity policy instead of the profile's.
+        /// Nothing is written; the profile itself is left untouched.
+        ambiguity_policy_override: Option<AmbiguityPolicy>,
     },
 
     // ── Policy ───────────────────────────────────────────────────────────────
@@ -166,6 +299,32 @@ pub enum EngineQuery {
     // ── Snapshot head ─────────────────────────────────────────────────────────
     /// Get the manifest digest of the most recent committed snapshot for an ettle.
     SnapshotGetHead { realised_ettle_id: String },
+    /// The head snapshot of every root with its age and whether the working
+    /// state still matches its semantic digest.
+    HeadsList,
+    /// State of a queued snapshot commit (see
+    /// `EngineCommand::SnapshotCommitEnqueue`). `NotFound` for an unknown
+    /// ticket.
+    SnapshotCommitTicket { ticket: String },
+
+    // ── Ledger ────────────────────────────────────────────────────────────────
+    /// Recompute the snapshot ledger hash chain and report the first break.
+    LedgerVerify,
+    /// Compare N snapshots pairwise for semantic equality (same
+    /// `semantic_manifest_digest`) and group the equal ones into clusters.
+    SnapshotCompareMatrix { refs: Vec<SnapshotRef> },
+
+    // ── Settings ──────────────────────────────────────────────────────────────
+    /// Effective value and source of one known setting, or of every known
+    /// setting when `key` is `None`. Updates go through
+    /// `EngineCommand::SettingsSet`.
+    SettingsGet { key: Option<String> },
+
+    // ── Reports ───────────────────────────────────────────────────────────────
+    /// Assemble the "state of the architecture" report: tree analytics, the
+    /// most recent `recent_snapshot_limit` snapshots, open decisions,
+    /// constraint coverage and pending approvals.
+    ArchitectureReport { recent_snapshot_limit: u32 },
 }
 
 // ---------------------------------------------------------------------------
@@ -181,21 +340,38 @@ pub enum EngineQueryResult {
 
     // ── State ─────────────────────────────────────────────────────────────────
     StateVersion(StateVersionResult),
+    StoreStats(StoreStats),
+    StoreFingerprint(StoreFingerprint),
 
     // ── Ettle ─────────────────────────────────────────────────────────────────
     EttleGet(EttleGetResult),
     EttleList(EttlePage),
+    LeafList(EttlePage),
+    EptDiagnose(EptDiagnosis),
+    SampleLeaves(LeafSample),
+    EttleListReferrers(EttleReferrers),
+    EttleListByOwner(Vec<AssignedEttle>),
 
     // ── Constraint ────────────────────────────────────────────────────────────
     ConstraintGet(ettlex_core::model::Constraint),
     ConstraintListByFamily(Vec<ettlex_core::model::Constraint>),
+    ConstraintListAttachments(Vec<ConstraintAttachment>),
+    ConstraintListOrphans(Vec<ettlex_store::model::RelationRecord>),
+    EttleEffectiveConstraints(EffectiveConstraints),
+    RootGet(RegisteredRoot),
+    RootList(RootRegistry),
 
     // ── Decision ─────────────────────────────────────────────────────────────
     DecisionGet(ettlex_core::model::Decision),
     DecisionList(DecisionPage),
+    DecisionSearch(DecisionPage),
     DecisionListByTarget(Vec<ettlex_core::model::Decision>),
     EttleListDecisions(Vec<ettlex_core::model::Decision>),
 
+    // ── Comment ──────────────────────────────────────────────────────────────
+    CommentGet(ettlex_store::model::CommentRecord),
+    CommentList(CommentPage),
+
     // ── Snapshot / Manifest ───────────────────────────────────────────────────
     SnapshotGet(SnapshotGetResult),
     SnapshotList(Vec<SnapshotGetResult>),
@@ -209,6 +385,7 @@ pub enum EngineQueryResult {
     // ── Approval ─────────────────────────────────────────────────────────────
     ApprovalGet(ApprovalGetResult),
     ApprovalList(ApprovalPage),
+    ApprovalHistory(Vec<ApprovalEventRow>),
 
     // ── Predicate preview ────────────────────────────────────────────────────
     PredicatePreview(PredicatePreviewResult),
@@ -228,6 +405,23 @@ pub enum EngineQueryResult {
     // ── Snapshot head ─────────────────────────────────────────────────────────
     /// Result of a `SnapshotGetHead` query: manifest digest of the head, or None.
     SnapshotGetHead(Option<String>),
+    HeadsList(HeadsList),
+    /// Result of a `SnapshotCommitTicket` query.
+    SnapshotCommitTicket(CommitTicket),
+
+    // ── Ledger ────────────────────────────────────────────────────────────────
+    /// Result of a `LedgerVerify` query.
+    LedgerVerify(LedgerVerifyReport),
+    /// Result of a `SnapshotCompareMatrix` query.
+    SnapshotCompareMatrix(SnapshotCompareMatrix),
+
+    // ── Settings ──────────────────────────────────────────────────────────────
+    /// Result of a `SettingsGet` query, sorted by key.
+    SettingsGet(Vec<SettingEntry>),
+
+    // ── Reports ───────────────────────────────────────────────────────────────
+    /// Result of an `ArchitectureReport` query.
+    ArchitectureReport(Box<ettlex_core::render::ArchitectureReport>),
 }
 
 // ---------------------------------------------------------------------------
@@ -235,10 +429,14 @@ pub enum EngineQueryResult {
 // ---------------------------------------------------------------------------
 
 /// Resolve a `SnapshotRef` to raw manifest bytes.
-fn resolve_ref(snapshot_ref: &SnapshotRef, conn: &Connection, cas: &FsStore) -> Result<Vec<u8>> {
+pub(crate) fn resolve_ref(
+    snapshot_ref: &SnapshotRef,
+    conn: &Connection,
+    cas: &FsStore,
+) -> Result<Vec<u8>> {
     match snapshot_ref {
         SnapshotRef::SnapshotId(id) => {
-            let digest = fetch_snapshot_manifest_digest(conn, id)?;
+            let digest = fetch_snapshot_manifest_digest(conn, &resolve_snapshot_id(conn, id)?)?;
             fetch_manifest_bytes_by_digest(cas, &digest)
         }
         SnapshotRef::ManifestDigest(digest) => fetch_manifest_bytes_by_digest(cas, digest),
@@ -256,6 +454,8 @@ fn snapshot_row_to_result(row: ettlex_store::snapshot::query::SnapshotRow) -> Sn
         policy_ref: row.policy_ref,
         profile_ref: row.profile_ref,
         status: row.status,
+        message: row.message,
+        annotations: row.annotations,
     }
 }
 
@@ -283,6 +483,20 @@ fn sha256_hex(data: &[u8]) -> String {
 // apply_engine_query
 // ---------------------------------------------------------------------------
 
+/// EP content looked up in CAS by `ep_digest`. A missing blob, or one that
+/// is not an EP content document, is reported as no content.
+struct CasEpContent<'a>(&'a FsStore);
+
+impl diff::EpContentSource for CasEpContent<'_> {
+    fn ep_content(&self, ep_digest: &str) -> std::result::Result<Option<diff::EpContent>, ExError> {
+        match self.0.read(ep_digest) {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes).ok()),
+            Err(e) if e.kind() == ExErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
 /// Apply a read-only engine query.
 ///
 /// All branches use only `&Connection` (shared, non-mutable) and `&FsStore`.
@@ -293,6 +507,10 @@ fn sha256_hex(data: &[u8]) -> String {
 /// Pass `None` for non-policy queries. Policy queries with `policy_provider = None`
 /// return `Err(NotImplemented)`.
 ///
+/// Every query's latency is recorded for [`EngineQuery::StoreStats`]; queries
+/// over the configured slow-op threshold log a warning with the Ettle ID (if
+/// any) and result row count.
+///
 /// # Errors
 ///
 /// Error kinds depend on the query; see individual variant documentation.
@@ -301,10 +519,49 @@ pub fn apply_engine_query(
     conn: &Connection,
     cas: &FsStore,
     policy_provider: Option<&dyn ettlex_core::policy_provider::PolicyProvider>,
+) -> Result<EngineQueryResult> {
+    let query = resolve_query_ettle_refs(conn, query)?;
+    apply_resolved_query(query, conn, cas, policy_provider)
+}
+
+/// `apply_engine_query` after Ettle references have been resolved.
+fn apply_resolved_query(
+    mut query: EngineQuery,
+    conn: &Connection,
+    cas: &FsStore,
+    policy_provider: Option<&dyn ettlex_core::policy_provider::PolicyProvider>,
+) -> Result<EngineQueryResult> {
+    let (op, _) = query_trace::query_plan(&query);
+    let ettle_id = query_ettle_ref(&mut query).cloned();
+    let start = std::time::Instant::now();
+    let result = dispatch_query(query, conn, cas, policy_provider);
+    let elapsed = start.elapsed().as_millis() as u64;
+    log_op_slow!(
+        op,
+        duration_ms = elapsed,
+        ettle_id = ettle_id.as_deref(),
+        row_count = result.as_ref().ok().map(query_trace::result_row_count),
+        failed = result.is_err(),
+    );
+    result
+}
+
+fn dispatch_query(
+    query: EngineQuery,
+    conn: &Connection,
+    cas: &FsStore,
+    policy_provider: Option<&dyn ettlex_core::policy_provider::PolicyProvider>,
 ) -> Result<EngineQueryResult> {
     match query {
         // ── SnapshotDiff ──────────────────────────────────────────────────────
-        EngineQuery::SnapshotDiff { a_ref, b_ref } => {
+        EngineQuery::SnapshotDiff {
+            a_ref,
+            b_ref,
+            severity_rules,
+            ignore,
+            summary,
+            include_content,
+        } => {
             log_op_start!("snapshot_diff");
             let start = std::time::Instant::now();
 
@@ -312,8 +569,24 @@ pub fn apply_engine_query(
                 let a_bytes = resolve_ref(&a_ref, conn, cas)?;
                 let b_bytes = resolve_ref(&b_ref, conn, cas)?;
 
-                let structured_diff = diff::engine::compute_diff(&a_bytes, &b_bytes)?;
-                let human_summary = render_human_summary(&structured_diff);
+                let a = ParsedManifest::parse(&a_bytes)?;
+                let b = if a_bytes == b_bytes {
+                    None
+                } else {
+                    Some(ParsedManifest::parse(&b_bytes)?)
+                };
+                let b = b.as_ref().unwrap_or(&a);
+                let mut structured_diff =
+                    diff::engine::compute_diff_parsed(&a, b, &severity_rules)?;
+                if include_content {
+                    diff::add_content_diffs(&mut structured_diff, &a, b, &CasEpContent(cas))?;
+                }
+                let ignore = match ignore {
+                    Some(list) => list,
+                    None => profile_diff_ignore(conn, &b.manifest.profile_ref)?,
+                };
+                ignore.apply(&mut structured_diff);
+                let human_summary = render_human_summary_with(&structured_diff, &summary);
 
                 Ok(EngineQueryResult::SnapshotDiff(Box::new(
                     SnapshotDiffResult {
@@ -339,34 +612,11 @@ pub fn apply_engine_query(
             log_op_start!("state_get_version");
             let start = std::time::Instant::now();
 
-            let result = (|| -> Result<EngineQueryResult> {
-                let version: u64 = conn
-                    .query_row("SELECT COUNT(*) FROM command_log", [], |row| row.get(0))
-                    .map_err(|e| {
-                        ExError::new(ExErrorKind::Persistence)
-                            .with_op("state_get_version")
-                            .with_message(e.to_string())
-                    })?;
-
-                let head_digest: Option<String> = conn
-                    .query_row(
-                        "SELECT semantic_manifest_digest FROM snapshots
-                         ORDER BY created_at DESC, snapshot_id DESC LIMIT 1",
-                        [],
-                        |row| row.get(0),
-                    )
-                    .optional()
-                    .map_err(|e| {
-                        ExError::new(ExErrorKind::Persistence)
-                            .with_op("state_get_version")
-                            .with_message(e.to_string())
-                    })?;
-
-                Ok(EngineQueryResult::StateVersion(StateVersionResult {
-                    state_version: version,
-                    semantic_head_digest: head_digest,
-                }))
-            })();
+            let result = with_read_savepoint(conn, || {
+                let mut state = read_state_version(conn)?;
+                state.summary = Some(read_workspace_summary(conn)?);
+                Ok(EngineQueryResult::StateVersion(state))
+            });
 
             let elapsed = start.elapsed().as_millis() as u64;
             match &result {
@@ -379,19 +629,61 @@ pub fn apply_engine_query(
             result
         }
 
+        // ── StoreStats ────────────────────────────────────────────────────────
+        EngineQuery::StoreStats => {
+            log_op_start!("store_stats");
+            let start = std::time::Instant::now();
+
+            let result = Ok(EngineQueryResult::StoreStats(StoreStats {
+                tables: table_row_counts(conn, STORE_STATS_TABLES),
+                op_latencies: slow_op::op_latency_stats(),
+                op_outcomes: op_outcome::op_outcome_counts(),
+            }));
+
+            let elapsed = start.elapsed().as_millis() as u64;
+            log_op_end!("store_stats", duration_ms = elapsed);
+            result
+        }
+
+        // ── StoreFingerprint ──────────────────────────────────────────────────
+        EngineQuery::StoreFingerprint => {
+            log_op_start!("store_fingerprint");
+            let start = std::time::Instant::now();
+
+            let result = with_read_savepoint(conn, || {
+                compute_store_fingerprint(conn).map(EngineQueryResult::StoreFingerprint)
+            });
+
+            let elapsed = start.elapsed().as_millis() as u64;
+            match &result {
+                Ok(_) => log_op_end!("store_fingerprint", duration_ms = elapsed),
+                Err(e) => {
+                    let e_clone = e.clone();
+                    log_op_error!("store_fingerprint", e_clone, duration_ms = elapsed);
+                }
+            }
+            result
+        }
+
         // ── EttleGet ──────────────────────────────────────────────────────────
         EngineQuery::EttleGet { ettle_id } => {
             log_op_start!("ettle_get");
             let start = std::time::Instant::now();
 
             let result = (|| -> Result<EngineQueryResult> {
-                let ettle = SqliteRepo::get_ettle(conn, &ettle_id)?.ok_or_else(|| {
-                    ExError::new(ExErrorKind::NotFound)
-                        .with_op("ettle_get")
-                        .with_entity_id(&ettle_id)
-                        .with_message("ettle not found")
-                })?;
-                Ok(EngineQueryResult::EttleGet(EttleGetResult { ettle }))
+                let mut ettle =
+                    SqliteRepo::get_ettle_record(conn, &ettle_id)?.ok_or_else(|| {
+                        ExError::new(ExErrorKind::NotFound)
+                            .with_op("ettle_get")
+                            .with_entity_id(&ettle_id)
+                            .with_message("ettle not found")
+                    })?;
+                hydrate_ettle_content(&mut ettle, cas)?;
+                let assignees = ettle_assignees(conn, &ettle.id)?;
+                Ok(EngineQueryResult::EttleGet(EttleGetResult {
+                    ettle,
+                    assignees,
+                }))
             })();
 
             let elapsed = start.elapsed().as_millis() as u64;
@@ -412,7 +704,7 @@ pub fn apply_engine_query(
 
             let result = (|| -> Result<EngineQueryResult> {
                 let limit = opts.effective_limit();
-                let after_id = opts.decode_cursor();
+                let after_id: Option<String> = opts.after_key()?;
                 let raw = SqliteRepo::list_ettles_paginated(
                     conn,
                     opts.prefix_filter.as_deref(),
@@ -420,8 +712,12 @@ pub fn apply_engine_query(
                     limit + 1, // over-fetch by 1 to detect has_more
                 )?;
 
-                let page =
-                    Page::from_overshot(raw, limit, |e: &ettlex_core::model::Ettle| e.id.clone());
+                let page = Page::from_overshot(
+                    raw,
+                    limit,
+                    &opts.cursor_codec,
+                    |e: &ettlex_core::model::Ettle| e.id.clone(),
+                );
                 Ok(EngineQueryResult::EttleList(page))
             })();
 
@@ -436,6 +732,125 @@ pub fn apply_engine_query(
             result
         }
 
+        // ── LeafList ──────────────────────────────────────────────────────────
+        EngineQuery::LeafList {
+            root_ettle_id,
+            options,
+        } => {
+            log_op_start!("leaf_list");
+            let start = std::time::Instant::now();
+            let result = (|| -> Result<EngineQueryResult> {
+                if let Some(root) = &root_ettle_id {
+                    if SqliteRepo::get_ettle(conn, root)?.is_none() {
+                        return Err(ExError::new(ExErrorKind::NotFound)
+                            .with_op("leaf_list")
+                            .with_entity_id(root)
+                            .with_message("root ettle not found"));
+                    }
+                }
+                let limit = options.effective_limit();
+                let after_id: Option<String> = options.after_key()?;
+                let raw = SqliteRepo::list_leaf_ettles_paginated(
+                    conn,
+                    root_ettle_id.as_deref(),
+                    after_id.as_deref(),
+                    limit + 1,
+                )?;
+                let page = Page::from_overshot(
+                    raw,
+                    limit,
+                    &options.cursor_codec,
+                    |e: &ettlex_core::model::Ettle| e.id.clone(),
+                );
+                Ok(EngineQueryResult::LeafList(page))
+            })();
+            let elapsed = start.elapsed().as_millis() as u64;
+            match &result {
+                Ok(_) => log_op_end!("leaf_list", duration_ms = elapsed),
+                Err(e) => {
+                    let e_clone = e.clone();
+                    log_op_error!("leaf_list", e_clone, duration_ms = elapsed);
+                }
+            }
+            result
+        }
+
+        // ── EptDiagnose ───────────────────────────────────────────────────────
+        EngineQuery::EptDiagnose { leaf_ettle_id } => {
+            log_op_start!("ept_diagnose");
+            let start = std::time::Instant::now();
+            let result = diagnose_ept(conn, &leaf_ettle_id).map(EngineQueryResult::EptDiagnose);
+            let elapsed = start.elapsed().as_millis() as u64;
+            match &result {
+                Ok(_) => log_op_end!("ept_diagnose", duration_ms = elapsed),
+                Err(e) => {
+                    let e_clone = e.clone();
+                    log_op_error!("ept_diagnose", e_clone, duration_ms = elapsed);
+                }
+            }
+            result
+        }
+
+        // ── SampleLeaves ─────────────────────────────────────────────────────────
+        EngineQuery::SampleLeaves {
+            seed,
+            count,
+            filter,
+        } => {
+            log_op_start!("sample_leaves");
+            let start = std::time::Instant::now();
+            // One read savepoint so the sample and its state version agree
+            let result = with_read_savepoint(conn, || {
+                sample_leaves(conn, seed, count, &filter).map(EngineQueryResult::SampleLeaves)
+            });
+            let elapsed = start.elapsed().as_millis() as u64;
+            match &result {
+                Ok(_) => log_op_end!("sample_leaves", duration_ms = elapsed),
+                Err(e) => {
+                    let e_clone = e.clone();
+                    log_op_error!("sample_leaves", e_clone, duration_ms = elapsed);
+                }
+            }
+            result
+        }
+
+        // ── EttleListReferrers ────────────────────────────────────────────────
+        EngineQuery::EttleListReferrers { ettle_id } => {
+            log_op_start!("ettle_list_referrers");
+            let start = std::time::Instant::now();
+            let result = handle_ettle_list_referrers(conn, &ettle_id)
+                .map(EngineQueryResult::EttleListReferrers);
+            let elapsed = start.elapsed().as_millis() as u64;
+            match &result {
+                Ok(_) => log_op_end!("ettle_list_referrers", duration_ms = elapsed),
+                Err(e) => {
+                    let e_clone = e.clone();
+                    log_op_error!("ettle_list_referrers", e_clone, duration_ms = elapsed);
+                }
+            }
+            result
+        }
+
+        // ── EttleListByOwner ──────────────────────────────────────────────────
+        EngineQuery::EttleListByOwner {
+            owner,
+            include_reviewing,
+        } => {
+            log_op_start!("ettle_list_by_owner");
+            let start = std::time::Instant::now();
+            let result = ettles_by_owner(conn, &owner, include_reviewing)
+                .map(EngineQueryResult::EttleListByOwner);
+            let elapsed = start.elapsed().as_millis() as u64;
+            match &result {
+                Ok(_) => log_op_end!("ettle_list_by_owner", duration_ms = elapsed),
+                Err(e) => {
+                    let e_clone = e.clone();
+                    log_op_error!("ettle_list_by_owner", e_clone, duration_ms = elapsed);
+                }
+            }
+            result
+        }
+
         // ── ConstraintGet ─────────────────────────────────────────────────────
         EngineQuery::ConstraintGet { constraint_id } => {
             log_op_start!("constraint_get");
@@ -482,6 +897,100 @@ pub fn apply_engine_query(
             result
         }
 
+        // ── ConstraintListAttachments ─────────────────────────────────────────
+        EngineQuery::ConstraintListAttachments {
+            constraint_id,
+            include_tombstoned,
+        } => {
+            log_op_start!("constraint_list_attachments");
+            let start = std::time::Instant::now();
+            let result = list_constraint_attachments(conn, &constraint_id, include_tombstoned)
+                .map(EngineQueryResult::ConstraintListAttachments);
+            let elapsed = start.elapsed().as_millis() as u64;
+            match &result {
+                Ok(_) => log_op_end!("constraint_list_attachments", duration_ms = elapsed),
+                Err(e) => {
+                    let e_clone = e.clone();
+                    log_op_error!(
+                        "constraint_list_attachments",
+                        e_clone,
+                        duration_ms = elapsed
+                    );
+                }
+            }
+            result
+        }
+
+        // ── ConstraintListOrphans ─────────────────────────────────────────────
+        EngineQuery::ConstraintListOrphans => {
+            log_op_start!("constraint_list_orphans");
+            let start = std::time::Instant::now();
+            let result = SqliteRepo::list_dangling_constraint_relations(conn)
+                .map(EngineQueryResult::ConstraintListOrphans);
+            let elapsed = start.elapsed().as_millis() as u64;
+            match &result {
+                Ok(_) => log_op_end!("constraint_list_orphans", duration_ms = elapsed),
+                Err(e) => {
+                    let e_clone = e.clone();
+                    log_op_error!("constraint_list_orphans", e_clone, duration_ms = elapsed);
+                }
+            }
+            result
+        }
+
+        // ── EttleEffectiveConstraints ────────────────────────────────────────────
+        EngineQuery::EttleEffectiveConstraints { ettle_id } => {
+            log_op_start!("ettle_effective_constraints");
+            let start = std::time::Instant::now();
+            let result = effective_constraints(conn, &ettle_id)
+                .map(EngineQueryResult::EttleEffectiveConstraints);
+            let elapsed = start.elapsed().as_millis() as u64;
+            match &result {
+                Ok(_) => log_op_end!("ettle_effective_constraints", duration_ms = elapsed),
+                Err(e) => {
+                    let e_clone = e.clone();
+                    log_op_error!(
+                        "ettle_effective_constraints",
+                        e_clone,
+                        duration_ms = elapsed
+                    );
+                }
+            }
+            result
+        }
+
+        // ── RootGet ───────────────────────────────────────────────────────────
+        EngineQuery::RootGet { ettle_id } => {
+            log_op_start!("root_get", entity_id = %ettle_id);
+            let start = std::time::Instant::now();
+            let result = registered_root(conn, &ettle_id).map(EngineQueryResult::RootGet);
+            let elapsed = start.elapsed().as_millis() as u64;
+            match &result {
+                Ok(_) => log_op_end!("root_get", duration_ms = elapsed),
+                Err(e) => {
+                    let e_clone = e.clone();
+                    log_op_error!("root_get", e_clone, duration_ms = elapsed);
+                }
+            }
+            result
+        }
+
+        // ── RootList ──────────────────────────────────────────────────────────
+        EngineQuery::RootList { include_retired } => {
+            log_op_start!("root_list");
+            let start = std::time::Instant::now();
+            let result = root_registry(conn, include_retired).map(EngineQueryResult::RootList);
+            let elapsed = start.elapsed().as_millis() as u64;
+            match &result {
+                Ok(_) => log_op_end!("root_list", duration_ms = elapsed),
+                Err(e) => {
+                    let e_clone = e.clone();
+                    log_op_error!("root_list", e_clone, duration_ms = elapsed);
+                }
+            }
+            result
+        }
+
         // ── DecisionGet ───────────────────────────────────────────────────────
         EngineQuery::DecisionGet { decision_id } => {
             log_op_start!("decision_get");
@@ -512,26 +1021,17 @@ pub fn apply_engine_query(
             let start = std::time::Instant::now();
             let result = (|| -> Result<EngineQueryResult> {
                 let limit = opts.effective_limit();
-                let after_key: Option<(i64, String)> = opts.decode_cursor().and_then(|c| {
-                    // Cursor format: "ts_ms|decision_id"
-                    let parts: Vec<&str> = c.splitn(2, '|').collect();
-                    if parts.len() == 2 {
-                        parts[0]
-                            .parse::<i64>()
-                            .ok()
-                            .map(|ts| (ts, parts[1].to_string()))
-                    } else {
-                        None
-                    }
-                });
-                let raw = SqliteRepo::list_decisions_paginated(
-                    conn,
-                    after_key.as_ref().map(|(ts, id)| (*ts, id.as_str())),
-                    limit + 1,
-                )?;
-                let page = Page::from_overshot(raw, limit, |d: &ettlex_core::model::Decision| {
-                    format!("{}|{}", d.created_at.timestamp_millis(), d.decision_id)
-                });
+                let after_key: Option<PageKey> = opts.after_key()?;
+                let raw =
+                    SqliteRepo::list_decisions_paginated(conn, after_key.as_ref(), limit + 1)?;
+                let page = Page::from_overshot(
+                    raw,
+                    limit,
+                    &opts.cursor_codec,
+                    |d: &ettlex_core::model::Decision| {
+                        PageKey::new(d.created_at.timestamp_millis(), d.decision_id.clone())
+                    },
+                );
                 Ok(EngineQueryResult::DecisionList(page))
             })();
             let elapsed = start.elapsed().as_millis() as u64;
@@ -545,6 +1045,123 @@ pub fn apply_engine_query(
             result
         }
 
+        // ── DecisionSearch ────────────────────────────────────────────────────
+        EngineQuery::DecisionSearch {
+            text,
+            status_filter,
+            options,
+        } => {
+            log_op_start!("decision_search");
+            let start = std::time::Instant::now();
+            let result = (|| -> Result<EngineQueryResult> {
+                let text = text.trim();
+                if text.is_empty() {
+                    return Err(ExError::new(ExErrorKind::InvalidInput)
+                        .with_op("decision_search")
+                        .with_message("search text must not be empty"));
+                }
+                let limit = options.effective_limit();
+                let after_key: Option<PageKey> = options.after_key()?;
+                let raw = SqliteRepo::search_decisions_paginated(
+                    conn,
+                    text,
+                    status_filter.as_deref(),
+                    after_key.as_ref(),
+                    limit + 1,
+                )?;
+                let page = Page::from_overshot(
+                    raw,
+                    limit,
+                    &options.cursor_codec,
+                    |d: &ettlex_core::model::Decision| {
+                        PageKey::new(d.created_at.timestamp_millis(), d.decision_id.clone())
+                    },
+                );
+                Ok(EngineQueryResult::DecisionSearch(page))
+            })();
+            let elapsed = start.elapsed().as_millis() as u64;
+            match &result {
+                Ok(_) => log_op_end!("decision_search", duration_ms = elapsed),
+                Err(e) => {
+                    let e_clone = e.clone();
+                    log_op_error!("decision_search", e_clone, duration_ms = elapsed);
+                }
+            }
+            result
+        }
+
+        // ── CommentGet ────────────────────────────────────────────────────────
+        EngineQuery::CommentGet { comment_id } => {
+            log_op_start!("comment_get");
+            let start = std::time::Instant::now();
+            let result = crate::commands::comment::get_comment(conn, &comment_id, "comment_get")
+                .map(EngineQueryResult::CommentGet);
+            let elapsed = start.elapsed().as_millis() as u64;
+            match &result {
+                Ok(_) => log_op_end!("comment_get", duration_ms = elapsed),
+                Err(e) => {
+                    let e_clone = e.clone();
+                    log_op_error!("comment_get", e_clone, duration_ms = elapsed);
+                }
+            }
+            result
+        }
+
+        // ── CommentList ───────────────────────────────────────────────────────
+        EngineQuery::CommentList {
+            target_kind,
+            target_id,
+            include_resolved,
+            options,
+        } => {
+            log_op_start!("comment_list");
+            let start = std::time::Instant::now();
+            let result = (|| -> Result<EngineQueryResult> {
+                let target_id = if target_kind == "ettle" {
+                    resolve_ettle_ref(conn, &target_id)?
+                } else {
+                    target_id
+                };
+                if !SqliteRepo::comment_target_exists(conn, &target_kind, &target_id)? {
+                    return Err(ExError::new(ExErrorKind::NotFound)
+                        .with_op("comment_list")
+                        .with_entity_id(&target_id)
+                        .with_message(format!(
+                            "comment target not found: {} {}",
+                            target_kind, target_id
+                        )));
+                }
+                let limit = options.effective_limit();
+                let after_key: Option<PageKey> = options.after_key()?;
+                let raw = SqliteRepo::list_comments_paginated(
+                    conn,
+                    &target_kind,
+                    &target_id,
+                    include_resolved,
+                    after_key.as_ref(),
+                    limit + 1,
+                )?;
+                let page = Page::from_overshot(
+                    raw,
+                    limit,
+                    &options.cursor_codec,
+                    |c: &ettlex_store::model::CommentRecord| {
+                        PageKey::from_timestamp(&c.created_at, c.id.clone())
+                    },
+                );
+                Ok(EngineQueryResult::CommentList(page))
+            })();
+            let elapsed = start.elapsed().as_millis() as u64;
+            match &result {
+                Ok(_) => log_op_end!("comment_list", duration_ms = elapsed),
+                Err(e) => {
+                    let e_clone = e.clone();
+                    log_op_error!("comment_list", e_clone, duration_ms = elapsed);
+                }
+            }
+            result
+        }
+
         // ── DecisionListByTarget ──────────────────────────────────────────────
         EngineQuery::DecisionListByTarget {
             target_kind,
@@ -623,7 +1240,7 @@ pub fn apply_engine_query(
             log_op_start!("snapshot_get");
             let start = std::time::Instant::now();
             let result = (|| -> Result<EngineQueryResult> {
-                let row = fetch_snapshot_row(conn, &snapshot_id)?;
+                let row = fetch_snapshot_row(conn, &resolve_snapshot_id(conn, &snapshot_id)?)?;
                 Ok(EngineQueryResult::SnapshotGet(snapshot_row_to_result(row)))
             })();
             let elapsed = start.elapsed().as_millis() as u64;
@@ -638,11 +1255,18 @@ pub fn apply_engine_query(
         }
 
         // ── SnapshotList ──────────────────────────────────────────────────────
-        EngineQuery::SnapshotList { ettle_id } => {
+        EngineQuery::SnapshotList {
+            ettle_id,
+            registered_only,
+        } => {
             log_op_start!("snapshot_list");
             let start = std::time::Instant::now();
             let result = (|| -> Result<EngineQueryResult> {
-                let rows = list_snapshot_rows(conn, ettle_id.as_deref())?;
+                let mut rows = list_snapshot_rows(conn, ettle_id.as_deref())?;
+                if registered_only {
+                    let roots = active_root_ids(conn)?;
+                    rows.retain(|r| roots.contains(&r.root_ettle_id));
+                }
                 let results = rows.into_iter().map(snapshot_row_to_result).collect();
                 Ok(EngineQueryResult::SnapshotList(results))
             })();
@@ -662,7 +1286,7 @@ pub fn apply_engine_query(
             log_op_start!("manifest_get_by_snapshot");
             let start = std::time::Instant::now();
             let result = (|| -> Result<EngineQueryResult> {
-                let row = fetch_snapshot_row(conn, &snapshot_id)?;
+                let row = fetch_snapshot_row(conn, &resolve_snapshot_id(conn, &snapshot_id)?)?;
                 let bytes = fetch_manifest_bytes_by_digest(cas, &row.manifest_digest)?;
                 Ok(EngineQueryResult::ManifestGet(ManifestGetResult {
                     snapshot_id: row.snapshot_id,
@@ -766,12 +1390,17 @@ pub fn apply_engine_query(
         }
 
         // ── ProfileResolve ────────────────────────────────────────────────────
-        EngineQuery::ProfileResolve { profile_ref } => {
+        EngineQuery::ProfileResolve {
+            profile_ref,
+            environment,
+        } => {
             log_op_start!("profile_resolve");
             let start = std::time::Instant::now();
             let result = (|| -> Result<EngineQueryResult> {
                 let found = if let Some(ref pref) = profile_ref {
                     load_profile_full(conn, pref)?.map(|t| (pref.clone(), t.1, t.2))
+                } else if let Some(ref env) = environment {
+                    load_environment_default_profile(conn, env)?
                 } else {
                     load_default_profile(conn)?
                 };
@@ -789,10 +1418,13 @@ pub fn apply_engine_query(
                         }
                     }
                     Some((pref, digest, payload)) => {
+                        let resolved = resolve_profile_payload(conn, &pref, &payload)?;
                         Ok(EngineQueryResult::ProfileResolve(ProfileResolveResult {
                             profile_ref: pref,
                             profile_digest: digest,
                             parsed_profile: payload,
+                            effective_profile: resolved.effective,
+                            extends_chain: resolved.extends_chain,
                         }))
                     }
                 }
@@ -843,7 +1475,7 @@ pub fn apply_engine_query(
             let start = std::time::Instant::now();
             let result = (|| -> Result<EngineQueryResult> {
                 let limit = opts.effective_limit();
-                let after_ref = opts.decode_cursor();
+                let after_ref: Option<String> = opts.after_key()?;
                 let raw = list_profiles_paginated(conn, after_ref.as_deref(), limit + 1)?;
                 let as_results: Vec<ProfileGetResult> = raw
                     .into_iter()
@@ -853,9 +1485,12 @@ pub fn apply_engine_query(
                         payload_json: payload,
                     })
                     .collect();
-                let page = Page::from_overshot(as_results, limit, |p: &ProfileGetResult| {
-                    p.profile_ref.clone()
-                });
+                let page = Page::from_overshot(
+                    as_results,
+                    limit,
+                    &opts.cursor_codec,
+                    |p: &ProfileGetResult| p.profile_ref.clone(),
+                );
                 Ok(EngineQueryResult::ProfileList(page))
             })();
             let elapsed = start.elapsed().as_millis() as u64;
@@ -881,6 +1516,15 @@ pub fn apply_engine_query(
                         .with_message("approval request not found")
                 })?;
 
+                if let Some(swept_at) = approval_payload_swept_at(conn, &approval_token)? {
+                    return Err(ExError::new(ExErrorKind::Deleted)
+                        .with_op("approval_get")
+                        .with_entity_id(&approval_token)
+                        .with_message(format!(
+                            "approval payload was removed by the retention sweep at {} ms",
+                            swept_at
+                        )));
+                }
                 let request_digest = row.request_digest.clone().ok_or_else(|| {
                     ExError::new(ExErrorKind::ApprovalStorageCorrupt)
                         .with_op("approval_get")
@@ -910,11 +1554,17 @@ pub fn apply_engine_query(
                             .with_message(format!("CAS blob is not valid JSON: {}", e))
                     })?;
 
+                let payload = ApprovalRequestPayload::from_stored_json(&payload_json)
+                    .map_err(|e| e.with_op("approval_get").with_entity_id(&approval_token))?;
+
+                let assignees = assignees_of(conn, &payload.candidates)?;
                 Ok(EngineQueryResult::ApprovalGet(ApprovalGetResult {
                     approval_token,
                     request_digest,
                     semantic_request_digest: row.semantic_request_digest,
                     payload_json,
+                    payload,
+                    assignees,
                 }))
             })();
             let elapsed = start.elapsed().as_millis() as u64;
@@ -934,27 +1584,18 @@ pub fn apply_engine_query(
             let start = std::time::Instant::now();
             let result = (|| -> Result<EngineQueryResult> {
                 let limit = opts.effective_limit();
-                let after_key: Option<(i64, String)> = opts.decode_cursor().and_then(|c| {
-                    let parts: Vec<&str> = c.splitn(2, '|').collect();
-                    if parts.len() == 2 {
-                        parts[0]
-                            .parse::<i64>()
-                            .ok()
-                            .map(|ts| (ts, parts[1].to_string()))
-                    } else {
-                        None
-                    }
-                });
-                let raw = list_approval_rows_paginated(
-                    conn,
-                    after_key.as_ref().map(|(ts, tok)| (*ts, tok.as_str())),
-                    limit + 1,
-                )?;
+                let after_key: Option<PageKey> = opts.after_key()?;
+                let raw = list_approval_rows_paginated(conn, after_key.as_ref(), limit + 1)?;
                 let items: Vec<ApprovalListItem> =
                     raw.into_iter().map(approval_row_to_list_item).collect();
-                let page = Page::from_overshot(items, limit, |item: &ApprovalListItem| {
-                    format!("{}|{}", item.created_at, item.approval_token)
-                });
+                let page = Page::from_overshot(
+                    items,
+                    limit,
+                    &opts.cursor_codec,
+                    |item: &ApprovalListItem| {
+                        PageKey::new(item.created_at, item.approval_token.clone())
+                    },
+                );
                 Ok(EngineQueryResult::ApprovalList(page))
             })();
             let elapsed = start.elapsed().as_millis() as u64;
@@ -976,17 +1617,48 @@ pub fn apply_engine_query(
             .with_op("approval_list_by_kind")
             .with_message("ApprovalListByKind is not implemented in Phase 1")),
 
+        // ── ApprovalHistory ───────────────────────────────────────────────────
+        EngineQuery::ApprovalHistory { approval_token } => {
+            log_op_start!("approval_history");
+            let start = std::time::Instant::now();
+            let result = (|| -> Result<EngineQueryResult> {
+                if fetch_approval_row(conn, &approval_token)?.is_none() {
+                    return Err(ExError::new(ExErrorKind::ApprovalNotFound)
+                        .with_op("approval_history")
+                        .with_entity_id(&approval_token)
+                        .with_message("approval request not found"));
+                }
+                let events = list_approval_events(conn, &approval_token)?;
+                Ok(EngineQueryResult::ApprovalHistory(events))
+            })();
+            let elapsed = start.elapsed().as_millis() as u64;
+            match &result {
+                Ok(_) => log_op_end!("approval_history", duration_ms = elapsed),
+                Err(e) => {
+                    let e_clone = e.clone();
+                    log_op_error!("approval_history", e_clone, duration_ms = elapsed);
+                }
+            }
+            result
+        }
+
         // ── ConstraintPredicatesPreview ───────────────────────────────────────
         EngineQuery::ConstraintPredicatesPreview {
             profile_ref,
             context: _,
             candidates,
+            ambiguity_policy_override,
         } => {
             log_op_start!("constraint_predicates_preview");
             let start = std::time::Instant::now();
             let result = (|| -> Result<EngineQueryResult> {
-                // Resolve ambiguity policy from profile (read-only)
-                let ambiguity_policy = resolve_ambiguity_policy(conn, profile_ref.as_deref())?;
+                // Resolve ambiguity policy from profile (read-only), unless the
+                // caller is simulating a different one
+                let policy_overridden = ambiguity_policy_override.is_some();
+                let ambiguity_policy = match ambiguity_policy_override {
+                    Some(policy) => policy,
+                    None => resolve_ambiguity_policy(conn, profile_ref.as_deref())?,
+                };
 
                 // Build candidate entries (priority 0 for all — Phase 1 has no predicate eval)
                 let candidate_entries: Vec<CandidateEntry> = candidates
@@ -1009,7 +1681,7 @@ pub fn apply_engine_query(
                             (PreviewStatus::Selected, resolution.selected_profile_ref)
                         }
                     }
-                    DryRunConstraintStatus::RoutedForApproval => match ambiguity_policy {
+                    DryRunConstraintStatus::RoutedForApproval => match &ambiguity_policy {
                         AmbiguityPolicy::RouteForApproval => {
                             (PreviewStatus::RoutedForApproval, None)
                         }
@@ -1022,6 +1694,8 @@ pub fn apply_engine_query(
                         status,
                         selected,
                         candidates: resolution.candidates,
+                        ambiguity_policy,
+                        policy_overridden,
                     },
                 ))
             })();
@@ -1215,7 +1889,7 @@ pub fn apply_engine_query(
                     .query_row(
                         "SELECT manifest_digest FROM snapshots
                          WHERE root_ettle_id = ?1 AND status = 'committed'
-                         ORDER BY created_at DESC LIMIT 1",
+                         ORDER BY id DESC LIMIT 1",
                         [&realised_ettle_id],
                         |row| row.get(0),
                     )
@@ -1238,13 +1912,432 @@ pub fn apply_engine_query(
             }
             result
         }
+
+        // ── LedgerVerify ─────────────────────────────────────────────────────
+        EngineQuery::LedgerVerify => {
+            let start = std::time::Instant::now();
+            log_op_start!("ledger_verify");
+            let result = verify_ledger(conn).map(EngineQueryResult::LedgerVerify);
+            let elapsed = start.elapsed().as_millis() as u64;
+            match &result {
+                Ok(_) => log_op_end!("ledger_verify", duration_ms = elapsed),
+                Err(e) => {
+                    let e_clone = e.clone();
+                    log_op_error!("ledger_verify", e_clone, duration_ms = elapsed);
+                }
+            }
+            result
+        }
+
+        // ── HeadsList ─────────────────────────────────────────────────────────
+        EngineQuery::HeadsList => {
+            let start = std::time::Instant::now();
+            log_op_start!("heads_list");
+            let now_ms = chrono::Utc::now().timestamp_millis();
+            let result = list_heads(conn, cas, now_ms).map(EngineQueryResult::HeadsList);
+            let elapsed = start.elapsed().as_millis() as u64;
+            match &result {
+                Ok(_) => log_op_end!("heads_list", duration_ms = elapsed),
+                Err(e) => {
+                    let e_clone = e.clone();
+                    log_op_error!("heads_list", e_clone, duration_ms = elapsed);
+                }
+            }
+            result
+        }
+
+        // ── SnapshotCommitTicket ─────────────────────────────────────────────
+        EngineQuery::SnapshotCommitTicket { ticket } => {
+            let start = std::time::Instant::now();
+            log_op_start!("snapshot_commit_ticket");
+            let result =
+                get_commit_ticket(conn, &ticket).map(EngineQueryResult::SnapshotCommitTicket);
+            let elapsed = start.elapsed().as_millis() as u64;
+            match &result {
+                Ok(_) => log_op_end!("snapshot_commit_ticket", duration_ms = elapsed),
+                Err(e) => {
+                    let e_clone = e.clone();
+                    log_op_error!("snapshot_commit_ticket", e_clone, duration_ms = elapsed);
+                }
+            }
+            result
+        }
+
+        // ── SnapshotCompareMatrix ────────────────────────────────────────────
+        EngineQuery::SnapshotCompareMatrix { refs } => {
+            let start = std::time::Instant::now();
+            log_op_start!("snapshot_compare_matrix", refs = refs.len());
+            let result =
+                compare_snapshots(conn, cas, &refs).map(EngineQueryResult::SnapshotCompareMatrix);
+            let elapsed = start.elapsed().as_millis() as u64;
+            match &result {
+                Ok(_) => log_op_end!("snapshot_compare_matrix", duration_ms = elapsed),
+                Err(e) => {
+                    let e_clone = e.clone();
+                    log_op_error!("snapshot_compare_matrix", e_clone, duration_ms = elapsed);
+                }
+            }
+            result
+        }
+
+        // ── SettingsGet ──────────────────────────────────────────────────────
+        EngineQuery::SettingsGet { key } => {
+            let start = std::time::Instant::now();
+            log_op_start!("settings_get");
+            let result = settings_get(conn, key.as_deref()).map(EngineQueryResult::SettingsGet);
+            let elapsed = start.elapsed().as_millis() as u64;
+            match &result {
+                Ok(_) => log_op_end!("settings_get", duration_ms = elapsed),
+                Err(e) => {
+                    let e_clone = e.clone();
+                    log_op_error!("settings_get", e_clone, duration_ms = elapsed);
+                }
+            }
+            result
+        }
+
+        // ── ArchitectureReport ───────────────────────────────────────────────
+        EngineQuery::ArchitectureReport {
+            recent_snapshot_limit,
+        } => {
+            let start = std::time::Instant::now();
+            log_op_start!("architecture_report");
+            let result = build_architecture_report(conn, recent_snapshot_limit)
+                .map(|r| EngineQueryResult::ArchitectureReport(Box::new(r)));
+            let elapsed = start.elapsed().as_millis() as u64;
+            match &result {
+                Ok(_) => log_op_end!("architecture_report", duration_ms = elapsed),
+                Err(e) => {
+                    let e_clone = e.clone();
+                    log_op_error!("architecture_report", e_clone, duration_ms = elapsed);
+                }
+            }
+            result
+        }
+    }
+}
+
+/// `COUNT(*)` of each table, `None` for tables missing from this schema.
+///
+/// Table names come only from `STORE_STATS_TABLES`, never from caller input.
+fn table_row_counts(conn: &Connection, tables: &[&str]) -> Vec<TableRowCount> {
+    tables
+        .iter()
+        .map(|table| TableRowCount {
+            table: (*table).to_string(),
+            row_count: conn
+                .query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |r| r.get(0))
+                .ok(),
+        })
+        .collect()
+}
+
+/// Rewrite Ettle slugs in a query's Ettle-reference field to a canonical ID.
+fn resolve_query_ettle_refs(conn: &Connection, mut query: EngineQuery) -> Result<EngineQuery> {
+    if let Some(ettle_id) = query_ettle_ref(&mut query) {
+        *ettle_id = resolve_ettle_ref(conn, ettle_id)?;
+    }
+    Ok(query)
+}
+
+/// The query's Ettle-reference field, if it has one.
+fn query_ettle_ref(query: &mut EngineQuery) -> Option<&mut String> {
+    match query {
+        EngineQuery::EttleGet { ettle_id }
+        | EngineQuery::EttleListReferrers { ettle_id }
+        | EngineQuery::EttleListDecisions { ettle_id, .. }
+        | EngineQuery::SnapshotGetHead {
+            realised_ettle_id: ettle_id,
+        }
+        | EngineQuery::SnapshotList {
+            ettle_id: Some(ettle_id),
+            ..
+        }
+        | EngineQuery::RootGet { ettle_id }
+        | EngineQuery::LeafList {
+            root_ettle_id: Some(ettle_id),
+            ..
+        }
+        | EngineQuery::EptDiagnose {
+            leaf_ettle_id: ettle_id,
+        }
+        | EngineQuery::SampleLeaves {
+            filter:
+                LeafSampleFilter {
+                    root_ettle_id: Some(ettle_id),
+                    ..
+                },
+            ..
+        }
+        | EngineQuery::ConstraintListAttachments {
+            constraint_id: ettle_id,
+            ..
+        }
+        | EngineQuery::EttleEffectiveConstraints { ettle_id } => Some(ettle_id),
+        EngineQuery::DecisionListByTarget {
+            target_kind,
+            target_id,
+            ..
+        } if target_kind == "ettle" || target_kind == "constraint" => Some(target_id),
+        _ => None,
     }
 }
 
+// ---------------------------------------------------------------------------
+// apply_engine_query_with_options
+// ---------------------------------------------------------------------------
+
+/// Apply a read-only engine query with per-call options.
+///
+/// The query runs inside a read savepoint together with a
+/// [`read_state_version`] call, so the returned `state` describes exactly the
+/// database state the result was computed from. Clients composing several
+/// queries compare the stamps and retry when they differ.
+///
+/// With `options.explain == true` a [`QueryTrace`] is returned alongside the
+/// result, listing the tables each stage (`resolve`, `execute`) actually
+/// read with the rows returned from them, the hydration scope, and
+/// per-stage timings.
+///
+/// `options.deadline` and `options.cancel` bound how long the query may run;
+/// see [`query_cancel`](crate::commands::query_cancel).
+///
+/// # Errors
+///
+/// Same as `apply_engine_query`, plus `Timeout` when the deadline passes or
+/// the token is cancelled before the query finishes. No trace is returned
+/// for failed queries.
+pub fn apply_engine_query_with_options(
+    query: EngineQuery,
+    options: QueryOptions,
+    conn: &Connection,
+    cas: &FsStore,
+    policy_provider: Option<&dyn ettlex_core::policy_provider::PolicyProvider>,
+) -> Result<ExplainedQueryResult> {
+    let (op, _) = query_trace::query_plan(&query);
+    match QueryInterrupt::from_options(&options) {
+        Some(interrupt) => with_read_savepoint(conn, || {
+            interrupt.run(conn, op, || {
+                run_query_with_options(query, &options, conn, cas, policy_provider)
+            })
+        }),
+        None => with_read_savepoint(conn, || {
+            run_query_with_options(query, &options, conn, cas, policy_provider)
+        }),
+    }
+}
+
+fn run_query_with_options(
+    query: EngineQuery,
+    options: &QueryOptions,
+    conn: &Connection,
+    cas: &FsStore,
+    policy_provider: Option<&dyn ettlex_core::policy_provider::PolicyProvider>,
+) -> Result<ExplainedQueryResult> {
+    let state = read_state_version(conn)?;
+    if !options.explain {
+        let result = apply_engine_query(query, conn, cas, policy_provider)?;
+        return Ok(ExplainedQueryResult {
+            result,
+            state,
+            trace: None,
+        });
+    }
+
+    let (op, hydration_scope) = query_trace::query_plan(&query);
+    let (query, resolve) =
+        query_trace::capture_stage(conn, "resolve", || resolve_query_ettle_refs(conn, query))?;
+    let (result, execute) = query_trace::capture_stage(conn, "execute", || {
+        apply_resolved_query(query, conn, cas, policy_provider)
+    })?;
+
+    let stages = vec![resolve, execute];
+    let trace = QueryTrace {
+        op: op.to_string(),
+        tables: query_trace::merge_table_access(&stages),
+        hydration_scope,
+        result_rows: query_trace::result_row_count(&result),
+        stages,
+    };
+
+    Ok(ExplainedQueryResult {
+        result,
+        state,
+        trace: Some(trace),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// apply_engine_query_batch
+// ---------------------------------------------------------------------------
+
+/// Results of `apply_engine_query_batch`, in query order.
+#[derive(Debug, Clone)]
+pub struct BatchQueryResult {
+    /// One result per query, in the order the queries were given.
+    pub results: Vec<EngineQueryResult>,
+    /// State version and head digest shared by every result in the batch.
+    pub state: StateVersionResult,
+}
+
+/// Apply several read-only queries against one consistent database view.
+///
+/// All queries run inside a single read savepoint, so commits made by other
+/// connections while the batch runs are invisible to every query in it. Use
+/// this when assembling views from several queries (tree, decisions,
+/// snapshots) that must agree with each other.
+///
+/// # Errors
+///
+/// Fails fast: the first failing query's error is returned and no results
+/// are produced. Error kinds are those of the individual queries.
+pub fn apply_engine_query_batch(
+    queries: Vec<EngineQuery>,
+    conn: &Connection,
+    cas: &FsStore,
+    policy_provider: Option<&dyn ettlex_core::policy_provider::PolicyProvider>,
+) -> Result<BatchQueryResult> {
+    log_op_start!("engine_query_batch", queries = queries.len());
+    let start = std::time::Instant::now();
+
+    let result = with_read_savepoint(conn, || {
+        let state = read_state_version(conn)?;
+        let results = queries
+            .into_iter()
+            .map(|q| apply_engine_query(q, conn, cas, policy_provider))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(BatchQueryResult { results, state })
+    });
+
+    let elapsed = start.elapsed().as_millis() as u64;
+    match &result {
+        Ok(_) => log_op_end!("engine_query_batch", duration_ms = elapsed),
+        Err(e) => {
+            let e_clone = e.clone();
+            log_op_error!("engine_query_batch", e_clone, duration_ms = elapsed);
+        }
+    }
+    result
+}
+
+/// Read the current state version and semantic head digest.
+///
+/// `state_version` is the number of applied commands (rows in `command_log`);
+/// the head digest is that of the most recently committed snapshot.
+pub fn read_state_version(conn: &Connection) -> Result<StateVersionResult> {
+    let persistence = |e: rusqlite::Error| {
+        ExError::new(ExErrorKind::Persistence)
+            .with_op("state_get_version")
+            .with_message(e.to_string())
+    };
+
+    let state_version: u64 = conn
+        .query_row("SELECT COUNT(*) FROM command_log", [], |row| row.get(0))
+        .map_err(persistence)?;
+
+    let semantic_head_digest: Option<String> = conn
+        .query_row(
+            "SELECT semantic_manifest_digest FROM snapshots
+             ORDER BY id DESC LIMIT 1",
+            [],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(persistence)?;
+
+    Ok(StateVersionResult {
+        state_version,
+        semantic_head_digest,
+        summary: None,
+    })
+}
+
+/// Counts and per-root heads for the `StateGetVersion` heartbeat.
+fn read_workspace_summary(conn: &Connection) -> Result<WorkspaceSummary> {
+    let persistence = |e: rusqlite::Error| {
+        ExError::new(ExErrorKind::Persistence)
+            .with_op("state_get_version")
+            .with_message(e.to_string())
+    };
+    let count = |sql: &str| -> Result<u64> {
+        conn.query_row(sql, [], |row| row.get(0))
+            .map_err(persistence)
+    };
+
+    let ettle_count = count("SELECT COUNT(*) FROM ettles WHERE tombstoned_at IS NULL")?;
+    let snapshot_count = count("SELECT COUNT(*) FROM snapshots WHERE status = 'committed'")?;
+    let pending_approval_count =
+        count("SELECT COUNT(*) FROM approval_requests WHERE status = 'pending'")?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT s.root_ettle_id, r.display_name, s.snapshot_id, s.manifest_digest
+             FROM snapshots s
+             LEFT JOIN roots r ON r.ettle_id = s.root_ettle_id
+             WHERE s.status = 'committed'
+               AND COALESCE(r.status, 'active') = 'active'
+               AND s.id = (SELECT MAX(id) FROM snapshots
+                           WHERE root_ettle_id = s.root_ettle_id AND status = 'committed')
+             ORDER BY s.root_ettle_id",
+        )
+        .map_err(persistence)?;
+    let root_heads = stmt
+        .query_map([], |row| {
+            Ok(RootHead {
+                root_ettle_id: row.get(0)?,
+                display_name: row.get(1)?,
+                snapshot_id: row.get(2)?,
+                manifest_digest: row.get(3)?,
+            })
+        })
+        .map_err(persistence)?
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(persistence)?;
+
+    Ok(WorkspaceSummary {
+        ettle_count,
+        snapshot_count,
+        pending_approval_count,
+        root_heads,
+    })
+}
+
+/// Run `f` inside a savepoint so every read it performs observes a single
+/// database snapshot. The savepoint is released (never rolled back) since
+/// queries do not write; it nests correctly inside an open transaction.
+pub fn with_read_savepoint<T>(conn: &Connection, f: impl FnOnce() -> Result<T>) -> Result<T> {
+    let persistence = |e: rusqlite::Error| {
+        ExError::new(ExErrorKind::Persistence)
+            .with_op("read_savepoint")
+            .with_message(e.to_string())
+    };
+
+    conn.execute_batch("SAVEPOINT engine_read")
+        .map_err(persistence)?;
+    let result = f();
+    conn.execute_batch("RELEASE engine_read")
+        .map_err(persistence)?;
+    result
+}
+
 // ---------------------------------------------------------------------------
 // Internal query helpers
 // ---------------------------------------------------------------------------
 
+/// The `diff_ignore` list from a profile payload; empty when the profile or
+/// key is absent.
+fn profile_diff_ignore(conn: &Connection, profile_ref: &str) -> Result<DiffIgnoreList> {
+    use ettlex_store::profile::load_profile_payload;
+
+    match load_profile_payload(conn, profile_ref)? {
+        Some(payload) => match payload.get("diff_ignore") {
+            Some(value) => DiffIgnoreList::from_value(value),
+            None => Ok(DiffIgnoreList::default()),
+        },
+        None => Ok(DiffIgnoreList::default()),
+    }
+}
+
 fn resolve_ambiguity_policy(
     conn: &Connection,
     profile_ref: Option<&str>,
@@ -0,0 +1,149 @@
+//! Refinement path diagnosis for leaf Ettles.
+//!
+//! Walks `refinement` relations from a leaf Ettle up to its root the same way
+//! approval packets resolve a path (lowest relation ID wins), and reports
+//! every point where that resolution was ambiguous or broken so clients can
+//! fix the tree without reverse-engineering an error. Read-only.
+
+#![allow(clippy::result_large_err)]
+
+use std::collections::HashSet;
+
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_store::errors::Result;
+use ettlex_store::model::{EttleRecord, RelationListOpts};
+use ettlex_store::repo::SqliteRepo;
+use rusqlite::Connection;
+
+use crate::commands::read_tools::{EptDiagnosis, EptIssue, EptIssueKind, EptParentCandidate};
+
+const REFINEMENT: &str = "refinement";
+
+/// Diagnose the refinement path from `leaf_ettle_id` to its root.
+///
+/// # Errors
+/// * `NotFound` - No Ettle exists for `leaf_ettle_id`
+pub(crate) fn diagnose_ept(conn: &Connection, leaf_ettle_id: &str) -> Result<EptDiagnosis> {
+    if SqliteRepo::get_ettle_record(conn, leaf_ettle_id)?.is_none() {
+        return Err(ExError::new(ExErrorKind::NotFound)
+            .with_op("ept_diagnose")
+            .with_entity_id(leaf_ettle_id)
+            .with_message("leaf ettle not found"));
+    }
+
+    let mut issues = Vec::new();
+
+    let mut child_ettle_ids = Vec::new();
+    for child_id in
+        SqliteRepo::get_active_outgoing_relations_of_type(conn, leaf_ettle_id, REFINEMENT)?
+    {
+        if SqliteRepo::get_ettle_record(conn, &child_id)?
+            .as_ref()
+            .is_some_and(is_active)
+        {
+            child_ettle_ids.push(child_id);
+        }
+    }
+    if !child_ettle_ids.is_empty() {
+        child_ettle_ids.sort();
+        issues.push(EptIssue {
+            ettle_id: leaf_ettle_id.to_string(),
+            kind: EptIssueKind::NotALeaf { child_ettle_ids },
+            suggested_fix: "diagnose one of the child Ettles instead, or tombstone the \
+                            refinement relations to make this Ettle a leaf"
+                .to_string(),
+        });
+    }
+
+    let mut path = vec![leaf_ettle_id.to_string()];
+    let mut seen: HashSet<String> = HashSet::new();
+    seen.insert(leaf_ettle_id.to_string());
+    let mut current = leaf_ettle_id.to_string();
+    loop {
+        let mut relations = SqliteRepo::list_relations(
+            conn,
+            &RelationListOpts {
+                source_ettle_id: None,
+                target_ettle_id: Some(current.clone()),
+                relation_type: Some(REFINEMENT.to_string()),
+                include_tombstoned: false,
+            },
+        )?;
+        relations.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let mut candidates = Vec::new();
+        for relation in relations {
+            match SqliteRepo::get_ettle_record(conn, &relation.source_ettle_id)? {
+                Some(parent) if is_active(&parent) => candidates.push(EptParentCandidate {
+                    ordinal: candidates.len() as u32,
+                    relation_id: relation.id,
+                    parent_ettle_id: parent.id,
+                    title: parent.title,
+                }),
+                parent => {
+                    let parent_state = match parent {
+                        None => "missing",
+                        Some(p) if p.tombstoned_at.is_some() => "tombstoned",
+                        Some(_) => "archived",
+                    };
+                    issues.push(EptIssue {
+                        ettle_id: current.clone(),
+                        suggested_fix: format!(
+                            "tombstone relation {} or restore Ettle {}",
+                            relation.id, relation.source_ettle_id
+                        ),
+                        kind: EptIssueKind::BrokenLink {
+                            relation_id: relation.id,
+                            parent_ettle_id: relation.source_ettle_id,
+                            parent_state: parent_state.to_string(),
+                        },
+                    });
+                }
+            }
+        }
+
+        let Some(parent_id) = candidates.first().map(|c| c.parent_ettle_id.clone()) else {
+            break;
+        };
+        if candidates.len() > 1 {
+            let extra: Vec<&str> = candidates[1..]
+                .iter()
+                .map(|c| c.relation_id.as_str())
+                .collect();
+            issues.push(EptIssue {
+                ettle_id: current.clone(),
+                suggested_fix: format!(
+                    "keep a single refinement parent; tombstone relation(s) {}",
+                    extra.join(", ")
+                ),
+                kind: EptIssueKind::AmbiguousParent { candidates },
+            });
+        }
+        if !seen.insert(parent_id.clone()) {
+            issues.push(EptIssue {
+                ettle_id: current.clone(),
+                suggested_fix: format!(
+                    "tombstone the refinement relation from {} to {}",
+                    parent_id, current
+                ),
+                kind: EptIssueKind::Cycle {
+                    parent_ettle_id: parent_id,
+                },
+            });
+            break;
+        }
+        path.push(parent_id.clone());
+        current = parent_id;
+    }
+    path.reverse();
+
+    Ok(EptDiagnosis {
+        leaf_ettle_id: leaf_ettle_id.to_string(),
+        path,
+        issues,
+    })
+}
+
+fn is_active(record: &EttleRecord) -> bool {
+    record.tombstoned_at.is_none() && record.archived_at.is_none()
+}
@@ -1,16 +1,22 @@
 //! Engine handler for Ettle CRUD operations — Slice 01.
 //!
 //! This module owns all invariant enforcement for Ettle create / update /
-//! tombstone / get / list.  It delegates persistence to `SqliteRepo` and
+//! tombstone / restore / get / list.  It delegates persistence to `SqliteRepo` and
 //! never writes raw SQL itself.
 
 #![allow(clippy::result_large_err)]
 
 use ettlex_core::errors::{ExError, ExErrorKind};
-use ettlex_store::model::{EttleListOpts, EttleListPage, EttleRecord};
-use ettlex_store::repo::SqliteRepo;
+use ettlex_core::model::ContentFormat;
+use ettlex_store::cas::FsStore;
+use ettlex_store::errors::from_rusqlite;
+use ettlex_store::model::{EttleListOpts, EttleListPage, EttleRecord, RelationListOpts};
+use ettlex_store::repo::{CursorCodec, SqliteRepo};
 use rusqlite::Connection;
 
+use super::content_spill::{digests_changed, hydrate_ettle_content, prepare_content};
+use super::read_tools::{EttleReferrers, RefinementReferrer};
+
 type Result<T> = std::result::Result<T, ExError>;
 
 // ---------------------------------------------------------------------------
@@ -25,8 +31,14 @@ type Result<T> = std::result::Result<T, ExError>;
 ///   both absent (`MissingLinkType`).
 /// - If `reasoning_link_id` is supplied, the target must exist (`NotFound`)
 ///   and must not be tombstoned (`AlreadyTombstoned`).
+///
+/// A unique slug is derived from the title and assigned in the same
+/// transaction; it is not changed by later title updates. Bodies over the
+/// `content.max_inline_bytes` setting are stored in CAS.
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn handle_ettle_create(
     conn: &mut Connection,
+    cas: &FsStore,
     title: &str,
     why: Option<&str>,
     what: Option<&str>,
@@ -73,18 +85,34 @@ pub(crate) fn handle_ettle_create(
     let id = format!("ettle:{}", uuid::Uuid::now_v7());
     let now = chrono::Utc::now().to_rfc3339();
 
-    SqliteRepo::insert_ettle(
+    let content = prepare_content(
         conn,
+        cas,
+        None,
+        Some(why.unwrap_or("")),
+        Some(what.unwrap_or("")),
+        Some(how.unwrap_or("")),
+    )?;
+
+    let tx = conn.savepoint().map_err(from_rusqlite)?;
+    SqliteRepo::insert_ettle(
+        &tx,
         &id,
         title,
-        why.unwrap_or(""),
-        what.unwrap_or(""),
-        how.unwrap_or(""),
+        content.why.as_deref().unwrap_or(""),
+        content.what.as_deref().unwrap_or(""),
+        content.how.as_deref().unwrap_or(""),
         reasoning_link_id,
         reasoning_link_type,
         &now,
         &now,
     )?;
+    if digests_changed(None, &content) {
+        content.write_digests(&tx, &id)?;
+    }
+    let slug = SqliteRepo::allocate_ettle_slug(&tx, title)?;
+    SqliteRepo::set_ettle_slug(&tx, &id, &slug)?;
+    tx.commit().map_err(from_rusqlite)?;
 
     Ok(id)
 }
@@ -102,9 +130,12 @@ pub(crate) fn handle_ettle_create(
 /// - After merge, if link id is set but type is absent (neither supplied nor in existing
 ///   record), returns `MissingLinkType`.
 /// - Link target must exist and not be tombstoned.
+///
+/// Supplied bodies are placed inline or in CAS by the same rule as on create.
 #[allow(clippy::too_many_arguments)]
 pub(crate) fn handle_ettle_update(
     conn: &mut Connection,
+    cas: &FsStore,
     ettle_id: &str,
     title: Option<&str>,
     why: Option<&str>,
@@ -217,17 +248,24 @@ pub(crate) fn handle_ettle_update(
         reasoning_link_type
     };
 
+    let content = prepare_content(conn, cas, Some(&existing), why, what, how)?;
+
+    let tx = conn.savepoint().map_err(from_rusqlite)?;
     SqliteRepo::update_ettle(
-        conn,
+        &tx,
         ettle_id,
         title,
-        why,
-        what,
-        how,
+        content.why.as_deref(),
+        content.what.as_deref(),
+        content.how.as_deref(),
         store_link_id,
         store_link_type,
         &now,
     )?;
+    if digests_changed(Some(&existing), &content) {
+        content.write_digests(&tx, ettle_id)?;
+    }
+    tx.commit().map_err(from_rusqlite)?;
 
     Ok(())
 }
@@ -238,6 +276,10 @@ pub(crate) fn handle_ettle_update(
 
 /// Tombstone an Ettle (soft delete).
 ///
+/// Active incoming `constraint` relations are detached (tombstoned) in the
+/// same transaction, so no constraint stays attached to a tombstoned Ettle.
+/// Restoring the Ettle does not reattach them.
+///
 /// Invariants enforced:
 /// - Target must exist (`NotFound`) and not already be tombstoned (`AlreadyTombstoned`).
 /// - Must have no active (non-tombstoned) dependants (`HasActiveDependants`).
@@ -280,20 +322,226 @@ pub(crate) fn handle_ettle_tombstone(conn: &mut Connection, ettle_id: &str) -> R
     }
 
     let now = chrono::Utc::now().to_rfc3339();
-    SqliteRepo::tombstone_ettle(conn, ettle_id, &now)?;
+    let tx = conn.savepoint().map_err(from_rusqlite)?;
+    SqliteRepo::tombstone_ettle(&tx, ettle_id, &now)?;
+    let attachments = SqliteRepo::list_relations(
+        &tx,
+        &RelationListOpts {
+            target_ettle_id: Some(ettle_id.to_string()),
+            relation_type: Some("constraint".to_string()),
+            ..Default::default()
+        },
+    )?;
+    for rel in attachments {
+        SqliteRepo::tombstone_relation(&tx, &rel.id, &now)?;
+    }
+    tx.commit().map_err(from_rusqlite)?;
     Ok(())
 }
 
+// ---------------------------------------------------------------------------
+// handle_ettle_restore
+// ---------------------------------------------------------------------------
+
+/// Restore a tombstoned Ettle.
+///
+/// Invariants enforced:
+/// - Target must exist (`NotFound`) and currently be tombstoned (`InvalidInput`).
+/// - If the Ettle has a `reasoning_link_id`, the linked Ettle must still exist
+///   (`NotFound`) and be active (`AlreadyTombstoned`); restore it first.
+pub(crate) fn handle_ettle_restore(conn: &mut Connection, ettle_id: &str) -> Result<()> {
+    let existing = SqliteRepo::get_ettle_record(conn, ettle_id)?.ok_or_else(|| {
+        ExError::new(ExErrorKind::NotFound)
+            .with_op("ettle_restore")
+            .with_entity_id(ettle_id)
+            .with_message(format!("Ettle not found: {}", ettle_id))
+    })?;
+
+    if existing.tombstoned_at.is_none() {
+        return Err(ExError::new(ExErrorKind::InvalidInput)
+            .with_op("ettle_restore")
+            .with_entity_id(ettle_id)
+            .with_message(format!("Ettle is not tombstoned: {}", ettle_id)));
+    }
+
+    if let Some(link_id) = existing.reasoning_link_id.as_deref() {
+        let target = SqliteRepo::get_ettle_record(conn, link_id)?.ok_or_else(|| {
+            ExError::new(ExErrorKind::NotFound)
+                .with_op("ettle_restore")
+                .with_entity_id(link_id)
+                .with_message(format!("reasoning_link target not found: {}", link_id))
+        })?;
+        if target.tombstoned_at.is_some() {
+            return Err(ExError::new(ExErrorKind::AlreadyTombstoned)
+                .with_op("ettle_restore")
+                .with_entity_id(link_id)
+                .with_message(format!(
+                    "reasoning_link target is tombstoned and must be restored first: {}",
+                    link_id
+                )));
+        }
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    SqliteRepo::restore_ettle(conn, ettle_id, &now)?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// handle_ettle_archive / handle_ettle_unarchive
+// ---------------------------------------------------------------------------
+
+/// Archive an Ettle, and optionally its refinement subtree.
+///
+/// Archived Ettles drop out of default listings, renders and snapshot roots but
+/// are neither tombstoned nor deleted. Returns the IDs newly archived, in
+/// traversal order (target first).
+///
+/// Invariants enforced:
+/// - Target must exist (`NotFound`), not be tombstoned (`AlreadyTombstoned`),
+///   and not already be archived (`InvalidInput`).
+/// - With `subtree`, tombstoned and already-archived descendants are skipped.
+pub(crate) fn handle_ettle_archive(
+    conn: &mut Connection,
+    ettle_id: &str,
+    subtree: bool,
+) -> Result<Vec<String>> {
+    let existing = get_live_ettle(conn, ettle_id, "ettle_archive")?;
+    if existing.archived_at.is_some() {
+        return Err(ExError::new(ExErrorKind::InvalidInput)
+            .with_op("ettle_archive")
+            .with_entity_id(ettle_id)
+            .with_message(format!("Ettle is already archived: {}", ettle_id)));
+    }
+
+    let ids = collect_archive_targets(conn, ettle_id, subtree, false)?;
+    let now = chrono::Utc::now().to_rfc3339();
+    let tx = conn.savepoint().map_err(from_rusqlite)?;
+    SqliteRepo::set_ettles_archived(&tx, &ids, Some(&now), &now)?;
+    tx.commit().map_err(from_rusqlite)?;
+    Ok(ids)
+}
+
+/// Unarchive an Ettle, and optionally its refinement subtree.
+///
+/// Returns the IDs newly unarchived, in traversal order (target first).
+///
+/// Invariants enforced:
+/// - Target must exist (`NotFound`), not be tombstoned (`AlreadyTombstoned`),
+///   and currently be archived (`InvalidInput`).
+/// - With `subtree`, only archived, non-tombstoned descendants are touched.
+pub(crate) fn handle_ettle_unarchive(
+    conn: &mut Connection,
+    ettle_id: &str,
+    subtree: bool,
+) -> Result<Vec<String>> {
+    let existing = get_live_ettle(conn, ettle_id, "ettle_unarchive")?;
+    if existing.archived_at.is_none() {
+        return Err(ExError::new(ExErrorKind::InvalidInput)
+            .with_op("ettle_unarchive")
+            .with_entity_id(ettle_id)
+            .with_message(format!("Ettle is not archived: {}", ettle_id)));
+    }
+
+    let ids = collect_archive_targets(conn, ettle_id, subtree, true)?;
+    let now = chrono::Utc::now().to_rfc3339();
+    let tx = conn.savepoint().map_err(from_rusqlite)?;
+    SqliteRepo::set_ettles_archived(&tx, &ids, None, &now)?;
+    tx.commit().map_err(from_rusqlite)?;
+    Ok(ids)
+}
+
+// ---------------------------------------------------------------------------
+// handle_ettle_set_content_format
+// ---------------------------------------------------------------------------
+
+/// Declare the format of an Ettle's content.
+///
+/// Invariants enforced:
+/// - `content_format` must be `markdown`, `asciidoc` or `plain` (`InvalidInput`).
+/// - Target must exist (`NotFound`) and not be tombstoned (`AlreadyTombstoned`).
+pub(crate) fn handle_ettle_set_content_format(
+    conn: &Connection,
+    ettle_id: &str,
+    content_format: &str,
+) -> Result<()> {
+    let format: ContentFormat = content_format.parse().map_err(|e: ExError| {
+        e.with_op("ettle_set_content_format")
+            .with_entity_id(ettle_id)
+    })?;
+    get_live_ettle(conn, ettle_id, "ettle_set_content_format")?;
+    let now = chrono::Utc::now().to_rfc3339();
+    SqliteRepo::set_ettle_content_format(conn, ettle_id, format.as_str(), &now)
+}
+
+fn get_live_ettle(conn: &Connection, ettle_id: &str, op: &str) -> Result<EttleRecord> {
+    let existing = SqliteRepo::get_ettle_record(conn, ettle_id)?.ok_or_else(|| {
+        ExError::new(ExErrorKind::NotFound)
+            .with_op(op)
+            .with_entity_id(ettle_id)
+            .with_message(format!("Ettle not found: {}", ettle_id))
+    })?;
+    if existing.tombstoned_at.is_some() {
+        return Err(ExError::new(ExErrorKind::AlreadyTombstoned)
+            .with_op(op)
+            .with_entity_id(ettle_id)
+            .with_message(format!("Ettle is tombstoned: {}", ettle_id)));
+    }
+    Ok(existing)
+}
+
+/// Breadth-first walk of active refinement children starting at `root`.
+///
+/// The root is always included. Descendants are included only when live and
+/// their archived state matches `archived` (the state being flipped away from).
+fn collect_archive_targets(
+    conn: &Connection,
+    root: &str,
+    subtree: bool,
+    archived: bool,
+) -> Result<Vec<String>> {
+    let mut ids = vec![root.to_string()];
+    if !subtree {
+        return Ok(ids);
+    }
+    let mut visited: std::collections::BTreeSet<String> = std::collections::BTreeSet::new();
+    visited.insert(root.to_string());
+    let mut queue: std::collections::VecDeque<String> = std::collections::VecDeque::new();
+    queue.push_back(root.to_string());
+    while let Some(current) = queue.pop_front() {
+        let mut children =
+            SqliteRepo::get_active_outgoing_relations_of_type(conn, &current, "refinement")?;
+        children.sort();
+        for child in children {
+            if !visited.insert(child.clone()) {
+                continue;
+            }
+            let Some(record) = SqliteRepo::get_ettle_record(conn, &child)? else {
+                continue;
+            };
+            if record.tombstoned_at.is_some() {
+                continue;
+            }
+            if record.archived_at.is_some() == archived {
+                ids.push(child.clone());
+            }
+            queue.push_back(child);
+        }
+    }
+    Ok(ids)
+}
+
 // ---------------------------------------------------------------------------
 // handle_ettle_get
 // ---------------------------------------------------------------------------
 
-/// Get an Ettle record by ID.
+/// Get an Ettle record by ID or slug.
 ///
 /// Returns `NotFound` if the Ettle does not exist.
 /// Returns the record even if tombstoned (callers can inspect `tombstoned_at`).
 pub fn handle_ettle_get(conn: &Connection, ettle_id: &str) -> Result<EttleRecord> {
-    SqliteRepo::get_ettle_record(conn, ettle_id)?.ok_or_else(|| {
+    let resolved = resolve_ettle_ref(conn, ettle_id)?;
+    SqliteRepo::get_ettle_record(conn, &resolved)?.ok_or_else(|| {
         ExError::new(ExErrorKind::NotFound)
             .with_op("ettle_get")
             .with_entity_id(ettle_id)
@@ -301,15 +549,123 @@ pub fn handle_ettle_get(conn: &Connection, ettle_id: &str) -> Result<EttleRecord
     })
 }
 
+// ---------------------------------------------------------------------------
+// handle_ettle_list_referrers
+// ---------------------------------------------------------------------------
+
+/// List the `refinement` relations whose child is `ettle_id` (ID or slug),
+/// split into active and tombstoned.
+///
+/// Complements walking children via outgoing relations: together they make
+/// the refinement wiring around an Ettle fully visible, including history.
+/// Returns `NotFound` if the Ettle does not exist; tombstoned Ettles are
+/// allowed.
+pub fn handle_ettle_list_referrers(conn: &Connection, ettle_id: &str) -> Result<EttleReferrers> {
+    let child = handle_ettle_get(conn, ettle_id)?;
+    let relations = SqliteRepo::list_relations(
+        conn,
+        &RelationListOpts {
+            source_ettle_id: None,
+            target_ettle_id: Some(child.id.clone()),
+            relation_type: Some("refinement".to_string()),
+            include_tombstoned: true,
+        },
+    )?;
+
+    let mut referrers = EttleReferrers::default();
+    for rel in relations {
+        let parent =
+            SqliteRepo::get_ettle_record(conn, &rel.source_ettle_id)?.ok_or_else(|| {
+                ExError::new(ExErrorKind::Persistence)
+                    .with_op("ettle_list_referrers")
+                    .with_entity_id(&rel.id)
+                    .with_message(format!(
+                        "Relation {} comes from missing ettle {}",
+                        rel.id, rel.source_ettle_id
+                    ))
+            })?;
+        let ordinal = serde_json::from_str::<serde_json::Value>(&rel.properties_json)
+            .ok()
+            .and_then(|p| p.get("ordinal").and_then(serde_json::Value::as_u64));
+        let referrer = RefinementReferrer {
+            relation_id: rel.id,
+            parent_ettle_id: parent.id,
+            parent_title: parent.title,
+            parent_tombstoned: parent.tombstoned_at.is_some(),
+            ordinal,
+            created_at: rel.created_at,
+            tombstoned_at: rel.tombstoned_at,
+        };
+        if referrer.tombstoned_at.is_some() {
+            referrers.tombstoned.push(referrer);
+        } else {
+            referrers.active.push(referrer);
+        }
+    }
+    Ok(referrers)
+}
+
+// ---------------------------------------------------------------------------
+// resolve_ettle_ref
+// ---------------------------------------------------------------------------
+
+/// Resolve an Ettle reference (ID or slug) to its canonical Ettle ID.
+///
+/// An exact ID match wins over a slug match. References that match neither
+/// are returned unchanged, so the caller's own lookup reports `NotFound`
+/// with the reference the user actually typed.
+pub fn resolve_ettle_ref(conn: &Connection, reference: &str) -> Result<String> {
+    Ok(SqliteRepo::resolve_ettle_ref(conn, reference)?.unwrap_or_else(|| reference.to_string()))
+}
+
+// ---------------------------------------------------------------------------
+// ettle_content_digest
+// ---------------------------------------------------------------------------
+
+/// SHA-256 hex digest of an Ettle's editable content: title, why, what, how
+/// and content format.
+///
+/// Used as an optimistic-concurrency precondition by patch files: a change
+/// is only applied while the Ettle still has the digest the author saw.
+/// Spilled fields are read back from `cas`, so the digest covers the
+/// content wherever it is stored.
+///
+/// # Errors
+/// * `NotFound` / `Serialization` - a spilled field cannot be read back
+pub fn ettle_content_digest(record: &EttleRecord, cas: &FsStore) -> Result<String> {
+    use sha2::Digest;
+    let mut record = record.clone();
+    hydrate_ettle_content(&mut record, cas)?;
+    let content = serde_json::json!([
+        record.title,
+        record.why,
+        record.what,
+        record.how,
+        record.content_format,
+    ]);
+    let mut h = sha2::Sha256::new();
+    h.update(content.to_string().as_bytes());
+    Ok(format!("{:x}", h.finalize()))
+}
+
 // ---------------------------------------------------------------------------
 // handle_ettle_list
 // ---------------------------------------------------------------------------
 
-/// List Ettles with cursor-based pagination.
+/// List Ettles with cursor-based pagination and unsigned cursors.
 ///
 /// Invariants enforced:
 /// - `limit` must be 1..=500 (`InvalidInput`).
 pub fn handle_ettle_list(conn: &Connection, opts: EttleListOpts) -> Result<EttleListPage> {
+    handle_ettle_list_with_codec(conn, opts, &CursorCodec::unsigned())
+}
+
+/// [`handle_ettle_list`], encoding the next-page cursor with `codec`.
+pub fn handle_ettle_list_with_codec(
+    conn: &Connection,
+    opts: EttleListOpts,
+    codec: &CursorCodec,
+) -> Result<EttleListPage> {
     if opts.limit == 0 {
         return Err(ExError::new(ExErrorKind::InvalidInput)
             .with_op("ettle_list")
@@ -321,5 +677,5 @@ pub fn handle_ettle_list(conn: &Connection, opts: EttleListOpts) -> Result<Ettle
             .with_message("limit must not exceed 500"));
     }
 
-    SqliteRepo::list_ettles(conn, &opts)
+    SqliteRepo::list_ettles_with_codec(conn, &opts, codec)
 }
@@ -0,0 +1,135 @@
+//! Event-stream export of the store's history.
+//!
+//! The stream is the `command_log`: every command applied through
+//! `apply_command` appends one row in the same transaction as its write,
+//! naming the op and the entity it targeted or created. An event's `seq` is
+//! the row's `id`, which only grows and is never reused or rewritten, so a
+//! consumer that resumes with `after_seq` sees exactly the events it missed.
+//!
+//! Rows logged before migration 035 carry no op or entity. Snapshots
+//! committed through `apply_engine_command` directly (rather than
+//! `Command::SnapshotCommit`) and approval requests raised by a router are
+//! not commands and are not in the stream; the snapshot ledger export and
+//! `approval_events` cover them. Read-only.
+
+#![allow(clippy::result_large_err)]
+
+use std::io::Write;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_store::errors::Result;
+use ettlex_store::model::CommandLogRecord;
+use ettlex_store::repo::SqliteRepo;
+use rusqlite::Connection;
+use serde::Serialize;
+
+/// Log rows read per round trip.
+const READ_PAGE: usize = 500;
+
+/// One event of the history, serialised as one JSONL line.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HistoryEvent {
+    /// Position in the stream: the `command_log` row ID.
+    pub seq: u64,
+    /// RFC 3339 UTC timestamp with millisecond precision.
+    pub occurred_at: String,
+    /// Op name of the applied command, e.g. `ettle_create`,
+    /// `snapshot_commit`, `approval_transition` (`None` for rows logged
+    /// before migration 035).
+    pub op: Option<String>,
+    /// Entity the command targeted or created, if any.
+    pub entity_id: Option<String>,
+}
+
+/// Every event with `seq > after_seq`, in sequence order.
+///
+/// # Errors
+///
+/// `Persistence` on a database error; `Serialization` when a stored
+/// timestamp cannot be parsed.
+pub fn read_history(conn: &Connection, after_seq: u64) -> Result<Vec<HistoryEvent>> {
+    let mut events = Vec::new();
+    for_each_event(conn, after_seq, |event| {
+        events.push(event);
+        Ok(())
+    })?;
+    Ok(events)
+}
+
+/// Write the events with `seq > after_seq` to `out` as JSON lines; returns
+/// the number written.
+///
+/// # Errors
+///
+/// As [`read_history`], plus `Serialization` when an event cannot be
+/// encoded and `Io` when writing to `out` fails.
+pub fn export_history_jsonl<W: Write>(
+    conn: &Connection,
+    after_seq: u64,
+    mut out: W,
+) -> Result<usize> {
+    let io = |e: std::io::Error| {
+        ExError::new(ExErrorKind::Io)
+            .with_op("export_history_jsonl")
+            .with_message(e.to_string())
+    };
+
+    let mut count = 0;
+    for_each_event(conn, after_seq, |event| {
+        serde_json::to_writer(&mut out, &event).map_err(|e| {
+            ExError::new(ExErrorKind::Serialization)
+                .with_op("export_history_jsonl")
+                .with_entity_id(event.seq.to_string())
+                .with_message(e.to_string())
+        })?;
+        out.write_all(b"\n").map_err(io)?;
+        count += 1;
+        Ok(())
+    })?;
+    out.flush().map_err(io)?;
+    Ok(count)
+}
+
+/// Feed the events after `after_seq` to `f`, one page of log rows at a time.
+fn for_each_event(
+    conn: &Connection,
+    after_seq: u64,
+    mut f: impl FnMut(HistoryEvent) -> Result<()>,
+) -> Result<()> {
+    let mut after = i64::try_from(after_seq).unwrap_or(i64::MAX);
+    loop {
+        let page = SqliteRepo::list_command_log_after(conn, after, READ_PAGE)?;
+        let done = page.len() < READ_PAGE;
+        for record in page {
+            after = record.id;
+            f(to_event(record)?)?;
+        }
+        if done {
+            return Ok(());
+        }
+    }
+}
+
+fn to_event(record: CommandLogRecord) -> Result<HistoryEvent> {
+    let occurred_at = DateTime::parse_from_rfc3339(&record.applied_at)
+        .map(|t| {
+            t.with_timezone(&Utc)
+                .to_rfc3339_opts(SecondsFormat::Millis, true)
+        })
+        .map_err(|e| {
+            ExError::new(ExErrorKind::Serialization)
+                .with_op("read_history")
+                .with_entity_id(record.id.to_string())
+                .with_message(format!(
+                    "unparseable timestamp {:?}: {}",
+                    record.applied_at, e
+                ))
+        })?;
+    Ok(HistoryEvent {
+        seq: record.id as u64,
+        occurred_at,
+        op: record.op,
+        entity_id: record.entity_id,
+    })
+}
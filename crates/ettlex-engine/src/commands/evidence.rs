@@ -0,0 +1,248 @@
+//! Decision evidence quotas and retention.
+//!
+//! Limits come from the `evidence` object of the default profile payload:
+//!
+//! ```json
+//! { "evidence": {
+//!     "max_blob_bytes": 65536,
+//!     "max_total_bytes_per_decision": 262144,
+//!     "retention_days": 90 } }
+//! ```
+//!
+//! Every key is optional; an absent key (or no default profile) means no
+//! limit. Quotas are checked when a decision create or update supplies
+//! evidence. The retention sweep removes capture content belonging only to
+//! decisions tombstoned longer than the retention window.
+//!
+//! Evidence captures live in `decision_evidence_items`, not CAS, so pruning
+//! deletes those rows directly and leaves nothing for CAS GC to collect.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::model::Decision;
+use ettlex_core::ops::Store;
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_core::{log_op_end, log_op_error, log_op_start};
+use ettlex_store::cas::FsStore;
+use ettlex_store::errors::Result;
+use ettlex_store::profile::load_default_profile;
+use ettlex_store::repo::SqliteRepo;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use super::command::{apply_command_with_options, Command, CommandResult};
+use super::engine_command::CommandOptions;
+use super::settings::{evidence_retention_days, SETTING_EVIDENCE_RETENTION_DAYS};
+
+/// Profile payload key holding the [`EvidencePolicy`].
+pub const EVIDENCE_PROFILE_KEY: &str = "evidence";
+
+const MS_PER_DAY: i64 = 24 * 60 * 60 * 1000;
+
+/// Evidence limits and retention window. `None` fields are unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EvidencePolicy {
+    /// Largest capture content accepted, in bytes.
+    #[serde(default)]
+    pub max_blob_bytes: Option<u64>,
+    /// Largest excerpt plus capture content on one decision, in bytes.
+    #[serde(default)]
+    pub max_total_bytes_per_decision: Option<u64>,
+    /// Days a decision stays tombstoned before its capture may be pruned.
+    #[serde(default)]
+    pub retention_days: Option<u32>,
+}
+
+impl EvidencePolicy {
+    /// Read the policy from a profile payload; a missing key is the default.
+    pub fn from_profile_payload(payload: &serde_json::Value) -> Result<Self> {
+        match payload.get(EVIDENCE_PROFILE_KEY) {
+            None => Ok(Self::default()),
+            Some(value) => serde_json::from_value(value.clone()).map_err(|e| {
+                ExError::new(ExErrorKind::InvalidInput)
+                    .with_op("evidence_policy")
+                    .with_message(format!(
+                        "invalid '{}' profile key: {}",
+                        EVIDENCE_PROFILE_KEY, e
+                    ))
+            }),
+        }
+    }
+}
+
+/// The [`EvidencePolicy`] of the default profile, or no limits without one.
+pub fn load_evidence_policy(conn: &Connection) -> Result<EvidencePolicy> {
+    match load_default_profile(conn)? {
+        Some((_, _, payload)) => EvidencePolicy::from_profile_payload(&payload),
+        None => Ok(EvidencePolicy::default()),
+    }
+}
+
+/// Reject `decision` if its evidence exceeds `policy` (`InvalidEvidence`).
+pub(crate) fn check_evidence_quota(
+    policy: &EvidencePolicy,
+    store: &Store,
+    decision: &Decision,
+) -> Result<()> {
+    let capture_bytes = match &decision.evidence_capture_id {
+        Some(id) => store
+            .get_evidence_item(id)
+            .map(|item| item.content.len() as u64)
+            .unwrap_or(0),
+        None => 0,
+    };
+    let excerpt_bytes = decision
+        .evidence_excerpt
+        .as_ref()
+        .map_or(0, |e| e.len() as u64);
+
+    if let Some(max) = policy.max_blob_bytes {
+        if capture_bytes > max {
+            return Err(quota_error(
+                decision,
+                format!(
+                    "evidence capture is {} bytes; limit is {}",
+                    capture_bytes, max
+                ),
+            ));
+        }
+    }
+    if let Some(max) = policy.max_total_bytes_per_decision {
+        let total = capture_bytes + excerpt_bytes;
+        if total > max {
+            return Err(quota_error(
+                decision,
+                format!("decision evidence totals {} bytes; limit is {}", total, max),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn quota_error(decision: &Decision, message: String) -> ExError {
+    ExError::new(ExErrorKind::InvalidEvidence)
+        .with_op("evidence_quota")
+        .with_entity_id(&decision.decision_id)
+        .with_message(message)
+}
+
+/// One capture removed (or, in a dry run, eligible) by [`evidence_sweep`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PrunedEvidence {
+    pub evidence_capture_id: String,
+    /// A tombstoned decision that referenced the capture.
+    pub decision_id: String,
+    pub size_bytes: u64,
+}
+
+/// Outcome of [`evidence_sweep`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct EvidenceSweepReport {
+    /// Pruned captures, in capture ID order.
+    pub pruned: Vec<PrunedEvidence>,
+    pub bytes_freed: u64,
+}
+
+/// Prune captures of decisions tombstoned more than the retention window ago.
+///
+/// Applies `Command::EvidenceSweep`, so the prune is recorded in provenance
+/// and `command_log` like any other mutation; `dry_run` applies it with
+/// [`CommandOptions::dry_run`] and rolls it back.
+///
+/// `retention_days` overrides the `evidence.retention_days` setting, which
+/// overrides the profile's `retention_days`. Pruned
+/// decisions keep their `evidence_hash` but lose `evidence_capture_id`, so
+/// restoring one later yields a decision without capture content.
+///
+/// ## Errors
+///
+/// - `InvalidInput`: No retention window given or configured
+/// - `Persistence`: Database error
+pub fn evidence_sweep(
+    conn: &mut Connection,
+    cas: &FsStore,
+    retention_days: Option<u32>,
+    dry_run: bool,
+) -> Result<EvidenceSweepReport> {
+    log_op_start!("evidence_sweep");
+    let start = std::time::Instant::now();
+
+    let result = apply_command_with_options(
+        Command::EvidenceSweep { retention_days },
+        None,
+        CommandOptions { dry_run },
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .and_then(|(result, _)| match result {
+        CommandResult::EvidenceSweep {
+            pruned,
+            bytes_freed,
+        } => Ok(EvidenceSweepReport {
+            pruned,
+            bytes_freed,
+        }),
+        _ => Err(ExError::new(ExErrorKind::Internal)
+            .with_op("evidence_sweep")
+            .with_message("unexpected command result")),
+    });
+    match &result {
+        Ok(report) => log_op_end!(
+            "evidence_sweep",
+            duration_ms = start.elapsed().as_millis() as u64,
+            pruned = report.pruned.len()
+        ),
+        Err(e) => log_op_error!(
+            "evidence_sweep",
+            e.clone(),
+            duration_ms = start.elapsed().as_millis() as u64
+        ),
+    }
+    result
+}
+
+/// Prune expired captures. Applied as `Command::EvidenceSweep`, inside
+/// `apply_command`'s transaction.
+pub(crate) fn handle_evidence_sweep(
+    conn: &Connection,
+    retention_days: Option<u32>,
+) -> Result<EvidenceSweepReport> {
+    // Explicit argument, then the repository setting, then the profile
+    let configured = match evidence_retention_days(conn)? {
+        Some(days) => Some(days),
+        None => load_evidence_policy(conn)?.retention_days,
+    };
+    let days = retention_days.or(configured).ok_or_else(|| {
+        ExError::new(ExErrorKind::InvalidInput)
+            .with_op("evidence_sweep")
+            .with_message(format!(
+                "no retention window: pass one, set '{}', or set '{}.retention_days' in the default profile",
+                SETTING_EVIDENCE_RETENTION_DAYS, EVIDENCE_PROFILE_KEY
+            ))
+    })?;
+    let cutoff_ms = chrono::Utc::now().timestamp_millis() - i64::from(days) * MS_PER_DAY;
+
+    let expired = SqliteRepo::list_expired_evidence_items(conn, cutoff_ms)?;
+    let report = EvidenceSweepReport {
+        bytes_freed: expired.iter().map(|(_, _, size)| size).sum(),
+        pruned: expired
+            .into_iter()
+            .map(
+                |(evidence_capture_id, decision_id, size_bytes)| PrunedEvidence {
+                    evidence_capture_id,
+                    decision_id,
+                    size_bytes,
+                },
+            )
+            .collect(),
+    };
+    for item in &report.pruned {
+        SqliteRepo::prune_evidence_item(conn, &item.evidence_capture_id)?;
+    }
+    Ok(report)
+}
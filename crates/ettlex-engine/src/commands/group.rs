@@ -68,7 +68,7 @@ pub fn handle_group_get(conn: &Connection, group_id: String) -> Result<CommandRe
 // handle_group_list
 // ---------------------------------------------------------------------------
 
-/// List groups in deterministic order (created_at ASC, id ASC).
+/// List groups in deterministic order (created_at_ms ASC, id ASC).
 pub fn handle_group_list(conn: &Connection, include_tombstoned: bool) -> Result<CommandResult> {
     let items = SqliteRepo::list_groups(conn, include_tombstoned)?;
     Ok(CommandResult::GroupList { items })
@@ -116,6 +116,35 @@ pub fn handle_group_tombstone(conn: &mut Connection, group_id: String) -> Result
     Ok(CommandResult::GroupTombstone)
 }
 
+// ---------------------------------------------------------------------------
+// handle_group_restore
+// ---------------------------------------------------------------------------
+
+/// Restore a tombstoned group.
+///
+/// Invariants enforced:
+/// - Group must exist (`NotFound`).
+/// - Group must currently be tombstoned (`InvalidInput`).
+pub fn handle_group_restore(conn: &mut Connection, group_id: String) -> Result<CommandResult> {
+    let existing = SqliteRepo::get_group(conn, &group_id)?.ok_or_else(|| {
+        ExError::new(ExErrorKind::NotFound)
+            .with_op("group_restore")
+            .with_entity_id(&group_id)
+            .with_message(format!("Group not found: {}", group_id))
+    })?;
+
+    if existing.tombstoned_at.is_none() {
+        return Err(ExError::new(ExErrorKind::InvalidInput)
+            .with_op("group_restore")
+            .with_entity_id(&group_id)
+            .with_message(format!("Group is not tombstoned: {}", group_id)));
+    }
+
+    SqliteRepo::restore_group(conn, &group_id)?;
+
+    Ok(CommandResult::GroupRestore)
+}
+
 // ---------------------------------------------------------------------------
 // handle_group_member_add
 // ---------------------------------------------------------------------------
@@ -231,7 +260,7 @@ pub fn handle_group_member_remove(
 // handle_group_member_list
 // ---------------------------------------------------------------------------
 
-/// List members of a group in deterministic order (created_at ASC, id ASC).
+/// List members of a group in deterministic order (created_at_ms ASC, id ASC).
 pub fn handle_group_member_list(
     conn: &Connection,
     group_id: String,
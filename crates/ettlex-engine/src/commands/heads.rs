@@ -0,0 +1,251 @@
+//! Per-root ledger heads with a drift indicator.
+//!
+//! Backs the `HeadsList` engine query, which answers "which architectures
+//! are stale" in one call. For each root it reports the latest committed
+//! snapshot, how long ago it was committed, and whether the working state
+//! still produces the same `semantic_manifest_digest`.
+//!
+//! The working digest is computed the way the head was: the manifest is
+//! regenerated from the current Ettles for the head's `root_ettle_id`, with
+//! the head's policy, profile, store schema version and seed, and with the
+//! decision lifecycle check and topology digest the head recorded, if any.
+//! Drift therefore reflects changes to architecture content, structure and
+//! linked decisions only, not to those references.
+
+#![allow(clippy::result_large_err)]
+
+use std::collections::BTreeMap;
+
+use ettlex_core::diff::ParsedManifest;
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::ops::Store;
+use ettlex_core::snapshot::decision_lifecycle::{
+    record_decision_lifecycle, DecisionLifecycleSection,
+};
+use ettlex_core::snapshot::digest::record_topology_digest;
+use ettlex_core::snapshot::manifest::generate_manifest_with_input;
+use ettlex_core::traversal::rt::compute_rt;
+use ettlex_store::cas::FsStore;
+use ettlex_store::errors::Result;
+use ettlex_store::model::ROOT_STATUS_ACTIVE;
+use ettlex_store::repo::hydration::load_tree;
+use ettlex_store::repo::SqliteRepo;
+use ettlex_store::snapshot::query::fetch_manifest_bytes_by_digest;
+use rusqlite::Connection;
+use serde::Serialize;
+
+use crate::snapshot::{manifest_eval_input, refinement_edges};
+
+/// Whether a root's working state still matches its head snapshot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HeadDrift {
+    /// The working state regenerates the head's semantic digest.
+    InSync,
+    /// The working state differs, or the root Ettle is gone.
+    Drifted,
+    /// The root has never been snapshotted.
+    NoHead,
+    /// The head manifest is missing from CAS or unreadable.
+    Unknown,
+}
+
+/// The ledger head of one root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RootHeadStatus {
+    pub root_ettle_id: String,
+    /// Registry display name, when the root is registered.
+    pub display_name: Option<String>,
+    /// `None` together with the other head fields when `drift` is `NoHead`.
+    pub snapshot_id: Option<String>,
+    pub manifest_digest: Option<String>,
+    pub semantic_manifest_digest: Option<String>,
+    /// Commit time, Unix milliseconds.
+    pub committed_at_ms: Option<i64>,
+    /// Milliseconds between the commit and the query (0 if the clock is behind).
+    pub age_ms: Option<u64>,
+    /// Semantic digest of the regenerated working manifest, when one could
+    /// be generated.
+    pub working_semantic_digest: Option<String>,
+    pub drift: HeadDrift,
+}
+
+/// Result of a `HeadsList` query.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HeadsList {
+    /// One entry per root, sorted by root Ettle ID: every root with a
+    /// committed snapshot plus registered roots without one. Roots retired
+    /// in the registry are left out.
+    pub heads: Vec<RootHeadStatus>,
+}
+
+impl HeadsList {
+    /// Heads whose working state has drifted.
+    pub fn stale(&self) -> impl Iterator<Item = &RootHeadStatus> {
+        self.heads.iter().filter(|h| h.drift == HeadDrift::Drifted)
+    }
+}
+
+struct HeadRow {
+    snapshot_id: String,
+    manifest_digest: String,
+    semantic_manifest_digest: String,
+    created_at: i64,
+}
+
+/// Build the heads list, measuring ages against `now_ms`.
+///
+/// # Errors
+/// * `Persistence` - a SQLite read fails
+/// * `Io` - a CAS read fails for a reason other than a missing blob
+pub(crate) fn list_heads(conn: &Connection, cas: &FsStore, now_ms: i64) -> Result<HeadsList> {
+    let op = "heads_list";
+    let persistence = |e: rusqlite::Error| {
+        ExError::new(ExErrorKind::Persistence)
+            .with_op(op)
+            .with_message(e.to_string())
+    };
+
+    let mut roots: BTreeMap<String, Option<String>> = BTreeMap::new();
+    let mut retired = Vec::new();
+    for root in SqliteRepo::list_roots(conn, true)? {
+        if root.status == ROOT_STATUS_ACTIVE {
+            roots.insert(root.ettle_id, Some(root.display_name));
+        } else {
+            retired.push(root.ettle_id);
+        }
+    }
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT s.root_ettle_id, s.snapshot_id, s.manifest_digest,
+                    s.semantic_manifest_digest, s.created_at
+             FROM snapshots s
+             WHERE s.status = 'committed'
+               AND s.id = (SELECT MAX(id) FROM snapshots
+                           WHERE root_ettle_id = s.root_ettle_id AND status = 'committed')",
+        )
+        .map_err(persistence)?;
+    let mut heads: BTreeMap<String, HeadRow> = BTreeMap::new();
+    for row in stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                HeadRow {
+                    snapshot_id: row.get(1)?,
+                    manifest_digest: row.get(2)?,
+                    semantic_manifest_digest: row.get(3)?,
+                    created_at: row.get(4)?,
+                },
+            ))
+        })
+        .map_err(persistence)?
+    {
+        let (root_ettle_id, head) = row.map_err(persistence)?;
+        if !retired.contains(&root_ettle_id) {
+            roots.entry(root_ettle_id.clone()).or_insert(None);
+            heads.insert(root_ettle_id, head);
+        }
+    }
+
+    let store = if heads.is_empty() {
+        Store::new()
+    } else {
+        load_tree(conn)?
+    };
+
+    let mut out = Vec::with_capacity(roots.len());
+    for (root_ettle_id, display_name) in roots {
+        let Some(head) = heads.remove(&root_ettle_id) else {
+            out.push(RootHeadStatus {
+                root_ettle_id,
+                display_name,
+                snapshot_id: None,
+                manifest_digest: None,
+                semantic_manifest_digest: None,
+                committed_at_ms: None,
+                age_ms: None,
+                working_semantic_digest: None,
+                drift: HeadDrift::NoHead,
+            });
+            continue;
+        };
+
+        let working_semantic_digest = working_digest(conn, cas, &store, &head.manifest_digest)?;
+        let drift = match &working_semantic_digest {
+            WorkingDigest::Unknown => HeadDrift::Unknown,
+            WorkingDigest::Gone => HeadDrift::Drifted,
+            WorkingDigest::Digest(d) if *d == head.semantic_manifest_digest => HeadDrift::InSync,
+            WorkingDigest::Digest(_) => HeadDrift::Drifted,
+        };
+        out.push(RootHeadStatus {
+            root_ettle_id,
+            display_name,
+            snapshot_id: Some(head.snapshot_id),
+            manifest_digest: Some(head.manifest_digest),
+            semantic_manifest_digest: Some(head.semantic_manifest_digest),
+            committed_at_ms: Some(head.created_at),
+            age_ms: Some(now_ms.saturating_sub(head.created_at).max(0) as u64),
+            working_semantic_digest: match working_semantic_digest {
+                WorkingDigest::Digest(d) => Some(d),
+                _ => None,
+            },
+            drift,
+        });
+    }
+
+    Ok(HeadsList { heads: out })
+}
+
+enum WorkingDigest {
+    Digest(String),
+    /// The head's root Ettle no longer exists or is tombstoned.
+    Gone,
+    /// The head manifest could not be loaded.
+    Unknown,
+}
+
+/// Regenerate the head's manifest from the working state.
+fn working_digest(
+    conn: &Connection,
+    cas: &FsStore,
+    store: &Store,
+    manifest_digest: &str,
+) -> Result<WorkingDigest> {
+    let bytes = match fetch_manifest_bytes_by_digest(cas, manifest_digest) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == ExErrorKind::MissingBlob => return Ok(WorkingDigest::Unknown),
+        Err(e) => return Err(e),
+    };
+    let Ok(parsed) = ParsedManifest::parse(&bytes) else {
+        return Ok(WorkingDigest::Unknown);
+    };
+    let head = parsed.manifest;
+
+    let live = SqliteRepo::get_ettle_record(conn, &head.root_ettle_id)?
+        .is_some_and(|r| r.tombstoned_at.is_none());
+    if !live {
+        return Ok(WorkingDigest::Gone);
+    }
+    let ept = compute_rt(store, &head.root_ettle_id)?;
+    let input = manifest_eval_input(conn, cas, &ept, &head.profile_ref)?;
+    let mut working = generate_manifest_with_input(
+        ept.clone(),
+        head.policy_ref,
+        head.profile_ref,
+        head.root_ettle_id,
+        head.store_schema_version,
+        head.seed_digest,
+        store,
+        &input,
+    )?;
+    if let Some(section) = head.decision_lifecycle {
+        let section = DecisionLifecycleSection::check(&ept, store, section.mode);
+        record_decision_lifecycle(&mut working, section)?;
+    }
+    if head.topology_digest.is_some() {
+        let edges = refinement_edges(conn, &working.ept)?;
+        record_topology_digest(&mut working, &edges)?;
+    }
+    Ok(WorkingDigest::Digest(working.semantic_manifest_digest))
+}
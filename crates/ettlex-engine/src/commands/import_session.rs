@@ -0,0 +1,200 @@
+//! Import sessions — crash recovery for multi-transaction bulk imports.
+//!
+//! An importer opens a session before writing anything and records each
+//! Ettle and relation it creates in the same transaction as the entity.
+//! A session still `running` after its importer exited was interrupted; it
+//! can be rolled back here, or resumed by the importer from the recorded
+//! items (after [`verify_import_source`] confirms the source is unchanged).
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_store::cas::FsStore;
+use ettlex_store::errors::Result;
+use ettlex_store::model::{
+    ImportSessionItem, ImportSessionRecord, IMPORT_SESSION_COMPLETED, IMPORT_SESSION_ROLLED_BACK,
+    IMPORT_SESSION_RUNNING,
+};
+use ettlex_store::repo::SqliteRepo;
+use rusqlite::Connection;
+use sha2::{Digest, Sha256};
+
+use super::command::{apply_command, Command, CommandResult};
+
+/// Entities removed by [`rollback_import_session`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImportRollback {
+    pub relations: usize,
+    pub ettles: usize,
+}
+
+/// SHA-256 hex digest of an import source, as stored on its session.
+pub fn import_source_digest(source: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(source))
+}
+
+/// Open a `running` session for an import of `source` (read from
+/// `source_path`). `options` must hold whatever the importer needs to
+/// resume.
+pub fn open_import_session(
+    conn: &Connection,
+    kind: &str,
+    source_path: &str,
+    source: &[u8],
+    options: &serde_json::Value,
+) -> Result<ImportSessionRecord> {
+    let record = ImportSessionRecord {
+        session_id: format!("imp:{}", uuid::Uuid::now_v7()),
+        kind: kind.to_string(),
+        source_path: source_path.to_string(),
+        source_digest: import_source_digest(source),
+        options_json: options.to_string(),
+        status: IMPORT_SESSION_RUNNING.to_string(),
+        started_at_ms: chrono::Utc::now().timestamp_millis(),
+        finished_at_ms: None,
+    };
+    SqliteRepo::insert_import_session(conn, &record)?;
+    Ok(record)
+}
+
+/// Record an entity created by a session. Call inside the transaction that
+/// created it.
+pub fn record_import_item(
+    conn: &Connection,
+    session_id: &str,
+    item_kind: &str,
+    source_line: u64,
+    entity_id: &str,
+) -> Result<()> {
+    SqliteRepo::insert_import_session_item(
+        conn,
+        session_id,
+        &ImportSessionItem {
+            item_kind: item_kind.to_string(),
+            source_line,
+            entity_id: entity_id.to_string(),
+        },
+    )
+}
+
+/// Get a session that is still `running`.
+///
+/// # Errors
+/// * `NotFound` - No session has this ID
+/// * `InvalidInput` - The session already completed or was rolled back
+pub fn running_import_session(conn: &Connection, session_id: &str) -> Result<ImportSessionRecord> {
+    let session = SqliteRepo::get_import_session(conn, session_id)?.ok_or_else(|| {
+        ExError::new(ExErrorKind::NotFound)
+            .with_op("import_session")
+            .with_entity_id(session_id)
+            .with_message("import session not found")
+    })?;
+    if session.status != IMPORT_SESSION_RUNNING {
+        return Err(ExError::new(ExErrorKind::InvalidInput)
+            .with_op("import_session")
+            .with_entity_id(session_id)
+            .with_message(format!("import session is already {}", session.status)));
+    }
+    Ok(session)
+}
+
+/// Sessions left `running`, oldest first. Unless an import is in progress
+/// right now, these were interrupted.
+pub fn incomplete_import_sessions(conn: &Connection) -> Result<Vec<ImportSessionRecord>> {
+    SqliteRepo::list_import_sessions(conn, Some(IMPORT_SESSION_RUNNING))
+}
+
+/// Check that `source` is the file a session started from.
+///
+/// # Errors
+/// * `HeadMismatch` - The source changed since the session started
+pub fn verify_import_source(session: &ImportSessionRecord, source: &[u8]) -> Result<()> {
+    let actual = import_source_digest(source);
+    if actual != session.source_digest {
+        return Err(ExError::new(ExErrorKind::HeadMismatch)
+            .with_op("import_session_resume")
+            .with_entity_id(&session.session_id)
+            .with_message(format!(
+                "{} changed since the import started (expected {}, found {})",
+                session.source_path, session.source_digest, actual
+            )));
+    }
+    Ok(())
+}
+
+/// Mark a running session completed.
+pub fn complete_import_session(conn: &Connection, session_id: &str) -> Result<()> {
+    running_import_session(conn, session_id)?;
+    SqliteRepo::finish_import_session(
+        conn,
+        session_id,
+        IMPORT_SESSION_COMPLETED,
+        chrono::Utc::now().timestamp_millis(),
+    )?;
+    Ok(())
+}
+
+/// Roll back a running session by applying `Command::ImportSessionRollback`.
+///
+/// # Errors
+/// As [`handle_import_session_rollback`].
+pub fn rollback_import_session(
+    conn: &mut Connection,
+    cas: &FsStore,
+    session_id: &str,
+) -> Result<ImportRollback> {
+    let cmd = Command::ImportSessionRollback {
+        session_id: session_id.to_string(),
+    };
+    match apply_command(
+        cmd,
+        None,
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )? {
+        (CommandResult::ImportSessionRollback { relations, ettles }, _) => {
+            Ok(ImportRollback { relations, ettles })
+        }
+        _ => Err(ExError::new(ExErrorKind::Internal)
+            .with_op("import_session_rollback")
+            .with_message("unexpected command result")),
+    }
+}
+
+/// Delete everything a running session created and mark it rolled back.
+/// Applied as `Command::ImportSessionRollback`, inside `apply_command`'s
+/// transaction. The session's own `ettle_create` / `relation_create` undo
+/// journal entries go with the entities.
+///
+/// # Errors
+/// * `NotFound` / `InvalidInput` - See [`running_import_session`]
+/// * `HasActiveDependants` - Rows created outside the session reference one
+///   of its Ettles or relations; nothing is deleted
+pub(crate) fn handle_import_session_rollback(
+    conn: &Connection,
+    session_id: &str,
+) -> Result<ImportRollback> {
+    running_import_session(conn, session_id)?;
+    let external = SqliteRepo::import_session_external_refs(conn, session_id)?;
+    if !external.is_empty() {
+        return Err(ExError::new(ExErrorKind::HasActiveDependants)
+            .with_op("import_session_rollback")
+            .with_entity_id(session_id)
+            .with_message(format!(
+                "rows created outside the import reference its entities: {}",
+                external.join(", ")
+            )));
+    }
+    let (relations, ettles) = SqliteRepo::delete_import_session_entities(conn, session_id)?;
+    SqliteRepo::finish_import_session(
+        conn,
+        session_id,
+        IMPORT_SESSION_ROLLED_BACK,
+        chrono::Utc::now().timestamp_millis(),
+    )?;
+    Ok(ImportRollback { relations, ettles })
+}
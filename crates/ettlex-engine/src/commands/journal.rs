@@ -0,0 +1,879 @@
+//! Undo/redo journal for reversible write commands.
+//!
+//! Every successful [`apply_command`](super::command::apply_command) of a
+//! reversible command appends a `command_journal` row holding two lists of
+//! [`JournalStep`]s: one that reverts the command and one that reapplies it.
+//! Steps name concrete entity IDs and are replayed as ordinary commands, so
+//! an undo goes through the same invariant checks as any other write.
+//!
+//! [`undo`] reverts the newest applied entry and [`redo`] reapplies the
+//! oldest undone one. Recording a new entry discards the redo history.
+//! Commands with no clean inverse (snapshot commits, approval transitions,
+//! policy/profile/decision/comment creation, bulk constraint operations,
+//! content renames, root registry and assignment changes) are not journaled.
+//! Before replaying, undo checks that the fields the entry set still hold the
+//! values it recorded (and redo that they still hold the undone values), so
+//! an unjournaled write in between is reported as `HeadMismatch` rather than
+//! silently overwritten.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::approval_router::ApprovalRouter;
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::policy_provider::PolicyProvider;
+use ettlex_store::cas::FsStore;
+use ettlex_store::errors::Result;
+use ettlex_store::model::{
+    EttleRecord, JournalRecord, RelationListOpts, JOURNAL_APPLIED, JOURNAL_UNDONE,
+};
+use ettlex_store::repo::SqliteRepo;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use super::command::{apply_command_unjournaled, Command, CommandResult};
+use super::content_spill::hydrate_ettle_content;
+
+/// One replayable write in an undo or redo list.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum JournalStep {
+    EttleTombstone {
+        ettle_id: String,
+    },
+    EttleRestore {
+        ettle_id: String,
+    },
+    /// Set the listed Ettle fields; absent fields are left alone.
+    EttleSet {
+        ettle_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        why: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        what: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        how: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        link: Option<LinkState>,
+    },
+    EttleArchive {
+        ettle_id: String,
+    },
+    EttleUnarchive {
+        ettle_id: String,
+    },
+    EttleSetContentFormat {
+        ettle_id: String,
+        content_format: String,
+    },
+    RelationTombstone {
+        relation_id: String,
+    },
+    RelationRestore {
+        relation_id: String,
+    },
+    RelationSetProperties {
+        relation_id: String,
+        properties_json: JsonValue,
+    },
+    GroupTombstone {
+        group_id: String,
+    },
+    GroupRestore {
+        group_id: String,
+    },
+    GroupMemberAdd {
+        group_id: String,
+        ettle_id: String,
+    },
+    GroupMemberRemove {
+        group_id: String,
+        ettle_id: String,
+    },
+    CommentResolve {
+        comment_id: String,
+    },
+    CommentReopen {
+        comment_id: String,
+    },
+}
+
+/// Reasoning link an [`JournalStep::EttleSet`] writes; `id: None` clears it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkState {
+    pub id: Option<String>,
+    pub link_type: Option<String>,
+}
+
+impl JournalStep {
+    fn into_command(self) -> Command {
+        match self {
+            JournalStep::EttleTombstone { ettle_id } => Command::EttleTombstone { ettle_id },
+            JournalStep::EttleRestore { ettle_id } => Command::EttleRestore { ettle_id },
+            JournalStep::EttleSet {
+                ettle_id,
+                title,
+                why,
+                what,
+                how,
+                link,
+            } => {
+                let (reasoning_link_id, reasoning_link_type) = match link {
+                    Some(LinkState {
+                        id: Some(id),
+                        link_type,
+                    }) => (Some(Some(id)), Some(link_type)),
+                    Some(LinkState { id: None, .. }) => (Some(None), None),
+                    None => (None, None),
+                };
+                Command::EttleUpdate {
+                    ettle_id,
+                    title,
+                    why,
+                    what,
+                    how,
+                    reasoning_link_id,
+                    reasoning_link_type,
+                }
+            }
+            JournalStep::EttleArchive { ettle_id } => Command::EttleArchive {
+                ettle_id,
+                subtree: false,
+            },
+            JournalStep::EttleUnarchive { ettle_id } => Command::EttleUnarchive {
+                ettle_id,
+                subtree: false,
+            },
+            JournalStep::EttleSetContentFormat {
+                ettle_id,
+                content_format,
+            } => Command::EttleSetContentFormat {
+                ettle_id,
+                content_format,
+            },
+            JournalStep::RelationTombstone { relation_id } => {
+                Command::RelationTombstone { relation_id }
+            }
+            JournalStep::RelationRestore { relation_id } => {
+                Command::RelationRestore { relation_id }
+            }
+            JournalStep::RelationSetProperties {
+                relation_id,
+                properties_json,
+            } => Command::RelationUpdate {
+                relation_id,
+                properties_json: Some(properties_json),
+            },
+            JournalStep::GroupTombstone { group_id } => Command::GroupTombstone { group_id },
+            JournalStep::GroupRestore { group_id } => Command::GroupRestore { group_id },
+            JournalStep::GroupMemberAdd { group_id, ettle_id } => {
+                Command::GroupMemberAdd { group_id, ettle_id }
+            }
+            JournalStep::GroupMemberRemove { group_id, ettle_id } => {
+                Command::GroupMemberRemove { group_id, ettle_id }
+            }
+            JournalStep::CommentResolve { comment_id } => Command::CommentResolve { comment_id },
+            JournalStep::CommentReopen { comment_id } => Command::CommentReopen { comment_id },
+        }
+    }
+}
+
+/// The journal entry an [`undo`] or [`redo`] replayed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JournalEntry {
+    pub seq: i64,
+    pub op: String,
+    pub entity_id: Option<String>,
+}
+
+impl From<JournalRecord> for JournalEntry {
+    fn from(record: JournalRecord) -> Self {
+        JournalEntry {
+            seq: record.seq,
+            op: record.op,
+            entity_id: record.entity_id,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Recording
+// ---------------------------------------------------------------------------
+
+/// State captured before a reversible command runs.
+pub(crate) struct Pending {
+    op: &'static str,
+    entity_id: Option<String>,
+    before: Before,
+}
+
+enum Before {
+    /// Both step lists are known up front.
+    Steps {
+        undo: Vec<JournalStep>,
+        redo: Vec<JournalStep>,
+    },
+    /// The command creates an entity whose ID is in the result.
+    Created,
+    /// The Ettle as it was, with spilled content hydrated.
+    EttleUpdate(Box<EttleRecord>),
+    /// The affected IDs are in the result.
+    Archive,
+    Unarchive,
+    ContentFormat {
+        ettle_id: String,
+        previous: String,
+    },
+    RelationProperties {
+        relation_id: String,
+        previous: String,
+    },
+}
+
+/// Capture what is needed to reverse `cmd`, before it runs.
+///
+/// Returns `None` for commands that are not journaled, and for targets that
+/// do not exist (the command itself will report that).
+pub(crate) fn prepare(conn: &Connection, cas: &FsStore, cmd: &Command) -> Result<Option<Pending>> {
+    let pending = |op, entity_id: &str, before| {
+        Some(Pending {
+            op,
+            entity_id: Some(entity_id.to_string()),
+            before,
+        })
+    };
+    let swap = |undo: JournalStep, redo: JournalStep| Before::Steps {
+        undo: vec![undo],
+        redo: vec![redo],
+    };
+    Ok(match cmd {
+        Command::EttleCreate { .. } => Some(Pending {
+            op: "ettle_create",
+            entity_id: None,
+            before: Before::Created,
+        }),
+        Command::RelationCreate { .. } => Some(Pending {
+            op: "relation_create",
+            entity_id: None,
+            before: Before::Created,
+        }),
+        Command::GroupCreate { .. } => Some(Pending {
+            op: "group_create",
+            entity_id: None,
+            before: Before::Created,
+        }),
+        Command::EttleUpdate { ettle_id, .. } => {
+            let Some(mut record) = SqliteRepo::get_ettle_record(conn, ettle_id)? else {
+                return Ok(None);
+            };
+            hydrate_ettle_content(&mut record, cas)?;
+            pending(
+                "ettle_update",
+                ettle_id,
+                Before::EttleUpdate(Box::new(record)),
+            )
+        }
+        Command::EttleTombstone { ettle_id } => {
+            // Tombstoning detaches incoming constraints; undo reattaches them
+            let attachments = SqliteRepo::list_relations(
+                conn,
+                &RelationListOpts {
+                    target_ettle_id: Some(ettle_id.clone()),
+                    relation_type: Some("constraint".to_string()),
+                    ..Default::default()
+                },
+            )?;
+            let mut undo = vec![JournalStep::EttleRestore {
+                ettle_id: ettle_id.clone(),
+            }];
+            undo.extend(
+                attachments
+                    .into_iter()
+                    .map(|r| JournalStep::RelationRestore { relation_id: r.id }),
+            );
+            let redo = vec![JournalStep::EttleTombstone {
+                ettle_id: ettle_id.clone(),
+            }];
+            pending("ettle_tombstone", ettle_id, Before::Steps { undo, redo })
+        }
+        Command::EttleRestore { ettle_id } => pending(
+            "ettle_restore",
+            ettle_id,
+            swap(
+                JournalStep::EttleTombstone {
+                    ettle_id: ettle_id.clone(),
+                },
+                JournalStep::EttleRestore {
+                    ettle_id: ettle_id.clone(),
+                },
+            ),
+        ),
+        Command::EttleArchive { ettle_id, .. } => {
+            pending("ettle_archive", ettle_id, Before::Archive)
+        }
+        Command::EttleUnarchive { ettle_id, .. } => {
+            pending("ettle_unarchive", ettle_id, Before::Unarchive)
+        }
+        Command::EttleSetContentFormat { ettle_id, .. } => {
+            let Some(record) = SqliteRepo::get_ettle_record(conn, ettle_id)? else {
+                return Ok(None);
+            };
+            pending(
+                "ettle_set_content_format",
+                ettle_id,
+                Before::ContentFormat {
+                    ettle_id: ettle_id.clone(),
+                    previous: record.content_format,
+                },
+            )
+        }
+        Command::RelationUpdate { relation_id, .. } => {
+            let Some(record) = SqliteRepo::get_relation(conn, relation_id)? else {
+                return Ok(None);
+            };
+            pending(
+                "relation_update",
+                relation_id,
+                Before::RelationProperties {
+                    relation_id: relation_id.clone(),
+                    previous: record.properties_json,
+                },
+            )
+        }
+        Command::RelationTombstone { relation_id } => pending(
+            "relation_tombstone",
+            relation_id,
+            swap(
+                JournalStep::RelationRestore {
+                    relation_id: relation_id.clone(),
+                },
+                JournalStep::RelationTombstone {
+                    relation_id: relation_id.clone(),
+                },
+            ),
+        ),
+        Command::RelationRestore { relation_id } => pending(
+            "relation_restore",
+            relation_id,
+            swap(
+                JournalStep::RelationTombstone {
+                    relation_id: relation_id.clone(),
+                },
+                JournalStep::RelationRestore {
+                    relation_id: relation_id.clone(),
+                },
+            ),
+        ),
+        Command::GroupTombstone { group_id } => pending(
+            "group_tombstone",
+            group_id,
+            swap(
+                JournalStep::GroupRestore {
+                    group_id: group_id.clone(),
+                },
+                JournalStep::GroupTombstone {
+                    group_id: group_id.clone(),
+                },
+            ),
+        ),
+        Command::GroupRestore { group_id } => pending(
+            "group_restore",
+            group_id,
+            swap(
+                JournalStep::GroupTombstone {
+                    group_id: group_id.clone(),
+                },
+                JournalStep::GroupRestore {
+                    group_id: group_id.clone(),
+                },
+            ),
+        ),
+        Command::GroupMemberAdd { group_id, ettle_id } => pending(
+            "group_member_add",
+            group_id,
+            swap(
+                JournalStep::GroupMemberRemove {
+                    group_id: group_id.clone(),
+                    ettle_id: ettle_id.clone(),
+                },
+                JournalStep::GroupMemberAdd {
+                    group_id: group_id.clone(),
+                    ettle_id: ettle_id.clone(),
+                },
+            ),
+        ),
+        Command::GroupMemberRemove { group_id, ettle_id } => pending(
+            "group_member_remove",
+            group_id,
+            swap(
+                JournalStep::GroupMemberAdd {
+                    group_id: group_id.clone(),
+                    ettle_id: ettle_id.clone(),
+                },
+                JournalStep::GroupMemberRemove {
+                    group_id: group_id.clone(),
+                    ettle_id: ettle_id.clone(),
+                },
+            ),
+        ),
+        Command::CommentResolve { comment_id } => pending(
+            "comment_resolve",
+            comment_id,
+            swap(
+                JournalStep::CommentReopen {
+                    comment_id: comment_id.clone(),
+                },
+                JournalStep::CommentResolve {
+                    comment_id: comment_id.clone(),
+                },
+            ),
+        ),
+        Command::CommentReopen { comment_id } => pending(
+            "comment_reopen",
+            comment_id,
+            swap(
+                JournalStep::CommentResolve {
+                    comment_id: comment_id.clone(),
+                },
+                JournalStep::CommentReopen {
+                    comment_id: comment_id.clone(),
+                },
+            ),
+        ),
+        _ => None,
+    })
+}
+
+/// Append the journal entry for a command that succeeded with `result`,
+/// discarding any redo history. An `EttleUpdate` that changed nothing is
+/// not recorded.
+pub(crate) fn record(
+    conn: &Connection,
+    cas: &FsStore,
+    pending: Pending,
+    result: &CommandResult,
+) -> Result<()> {
+    let Pending {
+        op,
+        mut entity_id,
+        before,
+    } = pending;
+    let (undo, redo) = match (before, result) {
+        (Before::Steps { undo, redo }, _) => (undo, redo),
+        (Before::Created, CommandResult::EttleCreate { ettle_id }) => {
+            entity_id = Some(ettle_id.clone());
+            (
+                vec![JournalStep::EttleTombstone {
+                    ettle_id: ettle_id.clone(),
+                }],
+                vec![JournalStep::EttleRestore {
+                    ettle_id: ettle_id.clone(),
+                }],
+            )
+        }
+        (Before::Created, CommandResult::RelationCreate { relation_id }) => {
+            entity_id = Some(relation_id.clone());
+            (
+                vec![JournalStep::RelationTombstone {
+                    relation_id: relation_id.clone(),
+                }],
+                vec![JournalStep::RelationRestore {
+                    relation_id: relation_id.clone(),
+                }],
+            )
+        }
+        (Before::Created, CommandResult::GroupCreate { group_id }) => {
+            entity_id = Some(group_id.clone());
+            (
+                vec![JournalStep::GroupTombstone {
+                    group_id: group_id.clone(),
+                }],
+                vec![JournalStep::GroupRestore {
+                    group_id: group_id.clone(),
+                }],
+            )
+        }
+        (Before::EttleUpdate(before), _) => {
+            let Some(mut after) = SqliteRepo::get_ettle_record(conn, &before.id)? else {
+                return Ok(());
+            };
+            hydrate_ettle_content(&mut after, cas)?;
+            match ettle_update_steps(&before, &after) {
+                Some(steps) => steps,
+                None => return Ok(()),
+            }
+        }
+        (Before::Archive, CommandResult::EttleArchive { archived_ettle_ids }) => (
+            archived_ettle_ids
+                .iter()
+                .map(|id| JournalStep::EttleUnarchive {
+                    ettle_id: id.clone(),
+                })
+                .collect(),
+            archived_ettle_ids
+                .iter()
+                .map(|id| JournalStep::EttleArchive {
+                    ettle_id: id.clone(),
+                })
+                .collect(),
+        ),
+        (
+            Before::Unarchive,
+            CommandResult::EttleUnarchive {
+                unarchived_ettle_ids,
+            },
+        ) => (
+            unarchived_ettle_ids
+                .iter()
+                .map(|id| JournalStep::EttleArchive {
+                    ettle_id: id.clone(),
+                })
+                .collect(),
+            unarchived_ettle_ids
+                .iter()
+                .map(|id| JournalStep::EttleUnarchive {
+                    ettle_id: id.clone(),
+                })
+                .collect(),
+        ),
+        (Before::ContentFormat { ettle_id, previous }, _) => {
+            let Some(after) = SqliteRepo::get_ettle_record(conn, &ettle_id)? else {
+                return Ok(());
+            };
+            if after.content_format == previous {
+                return Ok(());
+            }
+            (
+                vec![JournalStep::EttleSetContentFormat {
+                    ettle_id: ettle_id.clone(),
+                    content_format: previous,
+                }],
+                vec![JournalStep::EttleSetContentFormat {
+                    ettle_id,
+                    content_format: after.content_format,
+                }],
+            )
+        }
+        (
+            Before::RelationProperties {
+                relation_id,
+                previous,
+            },
+            _,
+        ) => {
+            let Some(after) = SqliteRepo::get_relation(conn, &relation_id)? else {
+                return Ok(());
+            };
+            (
+                vec![JournalStep::RelationSetProperties {
+                    relation_id: relation_id.clone(),
+                    properties_json: parse_properties(&relation_id, &previous)?,
+                }],
+                vec![JournalStep::RelationSetProperties {
+                    properties_json: parse_properties(&relation_id, &after.properties_json)?,
+                    relation_id,
+                }],
+            )
+        }
+        (_, other) => {
+            return Err(ExError::new(ExErrorKind::Internal)
+                .with_op("command_journal")
+                .with_message(format!("unexpected result for {}: {:?}", op, other)))
+        }
+    };
+
+    SqliteRepo::discard_undone_journal_entries(conn)?;
+    SqliteRepo::insert_journal_entry(
+        conn,
+        &JournalRecord {
+            seq: 0,
+            op: op.to_string(),
+            entity_id,
+            undo_json: steps_json(&undo)?,
+            redo_json: steps_json(&redo)?,
+            status: JOURNAL_APPLIED.to_string(),
+            recorded_at_ms: chrono::Utc::now().timestamp_millis(),
+            undone_at_ms: None,
+        },
+    )?;
+    Ok(())
+}
+
+/// Undo and redo steps for the fields that differ between `before` and
+/// `after`, or `None` when nothing changed.
+fn ettle_update_steps(
+    before: &EttleRecord,
+    after: &EttleRecord,
+) -> Option<(Vec<JournalStep>, Vec<JournalStep>)> {
+    let changed = |a: &String, b: &String| (a != b).then(|| (a.clone(), b.clone()));
+    let title = changed(&before.title, &after.title);
+    let why = changed(&before.why, &after.why);
+    let what = changed(&before.what, &after.what);
+    let how = changed(&before.how, &after.how);
+    let link = (before.reasoning_link_id != after.reasoning_link_id
+        || before.reasoning_link_type != after.reasoning_link_type)
+        .then(|| {
+            let state = |r: &EttleRecord| LinkState {
+                id: r.reasoning_link_id.clone(),
+                link_type: r.reasoning_link_type.clone(),
+            };
+            (state(before), state(after))
+        });
+    if title.is_none() && why.is_none() && what.is_none() && how.is_none() && link.is_none() {
+        return None;
+    }
+
+    let step = |pick: fn((String, String)) -> String,
+                pick_link: fn((LinkState, LinkState)) -> LinkState| {
+        JournalStep::EttleSet {
+            ettle_id: before.id.clone(),
+            title: title.clone().map(pick),
+            why: why.clone().map(pick),
+            what: what.clone().map(pick),
+            how: how.clone().map(pick),
+            link: link.clone().map(pick_link),
+        }
+    };
+    Some((
+        vec![step(|(old, _)| old, |(old, _)| old)],
+        vec![step(|(_, new)| new, |(_, new)| new)],
+    ))
+}
+
+fn parse_properties(relation_id: &str, text: &str) -> Result<JsonValue> {
+    serde_json::from_str(text).map_err(|e| {
+        ExError::new(ExErrorKind::Serialization)
+            .with_op("command_journal")
+            .with_entity_id(relation_id)
+            .with_message(format!("relation properties_json is not JSON: {}", e))
+    })
+}
+
+fn steps_json(steps: &[JournalStep]) -> Result<String> {
+    serde_json::to_string(steps).map_err(|e| {
+        ExError::new(ExErrorKind::Serialization)
+            .with_op("command_journal")
+            .with_message(e.to_string())
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Undo / redo
+// ---------------------------------------------------------------------------
+
+/// Revert the newest applied journal entry.
+///
+/// The undo steps run in one savepoint as ordinary (unjournaled) commands,
+/// each with its own provenance event and `command_log` row. The entry is
+/// then marked undone and becomes the next [`redo`].
+///
+/// # Errors
+/// * `NotFound` - there is nothing to undo
+/// * `HeadMismatch` - a field the entry set has since been changed by a
+///   command outside the journal
+/// * Any error from a replayed step (the store is left unchanged)
+pub fn undo(
+    conn: &mut Connection,
+    cas: &FsStore,
+    policy_provider: &dyn PolicyProvider,
+    approval_router: &dyn ApprovalRouter,
+) -> Result<JournalEntry> {
+    let entry = SqliteRepo::next_undo_journal_entry(conn)?.ok_or_else(|| {
+        ExError::new(ExErrorKind::NotFound)
+            .with_op("undo")
+            .with_message("nothing to undo")
+    })?;
+    check_current(conn, cas, "undo", &entry, &entry.redo_json)?;
+    let steps = entry.undo_json.clone();
+    let undone_at_ms = chrono::Utc::now().timestamp_millis();
+    replay(
+        conn,
+        cas,
+        policy_provider,
+        approval_router,
+        "undo",
+        &entry,
+        &steps,
+        JOURNAL_UNDONE,
+        Some(undone_at_ms),
+    )?;
+    Ok(entry.into())
+}
+
+/// Reapply the oldest undone journal entry, reversing the last [`undo`].
+///
+/// # Errors
+/// * `NotFound` - there is nothing to redo
+/// * `HeadMismatch` - a field the undo restored has since been changed by a
+///   command outside the journal
+/// * Any error from a replayed step (the store is left unchanged)
+pub fn redo(
+    conn: &mut Connection,
+    cas: &FsStore,
+    policy_provider: &dyn PolicyProvider,
+    approval_router: &dyn ApprovalRouter,
+) -> Result<JournalEntry> {
+    let entry = SqliteRepo::next_redo_journal_entry(conn)?.ok_or_else(|| {
+        ExError::new(ExErrorKind::NotFound)
+            .with_op("redo")
+            .with_message("nothing to redo")
+    })?;
+    check_current(conn, cas, "redo", &entry, &entry.undo_json)?;
+    let steps = entry.redo_json.clone();
+    replay(
+        conn,
+        cas,
+        policy_provider,
+        approval_router,
+        "redo",
+        &entry,
+        &steps,
+        JOURNAL_APPLIED,
+        None,
+    )?;
+    Ok(entry.into())
+}
+
+/// Fail with `HeadMismatch` unless every field that `expected_json` sets
+/// already holds that value, i.e. the store is still in the state the entry
+/// left it in.
+fn check_current(
+    conn: &Connection,
+    cas: &FsStore,
+    op: &str,
+    entry: &JournalRecord,
+    expected_json: &str,
+) -> Result<()> {
+    let steps = parse_steps(op, entry, expected_json)?;
+    let mismatch = |entity_id: &str, field: &str| {
+        ExError::new(ExErrorKind::HeadMismatch)
+            .with_op(op)
+            .with_entity_id(entity_id)
+            .with_message(format!(
+                "{} of journal entry {} ({}): {} was changed outside the journal",
+                op, entry.seq, entry.op, field
+            ))
+    };
+    let ettle = |ettle_id: &str| -> Result<EttleRecord> {
+        let mut record = SqliteRepo::get_ettle_record(conn, ettle_id)?
+            .ok_or_else(|| mismatch(ettle_id, "ettle"))?;
+        hydrate_ettle_content(&mut record, cas)?;
+        Ok(record)
+    };
+    for step in steps {
+        match step {
+            JournalStep::EttleSet {
+                ettle_id,
+                title,
+                why,
+                what,
+                how,
+                link,
+            } => {
+                let current = ettle(&ettle_id)?;
+                let fields = [
+                    ("title", title, &current.title),
+                    ("why", why, &current.why),
+                    ("what", what, &current.what),
+                    ("how", how, &current.how),
+                ];
+                for (field, expected, actual) in fields {
+                    if expected.is_some_and(|v| &v != actual) {
+                        return Err(mismatch(&ettle_id, field));
+                    }
+                }
+                if link.is_some_and(|l| {
+                    l.id != current.reasoning_link_id || l.link_type != current.reasoning_link_type
+                }) {
+                    return Err(mismatch(&ettle_id, "reasoning link"));
+                }
+            }
+            JournalStep::EttleSetContentFormat {
+                ettle_id,
+                content_format,
+            } => {
+                if ettle(&ettle_id)?.content_format != content_format {
+                    return Err(mismatch(&ettle_id, "content_format"));
+                }
+            }
+            JournalStep::RelationSetProperties {
+                relation_id,
+                properties_json,
+            } => {
+                let current = SqliteRepo::get_relation(conn, &relation_id)?
+                    .ok_or_else(|| mismatch(&relation_id, "relation"))?;
+                if parse_properties(&relation_id, &current.properties_json)? != properties_json {
+                    return Err(mismatch(&relation_id, "properties"));
+                }
+            }
+            // Lifecycle steps are guarded by the replayed commands' own checks
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn parse_steps(op: &str, entry: &JournalRecord, steps_json: &str) -> Result<Vec<JournalStep>> {
+    serde_json::from_str(steps_json).map_err(|e| {
+        ExError::new(ExErrorKind::Serialization)
+            .with_op(op)
+            .with_message(format!("journal entry {} is unreadable: {}", entry.seq, e))
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn replay(
+    conn: &mut Connection,
+    cas: &FsStore,
+    policy_provider: &dyn PolicyProvider,
+    approval_router: &dyn ApprovalRouter,
+    op: &str,
+    entry: &JournalRecord,
+    steps_json: &str,
+    status: &str,
+    undone_at_ms: Option<i64>,
+) -> Result<()> {
+    let steps = parse_steps(op, entry, steps_json)?;
+    let persistence = |e: rusqlite::Error| {
+        ExError::new(ExErrorKind::Persistence)
+            .with_op(op)
+            .with_message(e.to_string())
+    };
+
+    conn.execute_batch("SAVEPOINT command_journal")
+        .map_err(persistence)?;
+    let result = steps
+        .into_iter()
+        .try_for_each(|step| {
+            apply_command_unjournaled(
+                step.into_command(),
+                conn,
+                cas,
+                policy_provider,
+                approval_router,
+            )
+            .map(|_| ())
+        })
+        .and_then(|()| SqliteRepo::set_journal_entry_status(conn, entry.seq, status, undone_at_ms));
+    match result {
+        Ok(()) => {
+            conn.execute_batch("RELEASE command_journal")
+                .map_err(persistence)?;
+            Ok(())
+        }
+        Err(e) => {
+            conn.execute_batch("ROLLBACK TO command_journal; RELEASE command_journal")
+                .map_err(persistence)?;
+            let message = format!(
+                "{} of journal entry {} ({}): {}",
+                op,
+                entry.seq,
+                entry.op,
+                e.message()
+            );
+            Err(e.with_message(message))
+        }
+    }
+}
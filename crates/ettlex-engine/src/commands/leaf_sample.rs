@@ -0,0 +1,100 @@
+//! Reproducible random samples of leaf Ettles for audit spot-checks.
+//!
+//! Candidates are the leaves listed by `LeafList`. Each one is ranked
+//! by `SHA-256("ettlex-leaf-sample/v1\n<seed>\n<ettle_id>")` and the `count`
+//! lowest-ranked are selected. The ranking depends only on the seed and the
+//! ID, so the draw is repeatable, needs no RNG state, and can be re-derived
+//! by hand from the published method. Adding or removing other leaves never
+//! reorders the ones that stay: a sample drawn later keeps every earlier
+//! pick that still ranks within `count`.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::model::Ettle;
+use ettlex_store::errors::Result;
+use ettlex_store::repo::SqliteRepo;
+use rusqlite::Connection;
+use sha2::{Digest, Sha256};
+
+use crate::commands::engine_query::read_state_version;
+use crate::commands::read_tools::{LeafSample, LeafSampleFilter};
+
+/// Version tag mixed into every rank; bump if the method changes.
+const SAMPLE_VERSION: &str = "ettlex-leaf-sample/v1";
+
+/// Leaves fetched per round trip while collecting the population.
+const SCAN_PAGE: usize = 500;
+
+/// Draw `count` leaf Ettles matching `filter`, ranked by `seed`.
+///
+/// Returns every matching leaf (in rank order) when fewer than `count` match.
+///
+/// # Errors
+///
+/// `InvalidInput` when `count` is zero; `NotFound` when
+/// `filter.root_ettle_id` names no Ettle.
+pub fn sample_leaves(
+    conn: &Connection,
+    seed: u64,
+    count: usize,
+    filter: &LeafSampleFilter,
+) -> Result<LeafSample> {
+    if count == 0 {
+        return Err(ExError::new(ExErrorKind::InvalidInput)
+            .with_op("sample_leaves")
+            .with_message("count must be at least 1"));
+    }
+    if let Some(root) = &filter.root_ettle_id {
+        if SqliteRepo::get_ettle(conn, root)?.is_none() {
+            return Err(ExError::new(ExErrorKind::NotFound)
+                .with_op("sample_leaves")
+                .with_entity_id(root)
+                .with_message("root ettle not found"));
+        }
+    }
+
+    let needle = filter.title_contains.as_deref().map(str::to_lowercase);
+    let mut ranked: Vec<(String, Ettle)> = Vec::new();
+    let mut after: Option<String> = None;
+    loop {
+        let page = SqliteRepo::list_leaf_ettles_paginated(
+            conn,
+            filter.root_ettle_id.as_deref(),
+            after.as_deref(),
+            SCAN_PAGE,
+        )?;
+        let done = page.len() < SCAN_PAGE;
+        after = page.last().map(|e| e.id.clone());
+        ranked.extend(
+            page.into_iter()
+                .filter(|e| {
+                    needle
+                        .as_deref()
+                        .map_or(true, |n| e.title.to_lowercase().contains(n))
+                })
+                .map(|e| (sample_rank(seed, &e.id), e)),
+        );
+        if done {
+            break;
+        }
+    }
+
+    let population = ranked.len() as u64;
+    ranked.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.id.cmp(&b.1.id)));
+    ranked.truncate(count);
+
+    Ok(LeafSample {
+        seed,
+        state_version: read_state_version(conn)?.state_version,
+        population,
+        items: ranked.into_iter().map(|(_, e)| e).collect(),
+    })
+}
+
+/// Lowercase hex rank of `ettle_id` under `seed`; lower ranks are drawn first.
+fn sample_rank(seed: u64, ettle_id: &str) -> String {
+    let mut h = Sha256::new();
+    h.update(format!("{}\n{}\n{}", SAMPLE_VERSION, seed, ettle_id).as_bytes());
+    format!("{:x}", h.finalize())
+}
@@ -3,11 +3,41 @@
 //! Provides high-level command functions that coordinate between
 //! core domain logic and persistence layer.
 
+pub mod api_token;
+pub mod approval_offline;
+pub mod approval_packet;
+pub mod approval_sweep;
+pub mod architecture_report;
+pub mod assignment;
 pub mod command;
+pub mod comment;
+pub mod commit_queue;
+pub mod constraint;
+pub mod content_spill;
+pub mod csv_import;
 pub mod decision;
+pub mod determinism;
 pub mod engine_command;
 pub mod engine_query;
+pub mod ept_diagnose;
 pub mod ettle;
+pub mod event_history;
+pub mod evidence;
 pub mod group;
+pub mod heads;
+pub mod import_session;
+pub mod journal;
+pub mod leaf_sample;
+pub mod patch;
+pub mod query_cancel;
+pub mod query_trace;
 pub mod read_tools;
+pub mod reconcile;
+pub mod refactor;
 pub mod relation;
+pub mod root;
+pub mod settings;
+pub mod snapshot_compare;
+pub mod store_fingerprint;
+pub mod tree_render;
+pub mod validate;
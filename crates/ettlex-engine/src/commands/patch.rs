@@ -0,0 +1,390 @@
+//! Patch files — incremental seed updates declared as deltas
+//!
+//! A [`PatchFile`] adds, updates and tombstones individual Ettles of an
+//! existing store. [`apply_patch`] checks every precondition first, then
+//! applies all ops as one [`apply_commands_atomic`] batch guarded by the
+//! state version the preconditions were checked against.
+
+#![allow(clippy::result_large_err)]
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::{ExError, ExErrorKind, Result};
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_store::cas::{CasDedupStats, FsStore};
+use ettlex_store::model::{EttleRecord, RelationListOpts};
+use ettlex_store::repo::SqliteRepo;
+use rusqlite::Connection;
+use serde::Deserialize;
+use serde_json::json;
+
+use super::command::{apply_commands_atomic, split_batch_error, Command, CommandResult};
+use super::engine_query::read_state_version;
+use super::ettle::{ettle_content_digest, resolve_ettle_ref};
+use super::root::ensure_new_root_allowed;
+
+const REFINEMENT: &str = "refinement";
+
+/// A patch file: deltas against an existing store
+///
+/// ```yaml
+/// ops:
+///   - add:
+///       key: refunds-v2       # optional; later ops may use it as `under`
+///       under: payments       # Ettle ID, slug or earlier key; omit for a root
+///       title: Refunds v2
+///       ordinal: 3            # default: after the parent's current children
+///   - update:
+///       id: refunds
+///       expect: 9f2c…         # content digest from `ettlex patch digest`
+///       what: Returns money to the payer.
+///   - tombstone:
+///       id: legacy-refunds
+///       expect: 41ab…
+/// ```
+///
+/// `update` and `tombstone` carry the content digest the author saw; the
+/// patch is refused if any of them no longer matches.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PatchFile {
+    pub ops: Vec<PatchOp>,
+}
+
+/// One delta in a [`PatchFile`]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum PatchOp {
+    Add {
+        #[serde(default)]
+        key: Option<String>,
+        #[serde(default)]
+        under: Option<String>,
+        title: String,
+        #[serde(default)]
+        why: Option<String>,
+        #[serde(default)]
+        what: Option<String>,
+        #[serde(default)]
+        how: Option<String>,
+        #[serde(default)]
+        ordinal: Option<u64>,
+    },
+    Update {
+        id: String,
+        expect: String,
+        #[serde(default)]
+        title: Option<String>,
+        #[serde(default)]
+        why: Option<String>,
+        #[serde(default)]
+        what: Option<String>,
+        #[serde(default)]
+        how: Option<String>,
+    },
+    Tombstone {
+        id: String,
+        expect: String,
+    },
+}
+
+/// Outcome of [`apply_patch`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchReport {
+    /// Number of ops executed
+    pub applied: usize,
+    /// Created Ettle IDs in op order, with each op's key (if any)
+    pub created: Vec<(Option<String>, String)>,
+    /// True when the changes were rolled back (`dry_run`)
+    pub dry_run: bool,
+    /// CAS blobs written versus reused for spilled content (CAS writes are
+    /// not rolled back by `dry_run`)
+    pub cas: CasDedupStats,
+}
+
+/// Apply `patch` as one atomic batch
+///
+/// Every `expect` digest is checked against the store before anything is
+/// written, and an Ettle may be the subject of at most one `update` or
+/// `tombstone`. The ops then run through [`apply_commands_atomic`] with the
+/// state version the checks saw, so a concurrent write fails the patch
+/// instead of slipping past its preconditions. With `dry_run` the batch
+/// still runs, so command errors are reported, but it is rolled back.
+///
+/// # Errors
+/// * `InvalidInput` - Duplicate `key`, an Ettle targeted twice, or an `under`
+///   naming a key defined later in the patch
+/// * `NotFound` - An `id` or `under` matches no Ettle
+/// * `HeadMismatch` - An Ettle's content digest differs from `expect`
+/// * `RootEttleInvalid` - An `add` without `under` while `roots.mode` is
+///   `registered`
+/// * `Concurrency` - The store changed after the preconditions were checked
+/// * Any command error; on error nothing is applied
+pub fn apply_patch(
+    conn: &mut Connection,
+    cas: &FsStore,
+    patch: &PatchFile,
+    dry_run: bool,
+) -> Result<PatchReport> {
+    let state_version = read_state_version(conn)?.state_version;
+    check_preconditions(conn, cas, patch)?;
+    let (commands, op_of_command, keys) = build_commands(conn, patch)?;
+    if commands.is_empty() {
+        return Ok(PatchReport {
+            applied: 0,
+            created: Vec::new(),
+            dry_run,
+            cas: CasDedupStats::default(),
+        });
+    }
+    let before = cas.dedup_stats();
+
+    let persistence = |e: rusqlite::Error| {
+        ExError::new(ExErrorKind::Persistence)
+            .with_op("patch_apply")
+            .with_message(e.to_string())
+    };
+    if dry_run {
+        conn.execute_batch("SAVEPOINT patch_dry_run")
+            .map_err(persistence)?;
+    }
+    let applied = apply_commands_atomic(
+        commands,
+        Some(state_version),
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    );
+    if dry_run {
+        conn.execute_batch("ROLLBACK TO patch_dry_run; RELEASE patch_dry_run")
+            .map_err(persistence)?;
+    }
+    let (results, _) = applied.map_err(|e| match split_batch_error(e) {
+        (Some(i), e) => match op_of_command.get(i) {
+            Some(&index) => at_op(e, index),
+            None => e,
+        },
+        (None, e) => e,
+    })?;
+
+    let created_ids = results.into_iter().filter_map(|result| match result {
+        CommandResult::EttleCreate { ettle_id } => Some(ettle_id),
+        _ => None,
+    });
+    Ok(PatchReport {
+        applied: patch.ops.len(),
+        created: keys.into_iter().zip(created_ids).collect(),
+        dry_run,
+        cas: cas.dedup_stats().since(&before),
+    })
+}
+
+/// Content digest to use as an `expect` precondition for `reference`
+///
+/// Returns `(digest, ettle_id)`.
+///
+/// # Errors
+/// * `NotFound` - `reference` matches no Ettle
+pub fn patch_digest(conn: &Connection, cas: &FsStore, reference: &str) -> Result<(String, String)> {
+    let record = existing(conn, reference, "patch_digest")?;
+    Ok((ettle_content_digest(&record, cas)?, record.id))
+}
+
+fn check_preconditions(conn: &Connection, cas: &FsStore, patch: &PatchFile) -> Result<()> {
+    let mut targeted = BTreeSet::new();
+    let mut keys = BTreeSet::new();
+    for (index, op) in patch.ops.iter().enumerate() {
+        let (id, expect) = match op {
+            PatchOp::Add { key, under, .. } => {
+                if let Some(under) = under.as_ref().filter(|u| !keys.contains(*u)) {
+                    let later = patch.ops[index..]
+                        .iter()
+                        .any(|o| matches!(o, PatchOp::Add { key: Some(k), .. } if k == under));
+                    if later {
+                        return Err(at_op(
+                            invalid(format!("'{}' is added by a later op", under)),
+                            index,
+                        ));
+                    }
+                    existing(conn, under, "patch_apply").map_err(|e| at_op(e, index))?;
+                }
+                if under.is_none() {
+                    ensure_new_root_allowed(conn, "patch_apply", "add without 'under'")
+                        .map_err(|e| at_op(e, index))?;
+                }
+                if let Some(key) = key {
+                    if !keys.insert(key.clone()) {
+                        return Err(at_op(
+                            invalid(format!("key '{}' is used more than once", key)),
+                            index,
+                        ));
+                    }
+                }
+                continue;
+            }
+            PatchOp::Update { id, expect, .. } | PatchOp::Tombstone { id, expect } => (id, expect),
+        };
+        let record = existing(conn, id, "patch_apply").map_err(|e| at_op(e, index))?;
+        if !targeted.insert(record.id.clone()) {
+            return Err(at_op(
+                invalid(format!("ettle '{}' is changed more than once", id)),
+                index,
+            ));
+        }
+        let actual = ettle_content_digest(&record, cas).map_err(|e| at_op(e, index))?;
+        if &actual != expect {
+            return Err(at_op(
+                ExError::new(ExErrorKind::HeadMismatch)
+                    .with_op("patch_apply")
+                    .with_entity_id(&record.id)
+                    .with_message(format!(
+                        "ettle '{}' changed since the patch was written \
+                         (expected {}, found {})",
+                        id, expect, actual
+                    )),
+                index,
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Commands, the op index of each command, and the key of each `add`
+type PatchCommands = (Vec<Command>, Vec<usize>, Vec<Option<String>>);
+
+/// Commands for every op, in op order
+///
+/// Ettles added earlier in the patch are referenced by the slug their
+/// create will allocate, since the batch cannot feed IDs forward.
+fn build_commands(conn: &Connection, patch: &PatchFile) -> Result<PatchCommands> {
+    let mut commands = Vec::new();
+    let mut op_of_command = Vec::new();
+    let mut keys = Vec::new();
+    let mut taken = HashSet::new();
+    let mut by_key: HashMap<&str, String> = HashMap::new();
+    // Per parent: highest ordinal so far and number of refinements
+    let mut children: HashMap<String, (Option<u64>, u64)> = HashMap::new();
+    for (index, op) in patch.ops.iter().enumerate() {
+        match op {
+            PatchOp::Add {
+                key,
+                under,
+                title,
+                why,
+                what,
+                how,
+                ordinal,
+            } => {
+                let slug = SqliteRepo::allocate_ettle_slug_excluding(conn, title, &taken)?;
+                taken.insert(slug.clone());
+                commands.push(Command::EttleCreate {
+                    title: title.clone(),
+                    ettle_id: None,
+                    why: why.clone(),
+                    what: what.clone(),
+                    how: how.clone(),
+                    reasoning_link_id: None,
+                    reasoning_link_type: None,
+                });
+                op_of_command.push(index);
+                if let Some(under) = under {
+                    let parent = match by_key.get(under.as_str()) {
+                        Some(slug) => slug.clone(),
+                        None => resolve_ettle_ref(conn, under)?,
+                    };
+                    let (highest, count) = match children.get(&parent) {
+                        Some(seen) => *seen,
+                        None => existing_children(conn, &parent)?,
+                    };
+                    let ordinal = ordinal.unwrap_or(highest.map_or(count, |h| h + 1));
+                    children.insert(
+                        parent.clone(),
+                        (Some(highest.map_or(ordinal, |h| h.max(ordinal))), count + 1),
+                    );
+                    commands.push(Command::RelationCreate {
+                        source_ettle_id: parent,
+                        target_ettle_id: slug.clone(),
+                        relation_type: REFINEMENT.to_string(),
+                        properties_json: Some(json!({ "ordinal": ordinal })),
+                        relation_id: None,
+                    });
+                    op_of_command.push(index);
+                }
+                if let Some(key) = key {
+                    by_key.insert(key, slug);
+                }
+                keys.push(key.clone());
+            }
+            PatchOp::Update {
+                id,
+                title,
+                why,
+                what,
+                how,
+                ..
+            } => {
+                commands.push(Command::EttleUpdate {
+                    ettle_id: resolve_ettle_ref(conn, id)?,
+                    title: title.clone(),
+                    why: why.clone(),
+                    what: what.clone(),
+                    how: how.clone(),
+                    reasoning_link_id: None,
+                    reasoning_link_type: None,
+                });
+                op_of_command.push(index);
+            }
+            PatchOp::Tombstone { id, .. } => {
+                commands.push(Command::EttleTombstone {
+                    ettle_id: resolve_ettle_ref(conn, id)?,
+                });
+                op_of_command.push(index);
+            }
+        }
+    }
+    Ok((commands, op_of_command, keys))
+}
+
+/// Highest `ordinal` and count of the parent's active refinements
+fn existing_children(conn: &Connection, parent_id: &str) -> Result<(Option<u64>, u64)> {
+    let relations = SqliteRepo::list_relations(
+        conn,
+        &RelationListOpts {
+            source_ettle_id: Some(parent_id.to_string()),
+            relation_type: Some(REFINEMENT.to_string()),
+            ..Default::default()
+        },
+    )?;
+    let highest = relations
+        .iter()
+        .filter_map(|r| {
+            serde_json::from_str::<serde_json::Value>(&r.properties_json)
+                .ok()
+                .and_then(|p| p.get("ordinal").and_then(serde_json::Value::as_u64))
+        })
+        .max();
+    Ok((highest, relations.len() as u64))
+}
+
+fn existing(conn: &Connection, reference: &str, op: &str) -> Result<EttleRecord> {
+    let ettle_id = resolve_ettle_ref(conn, reference)?;
+    SqliteRepo::get_ettle_record(conn, &ettle_id)?.ok_or_else(|| {
+        ExError::new(ExErrorKind::NotFound)
+            .with_op(op)
+            .with_entity_id(reference)
+            .with_message(format!("no ettle matches '{}'", reference))
+    })
+}
+
+fn at_op(e: ExError, index: usize) -> ExError {
+    let message = format!("op {}: {}", index + 1, e.message());
+    e.with_message(message)
+}
+
+fn invalid(message: String) -> ExError {
+    ExError::new(ExErrorKind::InvalidInput)
+        .with_op("patch_apply")
+        .with_message(message)
+}
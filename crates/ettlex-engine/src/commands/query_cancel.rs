@@ -0,0 +1,141 @@
+//! Deadlines and cancellation for `apply_engine_query_with_options`.
+//!
+//! When `QueryOptions` carries a deadline or a [`CancellationToken`], a SQLite
+//! progress handler is installed for the duration of the query. Once the
+//! deadline passes or the token is cancelled, the handler interrupts the
+//! running statement and the query fails with `ExErrorKind::Timeout`.
+//!
+//! Only SQLite work is interruptible. A query busy in Rust code (for example
+//! diffing two manifests already in memory) stops at its next statement.
+
+#![allow(clippy::result_large_err)]
+
+use std::os::raw::c_int;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use ettlex_core::errors::{ExError, ExErrorKind, Result};
+use rusqlite::Connection;
+
+use crate::commands::query_trace::QueryOptions;
+
+/// SQLite virtual-machine steps between progress handler calls.
+const PROGRESS_HANDLER_STEPS: c_int = 1000;
+
+/// Shared flag for cancelling a query from another thread.
+///
+/// Clones share the flag: cancel any clone to cancel the query holding one.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation. Idempotent.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Deadline and token taken from `QueryOptions` for one query.
+#[derive(Clone)]
+pub(crate) struct QueryInterrupt {
+    deadline: Option<Instant>,
+    cancel: Option<CancellationToken>,
+}
+
+impl QueryInterrupt {
+    /// `None` when the options set neither a deadline nor a token.
+    pub(crate) fn from_options(options: &QueryOptions) -> Option<Self> {
+        if options.deadline.is_none() && options.cancel.is_none() {
+            return None;
+        }
+        Some(Self {
+            deadline: options.deadline,
+            cancel: options.cancel.clone(),
+        })
+    }
+
+    /// Why the query must stop, if it must.
+    fn tripped(&self) -> Option<&'static str> {
+        if self.cancel.as_ref().is_some_and(|c| c.is_cancelled()) {
+            Some("query cancelled")
+        } else if self.deadline.is_some_and(|d| Instant::now() >= d) {
+            Some("query deadline exceeded")
+        } else {
+            None
+        }
+    }
+
+    /// Run `f` with the progress handler installed.
+    ///
+    /// The handler is removed before returning, so statements issued
+    /// afterwards (e.g. releasing the read savepoint) are not interrupted.
+    /// A failure observed after the interrupt tripped is reported as
+    /// `Timeout`; a query that completed anyway keeps its result.
+    pub(crate) fn run<T>(
+        &self,
+        conn: &Connection,
+        op: &str,
+        f: impl FnOnce() -> Result<T>,
+    ) -> Result<T> {
+        if let Some(reason) = self.tripped() {
+            return Err(timeout(op, reason));
+        }
+
+        let handler_state = self.clone();
+        conn.progress_handler(
+            PROGRESS_HANDLER_STEPS,
+            Some(move || handler_state.tripped().is_some()),
+        );
+        let result = f();
+        conn.progress_handler(0, None::<fn() -> bool>);
+
+        match (result, self.tripped()) {
+            (Err(_), Some(reason)) => Err(timeout(op, reason)),
+            (result, _) => result,
+        }
+    }
+}
+
+fn timeout(op: &str, reason: &str) -> ExError {
+    ExError::new(ExErrorKind::Timeout)
+        .with_op(op)
+        .with_message(reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const ENDLESS_SQL: &str =
+        "WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c) SELECT count(*) FROM c";
+
+    #[test]
+    fn test_deadline_interrupts_running_statement() {
+        let conn = Connection::open_in_memory().unwrap();
+        let interrupt = QueryInterrupt {
+            deadline: Some(Instant::now() + Duration::from_millis(50)),
+            cancel: None,
+        };
+        let err = interrupt
+            .run(&conn, "endless", || {
+                conn.query_row(ENDLESS_SQL, [], |r| r.get::<_, i64>(0))
+                    .map_err(|e| ExError::new(ExErrorKind::Persistence).with_message(e.to_string()))
+            })
+            .unwrap_err();
+        assert_eq!(err.kind(), ExErrorKind::Timeout);
+
+        // Handler removed: the connection is usable again.
+        let one: i64 = conn.query_row("SELECT 1", [], |r| r.get(0)).unwrap();
+        assert_eq!(one, 1);
+    }
+}
@@ -0,0 +1,459 @@
+//! Explain/trace support for `apply_engine_query_with_options`.
+//!
+//! When `QueryOptions::explain` is set, the query runs as normal and a
+//! [`QueryTrace`] is returned alongside the result describing what was
+//! read and where the time went. The trace is diagnostic only: it never
+//! changes the query result.
+//!
+//! Tables and row counts are observed, not planned: SQLite's trace hook
+//! reports each statement a stage runs and each row it returns, and the
+//! distinct statements are then re-prepared (never run) under an authorizer
+//! that names the tables they read.
+
+#![allow(clippy::result_large_err)]
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int, c_uint, c_void};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use ettlex_store::errors::Result;
+use ettlex_store::sql_audit;
+use rusqlite::ffi;
+use rusqlite::hooks::{AuthAction, AuthContext, Authorization};
+use rusqlite::Connection;
+use serde::Serialize;
+
+use crate::commands::engine_query::{EngineQuery, EngineQueryResult};
+use crate::commands::query_cancel::CancellationToken;
+use crate::commands::read_tools::StateVersionResult;
+
+// ---------------------------------------------------------------------------
+// Public types
+// ---------------------------------------------------------------------------
+
+/// Per-call options for `apply_engine_query_with_options`.
+#[derive(Debug, Clone, Default)]
+pub struct QueryOptions {
+    /// Collect and return a [`QueryTrace`] alongside the result.
+    pub explain: bool,
+    /// Fail with `Timeout` once this instant passes.
+    pub deadline: Option<Instant>,
+    /// Fail with `Timeout` once this token is cancelled.
+    pub cancel: Option<CancellationToken>,
+}
+
+impl QueryOptions {
+    /// Set `deadline` to `timeout` from now.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.deadline = Some(Instant::now() + timeout);
+        self
+    }
+}
+
+/// How much state a query loads to answer a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HydrationScope {
+    /// A single row looked up by key.
+    Point,
+    /// A bounded, cursor-paginated range of rows.
+    Page,
+    /// An unbounded filtered scan of one or more tables.
+    Scan,
+    /// Row lookup followed by one or more CAS blob reads.
+    CasBlob,
+    /// Answered by the policy provider, not the database.
+    PolicyProvider,
+}
+
+/// A table read during query execution, with the rows returned by the
+/// statements that read it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TableAccess {
+    /// Table name.
+    pub table: String,
+    /// Rows returned by statements that read this table. A statement over
+    /// several tables (a join, a subquery) counts its rows against each.
+    pub rows_returned: u64,
+}
+
+/// One stage of query execution: how long it took and what it read.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StageTrace {
+    /// Stage name (`resolve`, `execute`).
+    pub stage: String,
+    /// Elapsed time in microseconds.
+    pub duration_us: u64,
+    /// Tables the stage's statements read, in first-read order.
+    pub tables: Vec<TableAccess>,
+}
+
+/// Diagnostic trace for a single engine query.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct QueryTrace {
+    /// Operation name, matching the `op` field of the query's log events.
+    pub op: String,
+    /// Tables read across all stages, in first-read order.
+    pub tables: Vec<TableAccess>,
+    /// Hydration scope of the query.
+    pub hydration_scope: HydrationScope,
+    /// Number of items in the result (1 for single-item results).
+    pub result_rows: u64,
+    /// Per-stage timings and table reads, in execution order.
+    pub stages: Vec<StageTrace>,
+}
+
+/// The result of `apply_engine_query_with_options`.
+#[derive(Debug, Clone)]
+pub struct ExplainedQueryResult {
+    /// The query result, identical to what `apply_engine_query` returns.
+    pub result: EngineQueryResult,
+    /// State version and head digest observed while the query ran.
+    pub state: StateVersionResult,
+    /// The trace, present only when `QueryOptions::explain` was set.
+    pub trace: Option<QueryTrace>,
+}
+
+// ---------------------------------------------------------------------------
+// Trace construction
+// ---------------------------------------------------------------------------
+
+/// Op name and hydration scope of a query.
+pub(crate) fn query_plan(query: &EngineQuery) -> (&'static str, HydrationScope) {
+    use HydrationScope::*;
+    match query {
+        EngineQuery::SnapshotDiff { .. } => ("snapshot_diff", CasBlob),
+        EngineQuery::StateGetVersion => ("state_get_version", Scan),
+        EngineQuery::StoreStats => ("store_stats", Scan),
+        EngineQuery::StoreFingerprint => ("store_fingerprint", Scan),
+        EngineQuery::EttleGet { .. } => ("ettle_get", Point),
+        EngineQuery::EttleList(_) => ("ettle_list", Page),
+        EngineQuery::LeafList { .. } => ("leaf_list", Page),
+        EngineQuery::EptDiagnose { .. } => ("ept_diagnose", Scan),
+        EngineQuery::SampleLeaves { .. } => ("sample_leaves", Scan),
+        EngineQuery::EttleListReferrers { .. } => ("ettle_list_referrers", Scan),
+        EngineQuery::EttleListByOwner { .. } => ("ettle_list_by_owner", Scan),
+        EngineQuery::ConstraintGet { .. } => ("constraint_get", Point),
+        EngineQuery::ConstraintListByFamily { .. } => ("constraint_list_by_family", Scan),
+        EngineQuery::ConstraintListAttachments { .. } => ("constraint_list_attachments", Scan),
+        EngineQuery::ConstraintListOrphans => ("constraint_list_orphans", Scan),
+        EngineQuery::EttleEffectiveConstraints { .. } => ("ettle_effective_constraints", Scan),
+        EngineQuery::RootGet { .. } => ("root_get", Point),
+        EngineQuery::RootList { .. } => ("root_list", Scan),
+        EngineQuery::DecisionGet { .. } => ("decision_get", Point),
+        EngineQuery::DecisionList(_) => ("decision_list", Page),
+        EngineQuery::DecisionSearch { .. } => ("decision_search", Page),
+        EngineQuery::DecisionListByTarget { .. } => ("decision_list_by_target", Scan),
+        EngineQuery::EttleListDecisions { .. } => ("ettle_list_decisions", Scan),
+        EngineQuery::CommentGet { .. } => ("comment_get", Point),
+        EngineQuery::CommentList { .. } => ("comment_list", Page),
+        EngineQuery::SnapshotGet { .. } => ("snapshot_get", Point),
+        EngineQuery::SnapshotList { .. } => ("snapshot_list", Scan),
+        EngineQuery::ManifestGetBySnapshot { .. } => ("manifest_get_by_snapshot", CasBlob),
+        EngineQuery::ManifestGetByDigest { .. } => ("manifest_get_by_digest", CasBlob),
+        EngineQuery::ProfileGet { .. } => ("profile_get", Point),
+        EngineQuery::ProfileResolve { .. } => ("profile_resolve", Point),
+        EngineQuery::ProfileGetDefault => ("profile_get_default", Point),
+        EngineQuery::ProfileList(_) => ("profile_list", Page),
+        EngineQuery::ApprovalGet { .. } => ("approval_get", CasBlob),
+        EngineQuery::ApprovalList(_) => ("approval_list", Page),
+        EngineQuery::ApprovalListByKind { .. } => ("approval_list_by_kind", Page),
+        EngineQuery::ApprovalHistory { .. } => ("approval_history", Scan),
+        EngineQuery::ConstraintPredicatesPreview { .. } => ("constraint_predicates_preview", Point),
+        EngineQuery::PolicyList => ("policy_list", PolicyProvider),
+        EngineQuery::PolicyRead { .. } => ("policy_read", PolicyProvider),
+        EngineQuery::PolicyExport { .. } => ("policy_export", PolicyProvider),
+        EngineQuery::SnapshotManifestPolicyRef { .. } => ("snapshot_manifest_policy_ref", CasBlob),
+        EngineQuery::PolicyProjectForHandoff { .. } => {
+            ("policy_project_for_handoff", PolicyProvider)
+        }
+        EngineQuery::SnapshotGetHead { .. } => ("snapshot_get_head", Point),
+        EngineQuery::HeadsList => ("heads_list", CasBlob),
+        EngineQuery::SnapshotCommitTicket { .. } => ("snapshot_commit_ticket", Point),
+        EngineQuery::LedgerVerify => ("ledger_verify", Scan),
+        EngineQuery::SnapshotCompareMatrix { .. } => ("snapshot_compare_matrix", CasBlob),
+        EngineQuery::SettingsGet { .. } => ("settings_get", Scan),
+        EngineQuery::ArchitectureReport { .. } => ("architecture_report", Scan),
+    }
+}
+
+/// Number of items carried by a query result.
+pub(crate) fn result_row_count(result: &EngineQueryResult) -> u64 {
+    match result {
+        EngineQueryResult::EttleList(p) => p.items.len() as u64,
+        EngineQueryResult::LeafList(p) => p.items.len() as u64,
+        EngineQueryResult::SampleLeaves(s) => s.items.len() as u64,
+        EngineQueryResult::ConstraintListByFamily(v) => v.len() as u64,
+        EngineQueryResult::EttleListReferrers(r) => (r.active.len() + r.tombstoned.len()) as u64,
+        EngineQueryResult::EttleListByOwner(v) => v.len() as u64,
+        EngineQueryResult::ConstraintListAttachments(v) => v.len() as u64,
+        EngineQueryResult::EttleEffectiveConstraints(r) => r.constraints.len() as u64,
+        EngineQueryResult::ConstraintListOrphans(v) => v.len() as u64,
+        EngineQueryResult::DecisionList(p) => p.items.len() as u64,
+        EngineQueryResult::DecisionSearch(p) => p.items.len() as u64,
+        EngineQueryResult::DecisionListByTarget(v) => v.len() as u64,
+        EngineQueryResult::EttleListDecisions(v) => v.len() as u64,
+        EngineQueryResult::CommentList(p) => p.items.len() as u64,
+        EngineQueryResult::SnapshotList(v) => v.len() as u64,
+        EngineQueryResult::RootList(r) => (r.registered.len() + r.unregistered.len()) as u64,
+        EngineQueryResult::ProfileList(p) => p.items.len() as u64,
+        EngineQueryResult::ApprovalList(p) => p.items.len() as u64,
+        EngineQueryResult::ApprovalHistory(v) => v.len() as u64,
+        EngineQueryResult::PolicyList(v) => v.len() as u64,
+        EngineQueryResult::SnapshotGetHead(h) => u64::from(h.is_some()),
+        EngineQueryResult::LedgerVerify(r) => r.rows_checked,
+        EngineQueryResult::SnapshotCompareMatrix(m) => m.entries.len() as u64,
+        EngineQueryResult::HeadsList(h) => h.heads.len() as u64,
+        EngineQueryResult::SettingsGet(v) => v.len() as u64,
+        EngineQueryResult::StoreFingerprint(f) => f.ettle_count + f.relation_count,
+        _ => 1,
+    }
+}
+
+/// Tables read across `stages`, in first-read order, with row counts summed.
+pub(crate) fn merge_table_access(stages: &[StageTrace]) -> Vec<TableAccess> {
+    let mut tables: Vec<TableAccess> = Vec::new();
+    for access in stages.iter().flat_map(|s| &s.tables) {
+        add_access(&mut tables, &access.table, access.rows_returned);
+    }
+    tables
+}
+
+/// Elapsed microseconds since `start`.
+pub(crate) fn elapsed_us(start: Instant) -> u64 {
+    start.elapsed().as_micros() as u64
+}
+
+// ---------------------------------------------------------------------------
+// Stage capture
+// ---------------------------------------------------------------------------
+
+/// Run `f` as the named stage, recording its duration and the tables its
+/// statements read.
+///
+/// # Errors
+///
+/// Whatever `f` returns; a failed stage yields no trace.
+pub(crate) fn capture_stage<T>(
+    conn: &Connection,
+    stage: &str,
+    f: impl FnOnce() -> Result<T>,
+) -> Result<(T, StageTrace)> {
+    let capture = RefCell::new(StatementCapture::default());
+    let start = Instant::now();
+    let value = {
+        let _hook = TraceHook::install(conn, &capture);
+        f()?
+    };
+    let duration_us = elapsed_us(start);
+    let tables = table_access(conn, capture.into_inner().statements);
+    Ok((
+        value,
+        StageTrace {
+            stage: stage.to_string(),
+            duration_us,
+            tables,
+        },
+    ))
+}
+
+/// Statements a stage ran, fed by SQLite's trace hook.
+#[derive(Default)]
+struct StatementCapture {
+    /// Distinct statement texts, with the rows each returned in total.
+    statements: Vec<(String, u64)>,
+    /// Index into `statements` by SQL text.
+    by_sql: HashMap<String, usize>,
+    /// Index into `statements` of the run each statement handle is in.
+    running: HashMap<usize, usize>,
+}
+
+impl StatementCapture {
+    fn started(&mut self, handle: usize, sql: &str) {
+        let index = match self.by_sql.get(sql) {
+            Some(&index) => index,
+            None => {
+                self.statements.push((sql.to_string(), 0));
+                self.by_sql
+                    .insert(sql.to_string(), self.statements.len() - 1);
+                self.statements.len() - 1
+            }
+        };
+        // Handles are reused after finalize, so always rebind on a new run
+        self.running.insert(handle, index);
+    }
+
+    fn row(&mut self, handle: usize) {
+        if let Some(&index) = self.running.get(&handle) {
+            self.statements[index].1 += 1;
+        }
+    }
+}
+
+/// SQLite trace hook feeding a [`StatementCapture`] while in scope.
+///
+/// SQLite has one trace slot per connection, shared with
+/// [`sql_audit`]: while installed the hook forwards statements to the audit,
+/// and on drop it hands the slot back to the audit if auditing is on.
+struct TraceHook<'a> {
+    conn: &'a Connection,
+    _capture: &'a RefCell<StatementCapture>,
+}
+
+impl<'a> TraceHook<'a> {
+    fn install(conn: &'a Connection, capture: &'a RefCell<StatementCapture>) -> Self {
+        let ctx = capture as *const RefCell<StatementCapture> as *mut c_void;
+        // SAFETY: `capture` outlives the hook (both borrow `'a`) and `Drop`
+        // unregisters the callback before that borrow ends.
+        unsafe {
+            ffi::sqlite3_trace_v2(
+                conn.handle(),
+                (ffi::SQLITE_TRACE_STMT | ffi::SQLITE_TRACE_ROW) as c_uint,
+                Some(capture_callback),
+                ctx,
+            );
+        }
+        Self {
+            conn,
+            _capture: capture,
+        }
+    }
+}
+
+impl Drop for TraceHook<'_> {
+    fn drop(&mut self) {
+        let (mask, callback) = if sql_audit::is_enabled() {
+            (
+                ffi::SQLITE_TRACE_STMT as c_uint,
+                Some(audit_callback as TraceCallback),
+            )
+        } else {
+            (0, None)
+        };
+        // SAFETY: the connection handle is valid for `'a`; the audit
+        // callback takes no context.
+        unsafe {
+            ffi::sqlite3_trace_v2(self.conn.handle(), mask, callback, std::ptr::null_mut());
+        }
+    }
+}
+
+type TraceCallback = unsafe extern "C" fn(c_uint, *mut c_void, *mut c_void, *mut c_void) -> c_int;
+
+/// `sqlite3_trace_v2` callback recording statement runs and rows.
+///
+/// For `STMT` events `p` is the statement handle and `x` its SQL text, or a
+/// `--` comment when a trigger fires; for `ROW` events `p` is the handle.
+unsafe extern "C" fn capture_callback(
+    event: c_uint,
+    ctx: *mut c_void,
+    p: *mut c_void,
+    x: *mut c_void,
+) -> c_int {
+    // SAFETY: `ctx` is the capture registered by `TraceHook::install`, alive
+    // until the hook is removed.
+    let capture = unsafe { &*(ctx as *const RefCell<StatementCapture>) };
+    let Ok(mut capture) = capture.try_borrow_mut() else {
+        return 0;
+    };
+    if event == ffi::SQLITE_TRACE_STMT as c_uint {
+        // SAFETY: SQLite passes a NUL-terminated string for `STMT` events.
+        let sql = unsafe { CStr::from_ptr(x as *const c_char) }.to_string_lossy();
+        if !sql.starts_with("--") {
+            capture.started(p as usize, &sql);
+            // SAFETY: `p` is the statement handle of a `STMT` event.
+            unsafe { audit_statement(p) };
+        }
+    } else if event == ffi::SQLITE_TRACE_ROW as c_uint {
+        capture.row(p as usize);
+    }
+    0
+}
+
+/// `sqlite3_trace_v2` callback that only feeds the SQL audit.
+unsafe extern "C" fn audit_callback(
+    event: c_uint,
+    _ctx: *mut c_void,
+    p: *mut c_void,
+    x: *mut c_void,
+) -> c_int {
+    if event == ffi::SQLITE_TRACE_STMT as c_uint {
+        // SAFETY: SQLite passes a NUL-terminated string for `STMT` events.
+        let sql = unsafe { CStr::from_ptr(x as *const c_char) };
+        if !sql.to_bytes().starts_with(b"--") {
+            // SAFETY: `p` is the statement handle of a `STMT` event.
+            unsafe { audit_statement(p) };
+        }
+    }
+    0
+}
+
+/// Record the statement behind `stmt`, parameters expanded, in the SQL audit.
+///
+/// # Safety
+///
+/// `stmt` must be a live statement handle.
+unsafe fn audit_statement(stmt: *mut c_void) {
+    if !sql_audit::is_enabled() {
+        return;
+    }
+    // SAFETY: the caller guarantees `stmt` is live; the expanded text is
+    // owned by us and freed below.
+    unsafe {
+        let expanded = ffi::sqlite3_expanded_sql(stmt as *mut ffi::sqlite3_stmt);
+        if expanded.is_null() {
+            return;
+        }
+        sql_audit::record_statement(&CStr::from_ptr(expanded).to_string_lossy());
+        ffi::sqlite3_free(expanded as *mut c_void);
+    }
+}
+
+/// Tables read by each captured statement, found by re-preparing it (never
+/// running it) under an authorizer, with its rows counted against each.
+fn table_access(conn: &Connection, statements: Vec<(String, u64)>) -> Vec<TableAccess> {
+    let reads: Arc<Mutex<Vec<String>>> = Arc::default();
+    let sink = Arc::clone(&reads);
+    conn.authorizer(Some(move |ctx: AuthContext<'_>| {
+        if let AuthAction::Read { table_name, .. } = ctx.action {
+            let mut reads = lock(&sink);
+            if !reads.iter().any(|t| t == table_name) {
+                reads.push(table_name.to_string());
+            }
+        }
+        Authorization::Allow
+    }));
+
+    let mut tables: Vec<TableAccess> = Vec::new();
+    for (sql, rows) in statements {
+        lock(&reads).clear();
+        if conn.prepare(&sql).is_err() {
+            continue;
+        }
+        for table in lock(&reads).drain(..) {
+            add_access(&mut tables, &table, rows);
+        }
+    }
+    conn.authorizer(None::<fn(AuthContext<'_>) -> Authorization>);
+    tables
+}
+
+fn add_access(tables: &mut Vec<TableAccess>, table: &str, rows: u64) {
+    match tables.iter_mut().find(|t| t.table == table) {
+        Some(access) => access.rows_returned += rows,
+        None => tables.push(TableAccess {
+            table: table.to_string(),
+            rows_returned: rows,
+        }),
+    }
+}
+
+fn lock(reads: &Mutex<Vec<String>>) -> MutexGuard<'_, Vec<String>> {
+    // The list holds plain data, so a panic mid-update cannot break it
+    reads
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -4,7 +4,17 @@
 //! entity read, list, and compute queries. All types are plain data containers with
 //! no I/O or mutation.
 
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::approval_router::ApprovalRequestPayload;
+use ettlex_core::candidate_resolver::AmbiguityPolicy;
+use ettlex_core::errors::ExError;
+use ettlex_core::logging_facility::slow_op::OpLatencyStats;
 use ettlex_core::model::{Decision, Ettle};
+use ettlex_core::snapshot::ParsedManifest;
+use ettlex_store::model::{EttleRecord, RootRecord};
+use ettlex_store::repo::{CursorCodec, CursorKey};
+use serde::Serialize;
 use std::collections::BTreeMap;
 
 /// Default maximum items per paginated list query.
@@ -19,12 +29,14 @@ pub const DEFAULT_LIST_LIMIT: usize = 100;
 pub struct ListOptions {
     /// Maximum number of items to return (defaults to `DEFAULT_LIST_LIMIT`).
     pub limit: Option<usize>,
-    /// Opaque cursor from a previous response (base64-encoded sort key).
+    /// Opaque cursor from a previous response (see `ettlex_store::repo::cursor`).
     pub cursor: Option<String>,
     /// If set, only return items whose ID starts with this prefix.
     pub prefix_filter: Option<String>,
     /// If set, only return items whose title contains this substring.
     pub title_contains: Option<String>,
+    /// Signs and verifies cursors for this listing (unsigned by default).
+    pub cursor_codec: CursorCodec,
 }
 
 impl ListOptions {
@@ -33,9 +45,22 @@ impl ListOptions {
         self.limit.unwrap_or(DEFAULT_LIST_LIMIT)
     }
 
-    /// Decode the cursor to an after-key string.
-    pub fn decode_cursor(&self) -> Option<String> {
-        self.cursor.as_deref().and_then(|c| base64_decode(c).ok())
+    /// Decode the cursor to the listing's after-key; `None` without a cursor.
+    ///
+    /// # Errors
+    /// * `InvalidInput` - the cursor is malformed, belongs to a listing with a
+    ///   different key type, or fails signature verification
+    pub fn after_key<K: CursorKey>(&self) -> Result<Option<K>, ExError> {
+        self.cursor
+            .as_deref()
+            .map(|c| self.cursor_codec.decode(c))
+            .transpose()
+    }
+
+    /// Build the page for `raw` with this listing's limit and cursor codec.
+    /// See [`Page::from_overshot`].
+    pub fn page<T, K: CursorKey>(&self, raw: Vec<T>, key_fn: impl Fn(&T) -> K) -> Page<T> {
+        Page::from_overshot(raw, self.effective_limit(), &self.cursor_codec, key_fn)
     }
 }
 
@@ -54,14 +79,20 @@ impl<T> Page<T> {
     /// Build a page from a raw over-fetched slice.
     ///
     /// `raw` should contain `limit + 1` items at most. If `raw.len() > limit`,
-    /// the extra item is dropped and `has_more` is set to `true`.
-    pub fn from_overshot(mut raw: Vec<T>, limit: usize, cursor_fn: impl Fn(&T) -> String) -> Self {
+    /// the extra item is dropped, `has_more` is set to `true`, and the cursor
+    /// encodes `key_fn` of the last kept item with `codec`.
+    pub fn from_overshot<K: CursorKey>(
+        mut raw: Vec<T>,
+        limit: usize,
+        codec: &CursorCodec,
+        key_fn: impl Fn(&T) -> K,
+    ) -> Self {
         let has_more = raw.len() > limit;
         if has_more {
             raw.truncate(limit);
         }
         let cursor = if has_more {
-            raw.last().map(|item| base64_encode(&cursor_fn(item)))
+            raw.last().map(|item| codec.encode(&key_fn(item)))
         } else {
             None
         };
@@ -77,13 +108,124 @@ impl<T> Page<T> {
 // State
 // ---------------------------------------------------------------------------
 
-/// Result of a `StateGetVersion` query.
-#[derive(Debug, Clone)]
+/// Result of a `StateGetVersion` query, also used as the state stamp on
+/// `ExplainedQueryResult`.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct StateVersionResult {
-    /// Current schema migration version number (row count in `schema_version`).
+    /// Number of applied commands (row count in `command_log`).
     pub state_version: u64,
     /// Manifest digest of the most recent committed snapshot, if any.
     pub semantic_head_digest: Option<String>,
+    /// Workspace counts, filled in by `StateGetVersion` only; `None` on
+    /// state stamps.
+    pub summary: Option<WorkspaceSummary>,
+}
+
+/// Workspace-level counts returned by `StateGetVersion`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceSummary {
+    /// Ettles that are not tombstoned (archived ones included).
+    pub ettle_count: u64,
+    /// Committed snapshots across all roots.
+    pub snapshot_count: u64,
+    /// Approval requests still awaiting a decision.
+    pub pending_approval_count: u64,
+    /// Latest committed snapshot of each root, sorted by root Ettle ID.
+    /// Roots retired in the root registry are left out.
+    pub root_heads: Vec<RootHead>,
+}
+
+/// The latest committed snapshot of one root Ettle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RootHead {
+    pub root_ettle_id: String,
+    /// Registry display name, when the root is registered.
+    pub display_name: Option<String>,
+    pub snapshot_id: String,
+    pub manifest_digest: String,
+}
+
+/// Result of a `StoreFingerprint` query.
+///
+/// Digests are lowercase hex SHA-256. Equal `digest`s mean equal active
+/// content; the per-table digests narrow down where two stores differ.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoreFingerprint {
+    /// Digest over all active semantic content.
+    pub digest: String,
+    /// Digest over active ettles.
+    pub ettles_digest: String,
+    /// Digest over active relations (including constraint attachments).
+    pub relations_digest: String,
+    pub ettle_count: u64,
+    pub relation_count: u64,
+}
+
+/// Which leaves a `SampleLeaves` query draws from. The default is every leaf
+/// Ettle in the store.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LeafSampleFilter {
+    /// Only leaves under this root (ID or slug).
+    pub root_ettle_id: Option<String>,
+    /// Only leaves whose title contains this substring (case-insensitive).
+    pub title_contains: Option<String>,
+}
+
+/// Result of a `SampleLeaves` query.
+///
+/// The same `seed`, filter and `state_version` always give the same
+/// `items`, so an auditor can record the three and have anyone re-draw the
+/// sample.
+#[derive(Debug, Clone)]
+pub struct LeafSample {
+    pub seed: u64,
+    /// State version the sample was drawn at.
+    pub state_version: u64,
+    /// Number of leaves that matched the filter.
+    pub population: u64,
+    /// The sampled leaves, in selection order.
+    pub items: Vec<Ettle>,
+}
+
+/// Tables whose row counts are reported by `StoreStats`.
+pub(crate) const STORE_STATS_TABLES: &[&str] = &[
+    "ettles",
+    "relations",
+    "groups",
+    "group_members",
+    "decisions",
+    "comments",
+    "snapshots",
+    "roots",
+    "profiles",
+    "approval_requests",
+    "command_log",
+    "provenance_events",
+];
+
+/// A store table with its total row count.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TableRowCount {
+    /// Table name.
+    pub table: String,
+    /// `COUNT(*)` of the table, or `None` if the table does not exist in
+    /// this schema (e.g. legacy tables dropped by a migration).
+    pub row_count: Option<u64>,
+}
+
+/// Result of a `StoreStats` query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoreStats {
+    /// Row counts of the main store tables.
+    pub tables: Vec<TableRowCount>,
+    /// Latency percentiles per op, recorded in this process since start (or
+    /// the last `reset_op_latency_stats`), keyed by op name.
+    pub op_latencies: BTreeMap<String, OpLatencyStats>,
+    /// Outcome counts per op, recorded in this process since start (or the
+    /// last `reset_op_outcome_counts`), keyed by op name then outcome label.
+    /// `snapshot_commit` reports `committed`, `duplicate`, `routed`, `denied`
+    /// and `failed:<error code>`.
+    pub op_outcomes: BTreeMap<String, BTreeMap<String, u64>>,
 }
 
 // ---------------------------------------------------------------------------
@@ -93,8 +235,27 @@ pub struct StateVersionResult {
 /// Result of an `EttleGet` query.
 #[derive(Debug, Clone)]
 pub struct EttleGetResult {
-    /// The ettle entity.
-    pub ettle: Ettle,
+    /// The full ettle record, with spilled why/what/how content rehydrated.
+    pub ettle: EttleRecord,
+    /// Its owner and reviewers.
+    pub assignees: EttleAssignees,
+}
+
+/// The people assigned to an Ettle.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EttleAssignees {
+    pub owner: Option<String>,
+    /// Sorted.
+    pub reviewers: Vec<String>,
+}
+
+/// One item of an `EttleListByOwner` result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssignedEttle {
+    pub ettle_id: String,
+    pub title: String,
+    /// `owner` or `reviewer`.
+    pub role: String,
 }
 
 // ---------------------------------------------------------------------------
@@ -114,6 +275,21 @@ pub struct ManifestGetResult {
     pub manifest_bytes: Vec<u8>,
 }
 
+impl ManifestGetResult {
+    /// Parse `manifest_bytes` into a typed manifest.
+    ///
+    /// Top-level fields this version does not know about are kept in
+    /// [`ParsedManifest::unknown_fields`] rather than rejected.
+    ///
+    /// # Errors
+    ///
+    /// `InvalidManifest` or `MissingField` if the stored bytes are not a
+    /// readable manifest.
+    pub fn manifest(&self) -> Result<ParsedManifest, ExError> {
+        ParsedManifest::parse(&self.manifest_bytes)
+    }
+}
+
 // ---------------------------------------------------------------------------
 // EPT
 // ---------------------------------------------------------------------------
@@ -129,6 +305,58 @@ pub struct EptComputeResult {
     pub ept_digest: String,
 }
 
+/// Result of an `EptDiagnose` query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EptDiagnosis {
+    /// The leaf Ettle the refinement path was walked from.
+    pub leaf_ettle_id: String,
+    /// Ettle IDs on the resolved refinement path (root → leaf).
+    pub path: Vec<String>,
+    /// Problems found along the path, leaf first. Empty when the path is
+    /// unambiguous.
+    pub issues: Vec<EptIssue>,
+}
+
+/// A single problem found while walking a refinement path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EptIssue {
+    /// The Ettle at which the problem was found.
+    pub ettle_id: String,
+    /// What is wrong.
+    pub kind: EptIssueKind,
+    /// Human-readable suggestion for resolving the problem.
+    pub suggested_fix: String,
+}
+
+/// Kinds of refinement path problems reported by `EptDiagnose`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EptIssueKind {
+    /// The requested Ettle still has active refinement children.
+    NotALeaf { child_ettle_ids: Vec<String> },
+    /// More than one active parent refines this Ettle. The path follows the
+    /// first candidate (lowest relation ID).
+    AmbiguousParent { candidates: Vec<EptParentCandidate> },
+    /// An active refinement relation points at a parent that is missing,
+    /// tombstoned or archived (`parent_state`).
+    BrokenLink {
+        relation_id: String,
+        parent_ettle_id: String,
+        parent_state: String,
+    },
+    /// Following the parent would revisit an Ettle already on the path.
+    Cycle { parent_ettle_id: String },
+}
+
+/// A candidate parent in an `AmbiguousParent` issue.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EptParentCandidate {
+    /// 0-based position in resolution order (0 is the parent followed).
+    pub ordinal: u32,
+    pub relation_id: String,
+    pub parent_ettle_id: String,
+    pub title: String,
+}
+
 // ---------------------------------------------------------------------------
 // Profile
 // ---------------------------------------------------------------------------
@@ -151,8 +379,14 @@ pub struct ProfileResolveResult {
     pub profile_ref: String,
     /// SHA-256 digest of the raw `payload_json` bytes.
     pub profile_digest: String,
-    /// Parsed profile payload.
+    /// Parsed profile payload, as stored.
     pub parsed_profile: serde_json::Value,
+    /// Payload with the `extends` chain merged in; this is what the engine
+    /// acts on.
+    pub effective_profile: serde_json::Value,
+    /// Profiles merged into `effective_profile`, bases first and the
+    /// resolved profile last.
+    pub extends_chain: Vec<String>,
 }
 
 // ---------------------------------------------------------------------------
@@ -170,6 +404,11 @@ pub struct ApprovalGetResult {
     pub semantic_request_digest: String,
     /// Full parsed request payload from CAS.
     pub payload_json: serde_json::Value,
+    /// `payload_json` parsed into its typed schema.
+    pub payload: ApprovalRequestPayload,
+    /// Owners and reviewers of the candidate Ettles, sorted: the people to
+    /// route the request to.
+    pub assignees: Vec<String>,
 }
 
 // ---------------------------------------------------------------------------
@@ -185,6 +424,117 @@ pub struct DecisionContextResult {
     pub all_for_leaf: Vec<Decision>,
 }
 
+// ---------------------------------------------------------------------------
+// Constraint attachments
+// ---------------------------------------------------------------------------
+
+/// One `constraint` relation from a constraint Ettle to a governed Ettle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintAttachment {
+    /// ID of the `constraint` relation.
+    pub relation_id: String,
+    /// The governed (target) Ettle.
+    pub ettle_id: String,
+    /// Title of the governed Ettle.
+    pub ettle_title: String,
+    /// True if the governed Ettle itself is tombstoned.
+    pub ettle_tombstoned: bool,
+    /// Attachment ordinal recorded at attach time, if present.
+    pub ordinal: Option<u64>,
+    /// Relation properties (including `ordinal`).
+    pub properties_json: serde_json::Value,
+    /// When the attachment was created.
+    pub created_at: String,
+    /// When the attachment was tombstoned, if it was.
+    pub tombstoned_at: Option<String>,
+}
+
+/// Constraints in force for one Ettle, as returned by `EttleEffectiveConstraints`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EffectiveConstraints {
+    /// The Ettle the constraints apply to.
+    pub ettle_id: String,
+    /// Refinement path from the root down to `ettle_id`.
+    pub path: Vec<String>,
+    /// Constraints in force, root-most attachment first, then by ordinal.
+    pub constraints: Vec<EffectiveConstraint>,
+    /// Attachments on the path that do not apply, with the reason.
+    pub excluded: Vec<ExcludedConstraint>,
+}
+
+/// A constraint in force for an Ettle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EffectiveConstraint {
+    /// The constraint Ettle.
+    pub constraint_id: String,
+    /// Title of the constraint Ettle.
+    pub constraint_title: String,
+    /// The `constraint` relation that brings it into force.
+    pub relation_id: String,
+    /// Ettle on the path the relation is attached to.
+    pub attached_to: String,
+    /// True when `attached_to` is an ancestor rather than the Ettle itself.
+    pub inherited: bool,
+    /// `family` from the relation properties, if present.
+    pub family: Option<String>,
+    /// Attachment ordinal recorded at attach time, if present.
+    pub ordinal: Option<u64>,
+    /// Relation properties.
+    pub properties_json: serde_json::Value,
+}
+
+/// An attachment on the path that does not apply to the Ettle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExcludedConstraint {
+    pub constraint_id: String,
+    pub relation_id: String,
+    pub attached_to: String,
+    pub reason: ExclusionReason,
+}
+
+/// Why an attachment on the path is not in force.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExclusionReason {
+    /// Attached to an ancestor with `scope: "self"`.
+    ScopedToSelf,
+    /// The same constraint is attached nearer the Ettle.
+    Shadowed { by_relation_id: String },
+    /// An attachment nearer the Ettle set `override: true` for the same family.
+    Overridden { by_relation_id: String },
+}
+
+// ---------------------------------------------------------------------------
+// Ettle referrers
+// ---------------------------------------------------------------------------
+
+/// One `refinement` relation pointing at an Ettle from its parent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefinementReferrer {
+    /// ID of the `refinement` relation.
+    pub relation_id: String,
+    /// The refining (source) Ettle.
+    pub parent_ettle_id: String,
+    /// Title of the parent Ettle.
+    pub parent_title: String,
+    /// True if the parent Ettle itself is tombstoned.
+    pub parent_tombstoned: bool,
+    /// Child ordinal under the parent, if recorded.
+    pub ordinal: Option<u64>,
+    /// When the relation was created.
+    pub created_at: String,
+    /// When the relation was tombstoned, if it was.
+    pub tombstoned_at: Option<String>,
+}
+
+/// Result of an `EttleListReferrers` query, split by relation state.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct EttleReferrers {
+    /// Active `refinement` relations, in creation order.
+    pub active: Vec<RefinementReferrer>,
+    /// Tombstoned `refinement` relations, in creation order.
+    pub tombstoned: Vec<RefinementReferrer>,
+}
+
 // ---------------------------------------------------------------------------
 // Constraint predicate preview
 // ---------------------------------------------------------------------------
@@ -211,6 +561,11 @@ pub struct PredicatePreviewResult {
     pub selected: Option<String>,
     /// All candidate IDs.
     pub candidates: Vec<String>,
+    /// The ambiguity policy the preview was evaluated under.
+    pub ambiguity_policy: AmbiguityPolicy,
+    /// True if `ambiguity_policy` came from the query's override rather
+    /// than the profile.
+    pub policy_overridden: bool,
 }
 
 // ---------------------------------------------------------------------------
@@ -258,6 +613,9 @@ pub type EttlePage = Page<Ettle>;
 /// A page of `Decision` items.
 pub type DecisionPage = Page<Decision>;
 
+/// A page of `CommentRecord` items.
+pub type CommentPage = Page<ettlex_store::model::CommentRecord>;
+
 /// A page of `ProfileGetResult` items.
 pub type ProfilePage = Page<ProfileGetResult>;
 
@@ -296,6 +654,33 @@ pub struct SnapshotGetResult {
     pub policy_ref: String,
     pub profile_ref: String,
     pub status: String,
+    /// Free-text commit message, if one was given.
+    pub message: Option<String>,
+    /// Key/value annotations recorded at commit time.
+    pub annotations: BTreeMap<String, String>,
+}
+
+// ---------------------------------------------------------------------------
+// Root registry
+// ---------------------------------------------------------------------------
+
+/// A registered root with its snapshot head.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegisteredRoot {
+    pub root: RootRecord,
+    /// Latest committed snapshot for the root, in ledger order.
+    pub head_snapshot_id: Option<String>,
+    pub snapshot_count: u64,
+}
+
+/// Result of a `RootList` query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RootRegistry {
+    /// Registered roots ordered by display name.
+    pub registered: Vec<RegisteredRoot>,
+    /// Implicit roots (parentless, non-constraint active Ettles) missing
+    /// from the registry, sorted by ID.
+    pub unregistered: Vec<String>,
 }
 
 // ---------------------------------------------------------------------------
@@ -0,0 +1,683 @@
+//! Declarative reconciliation — plan and apply the changes that turn a
+//! refinement subtree into a desired state.
+//!
+//! [`plan_reconciliation`] diffs the declared tree against the store,
+//! [`assess_plan`] rates each change's risk, and [`execute_plan`] applies
+//! the plan as one [`apply_commands_atomic`] batch: every change goes
+//! through `apply_command`, and a failure applies nothing.
+
+#![allow(clippy::result_large_err)]
+
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+use std::fmt;
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_store::cas::{CasDedupStats, FsStore};
+use ettlex_store::model::{RelationListOpts, RelationRecord};
+use ettlex_store::repo::SqliteRepo;
+use ettlex_store::snapshot::query::list_snapshot_rows;
+use rusqlite::Connection;
+use serde::Deserialize;
+use serde_json::{json, Value as JsonValue};
+
+use super::command::{apply_commands_atomic, Command, CommandResult};
+use super::content_spill::stored_content_eq;
+use super::engine_query::read_state_version;
+use super::ettle::resolve_ettle_ref;
+
+type Result<T> = std::result::Result<T, ExError>;
+
+const REFINEMENT: &str = "refinement";
+const CONSTRAINT: &str = "constraint";
+
+/// Desired state of one refinement subtree
+///
+/// ```yaml
+/// root:
+///   id: payments            # existing Ettle ID or slug; omit to create
+///   title: Payments
+///   what: Moves money.
+///   children:
+///     - id: refunds
+///       title: Refunds
+///     - title: Chargebacks  # no id: created
+/// ```
+///
+/// `why`, `what` and `how` are only compared when present. Children are
+/// ordered: each refinement gets its list index as `ordinal`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DesiredState {
+    pub root: DesiredEttle,
+}
+
+/// One Ettle in a [`DesiredState`]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DesiredEttle {
+    #[serde(default)]
+    pub id: Option<String>,
+    pub title: String,
+    #[serde(default)]
+    pub why: Option<String>,
+    #[serde(default)]
+    pub what: Option<String>,
+    #[serde(default)]
+    pub how: Option<String>,
+    #[serde(default)]
+    pub children: Vec<DesiredEttle>,
+}
+
+/// An Ettle named by a plan: existing, or created earlier in the same plan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanNode {
+    Existing(String),
+    /// Position in the desired tree, e.g. `root/1/0`
+    New(String),
+}
+
+/// One step of a reconciliation plan, in execution order
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlannedChange {
+    CreateEttle {
+        path: String,
+        title: String,
+        why: Option<String>,
+        what: Option<String>,
+        how: Option<String>,
+    },
+    UpdateEttle {
+        ettle_id: String,
+        title: Option<String>,
+        why: Option<String>,
+        what: Option<String>,
+        how: Option<String>,
+    },
+    DetachRefinement {
+        relation_id: String,
+        parent_id: String,
+        child_id: String,
+    },
+    AttachRefinement {
+        parent: PlanNode,
+        child: PlanNode,
+        ordinal: usize,
+    },
+    ReorderRefinement {
+        relation_id: String,
+        properties_json: JsonValue,
+    },
+    TombstoneEttle {
+        ettle_id: String,
+    },
+}
+
+/// How risky a planned change is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RiskLevel {
+    Low,
+    Medium,
+    /// Destructive; refused unless destructive changes are allowed
+    High,
+}
+
+impl RiskLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RiskLevel::Low => "low",
+            RiskLevel::Medium => "medium",
+            RiskLevel::High => "high",
+        }
+    }
+}
+
+/// A planned change annotated by [`assess_plan`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssessedChange {
+    pub change: PlannedChange,
+    pub risk: RiskLevel,
+    /// Why the change got its risk level (empty for plain low-risk changes)
+    pub reasons: Vec<String>,
+}
+
+/// Outcome of [`execute_plan`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApplyReport {
+    /// Number of plan steps executed
+    pub changes: usize,
+    /// IDs of created Ettles by desired-tree path
+    pub created: BTreeMap<String, String>,
+    /// CAS blobs written versus reused for spilled content
+    pub cas: CasDedupStats,
+}
+
+/// Compute the minimal changes that turn the current subtree into `desired`
+///
+/// The current subtree is the root and everything reachable from it over
+/// active refinement relations. Refinements in it that are not declared are
+/// detached, and Ettles in it that are not declared are tombstoned
+/// (deepest first). Declared Ettles outside it are adopted.
+///
+/// # Errors
+/// * `NotFound` - A declared `id` matches no Ettle
+/// * `AlreadyTombstoned` - A declared `id` names a tombstoned Ettle
+/// * `InvalidInput` - An Ettle is declared twice or a title is empty
+pub fn plan_reconciliation(
+    conn: &Connection,
+    desired: &DesiredState,
+) -> Result<Vec<PlannedChange>> {
+    let mut walk = DesiredWalk::default();
+    let root = walk.visit(conn, &desired.root, "root".to_string())?;
+
+    let mut current_edges: BTreeMap<(String, String), RelationRecord> = BTreeMap::new();
+    let mut current_order = Vec::new();
+    if let PlanNode::Existing(root_id) = &root {
+        let mut queue = VecDeque::from([root_id.clone()]);
+        let mut seen = BTreeSet::new();
+        while let Some(parent) = queue.pop_front() {
+            if !seen.insert(parent.clone()) {
+                continue;
+            }
+            current_order.push(parent.clone());
+            for rel in active_refinements(conn, &parent)? {
+                let child = rel.target_ettle_id.clone();
+                let live = SqliteRepo::get_ettle_record(conn, &child)?
+                    .is_some_and(|r| r.tombstoned_at.is_none());
+                if live {
+                    queue.push_back(child.clone());
+                    current_edges.insert((parent.clone(), child), rel);
+                }
+            }
+        }
+    }
+
+    let desired_existing: BTreeSet<(String, String)> = walk
+        .edges
+        .iter()
+        .filter_map(|(p, c, _)| match (p, c) {
+            (PlanNode::Existing(p), PlanNode::Existing(c)) => Some((p.clone(), c.clone())),
+            _ => None,
+        })
+        .collect();
+
+    let mut plan = walk.creates;
+    plan.append(&mut walk.updates);
+    for ((parent_id, child_id), rel) in &current_edges {
+        if !desired_existing.contains(&(parent_id.clone(), child_id.clone())) {
+            plan.push(PlannedChange::DetachRefinement {
+                relation_id: rel.id.clone(),
+                parent_id: parent_id.clone(),
+                child_id: child_id.clone(),
+            });
+        }
+    }
+    let mut reorders = Vec::new();
+    for (parent, child, ordinal) in walk.edges {
+        let existing_rel = match (&parent, &child) {
+            (PlanNode::Existing(p), PlanNode::Existing(c)) => {
+                current_edges.get(&(p.clone(), c.clone()))
+            }
+            _ => None,
+        };
+        match existing_rel {
+            Some(rel) => {
+                let mut props: JsonValue =
+                    serde_json::from_str(&rel.properties_json).unwrap_or_else(|_| json!({}));
+                if props.get("ordinal").and_then(JsonValue::as_u64) != Some(ordinal as u64) {
+                    if !props.is_object() {
+                        props = json!({});
+                    }
+                    props["ordinal"] = json!(ordinal);
+                    reorders.push(PlannedChange::ReorderRefinement {
+                        relation_id: rel.id.clone(),
+                        properties_json: props,
+                    });
+                }
+            }
+            None => plan.push(PlannedChange::AttachRefinement {
+                parent,
+                child,
+                ordinal,
+            }),
+        }
+    }
+    plan.append(&mut reorders);
+    for ettle_id in current_order.iter().skip(1).rev() {
+        if !walk.declared.contains(ettle_id) {
+            plan.push(PlannedChange::TombstoneEttle {
+                ettle_id: ettle_id.clone(),
+            });
+        }
+    }
+    Ok(plan)
+}
+
+/// Annotate each planned change with its risk
+///
+/// An Ettle is *anchored* when a committed snapshot is rooted at it or at
+/// one of its refinement ancestors.
+///
+/// * High: tombstoning an Ettle (more so if it carries constraint
+///   attachments, which tombstoning removes) and detaching a refinement
+///   under an anchored Ettle, which breaks the EPTs of existing snapshots.
+/// * Medium: other detaches, and updating, reordering or attaching under
+///   an anchored Ettle.
+/// * Low: everything else.
+pub fn assess_plan(conn: &Connection, plan: Vec<PlannedChange>) -> Result<Vec<AssessedChange>> {
+    let anchored = anchored_ettle_ids(conn)?;
+    let anchored_reason = |id: &str| format!("touches {}, covered by a committed snapshot", id);
+    plan.into_iter()
+        .map(|change| {
+            let mut risk = RiskLevel::Low;
+            let mut reasons = Vec::new();
+            match &change {
+                PlannedChange::CreateEttle { .. } => {}
+                PlannedChange::UpdateEttle { ettle_id, .. } => {
+                    if anchored.contains(ettle_id) {
+                        risk = RiskLevel::Medium;
+                        reasons.push(anchored_reason(ettle_id));
+                    }
+                }
+                PlannedChange::DetachRefinement { parent_id, .. } => {
+                    if anchored.contains(parent_id) {
+                        risk = RiskLevel::High;
+                        reasons.push(format!(
+                            "breaks the EPT of committed snapshots covering {}",
+                            parent_id
+                        ));
+                    } else {
+                        risk = RiskLevel::Medium;
+                    }
+                }
+                PlannedChange::AttachRefinement { parent, .. } => {
+                    if let PlanNode::Existing(parent_id) = parent {
+                        if anchored.contains(parent_id) {
+                            risk = RiskLevel::Medium;
+                            reasons.push(anchored_reason(parent_id));
+                        }
+                    }
+                }
+                PlannedChange::ReorderRefinement { relation_id, .. } => {
+                    let parent = SqliteRepo::get_relation(conn, relation_id)?
+                        .map(|r| r.source_ettle_id)
+                        .filter(|p| anchored.contains(p));
+                    if let Some(parent_id) = parent {
+                        risk = RiskLevel::Medium;
+                        reasons.push(format!(
+                            "changes EPT order of committed snapshots covering {}",
+                            parent_id
+                        ));
+                    }
+                }
+                PlannedChange::TombstoneEttle { ettle_id } => {
+                    risk = RiskLevel::High;
+                    if anchored.contains(ettle_id) {
+                        reasons.push(anchored_reason(ettle_id));
+                    }
+                    let constraints = SqliteRepo::list_relations(
+                        conn,
+                        &RelationListOpts {
+                            target_ettle_id: Some(ettle_id.clone()),
+                            relation_type: Some(CONSTRAINT.to_string()),
+                            ..Default::default()
+                        },
+                    )?
+                    .len();
+                    if constraints > 0 {
+                        reasons.push(format!("removes {} constraint attachment(s)", constraints));
+                    }
+                }
+            }
+            Ok(AssessedChange {
+                change,
+                risk,
+                reasons,
+            })
+        })
+        .collect()
+}
+
+/// Execute an assessed plan from [`assess_plan`] as one atomic batch
+///
+/// The changes are re-assessed against the store before anything runs, and
+/// a plan with high-risk changes is refused unless `allow_destructive`; the
+/// risk levels the caller holds are only for display. New Ettles are referred to by the slug their create will allocate, so
+/// the whole plan is built up front. The batch is checked against the state
+/// version read when it was built; a concurrent write fails it with
+/// `HeadMismatch` instead of letting a slug resolve to another Ettle.
+///
+/// # Errors
+/// * `PolicyDenied` - The plan has high-risk changes and `allow_destructive`
+///   is off
+/// * Any command error, prefixed with its step index; on error nothing is
+///   applied
+pub fn execute_plan(
+    conn: &mut Connection,
+    cas: &FsStore,
+    assessed: &[AssessedChange],
+    allow_destructive: bool,
+) -> Result<ApplyReport> {
+    let state_version = read_state_version(conn)?.state_version;
+    let plan: Vec<PlannedChange> = assessed.iter().map(|a| a.change.clone()).collect();
+    check_destructive(&assess_plan(conn, plan.clone())?, allow_destructive)?;
+    let titles: Vec<&str> = plan
+        .iter()
+        .filter_map(|change| match change {
+            PlannedChange::CreateEttle { title, .. } => Some(title.as_str()),
+            _ => None,
+        })
+        .collect();
+    let mut slugs = allocate_slugs(conn, &titles)?.into_iter();
+    let mut new_slugs: BTreeMap<String, String> = BTreeMap::new();
+    let mut create_paths = Vec::new();
+    let mut commands = Vec::with_capacity(plan.len());
+    for change in &plan {
+        commands.push(match change.clone() {
+            PlannedChange::CreateEttle {
+                path,
+                title,
+                why,
+                what,
+                how,
+            } => {
+                let slug = slugs.next().ok_or_else(|| {
+                    ExError::new(ExErrorKind::Internal)
+                        .with_op("apply_desired_state")
+                        .with_message("slug allocation out of step with the plan")
+                })?;
+                new_slugs.insert(path.clone(), slug);
+                create_paths.push(path);
+                Command::EttleCreate {
+                    title,
+                    ettle_id: None,
+                    why,
+                    what,
+                    how,
+                    reasoning_link_id: None,
+                    reasoning_link_type: None,
+                }
+            }
+            PlannedChange::UpdateEttle {
+                ettle_id,
+                title,
+                why,
+                what,
+                how,
+            } => Command::EttleUpdate {
+                ettle_id,
+                title,
+                why,
+                what,
+                how,
+                reasoning_link_id: None,
+                reasoning_link_type: None,
+            },
+            PlannedChange::DetachRefinement { relation_id, .. } => {
+                Command::RelationTombstone { relation_id }
+            }
+            PlannedChange::AttachRefinement {
+                parent,
+                child,
+                ordinal,
+            } => Command::RelationCreate {
+                source_ettle_id: node_ref(&new_slugs, &parent)?,
+                target_ettle_id: node_ref(&new_slugs, &child)?,
+                relation_type: REFINEMENT.to_string(),
+                properties_json: Some(json!({ "ordinal": ordinal })),
+                relation_id: None,
+            },
+            PlannedChange::ReorderRefinement {
+                relation_id,
+                properties_json,
+            } => Command::RelationUpdate {
+                relation_id,
+                properties_json: Some(properties_json),
+            },
+            PlannedChange::TombstoneEttle { ettle_id } => Command::EttleTombstone { ettle_id },
+        });
+    }
+    if commands.is_empty() {
+        return Ok(ApplyReport {
+            changes: 0,
+            created: BTreeMap::new(),
+            cas: CasDedupStats::default(),
+        });
+    }
+    let before = cas.dedup_stats();
+
+    let (results, _) = apply_commands_atomic(
+        commands,
+        Some(state_version),
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )?;
+    let created_ids = results.into_iter().filter_map(|result| match result {
+        CommandResult::EttleCreate { ettle_id } => Some(ettle_id),
+        _ => None,
+    });
+    Ok(ApplyReport {
+        changes: plan.len(),
+        created: create_paths.into_iter().zip(created_ids).collect(),
+        cas: cas.dedup_stats().since(&before),
+    })
+}
+
+/// Refuse a plan with high-risk changes unless `allow_destructive`
+fn check_destructive(assessed: &[AssessedChange], allow_destructive: bool) -> Result<()> {
+    let high = assessed
+        .iter()
+        .filter(|a| a.risk == RiskLevel::High)
+        .count();
+    if high > 0 && !allow_destructive {
+        return Err(ExError::new(ExErrorKind::PolicyDenied)
+            .with_op("apply_desired_state")
+            .with_message(format!(
+                "plan has {} high-risk change(s) and destructive changes are not allowed",
+                high
+            )));
+    }
+    Ok(())
+}
+
+impl fmt::Display for PlanNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanNode::Existing(id) => write!(f, "{}", id),
+            PlanNode::New(path) => write!(f, "(new {})", path),
+        }
+    }
+}
+
+impl fmt::Display for PlannedChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlannedChange::CreateEttle { path, title, .. } => {
+                write!(f, "+ create ettle '{}' at {}", title, path)
+            }
+            PlannedChange::UpdateEttle {
+                ettle_id,
+                title,
+                why,
+                what,
+                how,
+            } => {
+                let fields: Vec<&str> = [
+                    ("title", title.is_some()),
+                    ("why", why.is_some()),
+                    ("what", what.is_some()),
+                    ("how", how.is_some()),
+                ]
+                .iter()
+                .filter(|(_, changed)| *changed)
+                .map(|(name, _)| *name)
+                .collect();
+                write!(f, "~ update ettle {} ({})", ettle_id, fields.join(", "))
+            }
+            PlannedChange::DetachRefinement {
+                relation_id,
+                parent_id,
+                child_id,
+            } => write!(
+                f,
+                "- detach refinement {} -> {} ({})",
+                parent_id, child_id, relation_id
+            ),
+            PlannedChange::AttachRefinement {
+                parent,
+                child,
+                ordinal,
+            } => write!(
+                f,
+                "+ attach refinement {} -> {} (ordinal {})",
+                parent, child, ordinal
+            ),
+            PlannedChange::ReorderRefinement {
+                relation_id,
+                properties_json,
+            } => write!(
+                f,
+                "~ reorder refinement {} (ordinal {})",
+                relation_id, properties_json["ordinal"]
+            ),
+            PlannedChange::TombstoneEttle { ettle_id } => {
+                write!(f, "- tombstone ettle {}", ettle_id)
+            }
+        }
+    }
+}
+
+/// Collects creates, field updates and edges from the desired tree
+#[derive(Default)]
+struct DesiredWalk {
+    creates: Vec<PlannedChange>,
+    updates: Vec<PlannedChange>,
+    /// (parent, child, ordinal) for every declared refinement
+    edges: Vec<(PlanNode, PlanNode, usize)>,
+    declared: BTreeSet<String>,
+}
+
+impl DesiredWalk {
+    fn visit(&mut self, conn: &Connection, node: &DesiredEttle, path: String) -> Result<PlanNode> {
+        if node.title.trim().is_empty() {
+            return Err(ExError::new(ExErrorKind::InvalidInput)
+                .with_op("apply_desired_state")
+                .with_message(format!("{}: title must not be empty", path)));
+        }
+        let this = match &node.id {
+            Some(reference) => {
+                let ettle_id = resolve_ettle_ref(conn, reference)?;
+                let record = SqliteRepo::get_ettle_record(conn, &ettle_id)?.ok_or_else(|| {
+                    ExError::new(ExErrorKind::NotFound)
+                        .with_op("apply_desired_state")
+                        .with_entity_id(reference)
+                        .with_message(format!("{}: no ettle matches '{}'", path, reference))
+                })?;
+                if record.tombstoned_at.is_some() {
+                    return Err(ExError::new(ExErrorKind::AlreadyTombstoned)
+                        .with_op("apply_desired_state")
+                        .with_entity_id(&ettle_id)
+                        .with_message(format!("{}: ettle is tombstoned", path)));
+                }
+                if !self.declared.insert(ettle_id.clone()) {
+                    return Err(ExError::new(ExErrorKind::InvalidInput)
+                        .with_op("apply_desired_state")
+                        .with_entity_id(&ettle_id)
+                        .with_message(format!("{}: ettle is declared more than once", path)));
+                }
+                let changed = |want: &Option<String>, have: &str, digest: &Option<String>| {
+                    want.as_ref()
+                        .filter(|w| !stored_content_eq(w, have, digest.as_deref()))
+                        .cloned()
+                };
+                let title = changed(&Some(node.title.clone()), &record.title, &None);
+                let why = changed(&node.why, &record.why, &record.why_digest);
+                let what = changed(&node.what, &record.what, &record.what_digest);
+                let how = changed(&node.how, &record.how, &record.how_digest);
+                if title.is_some() || why.is_some() || what.is_some() || how.is_some() {
+                    self.updates.push(PlannedChange::UpdateEttle {
+                        ettle_id: ettle_id.clone(),
+                        title,
+                        why,
+                        what,
+                        how,
+                    });
+                }
+                PlanNode::Existing(ettle_id)
+            }
+            None => {
+                self.creates.push(PlannedChange::CreateEttle {
+                    path: path.clone(),
+                    title: node.title.clone(),
+                    why: node.why.clone(),
+                    what: node.what.clone(),
+                    how: node.how.clone(),
+                });
+                PlanNode::New(path.clone())
+            }
+        };
+        for (ordinal, child) in node.children.iter().enumerate() {
+            let child_node = self.visit(conn, child, format!("{}/{}", path, ordinal))?;
+            self.edges.push((this.clone(), child_node, ordinal));
+        }
+        Ok(this)
+    }
+}
+
+/// Committed snapshot roots and everything under them over active refinements
+fn anchored_ettle_ids(conn: &Connection) -> Result<BTreeSet<String>> {
+    let mut queue: VecDeque<String> = list_snapshot_rows(conn, None)?
+        .into_iter()
+        .filter(|row| row.status == "committed")
+        .map(|row| row.root_ettle_id)
+        .collect();
+    let mut anchored = BTreeSet::new();
+    while let Some(id) = queue.pop_front() {
+        if anchored.insert(id.clone()) {
+            for rel in active_refinements(conn, &id)? {
+                queue.push_back(rel.target_ettle_id);
+            }
+        }
+    }
+    Ok(anchored)
+}
+
+fn active_refinements(conn: &Connection, parent: &str) -> Result<Vec<RelationRecord>> {
+    SqliteRepo::list_relations(
+        conn,
+        &RelationListOpts {
+            source_ettle_id: Some(parent.to_string()),
+            relation_type: Some(REFINEMENT.to_string()),
+            ..Default::default()
+        },
+    )
+}
+
+/// The slugs `EttleCreate` will allocate for `titles`, created in order
+fn allocate_slugs(conn: &Connection, titles: &[&str]) -> Result<Vec<String>> {
+    let mut taken = HashSet::new();
+    titles
+        .iter()
+        .map(|title| {
+            let slug = SqliteRepo::allocate_ettle_slug_excluding(conn, title, &taken)?;
+            taken.insert(slug.clone());
+            Ok(slug)
+        })
+        .collect()
+}
+
+/// Reference to a plan node: its ID, or the slug of an Ettle created
+/// earlier in the batch
+fn node_ref(new_slugs: &BTreeMap<String, String>, node: &PlanNode) -> Result<String> {
+    match node {
+        PlanNode::Existing(id) => Ok(id.clone()),
+        PlanNode::New(path) => new_slugs.get(path).cloned().ok_or_else(|| {
+            ExError::new(ExErrorKind::Internal)
+                .with_op("apply_desired_state")
+                .with_message(format!("{} used before it was created", path))
+        }),
+    }
+}
@@ -0,0 +1,251 @@
+//! Engine handler for renaming references inside Ettle content.
+//!
+//! Prose in an Ettle's `why` / `what` / `how` often names other Ettles by ID
+//! or slug. `ContentRefRename` finds every whole-token occurrence of `from`
+//! in the selected Ettles and rewrites it to `to`. A token boundary is any
+//! character outside `[A-Za-z0-9_:-]`, so renaming `payments-api` leaves
+//! `payments-api-2` and `old-payments-api` alone.
+//!
+//! Previews use the command's `dry_run` option. Callers that confirm
+//! occurrences one by one pass the sites they accepted back as `only`; a site
+//! that no longer matches (the content changed since the preview) is
+//! rejected rather than skipped.
+
+#![allow(clippy::result_large_err)]
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_store::cas::FsStore;
+use ettlex_store::errors::from_rusqlite;
+use ettlex_store::repo::SqliteRepo;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use super::command::CommandResult;
+use super::constraint::{resolve_selector, TargetSelector};
+use super::content_spill::{digests_changed, hydrate_ettle_content, prepare_content};
+
+type Result<T> = std::result::Result<T, ExError>;
+
+/// Ettle content field searched for references.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentField {
+    Why,
+    What,
+    How,
+}
+
+impl ContentField {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContentField::Why => "why",
+            ContentField::What => "what",
+            ContentField::How => "how",
+        }
+    }
+}
+
+/// Identifies one occurrence: Ettle, field and byte offset of the match.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ContentRefSite {
+    pub ettle_id: String,
+    pub field: ContentField,
+    pub offset: usize,
+}
+
+/// One occurrence of the renamed reference.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ContentRefOccurrence {
+    #[serde(flatten)]
+    pub site: ContentRefSite,
+    /// 1-based line number within the field.
+    pub line: usize,
+    /// The full line containing the match, before rewriting.
+    pub context: String,
+    /// Whether this occurrence was (or, in a dry run, would be) rewritten.
+    pub rewritten: bool,
+}
+
+// ---------------------------------------------------------------------------
+// handle_content_ref_rename
+// ---------------------------------------------------------------------------
+
+/// Rewrite whole-token occurrences of `from` to `to` in Ettle content.
+///
+/// `selector` limits the search (default: every active Ettle); `only`
+/// limits the rewrite to the listed sites. Occurrences are reported in
+/// Ettle id, field and offset order.
+///
+/// Invariants enforced:
+/// - `from` and `to` must be non-empty, free of surrounding whitespace and
+///   different (`InvalidInput`).
+/// - Selector roots must exist and be active.
+/// - Every site in `only` must be a current occurrence (`InvalidInput`).
+pub fn handle_content_ref_rename(
+    conn: &mut Connection,
+    cas: &FsStore,
+    from: String,
+    to: String,
+    selector: Option<TargetSelector>,
+    only: Option<Vec<ContentRefSite>>,
+) -> Result<CommandResult> {
+    validate_ref("from", &from)?;
+    validate_ref("to", &to)?;
+    if from == to {
+        return Err(invalid("from and to must differ"));
+    }
+
+    let ettle_ids: Vec<String> = match &selector {
+        Some(selector) => resolve_selector(conn, selector)?.into_iter().collect(),
+        None => SqliteRepo::list_active_ettle_ids(conn)?,
+    };
+
+    let mut occurrences = Vec::new();
+    let mut contents: BTreeMap<(String, ContentField), String> = BTreeMap::new();
+    for ettle_id in ettle_ids {
+        let Some(mut record) = SqliteRepo::get_ettle_record(conn, &ettle_id)? else {
+            continue;
+        };
+        hydrate_ettle_content(&mut record, cas)?;
+        for (field, text) in [
+            (ContentField::Why, record.why),
+            (ContentField::What, record.what),
+            (ContentField::How, record.how),
+        ] {
+            let offsets = find_token(&text, &from);
+            if offsets.is_empty() {
+                continue;
+            }
+            for offset in offsets {
+                occurrences.push(ContentRefOccurrence {
+                    site: ContentRefSite {
+                        ettle_id: ettle_id.clone(),
+                        field,
+                        offset,
+                    },
+                    line: text[..offset].matches('\n').count() + 1,
+                    context: line_at(&text, offset).to_string(),
+                    rewritten: true,
+                });
+            }
+            contents.insert((ettle_id.clone(), field), text);
+        }
+    }
+
+    if let Some(only) = only {
+        let selected: BTreeSet<ContentRefSite> = only.into_iter().collect();
+        let found: BTreeSet<&ContentRefSite> = occurrences.iter().map(|o| &o.site).collect();
+        if let Some(stale) = selected.iter().find(|s| !found.contains(s)) {
+            return Err(invalid(format!(
+                "no occurrence of '{}' at {} offset {} in {}",
+                from,
+                stale.field.as_str(),
+                stale.offset,
+                stale.ettle_id
+            ))
+            .with_entity_id(&stale.ettle_id));
+        }
+        for occurrence in &mut occurrences {
+            occurrence.rewritten = selected.contains(&occurrence.site);
+        }
+    }
+
+    // Rewrite per field, last offset first so earlier offsets stay valid.
+    let mut rewrites: BTreeMap<String, BTreeMap<ContentField, String>> = BTreeMap::new();
+    for occurrence in occurrences.iter().rev().filter(|o| o.rewritten) {
+        let site = &occurrence.site;
+        let key = (site.ettle_id.clone(), site.field);
+        let fields = rewrites.entry(site.ettle_id.clone()).or_default();
+        let text = fields
+            .entry(site.field)
+            .or_insert_with(|| contents.remove(&key).unwrap_or_default());
+        text.replace_range(site.offset..site.offset + from.len(), &to);
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let tx = conn.savepoint().map_err(from_rusqlite)?;
+    for (ettle_id, fields) in &rewrites {
+        let existing = SqliteRepo::get_ettle_record(&tx, ettle_id)?;
+        let content = prepare_content(
+            &tx,
+            cas,
+            existing.as_ref(),
+            fields.get(&ContentField::Why).map(String::as_str),
+            fields.get(&ContentField::What).map(String::as_str),
+            fields.get(&ContentField::How).map(String::as_str),
+        )?;
+        SqliteRepo::update_ettle(
+            &tx,
+            ettle_id,
+            None,
+            content.why.as_deref(),
+            content.what.as_deref(),
+            content.how.as_deref(),
+            None,
+            None,
+            &now,
+        )?;
+        if digests_changed(existing.as_ref(), &content) {
+            content.write_digests(&tx, ettle_id)?;
+        }
+    }
+    tx.commit().map_err(from_rusqlite)?;
+
+    Ok(CommandResult::ContentRefRename {
+        occurrences,
+        updated_ettle_ids: rewrites.into_keys().collect(),
+    })
+}
+
+/// Byte offsets of whole-token matches of `needle` in `text`.
+fn find_token(text: &str, needle: &str) -> Vec<usize> {
+    text.match_indices(needle)
+        .map(|(offset, _)| offset)
+        .filter(|&offset| {
+            let before = text[..offset].chars().next_back();
+            let after = text[offset + needle.len()..].chars().next();
+            !before.is_some_and(is_ref_char) && !after.is_some_and(is_ref_char)
+        })
+        .collect()
+}
+
+fn is_ref_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | ':' | '-')
+}
+
+fn line_at(text: &str, offset: usize) -> &str {
+    let start = text[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let end = text[offset..].find('\n').map_or(text.len(), |i| offset + i);
+    &text[start..end]
+}
+
+fn validate_ref(field: &str, value: &str) -> Result<()> {
+    if value.is_empty() || value.trim() != value {
+        return Err(invalid(format!(
+            "{} must be non-empty with no surrounding whitespace",
+            field
+        )));
+    }
+    Ok(())
+}
+
+fn invalid(message: impl Into<String>) -> ExError {
+    ExError::new(ExErrorKind::InvalidInput)
+        .with_op("content_ref_rename")
+        .with_message(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_token_respects_boundaries() {
+        let text = "See payments-api, not payments-api-2 or old-payments-api.\n(payments-api)";
+        let offsets = find_token(text, "payments-api");
+        assert_eq!(offsets, vec![4, 59]);
+        assert_eq!(line_at(text, 59), "(payments-api)");
+    }
+}
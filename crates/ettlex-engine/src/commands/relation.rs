@@ -138,7 +138,7 @@ pub fn handle_relation_create(
 ///
 /// A cycle exists if target can already reach source via the same relation type.
 /// Uses BFS from target, following outgoing relations of the given type.
-fn would_create_cycle(
+pub(crate) fn would_create_cycle(
     conn: &Connection,
     source_ettle_id: &str,
     target_ettle_id: &str,
@@ -172,7 +172,7 @@ fn would_create_cycle(
 }
 
 /// Parse the cycle_check flag from the registry entry's properties_json.
-fn is_cycle_check_enabled(properties_json: &str) -> bool {
+pub(crate) fn is_cycle_check_enabled(properties_json: &str) -> bool {
     if let Ok(val) = serde_json::from_str::<serde_json::Value>(properties_json) {
         if let Some(b) = val.get("cycle_check").and_then(|v| v.as_bool()) {
             return b;
@@ -256,7 +256,7 @@ pub fn handle_relation_get(conn: &Connection, relation_id: String) -> Result<Com
 /// Invariants enforced:
 /// - At least one of `source_ettle_id`, `target_ettle_id`, or `relation_type`
 ///   must be supplied (`InvalidInput`).
-/// - Results are sorted by (created_at ASC, id ASC).
+/// - Results are sorted by (created_at_ms ASC, id ASC).
 pub fn handle_relation_list(
     conn: &Connection,
     source_ettle_id: Option<String>,
@@ -317,3 +317,79 @@ pub fn handle_relation_tombstone(
 
     Ok(CommandResult::RelationTombstone)
 }
+
+// ---------------------------------------------------------------------------
+// handle_relation_restore
+// ---------------------------------------------------------------------------
+
+/// Restore a tombstoned relation.
+///
+/// Invariants enforced:
+/// - Relation must exist (`NotFound`) and currently be tombstoned (`InvalidInput`).
+/// - `relation_type` must still be registered and active (`InvalidInput`).
+/// - Source and target ettles must be active (`AlreadyTombstoned`).
+/// - If `cycle_check = true` for the type, restoring must not close a cycle
+///   formed since the tombstone (`CycleDetected`).
+pub fn handle_relation_restore(
+    conn: &mut Connection,
+    relation_id: String,
+) -> Result<CommandResult> {
+    let existing = SqliteRepo::get_relation(conn, &relation_id)?.ok_or_else(|| {
+        ExError::new(ExErrorKind::NotFound)
+            .with_op("relation_restore")
+            .with_entity_id(&relation_id)
+            .with_message(format!("Relation not found: {}", relation_id))
+    })?;
+
+    if existing.tombstoned_at.is_none() {
+        return Err(ExError::new(ExErrorKind::InvalidInput)
+            .with_op("relation_restore")
+            .with_entity_id(&relation_id)
+            .with_message(format!("Relation is not tombstoned: {}", relation_id)));
+    }
+
+    let type_entry = SqliteRepo::get_relation_type_entry(conn, &existing.relation_type)?
+        .filter(|e| e.tombstoned_at.is_none())
+        .ok_or_else(|| {
+            ExError::new(ExErrorKind::InvalidInput)
+                .with_op("relation_restore")
+                .with_entity_id(&relation_id)
+                .with_message(format!(
+                    "Relation type is no longer active: {}",
+                    existing.relation_type
+                ))
+        })?;
+
+    for endpoint in [&existing.source_ettle_id, &existing.target_ettle_id] {
+        let ettle = SqliteRepo::get_ettle_record(conn, endpoint)?.ok_or_else(|| {
+            ExError::new(ExErrorKind::NotFound)
+                .with_op("relation_restore")
+                .with_entity_id(endpoint)
+                .with_message(format!("Endpoint ettle not found: {}", endpoint))
+        })?;
+        if ettle.tombstoned_at.is_some() {
+            return Err(ExError::new(ExErrorKind::AlreadyTombstoned)
+                .with_op("relation_restore")
+                .with_entity_id(endpoint)
+                .with_message(format!("Endpoint ettle is tombstoned: {}", endpoint)));
+        }
+    }
+
+    if is_cycle_check_enabled(&type_entry.properties_json)
+        && would_create_cycle(
+            conn,
+            &existing.source_ettle_id,
+            &existing.target_ettle_id,
+            &existing.relation_type,
+        )?
+    {
+        return Err(ExError::new(ExErrorKind::CycleDetected)
+            .with_op("relation_restore")
+            .with_entity_id(&relation_id)
+            .with_message("Restoring this relation would introduce a cycle"));
+    }
+
+    SqliteRepo::restore_relation(conn, &relation_id)?;
+
+    Ok(CommandResult::RelationRestore)
+}
@@ -0,0 +1,234 @@
+//! Engine handlers for the root Ettle registry.
+//!
+//! Roots used to be implicit: any active Ettle without a refinement parent.
+//! The registry makes them explicit, with a display name, an optional owner
+//! and a status. Retired roots accept no new snapshots (enforced by the
+//! store), and when the `roots.mode` setting is `registered`, bulk tools
+//! refuse to create parentless Ettles that are not registered — the usual
+//! symptom of a mistyped parent in an import.
+
+#![allow(clippy::result_large_err)]
+
+use std::collections::BTreeSet;
+
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_store::model::{RootRecord, ROOT_STATUS_ACTIVE, ROOT_STATUS_RETIRED};
+use ettlex_store::repo::SqliteRepo;
+use ettlex_store::snapshot::query::fetch_root_snapshot_summary;
+use rusqlite::Connection;
+
+use super::command::CommandResult;
+use super::read_tools::{RegisteredRoot, RootRegistry};
+use super::settings::roots_registered_mode;
+
+type Result<T> = std::result::Result<T, ExError>;
+
+const ROOT_STATUSES: &[&str] = &[ROOT_STATUS_ACTIVE, ROOT_STATUS_RETIRED];
+
+/// Register an existing Ettle as a root.
+///
+/// Invariants enforced:
+/// - Display name must be non-blank (`InvalidTitle`) and unused
+///   (`AlreadyExists`).
+/// - The Ettle must exist (`NotFound`) and not be tombstoned
+///   (`AlreadyTombstoned`).
+/// - The Ettle must have no active refinement parent (`RootEttleInvalid`).
+/// - The Ettle must not already be registered (`AlreadyExists`).
+pub fn handle_root_register(
+    conn: &mut Connection,
+    ettle_id: String,
+    display_name: String,
+    owner: Option<String>,
+) -> Result<CommandResult> {
+    let op = "root_register";
+    let display_name = validate_display_name(op, &display_name)?;
+    let ettle = SqliteRepo::get_ettle_record(conn, &ettle_id)?.ok_or_else(|| {
+        ExError::new(ExErrorKind::NotFound)
+            .with_op(op)
+            .with_entity_id(&ettle_id)
+            .with_message(format!("Ettle not found: {}", ettle_id))
+    })?;
+    if ettle.tombstoned_at.is_some() {
+        return Err(ExError::new(ExErrorKind::AlreadyTombstoned)
+            .with_op(op)
+            .with_entity_id(&ettle_id)
+            .with_message(format!("Ettle is tombstoned: {}", ettle_id)));
+    }
+    if SqliteRepo::count_active_refinement_parents(conn, &ettle_id)? > 0 {
+        return Err(ExError::new(ExErrorKind::RootEttleInvalid)
+            .with_op(op)
+            .with_entity_id(&ettle_id)
+            .with_message("ettle has an active refinement parent and cannot be a root"));
+    }
+    if SqliteRepo::get_root(conn, &ettle_id)?.is_some() {
+        return Err(ExError::new(ExErrorKind::AlreadyExists)
+            .with_op(op)
+            .with_entity_id(&ettle_id)
+            .with_message(format!("Root is already registered: {}", ettle_id)));
+    }
+    ensure_display_name_free(conn, op, &display_name, None)?;
+
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    SqliteRepo::insert_root(
+        conn,
+        &RootRecord {
+            ettle_id,
+            display_name,
+            owner: owner.filter(|o| !o.trim().is_empty()),
+            status: ROOT_STATUS_ACTIVE.to_string(),
+            registered_at_ms: now_ms,
+            updated_at_ms: now_ms,
+        },
+    )?;
+    Ok(CommandResult::RootRegister)
+}
+
+/// Update a registered root's display name, owner or status.
+///
+/// Invariants enforced:
+/// - At least one field must be supplied (`EmptyUpdate`).
+/// - The root must be registered (`NotFound`).
+/// - Status must be `active` or `retired` (`InvalidInput`).
+/// - A new display name must be non-blank (`InvalidTitle`) and unused
+///   (`AlreadyExists`).
+pub fn handle_root_update(
+    conn: &mut Connection,
+    ettle_id: String,
+    display_name: Option<String>,
+    owner: Option<Option<String>>,
+    status: Option<String>,
+) -> Result<CommandResult> {
+    let op = "root_update";
+    if display_name.is_none() && owner.is_none() && status.is_none() {
+        return Err(ExError::new(ExErrorKind::EmptyUpdate)
+            .with_op(op)
+            .with_entity_id(&ettle_id)
+            .with_message("root_update requires display_name, owner or status"));
+    }
+    let mut record = get_root(conn, op, &ettle_id)?;
+    if let Some(name) = display_name {
+        let name = validate_display_name(op, &name)?;
+        ensure_display_name_free(conn, op, &name, Some(&ettle_id))?;
+        record.display_name = name;
+    }
+    if let Some(owner) = owner {
+        record.owner = owner.filter(|o| !o.trim().is_empty());
+    }
+    if let Some(status) = status {
+        if !ROOT_STATUSES.contains(&status.as_str()) {
+            return Err(ExError::new(ExErrorKind::InvalidInput)
+                .with_op(op)
+                .with_entity_id(&ettle_id)
+                .with_message(format!("unknown root status: {}", status))
+                .with_candidates(ROOT_STATUSES.iter().map(|s| s.to_string()).collect()));
+        }
+        record.status = status;
+    }
+    record.updated_at_ms = chrono::Utc::now().timestamp_millis();
+    SqliteRepo::update_root(conn, &record)?;
+    Ok(CommandResult::RootUpdate)
+}
+
+/// Registered roots with their heads, plus the implicit roots that are not
+/// registered.
+///
+/// Registered roots are ordered by display name; `unregistered` is sorted by
+/// ID and always excludes registered roots, retired or not.
+pub(crate) fn root_registry(conn: &Connection, include_retired: bool) -> Result<RootRegistry> {
+    let all = SqliteRepo::list_roots(conn, true)?;
+    let known: BTreeSet<&str> = all.iter().map(|r| r.ettle_id.as_str()).collect();
+    let unregistered = SqliteRepo::list_implicit_roots(conn)?
+        .into_iter()
+        .filter(|id| !known.contains(id.as_str()))
+        .collect();
+    let mut registered = Vec::new();
+    for root in all
+        .iter()
+        .filter(|r| include_retired || r.status == ROOT_STATUS_ACTIVE)
+    {
+        let (snapshot_count, head_snapshot_id) = fetch_root_snapshot_summary(conn, &root.ettle_id)?;
+        registered.push(RegisteredRoot {
+            root: root.clone(),
+            head_snapshot_id,
+            snapshot_count,
+        });
+    }
+    Ok(RootRegistry {
+        registered,
+        unregistered,
+    })
+}
+
+/// A single registry entry with its head snapshot.
+pub(crate) fn registered_root(conn: &Connection, ettle_id: &str) -> Result<RegisteredRoot> {
+    let root = get_root(conn, "root_get", ettle_id)?;
+    let (snapshot_count, head_snapshot_id) = fetch_root_snapshot_summary(conn, ettle_id)?;
+    Ok(RegisteredRoot {
+        root,
+        head_snapshot_id,
+        snapshot_count,
+    })
+}
+
+/// IDs of the active registered roots.
+pub(crate) fn active_root_ids(conn: &Connection) -> Result<BTreeSet<String>> {
+    Ok(SqliteRepo::list_roots(conn, false)?
+        .into_iter()
+        .map(|r| r.ettle_id)
+        .collect())
+}
+
+/// Refuse to create a new unregistered root when `roots.mode` is
+/// `registered`.
+///
+/// Bulk tools call this while planning, before they write, for every Ettle
+/// they would create without a parent. `what` describes the offending item
+/// (e.g. `"line 4"`) and is prefixed to the `RootEttleInvalid` message.
+pub fn ensure_new_root_allowed(conn: &Connection, op: &str, what: &str) -> Result<()> {
+    if !roots_registered_mode(conn)? {
+        return Ok(());
+    }
+    Err(ExError::new(ExErrorKind::RootEttleInvalid)
+        .with_op(op)
+        .with_message(format!(
+            "{}: would create an unregistered root (roots.mode is 'registered'); \
+             give it a parent or register it",
+            what
+        )))
+}
+
+fn get_root(conn: &Connection, op: &str, ettle_id: &str) -> Result<RootRecord> {
+    SqliteRepo::get_root(conn, ettle_id)?.ok_or_else(|| {
+        ExError::new(ExErrorKind::NotFound)
+            .with_op(op)
+            .with_entity_id(ettle_id)
+            .with_message(format!("Root is not registered: {}", ettle_id))
+    })
+}
+
+fn validate_display_name(op: &str, display_name: &str) -> Result<String> {
+    let name = display_name.trim();
+    if name.is_empty() {
+        return Err(ExError::new(ExErrorKind::InvalidTitle)
+            .with_op(op)
+            .with_message("display_name must not be empty or whitespace-only"));
+    }
+    Ok(name.to_string())
+}
+
+fn ensure_display_name_free(
+    conn: &Connection,
+    op: &str,
+    display_name: &str,
+    own_ettle_id: Option<&str>,
+) -> Result<()> {
+    match SqliteRepo::get_root_by_display_name(conn, display_name)? {
+        Some(other) if Some(other.ettle_id.as_str()) != own_ettle_id => {
+            Err(ExError::new(ExErrorKind::AlreadyExists)
+                .with_op(op)
+                .with_entity_id(&other.ettle_id)
+                .with_message(format!("display name already in use: {}", display_name)))
+        }
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,335 @@
+//! Typed repository settings.
+//!
+//! Backs the `SettingsGet` engine query and the `SettingsSet` engine
+//! command. Only keys declared in [`KNOWN_SETTINGS`] can be stored, and each
+//! value is checked against its key's type before it is written. Feature
+//! flags share the `settings` table but are managed by
+//! [`crate::feature_flags`].
+//!
+//! Precedence, highest first: an explicit value from the caller (a CLI flag
+//! or config file), the stored setting, then the built-in default.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_store::errors::Result;
+use ettlex_store::settings::{clear_setting, get_setting, set_setting};
+use rusqlite::Connection;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::validate::validate_ref;
+
+/// Days a rejected or expired approval request keeps its CAS payload.
+pub const SETTING_APPROVAL_RETENTION_DAYS: &str = "approval.retention_days";
+/// Largest why/what/how body, in bytes, stored in the `ettles` row; larger
+/// bodies spill to CAS.
+pub const SETTING_CONTENT_MAX_INLINE_BYTES: &str = "content.max_inline_bytes";
+/// Policy ref used when a snapshot commit names none.
+pub const SETTING_DEFAULT_POLICY_REF: &str = "policy.default_ref";
+/// `append` (every commit adds a row) or `semantic` (reuse a snapshot with
+/// the same semantic digest).
+pub const SETTING_DEDUP_MODE: &str = "snapshot.dedup_mode";
+/// Days a tombstoned decision keeps its evidence capture.
+pub const SETTING_EVIDENCE_RETENTION_DAYS: &str = "evidence.retention_days";
+/// Log level for hosts that initialise logging from the repository.
+pub const SETTING_LOG_LEVEL: &str = "log.level";
+/// `implicit` (any parentless Ettle is a root) or `registered` (new roots
+/// must be in the root registry).
+pub const SETTING_ROOTS_MODE: &str = "roots.mode";
+
+const DEDUP_MODES: &[&str] = &["append", "semantic"];
+const LOG_LEVELS: &[&str] = &["error", "warn", "info", "debug", "trace"];
+const ROOTS_MODES: &[&str] = &["implicit", "registered"];
+
+/// Value type of a known setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SettingType {
+    /// A `<name>@<version>` policy ref.
+    PolicyRef,
+    /// One of a fixed set of strings.
+    Enum(&'static [&'static str]),
+    /// An integer of at least 1.
+    PositiveInteger,
+}
+
+/// Declaration of a known setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SettingSpec {
+    pub key: &'static str,
+    pub description: &'static str,
+    pub value_type: SettingType,
+    /// Value used when the setting is not stored; `None` means unset.
+    pub default: Option<&'static str>,
+}
+
+/// Every setting that may be stored, sorted by key.
+pub const KNOWN_SETTINGS: &[SettingSpec] = &[
+    SettingSpec {
+        key: SETTING_APPROVAL_RETENTION_DAYS,
+        description: "Days a rejected or expired approval request keeps its CAS payload",
+        value_type: SettingType::PositiveInteger,
+        default: None,
+    },
+    SettingSpec {
+        key: SETTING_CONTENT_MAX_INLINE_BYTES,
+        description: "Largest why/what/how body in bytes kept in the ettles row; \
+                      larger bodies are stored in CAS (unset: no limit)",
+        value_type: SettingType::PositiveInteger,
+        default: None,
+    },
+    SettingSpec {
+        key: SETTING_EVIDENCE_RETENTION_DAYS,
+        description: "Days a tombstoned decision keeps its evidence capture \
+                      (overrides the default profile's evidence.retention_days)",
+        value_type: SettingType::PositiveInteger,
+        default: None,
+    },
+    SettingSpec {
+        key: SETTING_LOG_LEVEL,
+        description: "Log level: error, warn, info, debug or trace",
+        value_type: SettingType::Enum(LOG_LEVELS),
+        default: Some("info"),
+    },
+    SettingSpec {
+        key: SETTING_DEFAULT_POLICY_REF,
+        description: "Policy ref used when a snapshot commit names none",
+        value_type: SettingType::PolicyRef,
+        default: None,
+    },
+    SettingSpec {
+        key: SETTING_ROOTS_MODE,
+        description: "Roots: implicit (any parentless ettle) or registered \
+                      (imports and patches may not create unregistered roots)",
+        value_type: SettingType::Enum(ROOTS_MODES),
+        default: Some("implicit"),
+    },
+    SettingSpec {
+        key: SETTING_DEDUP_MODE,
+        description: "Snapshot commits: append (always a new row) or semantic \
+                      (reuse a snapshot with the same semantic digest)",
+        value_type: SettingType::Enum(DEDUP_MODES),
+        default: Some("append"),
+    },
+];
+
+/// Where an effective setting value came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SettingSource {
+    Default,
+    Stored,
+    Explicit,
+}
+
+/// Effective value of one setting, as returned by `SettingsGet`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SettingEntry {
+    pub key: String,
+    pub description: String,
+    /// `None` when the setting has no stored value and no default.
+    pub value: Option<Value>,
+    pub source: SettingSource,
+}
+
+/// Effective values of `key`, or of every known setting when `key` is `None`.
+///
+/// # Errors
+/// * `InvalidInput` - unknown key
+/// * `Persistence` / `Serialization` - the stored value cannot be read
+pub fn settings_get(conn: &Connection, key: Option<&str>) -> Result<Vec<SettingEntry>> {
+    match key {
+        Some(key) => Ok(vec![setting_entry(conn, spec(key)?)?]),
+        None => KNOWN_SETTINGS
+            .iter()
+            .map(|spec| setting_entry(conn, spec))
+            .collect(),
+    }
+}
+
+/// Store `value` for `key` after checking its type, or clear the stored
+/// value with `None`.
+///
+/// # Errors
+/// * `InvalidInput` - unknown key, or a value of the wrong type
+/// * `Persistence` - the write fails
+pub fn settings_set(conn: &Connection, key: &str, value: Option<Value>) -> Result<()> {
+    match validate_setting(key, value)? {
+        Some(value) => set_setting(conn, key, &value),
+        None => clear_setting(conn, key).map(|_| ()),
+    }
+}
+
+/// Check `value` against `key`'s declared type, normalising it (e.g. an
+/// integer given as a string).
+///
+/// # Errors
+/// * `InvalidInput` - unknown key, or a value of the wrong type
+pub fn validate_setting(key: &str, value: Option<Value>) -> Result<Option<Value>> {
+    let spec = spec(key)?;
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    let invalid = |expected: String| {
+        ExError::new(ExErrorKind::InvalidInput)
+            .with_op("settings_set")
+            .with_entity_id(key)
+            .with_message(format!("setting '{}' must be {}", key, expected))
+    };
+    let normalised = match spec.value_type {
+        SettingType::PolicyRef => {
+            let s = value
+                .as_str()
+                .ok_or_else(|| invalid("a <name>@<version> policy ref".to_string()))?;
+            Value::from(validate_ref("settings_set", key, s)?)
+        }
+        SettingType::Enum(allowed) => {
+            let s = value
+                .as_str()
+                .filter(|s| allowed.contains(s))
+                .ok_or_else(|| invalid(format!("one of {}", allowed.join(", "))))?;
+            Value::from(s)
+        }
+        SettingType::PositiveInteger => {
+            let n = match &value {
+                Value::Number(n) => n.as_u64(),
+                Value::String(s) => s.trim().parse().ok(),
+                _ => None,
+            }
+            .filter(|n| *n >= 1 && *n <= u64::from(u32::MAX))
+            .ok_or_else(|| invalid("a positive integer".to_string()))?;
+            Value::from(n)
+        }
+    };
+    Ok(Some(normalised))
+}
+
+/// Resolve `key` with an explicit value taking precedence over the stored
+/// one and the default.
+///
+/// # Errors
+/// Same as [`validate_setting`] and [`settings_get`].
+pub fn resolve_setting(
+    conn: &Connection,
+    key: &str,
+    explicit: Option<Value>,
+) -> Result<SettingEntry> {
+    let spec = spec(key)?;
+    if let Some(value) = validate_setting(key, explicit)? {
+        return Ok(SettingEntry {
+            key: spec.key.to_string(),
+            description: spec.description.to_string(),
+            value: Some(value),
+            source: SettingSource::Explicit,
+        });
+    }
+    setting_entry(conn, spec)
+}
+
+/// Default policy ref from the settings, if one is stored.
+///
+/// # Errors
+/// Same as [`settings_get`].
+pub fn default_policy_ref(conn: &Connection) -> Result<Option<String>> {
+    Ok(resolve_setting(conn, SETTING_DEFAULT_POLICY_REF, None)?
+        .value
+        .and_then(|v| v.as_str().map(str::to_string)))
+}
+
+/// Whether snapshot commits should reuse semantically identical snapshots;
+/// `explicit` (e.g. a CLI flag) wins over the stored dedup mode.
+///
+/// # Errors
+/// Same as [`settings_get`].
+pub fn dedup_enabled(conn: &Connection, explicit: Option<bool>) -> Result<bool> {
+    if let Some(explicit) = explicit {
+        return Ok(explicit);
+    }
+    let mode = resolve_setting(conn, SETTING_DEDUP_MODE, None)?.value;
+    Ok(mode.as_ref().and_then(Value::as_str) == Some("semantic"))
+}
+
+/// Whether the root registry is authoritative (`roots.mode` is
+/// `registered`).
+///
+/// # Errors
+/// Same as [`settings_get`].
+pub fn roots_registered_mode(conn: &Connection) -> Result<bool> {
+    let mode = resolve_setting(conn, SETTING_ROOTS_MODE, None)?.value;
+    Ok(mode.as_ref().and_then(Value::as_str) == Some("registered"))
+}
+
+/// Evidence retention window from the settings, if one is stored.
+///
+/// # Errors
+/// Same as [`settings_get`].
+pub fn evidence_retention_days(conn: &Connection) -> Result<Option<u32>> {
+    days_setting(conn, SETTING_EVIDENCE_RETENTION_DAYS)
+}
+
+/// Approval payload retention window from the settings, if one is stored.
+///
+/// # Errors
+/// Same as [`settings_get`].
+pub fn approval_retention_days(conn: &Connection) -> Result<Option<u32>> {
+    days_setting(conn, SETTING_APPROVAL_RETENTION_DAYS)
+}
+
+/// Inline content limit from the settings, if one is stored.
+///
+/// # Errors
+/// Same as [`settings_get`].
+pub fn content_max_inline_bytes(conn: &Connection) -> Result<Option<u64>> {
+    Ok(
+        resolve_setting(conn, SETTING_CONTENT_MAX_INLINE_BYTES, None)?
+            .value
+            .and_then(|v| v.as_u64()),
+    )
+}
+
+/// Effective log level name (`info` unless stored otherwise).
+///
+/// # Errors
+/// Same as [`settings_get`].
+pub fn log_level(conn: &Connection) -> Result<String> {
+    Ok(resolve_setting(conn, SETTING_LOG_LEVEL, None)?
+        .value
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_else(|| "info".to_string()))
+}
+
+fn days_setting(conn: &Connection, key: &str) -> Result<Option<u32>> {
+    Ok(resolve_setting(conn, key, None)?
+        .value
+        .and_then(|v| v.as_u64())
+        .and_then(|n| u32::try_from(n).ok()))
+}
+
+fn setting_entry(conn: &Connection, spec: &SettingSpec) -> Result<SettingEntry> {
+    let stored = get_setting(conn, spec.key)?;
+    let (value, source) = match stored {
+        Some(value) => (Some(value), SettingSource::Stored),
+        None => (spec.default.map(Value::from), SettingSource::Default),
+    };
+    Ok(SettingEntry {
+        key: spec.key.to_string(),
+        description: spec.description.to_string(),
+        value,
+        source,
+    })
+}
+
+fn spec(key: &str) -> Result<&'static SettingSpec> {
+    KNOWN_SETTINGS.iter().find(|s| s.key == key).ok_or_else(|| {
+        let known: Vec<&str> = KNOWN_SETTINGS.iter().map(|s| s.key).collect();
+        ExError::new(ExErrorKind::InvalidInput)
+            .with_op("settings")
+            .with_entity_id(key)
+            .with_message(format!(
+                "unknown setting '{}' (known: {})",
+                key,
+                known.join(", ")
+            ))
+    })
+}
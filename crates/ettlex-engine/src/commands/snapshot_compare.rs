@@ -0,0 +1,115 @@
+//! Pairwise semantic comparison of snapshots.
+//!
+//! Backs the `SnapshotCompareMatrix` engine query. Two snapshots are
+//! semantically equal when their manifests carry the same
+//! `semantic_manifest_digest`, i.e. they differ at most in `created_at`.
+//! Ledgers committed with dedup disabled accumulate such duplicates; the
+//! clusters show which snapshots could be collapsed into one.
+
+#![allow(clippy::result_large_err)]
+
+use std::collections::BTreeMap;
+
+use ettlex_core::diff::ParsedManifest;
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_store::cas::FsStore;
+use ettlex_store::errors::Result;
+use ettlex_store::snapshot::id_scheme::resolve_snapshot_id;
+use rusqlite::Connection;
+use serde::Serialize;
+
+use crate::commands::engine_query::{resolve_ref, SnapshotRef};
+
+/// Most snapshot refs one comparison accepts; the matrix grows as N².
+pub const MAX_COMPARE_REFS: usize = 256;
+
+/// One compared snapshot, in input order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SnapshotCompareEntry {
+    /// Full snapshot ID for `SnapshotId` refs; `None` for manifest digests.
+    pub snapshot_id: Option<String>,
+    pub manifest_digest: String,
+    pub semantic_manifest_digest: String,
+}
+
+/// Result of a `SnapshotCompareMatrix` query.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SnapshotCompareMatrix {
+    pub entries: Vec<SnapshotCompareEntry>,
+    /// `equal[i][j]` is true when entries `i` and `j` are semantically equal.
+    pub equal: Vec<Vec<bool>>,
+    /// Groups of semantically equal entries, as ascending indices into
+    /// `entries`, ordered by their first member. Every entry is in exactly
+    /// one cluster; singletons are included.
+    pub clusters: Vec<Vec<usize>>,
+}
+
+impl SnapshotCompareMatrix {
+    /// Clusters with more than one member.
+    pub fn duplicate_clusters(&self) -> impl Iterator<Item = &Vec<usize>> {
+        self.clusters.iter().filter(|c| c.len() > 1)
+    }
+}
+
+/// Resolve every ref and build the comparison matrix.
+///
+/// # Errors
+/// * `InvalidInput` - fewer than two refs, or more than [`MAX_COMPARE_REFS`]
+/// * `NotFound` / `AmbiguousSelection` - a snapshot ref does not resolve
+/// * `InvalidManifest` - a manifest cannot be parsed
+pub(crate) fn compare_snapshots(
+    conn: &Connection,
+    cas: &FsStore,
+    refs: &[SnapshotRef],
+) -> Result<SnapshotCompareMatrix> {
+    if refs.len() < 2 || refs.len() > MAX_COMPARE_REFS {
+        return Err(ExError::new(ExErrorKind::InvalidInput)
+            .with_op("snapshot_compare_matrix")
+            .with_message(format!(
+                "expected between 2 and {} snapshot refs, got {}",
+                MAX_COMPARE_REFS,
+                refs.len()
+            )));
+    }
+
+    let mut entries = Vec::with_capacity(refs.len());
+    for snapshot_ref in refs {
+        let snapshot_id = match snapshot_ref {
+            SnapshotRef::SnapshotId(id) => Some(resolve_snapshot_id(conn, id)?),
+            SnapshotRef::ManifestDigest(_) => None,
+        };
+        let parsed = ParsedManifest::parse(&resolve_ref(snapshot_ref, conn, cas)?)?;
+        entries.push(SnapshotCompareEntry {
+            snapshot_id,
+            manifest_digest: parsed.manifest.manifest_digest,
+            semantic_manifest_digest: parsed.manifest.semantic_manifest_digest,
+        });
+    }
+
+    let equal = entries
+        .iter()
+        .map(|a| {
+            entries
+                .iter()
+                .map(|b| a.semantic_manifest_digest == b.semantic_manifest_digest)
+                .collect()
+        })
+        .collect();
+
+    // Cluster by digest, then order clusters by their first (lowest) index
+    let mut by_digest: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (i, entry) in entries.iter().enumerate() {
+        by_digest
+            .entry(entry.semantic_manifest_digest.as_str())
+            .or_default()
+            .push(i);
+    }
+    let mut clusters: Vec<Vec<usize>> = by_digest.into_values().collect();
+    clusters.sort_by_key(|c| c[0]);
+
+    Ok(SnapshotCompareMatrix {
+        entries,
+        equal,
+        clusters,
+    })
+}
@@ -0,0 +1,119 @@
+//! Store fingerprint — one digest over all active semantic content.
+//!
+//! Active (non-tombstoned) ettles and relations are serialised as canonical
+//! JSON lines in ID order and hashed per table; the overall digest hashes
+//! the per-table digests under a version tag. Constraints and attachments
+//! are ettles and `constraint` relations, so they are covered by the same
+//! two tables.
+//!
+//! Timestamps are excluded: two stores holding the same content under the
+//! same IDs fingerprint identically however and whenever they were built,
+//! while any change to a stored field — including one made out-of-band with
+//! raw SQL — changes the digest.
+//!
+//! why/what/how are hashed as the SHA-256 of their content, so a body
+//! fingerprints the same whether it is stored inline or spilled to CAS.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_store::errors::Result;
+use rusqlite::Connection;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::commands::read_tools::StoreFingerprint;
+
+/// Version tag mixed into the overall digest; bump if the encoding changes.
+const FINGERPRINT_VERSION: &str = "ettlex-store-fingerprint/v2";
+
+/// Compute the fingerprint of the store behind `conn`.
+pub fn compute_store_fingerprint(conn: &Connection) -> Result<StoreFingerprint> {
+    let (ettles_digest, ettle_count) = digest_rows(
+        conn,
+        "SELECT id, title, why, why_digest, what, what_digest, how, how_digest,
+                content_format, reasoning_link_id, reasoning_link_type, slug,
+                archived_at IS NOT NULL
+         FROM ettles WHERE tombstoned_at IS NULL ORDER BY id",
+        |row| {
+            Ok(json!({
+                "id": row.get::<_, String>(0)?,
+                "title": row.get::<_, String>(1)?,
+                "why": content_digest(row.get(2)?, row.get(3)?),
+                "what": content_digest(row.get(4)?, row.get(5)?),
+                "how": content_digest(row.get(6)?, row.get(7)?),
+                "content_format": row.get::<_, String>(8)?,
+                "reasoning_link_id": row.get::<_, Option<String>>(9)?,
+                "reasoning_link_type": row.get::<_, Option<String>>(10)?,
+                "slug": row.get::<_, Option<String>>(11)?,
+                "archived": row.get::<_, bool>(12)?,
+            }))
+        },
+    )?;
+
+    let (relations_digest, relation_count) = digest_rows(
+        conn,
+        "SELECT id, relation_type, source_ettle_id, target_ettle_id, properties_json
+         FROM relations WHERE tombstoned_at IS NULL ORDER BY id",
+        |row| {
+            let raw: String = row.get(4)?;
+            // Canonicalise key order; unparseable text is hashed verbatim.
+            let properties = serde_json::from_str::<Value>(&raw).unwrap_or(Value::String(raw));
+            Ok(json!({
+                "id": row.get::<_, String>(0)?,
+                "relation_type": row.get::<_, String>(1)?,
+                "source_ettle_id": row.get::<_, String>(2)?,
+                "target_ettle_id": row.get::<_, String>(3)?,
+                "properties": properties,
+            }))
+        },
+    )?;
+
+    let mut h = Sha256::new();
+    h.update(
+        format!(
+            "{}\nettles:{}\nrelations:{}\n",
+            FINGERPRINT_VERSION, ettles_digest, relations_digest
+        )
+        .as_bytes(),
+    );
+
+    Ok(StoreFingerprint {
+        digest: format!("{:x}", h.finalize()),
+        ettles_digest,
+        relations_digest,
+        ettle_count,
+        relation_count,
+    })
+}
+
+/// SHA-256 of a content field: the CAS digest when spilled, otherwise the
+/// hash of the inline text (the CAS digest is the hash of the same bytes).
+fn content_digest(inline: String, spilled: Option<String>) -> String {
+    spilled.unwrap_or_else(|| format!("{:x}", Sha256::digest(inline.as_bytes())))
+}
+
+/// Hash each row of `sql` as one canonical JSON line; returns the hex digest
+/// and the row count.
+fn digest_rows(
+    conn: &Connection,
+    sql: &str,
+    to_json: impl Fn(&rusqlite::Row<'_>) -> rusqlite::Result<Value>,
+) -> Result<(String, u64)> {
+    let persistence = |e: rusqlite::Error| {
+        ExError::new(ExErrorKind::Persistence)
+            .with_op("store_fingerprint")
+            .with_message(e.to_string())
+    };
+
+    let mut stmt = conn.prepare(sql).map_err(persistence)?;
+    let mut rows = stmt.query([]).map_err(persistence)?;
+    let mut h = Sha256::new();
+    let mut count = 0u64;
+    while let Some(row) = rows.next().map_err(persistence)? {
+        h.update(to_json(row).map_err(persistence)?.to_string().as_bytes());
+        h.update(b"\n");
+        count += 1;
+    }
+    Ok((format!("{:x}", h.finalize()), count))
+}
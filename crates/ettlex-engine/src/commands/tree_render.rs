@@ -0,0 +1,133 @@
+//! Render-tree assembly for hierarchical Markdown rendering.
+//!
+//! Walks active refinement relations down from an Ettle into a
+//! [`RenderTreeNode`] tree and hands it to the pure renderer in
+//! `ettlex_core::render`. Read-only.
+
+#![allow(clippy::result_large_err)]
+
+use std::collections::BTreeSet;
+
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::render::{render_tree_markdown, RenderTreeNode, TreeRenderOptions};
+use ettlex_store::cas::FsStore;
+use ettlex_store::errors::Result;
+use ettlex_store::model::{EttleRecord, RelationListOpts};
+use ettlex_store::repo::SqliteRepo;
+use rusqlite::Connection;
+
+use crate::commands::content_spill::hydrate_ettle_content;
+use crate::commands::ettle::resolve_ettle_ref;
+
+/// Build the render tree rooted at `ettle_ref` (an ID or slug).
+///
+/// Children are the targets of active `refinement` relations, ordered by the
+/// relation's `ordinal` property (unordered relations last) and then by Ettle
+/// ID. Tombstoned and archived children are left out, and so is any Ettle
+/// already on the path from the root, so a refinement cycle cannot recurse.
+/// Levels deeper than `max_depth` below the root are not loaded. Content
+/// spilled to CAS is read back from `cas`.
+///
+/// # Errors
+/// * `NotFound` - `ettle_ref` matches no Ettle, or only a tombstoned one,
+///   or a spilled content blob is missing
+/// * `InvalidInput` - A stored `content_format` is not a known format
+pub fn build_render_tree(
+    conn: &Connection,
+    cas: &FsStore,
+    ettle_ref: &str,
+    max_depth: Option<usize>,
+) -> Result<RenderTreeNode> {
+    let ettle_id = resolve_ettle_ref(conn, ettle_ref)?;
+    let record = SqliteRepo::get_ettle_record(conn, &ettle_id)?
+        .filter(|r| r.tombstoned_at.is_none())
+        .ok_or_else(|| {
+            ExError::new(ExErrorKind::NotFound)
+                .with_op("render_tree")
+                .with_entity_id(ettle_ref)
+                .with_message(format!("Ettle not found: {}", ettle_ref))
+        })?;
+    let mut path = BTreeSet::new();
+    build_node(conn, cas, record, 0, max_depth, &mut path)
+}
+
+/// Build the render tree for `ettle_ref` and render it to Markdown.
+///
+/// # Errors
+/// Same as [`build_render_tree`].
+pub fn render_ettle_tree(
+    conn: &Connection,
+    cas: &FsStore,
+    ettle_ref: &str,
+    options: &TreeRenderOptions,
+) -> Result<String> {
+    let root = build_render_tree(conn, cas, ettle_ref, options.max_depth)?;
+    Ok(render_tree_markdown(&root, options))
+}
+
+fn build_node(
+    conn: &Connection,
+    cas: &FsStore,
+    mut record: EttleRecord,
+    depth: usize,
+    max_depth: Option<usize>,
+    path: &mut BTreeSet<String>,
+) -> Result<RenderTreeNode> {
+    path.insert(record.id.clone());
+    let mut children = Vec::new();
+    if max_depth.map_or(true, |max| depth < max) {
+        for child_id in ordered_children(conn, &record.id)? {
+            if path.contains(&child_id) {
+                continue;
+            }
+            let Some(child) = SqliteRepo::get_ettle_record(conn, &child_id)? else {
+                continue;
+            };
+            if child.tombstoned_at.is_some() || child.archived_at.is_some() {
+                continue;
+            }
+            children.push(build_node(conn, cas, child, depth + 1, max_depth, path)?);
+        }
+    }
+    path.remove(&record.id);
+    hydrate_ettle_content(&mut record, cas)?;
+    Ok(RenderTreeNode {
+        ettle_id: record.id,
+        title: record.title,
+        why: record.why,
+        what: record.what,
+        how: record.how,
+        content_format: record.content_format.parse()?,
+        children,
+    })
+}
+
+/// Targets of `parent`'s active refinements, by (ordinal, Ettle ID).
+fn ordered_children(conn: &Connection, parent: &str) -> Result<Vec<String>> {
+    let relations = SqliteRepo::list_relations(
+        conn,
+        &RelationListOpts {
+            source_ettle_id: Some(parent.to_string()),
+            target_ettle_id: None,
+            relation_type: Some("refinement".to_string()),
+            include_tombstoned: false,
+        },
+    )?;
+    let mut keyed: Vec<(Option<u64>, String)> = relations
+        .into_iter()
+        .map(|r| {
+            let ordinal = serde_json::from_str::<serde_json::Value>(&r.properties_json)
+                .ok()
+                .and_then(|p| p.get("ordinal").and_then(serde_json::Value::as_u64));
+            (ordinal, r.target_ettle_id)
+        })
+        .collect();
+    // `None` sorts first; put unordered children after ordered ones.
+    keyed.sort_by(|a, b| (a.0.is_none(), a.0, &a.1).cmp(&(b.0.is_none(), b.0, &b.1)));
+    let mut seen = BTreeSet::new();
+    Ok(keyed
+        .into_iter()
+        .map(|(_, id)| id)
+        .filter(|id| seen.insert(id.clone()))
+        .collect())
+}
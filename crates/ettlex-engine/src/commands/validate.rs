@@ -0,0 +1,247 @@
+//! Payload validation for engine commands.
+//!
+//! `apply_engine_command` runs [`validate_engine_command`] before any handler
+//! touches the database or CAS. Validation normalizes (trims) identifiers and
+//! refs and rejects malformed payloads with `InvalidInput`; every message is
+//! prefixed with the offending field name (`"profile_ref: ..."`).
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_store::profile::PROFILE_EXTENDS_KEY;
+
+use super::engine_command::EngineCommand;
+use super::settings::validate_setting;
+
+type Result<T> = std::result::Result<T, ExError>;
+
+/// Maximum length of an identifier or ref, in bytes.
+pub const MAX_ID_LEN: usize = 256;
+
+/// Validate and normalize an engine command payload.
+///
+/// Rules:
+/// - IDs: non-empty after trimming, at most [`MAX_ID_LEN`] bytes, ASCII
+///   alphanumerics plus `:`, `_`, `-`, `.`, `/`.
+/// - Policy/profile refs: `<name>@<version>`, where `name` follows the ID
+///   rules and `version` is non-empty ASCII alphanumerics plus `.`, `_`, `-`.
+/// - `SnapshotCommit`: `allow_dedup` cannot be combined with `dry_run`; the
+///   commit message (trimmed) and annotation keys must be non-blank.
+/// - `SnapshotCommitEnqueue`: as `SnapshotCommit`, and `dry_run` is
+///   rejected. `SnapshotCommitQueueDrain`: `limit`, if set, is at least 1.
+/// - `ProfileCreate`: `payload_json` must be a JSON object; its `extends`,
+///   if present, must be a ref or an array of refs.
+/// - `SettingsSet`: the key must be known and the value must match its type.
+pub fn validate_engine_command(cmd: EngineCommand) -> Result<EngineCommand> {
+    match cmd {
+        EngineCommand::SnapshotCommit {
+            leaf_ep_id,
+            policy_ref,
+            profile_ref,
+            mut options,
+        } => {
+            let op = "snapshot_commit";
+            let leaf_ep_id = validate_id(op, "leaf_ep_id", &leaf_ep_id)?;
+            let policy_ref = policy_ref
+                .map(|r| validate_ref(op, "policy_ref", &r))
+                .transpose()?;
+            let profile_ref = profile_ref
+                .map(|r| validate_ref(op, "profile_ref", &r))
+                .transpose()?;
+            options.expected_head = options
+                .expected_head
+                .map(|h| validate_id(op, "expected_head", &h))
+                .transpose()?;
+            if options.dry_run && options.allow_dedup {
+                return Err(invalid(
+                    op,
+                    "allow_dedup",
+                    "cannot be combined with dry_run",
+                ));
+            }
+            if let Some(message) = options.message.take() {
+                let message = message.trim();
+                if message.is_empty() {
+                    return Err(invalid(op, "message", "must not be blank"));
+                }
+                options.message = Some(message.to_string());
+            }
+            if options.annotations.keys().any(|k| k.trim().is_empty()) {
+                return Err(invalid(op, "annotations", "keys must not be blank"));
+            }
+            Ok(EngineCommand::SnapshotCommit {
+                leaf_ep_id,
+                policy_ref,
+                profile_ref,
+                options,
+            })
+        }
+        EngineCommand::SnapshotCommitEnqueue {
+            leaf_ep_id,
+            policy_ref,
+            profile_ref,
+            options,
+        } => {
+            if options.dry_run {
+                return Err(invalid(
+                    "snapshot_commit_enqueue",
+                    "dry_run",
+                    "a dry run cannot be queued",
+                ));
+            }
+            match validate_engine_command(EngineCommand::SnapshotCommit {
+                leaf_ep_id,
+                policy_ref,
+                profile_ref,
+                options,
+            })? {
+                EngineCommand::SnapshotCommit {
+                    leaf_ep_id,
+                    policy_ref,
+                    profile_ref,
+                    options,
+                } => Ok(EngineCommand::SnapshotCommitEnqueue {
+                    leaf_ep_id,
+                    policy_ref,
+                    profile_ref,
+                    options,
+                }),
+                other => Ok(other),
+            }
+        }
+        EngineCommand::SnapshotCommitQueueDrain { limit } => {
+            if limit == Some(0) {
+                return Err(invalid(
+                    "snapshot_commit_queue_drain",
+                    "limit",
+                    "must be at least 1",
+                ));
+            }
+            Ok(EngineCommand::SnapshotCommitQueueDrain { limit })
+        }
+        EngineCommand::ProfileCreate {
+            profile_ref,
+            payload_json,
+            source,
+        } => {
+            let op = "profile_create";
+            let profile_ref = validate_ref(op, "profile_ref", &profile_ref)?;
+            if !payload_json.is_object() {
+                return Err(invalid(op, "payload_json", "must be a JSON object"));
+            }
+            match payload_json.get(PROFILE_EXTENDS_KEY) {
+                None | Some(serde_json::Value::Null) => {}
+                Some(serde_json::Value::String(base)) => {
+                    validate_ref(op, "payload_json.extends", base)?;
+                }
+                Some(serde_json::Value::Array(bases)) => {
+                    for base in bases {
+                        let base = base.as_str().ok_or_else(|| {
+                            invalid(op, "payload_json.extends", "entries must be strings")
+                        })?;
+                        validate_ref(op, "payload_json.extends", base)?;
+                    }
+                }
+                Some(_) => {
+                    return Err(invalid(
+                        op,
+                        "payload_json.extends",
+                        "must be a ref or an array of refs",
+                    ))
+                }
+            }
+            Ok(EngineCommand::ProfileCreate {
+                profile_ref,
+                payload_json,
+                source,
+            })
+        }
+        EngineCommand::ProfileSetDefault {
+            profile_ref,
+            environment,
+        } => {
+            let op = "profile_set_default";
+            Ok(EngineCommand::ProfileSetDefault {
+                profile_ref: validate_ref(op, "profile_ref", &profile_ref)?,
+                environment: environment
+                    .map(|env| validate_id(op, "environment", &env))
+                    .transpose()?,
+            })
+        }
+        EngineCommand::SettingsSet { key, value } => Ok(EngineCommand::SettingsSet {
+            value: validate_setting(&key, value)?,
+            key,
+        }),
+        cmd @ (EngineCommand::Undo | EngineCommand::Redo) => Ok(cmd),
+    }
+}
+
+/// Trim and check an identifier. Returns the normalized value.
+pub fn validate_id(op: &str, field: &str, value: &str) -> Result<String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(invalid(op, field, "must not be empty"));
+    }
+    if value.len() > MAX_ID_LEN {
+        return Err(invalid(
+            op,
+            field,
+            &format!("must be at most {} bytes", MAX_ID_LEN),
+        ));
+    }
+    if let Some(c) = value.chars().find(|c| !is_id_char(*c)) {
+        return Err(invalid(
+            op,
+            field,
+            &format!("contains disallowed character {:?}", c),
+        ));
+    }
+    Ok(value.to_string())
+}
+
+/// Trim and check a `<name>@<version>` policy or profile ref.
+pub fn validate_ref(op: &str, field: &str, value: &str) -> Result<String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(invalid(op, field, "must not be empty"));
+    }
+    if value.len() > MAX_ID_LEN {
+        return Err(invalid(
+            op,
+            field,
+            &format!("must be at most {} bytes", MAX_ID_LEN),
+        ));
+    }
+    let (name, version) = value
+        .split_once('@')
+        .ok_or_else(|| invalid(op, field, "must have the form <name>@<version>"))?;
+    if name.is_empty() || !name.chars().all(is_id_char) {
+        return Err(invalid(
+            op,
+            field,
+            "name must be non-empty ASCII alphanumerics, ':', '_', '-', '.', '/'",
+        ));
+    }
+    if version.is_empty()
+        || !version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+    {
+        return Err(invalid(
+            op,
+            field,
+            "version must be non-empty ASCII alphanumerics, '.', '_', '-'",
+        ));
+    }
+    Ok(value.to_string())
+}
+
+fn is_id_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, ':' | '_' | '-' | '.' | '/')
+}
+
+fn invalid(op: &str, field: &str, reason: &str) -> ExError {
+    ExError::new(ExErrorKind::InvalidInput)
+        .with_op(op)
+        .with_message(format!("{}: {}", field, reason))
+}
@@ -0,0 +1,233 @@
+//! Feature flags for experimental commands.
+//!
+//! Every flag is declared in [`FEATURE_FLAGS`]. A flag's effective state is,
+//! from highest precedence:
+//!
+//! 1. a process-wide override set with [`set_feature_overrides`] (the CLI
+//!    and MCP server fill this from `ETTLEX_FEATURES`),
+//! 2. the repository setting `feature.<name>` in the `settings` table,
+//! 3. the flag's declared default.
+//!
+//! Entry points of gated commands call [`require_feature`], which fails with
+//! `NotImplemented` and a hint on how to enable the flag, so incomplete
+//! subsystems can ship dark.
+
+#![allow(clippy::result_large_err)]
+
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_store::cas::FsStore;
+use ettlex_store::errors::Result;
+use ettlex_store::settings::{clear_setting, get_setting, set_setting};
+use rusqlite::Connection;
+use serde::Serialize;
+
+use crate::commands::command::{apply_command, Command, CommandResult};
+
+/// Settings-table key prefix for persisted flag values.
+pub const FEATURE_SETTING_PREFIX: &str = "feature.";
+
+/// A declared feature flag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeatureFlag {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub default_enabled: bool,
+}
+
+/// Every known flag, sorted by name.
+pub const FEATURE_FLAGS: &[FeatureFlag] = &[
+    FeatureFlag {
+        name: "declarative_apply",
+        description: "`ettlex apply`: reconcile a subtree with a YAML description",
+        // Shipped before flags existed; on so current users are unaffected
+        default_enabled: true,
+    },
+    FeatureFlag {
+        name: "three_way_diff",
+        description: "Three-way snapshot diff and merge against a common base",
+        default_enabled: false,
+    },
+];
+
+/// Where a flag's effective value came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeatureSource {
+    Default,
+    Setting,
+    Override,
+}
+
+/// Effective state of one flag.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FeatureState {
+    pub name: String,
+    pub description: String,
+    pub enabled: bool,
+    pub source: FeatureSource,
+}
+
+static OVERRIDES: RwLock<BTreeMap<String, bool>> = RwLock::new(BTreeMap::new());
+
+/// Replace the process-wide overrides.
+pub fn set_feature_overrides(overrides: BTreeMap<String, bool>) {
+    if let Ok(mut guard) = OVERRIDES.write() {
+        *guard = overrides;
+    }
+}
+
+/// Parse an override list such as `three_way_diff,-declarative_apply`:
+/// a bare name enables a flag, a leading `-` disables it.
+///
+/// # Errors
+/// * `InvalidInput` - an entry names an unknown flag
+pub fn parse_feature_overrides(spec: &str) -> Result<BTreeMap<String, bool>> {
+    let mut overrides = BTreeMap::new();
+    for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (name, enabled) = match entry.strip_prefix('-') {
+            Some(name) => (name, false),
+            None => (entry.strip_prefix('+').unwrap_or(entry), true),
+        };
+        lookup(name)?;
+        overrides.insert(name.to_string(), enabled);
+    }
+    Ok(overrides)
+}
+
+/// Effective state of `name`.
+///
+/// # Errors
+/// * `InvalidInput` - unknown flag
+/// * `Persistence` / `Serialization` - the stored setting cannot be read
+pub fn feature_state(conn: &Connection, name: &str) -> Result<FeatureState> {
+    let flag = lookup(name)?;
+    let state = |enabled, source| FeatureState {
+        name: flag.name.to_string(),
+        description: flag.description.to_string(),
+        enabled,
+        source,
+    };
+
+    if let Some(enabled) = OVERRIDES.read().ok().and_then(|o| o.get(name).copied()) {
+        return Ok(state(enabled, FeatureSource::Override));
+    }
+    match get_setting(conn, &setting_key(name))? {
+        Some(value) => {
+            let enabled = value.as_bool().ok_or_else(|| {
+                ExError::new(ExErrorKind::Serialization)
+                    .with_op("feature_state")
+                    .with_entity_id(name)
+                    .with_message(format!("setting '{}' is not a boolean", setting_key(name)))
+            })?;
+            Ok(state(enabled, FeatureSource::Setting))
+        }
+        None => Ok(state(flag.default_enabled, FeatureSource::Default)),
+    }
+}
+
+/// Effective state of every declared flag, sorted by name.
+///
+/// # Errors
+/// Same as [`feature_state`].
+pub fn list_features(conn: &Connection) -> Result<Vec<FeatureState>> {
+    FEATURE_FLAGS
+        .iter()
+        .map(|flag| feature_state(conn, flag.name))
+        .collect()
+}
+
+/// Fail unless `name` is enabled.
+///
+/// # Errors
+/// * `NotImplemented` - the flag is disabled; the message says how to enable it
+/// * Errors from [`feature_state`]
+pub fn require_feature(conn: &Connection, name: &str) -> Result<()> {
+    let state = feature_state(conn, name)?;
+    if state.enabled {
+        return Ok(());
+    }
+    let hint = match state.source {
+        FeatureSource::Override => format!("remove '-{}' from ETTLEX_FEATURES", name),
+        _ => format!(
+            "run `ettlex feature enable {}` or set ETTLEX_FEATURES={}",
+            name, name
+        ),
+    };
+    Err(ExError::new(ExErrorKind::NotImplemented)
+        .with_op("require_feature")
+        .with_entity_id(name)
+        .with_message(format!(
+            "experimental feature '{}' is disabled; {}",
+            name, hint
+        )))
+}
+
+/// Persist `enabled` for `name`, or clear the setting with `None` so the
+/// default applies again, by applying `Command::FeatureSet`.
+///
+/// # Errors
+/// * `InvalidInput` - unknown flag
+/// * `Persistence` - the write fails
+pub fn set_feature(
+    conn: &mut Connection,
+    cas: &FsStore,
+    name: &str,
+    enabled: Option<bool>,
+) -> Result<()> {
+    let cmd = Command::FeatureSet {
+        name: name.to_string(),
+        enabled,
+    };
+    match apply_command(
+        cmd,
+        None,
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )? {
+        (CommandResult::FeatureSet, _) => Ok(()),
+        _ => Err(ExError::new(ExErrorKind::Internal)
+            .with_op("feature_set")
+            .with_message("unexpected command result")),
+    }
+}
+
+/// Store or clear the flag's setting. Applied as `Command::FeatureSet`.
+pub(crate) fn handle_feature_set(
+    conn: &Connection,
+    name: &str,
+    enabled: Option<bool>,
+) -> Result<()> {
+    lookup(name)?;
+    match enabled {
+        Some(enabled) => set_setting(conn, &setting_key(name), &enabled.into()),
+        None => clear_setting(conn, &setting_key(name)).map(|_| ()),
+    }
+}
+
+fn lookup(name: &str) -> Result<&'static FeatureFlag> {
+    FEATURE_FLAGS
+        .iter()
+        .find(|f| f.name == name)
+        .ok_or_else(|| {
+            let known: Vec<&str> = FEATURE_FLAGS.iter().map(|f| f.name).collect();
+            ExError::new(ExErrorKind::InvalidInput)
+                .with_op("feature_lookup")
+                .with_entity_id(name)
+                .with_message(format!(
+                    "unknown feature flag '{}' (known: {})",
+                    name,
+                    known.join(", ")
+                ))
+        })
+}
+
+fn setting_key(name: &str) -> String {
+    format!("{}{}", FEATURE_SETTING_PREFIX, name)
+}
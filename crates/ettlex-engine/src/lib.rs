@@ -4,4 +4,5 @@
 //! core domain logic and persistence layer.
 
 pub mod commands;
+pub mod feature_flags;
 pub mod snapshot;
@@ -2,15 +2,39 @@
 //!
 //! The snapshot commit pipeline has been deferred pending re-specification
 //! against the Ettle/Relation model. All operations return `NotImplemented`.
+//!
+//! Pieces the pipeline will compose are available already:
+//! [`decision_lifecycle_mode`] resolves the profile's decision lifecycle
+//! check, which `ettlex_core::snapshot::apply_decision_lifecycle` runs
+//! against a generated manifest, [`manifest_eval_input`] loads the relation
+//! data, content and template variables constraint families are evaluated
+//! against, [`store_ep_content`] writes the content blobs its entries'
+//! `ep_digest`s name, and [`refinement_edges`] loads the edges `ettlex_core::snapshot::record_topology_digest` hashes.
 
 #![allow(clippy::result_large_err)]
 
 use ettlex_core::approval_router::ApprovalRouter;
+use ettlex_core::constraint_engine::EttleContent;
+use ettlex_core::diff::EpContent;
 use ettlex_core::errors::{ExError, ExErrorKind};
 use ettlex_core::policy_provider::PolicyProvider;
+use ettlex_core::render::template::{
+    template_mode_from_profile_payload, variables_from_profile_payload, TemplateMode,
+    TemplateVariables,
+};
+use ettlex_core::snapshot::{
+    ConstraintsEnvelopeSummary, DecisionLifecycleMode, DecisionLifecyclePolicy, EpEntry,
+    ManifestEvalInput, RefinementEdges,
+};
 use ettlex_store::cas::FsStore;
 use ettlex_store::errors::Result;
+use ettlex_store::profile::{load_default_profile, load_profile_payload};
+use ettlex_store::repo::SqliteRepo;
+use ettlex_store::snapshot::SnapshotIdScheme;
 use rusqlite::Connection;
+use std::collections::BTreeMap;
+
+use crate::commands::content_spill::hydrate_ettle_content;
 
 /// Options for snapshot commit operations.
 #[derive(Debug, Clone, Default)]
@@ -18,6 +42,11 @@ pub struct SnapshotOptions {
     pub expected_head: Option<String>,
     pub dry_run: bool,
     pub allow_dedup: bool,
+    pub id_scheme: SnapshotIdScheme,
+    /// Free-text commit message recorded on the ledger row.
+    pub message: Option<String>,
+    /// Key/value annotations recorded on the ledger row (not digested).
+    pub annotations: BTreeMap<String, String>,
 }
 
 /// Result of a successful snapshot commit.
@@ -25,6 +54,11 @@ pub struct SnapshotOptions {
 pub struct SnapshotCommitResult {
     pub snapshot_id: String,
     pub manifest_digest: String,
+    /// Per-family counts, statuses and digests of the anchored constraints.
+    pub constraints_summary: ConstraintsEnvelopeSummary,
+    /// `true` when `allow_dedup` matched an existing snapshot and no new one
+    /// was written.
+    pub was_duplicate: bool,
 }
 
 /// Outcome of a snapshot commit attempt.
@@ -40,6 +74,38 @@ pub struct RoutedForApprovalResult {
     pub approval_token: String,
 }
 
+/// Classification of one snapshot commit attempt, logged and counted under
+/// the `snapshot_commit` op (see [`StoreStats::op_outcomes`]).
+///
+/// [`StoreStats::op_outcomes`]: crate::commands::read_tools::StoreStats::op_outcomes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotCommitClass {
+    /// A new snapshot was written.
+    Committed,
+    /// An identical snapshot already existed and was returned.
+    Duplicate,
+    /// The commit was routed for approval.
+    Routed,
+    /// The policy provider refused the commit.
+    Denied,
+    /// The commit failed with any other error.
+    Failed(ExErrorKind),
+}
+
+impl SnapshotCommitClass {
+    /// Outcome label: `committed`, `duplicate`, `routed`, `denied`, or
+    /// `failed:<error code>` (e.g. `failed:ERR_HEAD_MISMATCH`).
+    pub fn label(&self) -> String {
+        match self {
+            Self::Committed => "committed".to_string(),
+            Self::Duplicate => "duplicate".to_string(),
+            Self::Routed => "routed".to_string(),
+            Self::Denied => "denied".to_string(),
+            Self::Failed(kind) => format!("failed:{}", kind.code()),
+        }
+    }
+}
+
 /// Commit a snapshot for a leaf EP — STUB, returns `NotImplemented`.
 ///
 /// The snapshot pipeline has been deferred pending re-specification against
@@ -65,3 +131,125 @@ pub fn snapshot_commit_by_leaf(
              Re-specify against Ettle/Relation model.",
         ))
 }
+
+/// Decision lifecycle mode of the profile a commit runs under: `profile_ref`
+/// if given, else the default profile. No default profile means `Off`.
+///
+/// # Errors
+/// * `ProfileNotFound` - `profile_ref` does not exist
+/// * `InvalidInput` - The profile's `decision_lifecycle` key is malformed
+pub fn decision_lifecycle_mode(
+    conn: &Connection,
+    profile_ref: Option<&str>,
+) -> Result<DecisionLifecycleMode> {
+    let profile_ref = match profile_ref {
+        Some(r) => r.to_string(),
+        None => match load_default_profile(conn)? {
+            Some((r, _, _)) => r,
+            None => return Ok(DecisionLifecycleMode::Off),
+        },
+    };
+    let payload = load_profile_payload(conn, &profile_ref)?.ok_or_else(|| {
+        ExError::new(ExErrorKind::ProfileNotFound)
+            .with_op("decision_lifecycle_mode")
+            .with_entity_id(&profile_ref)
+            .with_message("profile not found")
+    })?;
+    Ok(DecisionLifecyclePolicy::from_profile_payload(&payload)?.mode)
+}
+
+/// Active refinement children of each entry in `ept`, for the manifest's
+/// topology digest. Entries without children are left out.
+///
+/// # Errors
+/// * `Persistence` - The relations query fails
+pub fn refinement_edges(conn: &Connection, ept: &[EpEntry]) -> Result<RefinementEdges> {
+    active_children(conn, ept.iter().map(|entry| entry.ep_id.as_str()))
+}
+
+/// Relation data and content for evaluating the constraint families of a
+/// manifest over `ept` with `generate_manifest_with_input`. Spilled content
+/// is read back from `cas`; EPT entries with no Ettle row are left out.
+/// `{{variables}}` in content resolve against `profile_ref` (see
+/// [`manifest_template_variables`]).
+///
+/// # Errors
+/// * `Persistence` - A relations or Ettle query fails
+/// * `MissingBlob` - Spilled content is missing from the CAS
+pub fn manifest_eval_input(
+    conn: &Connection,
+    cas: &FsStore,
+    ept: &[String],
+    profile_ref: &str,
+) -> Result<ManifestEvalInput> {
+    let mut content = BTreeMap::new();
+    for ettle_id in ept {
+        let Some(mut record) = SqliteRepo::get_ettle_record(conn, ettle_id)? else {
+            continue;
+        };
+        hydrate_ettle_content(&mut record, cas)?;
+        content.insert(
+            ettle_id.clone(),
+            EttleContent {
+                why: record.why,
+                what: record.what,
+                how: record.how,
+            },
+        );
+    }
+    let (variables, template_mode) = manifest_template_variables(conn, profile_ref)?;
+    Ok(ManifestEvalInput {
+        children: Some(active_children(conn, ept.iter().map(String::as_str))?),
+        content: Some(content),
+        variables: Some(variables),
+        template_mode,
+    })
+}
+
+/// Write the content blob of every Ettle in `input` to `cas`, so a content
+/// diff of the manifest generated from `input` can load it by `ep_digest`.
+/// Blobs already stored are reused.
+///
+/// # Errors
+/// * `Io` - A blob cannot be written
+pub fn store_ep_content(cas: &FsStore, input: &ManifestEvalInput) -> Result<()> {
+    for content in input.content.iter().flat_map(BTreeMap::values) {
+        cas.write(&EpContent::from(content).to_blob(), "json")?;
+    }
+    Ok(())
+}
+
+/// `{{variable}}` bindings and template mode from `profile_ref`'s payload:
+/// its `variables` object, and `Strict` when it sets `strict_variables`.
+/// A manifest may name a profile that was never stored; that yields no
+/// bindings and `Lenient`.
+///
+/// # Errors
+/// * `Persistence` - The profile query fails
+pub fn manifest_template_variables(
+    conn: &Connection,
+    profile_ref: &str,
+) -> Result<(TemplateVariables, TemplateMode)> {
+    Ok(match load_profile_payload(conn, profile_ref)? {
+        Some(payload) => (
+            variables_from_profile_payload(&payload),
+            template_mode_from_profile_payload(&payload),
+        ),
+        None => (TemplateVariables::new(), TemplateMode::Lenient),
+    })
+}
+
+fn active_children<'a>(
+    conn: &Connection,
+    ettle_ids: impl Iterator<Item = &'a str>,
+) -> Result<RefinementEdges> {
+    let mut edges = RefinementEdges::new();
+    for ettle_id in ettle_ids {
+        let children =
+            SqliteRepo::get_active_outgoing_relations_of_type(conn, ettle_id, "refinement")?;
+        if !children.is_empty() {
+            edges.insert(ettle_id.to_string(), children);
+        }
+    }
+    Ok(edges)
+}
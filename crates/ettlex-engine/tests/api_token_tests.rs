@@ -0,0 +1,121 @@
+//! API token tests.
+//!
+//! Tests cover: issuance stores only a hash, scope checks (with `admin`
+//! granting everything), revocation, and input validation.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::errors::ExErrorKind;
+use ettlex_engine::commands::api_token::{
+    authorize_api_token, issue_api_token, list_api_tokens, revoke_api_token, ApiScope,
+    API_TOKEN_PREFIX,
+};
+use ettlex_store::cas::FsStore;
+use ettlex_store::migrations::apply_migrations;
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+fn setup() -> (Connection, FsStore, TempDir) {
+    let dir = TempDir::new().expect("temp dir");
+    let mut conn = Connection::open_in_memory().expect("in-memory db");
+    apply_migrations(&mut conn).expect("migrations should apply");
+    let cas = FsStore::new(dir.path().join("cas"));
+    (conn, cas, dir)
+}
+
+fn scopes(names: &[&str]) -> Vec<String> {
+    names.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn test_issued_token_is_stored_hashed_and_authorizes_its_scopes() {
+    let (mut conn, cas, _dir) = setup();
+    let issued =
+        issue_api_token(&mut conn, &cas, "ci", &scopes(&["write", "read", "read"])).unwrap();
+    assert!(issued.secret.starts_with(API_TOKEN_PREFIX));
+    assert_eq!(issued.record.scopes, scopes(&["read", "write"]));
+
+    let stored: String = conn
+        .query_row("SELECT token_hash FROM api_tokens", [], |r| r.get(0))
+        .unwrap();
+    assert_ne!(stored, issued.secret);
+    assert!(!stored.contains(&issued.secret));
+
+    let record = authorize_api_token(&conn, Some(&issued.secret), ApiScope::Write).unwrap();
+    assert_eq!(record.token_id, issued.record.token_id);
+    let err = authorize_api_token(&conn, Some(&issued.secret), ApiScope::Approve).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::Forbidden);
+}
+
+#[test]
+fn test_admin_scope_grants_every_scope() {
+    let (mut conn, cas, _dir) = setup();
+    let issued = issue_api_token(&mut conn, &cas, "ops", &scopes(&["admin"])).unwrap();
+    for scope in ApiScope::ALL {
+        authorize_api_token(&conn, Some(&issued.secret), scope).unwrap();
+    }
+}
+
+#[test]
+fn test_unknown_missing_and_revoked_tokens_are_unauthorised() {
+    let (mut conn, cas, _dir) = setup();
+    let issued = issue_api_token(&mut conn, &cas, "laptop", &scopes(&["read"])).unwrap();
+
+    let err = authorize_api_token(&conn, None, ApiScope::Read).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::Unauthorised);
+    let err = authorize_api_token(&conn, Some("etx_guess"), ApiScope::Read).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::Unauthorised);
+
+    revoke_api_token(&mut conn, &cas, &issued.record.token_id).unwrap();
+    let err = authorize_api_token(&conn, Some(&issued.secret), ApiScope::Read).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::Unauthorised);
+
+    let err = revoke_api_token(&mut conn, &cas, &issued.record.token_id).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::AlreadyTombstoned);
+    let err = revoke_api_token(&mut conn, &cas, "tok:missing").unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+
+    assert!(list_api_tokens(&conn, false).unwrap().is_empty());
+    let all = list_api_tokens(&conn, true).unwrap();
+    assert_eq!(all.len(), 1);
+    assert!(all[0].revoked_at_ms.is_some());
+}
+
+#[test]
+fn test_issue_rejects_blank_names_and_bad_scopes() {
+    let (mut conn, cas, _dir) = setup();
+    let err = issue_api_token(&mut conn, &cas, "  ", &scopes(&["read"])).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+    let err = issue_api_token(&mut conn, &cas, "ci", &[]).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+    let err = issue_api_token(&mut conn, &cas, "ci", &scopes(&["superuser"])).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_issue_and_revoke_are_logged_commands_without_the_secret() {
+    let (mut conn, cas, _dir) = setup();
+    let issued = issue_api_token(&mut conn, &cas, "ci", &scopes(&["read"])).unwrap();
+    revoke_api_token(&mut conn, &cas, &issued.record.token_id).unwrap();
+
+    let kinds: Vec<String> = conn
+        .prepare("SELECT kind FROM provenance_events ORDER BY id")
+        .unwrap()
+        .query_map([], |r| r.get(0))
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(kinds, ["api_token_issued", "api_token_revoked"]);
+    let logged: i64 = conn
+        .query_row("SELECT COUNT(*) FROM command_log", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(logged, 2);
+    let leaked: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM provenance_events WHERE correlation_id LIKE ?1",
+            [format!("%{}%", issued.secret)],
+            |r| r.get(0),
+        )
+        .unwrap();
+    assert_eq!(leaked, 0);
+}
@@ -0,0 +1,147 @@
+//! Integration tests for offline approval export, response and import.
+
+use ettlex_core::approval_router::{ApprovalRouter, NoopApprovalRouter};
+use ettlex_core::errors::ExErrorKind;
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_engine::commands::approval_offline::{
+    approval_response_command, export_approval, respond_to_export, review_key_id, ApprovalDecision,
+    ApprovalExport,
+};
+use ettlex_engine::commands::command::{apply_command, Command};
+use ettlex_store::cas::FsStore;
+use ettlex_store::profile::{list_approval_events, SqliteApprovalRouter};
+use rusqlite::Connection;
+use sha2::Digest;
+use tempfile::TempDir;
+
+const KEY: &[u8] = b"review-key";
+
+fn setup() -> (TempDir, Connection, FsStore, String) {
+    let temp_dir = TempDir::new().unwrap();
+    let mut conn = Connection::open(temp_dir.path().join("test.db")).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    let cas = FsStore::new(temp_dir.path().join("cas"));
+    conn.execute(
+        "INSERT INTO ettles (id, title, why, what, how, created_at, updated_at)
+         VALUES ('ettle:a', 'Option A', '', 'Use Postgres', '',
+                 '2026-01-01T00:00:00Z', '2026-01-01T00:00:00Z')",
+        [],
+    )
+    .unwrap();
+    let token = SqliteApprovalRouter::new_with_cas(&mut conn, &cas)
+        .route_approval_request("AmbiguousSelection", vec!["ettle:a".to_string()])
+        .unwrap();
+    (temp_dir, conn, cas, token)
+}
+
+fn apply(conn: &mut Connection, cas: &FsStore, cmd: Command) {
+    apply_command(
+        cmd,
+        None,
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_export_respond_import_round_trip() {
+    let (_tmp, mut conn, cas, token) = setup();
+
+    let export = export_approval(&conn, &cas, &token, KEY).unwrap();
+    assert_eq!(export.key_id, review_key_id(KEY));
+    // The key ID is keyed, not a plain hash of the secret
+    let plain = format!("{:x}", sha2::Sha256::digest(KEY));
+    assert!(!plain.starts_with(&export.key_id));
+    assert_eq!(
+        export.packet.candidates[0].title.as_deref(),
+        Some("Option A")
+    );
+    assert_eq!(export.payload["reason_code"], "AmbiguousSelection");
+    assert!(export.render.contains(&token));
+
+    // The files travel as JSON
+    let export: ApprovalExport =
+        serde_json::from_str(&serde_json::to_string_pretty(&export).unwrap()).unwrap();
+    let response = respond_to_export(
+        &export,
+        KEY,
+        ApprovalDecision::Rejected,
+        " alice ",
+        Some("Needs a migration plan"),
+    )
+    .unwrap();
+    assert_eq!(response.reviewer, "alice");
+    assert_eq!(response.export_signature, export.signature);
+
+    let cmd = approval_response_command(&conn, &response, KEY).unwrap();
+    apply(&mut conn, &cas, cmd);
+
+    let events = list_approval_events(&conn, &token).unwrap();
+    let last = events.last().unwrap();
+    assert_eq!(last.to_status, "rejected");
+    assert_eq!(last.actor.as_deref(), Some("alice"));
+
+    // A second import of the same response finds the request resolved
+    let err = approval_response_command(&conn, &response, KEY).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::ConstraintViolation);
+    let err = export_approval(&conn, &cas, &token, KEY).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::ConstraintViolation);
+}
+
+#[test]
+fn test_tampered_or_foreign_files_are_rejected() {
+    let (_tmp, conn, cas, token) = setup();
+    let export = export_approval(&conn, &cas, &token, KEY).unwrap();
+
+    let mut tampered = export.clone();
+    tampered.render.push_str("\nApprove option B instead.\n");
+    let err =
+        respond_to_export(&tampered, KEY, ApprovalDecision::Approved, "alice", None).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::Unauthorised);
+
+    let err = export.verify(b"other-key").unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::Unauthorised);
+    assert!(err.message().contains("signed with key"));
+
+    let err = respond_to_export(&export, KEY, ApprovalDecision::Approved, "  ", None).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+
+    let response =
+        respond_to_export(&export, KEY, ApprovalDecision::Approved, "alice", None).unwrap();
+    let mut flipped = response.clone();
+    flipped.decision = ApprovalDecision::Rejected;
+    let err = approval_response_command(&conn, &flipped, KEY).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::Unauthorised);
+
+    let mut other = response.clone();
+    other.format = "ettlex.approval_response/v0".to_string();
+    let err = approval_response_command(&conn, &other, KEY).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+
+    let err = export_approval(&conn, &cas, &token, b"").unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_import_after_online_resolution_is_rejected() {
+    let (_tmp, mut conn, cas, token) = setup();
+    let export = export_approval(&conn, &cas, &token, KEY).unwrap();
+    let response =
+        respond_to_export(&export, KEY, ApprovalDecision::Approved, "alice", None).unwrap();
+
+    apply(
+        &mut conn,
+        &cas,
+        Command::ApprovalTransition {
+            approval_token: token.clone(),
+            status: "expired".to_string(),
+            actor: None,
+        },
+    );
+
+    let err = approval_response_command(&conn, &response, KEY).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::ConstraintViolation);
+}
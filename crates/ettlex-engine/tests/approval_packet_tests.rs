@@ -0,0 +1,91 @@
+//! Integration tests for approval review packet rendering.
+
+use ettlex_core::approval_router::ApprovalRouter;
+use ettlex_core::errors::ExErrorKind;
+use ettlex_engine::commands::approval_packet::{build_approval_packet, render_approval_request};
+use ettlex_store::cas::FsStore;
+use ettlex_store::profile::SqliteApprovalRouter;
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+fn setup() -> (TempDir, Connection, FsStore) {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("test.db");
+    let cas_path = temp_dir.path().join("cas");
+    let mut conn = Connection::open(&db_path).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    let cas = FsStore::new(cas_path);
+    (temp_dir, conn, cas)
+}
+
+fn insert_ettle(conn: &Connection, id: &str, title: &str, what: &str) {
+    conn.execute(
+        "INSERT INTO ettles (id, title, why, what, how, created_at, updated_at)
+         VALUES (?1, ?2, '', ?3, '', '2026-01-01T00:00:00Z', '2026-01-01T00:00:00Z')",
+        rusqlite::params![id, title, what],
+    )
+    .unwrap();
+}
+
+fn insert_refinement(conn: &Connection, id: &str, parent: &str, child: &str) {
+    conn.execute(
+        "INSERT INTO relations (id, source_ettle_id, target_ettle_id, relation_type,
+                                properties_json, created_at)
+         VALUES (?1, ?2, ?3, 'refinement', '{}', '2026-01-01T00:00:00Z')",
+        rusqlite::params![id, parent, child],
+    )
+    .unwrap();
+}
+
+fn route(conn: &mut Connection, cas: &FsStore, candidates: &[&str]) -> String {
+    let router = SqliteApprovalRouter::new_with_cas(conn, cas);
+    router
+        .route_approval_request(
+            "AmbiguousSelection",
+            candidates.iter().map(|c| c.to_string()).collect(),
+        )
+        .unwrap()
+}
+
+#[test]
+fn test_packet_hydrates_candidates_and_paths() {
+    let (_tmp, mut conn, cas) = setup();
+    insert_ettle(&conn, "ettle:root", "Root", "");
+    insert_ettle(&conn, "ettle:a", "Option A", "Use Postgres");
+    insert_ettle(&conn, "ettle:b", "Option B", "Use SQLite");
+    insert_refinement(&conn, "rel:1", "ettle:root", "ettle:a");
+
+    let token = route(&mut conn, &cas, &["ettle:a", "ettle:b", "ettle:gone"]);
+    let packet = build_approval_packet(&conn, &cas, &token).unwrap();
+
+    assert_eq!(packet.status, "pending");
+    assert_eq!(packet.reason_code, "AmbiguousSelection");
+    assert_eq!(packet.candidates.len(), 3);
+    assert_eq!(packet.candidates[0].path, vec!["Root", "Option A"]);
+    assert_eq!(
+        packet.candidates[0].summary.as_deref(),
+        Some("Use Postgres")
+    );
+    assert_eq!(packet.candidates[1].path, vec!["Option B"]);
+    assert!(packet.candidates[2].title.is_none());
+}
+
+#[test]
+fn test_render_approval_request_markdown() {
+    let (_tmp, mut conn, cas) = setup();
+    insert_ettle(&conn, "ettle:a", "Option A", "Use Postgres");
+    let token = route(&mut conn, &cas, &["ettle:a"]);
+
+    let md = render_approval_request(&conn, &cas, &token).unwrap();
+    assert!(md.contains(&token));
+    assert!(md.contains("Option A"));
+    assert!(md.contains("Use Postgres"));
+    assert!(md.contains("## How to respond"));
+}
+
+#[test]
+fn test_render_unknown_token_fails() {
+    let (_tmp, conn, cas) = setup();
+    let err = render_approval_request(&conn, &cas, "no-such-token").unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::ApprovalNotFound);
+}
@@ -0,0 +1,176 @@
+//! Retention sweep of approval request payload blobs.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::approval_router::{ApprovalRouter, NoopApprovalRouter};
+use ettlex_core::errors::ExErrorKind;
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_engine::commands::approval_sweep::approval_payload_sweep;
+use ettlex_engine::commands::command::{apply_command, Command, CommandResult};
+use ettlex_engine::commands::engine_query::{apply_engine_query, EngineQuery};
+use ettlex_engine::commands::settings::{settings_set, SETTING_APPROVAL_RETENTION_DAYS};
+use ettlex_store::cas::FsStore;
+use ettlex_store::profile::{fetch_approval_row, transition_approval, SqliteApprovalRouter};
+use rusqlite::Connection;
+use serde_json::json;
+use tempfile::TempDir;
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+fn setup() -> (TempDir, Connection, FsStore) {
+    let dir = TempDir::new().unwrap();
+    let mut conn = Connection::open(dir.path().join("test.db")).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    let cas = FsStore::new(dir.path().join("cas"));
+    (dir, conn, cas)
+}
+
+/// Route a request and move it to `status` `days_ago` days in the past.
+fn approval(conn: &mut Connection, cas: &FsStore, status: &str, days_ago: i64) -> String {
+    let token = SqliteApprovalRouter::new_with_cas(conn, cas)
+        .route_approval_request("AmbiguousSelection", vec![format!("ettle:{}", status)])
+        .unwrap();
+    if status != "pending" {
+        transition_approval(conn, &token, status, Some("reviewer")).unwrap();
+    }
+    let at = chrono::Utc::now().timestamp_millis() - days_ago * DAY_MS;
+    conn.execute(
+        "UPDATE approval_events SET occurred_at_ms = ?1 WHERE approval_token = ?2",
+        rusqlite::params![at, token],
+    )
+    .unwrap();
+    conn.execute(
+        "UPDATE approval_requests SET created_at = ?1 WHERE approval_token = ?2",
+        rusqlite::params![at, token],
+    )
+    .unwrap();
+    token
+}
+
+fn digest(conn: &Connection, token: &str) -> Option<String> {
+    fetch_approval_row(conn, token)
+        .unwrap()
+        .unwrap()
+        .request_digest
+}
+
+fn approval_get_kind(conn: &Connection, cas: &FsStore, token: &str) -> Option<ExErrorKind> {
+    let query = EngineQuery::ApprovalGet {
+        approval_token: token.to_string(),
+    };
+    apply_engine_query(query, conn, cas, None)
+        .err()
+        .map(|e| e.kind())
+}
+
+#[test]
+fn test_sweep_deletes_only_terminal_payloads_past_retention() {
+    let (_dir, mut conn, cas) = setup();
+    let rejected = approval(&mut conn, &cas, "rejected", 40);
+    let expired = approval(&mut conn, &cas, "expired", 40);
+    let approved = approval(&mut conn, &cas, "approved", 40);
+    let pending = approval(&mut conn, &cas, "pending", 40);
+    let recent = approval(&mut conn, &cas, "rejected", 5);
+    let rejected_digest = digest(&conn, &rejected).unwrap();
+
+    let preview = approval_payload_sweep(&mut conn, &cas, Some(30), true).unwrap();
+    assert_eq!(preview.swept.len(), 2);
+    assert_eq!(preview.blobs_deleted, 2);
+    assert!(cas.find_blob(&rejected_digest).unwrap().is_some());
+    assert!(digest(&conn, &rejected).is_some());
+
+    let report = approval_payload_sweep(&mut conn, &cas, Some(30), false).unwrap();
+    assert_eq!(report, preview);
+    let mut tokens = vec![rejected.clone(), expired.clone()];
+    tokens.sort();
+    let swept: Vec<&str> = report
+        .swept
+        .iter()
+        .map(|s| s.approval_token.as_str())
+        .collect();
+    assert_eq!(swept, tokens);
+    assert!(report.bytes_freed > 0);
+    assert!(cas.find_blob(&rejected_digest).unwrap().is_none());
+
+    assert_eq!(
+        approval_get_kind(&conn, &cas, &rejected),
+        Some(ExErrorKind::Deleted)
+    );
+    for token in [&approved, &pending, &recent] {
+        assert_eq!(approval_get_kind(&conn, &cas, token), None);
+    }
+
+    let again = approval_payload_sweep(&mut conn, &cas, Some(30), false).unwrap();
+    assert!(again.swept.is_empty());
+}
+
+#[test]
+fn test_sweep_keeps_blobs_still_referenced_elsewhere() {
+    let (_dir, mut conn, cas) = setup();
+    let rejected = approval(&mut conn, &cas, "rejected", 40);
+    let pending = approval(&mut conn, &cas, "pending", 1);
+    let shared = digest(&conn, &rejected).unwrap();
+    conn.execute(
+        "UPDATE approval_requests SET request_digest = ?1 WHERE approval_token = ?2",
+        rusqlite::params![shared, pending],
+    )
+    .unwrap();
+
+    let report = approval_payload_sweep(&mut conn, &cas, Some(30), false).unwrap();
+    assert_eq!(report.swept.len(), 1);
+    assert!(!report.swept[0].blob_deleted);
+    assert_eq!(report.blobs_deleted, 0);
+    assert!(digest(&conn, &rejected).is_none());
+    assert!(cas.find_blob(&shared).unwrap().is_some());
+}
+
+#[test]
+fn test_sweep_uses_retention_setting_and_requires_one() {
+    let (_dir, mut conn, cas) = setup();
+    approval(&mut conn, &cas, "expired", 10);
+
+    let err = approval_payload_sweep(&mut conn, &cas, None, false).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+
+    settings_set(&conn, SETTING_APPROVAL_RETENTION_DAYS, Some(json!(7))).unwrap();
+    let report = approval_payload_sweep(&mut conn, &cas, None, false).unwrap();
+    assert_eq!(report.swept.len(), 1);
+}
+
+#[test]
+fn test_sweep_is_a_logged_command_and_leaves_blobs_when_applied_directly() {
+    let (_dir, mut conn, cas) = setup();
+    let rejected = approval(&mut conn, &cas, "rejected", 40);
+    let payload = digest(&conn, &rejected).unwrap();
+    let provenance = |conn: &Connection| -> i64 {
+        conn.query_row(
+            "SELECT COUNT(*) FROM provenance_events WHERE kind = 'approval_payloads_swept'",
+            [],
+            |r| r.get(0),
+        )
+        .unwrap()
+    };
+
+    let (result, _) = apply_command(
+        Command::ApprovalPayloadSweep {
+            retention_days: Some(30),
+        },
+        None,
+        &mut conn,
+        &cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .unwrap();
+    match result {
+        CommandResult::ApprovalPayloadSweep { swept, .. } => {
+            assert_eq!(swept.len(), 1);
+            assert!(swept[0].blob_deleted);
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert_eq!(provenance(&conn), 1);
+    assert!(digest(&conn, &rejected).is_none());
+    // Unreferenced now, but deleting it is left to the caller or `cas gc`
+    assert!(cas.find_blob(&payload).unwrap().is_some());
+}
@@ -0,0 +1,178 @@
+//! Architecture report tests — the `ArchitectureReport` query aggregates tree
+//! analytics, snapshots, open decisions, constraint coverage and pending
+//! approvals, and both renderers produce every section.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::approval_router::{ApprovalRouter, NoopApprovalRouter};
+use ettlex_core::errors::ExError;
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_core::render::{
+    render_architecture_report_html, render_architecture_report_markdown, ArchitectureReport,
+};
+use ettlex_engine::commands::command::{apply_command, Command, CommandResult};
+use ettlex_engine::commands::decision::decision_create;
+use ettlex_engine::commands::engine_query::{apply_engine_query, EngineQuery, EngineQueryResult};
+use ettlex_store::cas::FsStore;
+use ettlex_store::migrations::apply_migrations;
+use ettlex_store::profile::SqliteApprovalRouter;
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+// ---------------------------------------------------------------------------
+// Test helpers
+// ---------------------------------------------------------------------------
+
+fn setup_db_with_cas() -> (Connection, FsStore, TempDir) {
+    let dir = TempDir::new().expect("temp dir");
+    let cas = FsStore::new(dir.path().join("cas"));
+    let mut conn = Connection::open_in_memory().expect("in-memory db");
+    apply_migrations(&mut conn).expect("migrations should apply");
+    (conn, cas, dir)
+}
+
+fn apply(conn: &mut Connection, cas: &FsStore, cmd: Command) -> Result<CommandResult, ExError> {
+    apply_command(
+        cmd,
+        None,
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .map(|(res, _sv)| res)
+}
+
+fn create_ettle(conn: &mut Connection, cas: &FsStore, title: &str) -> String {
+    match apply(
+        conn,
+        cas,
+        Command::EttleCreate {
+            title: title.to_string(),
+            ettle_id: None,
+            why: None,
+            what: None,
+            how: None,
+            reasoning_link_id: None,
+            reasoning_link_type: None,
+        },
+    )
+    .expect("ettle create should succeed")
+    {
+        CommandResult::EttleCreate { ettle_id } => ettle_id,
+        _ => panic!("unexpected result"),
+    }
+}
+
+fn relate(conn: &mut Connection, cas: &FsStore, source: &str, target: &str, kind: &str) {
+    apply(
+        conn,
+        cas,
+        Command::RelationCreate {
+            source_ettle_id: source.to_string(),
+            target_ettle_id: target.to_string(),
+            relation_type: kind.to_string(),
+            properties_json: None,
+            relation_id: None,
+        },
+    )
+    .expect("relation create should succeed");
+}
+
+fn create_decision(conn: &Connection, title: &str, status: &str) {
+    decision_create(
+        None,
+        title.to_string(),
+        Some(status.to_string()),
+        "text".to_string(),
+        "why".to_string(),
+        None,
+        None,
+        "none".to_string(),
+        None,
+        None,
+        None,
+        conn,
+    )
+    .expect("decision create should succeed");
+}
+
+fn report(conn: &Connection, cas: &FsStore) -> ArchitectureReport {
+    match apply_engine_query(
+        EngineQuery::ArchitectureReport {
+            recent_snapshot_limit: 5,
+        },
+        conn,
+        cas,
+        None,
+    )
+    .expect("report query should succeed")
+    {
+        EngineQueryResult::ArchitectureReport(r) => *r,
+        _ => panic!("expected ArchitectureReport"),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_report_on_empty_store() {
+    let (conn, cas, _dir) = setup_db_with_cas();
+    let r = report(&conn, &cas);
+    assert_eq!(r.tree.active_ettles, 0);
+    assert_eq!(r.tree.max_depth, 0);
+    assert!(r.recent_snapshots.is_empty());
+    assert!(r.open_decisions.is_empty());
+    assert!(r.pending_approvals.is_empty());
+}
+
+#[test]
+fn test_report_aggregates_all_sections() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let root = create_ettle(&mut conn, &cas, "Root");
+    let mid = create_ettle(&mut conn, &cas, "Mid");
+    let leaf = create_ettle(&mut conn, &cas, "Leaf");
+    let rule = create_ettle(&mut conn, &cas, "Rule");
+    relate(&mut conn, &cas, &root, &mid, "refinement");
+    relate(&mut conn, &cas, &mid, &leaf, "refinement");
+    relate(&mut conn, &cas, &rule, &leaf, "constraint");
+
+    create_decision(&conn, "Open one", "proposed");
+    create_decision(&conn, "Closed one", "accepted");
+
+    {
+        let router = SqliteApprovalRouter::new_with_cas(&mut conn, &cas);
+        router
+            .route_approval_request("AmbiguousSelection", vec![leaf.clone()])
+            .unwrap();
+    }
+
+    let r = report(&conn, &cas);
+    assert_eq!(r.tree.active_ettles, 4);
+    // Root and Rule have no refinement parent
+    assert_eq!(r.tree.root_count, 2);
+    assert_eq!(r.tree.max_depth, 3);
+    assert_eq!(r.tree.relations_by_type.get("refinement"), Some(&2));
+    assert_eq!(r.tree.relations_by_type.get("constraint"), Some(&1));
+    assert_eq!(r.constraint_coverage.constraint_ettles, 1);
+    assert_eq!(r.constraint_coverage.governed_ettles, 1);
+    assert_eq!(r.constraint_coverage.ungoverned_ettles, 2);
+    assert_eq!(r.open_decisions.len(), 1);
+    assert_eq!(r.open_decisions[0].title, "Open one");
+    assert_eq!(r.pending_approvals.len(), 1);
+
+    let md = render_architecture_report_markdown(&r);
+    let html = render_architecture_report_html(&r);
+    for section in [
+        "Tree analytics",
+        "Recent snapshots",
+        "Open decisions",
+        "Constraint coverage",
+        "Pending approvals",
+    ] {
+        assert!(md.contains(section), "markdown missing {}", section);
+        assert!(html.contains(section), "html missing {}", section);
+    }
+}
@@ -0,0 +1,208 @@
+//! Batch command tests — `Command::Batch` / `apply_commands_atomic` apply
+//! every command or none of them.
+
+#![allow(clippy::unwrap_used, clippy::result_large_err)]
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::ExErrorKind;
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_engine::commands::command::{
+    apply_command, apply_command_with_options, apply_commands_atomic, Command, CommandResult,
+};
+use ettlex_engine::commands::engine_command::CommandOptions;
+use ettlex_store::cas::FsStore;
+use ettlex_store::file_policy_provider::FilePolicyProvider;
+use rusqlite::Connection;
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+fn setup() -> (TempDir, Connection, FsStore) {
+    let dir = TempDir::new().unwrap();
+    let mut conn = Connection::open(dir.path().join("test.db")).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    let cas = FsStore::new(dir.path().join("cas"));
+    (dir, conn, cas)
+}
+
+fn count(conn: &Connection, table: &str) -> i64 {
+    conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |r| r.get(0))
+        .unwrap()
+}
+
+fn cmd(value: Value) -> Command {
+    serde_json::from_value(value).unwrap()
+}
+
+/// Root → Child refinement, built in one batch using slugs.
+fn tree_batch() -> Command {
+    cmd(json!({ "tag": "Batch", "commands": [
+        { "tag": "EttleCreate", "title": "Root" },
+        { "tag": "EttleCreate", "title": "Child" },
+        { "tag": "RelationCreate", "relation_type": "refinement",
+          "source_ettle_id": "root", "target_ettle_id": "child" },
+    ] }))
+}
+
+#[test]
+fn test_batch_applies_all_commands_in_order() {
+    let (_dir, mut conn, cas) = setup();
+
+    let (result, sv) = apply_command(
+        tree_batch(),
+        Some(0),
+        &mut conn,
+        &cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .unwrap();
+
+    let CommandResult::Batch { results } = result else {
+        panic!("expected Batch result, got {:?}", result);
+    };
+    assert_eq!(results.len(), 3);
+    assert!(matches!(results[0], CommandResult::EttleCreate { .. }));
+    assert!(matches!(results[2], CommandResult::RelationCreate { .. }));
+    // One command_log row and provenance event per command
+    assert_eq!(sv, 3);
+    assert_eq!(count(&conn, "command_log"), 3);
+    assert_eq!(count(&conn, "provenance_events"), 3);
+    assert_eq!(count(&conn, "relations"), 1);
+}
+
+#[test]
+fn test_batch_failure_rolls_back_every_command() {
+    let (_dir, mut conn, cas) = setup();
+
+    let err = apply_commands_atomic(
+        vec![
+            cmd(json!({ "tag": "EttleCreate", "title": "Root" })),
+            cmd(json!({ "tag": "EttleUpdate", "ettle_id": "ettle:missing", "title": "X" })),
+        ],
+        None,
+        &mut conn,
+        &cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .unwrap_err();
+
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+    assert!(err.message().starts_with("batch command 1: "));
+    assert_eq!(count(&conn, "ettles"), 0);
+    assert_eq!(count(&conn, "command_log"), 0);
+    assert_eq!(count(&conn, "provenance_events"), 0);
+}
+
+#[test]
+fn test_batch_checks_state_version_once_up_front() {
+    let (_dir, mut conn, cas) = setup();
+    apply_command(
+        cmd(json!({ "tag": "EttleCreate", "title": "Existing" })),
+        None,
+        &mut conn,
+        &cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .unwrap();
+
+    let err = apply_command(
+        tree_batch(),
+        Some(0),
+        &mut conn,
+        &cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::HeadMismatch);
+    assert_eq!(count(&conn, "ettles"), 1);
+
+    let (_, sv) = apply_command(
+        tree_batch(),
+        Some(1),
+        &mut conn,
+        &cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .unwrap();
+    assert_eq!(sv, 4);
+}
+
+#[test]
+fn test_batch_rejects_empty_and_disallowed_commands() {
+    let (_dir, mut conn, cas) = setup();
+
+    for commands in [
+        json!([]),
+        json!([{ "tag": "Batch", "commands": [{ "tag": "EttleCreate", "title": "A" }] }]),
+        json!([
+            { "tag": "EttleCreate", "title": "A" },
+            { "tag": "ApprovalTransition", "approval_token": "t", "status": "approved" },
+        ]),
+        json!([{ "tag": "SnapshotCommit", "leaf_ep_id": "ettle:a" }]),
+    ] {
+        let err = apply_command(
+            cmd(json!({ "tag": "Batch", "commands": commands })),
+            None,
+            &mut conn,
+            &cas,
+            &NoopPolicyProvider,
+            &NoopApprovalRouter,
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), ExErrorKind::InvalidInput, "{}", commands);
+    }
+    assert_eq!(count(&conn, "ettles"), 0);
+}
+
+#[test]
+fn test_batch_dry_run_leaves_no_trace() {
+    let (_dir, mut conn, cas) = setup();
+
+    let (result, sv) = apply_command_with_options(
+        tree_batch(),
+        None,
+        CommandOptions { dry_run: true },
+        &mut conn,
+        &cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .unwrap();
+
+    assert!(matches!(result, CommandResult::Batch { .. }));
+    assert_eq!(sv, 3);
+    assert_eq!(count(&conn, "ettles"), 0);
+    assert_eq!(count(&conn, "command_log"), 0);
+}
+
+#[test]
+fn test_batch_with_policy_create_is_rejected_without_side_effects() {
+    let (dir, mut conn, cas) = setup();
+    let policies_dir = dir.path().join("policies");
+    std::fs::create_dir_all(&policies_dir).unwrap();
+    let provider = FilePolicyProvider::new(&policies_dir);
+
+    let err = apply_commands_atomic(
+        vec![
+            cmd(json!({ "tag": "EttleCreate", "title": "Root" })),
+            cmd(json!({ "tag": "PolicyCreate", "policy_ref": "batch@0", "text": "# P" })),
+        ],
+        None,
+        &mut conn,
+        &cas,
+        &provider,
+        &NoopApprovalRouter,
+    )
+    .unwrap_err();
+
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+    assert!(err.message().starts_with("batch command 1: "));
+    assert_eq!(std::fs::read_dir(&policies_dir).unwrap().count(), 0);
+    assert_eq!(count(&conn, "ettles"), 0);
+    assert_eq!(count(&conn, "command_log"), 0);
+    assert_eq!(count(&conn, "provenance_events"), 0);
+}
@@ -0,0 +1,153 @@
+//! Dry-run tests — commands run with `CommandOptions { dry_run: true }` return
+//! the would-be result, surface the same errors, and leave no trace in the
+//! database.
+
+#![allow(clippy::unwrap_used, clippy::result_large_err)]
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::ExErrorKind;
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_engine::commands::command::{apply_command_with_options, Command, CommandResult};
+use ettlex_engine::commands::engine_command::{
+    apply_engine_command_with_options, CommandOptions, EngineCommand, EngineCommandResult,
+};
+use ettlex_store::cas::FsStore;
+use rusqlite::Connection;
+use serde_json::json;
+use tempfile::TempDir;
+
+const DRY_RUN: CommandOptions = CommandOptions { dry_run: true };
+
+fn setup() -> (TempDir, Connection, FsStore) {
+    let dir = TempDir::new().unwrap();
+    let mut conn = Connection::open(dir.path().join("test.db")).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    let cas = FsStore::new(dir.path().join("cas"));
+    (dir, conn, cas)
+}
+
+fn count(conn: &Connection, table: &str) -> i64 {
+    conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |r| r.get(0))
+        .unwrap()
+}
+
+fn ettle_create(title: &str) -> Command {
+    Command::EttleCreate {
+        title: title.to_string(),
+        ettle_id: None,
+        why: None,
+        what: None,
+        how: None,
+        reasoning_link_id: None,
+        reasoning_link_type: None,
+    }
+}
+
+fn apply(
+    conn: &mut Connection,
+    cas: &FsStore,
+    cmd: Command,
+    expected_sv: Option<u64>,
+    options: CommandOptions,
+) -> ettlex_store::errors::Result<(CommandResult, u64)> {
+    apply_command_with_options(
+        cmd,
+        expected_sv,
+        options,
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+}
+
+#[test]
+fn test_dry_run_ettle_create_returns_result_without_writes() {
+    let (_dir, mut conn, cas) = setup();
+    apply(
+        &mut conn,
+        &cas,
+        ettle_create("Real"),
+        None,
+        CommandOptions::default(),
+    )
+    .unwrap();
+
+    let (result, new_sv) =
+        apply(&mut conn, &cas, ettle_create("Preview"), Some(1), DRY_RUN).unwrap();
+
+    let CommandResult::EttleCreate { ettle_id } = result else {
+        panic!("expected EttleCreate result");
+    };
+    assert!(ettle_id.starts_with("ettle:"));
+    assert_eq!(new_sv, 2, "dry run reports the would-be state version");
+    assert_eq!(count(&conn, "ettles"), 1);
+    assert_eq!(count(&conn, "command_log"), 1);
+    assert_eq!(count(&conn, "provenance_events"), 1);
+}
+
+#[test]
+fn test_dry_run_surfaces_validation_errors() {
+    let (_dir, mut conn, cas) = setup();
+
+    let err = apply(&mut conn, &cas, ettle_create(""), None, DRY_RUN).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidTitle);
+
+    let err = apply(
+        &mut conn,
+        &cas,
+        Command::EttleTombstone {
+            ettle_id: "ettle:missing".to_string(),
+        },
+        None,
+        DRY_RUN,
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+
+    let err = apply(&mut conn, &cas, ettle_create("Stale"), Some(7), DRY_RUN).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::HeadMismatch);
+
+    assert_eq!(count(&conn, "command_log"), 0);
+}
+
+#[test]
+fn test_dry_run_engine_command_profile_create_not_persisted() {
+    let (_dir, mut conn, cas) = setup();
+
+    let result = apply_engine_command_with_options(
+        EngineCommand::ProfileCreate {
+            profile_ref: "profile/preview@0".to_string(),
+            payload_json: json!({ "ambiguity_policy": "deny" }),
+            source: None,
+        },
+        DRY_RUN,
+        &mut conn,
+        &cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .unwrap();
+
+    assert!(matches!(result, EngineCommandResult::ProfileCreate));
+    assert_eq!(count(&conn, "profiles"), 0);
+}
+
+#[test]
+fn test_dry_run_inside_open_transaction_keeps_outer_writes() {
+    let (_dir, mut conn, cas) = setup();
+    conn.execute_batch("BEGIN").unwrap();
+    apply(
+        &mut conn,
+        &cas,
+        ettle_create("Outer"),
+        None,
+        CommandOptions::default(),
+    )
+    .unwrap();
+    apply(&mut conn, &cas, ettle_create("Inner"), None, DRY_RUN).unwrap();
+    conn.execute_batch("COMMIT").unwrap();
+
+    assert_eq!(count(&conn, "ettles"), 1);
+    assert_eq!(count(&conn, "command_log"), 1);
+}
@@ -0,0 +1,268 @@
+//! Command journal tests — reversible commands are journaled with their
+//! inverse and replayed by `EngineCommand::Undo` / `EngineCommand::Redo`.
+
+#![allow(clippy::unwrap_used, clippy::result_large_err)]
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::ExErrorKind;
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_engine::commands::command::{apply_command, Command, CommandResult};
+use ettlex_engine::commands::engine_command::{
+    apply_engine_command, EngineCommand, EngineCommandResult,
+};
+use ettlex_engine::commands::journal::JournalEntry;
+use ettlex_store::cas::FsStore;
+use ettlex_store::errors::Result;
+use ettlex_store::model::EttleRecord;
+use ettlex_store::repo::SqliteRepo;
+use rusqlite::Connection;
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+fn setup() -> (TempDir, Connection, FsStore) {
+    let dir = TempDir::new().unwrap();
+    let mut conn = Connection::open(dir.path().join("test.db")).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    let cas = FsStore::new(dir.path().join("cas"));
+    (dir, conn, cas)
+}
+
+fn apply(conn: &mut Connection, cas: &FsStore, cmd: Value) -> CommandResult {
+    apply_command(
+        serde_json::from_value::<Command>(cmd).unwrap(),
+        None,
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .unwrap()
+    .0
+}
+
+fn create(conn: &mut Connection, cas: &FsStore, title: &str) -> String {
+    match apply(conn, cas, json!({ "tag": "EttleCreate", "title": title })) {
+        CommandResult::EttleCreate { ettle_id } => ettle_id,
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+fn undo(conn: &mut Connection, cas: &FsStore) -> Result<JournalEntry> {
+    match apply_engine_command(
+        EngineCommand::Undo,
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )? {
+        EngineCommandResult::Undo(entry) => Ok(entry),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+fn redo(conn: &mut Connection, cas: &FsStore) -> Result<JournalEntry> {
+    match apply_engine_command(
+        EngineCommand::Redo,
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )? {
+        EngineCommandResult::Redo(entry) => Ok(entry),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+fn record(conn: &Connection, id: &str) -> EttleRecord {
+    SqliteRepo::get_ettle_record(conn, id).unwrap().unwrap()
+}
+
+#[test]
+fn test_undo_and_redo_update_and_create() {
+    let (_dir, mut conn, cas) = setup();
+    let id = create(&mut conn, &cas, "Draft");
+    apply(
+        &mut conn,
+        &cas,
+        json!({ "tag": "EttleUpdate", "ettle_id": id, "title": "Final", "what": "body" }),
+    );
+
+    let entry = undo(&mut conn, &cas).unwrap();
+    assert_eq!(entry.op, "ettle_update");
+    assert_eq!(entry.entity_id.as_deref(), Some(id.as_str()));
+    let reverted = record(&conn, &id);
+    assert_eq!(reverted.title, "Draft");
+    assert_eq!(reverted.what, "");
+
+    let entry = undo(&mut conn, &cas).unwrap();
+    assert_eq!(entry.op, "ettle_create");
+    assert!(record(&conn, &id).tombstoned_at.is_some());
+
+    redo(&mut conn, &cas).unwrap();
+    assert!(record(&conn, &id).tombstoned_at.is_none());
+    assert_eq!(redo(&mut conn, &cas).unwrap().op, "ettle_update");
+    let reapplied = record(&conn, &id);
+    assert_eq!(reapplied.title, "Final");
+    assert_eq!(reapplied.what, "body");
+}
+
+#[test]
+fn test_undo_tombstone_reattaches_constraints() {
+    let (_dir, mut conn, cas) = setup();
+    let constraint = create(&mut conn, &cas, "Rule");
+    let target = create(&mut conn, &cas, "Target");
+    let relation_id = match apply(
+        &mut conn,
+        &cas,
+        json!({ "tag": "RelationCreate", "relation_type": "constraint",
+                "source_ettle_id": constraint, "target_ettle_id": target }),
+    ) {
+        CommandResult::RelationCreate { relation_id } => relation_id,
+        other => panic!("unexpected result {:?}", other),
+    };
+    apply(
+        &mut conn,
+        &cas,
+        json!({ "tag": "EttleTombstone", "ettle_id": target }),
+    );
+    let relation = |conn: &Connection| SqliteRepo::get_relation(conn, &relation_id).unwrap();
+    assert!(relation(&conn).unwrap().tombstoned_at.is_some());
+
+    undo(&mut conn, &cas).unwrap();
+    assert!(record(&conn, &target).tombstoned_at.is_none());
+    assert!(relation(&conn).unwrap().tombstoned_at.is_none());
+
+    redo(&mut conn, &cas).unwrap();
+    assert!(relation(&conn).unwrap().tombstoned_at.is_some());
+}
+
+#[test]
+fn test_new_command_discards_redo_history() {
+    let (_dir, mut conn, cas) = setup();
+    let id = create(&mut conn, &cas, "One");
+    apply(
+        &mut conn,
+        &cas,
+        json!({ "tag": "EttleUpdate", "ettle_id": id, "title": "Two" }),
+    );
+    undo(&mut conn, &cas).unwrap();
+    apply(
+        &mut conn,
+        &cas,
+        json!({ "tag": "EttleUpdate", "ettle_id": id, "title": "Three" }),
+    );
+
+    let err = redo(&mut conn, &cas).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+    undo(&mut conn, &cas).unwrap();
+    assert_eq!(record(&conn, &id).title, "One");
+}
+
+#[test]
+fn test_failed_undo_leaves_entry_in_place() {
+    let (_dir, mut conn, cas) = setup();
+    let err = undo(&mut conn, &cas).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+
+    let child = create(&mut conn, &cas, "Child");
+    let parent = create(&mut conn, &cas, "Parent");
+    // A link written outside the journal now blocks tombstoning the parent
+    conn.execute(
+        "UPDATE ettles SET reasoning_link_id = ?1, reasoning_link_type = 'refines' WHERE id = ?2",
+        [&parent, &child],
+    )
+    .unwrap();
+
+    let err = undo(&mut conn, &cas).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::HasActiveDependants);
+    assert!(err.message().starts_with("undo of journal entry"));
+    assert!(record(&conn, &parent).tombstoned_at.is_none());
+
+    // The entry stays next in line once the link is cleared
+    conn.execute(
+        "UPDATE ettles SET reasoning_link_id = NULL, reasoning_link_type = NULL WHERE id = ?1",
+        [&child],
+    )
+    .unwrap();
+    let entry = undo(&mut conn, &cas).unwrap();
+    assert_eq!(entry.entity_id.as_deref(), Some(parent.as_str()));
+}
+
+#[test]
+fn test_undo_refuses_to_overwrite_unjournaled_write() {
+    let (_dir, mut conn, cas) = setup();
+    let id = create(&mut conn, &cas, "Spec");
+    apply(
+        &mut conn,
+        &cas,
+        json!({ "tag": "EttleUpdate", "ettle_id": id, "what": "calls old-api" }),
+    );
+    // Not journaled: undo would otherwise revert `what` past this rewrite
+    apply(
+        &mut conn,
+        &cas,
+        json!({ "tag": "ContentRefRename", "from": "old-api", "to": "new-api" }),
+    );
+
+    let err = undo(&mut conn, &cas).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::HeadMismatch);
+    assert!(err
+        .message()
+        .contains("what was changed outside the journal"));
+    assert_eq!(record(&conn, &id).what, "calls new-api");
+
+    // Once the field is back in the recorded state the entry undoes cleanly
+    apply(
+        &mut conn,
+        &cas,
+        json!({ "tag": "ContentRefRename", "from": "new-api", "to": "old-api" }),
+    );
+    assert_eq!(undo(&mut conn, &cas).unwrap().op, "ettle_update");
+    assert_eq!(record(&conn, &id).what, "");
+
+    // Redo checks the undone state the same way
+    apply(
+        &mut conn,
+        &cas,
+        json!({ "tag": "EttleUpdate", "ettle_id": id, "title": "Spec v2" }),
+    );
+    undo(&mut conn, &cas).unwrap();
+    conn.execute("UPDATE ettles SET title = 'Edited' WHERE id = ?1", [&id])
+        .unwrap();
+    let err = redo(&mut conn, &cas).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::HeadMismatch);
+    assert_eq!(record(&conn, &id).title, "Edited");
+}
+
+#[test]
+fn test_failed_command_log_insert_rolls_back_the_whole_command() {
+    let (_dir, mut conn, cas) = setup();
+    conn.execute_batch(
+        "CREATE TRIGGER reject_log BEFORE INSERT ON command_log
+         BEGIN SELECT RAISE(ABORT, 'rejected'); END;",
+    )
+    .unwrap();
+
+    let err = apply_command(
+        serde_json::from_value::<Command>(json!({ "tag": "EttleCreate", "title": "A" })).unwrap(),
+        None,
+        &mut conn,
+        &cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .unwrap_err();
+
+    assert_eq!(err.kind(), ExErrorKind::Persistence);
+    for table in [
+        "ettles",
+        "provenance_events",
+        "command_journal",
+        "command_log",
+    ] {
+        let n: i64 = conn
+            .query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(n, 0, "{} must be rolled back", table);
+    }
+}
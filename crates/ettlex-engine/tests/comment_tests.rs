@@ -0,0 +1,291 @@
+//! Comment thread tests — add/reply/resolve/reopen commands and the
+//! `CommentGet` / `CommentList` queries.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_engine::commands::command::{apply_command, Command, CommandResult};
+use ettlex_engine::commands::engine_query::{apply_engine_query, EngineQuery, EngineQueryResult};
+use ettlex_engine::commands::read_tools::{CommentPage, ListOptions};
+use ettlex_store::cas::FsStore;
+use ettlex_store::migrations::apply_migrations;
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+// ---------------------------------------------------------------------------
+// Test helpers
+// ---------------------------------------------------------------------------
+
+fn setup_db_with_cas() -> (Connection, FsStore, TempDir) {
+    let dir = TempDir::new().expect("temp dir");
+    let cas = FsStore::new(dir.path().join("cas"));
+    let mut conn = Connection::open_in_memory().expect("in-memory db");
+    apply_migrations(&mut conn).expect("migrations should apply");
+    (conn, cas, dir)
+}
+
+fn apply(conn: &mut Connection, cas: &FsStore, cmd: Command) -> Result<CommandResult, ExError> {
+    apply_command(
+        cmd,
+        None,
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .map(|(res, _sv)| res)
+}
+
+fn create_ettle(conn: &mut Connection, cas: &FsStore, title: &str) -> String {
+    match apply(
+        conn,
+        cas,
+        Command::EttleCreate {
+            title: title.to_string(),
+            ettle_id: None,
+            why: None,
+            what: None,
+            how: None,
+            reasoning_link_id: None,
+            reasoning_link_type: None,
+        },
+    )
+    .expect("ettle create should succeed")
+    {
+        CommandResult::EttleCreate { ettle_id } => ettle_id,
+        _ => panic!("unexpected result"),
+    }
+}
+
+fn add_comment(
+    conn: &mut Connection,
+    cas: &FsStore,
+    target_id: &str,
+    body: &str,
+    parent: Option<&str>,
+) -> Result<String, ExError> {
+    match apply(
+        conn,
+        cas,
+        Command::CommentAdd {
+            target_kind: "ettle".to_string(),
+            target_id: target_id.to_string(),
+            body: body.to_string(),
+            author: Some("reviewer".to_string()),
+            parent_comment_id: parent.map(String::from),
+        },
+    )? {
+        CommandResult::CommentAdd { comment_id } => Ok(comment_id),
+        _ => panic!("unexpected result"),
+    }
+}
+
+fn list(
+    conn: &Connection,
+    cas: &FsStore,
+    target_id: &str,
+    include_resolved: bool,
+    options: ListOptions,
+) -> CommentPage {
+    match apply_engine_query(
+        EngineQuery::CommentList {
+            target_kind: "ettle".to_string(),
+            target_id: target_id.to_string(),
+            include_resolved,
+            options,
+        },
+        conn,
+        cas,
+        None,
+    )
+    .unwrap()
+    {
+        EngineQueryResult::CommentList(page) => page,
+        _ => panic!("expected CommentList"),
+    }
+}
+
+fn bodies(page: &CommentPage) -> Vec<&str> {
+    page.items.iter().map(|c| c.body.as_str()).collect()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_comment_thread_add_reply_and_get() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let e = create_ettle(&mut conn, &cas, "Payments API");
+
+    let root = add_comment(&mut conn, &cas, &e, "Why REST?", None).unwrap();
+    let reply = add_comment(&mut conn, &cas, &e, "Tooling.", Some(&root)).unwrap();
+
+    let got = match apply_engine_query(
+        EngineQuery::CommentGet {
+            comment_id: reply.clone(),
+        },
+        &conn,
+        &cas,
+        None,
+    )
+    .unwrap()
+    {
+        EngineQueryResult::CommentGet(c) => c,
+        _ => panic!("expected CommentGet"),
+    };
+    assert_eq!(got.parent_comment_id.as_deref(), Some(root.as_str()));
+    assert_eq!(got.author.as_deref(), Some("reviewer"));
+
+    let page = list(&conn, &cas, &e, false, ListOptions::default());
+    assert_eq!(bodies(&page), vec!["Why REST?", "Tooling."]);
+}
+
+#[test]
+fn test_resolved_threads_hidden_by_default_and_reopenable() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let e = create_ettle(&mut conn, &cas, "A");
+    let first = add_comment(&mut conn, &cas, &e, "first", None).unwrap();
+    add_comment(&mut conn, &cas, &e, "first-reply", Some(&first)).unwrap();
+    add_comment(&mut conn, &cas, &e, "second", None).unwrap();
+
+    apply(
+        &mut conn,
+        &cas,
+        Command::CommentResolve {
+            comment_id: first.clone(),
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        bodies(&list(&conn, &cas, &e, false, ListOptions::default())),
+        vec!["second"]
+    );
+    assert_eq!(
+        list(&conn, &cas, &e, true, ListOptions::default())
+            .items
+            .len(),
+        3
+    );
+
+    apply(
+        &mut conn,
+        &cas,
+        Command::CommentReopen { comment_id: first },
+    )
+    .unwrap();
+    assert_eq!(
+        list(&conn, &cas, &e, false, ListOptions::default())
+            .items
+            .len(),
+        3
+    );
+}
+
+#[test]
+fn test_comment_list_paginates() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let e = create_ettle(&mut conn, &cas, "A");
+    for body in ["c1", "c2", "c3"] {
+        add_comment(&mut conn, &cas, &e, body, None).unwrap();
+    }
+
+    let first = list(
+        &conn,
+        &cas,
+        &e,
+        false,
+        ListOptions {
+            limit: Some(2),
+            ..Default::default()
+        },
+    );
+    assert_eq!(bodies(&first), vec!["c1", "c2"]);
+    assert!(first.has_more);
+
+    let second = list(
+        &conn,
+        &cas,
+        &e,
+        false,
+        ListOptions {
+            limit: Some(2),
+            cursor: first.cursor.clone(),
+            ..Default::default()
+        },
+    );
+    assert_eq!(bodies(&second), vec!["c3"]);
+    assert!(!second.has_more);
+}
+
+#[test]
+fn test_comment_add_accepts_ettle_slug() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let e = create_ettle(&mut conn, &cas, "Payments API");
+    add_comment(&mut conn, &cas, "payments-api", "via slug", None).unwrap();
+    assert_eq!(
+        bodies(&list(&conn, &cas, &e, false, ListOptions::default())),
+        vec!["via slug"]
+    );
+}
+
+#[test]
+fn test_comment_validation_errors() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let e = create_ettle(&mut conn, &cas, "A");
+    let other = create_ettle(&mut conn, &cas, "B");
+    let root = add_comment(&mut conn, &cas, &e, "root", None).unwrap();
+    let reply = add_comment(&mut conn, &cas, &e, "reply", Some(&root)).unwrap();
+
+    let err = add_comment(&mut conn, &cas, &e, "   ", None).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+
+    // Replies nest one level deep and stay on the thread's target
+    let err = add_comment(&mut conn, &cas, &e, "nested", Some(&reply)).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+    let err = add_comment(&mut conn, &cas, &other, "moved", Some(&root)).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+
+    let err = apply(
+        &mut conn,
+        &cas,
+        Command::CommentAdd {
+            target_kind: "widget".to_string(),
+            target_id: "w:1".to_string(),
+            body: "x".to_string(),
+            author: None,
+            parent_comment_id: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidTargetKind);
+
+    let err = apply(
+        &mut conn,
+        &cas,
+        Command::CommentAdd {
+            target_kind: "decision".to_string(),
+            target_id: "d:missing".to_string(),
+            body: "x".to_string(),
+            author: None,
+            parent_comment_id: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+
+    // Only roots carry resolution status
+    let err = apply(
+        &mut conn,
+        &cas,
+        Command::CommentResolve { comment_id: reply },
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+
+    let err = apply(&mut conn, &cas, Command::CommentReopen { comment_id: root }).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+}
@@ -0,0 +1,407 @@
+//! ConstraintAttachBulk tests.
+//!
+//! Tests cover: subtree, group and title selectors, skipping existing
+//! attachments, computed ordinals, and all-or-nothing failure on cycles,
+//! plus the `ConstraintListAttachments` usage query.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_engine::commands::command::{apply_command, Command, CommandResult};
+use ettlex_engine::commands::constraint::TargetSelector;
+use ettlex_engine::commands::engine_query::{apply_engine_query, EngineQuery, EngineQueryResult};
+use ettlex_engine::commands::read_tools::ConstraintAttachment;
+use ettlex_store::cas::FsStore;
+use ettlex_store::migrations::apply_migrations;
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+// ---------------------------------------------------------------------------
+// Test helpers
+// ---------------------------------------------------------------------------
+
+fn setup_db_with_cas() -> (Connection, FsStore, TempDir) {
+    let dir = TempDir::new().expect("temp dir");
+    let cas = FsStore::new(dir.path().join("cas"));
+    let mut conn = Connection::open_in_memory().expect("in-memory db");
+    apply_migrations(&mut conn).expect("migrations should apply");
+    (conn, cas, dir)
+}
+
+fn apply(conn: &mut Connection, cas: &FsStore, cmd: Command) -> Result<CommandResult, ExError> {
+    apply_command(
+        cmd,
+        None,
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .map(|(res, _sv)| res)
+}
+
+fn create_ettle(conn: &mut Connection, cas: &FsStore, title: &str) -> String {
+    let res = apply(
+        conn,
+        cas,
+        Command::EttleCreate {
+            title: title.to_string(),
+            ettle_id: None,
+            why: None,
+            what: None,
+            how: None,
+            reasoning_link_id: None,
+            reasoning_link_type: None,
+        },
+    )
+    .expect("ettle create should succeed");
+    match res {
+        CommandResult::EttleCreate { ettle_id } => ettle_id,
+        _ => panic!("unexpected result"),
+    }
+}
+
+fn relate(conn: &mut Connection, cas: &FsStore, src: &str, tgt: &str, rel_type: &str) {
+    apply(
+        conn,
+        cas,
+        Command::RelationCreate {
+            source_ettle_id: src.to_string(),
+            target_ettle_id: tgt.to_string(),
+            relation_type: rel_type.to_string(),
+            properties_json: None,
+            relation_id: None,
+        },
+    )
+    .expect("relation create should succeed");
+}
+
+/// `(relation_ids, attached_ettle_ids, skipped_ettle_ids)`
+type BulkOutcome = (Vec<String>, Vec<String>, Vec<String>);
+
+fn attach_bulk(
+    conn: &mut Connection,
+    cas: &FsStore,
+    constraint: &str,
+    selector: TargetSelector,
+) -> Result<BulkOutcome, ExError> {
+    match apply(
+        conn,
+        cas,
+        Command::ConstraintAttachBulk {
+            constraint_ettle_id: constraint.to_string(),
+            selector,
+            properties_json: Some(serde_json::json!({ "family": "security" })),
+        },
+    )? {
+        CommandResult::ConstraintAttachBulk {
+            relation_ids,
+            attached_ettle_ids,
+            skipped_ettle_ids,
+        } => Ok((relation_ids, attached_ettle_ids, skipped_ettle_ids)),
+        _ => panic!("unexpected result"),
+    }
+}
+
+fn sorted(mut v: Vec<String>) -> Vec<String> {
+    v.sort();
+    v
+}
+
+// ---------------------------------------------------------------------------
+// Selectors
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_attach_bulk_subtree_selects_refinement_descendants() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let root = create_ettle(&mut conn, &cas, "Root");
+    let child = create_ettle(&mut conn, &cas, "Child");
+    let grandchild = create_ettle(&mut conn, &cas, "Grandchild");
+    let unrelated = create_ettle(&mut conn, &cas, "Unrelated");
+    let constraint = create_ettle(&mut conn, &cas, "Constraint");
+    relate(&mut conn, &cas, &root, &child, "refinement");
+    relate(&mut conn, &cas, &child, &grandchild, "refinement");
+
+    let (relation_ids, attached, skipped) = attach_bulk(
+        &mut conn,
+        &cas,
+        &constraint,
+        TargetSelector::Subtree {
+            root_ettle_id: root.clone(),
+        },
+    )
+    .unwrap();
+
+    assert_eq!(relation_ids.len(), 3);
+    assert_eq!(attached, sorted(vec![root, child, grandchild]));
+    assert!(skipped.is_empty());
+    assert!(!attached.contains(&unrelated));
+}
+
+#[test]
+fn test_attach_bulk_group_selector() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let a = create_ettle(&mut conn, &cas, "A");
+    let b = create_ettle(&mut conn, &cas, "B");
+    let constraint = create_ettle(&mut conn, &cas, "Constraint");
+    let group_id = match apply(
+        &mut conn,
+        &cas,
+        Command::GroupCreate {
+            name: "Targets".to_string(),
+        },
+    )
+    .unwrap()
+    {
+        CommandResult::GroupCreate { group_id } => group_id,
+        _ => panic!("unexpected result"),
+    };
+    for ettle_id in [&a, &b] {
+        apply(
+            &mut conn,
+            &cas,
+            Command::GroupMemberAdd {
+                group_id: group_id.clone(),
+                ettle_id: ettle_id.clone(),
+            },
+        )
+        .unwrap();
+    }
+
+    let (_, attached, _) = attach_bulk(
+        &mut conn,
+        &cas,
+        &constraint,
+        TargetSelector::Group { group_id },
+    )
+    .unwrap();
+    assert_eq!(attached, sorted(vec![a, b]));
+}
+
+#[test]
+fn test_attach_bulk_title_selector_skips_existing_and_self() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let a = create_ettle(&mut conn, &cas, "Payment API");
+    let b = create_ettle(&mut conn, &cas, "Refund api");
+    create_ettle(&mut conn, &cas, "Ledger");
+    let constraint = create_ettle(&mut conn, &cas, "API rate limits");
+    relate(&mut conn, &cas, &constraint, &a, "constraint");
+
+    let (_, attached, skipped) = attach_bulk(
+        &mut conn,
+        &cas,
+        &constraint,
+        TargetSelector::TitleContains {
+            text: "api".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(attached, vec![b.clone()]);
+    assert_eq!(skipped, vec![a]);
+
+    let props: String = conn
+        .query_row(
+            "SELECT properties_json FROM relations WHERE target_ettle_id = ?1",
+            [&b],
+            |r| r.get(0),
+        )
+        .unwrap();
+    let props: serde_json::Value = serde_json::from_str(&props).unwrap();
+    assert_eq!(props["ordinal"], 1);
+    assert_eq!(props["family"], "security");
+}
+
+#[test]
+fn test_attach_bulk_no_match_is_invalid_input() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let constraint = create_ettle(&mut conn, &cas, "Constraint");
+    let err = attach_bulk(
+        &mut conn,
+        &cas,
+        &constraint,
+        TargetSelector::TitleContains {
+            text: "nothing".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+}
+
+// ---------------------------------------------------------------------------
+// Atomicity
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_attach_bulk_cycle_writes_nothing() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let root = create_ettle(&mut conn, &cas, "Root");
+    let child = create_ettle(&mut conn, &cas, "Child");
+    let constraint = create_ettle(&mut conn, &cas, "Constraint");
+    relate(&mut conn, &cas, &root, &child, "refinement");
+    // child already constrains the constraint ettle: attaching back would cycle
+    relate(&mut conn, &cas, &child, &constraint, "constraint");
+
+    let before: i64 = conn
+        .query_row("SELECT COUNT(*) FROM relations", [], |r| r.get(0))
+        .unwrap();
+    let err = attach_bulk(
+        &mut conn,
+        &cas,
+        &constraint,
+        TargetSelector::Subtree {
+            root_ettle_id: root,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::CycleDetected);
+
+    let after: i64 = conn
+        .query_row("SELECT COUNT(*) FROM relations", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(before, after);
+}
+
+#[test]
+fn test_attach_bulk_command_deserialises_from_json() {
+    let cmd: Command = serde_json::from_value(serde_json::json!({
+        "tag": "ConstraintAttachBulk",
+        "constraint_ettle_id": "ettle:c",
+        "selector": { "kind": "subtree", "root_ettle_id": "ettle:r" }
+    }))
+    .unwrap();
+    match cmd {
+        Command::ConstraintAttachBulk { selector, .. } => assert_eq!(
+            selector,
+            TargetSelector::Subtree {
+                root_ettle_id: "ettle:r".to_string()
+            }
+        ),
+        _ => panic!("unexpected command"),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// ConstraintListAttachments
+// ---------------------------------------------------------------------------
+
+fn list_attachments(
+    conn: &Connection,
+    cas: &FsStore,
+    constraint: &str,
+    include_tombstoned: bool,
+) -> Result<Vec<ConstraintAttachment>, ExError> {
+    match apply_engine_query(
+        EngineQuery::ConstraintListAttachments {
+            constraint_id: constraint.to_string(),
+            include_tombstoned,
+        },
+        conn,
+        cas,
+        None,
+    )? {
+        EngineQueryResult::ConstraintListAttachments(v) => Ok(v),
+        _ => panic!("unexpected result"),
+    }
+}
+
+#[test]
+fn test_list_attachments_reports_targets_in_attachment_order() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let constraint = create_ettle(&mut conn, &cas, "No plaintext secrets");
+    let api = create_ettle(&mut conn, &cas, "Payments API");
+    let worker = create_ettle(&mut conn, &cas, "Payments worker");
+    let other = create_ettle(&mut conn, &cas, "Billing");
+
+    attach_bulk(
+        &mut conn,
+        &cas,
+        &constraint,
+        TargetSelector::TitleContains {
+            text: "payments".to_string(),
+        },
+    )
+    .unwrap();
+    attach_bulk(
+        &mut conn,
+        &cas,
+        &constraint,
+        TargetSelector::TitleContains {
+            text: "billing".to_string(),
+        },
+    )
+    .unwrap();
+
+    let attachments = list_attachments(&conn, &cas, &constraint, false).unwrap();
+    let mut first_batch = vec![api, worker];
+    first_batch.sort();
+    let expected: Vec<String> = first_batch.into_iter().chain([other]).collect();
+    assert_eq!(
+        attachments
+            .iter()
+            .map(|a| a.ettle_id.clone())
+            .collect::<Vec<_>>(),
+        expected
+    );
+    assert_eq!(
+        attachments.iter().map(|a| a.ordinal).collect::<Vec<_>>(),
+        vec![Some(0), Some(1), Some(2)]
+    );
+    assert_eq!(attachments[2].ettle_title, "Billing");
+    assert_eq!(attachments[0].properties_json["family"], "security");
+    assert!(attachments.iter().all(|a| !a.ettle_tombstoned));
+}
+
+#[test]
+fn test_list_attachments_tombstoned_only_on_request() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let constraint = create_ettle(&mut conn, &cas, "Constraint");
+    let a = create_ettle(&mut conn, &cas, "Target A");
+    let b = create_ettle(&mut conn, &cas, "Target B");
+    let (relation_ids, _, _) = attach_bulk(
+        &mut conn,
+        &cas,
+        &constraint,
+        TargetSelector::TitleContains {
+            text: "target".to_string(),
+        },
+    )
+    .unwrap();
+    apply(
+        &mut conn,
+        &cas,
+        Command::RelationTombstone {
+            relation_id: relation_ids[0].clone(),
+        },
+    )
+    .unwrap();
+
+    let active = list_attachments(&conn, &cas, &constraint, false).unwrap();
+    assert_eq!(active.len(), 1);
+    assert!(active[0].tombstoned_at.is_none());
+
+    let all = list_attachments(&conn, &cas, &constraint, true).unwrap();
+    assert_eq!(
+        sorted(all.iter().map(|x| x.ettle_id.clone()).collect()),
+        sorted(vec![a, b])
+    );
+    assert_eq!(all.iter().filter(|x| x.tombstoned_at.is_some()).count(), 1);
+}
+
+#[test]
+fn test_list_attachments_unknown_constraint_is_not_found() {
+    let (conn, cas, _dir) = setup_db_with_cas();
+    let err = list_attachments(&conn, &cas, "ettle:missing", false).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+}
+
+#[test]
+fn test_list_attachments_empty_for_unattached_constraint() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let constraint = create_ettle(&mut conn, &cas, "Constraint");
+    assert!(list_attachments(&conn, &cas, &constraint, true)
+        .unwrap()
+        .is_empty());
+}
@@ -0,0 +1,259 @@
+//! EttleEffectiveConstraints tests.
+//!
+//! Tests cover: inheritance along the refinement path, `scope: "self"`,
+//! nearest-attachment shadowing, family overrides and unknown Ettles.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_engine::commands::command::{apply_command, Command, CommandResult};
+use ettlex_engine::commands::engine_query::{apply_engine_query, EngineQuery, EngineQueryResult};
+use ettlex_engine::commands::read_tools::{EffectiveConstraints, ExclusionReason};
+use ettlex_store::cas::FsStore;
+use ettlex_store::migrations::apply_migrations;
+use rusqlite::Connection;
+use serde_json::json;
+use tempfile::TempDir;
+
+// ---------------------------------------------------------------------------
+// Test helpers
+// ---------------------------------------------------------------------------
+
+fn setup_db_with_cas() -> (Connection, FsStore, TempDir) {
+    let dir = TempDir::new().expect("temp dir");
+    let cas = FsStore::new(dir.path().join("cas"));
+    let mut conn = Connection::open_in_memory().expect("in-memory db");
+    apply_migrations(&mut conn).expect("migrations should apply");
+    (conn, cas, dir)
+}
+
+fn apply(conn: &mut Connection, cas: &FsStore, cmd: Command) -> CommandResult {
+    apply_command(
+        cmd,
+        None,
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .map(|(res, _sv)| res)
+    .expect("command should succeed")
+}
+
+fn create_ettle(conn: &mut Connection, cas: &FsStore, title: &str) -> String {
+    match apply(
+        conn,
+        cas,
+        Command::EttleCreate {
+            title: title.to_string(),
+            ettle_id: None,
+            why: None,
+            what: None,
+            how: None,
+            reasoning_link_id: None,
+            reasoning_link_type: None,
+        },
+    ) {
+        CommandResult::EttleCreate { ettle_id } => ettle_id,
+        _ => panic!("unexpected result"),
+    }
+}
+
+fn relate(
+    conn: &mut Connection,
+    cas: &FsStore,
+    src: &str,
+    tgt: &str,
+    rel_type: &str,
+    properties: Option<serde_json::Value>,
+) -> String {
+    match apply(
+        conn,
+        cas,
+        Command::RelationCreate {
+            source_ettle_id: src.to_string(),
+            target_ettle_id: tgt.to_string(),
+            relation_type: rel_type.to_string(),
+            properties_json: properties,
+            relation_id: None,
+        },
+    ) {
+        CommandResult::RelationCreate { relation_id } => relation_id,
+        _ => panic!("unexpected result"),
+    }
+}
+
+fn effective(
+    conn: &Connection,
+    cas: &FsStore,
+    ettle_id: &str,
+) -> Result<EffectiveConstraints, ExError> {
+    match apply_engine_query(
+        EngineQuery::EttleEffectiveConstraints {
+            ettle_id: ettle_id.to_string(),
+        },
+        conn,
+        cas,
+        None,
+    )? {
+        EngineQueryResult::EttleEffectiveConstraints(r) => Ok(r),
+        _ => panic!("unexpected result"),
+    }
+}
+
+/// Root → Service → Endpoint refinement chain.
+fn chain(conn: &mut Connection, cas: &FsStore) -> (String, String, String) {
+    let root = create_ettle(conn, cas, "Platform");
+    let service = create_ettle(conn, cas, "Payments service");
+    let endpoint = create_ettle(conn, cas, "Refund endpoint");
+    relate(conn, cas, &root, &service, "refinement", None);
+    relate(conn, cas, &service, &endpoint, "refinement", None);
+    (root, service, endpoint)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_effective_constraints_inherit_along_the_path() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let (root, service, endpoint) = chain(&mut conn, &cas);
+    let audit = create_ettle(&mut conn, &cas, "Audit every write");
+    let latency = create_ettle(&mut conn, &cas, "p99 under 200ms");
+    relate(&mut conn, &cas, &audit, &root, "constraint", None);
+    relate(&mut conn, &cas, &latency, &endpoint, "constraint", None);
+
+    let result = effective(&conn, &cas, &endpoint).unwrap();
+    assert_eq!(result.path, vec![root.clone(), service, endpoint.clone()]);
+    let ids: Vec<(&str, &str, bool)> = result
+        .constraints
+        .iter()
+        .map(|c| {
+            (
+                c.constraint_id.as_str(),
+                c.attached_to.as_str(),
+                c.inherited,
+            )
+        })
+        .collect();
+    assert_eq!(
+        ids,
+        vec![
+            (audit.as_str(), root.as_str(), true),
+            (latency.as_str(), endpoint.as_str(), false)
+        ]
+    );
+    assert_eq!(result.constraints[0].constraint_title, "Audit every write");
+    assert!(result.excluded.is_empty());
+
+    // The root itself only sees its own attachment
+    let at_root = effective(&conn, &cas, &root).unwrap();
+    assert_eq!(at_root.path, vec![root]);
+    assert_eq!(at_root.constraints.len(), 1);
+    assert!(!at_root.constraints[0].inherited);
+}
+
+#[test]
+fn test_self_scoped_attachments_are_not_inherited() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let (_root, service, endpoint) = chain(&mut conn, &cas);
+    let review = create_ettle(&mut conn, &cas, "Architecture review");
+    relate(
+        &mut conn,
+        &cas,
+        &review,
+        &service,
+        "constraint",
+        Some(json!({ "scope": "self" })),
+    );
+
+    let result = effective(&conn, &cas, &endpoint).unwrap();
+    assert!(result.constraints.is_empty());
+    assert_eq!(result.excluded.len(), 1);
+    assert_eq!(result.excluded[0].reason, ExclusionReason::ScopedToSelf);
+
+    let at_service = effective(&conn, &cas, &service).unwrap();
+    assert_eq!(at_service.constraints.len(), 1);
+}
+
+#[test]
+fn test_nearest_attachment_shadows_and_overrides_by_family() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let (root, service, endpoint) = chain(&mut conn, &cas);
+    let audit = create_ettle(&mut conn, &cas, "Audit every write");
+    let tls12 = create_ettle(&mut conn, &cas, "TLS 1.2+");
+    let tls13 = create_ettle(&mut conn, &cas, "TLS 1.3 only");
+
+    relate(&mut conn, &cas, &audit, &root, "constraint", None);
+    let near_audit = relate(
+        &mut conn,
+        &cas,
+        &audit,
+        &endpoint,
+        "constraint",
+        Some(json!({ "note": "refunds included" })),
+    );
+    relate(
+        &mut conn,
+        &cas,
+        &tls12,
+        &root,
+        "constraint",
+        Some(json!({ "family": "transport" })),
+    );
+    let override_rel = relate(
+        &mut conn,
+        &cas,
+        &tls13,
+        &service,
+        "constraint",
+        Some(json!({ "family": "transport", "override": true })),
+    );
+
+    let result = effective(&conn, &cas, &endpoint).unwrap();
+    let in_force: Vec<&str> = result
+        .constraints
+        .iter()
+        .map(|c| c.constraint_id.as_str())
+        .collect();
+    assert_eq!(in_force, vec![tls13.as_str(), audit.as_str()]);
+    assert_eq!(result.constraints[1].relation_id, near_audit);
+    assert_eq!(
+        result.constraints[1].properties_json["note"],
+        "refunds included"
+    );
+    assert_eq!(result.constraints[0].family.as_deref(), Some("transport"));
+
+    let mut reasons: Vec<(String, ExclusionReason)> = result
+        .excluded
+        .into_iter()
+        .map(|e| (e.constraint_id, e.reason))
+        .collect();
+    reasons.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut expected = vec![
+        (
+            audit.clone(),
+            ExclusionReason::Shadowed {
+                by_relation_id: near_audit,
+            },
+        ),
+        (
+            tls12.clone(),
+            ExclusionReason::Overridden {
+                by_relation_id: override_rel,
+            },
+        ),
+    ];
+    expected.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(reasons, expected);
+}
+
+#[test]
+fn test_effective_constraints_unknown_ep_is_not_found() {
+    let (conn, cas, _dir) = setup_db_with_cas();
+    let err = effective(&conn, &cas, "ettle:missing").unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+}
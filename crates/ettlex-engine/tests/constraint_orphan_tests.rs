@@ -0,0 +1,216 @@
+//! Dangling constraint attachment tests.
+//!
+//! Tests cover: detach-on-tombstone, the `ConstraintListOrphans` query, the
+//! `ConstraintSweepOrphans` command and the architecture report flag.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::ExError;
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_engine::commands::command::{apply_command, Command, CommandResult};
+use ettlex_engine::commands::constraint::TargetSelector;
+use ettlex_engine::commands::engine_query::{apply_engine_query, EngineQuery, EngineQueryResult};
+use ettlex_store::cas::FsStore;
+use ettlex_store::migrations::apply_migrations;
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+// ---------------------------------------------------------------------------
+// Test helpers
+// ---------------------------------------------------------------------------
+
+fn setup_db_with_cas() -> (Connection, FsStore, TempDir) {
+    let dir = TempDir::new().expect("temp dir");
+    let cas = FsStore::new(dir.path().join("cas"));
+    let mut conn = Connection::open_in_memory().expect("in-memory db");
+    apply_migrations(&mut conn).expect("migrations should apply");
+    (conn, cas, dir)
+}
+
+fn apply(conn: &mut Connection, cas: &FsStore, cmd: Command) -> Result<CommandResult, ExError> {
+    apply_command(
+        cmd,
+        None,
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .map(|(res, _sv)| res)
+}
+
+fn create_ettle(conn: &mut Connection, cas: &FsStore, title: &str) -> String {
+    match apply(
+        conn,
+        cas,
+        Command::EttleCreate {
+            title: title.to_string(),
+            ettle_id: None,
+            why: None,
+            what: None,
+            how: None,
+            reasoning_link_id: None,
+            reasoning_link_type: None,
+        },
+    )
+    .expect("ettle create should succeed")
+    {
+        CommandResult::EttleCreate { ettle_id } => ettle_id,
+        _ => panic!("unexpected result"),
+    }
+}
+
+/// Attach `constraint` to every Ettle titled "Target ..."; returns relation IDs.
+fn attach_to_targets(conn: &mut Connection, cas: &FsStore, constraint: &str) -> Vec<String> {
+    match apply(
+        conn,
+        cas,
+        Command::ConstraintAttachBulk {
+            constraint_ettle_id: constraint.to_string(),
+            selector: TargetSelector::TitleContains {
+                text: "target".to_string(),
+            },
+            properties_json: None,
+        },
+    )
+    .expect("attach should succeed")
+    {
+        CommandResult::ConstraintAttachBulk { relation_ids, .. } => relation_ids,
+        _ => panic!("unexpected result"),
+    }
+}
+
+fn orphan_ids(conn: &Connection, cas: &FsStore) -> Vec<String> {
+    match apply_engine_query(EngineQuery::ConstraintListOrphans, conn, cas, None).unwrap() {
+        EngineQueryResult::ConstraintListOrphans(v) => v.into_iter().map(|r| r.id).collect(),
+        _ => panic!("unexpected result"),
+    }
+}
+
+fn sweep(conn: &mut Connection, cas: &FsStore) -> Vec<String> {
+    match apply(conn, cas, Command::ConstraintSweepOrphans).unwrap() {
+        CommandResult::ConstraintSweepOrphans { relation_ids } => relation_ids,
+        _ => panic!("unexpected result"),
+    }
+}
+
+fn relation_tombstoned(conn: &Connection, relation_id: &str) -> bool {
+    conn.query_row(
+        "SELECT tombstoned_at IS NOT NULL FROM relations WHERE id = ?1",
+        [relation_id],
+        |r| r.get(0),
+    )
+    .unwrap()
+}
+
+fn relation_to(conn: &Connection, target_ettle_id: &str) -> String {
+    conn.query_row(
+        "SELECT id FROM relations WHERE target_ettle_id = ?1",
+        [target_ettle_id],
+        |r| r.get(0),
+    )
+    .unwrap()
+}
+
+/// Tombstone an Ettle behind the engine's back, as older data might be.
+fn tombstone_out_of_band(conn: &Connection, ettle_id: &str) {
+    conn.execute(
+        "UPDATE ettles SET tombstoned_at = '2026-01-01T00:00:00Z' WHERE id = ?1",
+        [ettle_id],
+    )
+    .unwrap();
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_tombstoning_governed_ettle_detaches_constraints() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let constraint = create_ettle(&mut conn, &cas, "Constraint");
+    let target = create_ettle(&mut conn, &cas, "Target A");
+    let relation_ids = attach_to_targets(&mut conn, &cas, &constraint);
+
+    apply(
+        &mut conn,
+        &cas,
+        Command::EttleTombstone {
+            ettle_id: target.clone(),
+        },
+    )
+    .unwrap();
+    assert!(relation_tombstoned(&conn, &relation_ids[0]));
+    assert!(orphan_ids(&conn, &cas).is_empty());
+
+    // Restoring the Ettle does not reattach the constraint
+    apply(&mut conn, &cas, Command::EttleRestore { ettle_id: target }).unwrap();
+    assert!(relation_tombstoned(&conn, &relation_ids[0]));
+}
+
+#[test]
+fn test_sweep_tombstones_only_dangling_attachments() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let constraint = create_ettle(&mut conn, &cas, "Constraint");
+    let a = create_ettle(&mut conn, &cas, "Target A");
+    create_ettle(&mut conn, &cas, "Target B");
+    let relation_ids = attach_to_targets(&mut conn, &cas, &constraint);
+    assert_eq!(relation_ids.len(), 2);
+    let dangling = relation_to(&conn, &a);
+
+    tombstone_out_of_band(&conn, &a);
+    assert_eq!(orphan_ids(&conn, &cas), vec![dangling.clone()]);
+
+    assert_eq!(sweep(&mut conn, &cas), vec![dangling.clone()]);
+    assert!(relation_tombstoned(&conn, &dangling));
+    assert_eq!(
+        relation_ids
+            .iter()
+            .filter(|id| !relation_tombstoned(&conn, id))
+            .count(),
+        1
+    );
+    assert!(orphan_ids(&conn, &cas).is_empty());
+
+    // Sweeping a clean store is a no-op
+    assert!(sweep(&mut conn, &cas).is_empty());
+}
+
+#[test]
+fn test_tombstoned_constraint_source_is_dangling() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let constraint = create_ettle(&mut conn, &cas, "Constraint");
+    create_ettle(&mut conn, &cas, "Target A");
+    let relation_ids = attach_to_targets(&mut conn, &cas, &constraint);
+
+    tombstone_out_of_band(&conn, &constraint);
+    assert_eq!(orphan_ids(&conn, &cas), relation_ids);
+}
+
+#[test]
+fn test_architecture_report_flags_dangling_attachments() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let constraint = create_ettle(&mut conn, &cas, "Constraint");
+    let a = create_ettle(&mut conn, &cas, "Target A");
+    attach_to_targets(&mut conn, &cas, &constraint);
+    tombstone_out_of_band(&conn, &a);
+
+    let dangling = |conn: &Connection| match apply_engine_query(
+        EngineQuery::ArchitectureReport {
+            recent_snapshot_limit: 5,
+        },
+        conn,
+        &cas,
+        None,
+    )
+    .unwrap()
+    {
+        EngineQueryResult::ArchitectureReport(r) => r.constraint_coverage.dangling_attachments,
+        _ => panic!("unexpected result"),
+    };
+    assert_eq!(dangling(&conn), 1);
+
+    sweep(&mut conn, &cas);
+    assert_eq!(dangling(&conn), 0);
+}
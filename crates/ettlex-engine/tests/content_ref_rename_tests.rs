@@ -0,0 +1,180 @@
+//! `ContentRefRename` tests: whole-token rewriting, dry-run preview,
+//! per-occurrence selection and selector scoping.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_engine::commands::command::{apply_command_with_options, Command, CommandResult};
+use ettlex_engine::commands::constraint::TargetSelector;
+use ettlex_engine::commands::engine_command::CommandOptions;
+use ettlex_engine::commands::ettle::handle_ettle_get;
+use ettlex_engine::commands::refactor::{ContentField, ContentRefOccurrence, ContentRefSite};
+use ettlex_store::cas::FsStore;
+use ettlex_store::migrations::apply_migrations;
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+// ---------------------------------------------------------------------------
+// Test helpers
+// ---------------------------------------------------------------------------
+
+fn setup() -> (Connection, FsStore, TempDir) {
+    let dir = TempDir::new().expect("temp dir");
+    let cas = FsStore::new(dir.path().join("cas"));
+    let mut conn = Connection::open_in_memory().expect("in-memory db");
+    apply_migrations(&mut conn).expect("migrations should apply");
+    (conn, cas, dir)
+}
+
+fn apply(
+    conn: &mut Connection,
+    cas: &FsStore,
+    cmd: Command,
+    dry_run: bool,
+) -> Result<CommandResult, ExError> {
+    apply_command_with_options(
+        cmd,
+        None,
+        CommandOptions { dry_run },
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .map(|(res, _sv)| res)
+}
+
+fn create_ettle(conn: &mut Connection, cas: &FsStore, title: &str, what: &str) -> String {
+    let cmd = Command::EttleCreate {
+        title: title.to_string(),
+        ettle_id: None,
+        why: None,
+        what: Some(what.to_string()),
+        how: None,
+        reasoning_link_id: None,
+        reasoning_link_type: None,
+    };
+    match apply(conn, cas, cmd, false).expect("ettle create should succeed") {
+        CommandResult::EttleCreate { ettle_id } => ettle_id,
+        _ => panic!("unexpected result"),
+    }
+}
+
+fn rename(
+    conn: &mut Connection,
+    cas: &FsStore,
+    selector: Option<TargetSelector>,
+    only: Option<Vec<ContentRefSite>>,
+    dry_run: bool,
+) -> Result<(Vec<ContentRefOccurrence>, Vec<String>), ExError> {
+    let cmd = Command::ContentRefRename {
+        from: "payments-api".to_string(),
+        to: "billing-api".to_string(),
+        selector,
+        only,
+    };
+    match apply(conn, cas, cmd, dry_run)? {
+        CommandResult::ContentRefRename {
+            occurrences,
+            updated_ettle_ids,
+        } => Ok((occurrences, updated_ettle_ids)),
+        _ => panic!("unexpected result"),
+    }
+}
+
+fn what_of(conn: &Connection, ettle_id: &str) -> String {
+    handle_ettle_get(conn, ettle_id).unwrap().what
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_rename_rewrites_whole_tokens_only() {
+    let (mut conn, cas, _dir) = setup();
+    let id = create_ettle(
+        &mut conn,
+        &cas,
+        "Checkout",
+        "Calls payments-api.\nNot payments-api-2; see (payments-api).",
+    );
+
+    let (occurrences, updated) = rename(&mut conn, &cas, None, None, false).unwrap();
+
+    assert_eq!(occurrences.len(), 2);
+    assert_eq!(occurrences[0].site.field, ContentField::What);
+    assert_eq!(occurrences[1].line, 2);
+    assert_eq!(
+        occurrences[1].context,
+        "Not payments-api-2; see (payments-api)."
+    );
+    assert_eq!(updated, vec![id.clone()]);
+    assert_eq!(
+        what_of(&conn, &id),
+        "Calls billing-api.\nNot payments-api-2; see (billing-api)."
+    );
+}
+
+#[test]
+fn test_dry_run_previews_without_writing() {
+    let (mut conn, cas, _dir) = setup();
+    let id = create_ettle(&mut conn, &cas, "Checkout", "Calls payments-api.");
+
+    let (occurrences, updated) = rename(&mut conn, &cas, None, None, true).unwrap();
+
+    assert_eq!(occurrences.len(), 1);
+    assert!(occurrences[0].rewritten);
+    assert_eq!(updated, vec![id.clone()]);
+    assert_eq!(what_of(&conn, &id), "Calls payments-api.");
+}
+
+#[test]
+fn test_only_rewrites_selected_occurrences_and_rejects_stale_sites() {
+    let (mut conn, cas, _dir) = setup();
+    let id = create_ettle(&mut conn, &cas, "Checkout", "payments-api, payments-api");
+
+    let (preview, _) = rename(&mut conn, &cas, None, None, true).unwrap();
+    let second = preview[1].site.clone();
+    let (occurrences, _) =
+        rename(&mut conn, &cas, None, Some(vec![second.clone()]), false).unwrap();
+
+    assert!(!occurrences[0].rewritten);
+    assert!(occurrences[1].rewritten);
+    assert_eq!(what_of(&conn, &id), "payments-api, billing-api");
+
+    // The accepted site no longer holds the old reference.
+    let err = rename(&mut conn, &cas, None, Some(vec![second]), false).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_selector_limits_scope() {
+    let (mut conn, cas, _dir) = setup();
+    let inside = create_ettle(&mut conn, &cas, "Target checkout", "payments-api");
+    let outside = create_ettle(&mut conn, &cas, "Ledger", "payments-api");
+
+    let selector = TargetSelector::TitleContains {
+        text: "target".to_string(),
+    };
+    let (_, updated) = rename(&mut conn, &cas, Some(selector), None, false).unwrap();
+
+    assert_eq!(updated, vec![inside.clone()]);
+    assert_eq!(what_of(&conn, &inside), "billing-api");
+    assert_eq!(what_of(&conn, &outside), "payments-api");
+}
+
+#[test]
+fn test_identical_from_and_to_rejected() {
+    let (mut conn, cas, _dir) = setup();
+    let cmd = Command::ContentRefRename {
+        from: "payments-api".to_string(),
+        to: "payments-api".to_string(),
+        selector: None,
+        only: None,
+    };
+    let err = apply(&mut conn, &cas, cmd, false).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+}
@@ -0,0 +1,163 @@
+//! Content spillover tests — why/what/how bodies over
+//! `content.max_inline_bytes` are stored in CAS and read back transparently.
+
+#![allow(clippy::unwrap_used, clippy::result_large_err)]
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::ExErrorKind;
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_engine::commands::command::{apply_command, Command, CommandResult};
+use ettlex_engine::commands::content_spill::{hydrate_ettle_content, stored_content_eq};
+use ettlex_engine::commands::engine_query::{apply_engine_query, EngineQuery, EngineQueryResult};
+use ettlex_engine::commands::settings::{settings_set, SETTING_CONTENT_MAX_INLINE_BYTES};
+use ettlex_engine::commands::tree_render::build_render_tree;
+use ettlex_store::cas::reachability::reachable_digests;
+use ettlex_store::cas::FsStore;
+use ettlex_store::model::EttleRecord;
+use ettlex_store::repo::SqliteRepo;
+use rusqlite::Connection;
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+fn setup(limit: Option<u64>) -> (TempDir, Connection, FsStore) {
+    let dir = TempDir::new().unwrap();
+    let mut conn = Connection::open(dir.path().join("test.db")).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    let cas = FsStore::new(dir.path().join("cas"));
+    if let Some(limit) = limit {
+        settings_set(&conn, SETTING_CONTENT_MAX_INLINE_BYTES, Some(json!(limit))).unwrap();
+    }
+    (dir, conn, cas)
+}
+
+fn apply(conn: &mut Connection, cas: &FsStore, cmd: Value) -> CommandResult {
+    apply_command(
+        serde_json::from_value::<Command>(cmd).unwrap(),
+        None,
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .unwrap()
+    .0
+}
+
+fn create(conn: &mut Connection, cas: &FsStore, what: &str) -> String {
+    match apply(
+        conn,
+        cas,
+        json!({ "tag": "EttleCreate", "title": "Big", "why": "short", "what": what }),
+    ) {
+        CommandResult::EttleCreate { ettle_id } => ettle_id,
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+fn record(conn: &Connection, id: &str) -> EttleRecord {
+    SqliteRepo::get_ettle_record(conn, id).unwrap().unwrap()
+}
+
+#[test]
+fn test_large_body_spills_to_cas_and_rehydrates() {
+    let (_dir, mut conn, cas) = setup(Some(16));
+    let body = "x".repeat(100);
+    let id = create(&mut conn, &cas, &body);
+
+    let mut stored = record(&conn, &id);
+    assert_eq!(stored.what, "");
+    assert_eq!(stored.why, "short");
+    assert!(stored.why_digest.is_none());
+    let digest = stored.what_digest.clone().unwrap();
+    assert!(reachable_digests(&conn).unwrap().contains(&digest));
+    assert!(stored_content_eq(&body, &stored.what, Some(&digest)));
+
+    hydrate_ettle_content(&mut stored, &cas).unwrap();
+    assert_eq!(stored.what, body);
+
+    let tree = build_render_tree(&conn, &cas, &id, None).unwrap();
+    assert_eq!(tree.what, body);
+}
+
+#[test]
+fn test_ettle_get_query_rehydrates_spilled_content() {
+    let (_dir, mut conn, cas) = setup(Some(16));
+    let body = "x".repeat(100);
+    let id = create(&mut conn, &cas, &body);
+
+    let query = EngineQuery::EttleGet {
+        ettle_id: id.clone(),
+    };
+    match apply_engine_query(query, &conn, &cas, None).unwrap() {
+        EngineQueryResult::EttleGet(r) => {
+            assert_eq!(r.ettle.id, id);
+            assert_eq!(r.ettle.what, body);
+            assert_eq!(r.ettle.why, "short");
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_update_moves_content_between_row_and_cas() {
+    let (_dir, mut conn, cas) = setup(Some(16));
+    let id = create(&mut conn, &cas, &"x".repeat(100));
+
+    // A title-only update keeps the spilled body
+    apply(
+        &mut conn,
+        &cas,
+        json!({ "tag": "EttleUpdate", "ettle_id": id, "title": "Renamed" }),
+    );
+    assert!(record(&conn, &id).what_digest.is_some());
+
+    apply(
+        &mut conn,
+        &cas,
+        json!({ "tag": "EttleUpdate", "ettle_id": id, "what": "small",
+                "how": "y".repeat(40) }),
+    );
+    let stored = record(&conn, &id);
+    assert_eq!(stored.what, "small");
+    assert!(stored.what_digest.is_none());
+    assert_eq!(stored.how, "");
+    assert!(stored.how_digest.is_some());
+}
+
+#[test]
+fn test_rename_rewrites_spilled_content() {
+    let (_dir, mut conn, cas) = setup(Some(16));
+    let id = create(&mut conn, &cas, "see old-api for the full contract");
+
+    apply(
+        &mut conn,
+        &cas,
+        json!({ "tag": "ContentRefRename", "from": "old-api", "to": "new-api" }),
+    );
+    let mut stored = record(&conn, &id);
+    assert!(stored.what_digest.is_some());
+    hydrate_ettle_content(&mut stored, &cas).unwrap();
+    assert_eq!(stored.what, "see new-api for the full contract");
+}
+
+#[test]
+fn test_no_limit_keeps_content_inline() {
+    let (_dir, mut conn, cas) = setup(None);
+    let body = "x".repeat(100_000);
+    let id = create(&mut conn, &cas, &body);
+
+    let stored = record(&conn, &id);
+    assert_eq!(stored.what, body);
+    assert!(stored.what_digest.is_none());
+}
+
+#[test]
+fn test_missing_blob_is_reported() {
+    let (dir, mut conn, cas) = setup(Some(16));
+    let id = create(&mut conn, &cas, &"x".repeat(100));
+    std::fs::remove_dir_all(dir.path().join("cas")).unwrap();
+
+    let mut stored = record(&conn, &id);
+    let err = hydrate_ettle_content(&mut stored, &cas).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+}
@@ -0,0 +1,236 @@
+//! CSV import tests — tree construction, ordinal
+//! ordering, column mapping, and all-or-nothing imports.
+
+#![allow(clippy::unwrap_used, clippy::result_large_err)]
+
+use ettlex_core::errors::ExErrorKind;
+use ettlex_engine::commands::csv_import::{import_csv, CsvColumnMapping};
+use ettlex_engine::commands::settings::settings_set;
+use ettlex_store::cas::FsStore;
+use ettlex_store::model::RelationListOpts;
+use ettlex_store::repo::SqliteRepo;
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+fn setup() -> (Connection, FsStore, TempDir) {
+    let dir = TempDir::new().unwrap();
+    let mut conn = Connection::open(dir.path().join("store.db")).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    let cas = FsStore::new(dir.path().join("cas"));
+    (conn, cas, dir)
+}
+
+fn count(conn: &Connection, table: &str) -> i64 {
+    conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |r| r.get(0))
+        .unwrap()
+}
+
+/// Refinement children of `parent` in creation order.
+fn children(conn: &Connection, parent: &str) -> Vec<String> {
+    let mut relations = SqliteRepo::list_relations(
+        conn,
+        &RelationListOpts {
+            source_ettle_id: Some(parent.to_string()),
+            relation_type: Some("refinement".to_string()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    relations.sort_by(|a, b| a.id.cmp(&b.id));
+    relations.into_iter().map(|r| r.target_ettle_id).collect()
+}
+
+#[test]
+fn test_import_csv_builds_tree_in_ordinal_order() {
+    let (mut conn, cas, _dir) = setup();
+    let csv = "\
+ref,parent,title,ordinal,why,what,how
+root,,Root,,Because,The system,
+b,root,Second,2,,,
+a,root,First,1,,,
+c,root,Unordered,,,,
+a1,a,Nested,,,,\"Carefully, with commas\"
+";
+    let report = import_csv(
+        &mut conn,
+        &cas,
+        csv.as_bytes(),
+        &CsvColumnMapping::default(),
+    )
+    .unwrap();
+
+    assert_eq!(report.ettles.len(), 5);
+    assert_eq!(report.refinements, 4);
+    let id = |key: &str| {
+        report
+            .ettles
+            .iter()
+            .find(|(k, _)| k.as_deref() == Some(key))
+            .map(|(_, id)| id.clone())
+            .unwrap()
+    };
+    assert_eq!(
+        children(&conn, &id("root")),
+        vec![id("a"), id("b"), id("c")]
+    );
+    assert_eq!(children(&conn, &id("a")), vec![id("a1")]);
+
+    let root = SqliteRepo::get_ettle_record(&conn, &id("root"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(root.why, "Because");
+    let nested = SqliteRepo::get_ettle_record(&conn, &id("a1"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(nested.how, "Carefully, with commas");
+}
+
+#[test]
+fn test_import_csv_attaches_to_existing_ettle_with_custom_mapping() {
+    let (mut conn, cas, _dir) = setup();
+    let existing = import_csv(
+        &mut conn,
+        &cas,
+        "title\nExisting\n".as_bytes(),
+        &CsvColumnMapping::default(),
+    )
+    .unwrap()
+    .ettles[0]
+        .1
+        .clone();
+
+    let mapping: CsvColumnMapping =
+        serde_json::from_str(r#"{ "key": "Req ID", "parent": "Parent", "title": "Summary" }"#)
+            .unwrap();
+    let csv = format!(
+        "Req ID,Parent,Summary\nR-1,{},Child requirement\n",
+        existing
+    );
+    let report = import_csv(&mut conn, &cas, csv.as_bytes(), &mapping).unwrap();
+
+    assert_eq!(children(&conn, &existing), vec![report.ettles[0].1.clone()]);
+}
+
+#[test]
+fn test_import_csv_rolls_back_on_unknown_parent() {
+    let (mut conn, cas, _dir) = setup();
+    let csv = "ref,parent,title\nroot,,Root\nchild,nowhere,Child\n";
+
+    let err = import_csv(
+        &mut conn,
+        &cas,
+        csv.as_bytes(),
+        &CsvColumnMapping::default(),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+    assert_eq!(count(&conn, "ettles"), 0);
+    assert_eq!(count(&conn, "command_log"), 0);
+}
+
+#[test]
+fn test_import_csv_rejects_parentless_rows_in_registered_roots_mode() {
+    let (mut conn, cas, _dir) = setup();
+    let existing = import_csv(
+        &mut conn,
+        &cas,
+        "title\nExisting\n".as_bytes(),
+        &CsvColumnMapping::default(),
+    )
+    .unwrap()
+    .ettles[0]
+        .1
+        .clone();
+    settings_set(&conn, "roots.mode", Some(serde_json::json!("registered"))).unwrap();
+
+    // A blank parent cell would silently create a second root
+    let csv = format!("parent,title\n{},Child\n,Orphan\n", existing);
+    let err = import_csv(
+        &mut conn,
+        &cas,
+        csv.as_bytes(),
+        &CsvColumnMapping::default(),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::RootEttleInvalid);
+    assert_eq!(count(&conn, "ettles"), 1);
+
+    let csv = format!("parent,title\n{},Child\n", existing);
+    import_csv(
+        &mut conn,
+        &cas,
+        csv.as_bytes(),
+        &CsvColumnMapping::default(),
+    )
+    .unwrap();
+    assert_eq!(count(&conn, "ettles"), 2);
+}
+
+#[test]
+fn test_import_csv_rejects_malformed_input() {
+    let (mut conn, cas, _dir) = setup();
+    let mapping = CsvColumnMapping::default();
+
+    let err = import_csv(
+        &mut conn,
+        &cas,
+        "name\nNo title column\n".as_bytes(),
+        &mapping,
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+
+    let err = import_csv(
+        &mut conn,
+        &cas,
+        "ref,title\nx,One\nx,Two\n".as_bytes(),
+        &mapping,
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+
+    let err = import_csv(
+        &mut conn,
+        &cas,
+        "title,ordinal\nOne,first\n".as_bytes(),
+        &mapping,
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidOrdinal);
+
+    let err = import_csv(&mut conn, &cas, "ref,title\nx,\n".as_bytes(), &mapping).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidTitle);
+
+    assert_eq!(count(&conn, "ettles"), 0);
+}
+
+#[test]
+fn test_import_csv_reports_reused_cas_blobs() {
+    let (mut conn, cas, _dir) = setup();
+    settings_set(
+        &conn,
+        "content.max_inline_bytes",
+        Some(serde_json::json!(8)),
+    )
+    .unwrap();
+    let csv = "\
+ref,parent,title,what
+root,,Root,Shared boilerplate body
+a,root,A,Shared boilerplate body
+b,root,B,Distinct body text
+";
+    let report = import_csv(
+        &mut conn,
+        &cas,
+        csv.as_bytes(),
+        &CsvColumnMapping::default(),
+    )
+    .unwrap();
+
+    assert_eq!(report.cas.blobs_written, 2);
+    assert_eq!(report.cas.blobs_reused, 1);
+    assert_eq!(
+        report.cas.bytes_reused,
+        "Shared boilerplate body".len() as u64
+    );
+}
@@ -0,0 +1,183 @@
+//! Decision links to constraints — a decision may target a constraint Ettle
+//! (`target_kind = "constraint"`) to record why the constraint was adopted or
+//! waived. Covers link validation, `DecisionListByTarget`, and the justified
+//! count in the architecture report.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_engine::commands::command::{apply_command, Command, CommandResult};
+use ettlex_engine::commands::decision::{decision_create, decision_link, decision_tombstone};
+use ettlex_engine::commands::engine_query::{apply_engine_query, EngineQuery, EngineQueryResult};
+use ettlex_store::cas::FsStore;
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+fn setup() -> (TempDir, Connection, FsStore) {
+    let dir = TempDir::new().unwrap();
+    let mut conn = Connection::open(dir.path().join("test.db")).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    let cas = FsStore::new(dir.path().join("cas"));
+    (dir, conn, cas)
+}
+
+fn apply(conn: &mut Connection, cas: &FsStore, cmd: Command) -> Result<CommandResult, ExError> {
+    apply_command(
+        cmd,
+        None,
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .map(|(res, _sv)| res)
+}
+
+fn create_ettle(conn: &mut Connection, cas: &FsStore, title: &str) -> String {
+    match apply(
+        conn,
+        cas,
+        Command::EttleCreate {
+            title: title.to_string(),
+            ettle_id: None,
+            why: None,
+            what: None,
+            how: None,
+            reasoning_link_id: None,
+            reasoning_link_type: None,
+        },
+    )
+    .unwrap()
+    {
+        CommandResult::EttleCreate { ettle_id } => ettle_id,
+        _ => panic!("unexpected result"),
+    }
+}
+
+fn attach_constraint(conn: &mut Connection, cas: &FsStore, constraint: &str, target: &str) {
+    apply(
+        conn,
+        cas,
+        Command::RelationCreate {
+            source_ettle_id: constraint.to_string(),
+            target_ettle_id: target.to_string(),
+            relation_type: "constraint".to_string(),
+            properties_json: None,
+            relation_id: None,
+        },
+    )
+    .unwrap();
+}
+
+fn create_decision(conn: &Connection, id: &str) {
+    decision_create(
+        Some(id.to_string()),
+        "Adopt the rule".to_string(),
+        Some("accepted".to_string()),
+        "We adopt the rule.".to_string(),
+        "It prevents outages.".to_string(),
+        None,
+        None,
+        "none".to_string(),
+        None,
+        None,
+        None,
+        conn,
+    )
+    .unwrap();
+}
+
+fn link(conn: &Connection, decision_id: &str, constraint_id: &str) -> ettlex_store::Result<()> {
+    decision_link(
+        decision_id.to_string(),
+        "constraint".to_string(),
+        constraint_id.to_string(),
+        "grounds".to_string(),
+        0,
+        conn,
+    )
+}
+
+#[test]
+fn test_link_to_constraint_ettle_is_listed_by_target() {
+    let (_dir, mut conn, cas) = setup();
+    let rule = create_ettle(&mut conn, &cas, "Rule");
+    create_decision(&conn, "decision:adopt");
+
+    link(&conn, "decision:adopt", &rule).unwrap();
+
+    let result = apply_engine_query(
+        EngineQuery::DecisionListByTarget {
+            target_kind: "constraint".to_string(),
+            target_id: rule.clone(),
+            include_tombstoned: false,
+        },
+        &conn,
+        &cas,
+        None,
+    )
+    .unwrap();
+    let EngineQueryResult::DecisionListByTarget(decisions) = result else {
+        panic!("expected DecisionListByTarget");
+    };
+    assert_eq!(decisions.len(), 1);
+    assert_eq!(decisions[0].decision_id, "decision:adopt");
+
+    // The same link twice is still a duplicate
+    let err = link(&conn, "decision:adopt", &rule).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::DuplicateLink);
+}
+
+#[test]
+fn test_link_to_missing_or_tombstoned_constraint_fails() {
+    let (_dir, mut conn, cas) = setup();
+    create_decision(&conn, "decision:adopt");
+
+    let err = link(&conn, "decision:adopt", "ettle:missing").unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+
+    let retired = create_ettle(&mut conn, &cas, "Retired");
+    apply(
+        &mut conn,
+        &cas,
+        Command::EttleTombstone {
+            ettle_id: retired.clone(),
+        },
+    )
+    .unwrap();
+    let err = link(&conn, "decision:adopt", &retired).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::AlreadyTombstoned);
+}
+
+#[test]
+fn test_report_counts_justified_constraints() {
+    let (_dir, mut conn, cas) = setup();
+    let app = create_ettle(&mut conn, &cas, "App");
+    let rule_a = create_ettle(&mut conn, &cas, "Rule A");
+    let rule_b = create_ettle(&mut conn, &cas, "Rule B");
+    attach_constraint(&mut conn, &cas, &rule_a, &app);
+    attach_constraint(&mut conn, &cas, &rule_b, &app);
+    create_decision(&conn, "decision:a");
+    create_decision(&conn, "decision:b");
+    link(&conn, "decision:a", &rule_a).unwrap();
+    link(&conn, "decision:b", &rule_b).unwrap();
+    // A tombstoned decision no longer justifies its constraint
+    decision_tombstone("decision:b".to_string(), &conn).unwrap();
+
+    let result = apply_engine_query(
+        EngineQuery::ArchitectureReport {
+            recent_snapshot_limit: 1,
+        },
+        &conn,
+        &cas,
+        None,
+    )
+    .unwrap();
+    let EngineQueryResult::ArchitectureReport(report) = result else {
+        panic!("expected ArchitectureReport");
+    };
+    assert_eq!(report.constraint_coverage.constraint_ettles, 2);
+    assert_eq!(report.constraint_coverage.justified_constraints, 1);
+}
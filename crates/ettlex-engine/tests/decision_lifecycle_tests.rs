@@ -0,0 +1,173 @@
+//! Decision lifecycle check: profile mode resolution and head drift for
+//! manifests that carry a `decision_lifecycle` section.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::ExErrorKind;
+use ettlex_core::ops::decision_ops::{attach_decision_to_target, create_decision};
+use ettlex_core::ops::Store;
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_core::snapshot::manifest::generate_manifest_with_input;
+use ettlex_core::snapshot::{apply_decision_lifecycle, DecisionLifecycleMode};
+use ettlex_engine::commands::engine_command::{apply_engine_command, EngineCommand};
+use ettlex_engine::commands::engine_query::{apply_engine_query, EngineQuery, EngineQueryResult};
+use ettlex_engine::commands::heads::HeadDrift;
+use ettlex_engine::snapshot::{decision_lifecycle_mode, manifest_eval_input};
+use ettlex_store::cas::FsStore;
+use ettlex_store::repo::hydration::load_tree;
+use ettlex_store::repo::SqliteRepo;
+use ettlex_store::snapshot::persist::{commit_snapshot, SnapshotOptions};
+use rusqlite::Connection;
+use serde_json::json;
+use tempfile::TempDir;
+
+fn setup() -> (TempDir, Connection, FsStore) {
+    let temp_dir = TempDir::new().unwrap();
+    let mut conn = Connection::open(temp_dir.path().join("test.db")).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    let cas = FsStore::new(temp_dir.path().join("cas"));
+    conn.execute(
+        "INSERT INTO ettles (id, title, created_at, updated_at)
+         VALUES ('ettle:a', 'A', '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z')",
+        [],
+    )
+    .unwrap();
+    (temp_dir, conn, cas)
+}
+
+fn create_profile(
+    conn: &mut Connection,
+    cas: &FsStore,
+    profile_ref: &str,
+    payload: serde_json::Value,
+) {
+    let cmd = EngineCommand::ProfileCreate {
+        profile_ref: profile_ref.to_string(),
+        payload_json: payload,
+        source: None,
+    };
+    apply_engine_command(cmd, conn, cas, &NoopPolicyProvider, &NoopApprovalRouter).unwrap();
+}
+
+/// Link a decision with `status` to `ettle:a` and persist both.
+fn link_decision(conn: &Connection, status: &str) {
+    let mut store = load_tree(conn).unwrap();
+    let id = create_decision(
+        &mut store,
+        Some("d:1".to_string()),
+        "Pick a queue".to_string(),
+        Some(status.to_string()),
+        "Use a queue".to_string(),
+        "Ordering".to_string(),
+        None,
+        None,
+        "none".to_string(),
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+    attach_decision_to_target(
+        &mut store,
+        &id,
+        "ettle".to_string(),
+        "ettle:a".to_string(),
+        "grounds".to_string(),
+        0,
+    )
+    .unwrap();
+    SqliteRepo::persist_decision(conn, store.get_decision(&id).unwrap()).unwrap();
+    for link in store.list_decision_links_for_target("ettle", "ettle:a") {
+        SqliteRepo::persist_decision_link(conn, link).unwrap();
+    }
+}
+
+fn head_drift(conn: &Connection, cas: &FsStore) -> HeadDrift {
+    match apply_engine_query(EngineQuery::HeadsList, conn, cas, None).unwrap() {
+        EngineQueryResult::HeadsList(h) => h.heads[0].drift,
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_mode_resolves_from_named_or_default_profile() {
+    let (_tmp, mut conn, cas) = setup();
+    assert_eq!(
+        decision_lifecycle_mode(&conn, None).unwrap(),
+        DecisionLifecycleMode::Off
+    );
+
+    create_profile(
+        &mut conn,
+        &cas,
+        "profile/base@0",
+        json!({"decision_lifecycle": {"mode": "block"}}),
+    );
+    create_profile(
+        &mut conn,
+        &cas,
+        "profile/child@0",
+        json!({"extends": "profile/base@0"}),
+    );
+    assert_eq!(
+        decision_lifecycle_mode(&conn, Some("profile/child@0")).unwrap(),
+        DecisionLifecycleMode::Block
+    );
+
+    let cmd = EngineCommand::ProfileSetDefault {
+        profile_ref: "profile/base@0".to_string(),
+        environment: None,
+    };
+    apply_engine_command(
+        cmd,
+        &mut conn,
+        &cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .unwrap();
+    assert_eq!(
+        decision_lifecycle_mode(&conn, None).unwrap(),
+        DecisionLifecycleMode::Block
+    );
+
+    let err = decision_lifecycle_mode(&conn, Some("profile/missing@0")).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::ProfileNotFound);
+}
+
+#[test]
+fn test_head_with_flagged_section_drifts_when_decision_is_accepted() {
+    let (_tmp, mut conn, cas) = setup();
+    link_decision(&conn, "proposed");
+
+    let store = load_tree(&conn).unwrap();
+    let ept = vec!["ettle:a".to_string()];
+    let input = manifest_eval_input(&conn, &cas, &ept, "profile/default@0").unwrap();
+    let mut manifest = generate_manifest_with_input(
+        ept,
+        "policy/default@0".into(),
+        "profile/default@0".into(),
+        "ettle:a".into(),
+        "0001".into(),
+        None,
+        &Store::new(),
+        &input,
+    )
+    .unwrap();
+    apply_decision_lifecycle(&mut manifest, &store, DecisionLifecycleMode::Flag).unwrap();
+    assert_eq!(
+        manifest.decision_lifecycle.as_ref().unwrap().proposed.len(),
+        1
+    );
+    commit_snapshot(&mut conn, &cas, manifest, SnapshotOptions::default()).unwrap();
+
+    assert_eq!(head_drift(&conn, &cas), HeadDrift::InSync);
+
+    conn.execute(
+        "UPDATE decisions SET status = 'accepted' WHERE decision_id = 'd:1'",
+        [],
+    )
+    .unwrap();
+    assert_eq!(head_drift(&conn, &cas), HeadDrift::Drifted);
+}
@@ -0,0 +1,185 @@
+//! Integration tests for `EngineQuery::DecisionSearch`.
+
+use ettlex_core::errors::ExErrorKind;
+use ettlex_engine::commands::decision::decision_create;
+use ettlex_engine::commands::engine_query::{apply_engine_query, EngineQuery, EngineQueryResult};
+use ettlex_engine::commands::read_tools::{DecisionPage, ListOptions};
+use ettlex_store::cas::FsStore;
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+fn setup() -> (TempDir, Connection, FsStore) {
+    let temp_dir = TempDir::new().unwrap();
+    let mut conn = Connection::open(temp_dir.path().join("test.db")).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    let cas = FsStore::new(temp_dir.path().join("cas"));
+    (temp_dir, conn, cas)
+}
+
+fn create(conn: &Connection, id: &str, title: &str, status: &str, text: &str, rationale: &str) {
+    decision_create(
+        Some(id.to_string()),
+        title.to_string(),
+        Some(status.to_string()),
+        text.to_string(),
+        rationale.to_string(),
+        None,
+        None,
+        "none".to_string(),
+        None,
+        None,
+        None,
+        conn,
+    )
+    .unwrap();
+}
+
+fn seed(conn: &Connection) {
+    create(
+        conn,
+        "d:1",
+        "Adopt Postgres",
+        "accepted",
+        "Use a relational store.",
+        "Mature.",
+    );
+    create(
+        conn,
+        "d:2",
+        "Caching layer",
+        "proposed",
+        "Add a POSTGRES read replica.",
+        "Load.",
+    );
+    create(
+        conn,
+        "d:3",
+        "Logging",
+        "accepted",
+        "Structured logs.",
+        "Postgres audit needs it.",
+    );
+    create(
+        conn,
+        "d:4",
+        "Frontend",
+        "accepted",
+        "Use React.",
+        "Team skills.",
+    );
+}
+
+fn search(
+    conn: &Connection,
+    cas: &FsStore,
+    text: &str,
+    status: Option<&str>,
+    options: ListOptions,
+) -> DecisionPage {
+    match apply_engine_query(
+        EngineQuery::DecisionSearch {
+            text: text.to_string(),
+            status_filter: status.map(String::from),
+            options,
+        },
+        conn,
+        cas,
+        None,
+    )
+    .unwrap()
+    {
+        EngineQueryResult::DecisionSearch(page) => page,
+        _ => panic!("expected DecisionSearch"),
+    }
+}
+
+fn ids(page: &DecisionPage) -> Vec<&str> {
+    page.items.iter().map(|d| d.decision_id.as_str()).collect()
+}
+
+#[test]
+fn test_search_matches_title_text_and_rationale_case_insensitively() {
+    let (_tmp, conn, cas) = setup();
+    seed(&conn);
+
+    let page = search(&conn, &cas, "postgres", None, ListOptions::default());
+    assert_eq!(ids(&page), vec!["d:1", "d:2", "d:3"]);
+    assert!(!page.has_more);
+}
+
+#[test]
+fn test_search_status_filter() {
+    let (_tmp, conn, cas) = setup();
+    seed(&conn);
+
+    let page = search(
+        &conn,
+        &cas,
+        "postgres",
+        Some("accepted"),
+        ListOptions::default(),
+    );
+    assert_eq!(ids(&page), vec!["d:1", "d:3"]);
+}
+
+#[test]
+fn test_search_paginates_with_cursor() {
+    let (_tmp, conn, cas) = setup();
+    seed(&conn);
+
+    let first = search(
+        &conn,
+        &cas,
+        "postgres",
+        None,
+        ListOptions {
+            limit: Some(2),
+            ..Default::default()
+        },
+    );
+    assert_eq!(first.items.len(), 2);
+    assert!(first.has_more);
+
+    let second = search(
+        &conn,
+        &cas,
+        "postgres",
+        None,
+        ListOptions {
+            limit: Some(2),
+            cursor: first.cursor.clone(),
+            ..Default::default()
+        },
+    );
+    let mut all: Vec<&str> = ids(&first);
+    all.extend(ids(&second));
+    assert_eq!(all, vec!["d:1", "d:2", "d:3"]);
+    assert!(!second.has_more);
+}
+
+#[test]
+fn test_search_treats_like_wildcards_literally() {
+    let (_tmp, conn, cas) = setup();
+    seed(&conn);
+
+    let page = search(&conn, &cas, "%", None, ListOptions::default());
+    assert!(page.items.is_empty());
+}
+
+#[test]
+fn test_search_empty_text_is_invalid() {
+    let (_tmp, conn, cas) = setup();
+
+    let err = apply_engine_query(
+        EngineQuery::DecisionSearch {
+            text: "  ".to_string(),
+            status_filter: None,
+            options: ListOptions::default(),
+        },
+        &conn,
+        &cas,
+        None,
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+}
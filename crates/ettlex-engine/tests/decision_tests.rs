@@ -1,8 +1,8 @@
 // Integration tests for decision command handlers.
-// Covers create, update, tombstone, link, unlink, and supersede operations.
+// Covers create, update, tombstone, restore, link, unlink, and supersede operations.
 
 use ettlex_engine::commands::decision::{
-    decision_create, decision_supersede, decision_tombstone, decision_update,
+    decision_create, decision_restore, decision_supersede, decision_tombstone, decision_update,
 };
 use rusqlite::Connection;
 use tempfile::TempDir;
@@ -192,6 +192,67 @@ fn test_decision_tombstone_nonexistent_fails() {
     assert!(result.is_err());
 }
 
+// ---------------------------------------------------------------------------
+// decision_restore
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_decision_restore_clears_tombstone() {
+    let (_tmp, conn) = setup_db();
+
+    let id = decision_create(
+        Some("decision:restore".to_string()),
+        "To be restored".to_string(),
+        None,
+        "Body.".to_string(),
+        "Rationale.".to_string(),
+        None,
+        None,
+        "none".to_string(),
+        None,
+        None,
+        None,
+        &conn,
+    )
+    .unwrap();
+
+    decision_tombstone(id.clone(), &conn).unwrap();
+    decision_restore(id.clone(), &conn).unwrap();
+
+    let tombstoned_at: Option<i64> = conn
+        .query_row(
+            "SELECT tombstoned_at FROM decisions WHERE decision_id = ?1",
+            [&id],
+            |r| r.get(0),
+        )
+        .unwrap();
+    assert!(tombstoned_at.is_none());
+}
+
+#[test]
+fn test_decision_restore_active_fails() {
+    let (_tmp, conn) = setup_db();
+
+    let id = decision_create(
+        Some("decision:active".to_string()),
+        "Still active".to_string(),
+        None,
+        "Body.".to_string(),
+        "Rationale.".to_string(),
+        None,
+        None,
+        "none".to_string(),
+        None,
+        None,
+        None,
+        &conn,
+    )
+    .unwrap();
+
+    let err = decision_restore(id, &conn).unwrap_err();
+    assert_eq!(err.kind(), ettlex_core::errors::ExErrorKind::InvalidInput);
+}
+
 // ---------------------------------------------------------------------------
 // decision_supersede
 // ---------------------------------------------------------------------------
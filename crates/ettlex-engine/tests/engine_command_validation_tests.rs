@@ -0,0 +1,302 @@
+//! Engine command validation tests — payloads are normalized or rejected with
+//! `InvalidInput` before any database access.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_engine::commands::engine_command::{
+    apply_engine_command, EngineCommand, EngineCommandResult,
+};
+use ettlex_engine::commands::validate::MAX_ID_LEN;
+use ettlex_engine::snapshot::SnapshotOptions;
+use ettlex_store::cas::FsStore;
+use ettlex_store::migrations::apply_migrations;
+use rusqlite::Connection;
+use serde_json::json;
+use tempfile::TempDir;
+
+// ---------------------------------------------------------------------------
+// Test helpers
+// ---------------------------------------------------------------------------
+
+fn setup() -> (Connection, FsStore, TempDir) {
+    let dir = TempDir::new().expect("temp dir");
+    let cas = FsStore::new(dir.path().join("cas"));
+    let mut conn = Connection::open_in_memory().expect("in-memory db");
+    apply_migrations(&mut conn).expect("migrations should apply");
+    (conn, cas, dir)
+}
+
+fn apply(
+    conn: &mut Connection,
+    cas: &FsStore,
+    cmd: EngineCommand,
+) -> Result<EngineCommandResult, ExError> {
+    apply_engine_command(cmd, conn, cas, &NoopPolicyProvider, &NoopApprovalRouter)
+}
+
+fn snapshot_commit(
+    leaf: &str,
+    profile_ref: Option<&str>,
+    options: SnapshotOptions,
+) -> EngineCommand {
+    EngineCommand::SnapshotCommit {
+        leaf_ep_id: leaf.to_string(),
+        policy_ref: None,
+        profile_ref: profile_ref.map(String::from),
+        options,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_profile_create_trims_ref_before_persisting() {
+    let (mut conn, cas, _dir) = setup();
+    apply(
+        &mut conn,
+        &cas,
+        EngineCommand::ProfileCreate {
+            profile_ref: "  profile/demo@0\n".to_string(),
+            payload_json: json!({ "ambiguity_policy": "deny" }),
+            source: None,
+        },
+    )
+    .unwrap();
+
+    let stored: String = conn
+        .query_row("SELECT profile_ref FROM profiles", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(stored, "profile/demo@0");
+}
+
+#[test]
+fn test_profile_set_default_environment_checked() {
+    let (mut conn, cas, _dir) = setup();
+    apply(
+        &mut conn,
+        &cas,
+        EngineCommand::ProfileCreate {
+            profile_ref: "profile/prod@0".to_string(),
+            payload_json: json!({}),
+            source: None,
+        },
+    )
+    .unwrap();
+
+    for bad in ["", "  ", "pro d"] {
+        let err = apply(
+            &mut conn,
+            &cas,
+            EngineCommand::ProfileSetDefault {
+                profile_ref: "profile/prod@0".to_string(),
+                environment: Some(bad.to_string()),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), ExErrorKind::InvalidInput, "env {:?}", bad);
+    }
+
+    apply(
+        &mut conn,
+        &cas,
+        EngineCommand::ProfileSetDefault {
+            profile_ref: "profile/prod@0".to_string(),
+            environment: Some(" prod ".to_string()),
+        },
+    )
+    .unwrap();
+    let stored: String = conn
+        .query_row(
+            "SELECT environment FROM profile_environment_defaults",
+            [],
+            |r| r.get(0),
+        )
+        .unwrap();
+    assert_eq!(stored, "prod");
+}
+
+#[test]
+fn test_malformed_refs_are_invalid_input() {
+    let (mut conn, cas, _dir) = setup();
+    for bad in [
+        "",
+        "   ",
+        "profile/demo",
+        "@0",
+        "profile/demo@",
+        "pro file@0",
+        "p@0@1",
+    ] {
+        let err = apply(
+            &mut conn,
+            &cas,
+            EngineCommand::ProfileSetDefault {
+                profile_ref: bad.to_string(),
+                environment: None,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), ExErrorKind::InvalidInput, "ref {:?}", bad);
+    }
+}
+
+#[test]
+fn test_profile_create_requires_object_payload() {
+    let (mut conn, cas, _dir) = setup();
+    let err = apply(
+        &mut conn,
+        &cas,
+        EngineCommand::ProfileCreate {
+            profile_ref: "profile/demo@0".to_string(),
+            payload_json: json!(["not", "an", "object"]),
+            source: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+
+    let count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM profiles", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn test_snapshot_commit_ids_validated_before_pipeline() {
+    let (mut conn, cas, _dir) = setup();
+    let too_long = "e".repeat(MAX_ID_LEN + 1);
+    for leaf in ["", "ettle one", "ettle;drop", too_long.as_str()] {
+        let err = apply(
+            &mut conn,
+            &cas,
+            snapshot_commit(leaf, None, SnapshotOptions::default()),
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), ExErrorKind::InvalidInput, "leaf {:?}", leaf);
+    }
+
+    let err = apply(
+        &mut conn,
+        &cas,
+        snapshot_commit("ettle:root", Some("no-version"), SnapshotOptions::default()),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+
+    let err = apply(
+        &mut conn,
+        &cas,
+        snapshot_commit(
+            "ettle:root",
+            None,
+            SnapshotOptions {
+                expected_head: Some(" ".to_string()),
+                ..Default::default()
+            },
+        ),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_snapshot_commit_rejects_dedup_with_dry_run() {
+    let (mut conn, cas, _dir) = setup();
+    let err = apply(
+        &mut conn,
+        &cas,
+        snapshot_commit(
+            "ettle:root",
+            None,
+            SnapshotOptions {
+                dry_run: true,
+                allow_dedup: true,
+                ..Default::default()
+            },
+        ),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+
+    // A well-formed payload passes validation and reaches the pipeline
+    let err = apply(
+        &mut conn,
+        &cas,
+        snapshot_commit(
+            " ettle:root ",
+            Some("profile/default@0"),
+            SnapshotOptions::default(),
+        ),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotImplemented);
+}
+
+#[test]
+fn test_snapshot_commit_rejects_blank_message_and_annotation_keys() {
+    let (mut conn, cas, _dir) = setup();
+    let err = apply(
+        &mut conn,
+        &cas,
+        snapshot_commit(
+            "ettle:root",
+            None,
+            SnapshotOptions {
+                message: Some("   ".to_string()),
+                ..Default::default()
+            },
+        ),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+
+    let mut options = SnapshotOptions::default();
+    options.annotations.insert(String::new(), "x".to_string());
+    let err = apply(
+        &mut conn,
+        &cas,
+        snapshot_commit("ettle:root", None, options),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_profile_create_extends_checked() {
+    let (mut conn, cas, _dir) = setup();
+    let create = |extends: serde_json::Value| EngineCommand::ProfileCreate {
+        profile_ref: "profile/team@0".to_string(),
+        payload_json: json!({ "extends": extends }),
+        source: None,
+    };
+
+    let err = apply(&mut conn, &cas, create(json!(42))).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+    let err = apply(&mut conn, &cas, create(json!(["no-version"]))).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+
+    // Well-formed but unknown base
+    let err = apply(&mut conn, &cas, create(json!("profile/org@0"))).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::ProfileNotFound);
+
+    // Self-extension
+    let err = apply(&mut conn, &cas, create(json!("profile/team@0"))).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::CycleDetected);
+
+    apply(
+        &mut conn,
+        &cas,
+        EngineCommand::ProfileCreate {
+            profile_ref: "profile/org@0".to_string(),
+            payload_json: json!({ "ambiguity_policy": "deny" }),
+            source: None,
+        },
+    )
+    .unwrap();
+    apply(&mut conn, &cas, create(json!("profile/org@0"))).unwrap();
+}
@@ -0,0 +1,182 @@
+//! Refinement path diagnosis tests — `EptDiagnose` reports the resolved
+//! root → leaf path plus ambiguous parents, broken links and non-leaf input.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_engine::commands::command::{apply_command, Command, CommandResult};
+use ettlex_engine::commands::engine_query::{apply_engine_query, EngineQuery, EngineQueryResult};
+use ettlex_engine::commands::read_tools::{EptDiagnosis, EptIssueKind};
+use ettlex_store::cas::FsStore;
+use ettlex_store::migrations::apply_migrations;
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+// ---------------------------------------------------------------------------
+// Test helpers
+// ---------------------------------------------------------------------------
+
+fn setup() -> (Connection, FsStore, TempDir) {
+    let dir = TempDir::new().expect("temp dir");
+    let cas = FsStore::new(dir.path().join("cas"));
+    let mut conn = Connection::open_in_memory().expect("in-memory db");
+    apply_migrations(&mut conn).expect("migrations should apply");
+    (conn, cas, dir)
+}
+
+fn apply(conn: &mut Connection, cas: &FsStore, cmd: Command) -> CommandResult {
+    apply_command(
+        cmd,
+        None,
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .expect("command should succeed")
+    .0
+}
+
+fn create_ettle(conn: &mut Connection, cas: &FsStore, title: &str) -> String {
+    match apply(
+        conn,
+        cas,
+        Command::EttleCreate {
+            title: title.to_string(),
+            ettle_id: None,
+            why: None,
+            what: None,
+            how: None,
+            reasoning_link_id: None,
+            reasoning_link_type: None,
+        },
+    ) {
+        CommandResult::EttleCreate { ettle_id } => ettle_id,
+        _ => panic!("unexpected result"),
+    }
+}
+
+fn refine(conn: &mut Connection, cas: &FsStore, parent: &str, child: &str) -> String {
+    match apply(
+        conn,
+        cas,
+        Command::RelationCreate {
+            source_ettle_id: parent.to_string(),
+            target_ettle_id: child.to_string(),
+            relation_type: "refinement".to_string(),
+            properties_json: None,
+            relation_id: None,
+        },
+    ) {
+        CommandResult::RelationCreate { relation_id } => relation_id,
+        _ => panic!("unexpected result"),
+    }
+}
+
+fn diagnose(conn: &Connection, cas: &FsStore, leaf: &str) -> Result<EptDiagnosis, ExError> {
+    match apply_engine_query(
+        EngineQuery::EptDiagnose {
+            leaf_ettle_id: leaf.to_string(),
+        },
+        conn,
+        cas,
+        None,
+    )? {
+        EngineQueryResult::EptDiagnose(d) => Ok(d),
+        _ => panic!("unexpected result"),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_ept_diagnose_clean_path_has_no_issues() {
+    let (mut conn, cas, _dir) = setup();
+    let root = create_ettle(&mut conn, &cas, "Root");
+    let mid = create_ettle(&mut conn, &cas, "Mid");
+    let leaf = create_ettle(&mut conn, &cas, "Leaf");
+    refine(&mut conn, &cas, &root, &mid);
+    refine(&mut conn, &cas, &mid, &leaf);
+
+    let d = diagnose(&conn, &cas, &leaf).unwrap();
+    assert_eq!(d.leaf_ettle_id, leaf);
+    assert_eq!(d.path, vec![root, mid, leaf]);
+    assert!(d.issues.is_empty());
+}
+
+#[test]
+fn test_ept_diagnose_reports_ambiguous_parent_candidates() {
+    let (mut conn, cas, _dir) = setup();
+    let p1 = create_ettle(&mut conn, &cas, "Parent One");
+    let p2 = create_ettle(&mut conn, &cas, "Parent Two");
+    let leaf = create_ettle(&mut conn, &cas, "Leaf");
+    let r1 = refine(&mut conn, &cas, &p1, &leaf);
+    let r2 = refine(&mut conn, &cas, &p2, &leaf);
+    let (first, second) = if r1 < r2 { (&p1, &p2) } else { (&p2, &p1) };
+
+    let d = diagnose(&conn, &cas, &leaf).unwrap();
+    assert_eq!(d.path, vec![first.clone(), leaf.clone()]);
+    assert_eq!(d.issues.len(), 1);
+    assert_eq!(d.issues[0].ettle_id, leaf);
+    let EptIssueKind::AmbiguousParent { candidates } = &d.issues[0].kind else {
+        panic!("expected AmbiguousParent, got {:?}", d.issues[0].kind);
+    };
+    let parents: Vec<(u32, &str)> = candidates
+        .iter()
+        .map(|c| (c.ordinal, c.parent_ettle_id.as_str()))
+        .collect();
+    assert_eq!(parents, vec![(0, first.as_str()), (1, second.as_str())]);
+    assert!(!d.issues[0].suggested_fix.is_empty());
+}
+
+#[test]
+fn test_ept_diagnose_reports_broken_link_to_tombstoned_parent() {
+    let (mut conn, cas, _dir) = setup();
+    let root = create_ettle(&mut conn, &cas, "Root");
+    let leaf = create_ettle(&mut conn, &cas, "Leaf");
+    let relation_id = refine(&mut conn, &cas, &root, &leaf);
+    conn.execute(
+        "UPDATE ettles SET tombstoned_at = '2026-01-01T00:00:00Z' WHERE id = ?1",
+        [&root],
+    )
+    .unwrap();
+
+    let d = diagnose(&conn, &cas, &leaf).unwrap();
+    assert_eq!(d.path, vec![leaf.clone()]);
+    assert_eq!(
+        d.issues[0].kind,
+        EptIssueKind::BrokenLink {
+            relation_id,
+            parent_ettle_id: root,
+            parent_state: "tombstoned".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_ept_diagnose_flags_non_leaf() {
+    let (mut conn, cas, _dir) = setup();
+    let root = create_ettle(&mut conn, &cas, "Root");
+    let child = create_ettle(&mut conn, &cas, "Child");
+    refine(&mut conn, &cas, &root, &child);
+
+    let d = diagnose(&conn, &cas, &root).unwrap();
+    assert_eq!(d.path, vec![root]);
+    assert_eq!(
+        d.issues[0].kind,
+        EptIssueKind::NotALeaf {
+            child_ettle_ids: vec![child]
+        }
+    );
+}
+
+#[test]
+fn test_ept_diagnose_unknown_leaf_not_found() {
+    let (conn, cas, _dir) = setup();
+    let err = diagnose(&conn, &cas, "ettle:missing").unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+}
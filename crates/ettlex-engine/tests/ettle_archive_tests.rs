@@ -0,0 +1,287 @@
+//! Ettle archive tests — archive/unarchive commands, subtree propagation,
+//! exclusion from default listings, and snapshot root rejection.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::ops::Store;
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_core::snapshot::manifest::generate_manifest;
+use ettlex_engine::commands::command::{apply_command, Command, CommandResult};
+use ettlex_engine::commands::engine_query::{apply_engine_query, EngineQuery, EngineQueryResult};
+use ettlex_engine::commands::ettle::handle_ettle_get;
+use ettlex_engine::commands::read_tools::ListOptions;
+use ettlex_store::cas::FsStore;
+use ettlex_store::migrations::apply_migrations;
+use ettlex_store::model::EttleListOpts;
+use ettlex_store::repo::SqliteRepo;
+use ettlex_store::snapshot::persist::{commit_snapshot, SnapshotOptions};
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+// ---------------------------------------------------------------------------
+// Test helpers
+// ---------------------------------------------------------------------------
+
+fn setup_db_with_cas() -> (Connection, FsStore, TempDir) {
+    let dir = TempDir::new().expect("temp dir");
+    let cas = FsStore::new(dir.path().join("cas"));
+    let mut conn = Connection::open_in_memory().expect("in-memory db");
+    apply_migrations(&mut conn).expect("migrations should apply");
+    (conn, cas, dir)
+}
+
+fn apply(conn: &mut Connection, cas: &FsStore, cmd: Command) -> Result<CommandResult, ExError> {
+    apply_command(
+        cmd,
+        None,
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .map(|(res, _sv)| res)
+}
+
+fn create_ettle(conn: &mut Connection, cas: &FsStore, title: &str) -> String {
+    let res = apply(
+        conn,
+        cas,
+        Command::EttleCreate {
+            title: title.to_string(),
+            ettle_id: None,
+            why: None,
+            what: None,
+            how: None,
+            reasoning_link_id: None,
+            reasoning_link_type: None,
+        },
+    )
+    .expect("ettle create should succeed");
+    match res {
+        CommandResult::EttleCreate { ettle_id } => ettle_id,
+        _ => panic!("unexpected result"),
+    }
+}
+
+fn refine(conn: &mut Connection, cas: &FsStore, parent: &str, child: &str) {
+    apply(
+        conn,
+        cas,
+        Command::RelationCreate {
+            source_ettle_id: parent.to_string(),
+            target_ettle_id: child.to_string(),
+            relation_type: "refinement".to_string(),
+            properties_json: None,
+            relation_id: None,
+        },
+    )
+    .expect("relation create should succeed");
+}
+
+fn archive(
+    conn: &mut Connection,
+    cas: &FsStore,
+    ettle_id: &str,
+    subtree: bool,
+) -> Result<Vec<String>, ExError> {
+    match apply(
+        conn,
+        cas,
+        Command::EttleArchive {
+            ettle_id: ettle_id.to_string(),
+            subtree,
+        },
+    )? {
+        CommandResult::EttleArchive { archived_ettle_ids } => Ok(archived_ettle_ids),
+        _ => panic!("unexpected result"),
+    }
+}
+
+fn unarchive(
+    conn: &mut Connection,
+    cas: &FsStore,
+    ettle_id: &str,
+    subtree: bool,
+) -> Result<Vec<String>, ExError> {
+    match apply(
+        conn,
+        cas,
+        Command::EttleUnarchive {
+            ettle_id: ettle_id.to_string(),
+            subtree,
+        },
+    )? {
+        CommandResult::EttleUnarchive {
+            unarchived_ettle_ids,
+        } => Ok(unarchived_ettle_ids),
+        _ => panic!("unexpected result"),
+    }
+}
+
+fn is_archived(conn: &Connection, ettle_id: &str) -> bool {
+    handle_ettle_get(conn, ettle_id)
+        .unwrap()
+        .archived_at
+        .is_some()
+}
+
+fn listed_ids(conn: &Connection, include_archived: bool) -> Vec<String> {
+    let page = SqliteRepo::list_ettles(
+        conn,
+        &EttleListOpts {
+            limit: 100,
+            cursor: None,
+            include_tombstoned: false,
+            include_archived,
+        },
+    )
+    .unwrap();
+    page.items.into_iter().map(|i| i.id).collect()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_archive_hides_from_default_listings_but_stays_readable() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let a = create_ettle(&mut conn, &cas, "A");
+    let b = create_ettle(&mut conn, &cas, "B");
+
+    assert_eq!(
+        archive(&mut conn, &cas, &a, false).unwrap(),
+        vec![a.clone()]
+    );
+
+    assert_eq!(listed_ids(&conn, false), vec![b.clone()]);
+    assert_eq!(listed_ids(&conn, true).len(), 2);
+
+    let page = match apply_engine_query(
+        EngineQuery::EttleList(ListOptions::default()),
+        &conn,
+        &cas,
+        None,
+    )
+    .unwrap()
+    {
+        EngineQueryResult::EttleList(p) => p,
+        _ => panic!("expected EttleList"),
+    };
+    let ids: Vec<&str> = page.items.iter().map(|e| e.id.as_str()).collect();
+    assert_eq!(ids, vec![b.as_str()]);
+
+    // Still readable by ID, and not tombstoned
+    let record = handle_ettle_get(&conn, &a).unwrap();
+    assert!(record.archived_at.is_some());
+    assert!(record.tombstoned_at.is_none());
+}
+
+#[test]
+fn test_archive_subtree_and_unarchive_subtree() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let root = create_ettle(&mut conn, &cas, "Root");
+    let child = create_ettle(&mut conn, &cas, "Child");
+    let grandchild = create_ettle(&mut conn, &cas, "Grandchild");
+    let other = create_ettle(&mut conn, &cas, "Other");
+    refine(&mut conn, &cas, &root, &child);
+    refine(&mut conn, &cas, &child, &grandchild);
+
+    let archived = archive(&mut conn, &cas, &root, true).unwrap();
+    assert_eq!(
+        archived,
+        vec![root.clone(), child.clone(), grandchild.clone()]
+    );
+    assert!(!is_archived(&conn, &other));
+    assert_eq!(listed_ids(&conn, false), vec![other]);
+
+    // Non-subtree unarchive touches only the target
+    let restored = unarchive(&mut conn, &cas, &root, false).unwrap();
+    assert_eq!(restored, vec![root.clone()]);
+    assert!(is_archived(&conn, &child));
+
+    // Subtree unarchive from the child picks up the grandchild
+    let restored = unarchive(&mut conn, &cas, &child, true).unwrap();
+    assert_eq!(restored, vec![child.clone(), grandchild.clone()]);
+    assert!(!is_archived(&conn, &grandchild));
+}
+
+#[test]
+fn test_archive_subtree_skips_already_archived_descendants() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let root = create_ettle(&mut conn, &cas, "Root");
+    let child = create_ettle(&mut conn, &cas, "Child");
+    refine(&mut conn, &cas, &root, &child);
+
+    archive(&mut conn, &cas, &child, false).unwrap();
+    let archived = archive(&mut conn, &cas, &root, true).unwrap();
+    assert_eq!(archived, vec![root]);
+}
+
+#[test]
+fn test_archive_state_errors() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let a = create_ettle(&mut conn, &cas, "A");
+
+    let err = unarchive(&mut conn, &cas, &a, false).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+
+    archive(&mut conn, &cas, &a, false).unwrap();
+    let err = archive(&mut conn, &cas, &a, false).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+
+    let err = archive(&mut conn, &cas, "ettle:missing", false).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+
+    let b = create_ettle(&mut conn, &cas, "B");
+    apply(
+        &mut conn,
+        &cas,
+        Command::EttleTombstone {
+            ettle_id: b.clone(),
+        },
+    )
+    .unwrap();
+    let err = archive(&mut conn, &cas, &b, false).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::AlreadyTombstoned);
+}
+
+#[test]
+fn test_archive_accepts_slug() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let a = create_ettle(&mut conn, &cas, "Payments API");
+
+    let archived = archive(&mut conn, &cas, "payments-api", false).unwrap();
+    assert_eq!(archived, vec![a]);
+}
+
+#[test]
+fn test_archived_ettle_cannot_root_snapshot() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let a = create_ettle(&mut conn, &cas, "A");
+    archive(&mut conn, &cas, &a, false).unwrap();
+
+    let manifest = generate_manifest(
+        vec![],
+        "policy/default@0".into(),
+        "profile/default@0".into(),
+        a.clone(),
+        "0001".into(),
+        None,
+        &Store::new(),
+    )
+    .unwrap();
+    let err = commit_snapshot(
+        &mut conn,
+        &cas,
+        manifest.clone(),
+        SnapshotOptions::default(),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::RootEttleInvalid);
+
+    unarchive(&mut conn, &cas, &a, false).unwrap();
+    commit_snapshot(&mut conn, &cas, manifest, SnapshotOptions::default()).unwrap();
+}
@@ -0,0 +1,191 @@
+//! Ettle ownership and review assignment tests.
+//!
+//! Tests cover: assigning and clearing owners and reviewers, `EttleGet`
+//! assignees, `EttleListByOwner` with and without reviewing, validation, and
+//! approval requests surfacing their candidates' assignees.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::approval_router::{ApprovalRouter, NoopApprovalRouter};
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_engine::commands::command::{apply_command, Command, CommandResult};
+use ettlex_engine::commands::engine_query::{apply_engine_query, EngineQuery, EngineQueryResult};
+use ettlex_engine::commands::read_tools::EttleAssignees;
+use ettlex_store::cas::FsStore;
+use ettlex_store::migrations::apply_migrations;
+use ettlex_store::profile::SqliteApprovalRouter;
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+fn setup() -> (Connection, FsStore, TempDir) {
+    let dir = TempDir::new().expect("temp dir");
+    let cas = FsStore::new(dir.path().join("cas"));
+    let mut conn = Connection::open(dir.path().join("test.db")).expect("db");
+    apply_migrations(&mut conn).expect("migrations should apply");
+    (conn, cas, dir)
+}
+
+fn try_apply(conn: &mut Connection, cas: &FsStore, cmd: Command) -> Result<CommandResult, ExError> {
+    apply_command(
+        cmd,
+        None,
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .map(|(res, _sv)| res)
+}
+
+fn create_ettle(conn: &mut Connection, cas: &FsStore, title: &str) -> String {
+    match try_apply(
+        conn,
+        cas,
+        Command::EttleCreate {
+            title: title.to_string(),
+            ettle_id: None,
+            why: None,
+            what: None,
+            how: None,
+            reasoning_link_id: None,
+            reasoning_link_type: None,
+        },
+    )
+    .expect("ettle create should succeed")
+    {
+        CommandResult::EttleCreate { ettle_id } => ettle_id,
+        _ => panic!("unexpected result"),
+    }
+}
+
+fn assign(
+    conn: &mut Connection,
+    cas: &FsStore,
+    ettle_id: &str,
+    owner: Option<Option<&str>>,
+    reviewers: Option<&[&str]>,
+) -> Result<CommandResult, ExError> {
+    try_apply(
+        conn,
+        cas,
+        Command::EttleAssign {
+            ettle_id: ettle_id.to_string(),
+            owner: owner.map(|o| o.map(str::to_string)),
+            reviewers: reviewers.map(|r| r.iter().map(|s| s.to_string()).collect()),
+        },
+    )
+}
+
+fn assignees(conn: &Connection, cas: &FsStore, ettle_id: &str) -> EttleAssignees {
+    let query = EngineQuery::EttleGet {
+        ettle_id: ettle_id.to_string(),
+    };
+    match apply_engine_query(query, conn, cas, None).unwrap() {
+        EngineQueryResult::EttleGet(r) => r.assignees,
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+fn owned_by(conn: &Connection, cas: &FsStore, owner: &str, reviewing: bool) -> Vec<String> {
+    let query = EngineQuery::EttleListByOwner {
+        owner: owner.to_string(),
+        include_reviewing: reviewing,
+    };
+    match apply_engine_query(query, conn, cas, None).unwrap() {
+        EngineQueryResult::EttleListByOwner(items) => items
+            .into_iter()
+            .map(|i| format!("{}:{}", i.title, i.role))
+            .collect(),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_assign_sets_and_clears_owner_and_reviewers() {
+    let (mut conn, cas, _dir) = setup();
+    let id = create_ettle(&mut conn, &cas, "Payments");
+
+    assign(
+        &mut conn,
+        &cas,
+        &id,
+        Some(Some("alice")),
+        Some(&["carol", " bob ", "carol"]),
+    )
+    .unwrap();
+    let a = assignees(&conn, &cas, &id);
+    assert_eq!(a.owner.as_deref(), Some("alice"));
+    assert_eq!(a.reviewers, vec!["bob", "carol"]);
+
+    // Absent fields are left alone.
+    assign(&mut conn, &cas, &id, Some(Some("dave")), None).unwrap();
+    let a = assignees(&conn, &cas, &id);
+    assert_eq!(a.owner.as_deref(), Some("dave"));
+    assert_eq!(a.reviewers, vec!["bob", "carol"]);
+
+    assign(&mut conn, &cas, &id, Some(None), Some(&[])).unwrap();
+    assert_eq!(assignees(&conn, &cas, &id), EttleAssignees::default());
+}
+
+#[test]
+fn test_list_by_owner_optionally_includes_reviewing() {
+    let (mut conn, cas, _dir) = setup();
+    let a = create_ettle(&mut conn, &cas, "A");
+    let b = create_ettle(&mut conn, &cas, "B");
+    let gone = create_ettle(&mut conn, &cas, "Gone");
+    assign(&mut conn, &cas, &a, Some(Some("alice")), None).unwrap();
+    assign(&mut conn, &cas, &b, Some(Some("bob")), Some(&["alice"])).unwrap();
+    assign(&mut conn, &cas, &gone, Some(Some("alice")), None).unwrap();
+    try_apply(&mut conn, &cas, Command::EttleTombstone { ettle_id: gone }).unwrap();
+
+    assert_eq!(owned_by(&conn, &cas, "alice", false), vec!["A:owner"]);
+    let mut all = owned_by(&conn, &cas, "alice", true);
+    all.sort();
+    assert_eq!(all, vec!["A:owner", "B:reviewer"]);
+    assert!(owned_by(&conn, &cas, "nobody", true).is_empty());
+}
+
+#[test]
+fn test_assign_validation() {
+    let (mut conn, cas, _dir) = setup();
+    let id = create_ettle(&mut conn, &cas, "Payments");
+
+    let err = assign(&mut conn, &cas, &id, None, None).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::EmptyUpdate);
+    let err = assign(&mut conn, &cas, &id, None, Some(&["  "])).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+    let err = assign(&mut conn, &cas, "ettle:missing", Some(Some("a")), None).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+
+    try_apply(
+        &mut conn,
+        &cas,
+        Command::EttleTombstone {
+            ettle_id: id.clone(),
+        },
+    )
+    .unwrap();
+    let err = assign(&mut conn, &cas, &id, Some(Some("a")), None).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::AlreadyTombstoned);
+}
+
+#[test]
+fn test_approval_get_lists_candidate_assignees() {
+    let (mut conn, cas, _dir) = setup();
+    let a = create_ettle(&mut conn, &cas, "A");
+    let b = create_ettle(&mut conn, &cas, "B");
+    assign(&mut conn, &cas, &a, Some(Some("alice")), Some(&["bob"])).unwrap();
+    assign(&mut conn, &cas, &b, Some(Some("bob")), None).unwrap();
+
+    let token = SqliteApprovalRouter::new_with_cas(&mut conn, &cas)
+        .route_approval_request("AmbiguousSelection", vec![a, b, "ettle:other".to_string()])
+        .unwrap();
+    let query = EngineQuery::ApprovalGet {
+        approval_token: token,
+    };
+    match apply_engine_query(query, &conn, &cas, None).unwrap() {
+        EngineQueryResult::ApprovalGet(r) => assert_eq!(r.assignees, vec!["alice", "bob"]),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
@@ -500,6 +500,7 @@ fn test_list_empty_returns_empty_page() {
         limit: 50,
         cursor: None,
         include_tombstoned: false,
+        include_archived: false,
     };
     let page: EttleListPage = handle_ettle_list(&conn, opts).unwrap();
     assert!(page.items.is_empty(), "empty store must return empty items");
@@ -522,6 +523,7 @@ fn test_list_single_ettle() {
         limit: 50,
         cursor: None,
         include_tombstoned: false,
+        include_archived: false,
     };
     let page = handle_ettle_list(&conn, opts).unwrap();
     assert_eq!(page.items.len(), 1);
@@ -544,6 +546,7 @@ fn test_list_pagination_cursor() {
         limit: 3,
         cursor: None,
         include_tombstoned: false,
+        include_archived: false,
     };
     let page1 = handle_ettle_list(&conn, opts).unwrap();
     assert_eq!(page1.items.len(), 3, "first page should have 3 items");
@@ -561,6 +564,7 @@ fn test_list_pagination_cursor() {
         limit: 3,
         cursor: cursor2,
         include_tombstoned: false,
+        include_archived: false,
     };
     let page2 = handle_ettle_list(&conn, opts2).unwrap();
     assert_eq!(
@@ -588,6 +592,7 @@ fn test_list_limit_zero_fails() {
         limit: 0,
         cursor: None,
         include_tombstoned: false,
+        include_archived: false,
     };
     let result = handle_ettle_list(&conn, opts);
     assert!(result.is_err(), "limit=0 must fail");
@@ -605,6 +610,7 @@ fn test_list_limit_over_500_fails() {
         limit: 501,
         cursor: None,
         include_tombstoned: false,
+        include_archived: false,
     };
     let result = handle_ettle_list(&conn, opts);
     assert!(result.is_err(), "limit>500 must fail");
@@ -652,6 +658,7 @@ fn test_list_excludes_tombstoned_by_default() {
         limit: 50,
         cursor: None,
         include_tombstoned: false,
+        include_archived: false,
     };
     let page = handle_ettle_list(&conn, opts).unwrap();
     let ids: Vec<_> = page.items.iter().map(|e| &e.id).collect();
@@ -688,6 +695,7 @@ fn test_list_include_tombstoned_flag() {
         limit: 50,
         cursor: None,
         include_tombstoned: true,
+        include_archived: false,
     };
     let page = handle_ettle_list(&conn, opts).unwrap();
     let ids: Vec<_> = page.items.iter().map(|e| &e.id).collect();
@@ -1593,6 +1601,63 @@ fn test_failed_command_no_provenance_event() {
     );
 }
 
+#[test]
+fn test_failed_provenance_write_rolls_back_the_mutation() {
+    let (_dir, mut conn, cas) = setup();
+    let id = create_ettle(&mut conn, &cas, "Before");
+    let count = |conn: &Connection, table: &str| -> u64 {
+        conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |r| r.get(0))
+            .unwrap()
+    };
+    let ettles = count(&conn, "ettles");
+    let log = count(&conn, "command_log");
+
+    // The mutation itself succeeds; the provenance step after it fails
+    conn.execute_batch(
+        "CREATE TRIGGER fail_provenance BEFORE INSERT ON provenance_events
+         BEGIN SELECT RAISE(ABORT, 'provenance unavailable'); END;",
+    )
+    .unwrap();
+    let result = apply_command(
+        Command::EttleUpdate {
+            ettle_id: id.clone(),
+            title: Some("After".to_string()),
+            why: None,
+            what: None,
+            how: None,
+            reasoning_link_id: None,
+            reasoning_link_type: None,
+        },
+        None,
+        &mut conn,
+        &cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    );
+    assert!(result.is_err());
+    let result = apply_command(
+        Command::EttleCreate {
+            title: "Never".to_string(),
+            ettle_id: None,
+            why: None,
+            what: None,
+            how: None,
+            reasoning_link_id: None,
+            reasoning_link_type: None,
+        },
+        None,
+        &mut conn,
+        &cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    );
+    assert!(result.is_err());
+
+    assert_eq!(handle_ettle_get(&conn, &id).unwrap().title, "Before");
+    assert_eq!(count(&conn, "ettles"), ettles);
+    assert_eq!(count(&conn, "command_log"), log);
+}
+
 // ---------------------------------------------------------------------------
 // SC-46: ettle_get_byte_identical
 // ---------------------------------------------------------------------------
@@ -1629,12 +1694,14 @@ fn test_ettle_list_byte_identical() {
         limit: 50,
         cursor: None,
         include_tombstoned: false,
+        include_archived: false,
     };
     let p1 = handle_ettle_list(&conn, opts.clone()).unwrap();
     let opts2 = EttleListOpts {
         limit: 50,
         cursor: None,
         include_tombstoned: false,
+        include_archived: false,
     };
     let p2 = handle_ettle_list(&conn, opts2).unwrap();
 
@@ -1699,6 +1766,7 @@ fn test_list_max_limit_succeeds() {
         limit: 500,
         cursor: None,
         include_tombstoned: false,
+        include_archived: false,
     };
     let result = handle_ettle_list(&conn, opts);
     assert!(result.is_ok(), "limit=500 must succeed: {:?}", result.err());
@@ -0,0 +1,157 @@
+//! `EttleListReferrers` tests — the refinement relations pointing at an
+//! Ettle, split into active and tombstoned.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_engine::commands::command::{apply_command, Command, CommandResult};
+use ettlex_engine::commands::engine_query::{apply_engine_query, EngineQuery, EngineQueryResult};
+use ettlex_engine::commands::ettle::handle_ettle_get;
+use ettlex_engine::commands::read_tools::EttleReferrers;
+use ettlex_store::cas::FsStore;
+use ettlex_store::migrations::apply_migrations;
+use rusqlite::Connection;
+use serde_json::json;
+use tempfile::TempDir;
+
+// ---------------------------------------------------------------------------
+// Test helpers
+// ---------------------------------------------------------------------------
+
+fn setup() -> (Connection, FsStore, TempDir) {
+    let dir = TempDir::new().expect("temp dir");
+    let cas = FsStore::new(dir.path().join("cas"));
+    let mut conn = Connection::open_in_memory().expect("in-memory db");
+    apply_migrations(&mut conn).expect("migrations should apply");
+    (conn, cas, dir)
+}
+
+fn apply(conn: &mut Connection, cas: &FsStore, cmd: Command) -> CommandResult {
+    apply_command(
+        cmd,
+        None,
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .expect("command should succeed")
+    .0
+}
+
+fn create_ettle(conn: &mut Connection, cas: &FsStore, title: &str) -> String {
+    match apply(
+        conn,
+        cas,
+        Command::EttleCreate {
+            title: title.to_string(),
+            ettle_id: None,
+            why: None,
+            what: None,
+            how: None,
+            reasoning_link_id: None,
+            reasoning_link_type: None,
+        },
+    ) {
+        CommandResult::EttleCreate { ettle_id } => ettle_id,
+        _ => panic!("unexpected result"),
+    }
+}
+
+fn relate(
+    conn: &mut Connection,
+    cas: &FsStore,
+    parent: &str,
+    child: &str,
+    relation_type: &str,
+    ordinal: Option<u64>,
+) -> String {
+    match apply(
+        conn,
+        cas,
+        Command::RelationCreate {
+            source_ettle_id: parent.to_string(),
+            target_ettle_id: child.to_string(),
+            relation_type: relation_type.to_string(),
+            properties_json: ordinal.map(|o| json!({ "ordinal": o })),
+            relation_id: None,
+        },
+    ) {
+        CommandResult::RelationCreate { relation_id } => relation_id,
+        _ => panic!("unexpected result"),
+    }
+}
+
+fn referrers(conn: &Connection, cas: &FsStore, ettle: &str) -> Result<EttleReferrers, ExError> {
+    match apply_engine_query(
+        EngineQuery::EttleListReferrers {
+            ettle_id: ettle.to_string(),
+        },
+        conn,
+        cas,
+        None,
+    )? {
+        EngineQueryResult::EttleListReferrers(r) => Ok(r),
+        _ => panic!("unexpected result"),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_referrers_split_active_and_tombstoned() {
+    let (mut conn, cas, _dir) = setup();
+    let old_parent = create_ettle(&mut conn, &cas, "Old parent");
+    let parent = create_ettle(&mut conn, &cas, "Parent");
+    let child = create_ettle(&mut conn, &cas, "Child");
+    let old_rel = relate(&mut conn, &cas, &old_parent, &child, "refinement", None);
+    apply(
+        &mut conn,
+        &cas,
+        Command::RelationTombstone {
+            relation_id: old_rel.clone(),
+        },
+    );
+    let rel = relate(&mut conn, &cas, &parent, &child, "refinement", Some(2));
+
+    let r = referrers(&conn, &cas, &child).unwrap();
+
+    assert_eq!(r.active.len(), 1);
+    assert_eq!(r.active[0].relation_id, rel);
+    assert_eq!(r.active[0].parent_ettle_id, parent);
+    assert_eq!(r.active[0].parent_title, "Parent");
+    assert_eq!(r.active[0].ordinal, Some(2));
+    assert_eq!(r.tombstoned.len(), 1);
+    assert_eq!(r.tombstoned[0].relation_id, old_rel);
+    assert!(r.tombstoned[0].tombstoned_at.is_some());
+}
+
+#[test]
+fn test_non_refinement_relations_are_ignored() {
+    let (mut conn, cas, _dir) = setup();
+    let constraint = create_ettle(&mut conn, &cas, "Constraint");
+    let child = create_ettle(&mut conn, &cas, "Child");
+    relate(&mut conn, &cas, &constraint, &child, "constraint", None);
+
+    let r = referrers(&conn, &cas, &child).unwrap();
+    assert_eq!(r, EttleReferrers::default());
+}
+
+#[test]
+fn test_referrers_accept_slug_and_reject_unknown_ettle() {
+    let (mut conn, cas, _dir) = setup();
+    let parent = create_ettle(&mut conn, &cas, "Parent");
+    let child = create_ettle(&mut conn, &cas, "Child");
+    relate(&mut conn, &cas, &parent, &child, "refinement", None);
+    let slug = handle_ettle_get(&conn, &child).unwrap().slug.expect("slug");
+
+    let r = referrers(&conn, &cas, &slug).unwrap();
+    assert_eq!(r.active[0].parent_ettle_id, parent);
+
+    let err = referrers(&conn, &cas, "ettle:missing").unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+}
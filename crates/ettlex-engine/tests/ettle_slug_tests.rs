@@ -0,0 +1,198 @@
+//! Ettle slug tests — derivation, collision handling, stability, and
+//! resolution of slugs wherever an ettle_id is accepted.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_engine::commands::command::{apply_command, Command, CommandResult};
+use ettlex_engine::commands::engine_query::{apply_engine_query, EngineQuery, EngineQueryResult};
+use ettlex_engine::commands::ettle::handle_ettle_get;
+use ettlex_store::cas::FsStore;
+use ettlex_store::migrations::apply_migrations;
+use ettlex_store::repo::SqliteRepo;
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+// ---------------------------------------------------------------------------
+// Test helpers
+// ---------------------------------------------------------------------------
+
+fn setup_db_with_cas() -> (Connection, FsStore, TempDir) {
+    let dir = TempDir::new().expect("temp dir");
+    let cas = FsStore::new(dir.path().join("cas"));
+    let mut conn = Connection::open_in_memory().expect("in-memory db");
+    apply_migrations(&mut conn).expect("migrations should apply");
+    (conn, cas, dir)
+}
+
+fn apply(conn: &mut Connection, cas: &FsStore, cmd: Command) -> Result<CommandResult, ExError> {
+    apply_command(
+        cmd,
+        None,
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .map(|(res, _sv)| res)
+}
+
+fn create_ettle(conn: &mut Connection, cas: &FsStore, title: &str) -> String {
+    let res = apply(
+        conn,
+        cas,
+        Command::EttleCreate {
+            title: title.to_string(),
+            ettle_id: None,
+            why: None,
+            what: None,
+            how: None,
+            reasoning_link_id: None,
+            reasoning_link_type: None,
+        },
+    )
+    .expect("ettle create should succeed");
+    match res {
+        CommandResult::EttleCreate { ettle_id } => ettle_id,
+        _ => panic!("unexpected result"),
+    }
+}
+
+fn slug_of(conn: &Connection, ettle_id: &str) -> Option<String> {
+    handle_ettle_get(conn, ettle_id).unwrap().slug
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_create_derives_slug_with_collision_suffix() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let a = create_ettle(&mut conn, &cas, "Payments API");
+    let b = create_ettle(&mut conn, &cas, "payments  api!");
+    let c = create_ettle(&mut conn, &cas, "???");
+    assert_eq!(slug_of(&conn, &a).as_deref(), Some("payments-api"));
+    assert_eq!(slug_of(&conn, &b).as_deref(), Some("payments-api-2"));
+    assert_eq!(slug_of(&conn, &c).as_deref(), Some("ettle"));
+}
+
+#[test]
+fn test_slug_is_stable_across_title_update() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let id = create_ettle(&mut conn, &cas, "Original");
+    apply(
+        &mut conn,
+        &cas,
+        Command::EttleUpdate {
+            ettle_id: "original".to_string(),
+            title: Some("Renamed".to_string()),
+            why: None,
+            what: None,
+            how: None,
+            reasoning_link_id: None,
+            reasoning_link_type: None,
+        },
+    )
+    .expect("update by slug should succeed");
+    let rec = handle_ettle_get(&conn, &id).unwrap();
+    assert_eq!(rec.title, "Renamed");
+    assert_eq!(rec.slug.as_deref(), Some("original"));
+}
+
+#[test]
+fn test_slug_not_reused_after_tombstone() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    create_ettle(&mut conn, &cas, "Legacy");
+    apply(
+        &mut conn,
+        &cas,
+        Command::EttleTombstone {
+            ettle_id: "legacy".to_string(),
+        },
+    )
+    .expect("tombstone by slug should succeed");
+    let again = create_ettle(&mut conn, &cas, "Legacy");
+    assert_eq!(slug_of(&conn, &again).as_deref(), Some("legacy-2"));
+}
+
+#[test]
+fn test_slug_accepted_by_relation_create_and_queries() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let parent = create_ettle(&mut conn, &cas, "Parent");
+    let child = create_ettle(&mut conn, &cas, "Child");
+    apply(
+        &mut conn,
+        &cas,
+        Command::RelationCreate {
+            source_ettle_id: "parent".to_string(),
+            target_ettle_id: "child".to_string(),
+            relation_type: "refinement".to_string(),
+            properties_json: None,
+            relation_id: None,
+        },
+    )
+    .expect("relation create by slug should succeed");
+    let rels = SqliteRepo::list_relations(
+        &conn,
+        &ettlex_store::model::RelationListOpts {
+            source_ettle_id: Some(parent.clone()),
+            target_ettle_id: None,
+            relation_type: None,
+            include_tombstoned: false,
+        },
+    )
+    .unwrap();
+    assert_eq!(rels.len(), 1);
+    assert_eq!(rels[0].target_ettle_id, child);
+
+    match apply_engine_query(
+        EngineQuery::EttleGet {
+            ettle_id: "child".to_string(),
+        },
+        &conn,
+        &cas,
+        None,
+    )
+    .unwrap()
+    {
+        EngineQueryResult::EttleGet(r) => assert_eq!(r.ettle.id, child),
+        _ => panic!("expected EttleGet"),
+    }
+}
+
+#[test]
+fn test_unknown_ref_reports_not_found() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let err = apply(
+        &mut conn,
+        &cas,
+        Command::EttleTombstone {
+            ettle_id: "no-such-slug".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+}
+
+#[test]
+fn test_migration_backfills_legacy_slugs() {
+    let (mut conn, _cas, _dir) = setup_db_with_cas();
+    conn.execute(
+        "INSERT INTO ettles (id, title, why, what, how, created_at, updated_at)
+         VALUES ('ettle:legacy', 'Legacy Row', '', '', '', '2026-01-01T00:00:00Z', '2026-01-01T00:00:00Z')",
+        [],
+    )
+    .unwrap();
+    apply_migrations(&mut conn).unwrap();
+    assert_eq!(
+        slug_of(&conn, "ettle:legacy").as_deref(),
+        Some("legacy-row")
+    );
+    assert_eq!(
+        SqliteRepo::resolve_ettle_ref(&conn, "legacy-row").unwrap(),
+        Some("ettle:legacy".to_string())
+    );
+}
@@ -0,0 +1,203 @@
+//! Event history tests — the stream is read from `command_log`, one event
+//! per applied command, with sequence numbers that only grow.
+
+#![allow(clippy::unwrap_used, clippy::result_large_err)]
+
+use ettlex_core::approval_router::{ApprovalRouter, NoopApprovalRouter};
+use ettlex_core::errors::ExErrorKind;
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_engine::commands::command::{apply_command, Command, CommandResult};
+use ettlex_engine::commands::engine_command::{apply_engine_command, EngineCommand};
+use ettlex_engine::commands::event_history::{export_history_jsonl, read_history, HistoryEvent};
+use ettlex_store::cas::FsStore;
+use ettlex_store::profile::SqliteApprovalRouter;
+use rusqlite::Connection;
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+fn setup() -> (TempDir, Connection, FsStore) {
+    let dir = TempDir::new().unwrap();
+    let mut conn = Connection::open(dir.path().join("test.db")).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    let cas = FsStore::new(dir.path().join("cas"));
+    (dir, conn, cas)
+}
+
+fn apply(conn: &mut Connection, cas: &FsStore, cmd: Value) -> CommandResult {
+    apply_command(
+        serde_json::from_value::<Command>(cmd).unwrap(),
+        None,
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .unwrap()
+    .0
+}
+
+fn ops(events: &[HistoryEvent]) -> Vec<(Option<&str>, Option<&str>)> {
+    events
+        .iter()
+        .map(|e| (e.op.as_deref(), e.entity_id.as_deref()))
+        .collect()
+}
+
+#[test]
+fn test_history_has_one_event_per_applied_command() {
+    let (_dir, mut conn, cas) = setup();
+    let CommandResult::EttleCreate { ettle_id: root } = apply(
+        &mut conn,
+        &cas,
+        json!({ "tag": "EttleCreate", "title": "Root" }),
+    ) else {
+        panic!("unexpected result");
+    };
+    let CommandResult::GroupCreate { group_id } = apply(
+        &mut conn,
+        &cas,
+        json!({ "tag": "GroupCreate", "name": "Payments" }),
+    ) else {
+        panic!("unexpected result");
+    };
+    apply(
+        &mut conn,
+        &cas,
+        json!({ "tag": "EttleUpdate", "ettle_id": root, "title": "Root v2" }),
+    );
+    let token = SqliteApprovalRouter::new_with_cas(&mut conn, &cas)
+        .route_approval_request("AmbiguousSelection", vec!["root".to_string()])
+        .unwrap();
+    apply(
+        &mut conn,
+        &cas,
+        json!({ "tag": "ApprovalTransition", "approval_token": token, "status": "approved",
+                "actor": "alice" }),
+    );
+
+    let events = read_history(&conn, 0).unwrap();
+    assert_eq!(
+        ops(&events),
+        [
+            (Some("ettle_create"), Some(root.as_str())),
+            (Some("group_create"), Some(group_id.as_str())),
+            (Some("ettle_update"), Some(root.as_str())),
+            (Some("approval_transition"), Some(token.as_str())),
+        ]
+    );
+    assert_eq!(
+        events.iter().map(|e| e.seq).collect::<Vec<_>>(),
+        [1, 2, 3, 4]
+    );
+    assert!(events
+        .windows(2)
+        .all(|w| w[0].occurred_at <= w[1].occurred_at));
+    assert!(events[0].occurred_at.ends_with('Z'));
+}
+
+#[test]
+fn test_sequence_numbers_are_append_only() {
+    let (_dir, mut conn, cas) = setup();
+    let CommandResult::EttleCreate { ettle_id } = apply(
+        &mut conn,
+        &cas,
+        json!({ "tag": "EttleCreate", "title": "A" }),
+    ) else {
+        panic!("unexpected result");
+    };
+    apply(
+        &mut conn,
+        &cas,
+        json!({ "tag": "EttleUpdate", "ettle_id": ettle_id, "what": "first" }),
+    );
+    let before = read_history(&conn, 0).unwrap();
+
+    // Editing again, undoing and a failed command never rewrite earlier events
+    apply(
+        &mut conn,
+        &cas,
+        json!({ "tag": "EttleUpdate", "ettle_id": ettle_id, "what": "second" }),
+    );
+    apply_engine_command(
+        EngineCommand::Undo,
+        &mut conn,
+        &cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .unwrap();
+    let failed = apply_command(
+        Command::EttleTombstone {
+            ettle_id: "ettle:missing".to_string(),
+        },
+        None,
+        &mut conn,
+        &cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    );
+    assert!(failed.is_err());
+
+    let after = read_history(&conn, 0).unwrap();
+    assert_eq!(after[..before.len()], before[..]);
+    assert_eq!(after.len(), before.len() + 2);
+    assert_eq!(after[2].op.as_deref(), Some("ettle_update"));
+    assert_eq!(after[3].entity_id.as_deref(), Some(ettle_id.as_str()));
+    assert_eq!(read_history(&conn, 2).unwrap(), after[2..]);
+}
+
+#[test]
+fn test_export_writes_jsonl_after_seq() {
+    let (_dir, mut conn, cas) = setup();
+    apply(
+        &mut conn,
+        &cas,
+        json!({ "tag": "EttleCreate", "title": "A" }),
+    );
+    apply(
+        &mut conn,
+        &cas,
+        json!({ "tag": "EttleCreate", "title": "B" }),
+    );
+
+    let mut out = Vec::new();
+    assert_eq!(export_history_jsonl(&conn, 0, &mut out).unwrap(), 2);
+    let lines: Vec<Value> = String::from_utf8(out)
+        .unwrap()
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(lines[1]["seq"], json!(2));
+    assert_eq!(lines[1]["op"], json!("ettle_create"));
+
+    let mut out = Vec::new();
+    assert_eq!(export_history_jsonl(&conn, 1, &mut out).unwrap(), 1);
+    assert!(String::from_utf8(out).unwrap().contains("\"seq\":2"));
+}
+
+#[test]
+fn test_rows_logged_before_ops_were_recorded_have_no_op() {
+    let (_dir, conn, _cas) = setup();
+    conn.execute(
+        "INSERT INTO command_log (applied_at) VALUES ('2026-01-02T00:00:00+00:00')",
+        [],
+    )
+    .unwrap();
+
+    let events = read_history(&conn, 0).unwrap();
+    assert_eq!(ops(&events), [(None, None)]);
+    assert_eq!(events[0].occurred_at, "2026-01-02T00:00:00.000Z");
+}
+
+#[test]
+fn test_unparseable_timestamp_is_reported() {
+    let (_dir, conn, _cas) = setup();
+    conn.execute(
+        "INSERT INTO command_log (applied_at, op) VALUES ('yesterday', 'ettle_create')",
+        [],
+    )
+    .unwrap();
+
+    let err = read_history(&conn, 0).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::Serialization);
+}
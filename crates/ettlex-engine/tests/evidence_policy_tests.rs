@@ -0,0 +1,223 @@
+// Integration tests for decision evidence quotas and the retention sweep.
+// Limits come from the default profile's `evidence` payload key.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::ExErrorKind;
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_engine::commands::command::{apply_command, Command};
+use ettlex_engine::commands::decision::{decision_create, decision_tombstone, decision_update};
+use ettlex_engine::commands::evidence::{evidence_sweep, load_evidence_policy};
+use ettlex_store::cas::FsStore;
+use rusqlite::Connection;
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+fn setup_db() -> (TempDir, Connection) {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("test.db");
+    let mut conn = Connection::open(&db_path).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    (temp_dir, conn)
+}
+
+fn set_default_profile(conn: &mut Connection, dir: &TempDir, payload: Value) {
+    let cas = FsStore::new(dir.path().join("cas"));
+    for cmd in [
+        Command::ProfileCreate {
+            profile_ref: "profile/evidence@0".to_string(),
+            payload_json: payload,
+            source: None,
+        },
+        Command::ProfileSetDefault {
+            profile_ref: "profile/evidence@0".to_string(),
+            environment: None,
+        },
+    ] {
+        apply_command(
+            cmd,
+            None,
+            conn,
+            &cas,
+            &NoopPolicyProvider,
+            &NoopApprovalRouter,
+        )
+        .unwrap();
+    }
+}
+
+fn create_with_capture(conn: &Connection, id: &str, capture: &str) -> ettlex_store::Result<String> {
+    decision_create(
+        Some(id.to_string()),
+        "Decision".to_string(),
+        None,
+        "Body.".to_string(),
+        "Rationale.".to_string(),
+        None,
+        None,
+        "capture".to_string(),
+        None,
+        Some(capture.to_string()),
+        None,
+        conn,
+    )
+}
+
+fn capture_count(conn: &Connection) -> i64 {
+    conn.query_row("SELECT COUNT(*) FROM decision_evidence_items", [], |r| {
+        r.get(0)
+    })
+    .unwrap()
+}
+
+/// Backdate a tombstone so it falls outside a retention window.
+fn tombstoned_days_ago(conn: &Connection, id: &str, days: i64) {
+    decision_tombstone(id.to_string(), conn).unwrap();
+    let at = chrono::Utc::now().timestamp_millis() - days * 24 * 60 * 60 * 1000;
+    conn.execute(
+        "UPDATE decisions SET tombstoned_at = ?1 WHERE decision_id = ?2",
+        rusqlite::params![at, id],
+    )
+    .unwrap();
+}
+
+// ---------------------------------------------------------------------------
+// Quotas
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_capture_over_blob_limit_is_rejected() {
+    let (tmp, mut conn) = setup_db();
+    set_default_profile(
+        &mut conn,
+        &tmp,
+        json!({ "evidence": { "max_blob_bytes": 8 } }),
+    );
+
+    create_with_capture(&conn, "decision:small", "8 bytes!").unwrap();
+    let err = create_with_capture(&conn, "decision:big", "nine byte").unwrap_err();
+
+    assert_eq!(err.kind(), ExErrorKind::InvalidEvidence);
+    assert_eq!(capture_count(&conn), 1);
+}
+
+#[test]
+fn test_total_limit_counts_excerpt_and_capture_only_when_evidence_changes() {
+    let (tmp, mut conn) = setup_db();
+    create_with_capture(&conn, "decision:a", "0123456789").unwrap();
+    set_default_profile(
+        &mut conn,
+        &tmp,
+        json!({ "evidence": { "max_total_bytes_per_decision": 12 } }),
+    );
+
+    // A title edit is not blocked by evidence recorded before the limit.
+    decision_update(
+        "decision:a".to_string(),
+        Some("Renamed".to_string()),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        &conn,
+    )
+    .unwrap();
+
+    // Adding a 3-byte excerpt takes the total to 13.
+    let err = decision_update(
+        "decision:a".to_string(),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        Some(Some("abc".to_string())),
+        None,
+        None,
+        &conn,
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidEvidence);
+}
+
+#[test]
+fn test_malformed_policy_is_rejected() {
+    let (tmp, mut conn) = setup_db();
+    set_default_profile(&mut conn, &tmp, json!({ "evidence": { "max_blobs": 1 } }));
+
+    let err = load_evidence_policy(&conn).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+}
+
+// ---------------------------------------------------------------------------
+// Retention sweep
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_sweep_prunes_only_captures_past_retention() {
+    let (tmp, mut conn) = setup_db();
+    let cas = FsStore::new(tmp.path().join("cas"));
+    create_with_capture(&conn, "decision:old", "old capture").unwrap();
+    create_with_capture(&conn, "decision:recent", "recent").unwrap();
+    create_with_capture(&conn, "decision:live", "live").unwrap();
+    tombstoned_days_ago(&conn, "decision:old", 40);
+    tombstoned_days_ago(&conn, "decision:recent", 5);
+
+    let sweeps = |conn: &Connection| -> (i64, i64) {
+        let count = |sql: &str| conn.query_row(sql, [], |r| r.get(0)).unwrap();
+        (
+            count("SELECT COUNT(*) FROM provenance_events WHERE kind = 'evidence_swept'"),
+            count("SELECT COUNT(*) FROM command_log"),
+        )
+    };
+    let before = sweeps(&conn);
+
+    let preview = evidence_sweep(&mut conn, &cas, Some(30), true).unwrap();
+    assert_eq!(preview.pruned.len(), 1);
+    assert_eq!(capture_count(&conn), 3);
+    assert_eq!(sweeps(&conn), before);
+
+    let report = evidence_sweep(&mut conn, &cas, Some(30), false).unwrap();
+    assert_eq!(report, preview);
+    // Applied as a command: one provenance event and one command_log row
+    assert_eq!(sweeps(&conn), (before.0 + 1, before.1 + 1));
+    assert_eq!(report.pruned[0].decision_id, "decision:old");
+    assert_eq!(report.bytes_freed, "old capture".len() as u64);
+    assert_eq!(capture_count(&conn), 2);
+
+    let capture_id: Option<String> = conn
+        .query_row(
+            "SELECT evidence_capture_id FROM decisions WHERE decision_id = 'decision:old'",
+            [],
+            |r| r.get(0),
+        )
+        .unwrap();
+    assert_eq!(capture_id, None);
+}
+
+#[test]
+fn test_sweep_uses_profile_retention_and_requires_one() {
+    let (tmp, mut conn) = setup_db();
+    let cas = FsStore::new(tmp.path().join("cas"));
+    create_with_capture(&conn, "decision:old", "old capture").unwrap();
+    tombstoned_days_ago(&conn, "decision:old", 10);
+
+    let err = evidence_sweep(&mut conn, &cas, None, false).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+
+    set_default_profile(
+        &mut conn,
+        &tmp,
+        json!({ "evidence": { "retention_days": 7 } }),
+    );
+    let report = evidence_sweep(&mut conn, &cas, None, false).unwrap();
+    assert_eq!(report.pruned.len(), 1);
+}
@@ -0,0 +1,87 @@
+//! Feature flag registry: defaults, repository settings, `ETTLEX_FEATURES`
+//! style overrides and the `NotImplemented` gate.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::errors::ExErrorKind;
+use ettlex_engine::feature_flags::{
+    feature_state, list_features, parse_feature_overrides, require_feature, set_feature,
+    set_feature_overrides, FeatureSource, FEATURE_FLAGS,
+};
+use ettlex_store::cas::FsStore;
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+fn setup() -> (TempDir, Connection, FsStore) {
+    let dir = TempDir::new().unwrap();
+    let mut conn = Connection::open(dir.path().join("test.db")).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    let cas = FsStore::new(dir.path().join("cas"));
+    (dir, conn, cas)
+}
+
+#[test]
+fn test_settings_toggle_flags_and_gate_commands() {
+    let (_dir, mut conn, cas) = setup();
+    // Only declarative_apply is touched here; overrides are tested on the other flag
+    let state = feature_state(&conn, "declarative_apply").unwrap();
+    assert!(state.enabled);
+    assert_eq!(state.source, FeatureSource::Default);
+    require_feature(&conn, "declarative_apply").unwrap();
+
+    set_feature(&mut conn, &cas, "declarative_apply", Some(false)).unwrap();
+    let state = feature_state(&conn, "declarative_apply").unwrap();
+    assert!(!state.enabled);
+    assert_eq!(state.source, FeatureSource::Setting);
+    let err = require_feature(&conn, "declarative_apply").unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotImplemented);
+
+    set_feature(&mut conn, &cas, "declarative_apply", None).unwrap();
+    require_feature(&conn, "declarative_apply").unwrap();
+
+    // Both changes were logged commands
+    let logged: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM provenance_events WHERE kind = 'feature_set'",
+            [],
+            |r| r.get(0),
+        )
+        .unwrap();
+    assert_eq!(logged, 2);
+
+    let names: Vec<String> = list_features(&conn)
+        .unwrap()
+        .into_iter()
+        .map(|s| s.name)
+        .collect();
+    assert_eq!(names.len(), FEATURE_FLAGS.len());
+    assert!(names.windows(2).all(|w| w[0] < w[1]));
+}
+
+#[test]
+fn test_overrides_take_precedence_over_settings() {
+    let (_dir, mut conn, cas) = setup();
+    let err = require_feature(&conn, "three_way_diff").unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotImplemented);
+
+    set_feature(&mut conn, &cas, "three_way_diff", Some(true)).unwrap();
+    set_feature_overrides(parse_feature_overrides(" -three_way_diff ").unwrap());
+    let state = feature_state(&conn, "three_way_diff").unwrap();
+    set_feature_overrides(Default::default());
+    assert!(!state.enabled);
+    assert_eq!(state.source, FeatureSource::Override);
+
+    require_feature(&conn, "three_way_diff").unwrap();
+}
+
+#[test]
+fn test_unknown_flags_are_rejected() {
+    let (_dir, mut conn, cas) = setup();
+    for err in [
+        feature_state(&conn, "warp_drive").unwrap_err(),
+        set_feature(&mut conn, &cas, "warp_drive", Some(true)).unwrap_err(),
+        parse_feature_overrides("three_way_diff,warp_drive").unwrap_err(),
+    ] {
+        assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+    }
+}
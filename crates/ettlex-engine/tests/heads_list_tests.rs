@@ -0,0 +1,137 @@
+//! `HeadsList`: per-root ledger heads with age and drift against the
+//! working state.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::ops::Store;
+use ettlex_core::snapshot::manifest::generate_manifest_with_input;
+use ettlex_engine::commands::engine_query::{apply_engine_query, EngineQuery, EngineQueryResult};
+use ettlex_engine::commands::heads::{HeadDrift, HeadsList, RootHeadStatus};
+use ettlex_engine::snapshot::manifest_eval_input;
+use ettlex_store::cas::FsStore;
+use ettlex_store::snapshot::persist::{commit_snapshot, SnapshotOptions};
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+fn setup() -> (TempDir, Connection, FsStore) {
+    let temp_dir = TempDir::new().unwrap();
+    let mut conn = Connection::open(temp_dir.path().join("test.db")).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    let cas = FsStore::new(temp_dir.path().join("cas"));
+    for id in ["ettle:a", "ettle:b", "ettle:c", "ettle:d", "ettle:e"] {
+        conn.execute(
+            "INSERT INTO ettles (id, title, created_at, updated_at)
+             VALUES (?1, ?1, '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z')",
+            [id],
+        )
+        .unwrap();
+    }
+    (temp_dir, conn, cas)
+}
+
+fn register(conn: &Connection, ettle_id: &str, name: &str, status: &str) {
+    conn.execute(
+        "INSERT INTO roots (ettle_id, display_name, status, registered_at_ms, updated_at_ms)
+         VALUES (?1, ?2, ?3, 0, 0)",
+        [ettle_id, name, status],
+    )
+    .unwrap();
+}
+
+/// Commit a snapshot of `root` whose EPT is `ept`.
+fn commit(conn: &mut Connection, cas: &FsStore, root: &str, ept: &[&str]) -> String {
+    let ept: Vec<String> = ept.iter().map(|s| s.to_string()).collect();
+    let input = manifest_eval_input(conn, cas, &ept, "profile/default@0").unwrap();
+    let manifest = generate_manifest_with_input(
+        ept,
+        "policy/default@0".into(),
+        "profile/default@0".into(),
+        root.into(),
+        "0001".into(),
+        None,
+        &Store::new(),
+        &input,
+    )
+    .unwrap();
+    commit_snapshot(conn, cas, manifest, SnapshotOptions::default())
+        .unwrap()
+        .snapshot_id
+}
+
+fn heads(conn: &Connection, cas: &FsStore) -> HeadsList {
+    match apply_engine_query(EngineQuery::HeadsList, conn, cas, None).unwrap() {
+        EngineQueryResult::HeadsList(h) => h,
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+fn summary(list: &HeadsList) -> Vec<(&str, HeadDrift)> {
+    list.heads
+        .iter()
+        .map(|h: &RootHeadStatus| (h.root_ettle_id.as_str(), h.drift))
+        .collect()
+}
+
+#[test]
+fn test_heads_list_reports_drift_per_root() {
+    let (_tmp, mut conn, cas) = setup();
+    register(&conn, "ettle:a", "Alpha", "active");
+    register(&conn, "ettle:d", "Delta", "active");
+
+    commit(&mut conn, &cas, "ettle:a", &["ettle:b"]);
+    let head_a = commit(&mut conn, &cas, "ettle:a", &["ettle:a"]);
+    // The working EPT of ettle:b is just ettle:b
+    commit(&mut conn, &cas, "ettle:b", &["ettle:b", "ettle:c"]);
+    commit(&mut conn, &cas, "ettle:c", &["ettle:c"]);
+    commit(&mut conn, &cas, "ettle:e", &["ettle:e"]);
+    register(&conn, "ettle:e", "Echo", "retired");
+    conn.execute(
+        "UPDATE ettles SET tombstoned_at = '2024-02-01T00:00:00Z' WHERE id = 'ettle:c'",
+        [],
+    )
+    .unwrap();
+
+    let list = heads(&conn, &cas);
+
+    assert_eq!(
+        summary(&list),
+        vec![
+            ("ettle:a", HeadDrift::InSync),
+            ("ettle:b", HeadDrift::Drifted),
+            ("ettle:c", HeadDrift::Drifted),
+            ("ettle:d", HeadDrift::NoHead),
+        ]
+    );
+    let a = &list.heads[0];
+    assert_eq!(a.display_name.as_deref(), Some("Alpha"));
+    assert_eq!(a.snapshot_id.as_deref(), Some(head_a.as_str()));
+    assert_eq!(a.working_semantic_digest, a.semantic_manifest_digest);
+    assert!(a.committed_at_ms.is_some() && a.age_ms.is_some());
+
+    let b = &list.heads[1];
+    assert!(b.working_semantic_digest.is_some());
+    assert_ne!(b.working_semantic_digest, b.semantic_manifest_digest);
+    assert!(list.heads[2].working_semantic_digest.is_none());
+    assert!(list.heads[3].snapshot_id.is_none() && list.heads[3].age_ms.is_none());
+
+    let stale: Vec<&str> = list.stale().map(|h| h.root_ettle_id.as_str()).collect();
+    assert_eq!(stale, vec!["ettle:b", "ettle:c"]);
+}
+
+#[test]
+fn test_heads_list_missing_manifest_blob_is_unknown() {
+    let (tmp, mut conn, cas) = setup();
+    commit(&mut conn, &cas, "ettle:a", &["ettle:a"]);
+    std::fs::remove_dir_all(tmp.path().join("cas")).unwrap();
+
+    let list = heads(&conn, &cas);
+
+    assert_eq!(summary(&list), vec![("ettle:a", HeadDrift::Unknown)]);
+    assert!(list.heads[0].snapshot_id.is_some());
+}
+
+#[test]
+fn test_heads_list_empty_workspace() {
+    let (_tmp, conn, cas) = setup();
+    assert!(heads(&conn, &cas).heads.is_empty());
+}
@@ -0,0 +1,240 @@
+//! Import session tests — batched imports, automatic rollback on
+//! error, and `import recover` finishing or undoing interrupted sessions.
+
+#![allow(clippy::unwrap_used, clippy::result_large_err)]
+
+use std::path::PathBuf;
+
+use ettlex_core::errors::ExErrorKind;
+use ettlex_engine::commands::csv_import::{import_csv_file, resume_csv_import, CsvColumnMapping};
+use ettlex_engine::commands::import_session::{
+    incomplete_import_sessions, rollback_import_session,
+};
+use ettlex_store::cas::FsStore;
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+const TREE: &str = "\
+ref,parent,title,ordinal
+root,,Root,
+a,root,First,1
+b,root,Second,2
+";
+
+fn setup(csv: &str) -> (Connection, FsStore, PathBuf, TempDir) {
+    let dir = TempDir::new().unwrap();
+    let mut conn = Connection::open(dir.path().join("store.db")).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    let cas = FsStore::new(dir.path().join("cas"));
+    let path = dir.path().join("tree.csv");
+    std::fs::write(&path, csv).unwrap();
+    (conn, cas, path, dir)
+}
+
+fn count(conn: &Connection, table: &str) -> i64 {
+    conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |r| r.get(0))
+        .unwrap()
+}
+
+fn session_status(conn: &Connection) -> String {
+    conn.query_row("SELECT status FROM import_sessions", [], |r| r.get(0))
+        .unwrap()
+}
+
+/// Import `TREE`, then wind the store back to how an import killed after
+/// creating `root` and `a` (line 3) would have left it.
+fn interrupted_import() -> (Connection, FsStore, PathBuf, TempDir, String) {
+    let (mut conn, cas, path, dir) = setup(TREE);
+    import_csv_file(
+        &mut conn,
+        &cas,
+        &path,
+        &CsvColumnMapping::default(),
+        Some(1),
+    )
+    .unwrap();
+    conn.execute_batch(
+        "DELETE FROM relations WHERE id IN (SELECT entity_id FROM import_session_items
+                                            WHERE source_line = 4);
+         DELETE FROM ettles WHERE id IN (SELECT entity_id FROM import_session_items
+                                         WHERE source_line = 4);
+         DELETE FROM import_session_items WHERE source_line = 4;
+         UPDATE import_sessions SET status = 'running', finished_at_ms = NULL;",
+    )
+    .unwrap();
+    let session_id = incomplete_import_sessions(&conn).unwrap()[0]
+        .session_id
+        .clone();
+    (conn, cas, path, dir, session_id)
+}
+
+#[test]
+fn test_batched_import_records_a_completed_session() {
+    let (mut conn, cas, path, _dir) = setup(TREE);
+    let report = import_csv_file(
+        &mut conn,
+        &cas,
+        &path,
+        &CsvColumnMapping::default(),
+        Some(2),
+    )
+    .unwrap();
+
+    assert_eq!(report.ettles.len(), 3);
+    assert_eq!(report.refinements, 2);
+    assert_eq!(session_status(&conn), "completed");
+    assert_eq!(count(&conn, "import_session_items"), 5);
+    assert!(incomplete_import_sessions(&conn).unwrap().is_empty());
+}
+
+#[test]
+fn test_failed_batched_import_rolls_back_committed_batches() {
+    let csv = "\
+ref,parent,title
+root,,Root
+a,root,First
+b,missing,Orphan
+";
+    let (mut conn, cas, path, _dir) = setup(csv);
+    let err = import_csv_file(
+        &mut conn,
+        &cas,
+        &path,
+        &CsvColumnMapping::default(),
+        Some(1),
+    )
+    .unwrap_err();
+
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+    assert_eq!(count(&conn, "ettles"), 0);
+    assert_eq!(count(&conn, "relations"), 0);
+    assert_eq!(session_status(&conn), "rolled_back");
+}
+
+#[test]
+fn test_recover_completes_an_interrupted_import() {
+    let (mut conn, cas, _path, _dir, session_id) = interrupted_import();
+    assert_eq!(count(&conn, "ettles"), 2);
+
+    let report = resume_csv_import(&mut conn, &cas, &session_id).unwrap();
+    assert_eq!(report.ettles.len(), 3);
+    assert_eq!(report.refinements, 2);
+    assert_eq!(count(&conn, "ettles"), 3);
+    assert_eq!(count(&conn, "relations"), 2);
+    assert_eq!(session_status(&conn), "completed");
+
+    let err = resume_csv_import(&mut conn, &cas, &session_id).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_recover_rolls_back_an_interrupted_import() {
+    let (mut conn, cas, _path, _dir, session_id) = interrupted_import();
+
+    let removed = rollback_import_session(&mut conn, &cas, &session_id).unwrap();
+    assert_eq!((removed.ettles, removed.relations), (2, 1));
+    assert_eq!(count(&conn, "ettles"), 0);
+    assert_eq!(count(&conn, "relations"), 0);
+    assert_eq!(session_status(&conn), "rolled_back");
+
+    let err = rollback_import_session(&mut conn, &cas, "imp:missing").unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+}
+
+#[test]
+fn test_recover_refuses_an_edited_source_or_outside_references() {
+    let (mut conn, cas, path, _dir, session_id) = interrupted_import();
+
+    std::fs::write(&path, format!("{}c,root,Third,3\n", TREE)).unwrap();
+    let err = resume_csv_import(&mut conn, &cas, &session_id).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::HeadMismatch);
+
+    conn.execute_batch(
+        "INSERT INTO ettles (id, title, reasoning_link_id, created_at, updated_at)
+         SELECT 'ettle:outside', 'Outside', entity_id, '2024-01-01', '2024-01-01'
+         FROM import_session_items WHERE source_line = 2;",
+    )
+    .unwrap();
+    let err = rollback_import_session(&mut conn, &cas, &session_id).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::HasActiveDependants);
+    assert_eq!(count(&conn, "ettles"), 3);
+}
+
+#[test]
+fn test_recover_rollback_is_a_logged_command_that_nests() {
+    let (mut conn, cas, _path, _dir, session_id) = interrupted_import();
+    let journal_before = count(&conn, "command_journal");
+    let log_before = count(&conn, "command_log");
+
+    // Runs as a savepoint inside the caller's transaction
+    conn.execute_batch("SAVEPOINT caller").unwrap();
+    let removed = rollback_import_session(&mut conn, &cas, &session_id).unwrap();
+    conn.execute_batch("RELEASE caller").unwrap();
+
+    assert_eq!((removed.ettles, removed.relations), (2, 1));
+    assert_eq!(count(&conn, "command_log"), log_before + 1);
+    // The import's own create entries go with the entities
+    assert_eq!(count(&conn, "command_journal"), journal_before - 3);
+    let logged: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM provenance_events WHERE kind = 'import_session_rolled_back'",
+            [],
+            |r| r.get(0),
+        )
+        .unwrap();
+    assert_eq!(logged, 1);
+}
+
+#[test]
+fn test_recover_refuses_comments_assignments_and_journaled_edits() {
+    let (mut conn, cas, _path, _dir, session_id) = interrupted_import();
+    let root = "(SELECT entity_id FROM import_session_items WHERE source_line = 2)";
+
+    for (insert, cleanup) in [
+        (
+            format!(
+                "INSERT INTO comments (id, target_kind, target_id, body, created_at)
+                 VALUES ('cmt:1', 'ettle', {}, 'note', '2024-01-01')",
+                root
+            ),
+            "DELETE FROM comments",
+        ),
+        (
+            format!(
+                "INSERT INTO ettle_assignments (ettle_id, role, principal, assigned_at_ms)
+                 VALUES ({}, 'owner', 'ana', 0)",
+                root
+            ),
+            "DELETE FROM ettle_assignments",
+        ),
+        (
+            format!(
+                "INSERT INTO decisions (decision_id, title, decision_text, rationale,
+                                        evidence_kind, created_at, updated_at)
+                 VALUES ('d:1', 'D', 'text', 'why', 'none', 0, 0);
+                 INSERT INTO decision_links (decision_id, target_kind, target_id, relation_kind,
+                                             created_at)
+                 VALUES ('d:1', 'ettle', {}, 'grounds', 0)",
+                root
+            ),
+            "DELETE FROM decision_links; DELETE FROM decisions",
+        ),
+        (
+            format!(
+                "INSERT INTO command_journal (op, entity_id, undo_json, redo_json, recorded_at_ms)
+                 VALUES ('ettle_update', {}, '[]', '[]', 0)",
+                root
+            ),
+            "DELETE FROM command_journal WHERE op = 'ettle_update'",
+        ),
+    ] {
+        conn.execute_batch(&insert).unwrap();
+        let err = rollback_import_session(&mut conn, &cas, &session_id).unwrap_err();
+        assert_eq!(err.kind(), ExErrorKind::HasActiveDependants, "{}", insert);
+        assert_eq!(count(&conn, "ettles"), 2);
+        conn.execute_batch(cleanup).unwrap();
+    }
+
+    rollback_import_session(&mut conn, &cas, &session_id).unwrap();
+    assert_eq!(count(&conn, "ettles"), 0);
+}
@@ -0,0 +1,223 @@
+//! Leaf discovery tests — `LeafList` over the refinement tree, subtree
+//! scoping, exclusion of tombstoned/archived children, and pagination.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_engine::commands::command::{apply_command, Command, CommandResult};
+use ettlex_engine::commands::engine_query::{apply_engine_query, EngineQuery, EngineQueryResult};
+use ettlex_engine::commands::read_tools::{EttlePage, ListOptions};
+use ettlex_store::cas::FsStore;
+use ettlex_store::migrations::apply_migrations;
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+// ---------------------------------------------------------------------------
+// Test helpers
+// ---------------------------------------------------------------------------
+
+fn setup() -> (Connection, FsStore, TempDir) {
+    let dir = TempDir::new().expect("temp dir");
+    let cas = FsStore::new(dir.path().join("cas"));
+    let mut conn = Connection::open_in_memory().expect("in-memory db");
+    apply_migrations(&mut conn).expect("migrations should apply");
+    (conn, cas, dir)
+}
+
+fn apply(conn: &mut Connection, cas: &FsStore, cmd: Command) -> CommandResult {
+    apply_command(
+        cmd,
+        None,
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .expect("command should succeed")
+    .0
+}
+
+fn create_ettle(conn: &mut Connection, cas: &FsStore, title: &str) -> String {
+    match apply(
+        conn,
+        cas,
+        Command::EttleCreate {
+            title: title.to_string(),
+            ettle_id: None,
+            why: None,
+            what: None,
+            how: None,
+            reasoning_link_id: None,
+            reasoning_link_type: None,
+        },
+    ) {
+        CommandResult::EttleCreate { ettle_id } => ettle_id,
+        _ => panic!("unexpected result"),
+    }
+}
+
+fn refine(conn: &mut Connection, cas: &FsStore, parent: &str, child: &str) {
+    apply(
+        conn,
+        cas,
+        Command::RelationCreate {
+            source_ettle_id: parent.to_string(),
+            target_ettle_id: child.to_string(),
+            relation_type: "refinement".to_string(),
+            properties_json: None,
+            relation_id: None,
+        },
+    );
+}
+
+fn leaf_page(
+    conn: &Connection,
+    cas: &FsStore,
+    root: Option<&str>,
+    options: ListOptions,
+) -> Result<EttlePage, ExError> {
+    match apply_engine_query(
+        EngineQuery::LeafList {
+            root_ettle_id: root.map(String::from),
+            options,
+        },
+        conn,
+        cas,
+        None,
+    )? {
+        EngineQueryResult::LeafList(page) => Ok(page),
+        _ => panic!("unexpected result"),
+    }
+}
+
+fn leaf_ids(conn: &Connection, cas: &FsStore, root: Option<&str>) -> Vec<String> {
+    leaf_page(conn, cas, root, ListOptions::default())
+        .unwrap()
+        .items
+        .into_iter()
+        .map(|e| e.id)
+        .collect()
+}
+
+fn sorted(mut ids: Vec<String>) -> Vec<String> {
+    ids.sort();
+    ids
+}
+
+/// root → {a → {a1, a2}, b}; returns (root, a, a1, a2, b).
+fn build_tree(conn: &mut Connection, cas: &FsStore) -> (String, String, String, String, String) {
+    let root = create_ettle(conn, cas, "Root");
+    let a = create_ettle(conn, cas, "A");
+    let a1 = create_ettle(conn, cas, "A1");
+    let a2 = create_ettle(conn, cas, "A2");
+    let b = create_ettle(conn, cas, "B");
+    refine(conn, cas, &root, &a);
+    refine(conn, cas, &root, &b);
+    refine(conn, cas, &a, &a1);
+    refine(conn, cas, &a, &a2);
+    (root, a, a1, a2, b)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_leaf_list_returns_childless_ettles() {
+    let (mut conn, cas, _dir) = setup();
+    let (_root, _a, a1, a2, b) = build_tree(&mut conn, &cas);
+    let unrelated = create_ettle(&mut conn, &cas, "Standalone");
+
+    assert_eq!(
+        leaf_ids(&conn, &cas, None),
+        sorted(vec![a1, a2, b, unrelated])
+    );
+}
+
+#[test]
+fn test_leaf_list_scoped_to_root_subtree() {
+    let (mut conn, cas, _dir) = setup();
+    let (root, a, a1, a2, b) = build_tree(&mut conn, &cas);
+    create_ettle(&mut conn, &cas, "Standalone");
+
+    assert_eq!(
+        leaf_ids(&conn, &cas, Some(&root)),
+        sorted(vec![a1.clone(), a2.clone(), b.clone()])
+    );
+    assert_eq!(leaf_ids(&conn, &cas, Some(&a)), sorted(vec![a1, a2]));
+    // A childless root is its own only leaf
+    assert_eq!(leaf_ids(&conn, &cas, Some(&b)), vec![b.clone()]);
+}
+
+#[test]
+fn test_leaf_list_ignores_tombstoned_and_archived_children() {
+    let (mut conn, cas, _dir) = setup();
+    let (_root, a, a1, a2, b) = build_tree(&mut conn, &cas);
+
+    apply(
+        &mut conn,
+        &cas,
+        Command::EttleArchive {
+            ettle_id: a1.clone(),
+            subtree: false,
+        },
+    );
+    apply(
+        &mut conn,
+        &cas,
+        Command::EttleTombstone {
+            ettle_id: a2.clone(),
+        },
+    );
+
+    // With both children gone, `a` becomes a leaf itself
+    assert_eq!(leaf_ids(&conn, &cas, None), sorted(vec![a, b]));
+}
+
+#[test]
+fn test_leaf_list_paginates_by_id() {
+    let (mut conn, cas, _dir) = setup();
+    let (_root, _a, a1, a2, b) = build_tree(&mut conn, &cas);
+    let expected = sorted(vec![a1, a2, b]);
+
+    let first = leaf_page(
+        &conn,
+        &cas,
+        None,
+        ListOptions {
+            limit: Some(2),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert!(first.has_more);
+    let second = leaf_page(
+        &conn,
+        &cas,
+        None,
+        ListOptions {
+            limit: Some(2),
+            cursor: first.cursor.clone(),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert!(!second.has_more);
+
+    let ids: Vec<String> = first
+        .items
+        .into_iter()
+        .chain(second.items)
+        .map(|e| e.id)
+        .collect();
+    assert_eq!(ids, expected);
+}
+
+#[test]
+fn test_leaf_list_unknown_root_not_found() {
+    let (conn, cas, _dir) = setup();
+    let err = leaf_page(&conn, &cas, Some("ettle:missing"), ListOptions::default()).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+}
@@ -0,0 +1,136 @@
+//! Manifest determinism check tests.
+//!
+//! Tests cover: repeated generation over a store of ettles is
+//! byte-identical and stable across calls, argument validation, and the
+//! relation data, content and template variables manifests evaluate
+//! constraint families against.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::errors::ExErrorKind;
+use ettlex_core::render::TemplateMode;
+use ettlex_engine::commands::determinism::verify_manifest_determinism;
+use ettlex_engine::snapshot::manifest_eval_input;
+use ettlex_store::cas::FsStore;
+use ettlex_store::migrations::apply_migrations;
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+fn setup() -> (TempDir, Connection, FsStore) {
+    let dir = TempDir::new().unwrap();
+    let mut conn = Connection::open_in_memory().expect("in-memory db");
+    apply_migrations(&mut conn).expect("migrations should apply");
+    for (id, title) in [("ettle:leaf", "Leaf"), ("ettle:b", "B"), ("ettle:c", "C")] {
+        conn.execute(
+            "INSERT INTO ettles (id, title, created_at, updated_at)
+             VALUES (?1, ?2, '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z')",
+            rusqlite::params![id, title],
+        )
+        .unwrap();
+    }
+    let cas = FsStore::new(dir.path().join("cas"));
+    (dir, conn, cas)
+}
+
+#[test]
+fn test_repeated_generation_is_byte_identical() {
+    let (_dir, conn, cas) = setup();
+
+    let report = verify_manifest_determinism(
+        &conn,
+        &cas,
+        "ettle:leaf",
+        "policy/default@0",
+        "profile/default@0",
+        8,
+    )
+    .unwrap();
+    assert_eq!(report.iterations, 8);
+    assert_eq!(report.manifest_bytes_digest.len(), 64);
+
+    let again = verify_manifest_determinism(
+        &conn,
+        &cas,
+        "ettle:leaf",
+        "policy/default@0",
+        "profile/default@0",
+        3,
+    )
+    .unwrap();
+    assert_eq!(again.manifest_bytes_digest, report.manifest_bytes_digest);
+    assert_eq!(
+        again.semantic_manifest_digest,
+        report.semantic_manifest_digest
+    );
+}
+
+#[test]
+fn test_determinism_check_validates_arguments() {
+    let (_dir, conn, cas) = setup();
+
+    let err = verify_manifest_determinism(&conn, &cas, "ettle:leaf", "p", "q", 1).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+    let err = verify_manifest_determinism(&conn, &cas, "ettle:missing", "p", "q", 2).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+}
+
+#[test]
+fn test_manifest_eval_input_loads_children_and_content() {
+    let (_dir, conn, cas) = setup();
+    for (id, child) in [("rel:1", "ettle:b"), ("rel:2", "ettle:c")] {
+        conn.execute(
+            "INSERT INTO relations (id, source_ettle_id, target_ettle_id, relation_type,
+                                    properties_json, created_at)
+             VALUES (?1, 'ettle:leaf', ?2, 'refinement', '{}', '2024-01-01T00:00:00Z')",
+            rusqlite::params![id, child],
+        )
+        .unwrap();
+    }
+    conn.execute(
+        "UPDATE relations SET tombstoned_at = '2024-01-02T00:00:00Z' WHERE id = 'rel:2'",
+        [],
+    )
+    .unwrap();
+
+    conn.execute(
+        "UPDATE ettles SET why = 'Because', what = 'Leaf body' WHERE id = 'ettle:leaf'",
+        [],
+    )
+    .unwrap();
+
+    let ept = vec![
+        "ettle:leaf".to_string(),
+        "ettle:b".to_string(),
+        "ettle:gone".to_string(),
+    ];
+    let input = manifest_eval_input(&conn, &cas, &ept, "profile/default@0").unwrap();
+    let children = input.children.unwrap();
+    assert_eq!(children.len(), 1);
+    assert_eq!(children["ettle:leaf"], vec!["ettle:b".to_string()]);
+    let content = input.content.unwrap();
+    assert_eq!(content.len(), 2);
+    assert_eq!(content["ettle:leaf"].why, "Because");
+    assert_eq!(content["ettle:leaf"].what, "Leaf body");
+    assert_eq!(content["ettle:b"].how, "");
+}
+
+#[test]
+fn test_manifest_eval_input_takes_template_variables_from_profile() {
+    let (_dir, conn, cas) = setup();
+    ettlex_store::profile::create_profile(
+        &conn,
+        "profile/prod@0",
+        &serde_json::json!({ "variables": { "env": "prod" }, "strict_variables": true }),
+    )
+    .unwrap();
+    let ept = vec!["ettle:leaf".to_string()];
+
+    let input = manifest_eval_input(&conn, &cas, &ept, "profile/prod@0").unwrap();
+    assert_eq!(input.variables.unwrap()["env"], "prod");
+    assert_eq!(input.template_mode, TemplateMode::Strict);
+
+    // A profile that was never stored binds nothing
+    let input = manifest_eval_input(&conn, &cas, &ept, "profile/default@0").unwrap();
+    assert!(input.variables.unwrap().is_empty());
+    assert_eq!(input.template_mode, TemplateMode::Lenient);
+}
@@ -0,0 +1,212 @@
+//! Cross-page pagination properties.
+//!
+//! Rows are seeded with heavily colliding timestamps written in different
+//! RFC 3339 spellings of the same instant. Walking every page must yield each
+//! row exactly once, in `(created_at_ms, id)` order, for any page size.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_engine::commands::engine_query::{apply_engine_query, EngineQuery, EngineQueryResult};
+use ettlex_engine::commands::read_tools::ListOptions;
+use ettlex_store::cas::FsStore;
+use ettlex_store::migrations::apply_migrations;
+use ettlex_store::model::{CommentRecord, RelationListOpts, RelationRecord};
+use ettlex_store::repo::{PageKey, SqliteRepo};
+use proptest::prelude::*;
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+// ---------------------------------------------------------------------------
+// Test helpers
+// ---------------------------------------------------------------------------
+
+fn setup() -> (Connection, FsStore, TempDir) {
+    let dir = TempDir::new().expect("temp dir");
+    let cas = FsStore::new(dir.path().join("cas"));
+    let mut conn = Connection::open_in_memory().expect("in-memory db");
+    apply_migrations(&mut conn).expect("migrations should apply");
+    conn.execute(
+        "INSERT INTO ettles (id, title, created_at, updated_at) \
+         VALUES ('ettle:a', 'A', '2026-01-01T00:00:00Z', '2026-01-01T00:00:00Z'), \
+                ('ettle:b', 'B', '2026-01-01T00:00:00Z', '2026-01-01T00:00:00Z')",
+        [],
+    )
+    .expect("seed ettles");
+    (conn, cas, dir)
+}
+
+/// The same instant (second `s` of the fixture minute) spelled three ways.
+fn timestamp(second: u8, spelling: u8) -> String {
+    match spelling {
+        0 => format!("2026-01-01T00:00:0{}Z", second),
+        1 => format!("2026-01-01T00:00:0{}.000+00:00", second),
+        _ => format!("2026-01-01T01:00:0{}+01:00", second),
+    }
+}
+
+/// `(second, spelling)` per row; row IDs are derived from the index.
+fn rows() -> impl Strategy<Value = Vec<(u8, u8)>> {
+    prop::collection::vec((0u8..3, 0u8..3), 1..25)
+}
+
+fn expected_order(keys: &mut [PageKey]) -> Vec<String> {
+    keys.sort();
+    keys.iter().map(|k| k.id.clone()).collect()
+}
+
+fn walk_comments(conn: &Connection, cas: &FsStore, limit: usize) -> Vec<String> {
+    let mut seen = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page = match apply_engine_query(
+            EngineQuery::CommentList {
+                target_kind: "ettle".to_string(),
+                target_id: "ettle:a".to_string(),
+                include_resolved: true,
+                options: ListOptions {
+                    limit: Some(limit),
+                    cursor,
+                    ..Default::default()
+                },
+            },
+            conn,
+            cas,
+            None,
+        )
+        .expect("comment list")
+        {
+            EngineQueryResult::CommentList(page) => page,
+            _ => panic!("expected CommentList"),
+        };
+        seen.extend(page.items.into_iter().map(|c| c.id));
+        if !page.has_more {
+            return seen;
+        }
+        cursor = page.cursor;
+    }
+}
+
+fn walk_decisions(conn: &Connection, cas: &FsStore, limit: usize) -> Vec<String> {
+    let mut seen = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page = match apply_engine_query(
+            EngineQuery::DecisionList(ListOptions {
+                limit: Some(limit),
+                cursor,
+                ..Default::default()
+            }),
+            conn,
+            cas,
+            None,
+        )
+        .expect("decision list")
+        {
+            EngineQueryResult::DecisionList(page) => page,
+            _ => panic!("expected DecisionList"),
+        };
+        seen.extend(page.items.into_iter().map(|d| d.decision_id));
+        if !page.has_more {
+            return seen;
+        }
+        cursor = page.cursor;
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Properties
+// ---------------------------------------------------------------------------
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(48))]
+
+    #[test]
+    fn prop_comment_pages_cover_every_row_once_in_key_order(
+        seed in rows(),
+        limit in 1usize..6,
+    ) {
+        let (conn, cas, _dir) = setup();
+        let mut keys = Vec::new();
+        // Insert in reverse so insertion order never accidentally matches key order
+        for (i, (second, spelling)) in seed.iter().enumerate().rev() {
+            let created_at = timestamp(*second, *spelling);
+            let id = format!("cmt:{:03}", i);
+            keys.push(PageKey::from_timestamp(&created_at, id.clone()));
+            SqliteRepo::insert_comment(
+                &conn,
+                &CommentRecord {
+                    id,
+                    target_kind: "ettle".to_string(),
+                    target_id: "ettle:a".to_string(),
+                    parent_comment_id: None,
+                    author: None,
+                    body: "x".to_string(),
+                    created_at,
+                    resolved_at_ms: None,
+                },
+            )
+            .unwrap();
+        }
+
+        prop_assert_eq!(walk_comments(&conn, &cas, limit), expected_order(&mut keys));
+    }
+
+    #[test]
+    fn prop_decision_pages_cover_every_row_once_in_key_order(
+        seed in prop::collection::vec(0i64..3, 1..25),
+        limit in 1usize..6,
+    ) {
+        let (conn, cas, _dir) = setup();
+        let mut keys = Vec::new();
+        for (i, ms) in seed.iter().enumerate().rev() {
+            let id = format!("d:{:03}", i);
+            keys.push(PageKey::new(*ms, id.clone()));
+            conn.execute(
+                "INSERT INTO decisions (decision_id, title, decision_text, rationale, \
+                 evidence_kind, evidence_hash, created_at, updated_at) \
+                 VALUES (?1, 't', 'x', 'r', 'none', '', ?2, ?2)",
+                rusqlite::params![id, ms],
+            )
+            .unwrap();
+        }
+
+        prop_assert_eq!(walk_decisions(&conn, &cas, limit), expected_order(&mut keys));
+    }
+
+    #[test]
+    fn prop_relation_listing_follows_key_order(seed in rows()) {
+        let (conn, _cas, _dir) = setup();
+        let mut keys = Vec::new();
+        for (i, (second, spelling)) in seed.iter().enumerate().rev() {
+            let created_at = timestamp(*second, *spelling);
+            let id = format!("rel:{:03}", i);
+            keys.push(PageKey::from_timestamp(&created_at, id.clone()));
+            SqliteRepo::insert_relation(
+                &conn,
+                &RelationRecord {
+                    id,
+                    source_ettle_id: "ettle:a".to_string(),
+                    target_ettle_id: "ettle:b".to_string(),
+                    relation_type: "semantic_peer".to_string(),
+                    properties_json: "{}".to_string(),
+                    created_at,
+                    tombstoned_at: None,
+                },
+            )
+            .unwrap();
+        }
+
+        let listed: Vec<String> = SqliteRepo::list_relations(
+            &conn,
+            &RelationListOpts {
+                source_ettle_id: Some("ettle:a".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .into_iter()
+        .map(|r| r.id)
+        .collect();
+        prop_assert_eq!(listed, expected_order(&mut keys));
+    }
+}
@@ -16,6 +16,7 @@
 //!   S-PC-13  test_policy_create_state_version_incremented
 //!   S-PC-14  test_policy_create_existing_policies_retrievable
 //!   S-PC-15  test_policy_create_must_not_overwrite_existing
+//!   S-PC-16  test_policy_create_dry_run_rejected_writes_no_file
 //!   DEFERRED: S-PC-concurrent — file-system atomic rename + SQLite uniqueness each guarantee
 //!             single-winner semantics; cross-system race is OS-level
 
@@ -24,7 +25,10 @@
 use ettlex_core::approval_router::NoopApprovalRouter;
 use ettlex_core::errors::ExErrorKind;
 use ettlex_core::policy_provider::PolicyProvider;
-use ettlex_engine::commands::command::{apply_command, Command, CommandResult};
+use ettlex_engine::commands::command::{
+    apply_command, apply_command_with_options, Command, CommandResult,
+};
+use ettlex_engine::commands::engine_command::CommandOptions;
 use ettlex_store::cas::FsStore;
 use ettlex_store::file_policy_provider::FilePolicyProvider;
 use rusqlite::Connection;
@@ -390,5 +394,37 @@ fn test_policy_create_must_not_overwrite_existing() {
     assert_eq!(current, original, "Original policy must not be overwritten");
 }
 
+// ---------------------------------------------------------------------------
+// S-PC-16: dry-run PolicyCreate is rejected and leaves no policy file behind
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_policy_create_dry_run_rejected_writes_no_file() {
+    let (_dir, mut conn, cas, policies_dir) = setup();
+    let provider = FilePolicyProvider::new(&policies_dir);
+    let cmd = || Command::PolicyCreate {
+        policy_ref: "dry_policy@0".to_string(),
+        text: "# Policy content".to_string(),
+    };
+
+    let err = apply_command_with_options(
+        cmd(),
+        None,
+        CommandOptions { dry_run: true },
+        &mut conn,
+        &cas,
+        &provider,
+        &NoopApprovalRouter,
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+    assert_eq!(fs::read_dir(&policies_dir).unwrap().count(), 0);
+    assert_eq!(sv(&conn), 0);
+
+    // The real run is unaffected by the rejected dry run.
+    apply_command(cmd(), None, &mut conn, &cas, &provider, &NoopApprovalRouter).unwrap();
+    assert!(provider.policy_read("dry_policy@0").is_ok());
+}
+
 // DEFERRED: S-PC-concurrent — file-system atomic rename + SQLite uniqueness each guarantee
 // single-winner semantics; cross-system race is OS-level. Deferred to load test phase.
@@ -57,6 +57,7 @@ fn test_preview_does_not_create_approval_request() {
             profile_ref: Some("profile/default@0".to_string()),
             context: json!({"env": "test"}),
             candidates: vec!["ep:a".to_string(), "ep:b".to_string()],
+            ambiguity_policy_override: None,
         },
         &conn,
         &cas,
@@ -92,6 +93,7 @@ fn test_preview_deterministic() {
         profile_ref: Some("profile/default@0".to_string()),
         context: json!({"key": "value"}),
         candidates: vec!["ep:x".to_string()],
+        ambiguity_policy_override: None,
     };
 
     let r1 = match apply_engine_query(query(), &conn, &cas, None).unwrap() {
@@ -134,6 +136,7 @@ fn test_preview_evaluation_disabled() {
             profile_ref: Some("profile/disabled@0".to_string()),
             context: json!({}),
             candidates: vec!["ep:only".to_string()],
+            ambiguity_policy_override: None,
         },
         &conn,
         &cas,
@@ -183,6 +186,7 @@ fn test_preview_empty_candidates_no_match() {
             profile_ref: None,
             context: json!({}),
             candidates: vec![],
+            ambiguity_policy_override: None,
         },
         &conn,
         &cas,
@@ -203,3 +207,61 @@ fn test_preview_empty_candidates_no_match() {
         _ => panic!("expected PredicatePreview"),
     }
 }
+
+// ---------------------------------------------------------------------------
+// Ambiguity policy override simulates without touching the profile
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_preview_ambiguity_policy_override() {
+    use ettlex_core::candidate_resolver::AmbiguityPolicy;
+
+    let (_tmp, conn, cas) = setup();
+
+    let payload = r#"{"ambiguity_policy": "fail_fast"}"#;
+    insert_profile(&conn, "profile/default@0", payload);
+
+    let preview = |policy: Option<AmbiguityPolicy>| match apply_engine_query(
+        EngineQuery::ConstraintPredicatesPreview {
+            profile_ref: Some("profile/default@0".to_string()),
+            context: json!({}),
+            candidates: vec!["ep:a".to_string(), "ep:b".to_string()],
+            ambiguity_policy_override: policy,
+        },
+        &conn,
+        &cas,
+        None,
+    )
+    .unwrap()
+    {
+        EngineQueryResult::PredicatePreview(r) => r,
+        _ => panic!("expected PredicatePreview"),
+    };
+
+    let from_profile = preview(None);
+    assert_eq!(from_profile.status, PreviewStatus::Ambiguous);
+    assert_eq!(from_profile.ambiguity_policy, AmbiguityPolicy::FailFast);
+    assert!(!from_profile.policy_overridden);
+
+    let simulated = preview(Some(AmbiguityPolicy::RouteForApproval));
+    assert_eq!(simulated.status, PreviewStatus::RoutedForApproval);
+    assert!(simulated.policy_overridden);
+
+    let deterministic = preview(Some(AmbiguityPolicy::ChooseDeterministic));
+    assert_eq!(deterministic.status, PreviewStatus::Selected);
+    assert_eq!(deterministic.selected.as_deref(), Some("ep:a"));
+
+    // The profile is unchanged and nothing was routed
+    let stored: String = conn
+        .query_row(
+            "SELECT payload_json FROM profiles WHERE profile_ref = 'profile/default@0'",
+            [],
+            |r| r.get(0),
+        )
+        .unwrap();
+    assert_eq!(stored, payload);
+    let approvals: i64 = conn
+        .query_row("SELECT COUNT(*) FROM approval_requests", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(approvals, 0);
+}
@@ -96,7 +96,10 @@ fn test_profile_resolve_null_uses_default() {
     insert_default_profile(&conn, "profile/default@0", r#"{"is_default": true}"#);
 
     let result = apply_engine_query(
-        EngineQuery::ProfileResolve { profile_ref: None },
+        EngineQuery::ProfileResolve {
+            profile_ref: None,
+            environment: None,
+        },
         &conn,
         &cas,
         None,
@@ -112,6 +115,121 @@ fn test_profile_resolve_null_uses_default() {
     }
 }
 
+// ---------------------------------------------------------------------------
+// profile.resolve selects the environment default, falling back to global
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_profile_resolve_environment_default() {
+    let (_tmp, conn, cas) = setup();
+    insert_default_profile(&conn, "profile/default@0", r#"{"tier": "global"}"#);
+    insert_profile(&conn, "profile/prod@0", r#"{"tier": "prod"}"#);
+    ettlex_store::profile::set_environment_default_profile(&conn, "prod", "profile/prod@0")
+        .unwrap();
+
+    let resolve = |environment: &str| match apply_engine_query(
+        EngineQuery::ProfileResolve {
+            profile_ref: None,
+            environment: Some(environment.to_string()),
+        },
+        &conn,
+        &cas,
+        None,
+    )
+    .unwrap()
+    {
+        EngineQueryResult::ProfileResolve(r) => r.profile_ref,
+        _ => panic!("expected ProfileResolve"),
+    };
+    assert_eq!(resolve("prod"), "profile/prod@0");
+    assert_eq!(resolve("dev"), "profile/default@0");
+}
+
+// ---------------------------------------------------------------------------
+// profile.resolve merges the extends chain
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_profile_resolve_exposes_effective_payload() {
+    let (_tmp, conn, cas) = setup();
+
+    insert_profile(
+        &conn,
+        "profile/org@0",
+        r#"{"ambiguity_policy": "route_for_approval", "vars": {"org": "acme"}}"#,
+    );
+    insert_profile(
+        &conn,
+        "profile/team@0",
+        r#"{"extends": "profile/org@0", "vars": {"team": "payments"}}"#,
+    );
+
+    let result = apply_engine_query(
+        EngineQuery::ProfileResolve {
+            profile_ref: Some("profile/team@0".to_string()),
+            environment: None,
+        },
+        &conn,
+        &cas,
+        None,
+    )
+    .unwrap();
+    match result {
+        EngineQueryResult::ProfileResolve(r) => {
+            assert_eq!(r.parsed_profile["extends"], "profile/org@0");
+            assert_eq!(
+                r.effective_profile,
+                serde_json::json!({
+                    "ambiguity_policy": "route_for_approval",
+                    "vars": { "org": "acme", "team": "payments" },
+                })
+            );
+            assert_eq!(r.extends_chain, vec!["profile/org@0", "profile/team@0"]);
+        }
+        _ => panic!("expected ProfileResolve"),
+    }
+
+    // The inherited policy is what the engine acts on
+    let preview = apply_engine_query(
+        EngineQuery::ConstraintPredicatesPreview {
+            profile_ref: Some("profile/team@0".to_string()),
+            context: serde_json::json!({}),
+            candidates: vec!["c1".to_string(), "c2".to_string()],
+            ambiguity_policy_override: None,
+        },
+        &conn,
+        &cas,
+        None,
+    )
+    .unwrap();
+    match preview {
+        EngineQueryResult::PredicatePreview(p) => assert_eq!(
+            p.ambiguity_policy,
+            ettlex_core::candidate_resolver::AmbiguityPolicy::RouteForApproval
+        ),
+        _ => panic!("expected PredicatePreview"),
+    }
+}
+
+#[test]
+fn test_profile_resolve_extends_cycle() {
+    let (_tmp, conn, cas) = setup();
+    insert_profile(&conn, "profile/a@0", r#"{"extends": "profile/b@0"}"#);
+    insert_profile(&conn, "profile/b@0", r#"{"extends": ["profile/a@0"]}"#);
+
+    let err = apply_engine_query(
+        EngineQuery::ProfileResolve {
+            profile_ref: Some("profile/a@0".to_string()),
+            environment: None,
+        },
+        &conn,
+        &cas,
+        None,
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ettlex_core::errors::ExErrorKind::CycleDetected);
+}
+
 // ---------------------------------------------------------------------------
 // S25: profile.resolve unknown → NotFound
 // ---------------------------------------------------------------------------
@@ -123,6 +241,7 @@ fn test_profile_resolve_unknown_not_found() {
     let err = apply_engine_query(
         EngineQuery::ProfileResolve {
             profile_ref: Some("profile/does-not-exist@99".to_string()),
+            environment: None,
         },
         &conn,
         &cas,
@@ -235,6 +354,14 @@ fn test_approval_get_digests_and_bytes() {
             // payload_json must have the expected fields
             assert!(r.payload_json.get("approval_token").is_some());
             assert!(r.payload_json.get("reason_code").is_some());
+            // typed payload mirrors the raw JSON
+            assert_eq!(r.payload.approval_token, token);
+            assert_eq!(r.payload.reason_code, "test_reason");
+            assert_eq!(
+                r.payload.candidates,
+                vec!["cand:a".to_string(), "cand:b".to_string()]
+            );
+            assert_eq!(r.payload.semantic_request_digest, r.semantic_request_digest);
         }
         _ => panic!("expected ApprovalGet"),
     }
@@ -363,3 +490,115 @@ fn test_approval_list_deterministic() {
         _ => panic!("expected ApprovalList"),
     }
 }
+
+// ---------------------------------------------------------------------------
+// approval.history records the lifecycle from creation to decision
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_approval_history_tracks_transitions() {
+    use ettlex_core::approval_router::{ApprovalRouter, NoopApprovalRouter};
+    use ettlex_core::policy_provider::NoopPolicyProvider;
+    use ettlex_engine::commands::command::{apply_command, Command, CommandResult};
+
+    let (_tmp, mut conn, cas) = setup();
+    let token = {
+        let router = SqliteApprovalRouter::new_with_cas(&mut conn, &cas);
+        router
+            .route_approval_request("test_reason", vec!["cand:a".to_string()])
+            .unwrap()
+    };
+
+    let (result, _) = apply_command(
+        Command::ApprovalTransition {
+            approval_token: token.clone(),
+            status: "rejected".to_string(),
+            actor: Some("reviewer@example".to_string()),
+        },
+        None,
+        &mut conn,
+        &cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .unwrap();
+    match result {
+        CommandResult::ApprovalTransition {
+            from_status,
+            to_status,
+            ..
+        } => {
+            assert_eq!(from_status, "pending");
+            assert_eq!(to_status, "rejected");
+        }
+        other => panic!("expected ApprovalTransition, got {:?}", other),
+    }
+
+    // A decided request cannot transition again
+    let err = apply_command(
+        Command::ApprovalTransition {
+            approval_token: token.clone(),
+            status: "approved".to_string(),
+            actor: None,
+        },
+        None,
+        &mut conn,
+        &cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.kind(),
+        ettlex_core::errors::ExErrorKind::ConstraintViolation
+    );
+
+    let events = match apply_engine_query(
+        EngineQuery::ApprovalHistory {
+            approval_token: token.clone(),
+        },
+        &conn,
+        &cas,
+        None,
+    )
+    .unwrap()
+    {
+        EngineQueryResult::ApprovalHistory(events) => events,
+        _ => panic!("expected ApprovalHistory"),
+    };
+    let transitions: Vec<_> = events
+        .iter()
+        .map(|e| {
+            (
+                e.from_status.as_deref(),
+                e.to_status.as_str(),
+                e.actor.as_deref(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        transitions,
+        vec![
+            (None, "pending", None),
+            (Some("pending"), "rejected", Some("reviewer@example")),
+        ]
+    );
+}
+
+#[test]
+fn test_approval_history_unknown_token() {
+    let (_tmp, conn, cas) = setup();
+    let err = apply_engine_query(
+        EngineQuery::ApprovalHistory {
+            approval_token: "00000000-0000-0000-0000-000000000000".to_string(),
+        },
+        &conn,
+        &cas,
+        None,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.kind(),
+        ettlex_core::errors::ExErrorKind::ApprovalNotFound
+    );
+}
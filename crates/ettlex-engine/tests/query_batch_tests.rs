@@ -0,0 +1,193 @@
+//! Query batch tests.
+//!
+//! Covers `apply_engine_query_batch`: ordering, fail-fast errors, and
+//! isolation from commits made by another connection mid-batch.
+
+#![allow(clippy::result_large_err)]
+
+use std::path::Path;
+use std::sync::Mutex;
+
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::policy_provider::{PolicyListEntry, PolicyProvider};
+use ettlex_engine::commands::engine_query::{
+    apply_engine_query_batch, read_state_version, EngineQuery, EngineQueryResult,
+};
+use ettlex_engine::commands::read_tools::{ListOptions, StateVersionResult};
+use ettlex_store::cas::FsStore;
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+fn open(path: &Path) -> Connection {
+    let conn = ettlex_store::db::open(path).unwrap();
+    ettlex_store::db::configure(&conn).unwrap();
+    conn
+}
+
+fn setup() -> (TempDir, Connection, FsStore) {
+    let temp_dir = TempDir::new().unwrap();
+    let mut conn = open(&temp_dir.path().join("test.db"));
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    let cas = FsStore::new(temp_dir.path().join("cas"));
+    (temp_dir, conn, cas)
+}
+
+/// `StateGetVersion` result reduced to the fields a state stamp carries.
+fn stamp_of(v: &StateVersionResult) -> StateVersionResult {
+    StateVersionResult {
+        summary: None,
+        ..v.clone()
+    }
+}
+
+fn insert_ettle(conn: &Connection, id: &str) {
+    conn.execute(
+        "INSERT INTO ettles (id, title, created_at, updated_at) \
+         VALUES (?1, 'E', '2026-01-01T00:00:00Z', '2026-01-01T00:00:00Z')",
+        [id],
+    )
+    .unwrap();
+    conn.execute(
+        "INSERT INTO command_log (applied_at) VALUES ('2026-01-01T00:00:00Z')",
+        [],
+    )
+    .unwrap();
+}
+
+fn ettle_count(result: &EngineQueryResult) -> usize {
+    match result {
+        EngineQueryResult::EttleList(page) => page.items.len(),
+        _ => panic!("expected EttleList"),
+    }
+}
+
+/// Policy provider that commits a new ettle through its own connection each
+/// time `policy_list` runs, simulating a concurrent writer mid-batch.
+struct WritingProvider {
+    writer: Mutex<Connection>,
+}
+
+impl PolicyProvider for WritingProvider {
+    fn policy_check(
+        &self,
+        _policy_ref: &str,
+        _profile_ref: Option<&str>,
+        _operation: &str,
+        _entity_id: Option<&str>,
+    ) -> Result<(), ExError> {
+        Ok(())
+    }
+
+    fn policy_read(&self, _policy_ref: &str) -> Result<String, ExError> {
+        Err(ExError::new(ExErrorKind::PolicyNotFound))
+    }
+
+    fn policy_export(&self, _policy_ref: &str, _export_kind: &str) -> Result<String, ExError> {
+        Err(ExError::new(ExErrorKind::PolicyNotFound))
+    }
+
+    fn policy_list(&self) -> Result<Vec<PolicyListEntry>, ExError> {
+        insert_ettle(&self.writer.lock().unwrap(), "ettle:concurrent");
+        Ok(Vec::new())
+    }
+
+    fn policy_project_for_handoff(
+        &self,
+        _policy_ref: &str,
+        _profile_ref: Option<&str>,
+    ) -> Result<Vec<u8>, ExError> {
+        Err(ExError::new(ExErrorKind::PolicyNotFound))
+    }
+}
+
+#[test]
+fn test_batch_returns_results_in_query_order() {
+    let (_tmp, conn, cas) = setup();
+    insert_ettle(&conn, "ettle:a");
+
+    let out = apply_engine_query_batch(
+        vec![
+            EngineQuery::StateGetVersion,
+            EngineQuery::EttleList(ListOptions::default()),
+            EngineQuery::SnapshotList {
+                ettle_id: None,
+                registered_only: false,
+            },
+        ],
+        &conn,
+        &cas,
+        None,
+    )
+    .unwrap();
+
+    assert_eq!(out.results.len(), 3);
+    match &out.results[0] {
+        EngineQueryResult::StateVersion(v) => assert_eq!(stamp_of(v), out.state),
+        _ => panic!("expected StateVersion"),
+    }
+    assert_eq!(ettle_count(&out.results[1]), 1);
+    assert!(matches!(out.results[2], EngineQueryResult::SnapshotList(_)));
+}
+
+#[test]
+fn test_batch_is_isolated_from_concurrent_commits() {
+    let (tmp, conn, cas) = setup();
+    insert_ettle(&conn, "ettle:a");
+    let provider = WritingProvider {
+        writer: Mutex::new(open(&tmp.path().join("test.db"))),
+    };
+
+    let out = apply_engine_query_batch(
+        vec![
+            EngineQuery::EttleList(ListOptions::default()),
+            EngineQuery::PolicyList,
+            EngineQuery::EttleList(ListOptions::default()),
+            EngineQuery::StateGetVersion,
+        ],
+        &conn,
+        &cas,
+        Some(&provider),
+    )
+    .unwrap();
+
+    // The writer committed between the two listings, but neither sees it
+    assert_eq!(ettle_count(&out.results[0]), 1);
+    assert_eq!(ettle_count(&out.results[2]), 1);
+    assert!(matches!(
+        &out.results[3],
+        EngineQueryResult::StateVersion(v) if stamp_of(v) == out.state
+    ));
+
+    // Outside the batch the commit is visible
+    assert_eq!(
+        read_state_version(&conn).unwrap().state_version,
+        out.state.state_version + 1
+    );
+}
+
+#[test]
+fn test_batch_fails_fast_and_releases_savepoint() {
+    let (_tmp, conn, cas) = setup();
+    let err = apply_engine_query_batch(
+        vec![
+            EngineQuery::StateGetVersion,
+            EngineQuery::EttleGet {
+                ettle_id: "ettle:missing".to_string(),
+            },
+        ],
+        &conn,
+        &cas,
+        None,
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+    assert!(conn.is_autocommit());
+}
+
+#[test]
+fn test_empty_batch_is_stamped() {
+    let (_tmp, conn, cas) = setup();
+    let out = apply_engine_query_batch(Vec::new(), &conn, &cas, None).unwrap();
+    assert!(out.results.is_empty());
+    assert_eq!(out.state, read_state_version(&conn).unwrap());
+}
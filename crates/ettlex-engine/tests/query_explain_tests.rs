@@ -0,0 +1,217 @@
+//! Query explain/trace mode tests.
+//!
+//! Covers `apply_engine_query_with_options` with and without `explain`.
+
+use ettlex_engine::commands::engine_query::{
+    apply_engine_query, apply_engine_query_with_options, EngineQuery, EngineQueryResult,
+};
+use ettlex_engine::commands::query_trace::{HydrationScope, QueryOptions};
+use ettlex_engine::commands::read_tools::ListOptions;
+use ettlex_store::cas::FsStore;
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+fn setup() -> (TempDir, Connection, FsStore) {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("test.db");
+    let cas_path = temp_dir.path().join("cas");
+    let mut conn = Connection::open(&db_path).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    let cas = FsStore::new(cas_path);
+    (temp_dir, conn, cas)
+}
+
+fn insert_profile(conn: &Connection, profile_ref: &str) {
+    conn.execute(
+        "INSERT INTO profiles (profile_ref, payload_json, is_default, created_at)
+         VALUES (?1, '{}', 0, 0)",
+        rusqlite::params![profile_ref],
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_explain_false_returns_no_trace() {
+    let (_tmp, conn, cas) = setup();
+
+    let out = apply_engine_query_with_options(
+        EngineQuery::StateGetVersion,
+        QueryOptions::default(),
+        &conn,
+        &cas,
+        None,
+    )
+    .unwrap();
+    assert!(out.trace.is_none());
+    assert!(matches!(out.result, EngineQueryResult::StateVersion(_)));
+}
+
+#[test]
+fn test_explain_reports_tables_rows_scope_and_stages() {
+    let (_tmp, conn, cas) = setup();
+    insert_profile(&conn, "profile/a@1");
+    insert_profile(&conn, "profile/b@1");
+    insert_profile(&conn, "profile/c@1");
+    insert_profile(&conn, "profile/d@1");
+    insert_profile(&conn, "profile/e@1");
+
+    let out = apply_engine_query_with_options(
+        EngineQuery::ProfileList(ListOptions {
+            limit: Some(2),
+            ..Default::default()
+        }),
+        QueryOptions {
+            explain: true,
+            ..Default::default()
+        },
+        &conn,
+        &cas,
+        None,
+    )
+    .unwrap();
+
+    let trace = out.trace.expect("explain should return a trace");
+    assert_eq!(trace.op, "profile_list");
+    assert_eq!(trace.hydration_scope, HydrationScope::Page);
+    assert_eq!(trace.result_rows, 2);
+    // The page query reads limit + 1 rows to detect a next page, not all 5
+    assert_eq!(trace.tables.len(), 1);
+    assert_eq!(trace.tables[0].table, "profiles");
+    assert_eq!(trace.tables[0].rows_returned, 3);
+    let stages: Vec<&str> = trace.stages.iter().map(|s| s.stage.as_str()).collect();
+    assert_eq!(stages, vec!["resolve", "execute"]);
+    assert!(trace.stages[0].tables.is_empty());
+    assert_eq!(trace.stages[1].tables, trace.tables);
+}
+
+#[test]
+fn test_explain_reports_only_tables_the_query_read() {
+    let (_tmp, conn, cas) = setup();
+    for id in ["ettle:a", "ettle:b", "ettle:c"] {
+        conn.execute(
+            "INSERT INTO ettles (id, title, created_at, updated_at)
+             VALUES (?1, 'T', '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z')",
+            rusqlite::params![id],
+        )
+        .unwrap();
+    }
+    insert_profile(&conn, "profile/a@1");
+
+    let out = apply_engine_query_with_options(
+        EngineQuery::EttleGet {
+            ettle_id: "ettle:b".to_string(),
+        },
+        QueryOptions {
+            explain: true,
+            ..Default::default()
+        },
+        &conn,
+        &cas,
+        None,
+    )
+    .unwrap();
+
+    let trace = out.trace.expect("explain should return a trace");
+    let ettles = trace
+        .tables
+        .iter()
+        .find(|t| t.table == "ettles")
+        .expect("ettle_get reads the ettles table");
+    assert!(ettles.rows_returned >= 1);
+    assert!(
+        ettles.rows_returned < 3,
+        "a point lookup must not count the table"
+    );
+    assert!(trace.tables.iter().all(|t| t.table != "profiles"));
+    assert!(trace.stages[0].tables.iter().any(|t| t.table == "ettles"));
+}
+
+#[test]
+fn test_explain_does_not_change_result() {
+    let (_tmp, conn, cas) = setup();
+    insert_profile(&conn, "profile/a@1");
+
+    let plain = apply_engine_query(
+        EngineQuery::ProfileList(ListOptions::default()),
+        &conn,
+        &cas,
+        None,
+    )
+    .unwrap();
+    let explained = apply_engine_query_with_options(
+        EngineQuery::ProfileList(ListOptions::default()),
+        QueryOptions {
+            explain: true,
+            ..Default::default()
+        },
+        &conn,
+        &cas,
+        None,
+    )
+    .unwrap();
+
+    match (plain, explained.result) {
+        (EngineQueryResult::ProfileList(a), EngineQueryResult::ProfileList(b)) => {
+            assert_eq!(a.items.len(), b.items.len());
+            assert_eq!(a.cursor, b.cursor);
+        }
+        _ => panic!("unexpected result variants"),
+    }
+}
+
+#[test]
+fn test_explain_propagates_query_errors() {
+    let (_tmp, conn, cas) = setup();
+
+    let err = apply_engine_query_with_options(
+        EngineQuery::ProfileGet {
+            profile_ref: "profile/missing@1".to_string(),
+        },
+        QueryOptions {
+            explain: true,
+            ..Default::default()
+        },
+        &conn,
+        &cas,
+        None,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.kind(),
+        ettlex_core::errors::ExErrorKind::ProfileNotFound
+    );
+}
+
+#[test]
+fn test_explain_keeps_the_sql_audit_fed() {
+    let (_tmp, mut conn, cas) = setup();
+    insert_profile(&conn, "profile/a@1");
+    ettlex_store::sql_audit::enable();
+    ettlex_store::sql_audit::attach(&mut conn);
+
+    let explained = QueryOptions {
+        explain: true,
+        ..Default::default()
+    };
+    apply_engine_query_with_options(
+        EngineQuery::ProfileList(ListOptions::default()),
+        explained,
+        &conn,
+        &cas,
+        None,
+    )
+    .unwrap();
+    // The explain hook hands SQLite's trace slot back to the audit
+    apply_engine_query(
+        EngineQuery::DecisionList(ListOptions::default()),
+        &conn,
+        &cas,
+        None,
+    )
+    .unwrap();
+
+    let entries = ettlex_store::sql_audit::entries();
+    ettlex_store::sql_audit::disable();
+    assert!(entries.iter().any(|e| e.sql.contains("FROM profiles")));
+    assert!(entries.iter().any(|e| e.sql.contains("FROM decisions")));
+}
@@ -0,0 +1,202 @@
+//! State stamping tests.
+//!
+//! Every `apply_engine_query_with_options` result carries the state version
+//! and head digest observed by the query.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::ExErrorKind;
+use ettlex_core::ops::Store;
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_core::snapshot::manifest::generate_manifest;
+use ettlex_engine::commands::command::{apply_command, Command};
+use ettlex_engine::commands::engine_query::{
+    apply_engine_query, apply_engine_query_with_options, read_state_version, EngineQuery,
+    EngineQueryResult,
+};
+use ettlex_engine::commands::query_trace::QueryOptions;
+use ettlex_engine::commands::read_tools::ListOptions;
+use ettlex_store::cas::FsStore;
+use ettlex_store::snapshot::persist::{commit_snapshot, SnapshotOptions};
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+fn setup() -> (TempDir, Connection, FsStore) {
+    let temp_dir = TempDir::new().unwrap();
+    let mut conn = Connection::open(temp_dir.path().join("test.db")).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    let cas = FsStore::new(temp_dir.path().join("cas"));
+    (temp_dir, conn, cas)
+}
+
+fn profile_list() -> EngineQuery {
+    EngineQuery::ProfileList(ListOptions::default())
+}
+
+#[test]
+fn test_stamp_matches_state_get_version() {
+    let (_tmp, conn, cas) = setup();
+    let out = apply_engine_query_with_options(
+        EngineQuery::StateGetVersion,
+        QueryOptions::default(),
+        &conn,
+        &cas,
+        None,
+    )
+    .unwrap();
+    match out.result {
+        EngineQueryResult::StateVersion(v) => {
+            assert_eq!(v.state_version, out.state.state_version);
+            assert_eq!(v.semantic_head_digest, out.state.semantic_head_digest);
+            assert!(v.summary.is_some());
+        }
+        _ => panic!("expected StateVersion"),
+    }
+    assert_eq!(out.state.state_version, 0);
+    assert_eq!(out.state.semantic_head_digest, None);
+    // Stamps stay cheap: no workspace summary
+    assert!(out.state.summary.is_none());
+}
+
+#[test]
+fn test_state_get_version_summarises_workspace() {
+    let (_tmp, mut conn, cas) = setup();
+    for id in ["ettle:a", "ettle:b", "ettle:gone"] {
+        conn.execute(
+            "INSERT INTO ettles (id, title, created_at, updated_at) \
+             VALUES (?1, 'E', '2026-01-01T00:00:00Z', '2026-01-01T00:00:00Z')",
+            [id],
+        )
+        .unwrap();
+    }
+    conn.execute(
+        "UPDATE ettles SET tombstoned_at = '2026-01-02T00:00:00Z' WHERE id = 'ettle:gone'",
+        [],
+    )
+    .unwrap();
+    for (token, status) in [("tok-1", "pending"), ("tok-2", "approved")] {
+        conn.execute(
+            "INSERT INTO approval_requests \
+             (approval_token, reason_code, candidate_set_json, semantic_request_digest, status, created_at) \
+             VALUES (?1, 'r', '[]', 'd', ?2, 0)",
+            [token, status],
+        )
+        .unwrap();
+    }
+    let mut heads = Vec::new();
+    for (root, ept) in [
+        ("ettle:b", vec!["ep:b:0"]),
+        ("ettle:a", vec!["ep:a:0"]),
+        ("ettle:a", vec!["ep:a:0", "ep:a:1"]),
+    ] {
+        let manifest = generate_manifest(
+            ept.into_iter().map(String::from).collect(),
+            "policy/default@0".into(),
+            "profile/default@0".into(),
+            root.into(),
+            "0001".into(),
+            None,
+            &Store::new(),
+        )
+        .unwrap();
+        let r = commit_snapshot(&mut conn, &cas, manifest, SnapshotOptions::default()).unwrap();
+        heads.push((root, r.snapshot_id));
+    }
+
+    let summary = match apply_engine_query(EngineQuery::StateGetVersion, &conn, &cas, None).unwrap()
+    {
+        EngineQueryResult::StateVersion(v) => v.summary.unwrap(),
+        _ => panic!("expected StateVersion"),
+    };
+    assert_eq!(summary.ettle_count, 2);
+    assert_eq!(summary.snapshot_count, 3);
+    assert_eq!(summary.pending_approval_count, 1);
+    let root_heads: Vec<(&str, &str)> = summary
+        .root_heads
+        .iter()
+        .map(|h| (h.root_ettle_id.as_str(), h.snapshot_id.as_str()))
+        .collect();
+    assert_eq!(
+        root_heads,
+        vec![
+            ("ettle:a", heads[2].1.as_str()),
+            ("ettle:b", heads[0].1.as_str())
+        ]
+    );
+}
+
+#[test]
+fn test_stamp_changes_after_command() {
+    let (_tmp, mut conn, cas) = setup();
+    let before =
+        apply_engine_query_with_options(profile_list(), QueryOptions::default(), &conn, &cas, None)
+            .unwrap()
+            .state;
+
+    apply_command(
+        Command::EttleCreate {
+            title: "Stamp".to_string(),
+            ettle_id: None,
+            why: None,
+            what: None,
+            how: None,
+            reasoning_link_id: None,
+            reasoning_link_type: None,
+        },
+        None,
+        &mut conn,
+        &cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .unwrap();
+
+    let after = apply_engine_query_with_options(
+        profile_list(),
+        QueryOptions {
+            explain: true,
+            ..Default::default()
+        },
+        &conn,
+        &cas,
+        None,
+    )
+    .unwrap();
+    assert_ne!(after.state, before);
+    assert_eq!(after.state, read_state_version(&conn).unwrap());
+}
+
+#[test]
+fn test_stamped_query_nests_inside_open_transaction() {
+    let (_tmp, mut conn, cas) = setup();
+    let tx = conn.transaction().unwrap();
+    tx.execute("INSERT INTO command_log (applied_at) VALUES (0)", [])
+        .unwrap();
+
+    // The savepoint nests, and the stamp sees the uncommitted write
+    let out =
+        apply_engine_query_with_options(profile_list(), QueryOptions::default(), &tx, &cas, None)
+            .unwrap();
+    assert_eq!(out.state.state_version, 1);
+    tx.rollback().unwrap();
+
+    assert_eq!(read_state_version(&conn).unwrap().state_version, 0);
+}
+
+#[test]
+fn test_failed_query_releases_savepoint() {
+    let (_tmp, conn, cas) = setup();
+    let err = apply_engine_query_with_options(
+        EngineQuery::ProfileGet {
+            profile_ref: "profile/missing@0".to_string(),
+        },
+        QueryOptions::default(),
+        &conn,
+        &cas,
+        None,
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::ProfileNotFound);
+    assert!(conn.is_autocommit());
+}
@@ -0,0 +1,86 @@
+//! Query deadline and cancellation tests.
+//!
+//! Covers `QueryOptions::deadline` / `QueryOptions::cancel` on
+//! `apply_engine_query_with_options`.
+
+use std::time::{Duration, Instant};
+
+use ettlex_core::errors::ExErrorKind;
+use ettlex_engine::commands::engine_query::{
+    apply_engine_query_with_options, EngineQuery, EngineQueryResult,
+};
+use ettlex_engine::commands::query_cancel::CancellationToken;
+use ettlex_engine::commands::query_trace::QueryOptions;
+use ettlex_store::cas::FsStore;
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+fn setup() -> (TempDir, Connection, FsStore) {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("test.db");
+    let cas_path = temp_dir.path().join("cas");
+    let mut conn = Connection::open(&db_path).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    let cas = FsStore::new(cas_path);
+    (temp_dir, conn, cas)
+}
+
+#[test]
+fn test_expired_deadline_fails_with_timeout() {
+    let (_tmp, conn, cas) = setup();
+
+    let options = QueryOptions {
+        deadline: Some(Instant::now()),
+        ..Default::default()
+    };
+    let err =
+        apply_engine_query_with_options(EngineQuery::StateGetVersion, options, &conn, &cas, None)
+            .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::Timeout);
+
+    // The read savepoint was released and the connection still works.
+    assert!(conn.is_autocommit());
+    let out = apply_engine_query_with_options(
+        EngineQuery::StateGetVersion,
+        QueryOptions::default(),
+        &conn,
+        &cas,
+        None,
+    )
+    .unwrap();
+    assert!(matches!(out.result, EngineQueryResult::StateVersion(_)));
+}
+
+#[test]
+fn test_cancelled_token_fails_with_timeout() {
+    let (_tmp, conn, cas) = setup();
+    let token = CancellationToken::new();
+    let handle = token.clone();
+    handle.cancel();
+
+    let options = QueryOptions {
+        cancel: Some(token),
+        ..Default::default()
+    };
+    let err =
+        apply_engine_query_with_options(EngineQuery::StateGetVersion, options, &conn, &cas, None)
+            .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::Timeout);
+}
+
+#[test]
+fn test_query_within_deadline_succeeds_with_trace() {
+    let (_tmp, conn, cas) = setup();
+
+    let options = QueryOptions {
+        explain: true,
+        cancel: Some(CancellationToken::new()),
+        ..Default::default()
+    }
+    .with_timeout(Duration::from_secs(30));
+    let out =
+        apply_engine_query_with_options(EngineQuery::StateGetVersion, options, &conn, &cas, None)
+            .unwrap();
+    assert!(out.trace.is_some());
+    assert!(matches!(out.result, EngineQueryResult::StateVersion(_)));
+}
@@ -0,0 +1,381 @@
+//! Restore command tests — EttleRestore, RelationRestore, GroupRestore,
+//! DecisionRestore.
+//!
+//! Tests cover: happy-path tombstone clearing, restoring active entities,
+//! and structural invariants (link targets and relation endpoints must be
+//! active before a dependant can be restored).
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_engine::commands::command::{apply_command, Command, CommandResult};
+use ettlex_engine::commands::decision::decision_tombstone;
+use ettlex_store::cas::FsStore;
+use ettlex_store::migrations::apply_migrations;
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+// ---------------------------------------------------------------------------
+// Test helpers
+// ---------------------------------------------------------------------------
+
+fn setup_db_with_cas() -> (Connection, FsStore, TempDir) {
+    let dir = TempDir::new().expect("temp dir");
+    let cas = FsStore::new(dir.path().join("cas"));
+    let mut conn = Connection::open_in_memory().expect("in-memory db");
+    apply_migrations(&mut conn).expect("migrations should apply");
+    (conn, cas, dir)
+}
+
+fn apply(conn: &mut Connection, cas: &FsStore, cmd: Command) -> Result<CommandResult, ExError> {
+    apply_command(
+        cmd,
+        None,
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .map(|(res, _sv)| res)
+}
+
+fn create_ettle(conn: &mut Connection, cas: &FsStore, link: Option<&str>) -> String {
+    let res = apply(
+        conn,
+        cas,
+        Command::EttleCreate {
+            title: "Restorable".to_string(),
+            ettle_id: None,
+            why: None,
+            what: None,
+            how: None,
+            reasoning_link_id: link.map(str::to_string),
+            reasoning_link_type: link.map(|_| "grounds".to_string()),
+        },
+    )
+    .expect("ettle create should succeed");
+    match res {
+        CommandResult::EttleCreate { ettle_id } => ettle_id,
+        _ => panic!("unexpected result"),
+    }
+}
+
+fn ettle_tombstoned_at(conn: &Connection, ettle_id: &str) -> Option<String> {
+    conn.query_row(
+        "SELECT tombstoned_at FROM ettles WHERE id = ?1",
+        [ettle_id],
+        |r| r.get(0),
+    )
+    .unwrap()
+}
+
+// ---------------------------------------------------------------------------
+// EttleRestore
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_ettle_restore_clears_tombstone() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let id = create_ettle(&mut conn, &cas, None);
+
+    apply(
+        &mut conn,
+        &cas,
+        Command::EttleTombstone {
+            ettle_id: id.clone(),
+        },
+    )
+    .unwrap();
+    assert!(ettle_tombstoned_at(&conn, &id).is_some());
+
+    let res = apply(
+        &mut conn,
+        &cas,
+        Command::EttleRestore {
+            ettle_id: id.clone(),
+        },
+    )
+    .unwrap();
+    assert!(matches!(res, CommandResult::EttleRestore));
+    assert!(ettle_tombstoned_at(&conn, &id).is_none());
+}
+
+#[test]
+fn test_ettle_restore_active_is_invalid_input() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let id = create_ettle(&mut conn, &cas, None);
+
+    let err = apply(&mut conn, &cas, Command::EttleRestore { ettle_id: id }).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_ettle_restore_not_found() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let err = apply(
+        &mut conn,
+        &cas,
+        Command::EttleRestore {
+            ettle_id: "ettle:missing".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+}
+
+#[test]
+fn test_ettle_restore_requires_active_link_target() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let parent = create_ettle(&mut conn, &cas, None);
+    let child = create_ettle(&mut conn, &cas, Some(&parent));
+
+    apply(
+        &mut conn,
+        &cas,
+        Command::EttleTombstone {
+            ettle_id: child.clone(),
+        },
+    )
+    .unwrap();
+    apply(
+        &mut conn,
+        &cas,
+        Command::EttleTombstone {
+            ettle_id: parent.clone(),
+        },
+    )
+    .unwrap();
+
+    let err = apply(
+        &mut conn,
+        &cas,
+        Command::EttleRestore {
+            ettle_id: child.clone(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::AlreadyTombstoned);
+
+    apply(&mut conn, &cas, Command::EttleRestore { ettle_id: parent }).unwrap();
+    apply(
+        &mut conn,
+        &cas,
+        Command::EttleRestore {
+            ettle_id: child.clone(),
+        },
+    )
+    .unwrap();
+    assert!(ettle_tombstoned_at(&conn, &child).is_none());
+}
+
+#[test]
+fn test_ettle_restore_appends_provenance_event() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let id = create_ettle(&mut conn, &cas, None);
+    apply(
+        &mut conn,
+        &cas,
+        Command::EttleTombstone {
+            ettle_id: id.clone(),
+        },
+    )
+    .unwrap();
+    apply(&mut conn, &cas, Command::EttleRestore { ettle_id: id }).unwrap();
+
+    let count: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM provenance_events WHERE kind = 'ettle_restored'",
+            [],
+            |r| r.get(0),
+        )
+        .unwrap();
+    assert_eq!(count, 1);
+}
+
+// ---------------------------------------------------------------------------
+// RelationRestore
+// ---------------------------------------------------------------------------
+
+fn create_relation(conn: &mut Connection, cas: &FsStore, src: &str, tgt: &str) -> String {
+    let res = apply(
+        conn,
+        cas,
+        Command::RelationCreate {
+            source_ettle_id: src.to_string(),
+            target_ettle_id: tgt.to_string(),
+            relation_type: "refinement".to_string(),
+            properties_json: None,
+            relation_id: None,
+        },
+    )
+    .expect("relation create should succeed");
+    match res {
+        CommandResult::RelationCreate { relation_id } => relation_id,
+        _ => panic!("unexpected result"),
+    }
+}
+
+#[test]
+fn test_relation_restore_clears_tombstone() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let a = create_ettle(&mut conn, &cas, None);
+    let b = create_ettle(&mut conn, &cas, None);
+    let rel = create_relation(&mut conn, &cas, &a, &b);
+
+    apply(
+        &mut conn,
+        &cas,
+        Command::RelationTombstone {
+            relation_id: rel.clone(),
+        },
+    )
+    .unwrap();
+    let res = apply(
+        &mut conn,
+        &cas,
+        Command::RelationRestore {
+            relation_id: rel.clone(),
+        },
+    )
+    .unwrap();
+    assert!(matches!(res, CommandResult::RelationRestore));
+
+    let tombstoned_at: Option<String> = conn
+        .query_row(
+            "SELECT tombstoned_at FROM relations WHERE id = ?1",
+            [&rel],
+            |r| r.get(0),
+        )
+        .unwrap();
+    assert!(tombstoned_at.is_none());
+}
+
+#[test]
+fn test_relation_restore_requires_active_endpoints() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let a = create_ettle(&mut conn, &cas, None);
+    let b = create_ettle(&mut conn, &cas, None);
+    let rel = create_relation(&mut conn, &cas, &a, &b);
+
+    apply(
+        &mut conn,
+        &cas,
+        Command::RelationTombstone {
+            relation_id: rel.clone(),
+        },
+    )
+    .unwrap();
+    apply(&mut conn, &cas, Command::EttleTombstone { ettle_id: b }).unwrap();
+
+    let err = apply(
+        &mut conn,
+        &cas,
+        Command::RelationRestore { relation_id: rel },
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::AlreadyTombstoned);
+}
+
+// ---------------------------------------------------------------------------
+// GroupRestore
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_group_restore_clears_tombstone() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let group_id = match apply(
+        &mut conn,
+        &cas,
+        Command::GroupCreate {
+            name: "Restorable group".to_string(),
+        },
+    )
+    .unwrap()
+    {
+        CommandResult::GroupCreate { group_id } => group_id,
+        _ => panic!("unexpected result"),
+    };
+
+    apply(
+        &mut conn,
+        &cas,
+        Command::GroupTombstone {
+            group_id: group_id.clone(),
+        },
+    )
+    .unwrap();
+    apply(
+        &mut conn,
+        &cas,
+        Command::GroupRestore {
+            group_id: group_id.clone(),
+        },
+    )
+    .unwrap();
+
+    let err = apply(&mut conn, &cas, Command::GroupRestore { group_id }).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+}
+
+// ---------------------------------------------------------------------------
+// DecisionRestore
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_decision_restore_clears_tombstone() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let decision_id = match apply(
+        &mut conn,
+        &cas,
+        Command::DecisionCreate {
+            title: "Restorable decision".to_string(),
+            status: None,
+            decision_text: "Body.".to_string(),
+            rationale: "Rationale.".to_string(),
+            alternatives_text: None,
+            consequences_text: None,
+            evidence_kind: "none".to_string(),
+            evidence_excerpt: None,
+            evidence_capture_content: None,
+            evidence_file_path: None,
+        },
+    )
+    .unwrap()
+    {
+        CommandResult::DecisionCreate { decision_id } => decision_id,
+        _ => panic!("unexpected result"),
+    };
+
+    decision_tombstone(decision_id.clone(), &conn).unwrap();
+    let res = apply(
+        &mut conn,
+        &cas,
+        Command::DecisionRestore {
+            decision_id: decision_id.clone(),
+        },
+    )
+    .unwrap();
+    assert!(matches!(res, CommandResult::DecisionRestore));
+
+    let tombstoned_at: Option<i64> = conn
+        .query_row(
+            "SELECT tombstoned_at FROM decisions WHERE decision_id = ?1",
+            [&decision_id],
+            |r| r.get(0),
+        )
+        .unwrap();
+    assert!(tombstoned_at.is_none());
+    let count: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM provenance_events WHERE kind = 'decision_restored'",
+            [],
+            |r| r.get(0),
+        )
+        .unwrap();
+    assert_eq!(count, 1);
+
+    let err = apply(&mut conn, &cas, Command::DecisionRestore { decision_id }).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+}
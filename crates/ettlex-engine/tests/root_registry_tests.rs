@@ -0,0 +1,276 @@
+//! Root registry tests.
+//!
+//! Tests cover: registration and listing with heads, implicit roots missing
+//! from the registry, rejection of child Ettles and duplicates, retirement
+//! scoping `SnapshotList` and workspace heads, and the `roots.mode` gate.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_engine::commands::command::{apply_command, Command, CommandResult};
+use ettlex_engine::commands::engine_query::{apply_engine_query, EngineQuery, EngineQueryResult};
+use ettlex_engine::commands::read_tools::RootRegistry;
+use ettlex_engine::commands::root::ensure_new_root_allowed;
+use ettlex_engine::commands::settings::settings_set;
+use ettlex_store::cas::FsStore;
+use ettlex_store::migrations::apply_migrations;
+use rusqlite::Connection;
+use serde_json::json;
+use tempfile::TempDir;
+
+// ---------------------------------------------------------------------------
+// Test helpers
+// ---------------------------------------------------------------------------
+
+fn setup_db_with_cas() -> (Connection, FsStore, TempDir) {
+    let dir = TempDir::new().expect("temp dir");
+    let cas = FsStore::new(dir.path().join("cas"));
+    let mut conn = Connection::open_in_memory().expect("in-memory db");
+    apply_migrations(&mut conn).expect("migrations should apply");
+    (conn, cas, dir)
+}
+
+fn try_apply(conn: &mut Connection, cas: &FsStore, cmd: Command) -> Result<CommandResult, ExError> {
+    apply_command(
+        cmd,
+        None,
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .map(|(res, _sv)| res)
+}
+
+fn create_ettle(conn: &mut Connection, cas: &FsStore, title: &str) -> String {
+    match try_apply(
+        conn,
+        cas,
+        Command::EttleCreate {
+            title: title.to_string(),
+            ettle_id: None,
+            why: None,
+            what: None,
+            how: None,
+            reasoning_link_id: None,
+            reasoning_link_type: None,
+        },
+    )
+    .expect("ettle create should succeed")
+    {
+        CommandResult::EttleCreate { ettle_id } => ettle_id,
+        _ => panic!("unexpected result"),
+    }
+}
+
+fn refine(conn: &mut Connection, cas: &FsStore, parent: &str, child: &str) {
+    try_apply(
+        conn,
+        cas,
+        Command::RelationCreate {
+            source_ettle_id: parent.to_string(),
+            target_ettle_id: child.to_string(),
+            relation_type: "refinement".to_string(),
+            properties_json: None,
+            relation_id: None,
+        },
+    )
+    .expect("relation create should succeed");
+}
+
+fn register(
+    conn: &mut Connection,
+    cas: &FsStore,
+    ettle_id: &str,
+    display_name: &str,
+) -> Result<CommandResult, ExError> {
+    try_apply(
+        conn,
+        cas,
+        Command::RootRegister {
+            ettle_id: ettle_id.to_string(),
+            display_name: display_name.to_string(),
+            owner: Some("platform-team".to_string()),
+        },
+    )
+}
+
+fn retire(conn: &mut Connection, cas: &FsStore, ettle_id: &str) {
+    try_apply(
+        conn,
+        cas,
+        Command::RootUpdate {
+            ettle_id: ettle_id.to_string(),
+            display_name: None,
+            owner: None,
+            status: Some("retired".to_string()),
+        },
+    )
+    .expect("root update should succeed");
+}
+
+fn insert_snapshot(conn: &Connection, snapshot_id: &str, root: &str, created_at: i64) {
+    conn.execute(
+        "INSERT INTO snapshots (snapshot_id, root_ettle_id, manifest_digest, semantic_manifest_digest,
+                                created_at, policy_ref, profile_ref, status)
+         VALUES (?1, ?2, ?3, ?3, ?4, 'policy/default@0', 'profile/default@0', 'committed')",
+        rusqlite::params![snapshot_id, root, format!("{:064x}", created_at), created_at],
+    )
+    .expect("insert snapshot");
+}
+
+fn query(conn: &Connection, cas: &FsStore, q: EngineQuery) -> Result<EngineQueryResult, ExError> {
+    apply_engine_query(q, conn, cas, None)
+}
+
+fn root_list(conn: &Connection, cas: &FsStore, include_retired: bool) -> RootRegistry {
+    match query(conn, cas, EngineQuery::RootList { include_retired }).unwrap() {
+        EngineQueryResult::RootList(r) => r,
+        _ => panic!("unexpected result"),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_registered_roots_list_heads_and_unregistered_roots() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let payments = create_ettle(&mut conn, &cas, "Payments");
+    let ledger = create_ettle(&mut conn, &cas, "Ledger");
+    let stray = create_ettle(&mut conn, &cas, "Stray");
+    let child = create_ettle(&mut conn, &cas, "Refunds");
+    refine(&mut conn, &cas, &payments, &child);
+
+    register(&mut conn, &cas, &payments, "Payments platform").unwrap();
+    register(&mut conn, &cas, &ledger, "General ledger").unwrap();
+    insert_snapshot(&conn, "snap:1", &payments, 1);
+    insert_snapshot(&conn, "snap:2", &payments, 2);
+
+    let registry = root_list(&conn, &cas, false);
+    let names: Vec<&str> = registry
+        .registered
+        .iter()
+        .map(|r| r.root.display_name.as_str())
+        .collect();
+    assert_eq!(names, vec!["General ledger", "Payments platform"]);
+    assert_eq!(registry.registered[1].snapshot_count, 2);
+    assert_eq!(
+        registry.registered[1].head_snapshot_id.as_deref(),
+        Some("snap:2")
+    );
+    assert_eq!(registry.registered[0].head_snapshot_id, None);
+    assert_eq!(registry.unregistered, vec![stray]);
+
+    let got = match query(
+        &conn,
+        &cas,
+        EngineQuery::RootGet {
+            ettle_id: ledger.clone(),
+        },
+    )
+    .unwrap()
+    {
+        EngineQueryResult::RootGet(r) => r,
+        _ => panic!("unexpected result"),
+    };
+    assert_eq!(got.root.owner.as_deref(), Some("platform-team"));
+    assert_eq!(got.root.status, "active");
+}
+
+#[test]
+fn test_register_rejects_children_duplicates_and_unknown_ettles() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let root = create_ettle(&mut conn, &cas, "Root");
+    let other = create_ettle(&mut conn, &cas, "Other");
+    let child = create_ettle(&mut conn, &cas, "Child");
+    refine(&mut conn, &cas, &root, &child);
+
+    let err = register(&mut conn, &cas, &child, "Child").unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::RootEttleInvalid);
+
+    register(&mut conn, &cas, &root, "Root").unwrap();
+    let err = register(&mut conn, &cas, &root, "Root again").unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::AlreadyExists);
+    let err = register(&mut conn, &cas, &other, "Root").unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::AlreadyExists);
+    let err = register(&mut conn, &cas, &other, "   ").unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidTitle);
+    let err = register(&mut conn, &cas, "ettle:missing", "Missing").unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+}
+
+#[test]
+fn test_retired_roots_drop_out_of_snapshot_lists_and_heads() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let live = create_ettle(&mut conn, &cas, "Live");
+    let old = create_ettle(&mut conn, &cas, "Old");
+    register(&mut conn, &cas, &live, "Live").unwrap();
+    register(&mut conn, &cas, &old, "Old").unwrap();
+    insert_snapshot(&conn, "snap:live", &live, 1);
+    insert_snapshot(&conn, "snap:old", &old, 2);
+    retire(&mut conn, &cas, &old);
+
+    let snapshots = match query(
+        &conn,
+        &cas,
+        EngineQuery::SnapshotList {
+            ettle_id: None,
+            registered_only: true,
+        },
+    )
+    .unwrap()
+    {
+        EngineQueryResult::SnapshotList(s) => s,
+        _ => panic!("unexpected result"),
+    };
+    let ids: Vec<&str> = snapshots.iter().map(|s| s.snapshot_id.as_str()).collect();
+    assert_eq!(ids, vec!["snap:live"]);
+
+    let summary = match query(&conn, &cas, EngineQuery::StateGetVersion).unwrap() {
+        EngineQueryResult::StateVersion(s) => s.summary.expect("workspace summary"),
+        _ => panic!("unexpected result"),
+    };
+    assert_eq!(summary.root_heads.len(), 1);
+    assert_eq!(summary.root_heads[0].display_name.as_deref(), Some("Live"));
+
+    assert_eq!(root_list(&conn, &cas, false).registered.len(), 1);
+    let all = root_list(&conn, &cas, true);
+    assert_eq!(all.registered.len(), 2);
+    // Retired roots are still known, so they are not reported as unregistered
+    assert!(all.unregistered.is_empty());
+}
+
+#[test]
+fn test_root_update_requires_a_field_and_a_registered_root() {
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let root = create_ettle(&mut conn, &cas, "Root");
+    let update = |status: Option<&str>| Command::RootUpdate {
+        ettle_id: root.clone(),
+        display_name: None,
+        owner: None,
+        status: status.map(str::to_string),
+    };
+
+    let err = try_apply(&mut conn, &cas, update(Some("retired"))).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+
+    register(&mut conn, &cas, &root, "Root").unwrap();
+    let err = try_apply(&mut conn, &cas, update(None)).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::EmptyUpdate);
+    let err = try_apply(&mut conn, &cas, update(Some("archived"))).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_registered_mode_rejects_new_roots() {
+    let (conn, _cas, _dir) = setup_db_with_cas();
+    ensure_new_root_allowed(&conn, "import_csv", "line 2").unwrap();
+
+    settings_set(&conn, "roots.mode", Some(json!("registered"))).unwrap();
+    let err = ensure_new_root_allowed(&conn, "import_csv", "line 2").unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::RootEttleInvalid);
+}
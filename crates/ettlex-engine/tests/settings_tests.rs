@@ -0,0 +1,148 @@
+//! Typed settings: `SettingsGet` / `SettingsSet`, validation against the known
+//! keys, and precedence of explicit values over stored ones and defaults.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::ExErrorKind;
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_engine::commands::engine_command::{
+    apply_engine_command, EngineCommand, EngineCommandResult,
+};
+use ettlex_engine::commands::engine_query::{apply_engine_query, EngineQuery, EngineQueryResult};
+use ettlex_engine::commands::evidence::evidence_sweep;
+use ettlex_engine::commands::settings::{
+    dedup_enabled, default_policy_ref, resolve_setting, settings_set, SettingEntry, SettingSource,
+    KNOWN_SETTINGS, SETTING_DEDUP_MODE, SETTING_EVIDENCE_RETENTION_DAYS,
+};
+use ettlex_store::cas::FsStore;
+use rusqlite::Connection;
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+fn setup() -> (TempDir, Connection, FsStore) {
+    let dir = TempDir::new().unwrap();
+    let mut conn = Connection::open(dir.path().join("test.db")).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    let cas = FsStore::new(dir.path().join("cas"));
+    (dir, conn, cas)
+}
+
+fn get(conn: &Connection, cas: &FsStore, key: Option<&str>) -> Vec<SettingEntry> {
+    let query = EngineQuery::SettingsGet {
+        key: key.map(str::to_string),
+    };
+    match apply_engine_query(query, conn, cas, None).unwrap() {
+        EngineQueryResult::SettingsGet(entries) => entries,
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+fn set(
+    conn: &mut Connection,
+    cas: &FsStore,
+    key: &str,
+    value: Option<Value>,
+) -> ettlex_store::errors::Result<EngineCommandResult> {
+    apply_engine_command(
+        EngineCommand::SettingsSet {
+            key: key.to_string(),
+            value,
+        },
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+}
+
+#[test]
+fn test_settings_get_reports_defaults_then_stored_values() {
+    let (_dir, mut conn, cas) = setup();
+    let all = get(&conn, &cas, None);
+    assert_eq!(all.len(), KNOWN_SETTINGS.len());
+    assert!(all.windows(2).all(|w| w[0].key < w[1].key));
+    assert!(all.iter().all(|e| e.source == SettingSource::Default));
+
+    let dedup = &get(&conn, &cas, Some(SETTING_DEDUP_MODE))[0];
+    assert_eq!(dedup.value, Some(json!("append")));
+
+    set(&mut conn, &cas, SETTING_DEDUP_MODE, Some(json!("semantic"))).unwrap();
+    let dedup = &get(&conn, &cas, Some(SETTING_DEDUP_MODE))[0];
+    assert_eq!(dedup.value, Some(json!("semantic")));
+    assert_eq!(dedup.source, SettingSource::Stored);
+
+    set(&mut conn, &cas, SETTING_DEDUP_MODE, None).unwrap();
+    let dedup = &get(&conn, &cas, Some(SETTING_DEDUP_MODE))[0];
+    assert_eq!(dedup.source, SettingSource::Default);
+}
+
+#[test]
+fn test_explicit_values_take_precedence() {
+    let (_dir, conn, _cas) = setup();
+    settings_set(&conn, SETTING_DEDUP_MODE, Some(json!("semantic"))).unwrap();
+    assert!(dedup_enabled(&conn, None).unwrap());
+    assert!(!dedup_enabled(&conn, Some(false)).unwrap());
+
+    let entry = resolve_setting(&conn, SETTING_DEDUP_MODE, Some(json!("append"))).unwrap();
+    assert_eq!(entry.source, SettingSource::Explicit);
+    assert_eq!(entry.value, Some(json!("append")));
+}
+
+#[test]
+fn test_invalid_values_and_unknown_keys_are_rejected() {
+    let (_dir, mut conn, cas) = setup();
+    for (key, value) in [
+        (SETTING_DEDUP_MODE, json!("sometimes")),
+        (SETTING_EVIDENCE_RETENTION_DAYS, json!(0)),
+        (SETTING_EVIDENCE_RETENTION_DAYS, json!("a week")),
+        ("policy.default_ref", json!("no-version")),
+        ("log.level", json!(3)),
+        ("ui.theme", json!("dark")),
+    ] {
+        let err = set(&mut conn, &cas, key, Some(value)).unwrap_err();
+        assert_eq!(err.kind(), ExErrorKind::InvalidInput, "{}", key);
+    }
+    assert!(get(&conn, &cas, None)
+        .iter()
+        .all(|e| e.source == SettingSource::Default));
+
+    let query = EngineQuery::SettingsGet {
+        key: Some("ui.theme".to_string()),
+    };
+    let err = apply_engine_query(query, &conn, &cas, None).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_settings_feed_policy_ref_and_evidence_retention() {
+    let (_dir, mut conn, cas) = setup();
+    assert_eq!(default_policy_ref(&conn).unwrap(), None);
+    set(
+        &mut conn,
+        &cas,
+        "policy.default_ref",
+        Some(json!("policy/default@0")),
+    )
+    .unwrap();
+    assert_eq!(
+        default_policy_ref(&conn).unwrap().as_deref(),
+        Some("policy/default@0")
+    );
+
+    let err = evidence_sweep(&mut conn, &cas, None, true).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+    // Integers given as strings (as from the CLI) are normalised
+    set(
+        &mut conn,
+        &cas,
+        SETTING_EVIDENCE_RETENTION_DAYS,
+        Some(json!("30")),
+    )
+    .unwrap();
+    assert_eq!(
+        get(&conn, &cas, Some(SETTING_EVIDENCE_RETENTION_DAYS))[0].value,
+        Some(json!(30))
+    );
+    evidence_sweep(&mut conn, &cas, None, true).unwrap();
+}
@@ -0,0 +1,105 @@
+//! `SnapshotCompareMatrix`: pairwise semantic equality and clustering of
+//! snapshots committed without dedup.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::errors::ExErrorKind;
+use ettlex_core::ops::Store;
+use ettlex_core::snapshot::manifest::generate_manifest;
+use ettlex_engine::commands::engine_query::{
+    apply_engine_query, EngineQuery, EngineQueryResult, SnapshotRef,
+};
+use ettlex_engine::commands::snapshot_compare::SnapshotCompareMatrix;
+use ettlex_store::cas::FsStore;
+use ettlex_store::errors::Result;
+use ettlex_store::snapshot::persist::{commit_snapshot, SnapshotOptions};
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+fn setup() -> (TempDir, Connection, FsStore) {
+    let temp_dir = TempDir::new().unwrap();
+    let mut conn = Connection::open(temp_dir.path().join("test.db")).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    let cas = FsStore::new(temp_dir.path().join("cas"));
+    (temp_dir, conn, cas)
+}
+
+/// Commit a manifest for `ept`; dedup is off, so equal EPTs get new rows.
+fn commit(conn: &mut Connection, cas: &FsStore, ept: &[&str]) -> (String, String) {
+    let manifest = generate_manifest(
+        ept.iter().map(|s| s.to_string()).collect(),
+        "policy/default@0".into(),
+        "profile/default@0".into(),
+        "ettle:root".into(),
+        "0001".into(),
+        None,
+        &Store::new(),
+    )
+    .unwrap();
+    let result = commit_snapshot(conn, cas, manifest, SnapshotOptions::default()).unwrap();
+    (result.snapshot_id, result.manifest_digest)
+}
+
+fn compare(
+    conn: &Connection,
+    cas: &FsStore,
+    refs: Vec<SnapshotRef>,
+) -> Result<SnapshotCompareMatrix> {
+    match apply_engine_query(EngineQuery::SnapshotCompareMatrix { refs }, conn, cas, None)? {
+        EngineQueryResult::SnapshotCompareMatrix(m) => Ok(m),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_matrix_clusters_semantically_identical_snapshots() {
+    let (_tmp, mut conn, cas) = setup();
+    let (a1, _) = commit(&mut conn, &cas, &["ep:root:0"]);
+    let (b, _) = commit(&mut conn, &cas, &["ep:root:0", "ep:root:1"]);
+    let (a2, a2_digest) = commit(&mut conn, &cas, &["ep:root:0"]);
+    assert_ne!(a1, a2);
+
+    let m = compare(
+        &conn,
+        &cas,
+        vec![
+            SnapshotRef::SnapshotId(a1.clone()),
+            SnapshotRef::SnapshotId(b.clone()),
+            SnapshotRef::ManifestDigest(a2_digest),
+        ],
+    )
+    .unwrap();
+
+    assert_eq!(m.entries[0].snapshot_id.as_deref(), Some(a1.as_str()));
+    assert_eq!(m.entries[2].snapshot_id, None);
+    assert_eq!(
+        m.equal,
+        vec![
+            vec![true, false, true],
+            vec![false, true, false],
+            vec![true, false, true],
+        ]
+    );
+    assert_eq!(m.clusters, vec![vec![0, 2], vec![1]]);
+    assert_eq!(m.duplicate_clusters().count(), 1);
+}
+
+#[test]
+fn test_matrix_requires_two_resolvable_refs() {
+    let (_tmp, mut conn, cas) = setup();
+    let (a, _) = commit(&mut conn, &cas, &["ep:root:0"]);
+
+    let err = compare(&conn, &cas, vec![SnapshotRef::SnapshotId(a.clone())]).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+
+    let err = compare(
+        &conn,
+        &cas,
+        vec![
+            SnapshotRef::SnapshotId(a),
+            SnapshotRef::SnapshotId("no-such-snapshot".to_string()),
+        ],
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+}
@@ -0,0 +1,114 @@
+//! `SnapshotDiff` content diffs over manifests generated from stored Ettles.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::diff::severity::SeverityRules;
+use ettlex_core::ops::Store;
+use ettlex_core::snapshot::manifest::generate_manifest_with_input;
+use ettlex_engine::commands::engine_query::{
+    apply_engine_query, EngineQuery, EngineQueryResult, SnapshotRef,
+};
+use ettlex_engine::snapshot::{manifest_eval_input, store_ep_content};
+use ettlex_store::cas::FsStore;
+use ettlex_store::snapshot::persist::{commit_snapshot, SnapshotOptions};
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+fn setup() -> (TempDir, Connection, FsStore) {
+    let temp_dir = TempDir::new().unwrap();
+    let mut conn = Connection::open(temp_dir.path().join("test.db")).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    conn.execute(
+        "INSERT INTO ettles (id, title, why, what, how, created_at, updated_at)
+         VALUES ('ettle:root', 'Root', 'Because.', 'One\nTwo\nThree', 'Same',
+                 '2024-01-01T00:00:00Z', '2024-01-01T00:00:00Z')",
+        [],
+    )
+    .unwrap();
+    let cas = FsStore::new(temp_dir.path().join("cas"));
+    (temp_dir, conn, cas)
+}
+
+fn commit(conn: &mut Connection, cas: &FsStore) -> String {
+    let ept = vec!["ettle:root".to_string()];
+    let input = manifest_eval_input(conn, cas, &ept, "profile/default@0").unwrap();
+    store_ep_content(cas, &input).unwrap();
+    let manifest = generate_manifest_with_input(
+        ept,
+        "policy/default@0".into(),
+        "profile/default@0".into(),
+        "ettle:root".into(),
+        "0001".into(),
+        None,
+        &Store::new(),
+        &input,
+    )
+    .unwrap();
+    commit_snapshot(conn, cas, manifest, SnapshotOptions::default())
+        .unwrap()
+        .snapshot_id
+}
+
+fn diff_with_content(
+    conn: &Connection,
+    cas: &FsStore,
+    a: &str,
+    b: &str,
+) -> ettlex_core::diff::model::SnapshotDiff {
+    let query = EngineQuery::SnapshotDiff {
+        a_ref: SnapshotRef::SnapshotId(a.to_string()),
+        b_ref: SnapshotRef::SnapshotId(b.to_string()),
+        severity_rules: SeverityRules::default(),
+        ignore: None,
+        summary: Default::default(),
+        include_content: true,
+    };
+    match apply_engine_query(query, conn, cas, None).unwrap() {
+        EngineQueryResult::SnapshotDiff(r) => r.structured_diff,
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_content_diff_loads_changed_ettle_content_from_cas() {
+    let (_tmp, mut conn, cas) = setup();
+    let a = commit(&mut conn, &cas);
+    conn.execute(
+        "UPDATE ettles SET what = 'One\n2\nThree' WHERE id = 'ettle:root'",
+        [],
+    )
+    .unwrap();
+    let b = commit(&mut conn, &cas);
+
+    let diff = diff_with_content(&conn, &cas, &a, &b);
+    assert_eq!(
+        diff.ep_content_changes.changed_eps,
+        vec!["ettle:root".to_string()]
+    );
+    let content_diffs = diff.content_diffs.unwrap();
+    assert_eq!(content_diffs.len(), 1);
+    let entry = &content_diffs[0];
+    assert!(entry.available);
+    assert_ne!(entry.old_digest, entry.new_digest);
+    assert_eq!(entry.field_diffs.keys().collect::<Vec<_>>(), vec!["what"]);
+    assert_eq!(
+        entry.field_diffs["what"],
+        "--- a/ettle:root/what\n+++ b/ettle:root/what\n@@ -1,3 +1,3 @@\n One\n-Two\n+2\n Three\n"
+    );
+}
+
+#[test]
+fn test_unchanged_content_keeps_its_digest() {
+    let (_tmp, mut conn, cas) = setup();
+    let a = commit(&mut conn, &cas);
+    conn.execute(
+        "UPDATE ettles SET title = 'Renamed' WHERE id = 'ettle:root'",
+        [],
+    )
+    .unwrap();
+    let b = commit(&mut conn, &cas);
+
+    let diff = diff_with_content(&conn, &cas, &a, &b);
+    assert!(diff.ep_content_changes.changed_eps.is_empty());
+    assert_eq!(diff.content_diffs, Some(Vec::new()));
+}
@@ -0,0 +1,119 @@
+//! `SnapshotDiff` ignore list: explicit lists and profile `diff_ignore`.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::diff::ignore::DiffIgnoreList;
+use ettlex_core::diff::model::DiffSeverity;
+use ettlex_core::diff::severity::{ChangeCategory, SeverityRules};
+use ettlex_core::errors::ExErrorKind;
+use ettlex_core::ops::Store;
+use ettlex_core::snapshot::manifest::generate_manifest;
+use ettlex_engine::commands::engine_query::{
+    apply_engine_query, EngineQuery, EngineQueryResult, SnapshotDiffResult, SnapshotRef,
+};
+use ettlex_store::cas::FsStore;
+use ettlex_store::errors::Result;
+use ettlex_store::snapshot::persist::{commit_snapshot, SnapshotOptions};
+use rusqlite::Connection;
+use serde_json::json;
+use tempfile::TempDir;
+
+const PROFILE: &str = "profile/review@1";
+
+fn setup(profile_payload: serde_json::Value) -> (TempDir, Connection, FsStore) {
+    let temp_dir = TempDir::new().unwrap();
+    let mut conn = Connection::open(temp_dir.path().join("test.db")).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    conn.execute(
+        "INSERT INTO profiles (profile_ref, payload_json, is_default, created_at)
+         VALUES (?1, ?2, 0, 0)",
+        rusqlite::params![PROFILE, profile_payload.to_string()],
+    )
+    .unwrap();
+    let cas = FsStore::new(temp_dir.path().join("cas"));
+    (temp_dir, conn, cas)
+}
+
+fn commit(conn: &mut Connection, cas: &FsStore, ept: &[&str]) -> String {
+    let manifest = generate_manifest(
+        ept.iter().map(|s| s.to_string()).collect(),
+        "policy/default@0".into(),
+        PROFILE.into(),
+        "ettle:root".into(),
+        "0001".into(),
+        None,
+        &Store::new(),
+    )
+    .unwrap();
+    commit_snapshot(conn, cas, manifest, SnapshotOptions::default())
+        .unwrap()
+        .snapshot_id
+}
+
+fn diff(
+    conn: &Connection,
+    cas: &FsStore,
+    a: &str,
+    b: &str,
+    ignore: Option<DiffIgnoreList>,
+) -> Result<SnapshotDiffResult> {
+    let query = EngineQuery::SnapshotDiff {
+        a_ref: SnapshotRef::SnapshotId(a.to_string()),
+        b_ref: SnapshotRef::SnapshotId(b.to_string()),
+        severity_rules: SeverityRules::default(),
+        ignore,
+        summary: Default::default(),
+        include_content: false,
+    };
+    match apply_engine_query(query, conn, cas, None)? {
+        EngineQueryResult::SnapshotDiff(r) => Ok(*r),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_profile_diff_ignore_suppresses_category() {
+    let (_tmp, mut conn, cas) =
+        setup(json!({ "diff_ignore": { "categories": ["ept_entry_added"] } }));
+    let a = commit(&mut conn, &cas, &["ep:root:0"]);
+    let b = commit(&mut conn, &cas, &["ep:root:0", "ep:root:1"]);
+
+    let result = diff(&conn, &cas, &a, &b, None).unwrap();
+    let d = &result.structured_diff;
+    assert!(d.ept_changes.added_eps.is_empty());
+    assert!(!d.ept_changes.changed);
+    assert_eq!(d.ignored_tags.len(), 1);
+    assert_eq!(d.ignored_tags[0].category, ChangeCategory::EptEntryAdded);
+    assert!(d
+        .severity_tags
+        .iter()
+        .all(|t| t.category != ChangeCategory::EptEntryAdded));
+    assert!(d.severity < DiffSeverity::Breaking);
+    assert!(result
+        .human_summary
+        .contains("hidden by the diff ignore list"));
+}
+
+#[test]
+fn test_explicit_ignore_list_overrides_profile() {
+    let (_tmp, mut conn, cas) =
+        setup(json!({ "diff_ignore": { "categories": ["ept_entry_added"] } }));
+    let a = commit(&mut conn, &cas, &["ep:root:0"]);
+    let b = commit(&mut conn, &cas, &["ep:root:0", "ep:root:1"]);
+
+    let result = diff(&conn, &cas, &a, &b, Some(DiffIgnoreList::default())).unwrap();
+    let d = &result.structured_diff;
+    assert_eq!(d.ept_changes.added_eps, vec!["ep:root:1".to_string()]);
+    assert!(d.ignored_tags.is_empty());
+    assert_eq!(d.severity, DiffSeverity::Breaking);
+}
+
+#[test]
+fn test_invalid_profile_diff_ignore_is_rejected() {
+    let (_tmp, mut conn, cas) = setup(json!({ "diff_ignore": { "categories": ["bogus"] } }));
+    let a = commit(&mut conn, &cas, &["ep:root:0"]);
+    let b = commit(&mut conn, &cas, &["ep:root:0", "ep:root:1"]);
+
+    let err = diff(&conn, &cas, &a, &b, None).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+}
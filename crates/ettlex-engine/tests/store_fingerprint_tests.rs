@@ -0,0 +1,241 @@
+//! Store fingerprint tests.
+//!
+//! Tests cover: stores with the same content fingerprint identically
+//! regardless of insertion order and timestamps, out-of-band edits change
+//! the digest, tombstoned rows are ignored, content fingerprints the same
+//! inline or spilled to CAS, and the engine query dispatch.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_engine::commands::command::{apply_command, Command, CommandResult};
+use ettlex_engine::commands::engine_query::{apply_engine_query, EngineQuery, EngineQueryResult};
+use ettlex_engine::commands::settings::{settings_set, SETTING_CONTENT_MAX_INLINE_BYTES};
+use ettlex_engine::commands::store_fingerprint::compute_store_fingerprint;
+use ettlex_store::cas::FsStore;
+use ettlex_store::migrations::apply_migrations;
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+fn setup() -> Connection {
+    let mut conn = Connection::open_in_memory().expect("in-memory db");
+    apply_migrations(&mut conn).expect("migrations should apply");
+    conn
+}
+
+fn insert_ettle(conn: &Connection, id: &str, title: &str, at: &str) {
+    conn.execute(
+        "INSERT INTO ettles (id, title, why, created_at, updated_at)
+         VALUES (?1, ?2, 'because', ?3, ?3)",
+        rusqlite::params![id, title, at],
+    )
+    .unwrap();
+}
+
+fn insert_relation(conn: &Connection, id: &str, source: &str, target: &str, props: &str, at: &str) {
+    conn.execute(
+        "INSERT INTO relations
+         (id, source_ettle_id, target_ettle_id, relation_type, properties_json, created_at)
+         VALUES (?1, ?2, ?3, 'refinement', ?4, ?5)",
+        rusqlite::params![id, source, target, props, at],
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_same_content_fingerprints_identically_across_stores() {
+    let a = setup();
+    insert_ettle(&a, "ettle:root", "Root", "2024-01-01T00:00:00Z");
+    insert_ettle(&a, "ettle:child", "Child", "2024-01-01T00:00:00Z");
+    insert_relation(
+        &a,
+        "rel:1",
+        "ettle:root",
+        "ettle:child",
+        r#"{"a":1,"b":2}"#,
+        "2024-01-01T00:00:00Z",
+    );
+
+    // Reverse insertion order, later timestamps, reordered JSON keys.
+    let b = setup();
+    insert_ettle(&b, "ettle:child", "Child", "2025-06-30T12:00:00Z");
+    insert_ettle(&b, "ettle:root", "Root", "2025-06-30T12:00:00Z");
+    insert_relation(
+        &b,
+        "rel:1",
+        "ettle:root",
+        "ettle:child",
+        r#"{"b":2,"a":1}"#,
+        "2025-06-30T12:00:00Z",
+    );
+
+    let fa = compute_store_fingerprint(&a).unwrap();
+    let fb = compute_store_fingerprint(&b).unwrap();
+    assert_eq!(fa, fb);
+    assert_eq!(fa.ettle_count, 2);
+    assert_eq!(fa.relation_count, 1);
+}
+
+#[test]
+fn test_out_of_band_edit_changes_the_digest() {
+    let conn = setup();
+    insert_ettle(&conn, "ettle:root", "Root", "2024-01-01T00:00:00Z");
+    let before = compute_store_fingerprint(&conn).unwrap();
+
+    conn.execute(
+        "UPDATE ettles SET how = 'sneaky' WHERE id = 'ettle:root'",
+        [],
+    )
+    .unwrap();
+    let after = compute_store_fingerprint(&conn).unwrap();
+
+    assert_ne!(before.digest, after.digest);
+    assert_ne!(before.ettles_digest, after.ettles_digest);
+    assert_eq!(before.relations_digest, after.relations_digest);
+}
+
+#[test]
+fn test_tombstoned_rows_are_ignored() {
+    let a = setup();
+    insert_ettle(&a, "ettle:root", "Root", "2024-01-01T00:00:00Z");
+
+    let b = setup();
+    insert_ettle(&b, "ettle:root", "Root", "2024-01-01T00:00:00Z");
+    insert_ettle(&b, "ettle:gone", "Gone", "2024-01-01T00:00:00Z");
+    insert_relation(
+        &b,
+        "rel:gone",
+        "ettle:root",
+        "ettle:gone",
+        "{}",
+        "2024-01-01T00:00:00Z",
+    );
+    assert_ne!(
+        compute_store_fingerprint(&a).unwrap().digest,
+        compute_store_fingerprint(&b).unwrap().digest
+    );
+
+    b.execute_batch(
+        "UPDATE relations SET tombstoned_at = '2024-02-01T00:00:00Z';
+         UPDATE ettles SET tombstoned_at = '2024-02-01T00:00:00Z' WHERE id = 'ettle:gone';",
+    )
+    .unwrap();
+    assert_eq!(
+        compute_store_fingerprint(&a).unwrap(),
+        compute_store_fingerprint(&b).unwrap()
+    );
+}
+
+#[test]
+fn test_spilled_and_inline_content_fingerprint_identically() {
+    let dir = TempDir::new().unwrap();
+    let cas = FsStore::new(dir.path().join("cas"));
+    let mut conn = setup();
+    let body = "x".repeat(100);
+
+    let apply = |conn: &mut Connection, cmd: Command| {
+        apply_command(
+            cmd,
+            None,
+            conn,
+            &cas,
+            &NoopPolicyProvider,
+            &NoopApprovalRouter,
+        )
+        .unwrap()
+        .0
+    };
+
+    let ettle_id = match apply(
+        &mut conn,
+        Command::EttleCreate {
+            title: "Payments".to_string(),
+            ettle_id: None,
+            why: Some("short".to_string()),
+            what: Some(body.clone()),
+            how: None,
+            reasoning_link_id: None,
+            reasoning_link_type: None,
+        },
+    ) {
+        CommandResult::EttleCreate { ettle_id } => ettle_id,
+        other => panic!("unexpected result: {:?}", other),
+    };
+    let inline = compute_store_fingerprint(&conn).unwrap();
+
+    // Lower the threshold and rewrite the same body so it moves to CAS.
+    settings_set(
+        &conn,
+        SETTING_CONTENT_MAX_INLINE_BYTES,
+        Some(serde_json::json!(16)),
+    )
+    .unwrap();
+    apply(
+        &mut conn,
+        Command::EttleUpdate {
+            ettle_id: ettle_id.clone(),
+            title: None,
+            why: None,
+            what: Some(body),
+            how: None,
+            reasoning_link_id: None,
+            reasoning_link_type: None,
+        },
+    );
+    let what_digest: Option<String> = conn
+        .query_row(
+            "SELECT what_digest FROM ettles WHERE id = ?1",
+            [&ettle_id],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert!(what_digest.is_some(), "content should have spilled");
+
+    assert_eq!(inline, compute_store_fingerprint(&conn).unwrap());
+}
+
+#[test]
+fn test_store_fingerprint_query_tracks_commands() {
+    let dir = TempDir::new().unwrap();
+    let cas = FsStore::new(dir.path().join("cas"));
+    let mut conn = setup();
+
+    let query = |conn: &Connection| match apply_engine_query(
+        EngineQuery::StoreFingerprint,
+        conn,
+        &cas,
+        None,
+    )
+    .unwrap()
+    {
+        EngineQueryResult::StoreFingerprint(f) => f,
+        other => panic!("unexpected result: {:?}", other),
+    };
+
+    let empty = query(&conn);
+    assert_eq!(empty.ettle_count, 0);
+
+    apply_command(
+        Command::EttleCreate {
+            title: "Payments".to_string(),
+            ettle_id: None,
+            why: None,
+            what: None,
+            how: None,
+            reasoning_link_id: None,
+            reasoning_link_type: None,
+        },
+        None,
+        &mut conn,
+        &cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .unwrap();
+
+    let after = query(&conn);
+    assert_eq!(after.ettle_count, 1);
+    assert_ne!(empty.digest, after.digest);
+    assert_eq!(after, query(&conn));
+}
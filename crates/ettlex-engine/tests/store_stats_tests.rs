@@ -0,0 +1,160 @@
+//! Store statistics tests — `StoreStats` reports table row counts and the
+//! latency percentiles and outcome counts recorded for engine queries and
+//! commands.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_engine::commands::command::{apply_command, Command};
+use ettlex_engine::commands::engine_command::{
+    apply_engine_command, apply_engine_command_with_options, CommandOptions, EngineCommand,
+};
+use ettlex_engine::commands::engine_query::{apply_engine_query, EngineQuery, EngineQueryResult};
+use ettlex_engine::commands::read_tools::{ListOptions, StoreStats};
+use ettlex_engine::snapshot::SnapshotOptions;
+use ettlex_store::cas::FsStore;
+use ettlex_store::migrations::apply_migrations;
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+fn setup() -> (Connection, FsStore, TempDir) {
+    let dir = TempDir::new().expect("temp dir");
+    let cas = FsStore::new(dir.path().join("cas"));
+    let mut conn = Connection::open_in_memory().expect("in-memory db");
+    apply_migrations(&mut conn).expect("migrations should apply");
+    (conn, cas, dir)
+}
+
+fn store_stats(conn: &Connection, cas: &FsStore) -> StoreStats {
+    match apply_engine_query(EngineQuery::StoreStats, conn, cas, None).unwrap() {
+        EngineQueryResult::StoreStats(s) => s,
+        _ => panic!("unexpected result"),
+    }
+}
+
+#[test]
+fn test_store_stats_reports_table_row_counts() {
+    let (mut conn, cas, _dir) = setup();
+    apply_command(
+        Command::EttleCreate {
+            title: "Counted".to_string(),
+            ettle_id: None,
+            why: None,
+            what: None,
+            how: None,
+            reasoning_link_id: None,
+            reasoning_link_type: None,
+        },
+        None,
+        &mut conn,
+        &cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .unwrap();
+
+    let stats = store_stats(&conn, &cas);
+    let row_count = |table: &str| {
+        stats
+            .tables
+            .iter()
+            .find(|t| t.table == table)
+            .and_then(|t| t.row_count)
+    };
+    assert_eq!(row_count("ettles"), Some(1));
+    assert_eq!(row_count("command_log"), Some(1));
+    assert_eq!(row_count("relations"), Some(0));
+}
+
+#[test]
+fn test_store_stats_aggregates_query_and_command_latencies() {
+    let (mut conn, cas, _dir) = setup();
+    for _ in 0..3 {
+        apply_engine_query(
+            EngineQuery::EttleList(ListOptions::default()),
+            &conn,
+            &cas,
+            None,
+        )
+        .unwrap();
+    }
+    apply_command(
+        Command::GroupCreate {
+            name: "timed".to_string(),
+        },
+        None,
+        &mut conn,
+        &cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .unwrap();
+
+    let stats = store_stats(&conn, &cas);
+    let list = stats.op_latencies["ettle_list"];
+    assert!(list.count >= 3);
+    assert!(list.p50_ms <= list.p95_ms && list.p95_ms <= list.p99_ms);
+    assert!(list.p99_ms <= list.max_ms);
+    assert!(stats.op_latencies["group_create"].count >= 1);
+}
+
+fn snapshot_commit_count(stats: &StoreStats, outcome: &str) -> u64 {
+    stats
+        .op_outcomes
+        .get("snapshot_commit")
+        .and_then(|o| o.get(outcome))
+        .copied()
+        .unwrap_or(0)
+}
+
+fn commit(leaf_ep_id: &str) -> EngineCommand {
+    EngineCommand::SnapshotCommit {
+        leaf_ep_id: leaf_ep_id.to_string(),
+        policy_ref: None,
+        profile_ref: None,
+        options: SnapshotOptions::default(),
+    }
+}
+
+#[test]
+fn test_store_stats_counts_snapshot_commit_outcomes_by_error_kind() {
+    let (mut conn, cas, _dir) = setup();
+    let before = store_stats(&conn, &cas);
+
+    // The snapshot pipeline is deferred, so a valid commit fails as not implemented.
+    apply_engine_command(
+        commit("ep:leaf:0"),
+        &mut conn,
+        &cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .unwrap_err();
+    apply_engine_command(
+        commit(""),
+        &mut conn,
+        &cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .unwrap_err();
+    // Dry runs are not commit attempts.
+    apply_engine_command_with_options(
+        commit("ep:leaf:0"),
+        CommandOptions { dry_run: true },
+        &mut conn,
+        &cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .unwrap_err();
+
+    let after = store_stats(&conn, &cas);
+    let delta = |outcome: &str| {
+        snapshot_commit_count(&after, outcome) - snapshot_commit_count(&before, outcome)
+    };
+    assert_eq!(delta("failed:ERR_NOT_IMPLEMENTED"), 1);
+    assert_eq!(delta("failed:ERR_INVALID_INPUT"), 1);
+    assert_eq!(delta("committed"), 0);
+}
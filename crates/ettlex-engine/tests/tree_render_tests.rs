@@ -0,0 +1,226 @@
+//! Hierarchical tree render tests — refinement order, skipped Ettles, depth
+//! limit, section numbering and declared content formats over a real store.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::errors::ExErrorKind;
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_core::render::TreeRenderOptions;
+use ettlex_engine::commands::command::{apply_command, Command, CommandResult};
+use ettlex_engine::commands::ettle::handle_ettle_get;
+use ettlex_engine::commands::tree_render::{build_render_tree, render_ettle_tree};
+use ettlex_store::cas::FsStore;
+use ettlex_store::migrations::apply_migrations;
+use rusqlite::Connection;
+use serde_json::json;
+use tempfile::TempDir;
+
+// ---------------------------------------------------------------------------
+// Test helpers
+// ---------------------------------------------------------------------------
+
+fn setup() -> (Connection, FsStore, TempDir) {
+    let dir = TempDir::new().expect("temp dir");
+    let cas = FsStore::new(dir.path().join("cas"));
+    let mut conn = Connection::open_in_memory().expect("in-memory db");
+    apply_migrations(&mut conn).expect("migrations should apply");
+    (conn, cas, dir)
+}
+
+fn apply(conn: &mut Connection, cas: &FsStore, cmd: Command) -> CommandResult {
+    apply_command(
+        cmd,
+        None,
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .expect("command should succeed")
+    .0
+}
+
+fn create_ettle(conn: &mut Connection, cas: &FsStore, title: &str) -> String {
+    match apply(
+        conn,
+        cas,
+        Command::EttleCreate {
+            title: title.to_string(),
+            ettle_id: None,
+            why: None,
+            what: Some(format!("{} what", title)),
+            how: None,
+            reasoning_link_id: None,
+            reasoning_link_type: None,
+        },
+    ) {
+        CommandResult::EttleCreate { ettle_id } => ettle_id,
+        _ => panic!("unexpected result"),
+    }
+}
+
+fn refine(conn: &mut Connection, cas: &FsStore, parent: &str, child: &str, ordinal: Option<u64>) {
+    apply(
+        conn,
+        cas,
+        Command::RelationCreate {
+            source_ettle_id: parent.to_string(),
+            target_ettle_id: child.to_string(),
+            relation_type: "refinement".to_string(),
+            properties_json: ordinal.map(|o| json!({ "ordinal": o })),
+            relation_id: None,
+        },
+    );
+}
+
+/// Root with children Second (ordinal 2), First (ordinal 1) and Unordered;
+/// First has one child, Leaf.
+fn payments_tree(conn: &mut Connection, cas: &FsStore) -> String {
+    let root = create_ettle(conn, cas, "Payments");
+    let second = create_ettle(conn, cas, "Second");
+    let first = create_ettle(conn, cas, "First");
+    let unordered = create_ettle(conn, cas, "Unordered");
+    let leaf = create_ettle(conn, cas, "Leaf");
+    refine(conn, cas, &root, &unordered, None);
+    refine(conn, cas, &root, &second, Some(2));
+    refine(conn, cas, &root, &first, Some(1));
+    refine(conn, cas, &first, &leaf, Some(0));
+    root
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_children_follow_ordinal_then_unordered() {
+    let (mut conn, cas, _dir) = setup();
+    let root = payments_tree(&mut conn, &cas);
+
+    let tree = build_render_tree(&conn, &cas, &root, None).unwrap();
+    let titles: Vec<&str> = tree.children.iter().map(|c| c.title.as_str()).collect();
+    assert_eq!(titles, ["First", "Second", "Unordered"]);
+    assert_eq!(tree.children[0].children[0].title, "Leaf");
+    assert_eq!(tree.children[0].what, "First what");
+}
+
+#[test]
+fn test_numbered_render_with_toc_and_depth_limit() {
+    let (mut conn, cas, _dir) = setup();
+    let root = payments_tree(&mut conn, &cas);
+
+    let full = render_ettle_tree(
+        &conn,
+        &cas,
+        &root,
+        &TreeRenderOptions {
+            numbering: true,
+            toc: true,
+            max_depth: None,
+        },
+    )
+    .unwrap();
+    assert!(full.contains("- [1. First](#1-first)\n  - [1.1 Leaf](#11-leaf)\n"));
+    assert!(full.contains("### 1.1 Leaf\n\n**What:** Leaf what\n"));
+    assert!(full.contains("## 3. Unordered\n"));
+
+    let shallow = render_ettle_tree(
+        &conn,
+        &cas,
+        &root,
+        &TreeRenderOptions {
+            numbering: true,
+            toc: false,
+            max_depth: Some(1),
+        },
+    )
+    .unwrap();
+    assert!(shallow.contains("## 2. Second\n"));
+    assert!(!shallow.contains("Leaf"));
+}
+
+#[test]
+fn test_archived_children_skipped_and_missing_root_not_found() {
+    let (mut conn, cas, _dir) = setup();
+    let root = payments_tree(&mut conn, &cas);
+    let second = build_render_tree(&conn, &cas, &root, None)
+        .unwrap()
+        .children[1]
+        .ettle_id
+        .clone();
+    apply(
+        &mut conn,
+        &cas,
+        Command::EttleArchive {
+            ettle_id: second,
+            subtree: false,
+        },
+    );
+
+    let tree = build_render_tree(&conn, &cas, &root, None).unwrap();
+    let titles: Vec<&str> = tree.children.iter().map(|c| c.title.as_str()).collect();
+    assert_eq!(titles, ["First", "Unordered"]);
+
+    let err = build_render_tree(&conn, &cas, "ettle:missing", None).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+}
+
+fn set_format(
+    conn: &mut Connection,
+    cas: &FsStore,
+    ettle_id: &str,
+    format: &str,
+) -> Result<(), ettlex_core::errors::ExError> {
+    apply_command(
+        Command::EttleSetContentFormat {
+            ettle_id: ettle_id.to_string(),
+            content_format: format.to_string(),
+        },
+        None,
+        conn,
+        cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .map(|_| ())
+}
+
+#[test]
+fn test_declared_content_format_is_persisted_and_fenced() {
+    let (mut conn, cas, _dir) = setup();
+    let root = payments_tree(&mut conn, &cas);
+    assert_eq!(
+        handle_ettle_get(&conn, &root).unwrap().content_format,
+        "markdown"
+    );
+
+    set_format(&mut conn, &cas, &root, "plain").unwrap();
+    assert_eq!(
+        handle_ettle_get(&conn, &root).unwrap().content_format,
+        "plain"
+    );
+
+    let out = render_ettle_tree(&conn, &cas, &root, &TreeRenderOptions::default()).unwrap();
+    assert!(out.contains("**What:**\n\n```text\nPayments what\n```\n"));
+    assert!(out.contains("**What:** First what\n"));
+}
+
+#[test]
+fn test_unknown_content_format_is_rejected() {
+    let (mut conn, cas, _dir) = setup();
+    let root = create_ettle(&mut conn, &cas, "Payments");
+
+    let err = set_format(&mut conn, &cas, &root, "rst").unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+
+    apply(
+        &mut conn,
+        &cas,
+        Command::EttleTombstone {
+            ettle_id: root.clone(),
+        },
+    );
+    let err = set_format(&mut conn, &cas, &root, "plain").unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::AlreadyTombstoned);
+}
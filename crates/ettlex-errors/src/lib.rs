@@ -4,11 +4,15 @@
 //! - `ExErrorKind` — stable, matchable error taxonomy
 //! - `ExError` — structured error type with rich context fields
 //! - `assert_err_kind!` and `assert_err_field!` — test assertion macros
+//! - `transport` — the shared HTTP / gRPC / exit-code mapping for each kind
 
 pub mod macros;
+pub mod transport;
 
 use ettlex_core_types::{RequestId, TraceId};
 
+pub use transport::{transport_mapping, transport_table, ErrorCategory, TransportMapping};
+
 /// Canonical error kind taxonomy
 ///
 /// This taxonomy provides a stable, structured classification of all errors
@@ -117,6 +121,10 @@ pub enum ExErrorKind {
     /// A link record is missing its type discriminator field
     MissingLinkType,
 
+    // Rendering
+    /// Strict template resolution found a `{{variable}}` with no bound value
+    UnresolvedVariable,
+
     // Integration/IO (future)
     Io,
     Serialization,
@@ -125,7 +133,7 @@ pub enum ExErrorKind {
     Timeout,
     Concurrency,
 
-    // Auth (future)
+    // Auth
     Unauthorised,
     Forbidden,
 
@@ -194,6 +202,7 @@ impl ExErrorKind {
             ExErrorKind::SelfReferentialLink => "ERR_SELF_REFERENTIAL_LINK",
             ExErrorKind::HasActiveDependants => "ERR_HAS_ACTIVE_DEPENDANTS",
             ExErrorKind::MissingLinkType => "ERR_MISSING_LINK_TYPE",
+            ExErrorKind::UnresolvedVariable => "ERR_UNRESOLVED_VARIABLE",
             ExErrorKind::Io => "ERR_IO",
             ExErrorKind::Serialization => "ERR_SERIALIZATION",
             ExErrorKind::Persistence => "ERR_PERSISTENCE",
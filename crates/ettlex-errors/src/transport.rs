@@ -0,0 +1,334 @@
+//! Transport-level error mapping
+//!
+//! Every `ExErrorKind` belongs to exactly one [`ErrorCategory`], and each
+//! category fixes an HTTP status, a gRPC status code and a process exit code.
+//! Frontends (CLI, MCP, HTTP) map errors through this table rather than
+//! keeping their own, so the same failure surfaces identically everywhere.
+//!
+//! Categories are named after the gRPC canonical codes. Exit codes follow
+//! the BSD `sysexits.h` conventions.
+
+use crate::ExErrorKind;
+
+/// Transport-neutral classification of an error kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ErrorCategory {
+    /// The request itself is malformed or refers to something ambiguously
+    InvalidArgument,
+    /// The referenced entity or document does not exist
+    NotFound,
+    /// The entity being created already exists
+    AlreadyExists,
+    /// The request is well-formed but the current state does not allow it
+    FailedPrecondition,
+    /// Lost an optimistic-concurrency race; retrying may succeed
+    Aborted,
+    /// No valid credentials were presented
+    Unauthenticated,
+    /// Credentials or policy do not permit the operation
+    PermissionDenied,
+    /// A configured size limit was exceeded
+    ResourceExhausted,
+    /// A dependency is unavailable
+    Unavailable,
+    /// The operation timed out
+    DeadlineExceeded,
+    /// The operation is not implemented in this build
+    Unimplemented,
+    /// Stored data is corrupt or violates an integrity invariant
+    DataLoss,
+    /// Filesystem or database I/O failed
+    Io,
+    /// Any other internal failure
+    Internal,
+}
+
+impl ErrorCategory {
+    /// All categories, in declaration order
+    pub const ALL: &'static [ErrorCategory] = &[
+        ErrorCategory::InvalidArgument,
+        ErrorCategory::NotFound,
+        ErrorCategory::AlreadyExists,
+        ErrorCategory::FailedPrecondition,
+        ErrorCategory::Aborted,
+        ErrorCategory::Unauthenticated,
+        ErrorCategory::PermissionDenied,
+        ErrorCategory::ResourceExhausted,
+        ErrorCategory::Unavailable,
+        ErrorCategory::DeadlineExceeded,
+        ErrorCategory::Unimplemented,
+        ErrorCategory::DataLoss,
+        ErrorCategory::Io,
+        ErrorCategory::Internal,
+    ];
+
+    /// Stable snake_case name
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCategory::InvalidArgument => "invalid_argument",
+            ErrorCategory::NotFound => "not_found",
+            ErrorCategory::AlreadyExists => "already_exists",
+            ErrorCategory::FailedPrecondition => "failed_precondition",
+            ErrorCategory::Aborted => "aborted",
+            ErrorCategory::Unauthenticated => "unauthenticated",
+            ErrorCategory::PermissionDenied => "permission_denied",
+            ErrorCategory::ResourceExhausted => "resource_exhausted",
+            ErrorCategory::Unavailable => "unavailable",
+            ErrorCategory::DeadlineExceeded => "deadline_exceeded",
+            ErrorCategory::Unimplemented => "unimplemented",
+            ErrorCategory::DataLoss => "data_loss",
+            ErrorCategory::Io => "io",
+            ErrorCategory::Internal => "internal",
+        }
+    }
+
+    /// HTTP response status
+    pub fn http_status(self) -> u16 {
+        match self {
+            ErrorCategory::InvalidArgument => 400,
+            ErrorCategory::NotFound => 404,
+            ErrorCategory::AlreadyExists
+            | ErrorCategory::FailedPrecondition
+            | ErrorCategory::Aborted => 409,
+            ErrorCategory::Unauthenticated => 401,
+            ErrorCategory::PermissionDenied => 403,
+            ErrorCategory::ResourceExhausted => 413,
+            ErrorCategory::Unavailable => 503,
+            ErrorCategory::DeadlineExceeded => 504,
+            ErrorCategory::Unimplemented => 501,
+            ErrorCategory::DataLoss | ErrorCategory::Io | ErrorCategory::Internal => 500,
+        }
+    }
+
+    /// gRPC status code number
+    pub fn grpc_code(self) -> u32 {
+        match self {
+            ErrorCategory::InvalidArgument => 3,
+            ErrorCategory::DeadlineExceeded => 4,
+            ErrorCategory::NotFound => 5,
+            ErrorCategory::AlreadyExists => 6,
+            ErrorCategory::PermissionDenied => 7,
+            ErrorCategory::ResourceExhausted => 8,
+            ErrorCategory::FailedPrecondition => 9,
+            ErrorCategory::Aborted => 10,
+            ErrorCategory::Unimplemented => 12,
+            ErrorCategory::Io | ErrorCategory::Internal => 13,
+            ErrorCategory::Unavailable => 14,
+            ErrorCategory::DataLoss => 15,
+            ErrorCategory::Unauthenticated => 16,
+        }
+    }
+
+    /// Process exit code for command-line frontends
+    pub fn exit_code(self) -> i32 {
+        match self {
+            // EX_DATAERR
+            ErrorCategory::InvalidArgument
+            | ErrorCategory::FailedPrecondition
+            | ErrorCategory::ResourceExhausted => 65,
+            // EX_NOINPUT
+            ErrorCategory::NotFound => 66,
+            // EX_UNAVAILABLE
+            ErrorCategory::Unavailable | ErrorCategory::Unimplemented => 69,
+            // EX_SOFTWARE
+            ErrorCategory::DataLoss | ErrorCategory::Internal => 70,
+            // EX_CANTCREAT
+            ErrorCategory::AlreadyExists => 73,
+            // EX_IOERR
+            ErrorCategory::Io => 74,
+            // EX_TEMPFAIL
+            ErrorCategory::Aborted | ErrorCategory::DeadlineExceeded => 75,
+            // EX_NOPERM
+            ErrorCategory::Unauthenticated | ErrorCategory::PermissionDenied => 77,
+        }
+    }
+}
+
+/// One row of the transport mapping table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransportMapping {
+    pub kind: ExErrorKind,
+    pub category: ErrorCategory,
+    pub http_status: u16,
+    pub grpc_code: u32,
+    pub exit_code: i32,
+}
+
+/// Map an error kind to its transport-level codes
+pub fn transport_mapping(kind: ExErrorKind) -> TransportMapping {
+    let category = kind.category();
+    TransportMapping {
+        kind,
+        category,
+        http_status: category.http_status(),
+        grpc_code: category.grpc_code(),
+        exit_code: category.exit_code(),
+    }
+}
+
+/// The full mapping table, one row per `ExErrorKind` in declaration order
+pub fn transport_table() -> Vec<TransportMapping> {
+    ExErrorKind::ALL
+        .iter()
+        .map(|&kind| transport_mapping(kind))
+        .collect()
+}
+
+impl ExErrorKind {
+    /// Every error kind, in declaration order
+    pub const ALL: &'static [ExErrorKind] = &[
+        ExErrorKind::InvalidInput,
+        ExErrorKind::InvalidTitle,
+        ExErrorKind::InvalidOrdinal,
+        ExErrorKind::NotFound,
+        ExErrorKind::Deleted,
+        ExErrorKind::ConstraintViolation,
+        ExErrorKind::IllegalReparent,
+        ExErrorKind::CycleDetected,
+        ExErrorKind::MultipleParents,
+        ExErrorKind::DuplicateMapping,
+        ExErrorKind::MissingMapping,
+        ExErrorKind::AmbiguousSelection,
+        ExErrorKind::TraversalBroken,
+        ExErrorKind::DeletedNodeInTraversal,
+        ExErrorKind::AmbiguousLeafSelection,
+        ExErrorKind::DeterminismViolation,
+        ExErrorKind::CannotDelete,
+        ExErrorKind::StrandsChild,
+        ExErrorKind::InvalidDecision,
+        ExErrorKind::InvalidEvidence,
+        ExErrorKind::InvalidEvidencePath,
+        ExErrorKind::DecisionTombstoned,
+        ExErrorKind::DuplicateLink,
+        ExErrorKind::InvalidTargetKind,
+        ExErrorKind::ProfileNotFound,
+        ExErrorKind::ProfileDefaultMissing,
+        ExErrorKind::ProfileConflict,
+        ExErrorKind::ApprovalNotFound,
+        ExErrorKind::ApprovalRoutingUnavailable,
+        ExErrorKind::ApprovalStorageCorrupt,
+        ExErrorKind::InvalidConstraintFamily,
+        ExErrorKind::AlreadyExists,
+        ExErrorKind::ConstraintTombstoned,
+        ExErrorKind::DuplicateAttachment,
+        ExErrorKind::HeadMismatch,
+        ExErrorKind::NotALeaf,
+        ExErrorKind::PolicyDenied,
+        ExErrorKind::RootEttleAmbiguous,
+        ExErrorKind::RootEttleInvalid,
+        ExErrorKind::EptAmbiguous,
+        ExErrorKind::RefinementIntegrityViolation,
+        ExErrorKind::NotImplemented,
+        ExErrorKind::PolicyNotFound,
+        ExErrorKind::PolicyExportFailed,
+        ExErrorKind::PolicyRefMissing,
+        ExErrorKind::PolicyExportTooLarge,
+        ExErrorKind::PolicyParseError,
+        ExErrorKind::PolicyConflict,
+        ExErrorKind::InvalidManifest,
+        ExErrorKind::MissingField,
+        ExErrorKind::MissingBlob,
+        ExErrorKind::InvariantViolation,
+        ExErrorKind::EmptyUpdate,
+        ExErrorKind::AlreadyTombstoned,
+        ExErrorKind::SelfReferentialLink,
+        ExErrorKind::HasActiveDependants,
+        ExErrorKind::MissingLinkType,
+        ExErrorKind::UnresolvedVariable,
+        ExErrorKind::Io,
+        ExErrorKind::Serialization,
+        ExErrorKind::Persistence,
+        ExErrorKind::ExternalService,
+        ExErrorKind::Timeout,
+        ExErrorKind::Concurrency,
+        ExErrorKind::Unauthorised,
+        ExErrorKind::Forbidden,
+        ExErrorKind::Internal,
+    ];
+
+    /// The transport-neutral category of this kind
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            ExErrorKind::InvalidInput
+            | ExErrorKind::InvalidTitle
+            | ExErrorKind::InvalidOrdinal
+            | ExErrorKind::IllegalReparent
+            | ExErrorKind::CycleDetected
+            | ExErrorKind::MultipleParents
+            | ExErrorKind::AmbiguousSelection
+            | ExErrorKind::AmbiguousLeafSelection
+            | ExErrorKind::InvalidDecision
+            | ExErrorKind::InvalidEvidence
+            | ExErrorKind::InvalidEvidencePath
+            | ExErrorKind::InvalidTargetKind
+            | ExErrorKind::InvalidConstraintFamily
+            | ExErrorKind::RootEttleAmbiguous
+            | ExErrorKind::RootEttleInvalid
+            | ExErrorKind::EptAmbiguous
+            | ExErrorKind::PolicyRefMissing
+            | ExErrorKind::PolicyParseError
+            | ExErrorKind::InvalidManifest
+            | ExErrorKind::MissingField
+            | ExErrorKind::EmptyUpdate
+            | ExErrorKind::SelfReferentialLink
+            | ExErrorKind::MissingLinkType
+            | ExErrorKind::UnresolvedVariable => ErrorCategory::InvalidArgument,
+
+            ExErrorKind::NotFound
+            | ExErrorKind::Deleted
+            | ExErrorKind::ProfileNotFound
+            | ExErrorKind::ApprovalNotFound
+            | ExErrorKind::PolicyNotFound => ErrorCategory::NotFound,
+
+            ExErrorKind::AlreadyExists
+            | ExErrorKind::DuplicateMapping
+            | ExErrorKind::DuplicateLink
+            | ExErrorKind::DuplicateAttachment
+            | ExErrorKind::ProfileConflict
+            | ExErrorKind::PolicyConflict => ErrorCategory::AlreadyExists,
+
+            ExErrorKind::ConstraintViolation
+            | ExErrorKind::MissingMapping
+            | ExErrorKind::CannotDelete
+            | ExErrorKind::StrandsChild
+            | ExErrorKind::DecisionTombstoned
+            | ExErrorKind::ConstraintTombstoned
+            | ExErrorKind::AlreadyTombstoned
+            | ExErrorKind::HasActiveDependants
+            | ExErrorKind::ProfileDefaultMissing
+            | ExErrorKind::NotALeaf
+            | ExErrorKind::PolicyExportFailed => ErrorCategory::FailedPrecondition,
+
+            ExErrorKind::HeadMismatch | ExErrorKind::Concurrency => ErrorCategory::Aborted,
+
+            ExErrorKind::Unauthorised => ErrorCategory::Unauthenticated,
+            ExErrorKind::Forbidden | ExErrorKind::PolicyDenied => ErrorCategory::PermissionDenied,
+
+            ExErrorKind::PolicyExportTooLarge => ErrorCategory::ResourceExhausted,
+
+            ExErrorKind::ApprovalRoutingUnavailable | ExErrorKind::ExternalService => {
+                ErrorCategory::Unavailable
+            }
+            ExErrorKind::Timeout => ErrorCategory::DeadlineExceeded,
+            ExErrorKind::NotImplemented => ErrorCategory::Unimplemented,
+
+            ExErrorKind::TraversalBroken
+            | ExErrorKind::DeletedNodeInTraversal
+            | ExErrorKind::DeterminismViolation
+            | ExErrorKind::ApprovalStorageCorrupt
+            | ExErrorKind::RefinementIntegrityViolation
+            | ExErrorKind::MissingBlob
+            | ExErrorKind::InvariantViolation => ErrorCategory::DataLoss,
+
+            ExErrorKind::Io | ExErrorKind::Persistence => ErrorCategory::Io,
+            ExErrorKind::Serialization | ExErrorKind::Internal => ErrorCategory::Internal,
+        }
+    }
+}
+
+impl crate::ExError {
+    /// The transport-level codes for this error's kind
+    pub fn transport(&self) -> TransportMapping {
+        transport_mapping(self.kind())
+    }
+}
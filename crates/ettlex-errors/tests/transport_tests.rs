@@ -0,0 +1,57 @@
+use std::collections::BTreeSet;
+
+use ettlex_errors::{transport_mapping, transport_table, ErrorCategory, ExError, ExErrorKind};
+
+#[test]
+fn test_transport_table_covers_every_kind_once() {
+    let table = transport_table();
+    assert_eq!(table.len(), ExErrorKind::ALL.len());
+
+    let codes: BTreeSet<&str> = table.iter().map(|row| row.kind.code()).collect();
+    assert_eq!(codes.len(), table.len(), "ExErrorKind::ALL has duplicates");
+
+    for row in &table {
+        assert_eq!(row.http_status, row.category.http_status());
+        assert_eq!(row.grpc_code, row.category.grpc_code());
+        assert_eq!(row.exit_code, row.category.exit_code());
+    }
+}
+
+#[test]
+fn test_transport_mapping_pins_representative_kinds() {
+    let cases = [
+        (ExErrorKind::InvalidInput, 400, 3, 65),
+        (ExErrorKind::NotFound, 404, 5, 66),
+        (ExErrorKind::AlreadyExists, 409, 6, 73),
+        (ExErrorKind::HasActiveDependants, 409, 9, 65),
+        (ExErrorKind::HeadMismatch, 409, 10, 75),
+        (ExErrorKind::Unauthorised, 401, 16, 77),
+        (ExErrorKind::PolicyDenied, 403, 7, 77),
+        (ExErrorKind::PolicyExportTooLarge, 413, 8, 65),
+        (ExErrorKind::ExternalService, 503, 14, 69),
+        (ExErrorKind::Timeout, 504, 4, 75),
+        (ExErrorKind::NotImplemented, 501, 12, 69),
+        (ExErrorKind::MissingBlob, 500, 15, 70),
+        (ExErrorKind::Persistence, 500, 13, 74),
+        (ExErrorKind::Internal, 500, 13, 70),
+    ];
+    for (kind, http, grpc, exit) in cases {
+        let m = transport_mapping(kind);
+        assert_eq!(
+            (m.http_status, m.grpc_code, m.exit_code),
+            (http, grpc, exit),
+            "wrong mapping for {:?}",
+            kind
+        );
+    }
+}
+
+#[test]
+fn test_category_names_are_unique_and_errors_expose_mapping() {
+    let names: BTreeSet<&str> = ErrorCategory::ALL.iter().map(|c| c.as_str()).collect();
+    assert_eq!(names.len(), ErrorCategory::ALL.len());
+
+    let err = ExError::new(ExErrorKind::Forbidden).with_message("no");
+    assert_eq!(err.transport().category, ErrorCategory::PermissionDenied);
+    assert_eq!(err.transport().http_status, 403);
+}
@@ -3,11 +3,20 @@
 //! This crate provides the foundational logging infrastructure for the EttleX workspace:
 //! - `init(profile)` — single initialisation point
 //! - `log_op_start!`, `log_op_end!`, `log_op_error!` — structured logging macros
+//! - `log_op_slow!` / `slow_op` — slow-operation thresholds and latency percentiles
+//! - `log_op_outcome!` / `op_outcome` — per-op outcome counters
 //! - `TestCapture` / `init_test_capture()` — deterministic test capture mode
 
 pub mod init;
 pub mod macros;
+pub mod op_outcome;
+pub mod slow_op;
 pub mod test_capture;
 
 pub use init::{init, Profile};
+pub use op_outcome::{op_outcome_counts, record_op_outcome, reset_op_outcome_counts};
+pub use slow_op::{
+    observe_op_latency, op_latency_stats, reset_op_latency_stats, set_slow_op_thresholds,
+    slow_op_thresholds, OpLatencyStats, SlowOpThresholds,
+};
 pub use test_capture::{init_test_capture, CapturedEvent, TestCapture, TestCaptureLayer};
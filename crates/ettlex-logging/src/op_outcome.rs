@@ -0,0 +1,50 @@
+//! Per-op outcome counters
+//!
+//! Ops whose result is more than success or failure (a commit that was
+//! deduplicated, routed for approval or denied) report a short outcome label
+//! through [`log_op_outcome!`](crate::log_op_outcome). Counts are process-wide
+//! and cumulative, so sampling them at intervals gives rates over time.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+static COUNTS: Mutex<BTreeMap<String, BTreeMap<String, u64>>> = Mutex::new(BTreeMap::new());
+
+/// Count one `outcome` of `op`
+///
+/// Used by [`log_op_outcome!`](crate::log_op_outcome); call directly only
+/// when logging the outcome yourself.
+pub fn record_op_outcome(op: &str, outcome: &str) {
+    let mut counts = COUNTS.lock().unwrap_or_else(|e| e.into_inner());
+    *counts
+        .entry(op.to_string())
+        .or_default()
+        .entry(outcome.to_string())
+        .or_default() += 1;
+}
+
+/// Outcome counts since start (or the last reset), keyed by op then outcome
+pub fn op_outcome_counts() -> BTreeMap<String, BTreeMap<String, u64>> {
+    COUNTS.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Discard all recorded outcome counts
+pub fn reset_op_outcome_counts() {
+    COUNTS.lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outcomes_counted_per_op() {
+        record_op_outcome("op_outcome_test", "committed");
+        record_op_outcome("op_outcome_test", "committed");
+        record_op_outcome("op_outcome_test", "routed");
+
+        let counts = &op_outcome_counts()["op_outcome_test"];
+        assert_eq!(counts["committed"], 2);
+        assert_eq!(counts["routed"], 1);
+    }
+}
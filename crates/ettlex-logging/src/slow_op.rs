@@ -0,0 +1,169 @@
+//! Slow-operation thresholds and per-op latency aggregation
+//!
+//! Thresholds are process-wide and disabled by default. Every observation
+//! made through [`log_op_slow!`](crate::log_op_slow) is also folded into a
+//! bounded per-op sample window, from which latency percentiles are read.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Mutex, RwLock};
+
+/// Number of most recent samples kept per op for percentile estimates
+pub const LATENCY_WINDOW: usize = 1024;
+
+/// Slow-operation warning thresholds
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SlowOpThresholds {
+    /// Threshold applied to every op without an override (`None` disables)
+    pub default_ms: Option<u64>,
+    /// Per-op overrides, keyed by op name
+    pub per_op_ms: BTreeMap<String, u64>,
+}
+
+impl SlowOpThresholds {
+    /// Threshold for `op`, if any
+    pub fn threshold_for(&self, op: &str) -> Option<u64> {
+        self.per_op_ms.get(op).copied().or(self.default_ms)
+    }
+}
+
+/// Latency summary for one op over the sample window
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OpLatencyStats {
+    /// Total observations since start (or the last reset)
+    pub count: u64,
+    /// Observations over the op's slow threshold
+    pub slow_count: u64,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+    pub p99_ms: u64,
+    /// Largest observation since start (or the last reset)
+    pub max_ms: u64,
+}
+
+#[derive(Default)]
+struct OpSamples {
+    count: u64,
+    slow_count: u64,
+    max_ms: u64,
+    window: VecDeque<u64>,
+}
+
+static THRESHOLDS: RwLock<SlowOpThresholds> = RwLock::new(SlowOpThresholds {
+    default_ms: None,
+    per_op_ms: BTreeMap::new(),
+});
+static SAMPLES: Mutex<BTreeMap<String, OpSamples>> = Mutex::new(BTreeMap::new());
+
+/// Replace the process-wide slow-op thresholds
+pub fn set_slow_op_thresholds(thresholds: SlowOpThresholds) {
+    let mut guard = THRESHOLDS.write().unwrap_or_else(|e| e.into_inner());
+    *guard = thresholds;
+}
+
+/// Current process-wide slow-op thresholds
+pub fn slow_op_thresholds() -> SlowOpThresholds {
+    THRESHOLDS.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Record one observation of `op` and return its threshold if it was exceeded
+///
+/// Used by [`log_op_slow!`](crate::log_op_slow); call directly only when
+/// emitting the warning yourself.
+pub fn observe_op_latency(op: &str, duration_ms: u64) -> Option<u64> {
+    let threshold = THRESHOLDS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .threshold_for(op);
+    let slow = threshold.filter(|&t| duration_ms > t);
+
+    let mut samples = SAMPLES.lock().unwrap_or_else(|e| e.into_inner());
+    let entry = samples.entry(op.to_string()).or_default();
+    entry.count += 1;
+    entry.max_ms = entry.max_ms.max(duration_ms);
+    if slow.is_some() {
+        entry.slow_count += 1;
+    }
+    if entry.window.len() == LATENCY_WINDOW {
+        entry.window.pop_front();
+    }
+    entry.window.push_back(duration_ms);
+    slow
+}
+
+/// Latency percentiles for every op observed so far, keyed by op name
+pub fn op_latency_stats() -> BTreeMap<String, OpLatencyStats> {
+    let samples = SAMPLES.lock().unwrap_or_else(|e| e.into_inner());
+    samples
+        .iter()
+        .map(|(op, s)| {
+            let mut sorted: Vec<u64> = s.window.iter().copied().collect();
+            sorted.sort_unstable();
+            let stats = OpLatencyStats {
+                count: s.count,
+                slow_count: s.slow_count,
+                p50_ms: percentile(&sorted, 50),
+                p95_ms: percentile(&sorted, 95),
+                p99_ms: percentile(&sorted, 99),
+                max_ms: s.max_ms,
+            };
+            (op.clone(), stats)
+        })
+        .collect()
+}
+
+/// Discard all recorded latency samples
+pub fn reset_op_latency_stats() {
+    SAMPLES.lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+/// Nearest-rank percentile of an ascending slice (0 when empty)
+fn percentile(sorted: &[u64], pct: usize) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = ((pct * sorted.len() + 99) / 100).max(1);
+    sorted[rank - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile_nearest_rank() {
+        let sorted: Vec<u64> = (1..=100).collect();
+        assert_eq!(percentile(&sorted, 50), 50);
+        assert_eq!(percentile(&sorted, 95), 95);
+        assert_eq!(percentile(&sorted, 99), 99);
+        assert_eq!(percentile(&[7], 99), 7);
+        assert_eq!(percentile(&[], 50), 0);
+    }
+
+    #[test]
+    fn test_threshold_override_wins() {
+        let t = SlowOpThresholds {
+            default_ms: Some(100),
+            per_op_ms: BTreeMap::from([("snapshot_diff".to_string(), 1000)]),
+        };
+        assert_eq!(t.threshold_for("snapshot_diff"), Some(1000));
+        assert_eq!(t.threshold_for("ettle_get"), Some(100));
+        assert_eq!(SlowOpThresholds::default().threshold_for("ettle_get"), None);
+    }
+
+    #[test]
+    fn test_observe_flags_slow_and_aggregates() {
+        set_slow_op_thresholds(SlowOpThresholds {
+            default_ms: None,
+            per_op_ms: BTreeMap::from([("slow_op_test".to_string(), 5)]),
+        });
+        assert_eq!(observe_op_latency("slow_op_test", 10), Some(5));
+        assert_eq!(observe_op_latency("slow_op_test", 5), None);
+        assert_eq!(observe_op_latency("slow_op_test", 1), None);
+
+        let stats = op_latency_stats()["slow_op_test"];
+        assert_eq!(stats.count, 3);
+        assert_eq!(stats.slow_count, 1);
+        assert_eq!(stats.p50_ms, 5);
+        assert_eq!(stats.max_ms, 10);
+    }
+}
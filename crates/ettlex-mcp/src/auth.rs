@@ -1,44 +1,121 @@
 //! Auth configuration and token validation for the MCP server.
 
+use ettlex_core::errors::ExErrorKind;
+use ettlex_memory::commands::api_token::{authorize_api_token, ApiScope};
+use rusqlite::Connection;
+use serde_json::Value;
+
 use crate::error::{McpError, MCP_AUTH_REQUIRED};
+use crate::server::WRITE_TOOLS;
 
 /// Authentication configuration for the MCP server.
 ///
-/// Supports a single shared token for development/testing.
+/// Supports a single shared token for development/testing, or API tokens
+/// issued into the store (`ettlex token issue`) with per-tool scopes.
 #[derive(Debug, Clone)]
 pub struct AuthConfig {
-    /// If `None`, auth is disabled (any token accepted).
-    required_token: Option<String>,
+    mode: AuthMode,
+}
+
+#[derive(Debug, Clone)]
+enum AuthMode {
+    /// Any or no token accepted.
+    Disabled,
+    /// The one token every request must present.
+    Shared(String),
+    /// Tokens from the store's `api_tokens` table, checked per scope.
+    ApiTokens,
 }
 
 impl AuthConfig {
     /// Disable authentication (accept any or no token).
     pub fn disabled() -> Self {
         Self {
-            required_token: None,
+            mode: AuthMode::Disabled,
         }
     }
 
     /// Require the given token on every request.
     pub fn with_token(token: impl Into<String>) -> Self {
         Self {
-            required_token: Some(token.into()),
+            mode: AuthMode::Shared(token.into()),
+        }
+    }
+
+    /// Require an unrevoked API token whose scopes cover the tool called
+    /// (see [`required_scope`]).
+    pub fn api_tokens() -> Self {
+        Self {
+            mode: AuthMode::ApiTokens,
         }
     }
 
     /// Validate the provided token against this config.
     ///
     /// Returns `Ok(())` on success or `Err(McpError { AuthRequired })` on failure.
+    /// API-token configs need the store and a scope; use [`AuthConfig::authorize`].
     pub fn validate(&self, token: &Option<String>) -> Result<(), McpError> {
-        match &self.required_token {
-            None => Ok(()), // auth disabled
-            Some(required) => match token {
+        match &self.mode {
+            AuthMode::Disabled => Ok(()),
+            AuthMode::Shared(required) => match token {
                 Some(t) if t == required => Ok(()),
-                _ => Err(McpError {
-                    error_code: MCP_AUTH_REQUIRED.to_string(),
-                    message: "Authentication required: missing or invalid token".to_string(),
-                }),
+                _ => Err(auth_required()),
             },
+            AuthMode::ApiTokens => Err(auth_required()),
+        }
+    }
+
+    /// Check the token may call `tool_name` with `params`.
+    ///
+    /// Missing, unknown and revoked tokens fail with `AuthRequired`; a valid
+    /// token without the needed scope fails with `Forbidden`.
+    pub fn authorize(
+        &self,
+        token: &Option<String>,
+        tool_name: &str,
+        params: &Value,
+        conn: &Connection,
+    ) -> Result<(), McpError> {
+        match &self.mode {
+            AuthMode::ApiTokens => {
+                let scope = required_scope(tool_name, params);
+                authorize_api_token(conn, token.as_deref(), scope)
+                    .map(|_| ())
+                    .map_err(|e| match e.kind() {
+                        ExErrorKind::Unauthorised => auth_required(),
+                        _ => McpError::from_ex_error(e),
+                    })
+            }
+            _ => self.validate(token),
         }
     }
 }
+
+/// Scope a tool call needs: `approve` for approval transitions and `admin`
+/// for API token commands through `ettlex_apply`, `write` for every other
+/// write tool (see [`WRITE_TOOLS`]), `read` for all other tools.
+pub fn required_scope(tool_name: &str, params: &Value) -> ApiScope {
+    if tool_name != "ettlex_apply" {
+        return if WRITE_TOOLS.contains(&tool_name) {
+            ApiScope::Write
+        } else {
+            ApiScope::Read
+        };
+    }
+    match params
+        .get("command")
+        .and_then(|c| c.get("tag"))
+        .and_then(Value::as_str)
+    {
+        Some("ApprovalTransition") => ApiScope::Approve,
+        Some("ApiTokenIssue" | "ApiTokenRevoke") => ApiScope::Admin,
+        _ => ApiScope::Write,
+    }
+}
+
+fn auth_required() -> McpError {
+    McpError::new(
+        MCP_AUTH_REQUIRED,
+        "Authentication required: missing or invalid token",
+    )
+}
@@ -1,6 +1,8 @@
 //! MCP-layer error types and mapping from engine errors.
 
 use ettlex_core::errors::ExError;
+use ettlex_memory::commands::read_tools::StateVersionResult;
+use serde_json::{json, Value};
 
 // ---------------------------------------------------------------------------
 // MCP error codes
@@ -13,6 +15,7 @@ pub const MCP_INVALID_COMMAND: &str = "InvalidCommand";
 pub const MCP_INVALID_INPUT: &str = "InvalidInput";
 pub const MCP_REQUEST_TOO_LARGE: &str = "RequestTooLarge";
 pub const MCP_RESPONSE_TOO_LARGE: &str = "ResponseTooLarge";
+pub const MCP_WRITES_DISABLED: &str = "WritesDisabled";
 
 // ---------------------------------------------------------------------------
 // McpError
@@ -25,6 +28,14 @@ pub struct McpError {
     pub error_code: String,
     /// Human-readable message.
     pub message: String,
+    /// Transport category (`ErrorCategory::as_str`), e.g. `"not_found"`.
+    pub category: &'static str,
+    /// Engine error code (`ERR_*`); `None` for MCP-layer errors.
+    pub ex_code: Option<&'static str>,
+    /// Operation that failed, when the engine reported one.
+    pub op: Option<Box<str>>,
+    /// Entity the error is about, when the engine reported one.
+    pub entity_id: Option<Box<str>>,
 }
 
 impl McpError {
@@ -33,6 +44,10 @@ impl McpError {
         Self {
             error_code: error_code.to_string(),
             message: message.into(),
+            category: mcp_code_category(error_code),
+            ex_code: None,
+            op: None,
+            entity_id: None,
         }
     }
 
@@ -47,8 +62,46 @@ impl McpError {
         Self {
             error_code: code,
             message,
+            category: e.transport().category.as_str(),
+            ex_code: Some(e.kind().code()),
+            op: e.op().map(Box::from),
+            entity_id: e.entity_id().map(Box::from),
         }
     }
+
+    /// The error as a JSON object, for a tool result's `structuredContent`.
+    ///
+    /// Absent `ex_code`, `op` and `entity_id` are omitted.
+    pub fn to_json(&self) -> Value {
+        let mut error = json!({
+            "error_code": self.error_code,
+            "category": self.category,
+            "message": self.message,
+        });
+        for (key, value) in [
+            ("code", self.ex_code),
+            ("op", self.op.as_deref()),
+            ("entity_id", self.entity_id.as_deref()),
+        ] {
+            if let Some(v) = value {
+                error[key] = json!(v);
+            }
+        }
+        error
+    }
+}
+
+/// Transport category of an MCP-layer error code, in the same vocabulary
+/// as engine errors.
+fn mcp_code_category(code: &str) -> &'static str {
+    match code {
+        MCP_AUTH_REQUIRED => "unauthenticated",
+        MCP_TOOL_NOT_FOUND => "not_found",
+        MCP_REQUEST_TOO_LARGE | MCP_RESPONSE_TOO_LARGE => "resource_exhausted",
+        MCP_WRITES_DISABLED => "permission_denied",
+        "Internal" => "internal",
+        _ => "invalid_argument",
+    }
 }
 
 /// Convert `"ERR_SOME_CODE"` → `"SomeCode"`.
@@ -84,4 +137,7 @@ pub struct McpResponse {
     pub correlation_id: Option<String>,
     /// The result — success payload or error.
     pub result: McpResult,
+    /// State version and head digest observed by a successful read tool;
+    /// `None` for `ettlex_apply` and for errors.
+    pub state: Option<StateVersionResult>,
 }
@@ -0,0 +1,63 @@
+//! Field masks for sparse tool responses.
+//!
+//! Heavyweight read tools accept an optional `fields` param listing the
+//! top-level response fields to return, e.g. `["id", "title"]` to skip the
+//! content bodies in a listing view. The entity's identifier is always
+//! returned. Omitting `fields` returns everything.
+
+use std::collections::BTreeSet;
+
+use serde_json::Value;
+
+use crate::error::{McpError, MCP_INVALID_INPUT};
+
+/// Parse the `fields` param against the fields a tool can return.
+///
+/// Returns `None` when the param is absent or null. Fails with
+/// `InvalidInput` if it is not an array of strings or names an unknown field.
+pub fn parse_field_mask(
+    params: &Value,
+    known: &[&str],
+) -> Result<Option<BTreeSet<String>>, McpError> {
+    let items = match params.get("fields") {
+        None | Some(Value::Null) => return Ok(None),
+        Some(Value::Array(items)) => items,
+        Some(_) => {
+            return Err(McpError::new(
+                MCP_INVALID_INPUT,
+                "fields must be an array of field names",
+            ))
+        }
+    };
+    let mut mask = BTreeSet::new();
+    for item in items {
+        let name = item.as_str().ok_or_else(|| {
+            McpError::new(MCP_INVALID_INPUT, "fields must be an array of field names")
+        })?;
+        if !known.contains(&name) {
+            return Err(McpError::new(
+                MCP_INVALID_INPUT,
+                format!(
+                    "unknown field '{}'; known fields: {}",
+                    name,
+                    known.join(", ")
+                ),
+            ));
+        }
+        mask.insert(name.to_string());
+    }
+    Ok(Some(mask))
+}
+
+/// Drop the top-level keys of `value` that are neither in `mask` nor in
+/// `always`. A `None` mask, or a non-object value, is returned unchanged.
+pub fn apply_field_mask(value: Value, mask: Option<&BTreeSet<String>>, always: &[&str]) -> Value {
+    match (value, mask) {
+        (Value::Object(map), Some(mask)) => Value::Object(
+            map.into_iter()
+                .filter(|(k, _)| mask.contains(k) || always.contains(&k.as_str()))
+                .collect(),
+        ),
+        (value, _) => value,
+    }
+}
@@ -8,5 +8,6 @@ pub mod auth;
 pub mod canonical;
 pub mod context;
 pub mod error;
+pub mod fields;
 pub mod server;
 pub mod tools;
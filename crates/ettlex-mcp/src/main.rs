@@ -6,10 +6,39 @@
 //! ## Usage
 //!
 //! ```text
-//! ettlex-mcp --db /path/to/repo.db [--cas /path/to/cas]
-//! ETTLEX_DB=/path/to/repo.db ettlex-mcp
+//! ettlex-mcp --db /path/to/repo.db [--cas /path/to/cas] [--env prod] [--verify-cas] [--auth tokens]
+//! ETTLEX_DB=/path/to/repo.db ETTLEX_ENV=prod ETTLEX_VERIFY_CAS=1 ettlex-mcp
 //! ```
 //!
+//! `--verify-cas` re-hashes every CAS blob on read, so corrupt manifests and
+//! approval payloads fail with `ERR_INVARIANT_VIOLATION` instead of being
+//! served.
+//!
+//! `ETTLEX_DB_KEY` opens the database with SQLCipher (requires a build with
+//! the `ettlex-store/sqlcipher` feature). `ETTLEX_CAS_KEY` (64 hex chars,
+//! key ID from `ETTLEX_CAS_KEY_ID`, default `default`) encrypts CAS blobs at
+//! rest.
+//!
+//! `ETTLEX_FEATURES` (e.g. `three_way_diff,-declarative_apply`) overrides
+//! the repository's experimental feature flags.
+//!
+//! `--auth tokens` (or `ETTLEX_AUTH=tokens`) requires an API token issued
+//! with `ettlex token issue` on every tool call: `read` for read tools,
+//! `write` for the write tools, `approve` for `ApprovalTransition` commands
+//! (`admin` grants all). The token is taken from the call's
+//! `_meta.auth_token`, falling back to `ETTLEX_TOKEN`.
+//!
+//! `--read-only` (or `ETTLEX_READ_ONLY=1`) refuses the write tools
+//! (`ettlex_apply`, `snapshot_commit`, `decision_create`) with
+//! `WritesDisabled`, whatever the token's scopes.
+//!
+//! Failed tool calls set `isError` and carry the error in
+//! `structuredContent.error`: `error_code`, the transport `category`, the
+//! engine `code` (`ERR_*`) and, when known, `op` and `entity_id`.
+//!
+//! `ETTLEX_CURSOR_SIGNING_KEY`, when set, signs pagination cursors; unsigned
+//! or tampered cursors are then rejected.
+//!
 //! ## Claude Desktop configuration (`claude_desktop_config.json`)
 //!
 //! ```json
@@ -31,6 +60,7 @@ use ettlex_core::policy_provider::NoopPolicyProvider;
 use ettlex_mcp::auth::AuthConfig;
 use ettlex_mcp::context::RequestContext;
 use ettlex_mcp::server::{McpResult, McpServer, McpToolCall};
+use ettlex_memory::feature_flags;
 use ettlex_store::cas::FsStore;
 use ettlex_store::migrations::apply_migrations;
 use rusqlite::Connection;
@@ -46,7 +76,8 @@ fn main() {
     let cas_path = resolve_cas_path(&args, &db_path);
 
     // Open DB and apply migrations
-    let mut conn = match Connection::open(&db_path) {
+    let opened = ettlex_store::env_keys::open_db(&db_path).map_err(|e| e.to_string());
+    let mut conn = match opened {
         Ok(c) => c,
         Err(e) => {
             let _ = writeln!(
@@ -63,8 +94,41 @@ fn main() {
         std::process::exit(1);
     }
 
-    let cas = FsStore::new(cas_path);
-    let server = McpServer::new(AuthConfig::disabled(), 1024 * 1024);
+    if let Some(spec) = std::env::var("ETTLEX_FEATURES")
+        .ok()
+        .filter(|s| !s.is_empty())
+    {
+        match feature_flags::parse_feature_overrides(&spec) {
+            Ok(overrides) => feature_flags::set_feature_overrides(overrides),
+            Err(e) => {
+                let _ = writeln!(io::stderr(), "ettlex-mcp: invalid ETTLEX_FEATURES: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let cas = match ettlex_store::env_keys::open_cas(cas_path) {
+        Ok(cas) => cas.with_verify_reads(resolve_verify_cas(&args)),
+        Err(e) => {
+            let _ = writeln!(io::stderr(), "ettlex-mcp: invalid ETTLEX_CAS_KEY: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let auth = match resolve_auth(&args) {
+        Ok(auth) => auth,
+        Err(mode) => {
+            let _ = writeln!(
+                io::stderr(),
+                "ettlex-mcp: unknown auth mode {:?} (expected none or tokens)",
+                mode
+            );
+            std::process::exit(1);
+        }
+    };
+    let server = McpServer::new(auth, 1024 * 1024)
+        .with_environment(resolve_environment(&args))
+        .with_read_only(resolve_read_only(&args))
+        .with_cursor_signing_key(resolve_cursor_signing_key());
 
     // MCP stdio loop
     let stdin = io::stdin();
@@ -179,22 +243,89 @@ fn handle_tools_list() -> Value {
     let tools = vec![
         tool_def(
             "ettlex_apply",
-            "Apply a write command (EttleCreate, EttleUpdate, EttleTombstone, SnapshotCommit, RelationCreate, RelationUpdate, RelationTombstone, GroupCreate, GroupTombstone, GroupMemberAdd, GroupMemberRemove, ProfileCreate, ProfileSetDefault, PolicyCreate).",
+            "Apply a write command (EttleCreate, EttleUpdate, EttleTombstone, EttleArchive, EttleUnarchive, EttleSetContentFormat, EttleAssign, SnapshotCommit, RelationCreate, RelationUpdate, RelationTombstone, GroupCreate, GroupTombstone, GroupMemberAdd, GroupMemberRemove, ProfileCreate, ProfileSetDefault, ApprovalTransition, PolicyCreate, DecisionCreate, CommentAdd, CommentResolve, CommentReopen, RootRegister, RootUpdate, or Batch with a `commands` array applied all-or-nothing).",
             json!({
                 "type": "object",
                 "required": ["command"],
                 "properties": {
                     "command": {
                         "type": "object",
-                        "description": "Tagged command object. Required field: tag. Tags: EttleCreate {title, why?, what?, how?, reasoning_link_id?, reasoning_link_type?}, EttleUpdate {ettle_id, title?, why?, what?, how?, reasoning_link_id?, reasoning_link_type?}, EttleTombstone {ettle_id}, SnapshotCommit {leaf_ep_id, policy_ref?}, RelationCreate {relation_type, source_ettle_id, target_ettle_id, properties_json?}, RelationUpdate {relation_id, properties_json}, RelationTombstone {relation_id}, GroupCreate {name}, GroupTombstone {group_id}, GroupMemberAdd {group_id, ettle_id}, GroupMemberRemove {group_id, ettle_id}, ProfileCreate {profile_ref, payload_json}, ProfileSetDefault {profile_ref}, PolicyCreate {policy_ref, text}."
+                        "description": "Tagged command object. Required field: tag. Tags: EttleCreate {title, why?, what?, how?, reasoning_link_id?, reasoning_link_type?}, EttleUpdate {ettle_id, title?, why?, what?, how?, reasoning_link_id?, reasoning_link_type?}, EttleTombstone {ettle_id}, EttleArchive {ettle_id, subtree?}, EttleUnarchive {ettle_id, subtree?}, EttleSetContentFormat {ettle_id, content_format}, EttleAssign {ettle_id, owner?, reviewers?}, SnapshotCommit {leaf_ep_id, policy_ref?, id_scheme?, message?, annotations?}, RelationCreate {relation_type, source_ettle_id, target_ettle_id, properties_json?}, RelationUpdate {relation_id, properties_json}, RelationTombstone {relation_id}, GroupCreate {name}, GroupTombstone {group_id}, GroupMemberAdd {group_id, ettle_id}, GroupMemberRemove {group_id, ettle_id}, ProfileCreate {profile_ref, payload_json}, ProfileSetDefault {profile_ref, environment?}, ApprovalTransition {approval_token, status, actor?}, PolicyCreate {policy_ref, text}, DecisionCreate {title, decision_text, rationale, status?, alternatives_text?, consequences_text?, evidence_kind?, evidence_excerpt?, evidence_capture_content?, evidence_file_path?}, CommentAdd {target_kind, target_id, body, author?, parent_comment_id?}, CommentResolve {comment_id}, CommentReopen {comment_id}, RootRegister {ettle_id, display_name, owner?}, RootUpdate {ettle_id, display_name?, owner?, status?}."
                     },
                     "expected_state_version": {
                         "type": "integer",
                         "description": "Optional OCC guard. Returns HeadMismatch if current version differs."
+                    },
+                    "dry_run": {
+                        "type": "boolean",
+                        "description": "Validate and execute the command, then roll back. Returns the would-be result with placeholder IDs and dry_run: true."
                     }
                 }
             }),
         ),
+        tool_def(
+            "snapshot_commit",
+            "Commit a snapshot of the EPT for a leaf Ettle (write). Same as ettlex_apply with a SnapshotCommit command.",
+            json!({
+                "type": "object",
+                "required": ["leaf_ep_id"],
+                "properties": {
+                    "leaf_ep_id": { "type": "string", "description": "Leaf Ettle ID" },
+                    "policy_ref": { "type": "string", "description": "Policy ref (default: the provider's default policy)" },
+                    "profile_ref": { "type": "string", "description": "Profile ref (default: the default profile)" },
+                    "expected_head": { "type": "string", "description": "Fail with HeadMismatch unless this is the current head manifest digest" },
+                    "id_scheme": { "type": "string", "enum": ["uuid", "root-counter", "digest-prefix"] },
+                    "message": { "type": "string" },
+                    "annotations": { "type": "object", "additionalProperties": { "type": "string" } },
+                    "expected_state_version": { "type": "integer", "description": "Optional OCC guard" },
+                    "dry_run": { "type": "boolean", "description": "Compute the manifest without committing" }
+                }
+            }),
+        ),
+        tool_def(
+            "decision_create",
+            "Record a new decision (write). Same as ettlex_apply with a DecisionCreate command.",
+            json!({
+                "type": "object",
+                "required": ["title", "decision_text", "rationale"],
+                "properties": {
+                    "title": { "type": "string" },
+                    "decision_text": { "type": "string" },
+                    "rationale": { "type": "string" },
+                    "status": { "type": "string", "description": "Default proposed" },
+                    "alternatives_text": { "type": "string" },
+                    "consequences_text": { "type": "string" },
+                    "evidence_kind": { "type": "string", "enum": ["none", "excerpt", "capture", "file"] },
+                    "evidence_excerpt": { "type": "string" },
+                    "evidence_capture_content": { "type": "string" },
+                    "evidence_file_path": { "type": "string" },
+                    "expected_state_version": { "type": "integer", "description": "Optional OCC guard" },
+                    "dry_run": { "type": "boolean", "description": "Validate and roll back" }
+                }
+            }),
+        ),
+        tool_def(
+            "ettle_list_constraints",
+            "List the constraints in force for an Ettle: attached directly or inherited along its refinement path, plus excluded attachments with the reason.",
+            json!({
+                "type": "object",
+                "required": ["ettle_id"],
+                "properties": {
+                    "ettle_id": { "type": "string", "description": "Ettle ID or slug" }
+                }
+            }),
+        ),
+        tool_def(
+            "ept_compute",
+            "Compute the EPT (refinement path root → leaf) for a leaf Ettle, with any ambiguous parents, broken links or cycles found on the way.",
+            json!({
+                "type": "object",
+                "required": ["leaf_ettle_id"],
+                "properties": {
+                    "leaf_ettle_id": { "type": "string", "description": "Leaf Ettle ID" }
+                }
+            }),
+        ),
         tool_def(
             "ettle_get",
             "Get a single ettle by ID.",
@@ -202,7 +333,8 @@ fn handle_tools_list() -> Value {
                 "type": "object",
                 "required": ["ettle_id"],
                 "properties": {
-                    "ettle_id": { "type": "string", "description": "Ettle ID (ettle:...)" }
+                    "ettle_id": { "type": "string", "description": "Ettle ID (ettle:...)" },
+                    "fields": { "type": "array", "items": { "type": "string" }, "description": "Return only these fields (id is always included), e.g. [\"title\"] to skip content bodies" }
                 }
             }),
         ),
@@ -214,7 +346,8 @@ fn handle_tools_list() -> Value {
                 "properties": {
                     "limit": { "type": "integer", "description": "Max results 1–500 (default 100)" },
                     "cursor": { "type": "string", "description": "Opaque pagination cursor" },
-                    "include_tombstoned": { "type": "boolean", "description": "If true, include tombstoned ettles (default false)" }
+                    "include_tombstoned": { "type": "boolean", "description": "If true, include tombstoned ettles (default false)" },
+                    "include_archived": { "type": "boolean", "description": "If true, include archived ettles (default false)" }
                 }
             }),
         ),
@@ -235,6 +368,8 @@ fn handle_tools_list() -> Value {
             json!({
                 "type": "object",
                 "properties": {
+                    "ettle_id": { "type": "string", "description": "Only snapshots of this root ettle" },
+                    "registered_only": { "type": "boolean", "description": "Skip roots that are not active in the root registry (default false)" },
                     "limit": { "type": "integer" },
                     "cursor": { "type": "string" }
                 }
@@ -258,19 +393,41 @@ fn handle_tools_list() -> Value {
                 "type": "object",
                 "required": ["snapshot_id"],
                 "properties": {
-                    "snapshot_id": { "type": "string" }
+                    "snapshot_id": { "type": "string" },
+                    "fields": { "type": "array", "items": { "type": "string" }, "description": "Return only these fields: manifest_digest, manifest_bytes (snapshot_id is always included)" }
                 }
             }),
         ),
         tool_def(
             "snapshot_diff",
-            "Compute a structured diff between two snapshots.",
+            "Compute a structured diff between two snapshots, tagging each change with a severity.",
             json!({
                 "type": "object",
                 "required": ["a_snapshot_id", "b_snapshot_id"],
                 "properties": {
                     "a_snapshot_id": { "type": "string" },
-                    "b_snapshot_id": { "type": "string" }
+                    "b_snapshot_id": { "type": "string" },
+                    "severity_rules": {
+                        "type": "object",
+                        "description": "Category → severity overrides, e.g. {\"constraint_ref_removed\": \"Semantic\"}"
+                    },
+                    "ignore": {
+                        "type": "object",
+                        "description": "Changes to suppress: {\"categories\": [...], \"fields\": [...]}. Defaults to the diff_ignore key of snapshot B's profile"
+                    },
+                    "summary_verbosity": {
+                        "type": "string",
+                        "enum": ["one-line", "summary", "detailed"],
+                        "description": "Detail of human_summary (default: detailed)"
+                    },
+                    "message_catalog": {
+                        "type": "object",
+                        "description": "Message catalog for human_summary: {\"locale\": \"de\", \"messages\": {...}}; missing messages fall back to English"
+                    },
+                    "include_content": {
+                        "type": "boolean",
+                        "description": "Line-diff the why/what/how content of each changed EP, loaded from CAS (default: false)"
+                    }
                 }
             }),
         ),
@@ -358,10 +515,27 @@ fn handle_tools_list() -> Value {
                     "candidates": {
                         "type": "array",
                         "items": { "type": "string" }
+                    },
+                    "ambiguity_policy": {
+                        "type": "string",
+                        "enum": ["fail_fast", "choose_deterministic", "route_for_approval"],
+                        "description": "Simulate under this policy instead of the profile's"
                     }
                 }
             }),
         ),
+        tool_def(
+            "ettle_list_by_owner",
+            "List the ettles a person owns, optionally including those they review.",
+            json!({
+                "type": "object",
+                "required": ["owner"],
+                "properties": {
+                    "owner": { "type": "string", "description": "Principal, as given to EttleAssign" },
+                    "include_reviewing": { "type": "boolean", "description": "Also list ettles where owner is a reviewer (default false)" }
+                }
+            }),
+        ),
         // ── Ettle decisions ──────────────────────────────────────────────
         tool_def(
             "ettle_list_decisions",
@@ -393,6 +567,21 @@ fn handle_tools_list() -> Value {
             json!({
                 "type": "object",
                 "properties": {
+                    "limit": { "type": "integer", "description": "Max results (default 100)" },
+                    "cursor": { "type": "string", "description": "Opaque pagination cursor" },
+                    "fields": { "type": "array", "items": { "type": "string" }, "description": "Per-item fields to return: title, status, decision_text, tombstoned_at (decision_id is always included)" }
+                }
+            }),
+        ),
+        tool_def(
+            "decision_search",
+            "Search decisions by text across title, decision text and rationale (case-insensitive).",
+            json!({
+                "type": "object",
+                "required": ["text"],
+                "properties": {
+                    "text": { "type": "string" },
+                    "status": { "type": "string", "description": "Only decisions with this status" },
                     "limit": { "type": "integer", "description": "Max results (default 100)" },
                     "cursor": { "type": "string", "description": "Opaque pagination cursor" }
                 }
@@ -425,7 +614,8 @@ fn handle_tools_list() -> Value {
                 "type": "object",
                 "required": ["manifest_digest"],
                 "properties": {
-                    "manifest_digest": { "type": "string", "description": "SHA-256 hex digest of the manifest" }
+                    "manifest_digest": { "type": "string", "description": "SHA-256 hex digest of the manifest" },
+                    "fields": { "type": "array", "items": { "type": "string" }, "description": "Return only these fields: snapshot_id, semantic_manifest_digest, manifest (manifest_digest is always included)" }
                 }
             }),
         ),
@@ -445,11 +635,12 @@ fn handle_tools_list() -> Value {
         // ── Profile resolve ───────────────────────────────────────────────
         tool_def(
             "profile_resolve",
-            "Resolve a profile by reference, or resolve the default profile if no reference is given.",
+            "Resolve a profile by reference, or resolve the default profile if no reference is given. The default is the environment's default when one is set, else the global default.",
             json!({
                 "type": "object",
                 "properties": {
-                    "profile_ref": { "type": ["string", "null"], "description": "Profile reference, or null for default" }
+                    "profile_ref": { "type": ["string", "null"], "description": "Profile reference, or null for default" },
+                    "environment": { "type": "string", "description": "Environment whose default to use (default: the server's --env / ETTLEX_ENV)" }
                 }
             }),
         ),
@@ -465,6 +656,17 @@ fn handle_tools_list() -> Value {
                 }
             }),
         ),
+        tool_def(
+            "approval_history",
+            "List the lifecycle events (created, approved, rejected, expired) of an approval request, oldest first.",
+            json!({
+                "type": "object",
+                "required": ["approval_token"],
+                "properties": {
+                    "approval_token": { "type": "string" }
+                }
+            }),
+        ),
         // ── Relation (read) ───────────────────────────────────────────────
         tool_def(
             "relation_get",
@@ -515,6 +717,53 @@ fn handle_tools_list() -> Value {
                 }
             }),
         ),
+        tool_def(
+            "comment_get",
+            "Get a single comment by ID.",
+            json!({
+                "type": "object",
+                "required": ["comment_id"],
+                "properties": {
+                    "comment_id": { "type": "string", "description": "Comment ID (cmt:...)" }
+                }
+            }),
+        ),
+        tool_def(
+            "comment_list",
+            "List comments on an ettle, decision or snapshot, oldest first. Comments in resolved threads are hidden unless include_resolved is true.",
+            json!({
+                "type": "object",
+                "required": ["target_kind", "target_id"],
+                "properties": {
+                    "target_kind": { "type": "string", "enum": ["ettle", "decision", "snapshot"] },
+                    "target_id": { "type": "string" },
+                    "include_resolved": { "type": "boolean", "description": "Include resolved threads (default false)" },
+                    "limit": { "type": "integer", "description": "Max results (default 100)" },
+                    "cursor": { "type": "string", "description": "Opaque pagination cursor" }
+                }
+            }),
+        ),
+        tool_def(
+            "root_get",
+            "Get a registered root with its head snapshot. Fails with NotFound for unregistered ettles.",
+            json!({
+                "type": "object",
+                "required": ["ettle_id"],
+                "properties": {
+                    "ettle_id": { "type": "string", "description": "Root ettle ID or slug" }
+                }
+            }),
+        ),
+        tool_def(
+            "root_list",
+            "List registered roots ordered by display name, plus the implicit roots (parentless, non-constraint ettles) missing from the registry.",
+            json!({
+                "type": "object",
+                "properties": {
+                    "include_retired": { "type": "boolean", "description": "Include retired roots (default false)" }
+                }
+            }),
+        ),
         tool_def(
             "group_member_list",
             "List group memberships filtered by group and/or ettle. At least one of group_id or ettle_id must be supplied.",
@@ -562,12 +811,20 @@ fn handle_tools_call(
         .cloned()
         .unwrap_or(Value::Object(Default::default()));
 
+    // Per-call token in `_meta`, else the process-wide ETTLEX_TOKEN
+    let auth_token = params
+        .get("_meta")
+        .and_then(|m| m.get("auth_token"))
+        .and_then(|t| t.as_str())
+        .map(str::to_string)
+        .or_else(|| std::env::var("ETTLEX_TOKEN").ok().filter(|t| !t.is_empty()));
+
     let payload_size = arguments.to_string().len();
     let call = McpToolCall {
         tool_name,
         params: arguments,
         context: RequestContext::default(),
-        auth_token: None,
+        auth_token,
         payload_size,
     };
 
@@ -576,12 +833,25 @@ fn handle_tools_call(
     match response.result {
         McpResult::Ok(value) => {
             let text = serde_json::to_string(&value).unwrap_or_default();
-            json!({
+            let mut result = json!({
                 "content": [{ "type": "text", "text": text }],
                 "isError": false
-            })
+            });
+            // Read tools carry the state they observed so clients can detect
+            // changes between calls
+            if let Some(state) = response.state {
+                result["_meta"] = json!({
+                    "state_version": state.state_version,
+                    "semantic_head_digest": state.semantic_head_digest,
+                });
+            }
+            result
+        }
+        McpResult::Err(err) => {
+            let mut result = tool_error(format!("{}: {}", err.error_code, err.message));
+            result["structuredContent"] = json!({ "error": err.to_json() });
+            result
         }
-        McpResult::Err(err) => tool_error(format!("{}: {}", err.error_code, err.message)),
     }
 }
 
@@ -611,6 +881,49 @@ fn resolve_db_path(args: &[String]) -> PathBuf {
     PathBuf::from("ettlex.db")
 }
 
+fn resolve_environment(args: &[String]) -> Option<String> {
+    // --env <name> takes priority
+    if let Some(pos) = args.iter().position(|a| a == "--env") {
+        if let Some(e) = args.get(pos + 1) {
+            return Some(e.clone());
+        }
+    }
+    // ETTLEX_ENV env var; unset means the global default profile applies
+    std::env::var("ETTLEX_ENV").ok().filter(|e| !e.is_empty())
+}
+
+/// `--auth <mode>` or `ETTLEX_AUTH`: `none` (default) or `tokens`.
+fn resolve_auth(args: &[String]) -> Result<AuthConfig, String> {
+    let mode = args
+        .iter()
+        .position(|a| a == "--auth")
+        .and_then(|pos| args.get(pos + 1).cloned())
+        .or_else(|| std::env::var("ETTLEX_AUTH").ok().filter(|m| !m.is_empty()));
+    match mode.as_deref() {
+        None | Some("none") => Ok(AuthConfig::disabled()),
+        Some("tokens") => Ok(AuthConfig::api_tokens()),
+        Some(other) => Err(other.to_string()),
+    }
+}
+
+fn resolve_read_only(args: &[String]) -> bool {
+    args.iter().any(|a| a == "--read-only")
+        || std::env::var("ETTLEX_READ_ONLY").is_ok_and(|v| v == "1" || v == "true")
+}
+
+fn resolve_verify_cas(args: &[String]) -> bool {
+    args.iter().any(|a| a == "--verify-cas")
+        || std::env::var("ETTLEX_VERIFY_CAS").is_ok_and(|v| v == "1" || v == "true")
+}
+
+#[allow(clippy::result_large_err)]
+fn resolve_cursor_signing_key() -> Option<Vec<u8>> {
+    std::env::var("ETTLEX_CURSOR_SIGNING_KEY")
+        .ok()
+        .filter(|k| !k.is_empty())
+        .map(String::into_bytes)
+}
+
 fn resolve_cas_path(args: &[String], db_path: &std::path::Path) -> PathBuf {
     // --cas <path> takes priority
     if let Some(pos) = args.iter().position(|a| a == "--cas") {
@@ -2,19 +2,26 @@
 
 use ettlex_core::approval_router::ApprovalRouter;
 use ettlex_core::policy_provider::PolicyProvider;
+use ettlex_memory::commands::engine_query::{read_state_version, with_read_savepoint};
+use ettlex_memory::commands::read_tools::StateVersionResult;
 use ettlex_store::cas::FsStore;
+use ettlex_store::repo::CursorCodec;
 use rusqlite::Connection;
 use serde_json::Value;
 
 use crate::auth::AuthConfig;
 use crate::context::RequestContext;
-use crate::error::{McpError, MCP_AUTH_REQUIRED, MCP_REQUEST_TOO_LARGE, MCP_TOOL_NOT_FOUND};
+use crate::error::{McpError, MCP_REQUEST_TOO_LARGE, MCP_TOOL_NOT_FOUND, MCP_WRITES_DISABLED};
 pub use crate::error::{McpResponse, McpResult};
 use crate::tools::{
-    apply, approval, constraint, decision, ettle, group, policy, predicate, profile, relation,
-    snapshot, state,
+    apply, approval, comment, constraint, decision, ettle, group, policy, predicate, profile,
+    relation, root, snapshot, state,
 };
 
+/// Tools that change the store. They need the `write` scope (`approve` for
+/// approval transitions) and are refused on a read-only server.
+pub const WRITE_TOOLS: &[&str] = &["ettlex_apply", "snapshot_commit", "decision_create"];
+
 // ---------------------------------------------------------------------------
 // Public types
 // ---------------------------------------------------------------------------
@@ -44,6 +51,9 @@ pub struct McpToolCall {
 pub struct McpServer {
     auth: AuthConfig,
     max_request_bytes: usize,
+    environment: Option<String>,
+    read_only: bool,
+    cursor_codec: CursorCodec,
 }
 
 impl McpServer {
@@ -52,9 +62,33 @@ impl McpServer {
         Self {
             auth,
             max_request_bytes,
+            environment: None,
+            read_only: false,
+            cursor_codec: CursorCodec::unsigned(),
         }
     }
 
+    /// Refuse every write tool with `WritesDisabled`, e.g. for an agent that
+    /// should only inspect the architecture.
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Sign list cursors with `key` and reject unsigned or tampered ones;
+    /// `None` leaves cursors unsigned.
+    pub fn with_cursor_signing_key(mut self, key: Option<Vec<u8>>) -> Self {
+        self.cursor_codec = CursorCodec::with_key(key);
+        self
+    }
+
+    /// Set the active environment (e.g. `prod`). `profile_resolve` calls
+    /// without an explicit `environment` resolve that environment's default.
+    pub fn with_environment(mut self, environment: Option<String>) -> Self {
+        self.environment = environment;
+        self
+    }
+
     /// Dispatch a tool call, returning a response with the correlation_id echoed.
     pub fn dispatch(
         &self,
@@ -66,13 +100,16 @@ impl McpServer {
     ) -> McpResponse {
         let correlation_id = call.context.correlation_id.clone();
 
-        let result = self.dispatch_inner(call, conn, cas, policy_provider, approval_router);
+        let (result, state) =
+            self.dispatch_inner(call, conn, cas, policy_provider, approval_router);
         McpResponse {
             correlation_id,
             result,
+            state,
         }
     }
 
+    #[allow(clippy::result_large_err)]
     fn dispatch_inner(
         &self,
         call: McpToolCall,
@@ -80,49 +117,120 @@ impl McpServer {
         cas: &FsStore,
         policy_provider: &dyn PolicyProvider,
         approval_router: &dyn ApprovalRouter,
-    ) -> McpResult {
+    ) -> (McpResult, Option<StateVersionResult>) {
         // 1. Size guard (checked before auth to prevent DoS parse)
         if call.payload_size > self.max_request_bytes {
-            return McpResult::Err(McpError::new(
-                MCP_REQUEST_TOO_LARGE,
-                format!(
-                    "payload size {} exceeds limit {}",
-                    call.payload_size, self.max_request_bytes
-                ),
-            ));
+            return (
+                McpResult::Err(McpError::new(
+                    MCP_REQUEST_TOO_LARGE,
+                    format!(
+                        "payload size {} exceeds limit {}",
+                        call.payload_size, self.max_request_bytes
+                    ),
+                )),
+                None,
+            );
         }
 
         // 2. Auth guard
-        if let Err(e) = self.auth.validate(&call.auth_token) {
-            return McpResult::Err(McpError::new(MCP_AUTH_REQUIRED, e.message));
+        if let Err(e) = self
+            .auth
+            .authorize(&call.auth_token, &call.tool_name, &call.params, conn)
+        {
+            return (McpResult::Err(e), None);
         }
 
-        // 3. Route by tool name
+        // 3. Writes go straight to the apply handler
+        if WRITE_TOOLS.contains(&call.tool_name.as_str()) {
+            if self.read_only {
+                return (
+                    McpResult::Err(McpError::new(
+                        MCP_WRITES_DISABLED,
+                        format!(
+                            "'{}' is a write tool; the server is read-only",
+                            call.tool_name
+                        ),
+                    )),
+                    None,
+                );
+            }
+            let p = &call.params;
+            let result = match call.tool_name.as_str() {
+                "snapshot_commit" => apply::handle_command_tool(
+                    "SnapshotCommit",
+                    p,
+                    conn,
+                    cas,
+                    policy_provider,
+                    approval_router,
+                ),
+                "decision_create" => apply::handle_command_tool(
+                    "DecisionCreate",
+                    p,
+                    conn,
+                    cas,
+                    policy_provider,
+                    approval_router,
+                ),
+                _ => apply::handle_apply(p, conn, cas, policy_provider, approval_router),
+            };
+            return (result, None);
+        }
+
+        // 4. Reads run in one savepoint with the state stamp, so the stamp
+        //    matches the data the tool saw
+        let stamped = with_read_savepoint(conn, || {
+            let state = read_state_version(conn)?;
+            Ok((self.route_read(&call, conn, cas, policy_provider), state))
+        });
+        match stamped {
+            Ok((result @ McpResult::Ok(_), state)) => (result, Some(state)),
+            Ok((result, _)) => (result, None),
+            Err(e) => (McpResult::Err(McpError::from_ex_error(e)), None),
+        }
+    }
+
+    fn route_read(
+        &self,
+        call: &McpToolCall,
+        conn: &Connection,
+        cas: &FsStore,
+        policy_provider: &dyn PolicyProvider,
+    ) -> McpResult {
         let p = &call.params;
+        let codec = &self.cursor_codec;
         match call.tool_name.as_str() {
-            // ── Write ──────────────────────────────────────────────────────
-            "ettlex_apply" => apply::handle_apply(p, conn, cas, policy_provider, approval_router),
-
             // ── Relation (read) ────────────────────────────────────────────
             "relation_get" => relation::handle_relation_get_tool(p, conn, cas, policy_provider),
-            "relation_list" => relation::handle_relation_list_tool(p, conn, cas, policy_provider),
+            "relation_list" => {
+                relation::handle_relation_list_tool(p, codec, conn, cas, policy_provider)
+            }
 
             // ── Group (read) ────────────────────────────────────────────────
             "group_get" => group::handle_group_get_tool(p, conn, cas, policy_provider),
-            "group_list" => group::handle_group_list_tool(p, conn, cas, policy_provider),
+            "group_list" => group::handle_group_list_tool(p, codec, conn, cas, policy_provider),
             "group_member_list" => {
-                group::handle_group_member_list_tool(p, conn, cas, policy_provider)
+                group::handle_group_member_list_tool(p, codec, conn, cas, policy_provider)
             }
 
             // ── Ettle ──────────────────────────────────────────────────────
             "ettle_get" => ettle::handle_ettle_get(p, conn, cas, policy_provider),
-            "ettle_list" => ettle::handle_ettle_list(p, conn, cas, policy_provider),
+            "ettle_list" => ettle::handle_ettle_list(p, codec, conn, cas, policy_provider),
+            "ettle_list_by_owner" => {
+                ettle::handle_ettle_list_by_owner(p, conn, cas, policy_provider)
+            }
 
             // ── Ettle (decisions) ──────────────────────────────────────────
             "ettle_list_decisions" => {
                 ettle::handle_ettle_list_decisions(p, conn, cas, policy_provider)
             }
 
+            // ── EP / EPT ───────────────────────────────────────────────────
+            "ettle_list_constraints" => {
+                constraint::handle_ettle_list_constraints(p, conn, cas, policy_provider)
+            }
+            "ept_compute" => snapshot::handle_ept_compute(p, conn, cas, policy_provider),
+
             // ── Constraint ─────────────────────────────────────────────────
             "constraint_get" => constraint::handle_constraint_get(p, conn, cas, policy_provider),
             "constraint_list_by_family" => {
@@ -131,11 +239,22 @@ impl McpServer {
 
             // ── Decision ───────────────────────────────────────────────────
             "decision_get" => decision::handle_decision_get(p, conn, cas, policy_provider),
-            "decision_list" => decision::handle_decision_list(p, conn, cas, policy_provider),
+            "decision_list" => decision::handle_decision_list(p, codec, conn, cas, policy_provider),
+            "decision_search" => {
+                decision::handle_decision_search(p, codec, conn, cas, policy_provider)
+            }
             "decision_list_by_target" => {
                 decision::handle_decision_list_by_target(p, conn, cas, policy_provider)
             }
 
+            // ── Comment ────────────────────────────────────────────────────
+            "comment_get" => comment::handle_comment_get(p, conn, cas, policy_provider),
+            "comment_list" => comment::handle_comment_list(p, codec, conn, cas, policy_provider),
+
+            // ── Root registry ──────────────────────────────────────────────
+            "root_get" => root::handle_root_get(p, conn, cas, policy_provider),
+            "root_list" => root::handle_root_list(p, conn, cas, policy_provider),
+
             // ── State ──────────────────────────────────────────────────────
             "state_get_version" => state::handle_state_get_version(p, conn, cas, policy_provider),
 
@@ -163,15 +282,22 @@ impl McpServer {
 
             // ── Profile ────────────────────────────────────────────────────
             "profile_get" => profile::handle_profile_get(p, conn, cas, policy_provider),
-            "profile_list" => profile::handle_profile_list(p, conn, cas, policy_provider),
+            "profile_list" => profile::handle_profile_list(p, codec, conn, cas, policy_provider),
             "profile_get_default" => {
                 profile::handle_profile_get_default(p, conn, cas, policy_provider)
             }
-            "profile_resolve" => profile::handle_profile_resolve(p, conn, cas, policy_provider),
+            "profile_resolve" => profile::handle_profile_resolve(
+                p,
+                self.environment.as_deref(),
+                conn,
+                cas,
+                policy_provider,
+            ),
 
             // ── Approval ───────────────────────────────────────────────────
             "approval_get" => approval::handle_approval_get(p, conn, cas, policy_provider),
-            "approval_list" => approval::handle_approval_list(p, conn, cas, policy_provider),
+            "approval_list" => approval::handle_approval_list(p, codec, conn, cas, policy_provider),
+            "approval_history" => approval::handle_approval_history(p, conn, cas, policy_provider),
 
             // ── Predicate ──────────────────────────────────────────────────
             "constraint_predicates_preview" => {
@@ -2,7 +2,7 @@
 
 use ettlex_core::approval_router::ApprovalRouter;
 use ettlex_core::policy_provider::PolicyProvider;
-use ettlex_memory::{apply_command, Command, CommandResult};
+use ettlex_memory::{apply_command_with_options, Command, CommandOptions, CommandResult};
 use ettlex_store::cas::FsStore;
 use rusqlite::Connection;
 use serde_json::{json, Value};
@@ -11,7 +11,10 @@ use crate::error::{McpError, McpResult, MCP_INVALID_COMMAND, MCP_INVALID_INPUT};
 
 /// Handle `ettlex.apply`.
 ///
-/// Params: `{ command: {...}, expected_state_version?: u64 }`
+/// Params: `{ command: {...}, expected_state_version?: u64, dry_run?: bool }`
+///
+/// With `dry_run: true` the command is validated and executed, then rolled
+/// back; the response carries the would-be result and `"dry_run": true`.
 pub fn handle_apply(
     params: &Value,
     conn: &mut Connection,
@@ -39,6 +42,17 @@ pub fn handle_apply(
         }
     };
 
+    let dry_run = match params.get("dry_run") {
+        Some(Value::Bool(b)) => *b,
+        Some(Value::Null) | None => false,
+        _ => {
+            return McpResult::Err(McpError::new(
+                MCP_INVALID_INPUT,
+                "dry_run must be a boolean",
+            ))
+        }
+    };
+
     // Deserialize command
     let cmd_value = match params.get("command") {
         Some(v) => v,
@@ -60,9 +74,10 @@ pub fn handle_apply(
     };
 
     // Dispatch
-    match apply_command(
+    match apply_command_with_options(
         cmd,
         expected_sv,
+        CommandOptions { dry_run },
         conn,
         cas,
         policy_provider,
@@ -70,24 +85,64 @@ pub fn handle_apply(
     ) {
         Ok((result, new_sv)) => {
             let result_json = command_result_to_json(&result);
-            McpResult::Ok(json!({
+            let mut response = json!({
                 "new_state_version": new_sv,
                 "result": result_json,
-            }))
+            });
+            if dry_run {
+                response["dry_run"] = json!(true);
+            }
+            McpResult::Ok(response)
         }
         Err(e) => McpResult::Err(McpError::from_ex_error(e)),
     }
 }
 
+/// Handle a dedicated write tool (`snapshot_commit`, `decision_create`):
+/// the tool's params are the fields of the `tag` command, plus the optional
+/// `expected_state_version` and `dry_run` of `ettlex_apply`.
+pub fn handle_command_tool(
+    tag: &str,
+    params: &Value,
+    conn: &mut Connection,
+    cas: &FsStore,
+    policy_provider: &dyn PolicyProvider,
+    approval_router: &dyn ApprovalRouter,
+) -> McpResult {
+    let mut command = match params {
+        Value::Object(fields) => fields.clone(),
+        Value::Null => Default::default(),
+        _ => return McpResult::Err(McpError::new(MCP_INVALID_INPUT, "params must be an object")),
+    };
+    let expected_state_version = command
+        .remove("expected_state_version")
+        .unwrap_or(Value::Null);
+    let dry_run = command.get("dry_run").cloned().unwrap_or(Value::Null);
+    command.insert("tag".to_string(), json!(tag));
+    handle_apply(
+        &json!({
+            "command": command,
+            "expected_state_version": expected_state_version,
+            "dry_run": dry_run,
+        }),
+        conn,
+        cas,
+        policy_provider,
+        approval_router,
+    )
+}
+
 fn command_result_to_json(r: &CommandResult) -> Value {
     match r {
         CommandResult::SnapshotCommit {
             snapshot_id,
             manifest_digest,
+            constraints_summary,
         } => json!({
             "tag": "SnapshotCommit",
             "snapshot_id": snapshot_id,
             "manifest_digest": manifest_digest,
+            "constraints_summary": constraints_summary,
         }),
         CommandResult::RoutedForApproval { approval_token } => json!({
             "tag": "RoutedForApproval",
@@ -99,11 +154,34 @@ fn command_result_to_json(r: &CommandResult) -> Value {
         }),
         CommandResult::ProfileCreate => json!({ "tag": "ProfileCreate" }),
         CommandResult::ProfileSetDefault => json!({ "tag": "ProfileSetDefault" }),
+        CommandResult::ApprovalTransition {
+            event_id,
+            from_status,
+            to_status,
+        } => json!({
+            "tag": "ApprovalTransition",
+            "event_id": event_id,
+            "from_status": from_status,
+            "to_status": to_status,
+        }),
         CommandResult::PolicyCreate { policy_ref } => {
             json!({ "tag": "PolicyCreate", "policy_ref": policy_ref })
         }
         CommandResult::EttleUpdate => json!({ "tag": "EttleUpdate" }),
         CommandResult::EttleTombstone => json!({ "tag": "EttleTombstone" }),
+        CommandResult::EttleRestore => json!({ "tag": "EttleRestore" }),
+        CommandResult::EttleArchive { archived_ettle_ids } => json!({
+            "tag": "EttleArchive",
+            "archived_ettle_ids": archived_ettle_ids,
+        }),
+        CommandResult::EttleUnarchive {
+            unarchived_ettle_ids,
+        } => json!({
+            "tag": "EttleUnarchive",
+            "unarchived_ettle_ids": unarchived_ettle_ids,
+        }),
+        CommandResult::EttleSetContentFormat => json!({ "tag": "EttleSetContentFormat" }),
+        CommandResult::EttleAssign => json!({ "tag": "EttleAssign" }),
         CommandResult::RelationCreate { relation_id } => {
             json!({ "tag": "RelationCreate", "relation_id": relation_id })
         }
@@ -115,6 +193,29 @@ fn command_result_to_json(r: &CommandResult) -> Value {
             json!({ "tag": "RelationList", "items": items })
         }
         CommandResult::RelationTombstone => json!({ "tag": "RelationTombstone" }),
+        CommandResult::RelationRestore => json!({ "tag": "RelationRestore" }),
+        CommandResult::ConstraintAttachBulk {
+            relation_ids,
+            attached_ettle_ids,
+            skipped_ettle_ids,
+        } => json!({
+            "tag": "ConstraintAttachBulk",
+            "relation_ids": relation_ids,
+            "attached_ettle_ids": attached_ettle_ids,
+            "skipped_ettle_ids": skipped_ettle_ids,
+        }),
+        CommandResult::ConstraintSweepOrphans { relation_ids } => json!({
+            "tag": "ConstraintSweepOrphans",
+            "relation_ids": relation_ids,
+        }),
+        CommandResult::ContentRefRename {
+            occurrences,
+            updated_ettle_ids,
+        } => json!({
+            "tag": "ContentRefRename",
+            "occurrences": occurrences,
+            "updated_ettle_ids": updated_ettle_ids,
+        }),
         CommandResult::GroupCreate { group_id } => {
             json!({ "tag": "GroupCreate", "group_id": group_id })
         }
@@ -125,10 +226,61 @@ fn command_result_to_json(r: &CommandResult) -> Value {
             json!({ "tag": "GroupList", "items": items })
         }
         CommandResult::GroupTombstone => json!({ "tag": "GroupTombstone" }),
+        CommandResult::GroupRestore => json!({ "tag": "GroupRestore" }),
         CommandResult::GroupMemberAdd => json!({ "tag": "GroupMemberAdd" }),
         CommandResult::GroupMemberRemove => json!({ "tag": "GroupMemberRemove" }),
         CommandResult::GroupMemberList { items } => {
             json!({ "tag": "GroupMemberList", "items": items })
         }
+        CommandResult::DecisionCreate { decision_id } => {
+            json!({ "tag": "DecisionCreate", "decision_id": decision_id })
+        }
+        CommandResult::DecisionRestore => json!({ "tag": "DecisionRestore" }),
+        CommandResult::CommentAdd { comment_id } => {
+            json!({ "tag": "CommentAdd", "comment_id": comment_id })
+        }
+        CommandResult::CommentResolve => json!({ "tag": "CommentResolve" }),
+        CommandResult::CommentReopen => json!({ "tag": "CommentReopen" }),
+        CommandResult::RootRegister => json!({ "tag": "RootRegister" }),
+        CommandResult::RootUpdate => json!({ "tag": "RootUpdate" }),
+        CommandResult::ApiTokenIssue {
+            token_id,
+            scopes,
+            secret,
+        } => json!({
+            "tag": "ApiTokenIssue",
+            "token_id": token_id,
+            "scopes": scopes,
+            "secret": secret,
+        }),
+        CommandResult::ApiTokenRevoke => json!({ "tag": "ApiTokenRevoke" }),
+        CommandResult::FeatureSet => json!({ "tag": "FeatureSet" }),
+        CommandResult::ApprovalPayloadSweep {
+            swept,
+            blobs_deleted,
+            bytes_freed,
+        } => json!({
+            "tag": "ApprovalPayloadSweep",
+            "swept": swept,
+            "blobs_deleted": blobs_deleted,
+            "bytes_freed": bytes_freed,
+        }),
+        CommandResult::EvidenceSweep {
+            pruned,
+            bytes_freed,
+        } => json!({
+            "tag": "EvidenceSweep",
+            "pruned": pruned,
+            "bytes_freed": bytes_freed,
+        }),
+        CommandResult::ImportSessionRollback { relations, ettles } => json!({
+            "tag": "ImportSessionRollback",
+            "relations": relations,
+            "ettles": ettles,
+        }),
+        CommandResult::Batch { results } => json!({
+            "tag": "Batch",
+            "results": results.iter().map(command_result_to_json).collect::<Vec<_>>(),
+        }),
     }
 }
@@ -3,11 +3,12 @@
 use ettlex_core::policy_provider::PolicyProvider;
 use ettlex_memory::commands::engine_query::{apply_engine_query, EngineQuery};
 use ettlex_store::cas::FsStore;
+use ettlex_store::repo::CursorCodec;
 use rusqlite::Connection;
 use serde_json::{json, Value};
 
 use crate::error::{McpError, McpResult, MCP_INVALID_INPUT};
-use crate::tools::ettle::parse_list_opts;
+use crate::tools::ettle::parse_paged_list_opts;
 
 /// Handle `approval.get`.
 ///
@@ -42,6 +43,9 @@ pub fn handle_approval_get(
                     "request_digest": r.request_digest,
                     "semantic_request_digest": r.semantic_request_digest,
                     "payload": r.payload_json,
+                    "typed_payload": serde_json::to_value(&r.payload)
+                        .unwrap_or(serde_json::Value::Null),
+                    "assignees": r.assignees,
                 }))
             } else {
                 McpResult::Err(McpError::new("Internal", "unexpected result variant"))
@@ -56,11 +60,12 @@ pub fn handle_approval_get(
 /// Params: `{ limit?: u64, cursor?: String }`
 pub fn handle_approval_list(
     params: &Value,
+    cursor_codec: &CursorCodec,
     conn: &Connection,
     cas: &FsStore,
     policy_provider: &dyn PolicyProvider,
 ) -> McpResult {
-    let opts = match parse_list_opts(params) {
+    let opts = match parse_paged_list_opts(params, cursor_codec) {
         Ok(o) => o,
         Err(e) => return e,
     };
@@ -99,3 +104,52 @@ pub fn handle_approval_list(
         Err(e) => McpResult::Err(McpError::from_ex_error(e)),
     }
 }
+
+/// Handle `approval_history`.
+///
+/// Params: `{ approval_token: String }`
+pub fn handle_approval_history(
+    params: &Value,
+    conn: &Connection,
+    cas: &FsStore,
+    policy_provider: &dyn PolicyProvider,
+) -> McpResult {
+    let approval_token = match params.get("approval_token").and_then(Value::as_str) {
+        Some(s) => s.to_string(),
+        None => {
+            return McpResult::Err(McpError::new(
+                MCP_INVALID_INPUT,
+                "missing 'approval_token' param",
+            ))
+        }
+    };
+
+    match apply_engine_query(
+        EngineQuery::ApprovalHistory { approval_token },
+        conn,
+        cas,
+        Some(policy_provider),
+    ) {
+        Ok(result) => {
+            use ettlex_memory::commands::engine_query::EngineQueryResult;
+            if let EngineQueryResult::ApprovalHistory(events) = result {
+                let items: Vec<Value> = events
+                    .iter()
+                    .map(|e| {
+                        json!({
+                            "event_id": e.id,
+                            "from_status": e.from_status,
+                            "to_status": e.to_status,
+                            "actor": e.actor,
+                            "occurred_at_ms": e.occurred_at_ms,
+                        })
+                    })
+                    .collect();
+                McpResult::Ok(json!({ "items": items }))
+            } else {
+                McpResult::Err(McpError::new("Internal", "unexpected result variant"))
+            }
+        }
+        Err(e) => McpResult::Err(McpError::from_ex_error(e)),
+    }
+}
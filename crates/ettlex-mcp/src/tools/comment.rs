@@ -0,0 +1,118 @@
+//! Handlers for `comment.*` tool group.
+
+use ettlex_core::policy_provider::PolicyProvider;
+use ettlex_memory::commands::engine_query::{apply_engine_query, EngineQuery, EngineQueryResult};
+use ettlex_store::cas::FsStore;
+use ettlex_store::model::CommentRecord;
+use ettlex_store::repo::CursorCodec;
+use rusqlite::Connection;
+use serde_json::{json, Value};
+
+use crate::error::{McpError, McpResult, MCP_INVALID_INPUT};
+use crate::tools::ettle::parse_paged_list_opts;
+
+fn comment_to_json(c: &CommentRecord) -> Value {
+    json!({
+        "comment_id": c.id,
+        "target_kind": c.target_kind,
+        "target_id": c.target_id,
+        "parent_comment_id": c.parent_comment_id,
+        "author": c.author,
+        "body": c.body,
+        "created_at": c.created_at,
+        "resolved_at_ms": c.resolved_at_ms,
+    })
+}
+
+/// Handle `comment_get`.
+///
+/// Params: `{ comment_id: String }`
+pub fn handle_comment_get(
+    params: &Value,
+    conn: &Connection,
+    cas: &FsStore,
+    policy_provider: &dyn PolicyProvider,
+) -> McpResult {
+    let comment_id = match params.get("comment_id").and_then(Value::as_str) {
+        Some(s) => s.to_string(),
+        None => {
+            return McpResult::Err(McpError::new(
+                MCP_INVALID_INPUT,
+                "missing 'comment_id' param",
+            ))
+        }
+    };
+
+    match apply_engine_query(
+        EngineQuery::CommentGet { comment_id },
+        conn,
+        cas,
+        Some(policy_provider),
+    ) {
+        Ok(EngineQueryResult::CommentGet(c)) => McpResult::Ok(comment_to_json(&c)),
+        Ok(_) => McpResult::Err(McpError::new("Internal", "unexpected result variant")),
+        Err(e) => McpResult::Err(McpError::from_ex_error(e)),
+    }
+}
+
+/// Handle `comment_list`.
+///
+/// Params: `{ target_kind: String, target_id: String, include_resolved?: bool,
+///            limit?: u64, cursor?: String }`
+pub fn handle_comment_list(
+    params: &Value,
+    cursor_codec: &CursorCodec,
+    conn: &Connection,
+    cas: &FsStore,
+    policy_provider: &dyn PolicyProvider,
+) -> McpResult {
+    let target_kind = match params.get("target_kind").and_then(Value::as_str) {
+        Some(s) => s.to_string(),
+        None => {
+            return McpResult::Err(McpError::new(
+                MCP_INVALID_INPUT,
+                "missing 'target_kind' param",
+            ))
+        }
+    };
+    let target_id = match params.get("target_id").and_then(Value::as_str) {
+        Some(s) => s.to_string(),
+        None => {
+            return McpResult::Err(McpError::new(
+                MCP_INVALID_INPUT,
+                "missing 'target_id' param",
+            ))
+        }
+    };
+    let include_resolved = params
+        .get("include_resolved")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let options = match parse_paged_list_opts(params, cursor_codec) {
+        Ok(o) => o,
+        Err(e) => return e,
+    };
+
+    match apply_engine_query(
+        EngineQuery::CommentList {
+            target_kind,
+            target_id,
+            include_resolved,
+            options,
+        },
+        conn,
+        cas,
+        Some(policy_provider),
+    ) {
+        Ok(EngineQueryResult::CommentList(page)) => {
+            let items: Vec<Value> = page.items.iter().map(comment_to_json).collect();
+            let mut resp = json!({ "items": items });
+            if let Some(cursor) = page.cursor {
+                resp["cursor"] = Value::String(cursor);
+            }
+            McpResult::Ok(resp)
+        }
+        Ok(_) => McpResult::Err(McpError::new("Internal", "unexpected result variant")),
+        Err(e) => McpResult::Err(McpError::from_ex_error(e)),
+    }
+}
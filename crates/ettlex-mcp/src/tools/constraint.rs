@@ -1,8 +1,9 @@
-//! Handlers for `constraint.*` tool group.
+//! Handlers for `constraint.*` tool group and `ettle_list_constraints`.
 
 use ettlex_core::model::Constraint;
 use ettlex_core::policy_provider::PolicyProvider;
-use ettlex_memory::commands::engine_query::{apply_engine_query, EngineQuery};
+use ettlex_memory::commands::engine_query::{apply_engine_query, EngineQuery, EngineQueryResult};
+use ettlex_memory::commands::read_tools::ExclusionReason;
 use ettlex_store::cas::FsStore;
 use rusqlite::Connection;
 use serde_json::{json, Value};
@@ -47,7 +48,6 @@ pub fn handle_constraint_get(
         Some(policy_provider),
     ) {
         Ok(result) => {
-            use ettlex_memory::commands::engine_query::EngineQueryResult;
             if let EngineQueryResult::ConstraintGet(c) = result {
                 McpResult::Ok(constraint_to_json(&c))
             } else {
@@ -86,7 +86,6 @@ pub fn handle_constraint_list_by_family(
         Some(policy_provider),
     ) {
         Ok(result) => {
-            use ettlex_memory::commands::engine_query::EngineQueryResult;
             if let EngineQueryResult::ConstraintListByFamily(cs) = result {
                 let items: Vec<Value> = cs.iter().map(constraint_to_json).collect();
                 McpResult::Ok(json!({ "items": items }))
@@ -97,3 +96,79 @@ pub fn handle_constraint_list_by_family(
         Err(e) => McpResult::Err(McpError::from_ex_error(e)),
     }
 }
+
+/// Handle `ettle_list_constraints`.
+///
+/// Params: `{ ettle_id: String }` (Ettle ID or slug)
+///
+/// Returns the constraints in force for the Ettle — attached directly or
+/// inherited along its refinement path — and the attachments excluded by
+/// `scope` or `override`, each with the reason.
+pub fn handle_ettle_list_constraints(
+    params: &Value,
+    conn: &Connection,
+    cas: &FsStore,
+    policy_provider: &dyn PolicyProvider,
+) -> McpResult {
+    let ettle_id = match params.get("ettle_id").and_then(Value::as_str) {
+        Some(s) => s.to_string(),
+        None => {
+            return McpResult::Err(McpError::new(MCP_INVALID_INPUT, "missing 'ettle_id' param"))
+        }
+    };
+
+    let e = match apply_engine_query(
+        EngineQuery::EttleEffectiveConstraints { ettle_id },
+        conn,
+        cas,
+        Some(policy_provider),
+    ) {
+        Ok(EngineQueryResult::EttleEffectiveConstraints(e)) => e,
+        Ok(_) => return McpResult::Err(McpError::new("Internal", "unexpected result variant")),
+        Err(e) => return McpResult::Err(McpError::from_ex_error(e)),
+    };
+
+    let constraints: Vec<Value> = e
+        .constraints
+        .iter()
+        .map(|c| {
+            json!({
+                "constraint_id": c.constraint_id,
+                "constraint_title": c.constraint_title,
+                "relation_id": c.relation_id,
+                "attached_to": c.attached_to,
+                "inherited": c.inherited,
+                "family": c.family,
+                "ordinal": c.ordinal,
+                "properties": c.properties_json,
+            })
+        })
+        .collect();
+    let excluded: Vec<Value> = e
+        .excluded
+        .iter()
+        .map(|x| {
+            let (reason, by) = match &x.reason {
+                ExclusionReason::ScopedToSelf => ("scoped_to_self", None),
+                ExclusionReason::Shadowed { by_relation_id } => ("shadowed", Some(by_relation_id)),
+                ExclusionReason::Overridden { by_relation_id } => {
+                    ("overridden", Some(by_relation_id))
+                }
+            };
+            json!({
+                "constraint_id": x.constraint_id,
+                "relation_id": x.relation_id,
+                "attached_to": x.attached_to,
+                "reason": reason,
+                "by_relation_id": by,
+            })
+        })
+        .collect();
+
+    McpResult::Ok(json!({
+        "ettle_id": e.ettle_id,
+        "path": e.path,
+        "constraints": constraints,
+        "excluded": excluded,
+    }))
+}
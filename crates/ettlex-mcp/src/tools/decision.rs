@@ -4,11 +4,23 @@ use ettlex_core::model::Decision;
 use ettlex_core::policy_provider::PolicyProvider;
 use ettlex_memory::commands::engine_query::{apply_engine_query, EngineQuery};
 use ettlex_store::cas::FsStore;
+use ettlex_store::repo::CursorCodec;
 use rusqlite::Connection;
 use serde_json::{json, Value};
 
 use crate::error::{McpError, McpResult, MCP_INVALID_INPUT};
-use crate::tools::ettle::parse_list_opts;
+use crate::fields::{apply_field_mask, parse_field_mask};
+use crate::tools::ettle::parse_paged_list_opts;
+
+/// Fields each `decision_list` item can return; `decision_id` is always
+/// included.
+const DECISION_FIELDS: &[&str] = &[
+    "decision_id",
+    "title",
+    "status",
+    "decision_text",
+    "tombstoned_at",
+];
 
 fn decision_to_json(d: &Decision) -> Value {
     json!({
@@ -59,17 +71,24 @@ pub fn handle_decision_get(
 
 /// Handle `decision_list`.
 ///
-/// Params: `{ limit?: u64, cursor?: String }`
+/// Params: `{ limit?: u64, cursor?: String, fields?: [String] }`
+///
+/// With `fields`, each item carries only `decision_id` plus the named fields.
 pub fn handle_decision_list(
     params: &Value,
+    cursor_codec: &CursorCodec,
     conn: &Connection,
     cas: &FsStore,
     policy_provider: &dyn PolicyProvider,
 ) -> McpResult {
-    let opts = match parse_list_opts(params) {
+    let opts = match parse_paged_list_opts(params, cursor_codec) {
         Ok(o) => o,
         Err(e) => return e,
     };
+    let mask = match parse_field_mask(params, DECISION_FIELDS) {
+        Ok(m) => m,
+        Err(e) => return McpResult::Err(e),
+    };
 
     match apply_engine_query(
         EngineQuery::DecisionList(opts),
@@ -80,6 +99,60 @@ pub fn handle_decision_list(
         Ok(result) => {
             use ettlex_memory::commands::engine_query::EngineQueryResult;
             if let EngineQueryResult::DecisionList(page) = result {
+                let items: Vec<Value> = page
+                    .items
+                    .iter()
+                    .map(|d| apply_field_mask(decision_to_json(d), mask.as_ref(), &["decision_id"]))
+                    .collect();
+                let mut resp = json!({ "items": items });
+                if let Some(cursor) = page.cursor {
+                    resp["cursor"] = Value::String(cursor);
+                }
+                McpResult::Ok(resp)
+            } else {
+                McpResult::Err(McpError::new("Internal", "unexpected result variant"))
+            }
+        }
+        Err(e) => McpResult::Err(McpError::from_ex_error(e)),
+    }
+}
+
+/// Handle `decision_search`.
+///
+/// Params: `{ text: String, status?: String, limit?: u64, cursor?: String }`
+pub fn handle_decision_search(
+    params: &Value,
+    cursor_codec: &CursorCodec,
+    conn: &Connection,
+    cas: &FsStore,
+    policy_provider: &dyn PolicyProvider,
+) -> McpResult {
+    let text = match params.get("text").and_then(Value::as_str) {
+        Some(s) => s.to_string(),
+        None => return McpResult::Err(McpError::new(MCP_INVALID_INPUT, "missing 'text' param")),
+    };
+    let status_filter = params
+        .get("status")
+        .and_then(Value::as_str)
+        .map(String::from);
+    let options = match parse_paged_list_opts(params, cursor_codec) {
+        Ok(o) => o,
+        Err(e) => return e,
+    };
+
+    match apply_engine_query(
+        EngineQuery::DecisionSearch {
+            text,
+            status_filter,
+            options,
+        },
+        conn,
+        cas,
+        Some(policy_provider),
+    ) {
+        Ok(result) => {
+            use ettlex_memory::commands::engine_query::EngineQueryResult;
+            if let EngineQueryResult::DecisionSearch(page) = result {
                 let items: Vec<Value> = page.items.iter().map(decision_to_json).collect();
                 let mut resp = json!({ "items": items });
                 if let Some(cursor) = page.cursor {
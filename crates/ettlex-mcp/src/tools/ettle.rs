@@ -1,27 +1,50 @@
 //! Handlers for `ettle.*` tool group.
 
 use ettlex_core::policy_provider::PolicyProvider;
-use ettlex_memory::commands::engine_query::{apply_engine_query, EngineQuery};
+use ettlex_memory::commands::assignment::ettle_assignees;
+use ettlex_memory::commands::engine_query::{apply_engine_query, EngineQuery, EngineQueryResult};
 use ettlex_memory::commands::read_tools::{base64_decode, ListOptions};
 use ettlex_store::cas::FsStore;
-use ettlex_store::model::EttleListOpts;
-use ettlex_store::repo::SqliteRepo;
+use ettlex_store::model::{EttleCursor, EttleListOpts};
+use ettlex_store::repo::CursorCodec;
 use rusqlite::Connection;
 use serde_json::{json, Value};
 
 use crate::error::{McpError, McpResult, MCP_INVALID_CURSOR, MCP_INVALID_INPUT};
+use crate::fields::{apply_field_mask, parse_field_mask};
+
+/// Fields `ettle_get` can return; `id` is always included.
+const ETTLE_FIELDS: &[&str] = &[
+    "id",
+    "ettle_id",
+    "title",
+    "why",
+    "what",
+    "how",
+    "reasoning_link_id",
+    "reasoning_link_type",
+    "created_at",
+    "updated_at",
+    "tombstoned_at",
+    "slug",
+    "archived_at",
+    "content_format",
+    "owner",
+    "reviewers",
+];
 
 /// Handle `ettle.get`.
 ///
-/// Params: `{ ettle_id: String }`
+/// Params: `{ ettle_id: String, fields?: [String] }`
 ///
 /// Delegates to `ettlex_memory::commands::ettle::handle_ettle_get`.
 /// Returns all v2 Ettle fields: id, title, why, what, how, reasoning_link_id,
-/// reasoning_link_type, created_at, updated_at, tombstoned_at.
+/// reasoning_link_type, created_at, updated_at, tombstoned_at, slug, plus
+/// the `owner` and `reviewers` — or only `id` plus those named in `fields`.
 pub fn handle_ettle_get(
     params: &Value,
     conn: &Connection,
-    _cas: &FsStore,
+    cas: &FsStore,
     _policy_provider: &dyn PolicyProvider,
 ) -> McpResult {
     let ettle_id = match params.get("ettle_id").and_then(Value::as_str) {
@@ -30,9 +53,21 @@ pub fn handle_ettle_get(
             return McpResult::Err(McpError::new(MCP_INVALID_INPUT, "missing 'ettle_id' param"))
         }
     };
+    let mask = match parse_field_mask(params, ETTLE_FIELDS) {
+        Ok(m) => m,
+        Err(e) => return McpResult::Err(e),
+    };
 
-    match ettlex_memory::commands::ettle::handle_ettle_get(conn, &ettle_id) {
-        Ok(r) => McpResult::Ok(json!({
+    let mut r = match ettlex_memory::commands::ettle::handle_ettle_get(conn, &ettle_id) {
+        Ok(r) => r,
+        Err(e) => return McpResult::Err(McpError::from_ex_error(e)),
+    };
+    if let Err(e) = ettlex_memory::commands::content_spill::hydrate_ettle_content(&mut r, cas) {
+        return McpResult::Err(McpError::from_ex_error(e));
+    }
+    match ettle_assignees(conn, &r.id) {
+        Ok(assignees) => McpResult::Ok(apply_field_mask(
+            json!({
             "id": r.id,
             // ettle_id is a backward-compatibility alias for id
             "ettle_id": r.id,
@@ -45,29 +80,42 @@ pub fn handle_ettle_get(
             "created_at": r.created_at,
             "updated_at": r.updated_at,
             "tombstoned_at": r.tombstoned_at,
-        })),
+            "slug": r.slug,
+            "archived_at": r.archived_at,
+            "content_format": r.content_format,
+            "owner": assignees.owner,
+            "reviewers": assignees.reviewers,
+            }),
+            mask.as_ref(),
+            &["id"],
+        )),
         Err(e) => McpResult::Err(McpError::from_ex_error(e)),
     }
 }
 
 /// Handle `ettle.list`.
 ///
-/// Params: `{ limit?: u64, cursor?: String, include_tombstoned?: bool }`
+/// Params: `{ limit?: u64, cursor?: String, include_tombstoned?: bool, include_archived?: bool }`
 ///
 /// Delegates to `ettlex_memory::commands::ettle::handle_ettle_list`.
-/// Returns `{ items: [{ id, title, tombstoned_at }], cursor? }`.
+/// Returns `{ items: [{ id, title, tombstoned_at, archived_at }], cursor? }`.
 pub fn handle_ettle_list(
     params: &Value,
+    cursor_codec: &CursorCodec,
     conn: &Connection,
     _cas: &FsStore,
     _policy_provider: &dyn PolicyProvider,
 ) -> McpResult {
-    let ettle_list_opts = match parse_ettle_list_opts(params) {
+    let ettle_list_opts = match parse_ettle_list_opts(params, cursor_codec) {
         Ok(o) => o,
         Err(e) => return e,
     };
 
-    match ettlex_memory::commands::ettle::handle_ettle_list(conn, ettle_list_opts) {
+    match ettlex_memory::commands::ettle::handle_ettle_list_with_codec(
+        conn,
+        ettle_list_opts,
+        cursor_codec,
+    ) {
         Ok(page) => {
             let items: Vec<Value> = page
                 .items
@@ -77,6 +125,7 @@ pub fn handle_ettle_list(
                         "id": item.id,
                         "title": item.title,
                         "tombstoned_at": item.tombstoned_at,
+                        "archived_at": item.archived_at,
                     })
                 })
                 .collect();
@@ -125,7 +174,6 @@ pub fn handle_ettle_list_decisions(
         Some(policy_provider),
     ) {
         Ok(result) => {
-            use ettlex_memory::commands::engine_query::EngineQueryResult;
             if let EngineQueryResult::EttleListDecisions(ds) = result {
                 let items: Vec<Value> = ds
                     .iter()
@@ -146,6 +194,47 @@ pub fn handle_ettle_list_decisions(
     }
 }
 
+/// Handle `ettle_list_by_owner`.
+///
+/// Params: `{ owner: String, include_reviewing?: bool }`
+///
+/// Returns `{ items: [{ ettle_id, title, role }] }`.
+pub fn handle_ettle_list_by_owner(
+    params: &Value,
+    conn: &Connection,
+    cas: &FsStore,
+    policy_provider: &dyn PolicyProvider,
+) -> McpResult {
+    let owner = match params.get("owner").and_then(Value::as_str) {
+        Some(s) => s.to_string(),
+        None => return McpResult::Err(McpError::new(MCP_INVALID_INPUT, "missing 'owner' param")),
+    };
+    let include_reviewing = params
+        .get("include_reviewing")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+
+    match apply_engine_query(
+        EngineQuery::EttleListByOwner {
+            owner,
+            include_reviewing,
+        },
+        conn,
+        cas,
+        Some(policy_provider),
+    ) {
+        Ok(EngineQueryResult::EttleListByOwner(items)) => {
+            let items: Vec<Value> = items
+                .iter()
+                .map(|i| json!({ "ettle_id": i.ettle_id, "title": i.title, "role": i.role }))
+                .collect();
+            McpResult::Ok(json!({ "items": items }))
+        }
+        Ok(_) => McpResult::Err(McpError::new("Internal", "unexpected result variant")),
+        Err(e) => McpResult::Err(McpError::from_ex_error(e)),
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Shared helpers
 // ---------------------------------------------------------------------------
@@ -155,7 +244,11 @@ pub fn handle_ettle_list_decisions(
 /// - `limit`: optional u64 (1..=500); defaults to 100 if absent.
 /// - `cursor`: optional base64 string decoded to `EttleCursor`.
 /// - `include_tombstoned`: optional bool; defaults to false.
-fn parse_ettle_list_opts(params: &Value) -> Result<EttleListOpts, McpResult> {
+/// - `include_archived`: optional bool; defaults to false.
+fn parse_ettle_list_opts(
+    params: &Value,
+    cursor_codec: &CursorCodec,
+) -> Result<EttleListOpts, McpResult> {
     let limit: u32 = match params.get("limit") {
         Some(Value::Number(n)) => n.as_u64().unwrap_or(100) as u32,
         Some(Value::Null) | None => 100,
@@ -168,7 +261,7 @@ fn parse_ettle_list_opts(params: &Value) -> Result<EttleListOpts, McpResult> {
     };
 
     let cursor = match params.get("cursor") {
-        Some(Value::String(s)) => match SqliteRepo::decode_ettle_cursor(s) {
+        Some(Value::String(s)) => match cursor_codec.decode::<EttleCursor>(s) {
             Ok(c) => Some(c),
             Err(_) => {
                 return Err(McpResult::Err(McpError::new(
@@ -191,10 +284,27 @@ fn parse_ettle_list_opts(params: &Value) -> Result<EttleListOpts, McpResult> {
         .and_then(Value::as_bool)
         .unwrap_or(false);
 
+    let include_archived = params
+        .get("include_archived")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+
     Ok(EttleListOpts {
         limit,
         cursor,
         include_tombstoned,
+        include_archived,
+    })
+}
+
+/// [`parse_list_opts`] for a listing whose cursors `cursor_codec` signs.
+pub(crate) fn parse_paged_list_opts(
+    params: &Value,
+    cursor_codec: &CursorCodec,
+) -> Result<ListOptions, McpResult> {
+    Ok(ListOptions {
+        cursor_codec: cursor_codec.clone(),
+        ..parse_list_opts(params)?
     })
 }
 
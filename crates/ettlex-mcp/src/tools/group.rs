@@ -8,7 +8,7 @@ use ettlex_core::policy_provider::PolicyProvider;
 use ettlex_memory::commands::group::{handle_group_get, handle_group_list};
 use ettlex_store::cas::FsStore;
 use ettlex_store::model::{GroupMemberRecord, GroupRecord};
-use ettlex_store::repo::SqliteRepo;
+use ettlex_store::repo::{CursorCodec, PageKey, SqliteRepo};
 use rusqlite::Connection;
 use serde_json::{json, Value};
 
@@ -18,37 +18,25 @@ use crate::error::{McpError, McpResult, MCP_INVALID_INPUT};
 // Pagination helpers
 // ---------------------------------------------------------------------------
 
-fn encode_cursor(key: &str) -> String {
-    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
-    URL_SAFE_NO_PAD.encode(key.as_bytes())
-}
-
-fn decode_cursor(cursor: &str) -> Option<String> {
-    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
-    URL_SAFE_NO_PAD
-        .decode(cursor.as_bytes())
-        .ok()
-        .and_then(|b| String::from_utf8(b).ok())
-}
-
 fn paginate_groups(
     items: Vec<GroupRecord>,
     limit: usize,
     cursor: Option<&str>,
+    codec: &CursorCodec,
 ) -> (Vec<GroupRecord>, Option<String>) {
-    let sort_key = |r: &GroupRecord| format!("{}\x00{}", r.created_at, r.id);
+    let sort_key = |r: &GroupRecord| PageKey::from_timestamp(&r.created_at, r.id.clone());
 
-    let after_key = cursor.and_then(decode_cursor);
+    let after_key = cursor.and_then(|c| codec.decode::<PageKey>(c).ok());
 
-    let filtered: Vec<GroupRecord> = match &after_key {
-        Some(key) => items.into_iter().filter(|r| sort_key(r) > *key).collect(),
-        None => items,
-    };
+    let filtered: Vec<GroupRecord> = items
+        .into_iter()
+        .filter(|r| sort_key(r).is_after(after_key.as_ref()))
+        .collect();
 
     let has_more = filtered.len() > limit;
     let page: Vec<GroupRecord> = filtered.into_iter().take(limit).collect();
     let next_cursor = if has_more {
-        page.last().map(|r| encode_cursor(&sort_key(r)))
+        page.last().map(|r| codec.encode(&sort_key(r)))
     } else {
         None
     };
@@ -59,20 +47,21 @@ fn paginate_members(
     items: Vec<GroupMemberRecord>,
     limit: usize,
     cursor: Option<&str>,
+    codec: &CursorCodec,
 ) -> (Vec<GroupMemberRecord>, Option<String>) {
-    let sort_key = |r: &GroupMemberRecord| format!("{}\x00{}", r.created_at, r.id);
+    let sort_key = |r: &GroupMemberRecord| PageKey::from_timestamp(&r.created_at, r.id.clone());
 
-    let after_key = cursor.and_then(decode_cursor);
+    let after_key = cursor.and_then(|c| codec.decode::<PageKey>(c).ok());
 
-    let filtered: Vec<GroupMemberRecord> = match &after_key {
-        Some(key) => items.into_iter().filter(|r| sort_key(r) > *key).collect(),
-        None => items,
-    };
+    let filtered: Vec<GroupMemberRecord> = items
+        .into_iter()
+        .filter(|r| sort_key(r).is_after(after_key.as_ref()))
+        .collect();
 
     let has_more = filtered.len() > limit;
     let page: Vec<GroupMemberRecord> = filtered.into_iter().take(limit).collect();
     let next_cursor = if has_more {
-        page.last().map(|r| encode_cursor(&sort_key(r)))
+        page.last().map(|r| codec.encode(&sort_key(r)))
     } else {
         None
     };
@@ -127,6 +116,7 @@ pub fn handle_group_get_tool(
 /// Returns `{ items: [{ group_id, name, created_at, tombstoned_at }], cursor? }`.
 pub fn handle_group_list_tool(
     params: &Value,
+    cursor_codec: &CursorCodec,
     conn: &Connection,
     _cas: &FsStore,
     _policy_provider: &dyn PolicyProvider,
@@ -145,7 +135,8 @@ pub fn handle_group_list_tool(
 
     match handle_group_list(conn, include_tombstoned) {
         Ok(ettlex_memory::commands::command::CommandResult::GroupList { items }) => {
-            let (page, next_cursor) = paginate_groups(items, limit, cursor.as_deref());
+            let (page, next_cursor) =
+                paginate_groups(items, limit, cursor.as_deref(), cursor_codec);
 
             let json_items: Vec<Value> = page
                 .iter()
@@ -187,6 +178,7 @@ pub fn handle_group_list_tool(
 /// filter is a spec gap filled at the MCP layer (Slice 02b store exception).
 pub fn handle_group_member_list_tool(
     params: &Value,
+    cursor_codec: &CursorCodec,
     conn: &Connection,
     _cas: &FsStore,
     _policy_provider: &dyn PolicyProvider,
@@ -226,7 +218,8 @@ pub fn handle_group_member_list_tool(
         include_tombstoned,
     ) {
         Ok(items) => {
-            let (page, next_cursor) = paginate_members(items, limit, cursor.as_deref());
+            let (page, next_cursor) =
+                paginate_members(items, limit, cursor.as_deref(), cursor_codec);
 
             let json_items: Vec<Value> = page
                 .iter()
@@ -2,6 +2,7 @@
 
 pub mod apply;
 pub mod approval;
+pub mod comment;
 pub mod constraint;
 pub mod decision;
 pub mod ettle;
@@ -10,5 +11,6 @@ pub mod policy;
 pub mod predicate;
 pub mod profile;
 pub mod relation;
+pub mod root;
 pub mod snapshot;
 pub mod state;
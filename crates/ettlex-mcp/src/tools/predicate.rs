@@ -1,5 +1,6 @@
 //! Handler for `constraint_predicates.*` tool group.
 
+use ettlex_core::candidate_resolver::AmbiguityPolicy;
 use ettlex_core::policy_provider::PolicyProvider;
 use ettlex_memory::commands::engine_query::{apply_engine_query, EngineQuery};
 use ettlex_store::cas::FsStore;
@@ -10,7 +11,7 @@ use crate::error::{McpError, McpResult, MCP_INVALID_INPUT};
 
 /// Handle `constraint_predicates.preview`.
 ///
-/// Params: `{ profile_ref?: String, context: Object, candidates: Array<String> }`
+/// Params: `{ profile_ref?: String, context: Object, candidates: Array<String>, ambiguity_policy?: String }`
 pub fn handle_predicate_preview(
     params: &Value,
     conn: &Connection,
@@ -41,11 +42,25 @@ pub fn handle_predicate_preview(
         }
     };
 
+    let ambiguity_policy_override = match params.get("ambiguity_policy") {
+        Some(Value::Null) | None => None,
+        Some(v) => match v.as_str().and_then(AmbiguityPolicy::from_name) {
+            Some(policy) => Some(policy),
+            None => {
+                return McpResult::Err(McpError::new(
+                    MCP_INVALID_INPUT,
+                    "'ambiguity_policy' must be one of fail_fast, choose_deterministic, route_for_approval",
+                ))
+            }
+        },
+    };
+
     match apply_engine_query(
         EngineQuery::ConstraintPredicatesPreview {
             profile_ref,
             context,
             candidates,
+            ambiguity_policy_override,
         },
         conn,
         cas,
@@ -65,6 +80,8 @@ pub fn handle_predicate_preview(
                     "status": status,
                     "selected": r.selected,
                     "candidates": r.candidates,
+                    "ambiguity_policy": r.ambiguity_policy.as_str(),
+                    "policy_overridden": r.policy_overridden,
                 }))
             } else {
                 McpResult::Err(McpError::new("Internal", "unexpected result variant"))
@@ -4,11 +4,12 @@ use ettlex_core::policy_provider::PolicyProvider;
 use ettlex_memory::commands::engine_query::{apply_engine_query, EngineQuery};
 use ettlex_memory::commands::read_tools::ListOptions;
 use ettlex_store::cas::FsStore;
+use ettlex_store::repo::CursorCodec;
 use rusqlite::Connection;
 use serde_json::{json, Value};
 
 use crate::error::{McpError, McpResult, MCP_INVALID_INPUT};
-use crate::tools::ettle::parse_list_opts;
+use crate::tools::ettle::parse_paged_list_opts;
 
 /// Handle `profile.get`.
 ///
@@ -56,11 +57,12 @@ pub fn handle_profile_get(
 /// Params: `{ limit?: u64, cursor?: String }`
 pub fn handle_profile_list(
     params: &Value,
+    cursor_codec: &CursorCodec,
     conn: &Connection,
     cas: &FsStore,
     policy_provider: &dyn PolicyProvider,
 ) -> McpResult {
-    let opts = match parse_list_opts(params) {
+    let opts = match parse_paged_list_opts(params, cursor_codec) {
         Ok(o) => o,
         Err(e) => return e,
     };
@@ -131,9 +133,12 @@ pub fn handle_profile_get_default(
 
 /// Handle `profile_resolve`.
 ///
-/// Params: `{ profile_ref?: String }`
+/// Params: `{ profile_ref?: String, environment?: String }`
+///
+/// `environment` defaults to the server's active environment.
 pub fn handle_profile_resolve(
     params: &Value,
+    active_environment: Option<&str>,
     conn: &Connection,
     cas: &FsStore,
     policy_provider: &dyn PolicyProvider,
@@ -142,9 +147,17 @@ pub fn handle_profile_resolve(
         .get("profile_ref")
         .and_then(Value::as_str)
         .map(String::from);
+    let environment = params
+        .get("environment")
+        .and_then(Value::as_str)
+        .or(active_environment)
+        .map(String::from);
 
     match apply_engine_query(
-        EngineQuery::ProfileResolve { profile_ref },
+        EngineQuery::ProfileResolve {
+            profile_ref,
+            environment,
+        },
         conn,
         cas,
         Some(policy_provider),
@@ -156,6 +169,8 @@ pub fn handle_profile_resolve(
                     "profile_ref": r.profile_ref,
                     "profile_digest": r.profile_digest,
                     "payload": r.parsed_profile,
+                    "effective_payload": r.effective_profile,
+                    "extends_chain": r.extends_chain,
                 }))
             } else {
                 McpResult::Err(McpError::new("Internal", "unexpected result variant"))
@@ -8,6 +8,7 @@ use ettlex_core::policy_provider::PolicyProvider;
 use ettlex_memory::commands::relation::{handle_relation_get, handle_relation_list};
 use ettlex_store::cas::FsStore;
 use ettlex_store::model::RelationRecord;
+use ettlex_store::repo::{CursorCodec, PageKey};
 use rusqlite::Connection;
 use serde_json::{json, Value};
 
@@ -17,39 +18,27 @@ use crate::error::{McpError, McpResult, MCP_INVALID_INPUT};
 // Pagination helpers
 // ---------------------------------------------------------------------------
 
-fn encode_cursor(key: &str) -> String {
-    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
-    URL_SAFE_NO_PAD.encode(key.as_bytes())
-}
-
-fn decode_cursor(cursor: &str) -> Option<String> {
-    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
-    URL_SAFE_NO_PAD
-        .decode(cursor.as_bytes())
-        .ok()
-        .and_then(|b| String::from_utf8(b).ok())
-}
-
 /// Apply cursor + limit pagination over a `Vec<RelationRecord>` already in
-/// `(created_at ASC, id ASC)` order.  Returns `(page_items, next_cursor)`.
+/// `(created_at_ms ASC, id ASC)` order.  Returns `(page_items, next_cursor)`.
 fn paginate_relations(
     items: Vec<RelationRecord>,
     limit: usize,
     cursor: Option<&str>,
+    codec: &CursorCodec,
 ) -> (Vec<RelationRecord>, Option<String>) {
-    let sort_key = |r: &RelationRecord| format!("{}\x00{}", r.created_at, r.id);
+    let sort_key = |r: &RelationRecord| PageKey::from_timestamp(&r.created_at, r.id.clone());
 
-    let after_key = cursor.and_then(decode_cursor);
+    let after_key = cursor.and_then(|c| codec.decode::<PageKey>(c).ok());
 
-    let filtered: Vec<RelationRecord> = match &after_key {
-        Some(key) => items.into_iter().filter(|r| sort_key(r) > *key).collect(),
-        None => items,
-    };
+    let filtered: Vec<RelationRecord> = items
+        .into_iter()
+        .filter(|r| sort_key(r).is_after(after_key.as_ref()))
+        .collect();
 
     let has_more = filtered.len() > limit;
     let page: Vec<RelationRecord> = filtered.into_iter().take(limit).collect();
     let next_cursor = if has_more {
-        page.last().map(|r| encode_cursor(&sort_key(r)))
+        page.last().map(|r| codec.encode(&sort_key(r)))
     } else {
         None
     };
@@ -113,6 +102,7 @@ pub fn handle_relation_get_tool(
 /// Returns `{ items: [...], cursor? }`.
 pub fn handle_relation_list_tool(
     params: &Value,
+    cursor_codec: &CursorCodec,
     conn: &Connection,
     _cas: &FsStore,
     _policy_provider: &dyn PolicyProvider,
@@ -153,7 +143,8 @@ pub fn handle_relation_list_tool(
         include_tombstoned,
     ) {
         Ok(ettlex_memory::commands::command::CommandResult::RelationList { items }) => {
-            let (page, next_cursor) = paginate_relations(items, limit, cursor.as_deref());
+            let (page, next_cursor) =
+                paginate_relations(items, limit, cursor.as_deref(), cursor_codec);
 
             let json_items: Vec<Value> = page
                 .iter()
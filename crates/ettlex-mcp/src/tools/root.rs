@@ -0,0 +1,83 @@
+//! Handlers for `root.*` tool group.
+
+use ettlex_core::policy_provider::PolicyProvider;
+use ettlex_memory::commands::engine_query::{apply_engine_query, EngineQuery, EngineQueryResult};
+use ettlex_memory::commands::read_tools::RegisteredRoot;
+use ettlex_store::cas::FsStore;
+use rusqlite::Connection;
+use serde_json::{json, Value};
+
+use crate::error::{McpError, McpResult, MCP_INVALID_INPUT};
+
+fn root_to_json(r: &RegisteredRoot) -> Value {
+    json!({
+        "ettle_id": r.root.ettle_id,
+        "display_name": r.root.display_name,
+        "owner": r.root.owner,
+        "status": r.root.status,
+        "registered_at_ms": r.root.registered_at_ms,
+        "updated_at_ms": r.root.updated_at_ms,
+        "head_snapshot_id": r.head_snapshot_id,
+        "snapshot_count": r.snapshot_count,
+    })
+}
+
+/// Handle `root_get`.
+///
+/// Params: `{ ettle_id: String }`
+pub fn handle_root_get(
+    params: &Value,
+    conn: &Connection,
+    cas: &FsStore,
+    policy_provider: &dyn PolicyProvider,
+) -> McpResult {
+    let ettle_id = match params.get("ettle_id").and_then(Value::as_str) {
+        Some(s) => s.to_string(),
+        None => {
+            return McpResult::Err(McpError::new(MCP_INVALID_INPUT, "missing 'ettle_id' param"))
+        }
+    };
+
+    match apply_engine_query(
+        EngineQuery::RootGet { ettle_id },
+        conn,
+        cas,
+        Some(policy_provider),
+    ) {
+        Ok(EngineQueryResult::RootGet(r)) => McpResult::Ok(root_to_json(&r)),
+        Ok(_) => McpResult::Err(McpError::new("Internal", "unexpected result variant")),
+        Err(e) => McpResult::Err(McpError::from_ex_error(e)),
+    }
+}
+
+/// Handle `root_list`.
+///
+/// Params: `{ include_retired?: bool }`
+pub fn handle_root_list(
+    params: &Value,
+    conn: &Connection,
+    cas: &FsStore,
+    policy_provider: &dyn PolicyProvider,
+) -> McpResult {
+    let include_retired = params
+        .get("include_retired")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+
+    match apply_engine_query(
+        EngineQuery::RootList { include_retired },
+        conn,
+        cas,
+        Some(policy_provider),
+    ) {
+        Ok(EngineQueryResult::RootList(registry)) => {
+            let items: Vec<Value> = registry.registered.iter().map(root_to_json).collect();
+            McpResult::Ok(json!({
+                "items": items,
+                "unregistered": registry.unregistered,
+            }))
+        }
+        Ok(_) => McpResult::Err(McpError::new("Internal", "unexpected result variant")),
+        Err(e) => McpResult::Err(McpError::from_ex_error(e)),
+    }
+}
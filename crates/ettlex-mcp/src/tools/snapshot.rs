@@ -1,18 +1,24 @@
-//! Handlers for `snapshot.*` tool group.
+//! Handlers for `snapshot.*` tool group and `ept_compute`.
 
+use ettlex_core::diff::human_summary::{SummaryOptions, SummaryVerbosity};
+use ettlex_core::diff::ignore::DiffIgnoreList;
+use ettlex_core::diff::messages::MessageCatalog;
+use ettlex_core::diff::severity::SeverityRules;
 use ettlex_core::policy_provider::PolicyProvider;
 use ettlex_memory::commands::engine_query::{apply_engine_query, EngineQuery, SnapshotRef};
-use ettlex_memory::commands::read_tools::ListOptions;
+use ettlex_memory::commands::read_tools::{EptIssueKind, ListOptions};
 use ettlex_store::cas::FsStore;
 use rusqlite::Connection;
 use serde_json::{json, Value};
 
 use crate::error::{McpError, McpResult, MCP_INVALID_INPUT};
+use crate::fields::{apply_field_mask, parse_field_mask};
 use crate::tools::ettle::parse_list_opts;
 
 /// Handle `snapshot.list`.
 ///
-/// Params: `{ ettle_id?: String, limit?: u64, cursor?: String }`
+/// Params: `{ ettle_id?: String, registered_only?: bool, limit?: u64,
+///            cursor?: String }`
 pub fn handle_snapshot_list(
     params: &Value,
     conn: &Connection,
@@ -23,6 +29,10 @@ pub fn handle_snapshot_list(
         .get("ettle_id")
         .and_then(Value::as_str)
         .map(String::from);
+    let registered_only = params
+        .get("registered_only")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
     if let Err(e) = parse_list_opts(params) {
         return e;
     } // validates cursor
@@ -33,7 +43,10 @@ pub fn handle_snapshot_list(
         .map(|n| n as usize);
 
     match apply_engine_query(
-        EngineQuery::SnapshotList { ettle_id },
+        EngineQuery::SnapshotList {
+            ettle_id,
+            registered_only,
+        },
         conn,
         cas,
         Some(policy_provider),
@@ -53,6 +66,8 @@ pub fn handle_snapshot_list(
                             "manifest_digest": r.manifest_digest,
                             "created_at": r.created_at,
                             "status": r.status,
+                            "message": r.message,
+                            "annotations": r.annotations,
                         })
                     })
                     .collect();
@@ -108,6 +123,8 @@ pub fn handle_snapshot_get(
                     "status": r.status,
                     "policy_ref": r.policy_ref,
                     "profile_ref": r.profile_ref,
+                    "message": r.message,
+                    "annotations": r.annotations,
                 }))
             } else {
                 McpResult::Err(McpError::new("Internal", "unexpected result variant"))
@@ -158,7 +175,10 @@ pub fn handle_snapshot_get_head(
 
 /// Handle `snapshot.get_manifest`.
 ///
-/// Params: `{ snapshot_id: String }`
+/// Params: `{ snapshot_id: String, fields?: [String] }`
+///
+/// `fields` selects among `manifest_digest` and `manifest_bytes`;
+/// `snapshot_id` is always returned.
 pub fn handle_snapshot_get_manifest(
     params: &Value,
     conn: &Connection,
@@ -174,6 +194,13 @@ pub fn handle_snapshot_get_manifest(
             ))
         }
     };
+    let mask = match parse_field_mask(
+        params,
+        &["snapshot_id", "manifest_digest", "manifest_bytes"],
+    ) {
+        Ok(m) => m,
+        Err(e) => return McpResult::Err(e),
+    };
 
     match apply_engine_query(
         EngineQuery::ManifestGetBySnapshot { snapshot_id },
@@ -185,11 +212,15 @@ pub fn handle_snapshot_get_manifest(
             use ettlex_memory::commands::engine_query::EngineQueryResult;
             if let EngineQueryResult::ManifestGet(r) = result {
                 let manifest_bytes = String::from_utf8_lossy(&r.manifest_bytes).to_string();
-                McpResult::Ok(json!({
-                    "snapshot_id": r.snapshot_id,
-                    "manifest_digest": r.manifest_digest,
-                    "manifest_bytes": manifest_bytes,
-                }))
+                McpResult::Ok(apply_field_mask(
+                    json!({
+                        "snapshot_id": r.snapshot_id,
+                        "manifest_digest": r.manifest_digest,
+                        "manifest_bytes": manifest_bytes,
+                    }),
+                    mask.as_ref(),
+                    &["snapshot_id"],
+                ))
             } else {
                 McpResult::Err(McpError::new("Internal", "unexpected result variant"))
             }
@@ -200,7 +231,9 @@ pub fn handle_snapshot_get_manifest(
 
 /// Handle `snapshot.diff`.
 ///
-/// Params: `{ a_snapshot_id: String, b_snapshot_id: String }`
+/// Params: `{ a_snapshot_id: String, b_snapshot_id: String, severity_rules?: Object, ignore?: Object,
+///            summary_verbosity?: "one-line" | "summary" | "detailed", message_catalog?: Object,
+///            include_content?: bool }`
 pub fn handle_snapshot_diff(
     params: &Value,
     conn: &Connection,
@@ -226,9 +259,70 @@ pub fn handle_snapshot_diff(
         }
     };
 
+    let severity_rules = match params.get("severity_rules") {
+        None | Some(Value::Null) => SeverityRules::default(),
+        Some(v) => match serde_json::from_value::<SeverityRules>(v.clone()) {
+            Ok(r) => r,
+            Err(e) => {
+                return McpResult::Err(McpError::new(
+                    MCP_INVALID_INPUT,
+                    format!("invalid 'severity_rules' param: {}", e),
+                ))
+            }
+        },
+    };
+
+    let ignore = match params.get("ignore") {
+        None | Some(Value::Null) => None,
+        Some(v) => match DiffIgnoreList::from_value(v) {
+            Ok(list) => Some(list),
+            Err(e) => {
+                return McpResult::Err(McpError::new(
+                    MCP_INVALID_INPUT,
+                    format!("invalid 'ignore' param: {}", e),
+                ))
+            }
+        },
+    };
+
+    let verbosity = match params.get("summary_verbosity") {
+        None | Some(Value::Null) => SummaryVerbosity::default(),
+        Some(v) => match v.as_str().map(str::parse::<SummaryVerbosity>) {
+            Some(Ok(verbosity)) => verbosity,
+            _ => {
+                return McpResult::Err(McpError::new(
+                    MCP_INVALID_INPUT,
+                    "invalid 'summary_verbosity' param: expected one-line, summary or detailed",
+                ))
+            }
+        },
+    };
+
+    let catalog = match params.get("message_catalog") {
+        None | Some(Value::Null) => MessageCatalog::english(),
+        Some(v) => match MessageCatalog::from_value(v) {
+            Ok(catalog) => catalog,
+            Err(e) => {
+                return McpResult::Err(McpError::new(
+                    MCP_INVALID_INPUT,
+                    format!("invalid 'message_catalog' param: {}", e),
+                ))
+            }
+        },
+    };
+
+    let include_content = params
+        .get("include_content")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+
     let query = EngineQuery::SnapshotDiff {
         a_ref: SnapshotRef::SnapshotId(a_id),
         b_ref: SnapshotRef::SnapshotId(b_id),
+        severity_rules,
+        ignore,
+        summary: SummaryOptions { verbosity, catalog },
+        include_content,
     };
 
     match apply_engine_query(query, conn, cas, Some(policy_provider)) {
@@ -239,6 +333,10 @@ pub fn handle_snapshot_diff(
                     serde_json::to_value(&r.structured_diff).unwrap_or(serde_json::Value::Null);
                 McpResult::Ok(json!({
                     "identity": structured["identity"],
+                    "severity": structured["severity"],
+                    "severity_tags": structured["severity_tags"],
+                    "ignored_tags": r.structured_diff.ignored_tags,
+                    "content_diffs": r.structured_diff.content_diffs,
                     "human_summary": r.human_summary,
                 }))
             } else {
@@ -251,7 +349,10 @@ pub fn handle_snapshot_diff(
 
 /// Handle `manifest_get_by_digest`.
 ///
-/// Params: `{ manifest_digest: String }`
+/// Params: `{ manifest_digest: String, fields?: [String] }`
+///
+/// `fields` selects among `snapshot_id`, `semantic_manifest_digest` and
+/// `manifest`; `manifest_digest` is always returned.
 pub fn handle_manifest_get_by_digest(
     params: &Value,
     conn: &Connection,
@@ -267,6 +368,18 @@ pub fn handle_manifest_get_by_digest(
             ))
         }
     };
+    let mask = match parse_field_mask(
+        params,
+        &[
+            "snapshot_id",
+            "manifest_digest",
+            "semantic_manifest_digest",
+            "manifest",
+        ],
+    ) {
+        Ok(m) => m,
+        Err(e) => return McpResult::Err(e),
+    };
 
     match apply_engine_query(
         EngineQuery::ManifestGetByDigest { manifest_digest },
@@ -279,12 +392,16 @@ pub fn handle_manifest_get_by_digest(
             if let EngineQueryResult::ManifestGet(r) = result {
                 let manifest_json: Value =
                     serde_json::from_slice(&r.manifest_bytes).unwrap_or(Value::Null);
-                McpResult::Ok(json!({
-                    "snapshot_id": r.snapshot_id,
-                    "manifest_digest": r.manifest_digest,
-                    "semantic_manifest_digest": r.semantic_manifest_digest,
-                    "manifest": manifest_json,
-                }))
+                McpResult::Ok(apply_field_mask(
+                    json!({
+                        "snapshot_id": r.snapshot_id,
+                        "manifest_digest": r.manifest_digest,
+                        "semantic_manifest_digest": r.semantic_manifest_digest,
+                        "manifest": manifest_json,
+                    }),
+                    mask.as_ref(),
+                    &["manifest_digest"],
+                ))
             } else {
                 McpResult::Err(McpError::new("Internal", "unexpected result variant"))
             }
@@ -294,4 +411,85 @@ pub fn handle_manifest_get_by_digest(
 }
 
 /// Unused import suppressor for ListOptions
+/// Handle `ept_compute`.
+///
+/// Params: `{ leaf_ettle_id: String }`
+///
+/// Returns the refinement path from the root down to the leaf (`ept`) and
+/// any problems found walking it (`issues`, leaf first). `ept` is the path
+/// a snapshot commit would use; it is only unambiguous when `issues` is
+/// empty.
+pub fn handle_ept_compute(
+    params: &Value,
+    conn: &Connection,
+    cas: &FsStore,
+    policy_provider: &dyn PolicyProvider,
+) -> McpResult {
+    let leaf_ettle_id = match params.get("leaf_ettle_id").and_then(Value::as_str) {
+        Some(s) => s.to_string(),
+        None => {
+            return McpResult::Err(McpError::new(
+                MCP_INVALID_INPUT,
+                "missing 'leaf_ettle_id' param",
+            ))
+        }
+    };
+
+    let d = match apply_engine_query(
+        EngineQuery::EptDiagnose { leaf_ettle_id },
+        conn,
+        cas,
+        Some(policy_provider),
+    ) {
+        Ok(ettlex_memory::commands::engine_query::EngineQueryResult::EptDiagnose(d)) => d,
+        Ok(_) => return McpResult::Err(McpError::new("Internal", "unexpected result variant")),
+        Err(e) => return McpResult::Err(McpError::from_ex_error(e)),
+    };
+
+    let issues: Vec<Value> = d
+        .issues
+        .iter()
+        .map(|i| {
+            let mut issue = match &i.kind {
+                EptIssueKind::NotALeaf { child_ettle_ids } => json!({
+                    "kind": "not_a_leaf",
+                    "child_ettle_ids": child_ettle_ids,
+                }),
+                EptIssueKind::AmbiguousParent { candidates } => json!({
+                    "kind": "ambiguous_parent",
+                    "candidates": candidates.iter().map(|c| json!({
+                        "ordinal": c.ordinal,
+                        "relation_id": c.relation_id,
+                        "parent_ettle_id": c.parent_ettle_id,
+                        "title": c.title,
+                    })).collect::<Vec<_>>(),
+                }),
+                EptIssueKind::BrokenLink {
+                    relation_id,
+                    parent_ettle_id,
+                    parent_state,
+                } => json!({
+                    "kind": "broken_link",
+                    "relation_id": relation_id,
+                    "parent_ettle_id": parent_ettle_id,
+                    "parent_state": parent_state,
+                }),
+                EptIssueKind::Cycle { parent_ettle_id } => json!({
+                    "kind": "cycle",
+                    "parent_ettle_id": parent_ettle_id,
+                }),
+            };
+            issue["ettle_id"] = json!(i.ettle_id);
+            issue["suggested_fix"] = json!(i.suggested_fix);
+            issue
+        })
+        .collect();
+
+    McpResult::Ok(json!({
+        "leaf_ettle_id": d.leaf_ettle_id,
+        "ept": d.path,
+        "issues": issues,
+    }))
+}
+
 fn _use_list_opts(_: ListOptions) {}
@@ -27,9 +27,30 @@ pub fn handle_state_get_version(
         Ok(result) => {
             use ettlex_memory::commands::engine_query::EngineQueryResult;
             if let EngineQueryResult::StateVersion(r) = result {
+                let summary = r.summary.map(|s| {
+                    let root_heads: Vec<Value> = s
+                        .root_heads
+                        .into_iter()
+                        .map(|h| {
+                            json!({
+                                "root_ettle_id": h.root_ettle_id,
+                                "display_name": h.display_name,
+                                "snapshot_id": h.snapshot_id,
+                                "manifest_digest": h.manifest_digest,
+                            })
+                        })
+                        .collect();
+                    json!({
+                        "ettle_count": s.ettle_count,
+                        "snapshot_count": s.snapshot_count,
+                        "pending_approval_count": s.pending_approval_count,
+                        "root_heads": root_heads,
+                    })
+                });
                 McpResult::Ok(json!({
                     "state_version": r.state_version,
                     "semantic_head_digest": r.semantic_head_digest,
+                    "summary": summary,
                 }))
             } else {
                 McpResult::Err(McpError::new("Internal", "unexpected result variant"))
@@ -0,0 +1,219 @@
+//! MCP agent tools — EPT and constraint reads, the dedicated write tools,
+//! the read-only gate and structured errors.
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_mcp::auth::{required_scope, AuthConfig};
+use ettlex_mcp::context::RequestContext;
+use ettlex_mcp::server::{McpResponse, McpResult, McpServer, McpToolCall};
+use ettlex_memory::commands::api_token::ApiScope;
+use ettlex_store::cas::FsStore;
+use rusqlite::Connection;
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+struct TestHarness {
+    _tmp: TempDir,
+    conn: Connection,
+    cas: FsStore,
+    server: McpServer,
+}
+
+impl TestHarness {
+    fn new(server: McpServer) -> Self {
+        let tmp = TempDir::new().unwrap();
+        let mut conn = Connection::open(tmp.path().join("test.db")).unwrap();
+        ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+        let cas = FsStore::new(tmp.path().join("cas"));
+        Self {
+            _tmp: tmp,
+            conn,
+            cas,
+            server,
+        }
+    }
+
+    fn call(&mut self, tool: &str, params: Value) -> McpResponse {
+        let size = params.to_string().len();
+        self.server.dispatch(
+            McpToolCall {
+                tool_name: tool.to_string(),
+                params,
+                context: RequestContext::default(),
+                auth_token: None,
+                payload_size: size,
+            },
+            &mut self.conn,
+            &self.cas,
+            &NoopPolicyProvider,
+            &NoopApprovalRouter,
+        )
+    }
+
+    fn ok(&mut self, tool: &str, params: Value) -> Value {
+        match self.call(tool, params).result {
+            McpResult::Ok(v) => v,
+            McpResult::Err(e) => panic!("{} failed: {} {}", tool, e.error_code, e.message),
+        }
+    }
+
+    fn create_ettle(&mut self, title: &str) -> String {
+        let v = self.ok(
+            "ettlex_apply",
+            json!({ "command": { "tag": "EttleCreate", "title": title } }),
+        );
+        v["result"]["ettle_id"].as_str().unwrap().to_string()
+    }
+
+    fn relate(&mut self, relation_type: &str, source: &str, target: &str) {
+        self.ok(
+            "ettlex_apply",
+            json!({ "command": {
+                "tag": "RelationCreate",
+                "relation_type": relation_type,
+                "source_ettle_id": source,
+                "target_ettle_id": target,
+            } }),
+        );
+    }
+}
+
+#[test]
+fn test_ept_compute_and_ettle_list_constraints() {
+    let mut h = TestHarness::new(McpServer::new(AuthConfig::disabled(), 1024 * 1024));
+    let root = h.create_ettle("Root");
+    let leaf = h.create_ettle("Leaf");
+    let rule = h.create_ettle("No shared DBs");
+    h.relate("refinement", &root, &leaf);
+    h.relate("constraint", &rule, &root);
+
+    let ept = h.ok("ept_compute", json!({ "leaf_ettle_id": leaf }));
+    assert_eq!(ept["ept"], json!([root, leaf]));
+    assert_eq!(ept["issues"], json!([]));
+
+    let not_leaf = h.ok("ept_compute", json!({ "leaf_ettle_id": root }));
+    assert_eq!(not_leaf["issues"][0]["kind"], json!("not_a_leaf"));
+
+    let constraints = h.ok("ettle_list_constraints", json!({ "ettle_id": leaf }));
+    assert_eq!(constraints["path"], json!([root, leaf]));
+    assert_eq!(constraints["constraints"][0]["constraint_id"], json!(rule));
+    assert_eq!(constraints["constraints"][0]["inherited"], json!(true));
+    assert_eq!(constraints["constraints"][0]["attached_to"], json!(root));
+
+    let resp = h.call("ept_compute", json!({}));
+    match resp.result {
+        McpResult::Err(e) => assert_eq!(e.error_code, "InvalidInput"),
+        McpResult::Ok(v) => panic!("expected error, got {}", v),
+    }
+}
+
+#[test]
+fn test_decision_create_tool_goes_through_command_log() {
+    let mut h = TestHarness::new(McpServer::new(AuthConfig::disabled(), 1024 * 1024));
+    let params = json!({
+        "title": "Use a queue",
+        "decision_text": "Commits go through a queue",
+        "rationale": "Ordering",
+    });
+
+    let dry = h.ok(
+        "decision_create",
+        json!({ "dry_run": true, "title": "x", "decision_text": "y", "rationale": "z" }),
+    );
+    assert_eq!(dry["dry_run"], json!(true));
+    assert_eq!(dry["new_state_version"], json!(1));
+
+    let created = h.ok("decision_create", params);
+    assert_eq!(created["result"]["tag"], json!("DecisionCreate"));
+    assert_eq!(created["new_state_version"], json!(1));
+    let decision_id = created["result"]["decision_id"].as_str().unwrap();
+
+    let got = h.ok("decision_get", json!({ "decision_id": decision_id }));
+    assert_eq!(got["title"], json!("Use a queue"));
+    assert_eq!(got["status"], json!("proposed"));
+
+    let stale = h.call(
+        "decision_create",
+        json!({ "expected_state_version": 0, "title": "t", "decision_text": "d", "rationale": "r" }),
+    );
+    match stale.result {
+        McpResult::Err(e) => assert_eq!(e.error_code, "HeadMismatch"),
+        McpResult::Ok(v) => panic!("expected error, got {}", v),
+    }
+}
+
+#[test]
+fn test_write_tools_need_write_scope_and_respect_read_only() {
+    assert_eq!(
+        required_scope("decision_create", &json!({})),
+        ApiScope::Write
+    );
+    assert_eq!(
+        required_scope("snapshot_commit", &json!({})),
+        ApiScope::Write
+    );
+    assert_eq!(required_scope("ept_compute", &json!({})), ApiScope::Read);
+    assert_eq!(
+        required_scope(
+            "ettlex_apply",
+            &json!({"command": {"tag": "ApiTokenIssue", "name": "x", "scopes": ["read"]}})
+        ),
+        ApiScope::Admin
+    );
+
+    let server = McpServer::new(AuthConfig::disabled(), 1024 * 1024).with_read_only(true);
+    let mut h = TestHarness::new(server);
+    for (tool, params) in [
+        (
+            "ettlex_apply",
+            json!({ "command": { "tag": "EttleCreate", "title": "A" } }),
+        ),
+        (
+            "decision_create",
+            json!({ "title": "t", "decision_text": "d", "rationale": "r" }),
+        ),
+        ("snapshot_commit", json!({ "leaf_ep_id": "ettle:a" })),
+    ] {
+        match h.call(tool, params).result {
+            McpResult::Err(e) => {
+                assert_eq!(e.error_code, "WritesDisabled");
+                assert_eq!(e.to_json()["category"], json!("permission_denied"));
+            }
+            McpResult::Ok(v) => panic!("{} should be refused, got {}", tool, v),
+        }
+    }
+    // Reads still work
+    h.ok("ettle_list", json!({}));
+}
+
+#[test]
+fn test_engine_errors_are_structured() {
+    let mut h = TestHarness::new(McpServer::new(AuthConfig::disabled(), 1024 * 1024));
+
+    let resp = h.call("ept_compute", json!({ "leaf_ettle_id": "ettle:missing" }));
+    let McpResult::Err(e) = resp.result else {
+        panic!("expected error");
+    };
+    let error = e.to_json();
+    assert_eq!(error["error_code"], json!("NotFound"));
+    assert_eq!(error["code"], json!("ERR_NOT_FOUND"));
+    assert_eq!(error["category"], json!("not_found"));
+    assert_eq!(error["entity_id"], json!("ettle:missing"));
+
+    // The snapshot pipeline is deferred; the tool reports that as a
+    // structured error rather than failing transport
+    let leaf = h.create_ettle("Leaf");
+    let resp = h.call("snapshot_commit", json!({ "leaf_ep_id": leaf }));
+    let McpResult::Err(e) = resp.result else {
+        panic!("expected error");
+    };
+    assert_eq!(e.to_json()["category"], json!("unimplemented"));
+
+    let resp = h.call("no_such_tool", json!({}));
+    let McpResult::Err(e) = resp.result else {
+        panic!("expected error");
+    };
+    let error = e.to_json();
+    assert_eq!(error["category"], json!("not_found"));
+    assert!(error.get("code").is_none());
+}
@@ -0,0 +1,126 @@
+//! MCP API-token auth tests — per-tool scope enforcement with tokens issued
+//! into the store.
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_mcp::auth::AuthConfig;
+use ettlex_mcp::context::RequestContext;
+use ettlex_mcp::server::{McpResponse, McpResult, McpServer, McpToolCall};
+use ettlex_memory::commands::api_token::{issue_api_token, revoke_api_token};
+use ettlex_store::cas::FsStore;
+use rusqlite::Connection;
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+struct TestHarness {
+    _tmp: TempDir,
+    conn: Connection,
+    cas: FsStore,
+    server: McpServer,
+}
+
+impl TestHarness {
+    fn new() -> Self {
+        let tmp = TempDir::new().unwrap();
+        let mut conn = Connection::open(tmp.path().join("test.db")).unwrap();
+        ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+        let cas = FsStore::new(tmp.path().join("cas"));
+        let server = McpServer::new(AuthConfig::api_tokens(), 1024 * 1024);
+        Self {
+            _tmp: tmp,
+            conn,
+            cas,
+            server,
+        }
+    }
+
+    /// Issue a token and return (token ID, secret).
+    fn issue(&mut self, scopes: &[&str]) -> (String, String) {
+        let scopes: Vec<String> = scopes.iter().map(|s| s.to_string()).collect();
+        let issued = issue_api_token(&mut self.conn, &self.cas, "test", &scopes).unwrap();
+        (issued.record.token_id, issued.secret)
+    }
+
+    fn call(&mut self, tool: &str, params: Value, token: Option<&str>) -> McpResponse {
+        let size = params.to_string().len();
+        self.server.dispatch(
+            McpToolCall {
+                tool_name: tool.to_string(),
+                params,
+                context: RequestContext::default(),
+                auth_token: token.map(str::to_string),
+                payload_size: size,
+            },
+            &mut self.conn,
+            &self.cas,
+            &NoopPolicyProvider,
+            &NoopApprovalRouter,
+        )
+    }
+}
+
+fn create_ettle() -> Value {
+    json!({ "command": { "tag": "EttleCreate", "title": "Scoped" } })
+}
+
+fn error_code(resp: &McpResponse) -> Option<&str> {
+    match &resp.result {
+        McpResult::Err(e) => Some(e.error_code.as_str()),
+        McpResult::Ok(_) => None,
+    }
+}
+
+#[test]
+fn test_read_token_reads_but_cannot_write() {
+    let mut h = TestHarness::new();
+    let (_, read) = h.issue(&["read"]);
+
+    let resp = h.call("ettle_list", json!({}), Some(&read));
+    assert_eq!(error_code(&resp), None);
+    let resp = h.call("ettlex_apply", create_ettle(), Some(&read));
+    assert_eq!(error_code(&resp), Some("Forbidden"));
+}
+
+#[test]
+fn test_write_token_writes_but_cannot_approve() {
+    let mut h = TestHarness::new();
+    let (_, write) = h.issue(&["write"]);
+
+    let resp = h.call("ettlex_apply", create_ettle(), Some(&write));
+    assert_eq!(error_code(&resp), None);
+    let resp = h.call(
+        "ettlex_apply",
+        json!({ "command": {
+            "tag": "ApprovalTransition",
+            "approval_token": "apr:missing",
+            "status": "approved"
+        } }),
+        Some(&write),
+    );
+    assert_eq!(error_code(&resp), Some("Forbidden"));
+}
+
+#[test]
+fn test_missing_unknown_and_revoked_tokens_need_auth() {
+    let mut h = TestHarness::new();
+    let (token_id, admin) = h.issue(&["admin"]);
+
+    assert_eq!(
+        error_code(&h.call("ettle_list", json!({}), None)),
+        Some("AuthRequired")
+    );
+    assert_eq!(
+        error_code(&h.call("ettle_list", json!({}), Some("etx_guess"))),
+        Some("AuthRequired")
+    );
+    assert_eq!(
+        error_code(&h.call("ettlex_apply", create_ettle(), Some(&admin))),
+        None
+    );
+
+    revoke_api_token(&mut h.conn, &h.cas, &token_id).unwrap();
+    assert_eq!(
+        error_code(&h.call("ettle_list", json!({}), Some(&admin))),
+        Some("AuthRequired")
+    );
+}
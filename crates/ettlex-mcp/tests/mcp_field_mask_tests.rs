@@ -0,0 +1,141 @@
+//! Field-mask tests — `fields` trims heavyweight read tool responses to the
+//! requested top-level fields, always keeping the entity identifier.
+
+#![allow(clippy::unwrap_used)]
+
+use ettlex_core::approval_router::NoopApprovalRouter;
+use ettlex_core::policy_provider::NoopPolicyProvider;
+use ettlex_mcp::auth::AuthConfig;
+use ettlex_mcp::context::RequestContext;
+use ettlex_mcp::error::McpResult;
+use ettlex_mcp::server::{McpResponse, McpServer, McpToolCall};
+use ettlex_store::cas::FsStore;
+use rusqlite::Connection;
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+struct Harness {
+    _tmp: TempDir,
+    conn: Connection,
+    cas: FsStore,
+    server: McpServer,
+}
+
+impl Harness {
+    fn new() -> Self {
+        let tmp = TempDir::new().unwrap();
+        let mut conn = Connection::open(tmp.path().join("test.db")).unwrap();
+        ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+        let cas = FsStore::new(tmp.path().join("cas"));
+        let server = McpServer::new(AuthConfig::disabled(), 1024 * 1024);
+        Self {
+            _tmp: tmp,
+            conn,
+            cas,
+            server,
+        }
+    }
+
+    fn call(&mut self, tool: &str, params: Value) -> McpResponse {
+        let size = params.to_string().len();
+        self.server.dispatch(
+            McpToolCall {
+                tool_name: tool.to_string(),
+                params,
+                context: RequestContext::default(),
+                auth_token: None,
+                payload_size: size,
+            },
+            &mut self.conn,
+            &self.cas,
+            &NoopPolicyProvider,
+            &NoopApprovalRouter,
+        )
+    }
+
+    fn create_ettle(&mut self) -> String {
+        let resp = self.call(
+            "ettlex_apply",
+            json!({ "command": {
+                "tag": "EttleCreate",
+                "title": "Payments",
+                "why": "A long rationale body",
+                "what": "A long description body",
+            } }),
+        );
+        ok(&resp)["result"]["ettle_id"]
+            .as_str()
+            .unwrap()
+            .to_string()
+    }
+}
+
+fn ok(r: &McpResponse) -> &Value {
+    match &r.result {
+        McpResult::Ok(v) => v,
+        McpResult::Err(e) => panic!("Expected Ok, got Err: {:?}", e),
+    }
+}
+
+fn error_code(r: &McpResponse) -> &str {
+    match &r.result {
+        McpResult::Err(e) => &e.error_code,
+        McpResult::Ok(v) => panic!("Expected Err, got Ok: {}", v),
+    }
+}
+
+fn keys(v: &Value) -> Vec<&str> {
+    v.as_object().unwrap().keys().map(String::as_str).collect()
+}
+
+#[test]
+fn test_ettle_get_returns_only_requested_fields() {
+    let mut h = Harness::new();
+    let id = h.create_ettle();
+
+    let full = h.call("ettle_get", json!({ "ettle_id": id }));
+    assert!(ok(&full).get("why").is_some());
+
+    let resp = h.call("ettle_get", json!({ "ettle_id": id, "fields": ["title"] }));
+    let v = ok(&resp);
+    let mut got = keys(v);
+    got.sort();
+    assert_eq!(got, vec!["id", "title"]);
+    assert_eq!(v["title"], "Payments");
+}
+
+#[test]
+fn test_decision_list_masks_every_item() {
+    let mut h = Harness::new();
+    h.conn
+        .execute_batch(
+            "INSERT INTO decisions
+             (decision_id, title, status, decision_text, rationale,
+              evidence_kind, evidence_hash, created_at, updated_at)
+             VALUES ('d:1', 'Use Rust', 'accepted', 'We will use Rust', 'Speed', 'none', '', 0, 0),
+                    ('d:2', 'Use SQLite', 'accepted', 'Embedded store', 'Ops', 'none', '', 0, 0);",
+        )
+        .unwrap();
+
+    let resp = h.call("decision_list", json!({ "fields": ["title", "status"] }));
+    let items = ok(&resp)["items"].as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    for item in items {
+        let mut got = keys(item);
+        got.sort();
+        assert_eq!(got, vec!["decision_id", "status", "title"]);
+    }
+}
+
+#[test]
+fn test_unknown_or_malformed_fields_are_invalid_input() {
+    let mut h = Harness::new();
+    let id = h.create_ettle();
+
+    let resp = h.call("ettle_get", json!({ "ettle_id": id, "fields": ["body"] }));
+    assert_eq!(error_code(&resp), "InvalidInput");
+    let resp = h.call("ettle_get", json!({ "ettle_id": id, "fields": "title" }));
+    assert_eq!(error_code(&resp), "InvalidInput");
+    let resp = h.call("decision_list", json!({ "fields": [1] }));
+    assert_eq!(error_code(&resp), "InvalidInput");
+}
@@ -34,6 +34,7 @@
 //!   S-PA-2     test_s_pa_2_profile_conflict
 //!   S-PA-3     test_s_pa_3_profile_set_default_not_found
 //!   S-PA-4     test_s_pa_4_profile_set_default_readable
+//!   S-STAMP-1  test_s_stamp_1_read_tools_carry_state_version
 //!
 //! Deleted (EP-era, retired by Slice 03 / migration 015):
 //!   S-INV-1    test_s_inv_1_delegation_only
@@ -361,6 +362,36 @@ fn test_s_occ_2_new_state_version() {
     assert_eq!(h.state_version(), before + 1);
 }
 
+// ---------------------------------------------------------------------------
+// S-OCC-3 — dry_run previews a command without changing state
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_s_occ_3_dry_run_does_not_advance_state() {
+    let mut h = TestHarness::new();
+    let before = h.state_version();
+    let v = assert_ok(h.call(
+        "ettlex_apply",
+        json!({
+            "command": { "tag": "EttleCreate", "title": "Preview" },
+            "dry_run": true
+        }),
+    ));
+    assert_eq!(v["dry_run"], json!(true));
+    assert_eq!(v["new_state_version"].as_u64(), Some(before + 1));
+    assert!(v["result"]["ettle_id"].is_string());
+    assert_eq!(h.state_version(), before);
+
+    let resp = h.call(
+        "ettlex_apply",
+        json!({
+            "command": { "tag": "EttleCreate", "title": "Preview" },
+            "dry_run": "yes"
+        }),
+    );
+    assert_error(&resp, "InvalidInput");
+}
+
 // ---------------------------------------------------------------------------
 // S-INV-2 — Write operations call Apply only
 // ---------------------------------------------------------------------------
@@ -440,6 +471,33 @@ fn test_s_page_2_ettle_list_cursor_deterministic() {
     assert_eq!(ids1, ids1b);
 }
 
+// ---------------------------------------------------------------------------
+// S-PAGE-3 — cursor signing keys are per server, not per process
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_s_page_3_cursor_signing_key_is_per_server() {
+    let mut h = TestHarness::new();
+    h.seed_ettles(3);
+    let auth = || AuthConfig::with_token("t:dev");
+    h.server = McpServer::new(auth(), 1024 * 1024).with_cursor_signing_key(Some(b"a".to_vec()));
+
+    let v1 = assert_ok(h.call("ettle_list", json!({ "limit": 2 })));
+    let cursor = v1["cursor"].as_str().unwrap().to_string();
+    let v2 = assert_ok(h.call("ettle_list", json!({ "limit": 2, "cursor": cursor })));
+    assert_eq!(v2["items"].as_array().unwrap().len(), 1);
+
+    // A server in the same process with another key rejects the cursor
+    h.server = McpServer::new(auth(), 1024 * 1024).with_cursor_signing_key(Some(b"b".to_vec()));
+    let resp = h.call("ettle_list", json!({ "limit": 2, "cursor": cursor }));
+    assert_error(&resp, "InvalidCursor");
+
+    // ...and an unsigned server is unaffected by either
+    h.server = McpServer::new(auth(), 1024 * 1024);
+    let unsigned = assert_ok(h.call("ettle_list", json!({ "limit": 2 })));
+    assert_ne!(unsigned["cursor"].as_str().unwrap(), cursor);
+}
+
 // ---------------------------------------------------------------------------
 // S-POL-1 — policy.get via MCP is deterministic
 // ---------------------------------------------------------------------------
@@ -842,3 +900,64 @@ fn test_s_pa_4_profile_set_default_readable() {
         "default profile must be 'profile/demo@0'"
     );
 }
+
+// ---------------------------------------------------------------------------
+// S-PA-5 — ProfileSetDefault with environment scopes profile_resolve
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_s_pa_5_profile_set_default_environment() {
+    let mut h = TestHarness::new();
+    h.seed_profile("profile/global@0", r#"{"tier":"global"}"#, true);
+    h.seed_profile("profile/prod@0", r#"{"tier":"prod"}"#, false);
+
+    let resp = h.call(
+        "ettlex_apply",
+        json!({
+            "command": {
+                "tag": "ProfileSetDefault",
+                "profile_ref": "profile/prod@0",
+                "environment": "prod"
+            }
+        }),
+    );
+    let _ = assert_ok(resp);
+
+    let r = assert_ok(h.call("profile_resolve", json!({ "environment": "prod" })));
+    assert_eq!(r["profile_ref"].as_str(), Some("profile/prod@0"));
+
+    // The global default is unchanged
+    let r = assert_ok(h.call("profile_resolve", json!({})));
+    assert_eq!(r["profile_ref"].as_str(), Some("profile/global@0"));
+}
+
+// ---------------------------------------------------------------------------
+// S-STAMP-1 — Read tools are stamped with the state they observed
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_s_stamp_1_read_tools_carry_state_version() {
+    let mut h = TestHarness::new();
+    let before = h
+        .call("ettle_list", json!({}))
+        .state
+        .expect("read is stamped");
+    assert_eq!(before.state_version, h.state_version());
+
+    let apply = h.call(
+        "ettlex_apply",
+        json!({ "command": { "tag": "EttleCreate", "title": "Stamp" } }),
+    );
+    assert!(apply.state.is_none(), "apply responses are not stamped");
+    let _ = assert_ok(apply);
+
+    let after = h
+        .call("ettle_list", json!({}))
+        .state
+        .expect("read is stamped");
+    assert_eq!(after.state_version, before.state_version + 1);
+
+    // Failed reads carry no stamp
+    let missing = h.call("ettle_get", json!({ "ettle_id": "ettle:missing" }));
+    assert!(missing.state.is_none());
+}
@@ -154,6 +154,8 @@ fn test_state_get_version_returns_version() {
         v.get("state_version").is_some(),
         "response must contain state_version"
     );
+    assert!(v["summary"]["ettle_count"].is_u64());
+    assert!(v["summary"]["root_heads"].is_array());
 }
 
 // ---------------------------------------------------------------------------
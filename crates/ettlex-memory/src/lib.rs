@@ -11,9 +11,13 @@ pub mod memory_manager;
 // Re-export the engine's commands module tree so downstream crates can
 // use `ettlex_memory::commands::*` without a direct ettlex-engine dep.
 pub use ettlex_engine::commands;
+pub use ettlex_engine::feature_flags;
 
 // Top-level re-exports for convenience
-pub use ettlex_engine::commands::command::{apply_command, Command, CommandResult};
+pub use ettlex_engine::commands::command::{
+    apply_command, apply_command_with_options, Command, CommandResult,
+};
+pub use ettlex_engine::commands::engine_command::CommandOptions;
 pub use ettlex_engine::commands::engine_query::{
     apply_engine_query, EngineQuery, EngineQueryResult,
 };
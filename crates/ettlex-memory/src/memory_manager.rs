@@ -10,6 +10,7 @@ use ettlex_store::model::{GroupRecord, RelationListOpts, RelationRecord};
 use ettlex_store::repo::SqliteRepo;
 use rusqlite::Connection;
 
+use crate::commands::content_spill::hydrate_ettle_content;
 use crate::{apply_command, Command, CommandResult};
 
 /// Rich context for an Ettle, assembled from relations and group memberships.
@@ -64,23 +65,26 @@ impl MemoryManager {
     /// Assemble a rich EttleContext for the given ettle_id.
     ///
     /// Fetches:
-    /// - WHY / WHAT / HOW from the Ettle record.
+    /// - WHY / WHAT / HOW from the Ettle record, with spilled fields read
+    ///   back from `cas`.
     /// - All active outgoing relations where source_ettle_id = ettle_id.
     /// - All active groups the ettle is a member of.
     pub fn assemble_ettle_context(
         &self,
         ettle_id: &str,
         conn: &Connection,
+        cas: &FsStore,
     ) -> Result<EttleContext, ExError> {
         use ettlex_core::errors::ExErrorKind;
 
         // Get ettle record
-        let record = SqliteRepo::get_ettle_record(conn, ettle_id)?.ok_or_else(|| {
+        let mut record = SqliteRepo::get_ettle_record(conn, ettle_id)?.ok_or_else(|| {
             ExError::new(ExErrorKind::NotFound)
                 .with_op("assemble_ettle_context")
                 .with_entity_id(ettle_id)
                 .with_message(format!("Ettle not found: {}", ettle_id))
         })?;
+        hydrate_ettle_content(&mut record, cas)?;
 
         // Get active outgoing relations
         let opts = RelationListOpts {
@@ -161,7 +161,7 @@ fn test_memory_manager_assemble_context_fields() {
 
     // Assemble context
     let ctx = mm
-        .assemble_ettle_context(&ettle_id, &conn)
+        .assemble_ettle_context(&ettle_id, &conn, &cas)
         .expect("assemble should succeed");
 
     assert_eq!(ctx.ettle_id, ettle_id);
@@ -177,11 +177,78 @@ fn test_memory_manager_assemble_context_fields() {
 
     // Assemble context for a non-existent ettle should fail
     let err = mm
-        .assemble_ettle_context("ettle:nonexistent", &conn)
+        .assemble_ettle_context("ettle:nonexistent", &conn, &cas)
         .expect_err("should fail for unknown ettle");
     assert_eq!(err.kind(), ExErrorKind::NotFound);
 }
 
+#[test]
+fn test_spilled_content_reaches_context_and_patch_digest() {
+    use ettlex_memory::commands::ettle::ettle_content_digest;
+    use ettlex_memory::commands::settings::{settings_set, SETTING_CONTENT_MAX_INLINE_BYTES};
+    use ettlex_store::repo::SqliteRepo;
+
+    let (mut conn, cas, _dir) = setup_db_with_cas();
+    let mm = MemoryManager::new();
+    let ettle_id = create_ettle(&mut conn, &cas, "Spilled");
+    let digest = |conn: &Connection| {
+        let record = SqliteRepo::get_ettle_record(conn, &ettle_id)
+            .unwrap()
+            .unwrap();
+        ettle_content_digest(&record, &cas).unwrap()
+    };
+    let inline_digest = digest(&conn);
+
+    settings_set(
+        &conn,
+        SETTING_CONTENT_MAX_INLINE_BYTES,
+        Some(serde_json::json!(16)),
+    )
+    .unwrap();
+    let update = |what: &str| Command::EttleUpdate {
+        ettle_id: ettle_id.clone(),
+        title: None,
+        why: None,
+        what: Some(what.to_string()),
+        how: None,
+        reasoning_link_id: None,
+        reasoning_link_type: None,
+    };
+    let long = "a what body well over the inline limit";
+    mm.apply_command(
+        update(long),
+        None,
+        &mut conn,
+        &cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .unwrap();
+    let stored = SqliteRepo::get_ettle_record(&conn, &ettle_id)
+        .unwrap()
+        .unwrap();
+    assert!(stored.what_digest.is_some());
+    assert_eq!(stored.what, "");
+
+    let ctx = mm.assemble_ettle_context(&ettle_id, &conn, &cas).unwrap();
+    assert_eq!(ctx.what.as_deref(), Some(long));
+    assert_eq!(ctx.why.as_deref(), Some("why field"));
+
+    // The digest follows the spilled body, not the empty column
+    let spilled_digest = digest(&conn);
+    assert_ne!(spilled_digest, inline_digest);
+    mm.apply_command(
+        update("another what body well over the inline limit"),
+        None,
+        &mut conn,
+        &cas,
+        &NoopPolicyProvider,
+        &NoopApprovalRouter,
+    )
+    .unwrap();
+    assert_ne!(digest(&conn), spilled_digest);
+}
+
 // ---------------------------------------------------------------------------
 // SC-S02-76: MemoryManager.assemble_ettle_context includes relations and groups
 // ---------------------------------------------------------------------------
@@ -252,7 +319,7 @@ fn test_memory_manager_assemble_context_groups() {
 
     // Assemble context for ettle_a
     let ctx = mm
-        .assemble_ettle_context(&ettle_a, &conn)
+        .assemble_ettle_context(&ettle_a, &conn, &cas)
         .expect("assemble should succeed");
 
     // ettle_a should have one outgoing relation
@@ -270,7 +337,7 @@ fn test_memory_manager_assemble_context_groups() {
 
     // ettle_b should have no outgoing relations (it is a target, not source)
     let ctx_b = mm
-        .assemble_ettle_context(&ettle_b, &conn)
+        .assemble_ettle_context(&ettle_b, &conn, &cas)
         .expect("assemble ettle_b should succeed");
     assert!(
         ctx_b.relations.is_empty(),
@@ -0,0 +1,12 @@
+-- Migration 016: Snapshot Ledger Hash Chain
+--
+-- Adds an append-only integrity chain to the snapshots ledger. Each row's
+-- chain_hash is SHA-256(prev_chain_hash || manifest_digest), where the
+-- previous row is the one immediately before it in ledger (id) order and the
+-- genesis row uses an empty prev_chain_hash. Any retroactive edit, insert or
+-- delete of a ledger row breaks every chain hash after it.
+--
+-- SQLite has no SHA-256 builtin, so existing rows are left NULL here and are
+-- backfilled by the store on the next snapshot commit.
+
+ALTER TABLE snapshots ADD COLUMN chain_hash TEXT;
@@ -0,0 +1,13 @@
+-- Migration 017: Ettle Slugs
+--
+-- Adds a unique, human-friendly alias for every Ettle, derived from its title
+-- at creation (e.g. "Payments API" -> "payments-api", then "payments-api-2"
+-- on collision). Slugs are stable: they never change when a title is edited
+-- and are never reused, even after tombstoning.
+--
+-- Deriving slugs needs collision handling that is awkward in plain SQL, so
+-- existing rows are left NULL here and backfilled by the migration runner.
+
+ALTER TABLE ettles ADD COLUMN slug TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_ettles_slug ON ettles(slug);
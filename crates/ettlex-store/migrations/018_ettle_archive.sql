@@ -0,0 +1,8 @@
+-- Migration 018: Ettle Archive State
+--
+-- Archiving retires an Ettle without deleting it: archived Ettles are hidden
+-- from default listings, renders and new snapshot roots, but remain readable
+-- by ID and can be unarchived at any time. Archive is independent of
+-- tombstoning; an archived Ettle is still "active" for referential checks.
+
+ALTER TABLE ettles ADD COLUMN archived_at TEXT;
@@ -0,0 +1,18 @@
+-- Migration 019: Comment Threads
+--
+-- Lightweight review conversations attached to Ettles, decisions and
+-- snapshots. A thread is a root comment (parent_comment_id IS NULL) plus its
+-- replies; resolution is tracked on the root only.
+
+CREATE TABLE comments (
+    id                TEXT PRIMARY KEY,
+    target_kind       TEXT NOT NULL,
+    target_id         TEXT NOT NULL,
+    parent_comment_id TEXT REFERENCES comments(id),
+    author            TEXT,
+    body              TEXT NOT NULL,
+    created_at        TEXT NOT NULL,
+    resolved_at       TEXT
+);
+CREATE INDEX idx_comments_target ON comments(target_kind, target_id, created_at, id);
+CREATE INDEX idx_comments_parent ON comments(parent_comment_id) WHERE parent_comment_id IS NOT NULL;
@@ -0,0 +1,26 @@
+-- Migration 020: Indexes for Hot Query Paths
+--
+-- Composite indexes for the lookups that dominate traversal, snapshot commit
+-- and decision reads on large stores. The EP-era targets (eps,
+-- ep_constraint_refs) were retired in 015; their access paths now go through
+-- `relations`, which only had single-column indexes.
+
+-- Children / constraints of an Ettle by relation type (refinement traversal,
+-- constraint attachment lookups).
+CREATE INDEX IF NOT EXISTS idx_relations_source_type
+    ON relations(source_ettle_id, relation_type, target_ettle_id)
+    WHERE tombstoned_at IS NULL;
+
+-- Parents of an Ettle by relation type.
+CREATE INDEX IF NOT EXISTS idx_relations_target_type
+    ON relations(target_ettle_id, relation_type, source_ettle_id)
+    WHERE tombstoned_at IS NULL;
+
+-- Decisions linked to a target; covers decision_id for the join.
+DROP INDEX IF EXISTS idx_decision_links_target;
+CREATE INDEX IF NOT EXISTS idx_decision_links_target
+    ON decision_links(target_kind, target_id, decision_id);
+
+-- Current head for a root (WHERE root_ettle_id = ? ORDER BY created_at DESC).
+CREATE INDEX IF NOT EXISTS idx_snapshots_root_created
+    ON snapshots(root_ettle_id, created_at);
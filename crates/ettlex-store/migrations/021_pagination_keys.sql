@@ -0,0 +1,37 @@
+-- Migration 021: Integer Pagination Keys
+--
+-- relations, groups, group_members and comments store created_at as RFC 3339
+-- text with mixed precision and offset suffixes, so ordering on the text does
+-- not match chronological order and page boundaries drift. Add an integer
+-- created_at_ms column and paginate on (created_at_ms, id) instead.
+-- New rows get the value from the repo layer; existing rows are backfilled
+-- from the text timestamp.
+
+ALTER TABLE relations ADD COLUMN created_at_ms INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE groups ADD COLUMN created_at_ms INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE group_members ADD COLUMN created_at_ms INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE comments ADD COLUMN created_at_ms INTEGER NOT NULL DEFAULT 0;
+
+UPDATE relations SET created_at_ms =
+    CAST(strftime('%s', created_at) AS INTEGER) * 1000
+    + CAST(substr(strftime('%f', created_at), 4) AS INTEGER)
+WHERE strftime('%s', created_at) IS NOT NULL;
+UPDATE groups SET created_at_ms =
+    CAST(strftime('%s', created_at) AS INTEGER) * 1000
+    + CAST(substr(strftime('%f', created_at), 4) AS INTEGER)
+WHERE strftime('%s', created_at) IS NOT NULL;
+UPDATE group_members SET created_at_ms =
+    CAST(strftime('%s', created_at) AS INTEGER) * 1000
+    + CAST(substr(strftime('%f', created_at), 4) AS INTEGER)
+WHERE strftime('%s', created_at) IS NOT NULL;
+UPDATE comments SET created_at_ms =
+    CAST(strftime('%s', created_at) AS INTEGER) * 1000
+    + CAST(substr(strftime('%f', created_at), 4) AS INTEGER)
+WHERE strftime('%s', created_at) IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_relations_created ON relations(created_at_ms, id);
+CREATE INDEX IF NOT EXISTS idx_groups_created ON groups(created_at_ms, id);
+CREATE INDEX IF NOT EXISTS idx_group_members_created ON group_members(group_id, created_at_ms, id);
+
+DROP INDEX IF EXISTS idx_comments_target;
+CREATE INDEX IF NOT EXISTS idx_comments_target ON comments(target_kind, target_id, created_at_ms, id);
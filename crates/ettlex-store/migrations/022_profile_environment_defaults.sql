@@ -0,0 +1,12 @@
+-- Migration 022: Environment-scoped default profiles
+--
+-- profiles.is_default holds one repository-wide default. Deployments that
+-- run dev/staging/prod against the same store need a default per
+-- environment; store them here. Resolution for an environment with no row
+-- falls back to the global is_default profile.
+
+CREATE TABLE IF NOT EXISTS profile_environment_defaults (
+    environment  TEXT PRIMARY KEY NOT NULL,
+    profile_ref  TEXT NOT NULL REFERENCES profiles(profile_ref),
+    updated_at   INTEGER NOT NULL   -- milliseconds since epoch
+);
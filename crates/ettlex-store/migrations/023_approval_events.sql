@@ -0,0 +1,23 @@
+-- Migration 023: Approval lifecycle events
+--
+-- approval_requests.status only holds the latest state. Record every
+-- transition (created → approved / rejected / expired) with its actor and
+-- time so the full lifecycle can be audited. from_status is NULL for the
+-- creation event. Existing requests are backfilled with a creation event
+-- carrying their current status; earlier transitions were never recorded.
+
+CREATE TABLE IF NOT EXISTS approval_events (
+    id              INTEGER PRIMARY KEY AUTOINCREMENT,
+    approval_token  TEXT NOT NULL REFERENCES approval_requests(approval_token),
+    from_status     TEXT,
+    to_status       TEXT NOT NULL,
+    actor           TEXT,
+    occurred_at     INTEGER NOT NULL   -- milliseconds since epoch
+);
+
+CREATE INDEX IF NOT EXISTS idx_approval_events_token ON approval_events(approval_token, id);
+
+INSERT INTO approval_events (approval_token, from_status, to_status, actor, occurred_at)
+SELECT approval_token, NULL, status, NULL, created_at
+FROM approval_requests
+ORDER BY created_at, approval_token;
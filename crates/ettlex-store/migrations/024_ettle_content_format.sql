@@ -0,0 +1,7 @@
+-- Migration 024: Ettle content format
+--
+-- Declares how an Ettle's why/what/how text is written so renderers can
+-- inline Markdown and fence everything else. One of 'markdown', 'asciidoc'
+-- or 'plain'; existing Ettles were all written as Markdown.
+
+ALTER TABLE ettles ADD COLUMN content_format TEXT NOT NULL DEFAULT 'markdown';
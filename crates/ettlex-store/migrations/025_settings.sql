@@ -0,0 +1,11 @@
+-- Migration 025: Repository settings
+--
+-- Key-value settings stored with the repository, such as feature flags
+-- (`feature.<name>`). Values are JSON so each key can carry its own type;
+-- callers validate keys and values. A missing row means "use the default".
+
+CREATE TABLE IF NOT EXISTS settings (
+    key         TEXT PRIMARY KEY,
+    value_json  TEXT NOT NULL,
+    updated_at  INTEGER NOT NULL   -- milliseconds since epoch
+);
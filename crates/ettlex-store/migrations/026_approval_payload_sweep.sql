@@ -0,0 +1,8 @@
+-- Migration 026: Approval payload sweep marker
+--
+-- Rejected and expired approval requests past their retention window have
+-- their CAS payload blob deleted. The sweep clears request_digest and records
+-- when it ran, so a later approval.get can report the payload as swept
+-- rather than as missing storage.
+
+ALTER TABLE approval_requests ADD COLUMN payload_swept_at INTEGER;  -- milliseconds since epoch
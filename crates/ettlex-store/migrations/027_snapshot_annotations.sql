@@ -0,0 +1,9 @@
+-- Migration 027: Snapshot commit messages and annotations
+--
+-- A snapshot may carry a free-text message and string key/value
+-- annotations, like a commit message. They live on the ledger row only:
+-- neither is part of the manifest, so neither affects the manifest,
+-- semantic or chain digests.
+
+ALTER TABLE snapshots ADD COLUMN message TEXT;
+ALTER TABLE snapshots ADD COLUMN annotations_json TEXT;  -- JSON object of strings; NULL when none
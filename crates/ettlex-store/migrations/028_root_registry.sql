@@ -0,0 +1,17 @@
+-- Migration 028: Root Ettle Registry
+--
+-- Roots were implicit: any active Ettle without an active refinement parent.
+-- The registry records the roots that are meant to exist, with a display
+-- name, an optional owner and a status. Retired roots accept no new
+-- snapshots. When the `roots.mode` setting is `registered`, tools that could
+-- create a new parentless Ettle (CSV import, patches) refuse unregistered
+-- ones.
+
+CREATE TABLE roots (
+    ettle_id      TEXT PRIMARY KEY REFERENCES ettles(id),
+    display_name  TEXT NOT NULL UNIQUE,
+    owner         TEXT,
+    status        TEXT NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'retired')),
+    registered_at TEXT NOT NULL,
+    updated_at    TEXT NOT NULL
+);
@@ -0,0 +1,15 @@
+-- Migration 029: API Tokens
+--
+-- Bearer tokens for server access. Only the SHA-256 of each secret is
+-- stored; the secret itself is shown once, at issuance. Scopes are a JSON
+-- array drawn from read, write, approve and admin. Revoked tokens keep their
+-- row so issuance history stays auditable.
+
+CREATE TABLE api_tokens (
+    token_id    TEXT PRIMARY KEY,
+    name        TEXT NOT NULL,
+    token_hash  TEXT NOT NULL UNIQUE,
+    scopes_json TEXT NOT NULL,
+    created_at  TEXT NOT NULL,
+    revoked_at  TEXT
+);
@@ -0,0 +1,28 @@
+-- Migration 030: Import Sessions
+--
+-- A bulk import that commits in several transactions can be cut off between
+-- them, leaving part of its rows behind. Each import opens a session row
+-- before writing anything and records every Ettle and relation it creates
+-- in the same transaction as the entity itself, so an interrupted session
+-- can later be found (status `running`) and either rolled back or resumed
+-- from the source file, whose digest is kept to detect edits in between.
+
+CREATE TABLE import_sessions (
+    session_id    TEXT PRIMARY KEY,
+    kind          TEXT NOT NULL,
+    source_path   TEXT NOT NULL,
+    source_digest TEXT NOT NULL,
+    options_json  TEXT NOT NULL DEFAULT '{}',
+    status        TEXT NOT NULL DEFAULT 'running'
+                  CHECK (status IN ('running', 'completed', 'rolled_back')),
+    started_at    TEXT NOT NULL,
+    finished_at   TEXT
+);
+
+CREATE TABLE import_session_items (
+    session_id  TEXT NOT NULL REFERENCES import_sessions(session_id),
+    item_kind   TEXT NOT NULL CHECK (item_kind IN ('ettle', 'relation')),
+    source_line INTEGER NOT NULL,
+    entity_id   TEXT NOT NULL,
+    PRIMARY KEY (session_id, item_kind, source_line)
+);
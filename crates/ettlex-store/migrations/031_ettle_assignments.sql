@@ -0,0 +1,17 @@
+-- Migration 031: Ettle Ownership and Review Assignments
+--
+-- Names the people responsible for an Ettle: at most one owner and any
+-- number of reviewers. Principals are free-form strings (a handle, an email
+-- address, a team name) so that routing integrations can map them however
+-- they like.
+
+CREATE TABLE ettle_assignments (
+    ettle_id    TEXT NOT NULL REFERENCES ettles(id),
+    role        TEXT NOT NULL CHECK (role IN ('owner', 'reviewer')),
+    principal   TEXT NOT NULL,
+    assigned_at TEXT NOT NULL,
+    PRIMARY KEY (ettle_id, role, principal)
+);
+CREATE UNIQUE INDEX idx_ettle_assignments_one_owner
+    ON ettle_assignments(ettle_id) WHERE role = 'owner';
+CREATE INDEX idx_ettle_assignments_principal ON ettle_assignments(principal, role);
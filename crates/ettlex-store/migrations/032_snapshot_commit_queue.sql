@@ -0,0 +1,23 @@
+-- Migration 032: Snapshot Commit Queue
+--
+-- Optional queued mode for snapshot commits. Clients enqueue a commit
+-- request and get a ticket back; a worker drains the queue in `seq` order and
+-- applies each request through the normal commit path, one at a time. That
+-- gives first-come-first-served fairness between bursty clients, and each
+-- request's `expected_head` is checked against the head left by the one
+-- before it. `request_json` holds the commit arguments. `outcome_json` is
+-- set once the ticket leaves `queued`/`applying`.
+
+CREATE TABLE snapshot_commit_queue (
+    seq           INTEGER PRIMARY KEY AUTOINCREMENT,
+    ticket        TEXT NOT NULL UNIQUE,
+    leaf_ettle_id TEXT NOT NULL,
+    request_json  TEXT NOT NULL,
+    status        TEXT NOT NULL DEFAULT 'queued'
+                  CHECK (status IN ('queued', 'applying', 'committed', 'routed', 'failed')),
+    enqueued_at   TEXT NOT NULL,
+    applied_at    TEXT,
+    outcome_json  TEXT
+);
+
+CREATE INDEX idx_snapshot_commit_queue_status ON snapshot_commit_queue(status, seq);
@@ -0,0 +1,11 @@
+-- Migration 033: Ettle content spillover
+--
+-- A why/what/how body larger than the `content.max_inline_bytes` setting is
+-- written to CAS instead of the row. Its column then holds '' and the
+-- matching `*_digest` column holds the CAS digest. NULL means the content is
+-- inline. The digest columns are CAS references, so blobs they name are
+-- never garbage.
+
+ALTER TABLE ettles ADD COLUMN why_digest TEXT;
+ALTER TABLE ettles ADD COLUMN what_digest TEXT;
+ALTER TABLE ettles ADD COLUMN how_digest TEXT;
@@ -0,0 +1,22 @@
+-- Migration 034: Command Journal
+--
+-- Undo/redo history for reversible write commands. Each row records one
+-- applied command as two JSON arrays of steps: `undo_json` reverts it and
+-- `redo_json` reapplies it against the same entity IDs. `status` is
+-- 'applied' until the entry is undone. Undo takes the newest applied entry;
+-- redo takes the oldest undone one. Recording a new entry discards every
+-- undone entry, as in an editor.
+
+CREATE TABLE command_journal (
+    seq         INTEGER PRIMARY KEY AUTOINCREMENT,
+    op          TEXT NOT NULL,
+    entity_id   TEXT,
+    undo_json   TEXT NOT NULL,
+    redo_json   TEXT NOT NULL,
+    status      TEXT NOT NULL DEFAULT 'applied'
+                CHECK (status IN ('applied', 'undone')),
+    recorded_at TEXT NOT NULL,
+    undone_at   TEXT
+);
+
+CREATE INDEX idx_command_journal_status ON command_journal(status, seq);
@@ -0,0 +1,10 @@
+-- Migration 035: Command log events
+--
+-- Each `command_log` row now names the command it applied (`op`, e.g.
+-- `ettle_update`) and the entity it targeted or created, so the log doubles
+-- as the store's event history. `id` is AUTOINCREMENT and rows are never
+-- updated or deleted, so it serves as an append-only sequence number. Rows
+-- written before this migration have NULL `op` and `entity_id`.
+
+ALTER TABLE command_log ADD COLUMN op TEXT;
+ALTER TABLE command_log ADD COLUMN entity_id TEXT;
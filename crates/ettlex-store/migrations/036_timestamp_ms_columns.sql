@@ -0,0 +1,109 @@
+-- Migration 036: Millisecond Timestamp Columns
+--
+-- Timestamps added since migration 019 were stored two ways: INTEGER
+-- milliseconds under a bare `*_at` name (022, 023, 025, 026) or RFC 3339
+-- text (019, 028 to 032, 034). Standardize them on integer milliseconds
+-- since epoch in `*_at_ms` columns, as 021 did for pagination keys.
+--
+-- Integer columns are renamed. Text columns get an `*_at_ms` column
+-- backfilled from the text timestamp, and the text column is dropped.
+-- comments.created_at already has its created_at_ms partner from 021.
+
+-- ---------------------------------------------------------------------------
+-- Integer milliseconds: rename only
+-- ---------------------------------------------------------------------------
+
+ALTER TABLE profile_environment_defaults RENAME COLUMN updated_at TO updated_at_ms;
+ALTER TABLE approval_events RENAME COLUMN occurred_at TO occurred_at_ms;
+ALTER TABLE settings RENAME COLUMN updated_at TO updated_at_ms;
+ALTER TABLE approval_requests RENAME COLUMN payload_swept_at TO payload_swept_at_ms;
+
+-- ---------------------------------------------------------------------------
+-- RFC 3339 text: add, backfill, drop
+-- ---------------------------------------------------------------------------
+
+-- comments (019)
+ALTER TABLE comments ADD COLUMN resolved_at_ms INTEGER;
+UPDATE comments SET resolved_at_ms =
+    CAST(strftime('%s', resolved_at) AS INTEGER) * 1000
+    + CAST(substr(strftime('%f', resolved_at), 4) AS INTEGER)
+WHERE strftime('%s', resolved_at) IS NOT NULL;
+ALTER TABLE comments DROP COLUMN resolved_at;
+
+-- roots (028)
+ALTER TABLE roots ADD COLUMN registered_at_ms INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE roots ADD COLUMN updated_at_ms INTEGER NOT NULL DEFAULT 0;
+UPDATE roots SET registered_at_ms =
+    CAST(strftime('%s', registered_at) AS INTEGER) * 1000
+    + CAST(substr(strftime('%f', registered_at), 4) AS INTEGER)
+WHERE strftime('%s', registered_at) IS NOT NULL;
+UPDATE roots SET updated_at_ms =
+    CAST(strftime('%s', updated_at) AS INTEGER) * 1000
+    + CAST(substr(strftime('%f', updated_at), 4) AS INTEGER)
+WHERE strftime('%s', updated_at) IS NOT NULL;
+ALTER TABLE roots DROP COLUMN registered_at;
+ALTER TABLE roots DROP COLUMN updated_at;
+
+-- api_tokens (029)
+ALTER TABLE api_tokens ADD COLUMN created_at_ms INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE api_tokens ADD COLUMN revoked_at_ms INTEGER;
+UPDATE api_tokens SET created_at_ms =
+    CAST(strftime('%s', created_at) AS INTEGER) * 1000
+    + CAST(substr(strftime('%f', created_at), 4) AS INTEGER)
+WHERE strftime('%s', created_at) IS NOT NULL;
+UPDATE api_tokens SET revoked_at_ms =
+    CAST(strftime('%s', revoked_at) AS INTEGER) * 1000
+    + CAST(substr(strftime('%f', revoked_at), 4) AS INTEGER)
+WHERE strftime('%s', revoked_at) IS NOT NULL;
+ALTER TABLE api_tokens DROP COLUMN created_at;
+ALTER TABLE api_tokens DROP COLUMN revoked_at;
+
+-- import_sessions (030)
+ALTER TABLE import_sessions ADD COLUMN started_at_ms INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE import_sessions ADD COLUMN finished_at_ms INTEGER;
+UPDATE import_sessions SET started_at_ms =
+    CAST(strftime('%s', started_at) AS INTEGER) * 1000
+    + CAST(substr(strftime('%f', started_at), 4) AS INTEGER)
+WHERE strftime('%s', started_at) IS NOT NULL;
+UPDATE import_sessions SET finished_at_ms =
+    CAST(strftime('%s', finished_at) AS INTEGER) * 1000
+    + CAST(substr(strftime('%f', finished_at), 4) AS INTEGER)
+WHERE strftime('%s', finished_at) IS NOT NULL;
+ALTER TABLE import_sessions DROP COLUMN started_at;
+ALTER TABLE import_sessions DROP COLUMN finished_at;
+
+-- ettle_assignments (031)
+ALTER TABLE ettle_assignments ADD COLUMN assigned_at_ms INTEGER NOT NULL DEFAULT 0;
+UPDATE ettle_assignments SET assigned_at_ms =
+    CAST(strftime('%s', assigned_at) AS INTEGER) * 1000
+    + CAST(substr(strftime('%f', assigned_at), 4) AS INTEGER)
+WHERE strftime('%s', assigned_at) IS NOT NULL;
+ALTER TABLE ettle_assignments DROP COLUMN assigned_at;
+
+-- snapshot_commit_queue (032)
+ALTER TABLE snapshot_commit_queue ADD COLUMN enqueued_at_ms INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE snapshot_commit_queue ADD COLUMN applied_at_ms INTEGER;
+UPDATE snapshot_commit_queue SET enqueued_at_ms =
+    CAST(strftime('%s', enqueued_at) AS INTEGER) * 1000
+    + CAST(substr(strftime('%f', enqueued_at), 4) AS INTEGER)
+WHERE strftime('%s', enqueued_at) IS NOT NULL;
+UPDATE snapshot_commit_queue SET applied_at_ms =
+    CAST(strftime('%s', applied_at) AS INTEGER) * 1000
+    + CAST(substr(strftime('%f', applied_at), 4) AS INTEGER)
+WHERE strftime('%s', applied_at) IS NOT NULL;
+ALTER TABLE snapshot_commit_queue DROP COLUMN enqueued_at;
+ALTER TABLE snapshot_commit_queue DROP COLUMN applied_at;
+
+-- command_journal (034)
+ALTER TABLE command_journal ADD COLUMN recorded_at_ms INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE command_journal ADD COLUMN undone_at_ms INTEGER;
+UPDATE command_journal SET recorded_at_ms =
+    CAST(strftime('%s', recorded_at) AS INTEGER) * 1000
+    + CAST(substr(strftime('%f', recorded_at), 4) AS INTEGER)
+WHERE strftime('%s', recorded_at) IS NOT NULL;
+UPDATE command_journal SET undone_at_ms =
+    CAST(strftime('%s', undone_at) AS INTEGER) * 1000
+    + CAST(substr(strftime('%f', undone_at), 4) AS INTEGER)
+WHERE strftime('%s', undone_at) IS NOT NULL;
+ALTER TABLE command_journal DROP COLUMN recorded_at;
+ALTER TABLE command_journal DROP COLUMN undone_at;
@@ -0,0 +1,151 @@
+//! Online backup of the store: a consistent SQLite copy plus a CAS manifest.
+//!
+//! ```text
+//! <out>/
+//!   store.db               page-level copy made with SQLite's backup API
+//!   backup-manifest.json   database digest and every CAS blob (digest, size)
+//! ```
+//!
+//! The database is copied in a single backup step, so the copy reflects one
+//! point in time even while other connections write or `VACUUM`. CAS is
+//! append-only and blobs are written before the rows that reference them are
+//! committed, so listing CAS *after* the copy yields a superset of what the
+//! copied database needs. Blobs themselves are not copied; the manifest is
+//! what a caller (CLI, HTTP handler) uses to ship or verify them.
+//!
+//! The manifest is written last: a directory without one is an incomplete
+//! backup.
+
+#![allow(clippy::result_large_err)]
+
+use std::fs::File;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use ettlex_core::errors::{ExError, ExErrorKind};
+use rusqlite::backup::{Backup, StepResult};
+use rusqlite::Connection;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::cas::{CasBlob, FsStore};
+use crate::db::BUSY_TIMEOUT;
+use crate::errors::{from_rusqlite, io_error, Result};
+
+/// Database copy inside the backup directory.
+pub const BACKUP_DB_FILE: &str = "store.db";
+
+/// Manifest written once the backup is complete.
+pub const BACKUP_MANIFEST_FILE: &str = "backup-manifest.json";
+
+const BACKUP_FORMAT_VERSION: u32 = 1;
+
+const BUSY_RETRY_PAUSE: Duration = Duration::from_millis(50);
+
+/// Contents of `backup-manifest.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BackupManifest {
+    pub format_version: u32,
+    /// RFC 3339 time the backup finished.
+    pub created_at: String,
+    pub db: BackupDbEntry,
+    /// Every CAS blob present after the copy, sorted by digest.
+    pub cas_blobs: Vec<CasBlob>,
+    pub cas_total_bytes: u64,
+}
+
+/// The copied database file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BackupDbEntry {
+    /// File name relative to the backup directory.
+    pub file: String,
+    /// SHA256 of the copied file.
+    pub sha256: String,
+    pub size_bytes: u64,
+}
+
+/// Back up the database behind `conn` and list `cas` into `out_dir`.
+///
+/// `out_dir` is created if missing and must otherwise be empty.
+///
+/// # Errors
+/// * `InvalidInput` - `out_dir` exists and is not empty.
+/// * `Persistence` - the SQLite backup failed.
+/// * `Serialization` - the manifest could not be encoded.
+/// * `Io` - reading CAS or writing the output failed.
+pub fn backup_store(conn: &Connection, cas: &FsStore, out_dir: &Path) -> Result<BackupManifest> {
+    prepare_out_dir(out_dir)?;
+
+    let db_path = out_dir.join(BACKUP_DB_FILE);
+    {
+        let mut dst = Connection::open(&db_path).map_err(from_rusqlite)?;
+        let backup = Backup::new(conn, &mut dst).map_err(from_rusqlite)?;
+        copy_all_pages(&backup)?;
+    }
+    let db = BackupDbEntry {
+        file: BACKUP_DB_FILE.to_string(),
+        sha256: file_sha256(&db_path)?,
+        size_bytes: std::fs::metadata(&db_path)
+            .map_err(|e| io_error("backup_store", e))?
+            .len(),
+    };
+
+    let cas_blobs = cas.list_blobs()?;
+    let manifest = BackupManifest {
+        format_version: BACKUP_FORMAT_VERSION,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        db,
+        cas_total_bytes: cas_blobs.iter().map(|b| b.size_bytes).sum(),
+        cas_blobs,
+    };
+
+    let bytes = serde_json::to_vec_pretty(&manifest).map_err(|e| {
+        ExError::new(ExErrorKind::Serialization)
+            .with_op("backup_store")
+            .with_message(format!("failed to serialize backup manifest: {}", e))
+    })?;
+    std::fs::write(out_dir.join(BACKUP_MANIFEST_FILE), bytes)
+        .map_err(|e| io_error("backup_store", e))?;
+    Ok(manifest)
+}
+
+/// Copy every page in one step, holding the source read lock for the
+/// duration instead of restarting on concurrent writes. A busy source is
+/// retried for up to [`BUSY_TIMEOUT`].
+fn copy_all_pages(backup: &Backup<'_, '_>) -> Result<()> {
+    let deadline = Instant::now() + BUSY_TIMEOUT;
+    loop {
+        match backup.step(-1).map_err(from_rusqlite)? {
+            StepResult::Done => return Ok(()),
+            _ if Instant::now() >= deadline => {
+                return Err(ExError::new(ExErrorKind::Persistence)
+                    .with_op("backup_store")
+                    .with_message("database stayed locked for the whole backup timeout"));
+            }
+            _ => std::thread::sleep(BUSY_RETRY_PAUSE),
+        }
+    }
+}
+
+fn prepare_out_dir(out_dir: &Path) -> Result<()> {
+    if out_dir.exists() {
+        let mut entries = std::fs::read_dir(out_dir).map_err(|e| io_error("backup_store", e))?;
+        if entries.next().is_some() {
+            return Err(ExError::new(ExErrorKind::InvalidInput)
+                .with_op("backup_store")
+                .with_message(format!(
+                    "output directory is not empty: {}",
+                    out_dir.display()
+                )));
+        }
+        return Ok(());
+    }
+    std::fs::create_dir_all(out_dir).map_err(|e| io_error("backup_store", e))
+}
+
+fn file_sha256(path: &Path) -> Result<String> {
+    let mut file = File::open(path).map_err(|e| io_error("backup_store", e))?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).map_err(|e| io_error("backup_store", e))?;
+    Ok(hex::encode(hasher.finalize()))
+}
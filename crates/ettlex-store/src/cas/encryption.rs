@@ -0,0 +1,352 @@
+//! Envelope encryption for CAS blobs at rest
+//!
+//! Each blob gets a fresh random data key. The blob is sealed with that key
+//! using AES-256-GCM, and the data key is sealed with a key encryption key
+//! (KEK) supplied by a [`KeyProvider`]. The on-disk layout is
+//!
+//! ```text
+//! MAGIC | key_id_len (u8) | key_id | wrap_nonce (12) | wrapped_dek (48) | data_nonce (12) | ciphertext+tag
+//! ```
+//!
+//! Digests are still computed over the plaintext, so blob identity, dedup
+//! and manifests are unaffected by turning encryption on. The data
+//! ciphertext is bound to its digest as associated data, so an encrypted
+//! blob copied to another digest's path fails to open.
+
+#![allow(clippy::result_large_err)]
+
+use std::collections::BTreeMap;
+
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+
+use crate::errors::Result;
+
+/// Prefix marking an encrypted blob
+pub const ENVELOPE_MAGIC: &[u8; 8] = b"EXCASE01";
+
+/// Length of key encryption keys and data keys, in bytes
+pub const KEY_LEN: usize = 32;
+
+const TAG_LEN: usize = 16;
+const WRAPPED_DEK_LEN: usize = KEY_LEN + TAG_LEN;
+
+/// Supplies the key encryption keys that wrap per-blob data keys
+///
+/// Implementations may fetch keys from a KMS, a keyring or configuration.
+/// Keys are looked up by ID on read, so retired keys must stay resolvable
+/// for as long as blobs sealed with them exist.
+pub trait KeyProvider: Send + Sync {
+    /// ID and key used to seal new blobs
+    ///
+    /// # Errors
+    /// Provider-specific; propagated from the write that needed the key.
+    fn current_key(&self) -> Result<(String, [u8; KEY_LEN])>;
+
+    /// Key with the given ID
+    ///
+    /// # Errors
+    /// * `NotFound` - the provider does not know `key_id`
+    fn key(&self, key_id: &str) -> Result<[u8; KEY_LEN]>;
+}
+
+/// A provider holding an in-memory current key, plus any retired keys
+/// still needed to read blobs sealed before a rotation
+#[derive(Clone)]
+pub struct StaticKeyProvider {
+    key_id: String,
+    key: [u8; KEY_LEN],
+    retired: BTreeMap<String, [u8; KEY_LEN]>,
+}
+
+impl StaticKeyProvider {
+    /// Wrap a raw 32-byte key under `key_id`
+    ///
+    /// # Errors
+    /// * `InvalidInput` - `key_id` is empty or longer than 255 bytes
+    pub fn new(key_id: impl Into<String>, key: [u8; KEY_LEN]) -> Result<Self> {
+        let key_id = key_id.into();
+        check_key_id(&key_id)?;
+        Ok(Self {
+            key_id,
+            key,
+            retired: BTreeMap::new(),
+        })
+    }
+
+    /// Keep `key` resolvable for reads under `key_id` without sealing new
+    /// blobs with it
+    ///
+    /// # Errors
+    /// * `InvalidInput` - bad key ID, or `key_id` is the current key's ID
+    pub fn with_retired_key(
+        mut self,
+        key_id: impl Into<String>,
+        key: [u8; KEY_LEN],
+    ) -> Result<Self> {
+        let key_id = key_id.into();
+        check_key_id(&key_id)?;
+        if key_id == self.key_id {
+            return Err(ExError::new(ExErrorKind::InvalidInput)
+                .with_op("cas_key_provider")
+                .with_message(format!("key ID '{}' is already the current key", key_id)));
+        }
+        self.retired.insert(key_id, key);
+        Ok(self)
+    }
+
+    /// ID of the key new blobs are sealed with
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// Parse a key given as 64 hex characters
+    ///
+    /// # Errors
+    /// * `InvalidInput` - bad key ID, or `hex_key` is not 32 bytes of hex
+    pub fn from_hex(key_id: impl Into<String>, hex_key: &str) -> Result<Self> {
+        Self::new(key_id, parse_hex_key(hex_key)?)
+    }
+}
+
+/// Parse a 32-byte key given as 64 hex characters
+///
+/// # Errors
+/// * `InvalidInput` - `hex_key` is not 32 bytes of hex
+pub fn parse_hex_key(hex_key: &str) -> Result<[u8; KEY_LEN]> {
+    hex::decode(hex_key.trim())
+        .ok()
+        .and_then(|bytes| <[u8; KEY_LEN]>::try_from(bytes).ok())
+        .ok_or_else(|| {
+            ExError::new(ExErrorKind::InvalidInput)
+                .with_op("cas_key_provider")
+                .with_message("encryption key must be 64 hex characters")
+        })
+}
+
+fn check_key_id(key_id: &str) -> Result<()> {
+    if key_id.is_empty() || key_id.len() > u8::MAX as usize {
+        return Err(ExError::new(ExErrorKind::InvalidInput)
+            .with_op("cas_key_provider")
+            .with_message("key ID must be 1 to 255 bytes"));
+    }
+    Ok(())
+}
+
+impl std::fmt::Debug for StaticKeyProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StaticKeyProvider")
+            .field("key_id", &self.key_id)
+            .field("retired", &self.retired.keys().collect::<Vec<_>>())
+            .finish_non_exhaustive()
+    }
+}
+
+impl KeyProvider for StaticKeyProvider {
+    fn current_key(&self) -> Result<(String, [u8; KEY_LEN])> {
+        Ok((self.key_id.clone(), self.key))
+    }
+
+    fn key(&self, key_id: &str) -> Result<[u8; KEY_LEN]> {
+        if key_id == self.key_id {
+            return Ok(self.key);
+        }
+        self.retired
+            .get(key_id)
+            .copied()
+            .ok_or_else(|| unknown_key(key_id))
+    }
+}
+
+/// Whether `bytes` carry the encrypted-blob prefix
+pub fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(ENVELOPE_MAGIC)
+}
+
+/// Key ID an encrypted blob was sealed with, if `bytes` is a well-formed envelope header
+pub fn envelope_key_id(bytes: &[u8]) -> Option<&str> {
+    let rest = bytes.strip_prefix(ENVELOPE_MAGIC.as_slice())?;
+    let (&len, rest) = rest.split_first()?;
+    rest.get(..len as usize)
+        .and_then(|id| std::str::from_utf8(id).ok())
+}
+
+/// Seal `plaintext` (whose digest is `digest`) with a fresh data key
+///
+/// # Errors
+/// * Errors from [`KeyProvider::current_key`]
+/// * `Internal` - the system random source or cipher failed
+pub fn seal(provider: &dyn KeyProvider, digest: &str, plaintext: &[u8]) -> Result<Vec<u8>> {
+    let (key_id, kek) = provider.current_key()?;
+    let id_len = u8::try_from(key_id.len())
+        .ok()
+        .filter(|len| *len > 0)
+        .ok_or_else(|| {
+            ExError::new(ExErrorKind::InvalidInput)
+                .with_op("cas_encrypt")
+                .with_message("key ID must be 1 to 255 bytes")
+        })?;
+
+    let rng = SystemRandom::new();
+    let mut dek = [0u8; KEY_LEN];
+    rng.fill(&mut dek)
+        .map_err(|_| crypto_failure("cas_encrypt"))?;
+    let wrap_nonce = random_nonce(&rng)?;
+    let data_nonce = random_nonce(&rng)?;
+
+    let mut wrapped = dek.to_vec();
+    cipher(&kek, "cas_encrypt")?
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(wrap_nonce),
+            Aad::from(key_id.as_bytes()),
+            &mut wrapped,
+        )
+        .map_err(|_| crypto_failure("cas_encrypt"))?;
+
+    let mut body = plaintext.to_vec();
+    cipher(&dek, "cas_encrypt")?
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(data_nonce),
+            Aad::from(digest.as_bytes()),
+            &mut body,
+        )
+        .map_err(|_| crypto_failure("cas_encrypt"))?;
+
+    let mut out = Vec::with_capacity(
+        ENVELOPE_MAGIC.len() + 1 + key_id.len() + 2 * NONCE_LEN + WRAPPED_DEK_LEN + body.len(),
+    );
+    out.extend_from_slice(ENVELOPE_MAGIC);
+    out.push(id_len);
+    out.extend_from_slice(key_id.as_bytes());
+    out.extend_from_slice(&wrap_nonce);
+    out.extend_from_slice(&wrapped);
+    out.extend_from_slice(&data_nonce);
+    out.extend_from_slice(&body);
+    Ok(out)
+}
+
+/// Open an envelope produced by [`seal`] for the blob `digest`
+///
+/// # Errors
+/// * `NotFound` - the provider does not know the envelope's key ID
+/// * `InvariantViolation` - the envelope is truncated or fails authentication
+///   (wrong key, tampered bytes, or an envelope belonging to another digest)
+pub fn open(provider: &dyn KeyProvider, digest: &str, envelope: &[u8]) -> Result<Vec<u8>> {
+    let key_id = envelope_key_id(envelope).ok_or_else(|| malformed(digest))?;
+    let header = ENVELOPE_MAGIC.len() + 1 + key_id.len();
+    let rest = &envelope[header..];
+    if rest.len() < 2 * NONCE_LEN + WRAPPED_DEK_LEN + TAG_LEN {
+        return Err(malformed(digest));
+    }
+    let (wrap_nonce, rest) = rest.split_at(NONCE_LEN);
+    let (wrapped, rest) = rest.split_at(WRAPPED_DEK_LEN);
+    let (data_nonce, body) = rest.split_at(NONCE_LEN);
+
+    let kek = provider.key(key_id)?;
+    let mut dek = wrapped.to_vec();
+    let dek = cipher(&kek, "cas_decrypt")?
+        .open_in_place(nonce(wrap_nonce)?, Aad::from(key_id.as_bytes()), &mut dek)
+        .map_err(|_| malformed(digest))?
+        .to_vec();
+
+    let mut plaintext = body.to_vec();
+    let len = cipher(&dek, "cas_decrypt")?
+        .open_in_place(
+            nonce(data_nonce)?,
+            Aad::from(digest.as_bytes()),
+            &mut plaintext,
+        )
+        .map_err(|_| malformed(digest))?
+        .len();
+    plaintext.truncate(len);
+    Ok(plaintext)
+}
+
+fn cipher(key: &[u8], op: &str) -> Result<LessSafeKey> {
+    UnboundKey::new(&AES_256_GCM, key)
+        .map(LessSafeKey::new)
+        .map_err(|_| crypto_failure(op))
+}
+
+fn random_nonce(rng: &SystemRandom) -> Result<[u8; NONCE_LEN]> {
+    let mut nonce = [0u8; NONCE_LEN];
+    rng.fill(&mut nonce)
+        .map_err(|_| crypto_failure("cas_encrypt"))?;
+    Ok(nonce)
+}
+
+fn nonce(bytes: &[u8]) -> Result<Nonce> {
+    Nonce::try_assume_unique_for_key(bytes).map_err(|_| crypto_failure("cas_decrypt"))
+}
+
+fn unknown_key(key_id: &str) -> ExError {
+    ExError::new(ExErrorKind::NotFound)
+        .with_op("cas_decrypt")
+        .with_entity_id(key_id)
+        .with_message(format!("no encryption key with ID '{}'", key_id))
+}
+
+fn malformed(digest: &str) -> ExError {
+    ExError::new(ExErrorKind::InvariantViolation)
+        .with_op("cas_decrypt")
+        .with_entity_id(digest)
+        .with_message(format!(
+            "encrypted CAS blob {} failed to decrypt (wrong key or corrupt envelope)",
+            digest
+        ))
+}
+
+fn crypto_failure(op: &str) -> ExError {
+    ExError::new(ExErrorKind::Internal)
+        .with_op(op.to_string())
+        .with_message("cryptographic operation failed")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider(id: &str, byte: u8) -> StaticKeyProvider {
+        StaticKeyProvider::new(id, [byte; KEY_LEN]).unwrap()
+    }
+
+    #[test]
+    fn test_seal_open_roundtrip() {
+        let p = provider("k1", 7);
+        let sealed = seal(&p, "abc", b"secret architecture").unwrap();
+        assert!(is_encrypted(&sealed));
+        assert_eq!(envelope_key_id(&sealed), Some("k1"));
+        assert!(!sealed.windows(b"secret".len()).any(|w| w == b"secret"));
+        assert_eq!(open(&p, "abc", &sealed).unwrap(), b"secret architecture");
+    }
+
+    #[test]
+    fn test_open_rejects_wrong_key_digest_and_tampering() {
+        let p = provider("k1", 7);
+        let sealed = seal(&p, "abc", b"body").unwrap();
+
+        let other = provider("k1", 8);
+        let err = open(&other, "abc", &sealed).unwrap_err();
+        assert_eq!(err.kind(), ExErrorKind::InvariantViolation);
+
+        let err = open(&p, "def", &sealed).unwrap_err();
+        assert_eq!(err.kind(), ExErrorKind::InvariantViolation);
+
+        let mut tampered = sealed.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        let err = open(&p, "abc", &tampered).unwrap_err();
+        assert_eq!(err.kind(), ExErrorKind::InvariantViolation);
+
+        let err = open(&provider("k2", 7), "abc", &sealed).unwrap_err();
+        assert_eq!(err.kind(), ExErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_from_hex_validates_length() {
+        assert!(StaticKeyProvider::from_hex("k", &"ab".repeat(32)).is_ok());
+        let err = StaticKeyProvider::from_hex("k", "abcd").unwrap_err();
+        assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+    }
+}
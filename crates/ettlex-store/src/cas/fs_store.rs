@@ -5,30 +5,179 @@
 #![allow(clippy::result_large_err)]
 
 use crate::cas::atomic::atomic_write;
+use crate::cas::encryption::{self, KeyProvider};
 use crate::cas::sharding::shard_path;
-use crate::errors::{cas_collision, cas_missing, io_error, Result};
+use crate::cas::stream::{readers_equal, CasReader, DigestReader};
+use crate::errors::{cas_collision, cas_corrupt, cas_missing, io_error, Result};
+use ettlex_core::errors::{ExError, ExErrorKind};
+use serde::Serialize;
 use sha2::{Digest, Sha256};
-use std::fs;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// Outcome of [`FsStore::write_tracked`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CasWrite {
+    /// SHA256 digest of the content
+    pub digest: String,
+    /// True if an identical blob already existed and nothing was written
+    pub reused: bool,
+}
+
+/// Running totals of new versus reused CAS blobs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CasDedupStats {
+    /// Blobs written for the first time
+    pub blobs_written: u64,
+    /// Blobs that matched existing content
+    pub blobs_reused: u64,
+    /// Bytes written for the first time
+    pub bytes_written: u64,
+    /// Bytes not re-stored because the blob already existed
+    pub bytes_reused: u64,
+}
+
+impl CasDedupStats {
+    /// Account for one write of `len` bytes
+    pub fn record(&mut self, write: &CasWrite, len: usize) {
+        if write.reused {
+            self.blobs_reused += 1;
+            self.bytes_reused += len as u64;
+        } else {
+            self.blobs_written += 1;
+            self.bytes_written += len as u64;
+        }
+    }
+
+    /// Totals accumulated since `earlier` was taken from the same store
+    pub fn since(&self, earlier: &CasDedupStats) -> CasDedupStats {
+        CasDedupStats {
+            blobs_written: self.blobs_written.saturating_sub(earlier.blobs_written),
+            blobs_reused: self.blobs_reused.saturating_sub(earlier.blobs_reused),
+            bytes_written: self.bytes_written.saturating_sub(earlier.bytes_written),
+            bytes_reused: self.bytes_reused.saturating_sub(earlier.bytes_reused),
+        }
+    }
+
+    /// True when no blob was written or reused
+    pub fn is_empty(&self) -> bool {
+        self.blobs_written == 0 && self.blobs_reused == 0
+    }
+}
+
+/// One blob found by [`FsStore::list_blobs`]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct CasBlob {
+    /// SHA256 digest (file stem)
+    pub digest: String,
+    /// File extension the blob was written with
+    pub extension: String,
+    /// Size on disk
+    pub size_bytes: u64,
+}
 
 /// Filesystem-based CAS store
 pub struct FsStore {
     root: PathBuf,
+    verify_reads: bool,
+    encryption: Option<Arc<dyn KeyProvider>>,
+    dedup: Mutex<CasDedupStats>,
 }
 
 impl FsStore {
     /// Create a new CAS store at the given root directory
     pub fn new(root: impl Into<PathBuf>) -> Self {
-        Self { root: root.into() }
+        Self {
+            root: root.into(),
+            verify_reads: false,
+            encryption: None,
+            dedup: Mutex::new(CasDedupStats::default()),
+        }
+    }
+
+    /// Re-hash every blob returned by [`FsStore::read`] and reject content
+    /// that no longer matches its digest
+    ///
+    /// Off by default; costs one SHA256 pass per read. Streaming reads are
+    /// not verified.
+    pub fn with_verify_reads(mut self, verify: bool) -> Self {
+        self.verify_reads = verify;
+        self
+    }
+
+    /// Encrypt new blobs at rest with keys from `provider`
+    ///
+    /// Blobs are sealed with AES-256-GCM envelope encryption (see
+    /// [`crate::cas::encryption`]); digests stay plaintext digests. Blobs
+    /// written before encryption was enabled remain readable as-is.
+    pub fn with_encryption(mut self, provider: Arc<dyn KeyProvider>) -> Self {
+        self.encryption = Some(provider);
+        self
+    }
+
+    /// Whether new blobs are encrypted at rest
+    pub fn is_encrypted(&self) -> bool {
+        self.encryption.is_some()
+    }
+
+    /// ID of the key new blobs are sealed with
+    ///
+    /// # Errors
+    /// * `InvalidInput` - the store has no key provider
+    pub fn current_key_id(&self) -> Result<String> {
+        self.provider("cas_key_id")?
+            .current_key()
+            .map(|(key_id, _)| key_id)
+    }
+
+    fn provider(&self, op: &str) -> Result<&Arc<dyn KeyProvider>> {
+        self.encryption.as_ref().ok_or_else(|| {
+            ExError::new(ExErrorKind::InvalidInput)
+                .with_op(op.to_string())
+                .with_message("operation requires a CAS key provider")
+        })
     }
 
     /// Write content to CAS and return the digest
     ///
     /// - Computes SHA256 digest
     /// - Writes atomically using temp→rename
-    /// - Idempotent: writing same content twice succeeds
+    /// - Idempotent: writing same content twice succeeds, and refreshes the
+    ///   blob's modified time so a GC sweep treats it as just written
     /// - Detects collisions: writing different content with same digest fails
     pub fn write(&self, content: &[u8], extension: &str) -> Result<String> {
+        self.write_tracked(content, extension).map(|w| w.digest)
+    }
+
+    /// New versus reused blobs across every write through this store
+    ///
+    /// Callers that import many bodies take the totals before and after and
+    /// report the difference with [`CasDedupStats::since`].
+    pub fn dedup_stats(&self) -> CasDedupStats {
+        *self.lock_dedup()
+    }
+
+    fn lock_dedup(&self) -> std::sync::MutexGuard<'_, CasDedupStats> {
+        // The totals are plain counters, so a panic mid-update cannot break them
+        self.dedup
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Write content to CAS, reporting whether an existing blob was reused
+    ///
+    /// Same semantics as [`FsStore::write`]. The outcome is also added to
+    /// [`FsStore::dedup_stats`].
+    pub fn write_tracked(&self, content: &[u8], extension: &str) -> Result<CasWrite> {
+        let write = self.write_blob(content, extension)?;
+        self.lock_dedup().record(&write, content.len());
+        Ok(write)
+    }
+
+    fn write_blob(&self, content: &[u8], extension: &str) -> Result<CasWrite> {
         // Compute digest
         let digest = self.compute_digest(content);
 
@@ -38,11 +187,15 @@ impl FsStore {
         // Check if file already exists
         if target_path.exists() {
             // Verify content matches (idempotency + collision detection)
-            let existing_content = fs::read(&target_path).map_err(|e| io_error("read_cas", e))?;
+            let existing_content = self.read_path(&target_path, &digest)?;
 
             if existing_content == content {
                 // Idempotent: same content, same digest - OK
-                return Ok(digest);
+                touch(&target_path)?;
+                return Ok(CasWrite {
+                    digest,
+                    reused: true,
+                });
             } else {
                 // Collision: different content, same digest - ERROR
                 return Err(cas_collision(&digest));
@@ -50,27 +203,296 @@ impl FsStore {
         }
 
         // Write atomically
-        atomic_write(&target_path, content)?;
+        match &self.encryption {
+            Some(provider) => atomic_write(
+                &target_path,
+                &encryption::seal(&**provider, &digest, content)?,
+            )?,
+            None => atomic_write(&target_path, content)?,
+        }
+
+        Ok(CasWrite {
+            digest,
+            reused: false,
+        })
+    }
+
+    /// Write content from a reader to CAS without buffering it in memory
+    ///
+    /// Same semantics as [`FsStore::write_tracked`]. The body is streamed into
+    /// a temp file under the store root while it is hashed, then renamed into
+    /// place. Returns the outcome and the number of bytes read.
+    ///
+    /// An encrypting store buffers the body instead: AES-GCM seals the blob
+    /// in one piece.
+    pub fn write_stream(&self, mut reader: impl Read, extension: &str) -> Result<(CasWrite, u64)> {
+        if self.encryption.is_some() {
+            let mut content = Vec::new();
+            reader
+                .read_to_end(&mut content)
+                .map_err(|e| io_error("write_cas_temp", e))?;
+            let write = self.write_tracked(&content, extension)?;
+            return Ok((write, content.len() as u64));
+        }
+        fs::create_dir_all(&self.root).map_err(|e| io_error("create_cas_dir", e))?;
+        let temp_path = self
+            .root
+            .join(format!("incoming-{}.tmp", uuid::Uuid::now_v7()));
+
+        let result = self.write_stream_via(&temp_path, reader, extension);
+        if temp_path.exists() {
+            let _ = fs::remove_file(&temp_path);
+        }
+        if let Ok((write, len)) = &result {
+            self.lock_dedup().record(write, *len as usize);
+        }
+        result
+    }
+
+    fn write_stream_via(
+        &self,
+        temp_path: &std::path::Path,
+        reader: impl Read,
+        extension: &str,
+    ) -> Result<(CasWrite, u64)> {
+        let mut reader = DigestReader::new(reader);
+        let mut temp = File::create(temp_path).map_err(|e| io_error("write_cas_temp", e))?;
+        io::copy(&mut reader, &mut temp).map_err(|e| io_error("write_cas_temp", e))?;
+        drop(temp);
+        let (digest, len) = reader.finish();
+
+        let target_path = shard_path(&self.root, &digest, extension);
+        if target_path.exists() {
+            let open = |p: &std::path::Path| File::open(p).map_err(|e| io_error("read_cas", e));
+            let same = readers_equal(
+                io::BufReader::new(open(temp_path)?),
+                io::BufReader::new(open(&target_path)?),
+            )
+            .map_err(|e| io_error("read_cas", e))?;
+            if !same {
+                return Err(cas_collision(&digest));
+            }
+            touch(&target_path)?;
+            return Ok((
+                CasWrite {
+                    digest,
+                    reused: true,
+                },
+                len,
+            ));
+        }
 
-        Ok(digest)
+        if let Some(parent) = target_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| io_error("create_cas_dir", e))?;
+        }
+        fs::rename(temp_path, &target_path).map_err(|e| io_error("rename_cas_temp", e))?;
+        Ok((
+            CasWrite {
+                digest,
+                reused: false,
+            },
+            len,
+        ))
     }
 
     /// Read content from CAS by digest
     ///
-    /// Returns error if blob not found
+    /// Returns error if blob not found. With [`FsStore::with_verify_reads`],
+    /// content that does not hash to `digest` fails with `InvariantViolation`.
     pub fn read(&self, digest: &str) -> Result<Vec<u8>> {
-        // Try common extensions
-        let extensions = ["txt", "bin", "json", "md"];
+        let path = self.locate(digest)?;
+        let content = self.read_path(&path, digest)?;
+        if self.verify_reads {
+            let actual = self.compute_digest(&content);
+            if actual != digest {
+                return Err(cas_corrupt(digest, &actual));
+            }
+        }
+        Ok(content)
+    }
+
+    /// Open a blob for streaming reads
+    ///
+    /// Prefer this to [`FsStore::read`] for blobs that may be large; the
+    /// returned reader pulls from disk on demand. Encrypted blobs are
+    /// decrypted into memory first.
+    pub fn read_stream(&self, digest: &str) -> Result<CasReader> {
+        let path = self.locate(digest)?;
+        let mut file = File::open(&path).map_err(|e| io_error("read_cas", e))?;
+        if self.blob_is_encrypted(&mut file)? {
+            return Ok(CasReader::from_bytes(self.read_path(&path, digest)?));
+        }
+        let size = file.metadata().map_err(|e| io_error("read_cas", e))?.len();
+        Ok(CasReader::new(file, size))
+    }
 
-        for ext in &extensions {
-            let path = shard_path(&self.root, digest, ext);
-            if path.exists() {
-                return fs::read(&path).map_err(|e| io_error("read_cas", e));
+    /// List every blob in the store, sorted by digest
+    ///
+    /// Only `<shard>/<digest>.<ext>` files are reported; temp files from
+    /// in-flight writes are skipped. A store whose root does not exist yet
+    /// is empty.
+    pub fn list_blobs(&self) -> Result<Vec<CasBlob>> {
+        if !self.root.exists() {
+            return Ok(Vec::new());
+        }
+        let mut blobs = Vec::new();
+        for shard in fs::read_dir(&self.root).map_err(|e| io_error("list_cas", e))? {
+            let shard = shard.map_err(|e| io_error("list_cas", e))?;
+            let shard_name = shard.file_name().to_string_lossy().into_owned();
+            if shard_name.len() != 2 || !shard.path().is_dir() {
+                continue;
+            }
+            for entry in fs::read_dir(shard.path()).map_err(|e| io_error("list_cas", e))? {
+                let entry = entry.map_err(|e| io_error("list_cas", e))?;
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let Some((digest, extension)) = name.split_once('.') else {
+                    continue;
+                };
+                if extension == "tmp" || !digest.starts_with(&shard_name) {
+                    continue;
+                }
+                let size_bytes = entry.metadata().map_err(|e| io_error("list_cas", e))?.len();
+                blobs.push(CasBlob {
+                    digest: digest.to_string(),
+                    extension: extension.to_string(),
+                    size_bytes,
+                });
             }
         }
+        blobs.sort();
+        Ok(blobs)
+    }
+
+    /// Look up one blob by digest without reading it
+    ///
+    /// Returns `None` when no blob with `digest` exists.
+    pub fn find_blob(&self, digest: &str) -> Result<Option<CasBlob>> {
+        let path = match self.locate(digest) {
+            Ok(path) => path,
+            Err(e) if e.kind() == ExErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let size_bytes = fs::metadata(&path)
+            .map_err(|e| io_error("stat_cas", e))?
+            .len();
+        let extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Ok(Some(CasBlob {
+            digest: digest.to_string(),
+            extension,
+            size_bytes,
+        }))
+    }
+
+    /// Delete one blob
+    ///
+    /// Callers decide what is safe to delete (see
+    /// [`crate::cas::reachability`]); the store itself keeps no references.
+    /// A blob that is already gone is not an error.
+    pub fn remove_blob(&self, blob: &CasBlob) -> Result<()> {
+        let path = shard_path(&self.root, &blob.digest, &blob.extension);
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(io_error("remove_cas", e)),
+        }
+    }
+
+    /// Last-modified time of one blob
+    ///
+    /// Lets sweeps leave alone blobs written moments ago whose referencing
+    /// row may not be committed yet.
+    pub fn blob_modified(&self, blob: &CasBlob) -> Result<SystemTime> {
+        let path = shard_path(&self.root, &blob.digest, &blob.extension);
+        fs::metadata(&path)
+            .and_then(|m| m.modified())
+            .map_err(|e| io_error("stat_cas", e))
+    }
+
+    /// Re-seal one blob under the provider's current key
+    ///
+    /// Plaintext blobs and blobs sealed with another key are decrypted,
+    /// checked against their digest and atomically rewritten. Returns
+    /// `false` without writing when the blob already uses the current key,
+    /// so an interrupted rotation can simply be run again.
+    ///
+    /// # Errors
+    /// * `InvalidInput` - the store has no key provider
+    /// * `NotFound` - the blob's key is unknown to the provider
+    /// * `InvariantViolation` - the blob fails to decrypt or does not hash
+    ///   to its digest
+    pub fn reseal(&self, blob: &CasBlob) -> Result<bool> {
+        let provider = self.provider("cas_reseal")?;
+        let (current_id, _) = provider.current_key()?;
+        let path = shard_path(&self.root, &blob.digest, &blob.extension);
+        let bytes = fs::read(&path).map_err(|e| io_error("read_cas", e))?;
+        if encryption::envelope_key_id(&bytes) == Some(current_id.as_str()) {
+            return Ok(false);
+        }
+
+        let plaintext = if encryption::is_encrypted(&bytes) {
+            encryption::open(&**provider, &blob.digest, &bytes)?
+        } else {
+            bytes
+        };
+        let actual = self.compute_digest(&plaintext);
+        if actual != blob.digest {
+            return Err(cas_corrupt(&blob.digest, &actual));
+        }
+        atomic_write(
+            &path,
+            &encryption::seal(&**provider, &blob.digest, &plaintext)?,
+        )?;
+        Ok(true)
+    }
+
+    /// Read a blob file, decrypting it if it is an encrypted envelope
+    fn read_path(&self, path: &std::path::Path, digest: &str) -> Result<Vec<u8>> {
+        let bytes = fs::read(path).map_err(|e| io_error("read_cas", e))?;
+        if !encryption::is_encrypted(&bytes) {
+            return Ok(bytes);
+        }
+        let provider = self.encryption.as_ref().ok_or_else(|| {
+            ExError::new(ExErrorKind::InvalidInput)
+                .with_op("cas_read")
+                .with_entity_id(digest)
+                .with_message(format!(
+                    "CAS blob {} is encrypted but no key provider is configured",
+                    digest
+                ))
+        })?;
+        encryption::open(&**provider, digest, &bytes)
+    }
+
+    /// Whether the blob behind `file` starts with the envelope prefix;
+    /// rewinds `file` afterwards
+    fn blob_is_encrypted(&self, file: &mut File) -> Result<bool> {
+        let mut prefix = [0u8; 8];
+        let mut filled = 0;
+        while filled < prefix.len() {
+            match file.read(&mut prefix[filled..]) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(e) => return Err(io_error("read_cas", e)),
+            }
+        }
+        file.seek(SeekFrom::Start(0))
+            .map_err(|e| io_error("read_cas", e))?;
+        Ok(encryption::is_encrypted(&prefix[..filled]))
+    }
+
+    /// Find the on-disk path of a blob
+    fn locate(&self, digest: &str) -> Result<PathBuf> {
+        // Try common extensions
+        let extensions = ["txt", "bin", "json", "md"];
 
-        // Not found with any extension
-        Err(cas_missing(digest))
+        extensions
+            .iter()
+            .map(|ext| shard_path(&self.root, digest, ext))
+            .find(|path| path.exists())
+            .ok_or_else(|| cas_missing(digest))
     }
 
     /// Compute SHA256 digest of content
@@ -82,6 +504,11 @@ impl FsStore {
     }
 }
 
+/// Set a reused blob's modified time to now, as a fresh write would.
+fn touch(path: &std::path::Path) -> Result<()> {
+    filetime::set_file_mtime(path, filetime::FileTime::now()).map_err(|e| io_error("touch_cas", e))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -115,6 +542,107 @@ mod tests {
         assert_eq!(digest1, digest2);
     }
 
+    #[test]
+    fn test_write_tracked_reports_reuse() {
+        let (cas, _dir) = setup_test_cas();
+        let mut stats = CasDedupStats::default();
+
+        let body = b"shared boilerplate";
+        for content in [&body[..], &body[..], b"unique"] {
+            let write = cas.write_tracked(content, "txt").unwrap();
+            stats.record(&write, content.len());
+        }
+
+        assert_eq!(stats.blobs_written, 2);
+        assert_eq!(stats.blobs_reused, 1);
+        assert_eq!(stats.bytes_reused, body.len() as u64);
+    }
+
+    #[test]
+    fn test_dedup_stats_accumulate_across_writes() {
+        let (cas, _dir) = setup_test_cas();
+        cas.write(b"first", "txt").unwrap();
+        let before = cas.dedup_stats();
+
+        cas.write(b"first", "txt").unwrap();
+        cas.write_stream(&b"second"[..], "txt").unwrap();
+
+        let delta = cas.dedup_stats().since(&before);
+        assert_eq!(delta.blobs_reused, 1);
+        assert_eq!(delta.bytes_reused, 5);
+        assert_eq!(delta.blobs_written, 1);
+        assert_eq!(delta.bytes_written, 6);
+        assert_eq!(cas.dedup_stats().blobs_written, 2);
+    }
+
+    #[test]
+    fn test_reused_write_refreshes_modified_time() {
+        let (cas, dir) = setup_test_cas();
+        let old = SystemTime::now() - std::time::Duration::from_secs(7200);
+        let backdate = |digest: &str| {
+            let path = shard_path(dir.path(), digest, "txt");
+            filetime::set_file_mtime(path, filetime::FileTime::from_system_time(old)).unwrap();
+        };
+        let blob = |digest: &str| cas.find_blob(digest).unwrap().unwrap();
+
+        let digest = cas.write(b"kept alive", "txt").unwrap();
+        backdate(&digest);
+        assert_eq!(cas.blob_modified(&blob(&digest)).unwrap(), old);
+        assert!(cas.write_tracked(b"kept alive", "txt").unwrap().reused);
+        assert!(cas.blob_modified(&blob(&digest)).unwrap() > old);
+
+        backdate(&digest);
+        let (write, _) = cas.write_stream(&b"kept alive"[..], "txt").unwrap();
+        assert!(write.reused);
+        assert!(cas.blob_modified(&blob(&digest)).unwrap() > old);
+    }
+
+    #[test]
+    fn test_stream_roundtrip_matches_buffered_write() {
+        let (cas, dir) = setup_test_cas();
+        let content: Vec<u8> = (0..100_000u32).flat_map(|i| i.to_le_bytes()).collect();
+
+        let (write, len) = cas.write_stream(&content[..], "bin").unwrap();
+        assert!(!write.reused);
+        assert_eq!(len, content.len() as u64);
+        assert_eq!(write.digest, cas.write(&content, "bin").unwrap());
+
+        let (again, _) = cas.write_stream(&content[..], "bin").unwrap();
+        assert!(again.reused);
+
+        let mut reader = cas.read_stream(&write.digest).unwrap();
+        assert_eq!(reader.size(), content.len() as u64);
+        let mut read_back = Vec::new();
+        reader.read_to_end(&mut read_back).unwrap();
+        assert_eq!(read_back, content);
+
+        // No temp files are left in the store root
+        let leftovers = fs::read_dir(dir.path())
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().ends_with(".tmp"))
+            .count();
+        assert_eq!(leftovers, 0);
+    }
+
+    #[test]
+    fn test_verify_reads_rejects_corrupt_blob() {
+        let (cas, dir) = setup_test_cas();
+        let digest = cas.write(b"original", "txt").unwrap();
+        fs::write(shard_path(dir.path(), &digest, "txt"), b"tampered").unwrap();
+
+        // Unverified reads return whatever is on disk
+        assert_eq!(cas.read(&digest).unwrap(), b"tampered");
+
+        let cas = cas.with_verify_reads(true);
+        let err = cas.read(&digest).unwrap_err();
+        assert_eq!(
+            err.kind(),
+            ettlex_core::errors::ExErrorKind::InvariantViolation
+        );
+        assert_eq!(err.entity_id(), Some(digest.as_str()));
+    }
+
     #[test]
     fn test_read_missing() {
         let (cas, _dir) = setup_test_cas();
@@ -0,0 +1,84 @@
+//! CAS garbage collection
+//!
+//! Deletes every blob outside the reachable set (see
+//! [`crate::cas::reachability`]): snapshot manifests, approval request
+//! payloads and spilled Ettle content. Manifests name no further blobs, so
+//! the referencing columns are the whole graph. Blobs younger than
+//! [`GcOptions::min_age`] are kept, since a writer may have stored one and
+//! not yet committed the row that references it.
+
+#![allow(clippy::result_large_err)]
+
+use std::time::{Duration, SystemTime};
+
+use rusqlite::Connection;
+use serde::Serialize;
+
+use crate::cas::reachability::reachable_digests;
+use crate::cas::{CasBlob, FsStore};
+use crate::errors::Result;
+
+/// Options for [`gc`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GcOptions {
+    /// Report what would be deleted without deleting anything
+    pub dry_run: bool,
+    /// Keep unreachable blobs modified more recently than this
+    pub min_age: Option<Duration>,
+}
+
+/// Outcome of [`gc`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct GcReport {
+    /// Blobs in the store
+    pub scanned: usize,
+    /// Blobs kept because the database references them
+    pub reachable: usize,
+    /// Unreachable blobs kept because they are younger than `min_age`
+    pub too_recent: usize,
+    /// Unreachable blobs deleted (or, in a dry run, that would be), by digest
+    pub deleted: Vec<CasBlob>,
+    /// Total size of `deleted`
+    pub bytes_freed: u64,
+}
+
+/// Delete blobs in `cas` that nothing in `conn` references
+///
+/// The reachable set is read once, before the store is listed, so a blob
+/// referenced by a row committed after that point is only protected by
+/// `min_age`.
+///
+/// # Errors
+/// * `Persistence` - a referencing table cannot be read
+/// * `Io` - the store cannot be listed or a blob cannot be removed; blobs
+///   removed before the failure stay removed
+pub fn gc(conn: &Connection, cas: &FsStore, options: GcOptions) -> Result<GcReport> {
+    let reachable = reachable_digests(conn)?;
+    let cutoff = options
+        .min_age
+        .and_then(|age| SystemTime::now().checked_sub(age));
+
+    let blobs = cas.list_blobs()?;
+    let mut report = GcReport {
+        scanned: blobs.len(),
+        ..Default::default()
+    };
+    for blob in blobs {
+        if reachable.contains(&blob.digest) {
+            report.reachable += 1;
+            continue;
+        }
+        if let Some(cutoff) = cutoff {
+            if cas.blob_modified(&blob)? > cutoff {
+                report.too_recent += 1;
+                continue;
+            }
+        }
+        if !options.dry_run {
+            cas.remove_blob(&blob)?;
+        }
+        report.bytes_freed += blob.size_bytes;
+        report.deleted.push(blob);
+    }
+    Ok(report)
+}
@@ -3,10 +3,25 @@
 //! Provides:
 //! - Filesystem-based CAS with atomic writes
 //! - Collision detection
+//! - Reuse reporting for deduplicated bulk writes
+//! - Streaming reads and writes for large blobs
+//! - Blob listing for backups and audits
+//! - The reachable set of digests referenced by the store database
+//! - Garbage collection of unreachable blobs
+//! - Optional envelope encryption at rest, with key rotation
 //! - Sharding by first 2 hex chars of digest
 
 mod atomic;
+pub mod encryption;
 mod fs_store;
+pub mod gc;
+pub mod reachability;
+pub mod rotation;
 mod sharding;
+mod stream;
 
-pub use fs_store::FsStore;
+pub use encryption::{KeyProvider, StaticKeyProvider};
+pub use fs_store::{CasBlob, CasDedupStats, CasWrite, FsStore};
+pub use gc::{gc, GcOptions, GcReport};
+pub use stream::CasReader;
+pub(crate) use stream::DigestReader;
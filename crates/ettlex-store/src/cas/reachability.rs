@@ -0,0 +1,47 @@
+//! CAS reachability
+//!
+//! The store database is the only thing that references CAS blobs, so the
+//! reachable set is every digest held in a referencing column. Anything
+//! outside it is garbage. Sweeps that delete blobs first drop their own
+//! references, then delete only digests that are no longer in this set, so
+//! a blob shared with a live row is never removed.
+
+#![allow(clippy::result_large_err)]
+
+use std::collections::BTreeSet;
+
+use rusqlite::Connection;
+
+use crate::errors::{from_rusqlite, Result};
+
+/// `(table, column)` pairs that hold CAS digests.
+pub const CAS_REFERENCES: &[(&str, &str)] = &[
+    ("approval_requests", "request_digest"),
+    ("ettles", "how_digest"),
+    ("ettles", "what_digest"),
+    ("ettles", "why_digest"),
+    ("snapshots", "manifest_digest"),
+];
+
+/// Every digest referenced by the store database.
+///
+/// # Errors
+/// * `Persistence` - a referencing table cannot be read
+pub fn reachable_digests(conn: &Connection) -> Result<BTreeSet<String>> {
+    let mut reachable = BTreeSet::new();
+    for (table, column) in CAS_REFERENCES {
+        let sql = format!(
+            "SELECT DISTINCT {column} FROM {table} WHERE {column} IS NOT NULL",
+            column = column,
+            table = table
+        );
+        let mut stmt = conn.prepare(&sql).map_err(from_rusqlite)?;
+        let digests = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(from_rusqlite)?;
+        for digest in digests {
+            reachable.insert(digest.map_err(from_rusqlite)?);
+        }
+    }
+    Ok(reachable)
+}
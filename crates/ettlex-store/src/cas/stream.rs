@@ -0,0 +1,94 @@
+//! Streaming access to CAS blobs
+//!
+//! Lets large blobs be copied in and out of the store without holding the
+//! whole body in memory.
+
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+
+/// Streaming reader over a single CAS blob, returned by [`FsStore::read_stream`]
+///
+/// [`FsStore::read_stream`]: crate::cas::FsStore::read_stream
+pub struct CasReader {
+    inner: Box<dyn Read + Send>,
+    size: u64,
+}
+
+impl CasReader {
+    pub(crate) fn new(file: File, size: u64) -> Self {
+        Self {
+            inner: Box::new(BufReader::new(file)),
+            size,
+        }
+    }
+
+    /// Reader over an already decrypted blob
+    pub(crate) fn from_bytes(content: Vec<u8>) -> Self {
+        Self {
+            size: content.len() as u64,
+            inner: Box::new(io::Cursor::new(content)),
+        }
+    }
+
+    /// Blob size in bytes: on disk when the reader was opened, or the
+    /// plaintext size for encrypted blobs
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+impl Read for CasReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+/// Reader adapter that hashes everything read through it
+pub(crate) struct DigestReader<R> {
+    inner: R,
+    hasher: Sha256,
+    len: u64,
+}
+
+impl<R: Read> DigestReader<R> {
+    pub(crate) fn new(inner: R) -> Self {
+        Self {
+            inner,
+            hasher: Sha256::new(),
+            len: 0,
+        }
+    }
+
+    /// Hex SHA256 digest and byte count of everything read so far
+    pub(crate) fn finish(self) -> (String, u64) {
+        (hex::encode(self.hasher.finalize()), self.len)
+    }
+}
+
+impl<R: Read> Read for DigestReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        self.len += n as u64;
+        Ok(n)
+    }
+}
+
+/// Compare two readers byte for byte
+pub(crate) fn readers_equal(mut a: impl Read, mut b: impl Read) -> io::Result<bool> {
+    let mut buf_a = [0u8; 8192];
+    let mut buf_b = [0u8; 8192];
+    loop {
+        let n = a.read(&mut buf_a)?;
+        if n == 0 {
+            return Ok(b.read(&mut buf_b[..1])? == 0);
+        }
+        match b.read_exact(&mut buf_b[..n]) {
+            Ok(()) if buf_a[..n] == buf_b[..n] => {}
+            Ok(()) => return Ok(false),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(false),
+            Err(e) => return Err(e),
+        }
+    }
+}
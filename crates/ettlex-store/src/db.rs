@@ -5,12 +5,86 @@
 #![allow(clippy::result_large_err)]
 
 use crate::errors::{from_rusqlite, Result};
+use ettlex_core::errors::{ExError, ExErrorKind};
 use rusqlite::Connection;
 use std::path::Path;
+use std::time::Duration;
+
+/// How long a connection waits for another writer's lock before giving up.
+pub const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// Open a SQLite database at the given path
+///
+/// The connection is attached to the SQL audit when it is enabled (see
+/// [`crate::sql_audit`]).
 pub fn open<P: AsRef<Path>>(path: P) -> Result<Connection> {
-    Connection::open(path).map_err(from_rusqlite)
+    let mut conn = Connection::open(path).map_err(from_rusqlite)?;
+    crate::sql_audit::attach(&mut conn);
+    Ok(conn)
+}
+
+/// Open a SQLCipher-encrypted database at the given path, keyed with `key`
+///
+/// A new file is encrypted on first write; an existing file must have been
+/// created with the same key. Requires the `sqlcipher` feature: plain SQLite
+/// silently ignores `PRAGMA key`, so the cipher is probed and the open fails
+/// rather than storing plaintext.
+///
+/// # Errors
+/// * `InvalidInput` - `key` is empty
+/// * `NotImplemented` - this build links plain SQLite, not SQLCipher
+/// * `Persistence` - the file cannot be opened, or `key` does not decrypt it
+pub fn open_encrypted<P: AsRef<Path>>(path: P, key: &str) -> Result<Connection> {
+    if key.is_empty() {
+        return Err(ExError::new(ExErrorKind::InvalidInput)
+            .with_op("db_open_encrypted")
+            .with_message("database encryption key must not be empty"));
+    }
+    let conn = Connection::open(path).map_err(from_rusqlite)?;
+    require_sqlcipher(&conn, "db_open_encrypted")?;
+    conn.pragma_update(None, "key", key)
+        .map_err(from_rusqlite)?;
+    // The key is only checked on first page access
+    conn.query_row("SELECT count(*) FROM sqlite_master", [], |_| Ok(()))
+        .map_err(from_rusqlite)?;
+    // Attached after keying so the key pragma is never traced
+    let mut conn = conn;
+    crate::sql_audit::attach(&mut conn);
+    Ok(conn)
+}
+
+/// Re-encrypt an open SQLCipher database under `new_key`
+///
+/// SQLCipher rewrites every page inside one transaction, so a crash leaves
+/// the database readable with either the old or the new key, never a mix.
+///
+/// # Errors
+/// * `InvalidInput` - `new_key` is empty
+/// * `NotImplemented` - this build links plain SQLite, not SQLCipher
+/// * `Persistence` - the rekey fails
+pub fn rekey(conn: &Connection, new_key: &str) -> Result<()> {
+    if new_key.is_empty() {
+        return Err(ExError::new(ExErrorKind::InvalidInput)
+            .with_op("db_rekey")
+            .with_message("database encryption key must not be empty"));
+    }
+    require_sqlcipher(conn, "db_rekey")?;
+    conn.pragma_update(None, "rekey", new_key)
+        .map_err(from_rusqlite)
+}
+
+fn require_sqlcipher(conn: &Connection, op: &str) -> Result<()> {
+    let cipher: Option<String> = conn
+        .query_row("PRAGMA cipher_version", [], |row| row.get(0))
+        .ok();
+    if cipher.is_none() {
+        return Err(ExError::new(ExErrorKind::NotImplemented)
+            .with_op(op.to_string())
+            .with_message(
+                "database encryption requires ettlex-store built with the `sqlcipher` feature",
+            ));
+    }
+    Ok(())
 }
 
 /// Open an in-memory SQLite database (for testing)
@@ -24,9 +98,14 @@ pub fn configure(conn: &Connection) -> Result<()> {
     conn.execute("PRAGMA foreign_keys = ON", [])
         .map_err(from_rusqlite)?;
 
-    // Set WAL mode for better concurrency
-    conn.execute("PRAGMA journal_mode = WAL", [])
+    // Set WAL mode for better concurrency. The pragma returns the new mode
+    // as a row, so it must go through execute_batch rather than execute.
+    conn.execute_batch("PRAGMA journal_mode = WAL")
         .map_err(from_rusqlite)?;
 
+    // Wait for competing writers (e.g. concurrent snapshot commits) instead of
+    // failing immediately with SQLITE_BUSY
+    conn.busy_timeout(BUSY_TIMEOUT).map_err(from_rusqlite)?;
+
     Ok(())
 }
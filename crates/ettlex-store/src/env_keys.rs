@@ -0,0 +1,62 @@
+//! Store encryption keys from the environment
+//!
+//! Every entry point (CLI commands, `ettlex serve`, the MCP server) opens the
+//! store through [`open_db`] and [`open_cas`], so a database keyed with
+//! `ETTLEX_DB_KEY` and a CAS sealed with `ETTLEX_CAS_KEY` are handled the same
+//! way everywhere. Unset or empty variables mean no encryption.
+
+#![allow(clippy::result_large_err)]
+
+use std::path::Path;
+use std::sync::Arc;
+
+use rusqlite::Connection;
+
+use crate::cas::{FsStore, StaticKeyProvider};
+use crate::errors::Result;
+
+/// SQLCipher key for the database.
+pub const DB_KEY_VAR: &str = "ETTLEX_DB_KEY";
+/// Hex AES-256 key sealing new CAS blobs.
+pub const CAS_KEY_VAR: &str = "ETTLEX_CAS_KEY";
+/// ID recorded with blobs sealed under [`CAS_KEY_VAR`]; default `default`.
+pub const CAS_KEY_ID_VAR: &str = "ETTLEX_CAS_KEY_ID";
+
+/// Open the database at `path`, keyed with `ETTLEX_DB_KEY` when it is set.
+///
+/// # Errors
+/// As [`crate::db::open`] or [`crate::db::open_encrypted`].
+pub fn open_db<P: AsRef<Path>>(path: P) -> Result<Connection> {
+    match env(DB_KEY_VAR) {
+        Some(key) => crate::db::open_encrypted(path, &key),
+        None => crate::db::open(path),
+    }
+}
+
+/// The CAS rooted at `root`, sealing with `ETTLEX_CAS_KEY` when it is set.
+///
+/// # Errors
+/// * `InvalidInput` - `ETTLEX_CAS_KEY` is not a 64-digit hex key
+pub fn open_cas<P: AsRef<Path>>(root: P) -> Result<FsStore> {
+    let cas = FsStore::new(root.as_ref());
+    Ok(match cas_key_provider()? {
+        Some(provider) => cas.with_encryption(Arc::new(provider)),
+        None => cas,
+    })
+}
+
+/// Key provider for `ETTLEX_CAS_KEY` / `ETTLEX_CAS_KEY_ID`, if a key is set.
+///
+/// # Errors
+/// * `InvalidInput` - `ETTLEX_CAS_KEY` is not a 64-digit hex key
+pub fn cas_key_provider() -> Result<Option<StaticKeyProvider>> {
+    let Some(key) = env(CAS_KEY_VAR) else {
+        return Ok(None);
+    };
+    let key_id = env(CAS_KEY_ID_VAR).unwrap_or_else(|| "default".to_string());
+    StaticKeyProvider::from_hex(key_id, &key).map(Some)
+}
+
+fn env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.is_empty())
+}
@@ -38,6 +38,17 @@ pub fn cas_missing(digest: &str) -> ExError {
         .with_message(format!("CAS blob not found for digest {}", digest))
 }
 
+/// Create a CAS corruption error (blob content does not hash to its digest)
+pub fn cas_corrupt(digest: &str, actual: &str) -> ExError {
+    ExError::new(ExErrorKind::InvariantViolation)
+        .with_op("cas_read")
+        .with_entity_id(digest)
+        .with_message(format!(
+            "CAS blob {} is corrupt: content hashes to {}",
+            digest, actual
+        ))
+}
+
 /// Create a seed validation error
 pub fn seed_validation(reason: &str) -> ExError {
     ExError::new(ExErrorKind::InvalidInput)
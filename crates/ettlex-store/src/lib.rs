@@ -5,15 +5,20 @@
 //! - Content-addressable storage (CAS) for blob storage
 //! - Repository layer for domain models (Ettle, Relation, Group, Decision, Snapshot, Profile)
 
+pub mod backup;
 pub mod cas;
+pub mod crypto;
 pub mod db;
+pub mod env_keys;
 pub mod errors;
 pub mod file_policy_provider;
 pub mod migrations;
 pub mod model;
 pub mod profile;
 pub mod repo;
+pub mod settings;
 pub mod snapshot;
+pub mod sql_audit;
 
 // Re-export key types
 pub use errors::Result;
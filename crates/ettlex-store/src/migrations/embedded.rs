@@ -71,5 +71,89 @@ pub fn get_migrations() -> Vec<Migration> {
             id: "015_ep_retirement",
             sql: include_str!("../../migrations/015_ep_retirement.sql"),
         },
+        Migration {
+            id: "016_snapshot_ledger_chain",
+            sql: include_str!("../../migrations/016_snapshot_ledger_chain.sql"),
+        },
+        Migration {
+            id: "017_ettle_slugs",
+            sql: include_str!("../../migrations/017_ettle_slugs.sql"),
+        },
+        Migration {
+            id: "018_ettle_archive",
+            sql: include_str!("../../migrations/018_ettle_archive.sql"),
+        },
+        Migration {
+            id: "019_comments",
+            sql: include_str!("../../migrations/019_comments.sql"),
+        },
+        Migration {
+            id: "020_hot_path_indexes",
+            sql: include_str!("../../migrations/020_hot_path_indexes.sql"),
+        },
+        Migration {
+            id: "021_pagination_keys",
+            sql: include_str!("../../migrations/021_pagination_keys.sql"),
+        },
+        Migration {
+            id: "022_profile_environment_defaults",
+            sql: include_str!("../../migrations/022_profile_environment_defaults.sql"),
+        },
+        Migration {
+            id: "023_approval_events",
+            sql: include_str!("../../migrations/023_approval_events.sql"),
+        },
+        Migration {
+            id: "024_ettle_content_format",
+            sql: include_str!("../../migrations/024_ettle_content_format.sql"),
+        },
+        Migration {
+            id: "025_settings",
+            sql: include_str!("../../migrations/025_settings.sql"),
+        },
+        Migration {
+            id: "026_approval_payload_sweep",
+            sql: include_str!("../../migrations/026_approval_payload_sweep.sql"),
+        },
+        Migration {
+            id: "027_snapshot_annotations",
+            sql: include_str!("../../migrations/027_snapshot_annotations.sql"),
+        },
+        Migration {
+            id: "028_root_registry",
+            sql: include_str!("../../migrations/028_root_registry.sql"),
+        },
+        Migration {
+            id: "029_api_tokens",
+            sql: include_str!("../../migrations/029_api_tokens.sql"),
+        },
+        Migration {
+            id: "030_import_sessions",
+            sql: include_str!("../../migrations/030_import_sessions.sql"),
+        },
+        Migration {
+            id: "031_ettle_assignments",
+            sql: include_str!("../../migrations/031_ettle_assignments.sql"),
+        },
+        Migration {
+            id: "032_snapshot_commit_queue",
+            sql: include_str!("../../migrations/032_snapshot_commit_queue.sql"),
+        },
+        Migration {
+            id: "033_ettle_content_spill",
+            sql: include_str!("../../migrations/033_ettle_content_spill.sql"),
+        },
+        Migration {
+            id: "034_command_journal",
+            sql: include_str!("../../migrations/034_command_journal.sql"),
+        },
+        Migration {
+            id: "035_command_log_events",
+            sql: include_str!("../../migrations/035_command_log_events.sql"),
+        },
+        Migration {
+            id: "036_timestamp_ms_columns",
+            sql: include_str!("../../migrations/036_timestamp_ms_columns.sql"),
+        },
     ]
 }
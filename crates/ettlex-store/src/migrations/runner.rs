@@ -22,6 +22,9 @@ pub fn apply_migrations(conn: &mut Connection) -> Result<()> {
         apply_migration(conn, migration.id, migration.sql)?;
     }
 
+    // Data backfills that cannot be expressed in SQL (idempotent)
+    crate::repo::SqliteRepo::backfill_ettle_slugs(conn)?;
+
     Ok(())
 }
 
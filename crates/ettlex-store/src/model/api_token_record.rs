@@ -0,0 +1,18 @@
+//! API token record types for the store layer.
+
+use serde::{Deserialize, Serialize};
+
+/// An issued API token as stored in the `api_tokens` table.
+///
+/// The secret is never stored; `token_hash` is its SHA-256 hex digest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiTokenRecord {
+    pub token_id: String,
+    /// Human-readable label, e.g. the client or pipeline using the token.
+    pub name: String,
+    pub token_hash: String,
+    /// Granted scopes, sorted.
+    pub scopes: Vec<String>,
+    pub created_at_ms: i64,
+    pub revoked_at_ms: Option<i64>,
+}
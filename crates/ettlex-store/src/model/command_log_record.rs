@@ -0,0 +1,16 @@
+//! Command log record types for the store layer.
+
+use serde::{Deserialize, Serialize};
+
+/// One applied command as stored in the `command_log` table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandLogRecord {
+    /// Log position; the row count is the store's `state_version`.
+    pub id: i64,
+    /// RFC 3339 time the command was applied.
+    pub applied_at: String,
+    /// Op name of the command, e.g. `ettle_update` (`None` before migration 035).
+    pub op: Option<String>,
+    /// Entity the command targeted or created, if any.
+    pub entity_id: Option<String>,
+}
@@ -0,0 +1,20 @@
+//! Comment record types for the store layer.
+
+use serde::{Deserialize, Serialize};
+
+/// A single comment as stored in the `comments` table.
+///
+/// A thread is a root comment (`parent_comment_id == None`) plus its replies.
+/// Replies share the root's target; `resolved_at_ms` is only set on roots.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommentRecord {
+    pub id: String,
+    /// One of `"ettle"`, `"decision"`, `"snapshot"`.
+    pub target_kind: String,
+    pub target_id: String,
+    pub parent_comment_id: Option<String>,
+    pub author: Option<String>,
+    pub body: String,
+    pub created_at: String,
+    pub resolved_at_ms: Option<i64>,
+}
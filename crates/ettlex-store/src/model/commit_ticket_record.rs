@@ -0,0 +1,31 @@
+//! Snapshot commit queue record types for the store layer.
+
+use serde::{Deserialize, Serialize};
+
+/// Status of a ticket waiting for the queue worker.
+pub const COMMIT_TICKET_QUEUED: &str = "queued";
+/// Status of a ticket a worker has claimed and is applying.
+pub const COMMIT_TICKET_APPLYING: &str = "applying";
+/// Status of a ticket whose commit wrote (or deduplicated to) a snapshot.
+pub const COMMIT_TICKET_COMMITTED: &str = "committed";
+/// Status of a ticket whose commit was routed for approval.
+pub const COMMIT_TICKET_ROUTED: &str = "routed";
+/// Status of a ticket whose commit failed.
+pub const COMMIT_TICKET_FAILED: &str = "failed";
+
+/// A queued snapshot commit as stored in the `snapshot_commit_queue` table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitTicketRecord {
+    /// Queue position; tickets are applied in ascending `seq`.
+    pub seq: i64,
+    pub ticket: String,
+    pub leaf_ettle_id: String,
+    /// Commit arguments (JSON), as given at enqueue time.
+    pub request_json: String,
+    /// One of the `COMMIT_TICKET_*` statuses.
+    pub status: String,
+    pub enqueued_at_ms: i64,
+    pub applied_at_ms: Option<i64>,
+    /// Result of the commit (JSON), once the ticket is resolved.
+    pub outcome_json: Option<String>,
+}
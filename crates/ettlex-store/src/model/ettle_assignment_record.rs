@@ -0,0 +1,18 @@
+//! Ettle ownership and review assignment record types for the store layer.
+
+use serde::{Deserialize, Serialize};
+
+/// Role of the single principal responsible for an Ettle.
+pub const ASSIGNMENT_ROLE_OWNER: &str = "owner";
+/// Role of a principal asked to review changes to an Ettle.
+pub const ASSIGNMENT_ROLE_REVIEWER: &str = "reviewer";
+
+/// One row of the `ettle_assignments` table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EttleAssignmentRecord {
+    pub ettle_id: String,
+    /// [`ASSIGNMENT_ROLE_OWNER`] or [`ASSIGNMENT_ROLE_REVIEWER`].
+    pub role: String,
+    pub principal: String,
+    pub assigned_at_ms: i64,
+}
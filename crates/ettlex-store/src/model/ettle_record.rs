@@ -13,6 +13,17 @@ pub struct EttleRecord {
     pub created_at: String,
     pub updated_at: String,
     pub tombstoned_at: Option<String>,
+    /// Stable human-friendly alias (`None` only for rows not yet backfilled).
+    pub slug: Option<String>,
+    /// When the Ettle was archived (`None` if not archived).
+    pub archived_at: Option<String>,
+    /// Declared format of why/what/how: `markdown`, `asciidoc` or `plain`.
+    pub content_format: String,
+    /// CAS digests of why/what/how bodies stored outside the row. A field
+    /// with a digest holds `""` until rehydrated from CAS.
+    pub why_digest: Option<String>,
+    pub what_digest: Option<String>,
+    pub how_digest: Option<String>,
 }
 
 /// Options for listing Ettles.
@@ -21,6 +32,8 @@ pub struct EttleListOpts {
     pub limit: u32,
     pub cursor: Option<EttleCursor>,
     pub include_tombstoned: bool,
+    /// Include archived Ettles (excluded by default).
+    pub include_archived: bool,
 }
 
 /// Cursor for Ettle list pagination (created_at, id).
@@ -34,8 +47,8 @@ pub struct EttleCursor {
 #[derive(Debug, Clone)]
 pub struct EttleListPage {
     pub items: Vec<EttleListItem>,
-    /// Cursor for the next page, if any. This is an `EttleCursor` encoded as
-    /// `{created_at},{id}` and then base64 URL-safe-no-pad encoded.
+    /// Cursor for the next page, if any: an `EttleCursor` encoded with the
+    /// listing's [`crate::repo::CursorCodec`].
     pub next_cursor: Option<String>,
 }
 
@@ -45,4 +58,5 @@ pub struct EttleListItem {
     pub id: String,
     pub title: String,
     pub tombstoned_at: Option<String>,
+    pub archived_at: Option<String>,
 }
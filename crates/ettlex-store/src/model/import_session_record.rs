@@ -0,0 +1,43 @@
+//! Import session record types for the store layer.
+
+use serde::{Deserialize, Serialize};
+
+/// Status of an import that has not finished (or was interrupted).
+pub const IMPORT_SESSION_RUNNING: &str = "running";
+/// Status of an import whose rows were all written.
+pub const IMPORT_SESSION_COMPLETED: &str = "completed";
+/// Status of an import whose written rows were removed again.
+pub const IMPORT_SESSION_ROLLED_BACK: &str = "rolled_back";
+
+/// Item kind of an Ettle created by an import.
+pub const IMPORT_ITEM_ETTLE: &str = "ettle";
+/// Item kind of a relation created by an import.
+pub const IMPORT_ITEM_RELATION: &str = "relation";
+
+/// A bulk import as stored in the `import_sessions` table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportSessionRecord {
+    pub session_id: String,
+    /// Importer that ran, e.g. `csv`.
+    pub kind: String,
+    pub source_path: String,
+    /// SHA-256 hex digest of the source file when the import started.
+    pub source_digest: String,
+    /// Importer options (JSON) needed to resume the import.
+    pub options_json: String,
+    /// [`IMPORT_SESSION_RUNNING`], [`IMPORT_SESSION_COMPLETED`] or
+    /// [`IMPORT_SESSION_ROLLED_BACK`].
+    pub status: String,
+    pub started_at_ms: i64,
+    pub finished_at_ms: Option<i64>,
+}
+
+/// An entity created by an import session, keyed by the source line that
+/// produced it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportSessionItem {
+    /// [`IMPORT_ITEM_ETTLE`] or [`IMPORT_ITEM_RELATION`].
+    pub item_kind: String,
+    pub source_line: u64,
+    pub entity_id: String,
+}
@@ -0,0 +1,28 @@
+//! Undo/redo journal record types for the store layer.
+
+use serde::{Deserialize, Serialize};
+
+/// Status of a journal entry whose command is in effect.
+pub const JOURNAL_APPLIED: &str = "applied";
+/// Status of a journal entry that has been undone and can be redone.
+pub const JOURNAL_UNDONE: &str = "undone";
+
+/// One reversible command as stored in the `command_journal` table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalRecord {
+    /// Journal position; entries are undone newest first.
+    pub seq: i64,
+    /// Op name of the recorded command, e.g. `ettle_update`.
+    pub op: String,
+    /// Entity the command targeted or created, if any.
+    pub entity_id: Option<String>,
+    /// Steps that revert the command (JSON array).
+    pub undo_json: String,
+    /// Steps that reapply the command (JSON array).
+    pub redo_json: String,
+    /// `JOURNAL_APPLIED` or `JOURNAL_UNDONE`.
+    pub status: String,
+    pub recorded_at_ms: i64,
+    /// When the entry was last undone (`None` while applied).
+    pub undone_at_ms: Option<i64>,
+}
@@ -1,9 +1,41 @@
 //! Store-layer model types (distinct from ettlex-core domain models).
 
+pub mod api_token_record;
+pub use api_token_record::ApiTokenRecord;
+
+pub mod comment_record;
+pub use comment_record::CommentRecord;
+
+pub mod command_log_record;
+pub use command_log_record::CommandLogRecord;
+
+pub mod commit_ticket_record;
+pub use commit_ticket_record::{
+    CommitTicketRecord, COMMIT_TICKET_APPLYING, COMMIT_TICKET_COMMITTED, COMMIT_TICKET_FAILED,
+    COMMIT_TICKET_QUEUED, COMMIT_TICKET_ROUTED,
+};
+
+pub mod ettle_assignment_record;
+pub use ettle_assignment_record::{
+    EttleAssignmentRecord, ASSIGNMENT_ROLE_OWNER, ASSIGNMENT_ROLE_REVIEWER,
+};
+
 pub mod ettle_record;
 pub use ettle_record::{EttleCursor, EttleListItem, EttleListOpts, EttleListPage, EttleRecord};
 
+pub mod import_session_record;
+pub use import_session_record::{
+    ImportSessionItem, ImportSessionRecord, IMPORT_ITEM_ETTLE, IMPORT_ITEM_RELATION,
+    IMPORT_SESSION_COMPLETED, IMPORT_SESSION_ROLLED_BACK, IMPORT_SESSION_RUNNING,
+};
+
+pub mod journal_record;
+pub use journal_record::{JournalRecord, JOURNAL_APPLIED, JOURNAL_UNDONE};
+
 pub mod relation_record;
 pub use relation_record::{
     GroupMemberRecord, GroupRecord, RelationListOpts, RelationRecord, RelationTypeEntry,
 };
+
+pub mod root_record;
+pub use root_record::{RootRecord, ROOT_STATUS_ACTIVE, ROOT_STATUS_RETIRED};
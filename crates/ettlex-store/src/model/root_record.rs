@@ -0,0 +1,21 @@
+//! Root registry record types for the store layer.
+
+use serde::{Deserialize, Serialize};
+
+/// Status of a registered root that still accepts snapshots.
+pub const ROOT_STATUS_ACTIVE: &str = "active";
+/// Status of a registered root that accepts no new snapshots.
+pub const ROOT_STATUS_RETIRED: &str = "retired";
+
+/// A registered root Ettle as stored in the `roots` table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RootRecord {
+    pub ettle_id: String,
+    /// Unique human-readable name for the tree.
+    pub display_name: String,
+    pub owner: Option<String>,
+    /// [`ROOT_STATUS_ACTIVE`] or [`ROOT_STATUS_RETIRED`].
+    pub status: String,
+    pub registered_at_ms: i64,
+    pub updated_at_ms: i64,
+}
@@ -2,11 +2,12 @@
 
 #![allow(clippy::result_large_err)]
 
-use ettlex_core::approval_router::ApprovalRouter;
+use ettlex_core::approval_router::{ApprovalRequestPayload, ApprovalRouter};
 use ettlex_core::errors::{ExError, ExErrorKind};
 use rusqlite::{Connection, OptionalExtension};
 
 use crate::errors::{from_rusqlite, Result};
+use crate::repo::PageKey;
 
 /// A raw row from the `approval_requests` table.
 #[derive(Debug, Clone)]
@@ -19,7 +20,7 @@ pub struct ApprovalRow {
     pub candidate_set_json: String,
     /// Deterministic semantic digest over `reason_code` + sorted candidate IDs
     pub semantic_request_digest: String,
-    /// Current status (`pending`, `approved`, `rejected`)
+    /// Current status (`pending`, `approved`, `rejected`, `expired`)
     pub status: String,
     /// Creation timestamp, milliseconds since epoch
     pub created_at: i64,
@@ -27,12 +28,59 @@ pub struct ApprovalRow {
     pub request_digest: Option<String>,
 }
 
-/// Load a profile's payload JSON from the profiles table.
+/// Status of a newly routed approval request.
+pub const APPROVAL_STATUS_PENDING: &str = "pending";
+
+/// Statuses a pending approval request may move to. All are terminal.
+pub const APPROVAL_TERMINAL_STATUSES: [&str; 3] = ["approved", "rejected", "expired"];
+
+/// Terminal statuses whose CAS payload may be swept after retention.
+/// Approved requests keep their payload as the record of the decision.
+pub const APPROVAL_SWEEPABLE_STATUSES: [&str; 2] = ["rejected", "expired"];
+
+/// An approval request whose payload blob is due for sweeping.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SweepableApproval {
+    pub approval_token: String,
+    pub status: String,
+    pub request_digest: String,
+    /// When the request reached its terminal status, milliseconds since
+    /// epoch (`created_at` for rows with no recorded transition)
+    pub terminal_at: i64,
+}
+
+/// A row from the `approval_events` table: one lifecycle transition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApprovalEventRow {
+    /// Monotonic event ID; orders events for a token
+    pub id: i64,
+    /// Approval token the event belongs to
+    pub approval_token: String,
+    /// Status before the transition (`None` for the creation event)
+    pub from_status: Option<String>,
+    /// Status after the transition
+    pub to_status: String,
+    /// Who made the transition, if known
+    pub actor: Option<String>,
+    /// Transition timestamp, milliseconds since epoch
+    pub occurred_at_ms: i64,
+}
+
+/// Load a profile's effective payload JSON from the profiles table.
 ///
-/// Returns `None` if no row with the given ref exists.
+/// `extends` is resolved (see [`resolve_profile`]), so callers always see the
+/// merged payload. Returns `None` if no row with the given ref exists.
 pub fn load_profile_payload(
     conn: &Connection,
     profile_ref: &str,
+) -> Result<Option<serde_json::Value>> {
+    Ok(resolve_profile(conn, profile_ref)?.map(|r| r.effective))
+}
+
+/// Load a profile's stored payload JSON, without resolving `extends`.
+fn load_raw_profile_payload(
+    conn: &Connection,
+    profile_ref: &str,
 ) -> Result<Option<serde_json::Value>> {
     let payload: Option<String> = conn
         .query_row(
@@ -60,6 +108,142 @@ pub fn load_profile_payload(
     }
 }
 
+// ---------------------------------------------------------------------------
+// Profile inheritance
+// ---------------------------------------------------------------------------
+
+/// Payload key naming the profile(s) a profile inherits from.
+pub const PROFILE_EXTENDS_KEY: &str = "extends";
+
+/// A profile payload with its `extends` chain merged in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedProfile {
+    /// The merged payload, without the `extends` key.
+    pub effective: serde_json::Value,
+    /// Every profile that contributed, in merge order (bases first, the
+    /// resolved profile last). A base reached twice is listed once, at its
+    /// first application.
+    pub extends_chain: Vec<String>,
+}
+
+/// Resolve a stored profile's `extends` chain.
+///
+/// `extends` is a profile ref or an array of refs. Bases are merged left to
+/// right, then the profile's own keys on top: objects merge key by key,
+/// everything else (arrays, scalars) is replaced wholesale.
+///
+/// Returns `None` if `profile_ref` does not exist.
+///
+/// # Errors
+///
+/// - `ProfileNotFound` if a base profile does not exist.
+/// - `CycleDetected` if the chain refers back to a profile being resolved.
+/// - `InvalidInput` if `extends` is neither a string nor an array of strings.
+pub fn resolve_profile(conn: &Connection, profile_ref: &str) -> Result<Option<ResolvedProfile>> {
+    match load_raw_profile_payload(conn, profile_ref)? {
+        None => Ok(None),
+        Some(payload) => resolve_profile_payload(conn, profile_ref, &payload).map(Some),
+    }
+}
+
+/// Resolve the `extends` chain of `payload` as if stored under `profile_ref`.
+///
+/// Used to check a profile before it is created. Errors as [`resolve_profile`].
+pub fn resolve_profile_payload(
+    conn: &Connection,
+    profile_ref: &str,
+    payload: &serde_json::Value,
+) -> Result<ResolvedProfile> {
+    let mut resolved = ResolvedProfile {
+        effective: serde_json::Value::Object(serde_json::Map::new()),
+        extends_chain: Vec::new(),
+    };
+    merge_profile(conn, profile_ref, payload, &mut Vec::new(), &mut resolved)?;
+    Ok(resolved)
+}
+
+fn merge_profile(
+    conn: &Connection,
+    profile_ref: &str,
+    payload: &serde_json::Value,
+    stack: &mut Vec<String>,
+    into: &mut ResolvedProfile,
+) -> Result<()> {
+    stack.push(profile_ref.to_string());
+
+    for base in profile_extends(profile_ref, payload)? {
+        // Checked before loading so self-extension of an unsaved payload
+        // reports the cycle rather than a missing profile
+        if stack.contains(&base) {
+            let mut path = stack.clone();
+            path.push(base.clone());
+            return Err(ExError::new(ExErrorKind::CycleDetected)
+                .with_op("resolve_profile")
+                .with_entity_id(&base)
+                .with_message(format!("profile extends cycle: {}", path.join(" -> "))));
+        }
+        let base_payload = load_raw_profile_payload(conn, &base)?.ok_or_else(|| {
+            ExError::new(ExErrorKind::ProfileNotFound)
+                .with_op("resolve_profile")
+                .with_entity_id(&base)
+                .with_message(format!(
+                    "base profile {} (extended by {}) not found",
+                    base, profile_ref
+                ))
+        })?;
+        merge_profile(conn, &base, &base_payload, stack, into)?;
+    }
+
+    let mut own = payload.clone();
+    if let serde_json::Value::Object(map) = &mut own {
+        map.remove(PROFILE_EXTENDS_KEY);
+    }
+    deep_merge(&mut into.effective, own);
+    if !into.extends_chain.iter().any(|r| r == profile_ref) {
+        into.extends_chain.push(profile_ref.to_string());
+    }
+
+    stack.pop();
+    Ok(())
+}
+
+/// The base refs named by a payload's `extends` key, in declaration order.
+fn profile_extends(profile_ref: &str, payload: &serde_json::Value) -> Result<Vec<String>> {
+    let invalid = || {
+        ExError::new(ExErrorKind::InvalidInput)
+            .with_op("resolve_profile")
+            .with_entity_id(profile_ref)
+            .with_message("extends must be a profile ref or an array of profile refs")
+    };
+    match payload.get(PROFILE_EXTENDS_KEY) {
+        None | Some(serde_json::Value::Null) => Ok(Vec::new()),
+        Some(serde_json::Value::String(s)) => Ok(vec![s.clone()]),
+        Some(serde_json::Value::Array(items)) => items
+            .iter()
+            .map(|v| v.as_str().map(String::from).ok_or_else(invalid))
+            .collect(),
+        Some(_) => Err(invalid()),
+    }
+}
+
+/// Merge `overlay` into `base`: objects merge recursively, anything else
+/// replaces the base value.
+fn deep_merge(base: &mut serde_json::Value, overlay: serde_json::Value) {
+    match (base, overlay) {
+        (serde_json::Value::Object(base_map), serde_json::Value::Object(overlay_map)) => {
+            for (key, value) in overlay_map {
+                match base_map.get_mut(&key) {
+                    Some(existing) => deep_merge(existing, value),
+                    None => {
+                        base_map.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
 /// Approval router backed by SQLite (writes to `approval_requests` table and CAS).
 ///
 /// When `cas` is provided (post-migration-007), the full request payload JSON is
@@ -130,13 +314,15 @@ impl ApprovalRouter for SqliteApprovalRouter<'_> {
 
         // Write full payload to CAS if available (migration 007+)
         let request_digest: Option<String> = if let Some(cas) = self.cas {
-            let payload = serde_json::json!({
-                "approval_token": token,
-                "reason_code": reason_code,
-                "candidate_set_json": candidate_json,
-                "semantic_request_digest": semantic_digest,
-                "created_at": now_ms,
-            });
+            let payload = ApprovalRequestPayload {
+                approval_token: token.clone(),
+                reason_code: reason_code.to_string(),
+                candidates: candidate_set,
+                semantic_request_digest: semantic_digest.clone(),
+                created_at: now_ms,
+                extra: Default::default(),
+            }
+            .to_stored_json();
             let payload_bytes = serde_json::to_string(&payload).map_err(|e| {
                 ExError::new(ExErrorKind::Serialization)
                     .with_message(format!("Failed to serialize approval payload: {}", e))
@@ -151,16 +337,18 @@ impl ApprovalRouter for SqliteApprovalRouter<'_> {
             None
         };
 
-        conn.execute(
+        let tx = conn.savepoint().map_err(from_rusqlite)?;
+        tx.execute(
             r#"INSERT INTO approval_requests
                (approval_token, reason_code, candidate_set_json, semantic_request_digest,
                 status, created_at, request_digest)
-               VALUES (?1, ?2, ?3, ?4, 'pending', ?5, ?6)"#,
+               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)"#,
             rusqlite::params![
                 token,
                 reason_code,
                 candidate_json,
                 semantic_digest,
+                APPROVAL_STATUS_PENDING,
                 now_ms,
                 request_digest
             ],
@@ -170,6 +358,8 @@ impl ApprovalRouter for SqliteApprovalRouter<'_> {
                 .with_op("route_approval_request")
                 .with_message(format!("Failed to insert approval request: {}", e))
         })?;
+        insert_approval_event(&tx, &token, None, APPROVAL_STATUS_PENDING, None, now_ms)?;
+        tx.commit().map_err(from_rusqlite)?;
 
         Ok(token)
     }
@@ -424,6 +614,85 @@ pub fn set_default_profile(conn: &Connection, profile_ref: &str) -> Result<()> {
     Ok(())
 }
 
+/// Set the default profile for one environment (e.g. `dev`, `staging`, `prod`).
+///
+/// Replaces any existing default for `environment`. The global `is_default`
+/// flag is left untouched. Returns `Err(ProfileNotFound)` if the profile does
+/// not exist.
+pub fn set_environment_default_profile(
+    conn: &Connection,
+    environment: &str,
+    profile_ref: &str,
+) -> Result<()> {
+    let exists: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM profiles WHERE profile_ref = ?1",
+            [profile_ref],
+            |row| row.get::<_, i64>(0),
+        )
+        .map(|n| n > 0)
+        .map_err(|e| {
+            ExError::new(ExErrorKind::Persistence)
+                .with_op("set_environment_default_profile")
+                .with_message(format!("DB error: {}", e))
+        })?;
+
+    if !exists {
+        return Err(ExError::new(ExErrorKind::ProfileNotFound)
+            .with_entity_id(profile_ref)
+            .with_message("Profile not found"));
+    }
+
+    let now_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0);
+
+    conn.execute(
+        "INSERT INTO profile_environment_defaults (environment, profile_ref, updated_at_ms)
+         VALUES (?1, ?2, ?3)
+         ON CONFLICT(environment) DO UPDATE SET
+             profile_ref = excluded.profile_ref,
+             updated_at_ms = excluded.updated_at_ms",
+        rusqlite::params![environment, profile_ref, now_ms],
+    )
+    .map_err(|e| {
+        ExError::new(ExErrorKind::Persistence)
+            .with_op("set_environment_default_profile")
+            .with_message(format!("DB upsert error: {}", e))
+    })?;
+
+    Ok(())
+}
+
+/// Load the default profile for `environment`:
+/// `(profile_ref, sha256_of_payload, payload_json)`.
+///
+/// Falls back to the global default (see [`load_default_profile`]) when the
+/// environment has no default of its own.
+pub fn load_environment_default_profile(
+    conn: &Connection,
+    environment: &str,
+) -> Result<Option<(String, String, serde_json::Value)>> {
+    let pref: Option<String> = conn
+        .query_row(
+            "SELECT profile_ref FROM profile_environment_defaults WHERE environment = ?1",
+            [environment],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| {
+            ExError::new(ExErrorKind::Persistence)
+                .with_op("load_environment_default_profile")
+                .with_message(format!("DB error: {}", e))
+        })?;
+
+    match pref {
+        Some(pref) => load_profile_full(conn, &pref),
+        None => load_default_profile(conn),
+    }
+}
+
 /// Fetch an approval request row by token.
 ///
 /// Returns `None` if no row with the given `approval_token` exists.
@@ -479,13 +748,214 @@ pub fn fetch_approval_row(conn: &Connection, approval_token: &str) -> Result<Opt
     }
 }
 
+fn insert_approval_event(
+    conn: &Connection,
+    approval_token: &str,
+    from_status: Option<&str>,
+    to_status: &str,
+    actor: Option<&str>,
+    occurred_at_ms: i64,
+) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO approval_events
+         (approval_token, from_status, to_status, actor, occurred_at_ms)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        rusqlite::params![
+            approval_token,
+            from_status,
+            to_status,
+            actor,
+            occurred_at_ms
+        ],
+    )
+    .map_err(|e| {
+        ExError::new(ExErrorKind::Persistence)
+            .with_op("insert_approval_event")
+            .with_message(format!("Failed to insert approval event: {}", e))
+    })?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Move a pending approval request to `to_status` and record the event.
+///
+/// `to_status` must be one of [`APPROVAL_TERMINAL_STATUSES`] (`InvalidInput`
+/// otherwise). Returns `ApprovalNotFound` for an unknown token and
+/// `ConstraintViolation` if the request is no longer pending. The status
+/// update and the event are written in one transaction.
+pub fn transition_approval(
+    conn: &mut Connection,
+    approval_token: &str,
+    to_status: &str,
+    actor: Option<&str>,
+) -> Result<ApprovalEventRow> {
+    if !APPROVAL_TERMINAL_STATUSES.contains(&to_status) {
+        return Err(ExError::new(ExErrorKind::InvalidInput)
+            .with_op("transition_approval")
+            .with_entity_id(approval_token)
+            .with_message(format!(
+                "status must be one of {:?}, got '{}'",
+                APPROVAL_TERMINAL_STATUSES, to_status
+            )));
+    }
+
+    let tx = conn.savepoint().map_err(from_rusqlite)?;
+    let from_status: String = tx
+        .query_row(
+            "SELECT status FROM approval_requests WHERE approval_token = ?1",
+            [approval_token],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(from_rusqlite)?
+        .ok_or_else(|| {
+            ExError::new(ExErrorKind::ApprovalNotFound)
+                .with_op("transition_approval")
+                .with_entity_id(approval_token)
+                .with_message("approval request not found")
+        })?;
+    if from_status != APPROVAL_STATUS_PENDING {
+        return Err(ExError::new(ExErrorKind::ConstraintViolation)
+            .with_op("transition_approval")
+            .with_entity_id(approval_token)
+            .with_message(format!(
+                "approval request is already '{}'; only pending requests can transition",
+                from_status
+            )));
+    }
+
+    let now_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0);
+    tx.execute(
+        "UPDATE approval_requests SET status = ?1 WHERE approval_token = ?2",
+        rusqlite::params![to_status, approval_token],
+    )
+    .map_err(from_rusqlite)?;
+    let id = insert_approval_event(
+        &tx,
+        approval_token,
+        Some(&from_status),
+        to_status,
+        actor,
+        now_ms,
+    )?;
+    tx.commit().map_err(from_rusqlite)?;
+
+    Ok(ApprovalEventRow {
+        id,
+        approval_token: approval_token.to_string(),
+        from_status: Some(from_status),
+        to_status: to_status.to_string(),
+        actor: actor.map(String::from),
+        occurred_at_ms: now_ms,
+    })
+}
+
+/// List the lifecycle events for an approval token, oldest first.
+///
+/// Returns an empty list for an unknown token; callers that need to tell
+/// "unknown" from "no events" should check [`fetch_approval_row`].
+pub fn list_approval_events(
+    conn: &Connection,
+    approval_token: &str,
+) -> Result<Vec<ApprovalEventRow>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, approval_token, from_status, to_status, actor, occurred_at_ms
+             FROM approval_events
+             WHERE approval_token = ?1
+             ORDER BY id",
+        )
+        .map_err(from_rusqlite)?;
+    let rows = stmt
+        .query_map([approval_token], |row| {
+            Ok(ApprovalEventRow {
+                id: row.get(0)?,
+                approval_token: row.get(1)?,
+                from_status: row.get(2)?,
+                to_status: row.get(3)?,
+                actor: row.get(4)?,
+                occurred_at_ms: row.get(5)?,
+            })
+        })
+        .map_err(from_rusqlite)?
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(from_rusqlite)?;
+    Ok(rows)
+}
+
+/// List rejected or expired requests that reached that status before
+/// `cutoff_ms` and still reference a payload blob, in token order.
+pub fn list_sweepable_approvals(
+    conn: &Connection,
+    cutoff_ms: i64,
+) -> Result<Vec<SweepableApproval>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT approval_token, status, request_digest, terminal_at FROM (
+                 SELECT r.approval_token, r.status, r.request_digest,
+                        COALESCE(
+                            (SELECT MAX(e.occurred_at_ms) FROM approval_events e
+                             WHERE e.approval_token = r.approval_token
+                               AND e.to_status = r.status),
+                            r.created_at) AS terminal_at
+                 FROM approval_requests r
+                 WHERE r.status IN (?1, ?2) AND r.request_digest IS NOT NULL)
+             WHERE terminal_at < ?3
+             ORDER BY approval_token",
+        )
+        .map_err(from_rusqlite)?;
+    let [rejected, expired] = APPROVAL_SWEEPABLE_STATUSES;
+    let rows = stmt
+        .query_map(rusqlite::params![rejected, expired, cutoff_ms], |row| {
+            Ok(SweepableApproval {
+                approval_token: row.get(0)?,
+                status: row.get(1)?,
+                request_digest: row.get(2)?,
+                terminal_at: row.get(3)?,
+            })
+        })
+        .map_err(from_rusqlite)?
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(from_rusqlite)?;
+    Ok(rows)
+}
+
+/// Drop a request's payload reference and record when it was swept.
+pub fn mark_approval_payload_swept(
+    conn: &Connection,
+    approval_token: &str,
+    swept_at: i64,
+) -> Result<()> {
+    conn.execute(
+        "UPDATE approval_requests SET request_digest = NULL, payload_swept_at_ms = ?1
+         WHERE approval_token = ?2",
+        rusqlite::params![swept_at, approval_token],
+    )
+    .map_err(from_rusqlite)?;
+    Ok(())
+}
+
+/// When the request's payload was swept, if it was.
+pub fn approval_payload_swept_at(conn: &Connection, approval_token: &str) -> Result<Option<i64>> {
+    conn.query_row(
+        "SELECT payload_swept_at_ms FROM approval_requests WHERE approval_token = ?1",
+        [approval_token],
+        |row| row.get(0),
+    )
+    .optional()
+    .map(Option::flatten)
+    .map_err(from_rusqlite)
+}
+
 /// List approval rows with cursor-based pagination, ordered by `(created_at, approval_token)`.
 ///
-/// `after_key` is `(created_at_ms, approval_token)` exclusive lower bound.
+/// `after_key` is the `(created_at_ms, approval_token)` exclusive lower bound.
 /// Gracefully falls back to the pre-migration-007 schema if `request_digest` column is absent.
 pub fn list_approval_rows_paginated(
     conn: &Connection,
-    after_key: Option<(i64, &str)>,
+    after_key: Option<&PageKey>,
     limit: usize,
 ) -> Result<Vec<ApprovalRow>> {
     // Try with request_digest column first (post-migration-007).
@@ -503,10 +973,10 @@ pub fn list_approval_rows_paginated(
 
 fn query_approval_rows_with_digest(
     conn: &Connection,
-    after_key: Option<(i64, &str)>,
+    after_key: Option<&PageKey>,
     limit: usize,
 ) -> Result<Vec<ApprovalRow>> {
-    if let Some((ts, tok)) = after_key {
+    if let Some(k) = after_key {
         let sql = format!(
             "SELECT approval_token, reason_code, candidate_set_json,
                     semantic_request_digest, status, created_at, request_digest
@@ -517,7 +987,10 @@ fn query_approval_rows_with_digest(
         );
         let mut stmt = conn.prepare(&sql).map_err(from_rusqlite)?;
         let collected: std::result::Result<Vec<ApprovalRow>, _> = stmt
-            .query_map(rusqlite::params![ts, tok], approval_row_with_digest)
+            .query_map(
+                rusqlite::params![k.created_at_ms, k.id],
+                approval_row_with_digest,
+            )
             .map_err(from_rusqlite)?
             .collect();
         collected.map_err(from_rusqlite)
@@ -540,10 +1013,10 @@ fn query_approval_rows_with_digest(
 
 fn query_approval_rows_no_digest(
     conn: &Connection,
-    after_key: Option<(i64, &str)>,
+    after_key: Option<&PageKey>,
     limit: usize,
 ) -> Result<Vec<ApprovalRow>> {
-    if let Some((ts, tok)) = after_key {
+    if let Some(k) = after_key {
         let sql = format!(
             "SELECT approval_token, reason_code, candidate_set_json,
                     semantic_request_digest, status, created_at
@@ -554,7 +1027,10 @@ fn query_approval_rows_no_digest(
         );
         let mut stmt = conn.prepare(&sql).map_err(from_rusqlite)?;
         let collected: std::result::Result<Vec<ApprovalRow>, _> = stmt
-            .query_map(rusqlite::params![ts, tok], approval_row_no_digest)
+            .query_map(
+                rusqlite::params![k.created_at_ms, k.id],
+                approval_row_no_digest,
+            )
             .map_err(from_rusqlite)?
             .collect();
         collected.map_err(from_rusqlite)
@@ -575,7 +1051,7 @@ fn query_approval_rows_no_digest(
     }
 }
 
-fn approval_row_with_digest(row: &rusqlite::Row<'_>) -> rusqlite::Result<ApprovalRow> {
+pub(crate) fn approval_row_with_digest(row: &rusqlite::Row<'_>) -> rusqlite::Result<ApprovalRow> {
     Ok(ApprovalRow {
         approval_token: row.get(0)?,
         reason_code: row.get(1)?,
@@ -632,6 +1108,104 @@ mod tests {
 
     // ── load_profile_full ──────────────────────────────────────────────────
 
+    #[test]
+    fn test_resolve_profile_merges_extends_chain() {
+        let conn = setup();
+        insert_profile(
+            &conn,
+            "profile/org@0",
+            false,
+            r#"{"ambiguity_policy":"fail_fast","vars":{"org":"acme","env":"prod"},"tags":["a"]}"#,
+        );
+        insert_profile(
+            &conn,
+            "profile/team@0",
+            false,
+            r#"{"extends":"profile/org@0","vars":{"env":"dev"},"tags":["b"]}"#,
+        );
+
+        let r = resolve_profile(&conn, "profile/team@0").unwrap().unwrap();
+        assert_eq!(
+            r.effective,
+            serde_json::json!({
+                "ambiguity_policy": "fail_fast",
+                "vars": { "org": "acme", "env": "dev" },
+                "tags": ["b"],
+            })
+        );
+        assert_eq!(r.extends_chain, vec!["profile/org@0", "profile/team@0"]);
+        assert_eq!(
+            load_profile_payload(&conn, "profile/team@0").unwrap(),
+            Some(r.effective)
+        );
+    }
+
+    #[test]
+    fn test_resolve_profile_composes_bases_left_to_right() {
+        let conn = setup();
+        insert_profile(&conn, "profile/base@0", false, r#"{"x":0}"#);
+        insert_profile(
+            &conn,
+            "profile/a@0",
+            false,
+            r#"{"extends":"profile/base@0","x":1}"#,
+        );
+        insert_profile(
+            &conn,
+            "profile/b@0",
+            false,
+            r#"{"extends":"profile/base@0","x":2}"#,
+        );
+        insert_profile(
+            &conn,
+            "profile/c@0",
+            false,
+            r#"{"extends":["profile/a@0","profile/b@0"]}"#,
+        );
+
+        let r = resolve_profile(&conn, "profile/c@0").unwrap().unwrap();
+        assert_eq!(r.effective, serde_json::json!({ "x": 2 }));
+        assert_eq!(
+            r.extends_chain,
+            vec![
+                "profile/base@0",
+                "profile/a@0",
+                "profile/b@0",
+                "profile/c@0"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_profile_detects_cycle() {
+        let conn = setup();
+        insert_profile(&conn, "profile/a@0", false, r#"{"extends":"profile/b@0"}"#);
+        insert_profile(&conn, "profile/b@0", false, r#"{"extends":"profile/a@0"}"#);
+        let err = resolve_profile(&conn, "profile/a@0").unwrap_err();
+        assert_eq!(err.kind(), ExErrorKind::CycleDetected);
+    }
+
+    #[test]
+    fn test_resolve_profile_missing_base_and_bad_extends() {
+        let conn = setup();
+        insert_profile(
+            &conn,
+            "profile/a@0",
+            false,
+            r#"{"extends":"profile/gone@0"}"#,
+        );
+        insert_profile(&conn, "profile/b@0", false, r#"{"extends":[1]}"#);
+        assert_eq!(
+            resolve_profile(&conn, "profile/a@0").unwrap_err().kind(),
+            ExErrorKind::ProfileNotFound
+        );
+        assert_eq!(
+            resolve_profile(&conn, "profile/b@0").unwrap_err().kind(),
+            ExErrorKind::InvalidInput
+        );
+        assert!(resolve_profile(&conn, "profile/none@0").unwrap().is_none());
+    }
+
     #[test]
     fn test_load_profile_full_found() {
         let conn = setup();
@@ -672,6 +1246,77 @@ mod tests {
         assert!(result.is_none());
     }
 
+    // ── environment defaults ──────────────────────────────────────────────
+
+    #[test]
+    fn test_environment_default_profile_overrides_global() {
+        let conn = setup();
+        insert_profile(&conn, "prof/global@0", true, r#"{"env": "any"}"#);
+        insert_profile(&conn, "prof/prod@0", false, r#"{"env": "prod"}"#);
+        set_environment_default_profile(&conn, "prod", "prof/prod@0").unwrap();
+
+        let (pref, _, val) = load_environment_default_profile(&conn, "prod")
+            .unwrap()
+            .unwrap();
+        assert_eq!(pref, "prof/prod@0");
+        assert_eq!(val["env"], "prod");
+
+        // Unconfigured environments fall back to the global default
+        let (pref, _, _) = load_environment_default_profile(&conn, "dev")
+            .unwrap()
+            .unwrap();
+        assert_eq!(pref, "prof/global@0");
+    }
+
+    #[test]
+    fn test_set_environment_default_profile_replaces_and_checks_profile() {
+        let conn = setup();
+        insert_profile(&conn, "prof/a@0", false, "{}");
+        insert_profile(&conn, "prof/b@0", false, "{}");
+        set_environment_default_profile(&conn, "staging", "prof/a@0").unwrap();
+        set_environment_default_profile(&conn, "staging", "prof/b@0").unwrap();
+        let (pref, _, _) = load_environment_default_profile(&conn, "staging")
+            .unwrap()
+            .unwrap();
+        assert_eq!(pref, "prof/b@0");
+
+        let err = set_environment_default_profile(&conn, "staging", "prof/missing@0").unwrap_err();
+        assert_eq!(err.kind(), ExErrorKind::ProfileNotFound);
+        assert!(load_default_profile(&conn).unwrap().is_none());
+    }
+
+    // ── approval events ───────────────────────────────────────────────────
+
+    #[test]
+    fn test_transition_approval_records_event() {
+        let mut conn = setup();
+        insert_approval(&conn, "tok-1", "ambiguous");
+
+        let event = transition_approval(&mut conn, "tok-1", "approved", Some("alice")).unwrap();
+        assert_eq!(event.from_status.as_deref(), Some("pending"));
+        assert_eq!(event.to_status, "approved");
+
+        let row = fetch_approval_row(&conn, "tok-1").unwrap().unwrap();
+        assert_eq!(row.status, "approved");
+        assert_eq!(list_approval_events(&conn, "tok-1").unwrap(), vec![event]);
+    }
+
+    #[test]
+    fn test_transition_approval_rejects_bad_transitions() {
+        let mut conn = setup();
+        insert_approval(&conn, "tok-1", "ambiguous");
+
+        let err = transition_approval(&mut conn, "tok-1", "pending", None).unwrap_err();
+        assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+        let err = transition_approval(&mut conn, "tok-missing", "rejected", None).unwrap_err();
+        assert_eq!(err.kind(), ExErrorKind::ApprovalNotFound);
+
+        transition_approval(&mut conn, "tok-1", "expired", None).unwrap();
+        let err = transition_approval(&mut conn, "tok-1", "approved", None).unwrap_err();
+        assert_eq!(err.kind(), ExErrorKind::ConstraintViolation);
+        assert_eq!(list_approval_events(&conn, "tok-1").unwrap().len(), 1);
+    }
+
     // ── list_profiles_paginated ───────────────────────────────────────────
 
     #[test]
@@ -751,9 +1396,9 @@ mod tests {
         }
         let page1 = list_approval_rows_paginated(&conn, None, 2).unwrap();
         assert_eq!(page1.len(), 2);
-        let after_ts = page1.last().unwrap().created_at;
-        let after_tok = page1.last().unwrap().approval_token.clone();
-        let page2 = list_approval_rows_paginated(&conn, Some((after_ts, &after_tok)), 2).unwrap();
+        let last = page1.last().unwrap();
+        let after = PageKey::new(last.created_at, last.approval_token.clone());
+        let page2 = list_approval_rows_paginated(&conn, Some(&after), 2).unwrap();
         assert_eq!(page2.len(), 2);
         // Pages must be disjoint
         let toks1: Vec<_> = page1.iter().map(|r| r.approval_token.clone()).collect();
@@ -791,9 +1436,9 @@ mod tests {
         }
         let page1 = query_approval_rows_no_digest(&conn, None, 2).unwrap();
         assert_eq!(page1.len(), 2);
-        let after_ts = page1.last().unwrap().created_at;
-        let after_tok = page1.last().unwrap().approval_token.clone();
-        let page2 = query_approval_rows_no_digest(&conn, Some((after_ts, &after_tok)), 2).unwrap();
+        let last = page1.last().unwrap();
+        let after = PageKey::new(last.created_at, last.approval_token.clone());
+        let page2 = query_approval_rows_no_digest(&conn, Some(&after), 2).unwrap();
         assert_eq!(page2.len(), 2);
     }
 }
@@ -0,0 +1,219 @@
+//! Typed, versioned pagination cursors shared by every list query.
+//!
+//! A cursor is opaque to clients: URL-safe base64 (no padding) of
+//!
+//! ```text
+//! v1:<kind>:<signature>:<payload>
+//! ```
+//!
+//! `kind` names the key type ([`CursorKey::KIND`]) so a cursor from one
+//! listing cannot be replayed against a listing with a different sort key.
+//! `signature` is empty unless a signing key is configured, in which case it
+//! is the hex HMAC-SHA256 of `v1:<kind>:<payload>`. `payload` is the key's own
+//! encoding and may contain `:`.
+//!
+//! Cursors written before versioning ("v0") are base64 of the bare payload,
+//! standard or URL-safe, padded or not. They still decode, so cursors stored
+//! by clients survive the upgrade; new cursors are always v1. With a signing
+//! key configured, only signed v1 cursors are accepted.
+//!
+//! There is no process-wide key: whoever serves a listing owns a
+//! [`CursorCodec`] and hands it to the query (e.g. through
+//! `ListOptions::cursor_codec` in the engine), so two servers in one process
+//! can sign with different keys.
+
+#![allow(clippy::result_large_err)]
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine as _;
+use ettlex_core::errors::{ExError, ExErrorKind};
+
+use crate::crypto::{hmac_sha256, verify_hmac_sha256_hex};
+use crate::errors::Result;
+use crate::model::EttleCursor;
+use crate::repo::page_key::PageKey;
+
+/// Version tag of the cursors this build writes.
+pub const CURSOR_VERSION: &str = "v1";
+
+/// A listing's sort key, as carried in a cursor.
+pub trait CursorKey: Sized {
+    /// Stable tag for this key type. Never change it: stored cursors carry it.
+    const KIND: &'static str;
+
+    /// Encode the key as the cursor payload.
+    fn to_payload(&self) -> String;
+
+    /// Parse a payload written by [`CursorKey::to_payload`] (or by the
+    /// pre-versioning encoder for this listing).
+    fn from_payload(payload: &str) -> Option<Self>;
+}
+
+/// Bare ID keys (Ettle and profile listings ordered by ID).
+impl CursorKey for String {
+    const KIND: &'static str = "id";
+
+    fn to_payload(&self) -> String {
+        self.clone()
+    }
+
+    fn from_payload(payload: &str) -> Option<Self> {
+        Some(payload.to_string())
+    }
+}
+
+/// `(created_at_ms, id)` keyset keys.
+impl CursorKey for PageKey {
+    const KIND: &'static str = "ts";
+
+    fn to_payload(&self) -> String {
+        self.encode()
+    }
+
+    fn from_payload(payload: &str) -> Option<Self> {
+        PageKey::decode(payload)
+    }
+}
+
+/// `(created_at text, id)` keys of [`crate::repo::SqliteRepo::list_ettles`].
+impl CursorKey for EttleCursor {
+    const KIND: &'static str = "ettle";
+
+    fn to_payload(&self) -> String {
+        format!("{},{}", self.created_at, self.id)
+    }
+
+    fn from_payload(payload: &str) -> Option<Self> {
+        // Split on the last comma: IDs never contain one
+        let (created_at, id) = payload.rsplit_once(',')?;
+        Some(EttleCursor {
+            created_at: created_at.to_string(),
+            id: id.to_string(),
+        })
+    }
+}
+
+/// Encodes and decodes cursors, optionally signing them.
+#[derive(Clone, Default)]
+pub struct CursorCodec {
+    signing_key: Option<Vec<u8>>,
+}
+
+impl std::fmt::Debug for CursorCodec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never print the key
+        f.debug_struct("CursorCodec")
+            .field("signed", &self.signing_key.is_some())
+            .finish()
+    }
+}
+
+impl CursorCodec {
+    /// A codec that neither signs nor requires signatures.
+    pub fn unsigned() -> Self {
+        Self::default()
+    }
+
+    /// A codec that signs with `key` and rejects unsigned cursors.
+    pub fn signed(key: impl Into<Vec<u8>>) -> Self {
+        Self {
+            signing_key: Some(key.into()),
+        }
+    }
+
+    /// [`CursorCodec::signed`] with `key`, or [`CursorCodec::unsigned`].
+    pub fn with_key(key: Option<Vec<u8>>) -> Self {
+        Self { signing_key: key }
+    }
+
+    /// Encode `key` as a v1 cursor.
+    pub fn encode<K: CursorKey>(&self, key: &K) -> String {
+        let payload = key.to_payload();
+        let signature = self
+            .signing_key
+            .as_deref()
+            .map(|secret| signature(secret, K::KIND, &payload))
+            .unwrap_or_default();
+        let raw = format!("{}:{}:{}:{}", CURSOR_VERSION, K::KIND, signature, payload);
+        URL_SAFE_NO_PAD.encode(raw.as_bytes())
+    }
+
+    /// Decode a cursor for a listing keyed by `K`.
+    ///
+    /// # Errors
+    /// * `InvalidInput` - not base64 text, a different key kind, an unknown
+    ///   version, a malformed payload, or a missing or wrong signature
+    pub fn decode<K: CursorKey>(&self, cursor: &str) -> Result<K> {
+        let normalised: String = cursor
+            .trim_end_matches('=')
+            .chars()
+            .map(|c| match c {
+                '+' => '-',
+                '/' => '_',
+                c => c,
+            })
+            .collect();
+        let raw = URL_SAFE_NO_PAD
+            .decode(normalised.as_bytes())
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or_else(|| invalid("cursor is not base64-encoded text"))?;
+
+        let Some(rest) = raw.strip_prefix("v1:") else {
+            if is_versioned(&raw) {
+                return Err(invalid("unsupported cursor version"));
+            }
+            return self.decode_v0(&raw);
+        };
+        let mut parts = rest.splitn(3, ':');
+        let (Some(kind), Some(sig), Some(payload)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid("truncated v1 cursor"));
+        };
+        if kind != K::KIND {
+            return Err(invalid(&format!(
+                "cursor is for a '{}' listing, expected '{}'",
+                kind,
+                K::KIND
+            )));
+        }
+        if let Some(secret) = &self.signing_key {
+            if !verify_hmac_sha256_hex(secret, &signed_message(kind, payload), sig) {
+                return Err(invalid("cursor signature does not match"));
+            }
+        }
+        K::from_payload(payload).ok_or_else(|| invalid("malformed cursor payload"))
+    }
+
+    fn decode_v0<K: CursorKey>(&self, payload: &str) -> Result<K> {
+        if self.signing_key.is_some() {
+            return Err(invalid(
+                "unsigned cursor rejected: cursor signing is enabled",
+            ));
+        }
+        K::from_payload(payload).ok_or_else(|| invalid("malformed cursor payload"))
+    }
+}
+
+/// Whether `raw` starts with a `v<digits>:` version tag.
+fn is_versioned(raw: &str) -> bool {
+    raw.strip_prefix('v')
+        .and_then(|r| r.split_once(':'))
+        .is_some_and(|(n, _)| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+fn invalid(message: &str) -> ExError {
+    ExError::new(ExErrorKind::InvalidInput)
+        .with_op("cursor_decode")
+        .with_message(message)
+}
+
+/// Hex HMAC-SHA256 of `v1:<kind>:<payload>` under `secret`.
+fn signature(secret: &[u8], kind: &str, payload: &str) -> String {
+    hex::encode(hmac_sha256(secret, &signed_message(kind, payload)))
+}
+
+/// The bytes a cursor signature covers.
+fn signed_message(kind: &str, payload: &str) -> Vec<u8> {
+    format!("{}:{}:{}", CURSOR_VERSION, kind, payload).into_bytes()
+}
@@ -2,7 +2,11 @@
 //!
 //! Bridges Phase 0.5 in-memory Store to SQLite persistence
 
+pub mod cursor;
 pub mod hydration;
+pub mod page_key;
 pub mod sqlite_repo;
 
+pub use cursor::{CursorCodec, CursorKey};
+pub use page_key::PageKey;
 pub use sqlite_repo::SqliteRepo;
@@ -0,0 +1,93 @@
+//! Stable keyset-pagination keys.
+//!
+//! Every paginated listing orders on `(created_at_ms, id)`. The integer
+//! millisecond timestamp gives a total chronological order regardless of how
+//! the textual `created_at` was formatted (RFC 3339 with or without
+//! fractional seconds, `Z` vs `+00:00`); the unique `id` breaks ties between
+//! rows created in the same millisecond, so no row can be skipped or repeated
+//! across pages.
+
+use std::cmp::Ordering;
+
+/// Exclusive lower bound for the next page: the last row of the previous one.
+///
+/// Ordering matches SQL `ORDER BY created_at_ms, id` (SQLite compares TEXT
+/// bytewise, as does `String`).
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PageKey {
+    pub created_at_ms: i64,
+    pub id: String,
+}
+
+impl PageKey {
+    pub fn new(created_at_ms: i64, id: impl Into<String>) -> Self {
+        Self {
+            created_at_ms,
+            id: id.into(),
+        }
+    }
+
+    /// Key for a row whose `created_at` is stored as RFC 3339 text.
+    pub fn from_timestamp(created_at: &str, id: impl Into<String>) -> Self {
+        Self::new(timestamp_ms(created_at), id)
+    }
+
+    /// Cursor payload: `"<created_at_ms>|<id>"`.
+    pub fn encode(&self) -> String {
+        format!("{}|{}", self.created_at_ms, self.id)
+    }
+
+    /// Parse a payload produced by [`PageKey::encode`]. Returns `None` for
+    /// anything malformed, which callers treat as "start from the beginning".
+    pub fn decode(s: &str) -> Option<Self> {
+        let (ms, id) = s.split_once('|')?;
+        Some(Self::new(ms.parse().ok()?, id))
+    }
+
+    /// Whether a row with this key sorts strictly after `after`.
+    pub fn is_after(&self, after: Option<&PageKey>) -> bool {
+        after.map_or(true, |a| self.cmp(a) == Ordering::Greater)
+    }
+}
+
+/// Milliseconds since the Unix epoch for an RFC 3339 timestamp, rounded to the
+/// nearest millisecond (matching SQLite's own parsing, used to backfill
+/// `created_at_ms` in migration 021). Unparseable input maps to 0, the column
+/// default.
+pub fn timestamp_ms(ts: &str) -> i64 {
+    match chrono::DateTime::parse_from_rfc3339(ts) {
+        Ok(dt) => {
+            let ms = dt.timestamp_millis();
+            if dt.timestamp_subsec_nanos() % 1_000_000 >= 500_000 {
+                ms + 1
+            } else {
+                ms
+            }
+        }
+        Err(_) => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timestamp_ms_ignores_text_format() {
+        let a = timestamp_ms("2026-01-01T00:00:00Z");
+        assert_eq!(a, timestamp_ms("2026-01-01T00:00:00.000+00:00"));
+        assert_eq!(a, timestamp_ms("2026-01-01T01:00:00+01:00"));
+        assert_eq!(a + 1, timestamp_ms("2026-01-01T00:00:00.000600Z"));
+        assert_eq!(0, timestamp_ms("not a timestamp"));
+    }
+
+    #[test]
+    fn test_page_key_round_trip_and_order() {
+        let k = PageKey::new(5, "cmt:a|b");
+        assert_eq!(PageKey::decode(&k.encode()), Some(k.clone()));
+        assert!(PageKey::new(5, "cmt:b").is_after(Some(&k)));
+        assert!(!PageKey::new(4, "zzz").is_after(Some(&k)));
+        assert!(k.is_after(None));
+        assert_eq!(PageKey::decode("garbage"), None);
+    }
+}
@@ -6,13 +6,21 @@
 
 use crate::errors::{from_rusqlite, Result};
 use crate::model::{
+    ApiTokenRecord, CommandLogRecord, CommentRecord, CommitTicketRecord, EttleAssignmentRecord,
     EttleCursor, EttleListItem, EttleListOpts, EttleListPage, EttleRecord, GroupMemberRecord,
-    GroupRecord, RelationListOpts, RelationRecord, RelationTypeEntry,
+    GroupRecord, ImportSessionItem, ImportSessionRecord, JournalRecord, RelationListOpts,
+    RelationRecord, RelationTypeEntry, RootRecord, COMMIT_TICKET_APPLYING, COMMIT_TICKET_QUEUED,
+    IMPORT_SESSION_RUNNING, JOURNAL_APPLIED, JOURNAL_UNDONE,
 };
-use base64::Engine as _;
+use crate::profile::{approval_row_with_digest, ApprovalRow, APPROVAL_STATUS_PENDING};
+use crate::repo::cursor::CursorCodec;
+use crate::repo::page_key::{timestamp_ms, PageKey};
+use crate::snapshot::query::{row_to_snapshot_row, SnapshotRow};
 use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::model::slug::slugify;
 use ettlex_core::model::{Constraint, Decision, DecisionEvidenceItem, DecisionLink, Ettle};
 use rusqlite::{Connection, OptionalExtension, Transaction};
+use std::collections::HashSet;
 
 /// SQLite repository for Ettles and relations.
 pub struct SqliteRepo;
@@ -61,7 +69,8 @@ impl SqliteRepo {
         let result = conn
             .query_row(
                 "SELECT id, title, why, what, how, reasoning_link_id, reasoning_link_type, \
-                 created_at, updated_at, tombstoned_at \
+                 created_at, updated_at, tombstoned_at, slug, archived_at, content_format, \
+                 why_digest, what_digest, how_digest \
                  FROM ettles WHERE id = ?1",
                 [ettle_id],
                 |row| {
@@ -76,6 +85,12 @@ impl SqliteRepo {
                         created_at: row.get(7)?,
                         updated_at: row.get(8)?,
                         tombstoned_at: row.get(9)?,
+                        slug: row.get(10)?,
+                        archived_at: row.get(11)?,
+                        content_format: row.get(12)?,
+                        why_digest: row.get(13)?,
+                        what_digest: row.get(14)?,
+                        how_digest: row.get(15)?,
                     })
                 },
             )
@@ -84,8 +99,109 @@ impl SqliteRepo {
         Ok(result)
     }
 
-    /// List Ettles using cursor-based pagination on (created_at, id).
+    // -------------------------------------------------------------------------
+    // Ettle slugs
+    // -------------------------------------------------------------------------
+
+    /// Allocate an unused slug derived from `title`.
+    ///
+    /// Collisions (with active or tombstoned Ettles) are resolved by appending
+    /// `-2`, `-3`, … Titles without ASCII alphanumerics fall back to `ettle`.
+    pub fn allocate_ettle_slug(conn: &Connection, title: &str) -> Result<String> {
+        Self::allocate_ettle_slug_excluding(conn, title, &HashSet::new())
+    }
+
+    /// As [`Self::allocate_ettle_slug`], also treating `taken` as used.
+    /// Predicts the slugs of Ettles created in sequence in one batch.
+    pub fn allocate_ettle_slug_excluding(
+        conn: &Connection,
+        title: &str,
+        taken: &HashSet<String>,
+    ) -> Result<String> {
+        let base = match slugify(title) {
+            s if s.is_empty() => "ettle".to_string(),
+            s => s,
+        };
+        let mut candidate = base.clone();
+        let mut n = 2u64;
+        while taken.contains(&candidate) || Self::find_ettle_id_by_slug(conn, &candidate)?.is_some()
+        {
+            candidate = format!("{}-{}", base, n);
+            n += 1;
+        }
+        Ok(candidate)
+    }
+
+    /// Set an Ettle's slug. Fails with `ConstraintViolation` if the slug is taken.
+    pub fn set_ettle_slug(conn: &Connection, ettle_id: &str, slug: &str) -> Result<()> {
+        conn.execute(
+            "UPDATE ettles SET slug = ?1 WHERE id = ?2",
+            rusqlite::params![slug, ettle_id],
+        )
+        .map_err(from_rusqlite)?;
+        Ok(())
+    }
+
+    /// Look up an Ettle ID by slug (including tombstoned Ettles).
+    pub fn find_ettle_id_by_slug(conn: &Connection, slug: &str) -> Result<Option<String>> {
+        conn.query_row("SELECT id FROM ettles WHERE slug = ?1", [slug], |row| {
+            row.get(0)
+        })
+        .optional()
+        .map_err(from_rusqlite)
+    }
+
+    /// Resolve an Ettle reference that may be either an ID or a slug.
+    ///
+    /// An exact ID match wins; otherwise the reference is looked up as a slug.
+    /// Returns `None` if neither matches.
+    pub fn resolve_ettle_ref(conn: &Connection, reference: &str) -> Result<Option<String>> {
+        let by_id: Option<String> = conn
+            .query_row("SELECT id FROM ettles WHERE id = ?1", [reference], |row| {
+                row.get(0)
+            })
+            .optional()
+            .map_err(from_rusqlite)?;
+        match by_id {
+            Some(id) => Ok(Some(id)),
+            None => Self::find_ettle_id_by_slug(conn, reference),
+        }
+    }
+
+    /// Assign slugs to Ettles created before slugs existed.
+    ///
+    /// Rows are processed in `(created_at, id)` order so earlier Ettles win
+    /// the unsuffixed slug. Returns the number of rows updated.
+    pub fn backfill_ettle_slugs(conn: &Connection) -> Result<u64> {
+        let mut stmt = conn
+            .prepare("SELECT id, title FROM ettles WHERE slug IS NULL ORDER BY created_at, id")
+            .map_err(from_rusqlite)?;
+        let pending = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(from_rusqlite)?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(from_rusqlite)?;
+        for (id, title) in &pending {
+            let slug = Self::allocate_ettle_slug(conn, title)?;
+            Self::set_ettle_slug(conn, id, &slug)?;
+        }
+        Ok(pending.len() as u64)
+    }
+
+    /// List Ettles using cursor-based pagination on (created_at, id), with
+    /// unsigned cursors.
     pub fn list_ettles(conn: &Connection, opts: &EttleListOpts) -> Result<EttleListPage> {
+        Self::list_ettles_with_codec(conn, opts, &CursorCodec::unsigned())
+    }
+
+    /// [`SqliteRepo::list_ettles`], encoding the next-page cursor with `codec`.
+    pub fn list_ettles_with_codec(
+        conn: &Connection,
+        opts: &EttleListOpts,
+        codec: &CursorCodec,
+    ) -> Result<EttleListPage> {
         // Fetch limit+1 rows so we can detect if there's a next page
         let fetch_limit = opts.limit as i64 + 1;
 
@@ -94,71 +210,38 @@ impl SqliteRepo {
                 id: row.get(0)?,
                 title: row.get(1)?,
                 tombstoned_at: row.get(2)?,
+                archived_at: row.get(3)?,
             })
         }
 
-        let rows: Vec<EttleListItem> = match (&opts.cursor, opts.include_tombstoned) {
-            (Some(c), true) => {
-                let mut stmt = conn
-                    .prepare(
-                        "SELECT id, title, tombstoned_at FROM ettles \
-                         WHERE (created_at > ?1 OR (created_at = ?1 AND id > ?2)) \
-                         ORDER BY created_at, id LIMIT ?3",
-                    )
-                    .map_err(from_rusqlite)?;
-                let rows = stmt
-                    .query_map(rusqlite::params![c.created_at, c.id, fetch_limit], map_row)
-                    .map_err(from_rusqlite)?
-                    .collect::<std::result::Result<Vec<_>, _>>()
-                    .map_err(from_rusqlite)?;
-                rows
-            }
-            (Some(c), false) => {
-                let mut stmt = conn
-                    .prepare(
-                        "SELECT id, title, tombstoned_at FROM ettles \
-                         WHERE tombstoned_at IS NULL \
-                         AND (created_at > ?1 OR (created_at = ?1 AND id > ?2)) \
-                         ORDER BY created_at, id LIMIT ?3",
-                    )
-                    .map_err(from_rusqlite)?;
-                let rows = stmt
-                    .query_map(rusqlite::params![c.created_at, c.id, fetch_limit], map_row)
-                    .map_err(from_rusqlite)?
-                    .collect::<std::result::Result<Vec<_>, _>>()
-                    .map_err(from_rusqlite)?;
-                rows
-            }
-            (None, true) => {
-                let mut stmt = conn
-                    .prepare(
-                        "SELECT id, title, tombstoned_at FROM ettles \
-                         ORDER BY created_at, id LIMIT ?1",
-                    )
-                    .map_err(from_rusqlite)?;
-                let rows = stmt
-                    .query_map([fetch_limit], map_row)
-                    .map_err(from_rusqlite)?
-                    .collect::<std::result::Result<Vec<_>, _>>()
-                    .map_err(from_rusqlite)?;
-                rows
-            }
-            (None, false) => {
-                let mut stmt = conn
-                    .prepare(
-                        "SELECT id, title, tombstoned_at FROM ettles \
-                         WHERE tombstoned_at IS NULL \
-                         ORDER BY created_at, id LIMIT ?1",
-                    )
-                    .map_err(from_rusqlite)?;
-                let rows = stmt
-                    .query_map([fetch_limit], map_row)
-                    .map_err(from_rusqlite)?
-                    .collect::<std::result::Result<Vec<_>, _>>()
-                    .map_err(from_rusqlite)?;
-                rows
-            }
+        let mut conditions: Vec<&str> = Vec::new();
+        if !opts.include_tombstoned {
+            conditions.push("tombstoned_at IS NULL");
+        }
+        if !opts.include_archived {
+            conditions.push("archived_at IS NULL");
+        }
+        if opts.cursor.is_some() {
+            conditions.push("(created_at > ?2 OR (created_at = ?2 AND id > ?3))");
+        }
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {} ", conditions.join(" AND "))
         };
+        let sql = format!(
+            "SELECT id, title, tombstoned_at, archived_at FROM ettles {}\
+             ORDER BY created_at, id LIMIT ?1",
+            where_clause
+        );
+        let mut stmt = conn.prepare(&sql).map_err(from_rusqlite)?;
+        let rows: Vec<EttleListItem> = match &opts.cursor {
+            Some(c) => stmt.query_map(rusqlite::params![fetch_limit, c.created_at, c.id], map_row),
+            None => stmt.query_map(rusqlite::params![fetch_limit], map_row),
+        }
+        .map_err(from_rusqlite)?
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(from_rusqlite)?;
 
         // Detect next page
         let has_more = rows.len() > opts.limit as usize;
@@ -177,8 +260,10 @@ impl SqliteRepo {
                         |r| r.get(0),
                     )
                     .map_err(from_rusqlite)?;
-                let raw = format!("{},{}", created_at, last.id);
-                Some(base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(raw.as_bytes()))
+                Some(codec.encode(&EttleCursor {
+                    created_at,
+                    id: last.id.clone(),
+                }))
             } else {
                 None
             }
@@ -189,29 +274,11 @@ impl SqliteRepo {
         Ok(EttleListPage { items, next_cursor })
     }
 
-    /// Decode a base64 cursor string into an `EttleCursor`.
+    /// Decode an unsigned cursor string from [`SqliteRepo::list_ettles`]
+    /// into an `EttleCursor`. Pre-versioning cursors are still accepted.
+    /// Signed listings decode with their own [`CursorCodec`].
     pub fn decode_ettle_cursor(encoded: &str) -> Result<EttleCursor> {
-        let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
-            .decode(encoded.as_bytes())
-            .map_err(|e| {
-                ExError::new(ExErrorKind::InvalidInput)
-                    .with_op("decode_ettle_cursor")
-                    .with_message(format!("invalid cursor base64: {}", e))
-            })?;
-        let s = String::from_utf8(bytes).map_err(|e| {
-            ExError::new(ExErrorKind::InvalidInput)
-                .with_op("decode_ettle_cursor")
-                .with_message(format!("cursor not valid UTF-8: {}", e))
-        })?;
-        // Split on the LAST comma to handle created_at values that might contain commas
-        let comma_pos = s.rfind(',').ok_or_else(|| {
-            ExError::new(ExErrorKind::InvalidInput)
-                .with_op("decode_ettle_cursor")
-                .with_message("cursor missing comma separator")
-        })?;
-        let created_at = s[..comma_pos].to_string();
-        let id = s[comma_pos + 1..].to_string();
-        Ok(EttleCursor { created_at, id })
+        CursorCodec::unsigned().decode(encoded)
     }
 
     /// Update an existing Ettle's content fields.
@@ -275,6 +342,40 @@ impl SqliteRepo {
         Ok(())
     }
 
+    /// Set the CAS digests of an Ettle's spilled why/what/how bodies.
+    ///
+    /// `None` marks the field as stored inline. Does not touch the content
+    /// columns or `updated_at`.
+    pub fn set_ettle_content_digests(
+        conn: &Connection,
+        id: &str,
+        why_digest: Option<&str>,
+        what_digest: Option<&str>,
+        how_digest: Option<&str>,
+    ) -> Result<()> {
+        conn.execute(
+            "UPDATE ettles SET why_digest = ?1, what_digest = ?2, how_digest = ?3 WHERE id = ?4",
+            rusqlite::params![why_digest, what_digest, how_digest, id],
+        )
+        .map_err(from_rusqlite)?;
+        Ok(())
+    }
+
+    /// Set an Ettle's `content_format`, bumping `updated_at`.
+    pub fn set_ettle_content_format(
+        conn: &Connection,
+        id: &str,
+        content_format: &str,
+        updated_at: &str,
+    ) -> Result<()> {
+        conn.execute(
+            "UPDATE ettles SET content_format = ?1, updated_at = ?2 WHERE id = ?3",
+            rusqlite::params![content_format, updated_at, id],
+        )
+        .map_err(from_rusqlite)?;
+        Ok(())
+    }
+
     /// Set `tombstoned_at` on an Ettle.
     pub fn tombstone_ettle(conn: &Connection, id: &str, tombstoned_at: &str) -> Result<()> {
         conn.execute(
@@ -285,6 +386,112 @@ impl SqliteRepo {
         Ok(())
     }
 
+    /// Set or clear `archived_at` on a batch of Ettles, bumping `updated_at`.
+    pub fn set_ettles_archived(
+        conn: &Connection,
+        ids: &[String],
+        archived_at: Option<&str>,
+        updated_at: &str,
+    ) -> Result<()> {
+        let mut stmt = conn
+            .prepare("UPDATE ettles SET archived_at = ?1, updated_at = ?2 WHERE id = ?3")
+            .map_err(from_rusqlite)?;
+        for id in ids {
+            stmt.execute(rusqlite::params![archived_at, updated_at, id])
+                .map_err(from_rusqlite)?;
+        }
+        Ok(())
+    }
+
+    /// Clear `tombstoned_at` on an Ettle.
+    pub fn restore_ettle(conn: &Connection, id: &str, updated_at: &str) -> Result<()> {
+        conn.execute(
+            "UPDATE ettles SET tombstoned_at = NULL, updated_at = ?1 WHERE id = ?2",
+            rusqlite::params![updated_at, id],
+        )
+        .map_err(from_rusqlite)?;
+        Ok(())
+    }
+
+    /// IDs of all active (non-tombstoned) Ettles, ordered by id.
+    pub fn list_active_ettle_ids(conn: &Connection) -> Result<Vec<String>> {
+        let mut stmt = conn
+            .prepare("SELECT id FROM ettles WHERE tombstoned_at IS NULL ORDER BY id ASC")
+            .map_err(from_rusqlite)?;
+        let rows = stmt
+            .query_map([], |row| row.get(0))
+            .map_err(from_rusqlite)?
+            .collect::<std::result::Result<Vec<String>, _>>()
+            .map_err(from_rusqlite)?;
+        Ok(rows)
+    }
+
+    /// IDs of Ettles that are neither tombstoned nor archived, ordered by id.
+    pub fn list_unarchived_ettle_ids(conn: &Connection) -> Result<Vec<String>> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id FROM ettles WHERE tombstoned_at IS NULL AND archived_at IS NULL \
+                 ORDER BY id ASC",
+            )
+            .map_err(from_rusqlite)?;
+        let rows = stmt
+            .query_map([], |row| row.get(0))
+            .map_err(from_rusqlite)?
+            .collect::<std::result::Result<Vec<String>, _>>()
+            .map_err(from_rusqlite)?;
+        Ok(rows)
+    }
+
+    /// Count tombstoned Ettles.
+    pub fn count_tombstoned_ettles(conn: &Connection) -> Result<u64> {
+        let count: u64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM ettles WHERE tombstoned_at IS NOT NULL",
+                [],
+                |r| r.get(0),
+            )
+            .map_err(from_rusqlite)?;
+        Ok(count)
+    }
+
+    /// Count archived Ettles that are not tombstoned.
+    pub fn count_archived_ettles(conn: &Connection) -> Result<u64> {
+        let count: u64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM ettles WHERE tombstoned_at IS NULL AND archived_at IS NOT NULL",
+                [],
+                |r| r.get(0),
+            )
+            .map_err(from_rusqlite)?;
+        Ok(count)
+    }
+
+    /// IDs of active Ettles whose title contains `needle` (case-insensitive), ordered by id.
+    pub fn list_active_ettle_ids_title_contains(
+        conn: &Connection,
+        needle: &str,
+    ) -> Result<Vec<String>> {
+        let pattern = format!(
+            "%{}%",
+            needle
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        );
+        let mut stmt = conn
+            .prepare(
+                "SELECT id FROM ettles WHERE tombstoned_at IS NULL \
+                 AND title LIKE ?1 ESCAPE '\\' ORDER BY id ASC",
+            )
+            .map_err(from_rusqlite)?;
+        let rows = stmt
+            .query_map([pattern], |row| row.get(0))
+            .map_err(from_rusqlite)?
+            .collect::<std::result::Result<Vec<String>, _>>()
+            .map_err(from_rusqlite)?;
+        Ok(rows)
+    }
+
     /// Count active (non-tombstoned) Ettles that have `reasoning_link_id = ettle_id`.
     pub fn get_active_ettle_dependants_count(conn: &Connection, ettle_id: &str) -> Result<u64> {
         let count: u64 = conn
@@ -787,6 +994,25 @@ impl SqliteRepo {
         Ok(links)
     }
 
+    /// IDs of constraints linked from at least one active Decision via an active link.
+    pub fn list_justified_constraint_ids(conn: &Connection) -> Result<Vec<String>> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT DISTINCT l.target_id FROM decision_links l
+                 JOIN decisions d ON d.decision_id = l.decision_id
+                 WHERE l.target_kind = 'constraint' AND l.tombstoned_at IS NULL
+                   AND d.tombstoned_at IS NULL
+                 ORDER BY l.target_id",
+            )
+            .map_err(from_rusqlite)?;
+        let rows = stmt
+            .query_map([], |row| row.get(0))
+            .map_err(from_rusqlite)?
+            .collect::<std::result::Result<Vec<String>, _>>()
+            .map_err(from_rusqlite)?;
+        Ok(rows)
+    }
+
     /// List all Decision Evidence Items
     pub fn list_all_evidence_items(conn: &Connection) -> Result<Vec<DecisionEvidenceItem>> {
         let mut stmt = conn
@@ -819,6 +1045,57 @@ impl SqliteRepo {
         Ok(items)
     }
 
+    /// Evidence captures whose every referencing decision was tombstoned at
+    /// or before `tombstoned_before_ms`.
+    ///
+    /// Returns `(evidence_capture_id, decision_id, content_bytes)` sorted by
+    /// capture ID. Captures still referenced by an active (or more recently
+    /// tombstoned) decision are never listed.
+    pub fn list_expired_evidence_items(
+        conn: &Connection,
+        tombstoned_before_ms: i64,
+    ) -> Result<Vec<(String, String, u64)>> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT e.evidence_capture_id, MIN(d.decision_id), length(CAST(e.content AS BLOB))
+                 FROM decision_evidence_items e
+                 JOIN decisions d ON d.evidence_capture_id = e.evidence_capture_id
+                 WHERE d.tombstoned_at IS NOT NULL AND d.tombstoned_at <= ?1
+                   AND NOT EXISTS (
+                       SELECT 1 FROM decisions live
+                       WHERE live.evidence_capture_id = e.evidence_capture_id
+                         AND (live.tombstoned_at IS NULL OR live.tombstoned_at > ?1))
+                 GROUP BY e.evidence_capture_id
+                 ORDER BY e.evidence_capture_id",
+            )
+            .map_err(from_rusqlite)?;
+        let rows = stmt
+            .query_map([tombstoned_before_ms], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get::<_, i64>(2)? as u64))
+            })
+            .map_err(from_rusqlite)?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(from_rusqlite)?;
+        Ok(rows)
+    }
+
+    /// Delete an evidence capture and clear `evidence_capture_id` on the
+    /// decisions that referenced it. Their `evidence_hash` is kept as a
+    /// record of what was captured.
+    pub fn prune_evidence_item(conn: &Connection, evidence_capture_id: &str) -> Result<()> {
+        conn.execute(
+            "UPDATE decisions SET evidence_capture_id = NULL WHERE evidence_capture_id = ?1",
+            [evidence_capture_id],
+        )
+        .map_err(from_rusqlite)?;
+        conn.execute(
+            "DELETE FROM decision_evidence_items WHERE evidence_capture_id = ?1",
+            [evidence_capture_id],
+        )
+        .map_err(from_rusqlite)?;
+        Ok(())
+    }
+
     /// Get an Ettle from the database by ID (current schema: id, title, created_at, updated_at).
     pub fn get_ettle(conn: &Connection, ettle_id: &str) -> Result<Option<Ettle>> {
         let result = conn
@@ -859,8 +1136,9 @@ impl SqliteRepo {
         after_id: Option<&str>,
         limit: usize,
     ) -> Result<Vec<Ettle>> {
-        // Build query dynamically based on optional filters
-        let mut conditions: Vec<String> = Vec::new();
+        // Build query dynamically based on optional filters. Archived Ettles
+        // are never part of the default listing.
+        let mut conditions: Vec<String> = vec!["archived_at IS NULL".to_string()];
         let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
 
         if let Some(after) = after_id {
@@ -878,11 +1156,7 @@ impl SqliteRepo {
             params.push(Box::new(format!("{}%", escaped)));
         }
 
-        let where_clause = if conditions.is_empty() {
-            String::new()
-        } else {
-            format!("WHERE {}", conditions.join(" AND "))
-        };
+        let where_clause = format!("WHERE {}", conditions.join(" AND "));
 
         let sql = format!(
             "SELECT id, title, created_at, updated_at
@@ -897,22 +1171,66 @@ impl SqliteRepo {
         let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
 
         let ettles = stmt
-            .query_map(param_refs.as_slice(), |row| {
-                let id: String = row.get(0)?;
-                let title: String = row.get(1)?;
-                let created_at_str: String = row.get(2)?;
-                let updated_at_str: String = row.get(3)?;
-
-                let mut ettle = Ettle::new(id, title);
-                if let Ok(ts) = chrono::DateTime::parse_from_rfc3339(&created_at_str) {
-                    ettle.created_at = ts.with_timezone(&chrono::Utc);
-                }
-                if let Ok(ts) = chrono::DateTime::parse_from_rfc3339(&updated_at_str) {
-                    ettle.updated_at = ts.with_timezone(&chrono::Utc);
-                }
-
-                Ok(ettle)
-            })
+            .query_map(param_refs.as_slice(), ettle_summary_from_row)
+            .map_err(from_rusqlite)?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(from_rusqlite)?;
+
+        Ok(ettles)
+    }
+
+    /// List leaf Ettles in ID order, with keyset pagination.
+    ///
+    /// A leaf is an active (not tombstoned, not archived) Ettle with no active
+    /// `refinement` relation to an active child. With `root_ettle_id`, only
+    /// leaves reachable from that root through active refinements are listed;
+    /// the root itself counts if it has no children.
+    pub fn list_leaf_ettles_paginated(
+        conn: &Connection,
+        root_ettle_id: Option<&str>,
+        after_id: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<Ettle>> {
+        const ACTIVE_CHILD: &str = "r.relation_type = 'refinement' AND r.tombstoned_at IS NULL
+             AND c.tombstoned_at IS NULL AND c.archived_at IS NULL";
+
+        let subtree_filter = if root_ettle_id.is_some() {
+            "AND e.id IN subtree"
+        } else {
+            ""
+        };
+        let sql = format!(
+            "WITH RECURSIVE subtree(id) AS (
+                 SELECT ?1
+                 UNION
+                 SELECT r.target_ettle_id FROM relations r
+                 JOIN subtree s ON r.source_ettle_id = s.id
+                 JOIN ettles c ON c.id = r.target_ettle_id
+                 WHERE {active_child}
+             )
+             SELECT e.id, e.title, e.created_at, e.updated_at
+             FROM ettles e
+             WHERE e.tombstoned_at IS NULL AND e.archived_at IS NULL
+               AND (?2 IS NULL OR e.id > ?2)
+               {subtree_filter}
+               AND NOT EXISTS (
+                   SELECT 1 FROM relations r
+                   JOIN ettles c ON c.id = r.target_ettle_id
+                   WHERE r.source_ettle_id = e.id AND {active_child}
+               )
+             ORDER BY e.id
+             LIMIT {limit}",
+            active_child = ACTIVE_CHILD,
+            subtree_filter = subtree_filter,
+            limit = limit
+        );
+
+        let mut stmt = conn.prepare(&sql).map_err(from_rusqlite)?;
+        let ettles = stmt
+            .query_map(
+                rusqlite::params![root_ettle_id, after_id],
+                ettle_summary_from_row,
+            )
             .map_err(from_rusqlite)?
             .collect::<std::result::Result<Vec<_>, _>>()
             .map_err(from_rusqlite)?;
@@ -987,10 +1305,10 @@ impl SqliteRepo {
 
     /// List Decisions with cursor-based pagination.
     ///
-    /// `after_key` is `(created_at_ms, decision_id)` exclusive lower bound.
+    /// `after_key` is the `(created_at_ms, decision_id)` exclusive lower bound.
     pub fn list_decisions_paginated(
         conn: &Connection,
-        after_key: Option<(i64, &str)>,
+        after_key: Option<&PageKey>,
         limit: usize,
     ) -> Result<Vec<Decision>> {
         let sql = match after_key {
@@ -1021,10 +1339,55 @@ impl SqliteRepo {
 
         match after_key {
             None => Self::query_decisions(&mut stmt, []),
-            Some((ts, id)) => Self::query_decisions(&mut stmt, rusqlite::params![ts, id]),
+            Some(k) => Self::query_decisions(&mut stmt, rusqlite::params![k.created_at_ms, k.id]),
         }
     }
 
+    /// Search decisions whose title, decision_text or rationale contains
+    /// `text` (case-insensitive), ordered and paginated like
+    /// [`Self::list_decisions_paginated`].
+    ///
+    /// `status` restricts matches to a single decision status.
+    pub fn search_decisions_paginated(
+        conn: &Connection,
+        text: &str,
+        status: Option<&str>,
+        after_key: Option<&PageKey>,
+        limit: usize,
+    ) -> Result<Vec<Decision>> {
+        let pattern = format!(
+            "%{}%",
+            text.replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        );
+        let sql = format!(
+            "SELECT decision_id, title, status, decision_text, rationale,
+                    alternatives_text, consequences_text, evidence_kind,
+                    evidence_excerpt, evidence_capture_id, evidence_file_path,
+                    evidence_hash, created_at, updated_at, tombstoned_at
+             FROM decisions
+             WHERE (title LIKE ?1 ESCAPE '\\'
+                    OR decision_text LIKE ?1 ESCAPE '\\'
+                    OR rationale LIKE ?1 ESCAPE '\\')
+               AND (?2 IS NULL OR status = ?2)
+               AND (?3 IS NULL OR created_at > ?3 OR (created_at = ?3 AND decision_id > ?4))
+             ORDER BY created_at, decision_id
+             LIMIT {}",
+            limit
+        );
+
+        let mut stmt = conn.prepare(&sql).map_err(from_rusqlite)?;
+        let (after_ts, after_id) = match after_key {
+            Some(k) => (Some(k.created_at_ms), Some(k.id.as_str())),
+            None => (None, None),
+        };
+        Self::query_decisions(
+            &mut stmt,
+            rusqlite::params![pattern, status, after_ts, after_id],
+        )
+    }
+
     // -----------------------------------------------------------------------
     // Internal helpers
     // -----------------------------------------------------------------------
@@ -1155,7 +1518,7 @@ impl SqliteRepo {
     pub fn insert_relation(conn: &Connection, record: &RelationRecord) -> Result<()> {
         conn.execute(
             "INSERT INTO relations (id, source_ettle_id, target_ettle_id, relation_type, \
-             properties_json, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+             properties_json, created_at, created_at_ms) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
             rusqlite::params![
                 record.id,
                 record.source_ettle_id,
@@ -1163,6 +1526,7 @@ impl SqliteRepo {
                 record.relation_type,
                 record.properties_json,
                 record.created_at,
+                timestamp_ms(&record.created_at),
             ],
         )
         .map_err(from_rusqlite)?;
@@ -1194,7 +1558,7 @@ impl SqliteRepo {
         Ok(result)
     }
 
-    /// List relations with optional filters, sorted by (created_at ASC, id ASC).
+    /// List relations with optional filters, sorted by (created_at_ms ASC, id ASC).
     pub fn list_relations(
         conn: &Connection,
         opts: &RelationListOpts,
@@ -1222,7 +1586,7 @@ impl SqliteRepo {
         let sql = format!(
             "SELECT id, source_ettle_id, target_ettle_id, relation_type, \
              properties_json, created_at, tombstoned_at \
-             FROM relations {} ORDER BY created_at ASC, id ASC",
+             FROM relations {} ORDER BY created_at_ms ASC, id ASC",
             where_clause
         );
 
@@ -1272,6 +1636,17 @@ impl SqliteRepo {
         Ok(rows_changed > 0)
     }
 
+    /// Clear `tombstoned_at` on a relation. Returns false if not found.
+    pub fn restore_relation(conn: &Connection, id: &str) -> Result<bool> {
+        let rows_changed = conn
+            .execute(
+                "UPDATE relations SET tombstoned_at = NULL WHERE id = ?1",
+                [id],
+            )
+            .map_err(from_rusqlite)?;
+        Ok(rows_changed > 0)
+    }
+
     /// Count active outgoing constraint relations from a source ettle.
     pub fn count_active_outgoing_constraint_relations(
         conn: &Connection,
@@ -1289,23 +1664,57 @@ impl SqliteRepo {
         Ok(count)
     }
 
-    /// Get target_ettle_ids of active outgoing relations of a given type from source.
-    pub fn get_active_outgoing_relations_of_type(
-        conn: &Connection,
-        source_ettle_id: &str,
-        relation_type: &str,
-    ) -> Result<Vec<String>> {
+    /// List active `constraint` relations with a missing or tombstoned
+    /// endpoint Ettle, sorted by (created_at_ms ASC, id ASC).
+    pub fn list_dangling_constraint_relations(conn: &Connection) -> Result<Vec<RelationRecord>> {
         let mut stmt = conn
             .prepare(
-                "SELECT target_ettle_id FROM relations \
-                 WHERE source_ettle_id = ?1 AND relation_type = ?2 AND tombstoned_at IS NULL",
+                "SELECT r.id, r.source_ettle_id, r.target_ettle_id, r.relation_type, \
+                 r.properties_json, r.created_at, r.tombstoned_at \
+                 FROM relations r \
+                 LEFT JOIN ettles s ON s.id = r.source_ettle_id \
+                 LEFT JOIN ettles t ON t.id = r.target_ettle_id \
+                 WHERE r.relation_type = 'constraint' AND r.tombstoned_at IS NULL \
+                 AND (s.id IS NULL OR s.tombstoned_at IS NOT NULL \
+                      OR t.id IS NULL OR t.tombstoned_at IS NOT NULL) \
+                 ORDER BY r.created_at_ms ASC, r.id ASC",
             )
             .map_err(from_rusqlite)?;
         let rows = stmt
-            .query_map(rusqlite::params![source_ettle_id, relation_type], |row| {
-                row.get(0)
-            })
-            .map_err(from_rusqlite)?
+            .query_map([], |row| {
+                Ok(RelationRecord {
+                    id: row.get(0)?,
+                    source_ettle_id: row.get(1)?,
+                    target_ettle_id: row.get(2)?,
+                    relation_type: row.get(3)?,
+                    properties_json: row.get(4)?,
+                    created_at: row.get(5)?,
+                    tombstoned_at: row.get(6)?,
+                })
+            })
+            .map_err(from_rusqlite)?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(from_rusqlite)?;
+        Ok(rows)
+    }
+
+    /// Get target_ettle_ids of active outgoing relations of a given type from source.
+    pub fn get_active_outgoing_relations_of_type(
+        conn: &Connection,
+        source_ettle_id: &str,
+        relation_type: &str,
+    ) -> Result<Vec<String>> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT target_ettle_id FROM relations \
+                 WHERE source_ettle_id = ?1 AND relation_type = ?2 AND tombstoned_at IS NULL",
+            )
+            .map_err(from_rusqlite)?;
+        let rows = stmt
+            .query_map(rusqlite::params![source_ettle_id, relation_type], |row| {
+                row.get(0)
+            })
+            .map_err(from_rusqlite)?
             .collect::<std::result::Result<Vec<String>, _>>()
             .map_err(from_rusqlite)?;
         Ok(rows)
@@ -1318,8 +1727,13 @@ impl SqliteRepo {
     /// Insert a new group row.
     pub fn insert_group(conn: &Connection, record: &GroupRecord) -> Result<()> {
         conn.execute(
-            "INSERT INTO groups (id, name, created_at) VALUES (?1, ?2, ?3)",
-            rusqlite::params![record.id, record.name, record.created_at],
+            "INSERT INTO groups (id, name, created_at, created_at_ms) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![
+                record.id,
+                record.name,
+                record.created_at,
+                timestamp_ms(&record.created_at)
+            ],
         )
         .map_err(from_rusqlite)?;
         Ok(())
@@ -1345,13 +1759,14 @@ impl SqliteRepo {
         Ok(result)
     }
 
-    /// List groups sorted by created_at ASC.
+    /// List groups sorted by (created_at_ms ASC, id ASC).
     pub fn list_groups(conn: &Connection, include_tombstoned: bool) -> Result<Vec<GroupRecord>> {
         let sql = if include_tombstoned {
-            "SELECT id, name, created_at, tombstoned_at FROM groups ORDER BY created_at ASC, id ASC"
+            "SELECT id, name, created_at, tombstoned_at FROM groups \
+             ORDER BY created_at_ms ASC, id ASC"
         } else {
             "SELECT id, name, created_at, tombstoned_at FROM groups \
-             WHERE tombstoned_at IS NULL ORDER BY created_at ASC, id ASC"
+             WHERE tombstoned_at IS NULL ORDER BY created_at_ms ASC, id ASC"
         };
         let mut stmt = conn.prepare(sql).map_err(from_rusqlite)?;
         let rows = stmt
@@ -1380,6 +1795,14 @@ impl SqliteRepo {
         Ok(rows_changed > 0)
     }
 
+    /// Clear `tombstoned_at` on a group. Returns false if not found.
+    pub fn restore_group(conn: &Connection, id: &str) -> Result<bool> {
+        let rows_changed = conn
+            .execute("UPDATE groups SET tombstoned_at = NULL WHERE id = ?1", [id])
+            .map_err(from_rusqlite)?;
+        Ok(rows_changed > 0)
+    }
+
     /// Count active (non-tombstoned) members in a group.
     pub fn count_active_group_members(conn: &Connection, group_id: &str) -> Result<u64> {
         let count: u64 = conn
@@ -1399,13 +1822,14 @@ impl SqliteRepo {
     /// Insert a new group member row.
     pub fn insert_group_member(conn: &Connection, record: &GroupMemberRecord) -> Result<()> {
         conn.execute(
-            "INSERT INTO group_members (id, group_id, ettle_id, created_at) \
-             VALUES (?1, ?2, ?3, ?4)",
+            "INSERT INTO group_members (id, group_id, ettle_id, created_at, created_at_ms) \
+             VALUES (?1, ?2, ?3, ?4, ?5)",
             rusqlite::params![
                 record.id,
                 record.group_id,
                 record.ettle_id,
-                record.created_at
+                record.created_at,
+                timestamp_ms(&record.created_at)
             ],
         )
         .map_err(from_rusqlite)?;
@@ -1464,7 +1888,7 @@ impl SqliteRepo {
         Ok(result)
     }
 
-    /// List members of a group sorted by created_at ASC.
+    /// List members of a group sorted by (created_at_ms ASC, id ASC).
     pub fn list_group_members(
         conn: &Connection,
         group_id: &str,
@@ -1472,11 +1896,11 @@ impl SqliteRepo {
     ) -> Result<Vec<GroupMemberRecord>> {
         let sql = if include_tombstoned {
             "SELECT id, group_id, ettle_id, created_at, tombstoned_at \
-             FROM group_members WHERE group_id = ?1 ORDER BY created_at ASC, id ASC"
+             FROM group_members WHERE group_id = ?1 ORDER BY created_at_ms ASC, id ASC"
         } else {
             "SELECT id, group_id, ettle_id, created_at, tombstoned_at \
              FROM group_members WHERE group_id = ?1 AND tombstoned_at IS NULL \
-             ORDER BY created_at ASC, id ASC"
+             ORDER BY created_at_ms ASC, id ASC"
         };
         let mut stmt = conn.prepare(sql).map_err(from_rusqlite)?;
         let rows = stmt
@@ -1515,7 +1939,7 @@ impl SqliteRepo {
     /// At least one of `group_id` or `ettle_id` should be supplied by the caller;
     /// this function does not enforce that constraint (callers must).
     ///
-    /// Results are ordered `created_at ASC, id ASC`.
+    /// Results are ordered `created_at_ms ASC, id ASC`.
     pub fn list_group_members_by_filter(
         conn: &Connection,
         group_id: Option<&str>,
@@ -1541,7 +1965,7 @@ impl SqliteRepo {
 
         let sql = format!(
             "SELECT id, group_id, ettle_id, created_at, tombstoned_at \
-             FROM group_members {} ORDER BY created_at ASC, id ASC",
+             FROM group_members {} ORDER BY created_at_ms ASC, id ASC",
             where_clause
         );
 
@@ -1574,7 +1998,7 @@ impl SqliteRepo {
                  FROM groups g \
                  INNER JOIN group_members gm ON g.id = gm.group_id \
                  WHERE gm.ettle_id = ?1 AND gm.tombstoned_at IS NULL AND g.tombstoned_at IS NULL \
-                 ORDER BY g.created_at ASC, g.id ASC",
+                 ORDER BY g.created_at_ms ASC, g.id ASC",
             )
             .map_err(from_rusqlite)?;
         let rows = stmt
@@ -1591,6 +2015,1000 @@ impl SqliteRepo {
             .map_err(from_rusqlite)?;
         Ok(rows)
     }
+
+    // =========================================================================
+    // Root registry
+    // =========================================================================
+
+    /// Insert a new root registry row.
+    pub fn insert_root(conn: &Connection, record: &RootRecord) -> Result<()> {
+        conn.execute(
+            "INSERT INTO roots \
+             (ettle_id, display_name, owner, status, registered_at_ms, updated_at_ms) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![
+                record.ettle_id,
+                record.display_name,
+                record.owner,
+                record.status,
+                record.registered_at_ms,
+                record.updated_at_ms
+            ],
+        )
+        .map_err(from_rusqlite)?;
+        Ok(())
+    }
+
+    /// Get the registry row for a root Ettle.
+    pub fn get_root(conn: &Connection, ettle_id: &str) -> Result<Option<RootRecord>> {
+        conn.query_row(
+            "SELECT ettle_id, display_name, owner, status, registered_at_ms, updated_at_ms \
+             FROM roots WHERE ettle_id = ?1",
+            [ettle_id],
+            Self::map_root_row,
+        )
+        .optional()
+        .map_err(from_rusqlite)
+    }
+
+    /// Get the registry row with the given display name.
+    pub fn get_root_by_display_name(
+        conn: &Connection,
+        display_name: &str,
+    ) -> Result<Option<RootRecord>> {
+        conn.query_row(
+            "SELECT ettle_id, display_name, owner, status, registered_at_ms, updated_at_ms \
+             FROM roots WHERE display_name = ?1",
+            [display_name],
+            Self::map_root_row,
+        )
+        .optional()
+        .map_err(from_rusqlite)
+    }
+
+    /// List registered roots ordered by display name. Retired roots are
+    /// skipped unless `include_retired` is true.
+    pub fn list_roots(conn: &Connection, include_retired: bool) -> Result<Vec<RootRecord>> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT ettle_id, display_name, owner, status, registered_at_ms, updated_at_ms \
+                 FROM roots WHERE ?1 OR status = 'active' \
+                 ORDER BY display_name, ettle_id",
+            )
+            .map_err(from_rusqlite)?;
+        let rows = stmt
+            .query_map([include_retired], Self::map_root_row)
+            .map_err(from_rusqlite)?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(from_rusqlite)?;
+        Ok(rows)
+    }
+
+    /// Overwrite the mutable fields of a root registry row.
+    pub fn update_root(conn: &Connection, record: &RootRecord) -> Result<()> {
+        conn.execute(
+            "UPDATE roots SET display_name = ?1, owner = ?2, status = ?3, updated_at_ms = ?4 \
+             WHERE ettle_id = ?5",
+            rusqlite::params![
+                record.display_name,
+                record.owner,
+                record.status,
+                record.updated_at_ms,
+                record.ettle_id
+            ],
+        )
+        .map_err(from_rusqlite)?;
+        Ok(())
+    }
+
+    /// Count active refinement relations into `ettle_id` whose source Ettle
+    /// is active (neither tombstoned nor archived).
+    pub fn count_active_refinement_parents(conn: &Connection, ettle_id: &str) -> Result<u64> {
+        conn.query_row(
+            "SELECT COUNT(*) FROM relations r JOIN ettles p ON p.id = r.source_ettle_id \
+             WHERE r.target_ettle_id = ?1 AND r.relation_type = 'refinement' \
+               AND r.tombstoned_at IS NULL \
+               AND p.tombstoned_at IS NULL AND p.archived_at IS NULL",
+            [ettle_id],
+            |row| row.get(0),
+        )
+        .map_err(from_rusqlite)
+    }
+
+    /// IDs of the implicit roots: active Ettles with no active refinement
+    /// parent that are not themselves constraints. Sorted by ID.
+    pub fn list_implicit_roots(conn: &Connection) -> Result<Vec<String>> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT e.id FROM ettles e \
+                 WHERE e.tombstoned_at IS NULL AND e.archived_at IS NULL \
+                   AND NOT EXISTS ( \
+                     SELECT 1 FROM relations r JOIN ettles p ON p.id = r.source_ettle_id \
+                     WHERE r.target_ettle_id = e.id AND r.relation_type = 'refinement' \
+                       AND r.tombstoned_at IS NULL \
+                       AND p.tombstoned_at IS NULL AND p.archived_at IS NULL) \
+                   AND NOT EXISTS ( \
+                     SELECT 1 FROM relations c \
+                     WHERE c.source_ettle_id = e.id AND c.relation_type = 'constraint' \
+                       AND c.tombstoned_at IS NULL) \
+                 ORDER BY e.id",
+            )
+            .map_err(from_rusqlite)?;
+        let rows = stmt
+            .query_map([], |row| row.get(0))
+            .map_err(from_rusqlite)?
+            .collect::<std::result::Result<Vec<String>, _>>()
+            .map_err(from_rusqlite)?;
+        Ok(rows)
+    }
+
+    fn map_root_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<RootRecord> {
+        Ok(RootRecord {
+            ettle_id: row.get(0)?,
+            display_name: row.get(1)?,
+            owner: row.get(2)?,
+            status: row.get(3)?,
+            registered_at_ms: row.get(4)?,
+            updated_at_ms: row.get(5)?,
+        })
+    }
+
+    // =========================================================================
+    // API tokens
+    // =========================================================================
+
+    /// Insert a newly issued API token.
+    pub fn insert_api_token(conn: &Connection, record: &ApiTokenRecord) -> Result<()> {
+        let scopes_json = serde_json::to_string(&record.scopes).map_err(|e| {
+            ExError::new(ExErrorKind::Serialization)
+                .with_op("insert_api_token")
+                .with_message(e.to_string())
+        })?;
+        conn.execute(
+            "INSERT INTO api_tokens \
+             (token_id, name, token_hash, scopes_json, created_at_ms, revoked_at_ms) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![
+                record.token_id,
+                record.name,
+                record.token_hash,
+                scopes_json,
+                record.created_at_ms,
+                record.revoked_at_ms
+            ],
+        )
+        .map_err(from_rusqlite)?;
+        Ok(())
+    }
+
+    /// Get an API token by ID, revoked or not.
+    pub fn get_api_token(conn: &Connection, token_id: &str) -> Result<Option<ApiTokenRecord>> {
+        conn.query_row(
+            "SELECT token_id, name, token_hash, scopes_json, created_at_ms, revoked_at_ms \
+             FROM api_tokens WHERE token_id = ?1",
+            [token_id],
+            Self::map_api_token_row,
+        )
+        .optional()
+        .map_err(from_rusqlite)
+    }
+
+    /// Get the API token whose secret hashes to `token_hash`, revoked or not.
+    pub fn get_api_token_by_hash(
+        conn: &Connection,
+        token_hash: &str,
+    ) -> Result<Option<ApiTokenRecord>> {
+        conn.query_row(
+            "SELECT token_id, name, token_hash, scopes_json, created_at_ms, revoked_at_ms \
+             FROM api_tokens WHERE token_hash = ?1",
+            [token_hash],
+            Self::map_api_token_row,
+        )
+        .optional()
+        .map_err(from_rusqlite)
+    }
+
+    /// List API tokens in issuance order. Revoked tokens are skipped unless
+    /// `include_revoked` is true.
+    pub fn list_api_tokens(
+        conn: &Connection,
+        include_revoked: bool,
+    ) -> Result<Vec<ApiTokenRecord>> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT token_id, name, token_hash, scopes_json, created_at_ms, revoked_at_ms \
+                 FROM api_tokens WHERE ?1 OR revoked_at_ms IS NULL \
+                 ORDER BY created_at_ms, token_id",
+            )
+            .map_err(from_rusqlite)?;
+        let rows = stmt
+            .query_map([include_revoked], Self::map_api_token_row)
+            .map_err(from_rusqlite)?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(from_rusqlite)?;
+        Ok(rows)
+    }
+
+    /// Mark an API token revoked. Returns `false` if it was already revoked
+    /// or does not exist.
+    pub fn revoke_api_token(conn: &Connection, token_id: &str, revoked_at_ms: i64) -> Result<bool> {
+        let changed = conn
+            .execute(
+                "UPDATE api_tokens SET revoked_at_ms = ?1 \
+                 WHERE token_id = ?2 AND revoked_at_ms IS NULL",
+                rusqlite::params![revoked_at_ms, token_id],
+            )
+            .map_err(from_rusqlite)?;
+        Ok(changed > 0)
+    }
+
+    fn map_api_token_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<ApiTokenRecord> {
+        let scopes_json: String = row.get(3)?;
+        let scopes = serde_json::from_str(&scopes_json).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(3, rusqlite::types::Type::Text, Box::new(e))
+        })?;
+        Ok(ApiTokenRecord {
+            token_id: row.get(0)?,
+            name: row.get(1)?,
+            token_hash: row.get(2)?,
+            scopes,
+            created_at_ms: row.get(4)?,
+            revoked_at_ms: row.get(5)?,
+        })
+    }
+
+    // =========================================================================
+    // Ettle assignments
+    // =========================================================================
+
+    /// List an Ettle's owner and reviewers, by role then principal.
+    pub fn list_ettle_assignments(
+        conn: &Connection,
+        ettle_id: &str,
+    ) -> Result<Vec<EttleAssignmentRecord>> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT ettle_id, role, principal, assigned_at_ms FROM ettle_assignments \
+                 WHERE ettle_id = ?1 ORDER BY role, principal",
+            )
+            .map_err(from_rusqlite)?;
+        let rows = stmt
+            .query_map([ettle_id], Self::map_ettle_assignment_row)
+            .map_err(from_rusqlite)?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(from_rusqlite)?;
+        Ok(rows)
+    }
+
+    /// Replace the principals holding `role` on an Ettle. Principals that
+    /// keep the role keep their original `assigned_at_ms`.
+    pub fn set_ettle_assignments(
+        conn: &Connection,
+        ettle_id: &str,
+        role: &str,
+        principals: &[String],
+        assigned_at_ms: i64,
+    ) -> Result<()> {
+        let keep = serde_json::to_string(principals).map_err(|e| {
+            ExError::new(ExErrorKind::Serialization)
+                .with_op("set_ettle_assignments")
+                .with_message(e.to_string())
+        })?;
+        conn.execute(
+            "DELETE FROM ettle_assignments WHERE ettle_id = ?1 AND role = ?2 \
+             AND principal NOT IN (SELECT value FROM json_each(?3))",
+            rusqlite::params![ettle_id, role, keep],
+        )
+        .map_err(from_rusqlite)?;
+        for principal in principals {
+            conn.execute(
+                "INSERT OR IGNORE INTO ettle_assignments \
+                 (ettle_id, role, principal, assigned_at_ms) VALUES (?1, ?2, ?3, ?4)",
+                rusqlite::params![ettle_id, role, principal, assigned_at_ms],
+            )
+            .map_err(from_rusqlite)?;
+        }
+        Ok(())
+    }
+
+    /// List the assignments a principal holds on non-tombstoned Ettles,
+    /// restricted to `roles`, by Ettle ID then role.
+    pub fn list_assignments_by_principal(
+        conn: &Connection,
+        principal: &str,
+        roles: &[&str],
+    ) -> Result<Vec<EttleAssignmentRecord>> {
+        let roles_json = serde_json::to_string(roles).map_err(|e| {
+            ExError::new(ExErrorKind::Serialization)
+                .with_op("list_assignments_by_principal")
+                .with_message(e.to_string())
+        })?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT a.ettle_id, a.role, a.principal, a.assigned_at_ms \
+                 FROM ettle_assignments a JOIN ettles e ON e.id = a.ettle_id \
+                 WHERE a.principal = ?1 AND e.tombstoned_at IS NULL \
+                   AND a.role IN (SELECT value FROM json_each(?2)) \
+                 ORDER BY a.ettle_id, a.role",
+            )
+            .map_err(from_rusqlite)?;
+        let rows = stmt
+            .query_map(
+                rusqlite::params![principal, roles_json],
+                Self::map_ettle_assignment_row,
+            )
+            .map_err(from_rusqlite)?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(from_rusqlite)?;
+        Ok(rows)
+    }
+
+    fn map_ettle_assignment_row(
+        row: &rusqlite::Row<'_>,
+    ) -> rusqlite::Result<EttleAssignmentRecord> {
+        Ok(EttleAssignmentRecord {
+            ettle_id: row.get(0)?,
+            role: row.get(1)?,
+            principal: row.get(2)?,
+            assigned_at_ms: row.get(3)?,
+        })
+    }
+
+    // =========================================================================
+    // Import sessions
+    // =========================================================================
+
+    /// Insert a newly opened import session.
+    pub fn insert_import_session(conn: &Connection, record: &ImportSessionRecord) -> Result<()> {
+        conn.execute(
+            "INSERT INTO import_sessions \
+             (session_id, kind, source_path, source_digest, options_json, status, \
+             started_at_ms, finished_at_ms) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            rusqlite::params![
+                record.session_id,
+                record.kind,
+                record.source_path,
+                record.source_digest,
+                record.options_json,
+                record.status,
+                record.started_at_ms,
+                record.finished_at_ms
+            ],
+        )
+        .map_err(from_rusqlite)?;
+        Ok(())
+    }
+
+    /// Get an import session by ID.
+    pub fn get_import_session(
+        conn: &Connection,
+        session_id: &str,
+    ) -> Result<Option<ImportSessionRecord>> {
+        conn.query_row(
+            "SELECT session_id, kind, source_path, source_digest, options_json, status, \
+             started_at_ms, finished_at_ms \
+             FROM import_sessions WHERE session_id = ?1",
+            [session_id],
+            Self::map_import_session_row,
+        )
+        .optional()
+        .map_err(from_rusqlite)
+    }
+
+    /// List import sessions in start order, optionally only those with
+    /// `status`.
+    pub fn list_import_sessions(
+        conn: &Connection,
+        status: Option<&str>,
+    ) -> Result<Vec<ImportSessionRecord>> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT session_id, kind, source_path, source_digest, options_json, status, \
+                 started_at_ms, finished_at_ms \
+                 FROM import_sessions WHERE ?1 IS NULL OR status = ?1 \
+                 ORDER BY started_at_ms, session_id",
+            )
+            .map_err(from_rusqlite)?;
+        let rows = stmt
+            .query_map([status], Self::map_import_session_row)
+            .map_err(from_rusqlite)?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(from_rusqlite)?;
+        Ok(rows)
+    }
+
+    /// Move a running import session to `status`. Returns `false` if the
+    /// session does not exist or is no longer running.
+    pub fn finish_import_session(
+        conn: &Connection,
+        session_id: &str,
+        status: &str,
+        finished_at_ms: i64,
+    ) -> Result<bool> {
+        let changed = conn
+            .execute(
+                "UPDATE import_sessions SET status = ?1, finished_at_ms = ?2 \
+                 WHERE session_id = ?3 AND status = ?4",
+                rusqlite::params![status, finished_at_ms, session_id, IMPORT_SESSION_RUNNING],
+            )
+            .map_err(from_rusqlite)?;
+        Ok(changed > 0)
+    }
+
+    /// Record an entity created by an import session.
+    pub fn insert_import_session_item(
+        conn: &Connection,
+        session_id: &str,
+        item: &ImportSessionItem,
+    ) -> Result<()> {
+        conn.execute(
+            "INSERT INTO import_session_items (session_id, item_kind, source_line, entity_id) \
+             VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![
+                session_id,
+                item.item_kind,
+                item.source_line as i64,
+                item.entity_id
+            ],
+        )
+        .map_err(from_rusqlite)?;
+        Ok(())
+    }
+
+    /// List the entities created by an import session, by kind then line.
+    pub fn list_import_session_items(
+        conn: &Connection,
+        session_id: &str,
+    ) -> Result<Vec<ImportSessionItem>> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT item_kind, source_line, entity_id FROM import_session_items \
+                 WHERE session_id = ?1 ORDER BY item_kind, source_line",
+            )
+            .map_err(from_rusqlite)?;
+        let rows = stmt
+            .query_map([session_id], |row| {
+                Ok(ImportSessionItem {
+                    item_kind: row.get(0)?,
+                    source_line: row.get::<_, i64>(1)? as u64,
+                    entity_id: row.get(2)?,
+                })
+            })
+            .map_err(from_rusqlite)?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(from_rusqlite)?;
+        Ok(rows)
+    }
+
+    /// IDs of rows outside an import session that reference an Ettle or
+    /// relation it created: relations, group memberships, reasoning links,
+    /// registered roots, comments, decision links, assignments and undo
+    /// journal entries (as `journal:<seq>`) other than the session's own
+    /// creates. Sorted.
+    pub fn import_session_external_refs(
+        conn: &Connection,
+        session_id: &str,
+    ) -> Result<Vec<String>> {
+        let mut stmt = conn
+            .prepare(
+                "WITH ettle_ids AS (SELECT entity_id FROM import_session_items \
+                                    WHERE session_id = ?1 AND item_kind = 'ettle'), \
+                      relation_ids AS (SELECT entity_id FROM import_session_items \
+                                       WHERE session_id = ?1 AND item_kind = 'relation') \
+                 SELECT id FROM relations \
+                 WHERE (source_ettle_id IN ettle_ids OR target_ettle_id IN ettle_ids) \
+                   AND id NOT IN relation_ids \
+                 UNION SELECT id FROM group_members WHERE ettle_id IN ettle_ids \
+                 UNION SELECT id FROM ettles \
+                 WHERE reasoning_link_id IN ettle_ids AND id NOT IN ettle_ids \
+                 UNION SELECT ettle_id FROM roots WHERE ettle_id IN ettle_ids \
+                 UNION SELECT id FROM comments \
+                 WHERE target_kind = 'ettle' AND target_id IN ettle_ids \
+                 UNION SELECT decision_id FROM decision_links \
+                 WHERE target_kind = 'ettle' AND target_id IN ettle_ids \
+                 UNION SELECT ettle_id FROM ettle_assignments WHERE ettle_id IN ettle_ids \
+                 UNION SELECT 'journal:' || seq FROM command_journal \
+                 WHERE (entity_id IN ettle_ids OR entity_id IN relation_ids) \
+                   AND op NOT IN ('ettle_create', 'relation_create') \
+                 ORDER BY 1",
+            )
+            .map_err(from_rusqlite)?;
+        let rows = stmt
+            .query_map([session_id], |row| row.get(0))
+            .map_err(from_rusqlite)?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(from_rusqlite)?;
+        Ok(rows)
+    }
+
+    /// Hard-delete the relations and Ettles an import session created, with
+    /// their creating undo journal entries. Returns `(relations, ettles)`
+    /// deleted. Callers check [`Self::import_session_external_refs`] first.
+    pub fn delete_import_session_entities(
+        conn: &Connection,
+        session_id: &str,
+    ) -> Result<(usize, usize)> {
+        conn.execute(
+            "DELETE FROM command_journal \
+             WHERE op IN ('ettle_create', 'relation_create') AND entity_id IN \
+             (SELECT entity_id FROM import_session_items WHERE session_id = ?1)",
+            [session_id],
+        )
+        .map_err(from_rusqlite)?;
+        let relations = conn
+            .execute(
+                "DELETE FROM relations WHERE id IN \
+                 (SELECT entity_id FROM import_session_items \
+                  WHERE session_id = ?1 AND item_kind = 'relation')",
+                [session_id],
+            )
+            .map_err(from_rusqlite)?;
+        let ettles = conn
+            .execute(
+                "DELETE FROM ettles WHERE id IN \
+                 (SELECT entity_id FROM import_session_items \
+                  WHERE session_id = ?1 AND item_kind = 'ettle')",
+                [session_id],
+            )
+            .map_err(from_rusqlite)?;
+        Ok((relations, ettles))
+    }
+
+    fn map_import_session_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<ImportSessionRecord> {
+        Ok(ImportSessionRecord {
+            session_id: row.get(0)?,
+            kind: row.get(1)?,
+            source_path: row.get(2)?,
+            source_digest: row.get(3)?,
+            options_json: row.get(4)?,
+            status: row.get(5)?,
+            started_at_ms: row.get(6)?,
+            finished_at_ms: row.get(7)?,
+        })
+    }
+
+    // =========================================================================
+    // Snapshot commit queue
+    // =========================================================================
+
+    /// Append a commit request to the queue. `record.seq` is ignored; the
+    /// assigned queue position is returned.
+    pub fn insert_commit_ticket(conn: &Connection, record: &CommitTicketRecord) -> Result<i64> {
+        conn.execute(
+            "INSERT INTO snapshot_commit_queue \
+             (ticket, leaf_ettle_id, request_json, status, enqueued_at_ms, applied_at_ms, outcome_json) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            rusqlite::params![
+                record.ticket,
+                record.leaf_ettle_id,
+                record.request_json,
+                record.status,
+                record.enqueued_at_ms,
+                record.applied_at_ms,
+                record.outcome_json
+            ],
+        )
+        .map_err(from_rusqlite)?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Get a queued commit by ticket.
+    pub fn get_commit_ticket(
+        conn: &Connection,
+        ticket: &str,
+    ) -> Result<Option<CommitTicketRecord>> {
+        conn.query_row(
+            "SELECT seq, ticket, leaf_ettle_id, request_json, status, enqueued_at_ms, \
+             applied_at_ms, outcome_json \
+             FROM snapshot_commit_queue WHERE ticket = ?1",
+            [ticket],
+            Self::map_commit_ticket_row,
+        )
+        .optional()
+        .map_err(from_rusqlite)
+    }
+
+    /// The oldest ticket still waiting for the worker, if any.
+    pub fn next_queued_commit_ticket(conn: &Connection) -> Result<Option<CommitTicketRecord>> {
+        conn.query_row(
+            "SELECT seq, ticket, leaf_ettle_id, request_json, status, enqueued_at_ms, \
+             applied_at_ms, outcome_json \
+             FROM snapshot_commit_queue WHERE status = ?1 ORDER BY seq LIMIT 1",
+            [COMMIT_TICKET_QUEUED],
+            Self::map_commit_ticket_row,
+        )
+        .optional()
+        .map_err(from_rusqlite)
+    }
+
+    /// Number of queued tickets ahead of queue position `seq`.
+    pub fn count_queued_commit_tickets_before(conn: &Connection, seq: i64) -> Result<u64> {
+        conn.query_row(
+            "SELECT COUNT(*) FROM snapshot_commit_queue WHERE status = ?1 AND seq < ?2",
+            rusqlite::params![COMMIT_TICKET_QUEUED, seq],
+            |row| row.get::<_, i64>(0),
+        )
+        .map(|n| n as u64)
+        .map_err(from_rusqlite)
+    }
+
+    /// Move a queued ticket to `applying`. Returns `false` if another worker
+    /// claimed it first.
+    pub fn claim_commit_ticket(conn: &Connection, ticket: &str) -> Result<bool> {
+        let changed = conn
+            .execute(
+                "UPDATE snapshot_commit_queue SET status = ?1 WHERE ticket = ?2 AND status = ?3",
+                rusqlite::params![COMMIT_TICKET_APPLYING, ticket, COMMIT_TICKET_QUEUED],
+            )
+            .map_err(from_rusqlite)?;
+        Ok(changed > 0)
+    }
+
+    /// Record the outcome of a claimed ticket. Returns `false` if the ticket
+    /// does not exist or is not `applying`.
+    pub fn finish_commit_ticket(
+        conn: &Connection,
+        ticket: &str,
+        status: &str,
+        outcome_json: &str,
+        applied_at_ms: i64,
+    ) -> Result<bool> {
+        let changed = conn
+            .execute(
+                "UPDATE snapshot_commit_queue \
+                 SET status = ?1, outcome_json = ?2, applied_at_ms = ?3 \
+                 WHERE ticket = ?4 AND status = ?5",
+                rusqlite::params![
+                    status,
+                    outcome_json,
+                    applied_at_ms,
+                    ticket,
+                    COMMIT_TICKET_APPLYING
+                ],
+            )
+            .map_err(from_rusqlite)?;
+        Ok(changed > 0)
+    }
+
+    fn map_commit_ticket_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<CommitTicketRecord> {
+        Ok(CommitTicketRecord {
+            seq: row.get(0)?,
+            ticket: row.get(1)?,
+            leaf_ettle_id: row.get(2)?,
+            request_json: row.get(3)?,
+            status: row.get(4)?,
+            enqueued_at_ms: row.get(5)?,
+            applied_at_ms: row.get(6)?,
+            outcome_json: row.get(7)?,
+        })
+    }
+
+    // =========================================================================
+    // Command log
+    // =========================================================================
+
+    /// Append a `command_log` row and return its `id`.
+    pub fn insert_command_log_entry(
+        conn: &Connection,
+        applied_at: &str,
+        op: &str,
+        entity_id: Option<&str>,
+    ) -> Result<i64> {
+        conn.execute(
+            "INSERT INTO command_log (applied_at, op, entity_id) VALUES (?1, ?2, ?3)",
+            rusqlite::params![applied_at, op, entity_id],
+        )
+        .map_err(from_rusqlite)?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Up to `limit` log rows with `id > after_id`, oldest first.
+    pub fn list_command_log_after(
+        conn: &Connection,
+        after_id: i64,
+        limit: usize,
+    ) -> Result<Vec<CommandLogRecord>> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, applied_at, op, entity_id FROM command_log \
+                 WHERE id > ?1 ORDER BY id LIMIT ?2",
+            )
+            .map_err(from_rusqlite)?;
+        let rows = stmt
+            .query_map(rusqlite::params![after_id, limit as i64], |row| {
+                Ok(CommandLogRecord {
+                    id: row.get(0)?,
+                    applied_at: row.get(1)?,
+                    op: row.get(2)?,
+                    entity_id: row.get(3)?,
+                })
+            })
+            .map_err(from_rusqlite)?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(from_rusqlite)?;
+        Ok(rows)
+    }
+
+    // =========================================================================
+    // Command journal
+    // =========================================================================
+
+    /// Append a journal entry and return its `seq`.
+    pub fn insert_journal_entry(conn: &Connection, record: &JournalRecord) -> Result<i64> {
+        conn.execute(
+            "INSERT INTO command_journal \
+             (op, entity_id, undo_json, redo_json, status, recorded_at_ms, undone_at_ms) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            rusqlite::params![
+                record.op,
+                record.entity_id,
+                record.undo_json,
+                record.redo_json,
+                record.status,
+                record.recorded_at_ms,
+                record.undone_at_ms
+            ],
+        )
+        .map_err(from_rusqlite)?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Delete every undone entry, ending the redo history. Returns the number
+    /// of entries removed.
+    pub fn discard_undone_journal_entries(conn: &Connection) -> Result<usize> {
+        conn.execute(
+            "DELETE FROM command_journal WHERE status = ?1",
+            [JOURNAL_UNDONE],
+        )
+        .map_err(from_rusqlite)
+    }
+
+    /// The entry the next undo reverts: the newest applied one.
+    pub fn next_undo_journal_entry(conn: &Connection) -> Result<Option<JournalRecord>> {
+        conn.query_row(
+            "SELECT seq, op, entity_id, undo_json, redo_json, status, recorded_at_ms, undone_at_ms \
+             FROM command_journal WHERE status = ?1 ORDER BY seq DESC LIMIT 1",
+            [JOURNAL_APPLIED],
+            Self::map_journal_row,
+        )
+        .optional()
+        .map_err(from_rusqlite)
+    }
+
+    /// The entry the next redo reapplies: the oldest undone one.
+    pub fn next_redo_journal_entry(conn: &Connection) -> Result<Option<JournalRecord>> {
+        conn.query_row(
+            "SELECT seq, op, entity_id, undo_json, redo_json, status, recorded_at_ms, undone_at_ms \
+             FROM command_journal WHERE status = ?1 ORDER BY seq LIMIT 1",
+            [JOURNAL_UNDONE],
+            Self::map_journal_row,
+        )
+        .optional()
+        .map_err(from_rusqlite)
+    }
+
+    /// Set an entry's status; `undone_at_ms` is cleared when it is reapplied.
+    pub fn set_journal_entry_status(
+        conn: &Connection,
+        seq: i64,
+        status: &str,
+        undone_at_ms: Option<i64>,
+    ) -> Result<()> {
+        conn.execute(
+            "UPDATE command_journal SET status = ?1, undone_at_ms = ?2 WHERE seq = ?3",
+            rusqlite::params![status, undone_at_ms, seq],
+        )
+        .map_err(from_rusqlite)?;
+        Ok(())
+    }
+
+    fn map_journal_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<JournalRecord> {
+        Ok(JournalRecord {
+            seq: row.get(0)?,
+            op: row.get(1)?,
+            entity_id: row.get(2)?,
+            undo_json: row.get(3)?,
+            redo_json: row.get(4)?,
+            status: row.get(5)?,
+            recorded_at_ms: row.get(6)?,
+            undone_at_ms: row.get(7)?,
+        })
+    }
+
+    // =========================================================================
+    // Snapshot and approval summaries
+    // =========================================================================
+
+    /// The `limit` most recently committed snapshots, newest first (ledger order).
+    pub fn list_recent_snapshots(conn: &Connection, limit: u32) -> Result<Vec<SnapshotRow>> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT snapshot_id, root_ettle_id, manifest_digest, semantic_manifest_digest,
+                        created_at, parent_snapshot_id, policy_ref, profile_ref, status,
+                        message, annotations_json
+                 FROM snapshots ORDER BY id DESC LIMIT ?1",
+            )
+            .map_err(from_rusqlite)?;
+        let rows = stmt
+            .query_map([limit], row_to_snapshot_row)
+            .map_err(from_rusqlite)?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(from_rusqlite)?;
+        Ok(rows)
+    }
+
+    /// Pending approval requests, ordered by (created_at, approval_token).
+    pub fn list_pending_approvals(conn: &Connection) -> Result<Vec<ApprovalRow>> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT approval_token, reason_code, candidate_set_json,
+                        semantic_request_digest, status, created_at, request_digest
+                 FROM approval_requests
+                 WHERE status = ?1 ORDER BY created_at, approval_token",
+            )
+            .map_err(from_rusqlite)?;
+        let rows = stmt
+            .query_map([APPROVAL_STATUS_PENDING], approval_row_with_digest)
+            .map_err(from_rusqlite)?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(from_rusqlite)?;
+        Ok(rows)
+    }
+
+    // =========================================================================
+    // Comments
+    // =========================================================================
+
+    /// Insert a new comment row.
+    pub fn insert_comment(conn: &Connection, record: &CommentRecord) -> Result<()> {
+        conn.execute(
+            "INSERT INTO comments \
+             (id, target_kind, target_id, parent_comment_id, author, body, created_at, \
+             resolved_at_ms, created_at_ms) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            rusqlite::params![
+                record.id,
+                record.target_kind,
+                record.target_id,
+                record.parent_comment_id,
+                record.author,
+                record.body,
+                record.created_at,
+                record.resolved_at_ms,
+                timestamp_ms(&record.created_at)
+            ],
+        )
+        .map_err(from_rusqlite)?;
+        Ok(())
+    }
+
+    /// Get a comment by ID.
+    pub fn get_comment(conn: &Connection, id: &str) -> Result<Option<CommentRecord>> {
+        conn.query_row(
+            "SELECT id, target_kind, target_id, parent_comment_id, author, body, \
+             created_at, resolved_at_ms FROM comments WHERE id = ?1",
+            [id],
+            Self::map_comment_row,
+        )
+        .optional()
+        .map_err(from_rusqlite)
+    }
+
+    /// Set or clear `resolved_at_ms` on a comment.
+    pub fn set_comment_resolved(
+        conn: &Connection,
+        id: &str,
+        resolved_at_ms: Option<i64>,
+    ) -> Result<()> {
+        conn.execute(
+            "UPDATE comments SET resolved_at_ms = ?1 WHERE id = ?2",
+            rusqlite::params![resolved_at_ms, id],
+        )
+        .map_err(from_rusqlite)?;
+        Ok(())
+    }
+
+    /// List comments on a target ordered by (created_at_ms, id), starting after
+    /// `after_key` if given.
+    ///
+    /// When `include_resolved` is false, every comment belonging to a
+    /// resolved thread (root or reply) is skipped.
+    pub fn list_comments_paginated(
+        conn: &Connection,
+        target_kind: &str,
+        target_id: &str,
+        include_resolved: bool,
+        after_key: Option<&PageKey>,
+        limit: usize,
+    ) -> Result<Vec<CommentRecord>> {
+        let sql = format!(
+            "SELECT c.id, c.target_kind, c.target_id, c.parent_comment_id, c.author, c.body,
+                    c.created_at, c.resolved_at_ms
+             FROM comments c
+             LEFT JOIN comments root ON root.id = c.parent_comment_id
+             WHERE c.target_kind = ?1 AND c.target_id = ?2
+               AND (?3 OR COALESCE(root.resolved_at_ms, c.resolved_at_ms) IS NULL)
+               AND (?4 IS NULL OR c.created_at_ms > ?4 OR (c.created_at_ms = ?4 AND c.id > ?5))
+             ORDER BY c.created_at_ms, c.id
+             LIMIT {}",
+            limit
+        );
+        let (after_ts, after_id) = match after_key {
+            Some(k) => (Some(k.created_at_ms), Some(k.id.as_str())),
+            None => (None, None),
+        };
+        let mut stmt = conn.prepare(&sql).map_err(from_rusqlite)?;
+        let rows = stmt
+            .query_map(
+                rusqlite::params![target_kind, target_id, include_resolved, after_ts, after_id],
+                Self::map_comment_row,
+            )
+            .map_err(from_rusqlite)?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(from_rusqlite)?;
+        Ok(rows)
+    }
+
+    /// Whether a comment target of the given kind exists.
+    ///
+    /// Returns `InvalidTargetKind` for an unsupported `target_kind`.
+    pub fn comment_target_exists(
+        conn: &Connection,
+        target_kind: &str,
+        target_id: &str,
+    ) -> Result<bool> {
+        let sql = match target_kind {
+            "ettle" => "SELECT COUNT(*) FROM ettles WHERE id = ?1",
+            "decision" => "SELECT COUNT(*) FROM decisions WHERE decision_id = ?1",
+            "snapshot" => "SELECT COUNT(*) FROM snapshots WHERE snapshot_id = ?1",
+            other => {
+                return Err(ExError::new(ExErrorKind::InvalidTargetKind)
+                    .with_op("comment_target_exists")
+                    .with_message(format!("unsupported comment target_kind: {}", other))
+                    .with_candidates(vec![
+                        "ettle".to_string(),
+                        "decision".to_string(),
+                        "snapshot".to_string(),
+                    ]))
+            }
+        };
+        let count: i64 = conn
+            .query_row(sql, [target_id], |row| row.get(0))
+            .map_err(from_rusqlite)?;
+        Ok(count > 0)
+    }
+
+    fn map_comment_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<CommentRecord> {
+        Ok(CommentRecord {
+            id: row.get(0)?,
+            target_kind: row.get(1)?,
+            target_id: row.get(2)?,
+            parent_comment_id: row.get(3)?,
+            author: row.get(4)?,
+            body: row.get(5)?,
+            created_at: row.get(6)?,
+            resolved_at_ms: row.get(7)?,
+        })
+    }
+}
+
+/// Map an `(id, title, created_at, updated_at)` row to a summary `Ettle`.
+fn ettle_summary_from_row(row: &rusqlite::Row) -> rusqlite::Result<Ettle> {
+    let id: String = row.get(0)?;
+    let title: String = row.get(1)?;
+    let created_at_str: String = row.get(2)?;
+    let updated_at_str: String = row.get(3)?;
+
+    let mut ettle = Ettle::new(id, title);
+    if let Ok(ts) = chrono::DateTime::parse_from_rfc3339(&created_at_str) {
+        ettle.created_at = ts.with_timezone(&chrono::Utc);
+    }
+    if let Ok(ts) = chrono::DateTime::parse_from_rfc3339(&updated_at_str) {
+        ettle.updated_at = ts.with_timezone(&chrono::Utc);
+    }
+
+    Ok(ettle)
 }
 
 #[cfg(test)]
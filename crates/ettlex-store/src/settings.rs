@@ -0,0 +1,112 @@
+//! Repository settings stored in the `settings` table.
+//!
+//! A plain JSON key-value store. Keys and value types are owned by the
+//! callers (e.g. the engine's feature flag registry); this module only
+//! persists them.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::errors::{ExError, ExErrorKind};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::Value;
+
+use crate::errors::{from_rusqlite, Result};
+
+/// One stored setting.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SettingRow {
+    pub key: String,
+    pub value: Value,
+    /// Last write, milliseconds since epoch
+    pub updated_at_ms: i64,
+}
+
+/// Read a setting; `None` when it has never been set (or was cleared).
+///
+/// # Errors
+/// * `Persistence` - the query fails
+/// * `Serialization` - the stored value is not valid JSON
+pub fn get_setting(conn: &Connection, key: &str) -> Result<Option<Value>> {
+    let raw: Option<String> = conn
+        .query_row(
+            "SELECT value_json FROM settings WHERE key = ?1",
+            [key],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(from_rusqlite)?;
+    raw.map(|json| parse_value(key, &json)).transpose()
+}
+
+/// Store `value` under `key`, replacing any previous value.
+///
+/// # Errors
+/// * `Persistence` - the write fails
+pub fn set_setting(conn: &Connection, key: &str, value: &Value) -> Result<()> {
+    conn.execute(
+        "INSERT INTO settings (key, value_json, updated_at_ms) VALUES (?1, ?2, ?3)
+         ON CONFLICT(key) DO UPDATE SET value_json = excluded.value_json,
+                                        updated_at_ms = excluded.updated_at_ms",
+        params![
+            key,
+            value.to_string(),
+            chrono::Utc::now().timestamp_millis()
+        ],
+    )
+    .map_err(from_rusqlite)?;
+    Ok(())
+}
+
+/// Remove `key`, reverting it to its default. Returns whether it was set.
+///
+/// # Errors
+/// * `Persistence` - the delete fails
+pub fn clear_setting(conn: &Connection, key: &str) -> Result<bool> {
+    let removed = conn
+        .execute("DELETE FROM settings WHERE key = ?1", [key])
+        .map_err(from_rusqlite)?;
+    Ok(removed > 0)
+}
+
+/// Every setting whose key starts with `prefix`, ordered by key.
+///
+/// # Errors
+/// * `Persistence` - the query fails
+/// * `Serialization` - a stored value is not valid JSON
+pub fn list_settings(conn: &Connection, prefix: &str) -> Result<Vec<SettingRow>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT key, value_json, updated_at_ms FROM settings
+             WHERE substr(key, 1, length(?1)) = ?1 ORDER BY key",
+        )
+        .map_err(from_rusqlite)?;
+    let rows = stmt
+        .query_map([prefix], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        })
+        .map_err(from_rusqlite)?
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(from_rusqlite)?;
+    rows.into_iter()
+        .map(|(key, json, updated_at_ms)| {
+            Ok(SettingRow {
+                value: parse_value(&key, &json)?,
+                key,
+                updated_at_ms,
+            })
+        })
+        .collect()
+}
+
+fn parse_value(key: &str, json: &str) -> Result<Value> {
+    serde_json::from_str(json).map_err(|e| {
+        ExError::new(ExErrorKind::Serialization)
+            .with_op("settings_get")
+            .with_entity_id(key)
+            .with_message(format!("setting '{}' holds invalid JSON: {}", key, e))
+    })
+}
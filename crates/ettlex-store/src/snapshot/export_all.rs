@@ -0,0 +1,201 @@
+//! Bulk export of every committed manifest, resumable after interruption.
+//!
+//! Walks the ledger in commit order and writes one directory per snapshot:
+//!
+//! ```text
+//! <out>/
+//!   export-state.json          progress: snapshot IDs already exported
+//!   snapshots/<snapshot_id>/   manifest.json (+ blobs/ and index.json with blobs)
+//! ```
+//!
+//! Each snapshot is written to `<snapshot_id>.partial` and renamed into place
+//! once complete; `export-state.json` is rewritten (via a temporary file)
+//! after every snapshot. An interrupted export therefore leaves at most one
+//! partial directory, which a resumed run discards and redoes. Resuming also
+//! picks up snapshots committed since the previous run, so the same output
+//! directory can be refreshed periodically.
+
+#![allow(clippy::result_large_err)]
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use ettlex_core::errors::{ExError, ExErrorKind};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::cas::FsStore;
+use crate::errors::{io_error, Result};
+use crate::snapshot::materialize::materialize_snapshot;
+use crate::snapshot::query::{fetch_manifest_bytes_by_digest, list_snapshot_rows};
+
+/// Progress file written at the top of the output directory.
+pub const EXPORT_STATE_FILE: &str = "export-state.json";
+
+const EXPORT_STATE_VERSION: u32 = 1;
+
+/// How to run [`export_all_snapshots`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExportAllOptions {
+    /// Also copy every CAS blob each manifest references.
+    pub include_blobs: bool,
+    /// Continue from `export-state.json` instead of requiring an empty directory.
+    pub resume: bool,
+}
+
+/// Contents of `export-state.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportState {
+    pub format_version: u32,
+    pub include_blobs: bool,
+    /// Snapshot IDs fully written, in the order they were exported.
+    pub exported: Vec<String>,
+}
+
+/// Outcome of one export run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportAllReport {
+    /// Snapshots written by this run, in ledger order.
+    pub exported: Vec<String>,
+    /// Snapshots skipped because an earlier run already exported them.
+    pub skipped: usize,
+    /// Snapshots in the ledger.
+    pub total: usize,
+}
+
+/// Export every snapshot in the ledger to `out_dir`.
+///
+/// # Errors
+/// * `InvalidInput` - `out_dir` is not empty and `resume` is off, holds no
+///   state file to resume from, or was started with a different `include_blobs`.
+/// * `MissingBlob` - a manifest is missing from CAS.
+/// * `DeterminismViolation` - a blob's bytes do not hash to its digest.
+/// * `Serialization` - the state file cannot be read or written.
+/// * `Io` - writing the output failed.
+pub fn export_all_snapshots(
+    conn: &Connection,
+    cas: &FsStore,
+    out_dir: &Path,
+    options: ExportAllOptions,
+) -> Result<ExportAllReport> {
+    let mut state = open_export_dir(out_dir, options)?;
+    let done: BTreeSet<String> = state.exported.iter().cloned().collect();
+
+    let snapshots_dir = out_dir.join("snapshots");
+    std::fs::create_dir_all(&snapshots_dir).map_err(|e| io_error("export_all_snapshots", e))?;
+
+    let rows = list_snapshot_rows(conn, None)?;
+    let total = rows.len();
+    let mut exported = Vec::new();
+    for row in rows {
+        if done.contains(&row.snapshot_id) {
+            continue;
+        }
+        let final_dir = snapshots_dir.join(&row.snapshot_id);
+        let partial_dir = snapshots_dir.join(format!("{}.partial", row.snapshot_id));
+        // Leftovers from an interrupted run: not recorded, so not trusted.
+        remove_dir_if_exists(&partial_dir)?;
+        remove_dir_if_exists(&final_dir)?;
+
+        if options.include_blobs {
+            materialize_snapshot(conn, cas, &row.snapshot_id, &partial_dir)?;
+        } else {
+            let bytes = fetch_manifest_bytes_by_digest(cas, &row.manifest_digest)?;
+            std::fs::create_dir_all(&partial_dir)
+                .map_err(|e| io_error("export_all_snapshots", e))?;
+            std::fs::write(partial_dir.join("manifest.json"), bytes)
+                .map_err(|e| io_error("export_all_snapshots", e))?;
+        }
+        std::fs::rename(&partial_dir, &final_dir)
+            .map_err(|e| io_error("export_all_snapshots", e))?;
+
+        state.exported.push(row.snapshot_id.clone());
+        write_state(out_dir, &state)?;
+        exported.push(row.snapshot_id);
+    }
+
+    Ok(ExportAllReport {
+        skipped: total - exported.len(),
+        exported,
+        total,
+    })
+}
+
+/// Validate `out_dir` for a fresh or resumed export and return the starting state.
+fn open_export_dir(out_dir: &Path, options: ExportAllOptions) -> Result<ExportState> {
+    let fresh = ExportState {
+        format_version: EXPORT_STATE_VERSION,
+        include_blobs: options.include_blobs,
+        exported: Vec::new(),
+    };
+    if !out_dir.exists() {
+        std::fs::create_dir_all(out_dir).map_err(|e| io_error("export_all_snapshots", e))?;
+        write_state(out_dir, &fresh)?;
+        return Ok(fresh);
+    }
+
+    let state_path = out_dir.join(EXPORT_STATE_FILE);
+    if options.resume && state_path.exists() {
+        let bytes = std::fs::read(&state_path).map_err(|e| io_error("export_all_snapshots", e))?;
+        let state: ExportState = serde_json::from_slice(&bytes).map_err(|e| {
+            ExError::new(ExErrorKind::Serialization)
+                .with_op("export_all_snapshots")
+                .with_message(format!("unreadable {}: {}", EXPORT_STATE_FILE, e))
+        })?;
+        if state.format_version != EXPORT_STATE_VERSION {
+            return Err(invalid(format!(
+                "unsupported {} format_version {}",
+                EXPORT_STATE_FILE, state.format_version
+            )));
+        }
+        if state.include_blobs != options.include_blobs {
+            return Err(invalid(format!(
+                "export was started with include_blobs = {}; resume with the same setting",
+                state.include_blobs
+            )));
+        }
+        return Ok(state);
+    }
+
+    let mut entries =
+        std::fs::read_dir(out_dir).map_err(|e| io_error("export_all_snapshots", e))?;
+    if entries.next().is_some() {
+        return Err(invalid(if options.resume {
+            format!(
+                "no {} to resume from in non-empty directory: {}",
+                EXPORT_STATE_FILE,
+                out_dir.display()
+            )
+        } else {
+            format!("output directory is not empty: {}", out_dir.display())
+        }));
+    }
+    write_state(out_dir, &fresh)?;
+    Ok(fresh)
+}
+
+/// Replace `export-state.json` atomically.
+fn write_state(out_dir: &Path, state: &ExportState) -> Result<()> {
+    let bytes = serde_json::to_vec_pretty(state).map_err(|e| {
+        ExError::new(ExErrorKind::Serialization)
+            .with_op("export_all_snapshots")
+            .with_message(format!("failed to serialize export state: {}", e))
+    })?;
+    let tmp = out_dir.join(format!("{}.tmp", EXPORT_STATE_FILE));
+    std::fs::write(&tmp, bytes).map_err(|e| io_error("export_all_snapshots", e))?;
+    std::fs::rename(&tmp, out_dir.join(EXPORT_STATE_FILE))
+        .map_err(|e| io_error("export_all_snapshots", e))
+}
+
+fn remove_dir_if_exists(dir: &Path) -> Result<()> {
+    if dir.exists() {
+        std::fs::remove_dir_all(dir).map_err(|e| io_error("export_all_snapshots", e))?;
+    }
+    Ok(())
+}
+
+fn invalid(message: String) -> ExError {
+    ExError::new(ExErrorKind::InvalidInput)
+        .with_op("export_all_snapshots")
+        .with_message(message)
+}
@@ -0,0 +1,201 @@
+//! Snapshot ID schemes.
+//!
+//! Snapshot IDs default to UUIDv7. Two human-oriented alternatives are
+//! available per commit:
+//!
+//! - `root-counter`: `snap-<root-slug>-<n>`, where `n` counts the root's
+//!   snapshots from 1.
+//! - `digest-prefix`: `snap-<first 12 hex chars of the manifest digest>`.
+//!
+//! Whatever the scheme, the generated ID is stored verbatim in
+//! `snapshots.snapshot_id` and is unique across the ledger: on collision a
+//! `-<n>` suffix is appended. [`resolve_snapshot_id`] accepts either a full
+//! ID or an unambiguous prefix, so short forms work for every scheme.
+
+#![allow(clippy::result_large_err)]
+
+use std::fmt;
+use std::str::FromStr;
+
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::model::slug::slugify;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{from_rusqlite, Result};
+
+/// Number of manifest digest hex characters used by `DigestPrefix`.
+const DIGEST_PREFIX_LEN: usize = 12;
+
+/// How new snapshot IDs are generated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SnapshotIdScheme {
+    /// UUIDv7 (temporally ordered). The default.
+    #[default]
+    Uuid,
+    /// `snap-<root-slug>-<counter>`.
+    RootCounter,
+    /// `snap-<manifest digest prefix>`.
+    DigestPrefix,
+}
+
+impl SnapshotIdScheme {
+    /// Canonical string form (`uuid`, `root-counter`, `digest-prefix`).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SnapshotIdScheme::Uuid => "uuid",
+            SnapshotIdScheme::RootCounter => "root-counter",
+            SnapshotIdScheme::DigestPrefix => "digest-prefix",
+        }
+    }
+}
+
+impl fmt::Display for SnapshotIdScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SnapshotIdScheme {
+    type Err = ExError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "uuid" => Ok(SnapshotIdScheme::Uuid),
+            "root-counter" => Ok(SnapshotIdScheme::RootCounter),
+            "digest-prefix" => Ok(SnapshotIdScheme::DigestPrefix),
+            other => Err(ExError::new(ExErrorKind::InvalidInput)
+                .with_op("parse_snapshot_id_scheme")
+                .with_message(format!(
+                    "unknown snapshot id scheme '{}' (expected uuid, root-counter or digest-prefix)",
+                    other
+                ))),
+        }
+    }
+}
+
+/// Generate a new, ledger-unique snapshot ID under `scheme`.
+pub fn generate_snapshot_id(
+    conn: &Connection,
+    scheme: SnapshotIdScheme,
+    root_ettle_id: &str,
+    manifest_digest: &str,
+) -> Result<String> {
+    let base = match scheme {
+        SnapshotIdScheme::Uuid => return Ok(uuid::Uuid::now_v7().to_string()),
+        SnapshotIdScheme::RootCounter => {
+            let count: i64 = conn
+                .query_row(
+                    "SELECT COUNT(*) FROM snapshots WHERE root_ettle_id = ?1",
+                    [root_ettle_id],
+                    |row| row.get(0),
+                )
+                .map_err(from_rusqlite)?;
+            format!("snap-{}-{}", root_slug(root_ettle_id), count + 1)
+        }
+        SnapshotIdScheme::DigestPrefix => {
+            let prefix: String = manifest_digest.chars().take(DIGEST_PREFIX_LEN).collect();
+            format!("snap-{}", prefix)
+        }
+    };
+
+    let mut candidate = base.clone();
+    let mut n = 2u64;
+    while snapshot_id_exists(conn, &candidate)? {
+        candidate = format!("{}-{}", base, n);
+        n += 1;
+    }
+    Ok(candidate)
+}
+
+/// Resolve a full snapshot ID or an unambiguous prefix of one.
+///
+/// An exact match always wins over prefix matches.
+///
+/// # Errors
+/// * `NotFound` - No snapshot ID equals or starts with `id_or_prefix`
+/// * `AmbiguousSelection` - Several IDs share the prefix (listed in candidates)
+pub fn resolve_snapshot_id(conn: &Connection, id_or_prefix: &str) -> Result<String> {
+    if snapshot_id_exists(conn, id_or_prefix)? {
+        return Ok(id_or_prefix.to_string());
+    }
+    let mut matches: Vec<String> = Vec::new();
+    if !id_or_prefix.is_empty() {
+        let mut stmt = conn
+            .prepare(
+                "SELECT snapshot_id FROM snapshots
+                 WHERE substr(snapshot_id, 1, ?2) = ?1
+                 ORDER BY snapshot_id ASC LIMIT 10",
+            )
+            .map_err(from_rusqlite)?;
+        matches = stmt
+            .query_map(
+                rusqlite::params![id_or_prefix, id_or_prefix.chars().count() as i64],
+                |row| row.get(0),
+            )
+            .map_err(from_rusqlite)?
+            .collect::<std::result::Result<Vec<String>, _>>()
+            .map_err(from_rusqlite)?;
+    }
+    match matches.len() {
+        0 => Err(ExError::new(ExErrorKind::NotFound)
+            .with_op("resolve_snapshot_id")
+            .with_entity_id(id_or_prefix)
+            .with_message("snapshot not found")),
+        1 => Ok(matches.remove(0)),
+        _ => Err(ExError::new(ExErrorKind::AmbiguousSelection)
+            .with_op("resolve_snapshot_id")
+            .with_entity_id(id_or_prefix)
+            .with_message("snapshot id prefix matches more than one snapshot")
+            .with_candidates(matches)),
+    }
+}
+
+fn snapshot_id_exists(conn: &Connection, snapshot_id: &str) -> Result<bool> {
+    Ok(conn
+        .query_row(
+            "SELECT 1 FROM snapshots WHERE snapshot_id = ?1",
+            [snapshot_id],
+            |_| Ok(()),
+        )
+        .optional()
+        .map_err(from_rusqlite)?
+        .is_some())
+}
+
+/// Slug of an ID for use in snapshot IDs, dropping any `<kind>:` prefix.
+fn root_slug(id: &str) -> String {
+    let body = id.split_once(':').map(|(_, rest)| rest).unwrap_or(id);
+    let slug = slugify(body);
+    if slug.is_empty() {
+        "root".to_string()
+    } else {
+        slug
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scheme_round_trips_through_str() {
+        for scheme in [
+            SnapshotIdScheme::Uuid,
+            SnapshotIdScheme::RootCounter,
+            SnapshotIdScheme::DigestPrefix,
+        ] {
+            assert_eq!(scheme.as_str().parse::<SnapshotIdScheme>().unwrap(), scheme);
+        }
+        let err = "sequential".parse::<SnapshotIdScheme>().unwrap_err();
+        assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_root_slug() {
+        assert_eq!(root_slug("ettle:Payments API"), "payments-api");
+        assert_eq!(root_slug("root_1"), "root-1");
+        assert_eq!(root_slug("ettle:!!"), "root");
+    }
+}
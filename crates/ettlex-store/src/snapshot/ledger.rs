@@ -0,0 +1,202 @@
+//! Snapshot ledger integrity chain.
+//!
+//! Every row in `snapshots` carries a `chain_hash` linking it to the row
+//! before it (in `id` order):
+//!
+//! ```text
+//! chain_hash[n] = sha256_hex(chain_hash[n-1] || manifest_digest[n])
+//! chain_hash[0] = sha256_hex(manifest_digest[0])
+//! ```
+//!
+//! Rewriting, inserting or deleting any historical row changes every later
+//! chain hash, so [`verify_ledger`] detects tampering without signatures.
+
+#![allow(clippy::result_large_err)]
+
+use rusqlite::{Connection, OptionalExtension};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::errors::{from_rusqlite, Result};
+
+/// Compute the chain hash for a ledger row.
+///
+/// `prev_chain_hash` is `None` for the genesis row.
+pub fn compute_chain_hash(prev_chain_hash: Option<&str>, manifest_digest: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(prev_chain_hash.unwrap_or("").as_bytes());
+    hasher.update(manifest_digest.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Chain hash of the last row in the ledger, or `None` if the ledger is empty.
+///
+/// Call [`backfill_chain`] first if the ledger may contain unchained rows.
+pub fn last_chain_hash(conn: &Connection) -> Result<Option<String>> {
+    let hash: Option<Option<String>> = conn
+        .query_row(
+            "SELECT chain_hash FROM snapshots ORDER BY id DESC LIMIT 1",
+            [],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(from_rusqlite)?;
+    Ok(hash.flatten())
+}
+
+/// Chain hash to extend when appending a new ledger row.
+///
+/// Reads only the head row. Rows written since migration 016 are chained on
+/// insert, so a chained head implies a fully chained ledger; an unchained
+/// head means pre-chain rows remain and triggers a one-off [`backfill_chain`].
+pub fn head_chain_hash(conn: &Connection) -> Result<Option<String>> {
+    let head: Option<Option<String>> = conn
+        .query_row(
+            "SELECT chain_hash FROM snapshots ORDER BY id DESC LIMIT 1",
+            [],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(from_rusqlite)?;
+    match head {
+        None => Ok(None),
+        Some(Some(hash)) => Ok(Some(hash)),
+        Some(None) => {
+            backfill_chain(conn)?;
+            last_chain_hash(conn)
+        }
+    }
+}
+
+/// Fill `chain_hash` for rows committed before the chain existed.
+///
+/// Rows are processed in `id` order, extending the chain from the last
+/// already-chained predecessor. Returns the number of rows updated.
+/// Already-chained rows are never rewritten.
+pub fn backfill_chain(conn: &Connection) -> Result<u64> {
+    let rows = load_chain_rows(conn)?;
+    let mut prev: Option<String> = None;
+    let mut updated = 0u64;
+    for row in rows {
+        let hash = match row.chain_hash {
+            Some(h) => h,
+            None => {
+                let h = compute_chain_hash(prev.as_deref(), &row.manifest_digest);
+                conn.execute(
+                    "UPDATE snapshots SET chain_hash = ?1 WHERE id = ?2",
+                    rusqlite::params![h, row.id],
+                )
+                .map_err(from_rusqlite)?;
+                updated += 1;
+                h
+            }
+        };
+        prev = Some(hash);
+    }
+    Ok(updated)
+}
+
+/// The first ledger row whose stored chain hash does not match recomputation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LedgerBreak {
+    /// 0-based position of the row in ledger order.
+    pub position: u64,
+    /// Snapshot ID of the offending row.
+    pub snapshot_id: String,
+    /// Chain hash recomputed from the preceding row.
+    pub expected_chain_hash: String,
+    /// Chain hash stored on the row (`None` if unchained).
+    pub stored_chain_hash: Option<String>,
+}
+
+/// Outcome of [`verify_ledger`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LedgerVerifyReport {
+    /// Number of rows examined (stops at the first break).
+    pub rows_checked: u64,
+    /// Chain hash of the last verified row (the ledger head).
+    pub head_chain_hash: Option<String>,
+    /// The first break in the chain, if any.
+    pub first_break: Option<LedgerBreak>,
+}
+
+impl LedgerVerifyReport {
+    /// True when every row's chain hash matched.
+    pub fn is_intact(&self) -> bool {
+        self.first_break.is_none()
+    }
+}
+
+/// Recompute the chain over the whole ledger and compare with stored hashes.
+///
+/// Read-only. Verification stops at the first mismatching (or unchained) row.
+pub fn verify_ledger(conn: &Connection) -> Result<LedgerVerifyReport> {
+    let rows = load_chain_rows(conn)?;
+    let mut prev: Option<String> = None;
+    let mut rows_checked = 0u64;
+    for (position, row) in rows.into_iter().enumerate() {
+        rows_checked += 1;
+        let expected = compute_chain_hash(prev.as_deref(), &row.manifest_digest);
+        if row.chain_hash.as_deref() != Some(expected.as_str()) {
+            return Ok(LedgerVerifyReport {
+                rows_checked,
+                head_chain_hash: prev,
+                first_break: Some(LedgerBreak {
+                    position: position as u64,
+                    snapshot_id: row.snapshot_id,
+                    expected_chain_hash: expected,
+                    stored_chain_hash: row.chain_hash,
+                }),
+            });
+        }
+        prev = Some(expected);
+    }
+    Ok(LedgerVerifyReport {
+        rows_checked,
+        head_chain_hash: prev,
+        first_break: None,
+    })
+}
+
+struct ChainRow {
+    id: i64,
+    snapshot_id: String,
+    manifest_digest: String,
+    chain_hash: Option<String>,
+}
+
+fn load_chain_rows(conn: &Connection) -> Result<Vec<ChainRow>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, snapshot_id, manifest_digest, chain_hash FROM snapshots ORDER BY id ASC",
+        )
+        .map_err(from_rusqlite)?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(ChainRow {
+                id: row.get(0)?,
+                snapshot_id: row.get(1)?,
+                manifest_digest: row.get(2)?,
+                chain_hash: row.get(3)?,
+            })
+        })
+        .map_err(from_rusqlite)?
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(from_rusqlite)?;
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain_hash_depends_on_predecessor() {
+        let a = compute_chain_hash(None, "digest-1");
+        let b = compute_chain_hash(Some(&a), "digest-2");
+        let b_other = compute_chain_hash(Some("tampered"), "digest-2");
+        assert_eq!(a.len(), 64);
+        assert_ne!(b, b_other);
+        assert_eq!(b, compute_chain_hash(Some(&a), "digest-2"));
+    }
+}
@@ -0,0 +1,131 @@
+//! Export of the snapshot ledger as JSON lines, for external analytics.
+//!
+//! One line per `snapshots` row, in commit order (`created_at`,
+//! `snapshot_id`). Rows are read and written one at a time, so memory use
+//! does not grow with the ledger. With `include_manifest_summary`, each line
+//! also carries a `manifest` object read from the snapshot's CAS manifest.
+
+#![allow(clippy::result_large_err)]
+
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use ettlex_core::errors::{ExError, ExErrorKind};
+use ettlex_core::snapshot::{ConstraintsEnvelopeSummary, ParsedManifest};
+use rusqlite::Connection;
+use serde::Serialize;
+
+use crate::cas::FsStore;
+use crate::errors::{from_rusqlite, io_error, Result};
+use crate::snapshot::query::{fetch_manifest_bytes_by_digest, row_to_snapshot_row};
+
+/// How to run [`export_ledger_jsonl`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LedgerExportOptions {
+    /// Read each manifest from CAS and add its summary fields.
+    pub include_manifest_summary: bool,
+}
+
+/// One exported line.
+#[derive(Debug, Clone, Serialize)]
+pub struct LedgerExportRow {
+    pub snapshot_id: String,
+    pub root_ettle_id: String,
+    pub manifest_digest: String,
+    pub semantic_manifest_digest: String,
+    /// Milliseconds since epoch
+    pub created_at: i64,
+    pub parent_snapshot_id: Option<String>,
+    pub policy_ref: String,
+    pub profile_ref: String,
+    pub status: String,
+    pub message: Option<String>,
+    pub annotations: BTreeMap<String, String>,
+    /// Ledger chain hash; `None` for rows predating the chain backfill
+    pub chain_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<LedgerManifestSummary>,
+}
+
+/// Manifest fields added with `include_manifest_summary`.
+#[derive(Debug, Clone, Serialize)]
+pub struct LedgerManifestSummary {
+    pub manifest_schema_version: u32,
+    pub store_schema_version: String,
+    pub seed_digest: Option<String>,
+    pub ept_len: usize,
+    pub constraints: ConstraintsEnvelopeSummary,
+}
+
+/// Write every ledger row to `out` as JSON lines; returns the row count.
+///
+/// # Errors
+/// * `MissingBlob` / `InvalidManifest` - with `include_manifest_summary`, a
+///   manifest is missing from CAS or unreadable.
+/// * `Serialization` - a row cannot be encoded.
+/// * `Io` - writing to `out` failed.
+/// * `Persistence` - the ledger cannot be read.
+pub fn export_ledger_jsonl<W: Write>(
+    conn: &Connection,
+    cas: &FsStore,
+    mut out: W,
+    options: LedgerExportOptions,
+) -> Result<usize> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT snapshot_id, root_ettle_id, manifest_digest, semantic_manifest_digest,
+                    created_at, parent_snapshot_id, policy_ref, profile_ref, status,
+                    message, annotations_json, chain_hash
+             FROM snapshots
+             ORDER BY created_at, snapshot_id",
+        )
+        .map_err(from_rusqlite)?;
+    let rows = stmt
+        .query_map([], |row| Ok((row_to_snapshot_row(row)?, row.get(11)?)))
+        .map_err(from_rusqlite)?;
+
+    let mut count = 0;
+    for row in rows {
+        let (row, chain_hash) = row.map_err(from_rusqlite)?;
+        let manifest = if options.include_manifest_summary {
+            let bytes = fetch_manifest_bytes_by_digest(cas, &row.manifest_digest)?;
+            let manifest = ParsedManifest::parse(&bytes)?.manifest;
+            Some(LedgerManifestSummary {
+                manifest_schema_version: manifest.manifest_schema_version,
+                store_schema_version: manifest.store_schema_version,
+                seed_digest: manifest.seed_digest,
+                ept_len: manifest.ept.len(),
+                constraints: manifest.constraints.summary(),
+            })
+        } else {
+            None
+        };
+        let line = LedgerExportRow {
+            snapshot_id: row.snapshot_id,
+            root_ettle_id: row.root_ettle_id,
+            manifest_digest: row.manifest_digest,
+            semantic_manifest_digest: row.semantic_manifest_digest,
+            created_at: row.created_at,
+            parent_snapshot_id: row.parent_snapshot_id,
+            policy_ref: row.policy_ref,
+            profile_ref: row.profile_ref,
+            status: row.status,
+            message: row.message,
+            annotations: row.annotations,
+            chain_hash,
+            manifest,
+        };
+        serde_json::to_writer(&mut out, &line).map_err(|e| {
+            ExError::new(ExErrorKind::Serialization)
+                .with_op("export_ledger_jsonl")
+                .with_entity_id(&line.snapshot_id)
+                .with_message(e.to_string())
+        })?;
+        out.write_all(b"\n")
+            .map_err(|e| io_error("export_ledger_jsonl", e))?;
+        count += 1;
+    }
+    out.flush()
+        .map_err(|e| io_error("export_ledger_jsonl", e))?;
+    Ok(count)
+}
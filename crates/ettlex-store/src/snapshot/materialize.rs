@@ -0,0 +1,222 @@
+//! Materialize a snapshot's CAS content into a plain directory.
+//!
+//! Produces a self-contained copy of one snapshot that can be attached to an
+//! audit ticket without access to the store:
+//!
+//! ```text
+//! <out>/
+//!   manifest.json        exact manifest bytes (hashes to manifest_digest)
+//!   blobs/<digest>       every CAS blob the manifest references
+//!   index.json           snapshot metadata + blob inventory
+//! ```
+//!
+//! "Referenced" means any 64-hex digest string anywhere in the manifest that
+//! resolves to a CAS blob. Digest-shaped values with no blob (computed
+//! digests such as `ept_digest`) are listed under `unresolved` in the index so
+//! nothing is silently dropped.
+
+#![allow(clippy::result_large_err)]
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use ettlex_core::errors::{ExError, ExErrorKind};
+use rusqlite::Connection;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::cas::{DigestReader, FsStore};
+use crate::errors::{io_error, Result};
+use crate::snapshot::id_scheme::resolve_snapshot_id;
+use crate::snapshot::query::{fetch_manifest_bytes_by_digest, fetch_snapshot_row};
+
+/// Manifest fields that digest the manifest itself rather than reference content.
+const SELF_DIGEST_POINTERS: &[&str] = &["/manifest_digest", "/semantic_manifest_digest"];
+
+/// One blob written to `blobs/`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MaterializedBlob {
+    pub digest: String,
+    /// Path relative to the output directory.
+    pub path: String,
+    pub size_bytes: u64,
+    /// JSON pointers into the manifest where the digest appears.
+    pub referenced_at: Vec<String>,
+}
+
+/// A digest-shaped manifest value with no matching CAS blob.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnresolvedDigest {
+    pub digest: String,
+    pub referenced_at: Vec<String>,
+}
+
+/// Contents of `index.json`; also returned to the caller.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MaterializeIndex {
+    pub snapshot_id: String,
+    pub root_ettle_id: String,
+    pub manifest_digest: String,
+    pub semantic_manifest_digest: String,
+    pub manifest_path: String,
+    pub blobs: Vec<MaterializedBlob>,
+    pub unresolved: Vec<UnresolvedDigest>,
+}
+
+/// Write the manifest and every referenced CAS blob for a snapshot to `out_dir`.
+///
+/// `snapshot_ref` may be a full snapshot ID or an unambiguous prefix.
+///
+/// # Errors
+/// * `NotFound` / `AmbiguousSelection` - the snapshot reference does not resolve.
+/// * `MissingBlob` - the manifest itself is missing from CAS.
+/// * `InvalidInput` - `out_dir` exists and is not an empty directory.
+/// * `DeterminismViolation` - a blob's bytes do not hash to its digest.
+/// * `Io` - writing the output failed.
+pub fn materialize_snapshot(
+    conn: &Connection,
+    cas: &FsStore,
+    snapshot_ref: &str,
+    out_dir: &Path,
+) -> Result<MaterializeIndex> {
+    let snapshot_id = resolve_snapshot_id(conn, snapshot_ref)?;
+    let row = fetch_snapshot_row(conn, &snapshot_id)?;
+    let manifest_bytes = fetch_manifest_bytes_by_digest(cas, &row.manifest_digest)?;
+    let manifest: Value = serde_json::from_slice(&manifest_bytes).map_err(|e| {
+        ExError::new(ExErrorKind::InvalidManifest)
+            .with_op("materialize_snapshot")
+            .with_entity_id(&row.manifest_digest)
+            .with_message(format!("manifest is not valid JSON: {}", e))
+    })?;
+
+    prepare_out_dir(out_dir)?;
+
+    let mut references: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    collect_digests(&manifest, String::new(), &mut references);
+
+    std::fs::write(out_dir.join("manifest.json"), &manifest_bytes)
+        .map_err(|e| io_error("materialize_snapshot", e))?;
+
+    let blobs_dir = out_dir.join("blobs");
+    let mut blobs = Vec::new();
+    let mut unresolved = Vec::new();
+    for (digest, referenced_at) in references {
+        let reader = match cas.read_stream(&digest) {
+            Ok(r) => r,
+            Err(e) if e.kind() == ExErrorKind::NotFound => {
+                unresolved.push(UnresolvedDigest {
+                    digest,
+                    referenced_at,
+                });
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        if blobs.is_empty() {
+            std::fs::create_dir_all(&blobs_dir).map_err(|e| io_error("materialize_snapshot", e))?;
+        }
+        let blob_path = blobs_dir.join(&digest);
+        let mut reader = DigestReader::new(reader);
+        let mut out =
+            std::fs::File::create(&blob_path).map_err(|e| io_error("materialize_snapshot", e))?;
+        std::io::copy(&mut reader, &mut out).map_err(|e| io_error("materialize_snapshot", e))?;
+        let (actual, size_bytes) = reader.finish();
+        if actual != digest {
+            let _ = std::fs::remove_file(&blob_path);
+            return Err(ExError::new(ExErrorKind::DeterminismViolation)
+                .with_op("materialize_snapshot")
+                .with_entity_id(&digest)
+                .with_message("CAS blob content does not match its digest"));
+        }
+        blobs.push(MaterializedBlob {
+            path: format!("blobs/{}", digest),
+            digest,
+            size_bytes,
+            referenced_at,
+        });
+    }
+
+    let index = MaterializeIndex {
+        snapshot_id: row.snapshot_id,
+        root_ettle_id: row.root_ettle_id,
+        manifest_digest: row.manifest_digest,
+        semantic_manifest_digest: row.semantic_manifest_digest,
+        manifest_path: "manifest.json".to_string(),
+        blobs,
+        unresolved,
+    };
+    let index_bytes = serde_json::to_vec_pretty(&index).map_err(|e| {
+        ExError::new(ExErrorKind::Serialization)
+            .with_op("materialize_snapshot")
+            .with_message(format!("failed to serialize index: {}", e))
+    })?;
+    std::fs::write(out_dir.join("index.json"), index_bytes)
+        .map_err(|e| io_error("materialize_snapshot", e))?;
+
+    Ok(index)
+}
+
+/// Create `out_dir` if needed; refuse to write into a non-empty directory.
+fn prepare_out_dir(out_dir: &Path) -> Result<()> {
+    if out_dir.exists() {
+        let mut entries =
+            std::fs::read_dir(out_dir).map_err(|e| io_error("materialize_snapshot", e))?;
+        if entries.next().is_some() {
+            return Err(ExError::new(ExErrorKind::InvalidInput)
+                .with_op("materialize_snapshot")
+                .with_message(format!(
+                    "output directory is not empty: {}",
+                    out_dir.display()
+                )));
+        }
+        return Ok(());
+    }
+    std::fs::create_dir_all(out_dir).map_err(|e| io_error("materialize_snapshot", e))
+}
+
+/// Record every 64-char lowercase hex string in `value`, keyed by digest, with
+/// the JSON pointer(s) where it occurs.
+fn collect_digests(value: &Value, pointer: String, out: &mut BTreeMap<String, Vec<String>>) {
+    match value {
+        Value::String(s) if is_digest(s) && !SELF_DIGEST_POINTERS.contains(&pointer.as_str()) => {
+            out.entry(s.clone()).or_default().push(pointer);
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                collect_digests(item, format!("{}/{}", pointer, i), out);
+            }
+        }
+        Value::Object(map) => {
+            for (key, item) in map {
+                let escaped = key.replace('~', "~0").replace('/', "~1");
+                collect_digests(item, format!("{}/{}", pointer, escaped), out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn is_digest(s: &str) -> bool {
+    s.len() == 64 && s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_collect_digests_skips_self_digests_and_records_pointers() {
+        let d = "ab".repeat(32);
+        let manifest = json!({
+            "manifest_digest": "cd".repeat(32),
+            "semantic_manifest_digest": "ef".repeat(32),
+            "ept": [{ "ep_digest": d }, { "ep_digest": d }],
+            "policy_ref": "policy/default@0",
+        });
+        let mut out = BTreeMap::new();
+        collect_digests(&manifest, String::new(), &mut out);
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[&d], vec!["/ept/0/ep_digest", "/ept/1/ep_digest"]);
+    }
+}
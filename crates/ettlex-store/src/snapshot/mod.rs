@@ -10,16 +10,35 @@
 //! - Atomic commit of both CAS + ledger
 //! - Idempotency checks (same semantic state → same snapshot ID)
 //! - Optimistic concurrency via expected_head validation
+//! - Ledger integrity hash chain and verification
+//! - Snapshot ID generation and prefix resolution
+//! - Materializing a snapshot's CAS content to a plain directory
+//! - Resumable bulk export of every committed manifest
+//! - JSON-lines export of the ledger for external analytics
 //!
 //! ## Non-Responsibilities
 //!
 //! - Manifest generation (handled by `ettlex-core`)
 //! - Orchestration (handled by `ettlex-engine`)
 
+pub mod export_all;
+pub mod id_scheme;
+pub mod ledger;
+pub mod ledger_export;
+pub mod materialize;
 pub mod persist;
 pub mod query;
 
 // Re-export primary types
+pub use export_all::{
+    export_all_snapshots, ExportAllOptions, ExportAllReport, ExportState, EXPORT_STATE_FILE,
+};
+pub use id_scheme::{generate_snapshot_id, resolve_snapshot_id, SnapshotIdScheme};
+pub use ledger::{verify_ledger, LedgerBreak, LedgerVerifyReport};
+pub use ledger_export::{
+    export_ledger_jsonl, LedgerExportOptions, LedgerExportRow, LedgerManifestSummary,
+};
+pub use materialize::{materialize_snapshot, MaterializeIndex, MaterializedBlob, UnresolvedDigest};
 pub use persist::{
     commit_snapshot, persist_manifest_to_cas, SnapshotCommitResult, SnapshotOptions,
 };
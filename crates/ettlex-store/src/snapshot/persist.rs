@@ -5,14 +5,20 @@
 
 #![allow(clippy::result_large_err)]
 
+use std::collections::BTreeMap;
+
 use crate::cas::FsStore;
 use crate::errors::Result;
+use crate::model::ROOT_STATUS_RETIRED;
+use crate::repo::SqliteRepo;
+use crate::snapshot::id_scheme::{generate_snapshot_id, SnapshotIdScheme};
+use crate::snapshot::ledger;
 use ettlex_core::errors::{ExError, ExErrorKind};
-use ettlex_core::snapshot::manifest::SnapshotManifest;
-use rusqlite::{Connection, OptionalExtension, Transaction};
+use ettlex_core::snapshot::manifest::{ConstraintsEnvelopeSummary, SnapshotManifest};
+use rusqlite::{Connection, ErrorCode, OptionalExtension, TransactionBehavior};
 
 /// Options for snapshot commit operation.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct SnapshotOptions {
     /// Expected current head snapshot ID (for optimistic concurrency)
     pub expected_head: Option<String>,
@@ -21,6 +27,13 @@ pub struct SnapshotOptions {
     /// If true, return existing snapshot when semantic digest matches (idempotent).
     /// Default false = append-only (each commit creates a new row).
     pub allow_dedup: bool,
+    /// Scheme used to generate the new snapshot's ID.
+    pub id_scheme: SnapshotIdScheme,
+    /// Free-text commit message stored on the ledger row.
+    pub message: Option<String>,
+    /// Key/value annotations stored on the ledger row. Like `message`, they
+    /// are not part of the manifest and do not affect any digest.
+    pub annotations: BTreeMap<String, String>,
 }
 
 /// Result of a snapshot commit operation.
@@ -34,6 +47,8 @@ pub struct SnapshotCommitResult {
     pub semantic_manifest_digest: String,
     /// Whether this was a duplicate (idempotent return)
     pub was_duplicate: bool,
+    /// Summary of the manifest's constraints envelope
+    pub constraints_summary: ConstraintsEnvelopeSummary,
 }
 
 /// Persist a snapshot manifest to content-addressable storage.
@@ -87,9 +102,11 @@ pub fn persist_manifest_to_cas(store: &FsStore, manifest: &SnapshotManifest) ->
 /// ## Arguments
 ///
 /// - `tx`: Database transaction
-/// - `snapshot_id`: UUIDv7 identifier for this snapshot
+/// - `snapshot_id`: Identifier for this snapshot (see `SnapshotIdScheme`)
 /// - `manifest`: Snapshot manifest with metadata
 /// - `parent_snapshot_id`: Optional parent snapshot for history tracking
+/// - `chain_hash`: Ledger integrity chain hash for this row (see `ledger`)
+/// - `options`: Supplies the commit message and annotations
 ///
 /// ## Returns
 ///
@@ -99,10 +116,12 @@ pub fn persist_manifest_to_cas(store: &FsStore, manifest: &SnapshotManifest) ->
 ///
 /// - `ExErrorKind::Persistence`: Database insert failed
 fn create_snapshot_ledger_entry(
-    tx: &Transaction,
+    tx: &Connection,
     snapshot_id: &str,
     manifest: &SnapshotManifest,
     parent_snapshot_id: Option<String>,
+    chain_hash: &str,
+    options: &SnapshotOptions,
 ) -> Result<i64> {
     // Convert RFC3339 timestamp to Unix milliseconds
     let created_at_ms = chrono::DateTime::parse_from_rfc3339(&manifest.created_at)
@@ -112,6 +131,15 @@ fn create_snapshot_ledger_entry(
                 .with_message(format!("Invalid timestamp in manifest: {}", e))
         })?
         .timestamp_millis();
+    let annotations_json = if options.annotations.is_empty() {
+        None
+    } else {
+        Some(serde_json::to_string(&options.annotations).map_err(|e| {
+            ExError::new(ExErrorKind::Serialization)
+                .with_op("create_snapshot_ledger_entry")
+                .with_message(format!("Failed to serialize annotations: {}", e))
+        })?)
+    };
 
     let row_id = tx
         .execute(
@@ -125,8 +153,11 @@ fn create_snapshot_ledger_entry(
                 parent_snapshot_id,
                 policy_ref,
                 profile_ref,
-                status
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                status,
+                chain_hash,
+                message,
+                annotations_json
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
             "#,
             rusqlite::params![
                 snapshot_id,
@@ -138,6 +169,9 @@ fn create_snapshot_ledger_entry(
                 manifest.policy_ref,
                 manifest.profile_ref,
                 "committed",
+                chain_hash,
+                options.message,
+                annotations_json,
             ],
         )
         .map_err(|e| {
@@ -159,10 +193,12 @@ fn create_snapshot_ledger_entry(
 /// Query for an existing snapshot by semantic digest.
 ///
 /// Checks if a snapshot with the given semantic digest already exists.
-/// Used for idempotency checks.
+/// Used for idempotency checks. The semantic digest covers the constraints
+/// envelope, so the caller's `constraints_summary` also describes the match.
 fn query_by_semantic_digest(
-    tx: &Transaction,
+    tx: &Connection,
     semantic_digest: &str,
+    constraints_summary: &ConstraintsEnvelopeSummary,
 ) -> Result<Option<SnapshotCommitResult>> {
     let mut stmt = tx
         .prepare(
@@ -186,6 +222,7 @@ fn query_by_semantic_digest(
                 manifest_digest: row.get(1)?,
                 semantic_manifest_digest: row.get(2)?,
                 was_duplicate: true,
+                constraints_summary: constraints_summary.clone(),
             })
         })
         .optional()
@@ -198,17 +235,87 @@ fn query_by_semantic_digest(
     Ok(result)
 }
 
+/// Reject archived Ettles as snapshot roots.
+///
+/// Roots with no `ettles` row (legacy or synthetic roots) are left to the
+/// caller's own validation.
+fn ensure_root_not_archived(conn: &Connection, root_ettle_id: &str) -> Result<()> {
+    let archived_at: Option<Option<String>> = conn
+        .query_row(
+            "SELECT archived_at FROM ettles WHERE id = ?1",
+            [root_ettle_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| {
+            ExError::new(ExErrorKind::Persistence)
+                .with_op("commit_snapshot")
+                .with_message(format!("Failed to read root ettle: {}", e))
+        })?;
+    if archived_at.flatten().is_some() {
+        return Err(ExError::new(ExErrorKind::RootEttleInvalid)
+            .with_op("commit_snapshot")
+            .with_entity_id(root_ettle_id)
+            .with_message(format!(
+                "Archived ettle cannot be a snapshot root: {}",
+                root_ettle_id
+            )));
+    }
+    Ok(())
+}
+
+/// Reject roots the registry marks as retired.
+///
+/// Unregistered roots are accepted; whether they may exist at all is a
+/// policy decision made above the store.
+fn ensure_root_not_retired(conn: &Connection, root_ettle_id: &str) -> Result<()> {
+    let retired = SqliteRepo::get_root(conn, root_ettle_id)?
+        .is_some_and(|root| root.status == ROOT_STATUS_RETIRED);
+    if retired {
+        return Err(ExError::new(ExErrorKind::RootEttleInvalid)
+            .with_op("commit_snapshot")
+            .with_entity_id(root_ettle_id)
+            .with_message(format!(
+                "Retired root accepts no new snapshots: {}",
+                root_ettle_id
+            )));
+    }
+    Ok(())
+}
+
+/// Reject blank commit messages and blank annotation keys.
+fn validate_annotations(options: &SnapshotOptions) -> Result<()> {
+    let invalid = |message: &str| {
+        ExError::new(ExErrorKind::InvalidInput)
+            .with_op("commit_snapshot")
+            .with_message(message.to_string())
+    };
+    if options
+        .message
+        .as_deref()
+        .is_some_and(|m| m.trim().is_empty())
+    {
+        return Err(invalid("commit message must not be blank"));
+    }
+    if options.annotations.keys().any(|k| k.trim().is_empty()) {
+        return Err(invalid("annotation keys must not be blank"));
+    }
+    Ok(())
+}
+
 /// Query for the current head snapshot (manifest_digest + snapshot_id) for a given root ettle.
 ///
-/// Head is defined as the most recent committed snapshot.
-fn query_current_head(tx: &Transaction, root_ettle_id: &str) -> Result<Option<(String, String)>> {
+/// Head is defined as the most recently committed snapshot in ledger order
+/// (`id`). Manifest `created_at` is stamped before the write lock is taken,
+/// so under concurrent commits it does not reflect commit order.
+fn query_current_head(tx: &Connection, root_ettle_id: &str) -> Result<Option<(String, String)>> {
     let mut stmt = tx
         .prepare(
             r#"
             SELECT manifest_digest, snapshot_id
             FROM snapshots
             WHERE root_ettle_id = ?1
-            ORDER BY created_at DESC
+            ORDER BY id DESC
             LIMIT 1
             "#,
         )
@@ -233,18 +340,24 @@ fn query_current_head(tx: &Transaction, root_ettle_id: &str) -> Result<Option<(S
 /// Commit a snapshot atomically to both CAS and ledger.
 ///
 /// This is the main entry point for snapshot persistence. It performs:
-/// 1. Expected head validation (if provided)
-/// 2. Idempotency check (return existing if semantic digest matches)
-/// 3. Persist manifest to CAS
-/// 4. Create ledger entry
-/// 5. Commit transaction atomically
+/// 1. Take the database write lock (`BEGIN IMMEDIATE`)
+/// 2. Expected head validation (if provided)
+/// 3. Idempotency check (return existing if semantic digest matches)
+/// 4. Persist manifest to CAS
+/// 5. Extend the ledger hash chain and create ledger entry
+/// 6. Commit transaction atomically
+///
+/// Because the lock is taken before the head is read, at most one of several
+/// concurrent commits against the same `expected_head` can succeed, even
+/// across separate connections or processes.
 ///
 /// ## Arguments
 ///
 /// - `conn`: Database connection
 /// - `cas_store`: CAS store instance
 /// - `manifest`: Snapshot manifest to commit
-/// - `options`: Commit options (expected_head, dry_run)
+/// - `options`: Commit options (expected_head, dry_run, allow_dedup, id_scheme,
+///   message, annotations)
 ///
 /// ## Returns
 ///
@@ -252,7 +365,12 @@ fn query_current_head(tx: &Transaction, root_ettle_id: &str) -> Result<Option<(S
 ///
 /// ## Errors
 ///
-/// - `ExErrorKind::Concurrency`: Expected head mismatch
+/// - `ExErrorKind::RootEttleInvalid`: Root Ettle is archived, or retired in
+///   the root registry
+/// - `ExErrorKind::InvalidInput`: Blank commit message or annotation key
+/// - `ExErrorKind::HeadMismatch`: Expected head mismatch
+/// - `ExErrorKind::Concurrency`: Write lock not acquired within the
+///   connection's busy timeout
 /// - `ExErrorKind::Persistence`: CAS or database error
 /// - `ExErrorKind::Serialization`: Manifest serialization failed
 ///
@@ -266,6 +384,11 @@ pub fn commit_snapshot(
     manifest: SnapshotManifest,
     options: SnapshotOptions,
 ) -> Result<SnapshotCommitResult> {
+    ensure_root_not_archived(conn, &manifest.root_ettle_id)?;
+    ensure_root_not_retired(conn, &manifest.root_ettle_id)?;
+    validate_annotations(&options)?;
+    let constraints_summary = manifest.constraints.summary();
+
     // Dry-run mode: compute digests but don't persist
     if options.dry_run {
         return Ok(SnapshotCommitResult {
@@ -273,18 +396,56 @@ pub fn commit_snapshot(
             manifest_digest: manifest.manifest_digest.clone(),
             semantic_manifest_digest: manifest.semantic_manifest_digest.clone(),
             was_duplicate: false,
+            constraints_summary,
         });
     }
 
-    let tx = conn.transaction().map_err(|e| {
+    // Inside a caller's transaction (apply_command opens one, holding the
+    // write lock) the snapshot is written as part of it.
+    if !conn.is_autocommit() {
+        return write_snapshot(conn, cas_store, &manifest, &options, constraints_summary);
+    }
+
+    // BEGIN IMMEDIATE takes the database write lock before the head is read,
+    // so concurrent committers on other connections serialize here and the
+    // loser re-reads the winner's head (→ HeadMismatch) instead of racing it.
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| match e {
+            rusqlite::Error::SqliteFailure(ref f, _) if f.code == ErrorCode::DatabaseBusy => {
+                ExError::new(ExErrorKind::Concurrency)
+                    .with_op("commit_snapshot")
+                    .with_entity_id(manifest.root_ettle_id.clone())
+                    .with_message(format!(
+                        "Snapshot ledger is locked by another writer: {}",
+                        e
+                    ))
+            }
+            _ => ExError::new(ExErrorKind::Persistence)
+                .with_op("commit_snapshot")
+                .with_message(format!("Failed to start transaction: {}", e)),
+        })?;
+
+    let result = write_snapshot(&tx, cas_store, &manifest, &options, constraints_summary)?;
+    tx.commit().map_err(|e| {
         ExError::new(ExErrorKind::Persistence)
             .with_op("commit_snapshot")
-            .with_message(format!("Failed to start transaction: {}", e))
+            .with_message(format!("Failed to commit transaction: {}", e))
     })?;
+    Ok(result)
+}
 
+/// Steps 1–7 of [`commit_snapshot`], run against an open transaction.
+fn write_snapshot(
+    tx: &Connection,
+    cas_store: &FsStore,
+    manifest: &SnapshotManifest,
+    options: &SnapshotOptions,
+    constraints_summary: ConstraintsEnvelopeSummary,
+) -> Result<SnapshotCommitResult> {
     // 1. Validate expected head if provided; resolve parent snapshot_id for FK
     let parent_snapshot_id = if let Some(expected) = &options.expected_head {
-        let current = query_current_head(&tx, &manifest.root_ettle_id)?;
+        let current = query_current_head(tx, &manifest.root_ettle_id)?;
         match current {
             Some((ref manifest_digest, ref snapshot_id)) if manifest_digest == expected => {
                 // Head matches — use snapshot_id (UUID) as FK for parent_snapshot_id
@@ -303,12 +464,14 @@ pub fn commit_snapshot(
         }
     } else {
         // No expected head, but link to existing head as parent if any
-        query_current_head(&tx, &manifest.root_ettle_id)?.map(|(_, sid)| sid)
+        query_current_head(tx, &manifest.root_ettle_id)?.map(|(_, sid)| sid)
     };
 
     // 2. Check idempotency (only when allow_dedup=true; default is append-only)
     if options.allow_dedup {
-        if let Some(existing) = query_by_semantic_digest(&tx, &manifest.semantic_manifest_digest)? {
+        if let Some(existing) =
+            query_by_semantic_digest(tx, &manifest.semantic_manifest_digest, &constraints_summary)?
+        {
             tracing::info!(
                 snapshot_id = %existing.snapshot_id,
                 semantic_digest = %manifest.semantic_manifest_digest,
@@ -322,29 +485,39 @@ pub fn commit_snapshot(
     // 3. Persist manifest to CAS (outside transaction, idempotent)
     // CAS computes digest of the actual JSON bytes written. We use this as the
     // official manifest_digest since it's what we can use to retrieve the manifest.
-    let cas_manifest_digest = persist_manifest_to_cas(cas_store, &manifest)?;
+    let cas_manifest_digest = persist_manifest_to_cas(cas_store, manifest)?;
 
-    // 4. Generate snapshot ID (UUIDv7 for temporal ordering)
-    let snapshot_id = uuid::Uuid::now_v7().to_string();
+    // 4. Generate snapshot ID under the requested scheme (UUIDv7 by default)
+    let snapshot_id = generate_snapshot_id(
+        tx,
+        options.id_scheme,
+        &manifest.root_ettle_id,
+        &cas_manifest_digest,
+    )?;
 
     // 5. Create modified manifest with CAS digest (for ledger storage)
     let mut manifest_for_ledger = manifest.clone();
     manifest_for_ledger.manifest_digest = cas_manifest_digest.clone();
 
-    // 6. Create ledger entry (inside transaction)
-    create_snapshot_ledger_entry(&tx, &snapshot_id, &manifest_for_ledger, parent_snapshot_id)?;
+    // 6. Extend the ledger integrity chain from the current head
+    let prev_chain_hash = ledger::head_chain_hash(tx)?;
+    let chain_hash = ledger::compute_chain_hash(prev_chain_hash.as_deref(), &cas_manifest_digest);
 
-    // 7. Commit transaction
-    tx.commit().map_err(|e| {
-        ExError::new(ExErrorKind::Persistence)
-            .with_op("commit_snapshot")
-            .with_message(format!("Failed to commit transaction: {}", e))
-    })?;
+    // 7. Create ledger entry (inside transaction)
+    create_snapshot_ledger_entry(
+        tx,
+        &snapshot_id,
+        &manifest_for_ledger,
+        parent_snapshot_id,
+        &chain_hash,
+        options,
+    )?;
 
     Ok(SnapshotCommitResult {
         snapshot_id,
         manifest_digest: cas_manifest_digest,
-        semantic_manifest_digest: manifest.semantic_manifest_digest,
+        semantic_manifest_digest: manifest.semantic_manifest_digest.clone(),
         was_duplicate: false,
+        constraints_summary,
     })
 }
@@ -5,6 +5,8 @@
 
 #![allow(clippy::result_large_err)]
 
+use std::collections::BTreeMap;
+
 use crate::cas::FsStore;
 use crate::errors::{from_rusqlite, Result};
 use ettlex_core::errors::{ExError, ExErrorKind};
@@ -31,6 +33,10 @@ pub struct SnapshotRow {
     pub profile_ref: String,
     /// Status (`committed`, `draft`, etc.)
     pub status: String,
+    /// Commit message, if one was given
+    pub message: Option<String>,
+    /// Key/value annotations (empty when none were given)
+    pub annotations: BTreeMap<String, String>,
 }
 
 /// Fetch the manifest digest for a snapshot by its snapshot ID.
@@ -95,7 +101,8 @@ pub fn fetch_manifest_bytes_by_digest(cas: &FsStore, manifest_digest: &str) -> R
 pub fn fetch_snapshot_row(conn: &Connection, snapshot_id: &str) -> Result<SnapshotRow> {
     conn.query_row(
         "SELECT snapshot_id, root_ettle_id, manifest_digest, semantic_manifest_digest,
-                created_at, parent_snapshot_id, policy_ref, profile_ref, status
+                created_at, parent_snapshot_id, policy_ref, profile_ref, status,
+                message, annotations_json
          FROM snapshots WHERE snapshot_id = ?1",
         [snapshot_id],
         row_to_snapshot_row,
@@ -120,7 +127,7 @@ pub fn list_snapshot_rows(conn: &Connection, ettle_id: Option<&str>) -> Result<V
                 .prepare(
                     "SELECT snapshot_id, root_ettle_id, manifest_digest,
                             semantic_manifest_digest, created_at, parent_snapshot_id,
-                            policy_ref, profile_ref, status
+                            policy_ref, profile_ref, status, message, annotations_json
                      FROM snapshots
                      ORDER BY created_at, snapshot_id",
                 )
@@ -136,7 +143,7 @@ pub fn list_snapshot_rows(conn: &Connection, ettle_id: Option<&str>) -> Result<V
                 .prepare(
                     "SELECT snapshot_id, root_ettle_id, manifest_digest,
                             semantic_manifest_digest, created_at, parent_snapshot_id,
-                            policy_ref, profile_ref, status
+                            policy_ref, profile_ref, status, message, annotations_json
                      FROM snapshots
                      WHERE root_ettle_id = ?1
                      ORDER BY created_at, snapshot_id",
@@ -170,15 +177,16 @@ pub fn fetch_snapshot_digests(conn: &Connection, snapshot_id: &str) -> Result<(S
     })
 }
 
-/// Fetch the most recently committed snapshot row (by `created_at DESC`).
+/// Fetch the most recently committed snapshot row (last in ledger order).
 ///
 /// Returns `None` if no snapshots exist yet.
 pub fn fetch_head_snapshot(conn: &Connection) -> Result<Option<SnapshotRow>> {
     conn.query_row(
         "SELECT snapshot_id, root_ettle_id, manifest_digest, semantic_manifest_digest,
-                created_at, parent_snapshot_id, policy_ref, profile_ref, status
+                created_at, parent_snapshot_id, policy_ref, profile_ref, status,
+                message, annotations_json
          FROM snapshots
-         ORDER BY created_at DESC, snapshot_id DESC
+         ORDER BY id DESC
          LIMIT 1",
         [],
         row_to_snapshot_row,
@@ -187,7 +195,24 @@ pub fn fetch_head_snapshot(conn: &Connection) -> Result<Option<SnapshotRow>> {
     .map_err(from_rusqlite)
 }
 
-fn row_to_snapshot_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<SnapshotRow> {
+/// Snapshot count and head (latest committed, in ledger order) for one root.
+pub fn fetch_root_snapshot_summary(
+    conn: &Connection,
+    root_ettle_id: &str,
+) -> Result<(u64, Option<String>)> {
+    conn.query_row(
+        "SELECT COUNT(*),
+                (SELECT snapshot_id FROM snapshots
+                 WHERE root_ettle_id = ?1 AND status = 'committed'
+                 ORDER BY id DESC LIMIT 1)
+         FROM snapshots WHERE root_ettle_id = ?1",
+        [root_ettle_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+    .map_err(from_rusqlite)
+}
+
+pub(crate) fn row_to_snapshot_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<SnapshotRow> {
     Ok(SnapshotRow {
         snapshot_id: row.get(0)?,
         root_ettle_id: row.get(1)?,
@@ -198,9 +223,27 @@ fn row_to_snapshot_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<SnapshotRow>
         policy_ref: row.get(6)?,
         profile_ref: row.get(7)?,
         status: row.get(8)?,
+        message: row.get(9)?,
+        annotations: parse_annotations(row.get(10)?, 10)?,
     })
 }
 
+fn parse_annotations(
+    json: Option<String>,
+    column: usize,
+) -> rusqlite::Result<BTreeMap<String, String>> {
+    match json {
+        None => Ok(BTreeMap::new()),
+        Some(json) => serde_json::from_str(&json).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(
+                column,
+                rusqlite::types::Type::Text,
+                Box::new(e),
+            )
+        }),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
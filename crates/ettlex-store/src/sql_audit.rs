@@ -0,0 +1,181 @@
+//! Debug audit of the SQL executed by the store.
+//!
+//! When enabled, every statement run on an attached connection is recorded
+//! in a process-wide ring buffer, oldest entries dropping off first. After a
+//! failing command the buffer shows what the store actually ran, which is
+//! usually enough to tell "wrong query" from "wrong data".
+//!
+//! SQLite's trace hook reports statements with their bound parameters
+//! expanded inline, so every literal (string, blob and number) is replaced
+//! with `***REDACTED***` before it is stored: parameter values are treated as
+//! `Sensitive` and never leave this module.
+//!
+//! Auditing is off by default. [`enable`] turns it on for the process;
+//! connections opened afterwards through [`crate::db::open`] are attached
+//! automatically, others with [`attach`].
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use rusqlite::Connection;
+
+/// Entries kept when no capacity has been set.
+pub const DEFAULT_CAPACITY: usize = 256;
+
+const REDACTED: &str = "***REDACTED***";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static NEXT_SEQ: AtomicU64 = AtomicU64::new(1);
+static RING: Mutex<Ring> = Mutex::new(Ring {
+    capacity: DEFAULT_CAPACITY,
+    entries: VecDeque::new(),
+});
+
+struct Ring {
+    capacity: usize,
+    entries: VecDeque<SqlAuditEntry>,
+}
+
+/// One recorded statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SqlAuditEntry {
+    /// Process-wide sequence number, increasing in execution order.
+    pub seq: u64,
+    /// The statement with literals redacted.
+    pub sql: String,
+}
+
+/// Turn auditing on for this process.
+pub fn enable() {
+    ENABLED.store(true, Ordering::SeqCst);
+}
+
+/// Turn auditing off. Recorded entries are kept until [`clear`].
+pub fn disable() {
+    ENABLED.store(false, Ordering::SeqCst);
+}
+
+/// Whether auditing is on.
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::SeqCst)
+}
+
+/// Install the audit hook on `conn` if auditing is enabled.
+///
+/// SQLite allows one trace hook per connection; attaching replaces any
+/// other hook already set.
+pub fn attach(conn: &mut Connection) {
+    if is_enabled() {
+        conn.trace(Some(record));
+    }
+}
+
+/// Record `sql` (literals redacted) if auditing is enabled.
+///
+/// For hooks that take over SQLite's trace slot from [`attach`] and must
+/// keep the audit fed while they hold it.
+pub fn record_statement(sql: &str) {
+    record(sql);
+}
+
+/// Keep at most `capacity` entries (minimum 1), dropping the oldest.
+pub fn set_capacity(capacity: usize) {
+    let mut ring = lock();
+    ring.capacity = capacity.max(1);
+    let excess = ring.entries.len().saturating_sub(ring.capacity);
+    ring.entries.drain(..excess);
+}
+
+/// The recorded statements, oldest first.
+pub fn entries() -> Vec<SqlAuditEntry> {
+    lock().entries.iter().cloned().collect()
+}
+
+/// Discard all recorded statements.
+pub fn clear() {
+    lock().entries.clear();
+}
+
+/// Replace every string, blob and numeric literal in `sql` with
+/// `***REDACTED***`. Identifiers, keywords, `NULL` and placeholders are kept.
+pub fn redact_sql(sql: &str) -> String {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut out = String::with_capacity(sql.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let prev_is_ident = out
+            .chars()
+            .next_back()
+            .is_some_and(|p| p.is_alphanumeric() || matches!(p, '_' | '$' | '?'));
+        if c == '\''
+            || (matches!(c, 'x' | 'X') && !prev_is_ident && chars.get(i + 1) == Some(&'\''))
+        {
+            // String or blob literal; `''` is an escaped quote
+            i += if c == '\'' { 1 } else { 2 };
+            while i < chars.len() {
+                if chars[i] == '\'' {
+                    if chars.get(i + 1) == Some(&'\'') {
+                        i += 2;
+                        continue;
+                    }
+                    i += 1;
+                    break;
+                }
+                i += 1;
+            }
+            out.push_str(REDACTED);
+        } else if c == '"' || c == '`' || c == '[' {
+            // Quoted identifier: copy verbatim
+            let close = if c == '[' { ']' } else { c };
+            out.push(c);
+            i += 1;
+            while i < chars.len() {
+                out.push(chars[i]);
+                i += 1;
+                if chars[i - 1] == close {
+                    break;
+                }
+            }
+        } else if (c.is_ascii_digit()
+            || (c == '.' && chars.get(i + 1).is_some_and(char::is_ascii_digit)))
+            && !prev_is_ident
+        {
+            while i < chars.len() {
+                let d = chars[i];
+                let exponent_sign = matches!(d, '+' | '-') && matches!(chars[i - 1], 'e' | 'E');
+                if d.is_ascii_alphanumeric() || d == '.' || exponent_sign {
+                    i += 1;
+                } else {
+                    break;
+                }
+            }
+            out.push_str(REDACTED);
+        } else {
+            out.push(c);
+            i += 1;
+        }
+    }
+    out
+}
+
+fn record(sql: &str) {
+    if !is_enabled() {
+        return;
+    }
+    let entry = SqlAuditEntry {
+        seq: NEXT_SEQ.fetch_add(1, Ordering::SeqCst),
+        sql: redact_sql(sql),
+    };
+    let mut ring = lock();
+    while ring.entries.len() >= ring.capacity {
+        ring.entries.pop_front();
+    }
+    ring.entries.push_back(entry);
+}
+
+fn lock() -> std::sync::MutexGuard<'static, Ring> {
+    // The ring holds plain data, so a panic mid-update cannot break it
+    RING.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
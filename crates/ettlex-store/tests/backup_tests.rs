@@ -0,0 +1,87 @@
+// Test suite for online store backup
+// Tests the database copy, the CAS manifest and output-directory checks
+
+use ettlex_core::errors::ExErrorKind;
+use ettlex_core::ops::Store;
+use ettlex_core::snapshot::manifest::generate_manifest;
+use ettlex_store::backup::{backup_store, BACKUP_DB_FILE, BACKUP_MANIFEST_FILE};
+use ettlex_store::cas::FsStore;
+use ettlex_store::snapshot::persist::{commit_snapshot, SnapshotOptions};
+use ettlex_store::snapshot::query::list_snapshot_rows;
+use rusqlite::Connection;
+use sha2::{Digest, Sha256};
+use tempfile::TempDir;
+
+fn setup_test_env() -> (TempDir, Connection, FsStore) {
+    let temp_dir = TempDir::new().unwrap();
+    let mut conn = Connection::open(temp_dir.path().join("test.db")).unwrap();
+    ettlex_store::db::configure(&conn).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    let cas = FsStore::new(temp_dir.path().join("cas"));
+    (temp_dir, conn, cas)
+}
+
+fn commit(conn: &mut Connection, cas: &FsStore, root: &str) -> String {
+    let manifest = generate_manifest(
+        vec![format!("ep:{}:0", root)],
+        "policy/default@0".into(),
+        "profile/default@0".into(),
+        format!("ettle:{}", root),
+        "0001".into(),
+        None,
+        &Store::new(),
+    )
+    .unwrap();
+    commit_snapshot(conn, cas, manifest, SnapshotOptions::default())
+        .unwrap()
+        .snapshot_id
+}
+
+#[test]
+fn test_backup_copies_db_and_lists_cas() {
+    let (temp_dir, mut conn, cas) = setup_test_env();
+    let snapshot_id = commit(&mut conn, &cas, "a");
+    let manifest_digest = list_snapshot_rows(&conn, None).unwrap()[0]
+        .manifest_digest
+        .clone();
+    let out = temp_dir.path().join("backup");
+
+    let manifest = backup_store(&conn, &cas, &out).unwrap();
+
+    // The copy opens on its own and holds the committed snapshot.
+    let copy = Connection::open(out.join(BACKUP_DB_FILE)).unwrap();
+    let rows = list_snapshot_rows(&copy, None).unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].snapshot_id, snapshot_id);
+
+    let bytes = std::fs::read(out.join(BACKUP_DB_FILE)).unwrap();
+    assert_eq!(manifest.db.sha256, hex::encode(Sha256::digest(&bytes)));
+    assert_eq!(manifest.db.size_bytes, bytes.len() as u64);
+    assert!(manifest
+        .cas_blobs
+        .iter()
+        .any(|b| b.digest == manifest_digest));
+    assert_eq!(
+        manifest.cas_total_bytes,
+        manifest.cas_blobs.iter().map(|b| b.size_bytes).sum::<u64>()
+    );
+    assert!(out.join(BACKUP_MANIFEST_FILE).exists());
+}
+
+#[test]
+fn test_backup_of_empty_cas_has_no_blobs() {
+    let (temp_dir, conn, cas) = setup_test_env();
+    let manifest = backup_store(&conn, &cas, &temp_dir.path().join("backup")).unwrap();
+    assert!(manifest.cas_blobs.is_empty());
+    assert_eq!(manifest.cas_total_bytes, 0);
+}
+
+#[test]
+fn test_non_empty_output_dir_is_rejected() {
+    let (temp_dir, conn, cas) = setup_test_env();
+    let out = temp_dir.path().join("backup");
+    backup_store(&conn, &cas, &out).unwrap();
+
+    let err = backup_store(&conn, &cas, &out).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+}
@@ -0,0 +1,107 @@
+//! CAS garbage collection tests — only blobs outside the reachable set are
+//! deleted, and a dry run deletes nothing.
+
+#![allow(clippy::unwrap_used, clippy::result_large_err)]
+
+use std::time::Duration;
+
+use ettlex_store::cas::{gc, FsStore, GcOptions};
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+fn setup() -> (TempDir, Connection, FsStore) {
+    let dir = TempDir::new().unwrap();
+    let mut conn = Connection::open(dir.path().join("test.db")).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    let cas = FsStore::new(dir.path().join("cas"));
+    (dir, conn, cas)
+}
+
+/// A manifest and a spilled body that are referenced, plus one orphan.
+fn populate(conn: &Connection, cas: &FsStore) -> (String, String, String) {
+    let manifest = cas.write(b"{\"manifest\":true}", "json").unwrap();
+    let body = cas.write(b"a long spilled body", "txt").unwrap();
+    let orphan = cas.write(b"nobody points here", "txt").unwrap();
+    conn.execute(
+        "INSERT INTO snapshots (snapshot_id, root_ettle_id, manifest_digest,
+                                semantic_manifest_digest, created_at, policy_ref, profile_ref)
+         VALUES ('snap:1', 'ettle:a', ?1, 's1', 0, '', 'profile/default@0')",
+        [&manifest],
+    )
+    .unwrap();
+    conn.execute(
+        "INSERT INTO ettles (id, title, created_at, updated_at, what_digest)
+         VALUES ('ettle:a', 'A', '2026-01-01T00:00:00Z', '2026-01-01T00:00:00Z', ?1)",
+        [&body],
+    )
+    .unwrap();
+    (manifest, body, orphan)
+}
+
+#[test]
+fn test_gc_deletes_only_unreachable_blobs() {
+    let (_dir, conn, cas) = setup();
+    let (manifest, body, orphan) = populate(&conn, &cas);
+
+    let report = gc(&conn, &cas, GcOptions::default()).unwrap();
+    assert_eq!(report.scanned, 3);
+    assert_eq!(report.reachable, 2);
+    assert_eq!(report.deleted.len(), 1);
+    assert_eq!(report.deleted[0].digest, orphan);
+    assert_eq!(report.bytes_freed, b"nobody points here".len() as u64);
+
+    assert!(cas.find_blob(&orphan).unwrap().is_none());
+    assert!(cas.find_blob(&manifest).unwrap().is_some());
+    assert!(cas.find_blob(&body).unwrap().is_some());
+
+    // A second pass has nothing left to do
+    let again = gc(&conn, &cas, GcOptions::default()).unwrap();
+    assert!(again.deleted.is_empty());
+    assert_eq!(again.bytes_freed, 0);
+}
+
+#[test]
+fn test_gc_dry_run_reports_without_deleting() {
+    let (_dir, conn, cas) = setup();
+    let (_, _, orphan) = populate(&conn, &cas);
+
+    let report = gc(
+        &conn,
+        &cas,
+        GcOptions {
+            dry_run: true,
+            ..GcOptions::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(report.deleted.len(), 1);
+    assert!(report.bytes_freed > 0);
+    assert!(cas.find_blob(&orphan).unwrap().is_some());
+}
+
+#[test]
+fn test_gc_keeps_recent_blobs() {
+    let (_dir, conn, cas) = setup();
+    let (_, _, orphan) = populate(&conn, &cas);
+
+    let report = gc(
+        &conn,
+        &cas,
+        GcOptions {
+            min_age: Some(Duration::from_secs(3600)),
+            ..GcOptions::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(report.too_recent, 1);
+    assert!(report.deleted.is_empty());
+    assert!(cas.find_blob(&orphan).unwrap().is_some());
+}
+
+#[test]
+fn test_gc_on_empty_store() {
+    let (_dir, conn, cas) = setup();
+    let report = gc(&conn, &cas, GcOptions::default()).unwrap();
+    assert_eq!(report.scanned, 0);
+    assert!(report.deleted.is_empty());
+}
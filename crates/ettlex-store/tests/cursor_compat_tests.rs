@@ -0,0 +1,97 @@
+//! Cursor compatibility: cursors handed out by earlier builds must keep
+//! decoding. The literals below are frozen wire formats — if one of these
+//! tests fails, stored client cursors would break on upgrade.
+
+#![allow(clippy::result_large_err)]
+
+use ettlex_core::errors::ExErrorKind;
+use ettlex_store::model::EttleCursor;
+use ettlex_store::repo::{CursorCodec, PageKey};
+
+/// Pre-versioning engine ID cursor: standard padded base64 of the bare ID.
+const V0_ID: &str = "ZXR0bGU6MDE5OC9hYmM=";
+/// Pre-versioning keyset cursor: standard base64 of `"<ms>|<id>"`.
+const V0_PAGE_KEY: &str = "MTcwMDAwMDAwMDAwMHxkZWNpc2lvbjox";
+/// Pre-versioning `list_ettles` cursor: URL-safe base64 of `"<created_at>,<id>"`.
+const V0_ETTLE: &str = "MjAyNi0wMS0wMVQwMDowMDowMFosZXR0bGU6MQ";
+/// v1 unsigned keyset cursor for `(1700000000000, "decision:1")`.
+const V1_PAGE_KEY: &str = "djE6dHM6OjE3MDAwMDAwMDAwMDB8ZGVjaXNpb246MQ";
+/// v1 keyset cursor for the same key, signed with `b"secret"`.
+const V1_PAGE_KEY_SIGNED: &str = "djE6dHM6YzFkYjg0YWNhZWEzODljZTMzYTg2NTNlODg4MjdlMzliMDIxNGU5MGVjMWRlMDU3ZjEyMDBkYjE4MDNlY2Y5MToxNzAwMDAwMDAwMDAwfGRlY2lzaW9uOjE";
+
+fn page_key() -> PageKey {
+    PageKey::new(1_700_000_000_000, "decision:1")
+}
+
+#[test]
+fn test_v0_cursors_still_decode() {
+    let codec = CursorCodec::unsigned();
+    assert_eq!(codec.decode::<String>(V0_ID).unwrap(), "ettle:0198/abc");
+    assert_eq!(codec.decode::<PageKey>(V0_PAGE_KEY).unwrap(), page_key());
+    let ettle: EttleCursor = codec.decode(V0_ETTLE).unwrap();
+    assert_eq!(ettle.created_at, "2026-01-01T00:00:00Z");
+    assert_eq!(ettle.id, "ettle:1");
+}
+
+#[test]
+fn test_v1_wire_format_is_stable() {
+    let unsigned = CursorCodec::unsigned();
+    assert_eq!(unsigned.encode(&page_key()), V1_PAGE_KEY);
+    assert_eq!(unsigned.decode::<PageKey>(V1_PAGE_KEY).unwrap(), page_key());
+
+    let signed = CursorCodec::signed(b"secret".to_vec());
+    assert_eq!(signed.encode(&page_key()), V1_PAGE_KEY_SIGNED);
+    assert_eq!(
+        signed.decode::<PageKey>(V1_PAGE_KEY_SIGNED).unwrap(),
+        page_key()
+    );
+    // Turning signing off later keeps signed cursors usable
+    assert_eq!(
+        unsigned.decode::<PageKey>(V1_PAGE_KEY_SIGNED).unwrap(),
+        page_key()
+    );
+}
+
+#[test]
+fn test_round_trip_for_every_key_kind() {
+    let codec = CursorCodec::unsigned();
+    let id = "profile/with:colons|and/slashes@0".to_string();
+    assert_eq!(codec.decode::<String>(&codec.encode(&id)).unwrap(), id);
+
+    let ettle = EttleCursor {
+        created_at: "2026-03-04 05:06:07".to_string(),
+        id: "ettle:x".to_string(),
+    };
+    let back: EttleCursor = codec.decode(&codec.encode(&ettle)).unwrap();
+    assert_eq!((back.created_at, back.id), (ettle.created_at, ettle.id));
+}
+
+#[test]
+fn test_rejected_cursors() {
+    let unsigned = CursorCodec::unsigned();
+    let signed = CursorCodec::signed(b"secret".to_vec());
+    let rejected = [
+        // Key kind mismatch: a keyset cursor replayed against an ID listing
+        unsigned.decode::<String>(V1_PAGE_KEY).map(|_| ()),
+        // Unknown future version
+        unsigned.decode::<PageKey>("djI6dHM6OjF8YQ").map(|_| ()),
+        // Not base64
+        unsigned.decode::<PageKey>("not a cursor!").map(|_| ()),
+        // Malformed payload for the key type
+        unsigned.decode::<PageKey>(V0_ID).map(|_| ()),
+        // Signing enabled: unsigned v1, v0 and wrongly keyed cursors
+        signed.decode::<PageKey>(V1_PAGE_KEY).map(|_| ()),
+        signed.decode::<PageKey>(V0_PAGE_KEY).map(|_| ()),
+        CursorCodec::signed(b"other".to_vec())
+            .decode::<PageKey>(V1_PAGE_KEY_SIGNED)
+            .map(|_| ()),
+    ];
+    for (i, result) in rejected.into_iter().enumerate() {
+        assert_eq!(
+            result.unwrap_err().kind(),
+            ExErrorKind::InvalidInput,
+            "case {}",
+            i
+        );
+    }
+}
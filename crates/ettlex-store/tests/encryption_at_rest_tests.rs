@@ -0,0 +1,227 @@
+//! Encryption at rest: envelope-encrypted CAS blobs and the SQLCipher open path.
+
+#![allow(clippy::result_large_err)]
+
+use std::io::Read;
+use std::sync::Arc;
+
+use ettlex_core::errors::ExErrorKind;
+use ettlex_store::cas::{FsStore, StaticKeyProvider};
+use tempfile::TempDir;
+
+fn encrypted(root: &std::path::Path) -> FsStore {
+    let provider = StaticKeyProvider::new("team-key-1", [42; 32]).unwrap();
+    FsStore::new(root)
+        .with_encryption(Arc::new(provider))
+        .with_verify_reads(true)
+}
+
+fn blob_files(root: &std::path::Path) -> Vec<Vec<u8>> {
+    FsStore::new(root)
+        .list_blobs()
+        .unwrap()
+        .iter()
+        .map(|b| {
+            std::fs::read(
+                root.join(&b.digest[..2])
+                    .join(format!("{}.{}", b.digest, b.extension)),
+            )
+            .unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn test_encrypted_store_roundtrip_hides_plaintext_on_disk() {
+    let dir = TempDir::new().unwrap();
+    let cas = encrypted(dir.path());
+    let body = b"internal payment topology".to_vec();
+
+    let digest = cas.write(&body, "json").unwrap();
+    // Digest identity is unchanged by encryption
+    assert_eq!(
+        digest,
+        FsStore::new(dir.path().join("plain"))
+            .write(&body, "json")
+            .unwrap()
+    );
+    assert_eq!(cas.read(&digest).unwrap(), body);
+    assert!(cas.write_tracked(&body, "json").unwrap().reused);
+
+    let mut streamed = Vec::new();
+    let mut reader = cas.read_stream(&digest).unwrap();
+    assert_eq!(reader.size(), body.len() as u64);
+    reader.read_to_end(&mut streamed).unwrap();
+    assert_eq!(streamed, body);
+
+    let (write, len) = cas.write_stream(&b"streamed body"[..], "txt").unwrap();
+    assert_eq!(len, 13);
+    assert_eq!(cas.read(&write.digest).unwrap(), b"streamed body");
+
+    for file in blob_files(dir.path()) {
+        assert!(file.starts_with(b"EXCASE01"));
+        assert!(!file
+            .windows(8)
+            .any(|w| w == b"internal" || w == b"streamed"));
+    }
+}
+
+#[test]
+fn test_plaintext_blobs_stay_readable_after_enabling_encryption() {
+    let dir = TempDir::new().unwrap();
+    let digest = FsStore::new(dir.path()).write(b"legacy", "txt").unwrap();
+    assert_eq!(encrypted(dir.path()).read(&digest).unwrap(), b"legacy");
+}
+
+#[test]
+fn test_encrypted_blob_needs_the_right_key() {
+    let dir = TempDir::new().unwrap();
+    let digest = encrypted(dir.path()).write(b"secret", "txt").unwrap();
+
+    let err = FsStore::new(dir.path()).read(&digest).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+
+    let other = StaticKeyProvider::new("team-key-2", [42; 32]).unwrap();
+    let err = FsStore::new(dir.path())
+        .with_encryption(Arc::new(other))
+        .read(&digest)
+        .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+
+    let wrong = StaticKeyProvider::new("team-key-1", [7; 32]).unwrap();
+    let err = FsStore::new(dir.path())
+        .with_encryption(Arc::new(wrong))
+        .read(&digest)
+        .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvariantViolation);
+}
+
+#[cfg(not(feature = "sqlcipher"))]
+#[test]
+fn test_open_encrypted_refuses_plain_sqlite() {
+    let dir = TempDir::new().unwrap();
+    let err = ettlex_store::db::open_encrypted(dir.path().join("x.db"), "k").unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotImplemented);
+    // Same probe, and message, as rekey
+    let conn = ettlex_store::db::open(dir.path().join("y.db")).unwrap();
+    let rekey_err = ettlex_store::db::rekey(&conn, "k").unwrap_err();
+    assert_eq!(err.message(), rekey_err.message());
+}
+
+#[cfg(feature = "sqlcipher")]
+#[test]
+fn test_open_encrypted_requires_matching_key() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("x.db");
+    let mut conn = ettlex_store::db::open_encrypted(&path, "right").unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    drop(conn);
+
+    let err = ettlex_store::db::open_encrypted(&path, "wrong").unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::Persistence);
+    let conn = ettlex_store::db::open_encrypted(&path, "right").unwrap();
+
+    ettlex_store::db::rekey(&conn, "rotated").unwrap();
+    drop(conn);
+    assert!(ettlex_store::db::open_encrypted(&path, "right").is_err());
+    assert!(ettlex_store::db::open_encrypted(&path, "rotated").is_ok());
+}
+
+#[test]
+fn test_open_encrypted_rejects_empty_key() {
+    let dir = TempDir::new().unwrap();
+    let err = ettlex_store::db::open_encrypted(dir.path().join("x.db"), "").unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_rotation_reseals_blobs_and_resumes() {
+    use ettlex_store::cas::rotation::rotate_cas_key;
+
+    let dir = TempDir::new().unwrap();
+    let plain = FsStore::new(dir.path())
+        .write(b"plaintext blob", "txt")
+        .unwrap();
+    let old = encrypted(dir.path());
+    let sealed = old.write(b"old key blob", "json").unwrap();
+
+    let rotated = FsStore::new(dir.path()).with_encryption(Arc::new(
+        StaticKeyProvider::new("team-key-2", [9; 32])
+            .unwrap()
+            .with_retired_key("team-key-1", [42; 32])
+            .unwrap(),
+    ));
+    let mut progress = Vec::new();
+    let report = rotate_cas_key(&rotated, |p| progress.push((p.processed, p.total))).unwrap();
+    assert_eq!(report.key_id, "team-key-2");
+    assert_eq!(
+        (report.total, report.resealed, report.already_current),
+        (2, 2, 0)
+    );
+    assert_eq!(progress, vec![(1, 2), (2, 2)]);
+
+    // Running again (as after a crash) finds nothing left to do
+    let again = rotate_cas_key(&rotated, |_| {}).unwrap();
+    assert_eq!((again.resealed, again.already_current), (0, 2));
+
+    // Only the new key is needed from now on
+    let new_only = FsStore::new(dir.path()).with_encryption(Arc::new(
+        StaticKeyProvider::new("team-key-2", [9; 32]).unwrap(),
+    ));
+    assert_eq!(new_only.read(&plain).unwrap(), b"plaintext blob");
+    assert_eq!(new_only.read(&sealed).unwrap(), b"old key blob");
+    let err = old.read(&sealed).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+}
+
+#[test]
+fn test_rotation_requires_a_key_provider() {
+    let dir = TempDir::new().unwrap();
+    let err =
+        ettlex_store::cas::rotation::rotate_cas_key(&FsStore::new(dir.path()), |_| {}).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+}
+
+#[cfg(not(feature = "sqlcipher"))]
+#[test]
+fn test_rekey_refuses_plain_sqlite() {
+    let conn = ettlex_store::db::open_in_memory().unwrap();
+    let err = ettlex_store::db::rekey(&conn, "new").unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotImplemented);
+}
+
+#[test]
+fn test_env_cas_key_seals_blobs_opened_through_the_shared_helper() {
+    use ettlex_store::env_keys::{open_cas, CAS_KEY_ID_VAR, CAS_KEY_VAR};
+
+    // The only test in this binary that touches these variables.
+    let dir = TempDir::new().unwrap();
+    std::env::set_var(CAS_KEY_VAR, "2a".repeat(32));
+    std::env::set_var(CAS_KEY_ID_VAR, "env-key");
+    let digest = open_cas(dir.path())
+        .unwrap()
+        .write(b"secret", "txt")
+        .unwrap();
+    assert!(blob_files(dir.path())[0].starts_with(b"EXCASE01"));
+    assert_eq!(
+        open_cas(dir.path()).unwrap().read(&digest).unwrap(),
+        b"secret"
+    );
+
+    std::env::set_var(CAS_KEY_VAR, "not-hex");
+    let err = open_cas(dir.path()).err().unwrap();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+
+    std::env::remove_var(CAS_KEY_VAR);
+    std::env::remove_var(CAS_KEY_ID_VAR);
+    assert!(FsStore::new(dir.path()).read(&digest).is_err());
+    assert_eq!(
+        open_cas(dir.path())
+            .unwrap()
+            .write(b"plain", "txt")
+            .unwrap(),
+        FsStore::new(dir.path().join("plain"))
+            .write(b"plain", "txt")
+            .unwrap()
+    );
+}
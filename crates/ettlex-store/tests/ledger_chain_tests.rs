@@ -0,0 +1,177 @@
+// Test suite for the snapshot ledger integrity chain
+// Tests chain extension on commit, verification, tamper detection, and backfill
+
+use ettlex_core::ops::Store;
+use ettlex_core::snapshot::manifest::generate_manifest;
+use ettlex_store::cas::FsStore;
+use ettlex_store::snapshot::ledger::{backfill_chain, compute_chain_hash, verify_ledger};
+use ettlex_store::snapshot::persist::{commit_snapshot, SnapshotOptions};
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+fn setup_test_env() -> (TempDir, Connection, FsStore) {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("test.db");
+    let cas_path = temp_dir.path().join("cas");
+    let mut conn = Connection::open(&db_path).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    let cas = FsStore::new(cas_path);
+    (temp_dir, conn, cas)
+}
+
+fn commit(conn: &mut Connection, cas: &FsStore, root: &str) -> String {
+    let manifest = generate_manifest(
+        vec![format!("{}:0", root)],
+        "policy/default@0".into(),
+        "profile/default@0".into(),
+        root.into(),
+        "0001".into(),
+        None,
+        &Store::new(),
+    )
+    .unwrap();
+    commit_snapshot(
+        conn,
+        cas,
+        manifest,
+        SnapshotOptions {
+            expected_head: None,
+            dry_run: false,
+            allow_dedup: false,
+            ..Default::default()
+        },
+    )
+    .unwrap()
+    .snapshot_id
+}
+
+#[test]
+fn test_verify_empty_ledger_is_intact() {
+    let (_tmp, conn, _cas) = setup_test_env();
+    let report = verify_ledger(&conn).unwrap();
+    assert!(report.is_intact());
+    assert_eq!(report.rows_checked, 0);
+    assert!(report.head_chain_hash.is_none());
+}
+
+#[test]
+fn test_commits_extend_chain() {
+    let (_tmp, mut conn, cas) = setup_test_env();
+    commit(&mut conn, &cas, "ettle:a");
+    commit(&mut conn, &cas, "ettle:b");
+    commit(&mut conn, &cas, "ettle:a");
+
+    let rows: Vec<(String, String)> = conn
+        .prepare("SELECT manifest_digest, chain_hash FROM snapshots ORDER BY id ASC")
+        .unwrap()
+        .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[0].1, compute_chain_hash(None, &rows[0].0));
+    assert_eq!(rows[1].1, compute_chain_hash(Some(&rows[0].1), &rows[1].0));
+    assert_eq!(rows[2].1, compute_chain_hash(Some(&rows[1].1), &rows[2].0));
+
+    let report = verify_ledger(&conn).unwrap();
+    assert!(report.is_intact());
+    assert_eq!(report.rows_checked, 3);
+    assert_eq!(report.head_chain_hash.as_deref(), Some(rows[2].1.as_str()));
+}
+
+#[test]
+fn test_verify_detects_rewritten_digest() {
+    let (_tmp, mut conn, cas) = setup_test_env();
+    commit(&mut conn, &cas, "ettle:a");
+    let tampered = commit(&mut conn, &cas, "ettle:a");
+    commit(&mut conn, &cas, "ettle:a");
+
+    conn.execute(
+        "UPDATE snapshots SET manifest_digest = 'forged' WHERE snapshot_id = ?1",
+        [&tampered],
+    )
+    .unwrap();
+
+    let report = verify_ledger(&conn).unwrap();
+    let brk = report.first_break.expect("tampering should be detected");
+    assert_eq!(brk.position, 1);
+    assert_eq!(brk.snapshot_id, tampered);
+}
+
+#[test]
+fn test_verify_detects_deleted_row() {
+    let (_tmp, mut conn, cas) = setup_test_env();
+    commit(&mut conn, &cas, "ettle:a");
+    let removed = commit(&mut conn, &cas, "ettle:b");
+    let after = commit(&mut conn, &cas, "ettle:c");
+
+    conn.execute("DELETE FROM snapshots WHERE snapshot_id = ?1", [&removed])
+        .unwrap();
+
+    let report = verify_ledger(&conn).unwrap();
+    assert_eq!(report.first_break.unwrap().snapshot_id, after);
+}
+
+#[test]
+fn test_backfill_chains_legacy_rows() {
+    let (_tmp, mut conn, cas) = setup_test_env();
+    commit(&mut conn, &cas, "ettle:a");
+    commit(&mut conn, &cas, "ettle:b");
+    conn.execute("UPDATE snapshots SET chain_hash = NULL", [])
+        .unwrap();
+    assert!(!verify_ledger(&conn).unwrap().is_intact());
+
+    assert_eq!(backfill_chain(&conn).unwrap(), 2);
+    assert!(verify_ledger(&conn).unwrap().is_intact());
+
+    // Subsequent commits extend the backfilled chain
+    commit(&mut conn, &cas, "ettle:c");
+    let report = verify_ledger(&conn).unwrap();
+    assert!(report.is_intact());
+    assert_eq!(report.rows_checked, 3);
+}
+
+#[test]
+fn test_commit_on_legacy_ledger_backfills_once() {
+    let (_tmp, mut conn, cas) = setup_test_env();
+    commit(&mut conn, &cas, "ettle:a");
+    commit(&mut conn, &cas, "ettle:b");
+    conn.execute("UPDATE snapshots SET chain_hash = NULL", [])
+        .unwrap();
+
+    commit(&mut conn, &cas, "ettle:c");
+    let report = verify_ledger(&conn).unwrap();
+    assert!(report.is_intact());
+    assert_eq!(report.rows_checked, 3);
+}
+
+#[test]
+fn test_commit_extends_from_head_without_rescanning_ledger() {
+    let (_tmp, mut conn, cas) = setup_test_env();
+    let first = commit(&mut conn, &cas, "ettle:a");
+    commit(&mut conn, &cas, "ettle:b");
+    conn.execute(
+        "UPDATE snapshots SET chain_hash = NULL WHERE snapshot_id = ?1",
+        [&first],
+    )
+    .unwrap();
+
+    // The head is still chained, so the commit must not touch earlier rows
+    commit(&mut conn, &cas, "ettle:c");
+    let first_hash: Option<String> = conn
+        .query_row(
+            "SELECT chain_hash FROM snapshots WHERE snapshot_id = ?1",
+            [&first],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(first_hash, None);
+    assert_eq!(
+        verify_ledger(&conn)
+            .unwrap()
+            .first_break
+            .unwrap()
+            .snapshot_id,
+        first
+    );
+}
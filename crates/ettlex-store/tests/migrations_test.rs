@@ -25,29 +25,45 @@ fn test_apply_migrations_on_empty_db() {
         result.err()
     );
 
-    // And: All 15 expected tables exist (constraints/ep_constraint_refs dropped in 014,
+    // And: All 26 expected tables exist (constraints/ep_constraint_refs dropped in 014,
     //       mcp_command_log renamed to command_log in 014,
     //       relation_type_registry/relations/groups/group_members added in 014,
-    //       eps/cas_blobs/facet_snapshots dropped in 015)
+    //       eps/cas_blobs/facet_snapshots dropped in 015, comments added in 019,
+    //       profile_environment_defaults added in 022, approval_events added in 023,
+    //       settings added in 025, roots added in 028,
+    //       api_tokens added in 029, import_sessions/import_session_items
+    //       added in 030, ettle_assignments added in 031,
+    //       snapshot_commit_queue added in 032, command_journal added in 034)
     let tables = get_table_names(&conn);
-    assert_eq!(tables.len(), 15, "Should have exactly 15 tables");
+    assert_eq!(tables.len(), 26, "Should have exactly 26 tables");
 
     let expected_tables = vec![
         "schema_version",
         "ettles",
         "snapshots",
         "provenance_events",
-        "decisions",               // Added in migration 004
-        "decision_evidence_items", // Added in migration 004
-        "decision_links",          // Added in migration 004
-        "profiles",                // Added in migration 005
-        "approval_requests",       // Added in migration 006
-        "command_log",             // Added in migration 008, renamed in 014
-        "sqlite_sequence",         // Auto-created by SQLite for AUTOINCREMENT columns
-        "relation_type_registry",  // Added in migration 014
-        "relations",               // Added in migration 014
-        "groups",                  // Added in migration 014
-        "group_members",           // Added in migration 014
+        "decisions",                    // Added in migration 004
+        "decision_evidence_items",      // Added in migration 004
+        "decision_links",               // Added in migration 004
+        "profiles",                     // Added in migration 005
+        "approval_requests",            // Added in migration 006
+        "command_log",                  // Added in migration 008, renamed in 014
+        "sqlite_sequence",              // Auto-created by SQLite for AUTOINCREMENT columns
+        "relation_type_registry",       // Added in migration 014
+        "relations",                    // Added in migration 014
+        "groups",                       // Added in migration 014
+        "group_members",                // Added in migration 014
+        "comments",                     // Added in migration 019
+        "profile_environment_defaults", // Added in migration 022
+        "approval_events",              // Added in migration 023
+        "settings",                     // Added in migration 025
+        "roots",                        // Added in migration 028
+        "api_tokens",                   // Added in migration 029
+        "import_sessions",              // Added in migration 030
+        "import_session_items",         // Added in migration 030
+        "ettle_assignments",            // Added in migration 031
+        "snapshot_commit_queue",        // Added in migration 032
+        "command_journal",              // Added in migration 034
     ];
 
     for expected_table in &expected_tables {
@@ -77,8 +93,8 @@ fn test_migration_gap_fails() {
         .unwrap();
 
     assert_eq!(
-        version_count, 15,
-        "Should have exactly 15 migrations applied"
+        version_count, 36,
+        "Should have exactly 36 migrations applied"
     );
 }
 
@@ -101,7 +117,7 @@ fn test_migration_idempotency() {
         .query_row("SELECT COUNT(*) FROM schema_version", [], |row| row.get(0))
         .unwrap();
 
-    assert_eq!(version_count, 15, "Should still have exactly 15 migrations");
+    assert_eq!(version_count, 36, "Should still have exactly 36 migrations");
 }
 
 #[test]
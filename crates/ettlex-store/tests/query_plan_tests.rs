@@ -0,0 +1,127 @@
+// Test suite for hot-path index usage
+// Seeds a store large enough for the planner to care, runs ANALYZE, and
+// asserts EXPLAIN QUERY PLAN picks the indexes from migration 020 for the
+// queries the repo and snapshot layers issue on every traversal and commit.
+
+use rusqlite::Connection;
+
+fn setup_large_store() -> Connection {
+    let mut conn = Connection::open_in_memory().unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    // Plan shape is all we test; skip FK bookkeeping for the bulk fixture.
+    conn.execute_batch("PRAGMA foreign_keys = OFF;").unwrap();
+
+    let tx = conn.transaction().unwrap();
+    for i in 0..500 {
+        let parent = format!("ettle:{}", i);
+        for (j, rt) in ["refinement", "constraint", "option"].iter().enumerate() {
+            tx.execute(
+                "INSERT INTO relations (id, source_ettle_id, target_ettle_id, relation_type, created_at, tombstoned_at)
+                 VALUES (?1, ?2, ?3, ?4, '2026-01-01T00:00:00Z', CASE WHEN ?5 THEN '2026-01-02T00:00:00Z' END)",
+                rusqlite::params![
+                    format!("rel:{}:{}", i, j),
+                    parent,
+                    format!("ettle:{}", (i * 3 + j) % 500),
+                    rt,
+                    i % 10 == 0,
+                ],
+            )
+            .unwrap();
+        }
+        tx.execute(
+            "INSERT INTO decision_links (decision_id, target_kind, target_id, relation_kind, created_at)
+             VALUES (?1, 'ettle', ?2, 'grounds', 0)",
+            rusqlite::params![format!("d:{}", i % 50), parent],
+        )
+        .unwrap();
+        tx.execute(
+            "INSERT INTO snapshots (snapshot_id, root_ettle_id, manifest_digest, semantic_manifest_digest,
+                                    created_at, policy_ref, profile_ref)
+             VALUES (?1, ?2, ?3, ?3, ?4, 'policy/default@0', 'profile/default@0')",
+            rusqlite::params![
+                format!("snap:{}", i),
+                format!("ettle:{}", i % 20),
+                format!("{:064x}", i),
+                i as i64,
+            ],
+        )
+        .unwrap();
+    }
+    tx.commit().unwrap();
+    conn.execute_batch("ANALYZE;").unwrap();
+    conn
+}
+
+/// Concatenated `detail` column of EXPLAIN QUERY PLAN.
+fn plan(conn: &Connection, sql: &str) -> String {
+    let mut stmt = conn
+        .prepare(&format!("EXPLAIN QUERY PLAN {}", sql))
+        .unwrap();
+    let rows = stmt
+        .query_map([], |row| row.get::<_, String>(3))
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    rows.join("\n")
+}
+
+#[test]
+fn test_children_by_type_uses_composite_source_index() {
+    let conn = setup_large_store();
+    let p = plan(
+        &conn,
+        "SELECT target_ettle_id FROM relations
+         WHERE source_ettle_id = 'ettle:1' AND relation_type = 'refinement' AND tombstoned_at IS NULL",
+    );
+    assert!(
+        p.contains("USING INDEX idx_relations_source_type (source_ettle_id=? AND relation_type=?)"),
+        "unexpected plan: {}",
+        p
+    );
+}
+
+#[test]
+fn test_parents_by_type_uses_composite_target_index() {
+    let conn = setup_large_store();
+    let p = plan(
+        &conn,
+        "SELECT source_ettle_id FROM relations
+         WHERE target_ettle_id = 'ettle:1' AND relation_type = 'refinement' AND tombstoned_at IS NULL",
+    );
+    assert!(
+        p.contains("USING INDEX idx_relations_target_type (target_ettle_id=? AND relation_type=?)"),
+        "unexpected plan: {}",
+        p
+    );
+}
+
+#[test]
+fn test_decisions_by_target_uses_covering_link_index() {
+    let conn = setup_large_store();
+    let p = plan(
+        &conn,
+        "SELECT decision_id FROM decision_links
+         WHERE target_kind = 'ettle' AND target_id = 'ettle:1'",
+    );
+    assert!(
+        p.contains("COVERING INDEX idx_decision_links_target"),
+        "unexpected plan: {}",
+        p
+    );
+}
+
+#[test]
+fn test_current_head_lookup_avoids_sort() {
+    let conn = setup_large_store();
+    let p = plan(
+        &conn,
+        "SELECT manifest_digest, snapshot_id FROM snapshots
+         WHERE root_ettle_id = 'ettle:1' ORDER BY created_at DESC LIMIT 1",
+    );
+    assert!(
+        p.contains("idx_snapshots_root_created"),
+        "unexpected plan: {}",
+        p
+    );
+    assert!(!p.contains("TEMP B-TREE"), "unexpected sort: {}", p);
+}
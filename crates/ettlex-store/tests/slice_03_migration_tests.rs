@@ -56,6 +56,16 @@ fn test_ettles_columns_exact_after_migration() {
         "tombstoned_at",
         "created_at",
         "updated_at",
+        // Added by migration 017 (ettle slugs)
+        "slug",
+        // Added by migration 018 (ettle archive)
+        "archived_at",
+        // Added by migration 024 (ettle content format)
+        "content_format",
+        // Added by migration 033 (ettle content spillover)
+        "why_digest",
+        "what_digest",
+        "how_digest",
     ]
     .iter()
     .copied()
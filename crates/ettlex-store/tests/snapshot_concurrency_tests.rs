@@ -0,0 +1,135 @@
+// Test suite for concurrent snapshot commits
+// Spawns threads with their own connections to one on-disk database and
+// checks that the ledger write lock serializes head updates.
+
+#![allow(clippy::result_large_err)]
+
+use std::sync::{Arc, Barrier};
+use std::thread;
+
+use ettlex_core::errors::ExErrorKind;
+use ettlex_core::ops::Store;
+use ettlex_core::snapshot::manifest::{generate_manifest, SnapshotManifest};
+use ettlex_store::cas::FsStore;
+use ettlex_store::snapshot::ledger::verify_ledger;
+use ettlex_store::snapshot::persist::{commit_snapshot, SnapshotCommitResult, SnapshotOptions};
+use ettlex_store::Result;
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+const WRITERS: usize = 8;
+
+fn open(temp_dir: &TempDir) -> Connection {
+    let conn = ettlex_store::db::open(temp_dir.path().join("test.db")).unwrap();
+    ettlex_store::db::configure(&conn).unwrap();
+    conn
+}
+
+fn setup_test_env() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let mut conn = open(&temp_dir);
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    temp_dir
+}
+
+fn manifest(tag: &str) -> SnapshotManifest {
+    generate_manifest(
+        vec![format!("ep:{}:0", tag)],
+        "policy/default@0".into(),
+        "profile/default@0".into(),
+        "ettle:root".into(),
+        "0001".into(),
+        None,
+        &Store::new(),
+    )
+    .unwrap()
+}
+
+/// Run `WRITERS` commits at once, each on its own connection, all released
+/// by a shared barrier.
+fn race(
+    temp_dir: &TempDir,
+    round: usize,
+    expected_head: Option<String>,
+) -> Vec<Result<SnapshotCommitResult>> {
+    let barrier = Arc::new(Barrier::new(WRITERS));
+    let handles: Vec<_> = (0..WRITERS)
+        .map(|i| {
+            let barrier = Arc::clone(&barrier);
+            let mut conn = open(temp_dir);
+            let cas = FsStore::new(temp_dir.path().join("cas"));
+            let expected_head = expected_head.clone();
+            let manifest = manifest(&format!("r{}w{}", round, i));
+            thread::spawn(move || {
+                barrier.wait();
+                commit_snapshot(
+                    &mut conn,
+                    &cas,
+                    manifest,
+                    SnapshotOptions {
+                        expected_head,
+                        ..Default::default()
+                    },
+                )
+            })
+        })
+        .collect();
+    handles.into_iter().map(|h| h.join().unwrap()).collect()
+}
+
+#[test]
+fn test_only_one_commit_per_expected_head_wins() {
+    let temp_dir = setup_test_env();
+    let cas = FsStore::new(temp_dir.path().join("cas"));
+    let genesis = commit_snapshot(
+        &mut open(&temp_dir),
+        &cas,
+        manifest("genesis"),
+        SnapshotOptions::default(),
+    )
+    .unwrap();
+
+    let mut head = genesis.manifest_digest;
+    for round in 0..5 {
+        let results = race(&temp_dir, round, Some(head.clone()));
+
+        let winners: Vec<_> = results.iter().filter_map(|r| r.as_ref().ok()).collect();
+        assert_eq!(winners.len(), 1, "round {}: exactly one winner", round);
+        for err in results.iter().filter_map(|r| r.as_ref().err()) {
+            assert_eq!(err.kind(), ExErrorKind::HeadMismatch, "round {}", round);
+        }
+        head = winners[0].manifest_digest.clone();
+    }
+
+    let conn = open(&temp_dir);
+    let count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM snapshots", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(count, 6);
+    assert!(verify_ledger(&conn).unwrap().is_intact());
+}
+
+#[test]
+fn test_unconditional_concurrent_commits_form_a_linear_history() {
+    let temp_dir = setup_test_env();
+    let results = race(&temp_dir, 0, None);
+    assert!(results.iter().all(|r| r.is_ok()));
+
+    // Each commit links to the head it observed under the lock, so the
+    // parent pointers form one chain with no forks.
+    let conn = open(&temp_dir);
+    let mut stmt = conn
+        .prepare("SELECT snapshot_id, parent_snapshot_id FROM snapshots ORDER BY id")
+        .unwrap();
+    let rows: Vec<(String, Option<String>)> = stmt
+        .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))
+        .unwrap()
+        .collect::<std::result::Result<_, _>>()
+        .unwrap();
+    assert_eq!(rows.len(), WRITERS);
+    assert_eq!(rows[0].1, None);
+    for pair in rows.windows(2) {
+        assert_eq!(pair[1].1.as_deref(), Some(pair[0].0.as_str()));
+    }
+    assert!(verify_ledger(&conn).unwrap().is_intact());
+}
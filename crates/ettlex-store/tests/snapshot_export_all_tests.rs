@@ -0,0 +1,112 @@
+// Test suite for bulk snapshot export
+// Tests the output layout, resume after interruption and output-directory checks
+
+use ettlex_core::errors::ExErrorKind;
+use ettlex_core::ops::Store;
+use ettlex_core::snapshot::manifest::generate_manifest;
+use ettlex_store::cas::FsStore;
+use ettlex_store::snapshot::export_all::{
+    export_all_snapshots, ExportAllOptions, ExportState, EXPORT_STATE_FILE,
+};
+use ettlex_store::snapshot::persist::{commit_snapshot, SnapshotOptions};
+use rusqlite::Connection;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn setup_test_env() -> (TempDir, Connection, FsStore) {
+    let temp_dir = TempDir::new().unwrap();
+    let mut conn = Connection::open(temp_dir.path().join("test.db")).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    let cas = FsStore::new(temp_dir.path().join("cas"));
+    (temp_dir, conn, cas)
+}
+
+fn commit(conn: &mut Connection, cas: &FsStore, root: &str) -> String {
+    let manifest = generate_manifest(
+        vec![format!("ep:{}:0", root)],
+        "policy/default@0".into(),
+        "profile/default@0".into(),
+        format!("ettle:{}", root),
+        "0001".into(),
+        None,
+        &Store::new(),
+    )
+    .unwrap();
+    commit_snapshot(conn, cas, manifest, SnapshotOptions::default())
+        .unwrap()
+        .snapshot_id
+}
+
+fn read_state(out: &Path) -> ExportState {
+    serde_json::from_slice(&std::fs::read(out.join(EXPORT_STATE_FILE)).unwrap()).unwrap()
+}
+
+#[test]
+fn test_export_all_writes_every_manifest_in_ledger_order() {
+    let (temp_dir, mut conn, cas) = setup_test_env();
+    let ids = vec![commit(&mut conn, &cas, "a"), commit(&mut conn, &cas, "b")];
+    let out = temp_dir.path().join("archive");
+
+    let report = export_all_snapshots(&conn, &cas, &out, ExportAllOptions::default()).unwrap();
+
+    assert_eq!(report.exported, ids);
+    assert_eq!((report.skipped, report.total), (0, 2));
+    for id in &ids {
+        let dir = out.join("snapshots").join(id);
+        assert!(dir.join("manifest.json").exists());
+        assert!(!dir.join("index.json").exists());
+    }
+    assert_eq!(read_state(&out).exported, ids);
+}
+
+#[test]
+fn test_resume_skips_exported_and_redoes_partial_snapshot() {
+    let (temp_dir, mut conn, cas) = setup_test_env();
+    let first = commit(&mut conn, &cas, "a");
+    let out = temp_dir.path().join("archive");
+    let options = ExportAllOptions {
+        include_blobs: true,
+        resume: true,
+    };
+    export_all_snapshots(&conn, &cas, &out, options).unwrap();
+
+    // A later commit, plus debris from an interrupted attempt at it.
+    let second = commit(&mut conn, &cas, "b");
+    let partial = out.join("snapshots").join(format!("{}.partial", second));
+    std::fs::create_dir_all(&partial).unwrap();
+    std::fs::write(partial.join("manifest.json"), b"trunc").unwrap();
+
+    let report = export_all_snapshots(&conn, &cas, &out, options).unwrap();
+
+    assert_eq!(report.exported, vec![second.clone()]);
+    assert_eq!((report.skipped, report.total), (1, 2));
+    assert!(!partial.exists());
+    let dir = out.join("snapshots").join(&second);
+    assert!(dir.join("index.json").exists());
+    assert_ne!(std::fs::read(dir.join("manifest.json")).unwrap(), b"trunc");
+    assert_eq!(read_state(&out).exported, vec![first, second]);
+}
+
+#[test]
+fn test_non_empty_dir_without_resume_is_rejected() {
+    let (temp_dir, mut conn, cas) = setup_test_env();
+    commit(&mut conn, &cas, "a");
+    let out = temp_dir.path().join("archive");
+    export_all_snapshots(&conn, &cas, &out, ExportAllOptions::default()).unwrap();
+
+    let err = export_all_snapshots(&conn, &cas, &out, ExportAllOptions::default()).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+
+    // Resuming with a different blob setting would mix layouts.
+    let err = export_all_snapshots(
+        &conn,
+        &cas,
+        &out,
+        ExportAllOptions {
+            include_blobs: true,
+            resume: true,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+}
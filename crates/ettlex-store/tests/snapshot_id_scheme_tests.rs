@@ -0,0 +1,105 @@
+// Test suite for configurable snapshot ID schemes
+// Tests root-counter and digest-prefix generation, collision suffixes, and prefix resolution
+
+use ettlex_core::errors::ExErrorKind;
+use ettlex_core::ops::Store;
+use ettlex_core::snapshot::manifest::generate_manifest;
+use ettlex_store::cas::FsStore;
+use ettlex_store::snapshot::persist::{commit_snapshot, SnapshotOptions};
+use ettlex_store::snapshot::{generate_snapshot_id, resolve_snapshot_id, SnapshotIdScheme};
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+fn setup_test_env() -> (TempDir, Connection, FsStore) {
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("test.db");
+    let cas_path = temp_dir.path().join("cas");
+    let mut conn = Connection::open(&db_path).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    let cas = FsStore::new(cas_path);
+    (temp_dir, conn, cas)
+}
+
+fn commit(conn: &mut Connection, cas: &FsStore, root: &str, scheme: SnapshotIdScheme) -> String {
+    let manifest = generate_manifest(
+        vec![format!("{}:0", root)],
+        "policy/default@0".into(),
+        "profile/default@0".into(),
+        root.into(),
+        "0001".into(),
+        None,
+        &Store::new(),
+    )
+    .unwrap();
+    commit_snapshot(
+        conn,
+        cas,
+        manifest,
+        SnapshotOptions {
+            id_scheme: scheme,
+            ..Default::default()
+        },
+    )
+    .unwrap()
+    .snapshot_id
+}
+
+#[test]
+fn test_default_scheme_is_uuid() {
+    let (_tmp, mut conn, cas) = setup_test_env();
+    let id = commit(&mut conn, &cas, "ettle:a", SnapshotIdScheme::default());
+    assert!(uuid::Uuid::parse_str(&id).is_ok());
+}
+
+#[test]
+fn test_root_counter_scheme_counts_per_root() {
+    let (_tmp, mut conn, cas) = setup_test_env();
+    let s = SnapshotIdScheme::RootCounter;
+    assert_eq!(
+        commit(&mut conn, &cas, "ettle:Billing", s),
+        "snap-billing-1"
+    );
+    assert_eq!(
+        commit(&mut conn, &cas, "ettle:Billing", s),
+        "snap-billing-2"
+    );
+    assert_eq!(commit(&mut conn, &cas, "ettle:auth", s), "snap-auth-1");
+}
+
+#[test]
+fn test_digest_prefix_scheme_suffixes_collisions() {
+    let (_tmp, mut conn, cas) = setup_test_env();
+    let first = commit(&mut conn, &cas, "ettle:a", SnapshotIdScheme::DigestPrefix);
+    let digest: String = conn
+        .query_row(
+            "SELECT manifest_digest FROM snapshots WHERE snapshot_id = ?1",
+            [&first],
+            |r| r.get(0),
+        )
+        .unwrap();
+    assert_eq!(first, format!("snap-{}", &digest[..12]));
+
+    // Same digest again (e.g. an append-only re-commit) gets a suffix
+    let next =
+        generate_snapshot_id(&conn, SnapshotIdScheme::DigestPrefix, "ettle:a", &digest).unwrap();
+    assert_eq!(next, format!("{}-2", first));
+}
+
+#[test]
+fn test_resolve_snapshot_id_exact_and_prefix() {
+    let (_tmp, mut conn, cas) = setup_test_env();
+    let s = SnapshotIdScheme::RootCounter;
+    commit(&mut conn, &cas, "ettle:alpha", s);
+    commit(&mut conn, &cas, "ettle:beta", s);
+
+    assert_eq!(
+        resolve_snapshot_id(&conn, "snap-alpha-1").unwrap(),
+        "snap-alpha-1"
+    );
+    assert_eq!(resolve_snapshot_id(&conn, "snap-b").unwrap(), "snap-beta-1");
+
+    let err = resolve_snapshot_id(&conn, "snap-").unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::AmbiguousSelection);
+    let err = resolve_snapshot_id(&conn, "snap-gamma").unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+}
@@ -0,0 +1,119 @@
+// Test suite for the JSON-lines ledger export
+// Tests one line per snapshot in commit order, row fields, and the optional manifest summary
+
+use ettlex_core::errors::ExErrorKind;
+use ettlex_core::ops::Store;
+use ettlex_core::snapshot::manifest::generate_manifest;
+use ettlex_store::cas::FsStore;
+use ettlex_store::snapshot::ledger_export::{export_ledger_jsonl, LedgerExportOptions};
+use ettlex_store::snapshot::persist::{commit_snapshot, SnapshotOptions};
+use rusqlite::Connection;
+use serde_json::Value;
+use tempfile::TempDir;
+
+fn setup_test_env() -> (TempDir, Connection, FsStore) {
+    let temp_dir = TempDir::new().unwrap();
+    let mut conn = Connection::open(temp_dir.path().join("test.db")).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    let cas = FsStore::new(temp_dir.path().join("cas"));
+    (temp_dir, conn, cas)
+}
+
+fn commit(conn: &mut Connection, cas: &FsStore, root: &str, message: Option<&str>) -> String {
+    let manifest = generate_manifest(
+        vec![format!("ep:{}:0", root), format!("ep:{}:1", root)],
+        "policy/default@0".into(),
+        "profile/default@0".into(),
+        format!("ettle:{}", root),
+        "0001".into(),
+        None,
+        &Store::new(),
+    )
+    .unwrap();
+    let options = SnapshotOptions {
+        message: message.map(str::to_string),
+        ..SnapshotOptions::default()
+    };
+    commit_snapshot(conn, cas, manifest, options)
+        .unwrap()
+        .snapshot_id
+}
+
+fn export(conn: &Connection, cas: &FsStore, include_manifest_summary: bool) -> Vec<Value> {
+    let mut out = Vec::new();
+    let count = export_ledger_jsonl(
+        conn,
+        cas,
+        &mut out,
+        LedgerExportOptions {
+            include_manifest_summary,
+        },
+    )
+    .unwrap();
+    let lines: Vec<Value> = String::from_utf8(out)
+        .unwrap()
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(lines.len(), count);
+    lines
+}
+
+#[test]
+fn test_ledger_export_writes_one_line_per_snapshot_in_commit_order() {
+    let (_dir, mut conn, cas) = setup_test_env();
+    let first = commit(&mut conn, &cas, "a", Some("first"));
+    let second = commit(&mut conn, &cas, "b", None);
+
+    let lines = export(&conn, &cas, false);
+
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["snapshot_id"], first.as_str());
+    assert_eq!(lines[0]["root_ettle_id"], "ettle:a");
+    assert_eq!(lines[0]["message"], "first");
+    assert_eq!(lines[0]["annotations"], serde_json::json!({}));
+    assert!(lines[0]["chain_hash"].is_string());
+    assert_eq!(lines[1]["snapshot_id"], second.as_str());
+    assert_eq!(lines[1]["message"], Value::Null);
+    assert!(lines[1].get("manifest").is_none());
+}
+
+#[test]
+fn test_ledger_export_adds_manifest_summary_on_request() {
+    let (_dir, mut conn, cas) = setup_test_env();
+    commit(&mut conn, &cas, "a", None);
+
+    let lines = export(&conn, &cas, true);
+
+    let manifest = &lines[0]["manifest"];
+    assert_eq!(manifest["manifest_schema_version"], 1);
+    assert_eq!(manifest["store_schema_version"], "0001");
+    assert_eq!(manifest["ept_len"], 2);
+    assert_eq!(manifest["constraints"]["declared_ref_count"], 0);
+}
+
+#[test]
+fn test_ledger_export_of_empty_ledger_writes_nothing() {
+    let (_dir, conn, cas) = setup_test_env();
+    assert!(export(&conn, &cas, true).is_empty());
+}
+
+#[test]
+fn test_ledger_export_summary_needs_manifest_in_cas() {
+    let (dir, mut conn, cas) = setup_test_env();
+    commit(&mut conn, &cas, "a", None);
+    std::fs::remove_dir_all(dir.path().join("cas")).unwrap();
+
+    // Rows alone still export
+    assert_eq!(export(&conn, &cas, false).len(), 1);
+    let err = export_ledger_jsonl(
+        &conn,
+        &cas,
+        Vec::new(),
+        LedgerExportOptions {
+            include_manifest_summary: true,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::MissingBlob);
+}
@@ -0,0 +1,107 @@
+// Test suite for snapshot materialization
+// Tests the directory layout, blob inventory and output-directory safety checks
+
+use ettlex_core::errors::ExErrorKind;
+use ettlex_core::ops::Store;
+use ettlex_core::snapshot::manifest::{generate_manifest, EpEntry};
+use ettlex_store::cas::FsStore;
+use ettlex_store::snapshot::materialize::{materialize_snapshot, MaterializeIndex};
+use ettlex_store::snapshot::persist::{commit_snapshot, SnapshotOptions};
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+fn setup_test_env() -> (TempDir, Connection, FsStore) {
+    let temp_dir = TempDir::new().unwrap();
+    let mut conn = Connection::open(temp_dir.path().join("test.db")).unwrap();
+    ettlex_store::migrations::apply_migrations(&mut conn).unwrap();
+    let cas = FsStore::new(temp_dir.path().join("cas"));
+    (temp_dir, conn, cas)
+}
+
+/// Commit a snapshot whose first EPT entry references a real CAS blob.
+fn commit_with_blob(conn: &mut Connection, cas: &FsStore) -> (String, String) {
+    let blob_digest = cas.write(b"# Payments API\n", "md").unwrap();
+    let mut manifest = generate_manifest(
+        vec!["ep:root:0".into(), "ep:root:1".into()],
+        "policy/default@0".into(),
+        "profile/default@0".into(),
+        "ettle:root".into(),
+        "0001".into(),
+        None,
+        &Store::new(),
+    )
+    .unwrap();
+    manifest.ept[0] = EpEntry {
+        ep_digest: blob_digest.clone(),
+        ..manifest.ept[0].clone()
+    };
+    let result = commit_snapshot(conn, cas, manifest, SnapshotOptions::default()).unwrap();
+    (result.snapshot_id, blob_digest)
+}
+
+#[test]
+fn test_materialize_writes_manifest_blobs_and_index() {
+    let (temp_dir, mut conn, cas) = setup_test_env();
+    let (snapshot_id, blob_digest) = commit_with_blob(&mut conn, &cas);
+    let out = temp_dir.path().join("out");
+
+    let index = materialize_snapshot(&conn, &cas, &snapshot_id, &out).unwrap();
+
+    assert_eq!(index.snapshot_id, snapshot_id);
+    assert_eq!(index.blobs.len(), 1);
+    assert_eq!(index.blobs[0].digest, blob_digest);
+    assert_eq!(index.blobs[0].referenced_at, vec!["/ept/0/ep_digest"]);
+    assert_eq!(
+        std::fs::read(out.join("blobs").join(&blob_digest)).unwrap(),
+        b"# Payments API\n"
+    );
+
+    // manifest.json holds the exact CAS bytes
+    assert_eq!(
+        std::fs::read(out.join("manifest.json")).unwrap(),
+        cas.read(&index.manifest_digest).unwrap()
+    );
+
+    // Digests with no blob behind them are inventoried, not dropped
+    assert!(index
+        .unresolved
+        .iter()
+        .any(|u| u.referenced_at.contains(&"/ept_digest".to_string())));
+
+    let on_disk: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(out.join("index.json")).unwrap()).unwrap();
+    assert_eq!(on_disk["snapshot_id"], snapshot_id.as_str());
+    assert_eq!(
+        on_disk["blobs"][0]["path"],
+        format!("blobs/{}", blob_digest)
+    );
+}
+
+#[test]
+fn test_materialize_accepts_snapshot_id_prefix() {
+    let (temp_dir, mut conn, cas) = setup_test_env();
+    let (snapshot_id, _) = commit_with_blob(&mut conn, &cas);
+
+    let index: MaterializeIndex =
+        materialize_snapshot(&conn, &cas, &snapshot_id[..12], &temp_dir.path().join("o")).unwrap();
+    assert_eq!(index.snapshot_id, snapshot_id);
+}
+
+#[test]
+fn test_materialize_refuses_non_empty_output_dir() {
+    let (temp_dir, mut conn, cas) = setup_test_env();
+    let (snapshot_id, _) = commit_with_blob(&mut conn, &cas);
+    let out = temp_dir.path().join("out");
+    std::fs::create_dir_all(&out).unwrap();
+    std::fs::write(out.join("keep.txt"), b"x").unwrap();
+
+    let err = materialize_snapshot(&conn, &cas, &snapshot_id, &out).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_materialize_unknown_snapshot_is_not_found() {
+    let (temp_dir, conn, cas) = setup_test_env();
+    let err = materialize_snapshot(&conn, &cas, "nope", &temp_dir.path().join("out")).unwrap_err();
+    assert_eq!(err.kind(), ExErrorKind::NotFound);
+}
@@ -1,10 +1,12 @@
 // Test suite for snapshot persistence
 // Tests CAS storage, ledger entries, atomic commits, and idempotency
 
+use ettlex_core::constraint_engine::ConstraintFamilyStatus;
 use ettlex_core::ops::Store;
-use ettlex_core::snapshot::manifest::generate_manifest;
+use ettlex_core::snapshot::manifest::{generate_manifest, FamilyConstraints};
 use ettlex_store::cas::FsStore;
 use ettlex_store::snapshot::persist::{commit_snapshot, persist_manifest_to_cas, SnapshotOptions};
+use ettlex_store::snapshot::query::fetch_snapshot_row;
 use rusqlite::Connection;
 use tempfile::TempDir;
 
@@ -83,6 +85,7 @@ fn test_commit_snapshot_happy_path() {
             expected_head: None,
             dry_run: false,
             allow_dedup: false,
+            ..Default::default()
         },
     )
     .unwrap();
@@ -124,6 +127,7 @@ fn test_commit_snapshot_idempotent() {
             expected_head: None,
             dry_run: false,
             allow_dedup: true,
+            ..Default::default()
         },
     )
     .unwrap();
@@ -145,6 +149,7 @@ fn test_commit_snapshot_idempotent() {
             expected_head: None,
             dry_run: false,
             allow_dedup: true,
+            ..Default::default()
         },
     )
     .unwrap();
@@ -160,6 +165,60 @@ fn test_commit_snapshot_idempotent() {
     assert_eq!(count, 1);
 }
 
+#[test]
+fn test_commit_snapshot_returns_constraints_summary() {
+    let (_temp_dir, mut conn, cas) = setup_test_env();
+    let mut manifest = create_test_manifest();
+    manifest.constraints.declared_refs = vec!["c:1".into(), "c:2".into(), "c:3".into()];
+    manifest.constraints.families.insert(
+        "abb".into(),
+        FamilyConstraints {
+            status: ConstraintFamilyStatus::Uncomputed,
+            active_refs: vec!["c:1".into(), "c:2".into()],
+            outcomes: Vec::new(),
+            evidence: Vec::new(),
+            digest: "abb-digest".into(),
+        },
+    );
+    manifest.constraints.families.insert(
+        "obs".into(),
+        FamilyConstraints {
+            status: ConstraintFamilyStatus::Uncomputed,
+            active_refs: vec!["c:3".into()],
+            outcomes: Vec::new(),
+            evidence: Vec::new(),
+            digest: "obs-digest".into(),
+        },
+    );
+
+    let options = SnapshotOptions {
+        allow_dedup: true,
+        ..Default::default()
+    };
+    let result = commit_snapshot(&mut conn, &cas, manifest.clone(), options.clone()).unwrap();
+    let summary = &result.constraints_summary;
+    assert_eq!(summary.declared_ref_count, 3);
+    assert_eq!(
+        summary.constraints_digest,
+        manifest.constraints.constraints_digest
+    );
+    assert_eq!(
+        summary.families.keys().collect::<Vec<_>>(),
+        vec!["abb", "obs"]
+    );
+    assert_eq!(summary.families["abb"].active_ref_count, 2);
+    assert_eq!(summary.families["abb"].digest, "abb-digest");
+    assert_eq!(
+        summary.families["obs"].status,
+        ConstraintFamilyStatus::Uncomputed
+    );
+
+    // A deduplicated commit reports the same envelope
+    let dup = commit_snapshot(&mut conn, &cas, manifest, options).unwrap();
+    assert!(dup.was_duplicate);
+    assert_eq!(dup.constraints_summary, result.constraints_summary);
+}
+
 #[test]
 fn test_commit_snapshot_expected_head_success() {
     let (_temp_dir, mut conn, cas) = setup_test_env();
@@ -174,6 +233,7 @@ fn test_commit_snapshot_expected_head_success() {
             expected_head: None,
             dry_run: false,
             allow_dedup: false,
+            ..Default::default()
         },
     )
     .unwrap();
@@ -200,6 +260,7 @@ fn test_commit_snapshot_expected_head_success() {
             expected_head: Some(result1.manifest_digest.clone()),
             dry_run: false,
             allow_dedup: false,
+            ..Default::default()
         },
     )
     .unwrap();
@@ -231,6 +292,7 @@ fn test_commit_snapshot_expected_head_mismatch() {
             expected_head: Some("nonexistent-snapshot-id".into()),
             dry_run: false,
             allow_dedup: false,
+            ..Default::default()
         },
     );
 
@@ -253,6 +315,7 @@ fn test_commit_snapshot_dry_run() {
             expected_head: None,
             dry_run: true,
             allow_dedup: false,
+            ..Default::default()
         },
     )
     .unwrap();
@@ -288,6 +351,7 @@ fn test_commit_snapshot_atomic() {
             expected_head: None,
             dry_run: false,
             allow_dedup: false,
+            ..Default::default()
         },
     )
     .unwrap();
@@ -311,3 +375,106 @@ fn test_commit_snapshot_atomic() {
         .unwrap();
     assert_eq!(stored_digest, result.manifest_digest);
 }
+
+#[test]
+fn test_commit_snapshot_records_message_and_annotations() {
+    let (_temp_dir, mut conn, cas) = setup_test_env();
+    let manifest = create_test_manifest();
+
+    let mut annotations = std::collections::BTreeMap::new();
+    annotations.insert("ticket".to_string(), "ARCH-42".to_string());
+    annotations.insert("reviewer".to_string(), "ops".to_string());
+    let result = commit_snapshot(
+        &mut conn,
+        &cas,
+        manifest.clone(),
+        SnapshotOptions {
+            message: Some("Split payments service".into()),
+            annotations: annotations.clone(),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    // Annotations live on the ledger row only; the manifest is untouched
+    assert_eq!(
+        result.semantic_manifest_digest,
+        manifest.semantic_manifest_digest
+    );
+
+    let row = fetch_snapshot_row(&conn, &result.snapshot_id).unwrap();
+    assert_eq!(row.message.as_deref(), Some("Split payments service"));
+    assert_eq!(row.annotations, annotations);
+}
+
+#[test]
+fn test_commit_snapshot_without_annotations_reads_back_empty() {
+    let (_temp_dir, mut conn, cas) = setup_test_env();
+    let result = commit_snapshot(
+        &mut conn,
+        &cas,
+        create_test_manifest(),
+        SnapshotOptions::default(),
+    )
+    .unwrap();
+
+    let row = fetch_snapshot_row(&conn, &result.snapshot_id).unwrap();
+    assert_eq!(row.message, None);
+    assert!(row.annotations.is_empty());
+}
+
+#[test]
+fn test_commit_snapshot_rejects_blank_annotation_key() {
+    let (_temp_dir, mut conn, cas) = setup_test_env();
+    let mut annotations = std::collections::BTreeMap::new();
+    annotations.insert("  ".to_string(), "x".to_string());
+
+    let err = commit_snapshot(
+        &mut conn,
+        &cas,
+        create_test_manifest(),
+        SnapshotOptions {
+            annotations,
+            ..Default::default()
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ettlex_core::ExErrorKind::InvalidInput);
+
+    let count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM snapshots", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn test_commit_snapshot_rejects_retired_root() {
+    let (_temp_dir, mut conn, cas) = setup_test_env();
+    conn.execute_batch(
+        "INSERT INTO ettles (id, title, created_at, updated_at)
+             VALUES ('ettle:root', 'Root', '2026-01-01T00:00:00Z', '2026-01-01T00:00:00Z');
+         INSERT INTO roots (ettle_id, display_name, status, registered_at_ms, updated_at_ms)
+             VALUES ('ettle:root', 'Root', 'retired', 0, 0);",
+    )
+    .unwrap();
+
+    let err = commit_snapshot(
+        &mut conn,
+        &cas,
+        create_test_manifest(),
+        SnapshotOptions::default(),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ettlex_core::ExErrorKind::RootEttleInvalid);
+
+    // Reactivated roots accept snapshots again
+    conn.execute("UPDATE roots SET status = 'active'", [])
+        .unwrap();
+    commit_snapshot(
+        &mut conn,
+        &cas,
+        create_test_manifest(),
+        SnapshotOptions::default(),
+    )
+    .unwrap();
+}
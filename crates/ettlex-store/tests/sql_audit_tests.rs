@@ -0,0 +1,65 @@
+//! SQL audit ring buffer: capture, redaction and capacity.
+//!
+//! The audit is process-wide, so everything touching the ring runs in a
+//! single test.
+
+use ettlex_store::sql_audit::{self, redact_sql};
+use tempfile::TempDir;
+
+#[test]
+fn test_redact_sql_replaces_literals_only() {
+    assert_eq!(
+        redact_sql("SELECT \"t1\".id FROM t1 WHERE a = 'it''s' AND b = X'00ff' AND c = -1.5e+3 AND d IS NULL AND e = ?1 LIMIT 10"),
+        "SELECT \"t1\".id FROM t1 WHERE a = ***REDACTED*** AND b = ***REDACTED*** AND c = -***REDACTED*** AND d IS NULL AND e = ?1 LIMIT ***REDACTED***"
+    );
+}
+
+#[test]
+fn test_audit_records_redacted_statements_in_a_ring() {
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join("audit.db");
+
+    // Not enabled: connections are not attached
+    let conn = ettlex_store::db::open(&path).unwrap();
+    conn.execute_batch("CREATE TABLE secrets (k TEXT)").unwrap();
+    drop(conn);
+    assert!(sql_audit::entries()
+        .iter()
+        .all(|e| !e.sql.contains("secrets")));
+
+    sql_audit::enable();
+    let conn = ettlex_store::db::open(&path).unwrap();
+    conn.execute("INSERT INTO secrets (k) VALUES (?1)", ["hunter2"])
+        .unwrap();
+    let err = conn.execute("INSERT INTO no_such_table VALUES (1)", []);
+    assert!(err.is_err());
+
+    let recorded: Vec<_> = sql_audit::entries()
+        .into_iter()
+        .filter(|e| e.sql.contains("secrets"))
+        .collect();
+    assert_eq!(recorded.len(), 1);
+    assert_eq!(
+        recorded[0].sql,
+        "INSERT INTO secrets (k) VALUES (***REDACTED***)"
+    );
+    assert!(sql_audit::entries()
+        .iter()
+        .all(|e| !e.sql.contains("hunter2")));
+
+    sql_audit::set_capacity(2);
+    for _ in 0..3 {
+        conn.query_row("SELECT count(*) FROM secrets", [], |_| Ok(()))
+            .unwrap();
+    }
+    let entries = sql_audit::entries();
+    assert_eq!(entries.len(), 2);
+    assert!(entries[0].seq < entries[1].seq);
+
+    sql_audit::disable();
+    sql_audit::clear();
+    conn.query_row("SELECT count(*) FROM secrets", [], |_| Ok(()))
+        .unwrap();
+    assert!(sql_audit::entries().is_empty());
+    sql_audit::set_capacity(sql_audit::DEFAULT_CAPACITY);
+}